@@ -1,3 +1,6 @@
+use crate::protocol::v2::McpToolCallError;
+use crate::protocol::v2::McpToolCallResult;
+use crate::protocol::v2::McpToolCallStatus;
 use crate::protocol::v2::ThreadItem;
 use crate::protocol::v2::Turn;
 use crate::protocol::v2::TurnError;
@@ -6,9 +9,12 @@ use crate::protocol::v2::UserInput;
 use codex_protocol::protocol::AgentReasoningEvent;
 use codex_protocol::protocol::AgentReasoningRawContentEvent;
 use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpToolCallBeginEvent;
+use codex_protocol::protocol::McpToolCallEndEvent;
 use codex_protocol::protocol::ThreadRolledBackEvent;
 use codex_protocol::protocol::TurnAbortedEvent;
 use codex_protocol::protocol::UserMessageEvent;
+use std::collections::HashMap;
 
 /// Convert persisted [`EventMsg`] entries into a sequence of [`Turn`] values.
 ///
@@ -28,6 +34,9 @@ struct ThreadHistoryBuilder {
     current_turn: Option<PendingTurn>,
     next_turn_index: i64,
     next_item_index: i64,
+    /// Redacted arguments of MCP tool calls that have begun, by call id.
+    /// Rollouts do not keep the raw arguments.
+    mcp_arguments_previews: HashMap<String, String>,
 }
 
 impl ThreadHistoryBuilder {
@@ -37,6 +46,7 @@ impl ThreadHistoryBuilder {
             current_turn: None,
             next_turn_index: 1,
             next_item_index: 1,
+            mcp_arguments_previews: HashMap::new(),
         }
     }
 
@@ -61,6 +71,18 @@ impl ThreadHistoryBuilder {
             EventMsg::ThreadRolledBack(payload) => self.handle_thread_rollback(payload),
             EventMsg::UndoCompleted(_) => {}
             EventMsg::TurnAborted(payload) => self.handle_turn_aborted(payload),
+            EventMsg::McpToolCallBegin(payload) => self.handle_mcp_tool_call_begin(payload),
+            EventMsg::McpToolCallEnd(payload) => self.handle_mcp_tool_call_end(payload),
+            // Plans are not thread items; clients follow them through
+            // `turn/plan/updated`.
+            EventMsg::PlanUpdate(_) => {}
+            EventMsg::FileAccessLedger(_) => {}
+            EventMsg::ViewImageToolCall(payload) => {
+                self.ensure_turn().items.push(ThreadItem::ImageView {
+                    id: payload.call_id.clone(),
+                    path: payload.path.to_string_lossy().into_owned(),
+                });
+            }
             _ => {}
         }
     }
@@ -125,6 +147,49 @@ impl ThreadHistoryBuilder {
         });
     }
 
+    fn handle_mcp_tool_call_begin(&mut self, payload: &McpToolCallBeginEvent) {
+        self.mcp_arguments_previews
+            .insert(payload.call_id.clone(), payload.arguments_preview.clone());
+    }
+
+    fn handle_mcp_tool_call_end(&mut self, payload: &McpToolCallEndEvent) {
+        let preview = self.mcp_arguments_previews.remove(&payload.call_id);
+        let status = if payload.is_success() {
+            McpToolCallStatus::Completed
+        } else {
+            McpToolCallStatus::Failed
+        };
+        let (result, error) = match &payload.result {
+            Ok(value) => (
+                Some(McpToolCallResult {
+                    content: value.content.clone(),
+                    structured_content: value.structured_content.clone(),
+                }),
+                None,
+            ),
+            Err(message) => (
+                None,
+                Some(McpToolCallError {
+                    message: message.clone(),
+                }),
+            ),
+        };
+        self.ensure_turn().items.push(ThreadItem::McpToolCall {
+            id: payload.call_id.clone(),
+            server: payload.invocation.server.clone(),
+            tool: payload.invocation.tool.clone(),
+            status,
+            arguments: payload
+                .invocation
+                .arguments
+                .clone()
+                .unwrap_or_else(|| arguments_from_preview(preview)),
+            result,
+            error,
+            duration_ms: i64::try_from(payload.duration.as_millis()).ok(),
+        });
+    }
+
     fn handle_turn_aborted(&mut self, _payload: &TurnAbortedEvent) {
         let Some(turn) = self.current_turn.as_mut() else {
             return;
@@ -235,17 +300,34 @@ impl From<PendingTurn> for Turn {
     }
 }
 
+/// The arguments of an MCP tool call as recorded in a rollout: the redacted
+/// preview, parsed back into JSON unless truncation cut it short.
+fn arguments_from_preview(preview: Option<String>) -> serde_json::Value {
+    match preview {
+        Some(preview) if !preview.is_empty() => {
+            serde_json::from_str(&preview).unwrap_or(serde_json::Value::String(preview))
+        }
+        _ => serde_json::Value::Null,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use codex_protocol::protocol::AgentMessageEvent;
     use codex_protocol::protocol::AgentReasoningEvent;
     use codex_protocol::protocol::AgentReasoningRawContentEvent;
+    use codex_protocol::protocol::McpInvocation;
+    use codex_protocol::protocol::McpToolCallErrorSource;
     use codex_protocol::protocol::ThreadRolledBackEvent;
     use codex_protocol::protocol::TurnAbortReason;
     use codex_protocol::protocol::TurnAbortedEvent;
     use codex_protocol::protocol::UserMessageEvent;
+    use mcp_types::CallToolResult;
+    use mcp_types::ContentBlock;
+    use mcp_types::TextContent;
     use pretty_assertions::assert_eq;
+    use std::time::Duration;
 
     #[test]
     fn builds_multiple_turns_with_reasoning_items() {
@@ -255,6 +337,7 @@ mod tests {
                 images: Some(vec!["https://example.com/one.png".into()]),
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "Hi there".into(),
@@ -270,6 +353,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "Reply two".into(),
@@ -344,6 +428,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentReasoning(AgentReasoningEvent {
                 text: "first summary".into(),
@@ -382,6 +467,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn includes_mcp_tool_calls_from_end_events() {
+        let invocation = McpInvocation {
+            server: "docs".into(),
+            tool: "search".into(),
+            arguments: Some(serde_json::json!({ "query": "rollout" })),
+        };
+        let events = vec![
+            EventMsg::UserMessage(UserMessageEvent {
+                message: "Look it up".into(),
+                images: None,
+                text_elements: Vec::new(),
+                local_images: Vec::new(),
+                documents: Vec::new(),
+            }),
+            EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+                call_id: "call-1".into(),
+                invocation: invocation.clone(),
+                timeout_ms: None,
+                arguments_preview: r#"{"query":"rollout"}"#.into(),
+            }),
+            EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+                call_id: "call-1".into(),
+                invocation,
+                duration: Duration::from_millis(1500),
+                result: Err("connection closed".into()),
+                is_error: true,
+                error_source: Some(McpToolCallErrorSource::Transport),
+                result_preview: "connection closed".into(),
+            }),
+        ];
+
+        let turns = build_turns_from_event_msgs(&events);
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].items.len(), 2);
+        assert_eq!(
+            turns[0].items[1],
+            ThreadItem::McpToolCall {
+                id: "call-1".into(),
+                server: "docs".into(),
+                tool: "search".into(),
+                status: McpToolCallStatus::Failed,
+                arguments: serde_json::json!({ "query": "rollout" }),
+                result: None,
+                error: Some(McpToolCallError {
+                    message: "connection closed".into(),
+                }),
+                duration_ms: Some(1500),
+            }
+        );
+    }
+
+    #[test]
+    fn rebuilds_mcp_tool_calls_from_redacted_rollout_events() {
+        let invocation = McpInvocation {
+            server: "docs".into(),
+            tool: "search".into(),
+            arguments: Some(serde_json::json!({ "query": "rollout", "api_key": "abc123" })),
+        };
+        let begin = McpToolCallBeginEvent {
+            call_id: "call-1".into(),
+            invocation: invocation.clone(),
+            timeout_ms: None,
+            arguments_preview: r#"{"api_key":"[REDACTED]","query":"rollout"}"#.into(),
+        };
+        let end = McpToolCallEndEvent {
+            call_id: "call-1".into(),
+            invocation,
+            duration: Duration::from_millis(20),
+            result: Ok(CallToolResult {
+                content: Vec::new(),
+                is_error: Some(false),
+                structured_content: Some(serde_json::json!({ "token": "abc123" })),
+            }),
+            is_error: false,
+            error_source: None,
+            result_preview: r#"{"token":"[REDACTED]"}"#.into(),
+        };
+        let events = vec![
+            EventMsg::McpToolCallBegin(begin.without_raw_payload()),
+            EventMsg::McpToolCallEnd(end.without_raw_payload()),
+        ];
+
+        let turns = build_turns_from_event_msgs(&events);
+        assert_eq!(
+            turns[0].items,
+            vec![ThreadItem::McpToolCall {
+                id: "call-1".into(),
+                server: "docs".into(),
+                tool: "search".into(),
+                status: McpToolCallStatus::Completed,
+                arguments: serde_json::json!({ "api_key": "[REDACTED]", "query": "rollout" }),
+                result: Some(McpToolCallResult {
+                    content: vec![ContentBlock::TextContent(TextContent {
+                        annotations: None,
+                        text: r#"{"token":"[REDACTED]"}"#.into(),
+                        r#type: "text".into(),
+                    })],
+                    structured_content: None,
+                }),
+                error: None,
+                duration_ms: Some(20),
+            }]
+        );
+    }
+
     #[test]
     fn marks_turn_as_interrupted_when_aborted() {
         let events = vec![
@@ -390,6 +581,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "Working...".into(),
@@ -402,6 +594,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "Second attempt complete.".into(),
@@ -462,6 +655,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "A1".into(),
@@ -471,6 +665,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "A2".into(),
@@ -481,6 +676,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "A3".into(),
@@ -537,6 +733,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "A1".into(),
@@ -546,6 +743,7 @@ mod tests {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "A2".into(),
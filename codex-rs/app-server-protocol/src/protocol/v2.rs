@@ -1100,6 +1100,11 @@ pub struct ThreadStartParams {
     pub personality: Option<Personality>,
     pub ephemeral: Option<bool>,
     pub dynamic_tools: Option<Vec<DynamicToolSpec>>,
+    /// Named directories the thread works in besides `cwd`, by name. They add
+    /// to, or replace by name, the `workspace_roots` of config.toml, and are
+    /// writable under the workspace-write sandbox. Relative paths resolve
+    /// against `cwd`.
+    pub workspace_roots: Option<HashMap<String, String>>,
     /// If true, opt into emitting raw response items on the event stream.
     ///
     /// This is for internal use only (e.g. Codex Cloud).
@@ -1527,6 +1532,10 @@ pub struct Thread {
     pub source: SessionSource,
     /// Optional Git metadata captured when the thread was created.
     pub git_info: Option<GitInfo>,
+    /// Whether the thread is running in another process that accepts
+    /// read-only attach clients (`codex attach`). Only set by `thread/list`.
+    #[serde(default)]
+    pub live: bool,
     /// Only populated on `thread/resume`, `thread/rollback`, `thread/fork`, and `thread/read`
     /// (when `includeTurns` is true) responses.
     /// For all other responses and notifications returning a Thread,
@@ -1837,6 +1846,10 @@ pub enum UserInput {
         name: String,
         path: PathBuf,
     },
+    Document {
+        name: String,
+        text: String,
+    },
 }
 
 impl UserInput {
@@ -1852,6 +1865,7 @@ impl UserInput {
             UserInput::Image { url } => CoreUserInput::Image { image_url: url },
             UserInput::LocalImage { path } => CoreUserInput::LocalImage { path },
             UserInput::Skill { name, path } => CoreUserInput::Skill { name, path },
+            UserInput::Document { name, text } => CoreUserInput::Document { name, text },
         }
     }
 }
@@ -1869,6 +1883,7 @@ impl From<CoreUserInput> for UserInput {
             CoreUserInput::Image { image_url } => UserInput::Image { url: image_url },
             CoreUserInput::LocalImage { path } => UserInput::LocalImage { path },
             CoreUserInput::Skill { name, path } => UserInput::Skill { name, path },
+            CoreUserInput::Document { name, text } => UserInput::Document { name, text },
             _ => unreachable!("unsupported user input variant"),
         }
     }
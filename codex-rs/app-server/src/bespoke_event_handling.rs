@@ -205,9 +205,12 @@ pub(crate) async fn apply_bespoke_event_handling(
             turn_id,
             command,
             cwd,
+            injected_env: _,
             reason,
             proposed_execpolicy_amendment,
             parsed_cmd,
+            risk: _,
+            sandbox_denial_output: _,
         }) => match api_version {
             ApiVersion::V1 => {
                 let params = ExecCommandApprovalParams {
@@ -355,6 +358,20 @@ pub(crate) async fn apply_bespoke_event_handling(
                     .await;
             }
         }
+        EventMsg::McpSamplingRequest(request) if request.requires_approval => {
+            // App-server clients have no way to approve sampling requests yet,
+            // so decline them rather than leave the MCP server waiting.
+            if let Err(err) = conversation
+                .submit(Op::ResolveMcpSampling {
+                    server_name: request.server_name,
+                    request_id: request.id,
+                    decision: ReviewDecision::Denied,
+                })
+                .await
+            {
+                error!("failed to submit ResolveMcpSampling: {err}");
+            }
+        }
         // TODO(celia): properly construct McpToolCall TurnItem in core.
         EventMsg::McpToolCallBegin(begin_event) => {
             let notification = construct_mcp_tool_call_notification(
@@ -954,13 +971,13 @@ pub(crate) async fn apply_bespoke_event_handling(
                 .await;
         }
         EventMsg::ExecCommandEnd(exec_command_end_event) => {
+            let aggregated_output = exec_command_end_event.display_output().to_string();
             let ExecCommandEndEvent {
                 call_id,
                 command,
                 cwd,
                 parsed_cmd,
                 process_id,
-                aggregated_output,
                 exit_code,
                 duration,
                 ..
@@ -1808,6 +1825,7 @@ mod tests {
     use codex_app_server_protocol::TurnPlanStepStatus;
     use codex_core::protocol::CreditsSnapshot;
     use codex_core::protocol::McpInvocation;
+    use codex_core::protocol::McpToolCallErrorSource;
     use codex_core::protocol::RateLimitSnapshot;
     use codex_core::protocol::RateLimitWindow;
     use codex_core::protocol::TokenUsage;
@@ -2060,6 +2078,9 @@ mod tests {
                 total_tokens: 23,
             },
             model_context_window: Some(4096),
+            by_provider: Default::default(),
+            prompt_cache: Default::default(),
+            tool_output_tokens: 0,
         };
         let rate_limits = RateLimitSnapshot {
             primary: Some(RateLimitWindow {
@@ -2156,6 +2177,8 @@ mod tests {
                 tool: "list_mcp_resources".to_string(),
                 arguments: Some(serde_json::json!({"server": ""})),
             },
+            timeout_ms: None,
+            arguments_preview: String::new(),
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2314,6 +2337,8 @@ mod tests {
                 tool: "list_mcp_resources".to_string(),
                 arguments: None,
             },
+            timeout_ms: None,
+            arguments_preview: String::new(),
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2365,6 +2390,9 @@ mod tests {
             },
             duration: Duration::from_nanos(92708),
             result: Ok(result),
+            is_error: false,
+            error_source: None,
+            result_preview: String::new(),
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2408,6 +2436,9 @@ mod tests {
             },
             duration: Duration::from_millis(1),
             result: Err("boom".to_string()),
+            is_error: true,
+            error_source: Some(McpToolCallErrorSource::Transport),
+            result_preview: String::new(),
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2450,6 +2481,7 @@ mod tests {
             conversation_id,
             "turn-1",
             TurnDiffEvent {
+                turn_id: "turn-1".to_string(),
                 unified_diff: unified_diff.clone(),
             },
             ApiVersion::V2,
@@ -2485,6 +2517,7 @@ mod tests {
             conversation_id,
             "turn-1",
             TurnDiffEvent {
+                turn_id: "turn-1".to_string(),
                 unified_diff: "diff".to_string(),
             },
             ApiVersion::V1,
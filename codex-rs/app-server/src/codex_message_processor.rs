@@ -156,6 +156,7 @@ use codex_core::features::Feature;
 use codex_core::find_archived_thread_path_by_id_str;
 use codex_core::find_thread_path_by_id_str;
 use codex_core::git_info::git_diff_to_remote;
+use codex_core::live_session::is_live;
 use codex_core::mcp::collect_mcp_snapshot;
 use codex_core::mcp::group_tools_by_server;
 use codex_core::parse_cursor;
@@ -1433,6 +1434,7 @@ impl CodexMessageProcessor {
             base_instructions,
             developer_instructions,
             dynamic_tools,
+            workspace_roots,
             experimental_raw_events,
             personality,
             ephemeral,
@@ -1448,6 +1450,11 @@ impl CodexMessageProcessor {
             personality,
         );
         typesafe_overrides.ephemeral = ephemeral;
+        typesafe_overrides.workspace_roots = workspace_roots
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, path)| (name, PathBuf::from(path)))
+            .collect();
 
         let config = match derive_config_from_params(
             &self.cli_overrides,
@@ -1878,7 +1885,16 @@ impl CodexMessageProcessor {
             }
         };
 
-        let data = summaries.into_iter().map(summary_to_thread).collect();
+        let data = summaries
+            .into_iter()
+            .map(|summary| {
+                let live = is_live(&self.config.codex_home, summary.conversation_id);
+                Thread {
+                    live,
+                    ..summary_to_thread(summary)
+                }
+            })
+            .collect();
         let response = ThreadListResponse { data, next_cursor };
         self.outgoing.send_response(request_id, response).await;
     }
@@ -4802,6 +4818,7 @@ fn build_ephemeral_thread(thread_id: ThreadId, config_snapshot: &ThreadConfigSna
         cli_version: env!("CARGO_PKG_VERSION").to_string(),
         source: config_snapshot.session_source.clone().into(),
         git_info: None,
+        live: false,
         turns: Vec::new(),
     }
 }
@@ -4839,6 +4856,7 @@ pub(crate) fn summary_to_thread(summary: ConversationSummary) -> Thread {
         cli_version,
         source: source.into(),
         git_info,
+        live: false,
         turns: Vec::new(),
     }
 }
@@ -25,6 +25,27 @@ use crate::invocation::ExtractHeredocError;
 /// Detailed instructions for gpt-4.1 on how to use the `apply_patch` tool.
 pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_tool_instructions.md");
 
+/// Set to `1` in the environment of an `apply_patch` invocation to turn on
+/// [`ApplyPatchOptions::clear_readonly`].
+pub const CLEAR_READONLY_ENV_VAR: &str = "CODEX_APPLY_PATCH_CLEAR_READONLY";
+
+/// How [`apply_patch_with_options`] treats the files it changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApplyPatchOptions {
+    /// Make read-only files the patch updates or deletes writable for the
+    /// write and read-only again right after, instead of refusing the patch.
+    pub clear_readonly: bool,
+}
+
+impl ApplyPatchOptions {
+    /// Options requested through [`CLEAR_READONLY_ENV_VAR`].
+    pub fn from_env() -> Self {
+        Self {
+            clear_readonly: std::env::var(CLEAR_READONLY_ENV_VAR).is_ok_and(|value| value == "1"),
+        }
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ApplyPatchError {
     #[error(transparent)]
@@ -175,6 +196,16 @@ pub fn apply_patch(
     patch: &str,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_patch_with_options(patch, ApplyPatchOptions::default(), stdout, stderr)
+}
+
+/// Like [`apply_patch`], with control over read-only files.
+pub fn apply_patch_with_options(
+    patch: &str,
+    options: ApplyPatchOptions,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
     let hunks = match parse_patch(patch) {
         Ok(source) => source.hunks,
@@ -198,7 +229,7 @@ pub fn apply_patch(
         }
     };
 
-    apply_hunks(&hunks, stdout, stderr)?;
+    apply_hunks(&hunks, options, stdout, stderr)?;
 
     Ok(())
 }
@@ -206,6 +237,7 @@ pub fn apply_patch(
 /// Applies hunks and continues to update stdout/stderr
 pub fn apply_hunks(
     hunks: &[Hunk],
+    options: ApplyPatchOptions,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
@@ -236,7 +268,7 @@ pub fn apply_hunks(
         .collect::<Vec<&Path>>();
 
     // Delegate to a helper that applies each hunk to the filesystem.
-    match apply_hunks_to_files(hunks) {
+    match apply_hunks_to_files(hunks, options) {
         Ok(affected) => {
             print_summary(&affected, stdout).map_err(ApplyPatchError::from)?;
             Ok(())
@@ -267,10 +299,32 @@ pub struct AffectedPaths {
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
 /// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
+fn apply_hunks_to_files(
+    hunks: &[Hunk],
+    options: ApplyPatchOptions,
+) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
+    if !options.clear_readonly {
+        // Refuse up front so that no file of the patch is half applied.
+        let readonly: Vec<String> = hunks
+            .iter()
+            .flat_map(|hunk| match hunk {
+                Hunk::AddFile { path, .. } => [Some(path), None],
+                Hunk::DeleteFile { path } => [Some(path), None],
+                Hunk::UpdateFile {
+                    path, move_path, ..
+                } => [Some(path), move_path.as_ref()],
+            })
+            .flatten()
+            .filter(|path| is_readonly(path))
+            .map(|path| format!("file {} is read-only", path.display()))
+            .collect();
+        if !readonly.is_empty() {
+            anyhow::bail!("{}", readonly.join("\n"));
+        }
+    }
 
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
@@ -285,13 +339,11 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                         format!("Failed to create parent directories for {}", path.display())
                     })?;
                 }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                with_write_access(path, "write file", || std::fs::write(path, contents))?;
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                with_write_access(path, "delete file", || std::fs::remove_file(path))?;
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
@@ -309,14 +361,11 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                             format!("Failed to create parent directories for {}", dest.display())
                         })?;
                     }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    with_write_access(dest, "write file", || std::fs::write(dest, &new_contents))?;
+                    with_write_access(path, "remove original", || std::fs::remove_file(path))?;
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    with_write_access(path, "write file", || std::fs::write(path, &new_contents))?;
                     modified.push(path.clone());
                 }
             }
@@ -329,6 +378,82 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     })
 }
 
+fn is_readonly(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly())
+}
+
+/// Runs `change` on `path`. A read-only `path` is made writable first and
+/// read-only again afterwards, whether or not `change` succeeds; only patches
+/// applied with [`ApplyPatchOptions::clear_readonly`] get this far with one.
+/// A permission error names the file rather than surfacing as a bare I/O
+/// error.
+fn with_write_access(
+    path: &Path,
+    action: &str,
+    change: impl FnOnce() -> std::io::Result<()>,
+) -> anyhow::Result<()> {
+    let _restore = match ReadOnlyRestore::clear(path) {
+        Ok(restore) => restore,
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("Failed to clear the read-only flag of {}", path.display())
+            });
+        }
+    };
+    match change() {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+            anyhow::bail!("Failed to {action} {}: permission denied", path.display())
+        }
+        Err(err) => Err(err).with_context(|| format!("Failed to {action} {}", path.display())),
+    }
+}
+
+/// Sets the read-only flag of a file back when dropped.
+struct ReadOnlyRestore<'a> {
+    path: &'a Path,
+    permissions: Option<std::fs::Permissions>,
+}
+
+impl<'a> ReadOnlyRestore<'a> {
+    fn clear(path: &'a Path) -> std::io::Result<Self> {
+        let permissions = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.permissions().readonly() => Some(metadata.permissions()),
+            _ => None,
+        };
+        if let Some(original) = &permissions {
+            std::fs::set_permissions(path, writable(original))?;
+        }
+        Ok(Self { path, permissions })
+    }
+}
+
+impl Drop for ReadOnlyRestore<'_> {
+    fn drop(&mut self) {
+        // A deleted or moved file has nothing left to restore.
+        if let Some(permissions) = self.permissions.take()
+            && self.path.exists()
+        {
+            let _ = std::fs::set_permissions(self.path, permissions);
+        }
+    }
+}
+
+/// `permissions` with only the owner's write bit added.
+#[cfg(unix)]
+fn writable(permissions: &std::fs::Permissions) -> std::fs::Permissions {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::Permissions::from_mode(permissions.mode() | 0o200)
+}
+
+/// `permissions` without the read-only attribute.
+#[cfg(not(unix))]
+fn writable(permissions: &std::fs::Permissions) -> std::fs::Permissions {
+    let mut permissions = permissions.clone();
+    permissions.set_readonly(false);
+    permissions
+}
+
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
@@ -1061,5 +1186,59 @@ g
         let mut stderr = Vec::new();
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            format!("file {} is read-only\n", path.display())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "before\n");
+    }
+
+    #[test]
+    fn test_read_only_files_refuse_the_whole_patch() {
+        let dir = tempdir().unwrap();
+        let writable = dir.path().join("writable.txt");
+        let readonly = dir.path().join("readonly.txt");
+        fs::write(&writable, "before\n").unwrap();
+        fs::write(&readonly, "before\n").unwrap();
+        let mut perms = fs::metadata(&readonly).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&readonly, perms).unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after\n*** Delete File: {}",
+            writable.display(),
+            readonly.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        assert!(apply_patch(&patch, &mut stdout, &mut stderr).is_err());
+        assert_eq!(fs::read_to_string(&writable).unwrap(), "before\n");
+        assert!(readonly.exists());
+    }
+
+    #[test]
+    fn test_clear_readonly_restores_the_flag_when_a_later_file_fails() {
+        let dir = tempdir().unwrap();
+        let readonly = dir.path().join("readonly.txt");
+        let missing = dir.path().join("missing.txt");
+        fs::write(&readonly, "before\n").unwrap();
+        let mut perms = fs::metadata(&readonly).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&readonly, perms).unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after\n*** Update File: {}\n@@\n-old\n+new",
+            readonly.display(),
+            missing.display()
+        ));
+        let options = ApplyPatchOptions {
+            clear_readonly: true,
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        assert!(apply_patch_with_options(&patch, options, &mut stdout, &mut stderr).is_err());
+
+        assert_eq!(fs::read_to_string(&readonly).unwrap(), "after\n");
+        assert!(fs::metadata(&readonly).unwrap().permissions().readonly());
     }
 }
@@ -34,7 +34,13 @@ pub fn arg0_dispatch() -> Option<TempDir> {
             Some(patch_arg) => {
                 let mut stdout = std::io::stdout();
                 let mut stderr = std::io::stderr();
-                match codex_apply_patch::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
+                let options = codex_apply_patch::ApplyPatchOptions::from_env();
+                match codex_apply_patch::apply_patch_with_options(
+                    &patch_arg,
+                    options,
+                    &mut stdout,
+                    &mut stderr,
+                ) {
                     Ok(()) => 0,
                     Err(_) => 1,
                 }
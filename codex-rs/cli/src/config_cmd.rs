@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::profile::ConfigProfile;
+use codex_core::config::profile::resolve_profile;
+
+/// Subcommands:
+/// - `show` — print the merged config, or a single profile (with `--resolved`)
+#[derive(Debug, clap::Parser)]
+pub struct ConfigCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigSubcommand {
+    /// Print the config merged from every config layer, or a single profile.
+    Show(ShowArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ShowArgs {
+    /// Print only this profile.
+    #[arg(long = "profile", short = 'p', value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Apply the profile's `extends` chain and print the merged result. Uses
+    /// the active profile when `--profile` is not given.
+    #[arg(long)]
+    pub resolved: bool,
+}
+
+impl ConfigCli {
+    pub async fn run(self) -> Result<()> {
+        let ConfigCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        match subcommand {
+            ConfigSubcommand::Show(args) => {
+                run_show(&config_overrides, args).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn run_show(config_overrides: &CliConfigOverrides, show_args: ShowArgs) -> Result<()> {
+    let ShowArgs { profile, resolved } = show_args;
+    let overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    // Loading with the profile selected surfaces missing profiles and
+    // inheritance cycles with the same errors a session would report.
+    let config = Config::load_with_cli_overrides_and_harness_overrides(
+        overrides,
+        ConfigOverrides {
+            config_profile: profile.clone(),
+            ..Default::default()
+        },
+    )
+    .await
+    .context("failed to load configuration")?;
+    let effective_config = config.config_layer_stack.effective_config();
+
+    let profile_name = match profile {
+        Some(name) => name,
+        None if resolved => config
+            .active_profile
+            .clone()
+            .context("no profile is active; pass --profile <NAME>")?,
+        None => {
+            print!("{}", toml::to_string_pretty(&effective_config)?);
+            return Ok(());
+        }
+    };
+
+    let profiles: HashMap<String, ConfigProfile> = effective_config
+        .get("profiles")
+        .cloned()
+        .map(toml::Value::try_into)
+        .transpose()
+        .context("failed to parse profiles")?
+        .unwrap_or_default();
+    let profile = if resolved {
+        resolve_profile(&profiles, &profile_name)?
+    } else {
+        profiles
+            .get(&profile_name)
+            .cloned()
+            .with_context(|| format!("config profile `{profile_name}` not found"))?
+    };
+    print!("{}", toml::to_string_pretty(&profile)?);
+
+    Ok(())
+}
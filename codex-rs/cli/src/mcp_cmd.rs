@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -11,6 +12,7 @@ use codex_core::config::Config;
 use codex_core::config::edit::ConfigEditsBuilder;
 use codex_core::config::find_codex_home;
 use codex_core::config::load_global_mcp_servers;
+use codex_core::config::types::McpEnvSource;
 use codex_core::config::types::McpServerConfig;
 use codex_core::config::types::McpServerTransportConfig;
 use codex_core::mcp::auth::compute_auth_statuses;
@@ -220,6 +222,7 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
                 args: command_args,
                 env: env_map,
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: None,
             }
         }
@@ -235,6 +238,8 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
             bearer_token_env_var,
             http_headers: None,
             env_http_headers: None,
+            header_sources: None,
+            tls: None,
         },
         AddMcpTransportArgs { .. } => bail!("exactly one of --command or --url must be provided"),
     };
@@ -248,6 +253,8 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
         enabled_tools: None,
         disabled_tools: None,
         scopes: None,
+        max_restart_attempts: None,
+        sampling: None,
     };
 
     servers.insert(name.clone(), new_entry);
@@ -265,6 +272,7 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
         bearer_token_env_var: None,
         http_headers,
         env_http_headers,
+        ..
     } = transport
     {
         match supports_oauth_login(&url).await {
@@ -428,6 +436,7 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                         args,
                         env,
                         env_vars,
+                        env_sources,
                         cwd,
                     } => serde_json::json!({
                         "type": "stdio",
@@ -435,6 +444,7 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                         "args": args,
                         "env": env,
                         "env_vars": env_vars,
+                        "env_sources": env_sources,
                         "cwd": cwd,
                     }),
                     McpServerTransportConfig::StreamableHttp {
@@ -442,6 +452,8 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                         bearer_token_env_var,
                         http_headers,
                         env_http_headers,
+                        header_sources,
+                        tls,
                     } => {
                         serde_json::json!({
                             "type": "streamable_http",
@@ -449,6 +461,8 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                             "bearer_token_env_var": bearer_token_env_var,
                             "http_headers": http_headers,
                             "env_http_headers": env_http_headers,
+                            "header_sources": header_sources,
+                            "tls": tls,
                         })
                     }
                 };
@@ -488,6 +502,7 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => {
                 let args_display = if args.is_empty() {
@@ -495,7 +510,7 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                 } else {
                     args.join(" ")
                 };
-                let env_display = format_env_display(env.as_ref(), env_vars);
+                let env_display = stdio_env_display(env.as_ref(), env_vars, env_sources.as_ref());
                 let cwd_display = cwd
                     .as_ref()
                     .map(|path| path.display().to_string())
@@ -667,6 +682,7 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => serde_json::json!({
                 "type": "stdio",
@@ -674,6 +690,7 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 "args": args,
                 "env": env,
                 "env_vars": env_vars,
+                "env_sources": env_sources,
                 "cwd": cwd,
             }),
             McpServerTransportConfig::StreamableHttp {
@@ -681,12 +698,16 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                header_sources,
+                tls,
             } => serde_json::json!({
                 "type": "streamable_http",
                 "url": url,
                 "bearer_token_env_var": bearer_token_env_var,
                 "http_headers": http_headers,
                 "env_http_headers": env_http_headers,
+                "header_sources": header_sources,
+                "tls": tls,
             }),
         };
         let output = serde_json::to_string_pretty(&serde_json::json!({
@@ -739,6 +760,7 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
             args,
             env,
             env_vars,
+            env_sources,
             cwd,
         } => {
             println!("  transport: stdio");
@@ -755,7 +777,7 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 .filter(|value| !value.is_empty())
                 .unwrap_or_else(|| "-".to_string());
             println!("  cwd: {cwd_display}");
-            let env_display = format_env_display(env.as_ref(), env_vars);
+            let env_display = stdio_env_display(env.as_ref(), env_vars, env_sources.as_ref());
             println!("  env: {env_display}");
         }
         McpServerTransportConfig::StreamableHttp {
@@ -763,6 +785,8 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            header_sources,
+            tls,
         } => {
             println!("  transport: streamable_http");
             println!("  url: {url}");
@@ -794,6 +818,35 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 _ => "-".to_string(),
             };
             println!("  env_http_headers: {env_headers_display}");
+            let header_sources_display = match header_sources {
+                Some(map) if !map.is_empty() => {
+                    let mut pairs: Vec<_> = map.iter().collect();
+                    pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                    pairs
+                        .into_iter()
+                        .map(|(k, source)| format!("{k}={source}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+                _ => "-".to_string(),
+            };
+            println!("  header_sources: {header_sources_display}");
+            if let Some(tls) = tls {
+                let path_display = |path: &Option<PathBuf>| {
+                    path.as_ref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                };
+                println!(
+                    "  tls.ca_certificate: {}",
+                    path_display(&tls.ca_certificate)
+                );
+                println!(
+                    "  tls.client_certificate: {}",
+                    path_display(&tls.client_certificate)
+                );
+                println!("  tls.client_key: {}", path_display(&tls.client_key));
+            }
         }
     }
     if let Some(timeout) = server.startup_timeout_sec {
@@ -844,3 +897,17 @@ fn format_mcp_status(config: &McpServerConfig) -> String {
         "disabled".to_string()
     }
 }
+
+fn stdio_env_display(
+    env: Option<&HashMap<String, String>>,
+    env_vars: &[String],
+    env_sources: Option<&HashMap<String, McpEnvSource>>,
+) -> String {
+    let mut names = env_vars.to_vec();
+    if let Some(env_sources) = env_sources {
+        let mut sourced: Vec<String> = env_sources.keys().cloned().collect();
+        sourced.sort();
+        names.extend(sourced);
+    }
+    format_env_display(env, &names)
+}
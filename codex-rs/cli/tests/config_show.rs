@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use anyhow::Result;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::new(codex_utils_cargo_bin::cargo_bin("codex")?);
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+#[test]
+fn show_resolved_profile_applies_extends() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[profiles.base]
+model = "gpt-base"
+approval_policy = "on-request"
+
+[profiles.strict-sandbox]
+sandbox_mode = "read-only"
+
+[profiles.careful-review]
+extends = ["base", "strict-sandbox"]
+approval_policy = "untrusted"
+"#,
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args([
+            "config",
+            "show",
+            "--profile",
+            "careful-review",
+            "--resolved",
+        ])
+        .output()?;
+    assert!(output.status.success());
+    let resolved: toml::Value = toml::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(
+        resolved,
+        toml::from_str::<toml::Value>(
+            r#"
+model = "gpt-base"
+approval_policy = "untrusted"
+sandbox_mode = "read-only"
+"#
+        )?
+    );
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["config", "show", "--profile", "careful-review"])
+        .output()?;
+    assert!(output.status.success());
+    let raw: toml::Value = toml::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(
+        raw,
+        toml::from_str::<toml::Value>(
+            r#"
+extends = ["base", "strict-sandbox"]
+approval_policy = "untrusted"
+"#
+        )?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn show_reports_profile_cycles() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[profiles.a]
+extends = ["b"]
+
+[profiles.b]
+extends = ["a"]
+"#,
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["config", "show", "--profile", "a", "--resolved"])
+        .assert()
+        .failure()
+        .stderr(contains("config profile inheritance cycle: a -> b -> a"));
+
+    Ok(())
+}
@@ -33,12 +33,14 @@ async fn add_and_remove_server_updates_global_config() -> Result<()> {
             args,
             env,
             env_vars,
+            env_sources,
             cwd,
         } => {
             assert_eq!(command, "echo");
             assert_eq!(args, &vec!["hello".to_string()]);
             assert!(env.is_none());
             assert!(env_vars.is_empty());
+            assert!(env_sources.is_none());
             assert!(cwd.is_none());
         }
         other => panic!("unexpected transport: {other:?}"),
@@ -122,6 +124,7 @@ async fn add_streamable_http_without_manual_token() -> Result<()> {
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            ..
         } => {
             assert_eq!(url, "https://example.com/mcp");
             assert!(bearer_token_env_var.is_none());
@@ -164,6 +167,7 @@ async fn add_streamable_http_with_custom_env_var() -> Result<()> {
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            ..
         } => {
             assert_eq!(url, "https://example.com/issues");
             assert_eq!(bearer_token_env_var.as_deref(), Some("GITHUB_TOKEN"));
@@ -104,6 +104,7 @@ async fn list_and_get_render_expected_output() -> Result<()> {
                 "APP_TOKEN",
                 "WORKSPACE_ID"
               ],
+              "env_sources": null,
               "cwd": null
             },
             "startup_timeout_sec": null,
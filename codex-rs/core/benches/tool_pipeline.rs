@@ -0,0 +1,456 @@
+//! Benchmarks for the exec and event pipeline.
+//!
+//! Run with `cargo bench -p codex-core`. Everything runs offline against an
+//! in-memory session; no model, network, or git repository is needed. The
+//! `exec_allocations` group counts heap allocations instead of time.
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_core::bench_support::BenchSession;
+use codex_core::bench_support::ParseCache;
+use codex_core::bench_support::format_exec_output;
+use codex_core::bench_support::format_exec_output_separately;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::StreamOutput;
+use codex_core::parse_command::parse_command;
+use codex_core::protocol::AgentMessageDeltaEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandSource;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::turn_diff_tracker::TurnDiffTracker;
+use criterion::BatchSize;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::measurement::Measurement;
+use criterion::measurement::ValueFormatter;
+
+const KB: usize = 1024;
+const MB: usize = 1024 * KB;
+
+/// Byte budget used when formatting output, on the order of a model turn.
+const FORMAT_LIMIT_BYTES: usize = 10 * KB;
+
+/// Commands of the kind the model actually issues, including a heredoc.
+const COMMAND_CORPUS: &[&str] = &[
+    "ls -la",
+    "rg -n \"fn main\" src",
+    "cat src/lib.rs | head -n 200",
+    "git status --short && git diff --stat",
+    "cargo test -p codex-core -- --nocapture",
+    "sed -n '1,120p' core/src/codex.rs",
+    "find . -name '*.rs' -not -path './target/*' | xargs wc -l | sort -n | tail",
+    "cd codex-rs && just fmt && cargo clippy --all-targets -- -D warnings",
+    "python3 - <<'EOF'\nimport json\nprint(json.dumps({'ok': True}))\nEOF",
+    "cat <<'EOF' > notes.md\n# Notes\n\nSome text.\nEOF",
+];
+
+/// Heap allocations made by the process so far, counted by
+/// [`CountingAllocator`].
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+struct CountingAllocator;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// SAFETY: every call is forwarded unchanged to the system allocator.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Measures heap allocations rather than wall time.
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> u64 {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: u64) -> u64 {
+        ALLOCATIONS.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &u64, v2: &u64) -> u64 {
+        v1 + v2
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationFormatter
+    }
+}
+
+/// Reports allocation counts unscaled; the groups measured with
+/// [`Allocations`] set no throughput.
+struct AllocationFormatter;
+
+impl ValueFormatter for AllocationFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+fn output_of_size(bytes: usize) -> ExecToolCallOutput {
+    let line = "the quick brown fox jumps over the lazy dog 0123456789\n";
+    let text: String = line.repeat(bytes / line.len() + 1)[..bytes].to_string();
+    ExecToolCallOutput {
+        exit_code: 0,
+        stdout: StreamOutput::new(text.clone()),
+        stderr: StreamOutput::new(String::new()),
+        aggregated_output: StreamOutput::new(text),
+        duration: Duration::from_millis(25),
+        suspended: false,
+        timed_out: false,
+        deltas: Default::default(),
+    }
+}
+
+fn bash(script: &str) -> Vec<String> {
+    vec!["bash".to_string(), "-lc".to_string(), script.to_string()]
+}
+
+fn size_label(bytes: usize) -> String {
+    if bytes >= MB {
+        format!("{}MB", bytes / MB)
+    } else {
+        format!("{}KB", bytes / KB)
+    }
+}
+
+fn emitter_round_trip(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let session = runtime.block_on(BenchSession::new());
+    let command = bash("cargo build");
+
+    let mut group = c.benchmark_group("emitter_round_trip");
+    for bytes in [KB, 64 * KB, MB] {
+        let output = output_of_size(bytes);
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size_label(bytes)),
+            &output,
+            |b, output| {
+                b.iter_batched(
+                    || output.clone(),
+                    |output| runtime.block_on(session.exec_round_trip(&command, output)),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Allocations of one exec call's begin and end events. The command, cwd,
+/// and parsed command move from the emitter into the end event, so the
+/// count should not grow with the size of the command.
+fn exec_allocations(c: &mut Criterion<Allocations>) {
+    // A single thread keeps other workers' allocations out of the count.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let session = runtime.block_on(BenchSession::new());
+    let output = output_of_size(KB);
+
+    let mut group = c.benchmark_group("exec_allocations");
+    for (label, script) in [
+        ("short_command", "cargo build".to_string()),
+        (
+            "heredoc_50KB",
+            format!(
+                "cat <<'EOF' > out.txt\n{}EOF",
+                "printf 'some generated line of output'\n".repeat(50 * KB / 38)
+            ),
+        ),
+    ] {
+        let command = bash(&script);
+        group.bench_function(label, |b| {
+            b.iter_batched(
+                || output.clone(),
+                |output| runtime.block_on(session.exec_round_trip(&command, output)),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn format_exec_output_truncation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format_exec_output");
+    for bytes in [KB, MB, 100 * MB] {
+        let output = output_of_size(bytes);
+        if bytes >= 100 * MB {
+            group.sample_size(10);
+        }
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size_label(bytes)),
+            &output,
+            |b, output| b.iter(|| format_exec_output(black_box(output), FORMAT_LIMIT_BYTES)),
+        );
+    }
+    group.finish();
+}
+
+fn format_exec_output_single_pass(c: &mut Criterion) {
+    let output = output_of_size(10 * MB);
+
+    // One pass for both renderings versus the model and UI strings rendered
+    // separately.
+    let mut group = c.benchmark_group("format_exec_output_10MB");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(output.aggregated_output.text.len() as u64));
+    group.bench_function("single_pass", |b| {
+        b.iter(|| format_exec_output(black_box(&output), FORMAT_LIMIT_BYTES))
+    });
+    group.bench_function("separate", |b| {
+        b.iter(|| format_exec_output_separately(black_box(&output), FORMAT_LIMIT_BYTES))
+    });
+    group.finish();
+}
+
+fn parse_command_corpus(c: &mut Criterion) {
+    let commands: Vec<Vec<String>> = COMMAND_CORPUS.iter().map(|script| bash(script)).collect();
+    c.bench_function("parse_command/corpus", |b| {
+        b.iter(|| {
+            for command in &commands {
+                black_box(parse_command(black_box(command)));
+            }
+        });
+    });
+}
+
+fn parse_command_cache(c: &mut Criterion) {
+    let body = "printf 'some generated line of output'\n".repeat(50 * KB / 38);
+    let command = bash(&format!("cat <<'EOF' > out.txt\n{body}EOF"));
+
+    // A 50 KB heredoc parsed from scratch versus looked up again.
+    let mut group = c.benchmark_group("parse_command_cache/heredoc_50KB");
+    group.bench_function("cold", |b| {
+        b.iter_batched(
+            ParseCache::default,
+            |cache| cache.parse(black_box(&command)),
+            BatchSize::SmallInput,
+        );
+    });
+    let cache = ParseCache::default();
+    cache.parse(&command);
+    group.bench_function("cached", |b| b.iter(|| cache.parse(black_box(&command))));
+    group.finish();
+}
+
+fn turn_diff_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("turn_diff");
+    group.sample_size(20);
+    for files in [10, 100, 1_000] {
+        // Additions have no baseline, so every file diffs in full. A fresh
+        // tracker per iteration keeps the per-file diff cache cold.
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut changes = HashMap::new();
+        for i in 0..files {
+            let path = dir.path().join(format!("file-{i}.txt"));
+            let content = format!("line one of {i}\nline two\n");
+            std::fs::write(&path, &content).expect("write file");
+            changes.insert(path, FileChange::Add { content });
+        }
+
+        group.bench_function(BenchmarkId::from_parameter(files), |b| {
+            b.iter_batched(
+                || {
+                    let mut tracker = TurnDiffTracker::new();
+                    tracker.on_patch_begin(&changes);
+                    tracker
+                },
+                |mut tracker| tracker.get_unified_diff().expect("diff"),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn turn_diff_one_modified(c: &mut Criterion) {
+    // 2,000 tracked files with one modified between diffs; the others keep
+    // their cached diffs. Files are given an old mtime so none of them look
+    // racily clean.
+    let settled = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let write_settled = |path: &Path, content: &str| {
+        std::fs::write(path, content).expect("write file");
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .expect("open file")
+            .set_modified(settled)
+            .expect("set mtime");
+    };
+    let update = |path: &Path| {
+        (
+            path.to_path_buf(),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: None,
+            },
+        )
+    };
+    let dir = tempfile::tempdir().expect("tempdir");
+    let files: Vec<PathBuf> = (0..2_000)
+        .map(|i| dir.path().join(format!("file-{i:04}.txt")))
+        .collect();
+    for (i, file) in files.iter().enumerate() {
+        write_settled(file, &format!("line {i}\n").repeat(50));
+    }
+    let mut tracker = TurnDiffTracker::new();
+    tracker.on_patch_begin(&files.iter().map(PathBuf::as_path).map(update).collect());
+    for (i, file) in files.iter().enumerate() {
+        write_settled(file, &format!("line {i}\n").repeat(51));
+    }
+    tracker.get_unified_diff().expect("diff");
+
+    let modified = &files[1_234];
+    let modified_changes = HashMap::from([update(modified)]);
+    let mut round = 0u64;
+    let mut group = c.benchmark_group("turn_diff_2000_files");
+    group.sample_size(20);
+    group.bench_function("one_modified", |b| {
+        b.iter(|| {
+            round += 1;
+            tracker.on_patch_begin(&modified_changes);
+            std::fs::write(modified, format!("rewritten {round}\n")).expect("write file");
+            tracker.get_unified_diff().expect("diff")
+        });
+    });
+    group.finish();
+}
+
+fn event_serialization(c: &mut Criterion) {
+    let output = output_of_size(64 * KB).aggregated_output.text;
+    let events = vec![
+        EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+            delta: "Looking at the failing test now.".to_string(),
+        }),
+        EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+            call_id: "call-1".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: bash("cargo test"),
+            cwd: PathBuf::from("/repo"),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::ModelShell,
+            interaction_input: None,
+            stdout: output.clone(),
+            stderr: String::new(),
+            aggregated_output: output.clone(),
+            exit_code: 0,
+            outcome: None,
+            duration: Duration::from_millis(1_500),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
+            formatted_output: output,
+            saved_output_path: None,
+            approval: None,
+            error: None,
+        }),
+        EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: "call-2".to_string(),
+            turn_id: "turn-1".to_string(),
+            auto_approved: true,
+            changes: HashMap::from([(
+                PathBuf::from("src/lib.rs"),
+                FileChange::Update {
+                    unified_diff: "@@ -1 +1 @@\n-old\n+new\n".to_string(),
+                    move_path: None,
+                },
+            )]),
+            summaries: Vec::new(),
+            timeout_ms: None,
+        }),
+    ];
+    let bytes: usize = events
+        .iter()
+        .map(|event| serde_json::to_vec(event).expect("serialize").len())
+        .sum();
+
+    let mut group = c.benchmark_group("event_serialization");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.bench_function("mixed", |b| {
+        b.iter(|| {
+            for event in &events {
+                black_box(serde_json::to_vec(black_box(event)).expect("serialize"));
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    emitter_round_trip,
+    format_exec_output_truncation,
+    format_exec_output_single_pass,
+    parse_command_corpus,
+    parse_command_cache,
+    turn_diff_generation,
+    turn_diff_one_modified,
+    event_serialization
+);
+criterion_group!(
+    name = allocation_benches;
+    config = Criterion::default().with_measurement(Allocations);
+    targets = exec_allocations
+);
+criterion_main!(benches, allocation_benches);
@@ -0,0 +1,110 @@
+//! Drives a session from Rust with `codex_core::Conversation`.
+//!
+//! A local mock server stands in for the model provider: its first response
+//! calls the in-process `add` tool and its second answers with the sum.
+//! Point `config.model_provider` at a real provider to talk to a model.
+//!
+//! Run with `cargo run -p codex-core --example embed_conversation`.
+
+use std::sync::Arc;
+
+use codex_core::CodexAuth;
+use codex_core::Conversation;
+use codex_core::InProcessTool;
+use codex_core::ModelProviderInfo;
+use codex_core::ThreadManager;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_protocol::user_input::UserInput;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use futures::StreamExt;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("call-1", "add", r#"{"a":2,"b":3}"#),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_assistant_message("msg-1", "2 + 3 = 5"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let home = TempDir::new()?;
+    let mut config = load_default_config_for_test(&home).await;
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let manager = ThreadManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("dummy"),
+        config.model_provider.clone(),
+        config.codex_home.clone(),
+    );
+
+    let add = InProcessTool::new(
+        "add",
+        "Adds two integers.",
+        json!({
+            "type": "object",
+            "properties": {
+                "a": { "type": "integer" },
+                "b": { "type": "integer" }
+            },
+            "required": ["a", "b"],
+            "additionalProperties": false
+        }),
+        |arguments: Value| async move {
+            let operand = |name: &str| {
+                arguments[name]
+                    .as_i64()
+                    .ok_or_else(|| format!("`{name}` must be an integer"))
+            };
+            Ok((operand("a")? + operand("b")?).to_string())
+        },
+    );
+    let conversation = Conversation::start_in(Arc::new(manager), config, vec![add]).await?;
+
+    let events = conversation
+        .submit(vec![UserInput::Text {
+            text: "What is 2 + 3?".to_string(),
+            text_elements: Vec::new(),
+        }])
+        .await?;
+    let mut events = std::pin::pin!(events);
+    while let Some(event) = events.next().await {
+        match event {
+            EventMsg::McpToolCallBegin(begin) => {
+                println!("calling {}", begin.invocation.tool);
+            }
+            EventMsg::McpToolCallEnd(end) => {
+                println!("{} finished in {:?}", end.invocation.tool, end.duration);
+            }
+            EventMsg::AgentMessage(message) => println!("agent: {}", message.message),
+            EventMsg::TurnComplete(_) => println!("turn complete"),
+            _ => {}
+        }
+    }
+
+    conversation.shutdown().await?;
+    Ok(())
+}
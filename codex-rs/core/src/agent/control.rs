@@ -231,6 +231,7 @@ mod tests {
     async fn on_event_updates_status_from_task_started() {
         let status = agent_status_from_event(&EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }));
         assert_eq!(status, Some(AgentStatus::Running));
     }
@@ -1,13 +1,20 @@
+use async_trait::async_trait;
 use chrono::DateTime;
 use chrono::Utc;
 use codex_api::AuthProvider as ApiAuthProvider;
+use codex_api::ReqwestTransport;
 use codex_api::TransportError;
 use codex_api::error::ApiError;
 use codex_api::rate_limits::parse_rate_limit;
+use codex_client::HttpTransport;
+use codex_client::Request;
+use codex_client::Response;
+use codex_client::StreamResponse;
 use http::HeaderMap;
 use serde::Deserialize;
 
 use crate::auth::CodexAuth;
+use crate::default_client::build_reqwest_client;
 use crate::error::CodexErr;
 use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
@@ -164,3 +171,41 @@ impl ApiAuthProvider for CoreAuthProvider {
         self.account_id.clone()
     }
 }
+
+/// The HTTP transport for requests to a provider: reqwest, or the script of
+/// a [`crate::scripted_model::ScriptedModel`] in tests.
+#[derive(Clone)]
+pub(crate) enum ProviderTransport {
+    Reqwest(ReqwestTransport),
+    #[cfg(any(test, feature = "test-support"))]
+    Scripted(crate::scripted_model::ScriptedTransport),
+}
+
+pub(crate) fn provider_transport(provider: &ModelProviderInfo) -> ProviderTransport {
+    #[cfg(any(test, feature = "test-support"))]
+    if let Some(transport) = crate::scripted_model::scripted_transport(provider) {
+        return ProviderTransport::Scripted(transport);
+    }
+    #[cfg(not(any(test, feature = "test-support")))]
+    let _ = provider;
+    ProviderTransport::Reqwest(ReqwestTransport::new(build_reqwest_client()))
+}
+
+#[async_trait]
+impl HttpTransport for ProviderTransport {
+    async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+        match self {
+            Self::Reqwest(transport) => transport.execute(req).await,
+            #[cfg(any(test, feature = "test-support"))]
+            Self::Scripted(transport) => transport.execute(req).await,
+        }
+    }
+
+    async fn stream(&self, req: Request) -> Result<StreamResponse, TransportError> {
+        match self {
+            Self::Reqwest(transport) => transport.stream(req).await,
+            #[cfg(any(test, feature = "test-support"))]
+            Self::Scripted(transport) => transport.stream(req).await,
+        }
+    }
+}
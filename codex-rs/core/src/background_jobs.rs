@@ -0,0 +1,415 @@
+//! Shell commands the model started with `background: true`.
+//!
+//! The shell handler settles approval and the sandbox for the call as usual,
+//! then hands the prepared command to [`BackgroundJobManager::spawn`] and
+//! returns the job id to the model. The job runs detached from that turn
+//! until it exits, reaches its timeout or is killed; its end is reported with
+//! a `BackgroundJobStatusEvent` in whichever turn is running then, and its
+//! output is kept for `get_background_job`, in full in a log file under the
+//! session temp dir. Jobs still running at shutdown are killed so their
+//! final status reaches the rollout.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::protocol::BackgroundJobStatus;
+use codex_protocol::protocol::BackgroundJobStatusEvent;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::SandboxPolicy;
+use indexmap::IndexMap;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::codex::Session;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecToolCallOutput;
+use crate::sandboxing::ExecEnv;
+use crate::sandboxing::execute_env;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::formatted_truncate_text;
+
+/// Jobs that may run at once; starting another fails until one ends.
+pub(crate) const MAX_RUNNING_JOBS: usize = 4;
+
+/// Timeout of a background job whose call has no `timeout_ms`, before the
+/// `[tool_timeouts]` ceiling and the run deadline apply.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// A command ready to start as a background job.
+pub(crate) struct NewJob {
+    pub(crate) call_id: String,
+    /// The command as the model asked for it, for events and listings.
+    pub(crate) command: Vec<String>,
+    pub(crate) env: ExecEnv,
+    pub(crate) sandbox_policy: SandboxPolicy,
+    pub(crate) timeout: Duration,
+    pub(crate) truncation_policy: TruncationPolicy,
+}
+
+/// What the job tools report about one job.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct JobSnapshot {
+    pub(crate) id: String,
+    pub(crate) command: Vec<String>,
+    pub(crate) status: BackgroundJobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) elapsed_ms: u64,
+    /// Combined stdout and stderr, once the job has ended.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) output: Option<String>,
+    /// File holding the untruncated output, once the job has ended.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) log_path: Option<PathBuf>,
+}
+
+struct BackgroundJob {
+    call_id: String,
+    command: Vec<String>,
+    started_at: Instant,
+    cancel: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+    finished: Option<FinishedJob>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FinishedJob {
+    status: BackgroundJobStatus,
+    exit_code: Option<i32>,
+    duration: Duration,
+    output: String,
+    log_path: Option<PathBuf>,
+}
+
+#[derive(Default)]
+pub(crate) struct BackgroundJobManager {
+    next_id: AtomicU64,
+    jobs: Mutex<IndexMap<String, BackgroundJob>>,
+}
+
+impl BackgroundJobManager {
+    /// Starts `job` and returns the event announcing it. `sub_id` is the turn
+    /// that started the job; its end is reported there only when no other
+    /// turn is running by then.
+    pub(crate) async fn spawn(
+        &self,
+        session: Arc<Session>,
+        sub_id: String,
+        job: NewJob,
+    ) -> Result<BackgroundJobStatusEvent, String> {
+        let mut jobs = self.jobs.lock().await;
+        let running = jobs.values().filter(|job| job.finished.is_none()).count();
+        if running >= MAX_RUNNING_JOBS {
+            return Err(format!(
+                "{MAX_RUNNING_JOBS} background jobs are already running; wait for one to finish or kill one first"
+            ));
+        }
+
+        let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let cancel = CancellationToken::new();
+        let NewJob {
+            call_id,
+            command,
+            mut env,
+            sandbox_policy,
+            timeout,
+            truncation_policy,
+        } = job;
+        env.expiration = ExecExpiration::Cancellation(cancel.clone());
+        let handle = tokio::spawn(run_job(
+            session,
+            sub_id,
+            id.clone(),
+            env,
+            sandbox_policy,
+            timeout,
+            cancel.clone(),
+            truncation_policy,
+        ));
+        let event = BackgroundJobStatusEvent {
+            job_id: id.clone(),
+            call_id: call_id.clone(),
+            command: command.clone(),
+            status: BackgroundJobStatus::Running,
+            exit_code: None,
+            duration: Duration::ZERO,
+        };
+        jobs.insert(
+            id,
+            BackgroundJob {
+                call_id,
+                command,
+                started_at: Instant::now(),
+                cancel,
+                handle: Some(handle),
+                finished: None,
+            },
+        );
+        Ok(event)
+    }
+
+    pub(crate) async fn get(&self, id: &str) -> Option<JobSnapshot> {
+        let jobs = self.jobs.lock().await;
+        jobs.get(id).map(|job| job.snapshot(id, true))
+    }
+
+    /// Every job of the session, oldest first, without their output.
+    pub(crate) async fn list(&self) -> Vec<JobSnapshot> {
+        let jobs = self.jobs.lock().await;
+        jobs.iter()
+            .map(|(id, job)| job.snapshot(id, false))
+            .collect()
+    }
+
+    /// Kills job `id` and waits until its end has been reported.
+    pub(crate) async fn kill(&self, id: &str) -> Result<JobSnapshot, String> {
+        let handle = {
+            let mut jobs = self.jobs.lock().await;
+            let job = jobs
+                .get_mut(id)
+                .ok_or_else(|| format!("no background job `{id}`"))?;
+            if let Some(finished) = &job.finished {
+                return Err(format!("background job `{id}` already {}", finished.status));
+            }
+            job.cancel.cancel();
+            job.handle.take()
+        };
+        if let Some(handle) = handle {
+            let _ = handle.await;
+        }
+        self.get(id)
+            .await
+            .ok_or_else(|| format!("no background job `{id}`"))
+    }
+
+    /// Kills every running job and waits until their ends have been reported.
+    pub(crate) async fn terminate_all(&self) {
+        let handles: Vec<JoinHandle<()>> = {
+            let mut jobs = self.jobs.lock().await;
+            jobs.values_mut()
+                .filter(|job| job.finished.is_none())
+                .filter_map(|job| {
+                    job.cancel.cancel();
+                    job.handle.take()
+                })
+                .collect()
+        };
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    /// Records the end of job `id` and returns the event reporting it.
+    async fn finish(&self, id: &str, finished: FinishedJob) -> Option<BackgroundJobStatusEvent> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.get_mut(id)?;
+        let event = BackgroundJobStatusEvent {
+            job_id: id.to_string(),
+            call_id: job.call_id.clone(),
+            command: job.command.clone(),
+            status: finished.status,
+            exit_code: finished.exit_code,
+            duration: finished.duration,
+        };
+        job.finished = Some(finished);
+        Some(event)
+    }
+}
+
+impl BackgroundJob {
+    fn snapshot(&self, id: &str, with_output: bool) -> JobSnapshot {
+        let (status, exit_code, elapsed, output, log_path) = match &self.finished {
+            Some(finished) => (
+                finished.status,
+                finished.exit_code,
+                finished.duration,
+                with_output.then(|| finished.output.clone()),
+                finished.log_path.clone(),
+            ),
+            None => (
+                BackgroundJobStatus::Running,
+                None,
+                self.started_at.elapsed(),
+                None,
+                None,
+            ),
+        };
+        JobSnapshot {
+            id: id.to_string(),
+            command: self.command.clone(),
+            status,
+            exit_code,
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            output,
+            log_path,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_job(
+    session: Arc<Session>,
+    sub_id: String,
+    id: String,
+    env: ExecEnv,
+    sandbox_policy: SandboxPolicy,
+    timeout: Duration,
+    cancel: CancellationToken,
+    truncation_policy: TruncationPolicy,
+) {
+    let started_at = Instant::now();
+    let run = execute_env(env, &sandbox_policy, None);
+    tokio::pin!(run);
+    let mut timed_out = false;
+    let result = tokio::select! {
+        result = &mut run => result,
+        _ = tokio::time::sleep(timeout) => {
+            timed_out = true;
+            cancel.cancel();
+            run.await
+        }
+    };
+    let log_path = match session.tmpdir().await {
+        Some(tmpdir) => tmpdir.write_job_log(&id, &full_output(&result)).await,
+        None => None,
+    };
+    let mut finished = finished_job(result, timed_out, started_at.elapsed(), truncation_policy);
+    finished.log_path = log_path;
+    let Some(event) = session.services.background_jobs.finish(&id, finished).await else {
+        return;
+    };
+    let sub_id = session.active_sub_id().await.unwrap_or(sub_id);
+    session
+        .send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::BackgroundJobStatus(event),
+        })
+        .await;
+}
+
+/// Everything the job printed, or the error that kept it from running.
+fn full_output(result: &crate::error::Result<ExecToolCallOutput>) -> String {
+    match result {
+        Ok(output) => output.aggregated_output.text.clone(),
+        Err(CodexErr::Sandbox(SandboxErr::Timeout { output } | SandboxErr::Denied { output })) => {
+            output.aggregated_output.text.clone()
+        }
+        Err(err) => err.to_string(),
+    }
+}
+
+/// Classifies the end of a job. A run stopped through its cancellation
+/// token comes back as a timeout; `timed_out` tells a real timeout from a
+/// kill.
+fn finished_job(
+    result: crate::error::Result<ExecToolCallOutput>,
+    timed_out: bool,
+    duration: Duration,
+    truncation_policy: TruncationPolicy,
+) -> FinishedJob {
+    let output = formatted_truncate_text(&full_output(&result), truncation_policy);
+    let (status, exit_code) = match result {
+        Ok(output) => {
+            let status = if output.exit_code == 0 {
+                BackgroundJobStatus::Completed
+            } else {
+                BackgroundJobStatus::Failed
+            };
+            (status, Some(output.exit_code))
+        }
+        Err(CodexErr::Sandbox(SandboxErr::Timeout { .. })) => {
+            let status = if timed_out {
+                BackgroundJobStatus::TimedOut
+            } else {
+                BackgroundJobStatus::Killed
+            };
+            (status, None)
+        }
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => {
+            (BackgroundJobStatus::Failed, Some(output.exit_code))
+        }
+        Err(_) => (BackgroundJobStatus::Failed, None),
+    };
+    FinishedJob {
+        status,
+        exit_code,
+        duration,
+        output,
+        log_path: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::StreamOutput;
+    use pretty_assertions::assert_eq;
+
+    fn exec_output(exit_code: i32, text: &str) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code,
+            aggregated_output: StreamOutput::new(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn classify(result: crate::error::Result<ExecToolCallOutput>, timed_out: bool) -> FinishedJob {
+        finished_job(
+            result,
+            timed_out,
+            Duration::from_secs(1),
+            TruncationPolicy::Bytes(1024),
+        )
+    }
+
+    #[test]
+    fn exit_status_decides_completed_or_failed() {
+        assert_eq!(
+            classify(Ok(exec_output(0, "built")), false),
+            FinishedJob {
+                status: BackgroundJobStatus::Completed,
+                exit_code: Some(0),
+                duration: Duration::from_secs(1),
+                output: "built".to_string(),
+                log_path: None,
+            }
+        );
+        let failed = classify(Ok(exec_output(2, "error[E0425]")), false);
+        assert_eq!(
+            (failed.status, failed.exit_code),
+            (BackgroundJobStatus::Failed, Some(2))
+        );
+    }
+
+    #[test]
+    fn a_stopped_run_is_a_timeout_only_when_the_timeout_fired() {
+        let stopped = || {
+            Err(CodexErr::Sandbox(SandboxErr::Timeout {
+                output: Box::new(exec_output(124, "partial")),
+            }))
+        };
+
+        let timed_out = classify(stopped(), true);
+        assert_eq!(
+            (
+                timed_out.status,
+                timed_out.exit_code,
+                timed_out.output.as_str()
+            ),
+            (BackgroundJobStatus::TimedOut, None, "partial")
+        );
+        assert_eq!(
+            classify(stopped(), false).status,
+            BackgroundJobStatus::Killed
+        );
+    }
+}
@@ -101,7 +101,7 @@ pub fn extract_bash_command(command: &[String]) -> Option<(&str, &str)> {
     if !matches!(flag.as_str(), "-lc" | "-c")
         || !matches!(
             detect_shell_type(&PathBuf::from(shell)),
-            Some(ShellType::Zsh) | Some(ShellType::Bash) | Some(ShellType::Sh)
+            Some(ShellType::Zsh | ShellType::Bash | ShellType::Sh | ShellType::Fish)
         )
     {
         return None;
@@ -112,8 +112,18 @@ pub fn extract_bash_command(command: &[String]) -> Option<(&str, &str)> {
 /// Returns the sequence of plain commands within a `bash -lc "..."` or
 /// `zsh -lc "..."` invocation when the script only contains word-only commands
 /// joined by safe operators.
+///
+/// Always `None` for `fish -lc "..."`: fish quotes and expands words
+/// differently (`\'` is an escape inside single quotes, `$(...)` and `(...)`
+/// substitute), so the bash grammar cannot tell what a fish script runs.
 pub fn parse_shell_lc_plain_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
-    let (_, script) = extract_bash_command(command)?;
+    let (shell, script) = extract_bash_command(command)?;
+    if matches!(
+        detect_shell_type(&PathBuf::from(shell)),
+        Some(ShellType::Fish)
+    ) {
+        return None;
+    }
 
     let tree = try_parse_shell(script)?;
     try_parse_word_only_commands_sequence(&tree, script)
@@ -333,6 +343,16 @@ mod tests {
         assert_eq!(parsed, vec![vec!["ls".to_string()]]);
     }
 
+    #[test]
+    fn fish_lc_is_not_parsed_with_the_bash_grammar() {
+        let command = vec![
+            "/usr/bin/fish".to_string(),
+            "-lc".to_string(),
+            "git status && ls".to_string(),
+        ];
+        assert_eq!(parse_shell_lc_plain_commands(&command), None);
+    }
+
     #[test]
     fn accepts_concatenated_flag_and_value() {
         // Test case: -g"*.py" (flag directly concatenated with quoted value)
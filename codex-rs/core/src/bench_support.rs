@@ -0,0 +1,96 @@
+//! Entry points for the benchmarks in `core/benches`.
+//!
+//! Benchmarks are compiled as separate crates, so the crate-private pieces of
+//! the tool pipeline they measure are wrapped here. Only built with the
+//! `test-support` feature; not a stable API.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_channel::Receiver;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex::make_session_and_context_with_rx;
+use crate::exec::ExecToolCallOutput;
+use crate::parse_command::parse_command;
+use crate::parse_command_cache::ParsedCommandCache;
+use crate::protocol::Event;
+use crate::protocol::ExecCommandSource;
+use crate::tools::FormattedExecOutput;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::format_exec_output_for_model_structured;
+use crate::tools::format_exec_output_str;
+use crate::truncate::TruncationPolicy;
+
+/// An offline session and turn: no model client, network, or rollout.
+pub struct BenchSession {
+    session: Arc<Session>,
+    turn: Arc<TurnContext>,
+    rx: Receiver<Event>,
+}
+
+impl BenchSession {
+    pub async fn new() -> Self {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        Self { session, turn, rx }
+    }
+
+    /// Emits the begin and end events for one exec call that produced
+    /// `output`, then drains them. Returns the number of events emitted.
+    ///
+    /// Uses the unified exec emitter so that large outputs are never spilled
+    /// to disk and the measurement stays in memory.
+    pub async fn exec_round_trip(&self, command: &[String], output: ExecToolCallOutput) -> usize {
+        let emitter = ToolEmitter::unified_exec(
+            command.to_vec(),
+            PathBuf::from("/tmp"),
+            ExecCommandSource::ModelShell,
+            None,
+            self.session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(self.session.as_ref(), self.turn.as_ref(), "bench", None);
+        emitter.begin(ctx).await;
+        let _ = emitter.finish(ctx, Ok(output)).await;
+
+        let mut events = 0;
+        while self.rx.try_recv().is_ok() {
+            events += 1;
+        }
+        events
+    }
+}
+
+/// A session's cache of parsed commands.
+#[derive(Default)]
+pub struct ParseCache(ParsedCommandCache);
+
+impl ParseCache {
+    /// Parses `command`, or reuses its cached parse. Returns the number of
+    /// commands found.
+    pub fn parse(&self, command: &[String]) -> usize {
+        self.0.get_or_parse(command, parse_command).len()
+    }
+}
+
+/// Formats `output` for the model and the end event under a byte budget.
+/// Returns `(model, ui)`.
+pub fn format_exec_output(output: &ExecToolCallOutput, limit_bytes: usize) -> (String, String) {
+    let formatted =
+        FormattedExecOutput::new(output, None, false, TruncationPolicy::Bytes(limit_bytes));
+    (formatted.model, formatted.ui)
+}
+
+/// Formats `output` like [`format_exec_output`], but renders the model and
+/// end event strings separately, as before they shared one pass.
+pub fn format_exec_output_separately(
+    output: &ExecToolCallOutput,
+    limit_bytes: usize,
+) -> (String, String) {
+    let policy = TruncationPolicy::Bytes(limit_bytes);
+    (
+        format_exec_output_for_model_structured(output, policy),
+        format_exec_output_str(output, policy),
+    )
+}
@@ -0,0 +1,246 @@
+//! What the sandbox and policies let the model do, told to it up front so it
+//! does not spend turns on network access or writes that will be denied.
+//!
+//! With the `capability_descriptor` feature, the initial context carries a
+//! short `<capabilities>` developer message: the sandbox mode, network
+//! access, writable roots, approval policy, plan-only mode, the tools on
+//! offer, the command timeout and the tool budget. When a turn starts, or an
+//! override Op takes effect, with capabilities other than the ones the model
+//! was last told, a new message is recorded and `CapabilitiesChanged` is
+//! sent. The message stays within [`MAX_TOKENS`]: tools, then writable
+//! roots, that do not fit are counted instead of listed. The model can also
+//! ask with the `get_environment_capabilities` tool.
+
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::models::DeveloperInstructions;
+use codex_protocol::models::ResponseItem;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::features::Feature;
+use crate::protocol::CapabilitiesChangedEvent;
+use crate::protocol::EnvironmentCapabilities;
+use crate::protocol::EventMsg;
+use crate::protocol::SandboxPolicy;
+use crate::tools::spec::tool_names;
+use crate::truncate::approx_token_count;
+
+/// Approximate tokens the rendered descriptor may take.
+pub(crate) const MAX_TOKENS: usize = 200;
+
+/// The capabilities of `turn`.
+pub(crate) async fn describe(session: &Session, turn: &TurnContext) -> EnvironmentCapabilities {
+    let (sandbox_mode, writable_roots) = match &turn.sandbox_policy {
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. } => {
+            (SandboxMode::DangerFullAccess, Vec::new())
+        }
+        SandboxPolicy::ReadOnly => (SandboxMode::ReadOnly, Vec::new()),
+        policy @ SandboxPolicy::WorkspaceWrite { .. } => (
+            SandboxMode::WorkspaceWrite,
+            policy
+                .get_writable_roots_with_cwd(&turn.cwd)
+                .into_iter()
+                .map(|root| root.root.to_path_buf())
+                .collect(),
+        ),
+    };
+    let budget = session.tool_budget_limits().await;
+    EnvironmentCapabilities {
+        sandbox_mode,
+        network_access: turn.sandbox_policy.has_full_network_access(),
+        writable_roots,
+        approval_policy: turn.approval_policy,
+        plan_only: turn.plan_only,
+        tools: tool_names(&turn.tools_config, &turn.dynamic_tools),
+        command_timeout_ms: session.tool_timeouts().await.shell.as_millis() as u64,
+        max_tool_calls_per_turn: budget.max_calls_per_turn,
+        max_tool_calls_per_session: budget.max_calls_per_session,
+    }
+}
+
+/// The descriptor as the model reads it, within [`MAX_TOKENS`].
+pub(crate) fn render(capabilities: &EnvironmentCapabilities) -> String {
+    let mut tools = capabilities.tools.len();
+    let mut roots = capabilities.writable_roots.len();
+    loop {
+        let text = render_listing(capabilities, tools, roots);
+        if approx_token_count(&text) <= MAX_TOKENS || tools + roots == 0 {
+            return text;
+        }
+        if tools > 0 {
+            tools -= 1;
+        } else {
+            roots -= 1;
+        }
+    }
+}
+
+/// Records a descriptor when `turn`'s capabilities differ from the ones the
+/// model was last told, and sends `CapabilitiesChanged` unless it was not
+/// told any yet, as when a session resumes.
+pub(crate) async fn sync(session: &Session, turn: &TurnContext) {
+    if !session.enabled(Feature::CapabilityDescriptor) {
+        return;
+    }
+    let after = describe(session, turn).await;
+    let before = session.replace_capabilities(after.clone()).await;
+    if before.as_ref() == Some(&after) {
+        return;
+    }
+    session
+        .record_conversation_items(turn, &[developer_message(&after)])
+        .await;
+    if let Some(before) = before {
+        let event = CapabilitiesChangedEvent {
+            turn_id: turn.sub_id.clone(),
+            before,
+            after,
+        };
+        session
+            .send_event(turn, EventMsg::CapabilitiesChanged(event))
+            .await;
+    }
+}
+
+pub(crate) fn developer_message(capabilities: &EnvironmentCapabilities) -> ResponseItem {
+    DeveloperInstructions::new(render(capabilities)).into()
+}
+
+/// Renders the first `tools` tools and `roots` writable roots.
+fn render_listing(capabilities: &EnvironmentCapabilities, tools: usize, roots: usize) -> String {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let mut lines = vec![
+        "<capabilities>".to_string(),
+        "Anything these rule out will be denied; do not attempt it.".to_string(),
+        format!(
+            "sandbox: {}; network: {}; approvals: {}",
+            capabilities.sandbox_mode,
+            on_off(capabilities.network_access),
+            capabilities.approval_policy
+        ),
+    ];
+    let writable = match capabilities.sandbox_mode {
+        SandboxMode::ReadOnly => "none".to_string(),
+        SandboxMode::DangerFullAccess => "anywhere".to_string(),
+        SandboxMode::WorkspaceWrite => listing(
+            capabilities
+                .writable_roots
+                .iter()
+                .map(|root| root.display().to_string()),
+            roots,
+        ),
+    };
+    lines.push(format!("writable: {writable}"));
+    if capabilities.plan_only {
+        lines.push("plan-only: commands and patches are proposed, not run".to_string());
+    }
+    lines.push(format!(
+        "tools: {}",
+        listing(capabilities.tools.iter().cloned(), tools)
+    ));
+    let mut limits = vec![format!(
+        "command timeout {}s",
+        capabilities.command_timeout_ms / 1000
+    )];
+    if let Some(max) = capabilities.max_tool_calls_per_turn {
+        limits.push(format!("{max} tool calls per turn"));
+    }
+    if let Some(max) = capabilities.max_tool_calls_per_session {
+        limits.push(format!("{max} tool calls per session"));
+    }
+    lines.push(format!("limits: {}", limits.join(", ")));
+    lines.push("</capabilities>".to_string());
+    lines.join("\n")
+}
+
+/// The first `shown` of `items`, followed by how many were left out.
+fn listing(items: impl ExactSizeIterator<Item = String>, shown: usize) -> String {
+    let omitted = items.len().saturating_sub(shown);
+    let mut listed: Vec<String> = items.take(shown).collect();
+    if omitted > 0 {
+        listed.push(format!("+{omitted} more"));
+    }
+    if listed.is_empty() {
+        "none".to_string()
+    } else {
+        listed.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::AskForApproval;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn capabilities() -> EnvironmentCapabilities {
+        EnvironmentCapabilities {
+            sandbox_mode: SandboxMode::WorkspaceWrite,
+            network_access: false,
+            writable_roots: vec![PathBuf::from("/work/repo"), PathBuf::from("/tmp")],
+            approval_policy: AskForApproval::OnRequest,
+            plan_only: false,
+            tools: ["shell_command", "apply_patch", "update_plan", "view_image"]
+                .map(str::to_string)
+                .to_vec(),
+            command_timeout_ms: 10_000,
+            max_tool_calls_per_turn: Some(50),
+            max_tool_calls_per_session: None,
+        }
+    }
+
+    #[test]
+    fn renders_a_short_descriptor() {
+        let text = render(&capabilities());
+
+        assert_eq!(
+            text,
+            "<capabilities>
+Anything these rule out will be denied; do not attempt it.
+sandbox: workspace-write; network: off; approvals: on-request
+writable: /work/repo, /tmp
+tools: shell_command, apply_patch, update_plan, view_image
+limits: command timeout 10s, 50 tool calls per turn
+</capabilities>"
+        );
+        assert_eq!(approx_token_count(&text), 73);
+    }
+
+    #[test]
+    fn long_listings_are_counted_to_stay_within_the_budget() {
+        let capabilities = EnvironmentCapabilities {
+            writable_roots: (0..100)
+                .map(|index| PathBuf::from(format!("/work/projects/root-{index:03}")))
+                .collect(),
+            tools: (0..100)
+                .map(|index| format!("mcp_tool_{index:03}"))
+                .collect(),
+            plan_only: true,
+            ..capabilities()
+        };
+
+        let text = render(&capabilities);
+
+        assert_eq!(approx_token_count(&text), 199);
+        assert!(text.contains("tools: +100 more"), "{text}");
+        assert!(text.contains("/work/projects/root-000, "), "{text}");
+        assert!(text.contains(" more\nplan-only"), "{text}");
+    }
+
+    #[test]
+    fn describe_change_names_what_changed() {
+        let before = capabilities();
+        let after = EnvironmentCapabilities {
+            network_access: true,
+            approval_policy: AskForApproval::Never,
+            tools: vec!["shell_command".to_string()],
+            ..capabilities()
+        };
+
+        assert_eq!(
+            before.describe_change(&after),
+            "network access on, approvals never, 3 tool(s) removed"
+        );
+    }
+}
@@ -1,11 +1,17 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::PoisonError;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
 use crate::AuthManager;
 use crate::CodexAuth;
@@ -14,6 +20,8 @@ use crate::agent::AgentControl;
 use crate::agent::AgentStatus;
 use crate::agent::MAX_THREAD_SPAWN_DEPTH;
 use crate::agent::agent_status_from_event;
+use crate::background_jobs::BackgroundJobManager;
+use crate::capabilities;
 use crate::compact;
 use crate::compact::run_inline_auto_compact_task;
 use crate::compact::should_use_remote_compact_task;
@@ -22,18 +30,23 @@ use crate::connectors;
 use crate::exec_policy::ExecPolicyManager;
 use crate::features::Feature;
 use crate::features::Features;
+use crate::file_access::modified_time;
 use crate::models_manager::manager::ModelsManager;
 use crate::parse_command::parse_command;
+use crate::parse_command_cache::ParsedCommandCache;
 use crate::parse_turn_item;
 use crate::stream_events_utils::HandleOutputCtx;
 use crate::stream_events_utils::handle_non_tool_response_item;
 use crate::stream_events_utils::handle_output_item_done;
+use crate::stream_recovery;
+use crate::stream_recovery::PreservedOutput;
 use crate::terminal;
 use crate::truncate::TruncationPolicy;
 use crate::user_notification::UserNotifier;
 use crate::util::error_or_panic;
 use async_channel::Receiver;
 use async_channel::Sender;
+use codex_app_server_protocol::ConfigLayerMetadata;
 use codex_protocol::ThreadId;
 use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::config_types::ModeKind;
@@ -45,6 +58,8 @@ use codex_protocol::items::TurnItem;
 use codex_protocol::items::UserMessageItem;
 use codex_protocol::models::BaseInstructions;
 use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::parse_command::ParsedCommand;
+use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::FileChange;
 use codex_protocol::protocol::HasLegacyEvent;
 use codex_protocol::protocol::ItemCompletedEvent;
@@ -61,6 +76,7 @@ use codex_protocol::request_user_input::RequestUserInputArgs;
 use codex_protocol::request_user_input::RequestUserInputResponse;
 use codex_rmcp_client::ElicitationResponse;
 use codex_rmcp_client::OAuthCredentialsStoreMode;
+use codex_rmcp_client::ToolCallUpdateSender;
 use futures::future::BoxFuture;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
@@ -78,6 +94,7 @@ use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
+use toml::Value as TomlValue;
 use tracing::Instrument;
 use tracing::debug;
 use tracing::error;
@@ -94,25 +111,48 @@ use crate::client::ModelClientSession;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::codex_thread::ThreadConfigSnapshot;
+use crate::command_safety::command_risk::classify_command_risk;
 use crate::compact::collect_user_messages;
 use crate::config::Config;
 use crate::config::Constrained;
 use crate::config::ConstraintResult;
 use crate::config::GhostSnapshotConfig;
+use crate::config::types::LoopDetection;
 use crate::config::types::McpServerConfig;
+use crate::config::types::OutputLimits;
 use crate::config::types::ShellEnvironmentPolicy;
+use crate::config::types::TokenPrice;
+use crate::config::types::ToolBudget;
+use crate::config::types::ToolTimeouts;
+use crate::config::types::TurnProgress;
+use crate::config_loader::ConfigLayerStackOrdering;
+use crate::config_loader::describe_layer_source;
 use crate::context_manager::ContextManager;
+use crate::conversation::InProcessTool;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 #[cfg(test)]
 use crate::exec::StreamOutput;
+use crate::exec_env::create_env;
 use crate::exec_policy::ExecPolicyUpdateError;
 use crate::feedback_tags;
+use crate::git_state;
+use crate::headless_approvals::HeadlessDecision;
+use crate::hooks::HookPayload;
+use crate::hooks::HookRunner;
+use crate::hooks::run_hook_jobs;
+use crate::hooks::spawn_hook;
 use crate::instructions::UserInstructions;
+use crate::live_session::LiveEventStream;
+use crate::live_session::live_socket_path;
+use crate::loop_detection::FailureLoop;
 use crate::mcp::CODEX_APPS_MCP_SERVER_NAME;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp::effective_mcp_servers;
+use crate::mcp::sampling::McpSampling;
+use crate::mcp::sampling::run_sampling_jobs;
+use crate::mcp::tools_cache::McpToolsCache;
 use crate::mcp::with_codex_apps_mcp;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::model_provider_info::CHAT_WIRE_API_DEPRECATION_SUMMARY;
@@ -123,15 +163,29 @@ use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
 use crate::protocol::DeprecationNoticeEvent;
+use crate::protocol::EffectiveConfigLayer;
+use crate::protocol::EffectiveConfigResponseEvent;
+use crate::protocol::EffectiveConfigValue;
+use crate::protocol::EnvironmentCapabilities;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
+use crate::protocol::ExecApproval;
 use crate::protocol::ExecApprovalRequestEvent;
+use crate::protocol::ExecCommandBeginEvent;
+use crate::protocol::GitStateSnapshot;
+use crate::protocol::HeadlessApprovalDecisionEvent;
+use crate::protocol::HeadlessApprovalRequest;
 use crate::protocol::McpServerRefreshConfig;
+use crate::protocol::NotificationSentEvent;
 use crate::protocol::Op;
+use crate::protocol::ProviderModel;
+use crate::protocol::ProviderSwitchReason;
+use crate::protocol::ProviderSwitchedEvent;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReasoningContentDeltaEvent;
 use crate::protocol::ReasoningRawContentDeltaEvent;
+use crate::protocol::ReportedOutcome;
 use crate::protocol::RequestUserInputEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
@@ -140,15 +194,22 @@ use crate::protocol::SkillErrorInfo;
 use crate::protocol::SkillInterface as ProtocolSkillInterface;
 use crate::protocol::SkillMetadata as ProtocolSkillMetadata;
 use crate::protocol::StreamErrorEvent;
+use crate::protocol::StreamRecoveryStrategy;
+use crate::protocol::StreamRetryEvent;
 use crate::protocol::Submission;
 use crate::protocol::TokenCountEvent;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
-use crate::protocol::TurnDiffEvent;
+use crate::protocol::ToolBudgetRemaining;
+use crate::protocol::ToolCallTokens;
 use crate::protocol::WarningEvent;
+use crate::protocol::WorkspaceRoot;
+use crate::provider_failover;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::rollout::map_session_init_error;
+use crate::rollout::policy::redact_event_msg;
+use crate::session_tmpdir::SessionTmpdir;
 use crate::shell;
 use crate::shell_snapshot::ShellSnapshot;
 use crate::skills::SkillError;
@@ -157,22 +218,37 @@ use crate::skills::SkillMetadata;
 use crate::skills::SkillsManager;
 use crate::skills::build_skill_injections;
 use crate::state::ActiveTurn;
+use crate::state::OpenExecCall;
 use crate::state::SessionServices;
 use crate::state::SessionState;
+use crate::state::ToolActivity;
 use crate::tasks::GhostSnapshotTask;
 use crate::tasks::ReviewTask;
 use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
+use crate::tool_budget::BudgetExhausted;
+use crate::tool_digest::ToolDigestLog;
+use crate::tool_digest::TurnDigest;
+use crate::tool_tokens;
 use crate::tools::ToolRouter;
+use crate::tools::availability::ToolAvailability;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::events::emit_turn_diff;
+use crate::tools::exec_outputs::ExecOutputLog;
 use crate::tools::parallel::ToolCallRuntime;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::ToolsConfigParams;
+use crate::tools::spec::tool_names;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::turn_progress;
 use crate::unified_exec::UnifiedExecProcessManager;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
+use crate::workspace_primer;
+use crate::workspace_snapshots::SnapshotStore;
+use crate::workspace_watcher;
+use crate::workspace_watcher::WorkspaceWatcher;
 use codex_async_utils::OrCancelExt;
 use codex_otel::OtelManager;
 use codex_protocol::config_types::CollaborationMode;
@@ -238,6 +314,69 @@ fn maybe_push_chat_wire_api_deprecation(
     });
 }
 
+/// The report `Op::GetEffectiveConfig` answers with, also sent once after
+/// `SessionConfigured`.
+fn effective_config_report(config: &Config) -> EffectiveConfigResponseEvent {
+    let layer_stack = &config.config_layer_stack;
+    let layers = layer_stack
+        .get_layers(ConfigLayerStackOrdering::HighestPrecedenceFirst, true)
+        .into_iter()
+        .map(|layer| EffectiveConfigLayer {
+            source: describe_layer_source(&layer.name),
+            disabled_reason: layer.disabled_reason.clone(),
+        })
+        .collect();
+    let origins = layer_stack.origins();
+    let mut values = BTreeMap::new();
+    collect_effective_values(
+        &layer_stack.effective_config(),
+        &mut Vec::new(),
+        &origins,
+        &mut values,
+    );
+    EffectiveConfigResponseEvent { layers, values }
+}
+
+/// Pairs each leaf of the merged config with the layer that set it, using the
+/// same dotted keys as [`ConfigLayerStack::origins`].
+///
+/// [`ConfigLayerStack::origins`]: crate::config_loader::ConfigLayerStack::origins
+fn collect_effective_values(
+    value: &TomlValue,
+    path: &mut Vec<String>,
+    origins: &HashMap<String, ConfigLayerMetadata>,
+    values: &mut BTreeMap<String, EffectiveConfigValue>,
+) {
+    match value {
+        TomlValue::Table(table) => {
+            for (key, value) in table {
+                path.push(key.clone());
+                collect_effective_values(value, path, origins, values);
+                path.pop();
+            }
+        }
+        TomlValue::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                path.push(idx.to_string());
+                collect_effective_values(item, path, origins, values);
+                path.pop();
+            }
+        }
+        _ => {
+            let key = path.join(".");
+            if let Some(metadata) = origins.get(&key) {
+                values.insert(
+                    key,
+                    EffectiveConfigValue {
+                        value: value.to_string(),
+                        source: describe_layer_source(&metadata.name),
+                    },
+                );
+            }
+        }
+    }
+}
+
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
     #[allow(clippy::too_many_arguments)]
@@ -249,7 +388,8 @@ impl Codex {
         conversation_history: InitialHistory,
         session_source: SessionSource,
         agent_control: AgentControl,
-        dynamic_tools: Vec<DynamicToolSpec>,
+        mut dynamic_tools: Vec<DynamicToolSpec>,
+        in_process_tools: Vec<InProcessTool>,
     ) -> CodexResult<CodexSpawnOk> {
         let (tx_sub, rx_sub) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
         let (tx_event, rx_event) = async_channel::unbounded();
@@ -273,9 +413,13 @@ impl Codex {
         let enabled_skills = loaded_skills.enabled_skills();
         let user_instructions = get_user_instructions(&config, Some(&enabled_skills)).await;
 
-        let exec_policy = ExecPolicyManager::load(&config.features, &config.config_layer_stack)
-            .await
-            .map_err(|err| CodexErr::Fatal(format!("failed to load rules: {err}")))?;
+        let exec_policy = ExecPolicyManager::load(
+            &config.features,
+            &config.config_layer_stack,
+            &config.state_dir,
+        )
+        .await
+        .map_err(|err| CodexErr::Fatal(format!("failed to load rules: {err}")))?;
 
         let config = Arc::new(config);
         let _ = models_manager
@@ -313,8 +457,14 @@ impl Codex {
                 developer_instructions: None,
             },
         };
+        dynamic_tools.extend(in_process_tools.iter().map(|tool| tool.spec.clone()));
+        let in_process_tools = in_process_tools
+            .into_iter()
+            .map(|tool| (tool.name().to_string(), tool))
+            .collect();
         let session_configuration = SessionConfiguration {
             provider: config.model_provider.clone(),
+            provider_id: config.model_provider_id.clone(),
             collaboration_mode,
             model_reasoning_summary: config.model_reasoning_summary,
             developer_instructions: config.developer_instructions.clone(),
@@ -325,6 +475,9 @@ impl Codex {
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
             cwd: config.cwd.clone(),
+            workspace_roots: config.workspace_roots.clone(),
+            plan_only: config.plan_only,
+            tmpdir: None,
             original_config_do_not_use: Arc::clone(&config),
             session_source,
             dynamic_tools,
@@ -346,6 +499,7 @@ impl Codex {
             session_source_clone,
             skills_manager,
             agent_control,
+            in_process_tools,
         )
         .await
         .map_err(|e| {
@@ -424,6 +578,8 @@ pub(crate) struct Session {
     /// session.
     features: Features,
     pending_mcp_server_refresh_config: Mutex<Option<McpServerRefreshConfig>>,
+    /// Messages from `Op::RunMcpPrompt`, recorded before the next turn's input.
+    pending_mcp_prompt_items: Mutex<Vec<ResponseItem>>,
     pub(crate) active_turn: Mutex<Option<ActiveTurn>>,
     pub(crate) services: SessionServices,
     next_internal_sub_id: AtomicU64,
@@ -434,10 +590,17 @@ pub(crate) struct Session {
 pub(crate) struct TurnContext {
     pub(crate) sub_id: String,
     pub(crate) client: ModelClient,
+    /// Key of the client's provider in `model_providers`.
+    pub(crate) provider_id: String,
     /// The session's current working directory. All relative paths provided by
     /// the model as well as sandbox policies are resolved against this path
     /// instead of `std::env::current_dir()`.
     pub(crate) cwd: PathBuf,
+    pub(crate) workspace_roots: Vec<WorkspaceRoot>,
+    /// Tool calls propose rather than run; see `Op::SetPlanOnly`.
+    pub(crate) plan_only: bool,
+    /// The session's temp directory, exported to commands as `CODEX_TMPDIR`.
+    pub(crate) tmpdir: Option<PathBuf>,
     pub(crate) developer_instructions: Option<String>,
     pub(crate) compact_prompt: Option<String>,
     pub(crate) user_instructions: Option<String>,
@@ -451,6 +614,9 @@ pub(crate) struct TurnContext {
     pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
     pub(crate) tool_call_gate: Arc<ReadinessFlag>,
     pub(crate) truncation_policy: TruncationPolicy,
+    pub(crate) output_limits: OutputLimits,
+    pub(crate) loop_detection: LoopDetection,
+    pub(crate) turn_progress: TurnProgress,
     pub(crate) dynamic_tools: Vec<DynamicToolSpec>,
 }
 
@@ -461,6 +627,38 @@ impl TurnContext {
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
     }
 
+    /// `path` as shown in messages: relative to `cwd` when under it, else as
+    /// `name/relative/path` under the workspace root it falls in, else as is.
+    pub(crate) fn display_path(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.cwd) {
+            Ok(relative) => relative.display().to_string(),
+            Err(_) => WorkspaceRoot::display_path(&self.workspace_roots, path)
+                .unwrap_or_else(|| path.display().to_string()),
+        }
+    }
+
+    /// Truncation applied to command output sent back to the model:
+    /// `output_limits.model_output_bytes` when set, otherwise the model's own
+    /// policy.
+    pub(crate) fn exec_output_truncation_policy(&self) -> TruncationPolicy {
+        self.output_limits
+            .model_output_bytes
+            .map_or(self.truncation_policy, TruncationPolicy::Bytes)
+    }
+
+    /// The environment commands of this turn start from: the one
+    /// `shell_environment_policy` builds, plus `CODEX_TMPDIR`.
+    pub(crate) fn exec_env(&self) -> HashMap<String, String> {
+        let mut env = create_env(&self.shell_environment_policy);
+        if let Some(tmpdir) = &self.tmpdir {
+            env.insert(
+                crate::session_tmpdir::ENV_VAR.to_string(),
+                tmpdir.to_string_lossy().into_owned(),
+            );
+        }
+        env
+    }
+
     pub(crate) fn compact_prompt(&self) -> &str {
         self.compact_prompt
             .as_deref()
@@ -472,6 +670,9 @@ impl TurnContext {
 pub(crate) struct SessionConfiguration {
     /// Provider identifier ("openai", "openrouter", ...).
     provider: ModelProviderInfo,
+    /// Key of `provider` in `model_providers`; differs from the config's
+    /// after a `[provider_failover]` switch.
+    provider_id: String,
 
     collaboration_mode: CollaborationMode,
     model_reasoning_summary: ReasoningSummaryConfig,
@@ -504,6 +705,13 @@ pub(crate) struct SessionConfiguration {
     /// `ConfigureSession` operation so that the business-logic layer can
     /// operate deterministically.
     cwd: PathBuf,
+    /// Named directories besides `cwd`; all of them are writable roots of a
+    /// workspace-write `sandbox_policy`.
+    workspace_roots: Vec<WorkspaceRoot>,
+    /// Whether tool calls of new turns propose rather than run.
+    plan_only: bool,
+    /// The session's temp directory; `None` when it could not be created.
+    tmpdir: Option<SessionTmpdir>,
 
     // TODO(pakrym): Remove config from here
     original_config_do_not_use: Arc<Config>,
@@ -516,7 +724,7 @@ impl SessionConfiguration {
     fn thread_config_snapshot(&self) -> ThreadConfigSnapshot {
         ThreadConfigSnapshot {
             model: self.collaboration_mode.model().to_string(),
-            model_provider_id: self.original_config_do_not_use.model_provider_id.clone(),
+            model_provider_id: self.provider_id.clone(),
             approval_policy: self.approval_policy.value(),
             sandbox_policy: self.sandbox_policy.get().clone(),
             cwd: self.cwd.clone(),
@@ -540,7 +748,15 @@ impl SessionConfiguration {
         if let Some(approval_policy) = updates.approval_policy {
             next_configuration.approval_policy.set(approval_policy)?;
         }
-        if let Some(sandbox_policy) = updates.sandbox_policy.clone() {
+        if let Some(mut sandbox_policy) = updates.sandbox_policy.clone() {
+            crate::workspace_roots::extend_writable_roots(
+                &mut sandbox_policy,
+                &next_configuration.workspace_roots,
+            );
+            crate::session_tmpdir::extend_writable_roots(
+                &mut sandbox_policy,
+                next_configuration.tmpdir.as_ref(),
+            );
             next_configuration.sandbox_policy.set(sandbox_policy)?;
         }
         if let Some(cwd) = updates.cwd.clone() {
@@ -548,6 +764,51 @@ impl SessionConfiguration {
         }
         Ok(next_configuration)
     }
+
+    /// Applies an `Op::UpdateWorkspaceRoots`; returns the removed roots.
+    fn update_workspace_roots(
+        &mut self,
+        add: &[WorkspaceRoot],
+        remove: &[String],
+    ) -> Result<Vec<WorkspaceRoot>, String> {
+        let update = crate::workspace_roots::update(
+            &self.workspace_roots,
+            self.sandbox_policy.get(),
+            add,
+            remove,
+        )?;
+        self.sandbox_policy
+            .set(update.sandbox_policy)
+            .map_err(|err| err.to_string())?;
+        self.workspace_roots = update.roots;
+        Ok(update.removed)
+    }
+
+    fn provider_model(&self) -> ProviderModel {
+        ProviderModel {
+            provider: self.provider_id.clone(),
+            model: self.collaboration_mode.model().to_string(),
+        }
+    }
+
+    /// Moves to `provider_id` and, when set, `model`; returns where it moved
+    /// from and to.
+    fn switch_provider(
+        &mut self,
+        provider_id: String,
+        provider: ModelProviderInfo,
+        model: Option<String>,
+    ) -> (ProviderModel, ProviderModel) {
+        let from = self.provider_model();
+        self.provider = provider;
+        self.provider_id = provider_id;
+        if let Some(model) = model {
+            self.collaboration_mode = self
+                .collaboration_mode
+                .with_updates(Some(model), None, None);
+        }
+        (from, self.provider_model())
+    }
 }
 
 #[derive(Default, Clone)]
@@ -607,12 +868,23 @@ impl Session {
             model_info: &model_info,
             features: &per_turn_config.features,
             web_search_mode: per_turn_config.web_search_mode,
-        });
+        })
+        .with_availability(ToolAvailability::for_model(
+            &per_turn_config.model_tools,
+            &model_info.slug,
+        ));
 
         TurnContext {
             sub_id,
             client,
+            provider_id: session_configuration.provider_id.clone(),
             cwd: session_configuration.cwd.clone(),
+            workspace_roots: session_configuration.workspace_roots.clone(),
+            plan_only: session_configuration.plan_only,
+            tmpdir: session_configuration
+                .tmpdir
+                .as_ref()
+                .map(|tmpdir| tmpdir.path().to_path_buf()),
             developer_instructions: session_configuration.developer_instructions.clone(),
             compact_prompt: session_configuration.compact_prompt.clone(),
             user_instructions: session_configuration.user_instructions.clone(),
@@ -626,13 +898,16 @@ impl Session {
             codex_linux_sandbox_exe: per_turn_config.codex_linux_sandbox_exe.clone(),
             tool_call_gate: Arc::new(ReadinessFlag::new()),
             truncation_policy: model_info.truncation_policy.into(),
+            output_limits: per_turn_config.output_limits,
+            loop_detection: per_turn_config.loop_detection,
+            turn_progress: per_turn_config.turn_progress,
             dynamic_tools: session_configuration.dynamic_tools.clone(),
         }
     }
 
     #[allow(clippy::too_many_arguments)]
     async fn new(
-        session_configuration: SessionConfiguration,
+        mut session_configuration: SessionConfiguration,
         config: Arc<Config>,
         auth_manager: Arc<AuthManager>,
         models_manager: Arc<ModelsManager>,
@@ -643,6 +918,7 @@ impl Session {
         session_source: SessionSource,
         skills_manager: Arc<SkillsManager>,
         agent_control: AgentControl,
+        in_process_tools: HashMap<String, InProcessTool>,
     ) -> anyhow::Result<Arc<Self>> {
         debug!(
             "Configuring session: model={}; provider={:?}",
@@ -723,6 +999,26 @@ impl Session {
             .as_ref()
             .map(|rec| rec.rollout_path.clone());
 
+        // Sweep the temp dirs crashed sessions left behind before creating
+        // this one's, so a resumed session finds its own again.
+        let tmpdir_root = crate::session_tmpdir::root();
+        let sweep_root = tmpdir_root.clone();
+        let max_age = config.tmpdir_max_age;
+        let _ =
+            tokio::task::spawn_blocking(move || crate::session_tmpdir::sweep(&sweep_root, max_age))
+                .await;
+        match SessionTmpdir::create(&tmpdir_root, conversation_id, rollout_path.as_deref()).await {
+            Ok(tmpdir) => {
+                let mut sandbox_policy = session_configuration.sandbox_policy.get().clone();
+                crate::session_tmpdir::extend_writable_roots(&mut sandbox_policy, Some(&tmpdir));
+                if let Err(err) = session_configuration.sandbox_policy.set(sandbox_policy) {
+                    warn!("session temp dir is not writable under the sandbox policy: {err}");
+                }
+                session_configuration.tmpdir = Some(tmpdir);
+            }
+            Err(err) => warn!("failed to create session temp dir: {err}"),
+        }
+
         let mut post_session_configured_events = Vec::<Event>::new();
 
         for (alias, feature) in config.features.legacy_feature_usages() {
@@ -753,7 +1049,21 @@ impl Session {
                 }),
             });
         }
+        for warning in
+            crate::config_loader::unknown_config_key_warnings(&config.config_layer_stack).await
+        {
+            post_session_configured_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Warning(WarningEvent {
+                    message: warning.to_string(),
+                }),
+            });
+        }
         maybe_push_chat_wire_api_deprecation(&config, &mut post_session_configured_events);
+        post_session_configured_events.push(Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::EffectiveConfigResponse(effective_config_report(&config)),
+        });
 
         let auth = auth.as_ref();
         let otel_manager = OtelManager::new(
@@ -793,22 +1103,39 @@ impl Session {
             config.active_profile.clone(),
         );
 
-        let mut default_shell = shell::default_user_shell();
+        let mut default_shell = shell::user_shell_from_config(config.shell.as_ref());
         // Create the mutable state for the Session.
         if config.features.enabled(Feature::ShellSnapshot) {
             ShellSnapshot::start_snapshotting(
-                config.codex_home.clone(),
+                config.state_dir.clone(),
                 conversation_id,
                 &mut default_shell,
                 otel_manager.clone(),
             );
         }
-        let state = SessionState::new(session_configuration.clone());
+        let mut state = SessionState::new(session_configuration.clone());
+        state.set_tool_budget(config.tool_budget);
+        let (mcp_sampling, sampling_jobs) = McpSampling::new();
+        let (hooks, hook_jobs) = HookRunner::new();
+        let live_events = if config.features.enabled(Feature::LiveAttach) {
+            match LiveEventStream::start(live_socket_path(&config.codex_home, conversation_id)) {
+                Ok(stream) => Some(stream),
+                Err(err) => {
+                    warn!("failed to open live session socket: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         let services = SessionServices {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+            mcp_sampling,
+            hooks,
             unified_exec_manager: UnifiedExecProcessManager::default(),
+            background_jobs: BackgroundJobManager::default(),
             notifier: UserNotifier::new(config.notify.clone()),
             rollout: Mutex::new(rollout_recorder),
             user_shell: Arc::new(default_shell),
@@ -820,6 +1147,17 @@ impl Session {
             tool_approvals: Mutex::new(ApprovalStore::default()),
             skills_manager,
             agent_control,
+            tool_timeouts: RwLock::new(config.tool_timeouts),
+            live_events: std::sync::Mutex::new(live_events),
+            parsed_commands: ParsedCommandCache::default(),
+            in_process_tools,
+            workspace_snapshots: Mutex::new(SnapshotStore::new(config.workspace_snapshots)),
+            exec_outputs: Mutex::new(ExecOutputLog::default()),
+            workspace_watcher: config
+                .features
+                .enabled(Feature::WorkspaceWatcher)
+                .then(WorkspaceWatcher::start)
+                .flatten(),
         };
 
         let sess = Arc::new(Session {
@@ -829,6 +1167,7 @@ impl Session {
             state: Mutex::new(state),
             features: config.features.clone(),
             pending_mcp_server_refresh_config: Mutex::new(None),
+            pending_mcp_prompt_items: Mutex::new(Vec::new()),
             active_turn: Mutex::new(None),
             services,
             next_internal_sub_id: AtomicU64::new(0),
@@ -837,6 +1176,8 @@ impl Session {
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
         let initial_messages = initial_history.get_event_msgs();
+        let default_turn = sess.new_default_turn().await;
+        let tools = tool_names(&default_turn.tools_config, &default_turn.dynamic_tools);
         let events = std::iter::once(Event {
             id: INITIAL_SUBMIT_ID.to_owned(),
             msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
@@ -852,12 +1193,28 @@ impl Session {
                 history_entry_count,
                 initial_messages,
                 rollout_path,
+                shell: Some(sess.services.user_shell.describe()),
+                tools: Some(tools),
+                tmpdir: session_configuration
+                    .tmpdir
+                    .as_ref()
+                    .map(|tmpdir| tmpdir.path().to_path_buf()),
             }),
         })
         .chain(post_session_configured_events.into_iter());
         for event in events {
             sess.send_event_raw(event).await;
         }
+        tokio::spawn(run_hook_jobs(Arc::downgrade(&sess), hook_jobs));
+        spawn_hook(
+            &sess,
+            &default_turn,
+            HookPayload::SessionStart {
+                thread_id: sess.conversation_id.to_string(),
+                cwd: default_turn.cwd.clone(),
+                model: default_turn.client.get_model(),
+            },
+        );
 
         // Construct sandbox_state before initialize() so it can be sent to each
         // MCP server immediately after it becomes ready (avoiding blocking).
@@ -868,10 +1225,11 @@ impl Session {
         };
         let cancel_token = sess.mcp_startup_cancellation_token().await;
 
-        sess.services
-            .mcp_connection_manager
-            .write()
-            .await
+        tokio::spawn(run_sampling_jobs(Arc::downgrade(&sess), sampling_jobs));
+        let mut mcp_connection_manager = sess.services.mcp_connection_manager.write().await;
+        mcp_connection_manager.set_sampling_jobs(sess.services.mcp_sampling.jobs());
+        mcp_connection_manager.set_tools_cache(McpToolsCache::new(&config.codex_home));
+        mcp_connection_manager
             .initialize(
                 &mcp_servers,
                 config.mcp_oauth_credentials_store_mode,
@@ -881,6 +1239,7 @@ impl Session {
                 sandbox_state,
             )
             .await;
+        drop(mcp_connection_manager);
 
         // record_initial_history can emit events. We record only after the SessionConfiguredEvent is emitted.
         sess.record_initial_history(initial_history).await;
@@ -931,6 +1290,14 @@ impl Session {
                 // Build and record initial items (user instructions + environment context)
                 let items = self.build_initial_context(&turn_context).await;
                 self.record_conversation_items(&turn_context, &items).await;
+                if self.features.enabled(Feature::WorkspacePrimer) {
+                    let primer = workspace_primer::gather(&turn_context.cwd).await;
+                    let message = workspace_primer::developer_message(&primer);
+                    self.record_conversation_items(&turn_context, &[message])
+                        .await;
+                    self.send_event(&turn_context, EventMsg::WorkspacePrimer(primer))
+                        .await;
+                }
                 // Ensure initial items are visible to immediate readers (e.g., tests, forks).
                 self.flush_rollout().await;
             }
@@ -982,6 +1349,11 @@ impl Session {
                     state.set_token_info(Some(info));
                 }
 
+                // Pick the plan back up so turn summaries keep reporting it.
+                if let Some(plan) = Self::last_plan_from_rollout(&rollout_items) {
+                    self.set_plan(plan).await;
+                }
+
                 // If persisting, persist all rollout items as-is (recorder filters)
                 if persist && !rollout_items.is_empty() {
                     self.persist_rollout_items(&rollout_items).await;
@@ -1004,6 +1376,13 @@ impl Session {
         })
     }
 
+    fn last_plan_from_rollout(rollout_items: &[RolloutItem]) -> Option<UpdatePlanArgs> {
+        rollout_items.iter().rev().find_map(|item| match item {
+            RolloutItem::EventMsg(EventMsg::PlanUpdate(plan)) => Some(plan.clone()),
+            _ => None,
+        })
+    }
+
     pub(crate) async fn update_settings(
         &self,
         updates: SessionSettingsUpdate,
@@ -1134,6 +1513,117 @@ impl Session {
             .await
     }
 
+    /// Moves the session to the next provider of `[provider_failover]` and
+    /// returns a context on it for the rest of `turn_context`'s turn, or
+    /// `None` when there is no chain or it is used up. The new context's
+    /// tools are derived from the new model.
+    pub(crate) async fn fail_over(
+        &self,
+        turn_context: &TurnContext,
+        reason: ProviderSwitchReason,
+        retried_in_turn: bool,
+    ) -> Option<Arc<TurnContext>> {
+        let (session_configuration, from, to) = {
+            let mut state = self.state.lock().await;
+            let target = state
+                .session_configuration
+                .original_config_do_not_use
+                .provider_failover
+                .as_ref()?
+                .chain
+                .get(state.failovers_used)?
+                .clone();
+            state.failovers_used += 1;
+            let (from, to) = state.session_configuration.switch_provider(
+                target.provider_id,
+                target.provider,
+                target.model,
+            );
+            (state.session_configuration.clone(), from, to)
+        };
+        warn!(
+            "switching from provider {} ({}) to {} ({}) after {reason:?}",
+            from.provider, from.model, to.provider, to.model
+        );
+        let next_turn = self
+            .new_turn_from_configuration(
+                turn_context.sub_id.clone(),
+                session_configuration,
+                Some(turn_context.final_output_json_schema.clone()),
+                false,
+            )
+            .await;
+        self.send_event(
+            &next_turn,
+            EventMsg::ProviderSwitched(ProviderSwitchedEvent {
+                from,
+                to,
+                reason,
+                retried_in_turn,
+            }),
+        )
+        .await;
+        Some(next_turn)
+    }
+
+    /// Switches the session to `model`, on `provider_id` when set, for the
+    /// turns that start from now on.
+    pub(crate) async fn switch_model(
+        &self,
+        model: String,
+        provider_id: Option<String>,
+    ) -> Result<(ProviderModel, ProviderModel), String> {
+        if model.trim().is_empty() {
+            return Err("model must not be empty".to_string());
+        }
+        let mut state = self.state.lock().await;
+        let configuration = &state.session_configuration;
+        let (provider_id, provider) = match provider_id {
+            Some(provider_id) => {
+                let Some(provider) = configuration
+                    .original_config_do_not_use
+                    .model_providers
+                    .get(&provider_id)
+                    .cloned()
+                else {
+                    return Err(format!("unknown model provider `{provider_id}`"));
+                };
+                (provider_id, provider)
+            }
+            None => (
+                configuration.provider_id.clone(),
+                configuration.provider.clone(),
+            ),
+        };
+        Ok(state
+            .session_configuration
+            .switch_provider(provider_id, provider, Some(model)))
+    }
+
+    /// The session's temp directory, if it could be created.
+    pub(crate) async fn tmpdir(&self) -> Option<SessionTmpdir> {
+        let state = self.state.lock().await;
+        state.session_configuration.tmpdir.clone()
+    }
+
+    /// Applies an `Op::UpdateWorkspaceRoots` for the turns that start from
+    /// now on; returns the removed roots.
+    pub(crate) async fn update_workspace_roots(
+        &self,
+        add: &[WorkspaceRoot],
+        remove: &[String],
+    ) -> Result<Vec<WorkspaceRoot>, String> {
+        let mut state = self.state.lock().await;
+        state
+            .session_configuration
+            .update_workspace_roots(add, remove)
+    }
+
+    pub(crate) async fn set_plan_only(&self, enabled: bool) {
+        let mut state = self.state.lock().await;
+        state.session_configuration.plan_only = enabled;
+    }
+
     pub(crate) async fn current_collaboration_mode(&self) -> CollaborationMode {
         let state = self.state.lock().await;
         state.session_configuration.collaboration_mode.clone()
@@ -1258,14 +1748,15 @@ impl Session {
         update_items
     }
 
-    /// Persist the event to rollout and send it to clients.
-    pub(crate) async fn send_event(&self, turn_context: &TurnContext, msg: EventMsg) {
+    /// Persist the event to rollout and send it to clients. Returns whether
+    /// the event reached the client channel; it is persisted either way.
+    pub(crate) async fn send_event(&self, turn_context: &TurnContext, msg: EventMsg) -> bool {
         let legacy_source = msg.clone();
         let event = Event {
             id: turn_context.sub_id.clone(),
             msg,
         };
-        self.send_event_raw(event).await;
+        let delivered = self.send_event_raw(event).await;
 
         let show_raw_agent_reasoning = self.show_raw_agent_reasoning();
         for legacy in legacy_source.as_legacy_events(show_raw_agent_reasoning) {
@@ -1275,18 +1766,53 @@ impl Session {
             };
             self.send_event_raw(legacy_event).await;
         }
+        delivered
     }
 
-    pub(crate) async fn send_event_raw(&self, event: Event) {
+    pub(crate) async fn send_event_raw(&self, event: Event) -> bool {
         // Record the last known agent status.
         if let Some(status) = agent_status_from_event(&event.msg) {
             self.agent_status.send_replace(status);
         }
-        // Persist the event into rollout (recorder filters as needed)
-        let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
+        if ToolDigestLog::records(&event.msg) {
+            let mut state = self.state.lock().await;
+            state.record_tool_digest_event(&event.id, &event.msg);
+        }
+        let notification = UserNotification::for_event(self.conversation_id.to_string(), &event);
+        let sub_id = event.id.clone();
+        let delivered = self.deliver_event(event).await;
+        if let Some(notification) = notification {
+            self.notify_user(&sub_id, &notification).await;
+        }
+        delivered
+    }
+
+    /// Persists `event` into the rollout (the recorder filters as needed)
+    /// and sends it to clients.
+    async fn deliver_event(&self, event: Event) -> bool {
+        let rollout_items = vec![RolloutItem::EventMsg(redact_event_msg(&event.msg))];
         self.persist_rollout_items(&rollout_items).await;
-        if let Err(e) = self.tx_event.send(event).await {
-            debug!("dropping event because channel is closed: {e}");
+        self.publish_live_event(&event);
+        match self.tx_event.send(event).await {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("dropping event because channel is closed: {e}");
+                false
+            }
+        }
+    }
+
+    /// Hands `notification` to the `notify` program, if one is configured,
+    /// and records that it did with a `NotificationSent` event.
+    pub(crate) async fn notify_user(&self, sub_id: &str, notification: &UserNotification) {
+        if self.services.notifier.notify(notification) {
+            self.deliver_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::NotificationSent(NotificationSentEvent {
+                    kind: notification.kind(),
+                }),
+            })
+            .await;
         }
     }
 
@@ -1300,14 +1826,41 @@ impl Session {
         if let Some(status) = agent_status_from_event(&event.msg) {
             self.agent_status.send_replace(status);
         }
-        self.persist_rollout_items(&[RolloutItem::EventMsg(event.msg.clone())])
+        self.persist_rollout_items(&[RolloutItem::EventMsg(redact_event_msg(&event.msg))])
             .await;
         self.flush_rollout().await;
+        self.publish_live_event(&event);
         if let Err(e) = self.tx_event.send(event).await {
             debug!("dropping event because channel is closed: {e}");
         }
     }
 
+    /// Copies the event to attached read-only clients, if any, in the
+    /// redacted form the rollout gets.
+    fn publish_live_event(&self, event: &Event) {
+        let live_events = self
+            .services
+            .live_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(live_events) = live_events.as_ref() {
+            live_events.publish(&Event {
+                id: event.id.clone(),
+                msg: redact_event_msg(&event.msg),
+            });
+        }
+    }
+
+    /// Stops accepting attach clients. Clients already attached see the
+    /// events published so far and then the end of the stream.
+    fn close_live_events(&self) {
+        self.services
+            .live_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+
     pub(crate) async fn emit_turn_item_started(&self, turn_context: &TurnContext, item: &TurnItem) {
         self.send_event(
             turn_context,
@@ -1343,13 +1896,13 @@ impl Session {
         amendment: &ExecPolicyAmendment,
     ) -> Result<(), ExecPolicyUpdateError> {
         let features = self.features.clone();
-        let codex_home = self
+        let state_dir = self
             .state
             .lock()
             .await
             .session_configuration
             .original_config_do_not_use
-            .codex_home
+            .state_dir
             .clone();
 
         if !features.enabled(Feature::ExecPolicy) {
@@ -1359,7 +1912,7 @@ impl Session {
 
         self.services
             .exec_policy
-            .append_amendment_and_update(&codex_home, amendment)
+            .append_amendment_and_update(&state_dir, amendment)
             .await?;
 
         Ok(())
@@ -1377,9 +1930,25 @@ impl Session {
         call_id: String,
         command: Vec<String>,
         cwd: PathBuf,
+        injected_env: BTreeMap<String, String>,
         reason: Option<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        sandbox_denial_output: Option<String>,
     ) -> ReviewDecision {
+        if let Some(decision) = self
+            .decide_headless_approval(
+                turn_context,
+                &call_id,
+                HeadlessApprovalRequest::Exec {
+                    command: command.clone(),
+                    cwd: cwd.clone(),
+                },
+            )
+            .await
+        {
+            return decision;
+        }
+
         let sub_id = turn_context.sub_id.clone();
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -1398,15 +1967,19 @@ impl Session {
             warn!("Overwriting existing pending approval for sub_id: {event_id}");
         }
 
-        let parsed_cmd = parse_command(&command);
+        let parsed_cmd = Arc::unwrap_or_clone(self.parsed_command(&command));
+        let risk = classify_command_risk(&command, &cwd);
         let event = EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
             call_id,
             turn_id: turn_context.sub_id.clone(),
             command,
             cwd,
+            injected_env,
             reason,
             proposed_execpolicy_amendment,
             parsed_cmd,
+            risk,
+            sandbox_denial_output,
         });
         self.send_event(turn_context, event).await;
         rx_approve.await.unwrap_or_default()
@@ -1420,6 +1993,21 @@ impl Session {
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
+        let mut files: Vec<PathBuf> = changes.keys().cloned().collect();
+        files.sort();
+        if let Some(decision) = self
+            .decide_headless_approval(
+                turn_context,
+                &call_id,
+                HeadlessApprovalRequest::Patch { files },
+            )
+            .await
+        {
+            let (tx_approve, rx_approve) = oneshot::channel();
+            let _ = tx_approve.send(decision);
+            return rx_approve;
+        }
+
         let sub_id = turn_context.sub_id.clone();
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -1449,6 +2037,49 @@ impl Session {
         rx_approve
     }
 
+    /// Answers an approval request with the configured headless approval
+    /// policy, if any. A denial's reason is kept for
+    /// [`Session::take_rejection_reason`].
+    async fn decide_headless_approval(
+        &self,
+        turn_context: &TurnContext,
+        call_id: &str,
+        request: HeadlessApprovalRequest,
+    ) -> Option<ReviewDecision> {
+        let config = self.get_config().await;
+        let headless = config.headless_approvals.as_ref()?;
+        let HeadlessDecision { decision, reason } = match &request {
+            HeadlessApprovalRequest::Exec { command, .. } => headless.decide_exec(command),
+            HeadlessApprovalRequest::Patch { .. } => headless.decide_patch(),
+        };
+        if decision == ReviewDecision::Denied {
+            let mut active = self.active_turn.lock().await;
+            if let Some(at) = active.as_mut() {
+                let mut ts = at.turn_state.lock().await;
+                ts.insert_rejection_reason(call_id.to_string(), reason.clone());
+            }
+        }
+        let event = EventMsg::HeadlessApprovalDecision(HeadlessApprovalDecisionEvent {
+            call_id: call_id.to_string(),
+            turn_id: turn_context.sub_id.clone(),
+            request,
+            policy: headless.to_string(),
+            decision: decision.clone(),
+            reason,
+        });
+        self.send_event(turn_context, event).await;
+        Some(decision)
+    }
+
+    /// The reason to give the model for the denied approval of `call_id`,
+    /// when something other than a person denied it.
+    pub(crate) async fn take_rejection_reason(&self, call_id: &str) -> Option<String> {
+        let mut active = self.active_turn.lock().await;
+        let at = active.as_mut()?;
+        let mut ts = at.turn_state.lock().await;
+        ts.take_rejection_reason(call_id)
+    }
+
     pub async fn request_user_input(
         &self,
         turn_context: &TurnContext,
@@ -1709,10 +2340,15 @@ impl Session {
                 .into(),
             );
         }
-        items.push(ResponseItem::from(EnvironmentContext::new(
-            Some(turn_context.cwd.clone()),
-            shell.as_ref().clone(),
+        items.push(ResponseItem::from(EnvironmentContext::from_turn_context(
+            turn_context,
+            shell.as_ref(),
         )));
+        if self.enabled(Feature::CapabilityDescriptor) {
+            let capabilities = capabilities::describe(self, turn_context).await;
+            items.push(capabilities::developer_message(&capabilities));
+            self.replace_capabilities(capabilities).await;
+        }
         items
     }
 
@@ -1733,18 +2369,50 @@ impl Session {
         state.clone_history()
     }
 
+    async fn history_len(&self) -> usize {
+        let state = self.state.lock().await;
+        state.history.raw_items().len()
+    }
+
+    /// History items recorded after the first `len`.
+    async fn history_since(&self, len: usize) -> Vec<ResponseItem> {
+        let state = self.state.lock().await;
+        state
+            .history
+            .raw_items()
+            .get(len..)
+            .map(<[ResponseItem]>::to_vec)
+            .unwrap_or_default()
+    }
+
     pub(crate) async fn update_token_usage_info(
         &self,
         turn_context: &TurnContext,
         token_usage: Option<&TokenUsage>,
     ) {
+        let cost = match token_usage {
+            Some(token_usage) => self
+                .token_price(turn_context)
+                .await
+                .map_or(0.0, |price| tool_tokens::usage_cost(&price, token_usage)),
+            None => 0.0,
+        };
         {
             let mut state = self.state.lock().await;
             if let Some(token_usage) = token_usage {
                 state.update_token_info_from_usage(
                     token_usage,
                     turn_context.client.get_model_context_window(),
+                    &turn_context.provider_id,
                 );
+                state.add_cost(cost);
+            }
+        }
+        if let Some(token_usage) = token_usage {
+            let active = self.active_turn.lock().await;
+            if let Some(at) = active.as_ref() {
+                let mut ts = at.turn_state.lock().await;
+                ts.record_token_usage(token_usage, cost);
             }
         }
         self.send_token_count_event(turn_context).await;
@@ -1764,6 +2432,9 @@ impl Session {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window: None,
+                by_provider: Default::default(),
+                prompt_cache: Default::default(),
+                tool_output_tokens: 0,
             });
 
             info.last_token_usage = TokenUsage {
@@ -1906,6 +2577,14 @@ impl Session {
             .await;
     }
 
+    /// The sub id of the running turn, if any.
+    pub(crate) async fn active_sub_id(&self) -> Option<String> {
+        let active = self.active_turn.lock().await;
+        active
+            .as_ref()
+            .and_then(|at| at.tasks.keys().next().cloned())
+    }
+
     /// Returns the input if there was no task running to inject into
     pub async fn inject_input(&self, input: Vec<UserInput>) -> Result<(), Vec<UserInput>> {
         let mut active = self.active_turn.lock().await;
@@ -2003,15 +2682,44 @@ impl Session {
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        timeout: Duration,
+        updates: Option<ToolCallUpdateSender>,
+        call_id: &str,
     ) -> anyhow::Result<CallToolResult> {
         self.services
             .mcp_connection_manager
             .read()
             .await
-            .call_tool(server, tool, arguments)
+            .call_tool(server, tool, arguments, timeout, updates, call_id)
+            .await
+    }
+
+    /// Tells `server` to stop the tool call `call_id`. Returns `false` if the
+    /// call is not running.
+    pub(crate) async fn cancel_mcp_tool_call(&self, server: &str, call_id: &str) -> bool {
+        self.services
+            .mcp_connection_manager
+            .read()
+            .await
+            .cancel_tool_call(server, call_id)
+    }
+
+    /// Returns the `tool_timeout_sec` configured for an MCP server, if any.
+    pub(crate) async fn mcp_tool_timeout(&self, server: &str) -> Option<Duration> {
+        self.services
+            .mcp_connection_manager
+            .read()
+            .await
+            .configured_tool_timeout(server)
             .await
     }
 
+    /// Current per-tool default timeouts. Read on every call so a reload
+    /// only affects calls made after it.
+    pub(crate) async fn tool_timeouts(&self) -> ToolTimeouts {
+        *self.services.tool_timeouts.read().await
+    }
+
     pub(crate) async fn parse_mcp_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
         self.services
             .mcp_connection_manager
@@ -2021,38 +2729,397 @@ impl Session {
             .await
     }
 
-    pub async fn interrupt_task(self: &Arc<Self>) {
-        info!("interrupt received: abort current task, if any");
-        let has_active_turn = { self.active_turn.lock().await.is_some() };
-        if has_active_turn {
-            self.abort_all_tasks(TurnAbortReason::Interrupted).await;
-        } else {
-            self.cancel_mcp_startup().await;
+    /// Tracks the cancellation token of a tool call that is being dispatched
+    /// so [`Op::CancelToolCall`] can stop it without aborting the turn.
+    pub(crate) async fn register_running_tool_call(
+        &self,
+        call_id: String,
+        cancellation_token: CancellationToken,
+    ) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.insert_running_tool_call(call_id, cancellation_token);
         }
     }
 
-    pub(crate) fn notifier(&self) -> &UserNotifier {
-        &self.services.notifier
+    pub(crate) async fn unregister_running_tool_call(&self, call_id: &str) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.remove_running_tool_call(call_id);
+        }
     }
 
-    pub(crate) fn user_shell(&self) -> Arc<shell::Shell> {
-        Arc::clone(&self.services.user_shell)
+    /// Cancels the running tool call `call_id`. Returns `false` when no such
+    /// call is running in the current turn.
+    pub(crate) async fn cancel_tool_call(&self, call_id: &str) -> bool {
+        info!("cancel received for tool call {call_id}");
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => {
+                let mut ts = at.turn_state.lock().await;
+                ts.cancel_running_tool_call(call_id)
+            }
+            None => false,
+        }
     }
 
-    async fn refresh_mcp_servers_if_requested(&self, turn_context: &TurnContext) {
-        let refresh_config = { self.pending_mcp_server_refresh_config.lock().await.take() };
-        let Some(refresh_config) = refresh_config else {
-            return;
-        };
+    /// Adds the wall time of one finished tool call to the current turn.
+    pub(crate) async fn record_tool_time(&self, elapsed: Duration) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_tool_time(elapsed);
+        }
+    }
 
-        let McpServerRefreshConfig {
-            mcp_servers,
-            mcp_oauth_credentials_store_mode,
-        } = refresh_config;
+    /// Adds the time spent streaming one model response to the current turn.
+    /// Whether tool call `call_id` may run: false when the turn already
+    /// dispatched a call with that id, as when a retried stream repeats the
+    /// calls of the response that was cut off.
+    pub(crate) async fn dispatch_tool_call_once(&self, call_id: &str) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => {
+                let mut ts = at.turn_state.lock().await;
+                ts.mark_tool_call_dispatched(call_id)
+            }
+            None => true,
+        }
+    }
 
-        let mcp_servers =
-            match serde_json::from_value::<HashMap<String, McpServerConfig>>(mcp_servers) {
-                Ok(servers) => servers,
+    pub(crate) async fn record_model_time(&self, elapsed: Duration) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_model_time(elapsed);
+        }
+    }
+
+    /// Adds the output of one finished exec call to what the next model
+    /// request will carry, for [`crate::turn_progress`].
+    pub(crate) async fn record_tool_output_bytes(&self, bytes: usize) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_tool_output_bytes(bytes);
+        }
+    }
+
+    /// Adds the estimated tokens of the output tool call `call_id` sent to
+    /// the model to the current turn and the session.
+    pub(crate) async fn record_tool_output_tokens(
+        &self,
+        call_id: &str,
+        tool_name: &str,
+        estimated_tokens: u64,
+    ) {
+        self.state
+            .lock()
+            .await
+            .record_tool_output_tokens(estimated_tokens);
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_tool_output_tokens(ToolCallTokens {
+                call_id: call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                estimated_tokens,
+            });
+        }
+    }
+
+    /// Price of the turn's model under `[token_prices]`, if it has one.
+    pub(crate) async fn token_price(&self, turn_context: &TurnContext) -> Option<TokenPrice> {
+        self.get_config()
+            .await
+            .token_prices
+            .get(&turn_context.client.get_model())
+            .copied()
+    }
+
+    /// Approximate cost of the session's model requests so far.
+    pub(crate) async fn session_cost(&self) -> f64 {
+        self.state.lock().await.cost()
+    }
+
+    /// Adds `path` to the files read this turn, remembering its modification
+    /// time as of the read.
+    pub(crate) async fn record_file_read(&self, path: PathBuf, modified: Option<SystemTime>) {
+        let path = dunce::canonicalize(&path).unwrap_or(path);
+        if let Some(watcher) = self.workspace_watcher() {
+            watcher.record_seen(path.clone(), modified);
+        }
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_file_read(path, modified);
+        }
+    }
+
+    /// Adds `path` to the files written this turn, remembering its
+    /// modification time after the write.
+    pub(crate) async fn record_file_write(&self, path: PathBuf, modified: Option<SystemTime>) {
+        let path = dunce::canonicalize(&path).unwrap_or(path);
+        if let Some(watcher) = self.workspace_watcher() {
+            watcher.record_seen(path.clone(), modified);
+        }
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.record_file_write(path, modified);
+        }
+    }
+
+    /// Whether `path` was read or written this turn and has a different
+    /// modification time now, or the workspace watcher saw it change outside
+    /// Codex since it was last read or written.
+    pub(crate) async fn file_changed_since_seen(&self, path: &Path) -> bool {
+        let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if self
+            .workspace_watcher()
+            .is_some_and(|watcher| watcher.is_stale(&path))
+        {
+            return true;
+        }
+        let modified = modified_time(&path).await;
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at
+                .turn_state
+                .lock()
+                .await
+                .file_changed_since_seen(&path, modified),
+            None => false,
+        }
+    }
+
+    pub(crate) fn workspace_watcher(&self) -> Option<&WorkspaceWatcher> {
+        self.services.workspace_watcher.as_ref()
+    }
+
+    pub(crate) async fn set_plan(&self, plan: UpdatePlanArgs) {
+        self.state.lock().await.set_plan(plan);
+    }
+
+    pub(crate) async fn current_plan(&self) -> Option<UpdatePlanArgs> {
+        self.state.lock().await.plan()
+    }
+
+    /// Keeps `outcome` as the session's outcome; returns whether it replaced
+    /// an earlier one.
+    pub(crate) async fn record_outcome(&self, outcome: ReportedOutcome) -> bool {
+        self.state.lock().await.record_outcome(outcome)
+    }
+
+    async fn clear_pending_tool_output(&self) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            let mut ts = at.turn_state.lock().await;
+            ts.clear_pending_output();
+        }
+    }
+
+    pub(crate) async fn tool_activity(&self) -> Option<ToolActivity> {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => Some(at.turn_state.lock().await.tool_activity()),
+            None => None,
+        }
+    }
+
+    /// Remembers how exec call `call_id` was cleared to run, for its end event.
+    pub(crate) async fn record_exec_approval(&self, call_id: &str, approval: ExecApproval) {
+        let mut state = self.state.lock().await;
+        state.record_exec_approval(call_id.to_string(), approval);
+    }
+
+    pub(crate) async fn take_exec_approval(&self, call_id: &str) -> Option<ExecApproval> {
+        let mut state = self.state.lock().await;
+        state.take_exec_approval(call_id)
+    }
+
+    /// Remembers that the begin event of an exec call was sent, so the call
+    /// can be closed if its turn ends first; see [`Self::take_open_exec_calls`].
+    pub(crate) async fn record_open_exec_call(&self, begin: &ExecCommandBeginEvent) {
+        let mut state = self.state.lock().await;
+        state.open_exec_call(
+            begin.call_id.clone(),
+            OpenExecCall {
+                begin: begin.clone(),
+                started: Instant::now(),
+            },
+        );
+    }
+
+    pub(crate) async fn close_exec_call(&self, call_id: &str) {
+        let mut state = self.state.lock().await;
+        state.close_exec_call(call_id);
+    }
+
+    /// Takes the exec calls of `turn_id` that began but never sent an end
+    /// event.
+    pub(crate) async fn take_open_exec_calls(&self, turn_id: &str) -> Vec<OpenExecCall> {
+        let mut state = self.state.lock().await;
+        state.take_open_exec_calls(turn_id)
+    }
+
+    /// Takes the tool call digests of the turns since the last compaction.
+    pub(crate) async fn take_tool_digests(&self) -> Vec<TurnDigest> {
+        let mut state = self.state.lock().await;
+        state.take_tool_digests()
+    }
+
+    /// Records how the command with approval `signature` exited; see
+    /// [`crate::loop_detection`].
+    pub(crate) async fn record_command_exit(
+        &self,
+        signature: String,
+        turn_id: &str,
+        exit_code: i32,
+        threshold: u32,
+    ) -> Option<FailureLoop> {
+        let mut state = self.state.lock().await;
+        state.record_command_exit(signature, turn_id, exit_code, threshold)
+    }
+
+    /// Charges a tool call of turn `turn_id` against the tool budgets; see
+    /// [`crate::tool_budget`].
+    pub(crate) async fn charge_tool_call(&self, turn_id: &str) -> Result<(), BudgetExhausted> {
+        self.state.lock().await.tool_budget().charge_call(turn_id)
+    }
+
+    /// Adds `elapsed` to the command time of turn `turn_id`.
+    pub(crate) async fn record_exec_time(&self, turn_id: &str, elapsed: Duration) {
+        self.state
+            .lock()
+            .await
+            .tool_budget()
+            .record_exec_time(turn_id, elapsed);
+    }
+
+    pub(crate) async fn tool_budget_limits(&self) -> ToolBudget {
+        self.state.lock().await.tool_budget().limits()
+    }
+
+    /// See [`SessionState::replace_capabilities`].
+    pub(crate) async fn replace_capabilities(
+        &self,
+        capabilities: EnvironmentCapabilities,
+    ) -> Option<EnvironmentCapabilities> {
+        self.state.lock().await.replace_capabilities(capabilities)
+    }
+
+    pub(crate) async fn tool_budget_remaining(&self, turn_id: &str) -> ToolBudgetRemaining {
+        self.state.lock().await.tool_budget().remaining(turn_id)
+    }
+
+    pub(crate) async fn raise_tool_budget(
+        &self,
+        max_calls_per_turn: Option<u32>,
+        max_calls_per_session: Option<u32>,
+        max_exec_time_per_turn: Option<Duration>,
+    ) {
+        self.state.lock().await.tool_budget().raise(
+            max_calls_per_turn,
+            max_calls_per_session,
+            max_exec_time_per_turn,
+        );
+    }
+
+    /// Whether turn `turn_id` ran out of a tool budget with `end_turn` on.
+    async fn tool_budget_ends_turn(&self, turn_id: &str) -> bool {
+        self.state.lock().await.tool_budget().ends_turn(turn_id)
+    }
+
+    /// Handles `Op::SoftInterrupt`: the running tool calls finish and the
+    /// turn ends after them. A second soft interrupt aborts the turn.
+    pub async fn soft_interrupt_task(self: &Arc<Self>) {
+        info!("soft interrupt received: end the turn after the running tool call");
+        let already_requested = {
+            let active = self.active_turn.lock().await;
+            match active.as_ref() {
+                Some(at) => Some(at.turn_state.lock().await.request_soft_interrupt()),
+                None => None,
+            }
+        };
+        match already_requested {
+            Some(false) => {}
+            Some(true) => self.abort_all_tasks(TurnAbortReason::Interrupted).await,
+            None => self.cancel_mcp_startup().await,
+        }
+    }
+
+    /// Whether the current turn is to end after its running tool calls.
+    pub(crate) async fn turn_soft_interrupted(&self) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at.turn_state.lock().await.soft_interrupted(),
+            None => false,
+        }
+    }
+
+    /// Git state the current turn's next command is compared against.
+    pub(crate) async fn turn_git_state(&self) -> Option<GitStateSnapshot> {
+        let active = self.active_turn.lock().await;
+        let at = active.as_ref()?;
+        at.turn_state.lock().await.git_state().cloned()
+    }
+
+    pub(crate) async fn set_turn_git_state(&self, state: Option<GitStateSnapshot>) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            at.turn_state.lock().await.set_git_state(state);
+        }
+    }
+
+    /// Adds a line to the current turn's `TurnSummary`.
+    pub(crate) async fn add_turn_caution(&self, caution: String) {
+        let active = self.active_turn.lock().await;
+        if let Some(at) = active.as_ref() {
+            at.turn_state.lock().await.add_caution(caution);
+        }
+    }
+
+    pub async fn interrupt_task(self: &Arc<Self>) {
+        info!("interrupt received: abort current task, if any");
+        let has_active_turn = { self.active_turn.lock().await.is_some() };
+        if has_active_turn {
+            self.abort_all_tasks(TurnAbortReason::Interrupted).await;
+        } else {
+            self.cancel_mcp_startup().await;
+        }
+    }
+
+    pub(crate) fn user_shell(&self) -> Arc<shell::Shell> {
+        Arc::clone(&self.services.user_shell)
+    }
+
+    /// Parses `command` for display, reusing an earlier parse of the same
+    /// command vector in this session.
+    pub(crate) fn parsed_command(&self, command: &[String]) -> Arc<Vec<ParsedCommand>> {
+        self.services
+            .parsed_commands
+            .get_or_parse(command, |command| {
+                parse_command(&self.services.user_shell.canonical_exec_args(command))
+            })
+    }
+
+    async fn refresh_mcp_servers_if_requested(&self, turn_context: &TurnContext) {
+        let refresh_config = { self.pending_mcp_server_refresh_config.lock().await.take() };
+        let Some(refresh_config) = refresh_config else {
+            return;
+        };
+
+        let McpServerRefreshConfig {
+            mcp_servers,
+            mcp_oauth_credentials_store_mode,
+        } = refresh_config;
+
+        let mcp_servers =
+            match serde_json::from_value::<HashMap<String, McpServerConfig>>(mcp_servers) {
+                Ok(servers) => servers,
                 Err(err) => {
                     warn!("failed to parse MCP server refresh config: {err}");
                     return;
@@ -2085,6 +3152,8 @@ impl Session {
         let cancel_token = self.reset_mcp_startup_cancellation_token().await;
 
         let mut refreshed_manager = McpConnectionManager::default();
+        refreshed_manager.set_sampling_jobs(self.services.mcp_sampling.jobs());
+        refreshed_manager.set_tools_cache(McpToolsCache::new(&config.codex_home));
         refreshed_manager
             .initialize(
                 &mcp_servers,
@@ -2140,6 +3209,53 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::Interrupt => {
                 handlers::interrupt(&sess).await;
             }
+            Op::SoftInterrupt => {
+                handlers::soft_interrupt(&sess).await;
+            }
+            Op::CancelToolCall { call_id } => {
+                handlers::cancel_tool_call(&sess, &call_id).await;
+            }
+            Op::RaiseToolBudget {
+                max_calls_per_turn,
+                max_calls_per_session,
+                max_exec_ms_per_turn,
+            } => {
+                handlers::raise_tool_budget(
+                    &sess,
+                    max_calls_per_turn,
+                    max_calls_per_session,
+                    max_exec_ms_per_turn,
+                )
+                .await;
+            }
+            Op::SwitchModel { model, provider } => {
+                handlers::switch_model(&sess, sub.id.clone(), model, provider).await;
+            }
+            Op::UpdateWorkspaceRoots { add, remove } => {
+                handlers::update_workspace_roots(&sess, sub.id.clone(), add, remove).await;
+            }
+            Op::SetPlanOnly { enabled } => {
+                handlers::set_plan_only(&sess, sub.id.clone(), enabled).await;
+            }
+            Op::SnapshotWorkspace => {
+                handlers::snapshot_workspace(&sess, sub.id.clone()).await;
+            }
+            Op::RestoreSnapshot { id, force } => {
+                handlers::restore_snapshot(&sess, sub.id.clone(), id, force).await;
+            }
+            Op::ListSnapshots => {
+                handlers::list_snapshots(&sess, sub.id.clone()).await;
+            }
+            Op::UnifiedExecInput { process_id, input } => {
+                handlers::unified_exec_input(&sess, sub.id.clone(), process_id, input).await;
+            }
+            Op::ResizeUnifiedExecProcess {
+                process_id,
+                rows,
+                cols,
+            } => {
+                handlers::resize_unified_exec_process(&sess, &process_id, rows, cols).await;
+            }
             Op::OverrideTurnContext {
                 cwd,
                 approval_policy,
@@ -2204,9 +3320,31 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::RefreshMcpServers { config } => {
                 handlers::refresh_mcp_servers(&sess, config).await;
             }
+            Op::RetryMcpServer { server } => {
+                handlers::retry_mcp_server(&sess, sub.id.clone(), server).await;
+            }
+            Op::RefreshMcpTools { server } => {
+                handlers::refresh_mcp_tools(&sess, &config, sub.id.clone(), server).await;
+            }
+            Op::ListMcpPrompts => {
+                handlers::list_mcp_prompts(&sess, sub.id.clone()).await;
+            }
+            Op::RunMcpPrompt {
+                server,
+                prompt,
+                arguments,
+            } => {
+                handlers::run_mcp_prompt(&sess, sub.id.clone(), server, prompt, arguments).await;
+            }
             Op::ListCustomPrompts => {
                 handlers::list_custom_prompts(&sess, sub.id.clone()).await;
             }
+            Op::GetEffectiveConfig => {
+                handlers::get_effective_config(&sess, sub.id.clone()).await;
+            }
+            Op::ReloadConfig => {
+                handlers::reload_config(&sess, sub.id.clone()).await;
+            }
             Op::ListSkills { cwds, force_reload } => {
                 handlers::list_skills(&sess, sub.id.clone(), cwds, force_reload).await;
             }
@@ -2235,6 +3373,13 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             } => {
                 handlers::resolve_elicitation(&sess, server_name, request_id, decision).await;
             }
+            Op::ResolveMcpSampling {
+                server_name,
+                request_id,
+                decision,
+            } => {
+                handlers::resolve_mcp_sampling(&sess, server_name, request_id, decision).await;
+            }
             Op::Shutdown => {
                 if handlers::shutdown(&sess, sub.id.clone()).await {
                     break;
@@ -2251,36 +3396,58 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
 
 /// Operation handlers
 mod handlers {
+    use crate::SandboxState;
     use crate::codex::Session;
     use crate::codex::SessionSettingsUpdate;
     use crate::codex::TurnContext;
 
     use crate::codex::spawn_review_thread;
+    use crate::config::CONFIG_TOML_FILE;
     use crate::config::Config;
+    use crate::config::reload::ChangedKey;
+    use crate::config::reload::ReloadTarget;
+    use crate::config::reload::changed_keys;
+    use crate::config::reload::load_reloaded_config;
+    use crate::config::reload::relaxed_policies;
 
+    use crate::capabilities;
     use crate::mcp::auth::compute_auth_statuses;
     use crate::mcp::collect_mcp_snapshot_from_manager;
     use crate::mcp::effective_mcp_servers;
+    use crate::mcp::prompts::prompt_messages_to_response_items;
     use crate::review_prompts::resolve_review_request;
     use crate::tasks::CompactTask;
     use crate::tasks::RegularTask;
     use crate::tasks::UndoTask;
     use crate::tasks::UserShellCommandTask;
+    use crate::workspace_snapshots;
     use codex_protocol::custom_prompts::CustomPrompt;
     use codex_protocol::protocol::CodexErrorInfo;
+    use codex_protocol::protocol::ConfigChange;
+    use codex_protocol::protocol::ConfigReloadedEvent;
     use codex_protocol::protocol::ErrorEvent;
     use codex_protocol::protocol::Event;
     use codex_protocol::protocol::EventMsg;
     use codex_protocol::protocol::ListCustomPromptsResponseEvent;
     use codex_protocol::protocol::ListSkillsResponseEvent;
+    use codex_protocol::protocol::McpListPromptsResponseEvent;
+    use codex_protocol::protocol::McpPromptResponseEvent;
     use codex_protocol::protocol::McpServerRefreshConfig;
+    use codex_protocol::protocol::ModelChangedEvent;
     use codex_protocol::protocol::Op;
+    use codex_protocol::protocol::PlanOnlyChangedEvent;
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
     use codex_protocol::protocol::SkillsListEntry;
+    use codex_protocol::protocol::TerminalInteractionEvent;
+    use codex_protocol::protocol::TerminalInteractionSource;
     use codex_protocol::protocol::ThreadRolledBackEvent;
     use codex_protocol::protocol::TurnAbortReason;
     use codex_protocol::protocol::WarningEvent;
+    use codex_protocol::protocol::WorkspaceRoot;
+    use codex_protocol::protocol::WorkspaceRootsChangedEvent;
+    use codex_protocol::protocol::WorkspaceSnapshotReason;
+    use codex_protocol::protocol::WorkspaceSnapshotsEvent;
     use codex_protocol::request_user_input::RequestUserInputResponse;
 
     use crate::context_manager::is_user_turn_boundary;
@@ -2292,8 +3459,10 @@ mod handlers {
     use codex_rmcp_client::ElicitationAction;
     use codex_rmcp_client::ElicitationResponse;
     use mcp_types::RequestId;
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use std::sync::Arc;
+    use std::time::Duration;
     use tracing::info;
     use tracing::warn;
 
@@ -2301,6 +3470,208 @@ mod handlers {
         sess.interrupt_task().await;
     }
 
+    pub async fn soft_interrupt(sess: &Arc<Session>) {
+        sess.soft_interrupt_task().await;
+    }
+
+    pub async fn cancel_tool_call(sess: &Arc<Session>, call_id: &str) {
+        if !sess.cancel_tool_call(call_id).await {
+            debug!("no running tool call to cancel for call_id: {call_id}");
+        }
+    }
+
+    pub async fn raise_tool_budget(
+        sess: &Session,
+        max_calls_per_turn: Option<u32>,
+        max_calls_per_session: Option<u32>,
+        max_exec_ms_per_turn: Option<u64>,
+    ) {
+        sess.raise_tool_budget(
+            max_calls_per_turn,
+            max_calls_per_session,
+            max_exec_ms_per_turn.map(Duration::from_millis),
+        )
+        .await;
+    }
+
+    pub async fn update_workspace_roots(
+        sess: &Arc<Session>,
+        sub_id: String,
+        add: Vec<WorkspaceRoot>,
+        remove: Vec<String>,
+    ) {
+        let removed = match sess.update_workspace_roots(&add, &remove).await {
+            Ok(removed) => removed,
+            Err(message) => {
+                sess.send_event_raw(Event {
+                    id: sub_id,
+                    msg: EventMsg::Error(ErrorEvent {
+                        message,
+                        codex_error_info: Some(CodexErrorInfo::BadRequest),
+                    }),
+                })
+                .await;
+                return;
+            }
+        };
+        // The next turn tells the model about the new roots and sandbox
+        // through the usual environment and permissions updates.
+        let turn = sess.new_default_turn_with_sub_id(sub_id).await;
+        sess.send_event(
+            &turn,
+            EventMsg::WorkspaceRootsChanged(WorkspaceRootsChangedEvent {
+                added: add,
+                removed,
+                roots: turn.workspace_roots.clone(),
+                writable_roots: crate::workspace_roots::writable_roots(&turn.sandbox_policy),
+            }),
+        )
+        .await;
+    }
+
+    pub async fn set_plan_only(sess: &Arc<Session>, sub_id: String, enabled: bool) {
+        // Turns already running keep the mode they started with, and what
+        // they proposed stays unexecuted: proposals are only ever reported.
+        sess.set_plan_only(enabled).await;
+        let turn = sess.new_default_turn_with_sub_id(sub_id).await;
+        sess.send_event(
+            &turn,
+            EventMsg::PlanOnlyChanged(PlanOnlyChangedEvent {
+                plan_only: turn.plan_only,
+            }),
+        )
+        .await;
+        capabilities::sync(sess, &turn).await;
+    }
+
+    pub async fn snapshot_workspace(sess: &Arc<Session>, sub_id: String) {
+        let turn = sess.new_default_turn_with_sub_id(sub_id.clone()).await;
+        let reason = WorkspaceSnapshotReason::Requested;
+        if let Err(err) = workspace_snapshots::take(sess, &turn, reason, Vec::new()).await {
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("could not snapshot the working tree: {err}"),
+                    codex_error_info: Some(CodexErrorInfo::Other),
+                }),
+            })
+            .await;
+        }
+    }
+
+    pub async fn restore_snapshot(sess: &Arc<Session>, sub_id: String, id: String, force: bool) {
+        let turn = sess.new_default_turn_with_sub_id(sub_id).await;
+        let event = workspace_snapshots::restore(sess, &turn, &id, force).await;
+        sess.send_event(&turn, EventMsg::WorkspaceSnapshotRestored(event))
+            .await;
+    }
+
+    pub async fn list_snapshots(sess: &Arc<Session>, sub_id: String) {
+        let snapshots = sess.services.workspace_snapshots.lock().await.list();
+        let turn = sess.new_default_turn_with_sub_id(sub_id).await;
+        sess.send_event(
+            &turn,
+            EventMsg::WorkspaceSnapshots(WorkspaceSnapshotsEvent { snapshots }),
+        )
+        .await;
+    }
+
+    pub async fn switch_model(
+        sess: &Arc<Session>,
+        sub_id: String,
+        model: String,
+        provider: Option<String>,
+    ) {
+        // Built before the switch: when the history would not fit the new
+        // model, the current one, which still holds it, compacts it.
+        let previous_turn = sess.new_default_turn_with_sub_id(sub_id.clone()).await;
+        let (from, to) = match sess.switch_model(model, provider).await {
+            Ok(switch) => switch,
+            Err(message) => {
+                sess.send_event_raw(Event {
+                    id: sub_id,
+                    msg: EventMsg::Error(ErrorEvent {
+                        message,
+                        codex_error_info: Some(CodexErrorInfo::BadRequest),
+                    }),
+                })
+                .await;
+                return;
+            }
+        };
+        let next_turn = sess.new_default_turn_with_sub_id(sub_id).await;
+        let compact_limit = next_turn
+            .client
+            .get_model_info()
+            .auto_compact_token_limit()
+            .unwrap_or(i64::MAX);
+        let compacts_history = sess.get_total_token_usage().await >= compact_limit;
+        sess.send_event(
+            &next_turn,
+            EventMsg::ModelChanged(ModelChangedEvent {
+                from,
+                to,
+                model_context_window: next_turn.client.get_model_context_window(),
+                compacts_history,
+            }),
+        )
+        .await;
+        // A running turn keeps its model; the next turn then compacts on
+        // the new one when it starts.
+        if compacts_history && sess.active_turn.lock().await.is_none() {
+            sess.spawn_task(
+                Arc::clone(&previous_turn),
+                vec![UserInput::Text {
+                    text: previous_turn.compact_prompt().to_string(),
+                    text_elements: Vec::new(),
+                }],
+                CompactTask,
+            )
+            .await;
+        }
+    }
+
+    pub async fn unified_exec_input(
+        sess: &Session,
+        sub_id: String,
+        process_id: String,
+        input: String,
+    ) {
+        let msg = match sess
+            .services
+            .unified_exec_manager
+            .write_user_input(&process_id, &input)
+            .await
+        {
+            Ok(call_id) => EventMsg::TerminalInteraction(TerminalInteractionEvent {
+                call_id,
+                process_id,
+                stdin: input,
+                source: TerminalInteractionSource::User,
+            }),
+            Err(err) => EventMsg::Warning(WarningEvent {
+                message: format!("Cannot write to background terminal {process_id}: {err}"),
+            }),
+        };
+        sess.send_event_raw(Event { id: sub_id, msg }).await;
+    }
+
+    pub async fn resize_unified_exec_process(
+        sess: &Session,
+        process_id: &str,
+        rows: u16,
+        cols: u16,
+    ) {
+        if let Err(err) = sess
+            .services
+            .unified_exec_manager
+            .resize_process(process_id, rows, cols)
+            .await
+        {
+            debug!("failed to resize unified exec process {process_id}: {err}");
+        }
+    }
+
     pub async fn override_turn_context(
         sess: &Session,
         sub_id: String,
@@ -2341,6 +3712,7 @@ mod handlers {
             sess.record_conversation_items(&current_context, &update_items)
                 .await;
         }
+        capabilities::sync(sess, &current_context).await;
     }
 
     pub async fn user_input_or_turn(
@@ -2430,6 +3802,11 @@ mod handlers {
 
             sess.refresh_mcp_servers_if_requested(&current_context)
                 .await;
+            let prompt_items = std::mem::take(&mut *sess.pending_mcp_prompt_items.lock().await);
+            if !prompt_items.is_empty() {
+                sess.record_conversation_items(&current_context, &prompt_items)
+                    .await;
+            }
             sess.spawn_task(Arc::clone(&current_context), items, RegularTask)
                 .await;
             *previous_context = Some(current_context);
@@ -2481,6 +3858,25 @@ mod handlers {
         }
     }
 
+    pub async fn resolve_mcp_sampling(
+        sess: &Arc<Session>,
+        server_name: String,
+        request_id: RequestId,
+        decision: ReviewDecision,
+    ) {
+        if let Err(err) = sess
+            .services
+            .mcp_sampling
+            .resolve(server_name, request_id, decision)
+            .await
+        {
+            warn!(
+                error = %err,
+                "failed to resolve MCP sampling request in session"
+            );
+        }
+    }
+
     /// Propagate a user's exec approval decision to the session.
     /// Also optionally applies an execpolicy amendment.
     pub async fn exec_approval(sess: &Arc<Session>, id: String, decision: ReviewDecision) {
@@ -2585,6 +3981,121 @@ mod handlers {
         *guard = Some(refresh_config);
     }
 
+    pub async fn retry_mcp_server(sess: &Arc<Session>, sub_id: String, server: String) {
+        let sandbox_state = {
+            let state = sess.state.lock().await;
+            let session_configuration = &state.session_configuration;
+            SandboxState {
+                sandbox_policy: session_configuration.sandbox_policy.get().clone(),
+                codex_linux_sandbox_exe: session_configuration
+                    .original_config_do_not_use
+                    .codex_linux_sandbox_exe
+                    .clone(),
+                sandbox_cwd: session_configuration.cwd.clone(),
+            }
+        };
+        let cancel_token = sess.mcp_startup_cancellation_token().await;
+        let result = sess
+            .services
+            .mcp_connection_manager
+            .write()
+            .await
+            .retry_server(&server, sess.get_tx_event(), cancel_token, sandbox_state)
+            .await;
+        if let Err(err) = result {
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Warning(WarningEvent {
+                    message: format!("Cannot retry MCP server: {err}"),
+                }),
+            })
+            .await;
+        }
+    }
+
+    pub async fn refresh_mcp_tools(
+        sess: &Session,
+        config: &Arc<Config>,
+        sub_id: String,
+        server: String,
+    ) {
+        let result = sess
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .refresh_tools(&server)
+            .await;
+        match result {
+            Ok(status) => {
+                sess.send_event_raw(Event {
+                    id: sub_id.clone(),
+                    msg: EventMsg::McpServerStatus(status),
+                })
+                .await;
+                list_mcp_tools(sess, config, sub_id).await;
+            }
+            Err(err) => {
+                sess.send_event_raw(Event {
+                    id: sub_id,
+                    msg: EventMsg::Warning(WarningEvent {
+                        message: format!("Cannot refresh tools of MCP server '{server}': {err:#}"),
+                    }),
+                })
+                .await;
+            }
+        }
+    }
+
+    pub async fn list_mcp_prompts(sess: &Session, sub_id: String) {
+        let prompts = sess
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .list_all_prompts()
+            .await;
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::McpListPromptsResponse(McpListPromptsResponseEvent { prompts }),
+        })
+        .await;
+    }
+
+    pub async fn run_mcp_prompt(
+        sess: &Session,
+        sub_id: String,
+        server: String,
+        prompt: String,
+        arguments: HashMap<String, String>,
+    ) {
+        let result = sess
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .get_prompt(&server, &prompt, arguments)
+            .await;
+        let (error, messages) = match result {
+            Ok(result) => {
+                let items = prompt_messages_to_response_items(&result.messages);
+                sess.pending_mcp_prompt_items.lock().await.extend(items);
+                (None, result.messages)
+            }
+            Err(err) => (Some(format!("{err:#}")), Vec::new()),
+        };
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::McpPromptResponse(McpPromptResponseEvent {
+                server,
+                prompt,
+                error,
+                messages,
+            }),
+        })
+        .await;
+    }
+
     pub async fn list_mcp_tools(sess: &Session, config: &Arc<Config>, sub_id: String) {
         let mcp_connection_manager = sess.services.mcp_connection_manager.read().await;
         let auth = sess.services.auth_manager.auth().await;
@@ -2612,13 +4123,218 @@ mod handlers {
 
         let event = Event {
             id: sub_id,
-            msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
-                custom_prompts,
-            }),
+            msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
+                custom_prompts,
+            }),
+        };
+        sess.send_event_raw(event).await;
+    }
+
+    pub async fn get_effective_config(sess: &Session, sub_id: String) {
+        let config = {
+            let state = sess.state.lock().await;
+            Arc::clone(&state.session_configuration.original_config_do_not_use)
+        };
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::EffectiveConfigResponse(super::effective_config_report(&config)),
         };
         sess.send_event_raw(event).await;
     }
 
+    pub async fn reload_config(sess: &Session, sub_id: String) {
+        let (current, cwd, approval_policy, sandbox_policy) = {
+            let state = sess.state.lock().await;
+            let session_configuration = &state.session_configuration;
+            (
+                Arc::clone(&session_configuration.original_config_do_not_use),
+                session_configuration.cwd.clone(),
+                session_configuration.approval_policy.value(),
+                session_configuration.sandbox_policy.get().clone(),
+            )
+        };
+
+        let reloaded = match load_reloaded_config(&current, &cwd, &sandbox_policy).await {
+            Ok(reloaded) => reloaded,
+            Err(err) => {
+                sess.send_event_raw(Event {
+                    id: sub_id,
+                    msg: EventMsg::Warning(WarningEvent {
+                        message: format!("Failed to reload config: {err}"),
+                    }),
+                })
+                .await;
+                return;
+            }
+        };
+        let current_effective = current.config_layer_stack.effective_config();
+        let reloaded_effective = reloaded.config_layer_stack.effective_config();
+        let mut changes = changed_keys(
+            &current_effective,
+            &reloaded_effective,
+            current.active_profile.as_deref(),
+        );
+        let touches = |changes: &[ChangedKey], target: ReloadTarget| {
+            changes.iter().any(|change| change.target == target)
+        };
+
+        // Approval and sandbox changes are applied together, and only when
+        // they do not relax the session's policy or the project opted in to
+        // that, the same gate project configs go through at startup.
+        let mut updates = SessionSettingsUpdate::default();
+        if touches(&changes, ReloadTarget::ApprovalPolicy) {
+            updates.approval_policy = Some(reloaded.approval_policy.value());
+        }
+        if touches(&changes, ReloadTarget::SandboxPolicy) {
+            updates.sandbox_policy = Some(reloaded.sandbox_policy.get().clone());
+        }
+        let relaxed = relaxed_policies(
+            approval_policy,
+            &sandbox_policy,
+            &current_effective,
+            updates.approval_policy.unwrap_or(approval_policy),
+            updates.sandbox_policy.as_ref().unwrap_or(&sandbox_policy),
+            &reloaded_effective,
+            current.active_profile.as_deref(),
+        );
+        let policy_rejection = if !relaxed.is_empty()
+            && reloaded.active_project.allow_policy_overrides != Some(true)
+        {
+            Some(format!(
+                "relaxes {} for this session; set `allow_policy_overrides = true` in this project's `[projects]` entry in {} or start a new session",
+                relaxed.join(", "),
+                current.codex_home.join(CONFIG_TOML_FILE).display()
+            ))
+        } else if updates.approval_policy.is_some() || updates.sandbox_policy.is_some() {
+            sess.update_settings(updates.clone())
+                .await
+                .err()
+                .map(|err| err.to_string())
+        } else {
+            None
+        };
+        if let Some(reason) = &policy_rejection {
+            for change in &mut changes {
+                if matches!(
+                    change.target,
+                    ReloadTarget::ApprovalPolicy | ReloadTarget::SandboxPolicy
+                ) {
+                    change.target = ReloadTarget::Rejected(reason.clone());
+                }
+            }
+        }
+
+        if policy_rejection.is_none()
+            && let Some(sandbox_policy) = updates.sandbox_policy
+        {
+            let sandbox_state = SandboxState {
+                sandbox_policy,
+                codex_linux_sandbox_exe: current.codex_linux_sandbox_exe.clone(),
+                sandbox_cwd: cwd,
+            };
+            if let Err(e) = sess
+                .services
+                .mcp_connection_manager
+                .read()
+                .await
+                .notify_sandbox_state_change(&sandbox_state)
+                .await
+            {
+                warn!("Failed to notify sandbox state change to MCP servers: {e:#}");
+            }
+        }
+
+        let mut next_config = (*current).clone();
+        if touches(&changes, ReloadTarget::ToolTimeouts) {
+            // The run deadline comes from the command line, not the file.
+            let tool_timeouts = ToolTimeouts {
+                deadline: current.tool_timeouts.deadline,
+                ..reloaded.tool_timeouts
+            };
+            next_config.tool_timeouts = tool_timeouts;
+            *sess.services.tool_timeouts.write().await = tool_timeouts;
+        }
+        if touches(&changes, ReloadTarget::ToolOutputTokenLimit) {
+            next_config.tool_output_token_limit = reloaded.tool_output_token_limit;
+        }
+        if touches(&changes, ReloadTarget::ShellEnvironmentPolicy) {
+            next_config.shell_environment_policy = reloaded.shell_environment_policy.clone();
+        }
+        let mut mcp_statuses = Vec::new();
+        if touches(&changes, ReloadTarget::McpToolFilters) {
+            let reloaded_servers = reloaded.mcp_servers.get();
+            let mut servers = next_config.mcp_servers.get().clone();
+            for (name, server) in &mut servers {
+                if let Some(reloaded_server) = reloaded_servers.get(name) {
+                    server.enabled_tools = reloaded_server.enabled_tools.clone();
+                    server.disabled_tools = reloaded_server.disabled_tools.clone();
+                }
+            }
+            if let Err(err) = next_config.mcp_servers.set(servers) {
+                warn!("Failed to apply reloaded MCP tool filters to the session config: {err}");
+            }
+            mcp_statuses = sess
+                .services
+                .mcp_connection_manager
+                .write()
+                .await
+                .update_tool_filters(reloaded_servers);
+        }
+        // Keep diffing against the old layers while anything is rejected so
+        // the rejection is reported again on the next reload.
+        let rejected: Vec<String> = changes
+            .iter()
+            .filter_map(|change| match &change.target {
+                ReloadTarget::Rejected(reason) => Some(format!("{} ({reason})", change.key)),
+                _ => None,
+            })
+            .collect();
+        if rejected.is_empty() {
+            next_config.config_layer_stack = reloaded.config_layer_stack.clone();
+        }
+        {
+            let mut state = sess.state.lock().await;
+            state.session_configuration.original_config_do_not_use = Arc::new(next_config);
+        }
+
+        let changes = changes
+            .into_iter()
+            .map(|change| ConfigChange {
+                key: change.key,
+                old_value: change.old_value.map(|value| value.to_string()),
+                new_value: change.new_value.map(|value| value.to_string()),
+                rejected_reason: match change.target {
+                    ReloadTarget::Rejected(reason) => Some(reason),
+                    _ => None,
+                },
+            })
+            .collect();
+        sess.send_event_raw(Event {
+            id: sub_id.clone(),
+            msg: EventMsg::ConfigReloaded(ConfigReloadedEvent { changes }),
+        })
+        .await;
+        for status in mcp_statuses {
+            sess.send_event_raw(Event {
+                id: sub_id.clone(),
+                msg: EventMsg::McpServerStatus(status),
+            })
+            .await;
+        }
+        if !rejected.is_empty() {
+            sess.send_event_raw(Event {
+                id: sub_id,
+                msg: EventMsg::Warning(WarningEvent {
+                    message: format!(
+                        "Some config changes were not applied to this session: {}",
+                        rejected.join("; ")
+                    ),
+                }),
+            })
+            .await;
+        }
+    }
+
     pub async fn list_skills(
         sess: &Session,
         sub_id: String,
@@ -2722,6 +4438,12 @@ mod handlers {
             .unified_exec_manager
             .terminate_all_processes()
             .await;
+        sess.services.background_jobs.terminate_all().await;
+        sess.services.hooks.wait().await;
+        workspace_snapshots::shutdown(sess).await;
+        if let Some(tmpdir) = sess.tmpdir().await {
+            tmpdir.remove().await;
+        }
         info!("Shutting down Codex instance");
         let history = sess.clone_history().await;
         let turn_count = history
@@ -2760,6 +4482,7 @@ mod handlers {
             msg: EventMsg::ShutdownComplete,
         };
         sess.send_event_raw(event).await;
+        sess.close_live_events();
         true
     }
 
@@ -2823,7 +4546,11 @@ async fn spawn_review_thread(
         model_info: &review_model_info,
         features: &review_features,
         web_search_mode: Some(review_web_search_mode),
-    });
+    })
+    .with_availability(ToolAvailability::for_model(
+        &config.model_tools,
+        &review_model_info.slug,
+    ));
 
     let review_prompt = resolved.prompt.clone();
     let provider = parent_turn_context.client.get_provider();
@@ -2857,6 +4584,7 @@ async fn spawn_review_thread(
     let review_turn_context = TurnContext {
         sub_id: sub_id.to_string(),
         client,
+        provider_id: parent_turn_context.provider_id.clone(),
         tools_config,
         ghost_snapshot: parent_turn_context.ghost_snapshot.clone(),
         developer_instructions: None,
@@ -2867,11 +4595,17 @@ async fn spawn_review_thread(
         sandbox_policy: parent_turn_context.sandbox_policy.clone(),
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
         cwd: parent_turn_context.cwd.clone(),
+        workspace_roots: parent_turn_context.workspace_roots.clone(),
+        plan_only: parent_turn_context.plan_only,
+        tmpdir: parent_turn_context.tmpdir.clone(),
         final_output_json_schema: None,
         codex_linux_sandbox_exe: parent_turn_context.codex_linux_sandbox_exe.clone(),
         tool_call_gate: Arc::new(ReadinessFlag::new()),
         dynamic_tools: parent_turn_context.dynamic_tools.clone(),
         truncation_policy: model_info.truncation_policy.into(),
+        output_limits: parent_turn_context.output_limits,
+        loop_detection: parent_turn_context.loop_detection,
+        turn_progress: parent_turn_context.turn_progress,
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -2946,7 +4680,7 @@ fn errors_to_info(errors: &[SkillError]) -> Vec<SkillErrorInfo> {
 ///
 pub(crate) async fn run_turn(
     sess: Arc<Session>,
-    turn_context: Arc<TurnContext>,
+    mut turn_context: Arc<TurnContext>,
     input: Vec<UserInput>,
     cancellation_token: CancellationToken,
 ) -> Option<String> {
@@ -2955,15 +4689,20 @@ pub(crate) async fn run_turn(
     }
 
     let model_info = turn_context.client.get_model_info();
-    let auto_compact_limit = model_info.auto_compact_token_limit().unwrap_or(i64::MAX);
+    let mut auto_compact_limit = model_info.auto_compact_token_limit().unwrap_or(i64::MAX);
     let total_usage_tokens = sess.get_total_token_usage().await;
     if total_usage_tokens >= auto_compact_limit {
         run_auto_compact(&sess, &turn_context).await;
     }
     let event = EventMsg::TurnStarted(TurnStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        tools: Some(tool_names(
+            &turn_context.tools_config,
+            &turn_context.dynamic_tools,
+        )),
     });
     sess.send_event(&turn_context, event).await;
+    let _progress_reporter = turn_progress::spawn_reporter(&sess, &turn_context);
 
     let skills_outcome = Some(
         sess.services
@@ -2995,10 +4734,15 @@ pub(crate) async fn run_turn(
 
     sess.maybe_start_ghost_snapshot(Arc::clone(&turn_context), cancellation_token.child_token())
         .await;
+    git_state::record_baseline(&sess, &turn_context).await;
+    workspace_watcher::start_turn(&sess, &turn_context).await;
+    capabilities::sync(&sess, &turn_context).await;
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
-    let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+    let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(
+        TurnDiffTracker::with_workspace_roots(turn_context.workspace_roots.clone()),
+    ));
 
     let mut client_session = turn_context.client.new_session();
 
@@ -3028,6 +4772,8 @@ pub(crate) async fn run_turn(
             })
             .map(|user_message| user_message.message())
             .collect::<Vec<String>>();
+        sess.clear_pending_tool_output().await;
+        let history_len = sess.history_len().await;
         match run_sampling_request(
             Arc::clone(&sess),
             Arc::clone(&turn_context),
@@ -3043,6 +4789,16 @@ pub(crate) async fn run_turn(
                     needs_follow_up,
                     last_agent_message: sampling_request_last_agent_message,
                 } = sampling_request_output;
+                // A spent tool budget with `end_turn` on stops the turn here;
+                // the refused calls' results reach the model with the next
+                // user turn.
+                let needs_follow_up =
+                    needs_follow_up && !sess.tool_budget_ends_turn(&turn_context.sub_id).await;
+                // A soft interrupt ends the turn once the response's calls
+                // are settled; the task reports it as `TurnAborted`.
+                if sess.turn_soft_interrupted().await {
+                    break;
+                }
                 let total_usage_tokens = sess.get_total_token_usage().await;
                 let token_limit_reached = total_usage_tokens >= auto_compact_limit;
 
@@ -3054,14 +4810,27 @@ pub(crate) async fn run_turn(
 
                 if !needs_follow_up {
                     last_agent_message = sampling_request_last_agent_message;
-                    sess.notifier()
-                        .notify(&UserNotification::AgentTurnComplete {
+                    sess.notify_user(
+                        &turn_context.sub_id,
+                        &UserNotification::AgentTurnComplete {
                             thread_id: sess.conversation_id.to_string(),
                             turn_id: turn_context.sub_id.clone(),
                             cwd: turn_context.cwd.display().to_string(),
                             input_messages: sampling_request_input_messages,
                             last_assistant_message: last_agent_message.clone(),
-                        });
+                        },
+                    )
+                    .await;
+                    spawn_hook(
+                        &sess,
+                        &turn_context,
+                        HookPayload::TurnComplete {
+                            thread_id: sess.conversation_id.to_string(),
+                            turn_id: turn_context.sub_id.clone(),
+                            cwd: turn_context.cwd.clone(),
+                            last_agent_message: last_agent_message.clone(),
+                        },
+                    );
                     break;
                 }
                 continue;
@@ -3087,6 +4856,26 @@ pub(crate) async fn run_turn(
                 break;
             }
             Err(e) => {
+                // Move to the next `[provider_failover]` provider. The
+                // request is retried there unless part of its response was
+                // already recorded; later turns use the new provider either
+                // way.
+                if let Some(reason) = provider_failover::switch_reason(&e) {
+                    let retry_in_turn = sess.history_len().await == history_len;
+                    if let Some(next_turn) =
+                        sess.fail_over(&turn_context, reason, retry_in_turn).await
+                        && retry_in_turn
+                    {
+                        turn_context = next_turn;
+                        client_session = turn_context.client.new_session();
+                        auto_compact_limit = turn_context
+                            .client
+                            .get_model_info()
+                            .auto_compact_token_limit()
+                            .unwrap_or(i64::MAX);
+                        continue;
+                    }
+                }
                 info!("Turn error: {e:#}");
                 let event = EventMsg::Error(e.to_error_event(None));
                 sess.send_event(&turn_context, event).await;
@@ -3199,6 +4988,12 @@ async fn run_sampling_request(
     if let Some(connectors) = connectors_for_tools.as_ref() {
         mcp_tools = filter_codex_apps_mcp_tools(mcp_tools, connectors);
     }
+    mcp_tools.retain(|_, tool| {
+        !turn_context
+            .tools_config
+            .availability
+            .is_mcp_server_disabled(&tool.server_name)
+    });
     let router = Arc::new(ToolRouter::from_config(
         &turn_context.tools_config,
         Some(
@@ -3217,17 +5012,22 @@ async fn run_sampling_request(
 
     let base_instructions = sess.get_base_instructions().await;
 
-    let prompt = Prompt {
+    let mut prompt = Prompt {
         input,
         tools: router.specs(),
         parallel_tool_calls: model_supports_parallel,
         base_instructions,
         personality: turn_context.personality,
         output_schema: turn_context.final_output_json_schema.clone(),
+        max_output_tokens: None,
     };
 
+    let failover = turn_context.client.config().provider_failover.clone();
     let mut retries = 0;
+    // Retries per error class under `[provider_failover]`.
+    let mut class_retries: HashMap<ProviderSwitchReason, u64> = HashMap::new();
     loop {
+        let history_len = sess.history_len().await;
         let err = match try_run_sampling_request(
             Arc::clone(&router),
             Arc::clone(&sess),
@@ -3254,33 +5054,88 @@ async fn run_sampling_request(
             Err(err) => err,
         };
 
-        if !err.is_retryable() {
+        // With `[provider_failover]` each error class has its own retry
+        // policy; otherwise the provider's stream retry budget applies.
+        let policy = failover.as_ref().and_then(|failover| {
+            provider_failover::switch_reason(&err)
+                .map(|reason| (reason, provider_failover::retry_policy(failover, reason)))
+        });
+        if policy.is_none() && !err.is_retryable() {
             return Err(err);
         }
 
-        // Use the configured provider-specific stream retry budget.
-        let max_retries = turn_context.client.get_provider().stream_max_retries();
-        if retries < max_retries {
-            retries += 1;
-            let delay = match &err {
-                CodexErr::Stream(_, requested_delay) => {
-                    requested_delay.unwrap_or_else(|| backoff(retries))
-                }
-                _ => backoff(retries),
+        let (max_retries, attempt) = match policy {
+            Some((reason, policy)) => {
+                let attempt = class_retries.entry(reason).or_insert(0);
+                *attempt += 1;
+                (u64::from(policy.max_retries), *attempt)
+            }
+            None => {
+                retries += 1;
+                (
+                    turn_context.client.get_provider().stream_max_retries(),
+                    retries,
+                )
+            }
+        };
+        if attempt <= max_retries {
+            let delay = match (&err, policy) {
+                (CodexErr::Stream(_, Some(requested_delay)), _) => *requested_delay,
+                (_, Some((_, policy))) => provider_failover::retry_delay(policy, attempt),
+                _ => backoff(attempt),
             };
+            // A retry that cannot start before the run deadline only delays
+            // the timeout.
+            if sess
+                .tool_timeouts()
+                .await
+                .remaining()
+                .is_some_and(|remaining| delay >= remaining)
+            {
+                return Err(err);
+            }
             warn!(
-                "stream disconnected - retrying sampling request ({retries}/{max_retries} in {delay:?})...",
+                "stream disconnected - retrying sampling request ({attempt}/{max_retries} in {delay:?})...",
             );
 
+            // Output that completed before a disconnect stays recorded, so
+            // the retry starts from the history rather than from scratch.
+            let stream_retry = if provider_failover::switch_reason(&err)
+                == Some(ProviderSwitchReason::Disconnected)
+            {
+                let preserved = PreservedOutput::of(&sess.history_since(history_len).await);
+                let strategy = preserved.strategy();
+                if strategy != StreamRecoveryStrategy::Restart {
+                    prompt.input = stream_recovery::retry_input(
+                        sess.clone_history().await.for_prompt(),
+                        strategy,
+                    );
+                }
+                Some(StreamRetryEvent {
+                    attempt,
+                    max_retries,
+                    error: err.to_string(),
+                    items_preserved: preserved.items,
+                    tool_calls_preserved: preserved.tool_calls,
+                    strategy,
+                })
+            } else {
+                None
+            };
+
             // Surface retry information to any UI/front‑end so the
             // user understands what is happening instead of staring
             // at a seemingly frozen screen.
             sess.notify_stream_error(
                 &turn_context,
-                format!("Reconnecting... {retries}/{max_retries}"),
+                format!("Reconnecting... {attempt}/{max_retries}"),
                 err,
             )
             .await;
+            if let Some(stream_retry) = stream_retry {
+                sess.send_event(&turn_context, EventMsg::StreamRetry(stream_retry))
+                    .await;
+            }
 
             tokio::time::sleep(delay).await;
         } else {
@@ -3361,6 +5216,7 @@ async fn try_run_sampling_request(
     );
 
     sess.persist_rollout_items(&[rollout_item]).await;
+    let model_started = std::time::Instant::now();
     let mut stream = client_session
         .stream(prompt)
         .instrument(trace_span!("stream_request"))
@@ -3399,8 +5255,11 @@ async fn try_run_sampling_request(
             Err(codex_async_utils::CancelErr::Cancelled) => break Err(CodexErr::TurnAborted),
         };
 
+        // A failed stream still drains the tool calls it already started, so
+        // their output is recorded for the retry.
         let event = match event {
-            Some(res) => res?,
+            Some(Ok(event)) => event,
+            Some(Err(err)) => break Err(err),
             None => {
                 break Err(CodexErr::Stream(
                     "stream closed before response.completed".into(),
@@ -3540,63 +5399,225 @@ async fn try_run_sampling_request(
             }
         }
     };
+    sess.record_model_time(model_started.elapsed()).await;
 
     drain_in_flight(&mut in_flight, sess.clone(), turn_context.clone()).await?;
 
-    if should_emit_turn_diff {
-        let unified_diff = {
-            let mut tracker = turn_diff_tracker.lock().await;
-            tracker.get_unified_diff()
+    if should_emit_turn_diff {
+        emit_turn_diff(&sess, &turn_context, &turn_diff_tracker).await;
+    }
+
+    outcome
+}
+
+pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
+    responses.iter().rev().find_map(|item| {
+        if let ResponseItem::Message { role, content, .. } = item {
+            if role == "assistant" {
+                content.iter().rev().find_map(|ci| {
+                    if let ContentItem::OutputText { text } = ci {
+                        Some(text.clone())
+                    } else {
+                        None
+                    }
+                })
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+pub(crate) use tests::make_session_and_context;
+
+use crate::git_info::get_git_repo_root;
+#[cfg(any(test, feature = "test-support"))]
+pub(crate) use offline_session::make_session_and_context_with_rx;
+
+/// Offline `Session`/`TurnContext` construction shared by unit tests and the
+/// `test-support` benchmarks: no model, network, or rollout is involved.
+#[cfg(any(test, feature = "test-support"))]
+mod offline_session {
+    use super::*;
+    use crate::CodexAuth;
+    use crate::config::ConfigBuilder;
+    use crate::shell::default_user_shell;
+    use codex_app_server_protocol::AuthMode;
+    use codex_protocol::ThreadId;
+    use std::path::Path;
+
+    pub(crate) async fn build_test_config(codex_home: &Path) -> Config {
+        ConfigBuilder::default()
+            .codex_home(codex_home.to_path_buf())
+            .build()
+            .await
+            .unwrap_or_else(|err| panic!("load default test config: {err}"))
+    }
+
+    pub(crate) fn otel_manager(
+        conversation_id: ThreadId,
+        config: &Config,
+        model_info: &ModelInfo,
+        session_source: SessionSource,
+    ) -> OtelManager {
+        OtelManager::new(
+            conversation_id,
+            ModelsManager::get_model_offline(config.model.as_deref()).as_str(),
+            model_info.slug.as_str(),
+            None,
+            Some("test@test.com".to_string()),
+            Some(AuthMode::ChatGPT),
+            false,
+            "test".to_string(),
+            session_source,
+        )
+    }
+
+    // Like make_session_and_context, but returns Arc<Session> and the event receiver
+    // so tests and benchmarks can assert on emitted events.
+    pub(crate) async fn make_session_and_context_with_rx() -> (
+        Arc<Session>,
+        Arc<TurnContext>,
+        async_channel::Receiver<Event>,
+    ) {
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let codex_home = tempfile::tempdir().unwrap_or_else(|err| panic!("create temp dir: {err}"));
+        let config = build_test_config(codex_home.path()).await;
+        let config = Arc::new(config);
+        let conversation_id = ThreadId::default();
+        let auth_manager =
+            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key"));
+        let models_manager = Arc::new(ModelsManager::new(
+            config.codex_home.clone(),
+            auth_manager.clone(),
+        ));
+        let agent_control = AgentControl::default();
+        let exec_policy = ExecPolicyManager::default();
+        let (agent_status_tx, _agent_status_rx) = watch::channel(AgentStatus::PendingInit);
+        let model = ModelsManager::get_model_offline(config.model.as_deref());
+        let model_info = ModelsManager::construct_model_info_offline(model.as_str(), &config);
+        let reasoning_effort = config.model_reasoning_effort;
+        let collaboration_mode = CollaborationMode {
+            mode: ModeKind::Custom,
+            settings: Settings {
+                model,
+                reasoning_effort,
+                developer_instructions: None,
+            },
+        };
+        let session_configuration = SessionConfiguration {
+            provider: config.model_provider.clone(),
+            provider_id: config.model_provider_id.clone(),
+            collaboration_mode,
+            model_reasoning_summary: config.model_reasoning_summary,
+            developer_instructions: config.developer_instructions.clone(),
+            user_instructions: config.user_instructions.clone(),
+            personality: config.model_personality,
+            base_instructions: config
+                .base_instructions
+                .clone()
+                .unwrap_or_else(|| model_info.get_model_instructions(config.model_personality)),
+            compact_prompt: config.compact_prompt.clone(),
+            approval_policy: config.approval_policy.clone(),
+            sandbox_policy: config.sandbox_policy.clone(),
+            cwd: config.cwd.clone(),
+            workspace_roots: config.workspace_roots.clone(),
+            plan_only: config.plan_only,
+            tmpdir: None,
+            original_config_do_not_use: Arc::clone(&config),
+            session_source: SessionSource::Exec,
+            dynamic_tools: Vec::new(),
+        };
+        let per_turn_config = Session::build_per_turn_config(&session_configuration);
+        let model_info = ModelsManager::construct_model_info_offline(
+            session_configuration.collaboration_mode.model(),
+            &per_turn_config,
+        );
+        let otel_manager = otel_manager(
+            conversation_id,
+            config.as_ref(),
+            &model_info,
+            session_configuration.session_source.clone(),
+        );
+
+        let state = SessionState::new(session_configuration.clone());
+        let skills_manager = Arc::new(SkillsManager::new(config.codex_home.clone()));
+
+        let services = SessionServices {
+            mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
+            mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+            mcp_sampling: McpSampling::new().0,
+            hooks: HookRunner::new().0,
+            unified_exec_manager: UnifiedExecProcessManager::default(),
+            background_jobs: BackgroundJobManager::default(),
+            notifier: UserNotifier::new(None),
+            rollout: Mutex::new(None),
+            user_shell: Arc::new(default_user_shell()),
+            show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            exec_policy,
+            auth_manager: Arc::clone(&auth_manager),
+            otel_manager: otel_manager.clone(),
+            models_manager: Arc::clone(&models_manager),
+            tool_approvals: Mutex::new(ApprovalStore::default()),
+            skills_manager,
+            agent_control,
+            tool_timeouts: RwLock::new(config.tool_timeouts),
+            live_events: std::sync::Mutex::new(None),
+            parsed_commands: ParsedCommandCache::default(),
+            in_process_tools: HashMap::new(),
+            workspace_snapshots: Mutex::new(SnapshotStore::new(config.workspace_snapshots)),
+            exec_outputs: Mutex::new(ExecOutputLog::default()),
+            workspace_watcher: None,
         };
-        if let Ok(Some(unified_diff)) = unified_diff {
-            let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-            sess.clone().send_event(&turn_context, msg).await;
-        }
-    }
-
-    outcome
-}
 
-pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
-    responses.iter().rev().find_map(|item| {
-        if let ResponseItem::Message { role, content, .. } = item {
-            if role == "assistant" {
-                content.iter().rev().find_map(|ci| {
-                    if let ContentItem::OutputText { text } = ci {
-                        Some(text.clone())
-                    } else {
-                        None
-                    }
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    })
-}
+        let turn_context = Arc::new(Session::make_turn_context(
+            Some(Arc::clone(&auth_manager)),
+            &otel_manager,
+            session_configuration.provider.clone(),
+            &session_configuration,
+            per_turn_config,
+            model_info,
+            conversation_id,
+            "turn_id".to_string(),
+        ));
 
-#[cfg(test)]
-pub(crate) use tests::make_session_and_context;
+        let session = Arc::new(Session {
+            conversation_id,
+            tx_event,
+            agent_status: agent_status_tx,
+            state: Mutex::new(state),
+            features: config.features.clone(),
+            pending_mcp_server_refresh_config: Mutex::new(None),
+            pending_mcp_prompt_items: Mutex::new(Vec::new()),
+            active_turn: Mutex::new(None),
+            services,
+            next_internal_sub_id: AtomicU64::new(0),
+        });
 
-use crate::git_info::get_git_repo_root;
-#[cfg(test)]
-pub(crate) use tests::make_session_and_context_with_rx;
+        (session, turn_context, rx_event)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::CodexAuth;
-    use crate::config::ConfigBuilder;
     use crate::config::test_config;
     use crate::exec::ExecToolCallOutput;
     use crate::function_tool::FunctionCallError;
     use crate::shell::default_user_shell;
     use crate::tools::format_exec_output_str;
 
+    use super::offline_session::build_test_config;
+    use super::offline_session::otel_manager;
+
     use codex_protocol::ThreadId;
     use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::plan_tool::PlanItemArg;
+    use codex_protocol::plan_tool::StepStatus;
 
     use crate::protocol::CompactedItem;
     use crate::protocol::CreditsSnapshot;
@@ -3618,10 +5639,8 @@ mod tests {
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::registry::ToolHandler;
     use crate::turn_diff_tracker::TurnDiffTracker;
-    use codex_app_server_protocol::AuthMode;
     use codex_protocol::models::ContentItem;
     use codex_protocol::models::ResponseItem;
-    use std::path::Path;
     use std::time::Duration;
     use tokio::time::sleep;
 
@@ -3756,6 +5775,9 @@ mod tests {
                 total_tokens: 7,
             },
             model_context_window: Some(1_000),
+            by_provider: Default::default(),
+            prompt_cache: Default::default(),
+            tool_output_tokens: 0,
         };
         let info2 = TokenUsageInfo {
             total_token_usage: TokenUsage {
@@ -3773,6 +5795,9 @@ mod tests {
                 total_tokens: 35,
             },
             model_context_window: Some(2_000),
+            by_provider: Default::default(),
+            prompt_cache: Default::default(),
+            tool_output_tokens: 0,
         };
 
         rollout_items.push(RolloutItem::EventMsg(EventMsg::TokenCount(
@@ -3812,6 +5837,38 @@ mod tests {
         assert_eq!(actual, Some(info2));
     }
 
+    #[tokio::test]
+    async fn record_initial_history_restores_the_latest_plan() {
+        let (session, turn_context) = make_session_and_context().await;
+        let (mut rollout_items, _expected) = sample_rollout(&session, &turn_context).await;
+        let plan = |status| UpdatePlanArgs {
+            explanation: None,
+            plan: vec![PlanItemArg {
+                step: "write tests".to_string(),
+                status,
+            }],
+        };
+        rollout_items.push(RolloutItem::EventMsg(EventMsg::PlanUpdate(plan(
+            StepStatus::InProgress,
+        ))));
+        rollout_items.push(RolloutItem::EventMsg(EventMsg::PlanUpdate(plan(
+            StepStatus::Completed,
+        ))));
+
+        session
+            .record_initial_history(InitialHistory::Resumed(ResumedHistory {
+                conversation_id: ThreadId::default(),
+                history: rollout_items,
+                rollout_path: PathBuf::from("/tmp/resume.jsonl"),
+            }))
+            .await;
+
+        assert_eq!(
+            session.current_plan().await,
+            Some(plan(StepStatus::Completed))
+        );
+    }
+
     #[tokio::test]
     async fn record_initial_history_reconstructs_forked_transcript() {
         let (session, turn_context) = make_session_and_context().await;
@@ -3974,6 +6031,7 @@ mod tests {
         };
         let session_configuration = SessionConfiguration {
             provider: config.model_provider.clone(),
+            provider_id: config.model_provider_id.clone(),
             collaboration_mode,
             model_reasoning_summary: config.model_reasoning_summary,
             developer_instructions: config.developer_instructions.clone(),
@@ -3987,6 +6045,9 @@ mod tests {
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
             cwd: config.cwd.clone(),
+            workspace_roots: config.workspace_roots.clone(),
+            plan_only: config.plan_only,
+            tmpdir: None,
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
             dynamic_tools: Vec::new(),
@@ -4054,6 +6115,7 @@ mod tests {
         };
         let session_configuration = SessionConfiguration {
             provider: config.model_provider.clone(),
+            provider_id: config.model_provider_id.clone(),
             collaboration_mode,
             model_reasoning_summary: config.model_reasoning_summary,
             developer_instructions: config.developer_instructions.clone(),
@@ -4067,6 +6129,9 @@ mod tests {
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
             cwd: config.cwd.clone(),
+            workspace_roots: config.workspace_roots.clone(),
+            plan_only: config.plan_only,
+            tmpdir: None,
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
             dynamic_tools: Vec::new(),
@@ -4150,7 +6215,9 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new("Command output".to_string()),
             duration: StdDuration::from_secs(1),
+            suspended: false,
             timed_out: true,
+            deltas: Default::default(),
         };
         let (_, turn_context) = make_session_and_context().await;
 
@@ -4263,33 +6330,6 @@ mod tests {
         })
     }
 
-    async fn build_test_config(codex_home: &Path) -> Config {
-        ConfigBuilder::default()
-            .codex_home(codex_home.to_path_buf())
-            .build()
-            .await
-            .expect("load default test config")
-    }
-
-    fn otel_manager(
-        conversation_id: ThreadId,
-        config: &Config,
-        model_info: &ModelInfo,
-        session_source: SessionSource,
-    ) -> OtelManager {
-        OtelManager::new(
-            conversation_id,
-            ModelsManager::get_model_offline(config.model.as_deref()).as_str(),
-            model_info.slug.as_str(),
-            None,
-            Some("test@test.com".to_string()),
-            Some(AuthMode::ChatGPT),
-            false,
-            "test".to_string(),
-            session_source,
-        )
-    }
-
     pub(crate) async fn make_session_and_context() -> (Session, TurnContext) {
         let (tx_event, _rx_event) = async_channel::unbounded();
         let codex_home = tempfile::tempdir().expect("create temp dir");
@@ -4318,6 +6358,7 @@ mod tests {
         };
         let session_configuration = SessionConfiguration {
             provider: config.model_provider.clone(),
+            provider_id: config.model_provider_id.clone(),
             collaboration_mode,
             model_reasoning_summary: config.model_reasoning_summary,
             developer_instructions: config.developer_instructions.clone(),
@@ -4331,6 +6372,9 @@ mod tests {
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
             cwd: config.cwd.clone(),
+            workspace_roots: config.workspace_roots.clone(),
+            plan_only: config.plan_only,
+            tmpdir: None,
             original_config_do_not_use: Arc::clone(&config),
             session_source: SessionSource::Exec,
             dynamic_tools: Vec::new(),
@@ -4353,7 +6397,10 @@ mod tests {
         let services = SessionServices {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
+            mcp_sampling: McpSampling::new().0,
+            hooks: HookRunner::new().0,
             unified_exec_manager: UnifiedExecProcessManager::default(),
+            background_jobs: BackgroundJobManager::default(),
             notifier: UserNotifier::new(None),
             rollout: Mutex::new(None),
             user_shell: Arc::new(default_user_shell()),
@@ -4365,6 +6412,13 @@ mod tests {
             tool_approvals: Mutex::new(ApprovalStore::default()),
             skills_manager,
             agent_control,
+            tool_timeouts: RwLock::new(config.tool_timeouts),
+            live_events: std::sync::Mutex::new(None),
+            parsed_commands: ParsedCommandCache::default(),
+            in_process_tools: HashMap::new(),
+            workspace_snapshots: Mutex::new(SnapshotStore::new(config.workspace_snapshots)),
+            exec_outputs: Mutex::new(ExecOutputLog::default()),
+            workspace_watcher: None,
         };
 
         let turn_context = Session::make_turn_context(
@@ -4385,6 +6439,7 @@ mod tests {
             state: Mutex::new(state),
             features: config.features.clone(),
             pending_mcp_server_refresh_config: Mutex::new(None),
+            pending_mcp_prompt_items: Mutex::new(Vec::new()),
             active_turn: Mutex::new(None),
             services,
             next_internal_sub_id: AtomicU64::new(0),
@@ -4393,115 +6448,6 @@ mod tests {
         (session, turn_context)
     }
 
-    // Like make_session_and_context, but returns Arc<Session> and the event receiver
-    // so tests can assert on emitted events.
-    pub(crate) async fn make_session_and_context_with_rx() -> (
-        Arc<Session>,
-        Arc<TurnContext>,
-        async_channel::Receiver<Event>,
-    ) {
-        let (tx_event, rx_event) = async_channel::unbounded();
-        let codex_home = tempfile::tempdir().expect("create temp dir");
-        let config = build_test_config(codex_home.path()).await;
-        let config = Arc::new(config);
-        let conversation_id = ThreadId::default();
-        let auth_manager =
-            AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key"));
-        let models_manager = Arc::new(ModelsManager::new(
-            config.codex_home.clone(),
-            auth_manager.clone(),
-        ));
-        let agent_control = AgentControl::default();
-        let exec_policy = ExecPolicyManager::default();
-        let (agent_status_tx, _agent_status_rx) = watch::channel(AgentStatus::PendingInit);
-        let model = ModelsManager::get_model_offline(config.model.as_deref());
-        let model_info = ModelsManager::construct_model_info_offline(model.as_str(), &config);
-        let reasoning_effort = config.model_reasoning_effort;
-        let collaboration_mode = CollaborationMode {
-            mode: ModeKind::Custom,
-            settings: Settings {
-                model,
-                reasoning_effort,
-                developer_instructions: None,
-            },
-        };
-        let session_configuration = SessionConfiguration {
-            provider: config.model_provider.clone(),
-            collaboration_mode,
-            model_reasoning_summary: config.model_reasoning_summary,
-            developer_instructions: config.developer_instructions.clone(),
-            user_instructions: config.user_instructions.clone(),
-            personality: config.model_personality,
-            base_instructions: config
-                .base_instructions
-                .clone()
-                .unwrap_or_else(|| model_info.get_model_instructions(config.model_personality)),
-            compact_prompt: config.compact_prompt.clone(),
-            approval_policy: config.approval_policy.clone(),
-            sandbox_policy: config.sandbox_policy.clone(),
-            cwd: config.cwd.clone(),
-            original_config_do_not_use: Arc::clone(&config),
-            session_source: SessionSource::Exec,
-            dynamic_tools: Vec::new(),
-        };
-        let per_turn_config = Session::build_per_turn_config(&session_configuration);
-        let model_info = ModelsManager::construct_model_info_offline(
-            session_configuration.collaboration_mode.model(),
-            &per_turn_config,
-        );
-        let otel_manager = otel_manager(
-            conversation_id,
-            config.as_ref(),
-            &model_info,
-            session_configuration.session_source.clone(),
-        );
-
-        let state = SessionState::new(session_configuration.clone());
-        let skills_manager = Arc::new(SkillsManager::new(config.codex_home.clone()));
-
-        let services = SessionServices {
-            mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
-            mcp_startup_cancellation_token: Mutex::new(CancellationToken::new()),
-            unified_exec_manager: UnifiedExecProcessManager::default(),
-            notifier: UserNotifier::new(None),
-            rollout: Mutex::new(None),
-            user_shell: Arc::new(default_user_shell()),
-            show_raw_agent_reasoning: config.show_raw_agent_reasoning,
-            exec_policy,
-            auth_manager: Arc::clone(&auth_manager),
-            otel_manager: otel_manager.clone(),
-            models_manager: Arc::clone(&models_manager),
-            tool_approvals: Mutex::new(ApprovalStore::default()),
-            skills_manager,
-            agent_control,
-        };
-
-        let turn_context = Arc::new(Session::make_turn_context(
-            Some(Arc::clone(&auth_manager)),
-            &otel_manager,
-            session_configuration.provider.clone(),
-            &session_configuration,
-            per_turn_config,
-            model_info,
-            conversation_id,
-            "turn_id".to_string(),
-        ));
-
-        let session = Arc::new(Session {
-            conversation_id,
-            tx_event,
-            agent_status: agent_status_tx,
-            state: Mutex::new(state),
-            features: config.features.clone(),
-            pending_mcp_server_refresh_config: Mutex::new(None),
-            active_turn: Mutex::new(None),
-            services,
-            next_internal_sub_id: AtomicU64::new(0),
-        });
-
-        (session, turn_context, rx_event)
-    }
-
     #[tokio::test]
     async fn refresh_mcp_servers_is_deferred_until_next_turn() {
         let (session, turn_context) = make_session_and_context().await;
@@ -4796,6 +6742,67 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn failed_parallel_calls_answer_their_own_call_ids() {
+        let (session, turn_context, _rx) = make_session_and_context_with_rx().await;
+        let router = ToolRouter::from_config(
+            &turn_context.tools_config,
+            None,
+            turn_context.dynamic_tools.as_slice(),
+        );
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let mut calls = Vec::new();
+        for (call_id, name) in [("call-a", "missing_tool_a"), ("call-b", "missing_tool_b")] {
+            let item = ResponseItem::FunctionCall {
+                id: None,
+                name: name.to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.to_string(),
+            };
+            calls.push(
+                ToolRouter::build_tool_call(session.as_ref(), item)
+                    .await
+                    .expect("build tool call")
+                    .expect("tool call present"),
+            );
+        }
+
+        let responses = futures::future::join_all(calls.into_iter().map(|call| {
+            router.dispatch_tool_call(
+                Arc::clone(&session),
+                Arc::clone(&turn_context),
+                Arc::clone(&tracker),
+                call,
+            )
+        }))
+        .await;
+
+        let outputs: Vec<(String, String)> = responses
+            .into_iter()
+            .map(
+                |response| match response.expect("failure is reported to the model") {
+                    ResponseInputItem::FunctionCallOutput { call_id, output } => {
+                        (call_id, output.content)
+                    }
+                    other => panic!("expected function call output, got {other:?}"),
+                },
+            )
+            .collect();
+        assert_eq!(
+            outputs,
+            vec![
+                (
+                    "call-a".to_string(),
+                    "unsupported call: missing_tool_a".to_string()
+                ),
+                (
+                    "call-b".to_string(),
+                    "unsupported call: missing_tool_b".to_string()
+                ),
+            ]
+        );
+    }
+
     async fn sample_rollout(
         session: &Session,
         turn_context: &TurnContext,
@@ -58,6 +58,7 @@ pub(crate) async fn run_codex_thread_interactive(
         SessionSource::SubAgent(SubAgentSource::Review),
         parent_session.services.agent_control.clone(),
         Vec::new(),
+        Vec::new(),
     )
     .await?;
     let codex = Arc::new(codex);
@@ -314,8 +315,10 @@ async fn handle_exec_approval(
         parent_ctx.sub_id.clone(),
         event.command,
         event.cwd,
+        event.injected_env,
         event.reason,
         event.proposed_execpolicy_amendment,
+        event.sandbox_denial_output,
     );
     let decision = await_approval_with_cancel(
         approval_fut,
@@ -0,0 +1,220 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::approvals::CommandRisk;
+
+use crate::bash::extract_bash_command;
+use crate::bash::parse_shell_lc_plain_commands;
+
+/// Classifies what `command` would touch so an approval prompt can point at
+/// the risky parts instead of only showing the raw command line.
+///
+/// Returns `None` when a shell script cannot be split into plain commands;
+/// guessing at pipelines, substitutions or redirections would show a
+/// classification the command does not deserve.
+pub fn classify_command_risk(command: &[String], cwd: &Path) -> Option<CommandRisk> {
+    let commands = if extract_bash_command(command).is_some() {
+        parse_shell_lc_plain_commands(command)?
+    } else {
+        vec![command.to_vec()]
+    };
+
+    let mut risk = CommandRisk::default();
+    for words in &commands {
+        let words = strip_sudo(words);
+        let Some(program) = words.first().map(|p| program_name(p)) else {
+            continue;
+        };
+        let args = &words[1..];
+        for arg in destructive_args(program, args) {
+            if !risk.destructive_args.contains(&arg) {
+                risk.destructive_args.push(arg);
+            }
+        }
+        risk.network |= uses_network(program, args);
+        for path in named_paths(args, cwd) {
+            if !risk.paths.contains(&path) {
+                risk.paths.push(path);
+            }
+        }
+    }
+    risk.commands = commands;
+    Some(risk)
+}
+
+fn strip_sudo(words: &[String]) -> &[String] {
+    match words.split_first() {
+        Some((first, rest)) if first == "sudo" => strip_sudo(rest),
+        _ => words,
+    }
+}
+
+fn program_name(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+fn destructive_args(program: &str, args: &[String]) -> Vec<String> {
+    let subcommand = args.first().map(String::as_str);
+    let is_destructive: fn(&str) -> bool = match (program, subcommand) {
+        ("rm", _) => |arg| {
+            matches!(arg, "--recursive" | "--force")
+                || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains(['r', 'R', 'f']))
+        },
+        ("git", Some("reset")) => |arg| arg == "--hard",
+        ("git", Some("push")) => |arg| {
+            matches!(
+                arg,
+                "-f" | "--force" | "--force-with-lease" | "--delete" | "-d"
+            ) || arg.starts_with("--force-with-lease=")
+                || arg.starts_with('+')
+        },
+        ("git", Some("clean")) => |arg| arg.starts_with('-') && arg.contains('f'),
+        ("git", Some("branch")) => |arg| matches!(arg, "-D" | "--delete" | "-d"),
+        ("git", Some("checkout" | "restore")) => |arg| matches!(arg, "-f" | "--force" | "."),
+        ("chmod" | "chown" | "chgrp", _) => |arg| matches!(arg, "-R" | "--recursive"),
+        ("find", _) => |arg| arg == "-delete",
+        ("dd", _) => |arg| arg.starts_with("of="),
+        ("mv" | "cp", _) => |arg| matches!(arg, "-f" | "--force"),
+        _ => return Vec::new(),
+    };
+    args.iter()
+        .filter(|arg| is_destructive(arg))
+        .cloned()
+        .collect()
+}
+
+fn uses_network(program: &str, args: &[String]) -> bool {
+    if matches!(
+        program,
+        "curl" | "wget" | "ssh" | "scp" | "sftp" | "rsync" | "nc" | "ping" | "ftp" | "telnet"
+    ) {
+        return true;
+    }
+    let subcommand = args.first().map(String::as_str);
+    let network_subcommand = match program {
+        "git" => matches!(
+            subcommand,
+            Some("clone" | "fetch" | "pull" | "push" | "ls-remote" | "submodule")
+        ),
+        "npm" | "pnpm" | "yarn" | "bun" => matches!(
+            subcommand,
+            Some("install" | "i" | "add" | "ci" | "publish" | "update" | "upgrade")
+        ),
+        "pip" | "pip3" | "uv" | "gem" | "go" => {
+            matches!(
+                subcommand,
+                Some("install" | "get" | "download" | "add" | "sync")
+            )
+        }
+        "cargo" => matches!(
+            subcommand,
+            Some("install" | "publish" | "fetch" | "update" | "search")
+        ),
+        "apt" | "apt-get" | "brew" | "dnf" | "yum" => {
+            matches!(subcommand, Some("install" | "update" | "upgrade"))
+        }
+        _ => false,
+    };
+    network_subcommand || args.iter().any(|arg| is_url(arg))
+}
+
+fn is_url(arg: &str) -> bool {
+    ["http://", "https://", "ssh://", "git://", "ftp://"]
+        .iter()
+        .any(|scheme| arg.starts_with(scheme))
+}
+
+/// Arguments that name a filesystem path. Absolute and explicitly relative
+/// arguments always count; bare words like `origin/main` only when they exist
+/// under `cwd`. Flags and URLs are skipped.
+fn named_paths(args: &[String], cwd: &Path) -> Vec<PathBuf> {
+    args.iter()
+        .filter(|arg| !arg.starts_with('-') && !arg.is_empty() && !is_url(arg))
+        .filter_map(|arg| {
+            let arg = arg.split_once('=').map_or(arg.as_str(), |(_, value)| value);
+            if arg.starts_with('~') {
+                return Some(PathBuf::from(arg));
+            }
+            let explicit = arg.starts_with('/')
+                || arg.starts_with("./")
+                || arg.starts_with("../")
+                || matches!(arg, "." | "..");
+            // Collecting the components drops `.` segments, so `./build`
+            // reads as `<cwd>/build`.
+            let path: PathBuf = cwd.join(arg).components().collect();
+            (explicit || path.exists()).then_some(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(items: &[&str]) -> Vec<String> {
+        items.iter().map(std::string::ToString::to_string).collect()
+    }
+
+    #[test]
+    fn rm_rf_is_destructive_and_names_its_paths() {
+        let cwd = Path::new("/repo");
+        let risk = classify_command_risk(&vec_str(&["rm", "-rf", "./build", "/tmp/x"]), cwd)
+            .expect("plain command is classified");
+        assert_eq!(
+            risk,
+            CommandRisk {
+                commands: vec![vec_str(&["rm", "-rf", "./build", "/tmp/x"])],
+                destructive_args: vec_str(&["-rf"]),
+                paths: vec![PathBuf::from("/repo/build"), PathBuf::from("/tmp/x")],
+                network: false,
+            }
+        );
+    }
+
+    #[test]
+    fn shell_scripts_are_split_into_plain_commands() {
+        let risk = classify_command_risk(
+            &vec_str(&[
+                "bash",
+                "-lc",
+                "git fetch origin && sudo git reset --hard origin/main",
+            ]),
+            Path::new("/repo"),
+        )
+        .expect("word-only script is classified");
+        assert_eq!(
+            risk.commands,
+            vec![
+                vec_str(&["git", "fetch", "origin"]),
+                vec_str(&["sudo", "git", "reset", "--hard", "origin/main"]),
+            ]
+        );
+        assert_eq!(risk.destructive_args, vec_str(&["--hard"]));
+        assert!(risk.paths.is_empty());
+        assert!(risk.network);
+    }
+
+    #[test]
+    fn curl_urls_are_network_not_paths() {
+        let risk = classify_command_risk(
+            &vec_str(&["curl", "-fsSL", "https://example.com/install.sh"]),
+            Path::new("/repo"),
+        )
+        .expect("plain command is classified");
+        assert!(risk.network);
+        assert!(risk.paths.is_empty());
+        assert!(!risk.is_destructive());
+    }
+
+    #[test]
+    fn complex_scripts_are_not_classified() {
+        assert_eq!(
+            classify_command_risk(
+                &vec_str(&["bash", "-lc", "rm -rf $(cat targets.txt)"]),
+                Path::new("/repo"),
+            ),
+            None
+        );
+    }
+}
@@ -6,6 +6,7 @@ use crate::client_common::ResponseEvent;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::codex::get_last_assistant_message_from_turn;
+use crate::context_manager::ContextManager;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::features::Feature;
@@ -16,6 +17,7 @@ use crate::protocol::TurnContextItem;
 use crate::protocol::TurnStartedEvent;
 use crate::protocol::WarningEvent;
 use crate::session_prefix::TURN_ABORTED_OPEN_TAG;
+use crate::tool_digest;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
 use crate::truncate::truncate_text;
@@ -61,6 +63,7 @@ pub(crate) async fn run_compact_task(
 ) {
     let start_event = EventMsg::TurnStarted(TurnStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        tools: None,
     });
     sess.send_event(&turn_context, start_event).await;
     run_compact_task_inner(sess.clone(), turn_context, input).await;
@@ -74,6 +77,7 @@ async fn run_compact_task_inner(
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
 
     let mut history = sess.clone_history().await;
+    let history_before = history.clone();
     history.record_items(
         &[initial_input_for_turn.into()],
         turn_context.truncation_policy,
@@ -183,17 +187,29 @@ async fn run_compact_task_inner(
         .filter(|item| matches!(item, ResponseItem::GhostSnapshot { .. }))
         .cloned()
         .collect();
+    let (digest, turns_digested) = take_tool_digest(&sess, history_items).await;
+    let has_digest = digest.is_some();
+    new_history.extend(digest);
     new_history.extend(ghost_snapshots);
+    // Resuming would otherwise rebuild the history from the summary alone and
+    // lose the digest.
+    let replacement_history = has_digest.then(|| new_history.clone());
     sess.replace_history(new_history).await;
     sess.recompute_token_usage(&turn_context).await;
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: summary_text.clone(),
-        replacement_history: None,
+        replacement_history,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
-    let event = EventMsg::ContextCompacted(ContextCompactedEvent {});
+    let event = context_compacted_event(
+        &sess,
+        turn_context.as_ref(),
+        &history_before,
+        turns_digested,
+    )
+    .await;
     sess.send_event(&turn_context, event).await;
 
     let warning = EventMsg::Warning(WarningEvent {
@@ -202,6 +218,46 @@ async fn run_compact_task_inner(
     sess.send_event(&turn_context, warning).await;
 }
 
+/// The `<tool_digest>` message for the turns being compacted, carrying over
+/// the digests an earlier compaction left in `history`, and how many turns
+/// were digested this time.
+pub(crate) async fn take_tool_digest(
+    sess: &Session,
+    history: &[ResponseItem],
+) -> (Option<ResponseItem>, u32) {
+    let digests = sess.take_tool_digests().await;
+    let turns_digested = u32::try_from(digests.len()).unwrap_or(u32::MAX);
+    let item = tool_digest::digest_item(tool_digest::digests_in_history(history), digests);
+    (item, turns_digested)
+}
+
+/// The `ContextCompacted` event for replacing `before` with the session's
+/// current history.
+pub(crate) async fn context_compacted_event(
+    sess: &Session,
+    turn_context: &TurnContext,
+    before: &ContextManager,
+    turns_digested: u32,
+) -> EventMsg {
+    let items_compacted = before
+        .raw_items()
+        .iter()
+        .filter(|item| !matches!(item, ResponseItem::GhostSnapshot { .. }))
+        .count();
+    let tokens_before = before.estimate_token_count(turn_context).unwrap_or(0);
+    let tokens_after = sess
+        .clone_history()
+        .await
+        .estimate_token_count(turn_context)
+        .unwrap_or(0);
+    EventMsg::ContextCompacted(ContextCompactedEvent {
+        items_compacted: u32::try_from(items_compacted).unwrap_or(u32::MAX),
+        turns_digested,
+        tokens_before,
+        tokens_reclaimed: tokens_before - tokens_after,
+    })
+}
+
 pub fn content_items_to_text(content: &[ContentItem]) -> Option<String> {
     let mut pieces = Vec::new();
     for item in content {
@@ -3,9 +3,10 @@ use std::sync::Arc;
 use crate::Prompt;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::compact::context_compacted_event;
+use crate::compact::take_tool_digest;
 use crate::error::Result as CodexResult;
 use crate::protocol::CompactedItem;
-use crate::protocol::ContextCompactedEvent;
 use crate::protocol::EventMsg;
 use crate::protocol::RolloutItem;
 use crate::protocol::TurnStartedEvent;
@@ -21,6 +22,7 @@ pub(crate) async fn run_inline_remote_auto_compact_task(
 pub(crate) async fn run_remote_compact_task(sess: Arc<Session>, turn_context: Arc<TurnContext>) {
     let start_event = EventMsg::TurnStarted(TurnStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
+        tools: None,
     });
     sess.send_event(&turn_context, start_event).await;
 
@@ -51,12 +53,13 @@ async fn run_remote_compact_task_inner_impl(
         .collect();
 
     let prompt = Prompt {
-        input: history.for_prompt(),
+        input: history.clone().for_prompt(),
         tools: vec![],
         parallel_tool_calls: false,
         base_instructions: sess.get_base_instructions().await,
         personality: turn_context.personality,
         output_schema: None,
+        max_output_tokens: None,
     };
 
     let mut new_history = turn_context
@@ -64,6 +67,8 @@ async fn run_remote_compact_task_inner_impl(
         .compact_conversation_history(&prompt)
         .await?;
 
+    let (digest, turns_digested) = take_tool_digest(sess, history.raw_items()).await;
+    new_history.extend(digest);
     if !ghost_snapshots.is_empty() {
         new_history.extend(ghost_snapshots);
     }
@@ -77,7 +82,7 @@ async fn run_remote_compact_task_inner_impl(
     sess.persist_rollout_items(&[RolloutItem::Compacted(compacted_item)])
         .await;
 
-    let event = EventMsg::ContextCompacted(ContextCompactedEvent {});
+    let event = context_compacted_event(sess, turn_context, &history, turns_digested).await;
     sess.send_event(turn_context, event).await;
 
     Ok(())
@@ -57,8 +57,13 @@ pub enum ConfigEdit {
 
 // TODO(jif) move to a dedicated file
 mod document_helpers {
+    use crate::config::types::McpEnvSource;
+    use crate::config::types::McpSamplingConfig;
+    use crate::config::types::McpSamplingPolicy;
     use crate::config::types::McpServerConfig;
+    use crate::config::types::McpServerTlsConfig;
     use crate::config::types::McpServerTransportConfig;
+    use std::collections::HashMap;
     use toml_edit::Array as TomlArray;
     use toml_edit::InlineTable;
     use toml_edit::Item as TomlItem;
@@ -107,6 +112,7 @@ mod document_helpers {
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => {
                 entry["command"] = value(command.clone());
@@ -121,6 +127,11 @@ mod document_helpers {
                 if !env_vars.is_empty() {
                     entry["env_vars"] = array_from_iter(env_vars.iter().cloned());
                 }
+                if let Some(env_sources) = env_sources
+                    && !env_sources.is_empty()
+                {
+                    entry["env_sources"] = env_sources_table(env_sources);
+                }
                 if let Some(cwd) = cwd {
                     entry["cwd"] = value(cwd.to_string_lossy().to_string());
                 }
@@ -130,6 +141,8 @@ mod document_helpers {
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                header_sources,
+                tls,
             } => {
                 entry["url"] = value(url.clone());
                 if let Some(env_var) = bearer_token_env_var {
@@ -145,6 +158,14 @@ mod document_helpers {
                 {
                     entry["env_http_headers"] = table_from_pairs(headers.iter());
                 }
+                if let Some(header_sources) = header_sources
+                    && !header_sources.is_empty()
+                {
+                    entry["header_sources"] = env_sources_table(header_sources);
+                }
+                if let Some(tls) = tls {
+                    entry["tls"] = tls_table(tls);
+                }
             }
         }
 
@@ -172,6 +193,12 @@ mod document_helpers {
         {
             entry["scopes"] = array_from_iter(scopes.iter().cloned());
         }
+        if let Some(attempts) = config.max_restart_attempts {
+            entry["max_restart_attempts"] = value(i64::from(attempts));
+        }
+        if let Some(sampling) = &config.sampling {
+            entry["sampling"] = sampling_table(sampling);
+        }
 
         entry
     }
@@ -226,6 +253,60 @@ mod document_helpers {
         TomlItem::Value(array.into())
     }
 
+    fn env_sources_table(env_sources: &HashMap<String, McpEnvSource>) -> TomlItem {
+        let mut entries: Vec<_> = env_sources.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut table = TomlTable::new();
+        table.set_implicit(false);
+        for (key, source) in entries {
+            let mut inline = InlineTable::new();
+            match source {
+                McpEnvSource::Env(var) => inline.insert("env", var.as_str().into()),
+                McpEnvSource::Keychain(account) => {
+                    inline.insert("keychain", account.as_str().into())
+                }
+            };
+            table.insert(key, value(inline));
+        }
+        TomlItem::Table(table)
+    }
+
+    fn sampling_table(sampling: &McpSamplingConfig) -> TomlItem {
+        let mut table = TomlTable::new();
+        table.set_implicit(false);
+        let policy = match sampling.policy {
+            McpSamplingPolicy::Deny => "deny",
+            McpSamplingPolicy::Auto => "auto",
+            McpSamplingPolicy::Ask => "ask",
+        };
+        table.insert("policy", value(policy));
+        if let Some(model) = &sampling.model {
+            table.insert("model", value(model.clone()));
+        }
+        if let Some(budget) = sampling.token_budget {
+            table.insert(
+                "token_budget",
+                value(i64::try_from(budget).unwrap_or(i64::MAX)),
+            );
+        }
+        TomlItem::Table(table)
+    }
+
+    fn tls_table(tls: &McpServerTlsConfig) -> TomlItem {
+        let mut table = TomlTable::new();
+        table.set_implicit(false);
+        for (key, path) in [
+            ("ca_certificate", &tls.ca_certificate),
+            ("client_certificate", &tls.client_certificate),
+            ("client_key", &tls.client_key),
+        ] {
+            if let Some(path) = path {
+                table.insert(key, value(path.to_string_lossy().to_string()));
+            }
+        }
+        TomlItem::Table(table)
+    }
+
     fn table_from_pairs<'a, I>(pairs: I) -> TomlItem
     where
         I: IntoIterator<Item = (&'a String, &'a String)>,
@@ -824,6 +905,7 @@ impl ConfigEditsBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::McpEnvSource;
     use crate::config::types::McpServerTransportConfig;
     use codex_protocol::openai_models::ReasoningEffort;
     use pretty_assertions::assert_eq;
@@ -1370,6 +1452,14 @@ gpt-5 = "gpt-5.1"
                         .collect(),
                     ),
                     env_vars: vec!["FOO".to_string()],
+                    env_sources: Some(
+                        [(
+                            "TOKEN".to_string(),
+                            McpEnvSource::Keychain("stdio-token".to_string()),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
                     cwd: None,
                 },
                 enabled: true,
@@ -1379,6 +1469,8 @@ gpt-5 = "gpt-5.1"
                 enabled_tools: Some(vec!["one".to_string(), "two".to_string()]),
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1394,6 +1486,8 @@ gpt-5 = "gpt-5.1"
                             .collect(),
                     ),
                     env_http_headers: None,
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: false,
                 disabled_reason: None,
@@ -1402,6 +1496,8 @@ gpt-5 = "gpt-5.1"
                 enabled_tools: None,
                 disabled_tools: Some(vec!["forbidden".to_string()]),
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1433,6 +1529,9 @@ enabled_tools = [\"one\", \"two\"]
 [mcp_servers.stdio.env]
 A = \"1\"
 B = \"2\"
+
+[mcp_servers.stdio.env_sources]
+TOKEN = { keychain = \"stdio-token\" }
 ";
         assert_eq!(raw, expected);
     }
@@ -1459,6 +1558,7 @@ foo = { command = "cmd" }
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -1468,6 +1568,8 @@ foo = { command = "cmd" }
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1504,6 +1606,7 @@ foo = { command = "cmd" } # keep me
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: false,
@@ -1513,6 +1616,8 @@ foo = { command = "cmd" } # keep me
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1548,6 +1653,7 @@ foo = { command = "cmd", args = ["--flag"] } # keep me
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -1557,6 +1663,8 @@ foo = { command = "cmd", args = ["--flag"] } # keep me
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1593,6 +1701,7 @@ foo = { command = "cmd" }
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: false,
@@ -1602,6 +1711,8 @@ foo = { command = "cmd" }
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -1,22 +1,46 @@
 use crate::auth::AuthCredentialsStoreMode;
 use crate::config::edit::ConfigEdit;
 use crate::config::edit::ConfigEditsBuilder;
+use crate::config::types::CtrlCBehavior;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
+use crate::config::types::FailoverRetry;
+use crate::config::types::FailoverRetryToml;
+use crate::config::types::FailoverTarget;
 use crate::config::types::History;
+use crate::config::types::HooksConfig;
+use crate::config::types::HooksToml;
+use crate::config::types::LoopDetection;
+use crate::config::types::LoopDetectionToml;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerDisabledReason;
 use crate::config::types::McpServerTransportConfig;
+use crate::config::types::ModelToolsToml;
 use crate::config::types::Notice;
 use crate::config::types::Notifications;
 use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
+use crate::config::types::OutputLimits;
+use crate::config::types::OutputLimitsToml;
+use crate::config::types::ProviderFailover;
+use crate::config::types::ProviderFailoverToml;
 use crate::config::types::SandboxWorkspaceWrite;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
+use crate::config::types::ShellKind;
+use crate::config::types::ShellToml;
 use crate::config::types::SkillsConfig;
+use crate::config::types::TokenPrice;
+use crate::config::types::ToolBudget;
+use crate::config::types::ToolBudgetToml;
+use crate::config::types::ToolTimeouts;
+use crate::config::types::ToolTimeoutsToml;
 use crate::config::types::Tui;
+use crate::config::types::TurnProgress;
+use crate::config::types::TurnProgressToml;
 use crate::config::types::UriBasedFileOpener;
+use crate::config::types::WorkspaceSnapshots;
+use crate::config::types::WorkspaceSnapshotsToml;
 use crate::config_loader::ConfigLayerStack;
 use crate::config_loader::ConfigRequirements;
 use crate::config_loader::LoaderOverrides;
@@ -29,6 +53,7 @@ use crate::features::FeatureOverrides;
 use crate::features::Features;
 use crate::features::FeaturesToml;
 use crate::git_info::resolve_root_git_project_for_trust;
+use crate::headless_approvals::HeadlessApprovals;
 use crate::model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::OLLAMA_CHAT_PROVIDER_ID;
@@ -38,6 +63,7 @@ use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::WorkspaceRoot;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
 use codex_protocol::config_types::AltScreenMode;
@@ -63,16 +89,19 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 #[cfg(test)]
 use tempfile::tempdir;
 
 use crate::config::profile::ConfigProfile;
+use crate::config::profile::resolve_profile;
 use toml::Value as TomlValue;
 use toml_edit::DocumentMut;
 
 mod constraint;
 pub mod edit;
 pub mod profile;
+pub(crate) mod reload;
 pub mod schema;
 pub mod service;
 pub mod types;
@@ -93,6 +122,10 @@ pub(crate) const DEFAULT_AGENT_MAX_THREADS: Option<usize> = Some(6);
 
 pub const CONFIG_TOML_FILE: &str = "config.toml";
 
+/// Environment variable that redirects session rollouts, approved command
+/// rules, and caches away from `CODEX_HOME`.
+pub const CODEX_STATE_DIR_ENV_VAR: &str = "CODEX_STATE_DIR";
+
 #[cfg(test)]
 pub(crate) fn test_config() -> Config {
     let codex_home = tempdir().expect("create temp dir");
@@ -145,6 +178,11 @@ pub struct Config {
     /// was coerced to read-only because native auto mode is unsupported.
     pub forced_auto_mode_downgraded_on_windows: bool,
 
+    /// When `true`, tool calls that would run a command, change a file, or
+    /// reach an MCP server or client tool are reported as proposals and not
+    /// run. See `Op::SetPlanOnly`.
+    pub plan_only: bool,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
     /// When `true`, `AgentReasoning` events emitted by the backend will be
@@ -211,11 +249,19 @@ pub struct Config {
     /// - `never`: Never use alternate screen (inline mode, preserves scrollback).
     pub tui_alternate_screen: AltScreenMode,
 
+    /// What Ctrl-C does in the TUI while the agent is working
+    /// (`tui.ctrl_c` in `config.toml`).
+    pub tui_ctrl_c: CtrlCBehavior,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
     pub cwd: PathBuf,
 
+    /// Named directories the session works in besides `cwd`, sorted by name.
+    /// They are part of the sandbox's writable roots.
+    pub workspace_roots: Vec<WorkspaceRoot>,
+
     /// Preferred store for CLI auth credentials.
     /// file (default): Use a file in the Codex home directory.
     /// keyring: Use an OS-specific keyring service.
@@ -251,6 +297,41 @@ pub struct Config {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Default timeouts per tool type and the ceiling per-call overrides are
+    /// clamped to. Can be re-read mid-session via `Op::ReloadConfig`.
+    pub tool_timeouts: ToolTimeouts,
+
+    /// Shell that wraps `shell_command` and `exec_command` scripts. `None`
+    /// uses the user's login shell.
+    pub shell: Option<ShellToml>,
+
+    /// Per-model tool availability from `[[model_tools]]`, in file order.
+    pub model_tools: Vec<ModelToolsToml>,
+
+    /// Commands run on session start, after applied patches, and on turn
+    /// completion.
+    pub hooks: HooksConfig,
+
+    /// Byte budgets for command output sent to the model, carried on events,
+    /// written to rollouts, and spilled to disk.
+    pub output_limits: OutputLimits,
+
+    /// When to tell the model that a command keeps failing the same way.
+    pub loop_detection: LoopDetection,
+
+    /// How often to report progress while a turn runs tools.
+    pub turn_progress: TurnProgress,
+
+    /// Limits and retention of workspace snapshots.
+    pub workspace_snapshots: WorkspaceSnapshots,
+
+    /// Limits on the tool calls of a turn and of the session.
+    pub tool_budget: ToolBudget,
+
+    /// Fallback providers and per-error retry policies; `None` keeps the
+    /// provider's own stream retries and never switches providers.
+    pub provider_failover: Option<ProviderFailover>,
+
     /// Maximum number of agent threads that can be open concurrently.
     pub agent_max_threads: Option<usize>,
 
@@ -258,12 +339,31 @@ pub struct Config {
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
 
+    /// Directory where session rollouts, approved command rules, and caches
+    /// are written. Defaults to [`Config::codex_home`] but can be redirected
+    /// to a project-local folder via `state_dir` in config.toml or the
+    /// `CODEX_STATE_DIR` environment variable.
+    pub state_dir: PathBuf,
+
+    /// How long after its session last wrote to it or to its rollout a temp
+    /// directory left behind by a crashed session is kept before a new
+    /// session removes it.
+    pub tmpdir_max_age: Duration,
+
+    /// Token prices by model slug, used to show approximate costs in turn
+    /// summaries.
+    pub token_prices: BTreeMap<String, TokenPrice>,
+
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
     /// When true, session is not persisted on disk. Default to `false`
     pub ephemeral: bool,
 
+    /// Answers approval requests without asking anyone. Set by `codex exec
+    /// --approvals`.
+    pub headless_approvals: Option<HeadlessApprovals>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -416,14 +516,12 @@ impl ConfigBuilder {
         let config_toml: ConfigToml = match merged_toml.try_into() {
             Ok(config_toml) => config_toml,
             Err(err) => {
-                if let Some(config_error) =
-                    crate::config_loader::first_layer_config_error(&config_layer_stack).await
-                {
-                    return Err(crate::config_loader::io_error_from_config_error(
-                        std::io::ErrorKind::InvalidData,
-                        config_error,
-                        Some(err),
-                    ));
+                if let Some(config_err) = crate::config_loader::io_error_from_config_errors(
+                    std::io::ErrorKind::InvalidData,
+                    crate::config_loader::layer_config_errors(&config_layer_stack).await,
+                    Some(err.clone()),
+                ) {
+                    return Err(config_err);
                 }
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
             }
@@ -779,6 +877,9 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Propose commands and patches instead of running them.
+    pub plan_only: Option<bool>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -836,6 +937,12 @@ pub struct ConfigToml {
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Named directories the session works in besides its working directory,
+    /// such as a backend repository next to a frontend one. Each is writable
+    /// under the workspace-write sandbox.
+    #[serde(default)]
+    pub workspace_roots: BTreeMap<String, AbsolutePathBuf>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
@@ -845,6 +952,46 @@ pub struct ConfigToml {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Default timeouts per tool type.
+    #[serde(default)]
+    pub tool_timeouts: Option<ToolTimeoutsToml>,
+
+    /// Shell that wraps `shell_command` and `exec_command` scripts.
+    #[serde(default)]
+    pub shell: Option<ShellToml>,
+
+    /// Tools to enable or disable per model.
+    #[serde(default)]
+    pub model_tools: Option<Vec<ModelToolsToml>>,
+
+    /// Commands to run at points in a session's lifecycle.
+    #[serde(default)]
+    pub hooks: Option<HooksToml>,
+
+    /// Byte budgets for command output.
+    #[serde(default)]
+    pub output_limits: Option<OutputLimitsToml>,
+
+    /// Flagging commands that keep failing the same way.
+    #[serde(default)]
+    pub loop_detection: Option<LoopDetectionToml>,
+
+    /// Progress events while a turn runs tools.
+    #[serde(default)]
+    pub turn_progress: Option<TurnProgressToml>,
+
+    /// Limits and retention of workspace snapshots.
+    #[serde(default)]
+    pub workspace_snapshots: Option<WorkspaceSnapshotsToml>,
+
+    /// Limits on the tool calls of a turn and of the session.
+    #[serde(default)]
+    pub tool_budget: Option<ToolBudgetToml>,
+
+    /// Fallback providers to switch to when the current one keeps failing.
+    #[serde(default)]
+    pub provider_failover: Option<ProviderFailoverToml>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -915,6 +1062,23 @@ pub struct ConfigToml {
     #[serde(default)]
     pub project_root_markers: Option<Vec<String>>,
 
+    /// Directory where session rollouts, approved command rules, and caches
+    /// are written instead of `~/.codex`. Relative paths in a project
+    /// `.codex/config.toml` resolve against that `.codex` folder, which lets
+    /// a repository keep its sessions alongside the checkout.
+    pub state_dir: Option<AbsolutePathBuf>,
+
+    /// Hours after the last write to it or to its session's rollout that a
+    /// new session removes the temp directory a crashed session left behind.
+    /// Defaults to 24.
+    pub tmpdir_max_age_hours: Option<u64>,
+
+    /// Prices per 1,000 tokens by model slug, e.g.
+    /// `[token_prices."gpt-5.1"]`. Turn summaries show an approximate cost
+    /// for models listed here.
+    #[serde(default)]
+    pub token_prices: BTreeMap<String, TokenPrice>,
+
     /// When `true`, checks for Codex updates on startup and surfaces update prompts.
     /// Set to `false` only if your Codex updates are centrally managed.
     /// Defaults to `true`.
@@ -983,6 +1147,11 @@ impl From<ConfigToml> for UserSavedConfig {
 #[schemars(deny_unknown_fields)]
 pub struct ProjectConfig {
     pub trust_level: Option<TrustLevel>,
+    /// One-time confirmation that this project's `.codex/config.toml` may
+    /// relax `sandbox_mode`, `approval_policy`, or workspace-write network
+    /// access relative to the user config. Without it, such a project layer
+    /// is loaded but disabled.
+    pub allow_policy_overrides: Option<bool>,
 }
 
 impl ProjectConfig {
@@ -1128,16 +1297,7 @@ impl ConfigToml {
         let profile = override_profile.or_else(|| self.profile.clone());
 
         match profile {
-            Some(key) => {
-                if let Some(profile) = self.profiles.get(key.as_str()) {
-                    return Ok(profile.clone());
-                }
-
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("config profile `{key}` not found"),
-                ))
-            }
+            Some(key) => resolve_profile(&self.profiles, &key),
             None => Ok(ConfigProfile::default()),
         }
     }
@@ -1164,6 +1324,10 @@ pub struct ConfigOverrides {
     pub ephemeral: Option<bool>,
     /// Additional directories that should be treated as writable roots for this session.
     pub additional_writable_roots: Vec<PathBuf>,
+    /// Workspace roots added to, or replacing by name, those in config.toml.
+    /// Relative paths resolve against `cwd`.
+    pub workspace_roots: BTreeMap<String, PathBuf>,
+    pub headless_approvals: Option<HeadlessApprovals>,
 }
 
 /// Resolves the OSS provider from CLI override, profile config, or global config.
@@ -1252,6 +1416,8 @@ impl Config {
             tools_web_search_request: override_tools_web_search_request,
             ephemeral,
             additional_writable_roots,
+            workspace_roots: workspace_root_overrides,
+            headless_approvals,
         } = overrides;
 
         let active_profile_name = config_profile_key
@@ -1259,16 +1425,7 @@ impl Config {
             .or(cfg.profile.as_ref())
             .cloned();
         let config_profile = match active_profile_name.as_ref() {
-            Some(key) => cfg
-                .profiles
-                .get(key)
-                .ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        format!("config profile `{key}` not found"),
-                    )
-                })?
-                .clone(),
+            Some(key) => resolve_profile(&cfg.profiles, key)?,
             None => ConfigProfile::default(),
         };
 
@@ -1313,7 +1470,10 @@ impl Config {
             .collect::<Result<Vec<_>, _>>()?;
         let active_project = cfg
             .get_active_project(&resolved_cwd)
-            .unwrap_or(ProjectConfig { trust_level: None });
+            .unwrap_or(ProjectConfig {
+                trust_level: None,
+                allow_policy_overrides: None,
+            });
 
         let SandboxPolicyResolution {
             policy: mut sandbox_policy,
@@ -1326,6 +1486,21 @@ impl Config {
                 }
             }
         }
+        let mut workspace_roots = cfg.workspace_roots.clone();
+        for (name, path) in workspace_root_overrides {
+            let path = AbsolutePathBuf::resolve_path_against_base(path, &resolved_cwd)?;
+            workspace_roots.insert(name, path);
+        }
+        let workspace_roots = workspace_roots
+            .into_iter()
+            .map(|(name, path)| {
+                crate::workspace_roots::validate_name(&name).map_err(|message| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+                })?;
+                Ok(WorkspaceRoot { name, path })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        crate::workspace_roots::extend_writable_roots(&mut sandbox_policy, &workspace_roots);
         let approval_policy = approval_policy_override
             .or(config_profile.approval_policy)
             .or(cfg.approval_policy)
@@ -1378,6 +1553,13 @@ impl Config {
 
         let history = cfg.history.unwrap_or_default();
 
+        let state_dir = resolve_state_dir(
+            std::env::var_os(CODEX_STATE_DIR_ENV_VAR).map(PathBuf::from),
+            cfg.state_dir.as_ref(),
+            &codex_home,
+            &resolved_cwd,
+        );
+
         let agent_max_threads = cfg
             .agents
             .as_ref()
@@ -1390,6 +1572,24 @@ impl Config {
             ));
         }
 
+        let tool_timeouts = resolve_tool_timeouts(cfg.tool_timeouts.as_ref())?;
+        if let Some(shell) = cfg.shell.as_ref() {
+            validate_shell_config(shell)?;
+        }
+        let hooks = resolve_hooks(cfg.hooks.as_ref())?;
+        let output_limits = resolve_output_limits(cfg.output_limits.as_ref())?;
+        let loop_detection = resolve_loop_detection(cfg.loop_detection.as_ref())?;
+        let turn_progress = resolve_turn_progress(cfg.turn_progress.as_ref())?;
+        let workspace_snapshots = resolve_workspace_snapshots(cfg.workspace_snapshots.as_ref())?;
+        let tool_budget = resolve_tool_budget(
+            config_profile
+                .tool_budget
+                .as_ref()
+                .or(cfg.tool_budget.as_ref()),
+        )?;
+        let provider_failover =
+            resolve_provider_failover(cfg.provider_failover.as_ref(), &model_providers)?;
+
         let ghost_snapshot = {
             let mut config = GhostSnapshotConfig::default();
             if let Some(ghost_snapshot) = cfg.ghost_snapshot.as_ref()
@@ -1496,10 +1696,12 @@ impl Config {
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
+            workspace_roots,
             approval_policy: constrained_approval_policy,
             sandbox_policy: constrained_sandbox_policy,
             did_user_set_custom_approval_policy_or_sandbox_mode,
             forced_auto_mode_downgraded_on_windows,
+            plan_only: cfg.plan_only.unwrap_or(false),
             shell_environment_policy,
             notify: cfg.notify,
             user_instructions,
@@ -1531,11 +1733,28 @@ impl Config {
                 })
                 .collect(),
             tool_output_token_limit: cfg.tool_output_token_limit,
+            tool_timeouts,
+            shell: cfg.shell,
+            model_tools: cfg.model_tools.unwrap_or_default(),
+            hooks,
+            output_limits,
+            loop_detection,
+            turn_progress,
+            workspace_snapshots,
+            tool_budget,
+            provider_failover,
             agent_max_threads,
+            state_dir,
+            tmpdir_max_age: cfg
+                .tmpdir_max_age_hours
+                .map(|hours| Duration::from_secs(hours.saturating_mul(60 * 60)))
+                .unwrap_or(crate::session_tmpdir::DEFAULT_MAX_AGE),
+            token_prices: cfg.token_prices.clone(),
             codex_home,
             config_layer_stack,
             history,
             ephemeral: ephemeral.unwrap_or_default(),
+            headless_approvals,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
 
@@ -1593,6 +1812,7 @@ impl Config {
                 .as_ref()
                 .map(|t| t.alternate_screen)
                 .unwrap_or_default(),
+            tui_ctrl_c: cfg.tui.as_ref().map(|t| t.ctrl_c).unwrap_or_default(),
             otel: {
                 let t: OtelConfigToml = cfg.otel.unwrap_or_default();
                 let log_user_prompt = t.log_user_prompt.unwrap_or(false);
@@ -1728,6 +1948,353 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Resolves the directory used for sessions, approved command rules, and
+/// caches. `CODEX_STATE_DIR` wins over the `state_dir` config key, and both
+/// fall back to `codex_home`. A relative environment value is resolved
+/// against `cwd`.
+fn resolve_state_dir(
+    env_override: Option<PathBuf>,
+    configured: Option<&AbsolutePathBuf>,
+    codex_home: &Path,
+    cwd: &Path,
+) -> PathBuf {
+    if let Some(path) = env_override.filter(|path| !path.as_os_str().is_empty()) {
+        return if path.is_absolute() {
+            path
+        } else {
+            cwd.join(path)
+        };
+    }
+    configured
+        .map(AbsolutePathBuf::to_path_buf)
+        .unwrap_or_else(|| codex_home.to_path_buf())
+}
+
+/// Checks that `[shell]` only sets `argv` for a custom shell, and that a
+/// custom shell has a non-empty `argv` with at most one `{command}`.
+fn validate_shell_config(shell: &ShellToml) -> std::io::Result<()> {
+    let invalid = |message: &str| {
+        Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            message.to_string(),
+        ))
+    };
+    match (shell.kind, shell.argv.as_deref()) {
+        (Some(ShellKind::Custom), None | Some([])) => {
+            invalid("shell.argv must be set to a non-empty list when shell.kind = \"custom\"")
+        }
+        (Some(ShellKind::Custom), Some(argv)) => {
+            if argv.iter().filter(|arg| *arg == "{command}").count() > 1 {
+                return invalid("shell.argv may contain at most one \"{command}\" argument");
+            }
+            if shell.path.is_some() {
+                return invalid(
+                    "shell.path is not used when shell.kind = \"custom\"; put the binary in shell.argv",
+                );
+            }
+            Ok(())
+        }
+        (_, Some(_)) => invalid("shell.argv is only used when shell.kind = \"custom\""),
+        (_, None) => Ok(()),
+    }
+}
+
+/// Validates `[tool_timeouts]` and fills in defaults. Every configured value
+/// must be at least one millisecond, and no default may exceed `max_sec`.
+pub(crate) fn resolve_tool_timeouts(
+    toml: Option<&ToolTimeoutsToml>,
+) -> std::io::Result<ToolTimeouts> {
+    fn seconds(key: &str, value: Option<f64>) -> std::io::Result<Option<Duration>> {
+        let Some(secs) = value else {
+            return Ok(None);
+        };
+        let message = if !secs.is_finite() || secs < 0.0 {
+            format!("tool_timeouts.{key} must be a positive number of seconds, got {secs}")
+        } else if secs == 0.0 {
+            format!("tool_timeouts.{key} must be greater than zero")
+        } else if secs < 0.001 {
+            format!("tool_timeouts.{key} is {secs}s; the minimum is 0.001 (one millisecond)")
+        } else {
+            return Duration::try_from_secs_f64(secs).map(Some).map_err(|err| {
+                std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("tool_timeouts.{key} is out of range: {err}"),
+                )
+            });
+        };
+        Err(std::io::Error::new(ErrorKind::InvalidInput, message))
+    }
+
+    let defaults = ToolTimeouts::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+
+    let max = seconds("max_sec", toml.max_sec)?;
+    let resolve = |key: &str, value: Option<f64>, default: Duration| {
+        let timeout = seconds(key, value)?;
+        if let (Some(timeout), Some(max)) = (timeout, max)
+            && timeout > max
+        {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "tool_timeouts.{key} ({}s) exceeds tool_timeouts.max_sec ({}s)",
+                    timeout.as_secs_f64(),
+                    max.as_secs_f64()
+                ),
+            ));
+        }
+        Ok(timeout.unwrap_or(default))
+    };
+
+    Ok(ToolTimeouts {
+        shell: resolve("shell_sec", toml.shell_sec, defaults.shell)?,
+        unified_exec_write_wait: resolve(
+            "unified_exec_write_wait_sec",
+            toml.unified_exec_write_wait_sec,
+            defaults.unified_exec_write_wait,
+        )?,
+        apply_patch: resolve(
+            "apply_patch_sec",
+            toml.apply_patch_sec,
+            defaults.apply_patch,
+        )?,
+        mcp_call: resolve("mcp_call_sec", toml.mcp_call_sec, defaults.mcp_call)?,
+        max,
+        deadline: None,
+    })
+}
+
+fn resolve_hooks(toml: Option<&HooksToml>) -> std::io::Result<HooksConfig> {
+    let defaults = HooksConfig::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+
+    let command = |key: &str, argv: &Option<Vec<String>>| match argv {
+        Some(argv) if argv.is_empty() || argv[0].is_empty() => Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("hooks.{key} must name a program to run"),
+        )),
+        argv => Ok(argv.clone()),
+    };
+    let timeout = match toml.timeout_sec {
+        None => defaults.timeout,
+        Some(secs) if secs.is_finite() && secs > 0.0 => {
+            Duration::try_from_secs_f64(secs).map_err(|err| {
+                std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("hooks.timeout_sec is out of range: {err}"),
+                )
+            })?
+        }
+        Some(secs) => {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("hooks.timeout_sec must be a positive number of seconds, got {secs}"),
+            ));
+        }
+    };
+
+    Ok(HooksConfig {
+        on_session_start: command("on_session_start", &toml.on_session_start)?,
+        on_patch_applied: command("on_patch_applied", &toml.on_patch_applied)?,
+        on_turn_complete: command("on_turn_complete", &toml.on_turn_complete)?,
+        timeout,
+        sandbox: toml.sandbox.unwrap_or(defaults.sandbox),
+    })
+}
+
+fn resolve_output_limits(toml: Option<&OutputLimitsToml>) -> std::io::Result<OutputLimits> {
+    let defaults = OutputLimits::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+
+    let positive = |key: &str, bytes: Option<usize>| match bytes {
+        Some(0) => Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("output_limits.{key} must be greater than zero"),
+        )),
+        bytes => Ok(bytes),
+    };
+
+    Ok(OutputLimits {
+        model_output_bytes: positive("model_output_bytes", toml.model_output_bytes)?,
+        event_field_bytes: positive("event_field_bytes", toml.event_field_bytes)?,
+        rollout_record_bytes: positive("rollout_record_bytes", toml.rollout_record_bytes)?,
+        spill_threshold: positive("spill_threshold", toml.spill_threshold)?,
+        delta_chunk_bytes: positive("delta_chunk_bytes", toml.delta_chunk_bytes)?
+            .unwrap_or(defaults.delta_chunk_bytes),
+        patch_event_content_bytes: toml
+            .patch_event_content_bytes
+            .unwrap_or(defaults.patch_event_content_bytes),
+        interaction_input_bytes: positive("interaction_input_bytes", toml.interaction_input_bytes)?
+            .unwrap_or(defaults.interaction_input_bytes),
+    })
+}
+
+fn resolve_loop_detection(toml: Option<&LoopDetectionToml>) -> std::io::Result<LoopDetection> {
+    let defaults = LoopDetection::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+    if toml.failure_threshold == Some(0) {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "loop_detection.failure_threshold must be greater than zero",
+        ));
+    }
+    if toml.enabled == Some(false) {
+        return Ok(LoopDetection {
+            failure_threshold: None,
+        });
+    }
+    Ok(LoopDetection {
+        failure_threshold: toml.failure_threshold.or(defaults.failure_threshold),
+    })
+}
+
+fn resolve_provider_failover(
+    toml: Option<&ProviderFailoverToml>,
+    model_providers: &HashMap<String, ModelProviderInfo>,
+) -> std::io::Result<Option<ProviderFailover>> {
+    let Some(toml) = toml else {
+        return Ok(None);
+    };
+    let chain = toml
+        .chain
+        .iter()
+        .enumerate()
+        .map(|(index, target)| {
+            let provider = model_providers.get(&target.provider).ok_or_else(|| {
+                std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "provider_failover.chain[{index}].provider `{}` is not a known model provider",
+                        target.provider
+                    ),
+                )
+            })?;
+            if target.model.as_deref().is_some_and(str::is_empty) {
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("provider_failover.chain[{index}].model must not be empty"),
+                ));
+            }
+            Ok(FailoverTarget {
+                provider_id: target.provider.clone(),
+                provider: provider.clone(),
+                model: target.model.clone(),
+            })
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let retry = |toml: Option<&FailoverRetryToml>, max_retries: u32, backoff_ms: u64| {
+        let toml = toml.copied().unwrap_or_default();
+        FailoverRetry {
+            max_retries: toml.max_retries.unwrap_or(max_retries),
+            backoff: Duration::from_millis(toml.backoff_ms.unwrap_or(backoff_ms)),
+        }
+    };
+    Ok(Some(ProviderFailover {
+        chain,
+        rate_limit: retry(toml.rate_limit.as_ref(), 2, 2_000),
+        server_error: retry(toml.server_error.as_ref(), 2, 1_000),
+        disconnect: retry(toml.disconnect.as_ref(), 5, 200),
+    }))
+}
+
+fn resolve_tool_budget(toml: Option<&ToolBudgetToml>) -> std::io::Result<ToolBudget> {
+    let Some(toml) = toml else {
+        return Ok(ToolBudget::default());
+    };
+    for (key, value) in [
+        ("max_calls_per_turn", toml.max_calls_per_turn),
+        ("max_calls_per_session", toml.max_calls_per_session),
+    ] {
+        if value == Some(0) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("tool_budget.{key} must be greater than zero"),
+            ));
+        }
+    }
+    let max_exec_time_per_turn = match toml.max_exec_sec_per_turn {
+        None => None,
+        Some(secs) if secs.is_finite() && secs > 0.0 => {
+            Some(Duration::try_from_secs_f64(secs).map_err(|err| {
+                std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("tool_budget.max_exec_sec_per_turn is out of range: {err}"),
+                )
+            })?)
+        }
+        Some(secs) => {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "tool_budget.max_exec_sec_per_turn must be a positive number of seconds, got {secs}"
+                ),
+            ));
+        }
+    };
+    Ok(ToolBudget {
+        max_calls_per_turn: toml.max_calls_per_turn,
+        max_calls_per_session: toml.max_calls_per_session,
+        max_exec_time_per_turn,
+        end_turn: toml.end_turn.unwrap_or(false),
+    })
+}
+
+fn resolve_turn_progress(toml: Option<&TurnProgressToml>) -> std::io::Result<TurnProgress> {
+    let defaults = TurnProgress::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+    if toml.interval_ms == Some(0) {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "turn_progress.interval_ms must be greater than zero",
+        ));
+    }
+    if toml.enabled == Some(false) {
+        return Ok(TurnProgress { interval: None });
+    }
+    Ok(TurnProgress {
+        interval: toml
+            .interval_ms
+            .map(Duration::from_millis)
+            .or(defaults.interval),
+    })
+}
+
+fn resolve_workspace_snapshots(
+    toml: Option<&WorkspaceSnapshotsToml>,
+) -> std::io::Result<WorkspaceSnapshots> {
+    let defaults = WorkspaceSnapshots::default();
+    let Some(toml) = toml else {
+        return Ok(defaults);
+    };
+    if toml.max_snapshots == Some(0) {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "workspace_snapshots.max_snapshots must be greater than zero",
+        ));
+    }
+    Ok(WorkspaceSnapshots {
+        max_snapshots: toml.max_snapshots.unwrap_or(defaults.max_snapshots),
+        max_copy_bytes: toml.max_copy_bytes.unwrap_or(defaults.max_copy_bytes),
+        max_copy_files: toml.max_copy_files.unwrap_or(defaults.max_copy_files),
+        keep_after_session: toml
+            .keep_after_session
+            .unwrap_or(defaults.keep_after_session),
+        before_destructive_commands: toml
+            .before_destructive_commands
+            .unwrap_or(defaults.before_destructive_commands),
+    })
+}
+
 /// Returns the path to the folder where Codex logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -1764,6 +2331,7 @@ mod tests {
                 args: Vec::new(),
                 env: None,
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: None,
             },
             enabled: true,
@@ -1773,6 +2341,8 @@ mod tests {
             enabled_tools: None,
             disabled_tools: None,
             scopes: None,
+            max_restart_attempts: None,
+            sampling: None,
         }
     }
 
@@ -1783,6 +2353,8 @@ mod tests {
                 bearer_token_env_var: None,
                 http_headers: None,
                 env_http_headers: None,
+                header_sources: None,
+                tls: None,
             },
             enabled: true,
             disabled_reason: None,
@@ -1791,6 +2363,8 @@ mod tests {
             enabled_tools: None,
             disabled_tools: None,
             scopes: None,
+            max_restart_attempts: None,
+            sampling: None,
         }
     }
 
@@ -1844,6 +2418,7 @@ persistence = "none"
                 show_tooltips: true,
                 experimental_mode: None,
                 alternate_screen: AltScreenMode::Auto,
+                ctrl_c: CtrlCBehavior::Escalate,
             }
         );
     }
@@ -2447,6 +3022,134 @@ profile = "project"
         Ok(())
     }
 
+    #[test]
+    fn profile_extends_applies_nested_parents_in_order() -> anyhow::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[profiles.base]
+model = "gpt-base"
+approval_policy = "on-request"
+model_verbosity = "low"
+
+[profiles.base.features]
+apply_patch_freeform = true
+unified_exec = true
+
+[profiles.strict-sandbox]
+sandbox_mode = "read-only"
+approval_policy = "untrusted"
+
+[profiles.strict-sandbox.features]
+unified_exec = false
+
+[profiles.careful-review]
+extends = ["base", "strict-sandbox"]
+model = "gpt-careful"
+
+[profiles.client-a]
+extends = ["careful-review"]
+model_verbosity = "high"
+"#,
+        )?;
+
+        let resolved = resolve_profile(&cfg.profiles, "client-a")?;
+
+        assert_eq!(
+            resolved,
+            ConfigProfile {
+                // `careful-review` overrides its parents, `strict-sandbox`
+                // overrides `base`, and `client-a` overrides all of them.
+                model: Some("gpt-careful".to_string()),
+                approval_policy: Some(AskForApproval::UnlessTrusted),
+                sandbox_mode: Some(SandboxMode::ReadOnly),
+                model_verbosity: Some(Verbosity::High),
+                features: Some(crate::features::FeaturesToml {
+                    entries: BTreeMap::from([
+                        ("apply_patch_freeform".to_string(), true),
+                        ("unified_exec".to_string(), false),
+                    ]),
+                }),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn profile_extends_lists_are_not_inherited() -> anyhow::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[profiles.base]
+extends = ["root"]
+
+[profiles.root]
+model = "gpt-root"
+
+[profiles.other]
+model = "gpt-other"
+
+[profiles.child]
+extends = ["other", "base"]
+"#,
+        )?;
+
+        // `extends` is the only list a profile holds: a child's list replaces
+        // rather than extends its parents' lists, and the resolved profile
+        // keeps none.
+        let resolved = resolve_profile(&cfg.profiles, "child")?;
+        assert_eq!(resolved.model.as_deref(), Some("gpt-root"));
+        assert_eq!(resolved.extends, None);
+        Ok(())
+    }
+
+    #[test]
+    fn profile_extends_reports_cycles_and_missing_parents() -> anyhow::Result<()> {
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+profile = "a"
+
+[profiles.a]
+extends = ["b"]
+
+[profiles.b]
+extends = ["c"]
+
+[profiles.c]
+extends = ["a"]
+
+[profiles.orphan]
+extends = ["missing"]
+"#,
+        )?;
+
+        let err = resolve_profile(&cfg.profiles, "a").expect_err("cycle");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "config profile inheritance cycle: a -> b -> c -> a"
+        );
+
+        let err = resolve_profile(&cfg.profiles, "orphan").expect_err("missing parent");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(
+            err.to_string(),
+            "config profile `missing` not found (extended by `orphan`)"
+        );
+
+        let codex_home = TempDir::new()?;
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("active profile has a cycle");
+        assert_eq!(
+            err.to_string(),
+            "config profile inheritance cycle: a -> b -> c -> a"
+        );
+        Ok(())
+    }
+
     #[test]
     fn feature_table_overrides_legacy_flags() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -2608,6 +3311,7 @@ profile = "project"
                     args: vec!["hello".to_string()],
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -2617,6 +3321,8 @@ profile = "project"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
 
@@ -2635,12 +3341,14 @@ profile = "project"
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => {
                 assert_eq!(command, "echo");
                 assert_eq!(args, &vec!["hello".to_string()]);
                 assert!(env.is_none());
                 assert!(env_vars.is_empty());
+                assert!(env_sources.is_none());
                 assert!(cwd.is_none());
             }
             other => panic!("unexpected transport {other:?}"),
@@ -2763,6 +3471,7 @@ bearer_token = "secret"
                         ("ALPHA_VAR".to_string(), "1".to_string()),
                     ])),
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -2772,6 +3481,8 @@ bearer_token = "secret"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -2803,6 +3514,7 @@ ZIG_VAR = "3"
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => {
                 assert_eq!(command, "docs-server");
@@ -2813,6 +3525,7 @@ ZIG_VAR = "3"
                 assert_eq!(env.get("ALPHA_VAR"), Some(&"1".to_string()));
                 assert_eq!(env.get("ZIG_VAR"), Some(&"3".to_string()));
                 assert!(env_vars.is_empty());
+                assert!(env_sources.is_none());
                 assert!(cwd.is_none());
             }
             other => panic!("unexpected transport {other:?}"),
@@ -2833,6 +3546,7 @@ ZIG_VAR = "3"
                     args: Vec::new(),
                     env: None,
                     env_vars: vec!["ALPHA".to_string(), "BETA".to_string()],
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -2842,6 +3556,8 @@ ZIG_VAR = "3"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -2883,6 +3599,7 @@ ZIG_VAR = "3"
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: Some(cwd_path.clone()),
                 },
                 enabled: true,
@@ -2892,6 +3609,8 @@ ZIG_VAR = "3"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -2932,6 +3651,8 @@ ZIG_VAR = "3"
                     bearer_token_env_var: Some("MCP_TOKEN".to_string()),
                     http_headers: None,
                     env_http_headers: None,
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -2940,6 +3661,8 @@ ZIG_VAR = "3"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -2968,6 +3691,7 @@ startup_timeout_sec = 2.0
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                ..
             } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert_eq!(bearer_token_env_var.as_deref(), Some("MCP_TOKEN"));
@@ -2996,6 +3720,8 @@ startup_timeout_sec = 2.0
                         "X-Auth".to_string(),
                         "DOCS_AUTH".to_string(),
                     )])),
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -3004,6 +3730,8 @@ startup_timeout_sec = 2.0
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
         apply_blocking(
@@ -3072,6 +3800,8 @@ X-Auth = "DOCS_AUTH"
                         "X-Auth".to_string(),
                         "DOCS_AUTH".to_string(),
                     )])),
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -3080,6 +3810,8 @@ X-Auth = "DOCS_AUTH"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -3101,6 +3833,8 @@ X-Auth = "DOCS_AUTH"
                     bearer_token_env_var: None,
                     http_headers: None,
                     env_http_headers: None,
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -3109,6 +3843,8 @@ X-Auth = "DOCS_AUTH"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
         apply_blocking(
@@ -3133,6 +3869,7 @@ url = "https://example.com/mcp"
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                ..
             } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert!(bearer_token_env_var.is_none());
@@ -3168,6 +3905,8 @@ url = "https://example.com/mcp"
                             "X-Auth".to_string(),
                             "DOCS_AUTH".to_string(),
                         )])),
+                        header_sources: None,
+                        tls: None,
                     },
                     enabled: true,
                     disabled_reason: None,
@@ -3176,6 +3915,8 @@ url = "https://example.com/mcp"
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             ),
             (
@@ -3186,6 +3927,7 @@ url = "https://example.com/mcp"
                         args: vec!["--follow".to_string()],
                         env: None,
                         env_vars: Vec::new(),
+                        env_sources: None,
                         cwd: None,
                     },
                     enabled: true,
@@ -3195,6 +3937,8 @@ url = "https://example.com/mcp"
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             ),
         ]);
@@ -3268,6 +4012,7 @@ url = "https://example.com/mcp"
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: false,
@@ -3277,6 +4022,8 @@ url = "https://example.com/mcp"
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -3312,6 +4059,7 @@ url = "https://example.com/mcp"
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -3321,6 +4069,8 @@ url = "https://example.com/mcp"
                 enabled_tools: Some(vec!["allowed".to_string()]),
                 disabled_tools: Some(vec!["blocked".to_string()]),
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         )]);
 
@@ -3695,10 +4445,12 @@ model_verbosity = "high"
                 sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
                 did_user_set_custom_approval_policy_or_sandbox_mode: true,
                 forced_auto_mode_downgraded_on_windows: false,
+                plan_only: false,
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
                 cwd: fixture.cwd(),
+                workspace_roots: Vec::new(),
                 cli_auth_credentials_store_mode: Default::default(),
                 mcp_servers: Constrained::allow_any(HashMap::new()),
                 mcp_oauth_credentials_store_mode: Default::default(),
@@ -3707,11 +4459,25 @@ model_verbosity = "high"
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 project_doc_fallback_filenames: Vec::new(),
                 tool_output_token_limit: None,
+                tool_timeouts: ToolTimeouts::default(),
+                shell: None,
+                model_tools: Vec::new(),
+                hooks: HooksConfig::default(),
+                output_limits: OutputLimits::default(),
+                loop_detection: LoopDetection::default(),
+                turn_progress: TurnProgress::default(),
+                workspace_snapshots: WorkspaceSnapshots::default(),
+                tool_budget: ToolBudget::default(),
+                provider_failover: None,
                 agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+                state_dir: fixture.codex_home(),
+                tmpdir_max_age: crate::session_tmpdir::DEFAULT_MAX_AGE,
+                token_prices: BTreeMap::new(),
                 codex_home: fixture.codex_home(),
                 config_layer_stack: Default::default(),
                 history: History::default(),
                 ephemeral: false,
+                headless_approvals: None,
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
@@ -3733,7 +4499,10 @@ model_verbosity = "high"
                 ghost_snapshot: GhostSnapshotConfig::default(),
                 features: Features::with_defaults(),
                 active_profile: Some("o3".to_string()),
-                active_project: ProjectConfig { trust_level: None },
+                active_project: ProjectConfig {
+                    trust_level: None,
+                    allow_policy_overrides: None,
+                },
                 windows_wsl_setup_acknowledged: false,
                 notices: Default::default(),
                 check_for_update_on_startup: true,
@@ -3745,6 +4514,7 @@ model_verbosity = "high"
                 analytics_enabled: Some(true),
                 feedback_enabled: true,
                 tui_alternate_screen: AltScreenMode::Auto,
+                tui_ctrl_c: CtrlCBehavior::Escalate,
                 otel: OtelConfig::default(),
             },
             o3_profile_config
@@ -3777,10 +4547,12 @@ model_verbosity = "high"
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
             forced_auto_mode_downgraded_on_windows: false,
+            plan_only: false,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            workspace_roots: Vec::new(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
             mcp_oauth_credentials_store_mode: Default::default(),
@@ -3789,11 +4561,25 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_timeouts: ToolTimeouts::default(),
+            shell: None,
+            model_tools: Vec::new(),
+            hooks: HooksConfig::default(),
+            output_limits: OutputLimits::default(),
+            loop_detection: LoopDetection::default(),
+            turn_progress: TurnProgress::default(),
+            workspace_snapshots: WorkspaceSnapshots::default(),
+            tool_budget: ToolBudget::default(),
+            provider_failover: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            state_dir: fixture.codex_home(),
+            tmpdir_max_age: crate::session_tmpdir::DEFAULT_MAX_AGE,
+            token_prices: BTreeMap::new(),
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
             ephemeral: false,
+            headless_approvals: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3815,7 +4601,10 @@ model_verbosity = "high"
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("gpt3".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig {
+                trust_level: None,
+                allow_policy_overrides: None,
+            },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
@@ -3827,6 +4616,7 @@ model_verbosity = "high"
             analytics_enabled: Some(true),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_ctrl_c: CtrlCBehavior::Escalate,
             otel: OtelConfig::default(),
         };
 
@@ -3874,10 +4664,12 @@ model_verbosity = "high"
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
             forced_auto_mode_downgraded_on_windows: false,
+            plan_only: false,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            workspace_roots: Vec::new(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
             mcp_oauth_credentials_store_mode: Default::default(),
@@ -3886,11 +4678,25 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_timeouts: ToolTimeouts::default(),
+            shell: None,
+            model_tools: Vec::new(),
+            hooks: HooksConfig::default(),
+            output_limits: OutputLimits::default(),
+            loop_detection: LoopDetection::default(),
+            turn_progress: TurnProgress::default(),
+            workspace_snapshots: WorkspaceSnapshots::default(),
+            tool_budget: ToolBudget::default(),
+            provider_failover: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            state_dir: fixture.codex_home(),
+            tmpdir_max_age: crate::session_tmpdir::DEFAULT_MAX_AGE,
+            token_prices: BTreeMap::new(),
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
             ephemeral: false,
+            headless_approvals: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3912,7 +4718,10 @@ model_verbosity = "high"
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("zdr".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig {
+                trust_level: None,
+                allow_policy_overrides: None,
+            },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
@@ -3924,6 +4733,7 @@ model_verbosity = "high"
             analytics_enabled: Some(false),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_ctrl_c: CtrlCBehavior::Escalate,
             otel: OtelConfig::default(),
         };
 
@@ -3957,10 +4767,12 @@ model_verbosity = "high"
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
             forced_auto_mode_downgraded_on_windows: false,
+            plan_only: false,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
+            workspace_roots: Vec::new(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
             mcp_oauth_credentials_store_mode: Default::default(),
@@ -3969,11 +4781,25 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_timeouts: ToolTimeouts::default(),
+            shell: None,
+            model_tools: Vec::new(),
+            hooks: HooksConfig::default(),
+            output_limits: OutputLimits::default(),
+            loop_detection: LoopDetection::default(),
+            turn_progress: TurnProgress::default(),
+            workspace_snapshots: WorkspaceSnapshots::default(),
+            tool_budget: ToolBudget::default(),
+            provider_failover: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            state_dir: fixture.codex_home(),
+            tmpdir_max_age: crate::session_tmpdir::DEFAULT_MAX_AGE,
+            token_prices: BTreeMap::new(),
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
             ephemeral: false,
+            headless_approvals: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3995,7 +4821,10 @@ model_verbosity = "high"
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("gpt5".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig {
+                trust_level: None,
+                allow_policy_overrides: None,
+            },
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
@@ -4007,6 +4836,7 @@ model_verbosity = "high"
             analytics_enabled: Some(true),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_ctrl_c: CtrlCBehavior::Escalate,
             otel: OtelConfig::default(),
         };
 
@@ -4313,6 +5143,7 @@ mcp_oauth_callback_port = 5678
                     test_path.to_string_lossy().to_string(),
                     ProjectConfig {
                         trust_level: Some(TrustLevel::Untrusted),
+                        allow_policy_overrides: None,
                     },
                 )])),
                 ..Default::default()
@@ -4349,6 +5180,399 @@ mcp_oauth_callback_port = 5678
 
         Ok(())
     }
+
+    #[test]
+    fn state_dir_prefers_env_then_config_then_codex_home() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        let configured = AbsolutePathBuf::try_from(project.path().join(".codex/state"))?;
+
+        assert_eq!(
+            resolve_state_dir(None, None, codex_home.path(), project.path()),
+            codex_home.path().to_path_buf()
+        );
+        assert_eq!(
+            resolve_state_dir(None, Some(&configured), codex_home.path(), project.path()),
+            configured.to_path_buf()
+        );
+        assert_eq!(
+            resolve_state_dir(
+                Some(PathBuf::from("sessions-here")),
+                Some(&configured),
+                codex_home.path(),
+                project.path(),
+            ),
+            project.path().join("sessions-here")
+        );
+        assert_eq!(
+            resolve_state_dir(
+                Some(PathBuf::new()),
+                Some(&configured),
+                codex_home.path(),
+                project.path(),
+            ),
+            configured.to_path_buf()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tool_timeouts_fill_defaults_and_clamp_overrides() -> std::io::Result<()> {
+        assert_eq!(resolve_tool_timeouts(None)?, ToolTimeouts::default());
+
+        let timeouts = resolve_tool_timeouts(Some(&ToolTimeoutsToml {
+            shell_sec: Some(30.0),
+            mcp_call_sec: Some(0.5),
+            max_sec: Some(120.0),
+            ..Default::default()
+        }))?;
+        assert_eq!(
+            timeouts,
+            ToolTimeouts {
+                shell: Duration::from_secs(30),
+                mcp_call: Duration::from_millis(500),
+                max: Some(Duration::from_secs(120)),
+                ..ToolTimeouts::default()
+            }
+        );
+        assert_eq!(
+            timeouts.resolve(Some(600_000), timeouts.shell),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            timeouts.resolve(Some(1_000), timeouts.shell),
+            Duration::from_secs(1)
+        );
+
+        let with_deadline = ToolTimeouts {
+            deadline: Some(std::time::Instant::now() + Duration::from_secs(10)),
+            ..timeouts
+        };
+        assert!(with_deadline.resolve(Some(60_000), timeouts.shell) <= Duration::from_secs(10));
+        let expired = ToolTimeouts {
+            deadline: Some(std::time::Instant::now()),
+            ..timeouts
+        };
+        assert_eq!(expired.resolve(None, timeouts.shell), Duration::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn tool_timeouts_reject_invalid_values() {
+        let error_for = |toml: ToolTimeoutsToml| {
+            let err = resolve_tool_timeouts(Some(&toml)).expect_err("expected invalid timeouts");
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+            err.to_string()
+        };
+
+        assert_eq!(
+            error_for(ToolTimeoutsToml {
+                shell_sec: Some(0.0),
+                ..Default::default()
+            }),
+            "tool_timeouts.shell_sec must be greater than zero"
+        );
+        assert_eq!(
+            error_for(ToolTimeoutsToml {
+                apply_patch_sec: Some(-1.0),
+                ..Default::default()
+            }),
+            "tool_timeouts.apply_patch_sec must be a positive number of seconds, got -1"
+        );
+        assert_eq!(
+            error_for(ToolTimeoutsToml {
+                unified_exec_write_wait_sec: Some(0.0001),
+                ..Default::default()
+            }),
+            "tool_timeouts.unified_exec_write_wait_sec is 0.0001s; the minimum is 0.001 (one millisecond)"
+        );
+        assert_eq!(
+            error_for(ToolTimeoutsToml {
+                mcp_call_sec: Some(300.0),
+                max_sec: Some(120.0),
+                ..Default::default()
+            }),
+            "tool_timeouts.mcp_call_sec (300s) exceeds tool_timeouts.max_sec (120s)"
+        );
+    }
+
+    #[test]
+    fn hooks_fill_defaults_and_reject_invalid_values() -> std::io::Result<()> {
+        assert_eq!(resolve_hooks(None)?, HooksConfig::default());
+
+        let hooks = resolve_hooks(Some(
+            &toml::from_str::<HooksToml>(
+                r#"
+            on_patch_applied = ["cargo", "fmt"]
+            timeout_sec = 2.5
+            sandbox = false
+            "#,
+            )
+            .expect("valid [hooks] table"),
+        ))?;
+        assert_eq!(
+            hooks,
+            HooksConfig {
+                on_patch_applied: Some(vec!["cargo".to_string(), "fmt".to_string()]),
+                timeout: Duration::from_millis(2500),
+                sandbox: false,
+                ..HooksConfig::default()
+            }
+        );
+
+        let error_for = |toml: HooksToml| {
+            resolve_hooks(Some(&toml))
+                .expect_err("expected invalid hooks")
+                .to_string()
+        };
+        assert_eq!(
+            error_for(HooksToml {
+                on_turn_complete: Some(Vec::new()),
+                ..Default::default()
+            }),
+            "hooks.on_turn_complete must name a program to run"
+        );
+        assert_eq!(
+            error_for(HooksToml {
+                timeout_sec: Some(0.0),
+                ..Default::default()
+            }),
+            "hooks.timeout_sec must be a positive number of seconds, got 0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn output_limits_override_only_the_configured_budget() -> std::io::Result<()> {
+        assert_eq!(resolve_output_limits(None)?, OutputLimits::default());
+        assert_eq!(OutputLimits::default().delta_chunk_bytes, 8192);
+        assert_eq!(OutputLimits::default().patch_event_content_bytes, 65536);
+        assert_eq!(OutputLimits::default().interaction_input_bytes, 4096);
+
+        let resolve = |toml: &str| {
+            resolve_output_limits(Some(
+                &toml::from_str::<OutputLimitsToml>(toml).expect("valid [output_limits] table"),
+            ))
+        };
+        assert_eq!(
+            resolve("model_output_bytes = 4096")?,
+            OutputLimits {
+                model_output_bytes: Some(4096),
+                ..OutputLimits::default()
+            }
+        );
+        assert_eq!(
+            resolve("delta_chunk_bytes = 1024")?,
+            OutputLimits {
+                delta_chunk_bytes: 1024,
+                ..OutputLimits::default()
+            }
+        );
+        assert_eq!(
+            resolve("patch_event_content_bytes = 0")?,
+            OutputLimits {
+                patch_event_content_bytes: 0,
+                ..OutputLimits::default()
+            }
+        );
+        assert_eq!(
+            resolve("interaction_input_bytes = 512")?,
+            OutputLimits {
+                interaction_input_bytes: 512,
+                ..OutputLimits::default()
+            }
+        );
+        assert_eq!(
+            resolve("spill_threshold = 0")
+                .expect_err("expected invalid output limits")
+                .to_string(),
+            "output_limits.spill_threshold must be greater than zero"
+        );
+        assert_eq!(
+            resolve("interaction_input_bytes = 0")
+                .expect_err("expected invalid output limits")
+                .to_string(),
+            "output_limits.interaction_input_bytes must be greater than zero"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn loop_detection_threshold_defaults_and_can_be_disabled() -> std::io::Result<()> {
+        let resolve = |toml: &str| {
+            resolve_loop_detection(Some(
+                &toml::from_str::<LoopDetectionToml>(toml).expect("valid [loop_detection] table"),
+            ))
+        };
+        assert_eq!(resolve_loop_detection(None)?, LoopDetection::default());
+        assert_eq!(LoopDetection::default().failure_threshold, Some(3));
+        assert_eq!(resolve("failure_threshold = 5")?.failure_threshold, Some(5));
+        assert_eq!(resolve("enabled = false")?.failure_threshold, None);
+        assert_eq!(
+            resolve("failure_threshold = 0")
+                .expect_err("expected invalid loop detection")
+                .to_string(),
+            "loop_detection.failure_threshold must be greater than zero"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn turn_progress_interval_defaults_and_can_be_disabled() -> std::io::Result<()> {
+        let resolve = |toml: &str| {
+            resolve_turn_progress(Some(
+                &toml::from_str::<TurnProgressToml>(toml).expect("valid [turn_progress] table"),
+            ))
+        };
+        assert_eq!(resolve_turn_progress(None)?, TurnProgress::default());
+        assert_eq!(
+            TurnProgress::default().interval,
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            resolve("interval_ms = 500")?.interval,
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(resolve("enabled = false")?.interval, None);
+        assert_eq!(
+            resolve("interval_ms = 0")
+                .expect_err("expected invalid turn progress")
+                .to_string(),
+            "turn_progress.interval_ms must be greater than zero"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn provider_failover_resolves_chain_against_known_providers() -> std::io::Result<()> {
+        let providers = built_in_model_providers();
+        let resolve = |toml: &str| {
+            resolve_provider_failover(
+                Some(
+                    &toml::from_str::<ProviderFailoverToml>(toml)
+                        .expect("valid [provider_failover] table"),
+                ),
+                &providers,
+            )
+        };
+        assert_eq!(resolve_provider_failover(None, &providers)?, None);
+
+        let failover = resolve(
+            r#"
+chain = [{ provider = "ollama", model = "gpt-oss:20b" }]
+
+[rate_limit]
+max_retries = 0
+"#,
+        )?
+        .expect("failover is configured");
+        assert_eq!(
+            failover.chain,
+            vec![FailoverTarget {
+                provider_id: "ollama".to_string(),
+                provider: providers["ollama"].clone(),
+                model: Some("gpt-oss:20b".to_string()),
+            }]
+        );
+        assert_eq!(
+            failover.rate_limit,
+            FailoverRetry {
+                max_retries: 0,
+                backoff: Duration::from_secs(2),
+            }
+        );
+        assert_eq!(
+            failover.server_error,
+            FailoverRetry {
+                max_retries: 2,
+                backoff: Duration::from_secs(1),
+            }
+        );
+
+        assert_eq!(
+            resolve(r#"chain = [{ provider = "nope" }]"#)
+                .expect_err("expected unknown provider")
+                .to_string(),
+            "provider_failover.chain[0].provider `nope` is not a known model provider"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tool_budget_limits_are_off_unless_set() -> std::io::Result<()> {
+        let resolve = |toml: &str| {
+            resolve_tool_budget(Some(
+                &toml::from_str::<ToolBudgetToml>(toml).expect("valid [tool_budget] table"),
+            ))
+        };
+        assert_eq!(resolve_tool_budget(None)?, ToolBudget::default());
+        assert_eq!(
+            resolve("max_calls_per_turn = 40\nmax_exec_sec_per_turn = 90\nend_turn = true")?,
+            ToolBudget {
+                max_calls_per_turn: Some(40),
+                max_calls_per_session: None,
+                max_exec_time_per_turn: Some(Duration::from_secs(90)),
+                end_turn: true,
+            }
+        );
+        assert_eq!(
+            resolve("max_calls_per_session = 0")
+                .expect_err("expected invalid tool budget")
+                .to_string(),
+            "tool_budget.max_calls_per_session must be greater than zero"
+        );
+        assert_eq!(
+            resolve("max_exec_sec_per_turn = -1.0")
+                .expect_err("expected invalid tool budget")
+                .to_string(),
+            "tool_budget.max_exec_sec_per_turn must be a positive number of seconds, got -1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shell_config_requires_argv_only_for_custom_shells() {
+        let parse = |toml: &str| toml::from_str::<ShellToml>(toml).expect("valid [shell] table");
+
+        assert!(validate_shell_config(&parse(r#"kind = "fish""#)).is_ok());
+        assert!(
+            validate_shell_config(&parse(
+                r#"
+                kind = "custom"
+                argv = ["nu", "--login", "-c", "{command}"]
+                "#
+            ))
+            .is_ok()
+        );
+
+        let error = |toml: &str| {
+            validate_shell_config(&parse(toml))
+                .expect_err("expected invalid [shell] table")
+                .to_string()
+        };
+        assert_eq!(
+            error(r#"kind = "custom""#),
+            "shell.argv must be set to a non-empty list when shell.kind = \"custom\""
+        );
+        assert_eq!(
+            error(
+                r#"
+                kind = "bash"
+                argv = ["bash", "-c"]
+                "#
+            ),
+            "shell.argv is only used when shell.kind = \"custom\""
+        );
+        assert_eq!(
+            error(
+                r#"
+                kind = "custom"
+                argv = ["sh", "-c", "{command}", "{command}"]
+                "#
+            ),
+            "shell.argv may contain at most one \"{command}\" argument"
+        );
+    }
 }
 
 #[cfg(test)]
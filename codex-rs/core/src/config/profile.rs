@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use codex_utils_absolute_path::AbsolutePathBuf;
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -16,6 +18,9 @@ use codex_protocol::openai_models::ReasoningEffort;
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[schemars(deny_unknown_fields)]
 pub struct ConfigProfile {
+    /// Profiles to inherit settings from. Later entries override earlier ones,
+    /// and this profile's own settings override all of them.
+    pub extends: Option<Vec<String>>,
     pub model: Option<String>,
     /// The key in the `model_providers` map identifying the
     /// [`ModelProviderInfo`] to use.
@@ -46,6 +51,138 @@ pub struct ConfigProfile {
     #[schemars(schema_with = "crate::config::schema::features_schema")]
     pub features: Option<crate::features::FeaturesToml>,
     pub oss_provider: Option<String>,
+    /// Replaces the top-level `[tool_budget]` table while this profile is
+    /// active.
+    pub tool_budget: Option<crate::config::types::ToolBudgetToml>,
+}
+
+impl ConfigProfile {
+    /// Fills every key `self` leaves unset from `parent`.
+    ///
+    /// Each key states how it combines with the inherited value: `features`
+    /// merges flag by flag and `analytics` field by field; every other key,
+    /// including any list-valued one, replaces the inherited value as a whole.
+    /// `extends` is never inherited, and the result has none.
+    fn inherit_from(self, parent: &ConfigProfile) -> ConfigProfile {
+        let ConfigProfile {
+            extends: _,
+            model,
+            model_provider,
+            approval_policy,
+            sandbox_mode,
+            model_reasoning_effort,
+            model_reasoning_summary,
+            model_verbosity,
+            model_personality,
+            chatgpt_base_url,
+            model_instructions_file,
+            experimental_instructions_file,
+            experimental_compact_prompt_file,
+            include_apply_patch_tool,
+            experimental_use_unified_exec_tool,
+            experimental_use_freeform_apply_patch,
+            tools_web_search,
+            tools_view_image,
+            web_search,
+            analytics,
+            features,
+            oss_provider,
+            tool_budget,
+        } = self;
+
+        let analytics = match (analytics, &parent.analytics) {
+            (Some(analytics), Some(parent_analytics)) => {
+                Some(crate::config::types::AnalyticsConfigToml {
+                    enabled: analytics.enabled.or(parent_analytics.enabled),
+                })
+            }
+            (analytics, parent_analytics) => analytics.or_else(|| parent_analytics.clone()),
+        };
+        let features = match (features, &parent.features) {
+            (Some(features), Some(parent_features)) => {
+                let mut merged = parent_features.clone();
+                merged.entries.extend(features.entries);
+                Some(merged)
+            }
+            (features, parent_features) => features.or_else(|| parent_features.clone()),
+        };
+
+        ConfigProfile {
+            extends: None,
+            model: model.or_else(|| parent.model.clone()),
+            model_provider: model_provider.or_else(|| parent.model_provider.clone()),
+            approval_policy: approval_policy.or(parent.approval_policy),
+            sandbox_mode: sandbox_mode.or(parent.sandbox_mode),
+            model_reasoning_effort: model_reasoning_effort.or(parent.model_reasoning_effort),
+            model_reasoning_summary: model_reasoning_summary.or(parent.model_reasoning_summary),
+            model_verbosity: model_verbosity.or(parent.model_verbosity),
+            model_personality: model_personality.or(parent.model_personality),
+            chatgpt_base_url: chatgpt_base_url.or_else(|| parent.chatgpt_base_url.clone()),
+            model_instructions_file: model_instructions_file
+                .or_else(|| parent.model_instructions_file.clone()),
+            experimental_instructions_file: experimental_instructions_file
+                .or_else(|| parent.experimental_instructions_file.clone()),
+            experimental_compact_prompt_file: experimental_compact_prompt_file
+                .or_else(|| parent.experimental_compact_prompt_file.clone()),
+            include_apply_patch_tool: include_apply_patch_tool.or(parent.include_apply_patch_tool),
+            experimental_use_unified_exec_tool: experimental_use_unified_exec_tool
+                .or(parent.experimental_use_unified_exec_tool),
+            experimental_use_freeform_apply_patch: experimental_use_freeform_apply_patch
+                .or(parent.experimental_use_freeform_apply_patch),
+            tools_web_search: tools_web_search.or(parent.tools_web_search),
+            tools_view_image: tools_view_image.or(parent.tools_view_image),
+            web_search: web_search.or(parent.web_search),
+            analytics,
+            features,
+            oss_provider: oss_provider.or_else(|| parent.oss_provider.clone()),
+            tool_budget: tool_budget.or_else(|| parent.tool_budget.clone()),
+        }
+    }
+}
+
+/// Returns profile `name` with everything it `extends` applied, depth first
+/// and in list order. Fails if a profile in the chain does not exist or the
+/// chain loops back on itself; the error names the cycle.
+pub fn resolve_profile(
+    profiles: &HashMap<String, ConfigProfile>,
+    name: &str,
+) -> std::io::Result<ConfigProfile> {
+    resolve_profile_chain(profiles, name, &mut Vec::new())
+}
+
+fn resolve_profile_chain(
+    profiles: &HashMap<String, ConfigProfile>,
+    name: &str,
+    chain: &mut Vec<String>,
+) -> std::io::Result<ConfigProfile> {
+    if let Some(start) = chain.iter().position(|entry| entry == name) {
+        let cycle = chain[start..]
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(name))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("config profile inheritance cycle: {cycle}"),
+        ));
+    }
+    let Some(profile) = profiles.get(name) else {
+        let message = match chain.last() {
+            Some(child) => format!("config profile `{name}` not found (extended by `{child}`)"),
+            None => format!("config profile `{name}` not found"),
+        };
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, message));
+    };
+
+    chain.push(name.to_string());
+    let mut inherited = ConfigProfile::default();
+    for parent in profile.extends.iter().flatten() {
+        inherited = resolve_profile_chain(profiles, parent, chain)?.inherit_from(&inherited);
+    }
+    chain.pop();
+
+    Ok(profile.clone().inherit_from(&inherited))
 }
 
 impl From<ConfigProfile> for codex_app_server_protocol::Profile {
@@ -0,0 +1,457 @@
+//! Support for `Op::ReloadConfig`: re-reads the config files of a running
+//! session and decides which changed keys may be applied to it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_app_server_protocol::ConfigLayerSource;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::SandboxPolicy;
+use toml::Value as TomlValue;
+
+use crate::config::Config;
+use crate::config::ConfigBuilder;
+use crate::config::ConfigOverrides;
+use crate::config_loader::ConfigLayerStackOrdering;
+use crate::config_loader::approval_rank;
+use crate::config_loader::relaxed_policy_keys;
+use crate::config_loader::sandbox_rank;
+
+const MODEL_CHANGE_REASON: &str =
+    "the model and provider are fixed for a session; use /model or start a new session";
+const PROFILE_CHANGE_REASON: &str = "the active profile is chosen when a session starts";
+const NEW_SESSION_REASON: &str = "only takes effect in a new session";
+
+/// What a changed key affects in a running session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ReloadTarget {
+    ToolTimeouts,
+    ToolOutputTokenLimit,
+    ShellEnvironmentPolicy,
+    /// The tools an MCP server exposes to the model.
+    McpToolFilters,
+    ApprovalPolicy,
+    SandboxPolicy,
+    /// The change is not applied mid-session, for the given reason.
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChangedKey {
+    pub(crate) key: String,
+    pub(crate) old_value: Option<TomlValue>,
+    pub(crate) new_value: Option<TomlValue>,
+    pub(crate) target: ReloadTarget,
+}
+
+/// Loads the config for `cwd` again from disk. The session's `-c` overrides
+/// and active profile are kept, as are writable roots in
+/// `session_sandbox_policy` that did not come from a config file (for
+/// example `--add-dir`).
+pub(crate) async fn load_reloaded_config(
+    current: &Config,
+    cwd: &Path,
+    session_sandbox_policy: &SandboxPolicy,
+) -> std::io::Result<Config> {
+    let cli_overrides: Vec<(String, TomlValue)> = current
+        .config_layer_stack
+        .get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, false)
+        .into_iter()
+        .filter(|layer| matches!(layer.name, ConfigLayerSource::SessionFlags))
+        .filter_map(|layer| layer.config.as_table())
+        .flat_map(|table| {
+            table
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+        })
+        .collect();
+
+    let configured_roots =
+        configured_writable_roots(&current.config_layer_stack.effective_config());
+    let additional_writable_roots = match session_sandbox_policy {
+        SandboxPolicy::WorkspaceWrite { writable_roots, .. } => writable_roots
+            .iter()
+            .map(|root| root.to_path_buf())
+            .filter(|root| !configured_roots.contains(root))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    ConfigBuilder::default()
+        .codex_home(current.codex_home.clone())
+        .cli_overrides(cli_overrides)
+        .harness_overrides(ConfigOverrides {
+            cwd: Some(cwd.to_path_buf()),
+            config_profile: current.active_profile.clone(),
+            codex_linux_sandbox_exe: current.codex_linux_sandbox_exe.clone(),
+            additional_writable_roots,
+            ..Default::default()
+        })
+        .build()
+        .await
+}
+
+fn configured_writable_roots(effective_config: &TomlValue) -> Vec<PathBuf> {
+    effective_config
+        .get("sandbox_workspace_write")
+        .and_then(|table| table.get("writable_roots"))
+        .and_then(TomlValue::as_array)
+        .map(|roots| {
+            roots
+                .iter()
+                .filter_map(TomlValue::as_str)
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns every leaf key whose value differs between the `old` and `new`
+/// merged configs, sorted by key. Profiles other than `active_profile` are
+/// skipped since they do not affect the session, and so is `projects`: trust
+/// settings only decide which layers load, and any effect they have shows up
+/// in the other keys.
+pub(crate) fn changed_keys(
+    old: &TomlValue,
+    new: &TomlValue,
+    active_profile: Option<&str>,
+) -> Vec<ChangedKey> {
+    let mut old_values = BTreeMap::new();
+    flatten(old, "", &mut old_values);
+    let mut new_values = BTreeMap::new();
+    flatten(new, "", &mut new_values);
+
+    let mut keys: Vec<&String> = old_values.keys().chain(new_values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter(|key| old_values.get(*key) != new_values.get(*key))
+        .filter(|key| !key.starts_with("projects."))
+        .filter(|key| match key.strip_prefix("profiles.") {
+            Some(rest) => {
+                active_profile.is_some_and(|profile| rest.starts_with(&format!("{profile}.")))
+            }
+            None => true,
+        })
+        .map(|key| ChangedKey {
+            key: key.clone(),
+            old_value: old_values.get(key).cloned(),
+            new_value: new_values.get(key).cloned(),
+            target: reload_target(key, active_profile),
+        })
+        .collect()
+}
+
+fn flatten(value: &TomlValue, prefix: &str, out: &mut BTreeMap<String, TomlValue>) {
+    match value {
+        TomlValue::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(value, &key, out);
+            }
+        }
+        value => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn reload_target(key: &str, active_profile: Option<&str>) -> ReloadTarget {
+    let profile_key = active_profile.and_then(|profile| {
+        key.strip_prefix("profiles.")?
+            .strip_prefix(profile)?
+            .strip_prefix('.')
+    });
+    let setting = profile_key.unwrap_or(key);
+    let root = setting.split('.').next().unwrap_or(setting);
+    let in_profile = profile_key.is_some();
+
+    match root {
+        "approval_policy" => ReloadTarget::ApprovalPolicy,
+        "sandbox_mode" => ReloadTarget::SandboxPolicy,
+        "sandbox_workspace_write" if !in_profile => ReloadTarget::SandboxPolicy,
+        "tool_timeouts" if !in_profile => ReloadTarget::ToolTimeouts,
+        "tool_output_token_limit" if !in_profile => ReloadTarget::ToolOutputTokenLimit,
+        "shell_environment_policy" if !in_profile => ReloadTarget::ShellEnvironmentPolicy,
+        "mcp_servers" if !in_profile && is_mcp_tool_filter_key(setting) => {
+            ReloadTarget::McpToolFilters
+        }
+        "model"
+        | "model_provider"
+        | "model_providers"
+        | "model_reasoning_effort"
+        | "model_reasoning_summary"
+        | "model_verbosity"
+        | "review_model" => ReloadTarget::Rejected(MODEL_CHANGE_REASON.to_string()),
+        "profile" => ReloadTarget::Rejected(PROFILE_CHANGE_REASON.to_string()),
+        _ => ReloadTarget::Rejected(NEW_SESSION_REASON.to_string()),
+    }
+}
+
+/// Whether `key` (`mcp_servers.<name>.<field>`) is one of a server's tool
+/// allow or deny lists.
+fn is_mcp_tool_filter_key(key: &str) -> bool {
+    matches!(
+        key.splitn(3, '.').nth(2),
+        Some("enabled_tools" | "disabled_tools" | "include_tools" | "exclude_tools")
+    )
+}
+
+/// Returns the policy keys that `new_*` relaxes relative to `current_*`: a
+/// less restrictive sandbox mode, fewer approval prompts, network access
+/// being turned on, or writable roots being added.
+///
+/// The resolved policies catch changes that come from outside the config
+/// files, such as the session's command-line flags. The merged configs are
+/// put through the same check that keeps a project config from loading
+/// without `allow_policy_overrides`, limited to the keys a reload applies.
+pub(crate) fn relaxed_policies(
+    current_approval: AskForApproval,
+    current_sandbox: &SandboxPolicy,
+    current_config: &TomlValue,
+    new_approval: AskForApproval,
+    new_sandbox: &SandboxPolicy,
+    new_config: &TomlValue,
+    active_profile: Option<&str>,
+) -> Vec<String> {
+    let mut relaxed = Vec::new();
+    if sandbox_rank(sandbox_mode(new_sandbox)) > sandbox_rank(sandbox_mode(current_sandbox)) {
+        relaxed.push("sandbox_mode".to_string());
+    }
+    if approval_rank(new_approval) > approval_rank(current_approval) {
+        relaxed.push("approval_policy".to_string());
+    }
+    if new_sandbox.has_full_network_access() && !current_sandbox.has_full_network_access() {
+        relaxed.push("sandbox_workspace_write.network_access".to_string());
+    }
+
+    for key in relaxed_policy_keys(current_config, new_config) {
+        let applied = matches!(
+            reload_target(&key, active_profile),
+            ReloadTarget::ApprovalPolicy | ReloadTarget::SandboxPolicy
+        );
+        if applied && !relaxed.contains(&key) {
+            relaxed.push(key);
+        }
+    }
+    relaxed
+}
+
+fn sandbox_mode(policy: &SandboxPolicy) -> SandboxMode {
+    match policy {
+        SandboxPolicy::ReadOnly => SandboxMode::ReadOnly,
+        SandboxPolicy::WorkspaceWrite { .. } => SandboxMode::WorkspaceWrite,
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. } => {
+            SandboxMode::DangerFullAccess
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(toml: &str) -> TomlValue {
+        toml::from_str(toml).expect("valid toml")
+    }
+
+    #[test]
+    fn changed_keys_classify_settings_and_skip_inactive_profiles() {
+        let old = parse(
+            r#"
+            model = "gpt-5.1"
+            approval_policy = "on-request"
+
+            [tool_timeouts]
+            shell_sec = 10
+
+            [profiles.fast]
+            sandbox_mode = "read-only"
+
+            [profiles.other]
+            model = "o3"
+
+            [projects."/repo"]
+            trust_level = "trusted"
+            "#,
+        );
+        let new = parse(
+            r#"
+            model = "gpt-5.2"
+            approval_policy = "on-request"
+
+            [tool_timeouts]
+            shell_sec = 30
+
+            [profiles.fast]
+            sandbox_mode = "workspace-write"
+
+            [profiles.other]
+            model = "gpt-4.1"
+
+            [projects."/repo"]
+            trust_level = "trusted"
+            allow_policy_overrides = true
+            "#,
+        );
+
+        let changes = changed_keys(&old, &new, Some("fast"));
+        let summary: Vec<(&str, &ReloadTarget)> = changes
+            .iter()
+            .map(|change| (change.key.as_str(), &change.target))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "model",
+                    &ReloadTarget::Rejected(MODEL_CHANGE_REASON.to_string())
+                ),
+                ("profiles.fast.sandbox_mode", &ReloadTarget::SandboxPolicy),
+                ("tool_timeouts.shell_sec", &ReloadTarget::ToolTimeouts),
+            ]
+        );
+        assert_eq!(changes[2].old_value, Some(TomlValue::Integer(10)));
+        assert_eq!(changes[2].new_value, Some(TomlValue::Integer(30)));
+    }
+
+    #[test]
+    fn changed_keys_apply_mcp_tool_filters_only() {
+        let old = parse(
+            r#"
+            [mcp_servers.docs]
+            command = "docs-server"
+            include_tools = ["search"]
+            "#,
+        );
+        let new = parse(
+            r#"
+            [mcp_servers.docs]
+            command = "docs-server-v2"
+            include_tools = ["search", "read_*"]
+            exclude_tools = ["delete_everything"]
+            "#,
+        );
+
+        let changes = changed_keys(&old, &new, None);
+        let summary: Vec<(&str, &ReloadTarget)> = changes
+            .iter()
+            .map(|change| (change.key.as_str(), &change.target))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "mcp_servers.docs.command",
+                    &ReloadTarget::Rejected(NEW_SESSION_REASON.to_string())
+                ),
+                (
+                    "mcp_servers.docs.exclude_tools",
+                    &ReloadTarget::McpToolFilters
+                ),
+                (
+                    "mcp_servers.docs.include_tools",
+                    &ReloadTarget::McpToolFilters
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn relaxed_policies_flags_weaker_sandbox_approval_and_network() {
+        let workspace_write = |network_access| SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        };
+        let empty = parse("");
+
+        assert_eq!(
+            relaxed_policies(
+                AskForApproval::OnRequest,
+                &workspace_write(false),
+                &empty,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                &empty,
+                None,
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            relaxed_policies(
+                AskForApproval::OnRequest,
+                &workspace_write(false),
+                &empty,
+                AskForApproval::Never,
+                &workspace_write(true),
+                &empty,
+                None,
+            ),
+            vec!["approval_policy", "sandbox_workspace_write.network_access"]
+        );
+        assert_eq!(
+            relaxed_policies(
+                AskForApproval::OnRequest,
+                &SandboxPolicy::ReadOnly,
+                &empty,
+                AskForApproval::OnRequest,
+                &SandboxPolicy::DangerFullAccess,
+                &empty,
+                None,
+            ),
+            vec!["sandbox_mode", "sandbox_workspace_write.network_access"]
+        );
+    }
+
+    #[test]
+    fn relaxed_policies_flags_added_writable_roots_in_applied_keys_only() {
+        let policy = SandboxPolicy::new_workspace_write_policy();
+        let old = parse(
+            r#"
+            [sandbox_workspace_write]
+            writable_roots = ["/repo/a"]
+            "#,
+        );
+        let new = parse(
+            r#"
+            [sandbox_workspace_write]
+            writable_roots = ["/repo/a", "/repo/b"]
+
+            [profiles.other]
+            sandbox_mode = "danger-full-access"
+
+            [hooks]
+            sandbox = false
+            "#,
+        );
+        let relaxed = |old: &TomlValue, new: &TomlValue| {
+            relaxed_policies(
+                AskForApproval::OnRequest,
+                &policy,
+                old,
+                AskForApproval::OnRequest,
+                &policy,
+                new,
+                Some("fast"),
+            )
+        };
+
+        // The inactive profile and the hooks only take effect in a new
+        // session, so they do not hold back the sandbox change.
+        assert_eq!(
+            relaxed(&old, &new),
+            vec!["sandbox_workspace_write.writable_roots"]
+        );
+        assert_eq!(relaxed(&new, &old), Vec::<String>::new());
+    }
+}
@@ -14,6 +14,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
 use std::time::Duration;
+use std::time::Instant;
 use wildmatch::WildMatchPattern;
 
 use schemars::JsonSchema;
@@ -67,16 +68,28 @@ pub struct McpServerConfig {
     pub tool_timeout_sec: Option<Duration>,
 
     /// Explicit allow-list of tools exposed from this server. When set, only these tools will be registered.
+    /// Entries are tool names or `*`/`?` patterns; `include_tools` in config.toml sets the same list.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enabled_tools: Option<Vec<String>>,
 
     /// Explicit deny-list of tools. These tools will be removed after applying `enabled_tools`.
+    /// Entries are tool names or `*`/`?` patterns; `exclude_tools` in config.toml sets the same list.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub disabled_tools: Option<Vec<String>>,
 
     /// Optional OAuth scopes to request during MCP login.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub scopes: Option<Vec<String>>,
+
+    /// How many times in a row Codex restarts this server after it crashes
+    /// before disabling it for the session. Defaults to 3; `0` never restarts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restart_attempts: Option<u32>,
+
+    /// How `sampling/createMessage` requests from this server are answered.
+    /// Sampling is denied when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<McpSamplingConfig>,
 }
 
 // Raw MCP config shape used for deserialization and JSON Schema generation.
@@ -93,6 +106,8 @@ pub(crate) struct RawMcpServerConfig {
     #[serde(default)]
     pub env_vars: Option<Vec<String>>,
     #[serde(default)]
+    pub env_sources: Option<HashMap<String, McpEnvSource>>,
+    #[serde(default)]
     pub cwd: Option<PathBuf>,
     pub http_headers: Option<HashMap<String, String>>,
     #[serde(default)]
@@ -102,6 +117,10 @@ pub(crate) struct RawMcpServerConfig {
     pub url: Option<String>,
     pub bearer_token: Option<String>,
     pub bearer_token_env_var: Option<String>,
+    #[serde(default)]
+    pub header_sources: Option<HashMap<String, McpEnvSource>>,
+    #[serde(default)]
+    pub tls: Option<McpServerTlsConfig>,
 
     // shared
     #[serde(default)]
@@ -118,7 +137,15 @@ pub(crate) struct RawMcpServerConfig {
     #[serde(default)]
     pub disabled_tools: Option<Vec<String>>,
     #[serde(default)]
+    pub include_tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub exclude_tools: Option<Vec<String>>,
+    #[serde(default)]
     pub scopes: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_restart_attempts: Option<u32>,
+    #[serde(default)]
+    pub sampling: Option<McpSamplingConfig>,
 }
 
 impl<'de> Deserialize<'de> for McpServerConfig {
@@ -138,9 +165,9 @@ impl<'de> Deserialize<'de> for McpServerConfig {
         };
         let tool_timeout_sec = raw.tool_timeout_sec;
         let enabled = raw.enabled.unwrap_or_else(default_enabled);
-        let enabled_tools = raw.enabled_tools.clone();
-        let disabled_tools = raw.disabled_tools.clone();
         let scopes = raw.scopes.clone();
+        let max_restart_attempts = raw.max_restart_attempts;
+        let sampling = raw.sampling.take();
 
         fn throw_if_set<E, T>(transport: &str, field: &str, value: Option<&T>) -> Result<(), E>
         where
@@ -154,6 +181,36 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             )))
         }
 
+        fn either_list<E>(
+            field: &str,
+            value: Option<Vec<String>>,
+            alias: &str,
+            alias_value: Option<Vec<String>>,
+        ) -> Result<Option<Vec<String>>, E>
+        where
+            E: SerdeError,
+        {
+            match (value, alias_value) {
+                (Some(_), Some(_)) => {
+                    Err(E::custom(format!("{field} and {alias} cannot both be set")))
+                }
+                (value, alias_value) => Ok(value.or(alias_value)),
+            }
+        }
+
+        let enabled_tools = either_list(
+            "enabled_tools",
+            raw.enabled_tools.take(),
+            "include_tools",
+            raw.include_tools.take(),
+        )?;
+        let disabled_tools = either_list(
+            "disabled_tools",
+            raw.disabled_tools.take(),
+            "exclude_tools",
+            raw.exclude_tools.take(),
+        )?;
+
         let transport = if let Some(command) = raw.command.clone() {
             throw_if_set("stdio", "url", raw.url.as_ref())?;
             throw_if_set(
@@ -164,24 +221,37 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             throw_if_set("stdio", "bearer_token", raw.bearer_token.as_ref())?;
             throw_if_set("stdio", "http_headers", raw.http_headers.as_ref())?;
             throw_if_set("stdio", "env_http_headers", raw.env_http_headers.as_ref())?;
+            throw_if_set("stdio", "header_sources", raw.header_sources.as_ref())?;
+            throw_if_set("stdio", "tls", raw.tls.as_ref())?;
             McpServerTransportConfig::Stdio {
                 command,
                 args: raw.args.clone().unwrap_or_default(),
                 env: raw.env.clone(),
                 env_vars: raw.env_vars.clone().unwrap_or_default(),
+                env_sources: raw.env_sources.take(),
                 cwd: raw.cwd.take(),
             }
         } else if let Some(url) = raw.url.clone() {
             throw_if_set("streamable_http", "args", raw.args.as_ref())?;
             throw_if_set("streamable_http", "env", raw.env.as_ref())?;
             throw_if_set("streamable_http", "env_vars", raw.env_vars.as_ref())?;
+            throw_if_set("streamable_http", "env_sources", raw.env_sources.as_ref())?;
             throw_if_set("streamable_http", "cwd", raw.cwd.as_ref())?;
             throw_if_set("streamable_http", "bearer_token", raw.bearer_token.as_ref())?;
+            if let Some(tls) = &raw.tls
+                && tls.client_certificate.is_some() != tls.client_key.is_some()
+            {
+                return Err(SerdeError::custom(
+                    "tls.client_certificate and tls.client_key must be set together",
+                ));
+            }
             McpServerTransportConfig::StreamableHttp {
                 url,
                 bearer_token_env_var: raw.bearer_token_env_var.clone(),
                 http_headers: raw.http_headers.clone(),
                 env_http_headers: raw.env_http_headers.take(),
+                header_sources: raw.header_sources.take(),
+                tls: raw.tls.take(),
             }
         } else {
             return Err(SerdeError::custom("invalid transport"));
@@ -196,6 +266,8 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             enabled_tools,
             disabled_tools,
             scopes,
+            max_restart_attempts,
+            sampling,
         })
     }
 }
@@ -216,6 +288,10 @@ pub enum McpServerTransportConfig {
         env: Option<HashMap<String, String>>,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         env_vars: Vec<String>,
+        /// Environment variables whose values are looked up when the server is
+        /// spawned, so secrets never have to be written into the config.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        env_sources: Option<HashMap<String, McpEnvSource>>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         cwd: Option<PathBuf>,
     },
@@ -233,9 +309,77 @@ pub enum McpServerTransportConfig {
         /// HTTP headers where the value is sourced from an environment variable.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         env_http_headers: Option<HashMap<String, String>>,
+        /// HTTP headers whose values are looked up each time the server is
+        /// started, from Codex's environment or the OS keychain.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        header_sources: Option<HashMap<String, McpEnvSource>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tls: Option<McpServerTlsConfig>,
     },
 }
 
+/// TLS settings for a streamable HTTP server. All files are PEM encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct McpServerTlsConfig {
+    /// Certificates to trust in addition to the system roots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_certificate: Option<PathBuf>,
+    /// Certificate to present to the server. Requires `client_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+}
+
+/// Where the value of an `env_sources` entry comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum McpEnvSource {
+    /// Read the named variable from Codex's own environment.
+    Env(String),
+    /// Read the password stored in the OS keychain under this account name.
+    Keychain(String),
+}
+
+impl fmt::Display for McpEnvSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpEnvSource::Env(var) => write!(f, "env:{var}"),
+            McpEnvSource::Keychain(account) => write!(f, "keychain:{account}"),
+        }
+    }
+}
+
+/// Settings for `sampling/createMessage` requests, which let an MCP server
+/// ask Codex for a model completion.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct McpSamplingConfig {
+    #[serde(default)]
+    pub policy: McpSamplingPolicy,
+    /// Model that runs the completions. Defaults to the session's model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tokens that `auto` may spend on this server's requests per session.
+    /// Requests past it are sent to the user for approval. Unlimited when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_budget: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum McpSamplingPolicy {
+    /// Reject every request.
+    #[default]
+    Deny,
+    /// Run requests without asking, within `token_budget`.
+    Auto,
+    /// Ask the user to approve each request.
+    Ask,
+}
+
 mod option_duration_secs {
     use serde::Deserialize;
     use serde::Deserializer;
@@ -415,6 +559,439 @@ impl Default for OtelConfig {
     }
 }
 
+/// Default timeouts per tool type, in seconds, from the `[tool_timeouts]`
+/// table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolTimeoutsToml {
+    /// Timeout for `shell`/`shell_command` calls that do not pass `timeout_ms`.
+    pub shell_sec: Option<f64>,
+
+    /// How long `write_stdin` waits for output when the call does not pass
+    /// `yield_time_ms`.
+    pub unified_exec_write_wait_sec: Option<f64>,
+
+    /// Timeout for applying a patch.
+    pub apply_patch_sec: Option<f64>,
+
+    /// Timeout for MCP tool calls on servers without `tool_timeout_sec`.
+    pub mcp_call_sec: Option<f64>,
+
+    /// Ceiling for every resolved timeout, including per-call overrides.
+    pub max_sec: Option<f64>,
+}
+
+/// Effective per-tool timeouts after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolTimeouts {
+    pub shell: Duration,
+    pub unified_exec_write_wait: Duration,
+    pub apply_patch: Duration,
+    pub mcp_call: Duration,
+    pub max: Option<Duration>,
+    /// End of the whole run, set by `codex exec --max-duration` rather than
+    /// `config.toml`.
+    pub deadline: Option<Instant>,
+}
+
+impl ToolTimeouts {
+    /// Picks the per-call override when present, otherwise `default`, and
+    /// clamps the result to [`ToolTimeouts::max`] and to the time left before
+    /// [`ToolTimeouts::deadline`].
+    pub fn resolve(&self, override_ms: Option<u64>, default: Duration) -> Duration {
+        let mut timeout = override_ms.map_or(default, Duration::from_millis);
+        if let Some(max) = self.max {
+            timeout = timeout.min(max);
+        }
+        if let Some(remaining) = self.remaining() {
+            timeout = timeout.min(remaining);
+        }
+        timeout
+    }
+
+    /// Time left before [`ToolTimeouts::deadline`], or `None` without one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+impl Default for ToolTimeouts {
+    fn default() -> Self {
+        Self {
+            shell: Duration::from_millis(crate::exec::DEFAULT_EXEC_COMMAND_TIMEOUT_MS),
+            unified_exec_write_wait: Duration::from_millis(250),
+            apply_patch: Duration::from_millis(crate::exec::DEFAULT_EXEC_COMMAND_TIMEOUT_MS),
+            mcp_call: crate::mcp_connection_manager::DEFAULT_TOOL_TIMEOUT,
+            max: None,
+            deadline: None,
+        }
+    }
+}
+
+/// Shell that wraps scripts from the `shell_command` and `exec_command`
+/// tools, from the `[shell]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ShellToml {
+    /// Which shell to use. Defaults to the user's login shell.
+    pub kind: Option<ShellKind>,
+
+    /// Path to the shell binary. Looked up on `PATH` when unset.
+    pub path: Option<AbsolutePathBuf>,
+
+    /// Argument vector for `kind = "custom"`. An argument that is exactly
+    /// `{command}` is replaced with the script; without one the script is
+    /// appended.
+    pub argv: Option<Vec<String>>,
+}
+
+/// Commands to run at points in a session's lifecycle, from the `[hooks]`
+/// table. Each command is an argv vector.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct HooksToml {
+    /// Runs once the session is configured.
+    pub on_session_start: Option<Vec<String>>,
+
+    /// Runs after each patch the agent applies successfully.
+    pub on_patch_applied: Option<Vec<String>>,
+
+    /// Runs after each turn in which the agent finished responding.
+    pub on_turn_complete: Option<Vec<String>>,
+
+    /// Seconds a hook may run before it is killed. Defaults to 10.
+    pub timeout_sec: Option<f64>,
+
+    /// Run hooks inside the session's sandbox. Defaults to `true`.
+    pub sandbox: Option<bool>,
+}
+
+/// Effective `[hooks]` settings after defaults are applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HooksConfig {
+    pub on_session_start: Option<Vec<String>>,
+    pub on_patch_applied: Option<Vec<String>>,
+    pub on_turn_complete: Option<Vec<String>>,
+    pub timeout: Duration,
+    pub sandbox: bool,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_session_start: None,
+            on_patch_applied: None,
+            on_turn_complete: None,
+            timeout: Duration::from_secs(10),
+            sandbox: true,
+        }
+    }
+}
+
+/// Byte budgets for command output, from the `[output_limits]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct OutputLimitsToml {
+    /// Bytes of command output sent back to the model. Defaults to the
+    /// model's own truncation budget.
+    pub model_output_bytes: Option<usize>,
+
+    /// Bytes kept in each output field of an `ExecCommandEnd` event. Output
+    /// is otherwise only bounded by the capture limit.
+    pub event_field_bytes: Option<usize>,
+
+    /// Largest event record written to a session rollout. Larger event
+    /// records are left out of the rollout. Unlimited by default.
+    pub rollout_record_bytes: Option<usize>,
+
+    /// Command output larger than this is also saved in full to a file the
+    /// model is pointed to. Disabled by default.
+    pub spill_threshold: Option<usize>,
+
+    /// Largest chunk of output streamed in one `ExecCommandOutputDelta`
+    /// event. Defaults to 8192.
+    pub delta_chunk_bytes: Option<usize>,
+
+    /// Largest patch whose full file contents are included in its
+    /// `PatchApplyBegin` event; larger patches only carry per-file summaries.
+    /// Defaults to 65536.
+    pub patch_event_content_bytes: Option<usize>,
+
+    /// Bytes of `write_stdin` input kept in its `TerminalInteraction` event;
+    /// the rest is only counted. The session still receives all of it.
+    /// Defaults to 4096.
+    pub interaction_input_bytes: Option<usize>,
+}
+
+/// Effective `[output_limits]` settings after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLimits {
+    pub model_output_bytes: Option<usize>,
+    pub event_field_bytes: Option<usize>,
+    pub rollout_record_bytes: Option<usize>,
+    pub spill_threshold: Option<usize>,
+    pub delta_chunk_bytes: usize,
+    pub patch_event_content_bytes: usize,
+    pub interaction_input_bytes: usize,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            model_output_bytes: None,
+            event_field_bytes: None,
+            rollout_record_bytes: None,
+            spill_threshold: None,
+            delta_chunk_bytes: crate::exec::READ_CHUNK_SIZE,
+            patch_event_content_bytes: 64 * 1024,
+            interaction_input_bytes: 4 * 1024,
+        }
+    }
+}
+
+/// Noticing the model rerunning a command that keeps failing, from the
+/// `[loop_detection]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LoopDetectionToml {
+    /// Set to false to never flag repeated failures. Defaults to true.
+    pub enabled: Option<bool>,
+
+    /// Consecutive failures of one command with the same exit code after
+    /// which the model is told to change approach and a `LoopDetected`
+    /// event is sent. Defaults to 3.
+    pub failure_threshold: Option<u32>,
+}
+
+/// Effective `[loop_detection]` settings after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopDetection {
+    /// `None` when loop detection is disabled.
+    pub failure_threshold: Option<u32>,
+}
+
+impl Default for LoopDetection {
+    fn default() -> Self {
+        Self {
+            failure_threshold: Some(3),
+        }
+    }
+}
+
+/// Progress events during turns spent running tools, from the
+/// `[turn_progress]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct TurnProgressToml {
+    /// Set to false to never send `TurnProgress` events. Defaults to true.
+    pub enabled: Option<bool>,
+
+    /// How often to send a `TurnProgress` event while tools run, in
+    /// milliseconds. Defaults to 2000.
+    pub interval_ms: Option<u64>,
+}
+
+/// Effective `[turn_progress]` settings after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnProgress {
+    /// `None` when progress events are disabled.
+    pub interval: Option<Duration>,
+}
+
+impl Default for TurnProgress {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(2)),
+        }
+    }
+}
+
+/// Workspace snapshots taken with `Op::SnapshotWorkspace` or before a
+/// destructive command, from the `[workspace_snapshots]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WorkspaceSnapshotsToml {
+    /// Snapshots kept per session; the oldest is dropped past it. Defaults
+    /// to 10.
+    pub max_snapshots: Option<usize>,
+
+    /// Most bytes copied for a snapshot outside a git repository. Defaults
+    /// to 64 MiB.
+    pub max_copy_bytes: Option<u64>,
+
+    /// Most files copied for a snapshot outside a git repository. Defaults
+    /// to 5000.
+    pub max_copy_files: Option<usize>,
+
+    /// Set to true to keep the git refs of the session's snapshots when it
+    /// ends. Defaults to false.
+    pub keep_after_session: Option<bool>,
+
+    /// Set to false to not snapshot before a turn's first destructive
+    /// command. Defaults to true.
+    pub before_destructive_commands: Option<bool>,
+}
+
+/// Effective `[workspace_snapshots]` settings after defaults are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkspaceSnapshots {
+    pub max_snapshots: usize,
+    pub max_copy_bytes: u64,
+    pub max_copy_files: usize,
+    pub keep_after_session: bool,
+    pub before_destructive_commands: bool,
+}
+
+impl Default for WorkspaceSnapshots {
+    fn default() -> Self {
+        Self {
+            max_snapshots: 10,
+            max_copy_bytes: 64 * 1024 * 1024,
+            max_copy_files: 5_000,
+            keep_after_session: false,
+            before_destructive_commands: true,
+        }
+    }
+}
+
+/// Price of a model's tokens per 1,000, from a `[token_prices.<model>]`
+/// table. Only used to show approximate costs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct TokenPrice {
+    /// Input tokens not served from the prompt cache.
+    pub input: f64,
+
+    /// Input tokens served from the prompt cache. Defaults to `input`.
+    pub cached_input: Option<f64>,
+
+    /// Output tokens, reasoning tokens included.
+    pub output: f64,
+}
+
+/// Limits on the tool calls a turn and a session may make, from the
+/// `[tool_budget]` table. Every limit is off unless set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolBudgetToml {
+    /// Tool calls allowed in one turn.
+    pub max_calls_per_turn: Option<u32>,
+
+    /// Tool calls allowed in the whole session.
+    pub max_calls_per_session: Option<u32>,
+
+    /// Seconds the commands of one turn may run for in total.
+    pub max_exec_sec_per_turn: Option<f64>,
+
+    /// Set to true to end the turn when a budget runs out instead of letting
+    /// the model go on without tools. Defaults to false.
+    pub end_turn: Option<bool>,
+}
+
+/// Effective `[tool_budget]` settings; `None` limits are unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToolBudget {
+    pub max_calls_per_turn: Option<u32>,
+    pub max_calls_per_session: Option<u32>,
+    pub max_exec_time_per_turn: Option<Duration>,
+    pub end_turn: bool,
+}
+
+/// Fallback providers and retry policies from the `[provider_failover]`
+/// table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ProviderFailoverToml {
+    /// Providers to move to, in order, once the current one keeps failing.
+    #[serde(default)]
+    pub chain: Vec<FailoverTargetToml>,
+
+    /// Retries for rate limit errors (HTTP 429) before failing over.
+    pub rate_limit: Option<FailoverRetryToml>,
+
+    /// Retries for server errors (HTTP 5xx) before failing over.
+    pub server_error: Option<FailoverRetryToml>,
+
+    /// Retries for dropped connections and cut-off streams before failing
+    /// over.
+    pub disconnect: Option<FailoverRetryToml>,
+}
+
+/// One entry of `provider_failover.chain`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct FailoverTargetToml {
+    /// Key of a built-in provider or a `[model_providers]` entry.
+    pub provider: String,
+
+    /// Model to use with this provider. Defaults to the session's model.
+    pub model: Option<String>,
+}
+
+/// How one class of errors is retried on the current provider.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct FailoverRetryToml {
+    /// Retries before failing over.
+    pub max_retries: Option<u32>,
+
+    /// Delay before the first retry, in milliseconds; it doubles with each
+    /// further retry.
+    pub backoff_ms: Option<u64>,
+}
+
+/// Effective `[provider_failover]` settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderFailover {
+    pub chain: Vec<FailoverTarget>,
+    pub rate_limit: FailoverRetry,
+    pub server_error: FailoverRetry,
+    pub disconnect: FailoverRetry,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverTarget {
+    pub provider_id: String,
+    pub provider: crate::model_provider_info::ModelProviderInfo,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailoverRetry {
+    pub max_retries: u32,
+    pub backoff: Duration,
+}
+
+/// Tools to enable or disable for models whose slug matches `model`, from a
+/// `[[model_tools]]` entry. When several entries match, later ones win.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ModelToolsToml {
+    /// Model slug pattern. `*` matches any run of characters and `?` a single
+    /// character.
+    pub model: String,
+
+    /// Tools to enable (`true`) or disable (`false`), by tool name.
+    #[serde(default)]
+    pub tools: BTreeMap<String, bool>,
+
+    /// MCP servers whose tools to enable or disable, by server name.
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    Sh,
+    Powershell,
+    Custom,
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Notifications {
@@ -462,6 +1039,27 @@ pub struct Tui {
     /// scrollback in terminal multiplexers like Zellij that follow the xterm spec.
     #[serde(default)]
     pub alternate_screen: AltScreenMode,
+
+    /// What Ctrl-C does while the agent is working.
+    ///
+    /// - `escalate` (default): The first press cancels the running tool call,
+    ///   a second press ends the turn after its running tool calls, a third
+    ///   interrupts it and a fourth prompts to quit.
+    /// - `interrupt`: Every press interrupts the turn (original behavior).
+    #[serde(default)]
+    pub ctrl_c: CtrlCBehavior,
+}
+
+/// Ctrl-C handling in the TUI while the agent is working.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CtrlCBehavior {
+    /// Cancel the running tool call, then end the turn after its running
+    /// tool calls, then interrupt it, then quit.
+    #[default]
+    Escalate,
+    /// Interrupt the turn right away.
+    Interrupt,
 }
 
 const fn default_true() -> bool {
@@ -664,6 +1262,7 @@ mod tests {
                 args: vec![],
                 env: None,
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: None,
             }
         );
@@ -689,6 +1288,7 @@ mod tests {
                 args: vec!["hello".to_string(), "world".to_string()],
                 env: None,
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: None,
             }
         );
@@ -713,6 +1313,7 @@ mod tests {
                 args: vec!["hello".to_string(), "world".to_string()],
                 env: Some(HashMap::from([("FOO".to_string(), "BAR".to_string())])),
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: None,
             }
         );
@@ -736,6 +1337,7 @@ mod tests {
                 args: vec![],
                 env: None,
                 env_vars: vec!["FOO".to_string(), "BAR".to_string()],
+                env_sources: None,
                 cwd: None,
             }
         );
@@ -758,11 +1360,83 @@ mod tests {
                 args: vec![],
                 env: None,
                 env_vars: Vec::new(),
+                env_sources: None,
                 cwd: Some(PathBuf::from("/tmp")),
             }
         );
     }
 
+    #[test]
+    fn deserialize_stdio_command_server_config_with_env_sources() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+
+            [env_sources]
+            API_KEY = { env = "MY_API_KEY" }
+            GITHUB_TOKEN = { keychain = "github-token" }
+        "#,
+        )
+        .expect("should deserialize command config with env_sources");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::Stdio {
+                command: "echo".to_string(),
+                args: vec![],
+                env: None,
+                env_vars: Vec::new(),
+                env_sources: Some(HashMap::from([
+                    (
+                        "API_KEY".to_string(),
+                        McpEnvSource::Env("MY_API_KEY".to_string())
+                    ),
+                    (
+                        "GITHUB_TOKEN".to_string(),
+                        McpEnvSource::Keychain("github-token".to_string())
+                    ),
+                ])),
+                cwd: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_server_config_with_sampling() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+
+            [sampling]
+            policy = "auto"
+            model = "gpt-5-mini"
+            token_budget = 20000
+        "#,
+        )
+        .expect("should deserialize config with sampling");
+
+        assert_eq!(
+            cfg.sampling,
+            Some(McpSamplingConfig {
+                policy: McpSamplingPolicy::Auto,
+                model: Some("gpt-5-mini".to_string()),
+                token_budget: Some(20_000),
+            })
+        );
+
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            sampling = {}
+        "#,
+        )
+        .expect("should deserialize empty sampling table");
+        assert_eq!(
+            cfg.sampling.map(|sampling| sampling.policy),
+            Some(McpSamplingPolicy::Deny)
+        );
+    }
+
     #[test]
     fn deserialize_disabled_server_config() {
         let cfg: McpServerConfig = toml::from_str(
@@ -792,6 +1466,8 @@ mod tests {
                 bearer_token_env_var: None,
                 http_headers: None,
                 env_http_headers: None,
+                header_sources: None,
+                tls: None,
             }
         );
         assert!(cfg.enabled);
@@ -814,6 +1490,8 @@ mod tests {
                 bearer_token_env_var: Some("GITHUB_TOKEN".to_string()),
                 http_headers: None,
                 env_http_headers: None,
+                header_sources: None,
+                tls: None,
             }
         );
         assert!(cfg.enabled);
@@ -840,10 +1518,68 @@ mod tests {
                     "X-Token".to_string(),
                     "TOKEN_ENV".to_string()
                 )])),
+                header_sources: None,
+                tls: None,
             }
         );
     }
 
+    #[test]
+    fn deserialize_streamable_http_server_config_with_header_sources_and_tls() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://internal.example.com/mcp"
+
+            [header_sources]
+            Authorization = { keychain = "internal-mcp" }
+
+            [tls]
+            ca_certificate = "/etc/ssl/internal-ca.pem"
+            client_certificate = "/etc/ssl/client.pem"
+            client_key = "/etc/ssl/client-key.pem"
+        "#,
+        )
+        .expect("should deserialize http config with header_sources and tls");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                url: "https://internal.example.com/mcp".to_string(),
+                bearer_token_env_var: None,
+                http_headers: None,
+                env_http_headers: None,
+                header_sources: Some(HashMap::from([(
+                    "Authorization".to_string(),
+                    McpEnvSource::Keychain("internal-mcp".to_string())
+                )])),
+                tls: Some(McpServerTlsConfig {
+                    ca_certificate: Some(PathBuf::from("/etc/ssl/internal-ca.pem")),
+                    client_certificate: Some(PathBuf::from("/etc/ssl/client.pem")),
+                    client_key: Some(PathBuf::from("/etc/ssl/client-key.pem")),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_client_certificate_without_key() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://internal.example.com/mcp"
+
+            [tls]
+            client_certificate = "/etc/ssl/client.pem"
+        "#,
+        )
+        .expect_err("should reject a client certificate without a key");
+
+        assert!(
+            err.to_string()
+                .contains("tls.client_certificate and tls.client_key must be set together"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn deserialize_server_config_with_tool_filters() {
         let cfg: McpServerConfig = toml::from_str(
@@ -859,6 +1595,38 @@ mod tests {
         assert_eq!(cfg.disabled_tools, Some(vec!["blocked".to_string()]));
     }
 
+    #[test]
+    fn deserialize_server_config_with_include_and_exclude_tools() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            include_tools = ["read_*"]
+            exclude_tools = ["delete_everything"]
+        "#,
+        )
+        .expect("should deserialize include/exclude tools");
+
+        assert_eq!(cfg.enabled_tools, Some(vec!["read_*".to_string()]));
+        assert_eq!(
+            cfg.disabled_tools,
+            Some(vec!["delete_everything".to_string()])
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            enabled_tools = ["a"]
+            include_tools = ["b"]
+        "#,
+        )
+        .expect_err("should reject enabled_tools with include_tools");
+        assert!(
+            err.to_string()
+                .contains("enabled_tools and include_tools cannot both be set"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn deserialize_rejects_command_and_url() {
         toml::from_str::<McpServerConfig>(
@@ -20,6 +20,7 @@ use toml_edit::Value;
 use super::ConfigLayerEntry;
 use super::ConfigLayerStack;
 use super::ConfigLayerStackOrdering;
+use super::unknown_keys::unknown_keys_in_config_toml;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TextPosition {
@@ -51,32 +52,56 @@ impl ConfigError {
     }
 }
 
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.range.start.line,
+            self.range.start.column,
+            self.message
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct ConfigLoadError {
     error: ConfigError,
+    /// Further errors found in the same pass, in layer and file order.
+    other_errors: Vec<ConfigError>,
     source: Option<toml::de::Error>,
 }
 
 impl ConfigLoadError {
     pub fn new(error: ConfigError, source: Option<toml::de::Error>) -> Self {
-        Self { error, source }
+        Self {
+            error,
+            other_errors: Vec::new(),
+            source,
+        }
     }
 
     pub fn config_error(&self) -> &ConfigError {
         &self.error
     }
+
+    /// Every error found while loading, starting with [`Self::config_error`].
+    pub fn config_errors(&self) -> impl Iterator<Item = &ConfigError> {
+        std::iter::once(&self.error).chain(&self.other_errors)
+    }
 }
 
 impl fmt::Display for ConfigLoadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}:{}:{}: {}",
-            self.error.path.display(),
-            self.error.range.start.line,
-            self.error.range.start.column,
-            self.error.message
-        )
+        let mut errors = self.config_errors();
+        if let Some(error) = errors.next() {
+            write!(f, "{error}")?;
+        }
+        for error in errors {
+            write!(f, "\n{error}")?;
+        }
+        Ok(())
     }
 }
 
@@ -96,6 +121,25 @@ pub(crate) fn io_error_from_config_error(
     io::Error::new(kind, ConfigLoadError::new(error, source))
 }
 
+/// Like [`io_error_from_config_error`], but reports every error in `errors`.
+/// Returns `None` when `errors` is empty.
+pub(crate) fn io_error_from_config_errors(
+    kind: io::ErrorKind,
+    errors: Vec<ConfigError>,
+    source: Option<toml::de::Error>,
+) -> Option<io::Error> {
+    let mut errors = errors.into_iter();
+    let error = errors.next()?;
+    Some(io::Error::new(
+        kind,
+        ConfigLoadError {
+            error,
+            other_errors: errors.collect(),
+            source,
+        },
+    ))
+}
+
 pub(crate) fn config_error_from_toml(
     path: impl AsRef<Path>,
     contents: &str,
@@ -108,78 +152,125 @@ pub(crate) fn config_error_from_toml(
     ConfigError::new(path.as_ref().to_path_buf(), range, err.message())
 }
 
-pub(crate) fn config_error_from_config_toml(
+/// Returns every schema error in `contents`, sorted by position. A TOML
+/// syntax error stops parsing, so it is reported alone.
+pub(crate) fn config_errors_from_config_toml(
     path: impl AsRef<Path>,
     contents: &str,
-) -> Option<ConfigError> {
+) -> Vec<ConfigError> {
+    let path = path.as_ref();
     let deserializer = match toml::de::Deserializer::parse(contents) {
         Ok(deserializer) => deserializer,
-        Err(err) => return Some(config_error_from_toml(path, contents, err)),
+        Err(err) => return vec![config_error_from_toml(path, contents, err)],
     };
-
     let result: Result<ConfigToml, _> = serde_path_to_error::deserialize(deserializer);
-    match result {
-        Ok(_) => None,
-        Err(err) => {
-            let path_hint = err.path().clone();
-            let toml_err: toml::de::Error = err.into_inner();
-            let range = span_for_config_path(contents, &path_hint)
-                .or_else(|| toml_err.span())
-                .map(|span| text_range_from_span(contents, span))
-                .unwrap_or_else(default_range);
-            Some(ConfigError::new(
-                path.as_ref().to_path_buf(),
-                range,
-                toml_err.message(),
-            ))
+    let first_error = match result {
+        Ok(_) => return Vec::new(),
+        Err(err) => err,
+    };
+
+    // Deserialization stops at the first error, so check each top-level key
+    // on its own to report every problem in one pass.
+    let mut errors = Vec::new();
+    if let Ok(table) = toml::from_str::<toml::Table>(contents) {
+        for (key, value) in table {
+            let single_key = toml::Value::Table(toml::Table::from_iter([(key, value)]));
+            let result: Result<ConfigToml, _> = serde_path_to_error::deserialize(single_key);
+            if let Err(err) = result {
+                errors.push(config_error_from_path_error(path, contents, err));
+            }
         }
     }
+    if errors.is_empty() {
+        errors.push(config_error_from_path_error(path, contents, first_error));
+    }
+    errors.sort_by_key(|error| (error.range.start.line, error.range.start.column));
+    errors
 }
 
-pub(crate) async fn first_layer_config_error(layers: &ConfigLayerStack) -> Option<ConfigError> {
-    // When the merged config fails schema validation, we surface the first concrete
-    // per-file error to point users at a specific file and range rather than an
-    // opaque merged-layer failure.
-    first_layer_config_error_for_entries(
+fn config_error_from_path_error(
+    path: &Path,
+    contents: &str,
+    err: serde_path_to_error::Error<toml::de::Error>,
+) -> ConfigError {
+    let path_hint = err.path().clone();
+    let toml_err: toml::de::Error = err.into_inner();
+    let range = span_for_config_path(contents, &path_hint)
+        .or_else(|| toml_err.span())
+        .map(|span| text_range_from_span(contents, span))
+        .unwrap_or_else(default_range);
+    let message = if path_hint.iter().next().is_some() {
+        format!("`{path_hint}`: {}", toml_err.message())
+    } else {
+        toml_err.message().to_string()
+    };
+    ConfigError::new(path.to_path_buf(), range, message)
+}
+
+pub(crate) async fn layer_config_errors(layers: &ConfigLayerStack) -> Vec<ConfigError> {
+    // When the merged config fails schema validation, we surface the concrete
+    // per-file errors to point users at specific files and ranges rather than
+    // an opaque merged-layer failure.
+    layer_config_errors_for_entries(
         layers.get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, false),
     )
     .await
 }
 
-pub(crate) async fn first_layer_config_error_from_entries(
+pub(crate) async fn layer_config_errors_from_entries(
     layers: &[ConfigLayerEntry],
-) -> Option<ConfigError> {
-    first_layer_config_error_for_entries(layers.iter()).await
+) -> Vec<ConfigError> {
+    layer_config_errors_for_entries(layers.iter()).await
 }
 
-async fn first_layer_config_error_for_entries<'a, I>(layers: I) -> Option<ConfigError>
+async fn layer_config_errors_for_entries<'a, I>(layers: I) -> Vec<ConfigError>
 where
     I: IntoIterator<Item = &'a ConfigLayerEntry>,
 {
+    let mut errors = Vec::new();
+    for (path, contents) in read_layer_files(layers).await {
+        let Some(parent) = path.parent() else {
+            tracing::debug!("Config file {} has no parent directory", path.display());
+            continue;
+        };
+        let _guard = AbsolutePathBufGuard::new(parent);
+        errors.extend(config_errors_from_config_toml(&path, &contents));
+    }
+    errors
+}
+
+/// Returns a warning for every key in the enabled config files that Codex
+/// does not recognize. Keys under maps with free-form names, such as MCP
+/// server `env` tables, are never reported.
+pub(crate) async fn unknown_config_key_warnings(layers: &ConfigLayerStack) -> Vec<ConfigError> {
+    let mut warnings = Vec::new();
+    for (path, contents) in
+        read_layer_files(layers.get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, false))
+            .await
+    {
+        warnings.extend(unknown_keys_in_config_toml(&path, &contents));
+    }
+    warnings
+}
+
+async fn read_layer_files<'a, I>(layers: I) -> Vec<(PathBuf, String)>
+where
+    I: IntoIterator<Item = &'a ConfigLayerEntry>,
+{
+    let mut files = Vec::new();
     for layer in layers {
         let Some(path) = config_path_for_layer(layer) else {
             continue;
         };
-        let contents = match tokio::fs::read_to_string(&path).await {
-            Ok(contents) => contents,
-            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+        match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => files.push((path, contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
             Err(err) => {
                 tracing::debug!("Failed to read config file {}: {err}", path.display());
-                continue;
             }
-        };
-
-        let Some(parent) = path.parent() else {
-            tracing::debug!("Config file {} has no parent directory", path.display());
-            continue;
-        };
-        let _guard = AbsolutePathBufGuard::new(parent);
-        if let Some(error) = config_error_from_config_toml(&path, &contents) {
-            return Some(error);
         }
     }
-
-    None
+    files
 }
 
 fn config_path_for_layer(layer: &ConfigLayerEntry) -> Option<PathBuf> {
@@ -196,7 +287,7 @@ fn config_path_for_layer(layer: &ConfigLayerEntry) -> Option<PathBuf> {
     }
 }
 
-fn text_range_from_span(contents: &str, span: std::ops::Range<usize>) -> TextRange {
+pub(super) fn text_range_from_span(contents: &str, span: std::ops::Range<usize>) -> TextRange {
     let start = position_for_offset(contents, span.start);
     let end_index = if span.end > span.start {
         span.end - 1
@@ -6,7 +6,9 @@ mod layer_io;
 mod macos;
 mod merge;
 mod overrides;
+mod project_policy;
 mod state;
+mod unknown_keys;
 
 #[cfg(test)]
 mod tests;
@@ -40,17 +42,23 @@ pub use diagnostics::ConfigLoadError;
 pub use diagnostics::TextPosition;
 pub use diagnostics::TextRange;
 pub(crate) use diagnostics::config_error_from_toml;
-pub(crate) use diagnostics::first_layer_config_error;
-pub(crate) use diagnostics::first_layer_config_error_from_entries;
 pub use diagnostics::format_config_error;
 pub use diagnostics::format_config_error_with_source;
 pub(crate) use diagnostics::io_error_from_config_error;
+pub(crate) use diagnostics::io_error_from_config_errors;
+pub(crate) use diagnostics::layer_config_errors;
+pub(crate) use diagnostics::layer_config_errors_from_entries;
+pub(crate) use diagnostics::unknown_config_key_warnings;
 pub use merge::merge_toml_values;
 pub(crate) use overrides::build_cli_overrides_layer;
+pub(crate) use project_policy::approval_rank;
+pub(crate) use project_policy::relaxed_policy_keys;
+pub(crate) use project_policy::sandbox_rank;
 pub use state::ConfigLayerEntry;
 pub use state::ConfigLayerStack;
 pub use state::ConfigLayerStackOrdering;
 pub use state::LoaderOverrides;
+pub use state::describe_layer_source;
 
 /// On Unix systems, load requirements from this file path, if present.
 const DEFAULT_REQUIREMENTS_TOML_FILE_UNIX: &str = "/etc/codex/requirements.toml";
@@ -185,12 +193,12 @@ pub async fn load_config_layers_state(
         let project_root_markers = match project_root_markers_from_config(&merged_so_far) {
             Ok(markers) => markers.unwrap_or_else(default_project_root_markers),
             Err(err) => {
-                if let Some(config_error) = first_layer_config_error_from_entries(&layers).await {
-                    return Err(io_error_from_config_error(
-                        io::ErrorKind::InvalidData,
-                        config_error,
-                        None,
-                    ));
+                if let Some(config_err) = io_error_from_config_errors(
+                    io::ErrorKind::InvalidData,
+                    layer_config_errors_from_entries(&layers).await,
+                    None,
+                ) {
+                    return Err(config_err);
                 }
                 return Err(err);
             }
@@ -210,20 +218,25 @@ pub async fn load_config_layers_state(
                     .get_ref()
                     .and_then(|err| err.downcast_ref::<toml::de::Error>())
                     .cloned();
-                if let Some(config_error) = first_layer_config_error_from_entries(&layers).await {
-                    return Err(io_error_from_config_error(
-                        io::ErrorKind::InvalidData,
-                        config_error,
-                        source,
-                    ));
+                if let Some(config_err) = io_error_from_config_errors(
+                    io::ErrorKind::InvalidData,
+                    layer_config_errors_from_entries(&layers).await,
+                    source,
+                ) {
+                    return Err(config_err);
                 }
                 return Err(err);
             }
         };
+        let mut merged_below_project = TomlValue::Table(toml::map::Map::new());
+        for layer in &layers {
+            merge_toml_values(&mut merged_below_project, &layer.config);
+        }
         let project_layers = load_project_layers(
             &cwd,
             &project_trust_context.project_root,
             &project_trust_context,
+            merged_below_project,
         )
         .await?;
         layers.extend(project_layers);
@@ -453,6 +466,7 @@ struct ProjectTrustContext {
     project_root_key: String,
     repo_root_key: Option<String>,
     projects_trust: std::collections::HashMap<String, TrustLevel>,
+    projects_allowing_policy_overrides: std::collections::HashSet<String>,
     user_config_file: AbsolutePathBuf,
 }
 
@@ -519,6 +533,33 @@ impl ProjectTrustContext {
             )),
         }
     }
+
+    /// Returns a reason to disable a trusted project layer whose `config`
+    /// relaxes the sandbox or approval policy set by `merged_below`, unless
+    /// the user has already allowed it for this project.
+    fn policy_override_reason_for_dir(
+        &self,
+        dir: &AbsolutePathBuf,
+        merged_below: &TomlValue,
+        config: &TomlValue,
+    ) -> Option<String> {
+        let decision = self.decision_for_dir(dir);
+        let trust_key = decision.trust_key.as_str();
+        if self.projects_allowing_policy_overrides.contains(trust_key) {
+            return None;
+        }
+
+        let keys = project_policy::relaxed_policy_keys(merged_below, config);
+        if keys.is_empty() {
+            return None;
+        }
+
+        let keys = keys.join(", ");
+        let user_config_file = self.user_config_file.as_path().display();
+        Some(format!(
+            "config.toml relaxes {keys}. To load it, set allow_policy_overrides = true for {trust_key} in {user_config_file}."
+        ))
+    }
 }
 
 fn project_layer_entry(
@@ -527,6 +568,7 @@ fn project_layer_entry(
     layer_dir: &AbsolutePathBuf,
     config: TomlValue,
     config_toml_exists: bool,
+    merged_below: &TomlValue,
 ) -> ConfigLayerEntry {
     let source = ConfigLayerSource::Project {
         dot_codex_folder: dot_codex_folder.clone(),
@@ -534,6 +576,10 @@ fn project_layer_entry(
 
     if config_toml_exists && let Some(reason) = trust_context.disabled_reason_for_dir(layer_dir) {
         ConfigLayerEntry::new_disabled(source, config, reason)
+    } else if let Some(reason) =
+        trust_context.policy_override_reason_for_dir(layer_dir, merged_below, &config)
+    {
+        ConfigLayerEntry::new_disabled(source, config, reason)
     } else {
         ConfigLayerEntry::new(source, config)
     }
@@ -557,6 +603,11 @@ async fn project_trust_context(
         .as_ref()
         .map(|root| root.to_string_lossy().to_string());
 
+    let projects_allowing_policy_overrides = projects
+        .iter()
+        .filter(|(_, project)| project.allow_policy_overrides == Some(true))
+        .map(|(key, _)| key.clone())
+        .collect();
     let projects_trust = projects
         .into_iter()
         .filter_map(|(key, project)| project.trust_level.map(|trust_level| (key, trust_level)))
@@ -567,6 +618,7 @@ async fn project_trust_context(
         project_root_key,
         repo_root_key,
         projects_trust,
+        projects_allowing_policy_overrides,
         user_config_file: user_config_file.clone(),
     })
 }
@@ -655,10 +707,15 @@ async fn find_project_root(
 /// `project_root`, inclusive. The list is ordered in _increasing_ precdence,
 /// starting from folders closest to `project_root` (which is the lowest
 /// precedence) to those closest to `cwd` (which is the highest precedence).
+///
+/// `merged_below` is the merged config of the layers beneath the project
+/// layers; a trusted project layer that relaxes its sandbox or approval policy
+/// is disabled unless the user has opted in via `allow_policy_overrides`.
 async fn load_project_layers(
     cwd: &AbsolutePathBuf,
     project_root: &AbsolutePathBuf,
     trust_context: &ProjectTrustContext,
+    mut merged_below: TomlValue,
 ) -> io::Result<Vec<ConfigLayerEntry>> {
     let mut dirs = cwd
         .as_path()
@@ -711,14 +768,24 @@ async fn load_project_layers(
                             &layer_dir,
                             TomlValue::Table(toml::map::Map::new()),
                             true,
+                            &merged_below,
                         ));
                         continue;
                     }
                 };
                 let config =
                     resolve_relative_paths_in_config_toml(config, dot_codex_abs.as_path())?;
-                let entry =
-                    project_layer_entry(trust_context, &dot_codex_abs, &layer_dir, config, true);
+                let entry = project_layer_entry(
+                    trust_context,
+                    &dot_codex_abs,
+                    &layer_dir,
+                    config,
+                    true,
+                    &merged_below,
+                );
+                if !entry.is_disabled() {
+                    merge_toml_values(&mut merged_below, &entry.config);
+                }
                 layers.push(entry);
             }
             Err(err) => {
@@ -732,6 +799,7 @@ async fn load_project_layers(
                         &layer_dir,
                         TomlValue::Table(toml::map::Map::new()),
                         false,
+                        &merged_below,
                     ));
                 } else {
                     let config_file_display = config_file.as_path().display();
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use super::merge::merge_toml_values;
+use crate::config::profile::ConfigProfile;
+use crate::config::profile::resolve_profile;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use toml::Value as TomlValue;
+
+/// Returns the dotted keys in `project` that would relax the sandbox or
+/// approval policy, add writable roots, or move hooks out of the sandbox,
+/// relative to `below`, the merged config of every enabled layer with lower
+/// precedence than the project layer. A project that selects a profile, or
+/// changes what the selected profile `extends`, is checked against the
+/// policy that profile resolves to.
+///
+/// Unset values in `below` are compared against the defaults a trusted
+/// project gets (`workspace-write`, `on-request`, no network access), since
+/// only trusted project layers are ever enabled. Values that fail to parse
+/// are skipped here; they are reported when the merged config is
+/// deserialized.
+pub(crate) fn relaxed_policy_keys(below: &TomlValue, project: &TomlValue) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_relaxed_keys(below, project, &[], &mut keys);
+
+    if let Some(profiles) = project.get("profiles").and_then(TomlValue::as_table) {
+        for (name, profile) in profiles {
+            // A profile inherits top-level values it does not set, so compare
+            // against the profile in `below` first and fall back to its root.
+            let mut profile_below = below.clone();
+            if let Some(below_profile) = below
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name.as_str()))
+            {
+                merge_toml_values(&mut profile_below, below_profile);
+            }
+            collect_relaxed_keys(
+                &profile_below,
+                profile,
+                &["profiles", name.as_str()],
+                &mut keys,
+            );
+        }
+    }
+
+    let mut merged = below.clone();
+    merge_toml_values(&mut merged, project);
+    if let Some(name) = selected_profile(&merged)
+        && let Ok(selected) = resolve_profile(&policy_profiles(&merged), name)
+        && relaxes(&selected, below)
+    {
+        let key = if project.get("profile").is_some() {
+            "profile".to_string()
+        } else {
+            format!("profiles.{name}")
+        };
+        // Keys the project set in that profile itself are flagged already.
+        let set_in_profile = format!("{key}.");
+        if !keys
+            .iter()
+            .any(|flagged| flagged.starts_with(&set_in_profile))
+        {
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+fn selected_profile(config: &TomlValue) -> Option<&str> {
+    config.get("profile").and_then(TomlValue::as_str)
+}
+
+/// The profiles of `config`, keeping only the keys that decide their policy.
+fn policy_profiles(config: &TomlValue) -> HashMap<String, ConfigProfile> {
+    let Some(profiles) = config.get("profiles").and_then(TomlValue::as_table) else {
+        return HashMap::new();
+    };
+    profiles
+        .iter()
+        .map(|(name, profile)| {
+            let policy = ConfigProfile {
+                extends: parse(profile.get("extends")),
+                sandbox_mode: parse(profile.get("sandbox_mode")),
+                approval_policy: parse(profile.get("approval_policy")),
+                ..Default::default()
+            };
+            (name.clone(), policy)
+        })
+        .collect()
+}
+
+/// Whether the resolved `profile` sets a policy looser than the one `below`
+/// runs with, through its own selected profile or its top level.
+fn relaxes(profile: &ConfigProfile, below: &TomlValue) -> bool {
+    let below_profile = selected_profile(below)
+        .and_then(|name| resolve_profile(&policy_profiles(below), name).ok())
+        .unwrap_or_default();
+    let base_sandbox = below_profile
+        .sandbox_mode
+        .or_else(|| parse(below.get("sandbox_mode")))
+        .unwrap_or(SandboxMode::WorkspaceWrite);
+    let base_approval = below_profile
+        .approval_policy
+        .or_else(|| parse(below.get("approval_policy")))
+        .unwrap_or(AskForApproval::OnRequest);
+    profile
+        .sandbox_mode
+        .is_some_and(|mode| sandbox_rank(mode) > sandbox_rank(base_sandbox))
+        || profile
+            .approval_policy
+            .is_some_and(|policy| approval_rank(policy) > approval_rank(base_approval))
+}
+
+fn collect_relaxed_keys(
+    below: &TomlValue,
+    project: &TomlValue,
+    prefix: &[&str],
+    keys: &mut Vec<String>,
+) {
+    let dotted = |key: &str| {
+        prefix
+            .iter()
+            .copied()
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+
+    if let Some(mode) = parse::<SandboxMode>(project.get("sandbox_mode")) {
+        let base =
+            parse::<SandboxMode>(below.get("sandbox_mode")).unwrap_or(SandboxMode::WorkspaceWrite);
+        if sandbox_rank(mode) > sandbox_rank(base) {
+            keys.push(dotted("sandbox_mode"));
+        }
+    }
+
+    if let Some(policy) = parse::<AskForApproval>(project.get("approval_policy")) {
+        let base = parse::<AskForApproval>(below.get("approval_policy"))
+            .unwrap_or(AskForApproval::OnRequest);
+        if approval_rank(policy) > approval_rank(base) {
+            keys.push(dotted("approval_policy"));
+        }
+    }
+
+    let network_access = |config: &TomlValue| {
+        config
+            .get("sandbox_workspace_write")
+            .and_then(|table| table.get("network_access"))
+            .and_then(TomlValue::as_bool)
+    };
+    if network_access(project) == Some(true) && network_access(below) != Some(true) {
+        keys.push(dotted("sandbox_workspace_write.network_access"));
+    }
+
+    let writable_roots = |config: &TomlValue| {
+        config
+            .get("sandbox_workspace_write")
+            .and_then(|table| table.get("writable_roots"))
+            .and_then(TomlValue::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let below_roots = writable_roots(below);
+    if writable_roots(project)
+        .iter()
+        .any(|root| !below_roots.contains(root))
+    {
+        keys.push(dotted("sandbox_workspace_write.writable_roots"));
+    }
+
+    let hooks_sandbox = |config: &TomlValue| {
+        config
+            .get("hooks")
+            .and_then(|table| table.get("sandbox"))
+            .and_then(TomlValue::as_bool)
+    };
+    if hooks_sandbox(project) == Some(false) && hooks_sandbox(below) != Some(false) {
+        keys.push(dotted("hooks.sandbox"));
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: Option<&TomlValue>) -> Option<T> {
+    value.and_then(|value| value.clone().try_into().ok())
+}
+
+pub(crate) fn sandbox_rank(mode: SandboxMode) -> u8 {
+    match mode {
+        SandboxMode::ReadOnly => 0,
+        SandboxMode::WorkspaceWrite => 1,
+        SandboxMode::DangerFullAccess => 2,
+    }
+}
+
+pub(crate) fn approval_rank(policy: AskForApproval) -> u8 {
+    match policy {
+        AskForApproval::UnlessTrusted => 0,
+        AskForApproval::OnFailure | AskForApproval::OnRequest => 1,
+        AskForApproval::Never => 2,
+    }
+}
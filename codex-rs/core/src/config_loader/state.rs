@@ -1,3 +1,4 @@
+use crate::config::CONFIG_TOML_FILE;
 use crate::config_loader::ConfigRequirements;
 use crate::config_loader::ConfigRequirementsToml;
 
@@ -90,6 +91,25 @@ impl ConfigLayerEntry {
     }
 }
 
+/// Short human-readable description of a layer source, e.g.
+/// `project /repo/.codex/config.toml`, for surfacing config provenance.
+pub fn describe_layer_source(source: &ConfigLayerSource) -> String {
+    match source {
+        ConfigLayerSource::Mdm { domain, key } => format!("mdm {domain}:{key}"),
+        ConfigLayerSource::System { file } => format!("system {}", file.as_path().display()),
+        ConfigLayerSource::User { file } => format!("user {}", file.as_path().display()),
+        ConfigLayerSource::Project { dot_codex_folder } => format!(
+            "project {}",
+            dot_codex_folder.as_path().join(CONFIG_TOML_FILE).display()
+        ),
+        ConfigLayerSource::SessionFlags => "session flags".to_string(),
+        ConfigLayerSource::LegacyManagedConfigTomlFromFile { file } => {
+            format!("managed {}", file.as_path().display())
+        }
+        ConfigLayerSource::LegacyManagedConfigTomlFromMdm => "managed config (mdm)".to_string(),
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigLayerStackOrdering {
     LowestPrecedenceFirst,
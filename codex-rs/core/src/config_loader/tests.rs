@@ -121,10 +121,9 @@ async fn returns_config_error_for_schema_error_in_user_config() {
 
     let config_error = config_error_from_io(&err);
     let _guard = codex_utils_absolute_path::AbsolutePathBufGuard::new(tmp.path());
-    let expected_config_error =
-        super::diagnostics::config_error_from_config_toml(&config_path, contents)
-            .expect("schema error");
-    assert_eq!(config_error, &expected_config_error);
+    let expected_config_errors =
+        super::diagnostics::config_errors_from_config_toml(&config_path, contents);
+    assert_eq!(config_error, &expected_config_errors[0]);
 }
 
 #[test]
@@ -135,7 +134,9 @@ fn schema_error_points_to_feature_value() {
     std::fs::write(&config_path, contents).expect("write config");
 
     let _guard = codex_utils_absolute_path::AbsolutePathBufGuard::new(tmp.path());
-    let error = super::diagnostics::config_error_from_config_toml(&config_path, contents)
+    let error = super::diagnostics::config_errors_from_config_toml(&config_path, contents)
+        .into_iter()
+        .next()
         .expect("schema error");
 
     let value_line = contents.lines().nth(1).expect("value line");
@@ -144,6 +145,99 @@ fn schema_error_points_to_feature_value() {
     assert_eq!(error.range.start.column, value_column);
 }
 
+#[tokio::test]
+async fn reports_every_schema_error_in_one_pass() {
+    let tmp = tempdir().expect("tempdir");
+    let contents =
+        "model_context_window = \"big\"\nmodel = \"gpt-5.1\"\napproval_policy = \"nevr\"\n";
+    let config_path = tmp.path().join(CONFIG_TOML_FILE);
+    std::fs::write(&config_path, contents).expect("write config");
+
+    let err = ConfigBuilder::default()
+        .codex_home(tmp.path().to_path_buf())
+        .fallback_cwd(Some(tmp.path().to_path_buf()))
+        .build()
+        .await
+        .expect_err("expected error");
+
+    let load_error = err
+        .get_ref()
+        .and_then(|err| err.downcast_ref::<ConfigLoadError>())
+        .expect("expected ConfigLoadError");
+    let errors: Vec<_> = load_error.config_errors().collect();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        (errors[0].range.start.line, errors[0].range.start.column),
+        (1, 24)
+    );
+    assert!(
+        errors[0].message.starts_with("`model_context_window`: "),
+        "{}",
+        errors[0].message
+    );
+    assert_eq!(
+        (errors[1].range.start.line, errors[1].range.start.column),
+        (3, 19)
+    );
+    assert!(
+        errors[1].message.starts_with("`approval_policy`: "),
+        "{}",
+        errors[1].message
+    );
+}
+
+#[test]
+fn warns_about_unknown_keys_with_suggestions() {
+    let contents = r#"approval_polcy = "never"
+
+[profiles.fast]
+modle = "gpt-5.1"
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.docs.env]
+ANY_NAME = "allowed"
+
+[shell]
+kind = "custom"
+argv = ["/bin/bash", "-c", "{command}"]
+
+[tui]
+not_a_setting_at_all = true
+"#;
+    let config_path = Path::new("/tmp/config.toml");
+
+    let warnings = super::unknown_keys::unknown_keys_in_config_toml(config_path, contents);
+
+    let summary: Vec<(usize, usize, &str)> = warnings
+        .iter()
+        .map(|warning| {
+            (
+                warning.range.start.line,
+                warning.range.start.column,
+                warning.message.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                1,
+                1,
+                "unknown config key `approval_polcy`; did you mean `approval_policy`?"
+            ),
+            (
+                4,
+                1,
+                "unknown config key `profiles.fast.modle`; did you mean `model`?"
+            ),
+            (17, 1, "unknown config key `tui.not_a_setting_at_all`"),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn merges_managed_config_layer_on_top() {
     let tmp = tempdir().expect("tempdir");
@@ -911,3 +1005,202 @@ async fn project_root_markers_supports_alternate_markers() -> std::io::Result<()
 
     Ok(())
 }
+
+#[tokio::test]
+async fn trusted_project_layer_relaxing_policy_requires_opt_in() -> std::io::Result<()> {
+    let tmp = tempdir()?;
+    let project_root = tmp.path().join("project");
+    tokio::fs::create_dir_all(project_root.join(".codex")).await?;
+    tokio::fs::write(project_root.join(".git"), "gitdir: here").await?;
+    tokio::fs::write(
+        project_root.join(".codex").join(CONFIG_TOML_FILE),
+        r#"
+model = "project-model"
+sandbox_mode = "danger-full-access"
+approval_policy = "on-request"
+"#,
+    )
+    .await?;
+
+    let codex_home = tmp.path().join("home");
+    tokio::fs::create_dir_all(&codex_home).await?;
+    make_config_for_test(&codex_home, &project_root, TrustLevel::Trusted, None).await?;
+    let user_config_path = codex_home.join(CONFIG_TOML_FILE);
+    let user_config_contents = tokio::fs::read_to_string(&user_config_path).await?;
+    tokio::fs::write(
+        &user_config_path,
+        format!("model = \"user-model\"\n{user_config_contents}"),
+    )
+    .await?;
+
+    let cwd = AbsolutePathBuf::from_absolute_path(&project_root)?;
+    let layers = load_config_layers_state(
+        &codex_home,
+        Some(cwd.clone()),
+        &[] as &[(String, TomlValue)],
+        LoaderOverrides::default(),
+    )
+    .await?;
+    let project_layers: Vec<_> = layers
+        .get_layers(
+            super::ConfigLayerStackOrdering::HighestPrecedenceFirst,
+            true,
+        )
+        .into_iter()
+        .filter(|layer| matches!(layer.name, super::ConfigLayerSource::Project { .. }))
+        .collect();
+    assert_eq!(project_layers.len(), 1);
+    let reason = project_layers[0]
+        .disabled_reason
+        .as_deref()
+        .expect("expected relaxing project layer to be disabled");
+    assert!(reason.contains("sandbox_mode"), "{reason}");
+    assert!(!reason.contains("approval_policy"), "{reason}");
+    assert_eq!(
+        layers.effective_config().get("model"),
+        Some(&TomlValue::String("user-model".to_string()))
+    );
+
+    tokio::fs::write(
+        &user_config_path,
+        format!("model = \"user-model\"\n{user_config_contents}allow_policy_overrides = true\n"),
+    )
+    .await?;
+    let layers = load_config_layers_state(
+        &codex_home,
+        Some(cwd),
+        &[] as &[(String, TomlValue)],
+        LoaderOverrides::default(),
+    )
+    .await?;
+    let merged = layers.effective_config();
+    assert_eq!(
+        merged.get("model"),
+        Some(&TomlValue::String("project-model".to_string()))
+    );
+    assert_eq!(
+        merged.get("sandbox_mode"),
+        Some(&TomlValue::String("danger-full-access".to_string()))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn relaxed_policy_keys_compares_against_lower_layers() {
+    let below: TomlValue = toml::from_str(
+        r#"
+sandbox_mode = "danger-full-access"
+
+[profiles.strict]
+approval_policy = "untrusted"
+"#,
+    )
+    .expect("parse below");
+    let project: TomlValue = toml::from_str(
+        r#"
+sandbox_mode = "workspace-write"
+approval_policy = "never"
+
+[sandbox_workspace_write]
+network_access = true
+
+[hooks]
+sandbox = false
+
+[profiles.strict]
+approval_policy = "on-request"
+"#,
+    )
+    .expect("parse project");
+
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &project),
+        vec![
+            "approval_policy".to_string(),
+            "sandbox_workspace_write.network_access".to_string(),
+            "hooks.sandbox".to_string(),
+            "profiles.strict.approval_policy".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn relaxed_policy_keys_flags_new_writable_roots() {
+    let below: TomlValue = toml::from_str(
+        r#"
+[sandbox_workspace_write]
+writable_roots = ["/home/user/.cache"]
+"#,
+    )
+    .expect("parse below");
+    let kept: TomlValue = toml::from_str(
+        r#"
+[sandbox_workspace_write]
+writable_roots = ["/home/user/.cache"]
+"#,
+    )
+    .expect("parse kept");
+    let widened: TomlValue = toml::from_str(
+        r#"
+[sandbox_workspace_write]
+writable_roots = ["/home/user/.cache", "/"]
+"#,
+    )
+    .expect("parse widened");
+
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &kept),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &widened),
+        vec!["sandbox_workspace_write.writable_roots".to_string()]
+    );
+}
+
+#[test]
+fn relaxed_policy_keys_resolves_the_profile_a_project_selects() {
+    let below: TomlValue = toml::from_str(
+        r#"
+approval_policy = "on-request"
+
+[profiles.yolo]
+sandbox_mode = "danger-full-access"
+
+[profiles.unattended]
+extends = ["yolo"]
+approval_policy = "never"
+
+[profiles.careful]
+approval_policy = "untrusted"
+"#,
+    )
+    .expect("parse below");
+    let selects_unattended: TomlValue =
+        toml::from_str(r#"profile = "unattended""#).expect("parse project");
+    let selects_careful: TomlValue =
+        toml::from_str(r#"profile = "careful""#).expect("parse project");
+    let extends_yolo: TomlValue = toml::from_str(
+        r#"
+profile = "careful"
+
+[profiles.careful]
+extends = ["yolo"]
+"#,
+    )
+    .expect("parse project");
+
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &selects_unattended),
+        vec!["profile".to_string()]
+    );
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &selects_careful),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        super::project_policy::relaxed_policy_keys(&below, &extends_yolo),
+        vec!["profile".to_string()]
+    );
+}
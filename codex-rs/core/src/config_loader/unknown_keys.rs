@@ -0,0 +1,241 @@
+//! Finds keys in a `config.toml` that Codex does not recognize by walking the
+//! document alongside the generated config schema, and suggests the closest
+//! known key for each.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use serde_json::Value as JsonValue;
+use toml_edit::Document;
+use toml_edit::Item;
+use toml_edit::TableLike;
+use toml_edit::Value;
+
+use super::diagnostics::ConfigError;
+use super::diagnostics::text_range_from_span;
+
+static CONFIG_SCHEMA: LazyLock<JsonValue> = LazyLock::new(|| {
+    serde_json::to_value(crate::config::schema::config_schema()).unwrap_or_default()
+});
+
+/// Keys that are still accepted but left out of the schema: serde aliases
+/// and deprecated keys, which get their own notice.
+const UNLISTED_KEYS: &[&str] = &[
+    "experimental_instructions_file",
+    "ignore_untracked_files_over_bytes",
+    "large_untracked_dir_warning_threshold",
+    "web_search_request",
+];
+
+/// Returns a warning for every unknown key in `contents`. Keys under tables
+/// whose names are free-form (`mcp_servers`, `env`, `profiles`, ...) are only
+/// checked below the free-form level. Invalid TOML yields no warnings; the
+/// parse error is reported on its own.
+pub(crate) fn unknown_keys_in_config_toml(path: &Path, contents: &str) -> Vec<ConfigError> {
+    let Ok(document) = contents.parse::<Document<String>>() else {
+        return Vec::new();
+    };
+    let Some(root) = document.as_item().as_table_like() else {
+        return Vec::new();
+    };
+
+    let mut walker = SchemaWalker {
+        definitions: CONFIG_SCHEMA.get("definitions"),
+        path,
+        contents,
+        warnings: Vec::new(),
+    };
+    walker.check_table(root, &CONFIG_SCHEMA, &mut Vec::new());
+    walker.warnings
+}
+
+/// Keys an object schema accepts, merged across `allOf`/`anyOf`/`oneOf`.
+#[derive(Default)]
+struct ObjectShape<'s> {
+    properties: BTreeMap<&'s str, &'s JsonValue>,
+    additional_schema: Option<&'s JsonValue>,
+    allows_any_key: bool,
+    is_object: bool,
+}
+
+struct SchemaWalker<'a> {
+    definitions: Option<&'a JsonValue>,
+    path: &'a Path,
+    contents: &'a str,
+    warnings: Vec<ConfigError>,
+}
+
+impl<'a> SchemaWalker<'a> {
+    fn check_table(
+        &mut self,
+        table: &dyn TableLike,
+        schema: &'a JsonValue,
+        key_path: &mut Vec<String>,
+    ) {
+        let mut shape = ObjectShape::default();
+        self.collect_shape(schema, &mut shape);
+        // Values that are not tables in the schema are type errors, which
+        // deserialization reports.
+        if !shape.is_object {
+            return;
+        }
+
+        for (key, item) in table.iter() {
+            let child_schema = match shape.properties.get(key) {
+                Some(child_schema) => *child_schema,
+                None => match shape.additional_schema {
+                    Some(additional_schema) => additional_schema,
+                    None if shape.allows_any_key || UNLISTED_KEYS.contains(&key) => continue,
+                    None => {
+                        self.warn_unknown_key(table, key, key_path, &shape);
+                        continue;
+                    }
+                },
+            };
+
+            key_path.push(key.to_string());
+            self.check_item(item, child_schema, key_path);
+            key_path.pop();
+        }
+    }
+
+    fn check_item(&mut self, item: &Item, schema: &'a JsonValue, key_path: &mut Vec<String>) {
+        match item {
+            Item::Table(table) => self.check_table(table, schema, key_path),
+            Item::Value(Value::InlineTable(table)) => self.check_table(table, schema, key_path),
+            Item::ArrayOfTables(array) => {
+                if let Some(items_schema) = self.resolve(schema).get("items") {
+                    for table in array.iter() {
+                        self.check_table(table, items_schema, key_path);
+                    }
+                }
+            }
+            Item::Value(Value::Array(array)) => {
+                if let Some(items_schema) = self.resolve(schema).get("items") {
+                    for table in array.iter().filter_map(Value::as_inline_table) {
+                        self.check_table(table, items_schema, key_path);
+                    }
+                }
+            }
+            Item::Value(_) | Item::None => {}
+        }
+    }
+
+    fn collect_shape(&self, schema: &'a JsonValue, shape: &mut ObjectShape<'a>) {
+        let schema = self.resolve(schema);
+        let properties = schema.get("properties").and_then(JsonValue::as_object);
+        if let Some(properties) = properties {
+            shape.is_object = true;
+            shape
+                .properties
+                .extend(properties.iter().map(|(key, value)| (key.as_str(), value)));
+        }
+        match schema.get("additionalProperties") {
+            Some(JsonValue::Bool(false)) => shape.is_object = true,
+            Some(JsonValue::Bool(true)) => {
+                shape.is_object = true;
+                shape.allows_any_key = true;
+            }
+            Some(additional_schema) => {
+                shape.is_object = true;
+                shape.additional_schema = Some(additional_schema);
+            }
+            None => {
+                if properties.is_some()
+                    || schema.get("type").and_then(JsonValue::as_str) == Some("object")
+                {
+                    shape.is_object = true;
+                    shape.allows_any_key = true;
+                }
+            }
+        }
+        for combinator in ["allOf", "anyOf", "oneOf"] {
+            for branch in schema
+                .get(combinator)
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+            {
+                self.collect_shape(branch, shape);
+            }
+        }
+    }
+
+    fn resolve(&self, schema: &'a JsonValue) -> &'a JsonValue {
+        schema
+            .get("$ref")
+            .and_then(JsonValue::as_str)
+            .and_then(|reference| reference.strip_prefix("#/definitions/"))
+            .and_then(|name| self.definitions?.get(name))
+            .unwrap_or(schema)
+    }
+
+    fn warn_unknown_key(
+        &mut self,
+        table: &dyn TableLike,
+        key: &str,
+        key_path: &[String],
+        shape: &ObjectShape<'a>,
+    ) {
+        let Some(span) = table
+            .get_key_value(key)
+            .and_then(|(toml_key, _)| toml_key.span())
+        else {
+            return;
+        };
+        let dotted_key = key_path
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(".");
+        let mut message = format!("unknown config key `{dotted_key}`");
+        if let Some(suggestion) = closest_key(key, shape.properties.keys().copied()) {
+            message.push_str(&format!("; did you mean `{suggestion}`?"));
+        }
+        self.warnings.push(ConfigError::new(
+            self.path.to_path_buf(),
+            text_range_from_span(self.contents, span),
+            message,
+        ));
+    }
+}
+
+/// Returns the candidate closest to `key` by edit distance, if it is close
+/// enough to be a likely typo.
+fn closest_key<'s>(key: &str, candidates: impl Iterator<Item = &'s str>) -> Option<&'s str> {
+    let max_distance = (key.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance that counts swapping two adjacent characters as one edit,
+/// since that is a common typo (`modle` for `model`).
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let mut rows = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[left.len()][right.len()]
+}
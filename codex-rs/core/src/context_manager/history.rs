@@ -63,7 +63,7 @@ impl ContextManager {
                 continue;
             }
 
-            let processed = self.process_item(item_ref, policy);
+            let processed = truncate_for_history(item_ref, policy);
             self.items.push(processed);
         }
     }
@@ -199,12 +199,29 @@ impl ContextManager {
         &mut self,
         usage: &TokenUsage,
         model_context_window: Option<i64>,
+        provider_id: &str,
     ) {
         self.token_info = TokenUsageInfo::new_or_append(
             &self.token_info,
             &Some(usage.clone()),
             model_context_window,
         );
+        if let Some(info) = self.token_info.as_mut() {
+            info.attribute_to_provider(provider_id, usage);
+        }
+    }
+
+    /// Adds the estimated tokens of one tool output to the session's total.
+    pub(crate) fn record_tool_output_tokens(&mut self, tokens: u64) {
+        let info = self.token_info.get_or_insert_with(|| TokenUsageInfo {
+            total_token_usage: TokenUsage::default(),
+            last_token_usage: TokenUsage::default(),
+            model_context_window: None,
+            by_provider: Default::default(),
+            prompt_cache: Default::default(),
+            tool_output_tokens: 0,
+        });
+        info.tool_output_tokens = info.tool_output_tokens.saturating_add(tokens);
     }
 
     fn get_non_last_reasoning_items_tokens(&self) -> usize {
@@ -264,45 +281,43 @@ impl ContextManager {
         // all outputs must have a corresponding function/tool call
         normalize::remove_orphan_outputs(&mut self.items);
     }
+}
 
-    fn process_item(&self, item: &ResponseItem, policy: TruncationPolicy) -> ResponseItem {
-        let policy_with_serialization_budget = policy.mul(1.2);
-        match item {
-            ResponseItem::FunctionCallOutput { call_id, output } => {
-                let truncated =
-                    truncate_text(output.content.as_str(), policy_with_serialization_budget);
-                let truncated_items = output.content_items.as_ref().map(|items| {
-                    truncate_function_output_items_with_policy(
-                        items,
-                        policy_with_serialization_budget,
-                    )
-                });
-                ResponseItem::FunctionCallOutput {
-                    call_id: call_id.clone(),
-                    output: FunctionCallOutputPayload {
-                        content: truncated,
-                        content_items: truncated_items,
-                        success: output.success,
-                    },
-                }
+/// `item` as the history keeps it, with tool output truncated to `policy`.
+pub(crate) fn truncate_for_history(item: &ResponseItem, policy: TruncationPolicy) -> ResponseItem {
+    let policy_with_serialization_budget = policy.mul(1.2);
+    match item {
+        ResponseItem::FunctionCallOutput { call_id, output } => {
+            let truncated =
+                truncate_text(output.content.as_str(), policy_with_serialization_budget);
+            let truncated_items = output.content_items.as_ref().map(|items| {
+                truncate_function_output_items_with_policy(items, policy_with_serialization_budget)
+            });
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output: FunctionCallOutputPayload {
+                    content: truncated,
+                    content_items: truncated_items,
+                    success: output.success,
+                },
             }
-            ResponseItem::CustomToolCallOutput { call_id, output } => {
-                let truncated = truncate_text(output, policy_with_serialization_budget);
-                ResponseItem::CustomToolCallOutput {
-                    call_id: call_id.clone(),
-                    output: truncated,
-                }
+        }
+        ResponseItem::CustomToolCallOutput { call_id, output } => {
+            let truncated = truncate_text(output, policy_with_serialization_budget);
+            ResponseItem::CustomToolCallOutput {
+                call_id: call_id.clone(),
+                output: truncated,
             }
-            ResponseItem::Message { .. }
-            | ResponseItem::Reasoning { .. }
-            | ResponseItem::LocalShellCall { .. }
-            | ResponseItem::FunctionCall { .. }
-            | ResponseItem::WebSearchCall { .. }
-            | ResponseItem::CustomToolCall { .. }
-            | ResponseItem::Compaction { .. }
-            | ResponseItem::GhostSnapshot { .. }
-            | ResponseItem::Other => item.clone(),
         }
+        ResponseItem::Message { .. }
+        | ResponseItem::Reasoning { .. }
+        | ResponseItem::LocalShellCall { .. }
+        | ResponseItem::FunctionCall { .. }
+        | ResponseItem::WebSearchCall { .. }
+        | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::Compaction { .. }
+        | ResponseItem::GhostSnapshot { .. }
+        | ResponseItem::Other => item.clone(),
     }
 }
 
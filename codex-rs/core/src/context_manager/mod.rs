@@ -3,3 +3,4 @@ mod normalize;
 
 pub(crate) use history::ContextManager;
 pub(crate) use history::is_user_turn_boundary;
+pub(crate) use history::truncate_for_history;
@@ -0,0 +1,268 @@
+//! A small API for driving a session from Rust.
+//!
+//! [`Conversation`] wraps a [`ThreadManager`] and one [`CodexThread`]:
+//! [`Conversation::submit`] sends user input and returns the turn's events
+//! as a stream that ends with the turn, and approvals, interrupts and
+//! shutdown are plain method calls. Tools can be implemented in process
+//! with [`InProcessTool`]; the model sees them like any dynamic tool and
+//! their calls are reported as `McpToolCallBegin`/`McpToolCallEnd` from the
+//! server [`IN_PROCESS_TOOL_SERVER`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+use codex_protocol::ThreadId;
+use codex_protocol::dynamic_tools::DynamicToolSpec;
+use codex_protocol::protocol::SessionSource;
+use codex_protocol::user_input::UserInput;
+use futures::FutureExt;
+use futures::Stream;
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+use crate::AuthManager;
+use crate::codex_thread::CodexThread;
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::protocol::EventMsg;
+use crate::protocol::Op;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SessionConfiguredEvent;
+use crate::thread_manager::NewThread;
+use crate::thread_manager::ThreadManager;
+
+/// Server name reported in the MCP tool call events of in-process tools.
+pub const IN_PROCESS_TOOL_SERVER: &str = "in_process";
+
+type InProcessToolFn =
+    Arc<dyn Fn(Value) -> BoxFuture<'static, Result<String, String>> + Send + Sync>;
+
+/// A tool offered to the model and run by a closure in this process.
+///
+/// The closure gets the call's arguments. `Ok` text goes back to the model
+/// as a successful result and `Err` text as a failed one.
+#[derive(Clone)]
+pub struct InProcessTool {
+    pub(crate) spec: DynamicToolSpec,
+    handler: InProcessToolFn,
+}
+
+impl InProcessTool {
+    pub fn new<F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        Self {
+            spec: DynamicToolSpec {
+                name: name.into(),
+                description: description.into(),
+                input_schema,
+            },
+            handler: Arc::new(move |arguments| handler(arguments).boxed()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    pub(crate) async fn call(&self, arguments: Value) -> Result<String, String> {
+        (self.handler)(arguments).await
+    }
+}
+
+impl std::fmt::Debug for InProcessTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InProcessTool")
+            .field("spec", &self.spec)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ApprovalKind {
+    Exec,
+    Patch,
+}
+
+/// An approval request seen in a turn's events, by call id.
+#[derive(Debug, Clone)]
+struct PendingApproval {
+    /// Id of the event that asked; `Op::ExecApproval` and
+    /// `Op::PatchApproval` answer by it.
+    event_id: String,
+    kind: ApprovalKind,
+}
+
+type PendingApprovals = Arc<Mutex<HashMap<String, PendingApproval>>>;
+
+/// One session driven from Rust. See the module docs.
+pub struct Conversation {
+    manager: Arc<ThreadManager>,
+    thread_id: ThreadId,
+    thread: Arc<CodexThread>,
+    session_configured: SessionConfiguredEvent,
+    approvals: PendingApprovals,
+}
+
+impl Conversation {
+    /// Starts a session for `config`, signed in with the credentials stored
+    /// under `config.codex_home`.
+    pub async fn start(config: Config) -> CodexResult<Self> {
+        Self::start_with_tools(config, Vec::new()).await
+    }
+
+    /// Like [`Self::start`], offering the model `tools` as well.
+    pub async fn start_with_tools(config: Config, tools: Vec<InProcessTool>) -> CodexResult<Self> {
+        let auth_manager = AuthManager::shared(
+            config.codex_home.clone(),
+            true,
+            config.cli_auth_credentials_store_mode,
+        );
+        let manager =
+            ThreadManager::new(config.codex_home.clone(), auth_manager, SessionSource::Exec);
+        Self::start_in(Arc::new(manager), config, tools).await
+    }
+
+    /// Starts a session on `manager`, for embedders that run several
+    /// sessions or bring their own authentication.
+    pub async fn start_in(
+        manager: Arc<ThreadManager>,
+        config: Config,
+        tools: Vec<InProcessTool>,
+    ) -> CodexResult<Self> {
+        let NewThread {
+            thread_id,
+            thread,
+            session_configured,
+        } = manager
+            .start_thread_with_in_process_tools(config, tools)
+            .await?;
+        Ok(Self {
+            manager,
+            thread_id,
+            thread,
+            session_configured,
+            approvals: Arc::default(),
+        })
+    }
+
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    pub fn session_configured(&self) -> &SessionConfiguredEvent {
+        &self.session_configured
+    }
+
+    /// The underlying thread, for operations this API does not cover.
+    pub fn thread(&self) -> &Arc<CodexThread> {
+        &self.thread
+    }
+
+    /// Starts a turn with `input` and returns its events. The stream ends
+    /// after `TurnComplete` or `TurnAborted`, or when the session goes away.
+    /// Poll one turn's stream to its end before submitting the next.
+    pub async fn submit(
+        &self,
+        input: Vec<UserInput>,
+    ) -> CodexResult<impl Stream<Item = EventMsg> + Send + 'static> {
+        self.thread
+            .submit(Op::UserInput {
+                items: input,
+                final_output_json_schema: None,
+            })
+            .await?;
+        let thread = Arc::clone(&self.thread);
+        let approvals = Arc::clone(&self.approvals);
+        Ok(futures::stream::unfold(false, move |done| {
+            let thread = Arc::clone(&thread);
+            let approvals = Arc::clone(&approvals);
+            async move {
+                if done {
+                    return None;
+                }
+                let event = thread.next_event().await.ok()?;
+                let pending = match &event.msg {
+                    EventMsg::ExecApprovalRequest(request) => {
+                        Some((request.call_id.clone(), ApprovalKind::Exec))
+                    }
+                    EventMsg::ApplyPatchApprovalRequest(request) => {
+                        Some((request.call_id.clone(), ApprovalKind::Patch))
+                    }
+                    _ => None,
+                };
+                if let Some((call_id, kind)) = pending {
+                    let approval = PendingApproval {
+                        event_id: event.id.clone(),
+                        kind,
+                    };
+                    lock(&approvals).insert(call_id, approval);
+                }
+                let done = matches!(
+                    event.msg,
+                    EventMsg::TurnComplete(_)
+                        | EventMsg::TurnAborted(_)
+                        | EventMsg::ShutdownComplete
+                );
+                Some((event.msg, done))
+            }
+        }))
+    }
+
+    /// Answers the approval request of call `call_id` from the current turn.
+    pub async fn respond_to_approval(
+        &self,
+        call_id: &str,
+        decision: ReviewDecision,
+    ) -> CodexResult<()> {
+        let Some(PendingApproval { event_id, kind }) = lock(&self.approvals).remove(call_id) else {
+            return Err(CodexErr::InvalidRequest(format!(
+                "no approval request is pending for call {call_id}"
+            )));
+        };
+        let op = match kind {
+            ApprovalKind::Exec => Op::ExecApproval {
+                id: event_id,
+                decision,
+            },
+            ApprovalKind::Patch => Op::PatchApproval {
+                id: event_id,
+                decision,
+            },
+        };
+        self.thread.submit(op).await.map(drop)
+    }
+
+    /// Interrupts the current turn; its stream then ends with `TurnAborted`.
+    pub async fn interrupt(&self) -> CodexResult<()> {
+        self.thread.submit(Op::Interrupt).await.map(drop)
+    }
+
+    /// Shuts the session down and waits for it to finish.
+    pub async fn shutdown(self) -> CodexResult<()> {
+        self.thread.submit(Op::Shutdown).await?;
+        while let Ok(event) = self.thread.next_event().await {
+            if matches!(event.msg, EventMsg::ShutdownComplete) {
+                break;
+            }
+        }
+        self.manager.remove_thread(&self.thread_id).await;
+        Ok(())
+    }
+}
+
+fn lock(approvals: &PendingApprovals) -> MutexGuard<'_, HashMap<String, PendingApproval>> {
+    approvals.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,76 @@
+//! Totals for a set of file changes, shared by the session pickers and
+//! `codex exec --output-diff`.
+
+use serde::Serialize;
+
+/// Files and lines a change touches.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl DiffStats {
+    pub fn is_empty(&self) -> bool {
+        self.files_changed == 0
+    }
+
+    /// Counts a unified diff such as the one in a `TurnDiff` event. Only lines
+    /// inside hunks count, so `---`/`+++` file headers are left out.
+    pub fn from_unified_diff(unified_diff: &str) -> Self {
+        let mut stats = Self::default();
+        let mut in_hunk = false;
+        for line in unified_diff.lines() {
+            if line.starts_with("diff --git ") {
+                stats.files_changed += 1;
+                in_hunk = false;
+            } else if line.starts_with("@@") {
+                in_hunk = true;
+            } else if in_hunk && line.starts_with('+') {
+                stats.lines_added += 1;
+            } else if in_hunk && line.starts_with('-') {
+                stats.lines_removed += 1;
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn counts_files_and_changed_lines_of_a_unified_diff() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old();
++    new();
++    ++counter;
+ }
+diff --git a/README.md b/README.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1 @@
++--- a heading underline
+";
+
+        assert_eq!(
+            DiffStats::from_unified_diff(diff),
+            DiffStats {
+                files_changed: 2,
+                lines_added: 3,
+                lines_removed: 1,
+            }
+        );
+    }
+}
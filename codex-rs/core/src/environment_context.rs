@@ -4,6 +4,7 @@ use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::ENVIRONMENT_CONTEXT_CLOSE_TAG;
 use codex_protocol::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
+use codex_protocol::protocol::WorkspaceRoot;
 use serde::Deserialize;
 use serde::Serialize;
 use std::path::PathBuf;
@@ -13,11 +14,17 @@ use std::path::PathBuf;
 pub(crate) struct EnvironmentContext {
     pub cwd: Option<PathBuf>,
     pub shell: Shell,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspace_roots: Vec<WorkspaceRoot>,
 }
 
 impl EnvironmentContext {
     pub fn new(cwd: Option<PathBuf>, shell: Shell) -> Self {
-        Self { cwd, shell }
+        Self {
+            cwd,
+            shell,
+            workspace_roots: Vec::new(),
+        }
     }
 
     /// Compares two environment contexts, ignoring the shell. Useful when
@@ -28,9 +35,10 @@ impl EnvironmentContext {
             cwd,
             // should compare all fields except shell
             shell: _,
+            workspace_roots,
         } = other;
 
-        self.cwd == *cwd
+        self.cwd == *cwd && self.workspace_roots == *workspace_roots
     }
 
     pub fn diff(before: &TurnContext, after: &TurnContext, shell: &Shell) -> Self {
@@ -39,11 +47,23 @@ impl EnvironmentContext {
         } else {
             None
         };
-        EnvironmentContext::new(cwd, shell.clone())
+        // Roots are listed in full whenever any of them changed.
+        let workspace_roots = if before.workspace_roots != after.workspace_roots {
+            after.workspace_roots.clone()
+        } else {
+            Vec::new()
+        };
+        Self {
+            workspace_roots,
+            ..EnvironmentContext::new(cwd, shell.clone())
+        }
     }
 
     pub fn from_turn_context(turn_context: &TurnContext, shell: &Shell) -> Self {
-        Self::new(Some(turn_context.cwd.clone()), shell.clone())
+        Self {
+            workspace_roots: turn_context.workspace_roots.clone(),
+            ..Self::new(Some(turn_context.cwd.clone()), shell.clone())
+        }
     }
 }
 
@@ -55,6 +75,9 @@ impl EnvironmentContext {
     /// ```xml
     /// <environment_context>
     ///   <cwd>...</cwd>
+    ///   <workspace_roots>
+    ///     <root name="...">...</root>
+    ///   </workspace_roots>
     ///   <shell>...</shell>
     /// </environment_context>
     /// ```
@@ -63,6 +86,17 @@ impl EnvironmentContext {
         if let Some(cwd) = self.cwd {
             lines.push(format!("  <cwd>{}</cwd>", cwd.to_string_lossy()));
         }
+        if !self.workspace_roots.is_empty() {
+            lines.push("  <workspace_roots>".to_string());
+            for root in &self.workspace_roots {
+                lines.push(format!(
+                    "    <root name=\"{}\">{}</root>",
+                    root.name,
+                    root.path.to_string_lossy()
+                ));
+            }
+            lines.push("  </workspace_roots>".to_string());
+        }
 
         let shell_name = self.shell.name();
         lines.push(format!("  <shell>{shell_name}</shell>"));
@@ -97,6 +131,7 @@ mod tests {
             shell_type: ShellType::Bash,
             shell_path: PathBuf::from("/bin/bash"),
             shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+            argv_template: None,
         }
     }
 
@@ -116,6 +151,34 @@ mod tests {
         assert_eq!(context.serialize_to_xml(), expected);
     }
 
+    #[test]
+    fn serialize_environment_context_with_workspace_roots() {
+        let cwd = test_path_buf("/work/web");
+        let api = test_path_buf("/work/api");
+        let context = EnvironmentContext {
+            workspace_roots: vec![WorkspaceRoot {
+                name: "api".to_string(),
+                path: codex_utils_absolute_path::AbsolutePathBuf::from_absolute_path(&api)
+                    .expect("absolute path"),
+            }],
+            ..EnvironmentContext::new(Some(cwd.clone()), fake_shell())
+        };
+
+        let expected = format!(
+            r#"<environment_context>
+  <cwd>{cwd}</cwd>
+  <workspace_roots>
+    <root name="api">{api}</root>
+  </workspace_roots>
+  <shell>bash</shell>
+</environment_context>"#,
+            cwd = cwd.display(),
+            api = api.display(),
+        );
+
+        assert_eq!(context.serialize_to_xml(), expected);
+    }
+
     #[test]
     fn serialize_read_only_environment_context() {
         let context = EnvironmentContext::new(None, fake_shell());
@@ -191,6 +254,7 @@ mod tests {
                 shell_type: ShellType::Bash,
                 shell_path: "/bin/bash".into(),
                 shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+                argv_template: None,
             },
         );
         let context2 = EnvironmentContext::new(
@@ -199,6 +263,7 @@ mod tests {
                 shell_type: ShellType::Zsh,
                 shell_path: "/bin/zsh".into(),
                 shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+                argv_template: None,
             },
         );
 
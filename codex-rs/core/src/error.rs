@@ -12,6 +12,7 @@ use codex_protocol::ThreadId;
 use codex_protocol::protocol::CodexErrorInfo;
 use codex_protocol::protocol::ErrorEvent;
 use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::SpawnErrorKind;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
@@ -93,6 +94,14 @@ pub enum CodexErr {
     #[error("spawn failed: child stdout/stderr not captured")]
     Spawn,
 
+    /// The exec tool could not start the command at all, as opposed to the
+    /// command running and failing. `message` is what the model sees.
+    #[error("{message}")]
+    ExecSpawnFailed {
+        kind: SpawnErrorKind,
+        message: String,
+    },
+
     /// Returned by run_command_stream when the user pressed Ctrl‑C (SIGINT). Session uses this to
     /// surface a polite FunctionCallOutput back to the model instead of crashing the CLI.
     #[error("interrupted (Ctrl-C). Something went wrong? Hit `/feedback` to report the issue.")]
@@ -204,6 +213,7 @@ impl CodexErr {
             | CodexErr::ThreadNotFound(_)
             | CodexErr::AgentLimitReached { .. }
             | CodexErr::Spawn
+            | CodexErr::ExecSpawnFailed { .. }
             | CodexErr::SessionConfiguredNotFirstEvent
             | CodexErr::UsageLimitReached(_) => false,
             CodexErr::Stream(..)
@@ -639,7 +649,9 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new("aggregate detail".to_string()),
             duration: Duration::from_millis(10),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -655,7 +667,9 @@ mod tests {
             stderr: StreamOutput::new("stderr detail".to_string()),
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(10),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -671,7 +685,9 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(8),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -714,7 +730,9 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(5),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -13,6 +13,7 @@ use codex_protocol::models::is_image_close_tag_text;
 use codex_protocol::models::is_image_open_tag_text;
 use codex_protocol::models::is_local_image_close_tag_text;
 use codex_protocol::models::is_local_image_open_tag_text;
+use codex_protocol::models::parse_document_text;
 use codex_protocol::user_input::UserInput;
 use tracing::warn;
 use uuid::Uuid;
@@ -45,6 +46,13 @@ fn parse_user_message(message: &[ContentItem]) -> Option<UserMessageItem> {
                 if is_session_prefix(text) || is_user_shell_command_text(text) {
                     return None;
                 }
+                if let Some((name, text)) = parse_document_text(text) {
+                    content.push(UserInput::Document {
+                        name: name.to_string(),
+                        text: text.to_string(),
+                    });
+                    continue;
+                }
                 content.push(UserInput::Text {
                     text: text.clone(),
                     // Model input content does not carry UI element ranges.
@@ -192,6 +200,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn maps_document_text_back_to_document_input() {
+        let item = ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![
+                ContentItem::InputText {
+                    text: "fix this".to_string(),
+                },
+                ContentItem::InputText {
+                    text: codex_protocol::models::document_text("stdin", "error: linker failed"),
+                },
+            ],
+            end_turn: None,
+        };
+
+        let turn_item = parse_turn_item(&item).expect("expected user message turn item");
+
+        match turn_item {
+            TurnItem::UserMessage(user) => {
+                assert_eq!(user.message(), "fix this");
+                assert_eq!(
+                    user.content[1],
+                    UserInput::Document {
+                        name: "stdin".to_string(),
+                        text: "error: linker failed".to_string(),
+                    }
+                );
+            }
+            other => panic!("expected TurnItem::UserMessage, got {other:?}"),
+        }
+    }
+
     #[test]
     fn skips_local_image_label_text() {
         let image_url = "data:image/png;base64,abc".to_string();
@@ -6,25 +6,34 @@ use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
-use std::time::Instant;
 
 use async_channel::Sender;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
+use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::exec_timing::ExecStopwatch;
+use crate::exec_timing::ExecTiming;
 use crate::get_platform_sandbox;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecOutcome;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SpawnErrorKind;
 use crate::sandboxing::CommandSpec;
 use crate::sandboxing::ExecEnv;
 use crate::sandboxing::SandboxManager;
@@ -32,6 +41,7 @@ use crate::sandboxing::SandboxPermissions;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use crate::text_encoding::bytes_to_string_smart;
+use crate::unified_exec::head_tail_buffer::HeadTailBuffer;
 use codex_utils_pty::process_group::kill_child_process_group;
 
 pub const DEFAULT_EXEC_COMMAND_TIMEOUT_MS: u64 = 10_000;
@@ -44,19 +54,58 @@ const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 const EXEC_TIMEOUT_EXIT_CODE: i32 = 124; // conventional timeout exit code
 
 // I/O buffer sizing
-const READ_CHUNK_SIZE: usize = 8192; // bytes per read
-const AGGREGATE_BUFFER_INITIAL_CAPACITY: usize = 8 * 1024; // 8 KiB
+pub(crate) const READ_CHUNK_SIZE: usize = 8192; // bytes per read
 
-/// Hard cap on bytes retained from exec stdout/stderr/aggregated output.
+/// Hard cap on bytes retained from each of exec stdout and stderr.
 ///
 /// This mirrors unified exec's output cap so a single runaway command cannot
-/// OOM the process by dumping huge amounts of data to stdout/stderr.
+/// OOM the process by dumping huge amounts of data to stdout/stderr. Output
+/// over the cap keeps its first and last halves and drops the middle as it
+/// arrives, so nothing larger than the cap is ever buffered.
 const EXEC_OUTPUT_MAX_BYTES: usize = 1024 * 1024; // 1 MiB
 
+/// Hard cap on bytes retained from the interleaved stdout/stderr output.
+const EXEC_AGGREGATED_OUTPUT_MAX_BYTES: usize = EXEC_OUTPUT_MAX_BYTES * 2;
+
 /// Limit the number of ExecCommandOutputDelta events emitted per exec call.
 /// Aggregation still collects full output; only the live event stream is capped.
 pub(crate) const MAX_EXEC_OUTPUT_DELTAS_PER_CALL: usize = 10_000;
 
+/// How much of a call's output went out as `ExecCommandOutputDelta` events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutputDeltaStats {
+    pub bytes_emitted: u64,
+    /// Some output was never sent as a delta, so the deltas are not the
+    /// whole output.
+    pub incomplete: bool,
+}
+
+/// Counts the deltas of one call as they are sent. Shared by the tasks that
+/// stream its output.
+#[derive(Debug, Default)]
+pub(crate) struct DeltaTracker {
+    bytes_emitted: AtomicU64,
+    incomplete: AtomicBool,
+}
+
+impl DeltaTracker {
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.bytes_emitted
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dropped(&self) {
+        self.incomplete.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> OutputDeltaStats {
+        OutputDeltaStats {
+            bytes_emitted: self.bytes_emitted.load(Ordering::Relaxed),
+            incomplete: self.incomplete.load(Ordering::Relaxed),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ExecParams {
     pub command: Vec<String>,
@@ -132,6 +181,12 @@ pub struct StdoutStream {
     pub sub_id: String,
     pub call_id: String,
     pub tx_event: Sender<Event>,
+    /// Largest chunk of output sent in one `ExecCommandOutputDelta` event.
+    pub delta_chunk_bytes: usize,
+    /// File that receives every byte of the aggregated output as it arrives,
+    /// so output over `output_limits.spill_threshold` can be saved in full
+    /// without being held in memory.
+    pub spill_path: Option<PathBuf>,
 }
 
 pub async fn process_exec_tool_call(
@@ -217,10 +272,9 @@ pub(crate) async fn execute_exec_env(
         arg0,
     };
 
-    let start = Instant::now();
+    let stopwatch = ExecStopwatch::start();
     let raw_output_result = exec(params, sandbox, sandbox_policy, stdout_stream).await;
-    let duration = start.elapsed();
-    finalize_exec_result(raw_output_result, sandbox, duration)
+    finalize_exec_result(raw_output_result, sandbox, stopwatch.stop())
 }
 
 #[cfg(target_os = "windows")]
@@ -296,36 +350,18 @@ async fn exec_windows_sandbox(
     };
 
     let exit_status = synthetic_exit_status(capture.exit_code);
-    let mut stdout_text = capture.stdout;
-    if stdout_text.len() > EXEC_OUTPUT_MAX_BYTES {
-        stdout_text.truncate(EXEC_OUTPUT_MAX_BYTES);
-    }
-    let mut stderr_text = capture.stderr;
-    if stderr_text.len() > EXEC_OUTPUT_MAX_BYTES {
-        stderr_text.truncate(EXEC_OUTPUT_MAX_BYTES);
-    }
-    let stdout = StreamOutput {
-        text: stdout_text,
-        truncated_after_lines: None,
-    };
-    let stderr = StreamOutput {
-        text: stderr_text,
-        truncated_after_lines: None,
-    };
-    // Best-effort aggregate: stdout then stderr (capped).
-    let mut aggregated = Vec::with_capacity(
-        stdout
-            .text
-            .len()
-            .saturating_add(stderr.text.len())
-            .min(EXEC_OUTPUT_MAX_BYTES),
-    );
-    append_capped(&mut aggregated, &stdout.text, EXEC_OUTPUT_MAX_BYTES);
-    append_capped(&mut aggregated, &stderr.text, EXEC_OUTPUT_MAX_BYTES);
-    let aggregated_output = StreamOutput {
-        text: aggregated,
-        truncated_after_lines: None,
-    };
+    // The Windows sandbox hands back whole streams, so only the retained
+    // copies can be bounded here. The aggregate is stdout then stderr.
+    let mut aggregated = HeadTailBuffer::new(EXEC_AGGREGATED_OUTPUT_MAX_BYTES);
+    aggregated.push_chunk(capture.stdout.clone());
+    aggregated.push_chunk(capture.stderr.clone());
+    let mut stdout = HeadTailBuffer::new(EXEC_OUTPUT_MAX_BYTES);
+    stdout.push_chunk(capture.stdout);
+    let mut stderr = HeadTailBuffer::new(EXEC_OUTPUT_MAX_BYTES);
+    stderr.push_chunk(capture.stderr);
+    let stdout = StreamOutput::from_head_tail(stdout);
+    let stderr = StreamOutput::from_head_tail(stderr);
+    let aggregated_output = StreamOutput::from_head_tail(aggregated);
 
     Ok(RawExecToolCallOutput {
         exit_status,
@@ -333,13 +369,14 @@ async fn exec_windows_sandbox(
         stderr,
         aggregated_output,
         timed_out: capture.timed_out,
+        deltas: OutputDeltaStats::default(),
     })
 }
 
 fn finalize_exec_result(
     raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr>,
     sandbox_type: SandboxType,
-    duration: Duration,
+    timing: ExecTiming,
 ) -> Result<ExecToolCallOutput> {
     match raw_output_result {
         Ok(raw_output) => {
@@ -370,8 +407,10 @@ fn finalize_exec_result(
                 stdout,
                 stderr,
                 aggregated_output,
-                duration,
+                duration: timing.duration,
+                suspended: timing.suspended,
                 timed_out,
+                deltas: raw_output.deltas,
             };
 
             if timed_out {
@@ -480,6 +519,9 @@ pub(crate) fn is_likely_sandbox_denied(
 pub struct StreamOutput<T: Clone> {
     pub text: T,
     pub truncated_after_lines: Option<u32>,
+    /// Bytes dropped from the middle of the stream to stay within the
+    /// capture cap. `text` marks where they were.
+    pub omitted_bytes: usize,
 }
 
 #[derive(Debug)]
@@ -489,6 +531,7 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    pub deltas: OutputDeltaStats,
 }
 
 impl StreamOutput<String> {
@@ -496,6 +539,7 @@ impl StreamOutput<String> {
         Self {
             text,
             truncated_after_lines: None,
+            omitted_bytes: 0,
         }
     }
 }
@@ -505,18 +549,105 @@ impl StreamOutput<Vec<u8>> {
         StreamOutput {
             text: bytes_to_string_smart(&self.text),
             truncated_after_lines: self.truncated_after_lines,
+            omitted_bytes: self.omitted_bytes,
+        }
+    }
+
+    /// Joins the head and tail kept by a capped capture, with a marker where
+    /// the middle was dropped.
+    fn from_head_tail(buffer: HeadTailBuffer) -> Self {
+        let omitted_bytes = buffer.omitted_bytes();
+        let (mut text, tail) = buffer.into_head_and_tail();
+        if omitted_bytes > 0 {
+            text.extend_from_slice(
+                format!("\n[... {omitted_bytes} bytes omitted ...]\n").as_bytes(),
+            );
+        }
+        text.extend_from_slice(&tail);
+        Self {
+            text,
+            truncated_after_lines: None,
+            omitted_bytes,
         }
     }
 }
 
-#[inline]
-fn append_capped(dst: &mut Vec<u8>, src: &[u8], max_bytes: usize) {
-    if dst.len() >= max_bytes {
-        return;
+/// Stdout and stderr interleaved in arrival order, shared by both reader
+/// tasks. Every byte is also written to the spill file, when one is open.
+struct AggregatedCapture {
+    buffer: HeadTailBuffer,
+    spill: Option<SpillFile>,
+}
+
+struct SpillFile {
+    path: PathBuf,
+    file: tokio::fs::File,
+}
+
+type SharedAggregatedCapture = Arc<Mutex<AggregatedCapture>>;
+
+impl AggregatedCapture {
+    async fn new(spill_path: Option<&Path>) -> Self {
+        let spill = match spill_path {
+            Some(path) => SpillFile::create(path).await,
+            None => None,
+        };
+        Self {
+            buffer: HeadTailBuffer::new(EXEC_AGGREGATED_OUTPUT_MAX_BYTES),
+            spill,
+        }
+    }
+
+    async fn push(&mut self, chunk: &[u8]) {
+        self.buffer.push_chunk(chunk.to_vec());
+        if let Some(spill) = &mut self.spill
+            && let Err(err) = spill.file.write_all(chunk).await
+        {
+            self.abandon_spill(err).await;
+        }
+    }
+
+    /// Flushes the spill file and takes the retained output.
+    async fn finish(&mut self) -> StreamOutput<Vec<u8>> {
+        if let Some(spill) = &mut self.spill
+            && let Err(err) = spill.file.flush().await
+        {
+            self.abandon_spill(err).await;
+        }
+        StreamOutput::from_head_tail(std::mem::take(&mut self.buffer))
+    }
+
+    /// Removes a spill file that could not be written in full, so a partial
+    /// copy is never presented as the full output.
+    async fn abandon_spill(&mut self, err: io::Error) {
+        if let Some(SpillFile { path, file }) = self.spill.take() {
+            tracing::warn!("failed to save exec output to {}: {err}", path.display());
+            drop(file);
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+    }
+}
+
+impl SpillFile {
+    async fn create(path: &Path) -> Option<Self> {
+        let file = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::File::create(path).await
+        }
+        .await;
+        match file {
+            Ok(file) => Some(Self {
+                path: path.to_path_buf(),
+                file,
+            }),
+            Err(err) => {
+                tracing::warn!("failed to create {}: {err}", path.display());
+                None
+            }
+        }
     }
-    let remaining = max_bytes.saturating_sub(dst.len());
-    let take = remaining.min(src.len());
-    dst.extend_from_slice(&src[..take]);
 }
 
 #[derive(Clone, Debug)]
@@ -526,7 +657,13 @@ pub struct ExecToolCallOutput {
     pub stderr: StreamOutput<String>,
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
+    /// The machine most likely slept while the command ran; `duration` does
+    /// not include the time asleep.
+    pub suspended: bool,
     pub timed_out: bool,
+    /// What the `ExecCommandOutputDelta` events of the call covered; the
+    /// default when the output was not streamed.
+    pub deltas: OutputDeltaStats,
 }
 
 impl Default for ExecToolCallOutput {
@@ -537,11 +674,54 @@ impl Default for ExecToolCallOutput {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::ZERO,
+            suspended: false,
             timed_out: false,
+            deltas: OutputDeltaStats::default(),
+        }
+    }
+}
+
+impl ExecToolCallOutput {
+    /// How the process ended. Spawn failures and signals are reported as
+    /// errors instead, since they produce no output.
+    pub fn outcome(&self) -> ExecOutcome {
+        if self.timed_out {
+            ExecOutcome::TimedOut
+        } else {
+            ExecOutcome::Spawned {
+                exit_code: self.exit_code,
+            }
         }
     }
 }
 
+/// Classifies a failure to start `program` in `cwd`. The message names the
+/// cause precisely so the model does not mistake it for a failing command.
+pub(crate) fn spawn_error(err: &io::Error, program: &str, cwd: &Path) -> CodexErr {
+    let (kind, message) = if !cwd.is_dir() {
+        (
+            SpawnErrorKind::CwdNotFound,
+            format!("working directory does not exist: {}", cwd.display()),
+        )
+    } else {
+        match err.kind() {
+            io::ErrorKind::NotFound => (
+                SpawnErrorKind::CommandNotFound,
+                format!("command not found: {program}"),
+            ),
+            io::ErrorKind::PermissionDenied => (
+                SpawnErrorKind::PermissionDenied,
+                format!("permission denied: {program}"),
+            ),
+            _ => (
+                SpawnErrorKind::Other,
+                format!("failed to start command: {err}"),
+            ),
+        }
+    };
+    CodexErr::ExecSpawnFailed { kind, message }
+}
+
 #[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
 async fn exec(
     params: ExecParams,
@@ -578,12 +758,13 @@ async fn exec(
         PathBuf::from(program),
         args.into(),
         arg0_ref,
-        cwd,
+        cwd.clone(),
         sandbox_policy,
         StdioPolicy::RedirectForShellTool,
         env,
     )
-    .await?;
+    .await
+    .map_err(|err| spawn_error(&err, program, &cwd))?;
     consume_truncated_output(child, expiration, stdout_stream).await
 }
 
@@ -609,15 +790,25 @@ async fn consume_truncated_output(
         ))
     })?;
 
+    let spill_path = stdout_stream
+        .as_ref()
+        .and_then(|stream| stream.spill_path.as_deref());
+    let aggregated: SharedAggregatedCapture =
+        Arc::new(Mutex::new(AggregatedCapture::new(spill_path).await));
+    let deltas = Arc::new(DeltaTracker::default());
     let stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
         stdout_stream.clone(),
         false,
+        Arc::clone(&aggregated),
+        Arc::clone(&deltas),
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
+        Arc::clone(&aggregated),
+        Arc::clone(&deltas),
     ));
 
     let (exit_status, timed_out) = tokio::select! {
@@ -665,6 +856,7 @@ async fn consume_truncated_output(
                 Ok(StreamOutput {
                     text: Vec::new(),
                     truncated_after_lines: None,
+                    omitted_bytes: 0,
                 })
             }
         }
@@ -683,20 +875,7 @@ async fn consume_truncated_output(
         Duration::from_millis(IO_DRAIN_TIMEOUT_MS),
     )
     .await?;
-    // Best-effort aggregate: stdout then stderr (capped).
-    let mut aggregated = Vec::with_capacity(
-        stdout
-            .text
-            .len()
-            .saturating_add(stderr.text.len())
-            .min(EXEC_OUTPUT_MAX_BYTES),
-    );
-    append_capped(&mut aggregated, &stdout.text, EXEC_OUTPUT_MAX_BYTES);
-    append_capped(&mut aggregated, &stderr.text, EXEC_OUTPUT_MAX_BYTES * 2);
-    let aggregated_output = StreamOutput {
-        text: aggregated,
-        truncated_after_lines: None,
-    };
+    let aggregated_output = aggregated.lock().await.finish().await;
 
     Ok(RawExecToolCallOutput {
         exit_status,
@@ -704,16 +883,25 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        deltas: deltas.stats(),
     })
 }
 
+/// Reads one stream of the child to EOF. Each chunk is sent as a delta
+/// before it is captured, so a reader stopped in between may leave the
+/// deltas ahead of the captured output; `deltas` records what was sent.
 async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     mut reader: R,
     stream: Option<StdoutStream>,
     is_stderr: bool,
+    aggregated: SharedAggregatedCapture,
+    deltas: Arc<DeltaTracker>,
 ) -> io::Result<StreamOutput<Vec<u8>>> {
-    let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY.min(EXEC_OUTPUT_MAX_BYTES));
-    let mut tmp = [0u8; READ_CHUNK_SIZE];
+    let mut buf = HeadTailBuffer::new(EXEC_OUTPUT_MAX_BYTES);
+    let chunk_size = stream
+        .as_ref()
+        .map_or(READ_CHUNK_SIZE, |stream| stream.delta_chunk_bytes.max(1));
+    let mut tmp = vec![0u8; chunk_size];
     let mut emitted_deltas: usize = 0;
 
     loop {
@@ -722,9 +910,9 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
             break;
         }
 
-        if let Some(stream) = &stream
-            && emitted_deltas < MAX_EXEC_OUTPUT_DELTAS_PER_CALL
-        {
+        if stream.is_some() && emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+            deltas.record_dropped();
+        } else if let Some(stream) = &stream {
             let chunk = tmp[..n].to_vec();
             let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
                 call_id: stream.call_id.clone(),
@@ -739,19 +927,20 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
                 id: stream.sub_id.clone(),
                 msg,
             };
-            #[allow(clippy::let_unit_value)]
-            let _ = stream.tx_event.send(event).await;
+            if stream.tx_event.send(event).await.is_ok() {
+                deltas.record_sent(n);
+            } else {
+                deltas.record_dropped();
+            }
             emitted_deltas += 1;
         }
 
-        append_capped(&mut buf, &tmp[..n], EXEC_OUTPUT_MAX_BYTES);
+        buf.push_chunk(tmp[..n].to_vec());
+        aggregated.lock().await.push(&tmp[..n]).await;
         // Continue reading to EOF to avoid back-pressure
     }
 
-    Ok(StreamOutput {
-        text: buf,
-        truncated_after_lines: None,
-    })
+    Ok(StreamOutput::from_head_tail(buf))
 }
 
 #[cfg(unix)]
@@ -772,7 +961,6 @@ fn synthetic_exit_status(code: i32) -> ExitStatus {
 mod tests {
     use super::*;
     use std::time::Duration;
-    use tokio::io::AsyncWriteExt;
 
     fn make_exec_output(
         exit_code: i32,
@@ -786,7 +974,9 @@ mod tests {
             stderr: StreamOutput::new(stderr.to_string()),
             aggregated_output: StreamOutput::new(aggregated.to_string()),
             duration: Duration::from_millis(1),
+            suspended: false,
             timed_out: false,
+            deltas: OutputDeltaStats::default(),
         }
     }
 
@@ -834,16 +1024,198 @@ mod tests {
         ));
     }
 
+    async fn spawn_failure(program: &str, cwd: PathBuf) -> (SpawnErrorKind, String) {
+        let params = ExecParams {
+            command: vec![program.to_string()],
+            cwd,
+            expiration: 1_000.into(),
+            env: std::env::vars().collect(),
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            justification: None,
+            arg0: None,
+        };
+        match exec(
+            params,
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            None,
+        )
+        .await
+        {
+            Err(CodexErr::ExecSpawnFailed { kind, message }) => (kind, message),
+            other => panic!("expected a spawn failure, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_program_is_reported_as_command_not_found() {
+        let cwd = std::env::current_dir().expect("cwd");
+        assert_eq!(
+            spawn_failure("codex-definitely-missing-binary", cwd).await,
+            (
+                SpawnErrorKind::CommandNotFound,
+                "command not found: codex-definitely-missing-binary".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_cwd_is_reported_before_the_program() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cwd = dir.path().join("gone");
+        assert_eq!(
+            spawn_failure("codex-definitely-missing-binary", cwd.clone()).await,
+            (
+                SpawnErrorKind::CwdNotFound,
+                format!("working directory does not exist: {}", cwd.display())
+            )
+        );
+    }
+
+    #[test]
+    fn outcome_distinguishes_timeouts_from_exits() {
+        let mut output = make_exec_output(1, "", "", "");
+        assert_eq!(output.outcome(), ExecOutcome::Spawned { exit_code: 1 });
+        output.timed_out = true;
+        assert_eq!(output.outcome(), ExecOutcome::TimedOut);
+    }
+
+    async fn aggregated_capture(spill_path: Option<&Path>) -> SharedAggregatedCapture {
+        Arc::new(Mutex::new(AggregatedCapture::new(spill_path).await))
+    }
+
     #[tokio::test]
     async fn read_capped_limits_retained_bytes() {
         let (mut writer, reader) = tokio::io::duplex(1024);
-        let bytes = vec![b'a'; EXEC_OUTPUT_MAX_BYTES.saturating_add(128 * 1024)];
+        let mut bytes = vec![b'a'; EXEC_OUTPUT_MAX_BYTES.saturating_add(128 * 1024)];
+        bytes.extend_from_slice(b"end");
+        tokio::spawn(async move {
+            writer.write_all(&bytes).await.expect("write");
+        });
+
+        let out = read_capped(
+            reader,
+            None,
+            false,
+            aggregated_capture(None).await,
+            Arc::default(),
+        )
+        .await
+        .expect("read");
+        let marker = format!("\n[... {} bytes omitted ...]\n", 128 * 1024 + 3);
+        assert_eq!(out.omitted_bytes, 128 * 1024 + 3);
+        assert_eq!(out.text.len(), EXEC_OUTPUT_MAX_BYTES + marker.len());
+        assert!(out.text.ends_with(b"end"));
+    }
+
+    #[tokio::test]
+    async fn aggregated_capture_spills_every_byte_while_retaining_a_bounded_copy() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let spill_path = dir.path().join("nested").join("call.txt");
+        let aggregated = aggregated_capture(Some(&spill_path)).await;
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        let bytes = vec![b'a'; EXEC_AGGREGATED_OUTPUT_MAX_BYTES.saturating_add(1024)];
+        let expected_len = bytes.len();
         tokio::spawn(async move {
             writer.write_all(&bytes).await.expect("write");
         });
 
-        let out = read_capped(reader, None, false).await.expect("read");
-        assert_eq!(out.text.len(), EXEC_OUTPUT_MAX_BYTES);
+        read_capped(reader, None, false, Arc::clone(&aggregated), Arc::default())
+            .await
+            .expect("read");
+        let out = aggregated.lock().await.finish().await;
+
+        assert_eq!(out.omitted_bytes, 1024);
+        assert_eq!(
+            std::fs::metadata(&spill_path).expect("spill file").len(),
+            expected_len as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn read_capped_splits_deltas_by_stream_chunk_size() {
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            writer.write_all(b"0123456789").await.expect("write");
+        });
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let stream = StdoutStream {
+            sub_id: "sub".to_string(),
+            call_id: "call".to_string(),
+            tx_event,
+            delta_chunk_bytes: 4,
+            spill_path: None,
+        };
+
+        let aggregated = aggregated_capture(None).await;
+        let deltas = Arc::new(DeltaTracker::default());
+        let out = read_capped(
+            reader,
+            Some(stream),
+            false,
+            Arc::clone(&aggregated),
+            Arc::clone(&deltas),
+        )
+        .await
+        .expect("read");
+        assert_eq!(out.text, b"0123456789");
+        assert_eq!(aggregated.lock().await.finish().await.text, b"0123456789");
+
+        let mut chunks = Vec::new();
+        while let Ok(event) = rx_event.try_recv() {
+            if let EventMsg::ExecCommandOutputDelta(delta) = event.msg {
+                chunks.push(delta.chunk);
+            }
+        }
+        assert!(chunks.len() >= 3);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 4));
+        assert_eq!(chunks.concat(), b"0123456789");
+        assert_eq!(
+            deltas.stats(),
+            OutputDeltaStats {
+                bytes_emitted: 10,
+                incomplete: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn read_capped_keeps_output_when_the_event_channel_is_closed() {
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            writer.write_all(b"0123456789").await.expect("write");
+        });
+        let (tx_event, rx_event) = async_channel::unbounded();
+        drop(rx_event);
+        let stream = StdoutStream {
+            sub_id: "sub".to_string(),
+            call_id: "call".to_string(),
+            tx_event,
+            delta_chunk_bytes: 4,
+            spill_path: None,
+        };
+
+        let aggregated = aggregated_capture(None).await;
+        let deltas = Arc::new(DeltaTracker::default());
+        let out = read_capped(
+            reader,
+            Some(stream),
+            false,
+            Arc::clone(&aggregated),
+            Arc::clone(&deltas),
+        )
+        .await
+        .expect("read");
+
+        assert_eq!(out.text, b"0123456789");
+        assert_eq!(aggregated.lock().await.finish().await.text, b"0123456789");
+        assert_eq!(
+            deltas.stats(),
+            OutputDeltaStats {
+                bytes_emitted: 0,
+                incomplete: true,
+            }
+        );
     }
 
     #[cfg(unix)]
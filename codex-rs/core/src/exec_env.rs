@@ -1,6 +1,8 @@
 use crate::config::types::EnvironmentVariablePattern;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyInherit;
+use crate::redact::redact_env_value;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -15,6 +17,63 @@ pub fn create_env(policy: &ShellEnvironmentPolicy) -> HashMap<String, String> {
     populate_env(std::env::vars(), policy)
 }
 
+/// Checks the variables a tool call asks to set on top of the environment
+/// [`create_env`] builds from `policy`. Every name must pass the same default
+/// excludes, `exclude` and `include_only` rules as the inherited variables,
+/// so a call cannot bring back a variable the policy filters out.
+pub(crate) fn check_env_overrides(
+    overrides: &HashMap<String, String>,
+    policy: &ShellEnvironmentPolicy,
+) -> Result<(), String> {
+    let overrides: BTreeMap<&String, &String> = overrides.iter().collect();
+    for (name, value) in overrides {
+        if name.is_empty() || name.contains(['=', '\0']) {
+            return Err(format!("invalid environment variable name `{name}`"));
+        }
+        if value.contains('\0') {
+            return Err(format!("environment variable `{name}` contains a NUL byte"));
+        }
+        let blocked_by =
+            if !policy.ignore_default_excludes && matches_any(name, &default_excludes()) {
+                Some("the default excludes (*KEY*, *SECRET*, *TOKEN*)")
+            } else if matches_any(name, &policy.exclude) {
+                Some("`exclude`")
+            } else if !policy.include_only.is_empty() && !matches_any(name, &policy.include_only) {
+                Some("`include_only`")
+            } else {
+                None
+            };
+        if let Some(rule) = blocked_by {
+            return Err(format!(
+                "environment variable `{name}` is blocked by {rule} in [shell_environment_policy]; run the command without it"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The variables a call injected as its `ExecCommandBeginEvent` shows them,
+/// with secret-looking values redacted.
+pub(crate) fn injected_env(overrides: &HashMap<String, String>) -> BTreeMap<String, String> {
+    overrides
+        .iter()
+        .map(|(name, value)| (name.clone(), redact_env_value(name, value)))
+        .collect()
+}
+
+/// Does `name` match **any** pattern in `patterns`?
+fn matches_any(name: &str, patterns: &[EnvironmentVariablePattern]) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(name))
+}
+
+fn default_excludes() -> Vec<EnvironmentVariablePattern> {
+    vec![
+        EnvironmentVariablePattern::new_case_insensitive("*KEY*"),
+        EnvironmentVariablePattern::new_case_insensitive("*SECRET*"),
+        EnvironmentVariablePattern::new_case_insensitive("*TOKEN*"),
+    ]
+}
+
 fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String, String>
 where
     I: IntoIterator<Item = (String, String)>,
@@ -42,18 +101,9 @@ where
         }
     };
 
-    // Internal helper – does `name` match **any** pattern in `patterns`?
-    let matches_any = |name: &str, patterns: &[EnvironmentVariablePattern]| -> bool {
-        patterns.iter().any(|pattern| pattern.matches(name))
-    };
-
     // Step 2 – Apply the default exclude if not disabled.
     if !policy.ignore_default_excludes {
-        let default_excludes = vec![
-            EnvironmentVariablePattern::new_case_insensitive("*KEY*"),
-            EnvironmentVariablePattern::new_case_insensitive("*SECRET*"),
-            EnvironmentVariablePattern::new_case_insensitive("*TOKEN*"),
-        ];
+        let default_excludes = default_excludes();
         env_map.retain(|k, _| !matches_any(k, &default_excludes));
     }
 
@@ -248,4 +298,64 @@ mod tests {
         };
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn allowed_env_overrides_are_reported_redacted() {
+        let policy = ShellEnvironmentPolicy {
+            ignore_default_excludes: true,
+            ..Default::default()
+        };
+        let overrides = hashmap! {
+            "RUST_LOG".to_string() => "debug".to_string(),
+            "DB_PASSWORD".to_string() => "hunter2".to_string(),
+        };
+
+        assert_eq!(check_env_overrides(&overrides, &policy), Ok(()));
+        assert_eq!(
+            injected_env(&overrides),
+            BTreeMap::from([
+                ("DB_PASSWORD".to_string(), "[REDACTED]".to_string()),
+                ("RUST_LOG".to_string(), "debug".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn env_overrides_follow_the_policy_filters() {
+        let policy = ShellEnvironmentPolicy {
+            ignore_default_excludes: false,
+            exclude: vec![EnvironmentVariablePattern::new_case_insensitive("LD_*")],
+            include_only: vec![
+                EnvironmentVariablePattern::new_case_insensitive("LD_*"),
+                EnvironmentVariablePattern::new_case_insensitive("*_KEY"),
+                EnvironmentVariablePattern::new_case_insensitive("RUST_*"),
+            ],
+            ..Default::default()
+        };
+
+        for (name, message) in [
+            (
+                "LD_PRELOAD",
+                "environment variable `LD_PRELOAD` is blocked by `exclude` in [shell_environment_policy]; run the command without it",
+            ),
+            (
+                "API_KEY",
+                "environment variable `API_KEY` is blocked by the default excludes (*KEY*, *SECRET*, *TOKEN*) in [shell_environment_policy]; run the command without it",
+            ),
+            (
+                "CC",
+                "environment variable `CC` is blocked by `include_only` in [shell_environment_policy]; run the command without it",
+            ),
+            ("A=B", "invalid environment variable name `A=B`"),
+        ] {
+            let overrides = hashmap! {
+                "RUST_LOG".to_string() => "debug".to_string(),
+                name.to_string() => "x".to_string(),
+            };
+            assert_eq!(
+                check_env_overrides(&overrides, &policy),
+                Err(message.to_string())
+            );
+        }
+    }
 }
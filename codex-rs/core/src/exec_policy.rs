@@ -29,10 +29,13 @@ use crate::features::Feature;
 use crate::features::Features;
 use crate::sandboxing::SandboxPermissions;
 use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::RejectionReason;
 use shlex::try_join as shlex_try_join;
 
 const PROMPT_CONFLICT_REASON: &str =
     "approval required by policy, but AskForApproval is set to Never";
+const ALLOW_RULE_WITH_ENV_REASON: &str =
+    "an allow rule matches this command, but it sets environment variables the rule does not cover";
 const RULES_DIR_NAME: &str = "rules";
 const RULE_EXTENSION: &str = "rules";
 const DEFAULT_POLICY_FILE: &str = "default.rules";
@@ -97,9 +100,11 @@ impl ExecPolicyManager {
     pub(crate) async fn load(
         features: &Features,
         config_stack: &ConfigLayerStack,
+        state_dir: &Path,
     ) -> Result<Self, ExecPolicyError> {
         let (policy, warning) =
-            load_exec_policy_for_features_with_warning(features, config_stack).await?;
+            load_exec_policy_for_features_with_warning(features, config_stack, Some(state_dir))
+                .await?;
         if let Some(err) = warning.as_ref() {
             tracing::warn!("failed to parse rules: {err}");
         }
@@ -117,32 +122,84 @@ impl ExecPolicyManager {
         approval_policy: AskForApproval,
         sandbox_policy: &SandboxPolicy,
         sandbox_permissions: SandboxPermissions,
+    ) -> ExecApprovalRequirement {
+        self.create_exec_approval_requirement_for_command_with_env(
+            features,
+            command,
+            approval_policy,
+            sandbox_policy,
+            sandbox_permissions,
+            false,
+        )
+        .await
+    }
+
+    /// Like [`Self::create_exec_approval_requirement_for_command`], but when
+    /// the call `injects_env`, no command counts as known-safe: variables
+    /// such as `LD_PRELOAD` or `GIT_SSH_COMMAND` can make `ls` or `git status`
+    /// run arbitrary code. For the same reason an allow rule alone does not
+    /// let such a call run without approval.
+    pub(crate) async fn create_exec_approval_requirement_for_command_with_env(
+        &self,
+        features: &Features,
+        command: &[String],
+        approval_policy: AskForApproval,
+        sandbox_policy: &SandboxPolicy,
+        sandbox_permissions: SandboxPermissions,
+        injects_env: bool,
     ) -> ExecApprovalRequirement {
         let exec_policy = self.current();
         let commands =
             parse_shell_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
         let exec_policy_fallback = |cmd: &[String]| {
-            render_decision_for_unmatched_command(
-                approval_policy,
-                sandbox_policy,
-                cmd,
-                sandbox_permissions,
-            )
+            if injects_env {
+                render_decision_for_unsafe_command(
+                    approval_policy,
+                    sandbox_policy,
+                    cmd,
+                    sandbox_permissions,
+                )
+            } else {
+                render_decision_for_unmatched_command(
+                    approval_policy,
+                    sandbox_policy,
+                    cmd,
+                    sandbox_permissions,
+                )
+            }
         };
         let evaluation = exec_policy.check_multiple(commands.iter(), &exec_policy_fallback);
+        // An allow rule vouches for the command, not for the variables
+        // injected into it.
+        let allow_rule_with_env = injects_env
+            && evaluation.decision == Decision::Allow
+            && evaluation.matched_rules.iter().any(|rule_match| {
+                is_policy_match(rule_match) && rule_match.decision() == Decision::Allow
+            });
+        let decision = if allow_rule_with_env {
+            Decision::Prompt
+        } else {
+            evaluation.decision
+        };
 
-        match evaluation.decision {
+        match decision {
             Decision::Forbidden => ExecApprovalRequirement::Forbidden {
                 reason: derive_forbidden_reason(command, &evaluation),
             },
             Decision::Prompt => {
                 if matches!(approval_policy, AskForApproval::Never) {
                     ExecApprovalRequirement::Forbidden {
-                        reason: PROMPT_CONFLICT_REASON.to_string(),
+                        reason: RejectionReason::PolicyDenied {
+                            rule: PROMPT_CONFLICT_REASON.to_string(),
+                        },
                     }
                 } else {
                     ExecApprovalRequirement::NeedsApproval {
-                        reason: derive_prompt_reason(command, &evaluation),
+                        reason: if allow_rule_with_env {
+                            Some(ALLOW_RULE_WITH_ENV_REASON.to_string())
+                        } else {
+                            derive_prompt_reason(command, &evaluation)
+                        },
                         proposed_execpolicy_amendment: if features.enabled(Feature::ExecPolicy) {
                             try_derive_execpolicy_amendment_for_prompt_rules(
                                 &evaluation.matched_rules,
@@ -169,10 +226,10 @@ impl ExecPolicyManager {
 
     pub(crate) async fn append_amendment_and_update(
         &self,
-        codex_home: &Path,
+        state_dir: &Path,
         amendment: &ExecPolicyAmendment,
     ) -> Result<(), ExecPolicyUpdateError> {
-        let policy_path = default_policy_path(codex_home);
+        let policy_path = default_policy_path(state_dir);
         let prefix = amendment.command.clone();
         spawn_blocking({
             let policy_path = policy_path.clone();
@@ -203,19 +260,21 @@ pub async fn check_execpolicy_for_warnings(
     features: &Features,
     config_stack: &ConfigLayerStack,
 ) -> Result<Option<ExecPolicyError>, ExecPolicyError> {
-    let (_, warning) = load_exec_policy_for_features_with_warning(features, config_stack).await?;
+    let (_, warning) =
+        load_exec_policy_for_features_with_warning(features, config_stack, None).await?;
     Ok(warning)
 }
 
 async fn load_exec_policy_for_features_with_warning(
     features: &Features,
     config_stack: &ConfigLayerStack,
+    state_dir: Option<&Path>,
 ) -> Result<(Policy, Option<ExecPolicyError>), ExecPolicyError> {
     if !features.enabled(Feature::ExecPolicy) {
         return Ok((Policy::empty(), None));
     }
 
-    match load_exec_policy(config_stack).await {
+    match load_exec_policy_with_state_dir(config_stack, state_dir).await {
         Ok(policy) => Ok((policy, None)),
         Err(err @ ExecPolicyError::ParsePolicy { .. }) => Ok((Policy::empty(), Some(err))),
         Err(err) => Err(err),
@@ -223,10 +282,20 @@ async fn load_exec_policy_for_features_with_warning(
 }
 
 pub async fn load_exec_policy(config_stack: &ConfigLayerStack) -> Result<Policy, ExecPolicyError> {
+    load_exec_policy_with_state_dir(config_stack, None).await
+}
+
+/// Like [`load_exec_policy`], but also loads rules that were approved into
+/// `state_dir` when it is not already one of the config layer folders.
+async fn load_exec_policy_with_state_dir(
+    config_stack: &ConfigLayerStack,
+    state_dir: Option<&Path>,
+) -> Result<Policy, ExecPolicyError> {
     // Iterate the layers in increasing order of precedence, adding the *.rules
     // from each layer, so that higher-precedence layers can override
     // rules defined in lower-precedence ones.
     let mut policy_paths = Vec::new();
+    let mut policy_dirs = Vec::new();
     // Include disabled project layers so .codex/rules still applies when
     // project config.toml is trust-disabled.
     for layer in config_stack.get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, true) {
@@ -235,6 +304,15 @@ pub async fn load_exec_policy(config_stack: &ConfigLayerStack) -> Result<Policy,
             let policy_dir = config_folder.join(RULES_DIR_NAME).expect("safe join");
             let layer_policy_paths = collect_policy_files(&policy_dir).await?;
             policy_paths.extend(layer_policy_paths);
+            policy_dirs.push(policy_dir.to_path_buf());
+        }
+    }
+    // Rules approved during a session live under the state dir, which only
+    // differs from the user config folder when it was redirected.
+    if let Some(state_dir) = state_dir {
+        let policy_dir = state_dir.join(RULES_DIR_NAME);
+        if !policy_dirs.contains(&policy_dir) {
+            policy_paths.extend(collect_policy_files(&policy_dir).await?);
         }
     }
 
@@ -272,7 +350,21 @@ pub fn render_decision_for_unmatched_command(
     if is_known_safe_command(command) {
         return Decision::Allow;
     }
+    render_decision_for_unsafe_command(
+        approval_policy,
+        sandbox_policy,
+        command,
+        sandbox_permissions,
+    )
+}
 
+/// The [`Decision`] for an unmatched command that is not known to be safe.
+fn render_decision_for_unsafe_command(
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+    command: &[String],
+    sandbox_permissions: SandboxPermissions,
+) -> Decision {
     // On Windows, ReadOnly sandbox is not a real sandbox, so special-case it
     // here.
     let runtime_sandbox_provides_safety =
@@ -299,8 +391,7 @@ pub fn render_decision_for_unmatched_command(
             Decision::Allow
         }
         AskForApproval::UnlessTrusted => {
-            // We already checked `is_known_safe_command(command)` and it
-            // returned false, so we must prompt.
+            // The command is not known to be safe, so we must prompt.
             Decision::Prompt
         }
         AskForApproval::OnRequest => {
@@ -415,10 +506,10 @@ fn render_shlex_command(args: &[String]) -> String {
     shlex_try_join(args.iter().map(String::as_str)).unwrap_or_else(|_| args.join(" "))
 }
 
-/// Derive a string explaining why the command was forbidden. If `justification`
-/// is set by the user, this can contain instructions with recommended
-/// alternatives, for example.
-fn derive_forbidden_reason(command_args: &[String], evaluation: &Evaluation) -> String {
+/// Derive the reason the command was forbidden. If `justification` is set by
+/// the user, this can contain instructions with recommended alternatives, for
+/// example.
+fn derive_forbidden_reason(command_args: &[String], evaluation: &Evaluation) -> RejectionReason {
     let command = render_shlex_command(command_args);
 
     let most_specific_forbidden = evaluation
@@ -436,14 +527,16 @@ fn derive_forbidden_reason(command_args: &[String], evaluation: &Evaluation) ->
         .max_by_key(|(matched_prefix, _)| matched_prefix.len());
 
     match most_specific_forbidden {
-        Some((_matched_prefix, Some(justification))) => {
-            format!("`{command}` rejected: {justification}")
-        }
-        Some((matched_prefix, None)) => {
-            let prefix = render_shlex_command(matched_prefix);
-            format!("`{command}` rejected: policy forbids commands starting with `{prefix}`")
-        }
-        None => format!("`{command}` rejected: blocked by policy"),
+        Some((_matched_prefix, Some(justification))) => RejectionReason::PolicyDenied {
+            rule: format!("`{command}` rejected: {justification}"),
+        },
+        Some((matched_prefix, None)) => RejectionReason::DenyList {
+            command,
+            pattern: render_shlex_command(matched_prefix),
+        },
+        None => RejectionReason::PolicyDenied {
+            rule: format!("`{command}` rejected: blocked by policy"),
+        },
     }
 }
 
@@ -541,7 +634,7 @@ mod tests {
         let temp_dir = tempdir().expect("create temp dir");
         let config_stack = config_stack_for_dot_codex_folder(temp_dir.path());
 
-        let manager = ExecPolicyManager::load(&features, &config_stack)
+        let manager = ExecPolicyManager::load(&features, &config_stack, temp_dir.path())
             .await
             .expect("manager result");
         let policy = manager.current();
@@ -668,6 +761,39 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn loads_rules_approved_into_separate_state_dir() -> anyhow::Result<()> {
+        let codex_home = tempdir()?;
+        let state_dir = tempdir()?;
+        let config_stack = config_stack_for_dot_codex_folder(codex_home.path());
+        let policy_dir = state_dir.path().join(RULES_DIR_NAME);
+        fs::create_dir_all(&policy_dir)?;
+        fs::write(
+            policy_dir.join(DEFAULT_POLICY_FILE),
+            r#"prefix_rule(pattern=["cargo"], decision="forbidden")"#,
+        )?;
+
+        let manager =
+            ExecPolicyManager::load(&Features::with_defaults(), &config_stack, state_dir.path())
+                .await?;
+
+        assert_eq!(
+            Evaluation {
+                decision: Decision::Forbidden,
+                matched_rules: vec![RuleMatch::PrefixRuleMatch {
+                    matched_prefix: vec!["cargo".to_string()],
+                    decision: Decision::Forbidden,
+                    justification: None,
+                }],
+            },
+            manager
+                .current()
+                .check_multiple([vec!["cargo".to_string()]].iter(), &|_| Decision::Allow)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn loads_policies_from_multiple_config_layers() -> anyhow::Result<()> {
         let user_dir = tempdir()?;
@@ -768,7 +894,10 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         assert_eq!(
             requirement,
             ExecApprovalRequirement::Forbidden {
-                reason: "`bash -lc 'rm -rf /some/important/folder'` rejected: policy forbids commands starting with `rm`".to_string()
+                reason: RejectionReason::DenyList {
+                    command: "bash -lc 'rm -rf /some/important/folder'".to_string(),
+                    pattern: "rm".to_string(),
+                }
             }
         );
     }
@@ -806,7 +935,10 @@ prefix_rule(
         assert_eq!(
             requirement,
             ExecApprovalRequirement::Forbidden {
-                reason: "`rm -rf /some/important/folder` rejected: destructive command".to_string()
+                reason: RejectionReason::PolicyDenied {
+                    rule: "`rm -rf /some/important/folder` rejected: destructive command"
+                        .to_string()
+                }
             }
         );
     }
@@ -865,7 +997,9 @@ prefix_rule(
         assert_eq!(
             requirement,
             ExecApprovalRequirement::Forbidden {
-                reason: PROMPT_CONFLICT_REASON.to_string()
+                reason: RejectionReason::PolicyDenied {
+                    rule: PROMPT_CONFLICT_REASON.to_string()
+                }
             }
         );
     }
@@ -1002,6 +1136,82 @@ prefix_rule(
         );
     }
 
+    #[tokio::test]
+    async fn allow_rule_does_not_cover_injected_env() {
+        let policy_src = r#"prefix_rule(pattern=["cargo"], decision="allow")"#;
+        let mut parser = PolicyParser::new();
+        parser
+            .parse("test.rules", policy_src)
+            .expect("parse policy");
+        let manager = ExecPolicyManager::new(Arc::new(parser.build()));
+        let command = vec!["cargo".to_string(), "build".to_string()];
+        let features = Features::with_defaults();
+        let requirement_for = |approval_policy, injects_env| {
+            manager.create_exec_approval_requirement_for_command_with_env(
+                &features,
+                &command,
+                approval_policy,
+                &SandboxPolicy::DangerFullAccess,
+                SandboxPermissions::UseDefault,
+                injects_env,
+            )
+        };
+
+        assert!(matches!(
+            requirement_for(AskForApproval::OnRequest, false).await,
+            ExecApprovalRequirement::Skip {
+                bypass_sandbox: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            requirement_for(AskForApproval::OnRequest, true).await,
+            ExecApprovalRequirement::NeedsApproval {
+                reason: Some(ALLOW_RULE_WITH_ENV_REASON.to_string()),
+                proposed_execpolicy_amendment: None,
+            }
+        );
+        assert_eq!(
+            requirement_for(AskForApproval::Never, true).await,
+            ExecApprovalRequirement::Forbidden {
+                reason: RejectionReason::PolicyDenied {
+                    rule: PROMPT_CONFLICT_REASON.to_string(),
+                },
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn untrusted_safe_command_with_injected_env_needs_approval() {
+        let command = vec!["ls".to_string()];
+        let features = Features::with_defaults();
+        let manager = ExecPolicyManager::default();
+        let requirement_for = |injects_env| {
+            manager.create_exec_approval_requirement_for_command_with_env(
+                &features,
+                &command,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                SandboxPermissions::UseDefault,
+                injects_env,
+            )
+        };
+
+        assert!(matches!(
+            requirement_for(false).await,
+            ExecApprovalRequirement::Skip { .. }
+        ));
+        assert_eq!(
+            requirement_for(true).await,
+            ExecApprovalRequirement::NeedsApproval {
+                reason: None,
+                proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
+                    "ls".to_string()
+                ]))
+            }
+        );
+    }
+
     #[tokio::test]
     async fn proposed_execpolicy_amendment_is_disabled_when_execpolicy_feature_disabled() {
         let command = vec!["cargo".to_string(), "build".to_string()];
@@ -1265,7 +1475,9 @@ prefix_rule(
         // AskForApproval::Never.
         assert_eq!(
             ExecApprovalRequirement::Forbidden {
-                reason: "`rm -rf /important/data` rejected: blocked by policy".to_string(),
+                reason: RejectionReason::PolicyDenied {
+                    rule: "`rm -rf /important/data` rejected: blocked by policy".to_string(),
+                },
             },
             policy
                 .create_exec_approval_requirement_for_command(
@@ -0,0 +1,152 @@
+//! Timing of exec calls.
+//!
+//! Durations come from the monotonic clock only, so NTP corrections or a
+//! manually changed system clock cannot make them negative or absurdly large.
+//! The monotonic clock stops while the machine sleeps on most platforms, so
+//! the wall clock is read alongside it, but only to notice that.
+
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// Wall-clock time beyond the monotonic elapsed time above which a command is
+/// assumed to have spanned a suspend rather than a small clock correction.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Started when a command is spawned; [`ExecStopwatch::stop`] reads it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ExecStopwatch {
+    started: Instant,
+    started_wall: SystemTime,
+}
+
+/// How long a command ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ExecTiming {
+    /// Monotonic elapsed time; never negative.
+    pub duration: Duration,
+    /// The wall clock moved much further than `duration`, most likely
+    /// because the machine slept while the command ran.
+    pub suspended: bool,
+}
+
+impl ExecStopwatch {
+    pub(crate) fn start() -> Self {
+        Self::start_at(Instant::now(), SystemTime::now())
+    }
+
+    pub(crate) fn start_at(started: Instant, started_wall: SystemTime) -> Self {
+        Self {
+            started,
+            started_wall,
+        }
+    }
+
+    pub(crate) fn stop(&self) -> ExecTiming {
+        self.stop_at(Instant::now(), SystemTime::now())
+    }
+
+    pub(crate) fn stop_at(&self, now: Instant, now_wall: SystemTime) -> ExecTiming {
+        let duration = now.saturating_duration_since(self.started);
+        // A wall clock that went backwards says nothing about sleeping.
+        let wall = now_wall
+            .duration_since(self.started_wall)
+            .unwrap_or_default();
+        let suspended =
+            wall.saturating_sub(duration) > SUSPEND_THRESHOLD && wall > duration.saturating_mul(2);
+        ExecTiming {
+            duration,
+            suspended,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn stopwatch() -> (ExecStopwatch, Instant, SystemTime) {
+        let started = Instant::now();
+        let started_wall = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        (
+            ExecStopwatch::start_at(started, started_wall),
+            started,
+            started_wall,
+        )
+    }
+
+    #[test]
+    fn durations_follow_the_monotonic_clock() {
+        let (stopwatch, started, started_wall) = stopwatch();
+        let elapsed = Duration::from_millis(1_500);
+        assert_eq!(
+            stopwatch.stop_at(started + elapsed, started_wall + elapsed),
+            ExecTiming {
+                duration: elapsed,
+                suspended: false,
+            }
+        );
+    }
+
+    #[test]
+    fn wall_clock_moving_backwards_does_not_affect_the_duration() {
+        let (stopwatch, started, started_wall) = stopwatch();
+        let elapsed = Duration::from_secs(90);
+        assert_eq!(
+            stopwatch.stop_at(started + elapsed, started_wall - HOUR),
+            ExecTiming {
+                duration: elapsed,
+                suspended: false,
+            }
+        );
+    }
+
+    #[test]
+    fn a_stop_reading_before_the_start_saturates_at_zero() {
+        let (stopwatch, started, started_wall) = stopwatch();
+        let earlier = started.checked_sub(Duration::from_secs(1));
+        let Some(earlier) = earlier else {
+            return;
+        };
+        assert_eq!(
+            stopwatch.stop_at(earlier, started_wall),
+            ExecTiming::default()
+        );
+    }
+
+    #[test]
+    fn wall_clock_far_ahead_of_monotonic_marks_a_suspend() {
+        let (stopwatch, started, started_wall) = stopwatch();
+        let elapsed = Duration::from_secs(20);
+        assert_eq!(
+            stopwatch.stop_at(started + elapsed, started_wall + 8 * HOUR),
+            ExecTiming {
+                duration: elapsed,
+                suspended: true,
+            }
+        );
+    }
+
+    #[test]
+    fn small_clock_corrections_are_not_a_suspend() {
+        let (stopwatch, started, started_wall) = stopwatch();
+        // An NTP step of a few seconds on a short command.
+        let elapsed = Duration::from_secs(2);
+        let timing = stopwatch.stop_at(
+            started + elapsed,
+            started_wall + elapsed + Duration::from_secs(10),
+        );
+        assert!(!timing.suspended);
+
+        // A long command whose wall time is only slightly longer.
+        let elapsed = 2 * HOUR;
+        let timing = stopwatch.stop_at(
+            started + elapsed,
+            started_wall + elapsed + Duration::from_secs(45),
+        );
+        assert!(!timing.suspended);
+    }
+}
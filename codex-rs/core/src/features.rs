@@ -109,6 +109,36 @@ pub enum Feature {
     CollaborationModes,
     /// Use the Responses API WebSocket transport for OpenAI by default.
     ResponsesWebsockets,
+    /// Offer the `report_failure` tool; `codex exec` turns this on.
+    ReportFailure,
+    /// Offer the `report_outcome` tool for a machine-readable verdict.
+    ReportOutcome,
+    /// Let other processes attach a read-only event stream to the session.
+    LiveAttach,
+    /// Run read-only tool calls from one model response concurrently.
+    ParallelReadOnlyTools,
+    /// Let `apply_patch` change read-only files, setting the flag back after.
+    ApplyPatchClearReadOnly,
+    /// Offer the `search_workspace` tool.
+    SearchWorkspace,
+    /// Offer the `git` tool.
+    GitTool,
+    /// Let the `git` tool push, pull and fetch.
+    GitToolRemote,
+    /// Let shell calls run as background jobs, with tools to inspect and
+    /// kill them.
+    BackgroundJobs,
+    /// Tell the model about the working directory when a session starts.
+    WorkspacePrimer,
+    /// Watch the workspace for files changed outside Codex after the agent
+    /// read them.
+    WorkspaceWatcher,
+    /// Tell the model what the sandbox and policies allow, and offer the
+    /// `get_environment_capabilities` tool.
+    CapabilityDescriptor,
+    /// Keep the output of earlier commands and offer the `read_exec_output`
+    /// tool to page through it.
+    ReadExecOutput,
 }
 
 impl Feature {
@@ -465,4 +495,82 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::UnderDevelopment,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::ReportFailure,
+        key: "report_failure",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ReportOutcome,
+        key: "report_outcome",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::LiveAttach,
+        key: "live_attach",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ParallelReadOnlyTools,
+        key: "parallel_read_only_tools",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ApplyPatchClearReadOnly,
+        key: "apply_patch_clear_readonly",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SearchWorkspace,
+        key: "search_workspace",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::GitTool,
+        key: "git_tool",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::GitToolRemote,
+        key: "git_tool_remote",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::BackgroundJobs,
+        key: "background_jobs",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::WorkspacePrimer,
+        key: "workspace_primer",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::WorkspaceWatcher,
+        key: "workspace_watcher",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::CapabilityDescriptor,
+        key: "capability_descriptor",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ReadExecOutput,
+        key: "read_exec_output",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
 ];
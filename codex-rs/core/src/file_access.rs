@@ -0,0 +1,206 @@
+//! The files a turn read and wrote.
+//!
+//! Reads come from `read_file`, from commands recognized as reading a file,
+//! and from MCP resources with `file://` URIs. Writes come from patches and
+//! from a few commands whose targets can be told from their words (see
+//! [`command_writes`]). At the end of the turn the ledger becomes a
+//! `FileAccessLedger` event. Along the way it remembers each file's
+//! modification time as the agent last saw it, so a patch to a file that
+//! changed on disk since then can be flagged.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::bash::parse_shell_lc_plain_commands;
+use crate::protocol::FileAccessLedgerEvent;
+
+/// Longer lists are cut in the event, which then says how many were left out.
+const MAX_LEDGER_PATHS: usize = 200;
+
+#[derive(Debug, Default)]
+pub(crate) struct FileAccessLedger {
+    read: BTreeSet<PathBuf>,
+    written: BTreeSet<PathBuf>,
+    /// Modification time of each file when the agent last read or wrote it;
+    /// `None` for files that did not exist then.
+    seen: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileAccessLedger {
+    pub(crate) fn record_read(&mut self, path: PathBuf, modified: Option<SystemTime>) {
+        self.seen.insert(path.clone(), modified);
+        self.read.insert(path);
+    }
+
+    pub(crate) fn record_write(&mut self, path: PathBuf, modified: Option<SystemTime>) {
+        self.seen.insert(path.clone(), modified);
+        self.written.insert(path);
+    }
+
+    /// Whether the agent saw `path` this turn and its modification time has
+    /// changed to `modified` since.
+    pub(crate) fn changed_since_seen(&self, path: &Path, modified: Option<SystemTime>) -> bool {
+        self.seen.get(path).is_some_and(|seen| *seen != modified)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.read.is_empty() && self.written.is_empty()
+    }
+
+    /// The ledger as an event, with paths under `cwd` made relative to it.
+    pub(crate) fn event(&self, turn_id: &str, cwd: &Path) -> FileAccessLedgerEvent {
+        let (read, read_omitted) = listed(&self.read, cwd);
+        let (written, written_omitted) = listed(&self.written, cwd);
+        FileAccessLedgerEvent {
+            turn_id: turn_id.to_string(),
+            read,
+            written,
+            read_omitted,
+            written_omitted,
+        }
+    }
+}
+
+/// When `path` was last modified; `None` if it does not exist or the time
+/// cannot be read.
+pub(crate) async fn modified_time(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .and_then(|metadata| metadata.modified().ok())
+}
+
+/// `paths` relative to `cwd` where possible, sorted and capped at
+/// [`MAX_LEDGER_PATHS`]; also returns how many were left out.
+fn listed(paths: &BTreeSet<PathBuf>, cwd: &Path) -> (Vec<PathBuf>, usize) {
+    let relative: BTreeSet<PathBuf> = paths
+        .iter()
+        .map(|path| {
+            path.strip_prefix(cwd)
+                .map_or_else(|_| path.clone(), Path::to_path_buf)
+        })
+        .collect();
+    let omitted = relative.len().saturating_sub(MAX_LEDGER_PATHS);
+    (
+        relative.into_iter().take(MAX_LEDGER_PATHS).collect(),
+        omitted,
+    )
+}
+
+/// Files that `command`, run in `cwd`, creates, changes or deletes, as far as
+/// its words tell: the operands of `touch`, `mkdir`, `rm`, `rmdir` and
+/// `tee`, the destination of `cp`, `mv` and `install`, and the files of an
+/// in-place `sed`. Commands behind redirections or other shell syntax are
+/// not looked into.
+pub(crate) fn command_writes(command: &[String], cwd: &Path) -> Vec<PathBuf> {
+    let commands = parse_shell_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+    commands
+        .iter()
+        .flat_map(|words| written_operands(words))
+        .map(|operand| cwd.join(operand))
+        .collect()
+}
+
+fn written_operands(words: &[String]) -> Vec<&str> {
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let program = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let operands: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    match program {
+        "touch" | "mkdir" | "rm" | "rmdir" | "tee" => operands,
+        "cp" | "mv" | "install" if operands.len() >= 2 => {
+            operands.last().copied().into_iter().collect()
+        }
+        "sed" if args.iter().any(|arg| arg.starts_with("-i")) => {
+            operands.into_iter().skip(1).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn words(command: &[&str]) -> Vec<String> {
+        command.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn event_lists_paths_relative_to_cwd_and_counts_what_is_cut() {
+        let cwd = Path::new("/repo");
+        let mut ledger = FileAccessLedger::default();
+        ledger.record_read(PathBuf::from("/repo/src/lib.rs"), None);
+        ledger.record_read(PathBuf::from("/repo/src/lib.rs"), None);
+        ledger.record_read(PathBuf::from("/etc/hosts"), None);
+        for index in 0..MAX_LEDGER_PATHS + 3 {
+            ledger.record_write(PathBuf::from(format!("/repo/out/{index:03}.txt")), None);
+        }
+
+        let event = ledger.event("turn-1", cwd);
+
+        assert_eq!(
+            event.read,
+            vec![PathBuf::from("/etc/hosts"), PathBuf::from("src/lib.rs")]
+        );
+        assert_eq!(event.read_omitted, 0);
+        assert_eq!(event.written.len(), MAX_LEDGER_PATHS);
+        assert_eq!(event.written[0], PathBuf::from("out/000.txt"));
+        assert_eq!(event.written_omitted, 3);
+    }
+
+    #[test]
+    fn a_file_changed_after_it_was_last_seen_is_noticed() {
+        let path = Path::new("/repo/src/lib.rs");
+        let read_at = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let changed_at = read_at + Duration::from_secs(5);
+        let mut ledger = FileAccessLedger::default();
+
+        assert!(!ledger.changed_since_seen(path, Some(changed_at)));
+
+        ledger.record_read(path.to_path_buf(), Some(read_at));
+        assert!(!ledger.changed_since_seen(path, Some(read_at)));
+        assert!(ledger.changed_since_seen(path, Some(changed_at)));
+
+        ledger.record_write(path.to_path_buf(), Some(changed_at));
+        assert!(!ledger.changed_since_seen(path, Some(changed_at)));
+    }
+
+    #[test]
+    fn command_writes_finds_targets_of_known_commands() {
+        let cwd = Path::new("/repo");
+
+        assert_eq!(
+            command_writes(
+                &words(&["bash", "-lc", "mkdir -p out && cp a.txt out/b.txt"]),
+                cwd
+            ),
+            vec![PathBuf::from("/repo/out"), PathBuf::from("/repo/out/b.txt")]
+        );
+        assert_eq!(
+            command_writes(&words(&["sed", "-i", "s/a/b/", "src/lib.rs"]), cwd),
+            vec![PathBuf::from("/repo/src/lib.rs")]
+        );
+        assert_eq!(
+            command_writes(&words(&["sed", "s/a/b/", "src/lib.rs"]), cwd),
+            Vec::<PathBuf>::new()
+        );
+        assert_eq!(
+            command_writes(&words(&["cat", "src/lib.rs"]), cwd),
+            Vec::<PathBuf>::new()
+        );
+    }
+}
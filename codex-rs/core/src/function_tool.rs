@@ -9,3 +9,29 @@ pub enum FunctionCallError {
     #[error("Fatal error: {0}")]
     Fatal(String),
 }
+
+impl FunctionCallError {
+    /// Tags this error with the tool call that produced it.
+    pub fn for_call(
+        self,
+        call_id: impl Into<String>,
+        tool_name: impl Into<String>,
+    ) -> ToolCallError {
+        ToolCallError {
+            call_id: call_id.into(),
+            tool_name: tool_name.into(),
+            error: self,
+        }
+    }
+}
+
+/// A [`FunctionCallError`] together with the call it belongs to, so failures
+/// from calls running side by side can be told apart. Displays exactly like
+/// the wrapped error; the model never sees the extra fields.
+#[derive(Debug, Error, PartialEq)]
+#[error("{error}")]
+pub struct ToolCallError {
+    pub call_id: String,
+    pub tool_name: String,
+    pub error: FunctionCallError,
+}
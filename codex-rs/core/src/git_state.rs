@@ -0,0 +1,235 @@
+//! Noticing commands that change git state behind the agent's back.
+//!
+//! When a turn starts in a git repository, the session records HEAD, the
+//! current branch and the number of stashes. Every model command that exits
+//! 0 reads them again with a single `git rev-parse`; the stash count comes
+//! from the stash reflog, so no second `git` runs. A change made by a
+//! command that is not itself a git command meant to make it (`git commit`,
+//! `git checkout`, `git stash`, ...) is reported with `GitStateChanged` and
+//! a caution in the turn's summary. Each change is reported once: the values
+//! read after a command are what the next command is compared against.
+
+use std::path::Path;
+
+use codex_protocol::parse_command::ParsedCommand;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::git_info::get_git_repo_root;
+use crate::git_info::run_git_command_with_timeout;
+use crate::parse_command::shlex_join;
+use crate::protocol::EventMsg;
+use crate::protocol::GitStateChangedEvent;
+use crate::protocol::GitStateSnapshot;
+
+/// Git subcommands expected to move HEAD, switch branches or touch the
+/// stash.
+const MUTATING_SUBCOMMANDS: &[&str] = &[
+    "am",
+    "bisect",
+    "branch",
+    "checkout",
+    "cherry-pick",
+    "commit",
+    "merge",
+    "pull",
+    "rebase",
+    "reset",
+    "revert",
+    "stash",
+    "switch",
+];
+
+/// Reads the git state of the repository holding `cwd`. `None` outside a
+/// repository, before its first commit, or when `git` fails.
+pub(crate) async fn snapshot(cwd: &Path) -> Option<GitStateSnapshot> {
+    get_git_repo_root(cwd)?;
+    let output = run_git_command_with_timeout(
+        &[
+            "rev-parse",
+            "--git-common-dir",
+            "HEAD",
+            "--abbrev-ref",
+            "HEAD",
+        ],
+        cwd,
+    )
+    .await?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let mut lines = stdout.lines();
+    let common_dir = cwd.join(lines.next()?);
+    let head = lines.next()?.to_string();
+    let branch = match lines.next()? {
+        "HEAD" => None,
+        branch => Some(branch.to_string()),
+    };
+    let stash_count = tokio::fs::read_to_string(common_dir.join("logs/refs/stash"))
+        .await
+        .map_or(0, |reflog| reflog.lines().count() as u32);
+    Some(GitStateSnapshot {
+        head,
+        branch,
+        stash_count,
+    })
+}
+
+/// Whether one of `parsed` is a git command expected to change git state.
+pub(crate) fn is_git_mutation(parsed: &[ParsedCommand]) -> bool {
+    parsed.iter().any(|parsed| match parsed {
+        ParsedCommand::Unknown { cmd } => shlex::split(cmd)
+            .as_deref()
+            .and_then(git_subcommand)
+            .is_some_and(|subcommand| MUTATING_SUBCOMMANDS.contains(&subcommand)),
+        _ => false,
+    })
+}
+
+/// The subcommand of a `git` invocation, skipping global options such as
+/// `-C <path>` and `-c <name>=<value>`.
+fn git_subcommand(tokens: &[String]) -> Option<&str> {
+    let (program, args) = tokens.split_first()?;
+    if Path::new(program).file_name()? != "git" {
+        return None;
+    }
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace" => {
+                args.next();
+            }
+            arg if arg.starts_with('-') => {}
+            subcommand => return Some(subcommand),
+        }
+    }
+    None
+}
+
+/// Records the git state the turn's commands are compared against.
+pub(crate) async fn record_baseline(session: &Session, turn: &TurnContext) {
+    let state = snapshot(&turn.cwd).await;
+    session.set_turn_git_state(state).await;
+}
+
+/// Compares the git state after a successful exec call with the state
+/// before it, reporting a change unless `intended` or a git command in
+/// `parsed` accounts for it.
+pub(crate) async fn check_after_exec(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    command: &[String],
+    parsed: &[ParsedCommand],
+    intended: bool,
+) {
+    let Some(before) = session.turn_git_state().await else {
+        return;
+    };
+    let Some(after) = snapshot(&turn.cwd).await else {
+        return;
+    };
+    if after == before {
+        return;
+    }
+    session.set_turn_git_state(Some(after.clone())).await;
+    if intended || is_git_mutation(parsed) {
+        return;
+    }
+    session
+        .add_turn_caution(format!(
+            "`{}` {}",
+            shlex_join(command),
+            before.describe_change(&after)
+        ))
+        .await;
+    let event = GitStateChangedEvent {
+        command_call_id: call_id.to_string(),
+        turn_id: turn.sub_id.clone(),
+        command: command.to_vec(),
+        before,
+        after,
+    };
+    session
+        .send_event(turn, EventMsg::GitStateChanged(event))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command::parse_command;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    fn parsed(script: &str) -> Vec<ParsedCommand> {
+        parse_command(&["bash".to_string(), "-lc".to_string(), script.to_string()])
+    }
+
+    #[test]
+    fn git_commands_that_move_head_are_mutations() {
+        for script in [
+            "git commit -m 'fix'",
+            "git -C repo checkout main",
+            "git -c user.name=x stash push",
+            "cargo fmt && git commit -am fix",
+        ] {
+            assert!(is_git_mutation(&parsed(script)), "{script}");
+        }
+        for script in [
+            "git status",
+            "git log --oneline",
+            "make release",
+            "cargo test",
+        ] {
+            assert!(!is_git_mutation(&parsed(script)), "{script}");
+        }
+    }
+
+    #[test]
+    fn describe_change_names_each_part_that_changed() {
+        let before = GitStateSnapshot {
+            head: "1a2b3c4d5e6f".to_string(),
+            branch: Some("main".to_string()),
+            stash_count: 0,
+        };
+        let after = GitStateSnapshot {
+            head: "5d6e7f8a9b0c".to_string(),
+            branch: None,
+            stash_count: 1,
+        };
+        assert_eq!(
+            before.describe_change(&after),
+            "switched from main to detached HEAD, moved HEAD from 1a2b3c4 to 5d6e7f8, \
+             changed the stash from 0 to 1 entries"
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_reads_head_branch_and_stash_count() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(repo.path())
+                .status()
+                .expect("run git");
+            assert!(status.success(), "git {args:?}");
+        };
+        assert_eq!(snapshot(repo.path()).await, None);
+
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(repo.path().join("a.txt"), "a").expect("write");
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "first"]);
+        std::fs::write(repo.path().join("a.txt"), "b").expect("write");
+        git(&["stash", "-q"]);
+
+        let state = snapshot(repo.path()).await.expect("snapshot");
+        assert_eq!(state.branch.as_deref(), Some("main"));
+        assert_eq!(state.stash_count, 1);
+        assert_eq!(state.head.len(), 40);
+    }
+}
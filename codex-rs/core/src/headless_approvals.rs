@@ -0,0 +1,215 @@
+//! Answers approval requests without asking anyone, for runs such as
+//! `codex exec` where nobody is around to respond to a prompt. Every decision
+//! is reported as a `HeadlessApprovalDecision` event, and a denial's reason is
+//! returned to the model so it can change course.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::ReviewDecision;
+
+use crate::bash::parse_shell_lc_plain_commands;
+use crate::command_safety::is_safe_command::is_known_safe_command;
+
+const DENIAL_HINT: &str = "Do not retry it. If the change is still needed, \
+     describe the command in your final message so a person can run it.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessApprovals {
+    /// Approves commands known to only read state; denies everything else.
+    AutoReadonly,
+    /// Denies every request.
+    DenyAll,
+    /// Approves commands that start with one of the file's entries.
+    AllowList(AllowList),
+}
+
+/// Command prefixes read from an allow-list file: one per line, split like a
+/// shell would. Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowList {
+    path: PathBuf,
+    prefixes: Vec<Vec<String>>,
+}
+
+pub(crate) struct HeadlessDecision {
+    pub decision: ReviewDecision,
+    pub reason: String,
+}
+
+impl HeadlessApprovals {
+    /// Parses `auto-readonly`, `deny-all`, or `allow-list=<file>`, reading
+    /// the allow-list right away so a bad file fails before the run starts.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "auto-readonly" => Ok(Self::AutoReadonly),
+            "deny-all" => Ok(Self::DenyAll),
+            _ => match value.strip_prefix("allow-list=") {
+                Some(path) if !path.is_empty() => AllowList::load(Path::new(path))
+                    .map(Self::AllowList)
+                    .map_err(|err| format!("failed to read allow-list {path}: {err}")),
+                _ => Err(format!(
+                    "invalid approvals policy `{value}`; expected auto-readonly, deny-all, or allow-list=<file>"
+                )),
+            },
+        }
+    }
+
+    pub(crate) fn decide_exec(&self, command: &[String]) -> HeadlessDecision {
+        match self {
+            Self::AutoReadonly if is_known_safe_command(command) => approve("read-only command"),
+            Self::AutoReadonly => {
+                deny("only read-only commands are approved in this run.".to_string())
+            }
+            Self::DenyAll => deny("no approvals can be granted in this run.".to_string()),
+            Self::AllowList(allow_list) => match allow_list.matching_entry(command) {
+                Some(entry) => approve(&format!("matches allow-list entry `{entry}`")),
+                None => deny(format!(
+                    "the command does not match any entry of the allow-list {}.",
+                    allow_list.path.display()
+                )),
+            },
+        }
+    }
+
+    pub(crate) fn decide_patch(&self) -> HeadlessDecision {
+        deny("file edits that need approval cannot be approved in this run.".to_string())
+    }
+}
+
+impl fmt::Display for HeadlessApprovals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AutoReadonly => write!(f, "auto-readonly"),
+            Self::DenyAll => write!(f, "deny-all"),
+            Self::AllowList(allow_list) => write!(f, "allow-list={}", allow_list.path.display()),
+        }
+    }
+}
+
+impl AllowList {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let prefixes = Self::parse(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            prefixes,
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Vec<Vec<String>>, String> {
+        let mut prefixes = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match shlex::split(line) {
+                Some(prefix) if !prefix.is_empty() => prefixes.push(prefix),
+                _ => return Err(format!("line {} is not a valid command", index + 1)),
+            }
+        }
+        Ok(prefixes)
+    }
+
+    /// Returns the entry that allows `command`. A `bash -lc` script is only
+    /// allowed when every command in it is.
+    fn matching_entry(&self, command: &[String]) -> Option<String> {
+        let commands =
+            parse_shell_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+        let mut matched = None;
+        for command in &commands {
+            let prefix = self
+                .prefixes
+                .iter()
+                .find(|prefix| command.starts_with(prefix))?;
+            matched.get_or_insert_with(|| prefix.join(" "));
+        }
+        matched
+    }
+}
+
+fn approve(reason: &str) -> HeadlessDecision {
+    HeadlessDecision {
+        decision: ReviewDecision::Approved,
+        reason: reason.to_string(),
+    }
+}
+
+fn deny(why: String) -> HeadlessDecision {
+    HeadlessDecision {
+        decision: ReviewDecision::Denied,
+        reason: format!("rejected by the headless approval policy: {why} {DENIAL_HINT}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn cmd(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn allow_list(contents: &str) -> HeadlessApprovals {
+        HeadlessApprovals::AllowList(AllowList {
+            path: PathBuf::from("allow.txt"),
+            prefixes: AllowList::parse(contents).expect("valid allow-list"),
+        })
+    }
+
+    #[test]
+    fn auto_readonly_only_approves_known_safe_commands() {
+        let policy = HeadlessApprovals::AutoReadonly;
+        assert_eq!(
+            policy.decide_exec(&cmd(&["ls", "-la"])).decision,
+            ReviewDecision::Approved
+        );
+        let denied = policy.decide_exec(&cmd(&["rm", "-rf", "target"]));
+        assert_eq!(denied.decision, ReviewDecision::Denied);
+        assert!(denied.reason.contains("only read-only commands"));
+        assert_eq!(policy.decide_patch().decision, ReviewDecision::Denied);
+    }
+
+    #[test]
+    fn allow_list_matches_prefixes_of_every_command_in_a_script() {
+        let policy = allow_list("# build steps\ncargo test\n\ngit status\n");
+        assert_eq!(
+            policy
+                .decide_exec(&cmd(&["cargo", "test", "-p", "codex-core"]))
+                .decision,
+            ReviewDecision::Approved
+        );
+        assert_eq!(
+            policy
+                .decide_exec(&cmd(&["bash", "-lc", "git status && cargo test"]))
+                .decision,
+            ReviewDecision::Approved
+        );
+        assert_eq!(
+            policy
+                .decide_exec(&cmd(&["bash", "-lc", "cargo test && rm -rf /"]))
+                .decision,
+            ReviewDecision::Denied
+        );
+        assert_eq!(
+            policy.decide_exec(&cmd(&["cargo", "publish"])).decision,
+            ReviewDecision::Denied
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_policies() {
+        assert_eq!(
+            HeadlessApprovals::parse("deny-all"),
+            Ok(HeadlessApprovals::DenyAll)
+        );
+        assert!(HeadlessApprovals::parse("allow-all").is_err());
+        assert!(HeadlessApprovals::parse("allow-list=").is_err());
+        assert!(HeadlessApprovals::parse("allow-list=/does/not/exist").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Runs the user's `[hooks]` commands at points in a session's lifecycle and
+//! reports each run with a `HookExecuted` event.
+//!
+//! Hooks run in the background, so a slow hook does not hold up the session
+//! start, patch, or turn that triggered it; its event follows whenever the
+//! command finishes. Shutdown waits for running hooks to be reported.
+
+use std::path::PathBuf;
+use std::sync::Weak;
+use std::time::Instant;
+
+use async_channel::Receiver;
+use async_channel::Sender;
+use codex_protocol::protocol::HookExecutedEvent;
+use codex_protocol::protocol::HookTrigger;
+use codex_protocol::protocol::SandboxPolicy;
+use serde::Serialize;
+use tokio_util::task::TaskTracker;
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tracing::debug;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecParams;
+use crate::exec::process_exec_tool_call;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::sandboxing::SandboxPermissions;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+/// Set for every hook command to the name of its trigger. Hooks do not run
+/// while it is set, so a hook that launches Codex cannot trigger itself
+/// again.
+pub(crate) const CODEX_HOOK_ENV_VAR: &str = "CODEX_HOOK";
+
+/// Set for every hook command to the JSON-serialized [`HookPayload`].
+pub(crate) const CODEX_HOOK_PAYLOAD_ENV_VAR: &str = "CODEX_HOOK_PAYLOAD";
+
+/// Maximum bytes of stdout and of stderr attached to a `HookExecuted` event.
+const HOOK_OUTPUT_MAX_BYTES: usize = 8 * 1024;
+
+/// Describes the event that triggered a hook. Passed to the command as JSON
+/// in `CODEX_HOOK_PAYLOAD`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "hook", rename_all = "snake_case")]
+pub(crate) enum HookPayload {
+    SessionStart {
+        thread_id: String,
+        cwd: PathBuf,
+        model: String,
+    },
+    PatchApplied {
+        thread_id: String,
+        turn_id: String,
+        cwd: PathBuf,
+        call_id: String,
+        /// Files the patch added, deleted, or modified.
+        changed_paths: Vec<PathBuf>,
+    },
+    TurnComplete {
+        thread_id: String,
+        turn_id: String,
+        cwd: PathBuf,
+        last_agent_message: Option<String>,
+    },
+}
+
+impl HookPayload {
+    fn trigger(&self) -> HookTrigger {
+        match self {
+            HookPayload::SessionStart { .. } => HookTrigger::SessionStart,
+            HookPayload::PatchApplied { .. } => HookTrigger::PatchApplied,
+            HookPayload::TurnComplete { .. } => HookTrigger::TurnComplete,
+        }
+    }
+}
+
+fn trigger_name(trigger: HookTrigger) -> &'static str {
+    match trigger {
+        HookTrigger::SessionStart => "session_start",
+        HookTrigger::PatchApplied => "patch_applied",
+        HookTrigger::TurnComplete => "turn_complete",
+    }
+}
+
+/// Hands hooks to [`run_hook_jobs`] and keeps track of them until they are
+/// reported.
+pub(crate) struct HookRunner {
+    jobs: Sender<HookJob>,
+    running: TaskTracker,
+}
+
+/// A hook command with everything it needs from the turn that triggered it.
+pub(crate) struct HookJob {
+    trigger: HookTrigger,
+    sub_id: String,
+    params: ExecParams,
+    sandbox_policy: SandboxPolicy,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    /// Keeps [`HookRunner::wait`] waiting while the job is queued or running.
+    _running: TaskTrackerToken,
+}
+
+impl HookRunner {
+    /// The returned receiver must be drained by [`run_hook_jobs`].
+    pub(crate) fn new() -> (Self, Receiver<HookJob>) {
+        let (jobs, rx) = async_channel::unbounded();
+        let runner = Self {
+            jobs,
+            running: TaskTracker::new(),
+        };
+        (runner, rx)
+    }
+
+    /// Waits until every hook started so far has been reported.
+    pub(crate) async fn wait(&self) {
+        self.running.close();
+        self.running.wait().await;
+    }
+}
+
+/// Runs each queued hook in its own task for as long as the session lives.
+pub(crate) async fn run_hook_jobs(session: Weak<Session>, jobs: Receiver<HookJob>) {
+    while let Ok(job) = jobs.recv().await {
+        let Some(sess) = session.upgrade() else {
+            break;
+        };
+        tokio::spawn(async move { run_hook_job(&sess, job).await });
+    }
+}
+
+/// Starts the command configured for the payload's trigger, if there is one.
+/// Its result is reported later with a `HookExecuted` event. Hooks skip the
+/// approval flow. A failing hook is only reported through the event and never
+/// fails the operation that triggered it.
+pub(crate) fn spawn_hook(sess: &Session, turn: &TurnContext, payload: HookPayload) {
+    let config = turn.client.config();
+    let hooks = &config.hooks;
+    let trigger = payload.trigger();
+    let command = match trigger {
+        HookTrigger::SessionStart => hooks.on_session_start.as_ref(),
+        HookTrigger::PatchApplied => hooks.on_patch_applied.as_ref(),
+        HookTrigger::TurnComplete => hooks.on_turn_complete.as_ref(),
+    };
+    let Some(command) = command else {
+        return;
+    };
+    if std::env::var_os(CODEX_HOOK_ENV_VAR).is_some() {
+        debug!(
+            "skipping {} hook: running inside a hook",
+            trigger_name(trigger)
+        );
+        return;
+    }
+
+    let sandbox_policy = if hooks.sandbox {
+        turn.sandbox_policy.clone()
+    } else {
+        SandboxPolicy::DangerFullAccess
+    };
+    let mut env = turn.exec_env();
+    env.insert(
+        CODEX_HOOK_ENV_VAR.to_string(),
+        trigger_name(trigger).to_string(),
+    );
+    env.insert(
+        CODEX_HOOK_PAYLOAD_ENV_VAR.to_string(),
+        serde_json::to_string(&payload).unwrap_or_default(),
+    );
+    let params = ExecParams {
+        command: command.clone(),
+        cwd: turn.cwd.clone(),
+        expiration: ExecExpiration::Timeout(hooks.timeout),
+        env,
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        arg0: None,
+    };
+    let job = HookJob {
+        trigger,
+        sub_id: turn.sub_id.clone(),
+        params,
+        sandbox_policy,
+        codex_linux_sandbox_exe: turn.codex_linux_sandbox_exe.clone(),
+        _running: sess.services.hooks.running.token(),
+    };
+    if sess.services.hooks.jobs.try_send(job).is_err() {
+        debug!(
+            "skipping {} hook: the session is shutting down",
+            trigger_name(trigger)
+        );
+    }
+}
+
+async fn run_hook_job(sess: &Session, job: HookJob) {
+    let HookJob {
+        trigger,
+        sub_id,
+        params,
+        sandbox_policy,
+        codex_linux_sandbox_exe,
+        _running,
+    } = job;
+    let command = params.command.clone();
+    let cwd = params.cwd.clone();
+    let started = Instant::now();
+    let result = process_exec_tool_call(
+        params,
+        &sandbox_policy,
+        &cwd,
+        &codex_linux_sandbox_exe,
+        None,
+    )
+    .await;
+    // A command that exits non-zero under the sandbox, or runs out of time,
+    // still produced output worth reporting.
+    let result = match result {
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output } | SandboxErr::Timeout { output })) => {
+            Ok(*output)
+        }
+        result => result,
+    };
+    let event = match result {
+        Ok(output) => HookExecutedEvent {
+            hook: trigger,
+            command,
+            exit_code: (!output.timed_out).then_some(output.exit_code),
+            stdout: truncate_output(&output.stdout.text),
+            stderr: truncate_output(&output.stderr.text),
+            duration: output.duration,
+            timed_out: output.timed_out,
+            error: None,
+        },
+        Err(err) => HookExecutedEvent {
+            hook: trigger,
+            command,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration: started.elapsed(),
+            timed_out: false,
+            error: Some(err.to_string()),
+        },
+    };
+    sess.send_event_raw(Event {
+        id: sub_id,
+        msg: EventMsg::HookExecuted(event),
+    })
+    .await;
+}
+
+fn truncate_output(text: &str) -> String {
+    truncate_text(text, TruncationPolicy::Bytes(HOOK_OUTPUT_MAX_BYTES))
+}
@@ -8,7 +8,12 @@
 pub mod api_bridge;
 mod apply_patch;
 pub mod auth;
+mod background_jobs;
 pub mod bash;
+#[cfg(feature = "test-support")]
+#[doc(hidden)]
+pub mod bench_support;
+mod capabilities;
 mod client;
 mod client_common;
 pub mod codex;
@@ -16,6 +21,10 @@ mod codex_thread;
 mod compact_remote;
 pub use codex_thread::CodexThread;
 pub use codex_thread::ThreadConfigSnapshot;
+mod conversation;
+pub use conversation::Conversation;
+pub use conversation::IN_PROCESS_TOOL_SERVER;
+pub use conversation::InProcessTool;
 mod agent;
 mod codex_delegate;
 mod command_safety;
@@ -24,17 +33,26 @@ pub mod config_loader;
 pub mod connectors;
 mod context_manager;
 pub mod custom_prompts;
+mod diff_stats;
+pub use diff_stats::DiffStats;
 pub mod env;
 mod environment_context;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
 mod exec_policy;
+mod exec_timing;
 pub mod features;
+mod file_access;
 mod flags;
 pub mod git_info;
+mod git_state;
+pub mod headless_approvals;
+mod hooks;
 pub mod instructions;
 pub mod landlock;
+pub mod live_session;
+mod loop_detection;
 pub mod mcp;
 mod mcp_connection_manager;
 pub mod models_manager;
@@ -45,11 +63,14 @@ mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
 pub mod parse_command;
+mod parse_command_cache;
 pub mod path_utils;
 pub mod powershell;
 pub mod sandboxing;
 mod session_prefix;
+mod session_tmpdir;
 mod stream_events_utils;
+mod stream_recovery;
 mod text_encoding;
 pub mod token_data;
 mod truncate;
@@ -83,39 +104,58 @@ pub use auth::AuthManager;
 pub use auth::CodexAuth;
 pub mod default_client;
 pub mod project_doc;
+mod provider_failover;
+mod redact;
 mod rollout;
 pub(crate) mod safety;
+#[cfg(any(test, feature = "test-support"))]
+pub mod scripted_model;
 pub mod seatbelt;
 pub mod shell;
 pub mod shell_snapshot;
 pub mod skills;
 pub mod spawn;
 pub mod terminal;
+mod tool_budget;
+mod tool_digest;
+mod tool_tokens;
 mod tools;
 pub mod turn_diff_tracker;
+mod turn_progress;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
 pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::ThreadSummary;
+pub use rollout::archive_rollout;
 pub use rollout::find_archived_thread_path_by_id_str;
 #[deprecated(note = "use find_thread_path_by_id_str")]
 pub use rollout::find_conversation_path_by_id_str;
 pub use rollout::find_thread_path_by_id_str;
+pub use rollout::find_thread_path_by_id_str_in_state_dirs;
+pub use rollout::install_rollout_panic_hook;
 pub use rollout::list::Cursor;
 pub use rollout::list::ThreadItem;
+pub use rollout::list::ThreadProvenance;
 pub use rollout::list::ThreadSortKey;
 pub use rollout::list::ThreadsPage;
 pub use rollout::list::parse_cursor;
 pub use rollout::list::read_head_for_summary;
 pub use rollout::list::read_session_meta_line;
+pub use rollout::read_thread_summary;
 pub use rollout::rollout_date_parts;
+pub use rollout::sync_open_rollouts;
 mod function_tool;
 mod state;
 mod tasks;
 mod user_notification;
 mod user_shell_command;
 pub mod util;
+mod workspace_primer;
+mod workspace_roots;
+mod workspace_snapshots;
+mod workspace_watcher;
 
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
 pub use client::WEB_SEARCH_ELIGIBLE_HEADER;
@@ -0,0 +1,412 @@
+//! Read-only event stream that other processes can attach to while a session
+//! runs, e.g. `codex attach <SESSION_ID>`.
+//!
+//! A live session listens on `$CODEX_HOME/live/<thread id>.sock`, which only
+//! its owner can open. A client sends a one-line handshake and then receives
+//! the events emitted so far (up to [`REPLAY_BUFFER_BYTES`] of them) and
+//! every new event, one JSON-encoded [`Event`](codex_protocol::protocol::Event)
+//! per line. Nothing else is read from the socket, so attached clients cannot
+//! submit input or answer approvals, and a client going away only ends its
+//! own connection.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::EventMsg;
+
+/// Directory under `CODEX_HOME` holding the sockets of live sessions.
+pub const LIVE_SUBDIR: &str = "live";
+
+/// Socket a live session with `thread_id` listens on.
+pub fn live_socket_path(codex_home: &Path, thread_id: ThreadId) -> PathBuf {
+    codex_home
+        .join(LIVE_SUBDIR)
+        .join(format!("{thread_id}.sock"))
+}
+
+/// Most bytes of serialized events kept for replay to clients that attach
+/// later. The oldest events go first, except the very first one, which says
+/// what session this is.
+pub const REPLAY_BUFFER_BYTES: usize = 8 * 1024 * 1024;
+
+/// What an attach client sends before the server starts streaming. A
+/// connection that closes without it, like [`is_live`]'s, costs the session
+/// nothing.
+#[cfg(unix)]
+const ATTACH_HANDSHAKE: &[u8] = b"attach\n";
+
+/// Whether a session with `thread_id` is running and accepts attach clients.
+/// Only connects to the socket, so it is cheap to call for many sessions.
+pub fn is_live(codex_home: &Path, thread_id: ThreadId) -> bool {
+    #[cfg(unix)]
+    {
+        let path = live_socket_path(codex_home, thread_id);
+        // A socket left behind by a crashed run still exists but refuses
+        // connections.
+        path.exists() && std::os::unix::net::UnixStream::connect(path).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (codex_home, thread_id);
+        false
+    }
+}
+
+/// Streaming deltas are left out of the replay: the items they build up are
+/// replayed whole once they complete.
+fn is_replayed(msg: &EventMsg) -> bool {
+    !matches!(
+        msg,
+        EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::AgentMessageContentDelta(_)
+            | EventMsg::ReasoningContentDelta(_)
+            | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+            | EventMsg::McpToolCallOutputDelta(_)
+    )
+}
+
+#[cfg(unix)]
+pub use unix::LiveEventReader;
+#[cfg(unix)]
+pub(crate) use unix::LiveEventStream;
+#[cfg(not(unix))]
+pub(crate) use unsupported::LiveEventStream;
+
+/// Connects to the live session with `thread_id`.
+#[cfg(unix)]
+pub async fn attach(codex_home: &Path, thread_id: ThreadId) -> std::io::Result<LiveEventReader> {
+    LiveEventReader::connect(&live_socket_path(codex_home, thread_id)).await
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::collections::VecDeque;
+    use std::io;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::PoisonError;
+    use std::time::Duration;
+
+    use codex_protocol::protocol::Event;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::io::Lines;
+    use tokio::net::UnixListener;
+    use tokio::net::UnixStream;
+    use tokio::sync::broadcast;
+    use tokio::sync::broadcast::error::RecvError;
+    use tokio_util::task::AbortOnDropHandle;
+    use tracing::debug;
+    use tracing::warn;
+
+    use super::ATTACH_HANDSHAKE;
+    use super::REPLAY_BUFFER_BYTES;
+    use super::is_replayed;
+
+    /// Events a slow client may fall behind by before it is disconnected.
+    const CLIENT_BUFFER: usize = 4096;
+
+    /// How long a client has to send the handshake after connecting.
+    const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// One JSON-encoded event, newline included, shared by every client.
+    type Line = Arc<[u8]>;
+
+    /// Serialized events for clients that attach later, capped by size.
+    pub(super) struct ReplayBuffer {
+        first: Option<Line>,
+        rest: VecDeque<Line>,
+        rest_bytes: usize,
+        max_bytes: usize,
+    }
+
+    impl ReplayBuffer {
+        pub(super) fn new(max_bytes: usize) -> Self {
+            Self {
+                first: None,
+                rest: VecDeque::new(),
+                rest_bytes: 0,
+                max_bytes,
+            }
+        }
+
+        pub(super) fn push(&mut self, line: Line) {
+            if self.first.is_none() {
+                self.first = Some(line);
+                return;
+            }
+            self.rest_bytes += line.len();
+            self.rest.push_back(line);
+            while self.rest_bytes > self.max_bytes
+                && let Some(oldest) = self.rest.pop_front()
+            {
+                self.rest_bytes -= oldest.len();
+            }
+        }
+
+        pub(super) fn snapshot(&self) -> Vec<Line> {
+            self.first.iter().chain(&self.rest).cloned().collect()
+        }
+    }
+
+    struct Shared {
+        replay: ReplayBuffer,
+        tx: broadcast::Sender<Line>,
+    }
+
+    /// Server side of a live session: accepts attach clients and fans every
+    /// published event out to them.
+    pub(crate) struct LiveEventStream {
+        path: PathBuf,
+        shared: Arc<Mutex<Shared>>,
+        _accept_task: AbortOnDropHandle<()>,
+    }
+
+    impl LiveEventStream {
+        pub(crate) fn start(path: PathBuf) -> io::Result<Self> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+                // The events carry the whole transcript, so no other user may
+                // reach the socket, whatever the umask was.
+                std::fs::set_permissions(parent, std::fs::Permissions::from_mode(0o700))?;
+            }
+            // A socket left behind by a crashed run would make `bind` fail.
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+            let listener = UnixListener::bind(&path)?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            let (tx, _) = broadcast::channel(CLIENT_BUFFER);
+            let shared = Arc::new(Mutex::new(Shared {
+                replay: ReplayBuffer::new(REPLAY_BUFFER_BYTES),
+                tx,
+            }));
+            let accept_task =
+                AbortOnDropHandle::new(tokio::spawn(accept_clients(listener, Arc::clone(&shared))));
+            Ok(Self {
+                path,
+                shared,
+                _accept_task: accept_task,
+            })
+        }
+
+        pub(crate) fn publish(&self, event: &Event) {
+            let mut line = match serde_json::to_vec(event) {
+                Ok(line) => line,
+                Err(err) => {
+                    warn!("failed to serialize live session event: {err}");
+                    return;
+                }
+            };
+            line.push(b'\n');
+            let line: Line = line.into();
+            let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+            if is_replayed(&event.msg) {
+                shared.replay.push(Arc::clone(&line));
+            }
+            // No receivers just means nobody is attached right now.
+            let _ = shared.tx.send(line);
+        }
+    }
+
+    impl Drop for LiveEventStream {
+        fn drop(&mut self) {
+            if let Err(err) = std::fs::remove_file(&self.path) {
+                debug!("failed to remove live session socket: {err}");
+            }
+        }
+    }
+
+    async fn accept_clients(listener: UnixListener, shared: Arc<Mutex<Shared>>) {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    warn!("failed to accept live session client: {err}");
+                    continue;
+                }
+            };
+            tokio::spawn(serve_client(stream, Arc::clone(&shared)));
+        }
+    }
+
+    async fn serve_client(mut stream: UnixStream, shared: Arc<Mutex<Shared>>) {
+        let mut handshake = [0u8; ATTACH_HANDSHAKE.len()];
+        match tokio::time::timeout(HANDSHAKE_TIMEOUT, stream.read_exact(&mut handshake)).await {
+            Ok(Ok(_)) if handshake.as_slice() == ATTACH_HANDSHAKE => {}
+            // A liveness probe, or something that is not an attach client.
+            _ => return,
+        }
+        // Subscribing under the same lock as the replay snapshot means no
+        // event is missed or sent twice.
+        let (replay, mut rx) = {
+            let shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+            (shared.replay.snapshot(), shared.tx.subscribe())
+        };
+        for line in &replay {
+            if stream.write_all(line).await.is_err() {
+                return;
+            }
+        }
+        drop(replay);
+        loop {
+            let line = match rx.recv().await {
+                Ok(line) => line,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("live session client fell {skipped} events behind; disconnecting");
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if stream.write_all(&line).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Client side of a live session.
+    pub struct LiveEventReader {
+        lines: Lines<BufReader<UnixStream>>,
+    }
+
+    impl LiveEventReader {
+        pub(crate) async fn connect(path: &Path) -> io::Result<Self> {
+            let mut stream = UnixStream::connect(path).await?;
+            stream.write_all(ATTACH_HANDSHAKE).await?;
+            Ok(Self {
+                lines: BufReader::new(stream).lines(),
+            })
+        }
+
+        /// Next event of the session, or `None` once the session has ended.
+        pub async fn next_event(&mut self) -> io::Result<Option<Event>> {
+            let Some(line) = self.lines.next_line().await? else {
+                return Ok(None);
+            };
+            serde_json::from_str(&line)
+                .map(Some)
+                .map_err(io::Error::other)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use std::io;
+    use std::path::PathBuf;
+
+    use codex_protocol::protocol::Event;
+
+    pub(crate) struct LiveEventStream;
+
+    impl LiveEventStream {
+        pub(crate) fn start(_path: PathBuf) -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "live sessions are only supported on Unix",
+            ))
+        }
+
+        pub(crate) fn publish(&self, _event: &Event) {}
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::AgentMessageDeltaEvent;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use codex_protocol::protocol::Event;
+    use codex_protocol::protocol::WarningEvent;
+    use pretty_assertions::assert_eq;
+    use std::io;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    fn event(msg: EventMsg) -> Event {
+        Event {
+            id: "turn-1".to_string(),
+            msg,
+        }
+    }
+
+    fn message(text: &str) -> Event {
+        event(EventMsg::AgentMessage(AgentMessageEvent {
+            message: text.to_string(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn attached_client_gets_replay_then_live_events() -> io::Result<()> {
+        let codex_home = tempfile::tempdir()?;
+        let thread_id = ThreadId::new();
+        let path = live_socket_path(codex_home.path(), thread_id);
+        let stream = LiveEventStream::start(path.clone())?;
+        assert!(is_live(codex_home.path(), thread_id));
+        let mode = |path: &Path| -> io::Result<u32> {
+            Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+        };
+        assert_eq!(mode(&codex_home.path().join(LIVE_SUBDIR))?, 0o700);
+        assert_eq!(mode(&path)?, 0o600);
+
+        stream.publish(&message("before"));
+        stream.publish(&event(EventMsg::AgentMessageDelta(
+            AgentMessageDeltaEvent {
+                delta: "not replayed".to_string(),
+            },
+        )));
+        let mut reader = attach(codex_home.path(), thread_id).await?;
+        let mut second = attach(codex_home.path(), thread_id).await?;
+        drop(second.next_event().await?);
+        drop(second);
+
+        stream.publish(&event(EventMsg::Warning(WarningEvent {
+            message: "after".to_string(),
+        })));
+
+        assert_eq!(
+            serde_json::to_value(reader.next_event().await?)?,
+            serde_json::to_value(Some(message("before")))?
+        );
+        let Some(Event {
+            msg: EventMsg::Warning(warning),
+            ..
+        }) = reader.next_event().await?
+        else {
+            panic!("expected the warning published after attaching");
+        };
+        assert_eq!(warning.message, "after");
+
+        drop(stream);
+        assert!(!path.exists());
+        assert!(!is_live(codex_home.path(), thread_id));
+        Ok(())
+    }
+
+    #[test]
+    fn replay_keeps_the_first_event_and_the_newest_within_the_cap() {
+        let line = |text: &str| -> Arc<[u8]> { text.as_bytes().into() };
+        let mut replay = unix::ReplayBuffer::new(8);
+        for text in ["configured", "aaaa", "bbbb", "cccc"] {
+            replay.push(line(text));
+        }
+        assert_eq!(
+            replay.snapshot(),
+            vec![line("configured"), line("bbbb"), line("cccc")]
+        );
+
+        replay.push(line("a line longer than the cap"));
+        assert_eq!(replay.snapshot(), vec![line("configured")]);
+    }
+}
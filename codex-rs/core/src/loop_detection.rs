@@ -0,0 +1,224 @@
+//! Noticing the model rerunning a command that keeps failing the same way.
+//!
+//! Commands are told apart by the signature the approval cache stores them
+//! under, so two calls count as the same command exactly when a session
+//! approval of one would cover the other. A run that exits 0 clears the
+//! command's streak and a different exit code starts it over. Streaks outlive
+//! their turn, but are halved the first time a later turn runs the command,
+//! so a loop that spans turns is still noticed without an old streak
+//! tripping on the first retry.
+
+use std::collections::HashMap;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecToolCallOutput;
+use crate::function_tool::FunctionCallError;
+use crate::protocol::EventMsg;
+use crate::protocol::LoopDetectedEvent;
+use crate::tools::sandboxing::ToolError;
+
+/// Commands remembered at once. Past this, streaks from earlier turns are
+/// forgotten.
+const MAX_TRACKED_COMMANDS: usize = 256;
+
+#[derive(Default)]
+pub(crate) struct FailureLoopDetector {
+    streaks: HashMap<String, FailureStreak>,
+}
+
+struct FailureStreak {
+    exit_code: i32,
+    failures: u32,
+    /// The last turn that ran the command.
+    turn_id: String,
+}
+
+/// A command that has now failed `failures` times in a row with
+/// `exit_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FailureLoop {
+    pub(crate) exit_code: i32,
+    pub(crate) failures: u32,
+}
+
+impl FailureLoopDetector {
+    /// Records that the command with `signature` exited with `exit_code` in
+    /// turn `turn_id`. Returns its streak once it reaches `threshold`.
+    pub(crate) fn record(
+        &mut self,
+        signature: String,
+        turn_id: &str,
+        exit_code: i32,
+        threshold: u32,
+    ) -> Option<FailureLoop> {
+        if exit_code == 0 {
+            self.streaks.remove(&signature);
+            return None;
+        }
+        if self.streaks.len() >= MAX_TRACKED_COMMANDS && !self.streaks.contains_key(&signature) {
+            self.streaks.retain(|_, streak| streak.turn_id == turn_id);
+        }
+        let streak = self
+            .streaks
+            .entry(signature)
+            .or_insert_with(|| FailureStreak {
+                exit_code,
+                failures: 0,
+                turn_id: turn_id.to_string(),
+            });
+        if streak.exit_code != exit_code {
+            streak.exit_code = exit_code;
+            streak.failures = 0;
+        }
+        if streak.turn_id != turn_id {
+            streak.failures /= 2;
+            streak.turn_id = turn_id.to_string();
+        }
+        streak.failures += 1;
+        (streak.failures >= threshold).then_some(FailureLoop {
+            exit_code,
+            failures: streak.failures,
+        })
+    }
+}
+
+/// Exit code of a finished exec call, including one that timed out or was
+/// denied by the sandbox. `None` when the command never ran to an exit.
+pub(crate) fn exit_code_of(out: &Result<ExecToolCallOutput, ToolError>) -> Option<i32> {
+    match out {
+        Ok(output) => Some(output.exit_code),
+        Err(ToolError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output } | SandboxErr::Denied { output },
+        ))) => Some(output.exit_code),
+        Err(_) => None,
+    }
+}
+
+/// Records how `command` exited. Once it has failed the same way
+/// `[loop_detection] failure_threshold` times in a row, sends
+/// `LoopDetected` and returns advice to append to the model's result.
+pub(crate) async fn failure_loop_advice(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    command: &[String],
+    signature: Option<String>,
+    exit_code: i32,
+) -> Option<String> {
+    let threshold = turn.loop_detection.failure_threshold?;
+    let failure_loop = session
+        .record_command_exit(signature?, &turn.sub_id, exit_code, threshold)
+        .await?;
+    let event = LoopDetectedEvent {
+        call_id: call_id.to_string(),
+        turn_id: turn.sub_id.clone(),
+        command: command.to_vec(),
+        exit_code,
+        failures: failure_loop.failures,
+    };
+    session
+        .send_event(turn, EventMsg::LoopDetected(event))
+        .await;
+    Some(advice(failure_loop))
+}
+
+/// Appends `advice` to the text sent back to the model for an exec call.
+pub(crate) fn with_advice(
+    result: Result<String, FunctionCallError>,
+    advice: Option<String>,
+) -> Result<String, FunctionCallError> {
+    let Some(advice) = advice else {
+        return result;
+    };
+    match result {
+        Ok(content) => Ok(format!("{content}\n\n{advice}")),
+        Err(FunctionCallError::RespondToModel(content)) => Err(FunctionCallError::RespondToModel(
+            format!("{content}\n\n{advice}"),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+fn advice(failure_loop: FailureLoop) -> String {
+    format!(
+        "Note: this exact command has now failed {} times in a row with the same exit code ({}); consider a different approach instead of running it again.",
+        failure_loop.failures, failure_loop.exit_code
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn run(detector: &mut FailureLoopDetector, turn: &str, exit_code: i32) -> Option<u32> {
+        detector
+            .record("cargo test".to_string(), turn, exit_code, 3)
+            .map(|failure_loop| failure_loop.failures)
+    }
+
+    #[test]
+    fn identical_failures_trip_at_the_threshold_and_success_resets() {
+        let mut detector = FailureLoopDetector::default();
+        assert_eq!(run(&mut detector, "turn-1", 101), None);
+        assert_eq!(run(&mut detector, "turn-1", 101), None);
+        assert_eq!(run(&mut detector, "turn-1", 101), Some(3));
+        assert_eq!(run(&mut detector, "turn-1", 101), Some(4));
+
+        assert_eq!(run(&mut detector, "turn-1", 0), None);
+        assert_eq!(run(&mut detector, "turn-1", 101), None);
+
+        let other = detector.record("cargo build".to_string(), "turn-1", 101, 3);
+        assert_eq!(other, None);
+    }
+
+    #[test]
+    fn a_different_exit_code_starts_a_new_streak() {
+        let mut detector = FailureLoopDetector::default();
+        run(&mut detector, "turn-1", 1);
+        run(&mut detector, "turn-1", 1);
+        assert_eq!(run(&mut detector, "turn-1", 2), None);
+        assert_eq!(run(&mut detector, "turn-1", 2), None);
+        assert_eq!(run(&mut detector, "turn-1", 2), Some(3));
+    }
+
+    #[test]
+    fn streaks_are_halved_when_a_later_turn_runs_the_command() {
+        let mut detector = FailureLoopDetector::default();
+        for _ in 0..4 {
+            run(&mut detector, "turn-1", 1);
+        }
+        // 4 failures halve to 2, so the next one trips the threshold.
+        assert_eq!(run(&mut detector, "turn-2", 1), Some(3));
+
+        let mut detector = FailureLoopDetector::default();
+        run(&mut detector, "turn-1", 1);
+        run(&mut detector, "turn-1", 1);
+        assert_eq!(run(&mut detector, "turn-2", 1), None);
+    }
+
+    #[test]
+    fn advice_is_appended_to_successful_and_failed_results() {
+        let advice = || Some("try something else".to_string());
+        assert_eq!(
+            with_advice(Ok("output".to_string()), advice()).ok(),
+            Some("output\n\ntry something else".to_string())
+        );
+        match with_advice(
+            Err(FunctionCallError::RespondToModel("exit 1".to_string())),
+            advice(),
+        ) {
+            Err(FunctionCallError::RespondToModel(content)) => {
+                assert_eq!(content, "exit 1\n\ntry something else");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(
+            with_advice(Ok("output".to_string()), None).ok(),
+            Some("output".to_string())
+        );
+    }
+}
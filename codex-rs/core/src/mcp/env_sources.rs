@@ -0,0 +1,103 @@
+//! Resolves the `env_sources` of a stdio MCP server, and the `header_sources`
+//! of a streamable HTTP server, into concrete values. Values are only looked
+//! up when the server is started and are never written back into the config,
+//! so they stay out of session events and rollouts.
+
+use std::collections::HashMap;
+use std::env;
+
+use codex_keyring_store::KeyringStore;
+
+use crate::config::types::McpEnvSource;
+
+/// Keychain service under which `{ keychain = "<account>" }` entries are stored.
+pub const MCP_ENV_KEYRING_SERVICE: &str = "Codex MCP Env";
+
+/// Looks up every entry in `env_sources`. Fails with a message naming the
+/// first missing source (never its value) so the server can be reported as
+/// failed instead of being started without its credentials.
+pub(crate) fn resolve_env_sources(
+    env_sources: &HashMap<String, McpEnvSource>,
+    keyring_store: &dyn KeyringStore,
+) -> Result<HashMap<String, String>, String> {
+    let mut names: Vec<&String> = env_sources.keys().collect();
+    names.sort_unstable();
+
+    let mut resolved = HashMap::with_capacity(env_sources.len());
+    for name in names {
+        let value = match &env_sources[name] {
+            McpEnvSource::Env(var) => env::var(var).map_err(|_| {
+                format!("environment variable `{var}` for `{name}` is not set")
+            })?,
+            McpEnvSource::Keychain(account) => keyring_store
+                .load(MCP_ENV_KEYRING_SERVICE, account)
+                .map_err(|err| {
+                    format!(
+                        "failed to read keychain entry `{account}` for `{name}`: {}",
+                        err.message()
+                    )
+                })?
+                .ok_or_else(|| {
+                    format!(
+                        "keychain entry `{account}` for `{name}` not found in service `{MCP_ENV_KEYRING_SERVICE}`"
+                    )
+                })?,
+        };
+        resolved.insert(name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_keyring_store::tests::MockKeyringStore;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resolves_keychain_sources_and_reports_missing_ones() {
+        let keyring = MockKeyringStore::default();
+        keyring
+            .save(MCP_ENV_KEYRING_SERVICE, "github-token", "secret")
+            .expect("save");
+
+        let sources = HashMap::from([(
+            "GITHUB_TOKEN".to_string(),
+            McpEnvSource::Keychain("github-token".to_string()),
+        )]);
+        assert_eq!(
+            resolve_env_sources(&sources, &keyring),
+            Ok(HashMap::from([(
+                "GITHUB_TOKEN".to_string(),
+                "secret".to_string()
+            )]))
+        );
+
+        let sources = HashMap::from([(
+            "API_KEY".to_string(),
+            McpEnvSource::Keychain("missing".to_string()),
+        )]);
+        assert_eq!(
+            resolve_env_sources(&sources, &keyring),
+            Err(
+                "keychain entry `missing` for `API_KEY` not found in service `Codex MCP Env`"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_env_source_names_the_variable() {
+        let sources = HashMap::from([(
+            "API_KEY".to_string(),
+            McpEnvSource::Env("CODEX_TEST_MCP_ENV_SOURCE_UNSET".to_string()),
+        )]);
+        assert_eq!(
+            resolve_env_sources(&sources, &MockKeyringStore::default()),
+            Err(
+                "environment variable `CODEX_TEST_MCP_ENV_SOURCE_UNSET` for `API_KEY` is not set"
+                    .to_string()
+            )
+        );
+    }
+}
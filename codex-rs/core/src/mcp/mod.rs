@@ -1,4 +1,8 @@
 pub mod auth;
+pub(crate) mod env_sources;
+pub(crate) mod prompts;
+pub(crate) mod sampling;
+pub(crate) mod tools_cache;
 use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
@@ -90,6 +94,8 @@ fn codex_apps_mcp_server_config(config: &Config, auth: Option<&CodexAuth>) -> Mc
             bearer_token_env_var,
             http_headers,
             env_http_headers: None,
+            header_sources: None,
+            tls: None,
         },
         enabled: true,
         disabled_reason: None,
@@ -98,6 +104,8 @@ fn codex_apps_mcp_server_config(config: &Config, auth: Option<&CodexAuth>) -> Mc
         enabled_tools: None,
         disabled_tools: None,
         scopes: None,
+        max_restart_attempts: None,
+        sampling: None,
     }
 }
 
@@ -0,0 +1,198 @@
+//! Support for MCP prompts: checking user-supplied arguments against a
+//! prompt's definition and turning the messages returned by `prompts/get`
+//! into conversation items for the next turn.
+
+use std::collections::HashMap;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::Prompt;
+use mcp_types::PromptMessage;
+use mcp_types::Role;
+
+/// Checks `arguments` against the arguments `prompt` declares: every required
+/// argument must be given and no undeclared ones may be.
+pub(crate) fn validate_prompt_arguments(
+    prompt: &Prompt,
+    arguments: &HashMap<String, String>,
+) -> Result<(), String> {
+    let declared = prompt.arguments.as_deref().unwrap_or_default();
+
+    let mut missing: Vec<&str> = declared
+        .iter()
+        .filter(|argument| argument.required == Some(true))
+        .map(|argument| argument.name.as_str())
+        .filter(|name| !arguments.contains_key(*name))
+        .collect();
+    missing.sort_unstable();
+    if !missing.is_empty() {
+        return Err(format!(
+            "prompt `{}` is missing required arguments: {}",
+            prompt.name,
+            missing.join(", ")
+        ));
+    }
+
+    let mut unknown: Vec<&str> = arguments
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !declared.iter().any(|argument| argument.name == *name))
+        .collect();
+    unknown.sort_unstable();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "prompt `{}` does not take arguments: {}",
+            prompt.name,
+            unknown.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Converts prompt messages into conversation messages, one per prompt
+/// message. Content the model cannot take directly (audio, binary resources,
+/// and images in assistant messages) is described in text instead.
+pub(crate) fn prompt_messages_to_response_items(messages: &[PromptMessage]) -> Vec<ResponseItem> {
+    messages
+        .iter()
+        .map(|message| {
+            let (role, content) = match message.role {
+                Role::User => ("user", user_content(&message.content)),
+                Role::Assistant => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: content_text(&message.content),
+                    },
+                ),
+            };
+            ResponseItem::Message {
+                id: None,
+                role: role.to_string(),
+                content: vec![content],
+                end_turn: None,
+            }
+        })
+        .collect()
+}
+
+fn user_content(block: &ContentBlock) -> ContentItem {
+    match block {
+        ContentBlock::ImageContent(image) => ContentItem::InputImage {
+            image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+        },
+        other => ContentItem::InputText {
+            text: content_text(other),
+        },
+    }
+}
+
+fn content_text(block: &ContentBlock) -> String {
+    match block {
+        ContentBlock::TextContent(text) => text.text.clone(),
+        ContentBlock::ImageContent(image) => format!("[image: {}]", image.mime_type),
+        ContentBlock::AudioContent(audio) => format!("[audio: {}]", audio.mime_type),
+        ContentBlock::ResourceLink(link) => format!("[resource: {}]", link.uri),
+        ContentBlock::EmbeddedResource(embedded) => match &embedded.resource {
+            EmbeddedResourceResource::TextResourceContents(resource) => resource.text.clone(),
+            EmbeddedResourceResource::BlobResourceContents(resource) => {
+                format!("[resource: {}]", resource.uri)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use mcp_types::PromptArgument;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+
+    fn prompt() -> Prompt {
+        let argument = |name: &str, required: Option<bool>| PromptArgument {
+            description: None,
+            name: name.to_string(),
+            required,
+            title: None,
+        };
+        Prompt {
+            arguments: Some(vec![argument("topic", Some(true)), argument("tone", None)]),
+            description: None,
+            name: "summarize".to_string(),
+            title: None,
+        }
+    }
+
+    #[test]
+    fn validate_prompt_arguments_reports_missing_and_unknown_arguments() {
+        let prompt = prompt();
+        let args = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(
+            validate_prompt_arguments(&prompt, &args(&[("topic", "mcp")])),
+            Ok(())
+        );
+        assert_eq!(
+            validate_prompt_arguments(&prompt, &args(&[("tone", "dry")])),
+            Err("prompt `summarize` is missing required arguments: topic".to_string())
+        );
+        assert_eq!(
+            validate_prompt_arguments(&prompt, &args(&[("topic", "mcp"), ("length", "3")])),
+            Err("prompt `summarize` does not take arguments: length".to_string())
+        );
+    }
+
+    #[test]
+    fn prompt_messages_keep_their_roles() {
+        let messages = vec![
+            PromptMessage {
+                content: ContentBlock::TextContent(TextContent {
+                    annotations: None,
+                    text: "Review this diff.".to_string(),
+                    r#type: "text".to_string(),
+                }),
+                role: Role::User,
+            },
+            PromptMessage {
+                content: ContentBlock::ImageContent(ImageContent {
+                    annotations: None,
+                    data: "AAAA".to_string(),
+                    mime_type: "image/png".to_string(),
+                    r#type: "image".to_string(),
+                }),
+                role: Role::Assistant,
+            },
+        ];
+
+        assert_eq!(
+            prompt_messages_to_response_items(&messages),
+            vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "Review this diff.".to_string(),
+                    }],
+                    end_turn: None,
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "[image: image/png]".to_string(),
+                    }],
+                    end_turn: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Answers `sampling/createMessage` requests, through which an MCP server asks
+//! the client for a model completion. Each request is checked against the
+//! server's `sampling` policy, may wait for the user's approval, and runs on
+//! the session's model provider. Requests and their outcomes are emitted as
+//! `McpSamplingRequest`/`McpSamplingResponse` events, so they also end up in
+//! the rollout.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Weak;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+use async_channel::Receiver;
+use async_channel::Sender;
+use codex_protocol::models::BaseInstructions;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpSamplingRequestEvent;
+use codex_protocol::protocol::McpSamplingResponseEvent;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::TokenUsage;
+use codex_rmcp_client::SendSampling;
+use futures::FutureExt;
+use futures::StreamExt;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::RequestId;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use tokio::sync::Mutex;
+use tokio::sync::oneshot;
+
+use crate::Prompt;
+use crate::client::ModelClient;
+use crate::client_common::ResponseEvent;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::types::McpSamplingConfig;
+use crate::config::types::McpSamplingPolicy;
+
+const SAMPLING_EVENT_ID: &str = "mcp_sampling";
+
+/// A sampling request waiting to be answered by the session.
+pub(crate) struct SamplingJob {
+    server_name: String,
+    id: RequestId,
+    request: CreateMessageRequestParams,
+    config: McpSamplingConfig,
+    respond: oneshot::Sender<Result<CreateMessageResult, String>>,
+}
+
+/// Hands `server_name`'s sampling requests to the session through `jobs`.
+pub(crate) fn make_sampling_sender(
+    server_name: String,
+    config: McpSamplingConfig,
+    jobs: Sender<SamplingJob>,
+) -> SendSampling {
+    Box::new(move |id, request| {
+        let server_name = server_name.clone();
+        let config = config.clone();
+        let jobs = jobs.clone();
+        async move {
+            let (respond, response) = oneshot::channel();
+            jobs.send(SamplingJob {
+                server_name,
+                id,
+                request,
+                config,
+                respond,
+            })
+            .await
+            .map_err(|_| anyhow!("session is no longer running"))?;
+            response
+                .await
+                .context("sampling request was dropped")?
+                .map_err(|err| anyhow!(err))
+        }
+        .boxed()
+    })
+}
+
+/// The session's side of MCP sampling.
+pub(crate) struct McpSampling {
+    jobs: Sender<SamplingJob>,
+    state: Mutex<SamplingState>,
+}
+
+#[derive(Default)]
+struct SamplingState {
+    /// Requests waiting for `Op::ResolveMcpSampling`.
+    pending: HashMap<(String, RequestId), oneshot::Sender<ReviewDecision>>,
+    /// Tokens spent on each server's completions, including the `maxTokens`
+    /// reserved by requests still running.
+    tokens_spent: HashMap<String, u64>,
+    /// Servers whose requests no longer need approval.
+    approved_for_session: HashSet<String>,
+}
+
+impl McpSampling {
+    /// The returned receiver must be drained by [`run_sampling_jobs`].
+    pub(crate) fn new() -> (Self, Receiver<SamplingJob>) {
+        let (jobs, rx) = async_channel::unbounded();
+        let sampling = Self {
+            jobs,
+            state: Mutex::new(SamplingState::default()),
+        };
+        (sampling, rx)
+    }
+
+    pub(crate) fn jobs(&self) -> Sender<SamplingJob> {
+        self.jobs.clone()
+    }
+
+    pub(crate) async fn resolve(
+        &self,
+        server_name: String,
+        id: RequestId,
+        decision: ReviewDecision,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .await
+            .pending
+            .remove(&(server_name, id))
+            .ok_or_else(|| anyhow!("sampling request not found"))?
+            .send(decision)
+            .map_err(|e| anyhow!("failed to send sampling decision: {e:?}"))
+    }
+}
+
+/// Answers sampling requests until the session is dropped.
+pub(crate) async fn run_sampling_jobs(session: Weak<Session>, jobs: Receiver<SamplingJob>) {
+    while let Ok(job) = jobs.recv().await {
+        let Some(sess) = session.upgrade() else {
+            break;
+        };
+        tokio::spawn(async move { handle_sampling_job(&sess, job).await });
+    }
+}
+
+async fn handle_sampling_job(sess: &Arc<Session>, job: SamplingJob) {
+    let SamplingJob {
+        server_name,
+        id,
+        mut request,
+        config,
+        respond,
+    } = job;
+    let turn_context = sess.new_default_turn().await;
+    let model = config
+        .model
+        .clone()
+        .unwrap_or_else(|| turn_context.client.get_model());
+
+    // Register the pending approval before announcing the request so an
+    // immediate answer always finds it.
+    let (approval, reserved) = {
+        let mut state = sess.services.mcp_sampling.state.lock().await;
+        let tokens_spent = state.tokens_spent.get(&server_name).copied().unwrap_or(0);
+        let approved_for_session = state.approved_for_session.contains(&server_name);
+        if requires_approval(&config, tokens_spent, approved_for_session) {
+            let (tx, rx) = oneshot::channel();
+            state.pending.insert((server_name.clone(), id.clone()), tx);
+            (Some(rx), 0)
+        } else {
+            let max_tokens = u64::try_from(request.max_tokens).unwrap_or(0);
+            let reserved = reserve_tokens(&config, tokens_spent, approved_for_session, max_tokens);
+            if let Some(reserved) = reserved {
+                *state.tokens_spent.entry(server_name.clone()).or_default() += reserved;
+                request.max_tokens = i64::try_from(reserved).unwrap_or(i64::MAX);
+            }
+            (None, reserved.unwrap_or(0))
+        }
+    };
+    sess.send_event_raw(Event {
+        id: SAMPLING_EVENT_ID.to_string(),
+        msg: EventMsg::McpSamplingRequest(McpSamplingRequestEvent {
+            server_name: server_name.clone(),
+            id: id.clone(),
+            request: request.clone(),
+            model: model.clone(),
+            requires_approval: approval.is_some(),
+        }),
+    })
+    .await;
+
+    let approved = match approval {
+        None => true,
+        Some(rx) => match rx.await {
+            Ok(ReviewDecision::Approved) => true,
+            Ok(ReviewDecision::ApprovedForSession) => {
+                let mut state = sess.services.mcp_sampling.state.lock().await;
+                state.approved_for_session.insert(server_name.clone());
+                true
+            }
+            Ok(_) | Err(_) => false,
+        },
+    };
+    let (result, token_usage) = if approved {
+        match complete(sess, &turn_context, &model, &request).await {
+            Ok((result, token_usage)) => (Ok(result), token_usage),
+            Err(err) => (Err(err), None),
+        }
+    } else {
+        (
+            Err(format!(
+                "sampling request from MCP server '{server_name}' was declined by the user"
+            )),
+            None,
+        )
+    };
+
+    // Swap the reservation for what the completion actually used.
+    let spent = token_usage
+        .as_ref()
+        .map_or(0, |usage| u64::try_from(usage.total_tokens).unwrap_or(0));
+    if spent > 0 || reserved > 0 {
+        let mut state = sess.services.mcp_sampling.state.lock().await;
+        let total = state.tokens_spent.entry(server_name.clone()).or_default();
+        *total = total.saturating_sub(reserved) + spent;
+    }
+    sess.send_event_raw(Event {
+        id: SAMPLING_EVENT_ID.to_string(),
+        msg: EventMsg::McpSamplingResponse(McpSamplingResponseEvent {
+            server_name,
+            id,
+            result: result.clone(),
+            token_usage,
+        }),
+    })
+    .await;
+    let _ = respond.send(result);
+}
+
+/// `auto` runs requests without asking until the server has spent its token
+/// budget; after that, and always under `ask`, the user decides.
+fn requires_approval(
+    config: &McpSamplingConfig,
+    tokens_spent: u64,
+    approved_for_session: bool,
+) -> bool {
+    if approved_for_session {
+        return false;
+    }
+    match config.policy {
+        McpSamplingPolicy::Auto => config
+            .token_budget
+            .is_some_and(|budget| tokens_spent >= budget),
+        McpSamplingPolicy::Ask | McpSamplingPolicy::Deny => true,
+    }
+}
+
+/// Under `auto` with a budget, the request may generate at most what is left
+/// of it. Returns the clamped `maxTokens`, which the caller reserves up front so
+/// concurrent requests cannot overshoot the budget together.
+fn reserve_tokens(
+    config: &McpSamplingConfig,
+    tokens_spent: u64,
+    approved_for_session: bool,
+    max_tokens: u64,
+) -> Option<u64> {
+    if approved_for_session || config.policy != McpSamplingPolicy::Auto {
+        return None;
+    }
+    let budget = config.token_budget?;
+    Some(max_tokens.min(budget.saturating_sub(tokens_spent)))
+}
+
+async fn complete(
+    sess: &Session,
+    turn_context: &TurnContext,
+    model: &str,
+    request: &CreateMessageRequestParams,
+) -> Result<(CreateMessageResult, Option<TokenUsage>), String> {
+    let input = request
+        .messages
+        .iter()
+        .map(sampling_message_to_item)
+        .collect::<Result<Vec<_>, _>>()?;
+    let base_instructions = match &request.system_prompt {
+        Some(text) => BaseInstructions { text: text.clone() },
+        None => sess.get_base_instructions().await,
+    };
+    let prompt = Prompt {
+        input,
+        base_instructions,
+        max_output_tokens: u64::try_from(request.max_tokens)
+            .ok()
+            .filter(|max_tokens| *max_tokens > 0),
+        ..Default::default()
+    };
+
+    let client = sampling_client(sess, turn_context, model).await;
+    let mut client_session = client.new_session();
+    let mut stream = client_session
+        .stream(&prompt)
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut text = String::new();
+    loop {
+        match stream.next().await {
+            Some(Ok(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                role,
+                content,
+                ..
+            }))) if role == "assistant" => {
+                for item in content {
+                    if let ContentItem::OutputText { text: chunk } = item {
+                        text.push_str(&chunk);
+                    }
+                }
+            }
+            Some(Ok(ResponseEvent::Completed { token_usage, .. })) => {
+                let result = CreateMessageResult {
+                    content: CreateMessageResultContent::TextContent(TextContent {
+                        annotations: None,
+                        text,
+                        r#type: "text".to_string(),
+                    }),
+                    model: client.get_model(),
+                    role: Role::Assistant,
+                    stop_reason: Some("endTurn".to_string()),
+                };
+                return Ok((result, token_usage));
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(err)) => return Err(err.to_string()),
+            None => return Err("stream closed before response.completed".to_string()),
+        }
+    }
+}
+
+/// The turn's client, or one for `model` when the server's config picks a
+/// different model.
+async fn sampling_client(sess: &Session, turn_context: &TurnContext, model: &str) -> ModelClient {
+    if turn_context.client.get_model() == model {
+        return turn_context.client.clone();
+    }
+    let config = turn_context.client.config();
+    let model_info = sess
+        .services
+        .models_manager
+        .get_model_info(model, &config)
+        .await;
+    let mut per_turn_config = (*config).clone();
+    per_turn_config.model = Some(model.to_string());
+    let per_turn_config = Arc::new(per_turn_config);
+    ModelClient::new(
+        per_turn_config.clone(),
+        turn_context.client.get_auth_manager(),
+        model_info.clone(),
+        turn_context
+            .client
+            .get_otel_manager()
+            .with_model(model, model_info.slug.as_str()),
+        turn_context.client.get_provider(),
+        per_turn_config.model_reasoning_effort,
+        per_turn_config.model_reasoning_summary,
+        sess.conversation_id,
+        turn_context.client.get_session_source(),
+    )
+}
+
+fn sampling_message_to_item(message: &SamplingMessage) -> Result<ResponseItem, String> {
+    let (role, content) = match (&message.role, &message.content) {
+        (Role::User, SamplingMessageContent::TextContent(content)) => (
+            "user",
+            ContentItem::InputText {
+                text: content.text.clone(),
+            },
+        ),
+        (Role::Assistant, SamplingMessageContent::TextContent(content)) => (
+            "assistant",
+            ContentItem::OutputText {
+                text: content.text.clone(),
+            },
+        ),
+        (Role::User, SamplingMessageContent::ImageContent(image)) => (
+            "user",
+            ContentItem::InputImage {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            },
+        ),
+        (Role::Assistant, SamplingMessageContent::ImageContent(_)) => {
+            return Err("images in assistant messages are not supported".to_string());
+        }
+        (_, SamplingMessageContent::AudioContent(_)) => {
+            return Err("audio content is not supported".to_string());
+        }
+    };
+    Ok(ResponseItem::Message {
+        id: None,
+        role: role.to_string(),
+        content: vec![content],
+        end_turn: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use pretty_assertions::assert_eq;
+
+    fn config(policy: McpSamplingPolicy, token_budget: Option<u64>) -> McpSamplingConfig {
+        McpSamplingConfig {
+            policy,
+            model: None,
+            token_budget,
+        }
+    }
+
+    #[test]
+    fn auto_policy_asks_once_budget_is_spent() {
+        let auto = config(McpSamplingPolicy::Auto, Some(100));
+        assert!(!requires_approval(&auto, 99, false));
+        assert!(requires_approval(&auto, 100, false));
+        assert!(!requires_approval(&auto, 100, true));
+
+        let unlimited = config(McpSamplingPolicy::Auto, None);
+        assert!(!requires_approval(&unlimited, u64::MAX, false));
+
+        let ask = config(McpSamplingPolicy::Ask, None);
+        assert!(requires_approval(&ask, 0, false));
+        assert!(!requires_approval(&ask, 0, true));
+    }
+
+    #[test]
+    fn auto_policy_clamps_max_tokens_to_remaining_budget() {
+        let auto = config(McpSamplingPolicy::Auto, Some(100));
+        assert_eq!(reserve_tokens(&auto, 0, false, 40), Some(40));
+        assert_eq!(reserve_tokens(&auto, 70, false, 40), Some(30));
+        assert_eq!(reserve_tokens(&auto, 70, true, 40), None);
+
+        let unlimited = config(McpSamplingPolicy::Auto, None);
+        assert_eq!(reserve_tokens(&unlimited, 0, false, 40), None);
+
+        let ask = config(McpSamplingPolicy::Ask, Some(100));
+        assert_eq!(reserve_tokens(&ask, 0, false, 40), None);
+    }
+
+    #[test]
+    fn sampling_messages_convert_to_response_items() {
+        let user = SamplingMessage {
+            content: SamplingMessageContent::ImageContent(ImageContent {
+                annotations: None,
+                data: "AAAA".to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            }),
+            role: Role::User,
+        };
+        assert_eq!(
+            sampling_message_to_item(&user),
+            Ok(ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputImage {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                }],
+                end_turn: None,
+            })
+        );
+
+        let assistant = SamplingMessage {
+            content: SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: "hi".to_string(),
+                r#type: "text".to_string(),
+            }),
+            role: Role::Assistant,
+        };
+        assert_eq!(
+            sampling_message_to_item(&assistant),
+            Ok(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: "hi".to_string(),
+                }],
+                end_turn: None,
+            })
+        );
+    }
+}
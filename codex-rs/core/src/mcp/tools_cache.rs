@@ -0,0 +1,195 @@
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Utc;
+use mcp_types::Implementation;
+use mcp_types::Tool;
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tokio::fs;
+use tracing::warn;
+
+use crate::mcp_connection_manager::ToolInfo;
+
+const MCP_TOOLS_CACHE_DIR: &str = "mcp_tools_cache";
+
+/// Tool lists of MCP servers saved under `codex_home`, one file per
+/// configured server. An entry is only used while the server reports the
+/// same name and version it had when the tools were listed.
+#[derive(Debug, Clone)]
+pub(crate) struct McpToolsCache {
+    dir: PathBuf,
+}
+
+impl McpToolsCache {
+    pub(crate) fn new(codex_home: &Path) -> Self {
+        Self {
+            dir: codex_home.join(MCP_TOOLS_CACHE_DIR),
+        }
+    }
+
+    /// Returns the cached tools of `server_name`, or `None` if there are
+    /// none for this `server_info`.
+    pub(crate) async fn load(
+        &self,
+        server_name: &str,
+        server_info: &Implementation,
+    ) -> Option<Vec<ToolInfo>> {
+        let cache = match self.read(server_name).await {
+            Ok(cache) => cache?,
+            Err(err) => {
+                warn!("failed to load MCP tools cache for '{server_name}': {err}");
+                return None;
+            }
+        };
+        if cache.server_name != server_info.name || cache.server_version != server_info.version {
+            return None;
+        }
+        Some(
+            cache
+                .tools
+                .into_iter()
+                .map(|cached| ToolInfo {
+                    server_name: server_name.to_string(),
+                    tool_name: cached.tool.name.clone(),
+                    tool: cached.tool,
+                    connector_id: cached.connector_id,
+                    connector_name: cached.connector_name,
+                })
+                .collect(),
+        )
+    }
+
+    pub(crate) async fn store(
+        &self,
+        server_name: &str,
+        server_info: &Implementation,
+        tools: &[ToolInfo],
+    ) {
+        let cache = McpToolsCacheEntry {
+            fetched_at: Utc::now(),
+            server_name: server_info.name.clone(),
+            server_version: server_info.version.clone(),
+            tools: tools
+                .iter()
+                .map(|tool| CachedTool {
+                    tool: tool.tool.clone(),
+                    connector_id: tool.connector_id.clone(),
+                    connector_name: tool.connector_name.clone(),
+                })
+                .collect(),
+        };
+        if let Err(err) = self.write(server_name, &cache).await {
+            warn!("failed to write MCP tools cache for '{server_name}': {err}");
+        }
+    }
+
+    fn path(&self, server_name: &str) -> PathBuf {
+        self.dir.join(format!("{server_name}.json"))
+    }
+
+    async fn read(&self, server_name: &str) -> io::Result<Option<McpToolsCacheEntry>> {
+        match fs::read(self.path(server_name)).await {
+            Ok(contents) => {
+                let cache = serde_json::from_slice(&contents)
+                    .map_err(|err| io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+                Ok(Some(cache))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn write(&self, server_name: &str, cache: &McpToolsCacheEntry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        let json = serde_json::to_vec_pretty(cache)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+        // Other sessions may be reading or writing the same file, so each
+        // writer fills a temp file of its own and moves it into place.
+        let dir = self.dir.clone();
+        let path = self.path(server_name);
+        tokio::task::spawn_blocking(move || {
+            let mut tmp = NamedTempFile::new_in(&dir)?;
+            tmp.write_all(&json)?;
+            tmp.persist(path)?;
+            Ok(())
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct McpToolsCacheEntry {
+    fetched_at: DateTime<Utc>,
+    /// `serverInfo.name` from the server's `initialize` response.
+    server_name: String,
+    /// `serverInfo.version` from the server's `initialize` response.
+    server_version: String,
+    tools: Vec<CachedTool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedTool {
+    tool: Tool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connector_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connector_name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ToolInputSchema;
+    use pretty_assertions::assert_eq;
+
+    fn server_info(version: &str) -> Implementation {
+        Implementation {
+            name: "docs-server".to_string(),
+            version: version.to_string(),
+            title: None,
+            user_agent: None,
+        }
+    }
+
+    fn tool(name: &str) -> ToolInfo {
+        ToolInfo {
+            server_name: "docs".to_string(),
+            tool_name: name.to_string(),
+            tool: Tool {
+                annotations: None,
+                description: Some(format!("{name} description")),
+                input_schema: ToolInputSchema {
+                    properties: None,
+                    required: None,
+                    r#type: "object".to_string(),
+                },
+                name: name.to_string(),
+                output_schema: None,
+                title: None,
+            },
+            connector_id: None,
+            connector_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_tools_are_only_used_for_the_same_server_version() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let cache = McpToolsCache::new(codex_home.path());
+        assert_eq!(cache.load("docs", &server_info("1.0.0")).await, None);
+
+        let tools = vec![tool("search"), tool("fetch")];
+        cache.store("docs", &server_info("1.0.0"), &tools).await;
+
+        assert_eq!(cache.load("docs", &server_info("1.0.0")).await, Some(tools));
+        assert_eq!(cache.load("docs", &server_info("1.1.0")).await, None);
+        assert_eq!(cache.load("other", &server_info("1.0.0")).await, None);
+    }
+}
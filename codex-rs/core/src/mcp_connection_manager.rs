@@ -10,38 +10,57 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock as StdRwLock;
+use std::sync::Weak;
 use std::time::Duration;
+use std::time::Instant;
 
 use crate::mcp::auth::McpAuthStatusEntry;
+use crate::mcp::env_sources::resolve_env_sources;
+use crate::mcp::sampling::SamplingJob;
+use crate::mcp::sampling::make_sampling_sender;
+use crate::mcp::tools_cache::McpToolsCache;
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use async_channel::Sender;
 use codex_async_utils::CancelErr;
 use codex_async_utils::OrCancelExt;
+use codex_keyring_store::DefaultKeyringStore;
 use codex_protocol::approvals::ElicitationRequestEvent;
 use codex_protocol::protocol::Event;
 use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpServerState;
+use codex_protocol::protocol::McpServerStatusEvent;
 use codex_protocol::protocol::McpStartupCompleteEvent;
 use codex_protocol::protocol::McpStartupFailure;
 use codex_protocol::protocol::McpStartupStatus;
 use codex_protocol::protocol::McpStartupUpdateEvent;
 use codex_protocol::protocol::SandboxPolicy;
 use codex_rmcp_client::ElicitationResponse;
+use codex_rmcp_client::HttpTlsConfig;
 use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_rmcp_client::RmcpClient;
 use codex_rmcp_client::SendElicitation;
+use codex_rmcp_client::SendSampling;
+use codex_rmcp_client::ToolCallUpdateSender;
 use futures::future::BoxFuture;
 use futures::future::FutureExt;
 use futures::future::Shared;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
 use mcp_types::ListResourceTemplatesRequestParams;
 use mcp_types::ListResourceTemplatesResult;
 use mcp_types::ListResourcesRequestParams;
 use mcp_types::ListResourcesResult;
+use mcp_types::Prompt;
 use mcp_types::ReadResourceRequestParams;
 use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
@@ -60,10 +79,13 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 use tracing::warn;
+use wildmatch::WildMatchPattern;
 
 use crate::codex::INITIAL_SUBMIT_ID;
+use crate::config::types::McpSamplingPolicy;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerTransportConfig;
+use crate::mcp::prompts::validate_prompt_arguments;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -77,7 +99,30 @@ const MAX_TOOL_NAME_LENGTH: usize = 64;
 pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Default timeout for individual tool calls.
-const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+pub(crate) const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default for `max_restart_attempts`.
+const DEFAULT_MAX_RESTART_ATTEMPTS: u32 = 3;
+
+/// Delay before the first restart of a crashed server; doubles on every
+/// further attempt up to [`MAX_RESTART_BACKOFF`].
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A server that stays up this long after a restart is considered healthy
+/// again, and its restart attempts start over.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// How often a running server's connection is checked.
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Closing a streamable HTTP connection is not noticed on our side, so those
+/// servers are pinged this often and count as disconnected when a ping fails.
+const HTTP_PING_INTERVAL: Duration = Duration::from_secs(30);
+const HTTP_PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a call to a restarting server waits for it before failing.
+const RESTART_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// The Responses API requires tool names to match `^[a-zA-Z0-9_-]+$`.
 /// MCP server/tool names are user-controlled, so sanitize the fully-qualified
@@ -148,7 +193,7 @@ where
     qualified_tools
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct ToolInfo {
     pub(crate) server_name: String,
     pub(crate) tool_name: String,
@@ -213,13 +258,46 @@ impl ElicitationRequestManager {
 #[derive(Clone)]
 struct ManagedClient {
     client: Arc<RmcpClient>,
-    tools: Vec<ToolInfo>,
-    tool_filter: ToolFilter,
+    /// Replaced when the tools are listed again; turns started afterwards
+    /// see the new list.
+    tools: Arc<StdRwLock<Vec<ToolInfo>>>,
+    /// Set when `tools` came from the cache and the server has not been
+    /// asked for them yet.
+    tools_from_cache: bool,
+    server_info: Implementation,
+    tools_cache: Option<McpToolsCache>,
+    list_timeout: Option<Duration>,
     tool_timeout: Option<Duration>,
+    /// `tool_timeout_sec` from the server config, if set.
+    configured_tool_timeout: Option<Duration>,
     server_supports_sandbox_state_capability: bool,
+    /// Whether the server advertised the `resources` capability.
+    server_supports_resources: bool,
+    /// Prompts the server listed at startup.
+    prompts: Vec<Prompt>,
 }
 
 impl ManagedClient {
+    fn tools(&self) -> Vec<ToolInfo> {
+        self.tools
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Lists the server's tools again and caches them. Returns whether they
+    /// changed.
+    async fn refresh_tools(&self, server_name: &str) -> Result<bool> {
+        let tools = list_tools_for_client(server_name, &self.client, self.list_timeout).await?;
+        if let Some(cache) = &self.tools_cache {
+            cache.store(server_name, &self.server_info, &tools).await;
+        }
+        let mut current = self.tools.write().unwrap_or_else(PoisonError::into_inner);
+        let changed = *current != tools;
+        *current = tools;
+        Ok(changed)
+    }
+
     /// Returns once the server has ack'd the sandbox state update.
     async fn notify_sandbox_state_change(&self, sandbox_state: &SandboxState) -> Result<()> {
         if !self.server_supports_sandbox_state_capability {
@@ -237,51 +315,64 @@ impl ManagedClient {
     }
 }
 
+type ClientFuture = Shared<BoxFuture<'static, Result<ManagedClient, StartupOutcomeError>>>;
+
+/// The outcome of a server's latest start or restart.
+#[derive(Clone)]
+struct ClientSlot {
+    client: ClientFuture,
+    /// Set while the server is restarted after going down; callers then only
+    /// wait [`RESTART_WAIT_TIMEOUT`] for it.
+    restarting: bool,
+}
+
 #[derive(Clone)]
 struct AsyncManagedClient {
-    client: Shared<BoxFuture<'static, Result<ManagedClient, StartupOutcomeError>>>,
+    server_name: String,
+    slot: Arc<StdRwLock<ClientSlot>>,
 }
 
 impl AsyncManagedClient {
     fn new(
         server_name: String,
         config: McpServerConfig,
-        store_mode: OAuthCredentialsStoreMode,
         cancel_token: CancellationToken,
-        tx_event: Sender<Event>,
-        elicitation_requests: ElicitationRequestManager,
+        context: StartContext,
     ) -> Self {
-        let tool_filter = ToolFilter::from_config(&config);
-        let fut = async move {
-            if let Err(error) = validate_mcp_server_name(&server_name) {
-                return Err(error.into());
-            }
-
-            let client =
-                Arc::new(make_rmcp_client(&server_name, config.transport, store_mode).await?);
-            match start_server_task(
-                server_name,
-                client,
-                config.startup_timeout_sec.or(Some(DEFAULT_STARTUP_TIMEOUT)),
-                config.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT),
-                tool_filter,
-                tx_event,
-                elicitation_requests,
-            )
-            .or_cancel(&cancel_token)
-            .await
-            {
-                Ok(result) => result,
-                Err(CancelErr::Cancelled) => Err(StartupOutcomeError::Cancelled),
-            }
-        };
+        let client = start_client(server_name.clone(), config, cancel_token, context);
         Self {
-            client: fut.boxed().shared(),
+            server_name,
+            slot: Arc::new(StdRwLock::new(ClientSlot {
+                client,
+                restarting: false,
+            })),
         }
     }
 
+    fn slot(&self) -> ClientSlot {
+        self.slot
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The outcome of the latest start, if it has finished.
+    fn peek(&self) -> Option<Result<ManagedClient, StartupOutcomeError>> {
+        self.slot().client.peek().cloned()
+    }
+
     async fn client(&self) -> Result<ManagedClient, StartupOutcomeError> {
-        self.client.clone().await
+        let ClientSlot { client, restarting } = self.slot();
+        if !restarting {
+            return client.await;
+        }
+        tokio::time::timeout(RESTART_WAIT_TIMEOUT, client)
+            .await
+            .unwrap_or_else(|_| {
+                Err(StartupOutcomeError::Restarting {
+                    server: self.server_name.clone(),
+                })
+            })
     }
 
     async fn notify_sandbox_state_change(&self, sandbox_state: &SandboxState) -> Result<()> {
@@ -290,6 +381,356 @@ impl AsyncManagedClient {
     }
 }
 
+/// What starting a server needs besides its config. Shared by the first
+/// start and every restart.
+#[derive(Clone)]
+struct StartContext {
+    store_mode: OAuthCredentialsStoreMode,
+    tx_event: Sender<Event>,
+    elicitation_requests: ElicitationRequestManager,
+    sampling_jobs: Option<async_channel::Sender<SamplingJob>>,
+    tools_cache: Option<McpToolsCache>,
+}
+
+/// Launches `server_name` and completes its handshake. The returned future
+/// can be awaited by any number of callers.
+fn start_client(
+    server_name: String,
+    config: McpServerConfig,
+    cancel_token: CancellationToken,
+    context: StartContext,
+) -> ClientFuture {
+    let fut = async move {
+        if let Err(error) = validate_mcp_server_name(&server_name) {
+            return Err(error.into());
+        }
+
+        // Sampling is only offered to servers whose policy allows it.
+        let send_sampling = config
+            .sampling
+            .clone()
+            .filter(|sampling| sampling.policy != McpSamplingPolicy::Deny)
+            .zip(context.sampling_jobs.clone())
+            .map(|(sampling, jobs)| make_sampling_sender(server_name.clone(), sampling, jobs));
+
+        // Launching the server (or an OAuth token refresh) can hang too, so
+        // the startup timeout covers creating the client as well.
+        let startup_timeout = config
+            .startup_timeout_sec
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+        let client = match tokio::time::timeout(
+            startup_timeout,
+            make_rmcp_client(&server_name, config.transport, context.store_mode),
+        )
+        .or_cancel(&cancel_token)
+        .await
+        {
+            Ok(Ok(client)) => Arc::new(client?),
+            Ok(Err(_)) => {
+                return Err(StartupOutcomeError::Failed {
+                    error: format!(
+                        "timed out handshaking with MCP server after {startup_timeout:?}"
+                    ),
+                });
+            }
+            Err(CancelErr::Cancelled) => return Err(StartupOutcomeError::Cancelled),
+        };
+        match start_server_task(
+            server_name,
+            client,
+            Some(startup_timeout),
+            config.tool_timeout_sec,
+            send_sampling,
+            context,
+        )
+        .or_cancel(&cancel_token)
+        .await
+        {
+            Ok(result) => result,
+            Err(CancelErr::Cancelled) => Err(StartupOutcomeError::Cancelled),
+        }
+    };
+    fut.boxed().shared()
+}
+
+type ToolFilters = Arc<StdRwLock<HashMap<String, ToolFilter>>>;
+type ServerConfigs = Arc<StdRwLock<HashMap<String, McpServerConfig>>>;
+type SharedSandboxState = Arc<StdRwLock<Option<SandboxState>>>;
+
+/// Watches a running server and restarts it when its connection closes,
+/// backing off exponentially. A server that keeps crashing is disabled for
+/// the rest of the session.
+struct RestartSupervisor {
+    server_name: String,
+    /// Read on every restart, so one picks up changes made since the start.
+    server_configs: ServerConfigs,
+    cancel_token: CancellationToken,
+    context: StartContext,
+    /// The sandbox state servers were last told about.
+    sandbox_state: SharedSandboxState,
+    tool_filters: ToolFilters,
+    /// Dropped along with the manager, which ends supervision.
+    slot: Weak<StdRwLock<ClientSlot>>,
+}
+
+impl RestartSupervisor {
+    async fn run(self, mut managed: ManagedClient) {
+        let mut attempts = 0;
+        loop {
+            let up_since = Instant::now();
+            if managed.tools_from_cache {
+                self.refresh_tools(&managed).await;
+            }
+            if !self.wait_for_disconnect(&managed).await {
+                return;
+            }
+            if up_since.elapsed() >= STABLE_UPTIME {
+                attempts = 0;
+            }
+            let Some(max_attempts) = self.config().map(|config| {
+                config
+                    .max_restart_attempts
+                    .unwrap_or(DEFAULT_MAX_RESTART_ATTEMPTS)
+            }) else {
+                return;
+            };
+
+            // Calls made while the server is down wait on `restarted`.
+            let (restarted_tx, restarted_rx) = oneshot::channel();
+            let restarted = async move {
+                restarted_rx
+                    .await
+                    .unwrap_or(Err(StartupOutcomeError::Cancelled))
+            };
+            if !self.set_slot(restarted.boxed().shared(), true) {
+                return;
+            }
+            warn!("MCP server '{}' disconnected", self.server_name);
+            self.emit_status(
+                McpServerState::Down,
+                Some(format!("MCP server '{}' disconnected", self.server_name)),
+                Vec::new(),
+            )
+            .await;
+
+            let mut last_error = None;
+            let outcome = loop {
+                if attempts >= max_attempts {
+                    let error = match last_error {
+                        Some(error) => format!(
+                            "MCP server '{}' was disabled after {attempts} failed restarts: {error}",
+                            self.server_name
+                        ),
+                        None => format!(
+                            "MCP server '{}' was disabled after {attempts} failed restarts",
+                            self.server_name
+                        ),
+                    };
+                    break Err(StartupOutcomeError::Failed { error });
+                }
+                let backoff = restart_backoff(attempts);
+                if tokio::time::sleep(backoff)
+                    .or_cancel(&self.cancel_token)
+                    .await
+                    .is_err()
+                {
+                    break Err(StartupOutcomeError::Cancelled);
+                }
+                attempts += 1;
+                self.emit_status(McpServerState::Restarting, None, Vec::new())
+                    .await;
+                match self.restart().await {
+                    Ok(restarted) => break Ok(restarted),
+                    Err(StartupOutcomeError::Cancelled) => {
+                        break Err(StartupOutcomeError::Cancelled);
+                    }
+                    Err(error) => {
+                        warn!(
+                            "Restart {attempts} of MCP server '{}' failed: {error}",
+                            self.server_name
+                        );
+                        last_error = Some(error);
+                    }
+                }
+            };
+
+            let _ = restarted_tx.send(outcome.clone());
+            let keep_going = self.set_slot(
+                futures::future::ready(outcome.clone()).boxed().shared(),
+                false,
+            );
+            match outcome {
+                Ok(restarted) => {
+                    let filter = self.tool_filter();
+                    self.emit_status(
+                        McpServerState::Ready,
+                        None,
+                        effective_tool_names(&restarted.tools(), &filter),
+                    )
+                    .await;
+                    managed = restarted;
+                }
+                Err(StartupOutcomeError::Failed { error }) => {
+                    self.emit_status(McpServerState::Disabled, Some(error), Vec::new())
+                        .await;
+                    return;
+                }
+                Err(_) => return,
+            }
+            if !keep_going {
+                return;
+            }
+        }
+    }
+
+    /// Returns `true` once `managed` disconnects, or `false` if supervision
+    /// should stop first. Meanwhile, lists the tools again whenever the
+    /// server says they changed.
+    async fn wait_for_disconnect(&self, managed: &ManagedClient) -> bool {
+        let ping = self.config().is_some_and(|config| {
+            matches!(
+                config.transport,
+                McpServerTransportConfig::StreamableHttp { .. }
+            )
+        });
+        let mut last_ping = Instant::now();
+        loop {
+            let wake = async {
+                tokio::select! {
+                    () = tokio::time::sleep(CONNECTION_CHECK_INTERVAL) => false,
+                    () = managed.client.tool_list_changed() => true,
+                }
+            };
+            let Ok(tools_changed) = wake.or_cancel(&self.cancel_token).await else {
+                return false;
+            };
+            if self.slot.strong_count() == 0 {
+                return false;
+            }
+            if managed.client.is_closed().await {
+                return true;
+            }
+            if ping && last_ping.elapsed() >= HTTP_PING_INTERVAL {
+                if let Err(err) = managed.client.ping(Some(HTTP_PING_TIMEOUT)).await {
+                    warn!(
+                        "MCP server '{}' did not answer ping: {err:#}",
+                        self.server_name
+                    );
+                    return true;
+                }
+                last_ping = Instant::now();
+            }
+            if tools_changed {
+                self.refresh_tools(managed).await;
+            }
+        }
+    }
+
+    /// Reports the server again if its tools changed.
+    async fn refresh_tools(&self, managed: &ManagedClient) {
+        match managed.refresh_tools(&self.server_name).await {
+            Ok(true) => {
+                let filter = self.tool_filter();
+                self.emit_status(
+                    McpServerState::Ready,
+                    None,
+                    effective_tool_names(&managed.tools(), &filter),
+                )
+                .await;
+            }
+            Ok(false) => {}
+            Err(err) => warn!(
+                "Failed to list tools of MCP server '{}' again: {err:#}",
+                self.server_name
+            ),
+        }
+    }
+
+    async fn restart(&self) -> Result<ManagedClient, StartupOutcomeError> {
+        let Some(config) = self.config() else {
+            return Err(StartupOutcomeError::Failed {
+                error: format!("MCP server '{}' is no longer configured", self.server_name),
+            });
+        };
+        let managed = start_client(
+            self.server_name.clone(),
+            config,
+            self.cancel_token.clone(),
+            self.context.clone(),
+        )
+        .await?;
+        let sandbox_state = self
+            .sandbox_state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(sandbox_state) = sandbox_state
+            && let Err(e) = managed.notify_sandbox_state_change(&sandbox_state).await
+        {
+            warn!(
+                "Failed to notify sandbox state to MCP server {}: {e:#}",
+                self.server_name
+            );
+        }
+        Ok(managed)
+    }
+
+    fn config(&self) -> Option<McpServerConfig> {
+        self.server_configs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&self.server_name)
+            .cloned()
+    }
+
+    /// Returns `false` if the manager that owned the slot is gone.
+    fn set_slot(&self, client: ClientFuture, restarting: bool) -> bool {
+        let Some(slot) = self.slot.upgrade() else {
+            return false;
+        };
+        *slot.write().unwrap_or_else(PoisonError::into_inner) = ClientSlot { client, restarting };
+        true
+    }
+
+    fn tool_filter(&self) -> ToolFilter {
+        self.tool_filters
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&self.server_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn emit_status(
+        &self,
+        state: McpServerState,
+        error: Option<String>,
+        effective_tools: Vec<String>,
+    ) {
+        let _ = self
+            .context
+            .tx_event
+            .send(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::McpServerStatus(McpServerStatusEvent {
+                    server: self.server_name.clone(),
+                    state,
+                    error,
+                    configured_tools: self.tool_filter().configured_tools(),
+                    effective_tools,
+                }),
+            })
+            .await;
+    }
+}
+
+/// Delay before restart attempt `attempt` (zero-based).
+fn restart_backoff(attempt: u32) -> Duration {
+    INITIAL_RESTART_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RESTART_BACKOFF)
+}
+
 pub const MCP_SANDBOX_STATE_CAPABILITY: &str = "codex/sandbox-state";
 
 /// Custom MCP request to push sandbox state updates.
@@ -309,9 +750,39 @@ pub struct SandboxState {
 pub(crate) struct McpConnectionManager {
     clients: HashMap<String, AsyncManagedClient>,
     elicitation_requests: ElicitationRequestManager,
+    /// Where servers' sampling requests are sent; sampling is not offered
+    /// without it.
+    sampling_jobs: Option<async_channel::Sender<SamplingJob>>,
+    /// Where listed tools are kept for the next start; tools are always
+    /// listed at startup without it.
+    tools_cache: Option<McpToolsCache>,
+    /// Configs of the enabled servers, kept so a failed server can be retried.
+    /// Shared with the restart supervisors.
+    server_configs: ServerConfigs,
+    /// The sandbox state servers were last told about, so a restarted server
+    /// gets the current one. Shared with the restart supervisors.
+    sandbox_state: SharedSandboxState,
+    /// Tools each server may expose, kept apart from the clients so a config
+    /// reload can change them without restarting the server. Shared with the
+    /// restart supervisors.
+    tool_filters: ToolFilters,
+    store_mode: OAuthCredentialsStoreMode,
+    auth_entries: HashMap<String, McpAuthStatusEntry>,
 }
 
 impl McpConnectionManager {
+    /// Routes sampling requests from servers started after this call to
+    /// `jobs`.
+    pub(crate) fn set_sampling_jobs(&mut self, jobs: async_channel::Sender<SamplingJob>) {
+        self.sampling_jobs = Some(jobs);
+    }
+
+    /// Starts servers with the tools they listed last time, if they still
+    /// report the same version, and lists them again in the background.
+    pub(crate) fn set_tools_cache(&mut self, tools_cache: McpToolsCache) {
+        self.tools_cache = Some(tools_cache);
+    }
+
     pub async fn initialize(
         &mut self,
         mcp_servers: &HashMap<String, McpServerConfig>,
@@ -324,74 +795,37 @@ impl McpConnectionManager {
         if cancel_token.is_cancelled() {
             return;
         }
-        let mut clients = HashMap::new();
-        let mut join_set = JoinSet::new();
-        let elicitation_requests = ElicitationRequestManager::default();
-        let mcp_servers = mcp_servers.clone();
-        for (server_name, cfg) in mcp_servers.into_iter().filter(|(_, cfg)| cfg.enabled) {
-            let cancel_token = cancel_token.child_token();
-            let _ = emit_update(
-                &tx_event,
-                McpStartupUpdateEvent {
-                    server: server_name.clone(),
-                    status: McpStartupStatus::Starting,
-                },
-            )
-            .await;
-            let async_managed_client = AsyncManagedClient::new(
-                server_name.clone(),
-                cfg,
-                store_mode,
-                cancel_token.clone(),
-                tx_event.clone(),
-                elicitation_requests.clone(),
-            );
-            clients.insert(server_name.clone(), async_managed_client.clone());
-            let tx_event = tx_event.clone();
-            let auth_entry = auth_entries.get(&server_name).cloned();
-            let sandbox_state = initial_sandbox_state.clone();
-            join_set.spawn(async move {
-                let outcome = async_managed_client.client().await;
-                if cancel_token.is_cancelled() {
-                    return (server_name, Err(StartupOutcomeError::Cancelled));
-                }
-                let status = match &outcome {
-                    Ok(_) => {
-                        // Send sandbox state notification immediately after Ready
-                        if let Err(e) = async_managed_client
-                            .notify_sandbox_state_change(&sandbox_state)
-                            .await
-                        {
-                            warn!(
-                                "Failed to notify sandbox state to MCP server {server_name}: {e:#}",
-                            );
-                        }
-                        McpStartupStatus::Ready
-                    }
-                    Err(error) => {
-                        let error_str = mcp_init_error_display(
-                            server_name.as_str(),
-                            auth_entry.as_ref(),
-                            error,
-                        );
-                        McpStartupStatus::Failed { error: error_str }
-                    }
-                };
+        self.clients = HashMap::new();
+        self.elicitation_requests = ElicitationRequestManager::default();
+        let server_configs: HashMap<String, McpServerConfig> = mcp_servers
+            .iter()
+            .filter(|(_, cfg)| cfg.enabled)
+            .map(|(name, cfg)| (name.clone(), cfg.clone()))
+            .collect();
+        self.tool_filters = Arc::new(StdRwLock::new(
+            server_configs
+                .iter()
+                .map(|(name, cfg)| (name.clone(), ToolFilter::from_config(cfg)))
+                .collect(),
+        ));
+        self.server_configs = Arc::new(StdRwLock::new(server_configs.clone()));
+        self.sandbox_state = Arc::new(StdRwLock::new(Some(initial_sandbox_state.clone())));
+        self.store_mode = store_mode;
+        self.auth_entries = auth_entries;
 
-                let _ = emit_update(
+        let mut join_set = JoinSet::new();
+        for (server_name, cfg) in server_configs {
+            let startup = self
+                .start_server(
+                    server_name,
+                    cfg,
                     &tx_event,
-                    McpStartupUpdateEvent {
-                        server: server_name.clone(),
-                        status,
-                    },
+                    &cancel_token,
+                    &initial_sandbox_state,
                 )
                 .await;
-
-                (server_name, outcome)
-            });
+            join_set.spawn(startup);
         }
-        self.clients = clients;
-        self.elicitation_requests = elicitation_requests.clone();
         tokio::spawn(async move {
             let outcomes = join_set.join_all().await;
             let mut summary = McpStartupCompleteEvent::default();
@@ -399,6 +833,12 @@ impl McpConnectionManager {
                 match outcome {
                     Ok(_) => summary.ready.push(server_name),
                     Err(StartupOutcomeError::Cancelled) => summary.cancelled.push(server_name),
+                    Err(error @ StartupOutcomeError::Restarting { .. }) => {
+                        summary.failed.push(McpStartupFailure {
+                            server: server_name,
+                            error: error.to_string(),
+                        })
+                    }
                     Err(StartupOutcomeError::Failed { error }) => {
                         summary.failed.push(McpStartupFailure {
                             server: server_name,
@@ -416,13 +856,263 @@ impl McpConnectionManager {
         });
     }
 
-    async fn client_by_name(&self, name: &str) -> Result<ManagedClient> {
+    /// Starts `server_name` again after its startup failed or was cancelled.
+    /// Like the initial startup, this returns before the server is ready and
+    /// reports the outcome with an `McpServerStatus` event.
+    pub async fn retry_server(
+        &mut self,
+        server_name: &str,
+        tx_event: Sender<Event>,
+        cancel_token: CancellationToken,
+        sandbox_state: SandboxState,
+    ) -> Result<()> {
+        let cfg = self
+            .server_configs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(server_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown MCP server '{server_name}'"))?;
+        match self
+            .clients
+            .get(server_name)
+            .map(|client| client.peek().map(|outcome| outcome.is_ok()))
+        {
+            Some(None) => return Err(anyhow!("MCP server '{server_name}' is still starting")),
+            Some(Some(true)) => {
+                return Err(anyhow!("MCP server '{server_name}' is already running"));
+            }
+            Some(Some(false)) | None => {}
+        }
+        *self
+            .sandbox_state
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(sandbox_state.clone());
+
+        let startup = self
+            .start_server(
+                server_name.to_string(),
+                cfg,
+                &tx_event,
+                &cancel_token,
+                &sandbox_state,
+            )
+            .await;
+        tokio::spawn(startup);
+        Ok(())
+    }
+
+    /// Registers a client for `server_name` and returns a future that waits
+    /// for it to start and reports the outcome.
+    async fn start_server(
+        &mut self,
+        server_name: String,
+        cfg: McpServerConfig,
+        tx_event: &Sender<Event>,
+        cancel_token: &CancellationToken,
+        sandbox_state: &SandboxState,
+    ) -> impl Future<Output = (String, Result<ManagedClient, StartupOutcomeError>)> + Send + 'static
+    {
+        let cancel_token = cancel_token.child_token();
+        let _ = emit_update(
+            tx_event,
+            McpStartupUpdateEvent {
+                server: server_name.clone(),
+                status: McpStartupStatus::Starting,
+            },
+        )
+        .await;
+        let configured_tools = cfg.enabled_tools.clone();
+        let tool_filter = self.tool_filter(&server_name);
+        let context = StartContext {
+            store_mode: self.store_mode,
+            tx_event: tx_event.clone(),
+            elicitation_requests: self.elicitation_requests.clone(),
+            sampling_jobs: self.sampling_jobs.clone(),
+            tools_cache: self.tools_cache.clone(),
+        };
+        let async_managed_client = AsyncManagedClient::new(
+            server_name.clone(),
+            cfg.clone(),
+            cancel_token.clone(),
+            context.clone(),
+        );
         self.clients
+            .insert(server_name.clone(), async_managed_client.clone());
+        let supervisor = RestartSupervisor {
+            server_name: server_name.clone(),
+            server_configs: Arc::clone(&self.server_configs),
+            cancel_token: cancel_token.clone(),
+            context,
+            sandbox_state: Arc::clone(&self.sandbox_state),
+            tool_filters: Arc::clone(&self.tool_filters),
+            slot: Arc::downgrade(&async_managed_client.slot),
+        };
+        let tx_event = tx_event.clone();
+        let auth_entry = self.auth_entries.get(&server_name).cloned();
+        let sandbox_state = sandbox_state.clone();
+        async move {
+            let outcome = async_managed_client.client().await;
+            if cancel_token.is_cancelled() {
+                return (server_name, Err(StartupOutcomeError::Cancelled));
+            }
+            let (status, server_status) = match &outcome {
+                Ok(managed) => {
+                    // Send sandbox state notification immediately after Ready
+                    if let Err(e) = async_managed_client
+                        .notify_sandbox_state_change(&sandbox_state)
+                        .await
+                    {
+                        warn!("Failed to notify sandbox state to MCP server {server_name}: {e:#}",);
+                    }
+                    let effective_tools = effective_tool_names(&managed.tools(), &tool_filter);
+                    (
+                        McpStartupStatus::Ready,
+                        McpServerStatusEvent {
+                            server: server_name.clone(),
+                            state: McpServerState::Ready,
+                            error: None,
+                            configured_tools,
+                            effective_tools,
+                        },
+                    )
+                }
+                Err(error) => {
+                    let error_str =
+                        mcp_init_error_display(server_name.as_str(), auth_entry.as_ref(), error);
+                    let state = match error {
+                        StartupOutcomeError::Cancelled => McpServerState::Cancelled,
+                        StartupOutcomeError::Failed { .. }
+                        | StartupOutcomeError::Restarting { .. } => McpServerState::Failed,
+                    };
+                    (
+                        McpStartupStatus::Failed {
+                            error: error_str.clone(),
+                        },
+                        McpServerStatusEvent {
+                            server: server_name.clone(),
+                            state,
+                            error: Some(error_str),
+                            configured_tools,
+                            effective_tools: Vec::new(),
+                        },
+                    )
+                }
+            };
+
+            let _ = emit_update(
+                &tx_event,
+                McpStartupUpdateEvent {
+                    server: server_name.clone(),
+                    status,
+                },
+            )
+            .await;
+            let _ = tx_event
+                .send(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::McpServerStatus(server_status),
+                })
+                .await;
+
+            if let Ok(managed) = &outcome {
+                tokio::spawn(supervisor.run(managed.clone()));
+            }
+            (server_name, outcome)
+        }
+    }
+
+    /// Applies the tool allow/deny lists from `mcp_servers` to the servers
+    /// that are already configured, without restarting them. Returns a
+    /// status for every running server whose exposed tools changed.
+    pub fn update_tool_filters(
+        &mut self,
+        mcp_servers: &HashMap<String, McpServerConfig>,
+    ) -> Vec<McpServerStatusEvent> {
+        let mut statuses = Vec::new();
+        let mut tool_filters = self
+            .tool_filters
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut server_configs = self
+            .server_configs
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        for (server_name, cfg) in server_configs.iter_mut() {
+            let Some(updated) = mcp_servers.get(server_name) else {
+                continue;
+            };
+            cfg.enabled_tools = updated.enabled_tools.clone();
+            cfg.disabled_tools = updated.disabled_tools.clone();
+            let tool_filter = ToolFilter::from_config(cfg);
+            if tool_filters.get(server_name) == Some(&tool_filter) {
+                continue;
+            }
+            if let Some(Ok(managed)) = self
+                .clients
+                .get(server_name)
+                .and_then(AsyncManagedClient::peek)
+            {
+                statuses.push(McpServerStatusEvent {
+                    server: server_name.clone(),
+                    state: McpServerState::Ready,
+                    error: None,
+                    configured_tools: cfg.enabled_tools.clone(),
+                    effective_tools: effective_tool_names(&managed.tools(), &tool_filter),
+                });
+            }
+            tool_filters.insert(server_name.clone(), tool_filter);
+        }
+        statuses.sort_by(|a, b| a.server.cmp(&b.server));
+        statuses
+    }
+
+    fn tool_filter(&self, server_name: &str) -> ToolFilter {
+        self.tool_filters
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(server_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Lists `server_name`'s tools again. Turns started afterwards see the
+    /// new list.
+    pub async fn refresh_tools(&self, server_name: &str) -> Result<McpServerStatusEvent> {
+        let managed = self.client_by_name(server_name).await?;
+        managed.refresh_tools(server_name).await?;
+        let tool_filter = self.tool_filter(server_name);
+        Ok(McpServerStatusEvent {
+            server: server_name.to_string(),
+            state: McpServerState::Ready,
+            error: None,
+            configured_tools: tool_filter.configured_tools(),
+            effective_tools: effective_tool_names(&managed.tools(), &tool_filter),
+        })
+    }
+
+    async fn client_by_name(&self, name: &str) -> Result<ManagedClient> {
+        match self
+            .clients
             .get(name)
             .ok_or_else(|| anyhow!("unknown MCP server '{name}'"))?
             .client()
             .await
-            .context("failed to get client")
+        {
+            Ok(managed) => Ok(managed),
+            Err(error @ StartupOutcomeError::Restarting { .. }) => Err(error.into()),
+            Err(error) => Err(error).context("failed to get client"),
+        }
+    }
+
+    /// Like [`Self::client_by_name`], but fails for servers that do not
+    /// provide resources.
+    async fn resource_client_by_name(&self, name: &str) -> Result<ManagedClient> {
+        let managed = self.client_by_name(name).await?;
+        if !managed.server_supports_resources {
+            return Err(anyhow!("MCP server '{name}' does not provide resources"));
+        }
+        Ok(managed)
     }
 
     pub async fn resolve_elicitation(
@@ -441,11 +1131,15 @@ impl McpConnectionManager {
     #[instrument(level = "trace", skip_all)]
     pub async fn list_all_tools(&self) -> HashMap<String, ToolInfo> {
         let mut tools = HashMap::new();
-        for managed_client in self.clients.values() {
+        for (server_name, managed_client) in &self.clients {
+            // Don't hold up the turn for a server that is being restarted.
+            if managed_client.slot().restarting {
+                continue;
+            }
             if let Ok(client) = managed_client.client().await {
                 tools.extend(qualify_tools(filter_tools(
-                    client.tools,
-                    client.tool_filter,
+                    client.tools(),
+                    self.tool_filter(server_name),
                 )));
             }
         }
@@ -464,6 +1158,9 @@ impl McpConnectionManager {
             let Ok(managed_client) = async_managed_client.client().await else {
                 continue;
             };
+            if !managed_client.server_supports_resources {
+                continue;
+            }
             let timeout = managed_client.tool_timeout;
             let client = managed_client.client.clone();
 
@@ -529,6 +1226,9 @@ impl McpConnectionManager {
             let Ok(managed_client) = async_managed_client.client().await else {
                 continue;
             };
+            if !managed_client.server_supports_resources {
+                continue;
+            }
             let client = managed_client.client.clone();
             let timeout = managed_client.tool_timeout;
 
@@ -588,34 +1288,60 @@ impl McpConnectionManager {
         aggregated
     }
 
+    /// Returns the `tool_timeout_sec` configured for `server`, if any.
+    pub async fn configured_tool_timeout(&self, server: &str) -> Option<Duration> {
+        self.client_by_name(server)
+            .await
+            .ok()
+            .and_then(|client| client.configured_tool_timeout)
+    }
+
     /// Invoke the tool indicated by the (server, tool) pair.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        timeout: Duration,
+        updates: Option<ToolCallUpdateSender>,
+        call_id: &str,
     ) -> Result<mcp_types::CallToolResult> {
-        let client = self.client_by_name(server).await?;
-        if !client.tool_filter.allows(tool) {
+        if !self.tool_filter(server).allows(tool) {
             return Err(anyhow!(
                 "tool '{tool}' is disabled for MCP server '{server}'"
             ));
         }
+        let client = self.client_by_name(server).await?;
 
         client
             .client
-            .call_tool(tool.to_string(), arguments, client.tool_timeout)
+            .call_tool(
+                tool.to_string(),
+                arguments,
+                Some(timeout),
+                updates,
+                Some(call_id.to_string()),
+            )
             .await
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Cancels the in-flight tool call `call_id` on `server`. Returns `false`
+    /// if the server has no such call running.
+    pub fn cancel_tool_call(&self, server: &str, call_id: &str) -> bool {
+        match self.clients.get(server).and_then(AsyncManagedClient::peek) {
+            Some(Ok(managed)) => managed.client.cancel_tool_call(call_id),
+            _ => false,
+        }
+    }
+
     /// List resources from the specified server.
     pub async fn list_resources(
         &self,
         server: &str,
         params: Option<ListResourcesRequestParams>,
     ) -> Result<ListResourcesResult> {
-        let managed = self.client_by_name(server).await?;
+        let managed = self.resource_client_by_name(server).await?;
         let timeout = managed.tool_timeout;
 
         managed
@@ -625,13 +1351,54 @@ impl McpConnectionManager {
             .with_context(|| format!("resources/list failed for `{server}`"))
     }
 
+    /// Returns the prompts each ready server listed at startup, keyed by
+    /// server name. Servers without prompts are left out.
+    pub async fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        let mut prompts = HashMap::new();
+        for (server_name, managed_client) in &self.clients {
+            if let Ok(client) = managed_client.client().await
+                && !client.prompts.is_empty()
+            {
+                prompts.insert(server_name.clone(), client.prompts);
+            }
+        }
+        prompts
+    }
+
+    /// Fetches `prompt` from `server` with `prompts/get`. Arguments are
+    /// checked against the prompt's definition before the request is sent.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        prompt: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<GetPromptResult> {
+        let managed = self.client_by_name(server).await?;
+        let definition = managed
+            .prompts
+            .iter()
+            .find(|candidate| candidate.name == prompt)
+            .ok_or_else(|| anyhow!("MCP server '{server}' has no prompt named '{prompt}'"))?;
+        validate_prompt_arguments(definition, &arguments).map_err(|err| anyhow!(err))?;
+
+        let params = GetPromptRequestParams {
+            arguments: (!arguments.is_empty()).then(|| json!(arguments)),
+            name: prompt.to_string(),
+        };
+        managed
+            .client
+            .get_prompt(params, managed.tool_timeout)
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}` ({prompt})"))
+    }
+
     /// List resource templates from the specified server.
     pub async fn list_resource_templates(
         &self,
         server: &str,
         params: Option<ListResourceTemplatesRequestParams>,
     ) -> Result<ListResourceTemplatesResult> {
-        let managed = self.client_by_name(server).await?;
+        let managed = self.resource_client_by_name(server).await?;
         let client = managed.client.clone();
         let timeout = managed.tool_timeout;
 
@@ -647,7 +1414,7 @@ impl McpConnectionManager {
         server: &str,
         params: ReadResourceRequestParams,
     ) -> Result<ReadResourceResult> {
-        let managed = self.client_by_name(server).await?;
+        let managed = self.resource_client_by_name(server).await?;
         let client = managed.client.clone();
         let timeout = managed.tool_timeout;
         let uri = params.uri.clone();
@@ -666,6 +1433,10 @@ impl McpConnectionManager {
     }
 
     pub async fn notify_sandbox_state_change(&self, sandbox_state: &SandboxState) -> Result<()> {
+        *self
+            .sandbox_state
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(sandbox_state.clone());
         let mut join_set = JoinSet::new();
 
         for async_managed_client in self.clients.values() {
@@ -707,9 +1478,11 @@ async fn emit_update(
 }
 
 /// A tool is allowed to be used if both are true:
-/// 1. enabled is None (no allowlist is set) or the tool is explicitly enabled.
-/// 2. The tool is not explicitly disabled.
-#[derive(Default, Clone)]
+/// 1. enabled is None (no allowlist is set) or the tool matches an enabled pattern.
+/// 2. The tool does not match a disabled pattern.
+///
+/// Patterns are tool names that may contain `*` and `?` wildcards.
+#[derive(Default, Clone, Debug, PartialEq)]
 pub(crate) struct ToolFilter {
     enabled: Option<HashSet<String>>,
     disabled: HashSet<String>,
@@ -730,17 +1503,43 @@ impl ToolFilter {
         Self { enabled, disabled }
     }
 
+    /// The allowlist as reported in `McpServerStatus` events.
+    fn configured_tools(&self) -> Option<Vec<String>> {
+        self.enabled.as_ref().map(|enabled| {
+            let mut tools: Vec<String> = enabled.iter().cloned().collect();
+            tools.sort();
+            tools
+        })
+    }
+
     fn allows(&self, tool_name: &str) -> bool {
         if let Some(enabled) = &self.enabled
-            && !enabled.contains(tool_name)
+            && !matches_any(enabled, tool_name)
         {
             return false;
         }
 
-        !self.disabled.contains(tool_name)
+        !matches_any(&self.disabled, tool_name)
     }
 }
 
+fn matches_any(patterns: &HashSet<String>, tool_name: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| WildMatchPattern::<'*', '?'>::new(pattern).matches(tool_name))
+}
+
+/// Sorted names of the tools in `tools` that `filter` allows, as reported in
+/// `McpServerStatus` events.
+fn effective_tool_names(tools: &[ToolInfo], filter: &ToolFilter) -> Vec<String> {
+    let mut names: Vec<String> = filter_tools(tools.to_vec(), filter.clone())
+        .into_iter()
+        .map(|tool| tool.tool_name)
+        .collect();
+    names.sort();
+    names
+}
+
 fn filter_tools(tools: Vec<ToolInfo>, filter: ToolFilter) -> Vec<ToolInfo> {
     tools
         .into_iter()
@@ -783,6 +1582,8 @@ enum StartupOutcomeError {
     // `Clone`.
     #[error("MCP startup failed: {error}")]
     Failed { error: String },
+    #[error("MCP server '{server}' is restarting")]
+    Restarting { server: String },
 }
 
 impl From<anyhow::Error> for StartupOutcomeError {
@@ -797,16 +1598,15 @@ async fn start_server_task(
     server_name: String,
     client: Arc<RmcpClient>,
     startup_timeout: Option<Duration>, // TODO: cancel_token should handle this.
-    tool_timeout: Duration,
-    tool_filter: ToolFilter,
-    tx_event: Sender<Event>,
-    elicitation_requests: ElicitationRequestManager,
+    tool_timeout: Option<Duration>,
+    send_sampling: Option<SendSampling>,
+    context: StartContext,
 ) -> Result<ManagedClient, StartupOutcomeError> {
     let params = mcp_types::InitializeRequestParams {
         capabilities: ClientCapabilities {
             experimental: None,
             roots: None,
-            sampling: None,
+            sampling: send_sampling.as_ref().map(|_| json!({})),
             // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
             // indicates this should be an empty object.
             elicitation: Some(json!({})),
@@ -823,16 +1623,33 @@ async fn start_server_task(
         protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
     };
 
-    let send_elicitation = elicitation_requests.make_sender(server_name.clone(), tx_event);
+    let send_elicitation = context
+        .elicitation_requests
+        .make_sender(server_name.clone(), context.tx_event.clone());
 
     let initialize_result = client
-        .initialize(params, startup_timeout, send_elicitation)
+        .initialize(params, startup_timeout, send_elicitation, send_sampling)
         .await
         .map_err(StartupOutcomeError::from)?;
 
-    let tools = list_tools_for_client(&server_name, &client, startup_timeout)
-        .await
-        .map_err(StartupOutcomeError::from)?;
+    let server_info = initialize_result.server_info.clone();
+    let cached_tools = match &context.tools_cache {
+        Some(cache) => cache.load(&server_name, &server_info).await,
+        None => None,
+    };
+    let tools_from_cache = cached_tools.is_some();
+    let tools = match cached_tools {
+        Some(tools) => tools,
+        None => {
+            let tools = list_tools_for_client(&server_name, &client, startup_timeout)
+                .await
+                .map_err(StartupOutcomeError::from)?;
+            if let Some(cache) = &context.tools_cache {
+                cache.store(&server_name, &server_info, &tools).await;
+            }
+            tools
+        }
+    };
 
     let server_supports_sandbox_state_capability = initialize_result
         .capabilities
@@ -841,12 +1658,32 @@ async fn start_server_task(
         .and_then(|exp| exp.get(MCP_SANDBOX_STATE_CAPABILITY))
         .is_some();
 
+    let server_supports_resources = initialize_result.capabilities.resources.is_some();
+
+    // A server whose prompts cannot be listed still offers its tools.
+    let prompts = if initialize_result.capabilities.prompts.is_some() {
+        list_prompts_for_client(&client, startup_timeout)
+            .await
+            .unwrap_or_else(|err| {
+                warn!("Failed to list prompts for MCP server '{server_name}': {err:#}");
+                Vec::new()
+            })
+    } else {
+        Vec::new()
+    };
+
     let managed = ManagedClient {
         client: Arc::clone(&client),
-        tools,
-        tool_timeout: Some(tool_timeout),
-        tool_filter,
+        tools: Arc::new(StdRwLock::new(tools)),
+        tools_from_cache,
+        server_info,
+        tools_cache: context.tools_cache,
+        list_timeout: startup_timeout,
+        tool_timeout: Some(tool_timeout.unwrap_or(DEFAULT_TOOL_TIMEOUT)),
+        configured_tool_timeout: tool_timeout,
         server_supports_sandbox_state_capability,
+        server_supports_resources,
+        prompts,
     };
 
     Ok(managed)
@@ -863,8 +1700,19 @@ async fn make_rmcp_client(
             args,
             env,
             env_vars,
+            env_sources,
             cwd,
         } => {
+            let env = match env_sources {
+                Some(env_sources) if !env_sources.is_empty() => {
+                    let resolved = resolve_env_sources(&env_sources, &DefaultKeyringStore)
+                        .map_err(|error| StartupOutcomeError::Failed { error })?;
+                    let mut env = env.unwrap_or_default();
+                    env.extend(resolved);
+                    Some(env)
+                }
+                _ => env,
+            };
             let command_os: OsString = command.into();
             let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
             RmcpClient::new_stdio_client(command_os, args_os, env, &env_vars, cwd)
@@ -876,18 +1724,36 @@ async fn make_rmcp_client(
             http_headers,
             env_http_headers,
             bearer_token_env_var,
+            header_sources,
+            tls,
         } => {
             let resolved_bearer_token =
                 match resolve_bearer_token(server_name, bearer_token_env_var.as_deref()) {
                     Ok(token) => token,
                     Err(error) => return Err(error.into()),
                 };
+            let http_headers = match header_sources {
+                Some(header_sources) if !header_sources.is_empty() => {
+                    let resolved = resolve_env_sources(&header_sources, &DefaultKeyringStore)
+                        .map_err(|error| StartupOutcomeError::Failed { error })?;
+                    let mut http_headers = http_headers.unwrap_or_default();
+                    http_headers.extend(resolved);
+                    Some(http_headers)
+                }
+                _ => http_headers,
+            };
+            let tls = tls.map(|tls| HttpTlsConfig {
+                ca_certificate: tls.ca_certificate,
+                client_certificate: tls.client_certificate,
+                client_key: tls.client_key,
+            });
             RmcpClient::new_streamable_http_client(
                 server_name,
                 &url,
                 resolved_bearer_token,
                 http_headers,
                 env_http_headers,
+                tls,
                 store_mode,
             )
             .await
@@ -915,6 +1781,28 @@ async fn list_tools_for_client(
         .collect())
 }
 
+async fn list_prompts_for_client(
+    client: &Arc<RmcpClient>,
+    timeout: Option<Duration>,
+) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = cursor.as_ref().map(|next| ListPromptsRequestParams {
+            cursor: Some(next.clone()),
+        });
+        let response = client.list_prompts(params, timeout).await?;
+        prompts.extend(response.prompts);
+        match response.next_cursor {
+            Some(next) if cursor.as_ref() == Some(&next) => {
+                return Err(anyhow!("prompts/list returned duplicate cursor"));
+            }
+            Some(next) => cursor = Some(next),
+            None => return Ok(prompts),
+        }
+    }
+}
+
 fn validate_mcp_server_name(server_name: &str) -> Result<()> {
     let re = regex_lite::Regex::new(r"^[a-zA-Z0-9_-]+$")?;
     if !re.is_match(server_name) {
@@ -1139,6 +2027,29 @@ mod tests {
         assert!(!filter.allows("unknown"));
     }
 
+    #[test]
+    fn tool_filter_matches_patterns() {
+        let filter = ToolFilter {
+            enabled: Some(HashSet::from(["read_*".to_string(), "list".to_string()])),
+            disabled: HashSet::from(["read_secret?".to_string()]),
+        };
+
+        assert!(filter.allows("read_file"));
+        assert!(filter.allows("list"));
+        assert!(filter.allows("read_secrets_index"));
+        assert!(!filter.allows("read_secrets"));
+        assert!(!filter.allows("delete_everything"));
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_cap() {
+        assert_eq!(restart_backoff(0), Duration::from_millis(500));
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(3), Duration::from_secs(4));
+        assert_eq!(restart_backoff(10), MAX_RESTART_BACKOFF);
+        assert_eq!(restart_backoff(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
     #[test]
     fn filter_tools_applies_per_server_filters() {
         let server1_tools = vec![
@@ -1175,6 +2086,8 @@ mod tests {
                     bearer_token_env_var: None,
                     http_headers: None,
                     env_http_headers: None,
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -1183,6 +2096,8 @@ mod tests {
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
             auth_status: McpAuthStatus::Unsupported,
         };
@@ -1221,6 +2136,8 @@ mod tests {
                     bearer_token_env_var: Some("TOKEN".to_string()),
                     http_headers: None,
                     env_http_headers: None,
+                    header_sources: None,
+                    tls: None,
                 },
                 enabled: true,
                 disabled_reason: None,
@@ -1229,6 +2146,8 @@ mod tests {
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
             auth_status: McpAuthStatus::Unsupported,
         };
@@ -4,12 +4,16 @@ use tracing::error;
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
-use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
-use crate::protocol::McpToolCallBeginEvent;
-use crate::protocol::McpToolCallEndEvent;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpToolCallOutputDeltaEvent;
+use codex_protocol::protocol::McpToolCallProgressEvent;
+use codex_rmcp_client::ToolCallCancelled;
+use codex_rmcp_client::ToolCallUpdate;
 
 /// Handles the specified tool call dispatches the appropriate
 /// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
@@ -48,31 +52,60 @@ pub(crate) async fn handle_mcp_tool_call(
         arguments: arguments_value.clone(),
     };
 
-    let tool_call_begin_event = EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
-        call_id: call_id.clone(),
-        invocation: invocation.clone(),
-    });
-    notify_mcp_tool_call_event(sess, turn_context, tool_call_begin_event).await;
+    let tool_timeouts = sess.tool_timeouts().await;
+    // A server's own `tool_timeout_sec` wins over the global default.
+    let default_timeout = sess
+        .mcp_tool_timeout(&server)
+        .await
+        .unwrap_or(tool_timeouts.mcp_call);
+    let timeout = tool_timeouts.resolve(None, default_timeout);
+
+    let emitter = ToolEmitter::mcp(invocation, Some(timeout.as_millis() as u64));
+    let event_ctx = ToolEventCtx::new(sess, turn_context, &call_id, None);
+    emitter.begin(event_ctx).await;
 
     let start = Instant::now();
-    // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone())
-        .await
-        .map_err(|e| format!("tool call error: {e:?}"));
-    if let Err(e) = &result {
-        tracing::warn!("MCP tool call error: {e:?}");
+    // Perform the tool call, forwarding progress and partial output while it
+    // runs. Servers that report neither never send on `updates_rx`.
+    let (updates_tx, mut updates_rx) = tokio::sync::mpsc::unbounded_channel();
+    let call = sess.call_tool(
+        &server,
+        &tool_name,
+        arguments_value.clone(),
+        timeout,
+        Some(updates_tx),
+        &call_id,
+    );
+    tokio::pin!(call);
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(update) = updates_rx.recv() => {
+                emit_tool_call_update(sess, turn_context, &call_id, update).await;
+            }
+        }
+    };
+    // The turn was interrupted and the server has been told to stop.
+    let aborted = matches!(&result, Err(e) if e.downcast_ref::<ToolCallCancelled>().is_some());
+    let result = result.map_err(|e| format!("tool call error: {e:?}"));
+    if aborted {
+        emitter
+            .abort_mcp(event_ctx, start.elapsed(), "aborted by user".to_string())
+            .await;
+    } else {
+        if let Err(e) = &result {
+            tracing::warn!("MCP tool call error: {e:?}");
+        }
+        emitter
+            .finish_mcp(event_ctx, start.elapsed(), &result)
+            .await;
     }
-    let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
-        call_id: call_id.clone(),
-        invocation,
-        duration: start.elapsed(),
-        result: result.clone(),
-    });
 
-    notify_mcp_tool_call_event(sess, turn_context, tool_call_end_event.clone()).await;
-
-    let status = if result.is_ok() { "ok" } else { "error" };
+    let status = match &result {
+        Ok(_) => "ok",
+        Err(_) if aborted => "aborted",
+        Err(_) => "error",
+    };
     turn_context
         .client
         .get_otel_manager()
@@ -81,6 +114,50 @@ pub(crate) async fn handle_mcp_tool_call(
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
-async fn notify_mcp_tool_call_event(sess: &Session, turn_context: &TurnContext, event: EventMsg) {
-    sess.send_event(turn_context, event).await;
+async fn emit_tool_call_update(
+    sess: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    update: ToolCallUpdate,
+) {
+    let msg = match update {
+        ToolCallUpdate::Progress {
+            progress,
+            total,
+            message,
+        } => EventMsg::McpToolCallProgress(McpToolCallProgressEvent {
+            call_id: call_id.to_string(),
+            progress,
+            total,
+            percentage: progress_percentage(progress, total),
+            message,
+        }),
+        ToolCallUpdate::OutputDelta(delta) => {
+            EventMsg::McpToolCallOutputDelta(McpToolCallOutputDeltaEvent {
+                call_id: call_id.to_string(),
+                delta,
+            })
+        }
+    };
+    sess.send_event(turn_context, msg).await;
+}
+
+fn progress_percentage(progress: f64, total: Option<f64>) -> Option<f64> {
+    total
+        .filter(|total| *total > 0.0)
+        .map(|total| (progress / total * 100.0).clamp(0.0, 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn progress_percentage_needs_a_positive_total() {
+        assert_eq!(progress_percentage(5.0, Some(20.0)), Some(25.0));
+        assert_eq!(progress_percentage(30.0, Some(20.0)), Some(100.0));
+        assert_eq!(progress_percentage(5.0, Some(0.0)), None);
+        assert_eq!(progress_percentage(5.0, None), None);
+    }
 }
@@ -0,0 +1,116 @@
+//! Session-scoped memo of [`parse_command`](crate::parse_command::parse_command)
+//! results.
+//!
+//! A single tool call parses its command for the approval prompt, the begin
+//! event, any sandbox-denial retry, and the end event. Parsing a large heredoc
+//! is not free, so the session keeps the most recent results keyed by the
+//! command vector and hands out shared `Arc`s.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use codex_protocol::parse_command::ParsedCommand;
+
+/// Number of distinct commands remembered per session.
+const PARSED_COMMAND_CACHE_CAPACITY: usize = 32;
+
+pub(crate) struct ParsedCommandCache {
+    capacity: usize,
+    /// Most recently used entry first.
+    entries: Mutex<VecDeque<(Vec<String>, Arc<Vec<ParsedCommand>>)>>,
+}
+
+impl Default for ParsedCommandCache {
+    fn default() -> Self {
+        Self::with_capacity(PARSED_COMMAND_CACHE_CAPACITY)
+    }
+}
+
+impl ParsedCommandCache {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the cached parse for `command`, running `parse` on a miss.
+    ///
+    /// The parser runs without holding the lock, so two concurrent misses for
+    /// the same command may both parse; the later insert wins.
+    pub(crate) fn get_or_parse(
+        &self,
+        command: &[String],
+        parse: impl FnOnce(&[String]) -> Vec<ParsedCommand>,
+    ) -> Arc<Vec<ParsedCommand>> {
+        if let Some(parsed) = self.get(command) {
+            return parsed;
+        }
+        let parsed = Arc::new(parse(command));
+        self.insert(command.to_vec(), Arc::clone(&parsed));
+        parsed
+    }
+
+    fn get(&self, command: &[String]) -> Option<Arc<Vec<ParsedCommand>>> {
+        let mut entries = self.entries.lock().ok()?;
+        let index = entries
+            .iter()
+            .position(|(key, _)| key.as_slice() == command)?;
+        let entry = entries.remove(index)?;
+        let parsed = Arc::clone(&entry.1);
+        entries.push_front(entry);
+        Some(parsed)
+    }
+
+    fn insert(&self, command: Vec<String>, parsed: Arc<Vec<ParsedCommand>>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|(key, _)| key != &command);
+        entries.push_front((command, parsed));
+        entries.truncate(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command::parse_command;
+    use pretty_assertions::assert_eq;
+
+    fn command(script: &str) -> Vec<String> {
+        vec!["bash".to_string(), "-lc".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn repeated_commands_share_one_parse() {
+        let cache = ParsedCommandCache::default();
+        let cmd = command("ls -la");
+        let first = cache.get_or_parse(&cmd, parse_command);
+        let second = cache.get_or_parse(&cmd, |_| panic!("command was parsed twice"));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, parse_command(&cmd));
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = ParsedCommandCache::with_capacity(2);
+        let a = command("echo a");
+        let b = command("echo b");
+        let c = command("echo c");
+        cache.get_or_parse(&a, parse_command);
+        cache.get_or_parse(&b, parse_command);
+        // Touch `a` so `b` becomes the eviction candidate.
+        cache.get_or_parse(&a, |_| panic!("a should still be cached"));
+        cache.get_or_parse(&c, parse_command);
+
+        cache.get_or_parse(&a, |_| panic!("a should still be cached"));
+        let mut reparsed = false;
+        cache.get_or_parse(&b, |cmd| {
+            reparsed = true;
+            parse_command(cmd)
+        });
+        assert!(reparsed, "b should have been evicted");
+    }
+}
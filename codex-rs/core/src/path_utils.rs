@@ -1,4 +1,5 @@
 use codex_utils_absolute_path::AbsolutePathBuf;
+use icu_normalizer::ComposingNormalizerBorrowed;
 use std::collections::HashSet;
 use std::io;
 use std::path::Path;
@@ -112,6 +113,96 @@ pub fn write_atomically(write_path: &Path, contents: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// Normalizes a path that is about to go into an event. On Windows this drops
+/// the `\\?\` extended-length prefix when the path means the same without
+/// it and uses `\` as the only separator, so one file is always spelled the
+/// same way. Elsewhere paths are returned unchanged.
+pub fn normalize_event_path(path: &Path) -> PathBuf {
+    normalize_event_path_with_flag(path, cfg!(windows))
+}
+
+/// Key for comparing event paths: the normalized path, lowercased on Windows
+/// where the filesystem ignores case, and in Unicode NFC on macOS where the
+/// filesystem ignores normalization (`é` may arrive precomposed or as `e`
+/// plus a combining accent). Only use it for lookups; the path itself is
+/// what the filesystem should see.
+pub fn event_path_key(path: &Path) -> PathBuf {
+    event_path_key_with_flags(path, cfg!(windows), cfg!(target_os = "macos"))
+}
+
+fn normalize_event_path_with_flag(path: &Path, is_windows: bool) -> PathBuf {
+    match path.to_str() {
+        Some(text) if is_windows => PathBuf::from(normalize_windows_path(text)),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn event_path_key_with_flags(path: &Path, is_windows: bool, is_macos: bool) -> PathBuf {
+    let normalized = normalize_event_path_with_flag(path, is_windows);
+    match normalized.to_str() {
+        Some(text) if is_windows => PathBuf::from(text.to_lowercase()),
+        Some(text) if is_macos => {
+            let nfc = ComposingNormalizerBorrowed::new_nfc().normalize(text);
+            PathBuf::from(nfc.as_ref())
+        }
+        _ => normalized,
+    }
+}
+
+/// Windows paths are only simplified below this length; longer ones need the
+/// extended-length prefix.
+const WINDOWS_MAX_PATH: usize = 260;
+
+fn normalize_windows_path(path: &str) -> String {
+    let Some(verbatim) = path.strip_prefix(r"\\?\") else {
+        return path.replace('/', "\\");
+    };
+    // `/` is an ordinary character in a verbatim path, so these are only
+    // rewritten when nothing in them depends on the prefix.
+    let unc = verbatim
+        .get(..4)
+        .filter(|head| head.eq_ignore_ascii_case(r"UNC\"))
+        .map(|_| &verbatim[4..]);
+    match unc {
+        Some(unc) if unc.split('\\').count() >= 2 && is_plain_windows_path(unc, 0) => {
+            format!(r"\\{unc}")
+        }
+        None if has_drive_root(verbatim) && is_plain_windows_path(verbatim, 3) => {
+            verbatim.to_string()
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn has_drive_root(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
+/// Whether `path` (past its first `skip` bytes) reads the same with and
+/// without the extended-length prefix: short enough, no `/`, and no
+/// component that Windows would otherwise trim, resolve, or reserve.
+fn is_plain_windows_path(path: &str, skip: usize) -> bool {
+    const RESERVED: &[&str] = &[
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    if path.len() + 2 >= WINDOWS_MAX_PATH || path.contains('/') {
+        return false;
+    }
+    let rest = path.get(skip..).unwrap_or_default().trim_end_matches('\\');
+    rest.is_empty()
+        || rest.split('\\').all(|component| {
+            let stem = component.split('.').next().unwrap_or_default();
+            !component.is_empty()
+                && component != "."
+                && component != ".."
+                && !component.ends_with(['.', ' '])
+                && !component.contains(['<', '>', ':', '"', '|', '?', '*'])
+                && !RESERVED.iter().any(|name| stem.eq_ignore_ascii_case(name))
+        })
+}
+
 fn normalize_for_wsl(path: PathBuf) -> PathBuf {
     normalize_for_wsl_with_flag(path, env::is_wsl())
 }
@@ -240,4 +331,102 @@ mod tests {
             assert_eq!(normalized, path);
         }
     }
+
+    mod event_paths {
+        use super::super::event_path_key_with_flags;
+        use super::super::normalize_event_path_with_flag;
+        use pretty_assertions::assert_eq;
+        use std::path::Path;
+        use std::path::PathBuf;
+
+        fn normalize(path: &str) -> PathBuf {
+            normalize_event_path_with_flag(Path::new(path), true)
+        }
+
+        #[test]
+        fn extended_length_drive_paths_drop_the_prefix() {
+            assert_eq!(
+                normalize(r"\\?\C:\Users\Dev\repo\src\lib.rs"),
+                PathBuf::from(r"C:\Users\Dev\repo\src\lib.rs")
+            );
+            assert_eq!(normalize(r"\\?\D:\"), PathBuf::from(r"D:\"));
+        }
+
+        #[test]
+        fn extended_length_unc_paths_become_plain_unc() {
+            assert_eq!(
+                normalize(r"\\?\UNC\server\share\repo\a.txt"),
+                PathBuf::from(r"\\server\share\repo\a.txt")
+            );
+            assert_eq!(
+                normalize(r"\\server/share/repo\a.txt"),
+                PathBuf::from(r"\\server\share\repo\a.txt")
+            );
+        }
+
+        #[test]
+        fn extended_length_prefix_is_kept_when_it_matters() {
+            for path in [
+                r"\\?\C:\repo\trailing dot.",
+                r"\\?\C:\repo\con.txt",
+                r"\\?\C:\repo\..\escape",
+                r"\\?\C:\repo/literal-slash",
+                r"\\?\UNC\server",
+                r"\\?\Volume{6e7c1ea8-0000-0000-0000-100000000000}\repo",
+            ] {
+                assert_eq!(normalize(path), PathBuf::from(path), "{path}");
+            }
+            let long = format!(r"\\?\C:\{}", "a".repeat(300));
+            assert_eq!(normalize(&long), PathBuf::from(&long));
+        }
+
+        #[test]
+        fn mixed_separators_are_unified() {
+            assert_eq!(
+                normalize("C:/Users/Dev\\repo/src\\lib.rs"),
+                PathBuf::from(r"C:\Users\Dev\repo\src\lib.rs")
+            );
+        }
+
+        #[test]
+        fn normalizing_is_idempotent() {
+            for path in [
+                r"\\?\C:\Users\Dev\repo",
+                r"\\?\UNC\server\share\repo",
+                "C:/Users/Dev/repo",
+                r"\\?\C:\repo\con.txt",
+            ] {
+                let once = normalize(path);
+                assert_eq!(normalize(once.to_str().expect("utf-8")), once, "{path}");
+            }
+        }
+
+        #[test]
+        fn keys_ignore_case_and_spelling_on_windows_only() {
+            let spellings = [
+                r"C:\Users\Dev\Repo\Main.rs",
+                "c:/users/dev/repo/main.rs",
+                r"\\?\C:\USERS\dev\repo\MAIN.RS",
+            ];
+            let keys: Vec<PathBuf> = spellings
+                .iter()
+                .map(|path| event_path_key_with_flags(Path::new(path), true, false))
+                .collect();
+            assert_eq!(keys, vec![PathBuf::from(r"c:\users\dev\repo\main.rs"); 3]);
+
+            let unix = Path::new("/home/Dev/Repo");
+            assert_eq!(event_path_key_with_flags(unix, false, false), unix);
+            assert_eq!(normalize_event_path_with_flag(unix, false), unix);
+        }
+
+        #[test]
+        fn keys_ignore_unicode_normalization_on_macos_only() {
+            let nfc = Path::new("/Users/dev/r\u{e9}sum\u{e9}/caf\u{e9}.txt");
+            let nfd = Path::new("/Users/dev/re\u{301}sume\u{301}/cafe\u{301}.txt");
+            assert_eq!(event_path_key_with_flags(nfd, false, true), nfc);
+            assert_eq!(event_path_key_with_flags(nfc, false, true), nfc);
+
+            assert_eq!(event_path_key_with_flags(nfd, false, false), nfd);
+        }
+    }
 }
@@ -0,0 +1,113 @@
+//! Error classes and retry delays for `[provider_failover]`.
+//!
+//! Sampling requests that fail with a rate limit, a server error or a
+//! dropped stream are retried on the current provider as the class's
+//! [`FailoverRetry`] allows; once those retries are spent the session moves
+//! to the next provider of the chain (see `Session::fail_over`).
+
+use std::time::Duration;
+
+use codex_protocol::protocol::ProviderSwitchReason;
+use http::StatusCode;
+
+use crate::config::types::FailoverRetry;
+use crate::config::types::ProviderFailover;
+use crate::error::CodexErr;
+
+/// The class of `err`, or `None` for errors another provider would not fix.
+pub(crate) fn switch_reason(err: &CodexErr) -> Option<ProviderSwitchReason> {
+    match err {
+        CodexErr::RetryLimit(err) if err.status == StatusCode::TOO_MANY_REQUESTS => {
+            Some(ProviderSwitchReason::RateLimited)
+        }
+        CodexErr::UsageLimitReached(_) => Some(ProviderSwitchReason::RateLimited),
+        CodexErr::RetryLimit(err) if err.status.is_server_error() => {
+            Some(ProviderSwitchReason::ServerError)
+        }
+        CodexErr::UnexpectedStatus(err) if err.status.is_server_error() => {
+            Some(ProviderSwitchReason::ServerError)
+        }
+        CodexErr::InternalServerError => Some(ProviderSwitchReason::ServerError),
+        CodexErr::Stream(..)
+        | CodexErr::Timeout
+        | CodexErr::ConnectionFailed(_)
+        | CodexErr::ResponseStreamFailed(_) => Some(ProviderSwitchReason::Disconnected),
+        _ => None,
+    }
+}
+
+pub(crate) fn retry_policy(
+    failover: &ProviderFailover,
+    reason: ProviderSwitchReason,
+) -> FailoverRetry {
+    match reason {
+        ProviderSwitchReason::RateLimited => failover.rate_limit,
+        ProviderSwitchReason::ServerError => failover.server_error,
+        ProviderSwitchReason::Disconnected => failover.disconnect,
+    }
+}
+
+/// Delay before retry number `attempt` (starting at 1): the policy's
+/// backoff, doubled for each retry after the first.
+pub(crate) fn retry_delay(policy: FailoverRetry, attempt: u64) -> Duration {
+    let factor = u32::try_from(attempt.saturating_sub(1))
+        .ok()
+        .and_then(|shift| 1u32.checked_shl(shift))
+        .unwrap_or(u32::MAX);
+    policy.backoff.saturating_mul(factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RetryLimitReachedError;
+    use crate::error::UnexpectedResponseError;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn errors_are_classified_by_status() {
+        let retry_limit = |status| {
+            CodexErr::RetryLimit(RetryLimitReachedError {
+                status,
+                request_id: None,
+            })
+        };
+        let unexpected = |status| {
+            CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                status,
+                body: String::new(),
+                url: None,
+                request_id: None,
+            })
+        };
+        assert_eq!(
+            switch_reason(&retry_limit(StatusCode::TOO_MANY_REQUESTS)),
+            Some(ProviderSwitchReason::RateLimited)
+        );
+        assert_eq!(
+            switch_reason(&retry_limit(StatusCode::BAD_GATEWAY)),
+            Some(ProviderSwitchReason::ServerError)
+        );
+        assert_eq!(
+            switch_reason(&unexpected(StatusCode::SERVICE_UNAVAILABLE)),
+            Some(ProviderSwitchReason::ServerError)
+        );
+        assert_eq!(switch_reason(&unexpected(StatusCode::NOT_FOUND)), None);
+        assert_eq!(
+            switch_reason(&CodexErr::Stream("cut off".to_string(), None)),
+            Some(ProviderSwitchReason::Disconnected)
+        );
+        assert_eq!(switch_reason(&CodexErr::ContextWindowExceeded), None);
+    }
+
+    #[test]
+    fn retry_delay_doubles_per_attempt() {
+        let policy = FailoverRetry {
+            max_retries: 3,
+            backoff: Duration::from_millis(500),
+        };
+        assert_eq!(retry_delay(policy, 1), Duration::from_millis(500));
+        assert_eq!(retry_delay(policy, 3), Duration::from_secs(2));
+        assert!(retry_delay(policy, 64) >= retry_delay(policy, 32));
+    }
+}
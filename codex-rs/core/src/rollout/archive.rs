@@ -0,0 +1,72 @@
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+
+/// Moves the rollout at `path` out of `<root>/sessions` into
+/// `<root>/archived_sessions`, where session listings no longer find it.
+/// `root` is the codex home or project state dir the session was recorded
+/// under. Returns the archived path.
+pub async fn archive_rollout(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let sessions_dir = tokio::fs::canonicalize(root.join(SESSIONS_SUBDIR)).await?;
+    let rollout_path = tokio::fs::canonicalize(path).await?;
+    if !rollout_path.starts_with(&sessions_dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "rollout path `{}` must be in {}",
+                path.display(),
+                sessions_dir.display()
+            ),
+        ));
+    }
+    let Some(file_name) = rollout_path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("rollout path `{}` missing file name", path.display()),
+        ));
+    };
+
+    let archive_dir = root.join(ARCHIVED_SESSIONS_SUBDIR);
+    tokio::fs::create_dir_all(&archive_dir).await?;
+    let archived_path = archive_dir.join(file_name);
+    tokio::fs::rename(&rollout_path, &archived_path).await?;
+    Ok(archived_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn archives_only_rollouts_under_the_sessions_dir() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let day_dir = root.path().join(SESSIONS_SUBDIR).join("2025/01/02");
+        std::fs::create_dir_all(&day_dir).expect("sessions dir");
+        let rollout = day_dir.join("rollout-2025-01-02T00-00-00-abc.jsonl");
+        std::fs::write(&rollout, "{}\n").expect("rollout");
+        let outside = root.path().join("elsewhere.jsonl");
+        std::fs::write(&outside, "{}\n").expect("outside");
+
+        let err = archive_rollout(root.path(), &outside)
+            .await
+            .expect_err("files outside sessions are refused");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(outside.exists());
+
+        let archived = archive_rollout(root.path(), &rollout)
+            .await
+            .expect("archive");
+        assert_eq!(
+            archived,
+            root.path()
+                .join(ARCHIVED_SESSIONS_SUBDIR)
+                .join("rollout-2025-01-02T00-00-00-abc.jsonl")
+        );
+        assert!(archived.exists());
+        assert!(!rollout.exists());
+    }
+}
@@ -51,6 +51,18 @@ pub struct ThreadItem {
     /// RFC3339 timestamp string for the most recent update (from file mtime).
     /// updated_at is truncated to second precision to match created_at.
     pub updated_at: Option<String>,
+    /// Which state directory the rollout file was found in.
+    pub provenance: ThreadProvenance,
+}
+
+/// Location a listed thread was recorded in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThreadProvenance {
+    /// The global sessions directory under `CODEX_HOME`.
+    #[default]
+    Global,
+    /// A project-local state directory configured via `state_dir`.
+    Project,
 }
 
 #[allow(dead_code)]
@@ -291,6 +303,68 @@ pub(crate) async fn get_threads(
     .await
 }
 
+/// Like [`get_threads`], but also lists the project-local `state_dir` when it
+/// differs from `codex_home`, merging both into a single page. Each root is
+/// scanned from the same cursor, so paging stays stable across the merge.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn get_threads_across_state_dirs(
+    codex_home: &Path,
+    state_dir: &Path,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+    sort_key: ThreadSortKey,
+    allowed_sources: &[SessionSource],
+    model_providers: Option<&[String]>,
+    default_provider: &str,
+) -> io::Result<ThreadsPage> {
+    let global = get_threads(
+        codex_home,
+        page_size,
+        cursor,
+        sort_key,
+        allowed_sources,
+        model_providers,
+        default_provider,
+    )
+    .await?;
+    if state_dir == codex_home {
+        return Ok(global);
+    }
+    let mut project = get_threads(
+        state_dir,
+        page_size,
+        cursor,
+        sort_key,
+        allowed_sources,
+        model_providers,
+        default_provider,
+    )
+    .await?;
+    for item in &mut project.items {
+        item.provenance = ThreadProvenance::Project;
+    }
+
+    let mut more_matches_available = global.next_cursor.is_some() || project.next_cursor.is_some();
+    let mut items = global.items;
+    items.extend(project.items);
+    items.sort_by_key(|item| Reverse(sort_position(item, sort_key)));
+    if items.len() > page_size {
+        items.truncate(page_size);
+        more_matches_available = true;
+    }
+    let next_cursor = if more_matches_available {
+        build_next_cursor(&items, sort_key)
+    } else {
+        None
+    };
+    Ok(ThreadsPage {
+        items,
+        next_cursor,
+        num_scanned_files: global.num_scanned_files + project.num_scanned_files,
+        reached_scan_cap: global.reached_scan_cap || project.reached_scan_cap,
+    })
+}
+
 pub(crate) async fn get_threads_in_root(
     root: PathBuf,
     page_size: usize,
@@ -637,17 +711,22 @@ pub fn parse_cursor(token: &str) -> Option<Cursor> {
 }
 
 fn build_next_cursor(items: &[ThreadItem], sort_key: ThreadSortKey) -> Option<Cursor> {
-    let last = items.last()?;
-    let file_name = last.path.file_name()?.to_string_lossy();
+    let (ts, id) = sort_position(items.last()?, sort_key)?;
+    Some(Cursor::new(ts, id))
+}
+
+/// Returns the `(timestamp, id)` pair an item is ordered by for `sort_key`.
+fn sort_position(item: &ThreadItem, sort_key: ThreadSortKey) -> Option<(OffsetDateTime, Uuid)> {
+    let file_name = item.path.file_name()?.to_string_lossy();
     let (created_ts, id) = parse_timestamp_uuid_from_filename(&file_name)?;
     let ts = match sort_key {
         ThreadSortKey::CreatedAt => created_ts,
         ThreadSortKey::UpdatedAt => {
-            let updated_at = last.updated_at.as_deref()?;
+            let updated_at = item.updated_at.as_deref()?;
             OffsetDateTime::parse(updated_at, &Rfc3339).ok()?
         }
     };
-    Some(Cursor::new(ts, id))
+    Some((ts, id))
 }
 
 async fn build_thread_item(
@@ -688,6 +767,7 @@ async fn build_thread_item(
             head,
             created_at,
             updated_at: summary_updated_at,
+            provenance: ThreadProvenance::Global,
         });
     }
     None
@@ -943,8 +1023,8 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
     use tokio::io::AsyncBufReadExt;
 
     let file = tokio::fs::File::open(path).await?;
-    let reader = tokio::io::BufReader::new(file);
-    let mut lines = reader.lines();
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut buf = Vec::new();
     let mut summary = HeadTailSummary::default();
     let mut lines_scanned = 0usize;
 
@@ -953,8 +1033,13 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
             && !summary.saw_user_event
             && lines_scanned < head_limit + USER_EVENT_SCAN_LIMIT)
     {
-        let line_opt = lines.next_line().await?;
-        let Some(line) = line_opt else { break };
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).await? == 0 {
+            break;
+        }
+        // A writer killed mid-record can leave invalid UTF-8 on the last line;
+        // decode lossily so the complete records before it still count.
+        let line = String::from_utf8_lossy(&buf);
         let trimmed = line.trim();
         if trimmed.is_empty() {
             continue;
@@ -1110,6 +1195,21 @@ pub async fn find_thread_path_by_id_str(
     find_thread_path_by_id_str_in_subdir(codex_home, SESSIONS_SUBDIR, id_str).await
 }
 
+/// Locate a thread rollout file by its UUID string, checking the project-local
+/// `state_dir` before the global sessions directory under `codex_home`.
+pub async fn find_thread_path_by_id_str_in_state_dirs(
+    codex_home: &Path,
+    state_dir: &Path,
+    id_str: &str,
+) -> io::Result<Option<PathBuf>> {
+    if state_dir != codex_home
+        && let Some(path) = find_thread_path_by_id_str(state_dir, id_str).await?
+    {
+        return Ok(Some(path));
+    }
+    find_thread_path_by_id_str(codex_home, id_str).await
+}
+
 /// Locate an archived thread rollout file by its UUID string.
 pub async fn find_archived_thread_path_by_id_str(
     codex_home: &Path,
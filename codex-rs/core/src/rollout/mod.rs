@@ -7,21 +7,29 @@ pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 pub const INTERACTIVE_SESSION_SOURCES: &[SessionSource] =
     &[SessionSource::Cli, SessionSource::VSCode];
 
+pub mod archive;
 pub(crate) mod error;
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod summary;
 pub(crate) mod truncation;
 
+pub use archive::archive_rollout;
 pub use codex_protocol::protocol::SessionMeta;
 pub(crate) use error::map_session_init_error;
 pub use list::find_archived_thread_path_by_id_str;
 pub use list::find_thread_path_by_id_str;
 #[deprecated(note = "use find_thread_path_by_id_str")]
 pub use list::find_thread_path_by_id_str as find_conversation_path_by_id_str;
+pub use list::find_thread_path_by_id_str_in_state_dirs;
 pub use list::rollout_date_parts;
 pub use recorder::RolloutRecorder;
 pub use recorder::RolloutRecorderParams;
+pub use recorder::install_rollout_panic_hook;
+pub use recorder::sync_open_rollouts;
+pub use summary::ThreadSummary;
+pub use summary::read_thread_summary;
 
 #[cfg(test)]
 pub mod tests;
@@ -15,6 +15,19 @@ pub(crate) fn is_persisted_response_item(item: &RolloutItem) -> bool {
     }
 }
 
+/// The form of `ev` written to rollouts and copied to attached clients. MCP
+/// tool call events keep only their redacted previews, since the raw
+/// arguments and result can hold credentials.
+pub(crate) fn redact_event_msg(ev: &EventMsg) -> EventMsg {
+    match ev {
+        EventMsg::McpToolCallBegin(event) => {
+            EventMsg::McpToolCallBegin(event.without_raw_payload())
+        }
+        EventMsg::McpToolCallEnd(event) => EventMsg::McpToolCallEnd(event.without_raw_payload()),
+        ev => ev.clone(),
+    }
+}
+
 /// Whether a `ResponseItem` should be persisted in rollout files.
 #[inline]
 pub(crate) fn should_persist_response_item(item: &ResponseItem) -> bool {
@@ -47,7 +60,35 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::ExitedReviewMode(_)
         | EventMsg::ThreadRolledBack(_)
         | EventMsg::UndoCompleted(_)
-        | EventMsg::TurnAborted(_) => true,
+        | EventMsg::TurnAborted(_)
+        | EventMsg::McpToolCallBegin(_)
+        | EventMsg::McpToolCallEnd(_)
+        | EventMsg::McpSamplingRequest(_)
+        | EventMsg::McpSamplingResponse(_)
+        | EventMsg::HeadlessApprovalDecision(_)
+        | EventMsg::ToolCallBlocked(_)
+        | EventMsg::ToolCallAborted(_)
+        | EventMsg::FailureReported(_)
+        | EventMsg::OutcomeReported(_)
+        | EventMsg::LoopDetected(_)
+        | EventMsg::GitStateChanged(_)
+        | EventMsg::BudgetExceeded(_)
+        | EventMsg::ProviderSwitched(_)
+        | EventMsg::ModelChanged(_)
+        | EventMsg::StreamRetry(_)
+        | EventMsg::NotificationSent(_)
+        | EventMsg::WorkspaceRootsChanged(_)
+        | EventMsg::WorkspacePrimer(_)
+        | EventMsg::WorkspaceSnapshotCreated(_)
+        | EventMsg::WorkspaceSnapshotRestored(_)
+        | EventMsg::CapabilitiesChanged(_)
+        | EventMsg::PlanOnlyChanged(_)
+        | EventMsg::ProposedExec(_)
+        | EventMsg::ProposedPatch(_)
+        | EventMsg::BackgroundJobStatus(_)
+        | EventMsg::PlanUpdate(_)
+        | EventMsg::ViewImageToolCall(_)
+        | EventMsg::FileAccessLedger(_) => true,
         EventMsg::Error(_)
         | EventMsg::Warning(_)
         | EventMsg::TurnStarted(_)
@@ -58,8 +99,6 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::AgentReasoningSectionBreak(_)
         | EventMsg::RawResponseItem(_)
         | EventMsg::SessionConfigured(_)
-        | EventMsg::McpToolCallBegin(_)
-        | EventMsg::McpToolCallEnd(_)
         | EventMsg::WebSearchBegin(_)
         | EventMsg::WebSearchEnd(_)
         | EventMsg::ExecCommandBegin(_)
@@ -75,17 +114,34 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::StreamError(_)
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::PatchApplyEnd(_)
+        | EventMsg::FileReadBegin(_)
+        | EventMsg::FileReadEnd(_)
+        | EventMsg::WorkspaceSearchBegin(_)
+        | EventMsg::WorkspaceSearchEnd(_)
+        | EventMsg::ListDirBegin(_)
+        | EventMsg::ListDirEnd(_)
+        | EventMsg::ExecOutputReadBegin(_)
+        | EventMsg::ExecOutputReadEnd(_)
         | EventMsg::TurnDiff(_)
+        | EventMsg::TurnSummary(_)
+        | EventMsg::TurnProgress(_)
         | EventMsg::GetHistoryEntryResponse(_)
         | EventMsg::UndoStarted(_)
         | EventMsg::McpListToolsResponse(_)
+        | EventMsg::McpListPromptsResponse(_)
+        | EventMsg::McpPromptResponse(_)
+        | EventMsg::McpToolCallProgress(_)
+        | EventMsg::McpToolCallOutputDelta(_)
         | EventMsg::McpStartupUpdate(_)
         | EventMsg::McpStartupComplete(_)
+        | EventMsg::McpServerStatus(_)
         | EventMsg::ListCustomPromptsResponse(_)
+        | EventMsg::WorkspaceSnapshots(_)
+        | EventMsg::EffectiveConfigResponse(_)
+        | EventMsg::ConfigReloaded(_)
+        | EventMsg::HookExecuted(_)
         | EventMsg::ListSkillsResponse(_)
-        | EventMsg::PlanUpdate(_)
         | EventMsg::ShutdownComplete
-        | EventMsg::ViewImageToolCall(_)
         | EventMsg::DeprecationNotice(_)
         | EventMsg::ItemStarted(_)
         | EventMsg::ItemCompleted(_)
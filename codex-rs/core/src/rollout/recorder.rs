@@ -1,10 +1,15 @@
 //! Persist Codex session rollouts (.jsonl) so sessions can be replayed or inspected later.
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::fs::{self};
 use std::io::Error as IoError;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::task::Poll;
 
 use codex_protocol::ThreadId;
 use codex_protocol::models::BaseInstructions;
@@ -27,6 +32,7 @@ use super::list::ThreadListLayout;
 use super::list::ThreadSortKey;
 use super::list::ThreadsPage;
 use super::list::get_threads;
+use super::list::get_threads_across_state_dirs;
 use super::list::get_threads_in_root;
 use super::policy::is_persisted_response_item;
 use crate::config::Config;
@@ -41,6 +47,22 @@ use codex_protocol::protocol::SessionMeta;
 use codex_protocol::protocol::SessionMetaLine;
 use codex_protocol::protocol::SessionSource;
 
+/// Rollouts that still have a live writer task, keyed by path. The panic
+/// hook uses these to write the queued records and make them durable without
+/// waiting on the async writer, which may never get to run again.
+static OPEN_ROLLOUTS: LazyLock<Mutex<HashMap<PathBuf, OpenRollout>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// What [`sync_open_rollouts`] needs to finish a rollout without its writer.
+struct OpenRollout {
+    /// Duplicate of the writer's file handle.
+    file: File,
+    /// The writer's queue. The writer only locks it while polling, so it is
+    /// free whenever the writer is waiting or writing.
+    rx: Arc<Mutex<mpsc::Receiver<RolloutCmd>>>,
+    max_event_record_bytes: Option<usize>,
+}
+
 /// Records all [`ResponseItem`]s for a session and flushes them to disk after
 /// every update.
 ///
@@ -69,7 +91,7 @@ pub enum RolloutRecorderParams {
     },
 }
 
-enum RolloutCmd {
+pub(super) enum RolloutCmd {
     AddItems(Vec<RolloutItem>),
     /// Ensure all prior writes are processed; respond when flushed.
     Flush {
@@ -123,6 +145,33 @@ impl RolloutRecorder {
         .await
     }
 
+    /// List threads from the global sessions directory and, when it differs,
+    /// the project-local `state_dir`. Items carry their
+    /// [`ThreadProvenance`](super::list::ThreadProvenance).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn list_threads_with_state_dir(
+        codex_home: &Path,
+        state_dir: &Path,
+        page_size: usize,
+        cursor: Option<&Cursor>,
+        sort_key: ThreadSortKey,
+        allowed_sources: &[SessionSource],
+        model_providers: Option<&[String]>,
+        default_provider: &str,
+    ) -> std::io::Result<ThreadsPage> {
+        get_threads_across_state_dirs(
+            codex_home,
+            state_dir,
+            page_size,
+            cursor,
+            sort_key,
+            allowed_sources,
+            model_providers,
+            default_provider,
+        )
+        .await
+    }
+
     /// List archived threads (rollout files) under the archived sessions directory.
     pub async fn list_archived_threads(
         codex_home: &Path,
@@ -149,10 +198,12 @@ impl RolloutRecorder {
         .await
     }
 
-    /// Find the newest recorded thread path, optionally filtering to a matching cwd.
+    /// Find the newest recorded thread path across the global sessions
+    /// directory and `state_dir`, optionally filtering to a matching cwd.
     #[allow(clippy::too_many_arguments)]
     pub async fn find_latest_thread_path(
         codex_home: &Path,
+        state_dir: &Path,
         page_size: usize,
         cursor: Option<&Cursor>,
         sort_key: ThreadSortKey,
@@ -163,8 +214,9 @@ impl RolloutRecorder {
     ) -> std::io::Result<Option<PathBuf>> {
         let mut cursor = cursor.cloned();
         loop {
-            let page = Self::list_threads(
+            let page = Self::list_threads_with_state_dir(
                 codex_home,
+                state_dir,
                 page_size,
                 cursor.as_ref(),
                 sort_key,
@@ -225,16 +277,18 @@ impl RolloutRecorder {
                     }),
                 )
             }
-            RolloutRecorderParams::Resume { path } => (
-                tokio::fs::OpenOptions::new()
-                    .append(true)
-                    .open(&path)
-                    .await?,
-                path,
-                None,
-            ),
+            RolloutRecorderParams::Resume { path } => {
+                repair_truncated_tail(&path).await?;
+                (
+                    tokio::fs::OpenOptions::new()
+                        .append(true)
+                        .open(&path)
+                        .await?,
+                    path,
+                    None,
+                )
+            }
         };
-
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
 
@@ -246,7 +300,8 @@ impl RolloutRecorder {
         // Spawn a Tokio task that owns the file handle and performs async
         // writes. Using `tokio::fs::File` keeps everything on the async I/O
         // driver instead of blocking the runtime.
-        tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
+        let writer = JsonlWriter::new(file, config.output_limits.rollout_record_bytes);
+        tokio::task::spawn(rollout_writer(writer, rx, meta, cwd, rollout_path.clone()));
 
         Ok(Self { tx, rollout_path })
     }
@@ -283,7 +338,11 @@ impl RolloutRecorder {
 
     pub async fn get_rollout_history(path: &Path) -> std::io::Result<InitialHistory> {
         info!("Resuming rollout from {path:?}");
-        let text = tokio::fs::read_to_string(path).await?;
+        // Decode lossily: a writer killed mid-record may have cut the final
+        // line inside a multi-byte character. That line then fails to parse
+        // below and is skipped, while every complete record is kept.
+        let bytes = tokio::fs::read(path).await?;
+        let text = String::from_utf8_lossy(&bytes);
         if text.trim().is_empty() {
             return Err(IoError::other("empty session file"));
         }
@@ -383,10 +442,10 @@ struct LogFileInfo {
 }
 
 fn create_log_file(config: &Config, conversation_id: ThreadId) -> std::io::Result<LogFileInfo> {
-    // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
+    // Resolve <state_dir>/sessions/YYYY/MM/DD and create it if missing.
     let timestamp = OffsetDateTime::now_local()
         .map_err(|e| IoError::other(format!("failed to get local time: {e}")))?;
-    let mut dir = config.codex_home.clone();
+    let mut dir = config.state_dir.clone();
     dir.push(SESSIONS_SUBDIR);
     dir.push(timestamp.year().to_string());
     dir.push(format!("{:02}", u8::from(timestamp.month())));
@@ -403,10 +462,12 @@ fn create_log_file(config: &Config, conversation_id: ThreadId) -> std::io::Resul
 
     let filename = format!("rollout-{date_str}-{conversation_id}.jsonl");
 
+    // A project-local state dir may be shared by several checkouts, so never
+    // append to a rollout that another session already created.
     let path = dir.join(filename);
     let file = std::fs::OpenOptions::new()
         .append(true)
-        .create(true)
+        .create_new(true)
         .open(&path)?;
 
     Ok(LogFileInfo {
@@ -417,14 +478,144 @@ fn create_log_file(config: &Config, conversation_id: ThreadId) -> std::io::Resul
     })
 }
 
+/// Write the records still queued for every rollout that is being written,
+/// then make the files durable on disk.
+///
+/// Intended for panic hooks and other last-chance shutdown paths: it takes
+/// the queued records from the writer tasks instead of waiting for them, and
+/// never blocks on them. A record the writer is writing at that moment may
+/// land after the queued ones.
+pub fn sync_open_rollouts() {
+    let Ok(rollouts) = OPEN_ROLLOUTS.lock() else {
+        return;
+    };
+    for (path, rollout) in rollouts.iter() {
+        if let Err(e) = rollout.drain_and_sync() {
+            warn!("failed to sync rollout {}: {e}", path.display());
+        }
+    }
+}
+
+impl OpenRollout {
+    fn drain_and_sync(&self) -> std::io::Result<()> {
+        let mut acks = Vec::new();
+        if let Ok(mut rx) = self.rx.try_lock() {
+            while let Ok(cmd) = rx.try_recv() {
+                match cmd {
+                    RolloutCmd::AddItems(items) => {
+                        for item in items {
+                            if !is_persisted_response_item(&item) {
+                                continue;
+                            }
+                            if let Some(line) =
+                                encode_rollout_item(item, self.max_event_record_bytes)?
+                            {
+                                std::io::Write::write_all(&mut &self.file, line.as_bytes())?;
+                            }
+                        }
+                    }
+                    RolloutCmd::Flush { ack } | RolloutCmd::Shutdown { ack } => acks.push(ack),
+                }
+            }
+        }
+        self.file.sync_data()?;
+        for ack in acks {
+            let _ = ack.send(());
+        }
+        Ok(())
+    }
+}
+
+/// Install a panic hook that calls [`sync_open_rollouts`] before delegating to
+/// the previously installed hook.
+pub fn install_rollout_panic_hook() {
+    let prev_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        sync_open_rollouts();
+        prev_hook(info);
+    }));
+}
+
+pub(super) async fn register_open_rollout(
+    path: &Path,
+    writer: &JsonlWriter,
+    rx: Arc<Mutex<mpsc::Receiver<RolloutCmd>>>,
+) {
+    match writer.file.try_clone().await {
+        Ok(file) => {
+            let rollout = OpenRollout {
+                file: file.into_std().await,
+                rx,
+                max_event_record_bytes: writer.max_event_record_bytes,
+            };
+            if let Ok(mut rollouts) = OPEN_ROLLOUTS.lock() {
+                rollouts.insert(path.to_path_buf(), rollout);
+            }
+        }
+        Err(e) => warn!(
+            "failed to register rollout {} for sync: {e}",
+            path.display()
+        ),
+    }
+}
+
+pub(super) fn unregister_open_rollout(path: &Path) {
+    if let Ok(mut rollouts) = OPEN_ROLLOUTS.lock() {
+        rollouts.remove(path);
+    }
+}
+
+/// Drop a partial record left at the end of `path` by a writer that was
+/// killed mid-line, so records appended on resume start on a fresh line. A
+/// final record that is complete but lacks its newline is kept.
+pub(super) async fn repair_truncated_tail(path: &Path) -> std::io::Result<()> {
+    let bytes = tokio::fs::read(path).await?;
+    let complete_len = bytes
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |idx| idx + 1);
+    let tail = &bytes[complete_len..];
+    if tail.is_empty() {
+        return Ok(());
+    }
+    if serde_json::from_slice::<Value>(tail).is_ok() {
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(b"\n").await?;
+        return file.sync_data().await;
+    }
+    warn!(
+        "dropping {} bytes of truncated record at end of rollout {}",
+        tail.len(),
+        path.display()
+    );
+    let file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.set_len(complete_len as u64).await?;
+    file.sync_data().await
+}
+
 async fn rollout_writer(
-    file: tokio::fs::File,
-    mut rx: mpsc::Receiver<RolloutCmd>,
-    mut meta: Option<SessionMeta>,
+    writer: JsonlWriter,
+    rx: mpsc::Receiver<RolloutCmd>,
+    meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
+    rollout_path: PathBuf,
 ) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
+    let result =
+        run_rollout_writer(writer, Arc::new(Mutex::new(rx)), meta, cwd, &rollout_path).await;
+    unregister_open_rollout(&rollout_path);
+    result
+}
 
+async fn run_rollout_writer(
+    mut writer: JsonlWriter,
+    rx: Arc<Mutex<mpsc::Receiver<RolloutCmd>>>,
+    mut meta: Option<SessionMeta>,
+    cwd: std::path::PathBuf,
+    rollout_path: &Path,
+) -> std::io::Result<()> {
     // If we have a meta, collect git info asynchronously and write meta first
     if let Some(session_meta) = meta.take() {
         let git_info = collect_git_info(&cwd).await;
@@ -439,8 +630,12 @@ async fn rollout_writer(
             .await?;
     }
 
+    // Only now may the panic hook write queued records: before the meta is
+    // written they would end up ahead of it.
+    register_open_rollout(rollout_path, &writer, Arc::clone(&rx)).await;
+
     // Process rollout commands
-    while let Some(cmd) = rx.recv().await {
+    while let Some(cmd) = next_cmd(&rx).await {
         match cmd {
             RolloutCmd::AddItems(items) => {
                 for item in items {
@@ -450,14 +645,19 @@ async fn rollout_writer(
                 }
             }
             RolloutCmd::Flush { ack } => {
-                // Ensure underlying file is flushed and then ack.
-                if let Err(e) = writer.file.flush().await {
+                // Ensure underlying file is durably synced and then ack.
+                if let Err(e) = writer.sync().await {
                     let _ = ack.send(());
                     return Err(e);
                 }
                 let _ = ack.send(());
             }
             RolloutCmd::Shutdown { ack } => {
+                // Every record queued before the shutdown has been written by
+                // now; make them durable before acknowledging.
+                if let Err(e) = writer.sync().await {
+                    warn!("failed to sync rollout on shutdown: {e}");
+                }
                 let _ = ack.send(());
             }
         }
@@ -466,32 +666,81 @@ async fn rollout_writer(
     Ok(())
 }
 
-struct JsonlWriter {
+/// Waits for the next command. The queue is locked only while it is polled,
+/// so [`sync_open_rollouts`] can drain it whenever this task is suspended.
+async fn next_cmd(rx: &Mutex<mpsc::Receiver<RolloutCmd>>) -> Option<RolloutCmd> {
+    std::future::poll_fn(|cx| match rx.lock() {
+        Ok(mut rx) => rx.poll_recv(cx),
+        Err(_) => Poll::Ready(None),
+    })
+    .await
+}
+
+pub(super) struct JsonlWriter {
     file: tokio::fs::File,
+    /// `output_limits.rollout_record_bytes`: event records whose serialized
+    /// line is larger are left out of the rollout. Other records are always
+    /// written since resuming a session depends on them.
+    max_event_record_bytes: Option<usize>,
 }
 
 impl JsonlWriter {
-    async fn write_rollout_item(&mut self, rollout_item: RolloutItem) -> std::io::Result<()> {
-        let timestamp_format: &[FormatItem] = format_description!(
-            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-        );
-        let timestamp = OffsetDateTime::now_utc()
-            .format(timestamp_format)
-            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+    pub(super) fn new(file: tokio::fs::File, max_event_record_bytes: Option<usize>) -> Self {
+        Self {
+            file,
+            max_event_record_bytes,
+        }
+    }
 
-        let line = RolloutLine {
-            timestamp,
-            item: rollout_item,
+    pub(super) async fn write_rollout_item(
+        &mut self,
+        rollout_item: RolloutItem,
+    ) -> std::io::Result<()> {
+        let Some(json) = encode_rollout_item(rollout_item, self.max_event_record_bytes)? else {
+            return Ok(());
         };
-        self.write_line(&line).await
-    }
-    async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
-        let mut json = serde_json::to_string(item)?;
-        json.push('\n');
         self.file.write_all(json.as_bytes()).await?;
         self.file.flush().await?;
         Ok(())
     }
+
+    async fn sync(&mut self) -> std::io::Result<()> {
+        self.file.flush().await?;
+        self.file.sync_data().await
+    }
+}
+
+/// Serializes `rollout_item` as a timestamped JSONL record, newline
+/// included, so each record reaches the file in a single write. Returns
+/// `None` for an event record over `max_event_record_bytes`.
+fn encode_rollout_item(
+    rollout_item: RolloutItem,
+    max_event_record_bytes: Option<usize>,
+) -> std::io::Result<Option<String>> {
+    let timestamp_format: &[FormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+    let timestamp = OffsetDateTime::now_utc()
+        .format(timestamp_format)
+        .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+
+    let is_event = matches!(rollout_item, RolloutItem::EventMsg(_));
+    let line = RolloutLine {
+        timestamp,
+        item: rollout_item,
+    };
+    let mut json = serde_json::to_string(&line)?;
+    json.push('\n');
+    if is_event
+        && let Some(max_bytes) = max_event_record_bytes
+        && json.len() > max_bytes
+    {
+        warn!(
+            "leaving {} byte event record out of the rollout (output_limits.rollout_record_bytes = {max_bytes})",
+            json.len()
+        );
+        return Ok(None);
+    }
+    Ok(Some(json))
 }
 
 fn select_resume_path(page: &ThreadsPage, filter_cwd: Option<&Path>) -> Option<PathBuf> {
@@ -0,0 +1,140 @@
+//! Details about a recorded session for pickers that preview it before
+//! resuming: the agent's last reply and how much the session changed.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+
+use super::recorder::RolloutRecorder;
+use crate::diff_stats::DiffStats;
+use crate::protocol::EventMsg;
+use crate::tools::spec::ApplyPatchToolArgs;
+
+const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
+
+/// What a session did, read from its rollout file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ThreadSummary {
+    /// The last message the agent sent, if any.
+    pub last_agent_message: Option<String>,
+    /// Lines changed by the patches the agent applied over the session.
+    /// Patches are counted as written; one that later failed to apply still
+    /// counts.
+    pub diff_stats: DiffStats,
+}
+
+/// Reads the rollout at `path` and summarizes it.
+pub async fn read_thread_summary(path: &Path) -> io::Result<ThreadSummary> {
+    let history = RolloutRecorder::get_rollout_history(path).await?;
+    Ok(summarize_rollout_items(&history.get_rollout_items()))
+}
+
+fn summarize_rollout_items(items: &[RolloutItem]) -> ThreadSummary {
+    let mut last_agent_message = None;
+    let mut files = HashSet::new();
+    let mut diff_stats = DiffStats::default();
+    for item in items {
+        match item {
+            RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+                last_agent_message = Some(event.message.clone());
+            }
+            RolloutItem::ResponseItem(item) => {
+                if let Some(patch) = apply_patch_input(item) {
+                    count_patch(&patch, &mut files, &mut diff_stats);
+                }
+            }
+            _ => {}
+        }
+    }
+    diff_stats.files_changed = files.len();
+    ThreadSummary {
+        last_agent_message,
+        diff_stats,
+    }
+}
+
+/// The patch text of an `apply_patch` call, in either tool format.
+fn apply_patch_input(item: &ResponseItem) -> Option<String> {
+    match item {
+        ResponseItem::CustomToolCall { name, input, .. } if name == APPLY_PATCH_TOOL_NAME => {
+            Some(input.clone())
+        }
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } if name == APPLY_PATCH_TOOL_NAME => serde_json::from_str::<ApplyPatchToolArgs>(arguments)
+            .ok()
+            .map(|args| args.input),
+        _ => None,
+    }
+}
+
+fn count_patch(patch: &str, files: &mut HashSet<String>, stats: &mut DiffStats) {
+    for line in patch.lines() {
+        if let Some(path) = ["*** Add File: ", "*** Update File: ", "*** Delete File: "]
+            .iter()
+            .find_map(|header| line.strip_prefix(header))
+        {
+            files.insert(path.trim().to_string());
+        } else if line.starts_with("***") {
+            // Patch envelope and `*** Move to:` lines.
+        } else if line.starts_with('+') {
+            stats.lines_added += 1;
+        } else if line.starts_with('-') {
+            stats.lines_removed += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use pretty_assertions::assert_eq;
+
+    fn agent_message(message: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::AgentMessage(AgentMessageEvent {
+            message: message.to_string(),
+        }))
+    }
+
+    #[test]
+    fn summarizes_last_reply_and_patches_in_both_formats() {
+        let freeform =
+            "*** Begin Patch\n*** Update File: src/lib.rs\n@@\n-old\n+new\n+more\n*** End Patch";
+        let json = serde_json::json!({
+            "input": "*** Begin Patch\n*** Add File: README.md\n+hello\n*** Update File: src/lib.rs\n@@\n-gone\n*** End Patch"
+        });
+        let items = vec![
+            agent_message("first"),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call-1".to_string(),
+                name: "apply_patch".to_string(),
+                input: freeform.to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "apply_patch".to_string(),
+                arguments: json.to_string(),
+                call_id: "call-2".to_string(),
+            }),
+            agent_message("done"),
+        ];
+
+        assert_eq!(
+            summarize_rollout_items(&items),
+            ThreadSummary {
+                last_agent_message: Some("done".to_string()),
+                diff_stats: DiffStats {
+                    files_changed: 2,
+                    lines_added: 3,
+                    lines_removed: 2,
+                },
+            }
+        );
+    }
+}
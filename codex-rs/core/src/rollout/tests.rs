@@ -6,6 +6,8 @@ use std::fs::FileTimes;
 use std::fs::{self};
 use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use pretty_assertions::assert_eq;
 use tempfile::TempDir;
@@ -17,17 +19,27 @@ use time::macros::format_description;
 use uuid::Uuid;
 
 use crate::rollout::INTERACTIVE_SESSION_SOURCES;
+use crate::rollout::RolloutRecorder;
 use crate::rollout::list::Cursor;
 use crate::rollout::list::ThreadItem;
+use crate::rollout::list::ThreadProvenance;
 use crate::rollout::list::ThreadSortKey;
 use crate::rollout::list::ThreadsPage;
 use crate::rollout::list::get_threads;
+use crate::rollout::list::get_threads_across_state_dirs;
+use crate::rollout::recorder::JsonlWriter;
+use crate::rollout::recorder::RolloutCmd;
+use crate::rollout::recorder::register_open_rollout;
+use crate::rollout::recorder::repair_truncated_tail;
+use crate::rollout::recorder::unregister_open_rollout;
 use crate::rollout::rollout_date_parts;
+use crate::rollout::sync_open_rollouts;
 use anyhow::Result;
 use codex_protocol::ThreadId;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
 use codex_protocol::protocol::SessionMeta;
@@ -352,18 +364,21 @@ async fn test_list_conversations_latest_first() {
                 head: head_3,
                 created_at: Some("2025-01-03T12-00-00".into()),
                 updated_at: updated_times.first().cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
             ThreadItem {
                 path: p2,
                 head: head_2,
                 created_at: Some("2025-01-02T12-00-00".into()),
                 updated_at: updated_times.get(1).cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
             ThreadItem {
                 path: p3,
                 head: head_1,
                 created_at: Some("2025-01-01T12-00-00".into()),
                 updated_at: updated_times.get(2).cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
         ],
         next_cursor: None,
@@ -483,12 +498,14 @@ async fn test_pagination_cursor() {
                 head: head_5,
                 created_at: Some("2025-03-05T09-00-00".into()),
                 updated_at: updated_page1.first().cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
             ThreadItem {
                 path: p4,
                 head: head_4,
                 created_at: Some("2025-03-04T09-00-00".into()),
                 updated_at: updated_page1.get(1).cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
         ],
         next_cursor: Some(expected_cursor1.clone()),
@@ -551,12 +568,14 @@ async fn test_pagination_cursor() {
                 head: head_3,
                 created_at: Some("2025-03-03T09-00-00".into()),
                 updated_at: updated_page2.first().cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
             ThreadItem {
                 path: p2,
                 head: head_2,
                 created_at: Some("2025-03-02T09-00-00".into()),
                 updated_at: updated_page2.get(1).cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
         ],
         next_cursor: Some(expected_cursor2.clone()),
@@ -600,6 +619,7 @@ async fn test_pagination_cursor() {
             head: head_1,
             created_at: Some("2025-03-01T09-00-00".into()),
             updated_at: updated_page3.first().cloned().flatten(),
+            provenance: ThreadProvenance::Global,
         }],
         next_cursor: None,
         num_scanned_files: 5, // scanned 05, 04 (anchor), 03, 02 (anchor), 01
@@ -681,6 +701,7 @@ async fn test_get_thread_contents() {
             head: expected_head,
             created_at: Some(ts.into()),
             updated_at: page.items[0].updated_at.clone(),
+            provenance: ThreadProvenance::Global,
         }],
         next_cursor: None,
         num_scanned_files: 1,
@@ -886,6 +907,7 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
             images: None,
             text_elements: Vec::new(),
             local_images: Vec::new(),
+            documents: Vec::new(),
         })),
     };
     writeln!(file, "{}", serde_json::to_string(&user_event_line)?)?;
@@ -994,12 +1016,14 @@ async fn test_stable_ordering_same_second_pagination() {
                 head: head(u3),
                 created_at: Some(ts.to_string()),
                 updated_at: updated_page1.first().cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
             ThreadItem {
                 path: p2,
                 head: head(u2),
                 created_at: Some(ts.to_string()),
                 updated_at: updated_page1.get(1).cloned().flatten(),
+                provenance: ThreadProvenance::Global,
             },
         ],
         next_cursor: Some(expected_cursor1.clone()),
@@ -1033,6 +1057,7 @@ async fn test_stable_ordering_same_second_pagination() {
             head: head(u1),
             created_at: Some(ts.to_string()),
             updated_at: updated_page2.first().cloned().flatten(),
+            provenance: ThreadProvenance::Global,
         }],
         next_cursor: None,
         num_scanned_files: 3, // scanned u3, u2 (anchor), u1
@@ -1224,3 +1249,237 @@ async fn test_model_provider_filter_selects_only_matching_sessions() -> Result<(
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_list_threads_across_state_dirs_merges_and_pages() -> Result<()> {
+    let global_home = TempDir::new().unwrap();
+    let project_state = TempDir::new().unwrap();
+    let source = Some(SessionSource::VSCode);
+
+    let u1 = Uuid::from_u128(1);
+    let u2 = Uuid::from_u128(2);
+    let u3 = Uuid::from_u128(3);
+    write_session_file(global_home.path(), "2025-02-01T10-00-00", u1, 0, source)?;
+    write_session_file(project_state.path(), "2025-02-02T10-00-00", u2, 0, source)?;
+    write_session_file(global_home.path(), "2025-02-03T10-00-00", u3, 0, source)?;
+
+    let page1 = get_threads_across_state_dirs(
+        global_home.path(),
+        project_state.path(),
+        2,
+        None,
+        ThreadSortKey::CreatedAt,
+        INTERACTIVE_SESSION_SOURCES,
+        None,
+        TEST_PROVIDER,
+    )
+    .await?;
+    let listed: Vec<(String, ThreadProvenance)> = page1
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.path.file_name().unwrap().to_string_lossy().to_string(),
+                item.provenance,
+            )
+        })
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            (
+                format!("rollout-2025-02-03T10-00-00-{u3}.jsonl"),
+                ThreadProvenance::Global
+            ),
+            (
+                format!("rollout-2025-02-02T10-00-00-{u2}.jsonl"),
+                ThreadProvenance::Project
+            ),
+        ]
+    );
+
+    let page2 = get_threads_across_state_dirs(
+        global_home.path(),
+        project_state.path(),
+        2,
+        page1.next_cursor.as_ref(),
+        ThreadSortKey::CreatedAt,
+        INTERACTIVE_SESSION_SOURCES,
+        None,
+        TEST_PROVIDER,
+    )
+    .await?;
+    let listed: Vec<ThreadProvenance> = page2.items.iter().map(|item| item.provenance).collect();
+    assert_eq!(listed, vec![ThreadProvenance::Global]);
+    assert_eq!(page2.next_cursor, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_recovers_from_a_truncated_final_record() -> Result<()> {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let ts = "2025-07-01T09-00-00";
+    let uuid = Uuid::from_u128(7);
+    let day_dir = home.join("sessions").join("2025").join("07").join("01");
+    fs::create_dir_all(&day_dir)?;
+    let file_path = day_dir.join(format!("rollout-{ts}-{uuid}.jsonl"));
+    let mut file = File::create(&file_path)?;
+
+    let conversation_id = ThreadId::from_string(&uuid.to_string())?;
+    let meta_line = RolloutLine {
+        timestamp: ts.to_string(),
+        item: RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: conversation_id,
+                timestamp: ts.to_string(),
+                cwd: ".".into(),
+                originator: "test_originator".into(),
+                cli_version: "test_version".into(),
+                source: SessionSource::Cli,
+                model_provider: Some(TEST_PROVIDER.into()),
+                ..Default::default()
+            },
+            git: None,
+        }),
+    };
+    writeln!(file, "{}", serde_json::to_string(&meta_line)?)?;
+
+    let reply_line = RolloutLine {
+        timestamp: ts.to_string(),
+        item: RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "assistant".into(),
+            content: vec![ContentItem::OutputText {
+                text: "complete reply".into(),
+            }],
+            end_turn: None,
+        }),
+    };
+    writeln!(file, "{}", serde_json::to_string(&reply_line)?)?;
+
+    // End the file halfway through the next record, inside a multi-byte
+    // character and before its trailing newline, as a writer that died
+    // mid-write would leave it.
+    let partial_line = serde_json::to_string(&RolloutLine {
+        timestamp: ts.to_string(),
+        item: RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "assistant".into(),
+            content: vec![ContentItem::OutputText {
+                text: "partial reply ✓".into(),
+            }],
+            end_turn: None,
+        }),
+    })?;
+    let cut = partial_line.find('✓').expect("check mark in record") + 1;
+    file.write_all(&partial_line.as_bytes()[..cut])?;
+    drop(file);
+
+    let InitialHistory::Resumed(resumed) = RolloutRecorder::get_rollout_history(&file_path).await?
+    else {
+        panic!("expected resumed history");
+    };
+    assert_eq!(resumed.conversation_id, conversation_id);
+    assert_eq!(resumed.history.len(), 2);
+    let last = serde_json::to_value(&resumed.history[1])?;
+    assert_eq!(last, serde_json::to_value(&reply_line.item)?);
+
+    repair_truncated_tail(&file_path).await?;
+    let repaired = fs::read(&file_path)?;
+    let expected = format!(
+        "{}\n{}\n",
+        serde_json::to_string(&meta_line)?,
+        serde_json::to_string(&reply_line)?
+    );
+    assert_eq!(repaired, expected.into_bytes());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn oversized_event_records_are_left_out_of_rollout() -> Result<()> {
+    let temp = TempDir::new().unwrap();
+    let file_path = temp.path().join("rollout.jsonl");
+    let file = tokio::fs::File::create(&file_path).await?;
+    let mut writer = JsonlWriter::new(file, Some(512));
+
+    let event = |message: String| {
+        RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+            message,
+            images: None,
+            text_elements: Vec::new(),
+            local_images: Vec::new(),
+            documents: Vec::new(),
+        }))
+    };
+    let reply = |text: String| {
+        RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "assistant".into(),
+            content: vec![ContentItem::OutputText { text }],
+            end_turn: None,
+        })
+    };
+    writer.write_rollout_item(event("small".into())).await?;
+    writer.write_rollout_item(event("x".repeat(1024))).await?;
+    writer.write_rollout_item(reply("y".repeat(1024))).await?;
+    drop(writer);
+
+    let items = fs::read_to_string(&file_path)?
+        .lines()
+        .map(|line| serde_json::from_str::<RolloutLine>(line).map(|line| line.item))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        serde_json::to_value(&items)?,
+        serde_json::to_value(vec![event("small".into()), reply("y".repeat(1024))])?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn sync_open_rollouts_writes_records_still_queued() -> Result<()> {
+    let temp = TempDir::new().unwrap();
+    let file_path = temp.path().join("rollout.jsonl");
+    let file = tokio::fs::File::create(&file_path).await?;
+    let writer = JsonlWriter::new(file, None);
+    // No writer task drains this queue, as after a panic.
+    let (tx, rx) = tokio::sync::mpsc::channel(8);
+    register_open_rollout(&file_path, &writer, Arc::new(Mutex::new(rx))).await;
+
+    let reply = RolloutItem::ResponseItem(ResponseItem::Message {
+        id: None,
+        role: "assistant".into(),
+        content: vec![ContentItem::OutputText {
+            text: "queued reply".into(),
+        }],
+        end_turn: None,
+    });
+    let (ack, acked) = tokio::sync::oneshot::channel();
+    for cmd in [
+        RolloutCmd::AddItems(vec![reply.clone()]),
+        RolloutCmd::Flush { ack },
+    ] {
+        tx.send(cmd)
+            .await
+            .unwrap_or_else(|_| panic!("queue rollout command"));
+    }
+
+    sync_open_rollouts();
+    unregister_open_rollout(&file_path);
+
+    acked.await?;
+    let items = fs::read_to_string(&file_path)?
+        .lines()
+        .map(|line| serde_json::from_str::<RolloutLine>(line).map(|line| line.item))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        serde_json::to_value(&items)?,
+        serde_json::to_value(vec![reply])?
+    );
+
+    Ok(())
+}
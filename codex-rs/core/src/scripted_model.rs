@@ -0,0 +1,461 @@
+//! A model provider that answers from a script, without leaving the process.
+//!
+//! Tests build a [`ScriptedModel`] from a list of [`ScriptedTurn`]s and point
+//! `config.model_provider` at [`ScriptedModel::provider`]. Each streaming
+//! request to the provider takes the next turn and receives it as the same
+//! Responses API server-sent events a real server sends, so the client's SSE
+//! parsing, retries and usage reporting all run as they do in production.
+//! Turns can also fail with an HTTP status, be rate limited, or cut the
+//! stream off partway.
+//!
+//! The provider is the built-in OpenAI one with its base URL replaced, so
+//! sessions need API-key auth such as `CodexAuth::from_api_key`.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::sync::Weak;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use async_trait::async_trait;
+use codex_api::TransportError;
+use codex_client::ByteStream;
+use codex_client::HttpTransport;
+use codex_client::Request;
+use codex_client::Response;
+use codex_client::StreamResponse;
+use futures::StreamExt;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::StatusCode;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::built_in_model_providers;
+
+/// Scripted models by base URL, for [`scripted_transport`] to find.
+static SCRIPTED_MODELS: LazyLock<Mutex<HashMap<String, Weak<Script>>>> =
+    LazyLock::new(Mutex::default);
+
+static NEXT_MODEL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A scripted model provider. See the module docs.
+///
+/// Clones share the script. The provider stops answering once every clone
+/// is dropped.
+#[derive(Clone)]
+pub struct ScriptedModel {
+    script: Arc<Script>,
+}
+
+struct Script {
+    base_url: String,
+    turns: Mutex<VecDeque<ScriptedTurn>>,
+    requests: Mutex<Vec<Value>>,
+    next_response: AtomicU64,
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        lock(&SCRIPTED_MODELS).remove(&self.base_url);
+    }
+}
+
+impl ScriptedModel {
+    pub fn new(turns: impl IntoIterator<Item = ScriptedTurn>) -> Self {
+        let id = NEXT_MODEL_ID.fetch_add(1, Ordering::Relaxed);
+        let script = Arc::new(Script {
+            base_url: format!("http://scripted-model-{id}.invalid/v1"),
+            turns: Mutex::new(turns.into_iter().collect()),
+            requests: Mutex::default(),
+            next_response: AtomicU64::new(1),
+        });
+        lock(&SCRIPTED_MODELS).insert(script.base_url.clone(), Arc::downgrade(&script));
+        Self { script }
+    }
+
+    /// The provider to put in `config.model_provider`.
+    pub fn provider(&self) -> ModelProviderInfo {
+        ModelProviderInfo {
+            base_url: Some(self.script.base_url.clone()),
+            ..built_in_model_providers()["openai"].clone()
+        }
+    }
+
+    /// Appends `turn` to the script.
+    pub fn push_turn(&self, turn: ScriptedTurn) {
+        lock(&self.script.turns).push_back(turn);
+    }
+
+    /// Turns not yet answered.
+    pub fn remaining_turns(&self) -> usize {
+        lock(&self.script.turns).len()
+    }
+
+    /// Bodies of the streaming requests received so far, in order.
+    pub fn requests(&self) -> Vec<Value> {
+        lock(&self.script.requests).clone()
+    }
+}
+
+/// One response of a [`ScriptedModel`].
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedTurn {
+    /// `response.output_item.*` and delta events, in order.
+    events: Vec<Value>,
+    /// Raw server-sent events sent instead of `events`.
+    sse: Option<String>,
+    input_tokens: i64,
+    output_tokens: i64,
+    /// Number of events sent before the stream is cut off.
+    disconnect_after: Option<usize>,
+    error: Option<ScriptedError>,
+}
+
+#[derive(Debug, Clone)]
+struct ScriptedError {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: String,
+}
+
+impl ScriptedTurn {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A turn that sends `sse` verbatim, for fixtures written as raw
+    /// server-sent events.
+    pub fn sse(sse: impl Into<String>) -> Self {
+        Self {
+            sse: Some(sse.into()),
+            ..Self::default()
+        }
+    }
+
+    /// A turn whose request fails with `status` and `body`.
+    pub fn http_error(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            error: Some(ScriptedError {
+                status,
+                headers: HeaderMap::new(),
+                body: body.into(),
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// A turn whose request is refused with `429 Too Many Requests`, asking
+    /// the client to retry after `retry_after_secs`.
+    pub fn rate_limited(retry_after_secs: u64) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::RETRY_AFTER,
+            HeaderValue::from(retry_after_secs),
+        );
+        let body = json!({
+            "error": {
+                "type": "rate_limit_exceeded",
+                "message": format!("Rate limit reached. Please try again in {retry_after_secs}s."),
+            }
+        });
+        Self {
+            error: Some(ScriptedError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                headers,
+                body: body.to_string(),
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// An assistant message sent as one delta.
+    pub fn message(self, text: &str) -> Self {
+        self.message_deltas(&[text])
+    }
+
+    /// An assistant message streamed as `deltas`.
+    pub fn message_deltas(mut self, deltas: &[&str]) -> Self {
+        let id = format!("msg-{}", self.events.len());
+        self.events.push(json!({
+            "type": "response.output_item.added",
+            "item": {
+                "type": "message",
+                "role": "assistant",
+                "id": id,
+                "content": [{"type": "output_text", "text": ""}],
+            }
+        }));
+        for delta in deltas {
+            self.events.push(json!({
+                "type": "response.output_text.delta",
+                "delta": delta,
+            }));
+        }
+        self.events.push(json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "message",
+                "role": "assistant",
+                "id": id,
+                "content": [{"type": "output_text", "text": deltas.concat()}],
+            }
+        }));
+        self
+    }
+
+    /// A call of function tool `name` with `arguments`.
+    pub fn function_call(self, call_id: &str, name: &str, arguments: &Value) -> Self {
+        self.event(json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "function_call",
+                "call_id": call_id,
+                "name": name,
+                "arguments": arguments.to_string(),
+            }
+        }))
+    }
+
+    /// A call of freeform tool `name` with `input`.
+    pub fn custom_tool_call(self, call_id: &str, name: &str, input: &str) -> Self {
+        self.event(json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "custom_tool_call",
+                "call_id": call_id,
+                "name": name,
+                "input": input,
+            }
+        }))
+    }
+
+    /// Any other Responses API event, such as a reasoning item.
+    pub fn event(mut self, event: Value) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Token usage reported when the response completes.
+    pub fn usage(mut self, input_tokens: i64, output_tokens: i64) -> Self {
+        self.input_tokens = input_tokens;
+        self.output_tokens = output_tokens;
+        self
+    }
+
+    /// Cuts the stream off after its first `events` events, counting
+    /// `response.created`, so the response never completes.
+    pub fn disconnect_after(mut self, events: usize) -> Self {
+        self.disconnect_after = Some(events);
+        self
+    }
+
+    /// The turn as server-sent events for response `response_id`.
+    fn to_sse(&self, response_id: &str) -> Vec<String> {
+        if let Some(sse) = &self.sse {
+            return vec![sse.clone()];
+        }
+        let created = json!({
+            "type": "response.created",
+            "response": { "id": response_id },
+        });
+        let completed = json!({
+            "type": "response.completed",
+            "response": {
+                "id": response_id,
+                "usage": {
+                    "input_tokens": self.input_tokens,
+                    "input_tokens_details": null,
+                    "output_tokens": self.output_tokens,
+                    "output_tokens_details": null,
+                    "total_tokens": self.input_tokens + self.output_tokens,
+                },
+            },
+        });
+        std::iter::once(&created)
+            .chain(&self.events)
+            .chain(std::iter::once(&completed))
+            .take(self.disconnect_after.unwrap_or(usize::MAX))
+            .map(|event| {
+                let kind = event["type"].as_str().unwrap_or_default();
+                format!("event: {kind}\ndata: {event}\n\n")
+            })
+            .collect()
+    }
+}
+
+/// Answers requests for a [`ScriptedModel`]'s provider.
+#[derive(Clone)]
+pub(crate) struct ScriptedTransport {
+    script: Arc<Script>,
+}
+
+/// The transport of the scripted model `provider` points at, if any.
+pub(crate) fn scripted_transport(provider: &ModelProviderInfo) -> Option<ScriptedTransport> {
+    let base_url = provider.base_url.as_deref()?;
+    let script = lock(&SCRIPTED_MODELS).get(base_url)?.upgrade()?;
+    Some(ScriptedTransport { script })
+}
+
+#[async_trait]
+impl HttpTransport for ScriptedTransport {
+    async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+        if req.url.ends_with("/models") || req.url.contains("/models?") {
+            return Ok(Response {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: json!({ "models": [] }).to_string().into_bytes().into(),
+            });
+        }
+        Err(TransportError::Http {
+            status: StatusCode::NOT_FOUND,
+            url: Some(req.url),
+            headers: None,
+            body: Some("the scripted model only streams responses".to_string()),
+        })
+    }
+
+    async fn stream(&self, req: Request) -> Result<StreamResponse, TransportError> {
+        lock(&self.script.requests).push(req.body.clone().unwrap_or_default());
+        let Some(turn) = lock(&self.script.turns).pop_front() else {
+            return Err(TransportError::Http {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                url: Some(req.url),
+                headers: None,
+                body: Some("the scripted model has no turns left".to_string()),
+            });
+        };
+        if let Some(error) = turn.error {
+            return Err(TransportError::Http {
+                status: error.status,
+                url: Some(req.url),
+                headers: Some(error.headers),
+                body: Some(error.body),
+            });
+        }
+
+        let response = self.script.next_response.fetch_add(1, Ordering::Relaxed);
+        let chunks = turn.to_sse(&format!("resp-{response}"));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("text/event-stream"),
+        );
+        let bytes: ByteStream = futures::stream::iter(chunks)
+            .map(|chunk| Ok(chunk.into_bytes().into()))
+            .boxed();
+        Ok(StreamResponse {
+            status: StatusCode::OK,
+            headers,
+            bytes,
+        })
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Method;
+    use pretty_assertions::assert_eq;
+
+    async fn streamed_events(transport: &ScriptedTransport) -> Vec<Value> {
+        let request = Request::new(Method::POST, "http://scripted/v1/responses".to_string());
+        let response = transport.stream(request).await.expect("stream");
+        let chunks: Vec<_> = response.bytes.collect().await;
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let chunk = chunk.expect("chunk");
+                let text = String::from_utf8(chunk.to_vec()).expect("utf-8");
+                let data = text
+                    .lines()
+                    .find_map(|line| line.strip_prefix("data: "))
+                    .expect("data line");
+                serde_json::from_str(data).expect("json event")
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn turns_are_streamed_in_order_and_then_run_out() {
+        let model = ScriptedModel::new([
+            ScriptedTurn::new()
+                .function_call("call-1", "shell", &json!({"command": ["ls"]}))
+                .usage(10, 2),
+            ScriptedTurn::new().message_deltas(&["Hel", "lo"]),
+        ]);
+        let transport = scripted_transport(&model.provider()).expect("registered");
+
+        let first = streamed_events(&transport).await;
+        let kinds: Vec<&str> = first
+            .iter()
+            .map(|event| event["type"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "response.created",
+                "response.output_item.done",
+                "response.completed"
+            ]
+        );
+        assert_eq!(first[2]["response"]["usage"]["total_tokens"], json!(12));
+
+        let second = streamed_events(&transport).await;
+        assert_eq!(second[4]["item"]["content"][0]["text"], json!("Hello"));
+        assert_eq!(model.remaining_turns(), 0);
+        assert_eq!(model.requests().len(), 2);
+
+        let request = Request::new(Method::POST, "http://scripted/v1/responses".to_string());
+        assert!(matches!(
+            transport.stream(request).await,
+            Err(TransportError::Http { status, .. }) if status == StatusCode::INTERNAL_SERVER_ERROR
+        ));
+    }
+
+    #[tokio::test]
+    async fn disconnects_and_errors_are_injected() {
+        let model = ScriptedModel::new([
+            ScriptedTurn::new().message("partial").disconnect_after(2),
+            ScriptedTurn::rate_limited(3),
+        ]);
+        let transport = scripted_transport(&model.provider()).expect("registered");
+
+        let cut = streamed_events(&transport).await;
+        assert_eq!(cut.len(), 2);
+        assert_eq!(cut[1]["type"], json!("response.output_item.added"));
+
+        let request = Request::new(Method::POST, "http://scripted/v1/responses".to_string());
+        let Err(TransportError::Http {
+            status, headers, ..
+        }) = transport.stream(request).await
+        else {
+            panic!("expected an HTTP error");
+        };
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            headers.and_then(|headers| headers.get(http::header::RETRY_AFTER).cloned()),
+            Some(HeaderValue::from(3u64))
+        );
+    }
+
+    #[test]
+    fn dropping_the_model_unregisters_its_provider() {
+        let model = ScriptedModel::new([]);
+        let provider = model.provider();
+        assert!(scripted_transport(&provider).is_some());
+
+        drop(model);
+        assert!(scripted_transport(&provider).is_none());
+    }
+}
@@ -0,0 +1,385 @@
+//! Per-session temporary directory.
+//!
+//! Every session gets `<system temp dir>/codex/<thread id>` when it starts.
+//! Both directories are private to the current user, and a `codex` directory
+//! that another user created first is refused rather than shared.
+//! Commands see it as `CODEX_TMPDIR`, a workspace-write sandbox makes it
+//! writable, and it holds the session's spilled tool output and background
+//! job logs. A clean shutdown removes it. A running session holds a lock on
+//! `.lock` in its directory. A session that crashes leaves its directory
+//! behind; the next session to start sweeps unlocked directories whose
+//! session, going by the directory and the rollout it names, was last written
+//! more than `tmpdir_max_age_hours` ago. A resumed session picks up its
+//! directory again.
+
+use std::fs::File;
+use std::fs::TryLockError;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_utils_absolute_path::AbsolutePathBuf;
+use tracing::warn;
+
+/// Environment variable holding the directory in every command a session runs.
+pub(crate) const ENV_VAR: &str = "CODEX_TMPDIR";
+
+/// Default for `tmpdir_max_age_hours`.
+pub(crate) const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// File in a session's directory naming the rollout of that session.
+const ROLLOUT_MARKER: &str = ".rollout";
+
+/// File in a session's directory that the session keeps locked while it runs.
+const LOCK_FILE: &str = ".lock";
+
+const JOB_LOG_DIR: &str = "background_jobs";
+
+#[derive(Debug, Clone)]
+pub(crate) struct SessionTmpdir {
+    path: AbsolutePathBuf,
+    /// The locked [`LOCK_FILE`], released by [`Self::remove`] or when the
+    /// last clone is dropped.
+    lock: Arc<Mutex<Option<File>>>,
+}
+
+/// Directory under which every session's directory is created.
+pub(crate) fn root() -> PathBuf {
+    std::env::temp_dir().join("codex")
+}
+
+impl SessionTmpdir {
+    /// Creates the directory of `thread_id` under `root`, or reuses it when a
+    /// resumed session left one behind. `rollout_path` is recorded so the
+    /// sweep keeps the directory while the rollout is recent enough to be
+    /// resumed.
+    pub(crate) async fn create(
+        root: &Path,
+        thread_id: ThreadId,
+        rollout_path: Option<&Path>,
+    ) -> std::io::Result<Self> {
+        prepare_root(root).await?;
+        let path = AbsolutePathBuf::from_absolute_path(root.join(thread_id.to_string()))?;
+        create_private_dir(&path).await?;
+        if let Some(rollout_path) = rollout_path {
+            tokio::fs::write(
+                path.as_path().join(ROLLOUT_MARKER),
+                rollout_path.to_string_lossy().as_bytes(),
+            )
+            .await?;
+        }
+        let lock = lock_dir(path.as_path()).await?;
+        Ok(Self {
+            path,
+            lock: Arc::new(Mutex::new(Some(lock))),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &AbsolutePathBuf {
+        &self.path
+    }
+
+    /// Saves the full output of background job `id` and returns the file, or
+    /// `None` when it could not be written.
+    pub(crate) async fn write_job_log(&self, id: &str, output: &str) -> Option<PathBuf> {
+        let path = self
+            .path
+            .as_path()
+            .join(JOB_LOG_DIR)
+            .join(format!("{id}.log"));
+        let written = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, output).await
+        }
+        .await;
+        match written {
+            Ok(()) => Some(path),
+            Err(err) => {
+                warn!("failed to save log of {id} to {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Removes the directory and everything in it.
+    pub(crate) async fn remove(&self) {
+        // Windows cannot delete a file that is still open.
+        if let Ok(mut lock) = self.lock.lock() {
+            lock.take();
+        }
+        if let Err(err) = tokio::fs::remove_dir_all(&self.path).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!(
+                "failed to remove session temp dir {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Creates `root` if needed and makes sure only the current user can get
+/// into it. The system temp dir is shared, so a `codex` directory someone
+/// else made there first, or a symlink in its place, is an error.
+async fn prepare_root(root: &Path) -> std::io::Result<()> {
+    create_private_dir(root).await?;
+    let metadata = tokio::fs::symlink_metadata(root).await?;
+    check_owned(root, &metadata)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if metadata.permissions().mode() & 0o077 != 0 {
+            tokio::fs::set_permissions(root, std::fs::Permissions::from_mode(0o700)).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    let mut builder = tokio::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(path).await
+}
+
+/// Opens and locks the [`LOCK_FILE`] of `dir`. Another session holding the
+/// lock, one that resumed the same thread, keeps the directory alive just as
+/// well, so that is not an error.
+async fn lock_dir(dir: &Path) -> std::io::Result<File> {
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))
+        .await?
+        .into_std()
+        .await;
+    match file.try_lock() {
+        Ok(()) | Err(TryLockError::WouldBlock) => Ok(file),
+        Err(TryLockError::Error(err)) => Err(err),
+    }
+}
+
+/// Fails unless `root` is a real directory owned by the current user.
+fn check_owned(root: &Path, metadata: &std::fs::Metadata) -> std::io::Result<()> {
+    if !metadata.is_dir() {
+        return Err(std::io::Error::other(format!(
+            "{} is not a directory",
+            root.display()
+        )));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // SAFETY: geteuid has no preconditions and cannot fail.
+        let uid = unsafe { libc::geteuid() };
+        if metadata.uid() != uid {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is owned by another user", root.display()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Makes `tmpdir` writable under a workspace-write `policy`. Other policies
+/// are left as they are.
+pub(crate) fn extend_writable_roots(policy: &mut SandboxPolicy, tmpdir: Option<&SessionTmpdir>) {
+    if let (SandboxPolicy::WorkspaceWrite { writable_roots, .. }, Some(tmpdir)) = (policy, tmpdir)
+        && !writable_roots.contains(&tmpdir.path)
+    {
+        writable_roots.push(tmpdir.path.clone());
+    }
+}
+
+/// Removes the directories under `root` that no running session holds and
+/// whose session was last written more than `max_age` ago. Returns the
+/// removed directories.
+pub(crate) fn sweep(root: &Path, max_age: Duration) -> Vec<PathBuf> {
+    sweep_at(root, max_age, SystemTime::now())
+}
+
+fn sweep_at(root: &Path, max_age: Duration, now: SystemTime) -> Vec<PathBuf> {
+    if !std::fs::symlink_metadata(root).is_ok_and(|metadata| check_owned(root, &metadata).is_ok()) {
+        return Vec::new();
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut removed = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_dir() || is_locked(&path) {
+            continue;
+        }
+        let age = last_write(&path, &metadata)
+            .and_then(|written| now.duration_since(written).ok())
+            .unwrap_or_default();
+        if age < max_age {
+            continue;
+        }
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => removed.push(path),
+            Err(err) => warn!(
+                "failed to remove orphaned session temp dir {}: {err}",
+                path.display()
+            ),
+        }
+    }
+    removed
+}
+
+/// Whether a running session holds the lock of `dir`.
+fn is_locked(dir: &Path) -> bool {
+    File::open(dir.join(LOCK_FILE))
+        .is_ok_and(|file| matches!(file.try_lock(), Err(TryLockError::WouldBlock)))
+}
+
+/// When the session of `dir` last wrote anything we can see: the later of the
+/// directory's own mtime and that of the rollout it names.
+fn last_write(dir: &Path, metadata: &std::fs::Metadata) -> Option<SystemTime> {
+    let rollout_written = std::fs::read_to_string(dir.join(ROLLOUT_MARKER))
+        .ok()
+        .and_then(|rollout_path| std::fs::metadata(rollout_path.trim()).ok())
+        .and_then(|rollout| rollout.modified().ok());
+    metadata.modified().ok().max(rollout_written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn remove_deletes_the_directory_and_its_contents() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let tmpdir = SessionTmpdir::create(root.path(), ThreadId::new(), None)
+            .await
+            .expect("create tmpdir");
+        let log = tmpdir
+            .write_job_log("job-1", "built")
+            .await
+            .expect("job log");
+        assert_eq!(std::fs::read_to_string(&log).expect("read log"), "built");
+
+        tmpdir.remove().await;
+        assert!(!tmpdir.path().as_path().exists());
+    }
+
+    #[tokio::test]
+    async fn sweep_keeps_live_and_recently_written_sessions() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let create = |rollout: Option<PathBuf>| {
+            let root = root.path().to_path_buf();
+            async move {
+                SessionTmpdir::create(&root, ThreadId::new(), rollout.as_deref())
+                    .await
+                    .expect("create tmpdir")
+            }
+        };
+        // Sweep two hours from now, so every directory is old by its own
+        // mtime and only a lock or a recent rollout write keeps it.
+        let now = SystemTime::now() + Duration::from_secs(2 * 60 * 60);
+        let max_age = Duration::from_secs(60 * 60);
+
+        let written = root.path().join("written.jsonl");
+        std::fs::write(&written, "").expect("write rollout");
+        File::options()
+            .write(true)
+            .open(&written)
+            .expect("open rollout")
+            .set_modified(now - Duration::from_secs(10 * 60))
+            .expect("touch rollout");
+        let stale = root.path().join("stale.jsonl");
+        std::fs::write(&stale, "").expect("write rollout");
+
+        let live = create(None).await;
+        let crashed_recently = create(Some(written.clone())).await.path().clone();
+        let crashed_long_ago = create(Some(stale.clone())).await.path().clone();
+        let finished = create(Some(root.path().join("gone.jsonl")))
+            .await
+            .path()
+            .clone();
+        let ephemeral = create(None).await.path().clone();
+
+        assert_eq!(
+            sweep_at(root.path(), max_age, SystemTime::now()),
+            Vec::new()
+        );
+
+        let mut removed = sweep_at(root.path(), max_age, now);
+        removed.sort();
+        let mut expected = vec![
+            crashed_long_ago.to_path_buf(),
+            finished.to_path_buf(),
+            ephemeral.to_path_buf(),
+        ];
+        expected.sort();
+        assert_eq!(removed, expected);
+        assert!(live.path().as_path().is_dir());
+        assert!(crashed_recently.as_path().is_dir());
+        assert!(written.is_file() && stale.is_file());
+
+        live.remove().await;
+        assert!(!live.path().as_path().exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn directories_are_private_to_the_current_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let parent = tempfile::tempdir().expect("tempdir");
+        let root = parent.path().join("codex");
+        std::fs::create_dir(&root).expect("create root");
+        std::fs::set_permissions(&root, std::fs::Permissions::from_mode(0o777))
+            .expect("loosen root");
+
+        let tmpdir = SessionTmpdir::create(&root, ThreadId::new(), None)
+            .await
+            .expect("create tmpdir");
+
+        let mode = |path: &Path| {
+            std::fs::metadata(path)
+                .expect("metadata")
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode(&root), 0o700);
+        assert_eq!(mode(tmpdir.path().as_path()), 0o700);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinked_root_is_refused() {
+        let parent = tempfile::tempdir().expect("tempdir");
+        let elsewhere = parent.path().join("elsewhere");
+        std::fs::create_dir(&elsewhere).expect("create target");
+        let root = parent.path().join("codex");
+        std::os::unix::fs::symlink(&elsewhere, &root).expect("symlink root");
+
+        let result = SessionTmpdir::create(&root, ThreadId::new(), None).await;
+
+        assert!(result.is_err());
+        assert_eq!(sweep(&root, Duration::ZERO), Vec::new());
+        assert_eq!(
+            std::fs::read_dir(&elsewhere).expect("read target").count(),
+            0
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::config::types::ShellKind;
+use crate::config::types::ShellToml;
 use crate::shell_snapshot::ShellSnapshot;
 use serde::Deserialize;
 use serde::Serialize;
@@ -12,8 +14,15 @@ pub enum ShellType {
     PowerShell,
     Sh,
     Cmd,
+    Fish,
+    /// User-provided argv template from `[shell]` with `kind = "custom"`.
+    Custom,
 }
 
+/// Placeholder in a custom shell's argv template that is replaced with the
+/// script.
+const COMMAND_PLACEHOLDER: &str = "{command}";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Shell {
     pub(crate) shell_type: ShellType,
@@ -24,16 +33,35 @@ pub struct Shell {
         default = "empty_shell_snapshot_receiver"
     )]
     pub(crate) shell_snapshot: watch::Receiver<Option<Arc<ShellSnapshot>>>,
+    /// Argv template for [`ShellType::Custom`]; `None` for every other type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) argv_template: Option<Vec<String>>,
 }
 
 impl Shell {
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self.shell_type {
             ShellType::Zsh => "zsh",
             ShellType::Bash => "bash",
             ShellType::PowerShell => "powershell",
             ShellType::Sh => "sh",
             ShellType::Cmd => "cmd",
+            ShellType::Fish => "fish",
+            ShellType::Custom => self
+                .shell_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("custom"),
+        }
+    }
+
+    /// Human-readable description of how scripts are wrapped: the shell
+    /// binary, or the argv template for a custom shell.
+    pub fn describe(&self) -> String {
+        match &self.argv_template {
+            Some(template) => shlex::try_join(template.iter().map(String::as_str))
+                .unwrap_or_else(|_| template.join(" ")),
+            None => self.shell_path.display().to_string(),
         }
     }
 
@@ -41,7 +69,7 @@ impl Shell {
     /// use with `exec()` to run the shell command.
     pub fn derive_exec_args(&self, command: &str, use_login_shell: bool) -> Vec<String> {
         match self.shell_type {
-            ShellType::Zsh | ShellType::Bash | ShellType::Sh => {
+            ShellType::Zsh | ShellType::Bash | ShellType::Sh | ShellType::Fish => {
                 let arg = if use_login_shell { "-lc" } else { "-c" };
                 vec![
                     self.shell_path.to_string_lossy().to_string(),
@@ -65,7 +93,73 @@ impl Shell {
                 args.push(command.to_string());
                 args
             }
+            // The template decides whether the shell is a login shell.
+            ShellType::Custom => {
+                let template = self.argv_template.as_deref().unwrap_or_default();
+                let mut args: Vec<String> = template
+                    .iter()
+                    .map(|arg| {
+                        if arg == COMMAND_PLACEHOLDER {
+                            command.to_string()
+                        } else {
+                            arg.clone()
+                        }
+                    })
+                    .collect();
+                if !template.iter().any(|arg| arg == COMMAND_PLACEHOLDER) {
+                    args.push(command.to_string());
+                }
+                args
+            }
+        }
+    }
+
+    /// Rewrites a command produced by [`Shell::derive_exec_args`] for a
+    /// custom shell into the `[shell, "-c", script]` (or PowerShell
+    /// `-Command`) form that `parse_command` and the command safety checks
+    /// understand. This only happens when the template's program is a shell
+    /// those checks know; every other command is returned unchanged.
+    pub fn canonical_exec_args(&self, command: &[String]) -> Vec<String> {
+        self.extract_custom_script(command)
+            .and_then(
+                |(program, script)| match detect_shell_type(&PathBuf::from(program))? {
+                    ShellType::Zsh | ShellType::Bash | ShellType::Sh | ShellType::Fish => {
+                        Some(vec![
+                            program.to_string(),
+                            "-c".to_string(),
+                            script.to_string(),
+                        ])
+                    }
+                    ShellType::PowerShell => Some(vec![
+                        program.to_string(),
+                        "-Command".to_string(),
+                        script.to_string(),
+                    ]),
+                    ShellType::Cmd | ShellType::Custom => None,
+                },
+            )
+            .unwrap_or_else(|| command.to_vec())
+    }
+
+    /// Returns the template's program and the script when `command` matches
+    /// this shell's custom argv template.
+    fn extract_custom_script<'a>(&'a self, command: &'a [String]) -> Option<(&'a str, &'a str)> {
+        let template = self.argv_template.as_deref()?;
+        let program = template.first()?;
+        let script_index = template
+            .iter()
+            .position(|arg| arg == COMMAND_PLACEHOLDER)
+            .unwrap_or(template.len());
+        let expected_len = template.len().max(script_index + 1);
+        if command.len() != expected_len {
+            return None;
         }
+        let fixed_args_match = template
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != script_index)
+            .all(|(index, arg)| command[index] == *arg);
+        fixed_args_match.then_some((program.as_str(), command[script_index].as_str()))
     }
 
     /// Return the shell snapshot if existing.
@@ -81,7 +175,9 @@ pub(crate) fn empty_shell_snapshot_receiver() -> watch::Receiver<Option<Arc<Shel
 
 impl PartialEq for Shell {
     fn eq(&self, other: &Self) -> bool {
-        self.shell_type == other.shell_type && self.shell_path == other.shell_path
+        self.shell_type == other.shell_type
+            && self.shell_path == other.shell_path
+            && self.argv_template == other.argv_template
     }
 }
 
@@ -162,6 +258,7 @@ fn get_zsh_shell(path: Option<&PathBuf>) -> Option<Shell> {
         shell_type: ShellType::Zsh,
         shell_path,
         shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
     })
 }
 
@@ -172,6 +269,7 @@ fn get_bash_shell(path: Option<&PathBuf>) -> Option<Shell> {
         shell_type: ShellType::Bash,
         shell_path,
         shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
     })
 }
 
@@ -182,6 +280,7 @@ fn get_sh_shell(path: Option<&PathBuf>) -> Option<Shell> {
         shell_type: ShellType::Sh,
         shell_path,
         shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
     })
 }
 
@@ -198,6 +297,18 @@ fn get_powershell_shell(path: Option<&PathBuf>) -> Option<Shell> {
         shell_type: ShellType::PowerShell,
         shell_path,
         shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
+    })
+}
+
+fn get_fish_shell(path: Option<&PathBuf>) -> Option<Shell> {
+    let shell_path = get_shell_path(ShellType::Fish, path, "fish", vec!["/usr/bin/fish"]);
+
+    shell_path.map(|shell_path| Shell {
+        shell_type: ShellType::Fish,
+        shell_path,
+        shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
     })
 }
 
@@ -208,6 +319,7 @@ fn get_cmd_shell(path: Option<&PathBuf>) -> Option<Shell> {
         shell_type: ShellType::Cmd,
         shell_path,
         shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: None,
     })
 }
 
@@ -217,12 +329,14 @@ fn ultimate_fallback_shell() -> Shell {
             shell_type: ShellType::Cmd,
             shell_path: PathBuf::from("cmd.exe"),
             shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
         }
     } else {
         Shell {
             shell_type: ShellType::Sh,
             shell_path: PathBuf::from("/bin/sh"),
             shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
         }
     }
 }
@@ -240,6 +354,9 @@ pub fn get_shell(shell_type: ShellType, path: Option<&PathBuf>) -> Option<Shell>
         ShellType::PowerShell => get_powershell_shell(path),
         ShellType::Sh => get_sh_shell(path),
         ShellType::Cmd => get_cmd_shell(path),
+        ShellType::Fish => get_fish_shell(path),
+        // A custom shell only exists with the argv template from config.
+        ShellType::Custom => None,
     }
 }
 
@@ -249,6 +366,7 @@ pub fn detect_shell_type(shell_path: &PathBuf) -> Option<ShellType> {
         Some("sh") => Some(ShellType::Sh),
         Some("cmd") => Some(ShellType::Cmd),
         Some("bash") => Some(ShellType::Bash),
+        Some("fish") => Some(ShellType::Fish),
         Some("pwsh") => Some(ShellType::PowerShell),
         Some("powershell") => Some(ShellType::PowerShell),
         _ => {
@@ -268,6 +386,50 @@ pub fn default_user_shell() -> Shell {
     default_user_shell_from_path(get_user_shell_path())
 }
 
+/// Resolves the shell from the `[shell]` config table. Falls back to the
+/// user's login shell when the table is unset or the requested shell cannot
+/// be found.
+pub fn user_shell_from_config(config: Option<&ShellToml>) -> Shell {
+    let Some(config) = config else {
+        return default_user_shell();
+    };
+    let path = config.path.as_ref().map(|path| path.to_path_buf());
+    let shell_type = match config.kind {
+        None => {
+            return path
+                .as_ref()
+                .map(get_shell_by_model_provided_path)
+                .unwrap_or_else(default_user_shell);
+        }
+        Some(ShellKind::Custom) => {
+            if let Some(shell) = config.argv.as_deref().and_then(custom_shell) {
+                return shell;
+            }
+            tracing::warn!("shell.kind = \"custom\" requires a non-empty shell.argv");
+            return default_user_shell();
+        }
+        Some(ShellKind::Bash) => ShellType::Bash,
+        Some(ShellKind::Zsh) => ShellType::Zsh,
+        Some(ShellKind::Fish) => ShellType::Fish,
+        Some(ShellKind::Sh) => ShellType::Sh,
+        Some(ShellKind::Powershell) => ShellType::PowerShell,
+    };
+    get_shell(shell_type.clone(), path.as_ref()).unwrap_or_else(|| {
+        tracing::warn!("configured shell {shell_type:?} was not found; using the login shell");
+        default_user_shell()
+    })
+}
+
+fn custom_shell(argv: &[String]) -> Option<Shell> {
+    let program = argv.first()?;
+    Some(Shell {
+        shell_type: ShellType::Custom,
+        shell_path: PathBuf::from(program),
+        shell_snapshot: empty_shell_snapshot_receiver(),
+        argv_template: Some(argv.to_vec()),
+    })
+}
+
 fn default_user_shell_from_path(user_shell_path: Option<PathBuf>) -> Shell {
     if cfg!(windows) {
         get_shell(ShellType::PowerShell, None).unwrap_or(ultimate_fallback_shell())
@@ -312,7 +474,10 @@ mod detect_shell_type_tests {
             detect_shell_type(&PathBuf::from("powershell")),
             Some(ShellType::PowerShell)
         );
-        assert_eq!(detect_shell_type(&PathBuf::from("fish")), None);
+        assert_eq!(
+            detect_shell_type(&PathBuf::from("fish")),
+            Some(ShellType::Fish)
+        );
         assert_eq!(detect_shell_type(&PathBuf::from("other")), None);
         assert_eq!(
             detect_shell_type(&PathBuf::from("/bin/zsh")),
@@ -449,6 +614,7 @@ mod tests {
             shell_type: ShellType::Bash,
             shell_path: PathBuf::from("/bin/bash"),
             shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
         assert_eq!(
             test_bash_shell.derive_exec_args("echo hello", false),
@@ -463,6 +629,7 @@ mod tests {
             shell_type: ShellType::Zsh,
             shell_path: PathBuf::from("/bin/zsh"),
             shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
         assert_eq!(
             test_zsh_shell.derive_exec_args("echo hello", false),
@@ -477,6 +644,7 @@ mod tests {
             shell_type: ShellType::PowerShell,
             shell_path: PathBuf::from("pwsh.exe"),
             shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
         assert_eq!(
             test_powershell_shell.derive_exec_args("echo hello", false),
@@ -486,6 +654,42 @@ mod tests {
             test_powershell_shell.derive_exec_args("echo hello", true),
             vec!["pwsh.exe", "-Command", "echo hello"]
         );
+
+        let test_fish_shell = Shell {
+            shell_type: ShellType::Fish,
+            shell_path: PathBuf::from("/usr/bin/fish"),
+            shell_snapshot: empty_shell_snapshot_receiver(),
+            argv_template: None,
+        };
+        assert_eq!(
+            test_fish_shell.derive_exec_args("echo hello", true),
+            vec!["/usr/bin/fish", "-lc", "echo hello"]
+        );
+    }
+
+    #[test]
+    fn custom_shell_fills_template_and_canonicalizes_for_parsing() {
+        let argv = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        let bash = custom_shell(&argv(&["/bin/bash", "--norc", "-c", "{command}"])).unwrap();
+        let command = bash.derive_exec_args("ls -la", true);
+        assert_eq!(command, argv(&["/bin/bash", "--norc", "-c", "ls -la"]));
+        assert_eq!(bash.name(), "bash");
+        assert_eq!(
+            bash.canonical_exec_args(&command),
+            argv(&["/bin/bash", "-c", "ls -la"])
+        );
+
+        // Without a placeholder, the script is appended.
+        let nu = custom_shell(&argv(&["nu", "-c"])).unwrap();
+        let command = nu.derive_exec_args("ls", false);
+        assert_eq!(command, argv(&["nu", "-c", "ls"]));
+        // Unknown programs are left for the safety checks to treat as unknown.
+        assert_eq!(nu.canonical_exec_args(&command), command);
+
+        // Commands that do not match the template are returned unchanged.
+        let other = argv(&["/bin/bash", "-lc", "rm -rf /"]);
+        assert_eq!(bash.canonical_exec_args(&other), other);
     }
 
     #[tokio::test]
@@ -504,6 +708,7 @@ mod tests {
                     shell_type: ShellType::Zsh,
                     shell_path: PathBuf::from(shell_path),
                     shell_snapshot: empty_shell_snapshot_receiver(),
+                    argv_template: None,
                 }
             );
         }
@@ -112,7 +112,10 @@ impl Drop for ShellSnapshot {
 }
 
 async fn write_shell_snapshot(shell_type: ShellType, output_path: &Path) -> Result<PathBuf> {
-    if shell_type == ShellType::PowerShell || shell_type == ShellType::Cmd {
+    if matches!(
+        shell_type,
+        ShellType::PowerShell | ShellType::Cmd | ShellType::Fish | ShellType::Custom
+    ) {
         bail!("Shell snapshot not supported yet for {shell_type:?}");
     }
     let shell = get_shell(shell_type.clone(), None)
@@ -143,7 +146,9 @@ async fn capture_snapshot(shell: &Shell) -> Result<String> {
         ShellType::Bash => run_shell_script(shell, &bash_snapshot_script()).await,
         ShellType::Sh => run_shell_script(shell, &sh_snapshot_script()).await,
         ShellType::PowerShell => run_shell_script(shell, powershell_snapshot_script()).await,
-        ShellType::Cmd => bail!("Shell snapshotting is not yet supported for {shell_type:?}"),
+        ShellType::Cmd | ShellType::Fish | ShellType::Custom => {
+            bail!("Shell snapshotting is not yet supported for {shell_type:?}")
+        }
     }
 }
 
@@ -536,6 +541,7 @@ mod tests {
             shell_type: ShellType::Bash,
             shell_path: PathBuf::from("/bin/bash"),
             shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
 
         let snapshot = ShellSnapshot::try_new(dir.path(), ThreadId::new(), &shell)
@@ -567,6 +573,7 @@ mod tests {
             shell_type: ShellType::Sh,
             shell_path: PathBuf::from("/bin/sh"),
             shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
 
         let err = run_script_with_timeout(&shell, &script, Duration::from_secs(1), true)
@@ -591,6 +591,7 @@ mod tests {
                     trust_root.to_string_lossy().to_string(),
                     ProjectConfig {
                         trust_level: Some(TrustLevel::Trusted),
+                        allow_policy_overrides: None,
                     },
                 )])),
                 ..Default::default()
@@ -1,15 +1,26 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::AuthManager;
 use crate::RolloutRecorder;
 use crate::agent::AgentControl;
+use crate::background_jobs::BackgroundJobManager;
+use crate::config::types::ToolTimeouts;
+use crate::conversation::InProcessTool;
 use crate::exec_policy::ExecPolicyManager;
+use crate::hooks::HookRunner;
+use crate::live_session::LiveEventStream;
+use crate::mcp::sampling::McpSampling;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::models_manager::manager::ModelsManager;
+use crate::parse_command_cache::ParsedCommandCache;
 use crate::skills::SkillsManager;
+use crate::tools::exec_outputs::ExecOutputLog;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::unified_exec::UnifiedExecProcessManager;
 use crate::user_notification::UserNotifier;
+use crate::workspace_snapshots::SnapshotStore;
+use crate::workspace_watcher::WorkspaceWatcher;
 use codex_otel::OtelManager;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
@@ -18,7 +29,11 @@ use tokio_util::sync::CancellationToken;
 pub(crate) struct SessionServices {
     pub(crate) mcp_connection_manager: Arc<RwLock<McpConnectionManager>>,
     pub(crate) mcp_startup_cancellation_token: Mutex<CancellationToken>,
+    pub(crate) mcp_sampling: McpSampling,
+    /// Runs `[hooks]` commands in the background.
+    pub(crate) hooks: HookRunner,
     pub(crate) unified_exec_manager: UnifiedExecProcessManager,
+    pub(crate) background_jobs: BackgroundJobManager,
     pub(crate) notifier: UserNotifier,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) user_shell: Arc<crate::shell::Shell>,
@@ -30,4 +45,21 @@ pub(crate) struct SessionServices {
     pub(crate) tool_approvals: Mutex<ApprovalStore>,
     pub(crate) skills_manager: Arc<SkillsManager>,
     pub(crate) agent_control: AgentControl,
+    /// Per-tool default timeouts; replaced by `Op::ReloadConfig`.
+    pub(crate) tool_timeouts: RwLock<ToolTimeouts>,
+    /// Read-only copy of the event stream for attached clients; taken on
+    /// shutdown so the session stops being listed as live.
+    pub(crate) live_events: std::sync::Mutex<Option<LiveEventStream>>,
+    /// Recent `parse_command` results, shared by approvals, retries and exec events.
+    pub(crate) parsed_commands: ParsedCommandCache,
+    /// Dynamic tools run by closures in this process, by name.
+    pub(crate) in_process_tools: HashMap<String, InProcessTool>,
+    /// Set when the `workspace_watcher` feature is on.
+    pub(crate) workspace_watcher: Option<WorkspaceWatcher>,
+    /// Working-tree snapshots of `Op::SnapshotWorkspace` and destructive
+    /// commands.
+    pub(crate) workspace_snapshots: Mutex<SnapshotStore>,
+    /// Output of earlier exec calls for `read_exec_output`; only recorded
+    /// when the `read_exec_output` feature is on.
+    pub(crate) exec_outputs: Mutex<ExecOutputLog>,
 }
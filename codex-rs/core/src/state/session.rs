@@ -1,12 +1,27 @@
 //! Session-wide mutable state.
 
+use std::collections::HashMap;
+use std::time::Instant;
+
 use codex_protocol::models::ResponseItem;
+use codex_protocol::plan_tool::UpdatePlanArgs;
 
 use crate::codex::SessionConfiguration;
+use crate::config::types::ToolBudget;
 use crate::context_manager::ContextManager;
+use crate::loop_detection::FailureLoop;
+use crate::loop_detection::FailureLoopDetector;
+use crate::protocol::EnvironmentCapabilities;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecApproval;
+use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::RateLimitSnapshot;
+use crate::protocol::ReportedOutcome;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::tool_budget::ToolBudgetTracker;
+use crate::tool_digest::ToolDigestLog;
+use crate::tool_digest::TurnDigest;
 use crate::truncate::TruncationPolicy;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
@@ -15,6 +30,40 @@ pub(crate) struct SessionState {
     pub(crate) history: ContextManager,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
     pub(crate) server_reasoning_included: bool,
+    /// How exec calls were cleared to run, by call id, until their end event
+    /// is sent. Kept per session because a unified exec process can end
+    /// after its turn.
+    exec_approvals: HashMap<String, ExecApproval>,
+    /// Exec calls whose begin event was sent but whose end event was not,
+    /// by call id.
+    open_exec_calls: HashMap<String, OpenExecCall>,
+    /// Recent failures of each command, to notice the model retrying one
+    /// that keeps failing.
+    failure_loops: FailureLoopDetector,
+    /// The latest plan from `update_plan`, kept across turns.
+    plan: Option<UpdatePlanArgs>,
+    /// The latest outcome from `report_outcome`.
+    outcome: Option<ReportedOutcome>,
+    /// The `[tool_budget]` limits and what has been spent of them.
+    tool_budget: ToolBudgetTracker,
+    /// How many `[provider_failover]` chain entries the session has moved
+    /// to; the next switch goes to the entry at this index.
+    pub(crate) failovers_used: usize,
+    /// Tool calls of the turns since the last compaction, kept for the
+    /// `<tool_digest>` message compaction adds.
+    tool_digests: ToolDigestLog,
+    /// Approximate cost of the session's model requests, when the model has
+    /// a price under `[token_prices]`.
+    cost: f64,
+    /// The capabilities the model was last told about.
+    capabilities: Option<EnvironmentCapabilities>,
+}
+
+/// An exec call that has begun but not ended; see
+/// [`SessionState::take_open_exec_calls`].
+pub(crate) struct OpenExecCall {
+    pub(crate) begin: ExecCommandBeginEvent,
+    pub(crate) started: Instant,
 }
 
 impl SessionState {
@@ -26,6 +75,16 @@ impl SessionState {
             history,
             latest_rate_limits: None,
             server_reasoning_included: false,
+            exec_approvals: HashMap::new(),
+            open_exec_calls: HashMap::new(),
+            failure_loops: FailureLoopDetector::default(),
+            plan: None,
+            outcome: None,
+            tool_budget: ToolBudgetTracker::default(),
+            failovers_used: 0,
+            tool_digests: ToolDigestLog::default(),
+            cost: 0.0,
+            capabilities: None,
         }
     }
 
@@ -50,19 +109,109 @@ impl SessionState {
         self.history.set_token_info(info);
     }
 
+    pub(crate) fn record_exec_approval(&mut self, call_id: String, approval: ExecApproval) {
+        self.exec_approvals.insert(call_id, approval);
+    }
+
+    pub(crate) fn take_exec_approval(&mut self, call_id: &str) -> Option<ExecApproval> {
+        self.exec_approvals.remove(call_id)
+    }
+
+    pub(crate) fn open_exec_call(&mut self, call_id: String, call: OpenExecCall) {
+        self.open_exec_calls.insert(call_id, call);
+    }
+
+    pub(crate) fn close_exec_call(&mut self, call_id: &str) {
+        self.open_exec_calls.remove(call_id);
+    }
+
+    /// Removes and returns the calls of turn `turn_id` that are still open.
+    /// Calls backed by a unified exec process are left alone: their end
+    /// event comes from the process's exit watcher, even after the turn.
+    pub(crate) fn take_open_exec_calls(&mut self, turn_id: &str) -> Vec<OpenExecCall> {
+        self.open_exec_calls
+            .extract_if(|_, call| call.begin.turn_id == turn_id && call.begin.process_id.is_none())
+            .map(|(_, call)| call)
+            .collect()
+    }
+
+    pub(crate) fn record_command_exit(
+        &mut self,
+        signature: String,
+        turn_id: &str,
+        exit_code: i32,
+        threshold: u32,
+    ) -> Option<FailureLoop> {
+        self.failure_loops
+            .record(signature, turn_id, exit_code, threshold)
+    }
+
+    pub(crate) fn record_tool_digest_event(&mut self, sub_id: &str, msg: &EventMsg) {
+        self.tool_digests.record(sub_id, msg);
+    }
+
+    pub(crate) fn take_tool_digests(&mut self) -> Vec<TurnDigest> {
+        self.tool_digests.take()
+    }
+
+    pub(crate) fn set_plan(&mut self, plan: UpdatePlanArgs) {
+        self.plan = Some(plan);
+    }
+
+    pub(crate) fn plan(&self) -> Option<UpdatePlanArgs> {
+        self.plan.clone()
+    }
+
+    /// Keeps `outcome` as the session's outcome; returns whether it replaced
+    /// an earlier one.
+    pub(crate) fn record_outcome(&mut self, outcome: ReportedOutcome) -> bool {
+        self.outcome.replace(outcome).is_some()
+    }
+
+    pub(crate) fn set_tool_budget(&mut self, limits: ToolBudget) {
+        self.tool_budget = ToolBudgetTracker::new(limits);
+    }
+
+    pub(crate) fn tool_budget(&mut self) -> &mut ToolBudgetTracker {
+        &mut self.tool_budget
+    }
+
     // Token/rate limit helpers
     pub(crate) fn update_token_info_from_usage(
         &mut self,
         usage: &TokenUsage,
         model_context_window: Option<i64>,
+        provider_id: &str,
     ) {
-        self.history.update_token_info(usage, model_context_window);
+        self.history
+            .update_token_info(usage, model_context_window, provider_id);
     }
 
     pub(crate) fn token_info(&self) -> Option<TokenUsageInfo> {
         self.history.token_info()
     }
 
+    pub(crate) fn record_tool_output_tokens(&mut self, tokens: u64) {
+        self.history.record_tool_output_tokens(tokens);
+    }
+
+    pub(crate) fn add_cost(&mut self, cost: f64) {
+        self.cost += cost;
+    }
+
+    pub(crate) fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Keeps `capabilities` as the ones the model was told about; returns
+    /// the previous ones.
+    pub(crate) fn replace_capabilities(
+        &mut self,
+        capabilities: EnvironmentCapabilities,
+    ) -> Option<EnvironmentCapabilities> {
+        self.capabilities.replace(capabilities)
+    }
+
     pub(crate) fn set_rate_limits(&mut self, snapshot: RateLimitSnapshot) {
         self.latest_rate_limits = Some(merge_rate_limit_fields(
             self.latest_rate_limits.as_ref(),
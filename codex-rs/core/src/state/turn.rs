@@ -2,7 +2,13 @@
 
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 use tokio::sync::Mutex;
 use tokio::sync::Notify;
 use tokio_util::sync::CancellationToken;
@@ -14,7 +20,14 @@ use codex_protocol::request_user_input::RequestUserInputResponse;
 use tokio::sync::oneshot;
 
 use crate::codex::TurnContext;
+use crate::file_access::FileAccessLedger;
+use crate::protocol::FileAccessLedgerEvent;
+use crate::protocol::GitStateSnapshot;
+use crate::protocol::PromptCacheStats;
 use crate::protocol::ReviewDecision;
+use crate::protocol::TokenUsage;
+use crate::protocol::ToolCallTokens;
+use crate::protocol::TurnSummaryEvent;
 use crate::tasks::SessionTask;
 
 /// Metadata about the currently running turn.
@@ -70,9 +83,52 @@ impl ActiveTurn {
 #[derive(Default)]
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    /// Reasons for approvals that were denied without asking, by call id.
+    rejection_reasons: HashMap<String, String>,
     pending_user_input: HashMap<String, oneshot::Sender<RequestUserInputResponse>>,
     pending_dynamic_tools: HashMap<String, oneshot::Sender<DynamicToolResponse>>,
     pending_input: Vec<ResponseInputItem>,
+    /// Cancellation tokens of the tool calls being dispatched, by call id.
+    running_tool_calls: HashMap<String, CancellationToken>,
+    /// Call ids of every tool call dispatched this turn.
+    dispatched_tool_calls: HashSet<String>,
+    /// Where the turn's time went, reported in its `TurnSummary` event.
+    tool_calls: u32,
+    tool_time: Duration,
+    model_time: Duration,
+    prompt_cache: PromptCacheStats,
+    /// Since when at least one tool call has been running, if one is.
+    tools_busy_since: Option<Instant>,
+    /// Output of the tool calls finished since the last model request, which
+    /// the next request will carry.
+    pending_output_bytes: usize,
+    /// Files the turn read and wrote.
+    file_access: FileAccessLedger,
+    /// Set by `Op::SoftInterrupt`: the turn ends once its running tool calls
+    /// have finished.
+    soft_interrupted: bool,
+    /// Git state of the turn's repository as of the last check, or `None`
+    /// outside a repository.
+    git_state: Option<GitStateSnapshot>,
+    /// Lines appended to the turn's `TurnSummary`.
+    cautions: Vec<String>,
+    /// Estimated tokens of the tool output sent to the model, in total and
+    /// by call.
+    tool_output_tokens: u64,
+    tool_call_tokens: Vec<ToolCallTokens>,
+    /// Tokens the provider reported for the turn's model requests.
+    token_usage: TokenUsage,
+    /// Approximate cost of the turn's model requests.
+    cost: f64,
+}
+
+/// What a turn's tools are doing right now, for its `TurnProgress` events.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ToolActivity {
+    pub(crate) completed: u32,
+    pub(crate) running: u32,
+    pub(crate) busy_since: Option<Instant>,
+    pub(crate) pending_output_bytes: usize,
 }
 
 impl TurnState {
@@ -91,11 +147,22 @@ impl TurnState {
         self.pending_approvals.remove(key)
     }
 
+    pub(crate) fn insert_rejection_reason(&mut self, call_id: String, reason: String) {
+        self.rejection_reasons.insert(call_id, reason);
+    }
+
+    pub(crate) fn take_rejection_reason(&mut self, call_id: &str) -> Option<String> {
+        self.rejection_reasons.remove(call_id)
+    }
+
     pub(crate) fn clear_pending(&mut self) {
         self.pending_approvals.clear();
+        self.rejection_reasons.clear();
         self.pending_user_input.clear();
         self.pending_dynamic_tools.clear();
         self.pending_input.clear();
+        self.running_tool_calls.clear();
+        self.tools_busy_since = None;
     }
 
     pub(crate) fn insert_pending_user_input(
@@ -128,6 +195,158 @@ impl TurnState {
         self.pending_dynamic_tools.remove(key)
     }
 
+    pub(crate) fn insert_running_tool_call(
+        &mut self,
+        call_id: String,
+        cancellation_token: CancellationToken,
+    ) {
+        if self.running_tool_calls.is_empty() {
+            self.tools_busy_since = Some(Instant::now());
+        }
+        self.running_tool_calls.insert(call_id, cancellation_token);
+    }
+
+    pub(crate) fn remove_running_tool_call(&mut self, call_id: &str) {
+        self.running_tool_calls.remove(call_id);
+        self.clear_busy_if_idle();
+    }
+
+    /// Cancels the tool call `call_id`, returning whether it was running.
+    pub(crate) fn cancel_running_tool_call(&mut self, call_id: &str) -> bool {
+        match self.running_tool_calls.remove(call_id) {
+            Some(token) => {
+                token.cancel();
+                self.clear_busy_if_idle();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Asks the turn to end after its running tool calls, returning whether
+    /// that had already been asked.
+    pub(crate) fn request_soft_interrupt(&mut self) -> bool {
+        std::mem::replace(&mut self.soft_interrupted, true)
+    }
+
+    pub(crate) fn soft_interrupted(&self) -> bool {
+        self.soft_interrupted
+    }
+
+    pub(crate) fn git_state(&self) -> Option<&GitStateSnapshot> {
+        self.git_state.as_ref()
+    }
+
+    pub(crate) fn set_git_state(&mut self, state: Option<GitStateSnapshot>) {
+        self.git_state = state;
+    }
+
+    pub(crate) fn add_caution(&mut self, caution: String) {
+        self.cautions.push(caution);
+    }
+
+    /// Marks tool call `call_id` as dispatched, returning false when it
+    /// already was.
+    pub(crate) fn mark_tool_call_dispatched(&mut self, call_id: &str) -> bool {
+        self.dispatched_tool_calls.insert(call_id.to_string())
+    }
+
+    pub(crate) fn record_tool_time(&mut self, elapsed: Duration) {
+        self.tool_calls = self.tool_calls.saturating_add(1);
+        self.tool_time += elapsed;
+    }
+
+    pub(crate) fn record_model_time(&mut self, elapsed: Duration) {
+        self.model_time += elapsed;
+    }
+
+    /// Records the usage the provider reported for one model request, and
+    /// its approximate cost.
+    pub(crate) fn record_token_usage(&mut self, usage: &TokenUsage, cost: f64) {
+        self.prompt_cache.record(usage);
+        self.token_usage.add_assign(usage);
+        self.cost += cost;
+    }
+
+    pub(crate) fn record_tool_output_tokens(&mut self, tokens: ToolCallTokens) {
+        self.tool_output_tokens = self
+            .tool_output_tokens
+            .saturating_add(tokens.estimated_tokens);
+        self.tool_call_tokens.push(tokens);
+    }
+
+    pub(crate) fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub(crate) fn record_tool_output_bytes(&mut self, bytes: usize) {
+        self.pending_output_bytes = self.pending_output_bytes.saturating_add(bytes);
+    }
+
+    /// Called when a model request starts, since it carries every tool
+    /// output recorded so far.
+    pub(crate) fn clear_pending_output(&mut self) {
+        self.pending_output_bytes = 0;
+    }
+
+    pub(crate) fn tool_activity(&self) -> ToolActivity {
+        ToolActivity {
+            completed: self.tool_calls,
+            running: u32::try_from(self.running_tool_calls.len()).unwrap_or(u32::MAX),
+            busy_since: self.tools_busy_since,
+            pending_output_bytes: self.pending_output_bytes,
+        }
+    }
+
+    pub(crate) fn record_file_read(&mut self, path: PathBuf, modified: Option<SystemTime>) {
+        self.file_access.record_read(path, modified);
+    }
+
+    pub(crate) fn record_file_write(&mut self, path: PathBuf, modified: Option<SystemTime>) {
+        self.file_access.record_write(path, modified);
+    }
+
+    pub(crate) fn file_changed_since_seen(
+        &self,
+        path: &Path,
+        modified: Option<SystemTime>,
+    ) -> bool {
+        self.file_access.changed_since_seen(path, modified)
+    }
+
+    /// The files the turn read and wrote, or `None` if it touched none.
+    pub(crate) fn file_access_event(
+        &self,
+        turn_id: &str,
+        cwd: &Path,
+    ) -> Option<FileAccessLedgerEvent> {
+        if self.file_access.is_empty() {
+            return None;
+        }
+        Some(self.file_access.event(turn_id, cwd))
+    }
+
+    fn clear_busy_if_idle(&mut self) {
+        if self.running_tool_calls.is_empty() {
+            self.tools_busy_since = None;
+        }
+    }
+
+    pub(crate) fn summary(&self) -> TurnSummaryEvent {
+        TurnSummaryEvent {
+            tool_calls: self.tool_calls,
+            tool_time: self.tool_time,
+            model_time: self.model_time,
+            plan: None,
+            prompt_cache: self.prompt_cache,
+            cautions: self.cautions.clone(),
+            tool_output_tokens: self.tool_output_tokens,
+            tool_call_tokens: self.tool_call_tokens.clone(),
+            token_usage: self.token_usage.clone(),
+            cost: None,
+        }
+    }
+
     pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
         self.pending_input.push(input);
     }
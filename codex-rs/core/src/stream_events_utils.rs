@@ -50,6 +50,12 @@ pub(crate) async fn handle_output_item_done(
     match ToolRouter::build_tool_call(ctx.sess.as_ref(), item.clone()).await {
         // The model emitted a tool call; log it, persist the item immediately, and queue the tool execution.
         Ok(Some(call)) => {
+            // A stream retried after a disconnect may repeat calls that
+            // already ran; each call id runs once per turn.
+            if !call.call_id.is_empty() && !ctx.sess.dispatch_tool_call_once(&call.call_id).await {
+                debug!("skipping duplicate tool call {}", call.call_id);
+                return Ok(output);
+            }
             let payload_preview = call.payload.log_payload().into_owned();
             tracing::info!("ToolCall: {} {}", call.tool_name, payload_preview);
 
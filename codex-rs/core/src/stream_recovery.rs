@@ -0,0 +1,155 @@
+//! Recovery from a model stream cut off partway through a response.
+//!
+//! Output items that completed before the disconnect are already recorded,
+//! and the tool calls among them ran (see `handle_output_item_done`).
+//! Resending the original request would make the model redo that work, so
+//! the retry is built from the history instead. It carries the kept items
+//! and, when there are no tool outputs for the model to react to, a note
+//! asking it to continue where it stopped. Calls the model repeats anyway
+//! are dropped by `Session::dispatch_tool_call_once`.
+
+use codex_protocol::models::DeveloperInstructions;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::StreamRecoveryStrategy;
+
+const CONTINUATION_PROMPT: &str = "Your previous response was cut off by a network error. The items above are what was received. Continue from where it stopped without repeating them.";
+
+/// What was kept of a response whose stream was cut off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PreservedOutput {
+    pub(crate) items: u32,
+    pub(crate) tool_calls: u32,
+}
+
+impl PreservedOutput {
+    /// Counts the model output among `recorded`, the history items recorded
+    /// during the failed request. Tool outputs are not counted.
+    pub(crate) fn of(recorded: &[ResponseItem]) -> Self {
+        let mut preserved = Self::default();
+        for item in recorded {
+            match item {
+                ResponseItem::FunctionCall { .. }
+                | ResponseItem::CustomToolCall { .. }
+                | ResponseItem::LocalShellCall { .. } => {
+                    preserved.items += 1;
+                    preserved.tool_calls += 1;
+                }
+                ResponseItem::Message { role, .. } if role == "assistant" => {
+                    preserved.items += 1;
+                }
+                ResponseItem::Reasoning { .. } | ResponseItem::WebSearchCall { .. } => {
+                    preserved.items += 1;
+                }
+                ResponseItem::Message { .. }
+                | ResponseItem::FunctionCallOutput { .. }
+                | ResponseItem::CustomToolCallOutput { .. }
+                | ResponseItem::GhostSnapshot { .. }
+                | ResponseItem::Compaction { .. }
+                | ResponseItem::Other => {}
+            }
+        }
+        preserved
+    }
+
+    pub(crate) fn strategy(self) -> StreamRecoveryStrategy {
+        if self.tool_calls > 0 {
+            StreamRecoveryStrategy::ResumeAfterToolCalls
+        } else if self.items > 0 {
+            StreamRecoveryStrategy::Continue
+        } else {
+            StreamRecoveryStrategy::Restart
+        }
+    }
+}
+
+/// Input of the retried request: the history, which holds the kept items,
+/// followed by the continuation note for [`StreamRecoveryStrategy::Continue`].
+pub(crate) fn retry_input(
+    mut history: Vec<ResponseItem>,
+    strategy: StreamRecoveryStrategy,
+) -> Vec<ResponseItem> {
+    if strategy == StreamRecoveryStrategy::Continue {
+        history.push(DeveloperInstructions::new(CONTINUATION_PROMPT).into());
+    }
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn function_call(call_id: &str) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            id: None,
+            name: "shell_command".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    fn assistant_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+        }
+    }
+
+    #[test]
+    fn tool_calls_resume_after_them_and_outputs_are_not_counted() {
+        let recorded = vec![
+            assistant_message("running two commands"),
+            function_call("call-1"),
+            function_call("call-2"),
+            ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload::default(),
+            },
+        ];
+
+        let preserved = PreservedOutput::of(&recorded);
+
+        assert_eq!(
+            preserved,
+            PreservedOutput {
+                items: 3,
+                tool_calls: 2,
+            }
+        );
+        assert_eq!(
+            preserved.strategy(),
+            StreamRecoveryStrategy::ResumeAfterToolCalls
+        );
+        assert_eq!(
+            retry_input(recorded.clone(), preserved.strategy()),
+            recorded
+        );
+    }
+
+    #[test]
+    fn messages_alone_ask_the_model_to_continue() {
+        let preserved = PreservedOutput::of(&[assistant_message("half")]);
+        assert_eq!(preserved.strategy(), StreamRecoveryStrategy::Continue);
+
+        let input = retry_input(vec![assistant_message("half")], preserved.strategy());
+        assert_eq!(input.len(), 2);
+        assert!(matches!(
+            &input[1],
+            ResponseItem::Message { role, .. } if role == "developer"
+        ));
+    }
+
+    #[test]
+    fn nothing_kept_restarts_the_request() {
+        assert_eq!(
+            PreservedOutput::of(&[]).strategy(),
+            StreamRecoveryStrategy::Restart
+        );
+    }
+}
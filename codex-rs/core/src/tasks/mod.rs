@@ -24,10 +24,13 @@ use crate::protocol::EventMsg;
 use crate::protocol::TurnAbortReason;
 use crate::protocol::TurnAbortedEvent;
 use crate::protocol::TurnCompleteEvent;
+use crate::protocol::TurnCost;
 use crate::session_prefix::TURN_ABORTED_OPEN_TAG;
 use crate::state::ActiveTurn;
 use crate::state::RunningTask;
 use crate::state::TaskKind;
+use crate::tool_tokens;
+use crate::tools::events::close_open_exec_calls;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::RolloutItem;
@@ -180,9 +183,19 @@ impl Session {
         last_agent_message: Option<String>,
     ) {
         let mut active = self.active_turn.lock().await;
+        let mut summary = None;
+        let mut file_access = None;
+        let mut soft_interrupted = false;
+        let mut turn_cost = 0.0;
         let should_close_processes = if let Some(at) = active.as_mut()
             && at.remove_task(&turn_context.sub_id)
         {
+            let turn_state = at.turn_state.lock().await;
+            summary = Some(turn_state.summary());
+            turn_cost = turn_state.cost();
+            soft_interrupted = turn_state.soft_interrupted();
+            file_access = turn_state.file_access_event(&turn_context.sub_id, &turn_context.cwd);
+            drop(turn_state);
             *active = None;
             true
         } else {
@@ -191,9 +204,37 @@ impl Session {
         drop(active);
         if should_close_processes {
             self.close_unified_exec_processes().await;
+            close_open_exec_calls(self, turn_context.as_ref()).await;
         }
-        let event = EventMsg::TurnComplete(TurnCompleteEvent { last_agent_message });
+        if let Some(mut summary) = summary {
+            summary.plan = self.current_plan().await;
+            if let Some(price) = self.token_price(turn_context.as_ref()).await {
+                summary.cost = Some(TurnCost {
+                    turn: turn_cost,
+                    tool_output: tool_tokens::input_cost(&price, summary.tool_output_tokens),
+                    session: self.session_cost().await,
+                });
+            }
+            self.send_event(turn_context.as_ref(), EventMsg::TurnSummary(summary))
+                .await;
+        }
+        if let Some(file_access) = file_access {
+            self.send_event(
+                turn_context.as_ref(),
+                EventMsg::FileAccessLedger(file_access),
+            )
+            .await;
+        }
+        let event = if soft_interrupted {
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::SoftInterrupt,
+            })
+        } else {
+            EventMsg::TurnComplete(TurnCompleteEvent { last_agent_message })
+        };
         self.send_event(turn_context.as_ref(), event).await;
+        // Turn boundaries are the resume points; keep them durable.
+        self.flush_rollout().await;
     }
 
     async fn register_new_active_task(&self, task: RunningTask) {
@@ -246,6 +287,7 @@ impl Session {
         session_task
             .abort(session_ctx, Arc::clone(&task.turn_context))
             .await;
+        close_open_exec_calls(self, task.turn_context.as_ref()).await;
 
         if reason == TurnAbortReason::Interrupted {
             let marker = ResponseItem::Message {
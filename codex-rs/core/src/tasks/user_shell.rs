@@ -10,22 +10,29 @@ use tracing::error;
 use uuid::Uuid;
 
 use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
 use crate::exec::execute_exec_env;
-use crate::exec_env::create_env;
-use crate::parse_command::parse_command;
+use crate::path_utils::normalize_event_path;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::ExecCommandSource;
+use crate::protocol::ExecErrorInfo;
+use crate::protocol::ExecErrorKind;
+use crate::protocol::ExecOutcome;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::TurnStartedEvent;
 use crate::sandboxing::ExecEnv;
 use crate::sandboxing::SandboxPermissions;
 use crate::state::TaskKind;
+use crate::tools::events::limit_event_field;
+use crate::tools::events::sanitize_event_text;
+use crate::tools::events::sanitized_bytes_field;
 use crate::tools::format_exec_output_str;
 use crate::tools::runtimes::maybe_wrap_shell_lc_with_snapshot;
 use crate::user_shell_command::user_shell_command_record_item;
@@ -67,6 +74,7 @@ impl SessionTask for UserShellCommandTask {
 
         let event = EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: turn_context.client.get_model_context_window(),
+            tools: None,
         });
         let session = session.clone_session();
         session.send_event(turn_context.as_ref(), event).await;
@@ -83,8 +91,9 @@ impl SessionTask for UserShellCommandTask {
         let call_id = Uuid::new_v4().to_string();
         let raw_command = self.command.clone();
         let cwd = turn_context.cwd.clone();
+        let event_cwd = normalize_event_path(&cwd);
 
-        let parsed_cmd = parse_command(&display_command);
+        let parsed_cmd = session.parsed_command(&display_command);
         session
             .send_event(
                 turn_context.as_ref(),
@@ -93,10 +102,12 @@ impl SessionTask for UserShellCommandTask {
                     process_id: None,
                     turn_id: turn_context.sub_id.clone(),
                     command: display_command.clone(),
-                    cwd: cwd.clone(),
-                    parsed_cmd: parsed_cmd.clone(),
+                    cwd: event_cwd.clone(),
+                    parsed_cmd: parsed_cmd.as_ref().clone(),
                     source: ExecCommandSource::UserShell,
                     interaction_input: None,
+                    timeout_ms: None,
+                    injected_env: Default::default(),
                 }),
             )
             .await;
@@ -104,7 +115,7 @@ impl SessionTask for UserShellCommandTask {
         let exec_env = ExecEnv {
             command: exec_command.clone(),
             cwd: cwd.clone(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: turn_context.exec_env(),
             // TODO(zhao-oai): Now that we have ExecExpiration::Cancellation, we
             // should use that instead of an "arbitrarily large" timeout here.
             expiration: USER_SHELL_TIMEOUT_MS.into(),
@@ -118,6 +129,8 @@ impl SessionTask for UserShellCommandTask {
             sub_id: turn_context.sub_id.clone(),
             call_id: call_id.clone(),
             tx_event: session.get_tx_event(),
+            delta_chunk_bytes: turn_context.output_limits.delta_chunk_bytes,
+            spill_path: None,
         });
 
         let sandbox_policy = SandboxPolicy::DangerFullAccess;
@@ -134,7 +147,9 @@ impl SessionTask for UserShellCommandTask {
                     stderr: StreamOutput::new(aborted_message.clone()),
                     aggregated_output: StreamOutput::new(aborted_message.clone()),
                     duration: Duration::ZERO,
+                    suspended: false,
                     timed_out: false,
+                    deltas: Default::default(),
                 };
                 let output_items = [user_shell_command_record_item(
                     &raw_command,
@@ -152,21 +167,47 @@ impl SessionTask for UserShellCommandTask {
                             process_id: None,
                             turn_id: turn_context.sub_id.clone(),
                             command: display_command.clone(),
-                            cwd: cwd.clone(),
-                            parsed_cmd: parsed_cmd.clone(),
+                            cwd: event_cwd.clone(),
+                            parsed_cmd: parsed_cmd.as_ref().clone(),
                             source: ExecCommandSource::UserShell,
                             interaction_input: None,
                             stdout: String::new(),
-                            stderr: aborted_message.clone(),
-                            aggregated_output: aborted_message.clone(),
+                            stderr: String::new(),
+                            aggregated_output: String::new(),
                             exit_code: -1,
+                            outcome: None,
                             duration: Duration::ZERO,
-                            formatted_output: aborted_message,
+                            estimated: true,
+                            suspended: false,
+                            sanitized_bytes: None,
+                            delta_bytes_emitted: 0,
+                            deltas_incomplete: false,
+                            formatted_output: aborted_message.clone(),
+                            saved_output_path: None,
+                            approval: None,
+                            error: Some(ExecErrorInfo {
+                                kind: ExecErrorKind::Interrupted,
+                                message: aborted_message,
+                            }),
                         }),
                     )
                     .await;
             }
             Ok(Ok(output)) => {
+                let mut stdout = output.stdout.text.clone();
+                let mut stderr = output.stderr.text.clone();
+                let mut aggregated_output = output.aggregated_output.text.clone();
+                let mut formatted_output =
+                    format_exec_output_str(&output, turn_context.exec_output_truncation_policy());
+                let sanitized_bytes = [
+                    &mut stdout,
+                    &mut stderr,
+                    &mut aggregated_output,
+                    &mut formatted_output,
+                ]
+                .into_iter()
+                .map(sanitize_event_text)
+                .sum();
                 session
                     .send_event(
                         turn_context.as_ref(),
@@ -175,19 +216,34 @@ impl SessionTask for UserShellCommandTask {
                             process_id: None,
                             turn_id: turn_context.sub_id.clone(),
                             command: display_command.clone(),
-                            cwd: cwd.clone(),
-                            parsed_cmd: parsed_cmd.clone(),
+                            cwd: event_cwd.clone(),
+                            parsed_cmd: parsed_cmd.as_ref().clone(),
                             source: ExecCommandSource::UserShell,
                             interaction_input: None,
-                            stdout: output.stdout.text.clone(),
-                            stderr: output.stderr.text.clone(),
-                            aggregated_output: output.aggregated_output.text.clone(),
+                            stdout: limit_event_field(
+                                stdout,
+                                turn_context.output_limits.event_field_bytes,
+                            ),
+                            stderr: limit_event_field(
+                                stderr,
+                                turn_context.output_limits.event_field_bytes,
+                            ),
+                            aggregated_output: limit_event_field(
+                                aggregated_output,
+                                turn_context.output_limits.event_field_bytes,
+                            ),
                             exit_code: output.exit_code,
+                            outcome: Some(output.outcome()),
                             duration: output.duration,
-                            formatted_output: format_exec_output_str(
-                                &output,
-                                turn_context.truncation_policy,
-                            ),
+                            estimated: false,
+                            suspended: output.suspended,
+                            sanitized_bytes: sanitized_bytes_field(sanitized_bytes),
+                            delta_bytes_emitted: output.deltas.bytes_emitted,
+                            deltas_incomplete: output.deltas.incomplete,
+                            formatted_output,
+                            saved_output_path: None,
+                            approval: None,
+                            error: None,
                         }),
                     )
                     .await;
@@ -203,14 +259,34 @@ impl SessionTask for UserShellCommandTask {
             }
             Ok(Err(err)) => {
                 error!("user shell command failed: {err:?}");
-                let message = format!("execution error: {err:?}");
+                let (message, outcome, kind) = match err {
+                    CodexErr::ExecSpawnFailed { kind, message } => {
+                        let outcome = ExecOutcome::SpawnFailed {
+                            error_kind: kind,
+                            message: message.clone(),
+                        };
+                        (message, Some(outcome), ExecErrorKind::SpawnFailed)
+                    }
+                    err @ CodexErr::Sandbox(SandboxErr::Signal(signal)) => (
+                        format!("execution error: {err:?}"),
+                        Some(ExecOutcome::Killed { signal }),
+                        ExecErrorKind::Killed,
+                    ),
+                    err => (
+                        format!("execution error: {err:?}"),
+                        None,
+                        ExecErrorKind::Internal,
+                    ),
+                };
                 let exec_output = ExecToolCallOutput {
                     exit_code: -1,
                     stdout: StreamOutput::new(String::new()),
                     stderr: StreamOutput::new(message.clone()),
                     aggregated_output: StreamOutput::new(message.clone()),
                     duration: Duration::ZERO,
+                    suspended: false,
                     timed_out: false,
+                    deltas: Default::default(),
                 };
                 session
                     .send_event(
@@ -220,19 +296,28 @@ impl SessionTask for UserShellCommandTask {
                             process_id: None,
                             turn_id: turn_context.sub_id.clone(),
                             command: display_command,
-                            cwd,
-                            parsed_cmd,
+                            cwd: event_cwd,
+                            parsed_cmd: Arc::unwrap_or_clone(parsed_cmd),
                             source: ExecCommandSource::UserShell,
                             interaction_input: None,
-                            stdout: exec_output.stdout.text.clone(),
-                            stderr: exec_output.stderr.text.clone(),
-                            aggregated_output: exec_output.aggregated_output.text.clone(),
+                            stdout: String::new(),
+                            stderr: String::new(),
+                            aggregated_output: String::new(),
                             exit_code: exec_output.exit_code,
+                            outcome,
                             duration: exec_output.duration,
+                            estimated: true,
+                            suspended: false,
+                            sanitized_bytes: None,
+                            delta_bytes_emitted: 0,
+                            deltas_incomplete: false,
                             formatted_output: format_exec_output_str(
                                 &exec_output,
-                                turn_context.truncation_policy,
+                                turn_context.exec_output_truncation_policy(),
                             ),
+                            saved_output_path: None,
+                            approval: None,
+                            error: Some(ExecErrorInfo { kind, message }),
                         }),
                     )
                     .await;
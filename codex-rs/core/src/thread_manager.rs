@@ -9,6 +9,7 @@ use crate::codex::CodexSpawnOk;
 use crate::codex::INITIAL_SUBMIT_ID;
 use crate::codex_thread::CodexThread;
 use crate::config::Config;
+use crate::conversation::InProcessTool;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::models_manager::manager::ModelsManager;
@@ -215,6 +216,26 @@ impl ThreadManager {
             .await
     }
 
+    /// Starts a thread whose model can call `tools`, which run in this
+    /// process. See [`crate::Conversation`].
+    pub async fn start_thread_with_in_process_tools(
+        &self,
+        config: Config,
+        tools: Vec<InProcessTool>,
+    ) -> CodexResult<NewThread> {
+        self.state
+            .spawn_thread_with_source(
+                config,
+                InitialHistory::New,
+                Arc::clone(&self.state.auth_manager),
+                self.agent_control(),
+                self.state.session_source.clone(),
+                Vec::new(),
+                tools,
+            )
+            .await
+    }
+
     pub async fn resume_thread_from_rollout(
         &self,
         config: Config,
@@ -347,6 +368,7 @@ impl ThreadManagerState {
             agent_control,
             session_source,
             Vec::new(),
+            Vec::new(),
         )
         .await
     }
@@ -367,6 +389,7 @@ impl ThreadManagerState {
             agent_control,
             self.session_source.clone(),
             dynamic_tools,
+            Vec::new(),
         )
         .await
     }
@@ -379,6 +402,7 @@ impl ThreadManagerState {
         agent_control: AgentControl,
         session_source: SessionSource,
         dynamic_tools: Vec<codex_protocol::dynamic_tools::DynamicToolSpec>,
+        in_process_tools: Vec<InProcessTool>,
     ) -> CodexResult<NewThread> {
         let CodexSpawnOk {
             codex, thread_id, ..
@@ -391,6 +415,7 @@ impl ThreadManagerState {
             session_source,
             agent_control,
             dynamic_tools,
+            in_process_tools,
         )
         .await?;
         self.finalize_thread_spawn(codex, thread_id).await
@@ -0,0 +1,345 @@
+//! Limits on the tool calls of a turn and of the session.
+//!
+//! The `[tool_budget]` table caps how many tools a turn and the session may
+//! call and how long a turn's commands may run in total. Calls are charged
+//! as they are dispatched. Once a budget is spent, every further call fails
+//! with a message telling the model so, and the first refusal of each budget
+//! in a turn sends `BudgetExceeded`. With `end_turn` set, the turn then ends
+//! instead of going back to the model. `Op::RaiseToolBudget` raises the
+//! limits for the rest of the session.
+
+use std::time::Duration;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::types::ToolBudget;
+use crate::protocol::BudgetExceededEvent;
+use crate::protocol::EventMsg;
+use crate::protocol::ToolBudgetKind;
+use crate::protocol::ToolBudgetRemaining;
+
+#[derive(Debug, Default)]
+pub(crate) struct ToolBudgetTracker {
+    limits: ToolBudget,
+    session_calls: u32,
+    turn: TurnUsage,
+}
+
+/// What the latest turn to call a tool has spent.
+#[derive(Debug, Default)]
+struct TurnUsage {
+    turn_id: String,
+    calls: u32,
+    exec_time: Duration,
+    /// Budgets whose refusal was already reported this turn.
+    reported: Vec<ToolBudgetKind>,
+    /// Set once a budget ran out with `end_turn` on.
+    end_turn: bool,
+}
+
+/// A spent budget that refused a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BudgetExhausted {
+    pub(crate) kind: ToolBudgetKind,
+    /// Calls, or milliseconds for [`ToolBudgetKind::TurnExecTime`].
+    pub(crate) limit: u64,
+    /// Whether this is the budget's first refusal in the turn.
+    pub(crate) first: bool,
+    pub(crate) ends_turn: bool,
+}
+
+impl ToolBudgetTracker {
+    pub(crate) fn new(limits: ToolBudget) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn limits(&self) -> ToolBudget {
+        self.limits
+    }
+
+    /// Charges a call of turn `turn_id` against the budgets, or says which
+    /// one is spent. Refused calls are not charged.
+    pub(crate) fn charge_call(&mut self, turn_id: &str) -> Result<(), BudgetExhausted> {
+        let limits = self.limits;
+        let session_calls = self.session_calls;
+        let turn = self.turn_usage(turn_id);
+        let spent = if let Some(max) = limits.max_calls_per_turn
+            && turn.calls >= max
+        {
+            Some((ToolBudgetKind::TurnToolCalls, u64::from(max)))
+        } else if let Some(max) = limits.max_calls_per_session
+            && session_calls >= max
+        {
+            Some((ToolBudgetKind::SessionToolCalls, u64::from(max)))
+        } else if let Some(max) = limits.max_exec_time_per_turn
+            && turn.exec_time >= max
+        {
+            Some((ToolBudgetKind::TurnExecTime, duration_ms(max)))
+        } else {
+            None
+        };
+
+        if let Some((kind, limit)) = spent {
+            let first = !turn.reported.contains(&kind);
+            if first {
+                turn.reported.push(kind);
+            }
+            turn.end_turn |= limits.end_turn;
+            return Err(BudgetExhausted {
+                kind,
+                limit,
+                first,
+                ends_turn: limits.end_turn,
+            });
+        }
+        turn.calls = turn.calls.saturating_add(1);
+        self.session_calls = self.session_calls.saturating_add(1);
+        Ok(())
+    }
+
+    /// Adds `elapsed` to the command time of turn `turn_id`. Commands that
+    /// finish after a later turn made its first call are not counted.
+    pub(crate) fn record_exec_time(&mut self, turn_id: &str, elapsed: Duration) {
+        if self.turn.turn_id == turn_id {
+            self.turn.exec_time = self.turn.exec_time.saturating_add(elapsed);
+        }
+    }
+
+    /// Whether turn `turn_id` spent a budget and should end.
+    pub(crate) fn ends_turn(&self, turn_id: &str) -> bool {
+        self.turn.turn_id == turn_id && self.turn.end_turn
+    }
+
+    pub(crate) fn remaining(&self, turn_id: &str) -> ToolBudgetRemaining {
+        let (calls, exec_time) = if self.turn.turn_id == turn_id {
+            (self.turn.calls, self.turn.exec_time)
+        } else {
+            (0, Duration::ZERO)
+        };
+        ToolBudgetRemaining {
+            turn_tool_calls: self
+                .limits
+                .max_calls_per_turn
+                .map(|max| max.saturating_sub(calls)),
+            session_tool_calls: self
+                .limits
+                .max_calls_per_session
+                .map(|max| max.saturating_sub(self.session_calls)),
+            turn_exec_ms: self
+                .limits
+                .max_exec_time_per_turn
+                .map(|max| duration_ms(max.saturating_sub(exec_time))),
+        }
+    }
+
+    /// Raises the limits that are set to the given values where those are
+    /// higher. A raised budget is reported again if it runs out again, and
+    /// the current turn no longer ends because of it.
+    pub(crate) fn raise(
+        &mut self,
+        max_calls_per_turn: Option<u32>,
+        max_calls_per_session: Option<u32>,
+        max_exec_time_per_turn: Option<Duration>,
+    ) {
+        fn raise_to<T: Ord + Copy>(limit: &mut Option<T>, to: Option<T>) -> bool {
+            match (limit.as_mut(), to) {
+                (Some(limit), Some(to)) if to > *limit => {
+                    *limit = to;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        let raised = [
+            (
+                ToolBudgetKind::TurnToolCalls,
+                raise_to(&mut self.limits.max_calls_per_turn, max_calls_per_turn),
+            ),
+            (
+                ToolBudgetKind::SessionToolCalls,
+                raise_to(
+                    &mut self.limits.max_calls_per_session,
+                    max_calls_per_session,
+                ),
+            ),
+            (
+                ToolBudgetKind::TurnExecTime,
+                raise_to(
+                    &mut self.limits.max_exec_time_per_turn,
+                    max_exec_time_per_turn,
+                ),
+            ),
+        ];
+        let turn = &mut self.turn;
+        for (kind, raised) in raised {
+            if raised {
+                turn.reported.retain(|reported| *reported != kind);
+            }
+        }
+        if turn.reported.is_empty() {
+            turn.end_turn = false;
+        }
+    }
+
+    /// The usage of turn `turn_id`, starting it over when a new turn calls
+    /// its first tool.
+    fn turn_usage(&mut self, turn_id: &str) -> &mut TurnUsage {
+        if self.turn.turn_id != turn_id {
+            self.turn = TurnUsage {
+                turn_id: turn_id.to_string(),
+                ..Default::default()
+            };
+        }
+        &mut self.turn
+    }
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Charges the call `call_id` against the tool budgets. When a budget is
+/// spent, sends `BudgetExceeded` the first time in the turn and returns the
+/// message to fail the call with.
+pub(crate) async fn charge_tool_call(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+) -> Result<(), String> {
+    let Err(exhausted) = session.charge_tool_call(&turn.sub_id).await else {
+        return Ok(());
+    };
+    if exhausted.first {
+        let event = BudgetExceededEvent {
+            call_id: call_id.to_string(),
+            turn_id: turn.sub_id.clone(),
+            budget: exhausted.kind,
+            limit: exhausted.limit,
+            ends_turn: exhausted.ends_turn,
+        };
+        session
+            .send_event(turn, EventMsg::BudgetExceeded(event))
+            .await;
+    }
+    Err(refusal(exhausted))
+}
+
+fn refusal(exhausted: BudgetExhausted) -> String {
+    let spent = match exhausted.kind {
+        ToolBudgetKind::TurnToolCalls => {
+            format!(
+                "this turn has made all {} of its tool calls",
+                exhausted.limit
+            )
+        }
+        ToolBudgetKind::SessionToolCalls => {
+            format!(
+                "this session has made all {} of its tool calls",
+                exhausted.limit
+            )
+        }
+        ToolBudgetKind::TurnExecTime => format!(
+            "this turn's commands have run for all {}s they are allowed",
+            exhausted.limit / 1000
+        ),
+    };
+    format!(
+        "tool budget exhausted: {spent}. Do not call more tools; finish with what you have and tell the user what is left to do."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tracker(
+        turn: Option<u32>,
+        session: Option<u32>,
+        exec_secs: Option<u64>,
+    ) -> ToolBudgetTracker {
+        ToolBudgetTracker::new(ToolBudget {
+            max_calls_per_turn: turn,
+            max_calls_per_session: session,
+            max_exec_time_per_turn: exec_secs.map(Duration::from_secs),
+            end_turn: false,
+        })
+    }
+
+    #[test]
+    fn per_turn_calls_run_out_and_start_over_with_the_next_turn() {
+        let mut budget = tracker(Some(2), None, None);
+
+        assert_eq!(budget.charge_call("turn-1"), Ok(()));
+        assert_eq!(budget.charge_call("turn-1"), Ok(()));
+        let refused = BudgetExhausted {
+            kind: ToolBudgetKind::TurnToolCalls,
+            limit: 2,
+            first: true,
+            ends_turn: false,
+        };
+        assert_eq!(budget.charge_call("turn-1"), Err(refused));
+        assert_eq!(
+            budget.charge_call("turn-1"),
+            Err(BudgetExhausted {
+                first: false,
+                ..refused
+            })
+        );
+
+        assert_eq!(budget.charge_call("turn-2"), Ok(()));
+        assert_eq!(
+            budget.remaining("turn-2"),
+            ToolBudgetRemaining {
+                turn_tool_calls: Some(1),
+                session_tool_calls: None,
+                turn_exec_ms: None,
+            }
+        );
+    }
+
+    #[test]
+    fn session_calls_and_exec_time_are_budgeted() {
+        let mut budget = tracker(None, Some(3), Some(10));
+
+        budget.charge_call("turn-1").expect("within budget");
+        budget.record_exec_time("turn-1", Duration::from_secs(10));
+        assert_eq!(
+            budget.charge_call("turn-1").map_err(|refused| refused.kind),
+            Err(ToolBudgetKind::TurnExecTime)
+        );
+
+        budget.charge_call("turn-2").expect("within budget");
+        budget.charge_call("turn-2").expect("within budget");
+        assert_eq!(
+            budget.charge_call("turn-3").map_err(|refused| refused.kind),
+            Err(ToolBudgetKind::SessionToolCalls)
+        );
+        assert_eq!(budget.remaining("turn-3").session_tool_calls, Some(0));
+        assert_eq!(budget.remaining("turn-4").turn_exec_ms, Some(10_000));
+    }
+
+    #[test]
+    fn raising_a_spent_budget_lets_the_turn_go_on() {
+        let mut budget = ToolBudgetTracker::new(ToolBudget {
+            max_calls_per_turn: Some(1),
+            end_turn: true,
+            ..Default::default()
+        });
+        budget.charge_call("turn-1").expect("within budget");
+        assert!(budget.charge_call("turn-1").is_err());
+        assert!(budget.ends_turn("turn-1"));
+
+        budget.raise(Some(1), Some(50), None);
+        assert!(budget.ends_turn("turn-1"));
+
+        budget.raise(Some(2), None, None);
+        assert!(!budget.ends_turn("turn-1"));
+        assert_eq!(budget.charge_call("turn-1"), Ok(()));
+        assert_eq!(budget.remaining("turn-1").session_tool_calls, None);
+    }
+}
@@ -0,0 +1,483 @@
+//! Structured digests of the tool calls of compacted turns.
+//!
+//! Compaction replaces the history with a summary the model writes itself,
+//! which flattens exit codes and file lists into prose. [`ToolDigestLog`]
+//! builds a digest of each turn from the exec, patch and diff events the
+//! session emits, and compaction injects the digests back as one
+//! `<tool_digest>` message holding JSON.
+
+use std::path::PathBuf;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::parse_command::extract_shell_command;
+use crate::parse_command::shlex_join;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::ExecCommandSource;
+use crate::protocol::ExecErrorKind;
+use crate::protocol::FileChange;
+use crate::protocol::FileChangeKind;
+use crate::protocol::PatchApplyEndEvent;
+use crate::session_prefix::TOOL_DIGEST_OPEN_TAG;
+
+const TOOL_DIGEST_CLOSE_TAG: &str = "</tool_digest>";
+const TOOL_DIGEST_INTRO: &str = "Tool calls of the turns compacted out of this thread, oldest first, as recorded by Codex. Exit codes and line counts are exact.";
+/// Digests kept across compactions; older turns are dropped first.
+const MAX_DIGEST_TURNS: usize = 40;
+const MAX_COMMANDS_PER_TURN: usize = 30;
+const MAX_COMMAND_CHARS: usize = 200;
+
+/// What one turn did with tools.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TurnDigest {
+    pub(crate) turn_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) commands: Vec<CommandDigest>,
+    /// Earlier commands left out of `commands` because the turn ran too many.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub(crate) commands_omitted: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) patches: Vec<PatchDigest>,
+    /// The turn's changes as of its last diff, per file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) diff: Vec<FileDiffStat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CommandDigest {
+    pub(crate) command: String,
+    pub(crate) exit_code: i32,
+    /// Set when the command did not simply exit, e.g. it timed out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<ExecErrorKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PatchDigest {
+    pub(crate) path: PathBuf,
+    pub(crate) kind: FileChangeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) move_path: Option<PathBuf>,
+    pub(crate) added: usize,
+    pub(crate) removed: usize,
+    pub(crate) applied: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileDiffStat {
+    pub(crate) path: String,
+    pub(crate) added: usize,
+    pub(crate) removed: usize,
+}
+
+impl TurnDigest {
+    fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.patches.is_empty() && self.diff.is_empty()
+    }
+
+    /// Appends `later`, a digest of the same turn recorded after this one.
+    fn merge(&mut self, later: TurnDigest) {
+        self.commands.extend(later.commands);
+        self.commands_omitted += later.commands_omitted;
+        self.cap_commands();
+        self.patches.extend(later.patches);
+        if !later.diff.is_empty() {
+            self.diff = later.diff;
+        }
+    }
+
+    fn cap_commands(&mut self) {
+        let excess = self.commands.len().saturating_sub(MAX_COMMANDS_PER_TURN);
+        self.commands.drain(..excess);
+        self.commands_omitted += excess;
+    }
+}
+
+/// Digests of the turns since the last compaction, fed from the session's
+/// events.
+#[derive(Debug, Default)]
+pub(crate) struct ToolDigestLog {
+    turns: Vec<TurnDigest>,
+}
+
+impl ToolDigestLog {
+    /// Whether [`Self::record`] looks at `msg`; lets callers skip taking a
+    /// lock for every other event.
+    pub(crate) fn records(msg: &EventMsg) -> bool {
+        matches!(
+            msg,
+            EventMsg::ExecCommandEnd(_) | EventMsg::PatchApplyEnd(_) | EventMsg::TurnDiff(_)
+        )
+    }
+
+    /// Records `msg`, sent with the event id `sub_id`.
+    pub(crate) fn record(&mut self, sub_id: &str, msg: &EventMsg) {
+        match msg {
+            EventMsg::ExecCommandEnd(end) => {
+                if let Some(command) = command_digest(end) {
+                    let turn = self.turn_mut(&end.turn_id, sub_id);
+                    turn.commands.push(command);
+                    turn.cap_commands();
+                }
+            }
+            EventMsg::PatchApplyEnd(end) => {
+                let patches = patch_digests(end);
+                self.turn_mut(&end.turn_id, sub_id).patches.extend(patches);
+            }
+            EventMsg::TurnDiff(diff) => {
+                self.turn_mut(&diff.turn_id, sub_id).diff = diff_stats(&diff.unified_diff);
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes the digests recorded so far, oldest turn first.
+    pub(crate) fn take(&mut self) -> Vec<TurnDigest> {
+        std::mem::take(&mut self.turns)
+            .into_iter()
+            .filter(|turn| !turn.is_empty())
+            .collect()
+    }
+
+    fn turn_mut(&mut self, turn_id: &str, sub_id: &str) -> &mut TurnDigest {
+        let turn_id = if turn_id.is_empty() { sub_id } else { turn_id };
+        let index = match self.turns.iter().rposition(|turn| turn.turn_id == turn_id) {
+            Some(index) => index,
+            None => {
+                self.turns.push(TurnDigest {
+                    turn_id: turn_id.to_string(),
+                    ..Default::default()
+                });
+                self.turns.len() - 1
+            }
+        };
+        &mut self.turns[index]
+    }
+}
+
+/// The digests held by the `<tool_digest>` message an earlier compaction
+/// left in `history`, if any.
+pub(crate) fn digests_in_history(history: &[ResponseItem]) -> Vec<TurnDigest> {
+    history
+        .iter()
+        .find_map(|item| {
+            let ResponseItem::Message { role, content, .. } = item else {
+                return None;
+            };
+            if role != "user" {
+                return None;
+            }
+            content.iter().find_map(|content| match content {
+                ContentItem::InputText { text } => parse_digest_text(text),
+                _ => None,
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// The `<tool_digest>` message for `earlier` digests followed by `later`
+/// ones, or `None` when there is nothing to report.
+pub(crate) fn digest_item(
+    earlier: Vec<TurnDigest>,
+    later: Vec<TurnDigest>,
+) -> Option<ResponseItem> {
+    let mut turns: Vec<TurnDigest> = Vec::new();
+    for turn in earlier.into_iter().chain(later) {
+        match turns.last_mut() {
+            Some(last) if last.turn_id == turn.turn_id => last.merge(turn),
+            _ => turns.push(turn),
+        }
+    }
+    turns.drain(..turns.len().saturating_sub(MAX_DIGEST_TURNS));
+    if turns.is_empty() {
+        return None;
+    }
+    let json = serde_json::to_string(&turns).ok()?;
+    Some(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "{TOOL_DIGEST_OPEN_TAG}\n{TOOL_DIGEST_INTRO}\n{json}\n{TOOL_DIGEST_CLOSE_TAG}"
+            ),
+        }],
+        end_turn: None,
+    })
+}
+
+fn parse_digest_text(text: &str) -> Option<Vec<TurnDigest>> {
+    let body = text.trim_start().strip_prefix(TOOL_DIGEST_OPEN_TAG)?;
+    let body = body.trim_end().strip_suffix(TOOL_DIGEST_CLOSE_TAG)?;
+    let json = &body[body.find('[')?..];
+    serde_json::from_str(json).ok()
+}
+
+fn command_digest(end: &ExecCommandEndEvent) -> Option<CommandDigest> {
+    match end.source {
+        ExecCommandSource::ModelShell
+        | ExecCommandSource::ModelUnifiedExec
+        | ExecCommandSource::UserShell
+        | ExecCommandSource::EscalatedRetry => {}
+        ExecCommandSource::UserUnifiedExecInput | ExecCommandSource::Hook => return None,
+    }
+    let error = end.error.as_ref().map(|error| error.kind);
+    // Commands that never ran have nothing to report.
+    if matches!(
+        error,
+        Some(ExecErrorKind::Rejected | ExecErrorKind::InvalidWorkdir | ExecErrorKind::SpawnFailed)
+    ) {
+        return None;
+    }
+    let command = match extract_shell_command(&end.command) {
+        Some((_, script)) => script.to_string(),
+        None => shlex_join(&end.command),
+    };
+    Some(CommandDigest {
+        command: shorten(command),
+        exit_code: end.exit_code,
+        error,
+    })
+}
+
+fn shorten(command: String) -> String {
+    match command.char_indices().nth(MAX_COMMAND_CHARS) {
+        Some((cut, _)) => format!("{}…", &command[..cut]),
+        None => command,
+    }
+}
+
+fn patch_digests(end: &PatchApplyEndEvent) -> Vec<PatchDigest> {
+    let mut patches: Vec<PatchDigest> = end
+        .changes
+        .iter()
+        .map(|(path, change)| {
+            let (added, removed, move_path) = match change {
+                FileChange::Add { content } => (content.lines().count(), 0, None),
+                FileChange::Delete { content } => (0, content.lines().count(), None),
+                FileChange::Update {
+                    unified_diff,
+                    move_path,
+                } => {
+                    let (added, removed) = count_diff_lines(unified_diff);
+                    (added, removed, move_path.clone())
+                }
+            };
+            PatchDigest {
+                path: path.clone(),
+                kind: change.kind(),
+                move_path,
+                added,
+                removed,
+                applied: end.success,
+            }
+        })
+        .collect();
+    patches.sort_by(|a, b| a.path.cmp(&b.path));
+    patches
+}
+
+fn count_diff_lines(diff: &str) -> (usize, usize) {
+    let mut added = 0;
+    let mut removed = 0;
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    (added, removed)
+}
+
+/// Per-file line counts of a `git diff` style unified diff.
+fn diff_stats(unified_diff: &str) -> Vec<FileDiffStat> {
+    let mut files: Vec<FileDiffStat> = Vec::new();
+    for line in unified_diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header
+                .rsplit_once(" b/")
+                .map_or(header, |(_, path)| path)
+                .to_string();
+            files.push(FileDiffStat {
+                path,
+                added: 0,
+                removed: 0,
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if line.starts_with('+') {
+            file.added += 1;
+        } else if line.starts_with('-') {
+            file.removed += 1;
+        }
+    }
+    files
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::protocol::TurnDiffEvent;
+
+    fn exec_end(turn_id: &str, command: &[&str], exit_code: i32) -> EventMsg {
+        EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+            call_id: "call".to_string(),
+            process_id: None,
+            turn_id: turn_id.to_string(),
+            command: command.iter().map(ToString::to_string).collect(),
+            cwd: PathBuf::from("/repo"),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::ModelShell,
+            interaction_input: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            aggregated_output: String::new(),
+            exit_code,
+            outcome: None,
+            duration: Duration::from_millis(10),
+            estimated: false,
+            suspended: false,
+            formatted_output: String::new(),
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
+            saved_output_path: None,
+            approval: None,
+            error: None,
+        })
+    }
+
+    #[test]
+    fn records_commands_patches_and_the_last_diff_per_turn() {
+        let mut log = ToolDigestLog::default();
+        log.record(
+            "t1",
+            &exec_end("t1", &["bash", "-lc", "cargo test -p core"], 101),
+        );
+        log.record(
+            "t1",
+            &EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id: "patch".to_string(),
+                turn_id: "t1".to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+                success: true,
+                changes: HashMap::from([(
+                    PathBuf::from("src/lib.rs"),
+                    FileChange::Update {
+                        unified_diff: "--- a\n+++ b\n@@\n-old\n+new\n+more\n".to_string(),
+                        move_path: None,
+                    },
+                )]),
+            }),
+        );
+        for unified_diff in [
+            "diff --git a/x b/x\n+first\n",
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n-old\n+new\n+more\n",
+        ] {
+            log.record(
+                "t1",
+                &EventMsg::TurnDiff(TurnDiffEvent {
+                    turn_id: "t1".to_string(),
+                    unified_diff: unified_diff.to_string(),
+                }),
+            );
+        }
+        log.record("t2", &exec_end("", &["ls", "-la"], 0));
+
+        assert_eq!(
+            log.take(),
+            vec![
+                TurnDigest {
+                    turn_id: "t1".to_string(),
+                    commands: vec![CommandDigest {
+                        command: "cargo test -p core".to_string(),
+                        exit_code: 101,
+                        error: None,
+                    }],
+                    commands_omitted: 0,
+                    patches: vec![PatchDigest {
+                        path: PathBuf::from("src/lib.rs"),
+                        kind: FileChangeKind::Update,
+                        move_path: None,
+                        added: 2,
+                        removed: 1,
+                        applied: true,
+                    }],
+                    diff: vec![FileDiffStat {
+                        path: "src/lib.rs".to_string(),
+                        added: 2,
+                        removed: 1,
+                    }],
+                },
+                TurnDigest {
+                    turn_id: "t2".to_string(),
+                    commands: vec![CommandDigest {
+                        command: "ls -la".to_string(),
+                        exit_code: 0,
+                        error: None,
+                    }],
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(log.take(), Vec::new());
+    }
+
+    #[test]
+    fn digest_item_round_trips_and_merges_with_earlier_digests() {
+        let mut log = ToolDigestLog::default();
+        log.record("t1", &exec_end("t1", &["make"], 2));
+        let item = digest_item(Vec::new(), log.take()).expect("digest item");
+        let earlier = digests_in_history(&[item]);
+        assert_eq!(earlier.len(), 1);
+
+        // The same turn went on after a mid-turn compaction.
+        log.record("t1", &exec_end("t1", &["make", "test"], 0));
+        log.record("t2", &exec_end("t2", &["true"], 0));
+        let item = digest_item(earlier, log.take()).expect("digest item");
+        let turns = digests_in_history(&[item]);
+
+        assert_eq!(
+            turns
+                .iter()
+                .map(|turn| {
+                    let commands = turn
+                        .commands
+                        .iter()
+                        .map(|command| (command.command.as_str(), command.exit_code))
+                        .collect::<Vec<_>>();
+                    (turn.turn_id.as_str(), commands)
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                ("t1", vec![("make", 2), ("make test", 0)]),
+                ("t2", vec![("true", 0)]),
+            ]
+        );
+        assert_eq!(digest_item(Vec::new(), Vec::new()), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! How much of a turn's tokens, and of its cost, goes to tool output.
+//!
+//! When a tool call finishes, the output it sends back to the model is
+//! estimated in tokens as the history will keep it, truncated to the turn's
+//! limit, with the same byte heuristic the context manager uses. The turn's
+//! `TurnSummary` lists the estimate of every call next to the usage the
+//! provider reported, so the two can be compared, and `TokenCount` carries
+//! the session's total. With a price for the model under `[token_prices]`,
+//! the summary also carries approximate costs.
+
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+
+use crate::config::types::TokenPrice;
+use crate::context_manager::truncate_for_history;
+use crate::protocol::TokenUsage;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::approx_token_count;
+
+/// Estimated tokens of `output` once the history has truncated it to
+/// `policy`.
+pub(crate) fn estimate_output_tokens(output: &ResponseInputItem, policy: TruncationPolicy) -> u64 {
+    let tokens = match truncate_for_history(&ResponseItem::from(output.clone()), policy) {
+        ResponseItem::FunctionCallOutput { output, .. } => match &output.content_items {
+            Some(items) => items
+                .iter()
+                .map(|item| match item {
+                    FunctionCallOutputContentItem::InputText { text } => approx_token_count(text),
+                    FunctionCallOutputContentItem::InputImage { .. } => 0,
+                })
+                .sum(),
+            None => approx_token_count(&output.content),
+        },
+        ResponseItem::CustomToolCallOutput { output, .. } => approx_token_count(&output),
+        item => approx_token_count(&serde_json::to_string(&item).unwrap_or_default()),
+    };
+    tokens as u64
+}
+
+/// Approximate cost of a model request that used `usage`.
+pub(crate) fn usage_cost(price: &TokenPrice, usage: &TokenUsage) -> f64 {
+    let cached_input = price.cached_input.unwrap_or(price.input);
+    (usage.non_cached_input() as f64 * price.input
+        + usage.cached_input() as f64 * cached_input
+        + usage.output_tokens.max(0) as f64 * price.output)
+        / 1000.0
+}
+
+/// Approximate cost of sending `tokens` of uncached input to the model.
+pub(crate) fn input_cost(price: &TokenPrice, tokens: u64) -> f64 {
+    tokens as f64 * price.input / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn estimates_the_output_as_the_history_keeps_it() {
+        let output = |content: String| ResponseInputItem::FunctionCallOutput {
+            call_id: "call-1".to_string(),
+            output: FunctionCallOutputPayload {
+                content,
+                ..Default::default()
+            },
+        };
+        let policy = TruncationPolicy::Tokens(1_000);
+
+        assert_eq!(
+            estimate_output_tokens(&output("a".repeat(400)), policy),
+            100
+        );
+        let long = estimate_output_tokens(&output("a".repeat(400_000)), policy);
+        assert!(long < 2_000, "{long}");
+
+        let custom = ResponseInputItem::CustomToolCallOutput {
+            call_id: "call-2".to_string(),
+            output: "b".repeat(40),
+        };
+        assert_eq!(estimate_output_tokens(&custom, policy), 10);
+    }
+
+    #[test]
+    fn prices_cached_input_and_output_separately() {
+        let usage = TokenUsage {
+            input_tokens: 3_000,
+            cached_input_tokens: 1_000,
+            output_tokens: 500,
+            reasoning_output_tokens: 200,
+            total_tokens: 3_500,
+        };
+        let price = TokenPrice {
+            input: 0.002,
+            cached_input: Some(0.0005),
+            output: 0.008,
+        };
+        assert_eq!(usage_cost(&price, &usage), 0.0085);
+        assert_eq!(input_cost(&price, 500), 0.001);
+
+        let uncached = TokenPrice {
+            cached_input: None,
+            ..price
+        };
+        assert_eq!(usage_cost(&uncached, &usage), 0.01);
+    }
+}
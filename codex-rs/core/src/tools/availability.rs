@@ -0,0 +1,139 @@
+//! Per-model tool availability from `[[model_tools]]` config entries.
+
+use std::collections::BTreeMap;
+
+use wildmatch::WildMatchPattern;
+
+use crate::config::types::ModelToolsToml;
+use crate::tools::context::ToolPayload;
+
+/// Tool names that stop working when the `shell` tool is disabled, including
+/// the aliases kept for older prompts.
+const SHELL_TOOL_NAMES: &[&str] = &[
+    "shell",
+    "shell_command",
+    "container.exec",
+    "local_shell",
+    "exec_command",
+    "write_stdin",
+];
+
+/// Tool names backed by the `unified_exec` tool.
+const UNIFIED_EXEC_TOOL_NAMES: &[&str] = &["exec_command", "write_stdin"];
+
+/// Which tools and MCP servers the `[[model_tools]]` entries matching a model
+/// turn on or off. Tools without a setting keep their default availability.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ToolAvailability {
+    tools: BTreeMap<String, bool>,
+    mcp_servers: BTreeMap<String, bool>,
+}
+
+impl ToolAvailability {
+    /// Merges every entry whose `model` pattern matches `model_slug`, in
+    /// order, so later entries override earlier ones.
+    pub(crate) fn for_model(entries: &[ModelToolsToml], model_slug: &str) -> Self {
+        let mut availability = Self::default();
+        for entry in entries {
+            if !WildMatchPattern::<'*', '?'>::new(&entry.model).matches(model_slug) {
+                continue;
+            }
+            availability.tools.extend(entry.tools.clone());
+            availability.mcp_servers.extend(entry.mcp_servers.clone());
+        }
+        availability
+    }
+
+    pub(crate) fn is_enabled(&self, tool_name: &str) -> bool {
+        self.tools.get(tool_name) == Some(&true)
+    }
+
+    pub(crate) fn is_disabled(&self, tool_name: &str) -> bool {
+        self.tools.get(tool_name) == Some(&false)
+    }
+
+    pub(crate) fn is_mcp_server_disabled(&self, server_name: &str) -> bool {
+        self.mcp_servers.get(server_name) == Some(&false)
+    }
+
+    /// Returns `true` if a call to `tool_name` targets a tool this
+    /// configuration turned off, either directly, through the tool family it
+    /// belongs to, or through its MCP server.
+    pub(crate) fn blocks_call(&self, tool_name: &str, payload: &ToolPayload) -> bool {
+        if let ToolPayload::Mcp { server, .. } = payload
+            && self.is_mcp_server_disabled(server)
+        {
+            return true;
+        }
+        self.is_disabled(tool_name)
+            || (self.is_disabled("shell") && SHELL_TOOL_NAMES.contains(&tool_name))
+            || (self.is_disabled("unified_exec") && UNIFIED_EXEC_TOOL_NAMES.contains(&tool_name))
+    }
+}
+
+pub(crate) fn unavailable_tool_message(tool_name: &str) -> String {
+    format!("tool `{tool_name}` is not available in this configuration")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(model: &str, tools: &[(&str, bool)], mcp_servers: &[(&str, bool)]) -> ModelToolsToml {
+        ModelToolsToml {
+            model: model.to_string(),
+            tools: tools
+                .iter()
+                .map(|(name, enabled)| (name.to_string(), *enabled))
+                .collect(),
+            mcp_servers: mcp_servers
+                .iter()
+                .map(|(name, enabled)| (name.to_string(), *enabled))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn later_matching_entries_override_earlier_ones() {
+        let entries = vec![
+            entry(
+                "gpt-oss-*",
+                &[("web_search", false), ("apply_patch", false)],
+                &[("docs", false)],
+            ),
+            entry("gpt-oss-120b", &[("apply_patch", true)], &[]),
+            entry("o3", &[("view_image", false)], &[]),
+        ];
+
+        let availability = ToolAvailability::for_model(&entries, "gpt-oss-120b");
+        assert!(availability.is_disabled("web_search"));
+        assert!(availability.is_enabled("apply_patch"));
+        assert!(!availability.is_disabled("view_image"));
+        assert!(availability.is_mcp_server_disabled("docs"));
+
+        assert_eq!(
+            ToolAvailability::for_model(&entries, "gpt-5.1"),
+            ToolAvailability::default()
+        );
+    }
+
+    #[test]
+    fn blocks_calls_to_disabled_tool_families_and_servers() {
+        let entries = vec![entry("*", &[("unified_exec", false)], &[("docs", false)])];
+        let availability = ToolAvailability::for_model(&entries, "gpt-5.1");
+        let function = ToolPayload::Function {
+            arguments: "{}".to_string(),
+        };
+        let mcp = |server: &str| ToolPayload::Mcp {
+            server: server.to_string(),
+            tool: "search".to_string(),
+            raw_arguments: "{}".to_string(),
+        };
+
+        assert!(availability.blocks_call("exec_command", &function));
+        assert!(!availability.blocks_call("shell_command", &function));
+        assert!(availability.blocks_call("mcp__docs__search", &mcp("docs")));
+        assert!(!availability.blocks_call("mcp__other__search", &mcp("other")));
+    }
+}
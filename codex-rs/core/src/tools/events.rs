@@ -3,24 +3,69 @@ use crate::codex::TurnContext;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
+use crate::exec::OutputDeltaStats;
+use crate::features::Feature;
+use crate::file_access::command_writes;
+use crate::file_access::modified_time;
 use crate::function_tool::FunctionCallError;
-use crate::parse_command::parse_command;
+use crate::git_state;
+use crate::hooks::HookPayload;
+use crate::hooks::spawn_hook;
+use crate::path_utils::normalize_event_path;
 use crate::protocol::EventMsg;
+use crate::protocol::ExecApproval;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::ExecCommandSource;
+use crate::protocol::ExecErrorInfo;
+use crate::protocol::ExecErrorKind;
+use crate::protocol::ExecOutcome;
+use crate::protocol::ExecOutputReadBeginEvent;
+use crate::protocol::ExecOutputReadEndEvent;
 use crate::protocol::FileChange;
+use crate::protocol::FileChangeSummary;
+use crate::protocol::FileReadBeginEvent;
+use crate::protocol::FileReadEndEvent;
+use crate::protocol::ListDirBeginEvent;
+use crate::protocol::ListDirEndEvent;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpToolCallBeginEvent;
+use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallErrorSource;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::ProposedExecEvent;
+use crate::protocol::ProposedPatchEvent;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::WorkspaceSearchBeginEvent;
+use crate::protocol::WorkspaceSearchEndEvent;
+use crate::redact::redact_secrets;
+use crate::state::OpenExecCall;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::plan_only_output;
 use crate::tools::sandboxing::ToolError;
+use crate::tools::spill::spill_dir;
+use crate::tools::spill::spill_exec_output;
+use crate::tools::spill::spill_path;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
 use codex_protocol::parse_command::ParsedCommand;
+use codex_utils_string::take_bytes_at_char_boundary;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
+use std::time::Instant;
+use tracing::warn;
 
+use super::FormattedExecOutput;
 use super::format_exec_output_str;
 
 #[derive(Clone, Copy)]
@@ -48,61 +93,224 @@ impl<'a> ToolEventCtx<'a> {
 }
 
 pub(crate) enum ToolEventStage {
-    Begin,
     Success(ExecToolCallOutput),
     Failure(ToolEventFailure),
 }
 
 pub(crate) enum ToolEventFailure {
-    Output(ExecToolCallOutput),
-    Message(String),
+    /// The command ran, but timed out or was denied by the sandbox.
+    Output {
+        output: ExecToolCallOutput,
+        error: ExecErrorInfo,
+    },
+    /// The call failed before the command could run. `message` is what the
+    /// model sees.
+    Message {
+        kind: ExecErrorKind,
+        message: String,
+    },
+    /// The command never started or did not exit on its own. `message` is
+    /// what the model sees.
+    Abnormal {
+        outcome: ExecOutcome,
+        message: String,
+    },
 }
 
-pub(crate) async fn emit_exec_command_begin(
+fn exec_command_begin_event(
     ctx: ToolEventCtx<'_>,
-    command: &[String],
-    cwd: &Path,
-    parsed_cmd: &[ParsedCommand],
-    source: ExecCommandSource,
-    interaction_input: Option<String>,
-    process_id: Option<&str>,
-) {
+    exec: &ExecCommandInput,
+) -> ExecCommandBeginEvent {
+    ExecCommandBeginEvent {
+        call_id: ctx.call_id.to_string(),
+        process_id: exec.process_id.clone(),
+        turn_id: ctx.turn.sub_id.clone(),
+        command: exec.command.clone(),
+        cwd: exec.cwd.clone(),
+        parsed_cmd: exec.parsed_cmd.as_ref().clone(),
+        source: exec.source,
+        interaction_input: exec.interaction_input.clone(),
+        timeout_ms: exec.timeout_ms,
+        injected_env: exec.injected_env.clone(),
+    }
+}
+
+async fn emit_exec_command_begin(ctx: ToolEventCtx<'_>, exec: &ExecCommandInput) {
+    let _ = exec.started.set(Instant::now());
+    let begin = exec_command_begin_event(ctx, exec);
+    ctx.session.record_open_exec_call(&begin).await;
     ctx.session
-        .send_event(
-            ctx.turn,
-            EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
-                call_id: ctx.call_id.to_string(),
-                process_id: process_id.map(str::to_owned),
-                turn_id: ctx.turn.sub_id.clone(),
-                command: command.to_vec(),
-                cwd: cwd.to_path_buf(),
-                parsed_cmd: parsed_cmd.to_vec(),
-                source,
-                interaction_input,
-            }),
-        )
+        .send_event(ctx.turn, EventMsg::ExecCommandBegin(begin))
         .await;
 }
+
+/// The source an end event reports: a model command that only ran once the
+/// sandbox was lifted is an escalated retry.
+fn end_source(source: ExecCommandSource, approval: Option<ExecApproval>) -> ExecCommandSource {
+    match (source, approval) {
+        (
+            ExecCommandSource::ModelShell | ExecCommandSource::ModelUnifiedExec,
+            Some(ExecApproval::Escalated),
+        ) => ExecCommandSource::EscalatedRetry,
+        _ => source,
+    }
+}
+
+/// What the model and clients see for an exec call that was still open when
+/// its turn ended.
+const UNFINISHED_AT_TURN_END_MESSAGE: &str = "command did not finish before the turn ended";
+
+/// Sends end events for the exec calls of `turn` whose begin event was sent
+/// but whose end event never was, e.g. because the task running them was
+/// aborted, so no client is left showing them as running.
+/// Emit the turn's aggregated diff as a `TurnDiff` event.
+///
+/// The diff is computed outside the lock so concurrent patch begins are not
+/// blocked on reading and diffing every tracked file. It is sent under the
+/// lock, and only if no diff of a later snapshot went out first, so the last
+/// `TurnDiff` of a turn is its final diff.
+pub(crate) async fn emit_turn_diff(
+    session: &Session,
+    turn: &TurnContext,
+    tracker: &SharedTurnDiffTracker,
+) {
+    let snapshot = tracker.lock().await.snapshot();
+    let diff = snapshot.diff();
+    let mut tracker = tracker.lock().await;
+    if let Some(unified_diff) = tracker.apply_diff(diff) {
+        let msg = EventMsg::TurnDiff(TurnDiffEvent {
+            turn_id: turn.sub_id.clone(),
+            unified_diff,
+        });
+        session.send_event(turn, msg).await;
+    }
+}
+
+pub(crate) async fn close_open_exec_calls(session: &Session, turn: &TurnContext) {
+    for OpenExecCall { begin, started } in session.take_open_exec_calls(&turn.sub_id).await {
+        let approval = session.take_exec_approval(&begin.call_id).await;
+        warn!(
+            call_id = begin.call_id,
+            "exec call was still open at the end of its turn"
+        );
+        let message = UNFINISHED_AT_TURN_END_MESSAGE.to_string();
+        session
+            .send_event(
+                turn,
+                EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                    call_id: begin.call_id,
+                    process_id: begin.process_id,
+                    turn_id: begin.turn_id,
+                    command: begin.command,
+                    cwd: begin.cwd,
+                    parsed_cmd: begin.parsed_cmd,
+                    source: end_source(begin.source, approval),
+                    interaction_input: begin.interaction_input,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    aggregated_output: String::new(),
+                    exit_code: -1,
+                    outcome: None,
+                    duration: started.elapsed(),
+                    estimated: true,
+                    suspended: false,
+                    sanitized_bytes: None,
+                    delta_bytes_emitted: 0,
+                    deltas_incomplete: false,
+                    formatted_output: message.clone(),
+                    saved_output_path: None,
+                    approval,
+                    error: Some(ExecErrorInfo {
+                        kind: ExecErrorKind::Interrupted,
+                        message,
+                    }),
+                }),
+            )
+            .await;
+    }
+}
+
 // Concrete, allocation-free emitter: avoid trait objects and boxed futures.
+//
+// The begin event borrows the emitter and copies what it needs; the end event
+// consumes the emitter and takes its command, parsed command and cwd over, so
+// each call copies them once rather than once per event.
 pub(crate) enum ToolEmitter {
     Shell {
-        command: Vec<String>,
-        cwd: PathBuf,
-        source: ExecCommandSource,
-        parsed_cmd: Vec<ParsedCommand>,
+        exec: ExecCommandInput,
         freeform: bool,
     },
     ApplyPatch {
-        changes: HashMap<PathBuf, FileChange>,
+        changes: Arc<HashMap<PathBuf, FileChange>>,
         auto_approved: bool,
+        timeout_ms: Option<u64>,
     },
     UnifiedExec {
-        command: Vec<String>,
-        cwd: PathBuf,
-        source: ExecCommandSource,
-        parsed_cmd: Vec<ParsedCommand>,
-        process_id: Option<String>,
+        exec: ExecCommandInput,
     },
+    Mcp {
+        invocation: McpInvocation,
+        timeout_ms: Option<u64>,
+    },
+    ReadFile {
+        path: PathBuf,
+        start_line: usize,
+        end_line: Option<usize>,
+        /// Set when the begin event is emitted.
+        started: OnceLock<Instant>,
+    },
+    WorkspaceSearch {
+        query: String,
+        glob: Option<String>,
+        regex: bool,
+        /// Set when the begin event is emitted.
+        started: OnceLock<Instant>,
+    },
+    ListDir {
+        path: PathBuf,
+        depth: usize,
+        /// Set when the begin event is emitted.
+        started: OnceLock<Instant>,
+    },
+    ReadExecOutput {
+        source_call_id: String,
+        offset_lines: usize,
+        limit_lines: Option<usize>,
+        grep: Option<String>,
+        /// Set when the begin event is emitted.
+        started: OnceLock<Instant>,
+    },
+}
+
+/// What a finished `read_file` call returned, for its end event.
+pub(crate) struct FileReadSummary {
+    pub(crate) lines: usize,
+    pub(crate) bytes: usize,
+    pub(crate) truncated: bool,
+    pub(crate) binary: bool,
+}
+
+/// What a finished `search_workspace` call returned, for its end event.
+#[derive(Clone, Copy)]
+pub(crate) struct WorkspaceSearchSummary {
+    pub(crate) match_count: usize,
+    pub(crate) truncated: bool,
+}
+
+/// What a finished `list_dir` call returned, for its end event.
+#[derive(Clone, Copy)]
+pub(crate) struct ListDirSummary {
+    pub(crate) entry_count: usize,
+    pub(crate) truncated: bool,
+}
+
+/// What a finished `read_exec_output` call returned, for its end event.
+#[derive(Clone, Copy)]
+pub(crate) struct ExecOutputReadSummary {
+    pub(crate) lines: usize,
+    pub(crate) bytes: usize,
+    pub(crate) total_lines: usize,
+    pub(crate) truncated: bool,
 }
 
 impl ToolEmitter {
@@ -111,67 +319,128 @@ impl ToolEmitter {
         cwd: PathBuf,
         source: ExecCommandSource,
         freeform: bool,
+        timeout_ms: Option<u64>,
+        session: &Session,
     ) -> Self {
-        let parsed_cmd = parse_command(&command);
+        let parsed_cmd = session.parsed_command(&command);
         Self::Shell {
-            command,
-            cwd,
-            source,
-            parsed_cmd,
+            exec: ExecCommandInput {
+                command,
+                cwd: normalize_event_path(&cwd),
+                parsed_cmd,
+                source,
+                interaction_input: None,
+                process_id: None,
+                timeout_ms,
+                injected_env: BTreeMap::new(),
+                started: OnceLock::new(),
+            },
             freeform,
         }
     }
 
-    pub fn apply_patch(changes: HashMap<PathBuf, FileChange>, auto_approved: bool) -> Self {
+    pub fn apply_patch(
+        changes: Arc<HashMap<PathBuf, FileChange>>,
+        auto_approved: bool,
+        timeout_ms: Option<u64>,
+    ) -> Self {
         Self::ApplyPatch {
-            changes,
+            changes: normalize_change_paths(changes),
             auto_approved,
+            timeout_ms,
         }
     }
 
     pub fn unified_exec(
-        command: &[String],
+        command: Vec<String>,
         cwd: PathBuf,
         source: ExecCommandSource,
         process_id: Option<String>,
+        session: &Session,
     ) -> Self {
-        let parsed_cmd = parse_command(command);
+        let parsed_cmd = session.parsed_command(&command);
         Self::UnifiedExec {
-            command: command.to_vec(),
-            cwd,
-            source,
-            parsed_cmd,
-            process_id,
+            exec: ExecCommandInput {
+                command,
+                cwd: normalize_event_path(&cwd),
+                parsed_cmd,
+                source,
+                interaction_input: None,
+                process_id,
+                timeout_ms: None,
+                injected_env: BTreeMap::new(),
+                started: OnceLock::new(),
+            },
         }
     }
 
-    pub async fn emit(&self, ctx: ToolEventCtx<'_>, stage: ToolEventStage) {
-        match (self, stage) {
-            (
-                Self::Shell {
-                    command,
-                    cwd,
-                    source,
-                    parsed_cmd,
-                    ..
-                },
-                stage,
-            ) => {
-                emit_exec_stage(
-                    ctx,
-                    ExecCommandInput::new(command, cwd.as_path(), parsed_cmd, *source, None, None),
-                    stage,
-                )
-                .await;
-            }
+    /// Records the variables the call set on top of the policy environment
+    /// (values already redacted) on the begin event of an exec emitter.
+    pub fn with_injected_env(mut self, env: BTreeMap<String, String>) -> Self {
+        if let Self::Shell { exec, .. } | Self::UnifiedExec { exec } = &mut self {
+            exec.injected_env = env;
+        }
+        self
+    }
 
-            (
-                Self::ApplyPatch {
-                    changes,
-                    auto_approved,
-                },
-                ToolEventStage::Begin,
-            ) => {
+    pub fn mcp(invocation: McpInvocation, timeout_ms: Option<u64>) -> Self {
+        Self::Mcp {
+            invocation,
+            timeout_ms,
+        }
+    }
+
+    pub fn read_file(path: PathBuf, start_line: usize, end_line: Option<usize>) -> Self {
+        Self::ReadFile {
+            path: normalize_event_path(&path),
+            start_line,
+            end_line,
+            started: OnceLock::new(),
+        }
+    }
+
+    pub fn workspace_search(query: String, glob: Option<String>, regex: bool) -> Self {
+        Self::WorkspaceSearch {
+            query,
+            glob,
+            regex,
+            started: OnceLock::new(),
+        }
+    }
+
+    pub fn list_dir(path: PathBuf, depth: usize) -> Self {
+        Self::ListDir {
+            path: normalize_event_path(&path),
+            depth,
+            started: OnceLock::new(),
+        }
+    }
+
+    pub fn read_exec_output(
+        source_call_id: String,
+        offset_lines: usize,
+        limit_lines: Option<usize>,
+        grep: Option<String>,
+    ) -> Self {
+        Self::ReadExecOutput {
+            source_call_id,
+            offset_lines,
+            limit_lines,
+            grep,
+            started: OnceLock::new(),
+        }
+    }
+
+    pub async fn begin(&self, ctx: ToolEventCtx<'_>) {
+        match self {
+            Self::Shell { exec, .. } | Self::UnifiedExec { exec } => {
+                emit_exec_command_begin(ctx, exec).await;
+            }
+            Self::ApplyPatch {
+                changes,
+                auto_approved,
+                timeout_ms,
+            } => {
                 if let Some(tracker) = ctx.turn_diff_tracker {
                     let mut guard = tracker.lock().await;
                     guard.on_patch_begin(changes);
@@ -179,174 +448,613 @@ impl ToolEmitter {
                 ctx.session
                     .send_event(
                         ctx.turn,
-                        EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                        EventMsg::PatchApplyBegin(patch_apply_begin_event(
+                            ctx,
+                            changes,
+                            *auto_approved,
+                            *timeout_ms,
+                        )),
+                    )
+                    .await;
+            }
+            Self::Mcp {
+                invocation,
+                timeout_ms,
+            } => {
+                let arguments = invocation
+                    .arguments
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                ctx.session
+                    .send_event(
+                        ctx.turn,
+                        EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+                            call_id: ctx.call_id.to_string(),
+                            invocation: invocation.clone(),
+                            timeout_ms: *timeout_ms,
+                            arguments_preview: mcp_preview(ctx.turn, &arguments),
+                        }),
+                    )
+                    .await;
+            }
+            Self::ReadFile {
+                path,
+                start_line,
+                end_line,
+                started,
+            } => {
+                let _ = started.set(Instant::now());
+                ctx.session
+                    .send_event(
+                        ctx.turn,
+                        EventMsg::FileReadBegin(FileReadBeginEvent {
                             call_id: ctx.call_id.to_string(),
                             turn_id: ctx.turn.sub_id.clone(),
-                            auto_approved: *auto_approved,
-                            changes: changes.clone(),
+                            path: path.clone(),
+                            start_line: *start_line,
+                            end_line: *end_line,
                         }),
                     )
                     .await;
             }
-            (Self::ApplyPatch { changes, .. }, ToolEventStage::Success(output)) => {
-                emit_patch_end(
-                    ctx,
-                    changes.clone(),
-                    output.stdout.text.clone(),
-                    output.stderr.text.clone(),
-                    output.exit_code == 0,
-                )
-                .await;
+            Self::WorkspaceSearch {
+                query,
+                glob,
+                regex,
+                started,
+            } => {
+                let _ = started.set(Instant::now());
+                ctx.session
+                    .send_event(
+                        ctx.turn,
+                        EventMsg::WorkspaceSearchBegin(WorkspaceSearchBeginEvent {
+                            call_id: ctx.call_id.to_string(),
+                            turn_id: ctx.turn.sub_id.clone(),
+                            query: query.clone(),
+                            glob: glob.clone(),
+                            regex: *regex,
+                        }),
+                    )
+                    .await;
             }
-            (
-                Self::ApplyPatch { changes, .. },
-                ToolEventStage::Failure(ToolEventFailure::Output(output)),
-            ) => {
-                emit_patch_end(
-                    ctx,
-                    changes.clone(),
-                    output.stdout.text.clone(),
-                    output.stderr.text.clone(),
-                    output.exit_code == 0,
-                )
-                .await;
+            Self::ListDir {
+                path,
+                depth,
+                started,
+            } => {
+                let _ = started.set(Instant::now());
+                ctx.session
+                    .send_event(
+                        ctx.turn,
+                        EventMsg::ListDirBegin(ListDirBeginEvent {
+                            call_id: ctx.call_id.to_string(),
+                            turn_id: ctx.turn.sub_id.clone(),
+                            path: path.clone(),
+                            depth: *depth,
+                        }),
+                    )
+                    .await;
             }
-            (
-                Self::ApplyPatch { changes, .. },
-                ToolEventStage::Failure(ToolEventFailure::Message(message)),
-            ) => {
-                emit_patch_end(
+            Self::ReadExecOutput {
+                source_call_id,
+                offset_lines,
+                limit_lines,
+                grep,
+                started,
+            } => {
+                let _ = started.set(Instant::now());
+                ctx.session
+                    .send_event(
+                        ctx.turn,
+                        EventMsg::ExecOutputReadBegin(ExecOutputReadBeginEvent {
+                            call_id: ctx.call_id.to_string(),
+                            turn_id: ctx.turn.sub_id.clone(),
+                            source_call_id: source_call_id.clone(),
+                            offset_lines: *offset_lines,
+                            limit_lines: *limit_lines,
+                            grep: grep.clone(),
+                        }),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Reports a command or patch of a plan-only turn as `ProposedExec` or
+    /// `ProposedPatch`, with the payload its begin event would have had, in
+    /// place of running it. Returns what the model is told instead of the
+    /// result. Nothing is recorded that could run the call later.
+    pub async fn propose(&self, ctx: ToolEventCtx<'_>) -> ToolOutput {
+        let msg = match self {
+            Self::Shell { exec, .. } | Self::UnifiedExec { exec } => {
+                Some(EventMsg::ProposedExec(ProposedExecEvent {
+                    command: exec_command_begin_event(ctx, exec),
+                    plan_only: ctx.turn.plan_only,
+                }))
+            }
+            Self::ApplyPatch {
+                changes,
+                auto_approved,
+                timeout_ms,
+            } => Some(EventMsg::ProposedPatch(ProposedPatchEvent {
+                patch: patch_apply_begin_event(ctx, changes, *auto_approved, *timeout_ms),
+                plan_only: ctx.turn.plan_only,
+            })),
+            // The registry answers these calls itself in plan-only mode.
+            Self::Mcp { .. }
+            | Self::ReadFile { .. }
+            | Self::WorkspaceSearch { .. }
+            | Self::ListDir { .. }
+            | Self::ReadExecOutput { .. } => None,
+        };
+        if let Some(msg) = msg {
+            ctx.session.send_event(ctx.turn, msg).await;
+        }
+        plan_only_output()
+    }
+
+    /// Emits the end event for `stage`, moving the call's details into it.
+    pub async fn end(self, ctx: ToolEventCtx<'_>, stage: ToolEventStage) {
+        self.end_with_formatted_output(ctx, stage, None, None).await;
+    }
+
+    /// Like [`Self::end`], reusing the `formatted_output` already rendered
+    /// for the model and naming the file the full output of a shell call was
+    /// saved to.
+    async fn end_with_formatted_output(
+        self,
+        ctx: ToolEventCtx<'_>,
+        stage: ToolEventStage,
+        formatted_output: Option<String>,
+        saved_output_path: Option<PathBuf>,
+    ) {
+        match self {
+            Self::Shell { exec, .. } => {
+                let exec_result = ExecCommandResult::from_stage(
                     ctx,
-                    changes.clone(),
-                    String::new(),
-                    (*message).to_string(),
-                    false,
-                )
-                .await;
+                    stage,
+                    exec.elapsed(),
+                    formatted_output,
+                    saved_output_path,
+                );
+                emit_exec_end(ctx, exec, exec_result).await;
             }
-            (
-                Self::UnifiedExec {
-                    command,
-                    cwd,
-                    source,
-                    parsed_cmd,
-                    process_id,
-                },
-                stage,
-            ) => {
-                emit_exec_stage(
+            Self::UnifiedExec { exec } => {
+                let exec_result = ExecCommandResult::from_stage(
                     ctx,
-                    ExecCommandInput::new(
-                        command,
-                        cwd.as_path(),
-                        parsed_cmd,
-                        *source,
-                        None,
-                        process_id.as_deref(),
-                    ),
                     stage,
-                )
-                .await;
+                    exec.elapsed(),
+                    formatted_output,
+                    None,
+                );
+                emit_exec_end(ctx, exec, exec_result).await;
             }
+            Self::ApplyPatch { changes, .. } => {
+                let (stdout, stderr, success) = match stage {
+                    ToolEventStage::Success(output)
+                    | ToolEventStage::Failure(ToolEventFailure::Output { output, .. }) => (
+                        output.stdout.text,
+                        output.stderr.text,
+                        output.exit_code == 0,
+                    ),
+                    ToolEventStage::Failure(
+                        ToolEventFailure::Message { message, .. }
+                        | ToolEventFailure::Abnormal { message, .. },
+                    ) => (String::new(), message, false),
+                };
+                emit_patch_end(ctx, Arc::unwrap_or_clone(changes), stdout, stderr, success).await;
+            }
+            // MCP calls, file reads, searches, listings and output reads
+            // produce no exec output; they end through `finish_mcp`,
+            // `finish_read_file`, `finish_workspace_search`,
+            // `finish_list_dir` and `finish_read_exec_output`.
+            Self::Mcp { .. }
+            | Self::ReadFile { .. }
+            | Self::WorkspaceSearch { .. }
+            | Self::ListDir { .. }
+            | Self::ReadExecOutput { .. } => {}
         }
     }
 
-    pub async fn begin(&self, ctx: ToolEventCtx<'_>) {
-        self.emit(ctx, ToolEventStage::Begin).await;
-    }
-
-    fn format_exec_output_for_model(
+    /// Formats `output` for the model and the end event, spilling it to disk
+    /// first when it is too large. Also returns the spill file, if one was
+    /// written.
+    async fn format_exec_output(
         &self,
         output: &ExecToolCallOutput,
         ctx: ToolEventCtx<'_>,
-    ) -> String {
-        match self {
-            Self::Shell { freeform: true, .. } => {
-                super::format_exec_output_for_model_freeform(output, ctx.turn.truncation_policy)
+    ) -> (FormattedExecOutput, Option<PathBuf>) {
+        let (spilled, saved_output_path) = match self {
+            Self::Shell { .. } => {
+                let spill_dir = spill_dir(ctx.turn, &ctx.session.conversation_id.to_string());
+                let spilled = spill_exec_output(
+                    output,
+                    ctx.turn.output_limits.spill_threshold,
+                    &spill_dir,
+                    ctx.call_id,
+                )
+                .await;
+                let path = spilled
+                    .is_some()
+                    .then(|| spill_path(&spill_dir, ctx.call_id));
+                (spilled, path)
             }
-            _ => super::format_exec_output_for_model_structured(output, ctx.turn.truncation_policy),
-        }
+            Self::ApplyPatch { .. }
+            | Self::UnifiedExec { .. }
+            | Self::Mcp { .. }
+            | Self::ReadFile { .. }
+            | Self::WorkspaceSearch { .. }
+            | Self::ListDir { .. }
+            | Self::ReadExecOutput { .. } => (None, None),
+        };
+        let freeform = matches!(self, Self::Shell { freeform: true, .. });
+        let formatted = FormattedExecOutput::new(
+            output,
+            spilled.as_ref(),
+            freeform,
+            ctx.turn.exec_output_truncation_policy(),
+        );
+        (formatted, saved_output_path)
     }
 
     pub async fn finish(
-        &self,
+        self,
         ctx: ToolEventCtx<'_>,
         out: Result<ExecToolCallOutput, ToolError>,
     ) -> Result<String, FunctionCallError> {
-        let (event, result) = match out {
+        let (event, result, formatted_output, saved_output_path) = match out {
             Ok(output) => {
-                let content = self.format_exec_output_for_model(&output, ctx);
+                let (formatted, saved_output_path) = self.format_exec_output(&output, ctx).await;
                 let exit_code = output.exit_code;
                 let event = ToolEventStage::Success(output);
                 let result = if exit_code == 0 {
-                    Ok(content)
+                    Ok(formatted.model)
                 } else {
-                    Err(FunctionCallError::RespondToModel(content))
+                    Err(FunctionCallError::RespondToModel(formatted.model))
+                };
+                (event, result, Some(formatted.ui), saved_output_path)
+            }
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
+                let (formatted, saved_output_path) = self.format_exec_output(&output, ctx).await;
+                let error = ExecErrorInfo {
+                    kind: ExecErrorKind::Timeout,
+                    message: format!(
+                        "command timed out after {} milliseconds",
+                        output.duration.as_millis()
+                    ),
+                };
+                let event = ToolEventStage::Failure(ToolEventFailure::Output {
+                    output: *output,
+                    error,
+                });
+                let result = Err(FunctionCallError::RespondToModel(formatted.model));
+                (event, result, Some(formatted.ui), saved_output_path)
+            }
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
+                let (formatted, saved_output_path) = self.format_exec_output(&output, ctx).await;
+                let error = ExecErrorInfo {
+                    kind: ExecErrorKind::SandboxDenied,
+                    message: "the sandbox denied the command".to_string(),
                 };
-                (event, result)
+                let event = ToolEventStage::Failure(ToolEventFailure::Output {
+                    output: *output,
+                    error,
+                });
+                let result = Err(FunctionCallError::RespondToModel(formatted.model));
+                (event, result, Some(formatted.ui), saved_output_path)
             }
-            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
-            | Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
-                let response = self.format_exec_output_for_model(&output, ctx);
-                let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
-                let result = Err(FunctionCallError::RespondToModel(response));
-                (event, result)
+            Err(ToolError::Codex(CodexErr::ExecSpawnFailed { kind, message })) => {
+                let outcome = ExecOutcome::SpawnFailed {
+                    error_kind: kind,
+                    message: message.clone(),
+                };
+                let event = ToolEventStage::Failure(ToolEventFailure::Abnormal {
+                    outcome,
+                    message: message.clone(),
+                });
+                let result = Err(FunctionCallError::RespondToModel(message));
+                (event, result, None, None)
+            }
+            Err(ToolError::Codex(err @ CodexErr::Sandbox(SandboxErr::Signal(signal)))) => {
+                let message = format!("execution error: {err:?}");
+                let event = ToolEventStage::Failure(ToolEventFailure::Abnormal {
+                    outcome: ExecOutcome::Killed { signal },
+                    message: message.clone(),
+                });
+                let result = Err(FunctionCallError::RespondToModel(message));
+                (event, result, None, None)
             }
             Err(ToolError::Codex(err)) => {
                 let message = format!("execution error: {err:?}");
-                let event = ToolEventStage::Failure(ToolEventFailure::Message(message.clone()));
+                let event = ToolEventStage::Failure(ToolEventFailure::Message {
+                    kind: ExecErrorKind::Internal,
+                    message: message.clone(),
+                });
                 let result = Err(FunctionCallError::RespondToModel(message));
-                (event, result)
+                (event, result, None, None)
             }
-            Err(ToolError::Rejected(msg)) => {
-                // Normalize common rejection messages for exec tools so tests and
-                // users see a clear, consistent phrase.
-                let normalized = if msg == "rejected by user" {
-                    match self {
-                        Self::Shell { .. } | Self::UnifiedExec { .. } => {
-                            "exec command rejected by user".to_string()
-                        }
-                        Self::ApplyPatch { .. } => "patch rejected by user".to_string(),
-                    }
-                } else {
-                    msg
+            Err(ToolError::Rejected(reason)) => {
+                let subject = match &self {
+                    Self::Shell { .. } | Self::UnifiedExec { .. } => Some("exec command"),
+                    Self::ApplyPatch { .. } => Some("patch"),
+                    Self::Mcp { .. }
+                    | Self::ReadFile { .. }
+                    | Self::WorkspaceSearch { .. }
+                    | Self::ListDir { .. }
+                    | Self::ReadExecOutput { .. } => None,
                 };
-                let event = ToolEventStage::Failure(ToolEventFailure::Message(normalized.clone()));
-                let result = Err(FunctionCallError::RespondToModel(normalized));
-                (event, result)
+                let message = reason.message_for(subject);
+                let event = ToolEventStage::Failure(ToolEventFailure::Message {
+                    kind: ExecErrorKind::Rejected,
+                    message: message.clone(),
+                });
+                let result = Err(FunctionCallError::RespondToModel(message));
+                (event, result, None, None)
             }
         };
-        self.emit(ctx, event).await;
+        self.end_with_formatted_output(ctx, event, formatted_output, saved_output_path)
+            .await;
         result
     }
+
+    /// Ends a shell call that exited successfully with `summary` as the
+    /// `formatted_output` of its end event, for tools that parse the output
+    /// into a structured result instead of showing it as is.
+    pub(crate) async fn finish_summarized(
+        self,
+        ctx: ToolEventCtx<'_>,
+        output: ExecToolCallOutput,
+        summary: String,
+    ) {
+        self.end_with_formatted_output(ctx, ToolEventStage::Success(output), Some(summary), None)
+            .await;
+    }
+
+    /// Emits `McpToolCallEnd` for a call started with [`ToolEmitter::mcp`].
+    /// `Err` results are transport failures; results the server flagged with
+    /// `isError` are tool errors.
+    pub async fn finish_mcp(
+        self,
+        ctx: ToolEventCtx<'_>,
+        duration: Duration,
+        result: &Result<CallToolResult, String>,
+    ) {
+        self.emit_mcp_end(ctx, duration, result, McpToolCallErrorSource::Transport)
+            .await;
+    }
+
+    /// Like [`Self::finish_mcp`] for a call that was cancelled because its
+    /// turn was interrupted.
+    pub async fn abort_mcp(self, ctx: ToolEventCtx<'_>, duration: Duration, message: String) {
+        self.emit_mcp_end(
+            ctx,
+            duration,
+            &Err(message),
+            McpToolCallErrorSource::Aborted,
+        )
+        .await;
+    }
+
+    /// Emits `FileReadEnd` for a call started with [`ToolEmitter::read_file`].
+    pub async fn finish_read_file(
+        self,
+        ctx: ToolEventCtx<'_>,
+        result: Result<&FileReadSummary, &FunctionCallError>,
+    ) {
+        let Self::ReadFile { path, started, .. } = self else {
+            return;
+        };
+        let duration = started.get().map_or(Duration::ZERO, Instant::elapsed);
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        ctx.session
+            .send_event(
+                ctx.turn,
+                EventMsg::FileReadEnd(FileReadEndEvent {
+                    call_id: ctx.call_id.to_string(),
+                    turn_id: ctx.turn.sub_id.clone(),
+                    path,
+                    lines: summary.map_or(0, |summary| summary.lines),
+                    bytes: summary.map_or(0, |summary| summary.bytes),
+                    truncated: summary.is_some_and(|summary| summary.truncated),
+                    binary: summary.is_some_and(|summary| summary.binary),
+                    duration,
+                    error,
+                }),
+            )
+            .await;
+    }
+
+    /// Emits `WorkspaceSearchEnd` for a call started with
+    /// [`ToolEmitter::workspace_search`].
+    pub async fn finish_workspace_search(
+        self,
+        ctx: ToolEventCtx<'_>,
+        result: Result<WorkspaceSearchSummary, &FunctionCallError>,
+    ) {
+        let Self::WorkspaceSearch { query, started, .. } = self else {
+            return;
+        };
+        let duration = started.get().map_or(Duration::ZERO, Instant::elapsed);
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        ctx.session
+            .send_event(
+                ctx.turn,
+                EventMsg::WorkspaceSearchEnd(WorkspaceSearchEndEvent {
+                    call_id: ctx.call_id.to_string(),
+                    turn_id: ctx.turn.sub_id.clone(),
+                    query,
+                    match_count: summary.map_or(0, |summary| summary.match_count),
+                    truncated: summary.is_some_and(|summary| summary.truncated),
+                    duration,
+                    error,
+                }),
+            )
+            .await;
+    }
+
+    /// Emits `ListDirEnd` for a call started with [`ToolEmitter::list_dir`].
+    pub async fn finish_list_dir(
+        self,
+        ctx: ToolEventCtx<'_>,
+        result: Result<ListDirSummary, &FunctionCallError>,
+    ) {
+        let Self::ListDir { path, started, .. } = self else {
+            return;
+        };
+        let duration = started.get().map_or(Duration::ZERO, Instant::elapsed);
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        ctx.session
+            .send_event(
+                ctx.turn,
+                EventMsg::ListDirEnd(ListDirEndEvent {
+                    call_id: ctx.call_id.to_string(),
+                    turn_id: ctx.turn.sub_id.clone(),
+                    path,
+                    entry_count: summary.map_or(0, |summary| summary.entry_count),
+                    truncated: summary.is_some_and(|summary| summary.truncated),
+                    duration,
+                    error,
+                }),
+            )
+            .await;
+    }
+
+    /// Emits `ExecOutputReadEnd` for a call started with
+    /// [`ToolEmitter::read_exec_output`].
+    pub async fn finish_read_exec_output(
+        self,
+        ctx: ToolEventCtx<'_>,
+        result: Result<ExecOutputReadSummary, &FunctionCallError>,
+    ) {
+        let Self::ReadExecOutput {
+            source_call_id,
+            started,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let duration = started.get().map_or(Duration::ZERO, Instant::elapsed);
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        ctx.session
+            .send_event(
+                ctx.turn,
+                EventMsg::ExecOutputReadEnd(ExecOutputReadEndEvent {
+                    call_id: ctx.call_id.to_string(),
+                    turn_id: ctx.turn.sub_id.clone(),
+                    source_call_id,
+                    lines: summary.map_or(0, |summary| summary.lines),
+                    bytes: summary.map_or(0, |summary| summary.bytes),
+                    total_lines: summary.map_or(0, |summary| summary.total_lines),
+                    truncated: summary.is_some_and(|summary| summary.truncated),
+                    duration,
+                    error,
+                }),
+            )
+            .await;
+    }
+
+    async fn emit_mcp_end(
+        self,
+        ctx: ToolEventCtx<'_>,
+        duration: Duration,
+        result: &Result<CallToolResult, String>,
+        failure_source: McpToolCallErrorSource,
+    ) {
+        let Self::Mcp { invocation, .. } = self else {
+            return;
+        };
+        let (error_source, result_text) = match result {
+            Ok(result) if result.is_error.unwrap_or(false) => (
+                Some(McpToolCallErrorSource::Tool),
+                call_tool_result_text(result),
+            ),
+            Ok(result) => (None, call_tool_result_text(result)),
+            Err(message) => (Some(failure_source), message.clone()),
+        };
+        ctx.session
+            .send_event(
+                ctx.turn,
+                EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+                    call_id: ctx.call_id.to_string(),
+                    invocation,
+                    duration,
+                    result: result.clone(),
+                    is_error: error_source.is_some(),
+                    error_source,
+                    result_preview: mcp_preview(ctx.turn, &result_text),
+                }),
+            )
+            .await;
+    }
 }
 
-struct ExecCommandInput<'a> {
-    command: &'a [String],
-    cwd: &'a Path,
-    parsed_cmd: &'a [ParsedCommand],
+/// Redacts secrets in `text`, then truncates it to
+/// `output_limits.event_field_bytes`, or to the budget for command output
+/// sent to the model when that is unset.
+fn mcp_preview(turn: &TurnContext, text: &str) -> String {
+    let policy = turn.output_limits.event_field_bytes.map_or_else(
+        || turn.exec_output_truncation_policy(),
+        TruncationPolicy::Bytes,
+    );
+    truncate_text(&redact_secrets(text), policy)
+}
+
+/// Text content of an MCP tool result, with other content blocks summarized.
+/// Falls back to the structured content when there are no content blocks.
+fn call_tool_result_text(result: &CallToolResult) -> String {
+    if result.content.is_empty() {
+        return result
+            .structured_content
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+    }
+    result
+        .content
+        .iter()
+        .map(|block| match block {
+            ContentBlock::TextContent(text) => text.text.clone(),
+            ContentBlock::ImageContent(image) => format!("[image: {}]", image.mime_type),
+            ContentBlock::AudioContent(audio) => format!("[audio: {}]", audio.mime_type),
+            ContentBlock::ResourceLink(link) => format!("[resource link: {}]", link.uri),
+            ContentBlock::EmbeddedResource(_) => "[embedded resource]".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// What the begin and end events of an exec call share.
+pub(crate) struct ExecCommandInput {
+    command: Vec<String>,
+    cwd: PathBuf,
+    parsed_cmd: Arc<Vec<ParsedCommand>>,
     source: ExecCommandSource,
-    interaction_input: Option<&'a str>,
-    process_id: Option<&'a str>,
+    interaction_input: Option<String>,
+    process_id: Option<String>,
+    timeout_ms: Option<u64>,
+    injected_env: BTreeMap<String, String>,
+    /// Set when the begin event is emitted.
+    started: OnceLock<Instant>,
 }
 
-impl<'a> ExecCommandInput<'a> {
-    fn new(
-        command: &'a [String],
-        cwd: &'a Path,
-        parsed_cmd: &'a [ParsedCommand],
-        source: ExecCommandSource,
-        interaction_input: Option<&'a str>,
-        process_id: Option<&'a str>,
-    ) -> Self {
-        Self {
-            command,
-            cwd,
-            parsed_cmd,
-            source,
-            interaction_input,
-            process_id,
-        }
+impl ExecCommandInput {
+    /// Time since the begin event, or zero if it was never emitted.
+    fn elapsed(&self) -> Duration {
+        self.started.get().map_or(Duration::ZERO, Instant::elapsed)
     }
 }
 
@@ -355,81 +1063,341 @@ struct ExecCommandResult {
     stderr: String,
     aggregated_output: String,
     exit_code: i32,
+    outcome: Option<ExecOutcome>,
     duration: Duration,
+    estimated: bool,
+    suspended: bool,
+    deltas: OutputDeltaStats,
     formatted_output: String,
+    saved_output_path: Option<PathBuf>,
+    error: Option<ExecErrorInfo>,
 }
 
-async fn emit_exec_stage(
-    ctx: ToolEventCtx<'_>,
-    exec_input: ExecCommandInput<'_>,
-    stage: ToolEventStage,
-) {
-    match stage {
-        ToolEventStage::Begin => {
-            emit_exec_command_begin(
-                ctx,
-                exec_input.command,
-                exec_input.cwd,
-                exec_input.parsed_cmd,
-                exec_input.source,
-                exec_input.interaction_input.map(str::to_owned),
-                exec_input.process_id,
-            )
-            .await;
-        }
-        ToolEventStage::Success(output)
-        | ToolEventStage::Failure(ToolEventFailure::Output(output)) => {
-            let exec_result = ExecCommandResult {
-                stdout: output.stdout.text.clone(),
-                stderr: output.stderr.text.clone(),
-                aggregated_output: output.aggregated_output.text.clone(),
-                exit_code: output.exit_code,
-                duration: output.duration,
-                formatted_output: format_exec_output_str(&output, ctx.turn.truncation_policy),
-            };
-            emit_exec_end(ctx, exec_input, exec_result).await;
+impl ExecCommandResult {
+    /// `elapsed` is the time since the begin event, reported for failures
+    /// that produced no output of their own.
+    fn from_stage(
+        ctx: ToolEventCtx<'_>,
+        stage: ToolEventStage,
+        elapsed: Duration,
+        formatted_output: Option<String>,
+        saved_output_path: Option<PathBuf>,
+    ) -> Self {
+        let (output, error) = match stage {
+            ToolEventStage::Success(output) => (output, None),
+            ToolEventStage::Failure(ToolEventFailure::Output { output, error }) => {
+                (output, Some(error))
+            }
+            ToolEventStage::Failure(ToolEventFailure::Message { kind, message }) => {
+                return Self::without_output(kind, message, None, elapsed);
+            }
+            ToolEventStage::Failure(ToolEventFailure::Abnormal { outcome, message }) => {
+                let kind = match outcome {
+                    ExecOutcome::SpawnFailed { .. } => ExecErrorKind::SpawnFailed,
+                    ExecOutcome::TimedOut => ExecErrorKind::Timeout,
+                    ExecOutcome::Killed { .. } => ExecErrorKind::Killed,
+                    ExecOutcome::Spawned { .. } => ExecErrorKind::Internal,
+                };
+                return Self::without_output(kind, message, Some(outcome), elapsed);
+            }
+        };
+        let formatted_output = formatted_output.unwrap_or_else(|| {
+            format_exec_output_str(&output, ctx.turn.exec_output_truncation_policy())
+        });
+        Self {
+            outcome: Some(output.outcome()),
+            stdout: output.stdout.text,
+            stderr: output.stderr.text,
+            aggregated_output: output.aggregated_output.text,
+            exit_code: output.exit_code,
+            duration: output.duration,
+            estimated: false,
+            suspended: output.suspended,
+            deltas: output.deltas,
+            formatted_output,
+            saved_output_path,
+            error,
         }
-        ToolEventStage::Failure(ToolEventFailure::Message(message)) => {
-            let text = message.to_string();
-            let exec_result = ExecCommandResult {
-                stdout: String::new(),
-                stderr: text.clone(),
-                aggregated_output: text.clone(),
-                exit_code: -1,
-                duration: Duration::ZERO,
-                formatted_output: text,
-            };
-            emit_exec_end(ctx, exec_input, exec_result).await;
+    }
+
+    /// A call that produced no output of its own, reported with `message`
+    /// and the time it took as seen from outside the child process.
+    fn without_output(
+        kind: ExecErrorKind,
+        message: String,
+        outcome: Option<ExecOutcome>,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            stdout: String::new(),
+            stderr: String::new(),
+            aggregated_output: String::new(),
+            exit_code: -1,
+            outcome,
+            duration,
+            estimated: true,
+            suspended: false,
+            deltas: OutputDeltaStats::default(),
+            formatted_output: message.clone(),
+            saved_output_path: None,
+            error: Some(ExecErrorInfo { kind, message }),
         }
     }
 }
 
 async fn emit_exec_end(
     ctx: ToolEventCtx<'_>,
-    exec_input: ExecCommandInput<'_>,
+    exec: ExecCommandInput,
     exec_result: ExecCommandResult,
 ) {
+    let event_field_bytes = ctx.turn.output_limits.event_field_bytes;
+    let approval = ctx.session.take_exec_approval(ctx.call_id).await;
+    ctx.session.close_exec_call(ctx.call_id).await;
+    ctx.session
+        .record_exec_time(&ctx.turn.sub_id, exec_result.duration)
+        .await;
+    let succeeded = exec_result.exit_code == 0;
+    if succeeded {
+        record_command_file_access(ctx.session, &exec).await;
+    }
+    let git_check = succeeded.then(|| {
+        let by_user = !matches!(
+            exec.source,
+            ExecCommandSource::ModelShell | ExecCommandSource::ModelUnifiedExec
+        );
+        (exec.command.clone(), Arc::clone(&exec.parsed_cmd), by_user)
+    });
+    let ExecCommandResult {
+        mut stdout,
+        mut stderr,
+        mut aggregated_output,
+        mut formatted_output,
+        ..
+    } = exec_result;
+    let sanitized_bytes = [
+        &mut stdout,
+        &mut stderr,
+        &mut aggregated_output,
+        &mut formatted_output,
+    ]
+    .into_iter()
+    .map(sanitize_event_text)
+    .sum();
+    if ctx.session.enabled(Feature::ReadExecOutput) {
+        ctx.session.services.exec_outputs.lock().await.record(
+            ctx.call_id,
+            &exec.command,
+            exec_result.saved_output_path.clone(),
+            &aggregated_output,
+        );
+    }
     ctx.session
+        .record_tool_output_bytes(formatted_output.len())
+        .await;
+    let delivered = ctx
+        .session
         .send_event(
             ctx.turn,
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
                 call_id: ctx.call_id.to_string(),
-                process_id: exec_input.process_id.map(str::to_owned),
+                process_id: exec.process_id,
                 turn_id: ctx.turn.sub_id.clone(),
-                command: exec_input.command.to_vec(),
-                cwd: exec_input.cwd.to_path_buf(),
-                parsed_cmd: exec_input.parsed_cmd.to_vec(),
-                source: exec_input.source,
-                interaction_input: exec_input.interaction_input.map(str::to_owned),
-                stdout: exec_result.stdout,
-                stderr: exec_result.stderr,
-                aggregated_output: exec_result.aggregated_output,
+                command: exec.command,
+                cwd: exec.cwd,
+                parsed_cmd: Arc::unwrap_or_clone(exec.parsed_cmd),
+                source: end_source(exec.source, approval),
+                interaction_input: exec.interaction_input,
+                stdout: limit_event_field(stdout, event_field_bytes),
+                stderr: limit_event_field(stderr, event_field_bytes),
+                aggregated_output: limit_event_field(aggregated_output, event_field_bytes),
                 exit_code: exec_result.exit_code,
+                outcome: exec_result.outcome,
                 duration: exec_result.duration,
-                formatted_output: exec_result.formatted_output,
+                estimated: exec_result.estimated,
+                suspended: exec_result.suspended,
+                formatted_output,
+                sanitized_bytes: sanitized_bytes_field(sanitized_bytes),
+                delta_bytes_emitted: exec_result.deltas.bytes_emitted,
+                deltas_incomplete: exec_result.deltas.incomplete,
+                saved_output_path: exec_result.saved_output_path,
+                approval,
+                error: exec_result.error,
             }),
         )
         .await;
+    if !delivered {
+        warn!(
+            call_id = ctx.call_id,
+            "exec end event not delivered: the client channel is closed"
+        );
+    }
+    if let Some((command, parsed_cmd, by_user)) = git_check {
+        git_state::check_after_exec(
+            ctx.session,
+            ctx.turn,
+            ctx.call_id,
+            &command,
+            &parsed_cmd,
+            by_user,
+        )
+        .await;
+    }
+}
+
+/// Adds the files a successful command read and wrote to the turn's
+/// file-access ledger.
+async fn record_command_file_access(session: &Session, exec: &ExecCommandInput) {
+    for parsed in exec.parsed_cmd.iter() {
+        if let ParsedCommand::Read { path, .. } = parsed {
+            let path = exec.cwd.join(path);
+            let modified = modified_time(&path).await;
+            session.record_file_read(path, modified).await;
+        }
+    }
+    for path in command_writes(&exec.command, &exec.cwd) {
+        let modified = modified_time(&path).await;
+        session.record_file_write(path, modified).await;
+    }
+}
+
+/// Spells the paths in `changes` the way events do; see
+/// [`normalize_event_path`]. Returns `changes` itself when nothing changes.
+fn normalize_change_paths(
+    changes: Arc<HashMap<PathBuf, FileChange>>,
+) -> Arc<HashMap<PathBuf, FileChange>> {
+    let is_normalized = |path: &PathBuf| normalize_event_path(path) == *path;
+    let already_normalized = changes.iter().all(|(path, change)| {
+        is_normalized(path)
+            && match change {
+                FileChange::Update {
+                    move_path: Some(dest),
+                    ..
+                } => is_normalized(dest),
+                _ => true,
+            }
+    });
+    if already_normalized {
+        return changes;
+    }
+    let normalized = Arc::unwrap_or_clone(changes)
+        .into_iter()
+        .map(|(path, change)| {
+            let change = match change {
+                FileChange::Update {
+                    unified_diff,
+                    move_path,
+                } => FileChange::Update {
+                    unified_diff,
+                    move_path: move_path.as_deref().map(normalize_event_path),
+                },
+                other => other,
+            };
+            (normalize_event_path(&path), change)
+        })
+        .collect();
+    Arc::new(normalized)
+}
+
+/// Copies `changes` for a `PatchApplyBegin` event when their contents add up
+/// to at most `limit` bytes; larger patches are only summarized.
+fn patch_apply_begin_event(
+    ctx: ToolEventCtx<'_>,
+    changes: &HashMap<PathBuf, FileChange>,
+    auto_approved: bool,
+    timeout_ms: Option<u64>,
+) -> PatchApplyBeginEvent {
+    PatchApplyBeginEvent {
+        call_id: ctx.call_id.to_string(),
+        turn_id: ctx.turn.sub_id.clone(),
+        auto_approved,
+        changes: changes_within_limit(changes, ctx.turn.output_limits.patch_event_content_bytes),
+        summaries: summarize_changes(changes),
+        timeout_ms,
+    }
+}
+
+fn changes_within_limit(
+    changes: &HashMap<PathBuf, FileChange>,
+    limit: usize,
+) -> HashMap<PathBuf, FileChange> {
+    let total: usize = changes.values().map(|change| change.content().len()).sum();
+    if total > limit {
+        return HashMap::new();
+    }
+    changes.clone()
+}
+
+fn summarize_changes(changes: &HashMap<PathBuf, FileChange>) -> Vec<FileChangeSummary> {
+    let mut summaries: Vec<FileChangeSummary> = changes
+        .iter()
+        .map(|(path, change)| {
+            let content = change.content();
+            let move_path = match change {
+                FileChange::Update { move_path, .. } => move_path.clone(),
+                FileChange::Add { .. } | FileChange::Delete { .. } => None,
+            };
+            FileChangeSummary {
+                path: path.clone(),
+                kind: change.kind(),
+                move_path,
+                bytes: content.len() as u64,
+                sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+            }
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.path.cmp(&b.path));
+    summaries
+}
+
+/// Removes NUL and the other C0 control characters except `\n`, `\t` and
+/// `\r` from an event field, along with the rest of any ANSI escape sequence
+/// they start, so clients never receive raw terminal control codes. Returns
+/// the number of bytes removed.
+pub(crate) fn sanitize_event_text(text: &mut String) -> usize {
+    let is_removed = |c: char| c < ' ' && !matches!(c, '\n' | '\t' | '\r');
+    if !text.contains(is_removed) {
+        return 0;
+    }
+    let mut sanitized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_removed(c) {
+            sanitized.push(c);
+            continue;
+        }
+        // CSI sequences (`ESC [ params final`) are dropped whole.
+        if c == '\x1b' && chars.next_if_eq(&'[').is_some() {
+            while chars.next_if(|c| ('\x20'..='\x3f').contains(c)).is_some() {}
+            chars.next_if(|c| ('\x40'..='\x7e').contains(c));
+        }
+    }
+    let removed = text.len() - sanitized.len();
+    *text = sanitized;
+    removed
+}
+
+/// The `sanitized_bytes` field of an `ExecCommandEnd` event.
+pub(crate) fn sanitized_bytes_field(removed: usize) -> Option<u64> {
+    (removed > 0).then_some(removed as u64)
+}
+
+/// Applies `output_limits.event_field_bytes` to an output field of an
+/// `ExecCommandEnd` event, keeping the head and tail of longer text.
+pub(crate) fn limit_event_field(text: String, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) if text.len() > limit => truncate_text(&text, TruncationPolicy::Bytes(limit)),
+        _ => text,
+    }
+}
+
+/// Applies `output_limits.interaction_input_bytes` to the stdin of a
+/// `TerminalInteraction` event, keeping the beginning and counting the rest.
+pub(crate) fn limit_interaction_input(input: &str, limit: usize) -> String {
+    if input.len() <= limit {
+        return input.to_string();
+    }
+    let kept = take_bytes_at_char_boundary(input, limit);
+    format!("{kept}…{} more bytes", input.len() - kept.len())
 }
 
 async fn emit_patch_end(
@@ -439,6 +1407,24 @@ async fn emit_patch_end(
     stderr: String,
     success: bool,
 ) {
+    let mut changed_paths: Vec<PathBuf> = changes.keys().cloned().collect();
+    changed_paths.sort();
+    // Patches share the approval bookkeeping with exec calls but do not
+    // report it; drop the entry so it does not outlive the call.
+    let _ = ctx.session.take_exec_approval(ctx.call_id).await;
+    if success {
+        let written = changes.iter().flat_map(|(path, change)| {
+            let dest = match change {
+                FileChange::Update { move_path, .. } => move_path.as_ref(),
+                _ => None,
+            };
+            std::iter::once(path).chain(dest)
+        });
+        for path in written {
+            let modified = modified_time(path).await;
+            ctx.session.record_file_write(path.clone(), modified).await;
+        }
+    }
     ctx.session
         .send_event(
             ctx.turn,
@@ -454,14 +1440,530 @@ async fn emit_patch_end(
         .await;
 
     if let Some(tracker) = ctx.turn_diff_tracker {
-        let unified_diff = {
-            let mut guard = tracker.lock().await;
-            guard.get_unified_diff()
+        emit_turn_diff(ctx.session, ctx.turn, tracker).await;
+    }
+
+    // Files on disk just changed; make sure the rollout reflects that before
+    // anything else can go wrong.
+    ctx.session.flush_rollout().await;
+
+    if success {
+        spawn_hook(
+            ctx.session,
+            ctx.turn,
+            HookPayload::PatchApplied {
+                thread_id: ctx.session.conversation_id.to_string(),
+                turn_id: ctx.turn.sub_id.clone(),
+                cwd: ctx.turn.cwd.clone(),
+                call_id: ctx.call_id.to_string(),
+                changed_paths,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ToolEmitter;
+    use super::ToolEventCtx;
+    use super::call_tool_result_text;
+    use super::changes_within_limit;
+    use super::close_open_exec_calls;
+    use super::limit_event_field;
+    use super::limit_interaction_input;
+    use super::sanitize_event_text;
+    use super::summarize_changes;
+    use crate::codex::Session;
+    use crate::codex::TurnContext;
+    use crate::codex::make_session_and_context_with_rx;
+    use crate::error::CodexErr;
+    use crate::exec::ExecToolCallOutput;
+    use crate::exec::StreamOutput;
+    use crate::function_tool::FunctionCallError;
+    use crate::protocol::Event;
+    use crate::protocol::EventMsg;
+    use crate::protocol::ExecApproval;
+    use crate::protocol::ExecCommandSource;
+    use crate::protocol::ExecErrorInfo;
+    use crate::protocol::ExecErrorKind;
+    use crate::protocol::ExecOutcome;
+    use crate::protocol::FileChange;
+    use crate::protocol::PatchApplyBeginEvent;
+    use crate::protocol::SpawnErrorKind;
+    use crate::tools::sandboxing::RejectionReason;
+    use crate::tools::sandboxing::ToolError;
+    use mcp_types::CallToolResult;
+    use mcp_types::ContentBlock;
+    use mcp_types::ImageContent;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn control_characters_are_stripped_from_event_text() {
+        let mut text = "ok\tline\r\n\0nul\x07bell\x1b[1;31mred\x1b[0m é\n".to_string();
+        assert_eq!(sanitize_event_text(&mut text), 13);
+        assert_eq!(text, "ok\tline\r\nnulbellred é\n");
+
+        let mut clean = "plain output\n".to_string();
+        assert_eq!(sanitize_event_text(&mut clean), 0);
+        assert_eq!(clean, "plain output\n");
+    }
+
+    #[test]
+    fn unterminated_escape_sequences_do_not_swallow_following_lines() {
+        let mut text = "\x1b[12\nnext line\x1b".to_string();
+        assert_eq!(sanitize_event_text(&mut text), 5);
+        assert_eq!(text, "\nnext line");
+    }
+
+    #[test]
+    fn oversized_interaction_input_keeps_its_beginning() {
+        assert_eq!(limit_interaction_input("y\n", 4), "y\n");
+        assert_eq!(limit_interaction_input("abcdefgh", 3), "abc…5 more bytes");
+        // "é" is two bytes; the cut backs off rather than splitting it.
+        assert_eq!(limit_interaction_input("aéé", 2), "a…4 more bytes");
+    }
+
+    #[test]
+    fn event_fields_are_only_truncated_over_the_limit() {
+        let text = "a".repeat(64);
+        assert_eq!(limit_event_field(text.clone(), None), text);
+        assert_eq!(limit_event_field(text.clone(), Some(64)), text);
+
+        let limited = limit_event_field(text.clone(), Some(16));
+        assert!(limited.len() < text.len());
+        assert!(limited.starts_with("aaaa"));
+        assert!(limited.ends_with("aaaa"));
+    }
+
+    #[test]
+    fn large_patches_are_summarized_instead_of_copied() {
+        let changes = HashMap::from([
+            (
+                PathBuf::from("b.txt"),
+                FileChange::Update {
+                    unified_diff: "@@\n-old\n+new\n".to_string(),
+                    move_path: Some(PathBuf::from("c.txt")),
+                },
+            ),
+            (
+                PathBuf::from("a.txt"),
+                FileChange::Add {
+                    content: "hello\n".to_string(),
+                },
+            ),
+        ]);
+        assert_eq!(changes_within_limit(&changes, 19), changes);
+        assert_eq!(changes_within_limit(&changes, 18), HashMap::new());
+
+        let event = PatchApplyBeginEvent {
+            call_id: "call-1".to_string(),
+            turn_id: "turn-1".to_string(),
+            auto_approved: true,
+            changes: changes_within_limit(&changes, 18),
+            summaries: summarize_changes(&changes),
+            timeout_ms: None,
         };
-        if let Ok(Some(unified_diff)) = unified_diff {
-            ctx.session
-                .send_event(ctx.turn, EventMsg::TurnDiff(TurnDiffEvent { unified_diff }))
-                .await;
+        assert_eq!(
+            serde_json::to_value(&event).expect("serialize event"),
+            json!({
+                "call_id": "call-1",
+                "turn_id": "turn-1",
+                "auto_approved": true,
+                "changes": {},
+                "summaries": [
+                    {
+                        "path": "a.txt",
+                        "kind": "add",
+                        "bytes": 6,
+                        "sha256": "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03",
+                    },
+                    {
+                        "path": "b.txt",
+                        "kind": "update",
+                        "move_path": "c.txt",
+                        "bytes": 13,
+                        "sha256": "578fb2b594049b296d357e34bd1d7714ae88e299535b0c3c831e510ce80e6a9c",
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn mcp_result_text_summarizes_non_text_blocks() {
+        let result = CallToolResult {
+            content: vec![
+                ContentBlock::TextContent(TextContent {
+                    annotations: None,
+                    text: "found 2 pages".to_string(),
+                    r#type: "text".to_string(),
+                }),
+                ContentBlock::ImageContent(ImageContent {
+                    annotations: None,
+                    data: "aGVsbG8=".to_string(),
+                    mime_type: "image/png".to_string(),
+                    r#type: "image".to_string(),
+                }),
+            ],
+            is_error: None,
+            structured_content: None,
+        };
+        assert_eq!(
+            call_tool_result_text(&result),
+            "found 2 pages\n[image: image/png]"
+        );
+
+        let structured_only = CallToolResult {
+            content: Vec::new(),
+            is_error: None,
+            structured_content: Some(json!({ "pages": 2 })),
+        };
+        assert_eq!(call_tool_result_text(&structured_only), r#"{"pages":2}"#);
+    }
+
+    #[tokio::test]
+    async fn shell_end_event_carries_the_begin_event_command() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let command = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "cat <<'EOF'\nhi\nEOF".to_string(),
+        ];
+        let emitter = ToolEmitter::shell(
+            command.clone(),
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            Some(1_000),
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        emitter.begin(ctx).await;
+        let output = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new("hi\n".to_string()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new("hi\n".to_string()),
+            duration: Duration::from_millis(5),
+            suspended: false,
+            timed_out: false,
+            deltas: Default::default(),
+        };
+        emitter
+            .finish(ctx, Ok(output))
+            .await
+            .expect("command succeeded");
+
+        let mut begin = None;
+        let mut end = None;
+        while let Ok(event) = rx.try_recv() {
+            match event.msg {
+                EventMsg::ExecCommandBegin(ev) => begin = Some(ev),
+                EventMsg::ExecCommandEnd(ev) => end = Some(ev),
+                _ => {}
+            }
+        }
+        let begin = serde_json::to_value(begin.expect("begin event")).expect("serialize begin");
+        let end = serde_json::to_value(end.expect("end event")).expect("serialize end");
+        assert_eq!(begin["command"], json!(command));
+        for field in [
+            "call_id",
+            "turn_id",
+            "command",
+            "cwd",
+            "parsed_cmd",
+            "source",
+        ] {
+            assert_eq!(begin[field], end[field], "{field} differs");
+        }
+        assert_eq!(end["cwd"], json!("/tmp/work"));
+        assert_eq!(end["exit_code"], json!(0));
+        assert_eq!(end["outcome"], json!({ "type": "spawned", "exit_code": 0 }));
+        assert_eq!(end["duration"], json!({ "secs": 0, "nanos": 5_000_000 }));
+        assert_eq!(end.get("estimated"), None);
+    }
+
+    /// Runs `emitter` through a successful call that printed `hi` and
+    /// returns its begin and end events as pretty-printed JSON.
+    async fn exec_event_json(
+        session: &Session,
+        turn: &TurnContext,
+        rx: &async_channel::Receiver<Event>,
+        emitter: ToolEmitter,
+    ) -> (String, String) {
+        let ctx = ToolEventCtx::new(session, turn, "call-1", None);
+        emitter.begin(ctx).await;
+        let output = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new("hi\n".to_string()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new("hi\n".to_string()),
+            duration: Duration::from_millis(5),
+            suspended: false,
+            timed_out: false,
+            deltas: Default::default(),
+        };
+        emitter
+            .finish(ctx, Ok(output))
+            .await
+            .expect("command succeeded");
+
+        let mut begin = None;
+        let mut end = None;
+        while let Ok(event) = rx.try_recv() {
+            match event.msg {
+                EventMsg::ExecCommandBegin(ev) => {
+                    begin = Some(serde_json::to_string_pretty(&ev).expect("serialize begin"));
+                }
+                EventMsg::ExecCommandEnd(ev) => {
+                    end = Some(serde_json::to_string_pretty(&ev).expect("serialize end"));
+                }
+                _ => {}
+            }
         }
+        (begin.expect("begin event"), end.expect("end event"))
+    }
+
+    #[tokio::test]
+    async fn shell_exec_events_serialize_unchanged() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["bash".to_string(), "-lc".to_string(), "echo hi".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            Some(1_000),
+            session.as_ref(),
+        );
+
+        let (begin, end) = exec_event_json(&session, &turn, &rx, emitter).await;
+
+        insta::assert_snapshot!("shell_exec_command_begin", begin);
+        insta::assert_snapshot!("shell_exec_command_end", end);
+    }
+
+    #[tokio::test]
+    async fn unified_exec_events_serialize_unchanged() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::unified_exec(
+            vec!["bash".to_string(), "-lc".to_string(), "echo hi".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelUnifiedExec,
+            Some("1000".to_string()),
+            session.as_ref(),
+        );
+
+        let (begin, end) = exec_event_json(&session, &turn, &rx, emitter).await;
+
+        insta::assert_snapshot!("unified_exec_command_begin", begin);
+        insta::assert_snapshot!("unified_exec_command_end", end);
+    }
+
+    #[tokio::test]
+    async fn escalated_retries_report_their_own_source_on_the_end_event() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["touch".to_string(), "/etc/codex".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            None,
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        emitter.begin(ctx).await;
+        session
+            .record_exec_approval("call-1", ExecApproval::Escalated)
+            .await;
+        let output = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(String::new()),
+            duration: Duration::from_millis(5),
+            suspended: false,
+            timed_out: false,
+            deltas: Default::default(),
+        };
+        emitter
+            .finish(ctx, Ok(output))
+            .await
+            .expect("command succeeded");
+
+        let sources: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event.msg {
+                EventMsg::ExecCommandBegin(ev) => Some(ev.source),
+                EventMsg::ExecCommandEnd(ev) => Some(ev.source),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ExecCommandSource::ModelShell,
+                ExecCommandSource::EscalatedRetry
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_calls_report_time_since_begin() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["rm".to_string(), "-rf".to_string(), "build".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            None,
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        emitter.begin(ctx).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let rejection = RejectionReason::UserDenied { feedback: None };
+        let result = emitter
+            .finish(ctx, Err(ToolError::Rejected(rejection)))
+            .await;
+        assert_eq!(
+            result,
+            Err(FunctionCallError::RespondToModel(
+                "exec command rejected by user".to_string()
+            ))
+        );
+
+        let end = std::iter::from_fn(|| rx.try_recv().ok())
+            .find_map(|event| match event.msg {
+                EventMsg::ExecCommandEnd(ev) => Some(ev),
+                _ => None,
+            })
+            .expect("end event");
+        assert!(end.estimated);
+        assert!(
+            end.duration >= Duration::from_millis(20),
+            "duration {:?} should cover the time since begin",
+            end.duration
+        );
+        assert_eq!(
+            end.error,
+            Some(ExecErrorInfo {
+                kind: ExecErrorKind::Rejected,
+                message: "exec command rejected by user".to_string(),
+            })
+        );
+        assert_eq!(end.stderr, "");
+        assert_eq!(end.aggregated_output, "");
+        assert_eq!(end.formatted_output, "exec command rejected by user");
+    }
+
+    #[tokio::test]
+    async fn calls_still_open_at_turn_end_are_closed() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["sleep".to_string(), "600".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            None,
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        emitter.begin(ctx).await;
+        // The task running the call is aborted before it can send the end.
+        drop(emitter);
+
+        close_open_exec_calls(session.as_ref(), turn.as_ref()).await;
+        close_open_exec_calls(session.as_ref(), turn.as_ref()).await;
+
+        let ends: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event.msg {
+                EventMsg::ExecCommandEnd(ev) => Some(ev),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ends.len(), 1);
+        assert_eq!(ends[0].call_id, "call-1");
+        assert_eq!(ends[0].command, vec!["sleep", "600"]);
+        assert_eq!(ends[0].exit_code, -1);
+        assert!(ends[0].estimated);
+    }
+
+    #[tokio::test]
+    async fn end_events_close_the_call_after_the_client_is_gone() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["true".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            None,
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        emitter.begin(ctx).await;
+        drop(rx);
+
+        let output = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(String::new()),
+            duration: Duration::from_millis(5),
+            suspended: false,
+            timed_out: false,
+            deltas: Default::default(),
+        };
+        assert!(emitter.finish(ctx, Ok(output)).await.is_ok());
+
+        assert!(session.take_open_exec_calls(&turn.sub_id).await.is_empty());
+        let delivered = session
+            .send_event(turn.as_ref(), EventMsg::ShutdownComplete)
+            .await;
+        assert!(!delivered);
+    }
+
+    #[tokio::test]
+    async fn spawn_failures_are_not_reported_as_command_failures() {
+        let (session, turn, rx) = make_session_and_context_with_rx().await;
+        let emitter = ToolEmitter::shell(
+            vec!["missing-tool".to_string()],
+            PathBuf::from("/tmp/work"),
+            ExecCommandSource::ModelShell,
+            false,
+            None,
+            session.as_ref(),
+        );
+        let ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), "call-1", None);
+        let err = CodexErr::ExecSpawnFailed {
+            kind: SpawnErrorKind::CommandNotFound,
+            message: "command not found: missing-tool".to_string(),
+        };
+        let result = emitter.finish(ctx, Err(ToolError::Codex(err))).await;
+        let Err(FunctionCallError::RespondToModel(message)) = result else {
+            panic!("expected a model-facing error, got {result:?}");
+        };
+        assert_eq!(message, "command not found: missing-tool");
+
+        let end = std::iter::from_fn(|| rx.try_recv().ok())
+            .find_map(|event| match event.msg {
+                EventMsg::ExecCommandEnd(ev) => Some(ev),
+                _ => None,
+            })
+            .expect("end event");
+        assert_eq!(
+            end.outcome,
+            Some(ExecOutcome::SpawnFailed {
+                error_kind: SpawnErrorKind::CommandNotFound,
+                message: "command not found: missing-tool".to_string(),
+            })
+        );
+        assert_eq!(end.formatted_output, "command not found: missing-tool");
+        // No begin event was emitted, so there is no time to report.
+        assert_eq!(end.duration, Duration::ZERO);
+        assert!(end.estimated);
     }
 }
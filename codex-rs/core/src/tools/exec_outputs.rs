@@ -0,0 +1,358 @@
+//! The output of the session's earlier commands, kept so the model can page
+//! through it with `read_exec_output` instead of running a command again.
+//!
+//! With the `read_exec_output` feature, every shell and unified exec call
+//! that ends is recorded under its call id: output that was spilled to disk
+//! is read back from the spill file, anything else is retained in memory.
+//! Retained text stays within [`MAX_RETAINED_BYTES`], oldest evicted first,
+//! and only the newest [`MAX_ENTRIES`] calls are remembered at all.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_utils_string::take_bytes_at_char_boundary;
+use regex::Regex;
+
+use crate::function_tool::FunctionCallError;
+use crate::parse_command::shlex_join;
+
+/// Calls remembered; older ones are forgotten.
+const MAX_ENTRIES: usize = 100;
+/// Output retained in memory across all calls.
+const MAX_RETAINED_BYTES: usize = 8 * 1024 * 1024;
+/// Calls named when a lookup fails.
+const LISTED_ENTRIES: usize = 10;
+const MAX_LINE_LENGTH: usize = 500;
+
+/// The session's recorded exec outputs, oldest first.
+#[derive(Default)]
+pub(crate) struct ExecOutputLog {
+    entries: VecDeque<ExecOutputEntry>,
+    retained_bytes: usize,
+}
+
+struct ExecOutputEntry {
+    call_id: String,
+    command: String,
+    saved: Saved,
+}
+
+enum Saved {
+    Spilled(PathBuf),
+    Retained(Arc<str>),
+    /// Retained text dropped to stay within [`MAX_RETAINED_BYTES`].
+    Evicted,
+}
+
+/// Where the output of a recorded call can be read from.
+pub(crate) enum ExecOutputSource {
+    File(PathBuf),
+    Text(Arc<str>),
+}
+
+/// One page of an output, numbered by its line numbers in the full output.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ExecOutputPage {
+    pub(crate) content: String,
+    pub(crate) lines: usize,
+    /// Lines of the output, or of its lines matching the filter.
+    pub(crate) total_lines: usize,
+    pub(crate) truncated: bool,
+}
+
+impl ExecOutputLog {
+    /// Records the output of `call_id`: the spill file when there is one,
+    /// otherwise `text`.
+    pub(crate) fn record(
+        &mut self,
+        call_id: &str,
+        command: &[String],
+        spill_path: Option<PathBuf>,
+        text: &str,
+    ) {
+        if let Some(index) = self.index_of(call_id)
+            && let Some(entry) = self.entries.remove(index)
+        {
+            self.release(&entry);
+        }
+        let saved = match spill_path {
+            Some(path) => Saved::Spilled(path),
+            None => {
+                self.retained_bytes += text.len();
+                Saved::Retained(Arc::from(text))
+            }
+        };
+        self.entries.push_back(ExecOutputEntry {
+            call_id: call_id.to_string(),
+            command: shlex_join(command),
+            saved,
+        });
+        while self.entries.len() > MAX_ENTRIES {
+            if let Some(entry) = self.entries.pop_front() {
+                self.release(&entry);
+            }
+        }
+        for entry in self.entries.iter_mut() {
+            if self.retained_bytes <= MAX_RETAINED_BYTES {
+                break;
+            }
+            if let Saved::Retained(text) = &entry.saved {
+                self.retained_bytes -= text.len();
+                entry.saved = Saved::Evicted;
+            }
+        }
+    }
+
+    /// Where the output of `call_id` is, or what is available instead.
+    pub(crate) fn source(&self, call_id: &str) -> Result<ExecOutputSource, FunctionCallError> {
+        let entry = self.index_of(call_id).map(|index| &self.entries[index]);
+        let reason = match entry {
+            Some(ExecOutputEntry {
+                saved: Saved::Spilled(path),
+                ..
+            }) => return Ok(ExecOutputSource::File(path.clone())),
+            Some(ExecOutputEntry {
+                saved: Saved::Retained(text),
+                ..
+            }) => return Ok(ExecOutputSource::Text(Arc::clone(text))),
+            Some(ExecOutputEntry {
+                command,
+                saved: Saved::Evicted,
+                ..
+            }) => format!(
+                "the output of `{call_id}` (`{command}`) was evicted to keep retained output within {MAX_RETAINED_BYTES} bytes"
+            ),
+            None => format!("no output is kept for call_id `{call_id}`"),
+        };
+        Err(FunctionCallError::RespondToModel(format!(
+            "{reason}; {}",
+            self.available()
+        )))
+    }
+
+    /// The newest calls whose output can still be read.
+    fn available(&self) -> String {
+        let listed: Vec<String> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| !matches!(entry.saved, Saved::Evicted))
+            .take(LISTED_ENTRIES)
+            .map(|entry| format!("`{}` (`{}`)", entry.call_id, entry.command))
+            .collect();
+        if listed.is_empty() {
+            "no earlier command output is available".to_string()
+        } else {
+            format!("output is available for: {}", listed.join(", "))
+        }
+    }
+
+    fn index_of(&self, call_id: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.call_id == call_id)
+    }
+
+    fn release(&mut self, entry: &ExecOutputEntry) {
+        if let Saved::Retained(text) = &entry.saved {
+            self.retained_bytes -= text.len();
+        }
+    }
+}
+
+/// Reads a page of `source`: the lines matching `grep`, if given, after
+/// skipping `offset_lines` of them, at most `limit_lines` and `max_bytes`.
+/// Blocks on file reads.
+pub(crate) fn read_page(
+    source: &ExecOutputSource,
+    grep: Option<&Regex>,
+    offset_lines: usize,
+    limit_lines: Option<usize>,
+    max_bytes: usize,
+) -> Result<ExecOutputPage, FunctionCallError> {
+    match source {
+        ExecOutputSource::Text(text) => {
+            let lines = text.lines().map(|line| Ok(line.to_string()));
+            page(lines, grep, offset_lines, limit_lines, max_bytes)
+        }
+        ExecOutputSource::File(path) => {
+            let read_error = |err: std::io::Error| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to read saved output {}: {err}",
+                    path.display()
+                ))
+            };
+            let file = File::open(path).map_err(read_error)?;
+            let lines = BufReader::new(file).split(b'\n').map(|line| {
+                line.map(|mut bytes| {
+                    if bytes.last() == Some(&b'\r') {
+                        bytes.pop();
+                    }
+                    String::from_utf8_lossy(&bytes).into_owned()
+                })
+                .map_err(read_error)
+            });
+            page(lines, grep, offset_lines, limit_lines, max_bytes)
+        }
+    }
+}
+
+fn page(
+    lines: impl Iterator<Item = Result<String, FunctionCallError>>,
+    grep: Option<&Regex>,
+    offset_lines: usize,
+    limit_lines: Option<usize>,
+    max_bytes: usize,
+) -> Result<ExecOutputPage, FunctionCallError> {
+    let limit_lines = limit_lines.unwrap_or(usize::MAX);
+    let mut content = String::new();
+    let mut kept = 0;
+    let mut total_lines = 0;
+    let mut over_budget = false;
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if grep.is_some_and(|grep| !grep.is_match(&line)) {
+            continue;
+        }
+        total_lines += 1;
+        if total_lines <= offset_lines || kept == limit_lines || over_budget {
+            continue;
+        }
+        let line = take_bytes_at_char_boundary(&line, MAX_LINE_LENGTH);
+        let numbered = format!("L{}: {line}", index + 1);
+        if kept > 0 {
+            if content.len() + 1 + numbered.len() > max_bytes {
+                over_budget = true;
+                continue;
+            }
+            content.push('\n');
+        }
+        content.push_str(&numbered);
+        kept += 1;
+    }
+
+    let noun = if grep.is_some() {
+        "matching lines"
+    } else {
+        "lines"
+    };
+    if total_lines == 0 {
+        content = format!("[no {noun}]");
+    } else if offset_lines >= total_lines {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "offset_lines exceeds the {total_lines} {noun} of the output"
+        )));
+    }
+    let next = offset_lines + kept;
+    let remaining = total_lines - next.min(total_lines);
+    if over_budget {
+        content.push_str(&format!(
+            "\n[truncated: {remaining} more {noun} past the {max_bytes}-byte limit; continue with offset_lines={next}]"
+        ));
+    } else if remaining > 0 {
+        content.push_str(&format!(
+            "\n[{remaining} more {noun}; continue with offset_lines={next}]"
+        ));
+    }
+    Ok(ExecOutputPage {
+        content,
+        lines: kept,
+        total_lines,
+        truncated: over_budget,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text(text: &str) -> ExecOutputSource {
+        ExecOutputSource::Text(Arc::from(text))
+    }
+
+    fn message(err: FunctionCallError) -> String {
+        match err {
+            FunctionCallError::RespondToModel(message) => message,
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn pages_keep_the_line_numbers_of_the_full_output() -> anyhow::Result<()> {
+        let source = text("compiling a\nerror: one\ncompiling b\nerror: two\nerror: three\n");
+        let grep = Regex::new("^error")?;
+
+        let page = read_page(&source, Some(&grep), 1, Some(1), 1_000)?;
+
+        assert_eq!(
+            page,
+            ExecOutputPage {
+                content: "L4: error: two\n[1 more matching lines; continue with offset_lines=2]"
+                    .to_string(),
+                lines: 1,
+                total_lines: 3,
+                truncated: false,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pages_stop_at_the_byte_budget() -> anyhow::Result<()> {
+        let source = text(&"0123456789\n".repeat(10));
+
+        let page = read_page(&source, None, 0, None, 30)?;
+
+        assert_eq!(
+            page.content,
+            "L1: 0123456789\nL2: 0123456789\n\
+             [truncated: 8 more lines past the 30-byte limit; continue with offset_lines=2]"
+        );
+        assert!(page.truncated);
+        Ok(())
+    }
+
+    #[test]
+    fn spilled_output_is_read_from_its_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("call-1.txt");
+        std::fs::write(&path, "first\r\nsecond\n")?;
+        let mut log = ExecOutputLog::default();
+        log.record("call-1", &["make".to_string()], Some(path), "preview");
+
+        let page = read_page(&log.source("call-1")?, None, 0, None, 1_000)?;
+
+        assert_eq!(page.content, "L1: first\nL2: second");
+        Ok(())
+    }
+
+    #[test]
+    fn missing_outputs_name_what_is_still_available() {
+        let mut log = ExecOutputLog::default();
+        let big = "x".repeat(MAX_RETAINED_BYTES / 2 + 1);
+        log.record("call-1", &["cat".to_string(), "a".to_string()], None, &big);
+        log.record("call-2", &["cat".to_string(), "b".to_string()], None, &big);
+
+        let Err(evicted) = log.source("call-1") else {
+            panic!("call-1 should have been evicted");
+        };
+        assert_eq!(
+            message(evicted),
+            format!(
+                "the output of `call-1` (`cat a`) was evicted to keep retained output within {MAX_RETAINED_BYTES} bytes; output is available for: `call-2` (`cat b`)"
+            )
+        );
+        let Err(unknown) = log.source("call-3") else {
+            panic!("call-3 was never recorded");
+        };
+        assert_eq!(
+            message(unknown),
+            "no output is kept for call_id `call-3`; output is available for: `call-2` (`cat b`)"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::apply_patch;
 use crate::apply_patch::InternalApplyPatchInvocation;
@@ -10,7 +11,10 @@ use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
+use crate::protocol::EventMsg;
+use crate::protocol::WarningEvent;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -55,6 +59,39 @@ fn file_paths_for_action(action: &ApplyPatchAction) -> Vec<AbsolutePathBuf> {
     keys
 }
 
+/// Warns the user and the model when a file the patch touches changed on
+/// disk after it was last read or edited this turn, or, with the workspace
+/// watcher, outside Codex since it was last read or edited, since the patch
+/// may have been written against contents that are gone.
+async fn warn_about_changed_files(
+    session: &Session,
+    turn: &TurnContext,
+    file_paths: &[AbsolutePathBuf],
+) {
+    let mut changed = Vec::new();
+    for path in file_paths {
+        if session.file_changed_since_seen(path.as_path()).await {
+            changed.push(turn.display_path(path.as_path()));
+        }
+    }
+    if changed.is_empty() {
+        return;
+    }
+    let message = format!(
+        "{} changed on disk since it was last read or edited; re-read before relying on its contents",
+        changed.join(", ")
+    );
+    session
+        .send_event(
+            turn,
+            EventMsg::Warning(WarningEvent {
+                message: message.clone(),
+            }),
+        )
+        .await;
+    session.record_model_warning(message, turn).await;
+}
+
 fn to_abs_path(cwd: &Path, path: &Path) -> Option<AbsolutePathBuf> {
     AbsolutePathBuf::resolve_path_against_base(path, cwd).ok()
 }
@@ -76,6 +113,10 @@ impl ToolHandler for ApplyPatchHandler {
         true
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -105,62 +146,16 @@ impl ToolHandler for ApplyPatchHandler {
         let command = vec!["apply_patch".to_string(), patch_input.clone()];
         match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &cwd) {
             codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
-                match apply_patch::apply_patch(turn.as_ref(), changes).await {
-                    InternalApplyPatchInvocation::Output(item) => {
-                        let content = item?;
-                        Ok(ToolOutput::Function {
-                            content,
-                            content_items: None,
-                            success: Some(true),
-                        })
-                    }
-                    InternalApplyPatchInvocation::DelegateToExec(apply) => {
-                        let changes = convert_apply_patch_to_protocol(&apply.action);
-                        let file_paths = file_paths_for_action(&apply.action);
-                        let emitter =
-                            ToolEmitter::apply_patch(changes.clone(), apply.auto_approved);
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        emitter.begin(event_ctx).await;
-
-                        let req = ApplyPatchRequest {
-                            action: apply.action,
-                            file_paths,
-                            changes,
-                            exec_approval_requirement: apply.exec_approval_requirement,
-                            timeout_ms: None,
-                            codex_exe: turn.codex_linux_sandbox_exe.clone(),
-                        };
-
-                        let mut orchestrator = ToolOrchestrator::new();
-                        let mut runtime = ApplyPatchRuntime::new();
-                        let tool_ctx = ToolCtx {
-                            session: session.as_ref(),
-                            turn: turn.as_ref(),
-                            call_id: call_id.clone(),
-                            tool_name: tool_name.to_string(),
-                        };
-                        let out = orchestrator
-                            .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
-                            .await;
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        let content = emitter.finish(event_ctx, out).await?;
-                        Ok(ToolOutput::Function {
-                            content,
-                            content_items: None,
-                            success: Some(true),
-                        })
-                    }
-                }
+                run_apply_patch(
+                    changes,
+                    None,
+                    session.as_ref(),
+                    turn.as_ref(),
+                    Some(&tracker),
+                    &call_id,
+                    &tool_name,
+                )
+                .await
             }
             codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
                 Err(FunctionCallError::RespondToModel(format!(
@@ -201,53 +196,11 @@ pub(crate) async fn intercept_apply_patch(
                     turn,
                 )
                 .await;
-            match apply_patch::apply_patch(turn, changes).await {
-                InternalApplyPatchInvocation::Output(item) => {
-                    let content = item?;
-                    Ok(Some(ToolOutput::Function {
-                        content,
-                        content_items: None,
-                        success: Some(true),
-                    }))
-                }
-                InternalApplyPatchInvocation::DelegateToExec(apply) => {
-                    let changes = convert_apply_patch_to_protocol(&apply.action);
-                    let approval_keys = file_paths_for_action(&apply.action);
-                    let emitter = ToolEmitter::apply_patch(changes.clone(), apply.auto_approved);
-                    let event_ctx =
-                        ToolEventCtx::new(session, turn, call_id, tracker.as_ref().copied());
-                    emitter.begin(event_ctx).await;
-
-                    let req = ApplyPatchRequest {
-                        action: apply.action,
-                        file_paths: approval_keys,
-                        changes,
-                        exec_approval_requirement: apply.exec_approval_requirement,
-                        timeout_ms,
-                        codex_exe: turn.codex_linux_sandbox_exe.clone(),
-                    };
-
-                    let mut orchestrator = ToolOrchestrator::new();
-                    let mut runtime = ApplyPatchRuntime::new();
-                    let tool_ctx = ToolCtx {
-                        session,
-                        turn,
-                        call_id: call_id.to_string(),
-                        tool_name: tool_name.to_string(),
-                    };
-                    let out = orchestrator
-                        .run(&mut runtime, &req, &tool_ctx, turn, turn.approval_policy)
-                        .await;
-                    let event_ctx =
-                        ToolEventCtx::new(session, turn, call_id, tracker.as_ref().copied());
-                    let content = emitter.finish(event_ctx, out).await?;
-                    Ok(Some(ToolOutput::Function {
-                        content,
-                        content_items: None,
-                        success: Some(true),
-                    }))
-                }
-            }
+            run_apply_patch(
+                changes, timeout_ms, session, turn, tracker, call_id, tool_name,
+            )
+            .await
+            .map(Some)
         }
         codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
             Err(FunctionCallError::RespondToModel(format!(
@@ -262,6 +215,74 @@ pub(crate) async fn intercept_apply_patch(
     }
 }
 
+/// Runs a verified patch the same way whichever tool asked for it. The patch
+/// policy decides whether it needs approval, and its events come from
+/// [`ToolEmitter::apply_patch`], so `auto_approved` and the turn diff are the
+/// same for the `apply_patch` tool and a shell command that invokes it.
+async fn run_apply_patch(
+    action: ApplyPatchAction,
+    requested_timeout_ms: Option<u64>,
+    session: &Session,
+    turn: &TurnContext,
+    tracker: Option<&SharedTurnDiffTracker>,
+    call_id: &str,
+    tool_name: &str,
+) -> Result<ToolOutput, FunctionCallError> {
+    let apply = match apply_patch::apply_patch(turn, action).await {
+        InternalApplyPatchInvocation::Output(item) => {
+            return item.map(|content| ToolOutput::Function {
+                content,
+                content_items: None,
+                success: Some(true),
+            });
+        }
+        InternalApplyPatchInvocation::DelegateToExec(apply) => apply,
+    };
+    let changes = Arc::new(convert_apply_patch_to_protocol(&apply.action));
+    let file_paths = file_paths_for_action(&apply.action);
+    warn_about_changed_files(session, turn, &file_paths).await;
+    let tool_timeouts = session.tool_timeouts().await;
+    let timeout_ms = tool_timeouts
+        .resolve(requested_timeout_ms, tool_timeouts.apply_patch)
+        .as_millis() as u64;
+    let emitter =
+        ToolEmitter::apply_patch(Arc::clone(&changes), apply.auto_approved, Some(timeout_ms));
+    let event_ctx = ToolEventCtx::new(session, turn, call_id, tracker);
+    if turn.plan_only {
+        return Ok(emitter.propose(event_ctx).await);
+    }
+    emitter.begin(event_ctx).await;
+
+    let req = ApplyPatchRequest {
+        action: apply.action,
+        file_paths,
+        changes,
+        exec_approval_requirement: apply.exec_approval_requirement,
+        timeout_ms: Some(timeout_ms),
+        codex_exe: turn.codex_linux_sandbox_exe.clone(),
+        clear_readonly: session.features().enabled(Feature::ApplyPatchClearReadOnly),
+    };
+
+    let mut orchestrator = ToolOrchestrator::new();
+    let mut runtime = ApplyPatchRuntime::new();
+    let tool_ctx = ToolCtx {
+        session,
+        turn,
+        call_id: call_id.to_string(),
+        tool_name: tool_name.to_string(),
+    };
+    let out = orchestrator
+        .run(&mut runtime, &req, &tool_ctx, turn, turn.approval_policy)
+        .await;
+    let event_ctx = ToolEventCtx::new(session, turn, call_id, tracker);
+    let content = emitter.finish(event_ctx, out).await?;
+    Ok(ToolOutput::Function {
+        content,
+        content_items: None,
+        success: Some(true),
+    })
+}
+
 /// Returns a custom tool that can be used to edit files. Well-suited for GPT-5 models
 /// https://platform.openai.com/docs/guides/function-calling#custom-tools
 pub(crate) fn create_apply_patch_freeform_tool() -> ToolSpec {
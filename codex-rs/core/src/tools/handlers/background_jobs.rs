@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// `get_background_job` and `list_background_jobs`.
+pub struct BackgroundJobsHandler;
+
+/// `kill_background_job`.
+pub struct KillBackgroundJobHandler;
+
+#[derive(Deserialize)]
+struct JobIdArgs {
+    id: String,
+}
+
+fn function_arguments(payload: ToolPayload, tool_name: &str) -> Result<String, FunctionCallError> {
+    match payload {
+        ToolPayload::Function { arguments } => Ok(arguments),
+        _ => Err(FunctionCallError::RespondToModel(format!(
+            "{tool_name} handler received unsupported payload"
+        ))),
+    }
+}
+
+fn json_output<T: Serialize>(value: &T) -> Result<ToolOutput, FunctionCallError> {
+    let content = serde_json::to_string(value).map_err(|err| {
+        FunctionCallError::Fatal(format!("failed to serialize background job: {err}"))
+    })?;
+    Ok(ToolOutput::Function {
+        content,
+        content_items: None,
+        success: Some(true),
+    })
+}
+
+#[async_trait]
+impl ToolHandler for BackgroundJobsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_parallel_safe(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+        let arguments = function_arguments(payload, &tool_name)?;
+        let jobs = &session.services.background_jobs;
+
+        match tool_name.as_str() {
+            "get_background_job" => {
+                let JobIdArgs { id } = parse_arguments(&arguments)?;
+                let job = jobs.get(&id).await.ok_or_else(|| {
+                    FunctionCallError::RespondToModel(format!("no background job `{id}`"))
+                })?;
+                json_output(&job)
+            }
+            "list_background_jobs" => json_output(&jobs.list().await),
+            other => Err(FunctionCallError::RespondToModel(format!(
+                "unsupported background job tool: {other}"
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for KillBackgroundJobHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+        let arguments = function_arguments(payload, &tool_name)?;
+        let JobIdArgs { id } = parse_arguments(&arguments)?;
+        let job = session
+            .services
+            .background_jobs
+            .kill(&id)
+            .await
+            .map_err(FunctionCallError::RespondToModel)?;
+        json_output(&job)
+    }
+}
@@ -1,9 +1,16 @@
+use std::time::Instant;
+
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::conversation::IN_PROCESS_TOOL_SERVER;
+use crate::conversation::InProcessTool;
 use crate::function_tool::FunctionCallError;
+use crate::protocol::McpInvocation;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
 use crate::tools::handlers::parse_arguments;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
@@ -11,6 +18,9 @@ use async_trait::async_trait;
 use codex_protocol::dynamic_tools::DynamicToolCallRequest;
 use codex_protocol::dynamic_tools::DynamicToolResponse;
 use codex_protocol::protocol::EventMsg;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
 use serde_json::Value;
 use tokio::sync::oneshot;
 use tracing::warn;
@@ -47,6 +57,9 @@ impl ToolHandler for DynamicToolHandler {
         };
 
         let args: Value = parse_arguments(&arguments)?;
+        if let Some(tool) = session.services.in_process_tools.get(&tool_name) {
+            return Ok(run_in_process_tool(&session, turn.as_ref(), &call_id, tool, args).await);
+        }
         let response = request_dynamic_tool(&session, turn.as_ref(), call_id, tool_name, args)
             .await
             .ok_or_else(|| {
@@ -63,6 +76,47 @@ impl ToolHandler for DynamicToolHandler {
     }
 }
 
+/// Runs `tool` with the begin and end events of an MCP tool call.
+async fn run_in_process_tool(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    tool: &InProcessTool,
+    arguments: Value,
+) -> ToolOutput {
+    let emitter = ToolEmitter::mcp(
+        McpInvocation {
+            server: IN_PROCESS_TOOL_SERVER.to_string(),
+            tool: tool.name().to_string(),
+            arguments: Some(arguments.clone()),
+        },
+        None,
+    );
+    let ctx = ToolEventCtx::new(session, turn, call_id, None);
+    emitter.begin(ctx).await;
+    let started = Instant::now();
+    let result = tool.call(arguments).await;
+    let (content, success) = match &result {
+        Ok(output) => (output.clone(), true),
+        Err(message) => (message.clone(), false),
+    };
+    let reported = Ok(CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            annotations: None,
+            text: content.clone(),
+            r#type: "text".to_string(),
+        })],
+        is_error: Some(!success),
+        structured_content: None,
+    });
+    emitter.finish_mcp(ctx, started.elapsed(), &reported).await;
+    ToolOutput::Function {
+        content,
+        content_items: None,
+        success: Some(success),
+    }
+}
+
 async fn request_dynamic_tool(
     session: &Session,
     turn_context: &TurnContext,
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+
+use crate::capabilities;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct EnvironmentCapabilitiesHandler;
+
+#[async_trait]
+impl ToolHandler for EnvironmentCapabilitiesHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            ..
+        } = invocation;
+
+        if !matches!(payload, ToolPayload::Function { .. }) {
+            return Err(FunctionCallError::RespondToModel(
+                "get_environment_capabilities handler received unsupported payload".to_string(),
+            ));
+        }
+
+        let capabilities = capabilities::describe(session.as_ref(), turn.as_ref()).await;
+        Ok(ToolOutput::Function {
+            content: capabilities::render(&capabilities),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
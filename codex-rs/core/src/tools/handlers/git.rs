@@ -0,0 +1,1010 @@
+//! The `git` tool: a fixed set of git subcommands run with machine-readable
+//! flags, with output parsed into structured results.
+//!
+//! Each call is classified by what it touches so that approval can tell a
+//! `git status` from a `git commit`. Read-only calls never prompt, local
+//! writes follow the exec policy like any other command, and commits and
+//! remote operations ask for approval unless the policy is `Never` or an
+//! execpolicy rule allows them. Remote operations are only offered when
+//! `git_tool_remote` is enabled.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecToolCallOutput;
+use crate::function_tool::FunctionCallError;
+use crate::protocol::AskForApproval;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::sandbox_retry_may_prompt;
+use crate::tools::handlers::workdir::reject_workdir;
+use crate::tools::handlers::workdir::resolve_workdir;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::shell::ShellRequest;
+use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::ToolCtx;
+use crate::truncate::truncate_text;
+
+pub struct GitHandler;
+
+const DEFAULT_LOG_COUNT: usize = 20;
+const MAX_LOG_COUNT: usize = 200;
+/// Separates the fields of a `log`, `branch` or `stash list` record.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Ends a `log` record; subjects cannot contain it.
+const RECORD_SEPARATOR: char = '\x1e';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GitSubcommand {
+    Status,
+    Diff,
+    Log,
+    Branch,
+    Add,
+    Commit,
+    Stash,
+    Push,
+    Pull,
+    Fetch,
+}
+
+impl GitSubcommand {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Diff => "diff",
+            Self::Log => "log",
+            Self::Branch => "branch",
+            Self::Add => "add",
+            Self::Commit => "commit",
+            Self::Stash => "stash",
+            Self::Push => "push",
+            Self::Pull => "pull",
+            Self::Fetch => "fetch",
+        }
+    }
+
+    fn is_remote(self) -> bool {
+        matches!(self, Self::Push | Self::Pull | Self::Fetch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StashAction {
+    #[default]
+    Push,
+    Pop,
+    List,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitArgs {
+    subcommand: GitSubcommand,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    staged: bool,
+    #[serde(default)]
+    max_count: Option<usize>,
+    /// Branch to create; `branch` lists branches without it.
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    start_point: Option<String>,
+    #[serde(default)]
+    action: StashAction,
+    #[serde(default)]
+    remote: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    workdir: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+/// What a call can change, which decides how it is approved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GitAccess {
+    ReadOnly,
+    /// Changes the index, branches or stashes.
+    LocalWrite,
+    /// Records a commit.
+    Commit,
+    /// Talks to a remote.
+    Remote,
+}
+
+impl GitArgs {
+    fn access(&self) -> GitAccess {
+        match self.subcommand {
+            GitSubcommand::Status | GitSubcommand::Diff | GitSubcommand::Log => GitAccess::ReadOnly,
+            GitSubcommand::Branch if self.name.is_none() => GitAccess::ReadOnly,
+            GitSubcommand::Stash if self.action == StashAction::List => GitAccess::ReadOnly,
+            GitSubcommand::Branch | GitSubcommand::Add | GitSubcommand::Stash => {
+                GitAccess::LocalWrite
+            }
+            GitSubcommand::Commit => GitAccess::Commit,
+            GitSubcommand::Push | GitSubcommand::Pull | GitSubcommand::Fetch => GitAccess::Remote,
+        }
+    }
+
+    /// The arguments after `git`, or the message for the model when the
+    /// call is missing something its subcommand needs.
+    fn git_args(&self) -> Result<Vec<String>, String> {
+        let mut args: Vec<String> = Vec::new();
+        let push = |args: &mut Vec<String>, parts: &[&str]| {
+            args.extend(parts.iter().map(ToString::to_string));
+        };
+        match self.subcommand {
+            GitSubcommand::Status => {
+                push(&mut args, &["status", "--porcelain=v2", "--branch", "-z"]);
+                self.push_paths(&mut args);
+            }
+            GitSubcommand::Diff => {
+                push(&mut args, &["diff"]);
+                if self.staged {
+                    push(&mut args, &["--cached"]);
+                }
+                push(&mut args, &["--no-color", "--no-ext-diff"]);
+                self.push_paths(&mut args);
+            }
+            GitSubcommand::Log => {
+                let count = self
+                    .max_count
+                    .unwrap_or(DEFAULT_LOG_COUNT)
+                    .clamp(1, MAX_LOG_COUNT)
+                    .to_string();
+                push(
+                    &mut args,
+                    &[
+                        "log",
+                        "-n",
+                        count.as_str(),
+                        "--format=%H%x1f%an%x1f%aI%x1f%s%x1e",
+                    ],
+                );
+                self.push_paths(&mut args);
+            }
+            GitSubcommand::Branch => match &self.name {
+                None => push(
+                    &mut args,
+                    &[
+                        "branch",
+                        "--list",
+                        "--format=%(HEAD)%1f%(refname:short)%1f%(upstream:short)%1f%(objectname:short)",
+                    ],
+                ),
+                Some(name) => {
+                    check_ref("name", name)?;
+                    push(&mut args, &["branch", name.as_str()]);
+                    if let Some(start_point) = &self.start_point {
+                        check_ref("start_point", start_point)?;
+                        push(&mut args, &[start_point.as_str()]);
+                    }
+                }
+            },
+            GitSubcommand::Add => {
+                if self.paths.is_empty() {
+                    return Err("add needs at least one path".to_string());
+                }
+                push(&mut args, &["add"]);
+                self.push_paths(&mut args);
+            }
+            GitSubcommand::Commit => {
+                let message = self
+                    .message
+                    .as_deref()
+                    .filter(|message| !message.trim().is_empty())
+                    .ok_or_else(|| "commit needs a message".to_string())?;
+                push(&mut args, &["commit", "-m", message]);
+            }
+            GitSubcommand::Stash => match self.action {
+                StashAction::Push => {
+                    push(&mut args, &["stash", "push"]);
+                    if let Some(message) = &self.message {
+                        push(&mut args, &["-m", message.as_str()]);
+                    }
+                }
+                StashAction::Pop => push(&mut args, &["stash", "pop"]),
+                StashAction::List => push(&mut args, &["stash", "list", "--format=%gd%x1f%s"]),
+            },
+            GitSubcommand::Push | GitSubcommand::Pull | GitSubcommand::Fetch => {
+                push(&mut args, &[self.subcommand.name()]);
+                if self.subcommand == GitSubcommand::Pull {
+                    push(&mut args, &["--ff-only"]);
+                }
+                if let Some(remote) = &self.remote {
+                    check_ref("remote", remote)?;
+                    push(&mut args, &[remote.as_str()]);
+                    if let Some(branch) = &self.branch
+                        && self.subcommand != GitSubcommand::Fetch
+                    {
+                        check_ref("branch", branch)?;
+                        push(&mut args, &[branch.as_str()]);
+                    }
+                } else if self.branch.is_some() {
+                    return Err("branch needs a remote".to_string());
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    fn push_paths(&self, args: &mut Vec<String>) {
+        if !self.paths.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.paths.iter().cloned());
+        }
+    }
+}
+
+/// Rejects a ref or remote name git would read as an option.
+fn check_ref(field: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() || value.starts_with('-') {
+        Err(format!("{field} must be a ref name, got '{value}'"))
+    } else {
+        Ok(())
+    }
+}
+
+/// The argv that runs `git_args`. Global options keep the output free of
+/// pagers and colour.
+fn git_command(git_args: &[String]) -> Vec<String> {
+    ["git", "--no-pager", "-c", "color.ui=false"]
+        .into_iter()
+        .map(ToString::to_string)
+        .chain(git_args.iter().cloned())
+        .collect()
+}
+
+#[async_trait]
+impl ToolHandler for GitHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn matches_kind(&self, payload: &ToolPayload) -> bool {
+        matches!(payload, ToolPayload::Function { .. })
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return true;
+        };
+        serde_json::from_str::<GitArgs>(arguments)
+            .map(|args| args.access() != GitAccess::ReadOnly)
+            .unwrap_or(true)
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn is_parallel_safe(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return false;
+        };
+        serde_json::from_str::<GitArgs>(arguments)
+            .is_ok_and(|args| args.access() == GitAccess::ReadOnly)
+            && !sandbox_retry_may_prompt(&invocation.turn)
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "git handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: GitArgs = parse_arguments(&arguments)?;
+        if args.subcommand.is_remote() && !turn.tools_config.git_remote_operations {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "git {} is not enabled; remote operations need the git_tool_remote feature",
+                args.subcommand.name()
+            )));
+        }
+        let git_args = args.git_args().map_err(FunctionCallError::RespondToModel)?;
+        let command = git_command(&git_args);
+        let tool_timeouts = session.tool_timeouts().await;
+        let timeout_ms =
+            ExecExpiration::Timeout(tool_timeouts.resolve(args.timeout_ms, tool_timeouts.shell))
+                .timeout_ms();
+
+        let requested = turn.resolve_path(args.workdir.clone());
+        let cwd = match resolve_workdir(&turn.cwd, &requested) {
+            Ok(cwd) => cwd,
+            Err(message) => {
+                let emitter = ToolEmitter::shell(
+                    command,
+                    requested,
+                    ExecCommandSource::ModelShell,
+                    false,
+                    timeout_ms,
+                    session.as_ref(),
+                );
+                let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+                return Err(reject_workdir(emitter, event_ctx, message).await);
+            }
+        };
+
+        let emitter = ToolEmitter::shell(
+            command.clone(),
+            cwd.clone(),
+            ExecCommandSource::ModelShell,
+            false,
+            timeout_ms,
+            session.as_ref(),
+        );
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        if turn.plan_only {
+            return Ok(emitter.propose(event_ctx).await);
+        }
+        emitter.begin(event_ctx).await;
+
+        let policy_command: Vec<String> = std::iter::once("git".to_string())
+            .chain(git_args.iter().cloned())
+            .collect();
+        let exec_approval_requirement =
+            approval_requirement(&session, &turn, args.access(), &policy_command).await;
+        let req = ShellRequest {
+            command,
+            cwd,
+            timeout_ms,
+            env: git_env(&turn),
+            env_overrides: std::collections::HashMap::new(),
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            justification: None,
+            exec_approval_requirement,
+        };
+        let mut orchestrator = ToolOrchestrator::new();
+        let mut runtime = ShellRuntime::new();
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id: call_id.clone(),
+            tool_name,
+        };
+        let out = orchestrator
+            .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
+            .await;
+
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        let output = match out {
+            Ok(output) if output.exit_code == 0 => output,
+            out => {
+                let content = emitter.finish(event_ctx, out).await?;
+                return Ok(ToolOutput::Function {
+                    content,
+                    content_items: None,
+                    success: Some(true),
+                });
+            }
+        };
+
+        let (result, summary) = summarize(&args, &output, &turn);
+        emitter
+            .finish_summarized(event_ctx, output, summary.clone())
+            .await;
+        let mut result = result;
+        if let Value::Object(fields) = &mut result {
+            fields.insert("summary".to_string(), Value::String(summary));
+        }
+        Ok(ToolOutput::Function {
+            content: result.to_string(),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// The approval a call needs. See the module docs for the classification.
+async fn approval_requirement(
+    session: &Session,
+    turn: &TurnContext,
+    access: GitAccess,
+    policy_command: &[String],
+) -> ExecApprovalRequirement {
+    let requirement = session
+        .services
+        .exec_policy
+        .create_exec_approval_requirement_for_command(
+            &session.features(),
+            policy_command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+    match (access, requirement) {
+        (_, forbidden @ ExecApprovalRequirement::Forbidden { .. }) => forbidden,
+        (GitAccess::ReadOnly, _) => ExecApprovalRequirement::Skip {
+            bypass_sandbox: false,
+            proposed_execpolicy_amendment: None,
+        },
+        (
+            GitAccess::Commit | GitAccess::Remote,
+            ExecApprovalRequirement::Skip {
+                bypass_sandbox: false,
+                proposed_execpolicy_amendment,
+            },
+        ) if turn.approval_policy != AskForApproval::Never => {
+            ExecApprovalRequirement::NeedsApproval {
+                reason: Some(format!(
+                    "`{}` changes the repository history or a remote",
+                    policy_command.join(" ")
+                )),
+                proposed_execpolicy_amendment,
+            }
+        }
+        (_, requirement) => requirement,
+    }
+}
+
+/// The shell environment, with anything that could make git wait for input
+/// or take optional locks turned off.
+fn git_env(turn: &TurnContext) -> std::collections::HashMap<String, String> {
+    let mut env = turn.exec_env();
+    for (key, value) in [
+        ("GIT_TERMINAL_PROMPT", "0"),
+        ("GIT_OPTIONAL_LOCKS", "0"),
+        ("GIT_EDITOR", "true"),
+    ] {
+        env.insert(key.to_string(), value.to_string());
+    }
+    env
+}
+
+/// The structured result for the model and the one-line summary for the end
+/// event of a call that exited successfully.
+fn summarize(args: &GitArgs, output: &ExecToolCallOutput, turn: &TurnContext) -> (Value, String) {
+    let stdout = output.stdout.text.as_str();
+    match args.subcommand {
+        GitSubcommand::Status => {
+            let status = parse_status(stdout);
+            let summary = status.summary();
+            (json!(status), summary)
+        }
+        GitSubcommand::Diff => {
+            let files = parse_diff(stdout);
+            let added: usize = files.iter().map(|file| file.added).sum();
+            let removed: usize = files.iter().map(|file| file.removed).sum();
+            let summary = format!(
+                "{} changed, +{added} -{removed}",
+                plural(files.len(), "file")
+            );
+            let patch = truncate_text(stdout, turn.exec_output_truncation_policy());
+            (json!({ "files": files, "patch": patch }), summary)
+        }
+        GitSubcommand::Log => {
+            let commits = parse_log(stdout);
+            let summary = plural(commits.len(), "commit");
+            (json!({ "commits": commits }), summary)
+        }
+        GitSubcommand::Branch => match &args.name {
+            None => {
+                let branches = parse_branches(stdout);
+                let summary = match branches.iter().find(|branch| branch.current) {
+                    Some(current) => {
+                        format!("{}, on {}", plural(branches.len(), "branch"), current.name)
+                    }
+                    None => plural(branches.len(), "branch"),
+                };
+                (json!({ "branches": branches }), summary)
+            }
+            Some(name) => (json!({ "created": name }), format!("created branch {name}")),
+        },
+        GitSubcommand::Add => (
+            json!({ "staged": args.paths }),
+            format!("staged {}", plural(args.paths.len(), "path")),
+        ),
+        GitSubcommand::Commit => match parse_commit_line(stdout) {
+            Some(commit) => {
+                let summary = format!(
+                    "committed {} on {}: {}",
+                    commit.commit, commit.branch, commit.subject
+                );
+                (json!(commit), summary)
+            }
+            None => (json!({ "output": stdout.trim() }), "committed".to_string()),
+        },
+        GitSubcommand::Stash => match args.action {
+            StashAction::List => {
+                let stashes = parse_stashes(stdout);
+                let summary = plural(stashes.len(), "stash");
+                (json!({ "stashes": stashes }), summary)
+            }
+            StashAction::Push => (
+                json!({ "output": stdout.trim() }),
+                "stashed changes".to_string(),
+            ),
+            StashAction::Pop => (
+                json!({ "output": stdout.trim() }),
+                "applied and dropped the latest stash".to_string(),
+            ),
+        },
+        GitSubcommand::Push | GitSubcommand::Pull | GitSubcommand::Fetch => {
+            // Progress and ref updates go to stderr.
+            let text = output.aggregated_output.text.trim();
+            let target = args.remote.as_deref().unwrap_or("the default remote");
+            let summary = match args.subcommand {
+                GitSubcommand::Push => format!("pushed to {target}"),
+                GitSubcommand::Pull => format!("pulled from {target}"),
+                _ => format!("fetched from {target}"),
+            };
+            (json!({ "output": text }), summary)
+        }
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun) {
+        (1, _) => format!("1 {noun}"),
+        (_, "branch" | "stash") => format!("{count} {noun}es"),
+        _ => format!("{count} {noun}s"),
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct GitStatus {
+    /// `None` when HEAD is detached.
+    branch: Option<String>,
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    entries: Vec<StatusEntry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct StatusEntry {
+    path: String,
+    /// Status in the index: a `git status --short` letter, `.` when
+    /// unchanged, `?` for untracked and `!` for ignored files.
+    index: char,
+    /// Status in the working tree, with the same letters as `index`.
+    worktree: char,
+    /// Where a renamed or copied file came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_path: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    conflicted: bool,
+}
+
+impl GitStatus {
+    fn summary(&self) -> String {
+        let mut summary = match &self.branch {
+            Some(branch) => format!("on {branch}"),
+            None => "HEAD detached".to_string(),
+        };
+        if self.ahead > 0 || self.behind > 0 {
+            summary.push_str(&format!(" (ahead {}, behind {})", self.ahead, self.behind));
+        }
+        let count =
+            |pred: fn(&StatusEntry) -> bool| self.entries.iter().filter(|e| pred(e)).count();
+        let staged = count(|entry| !matches!(entry.index, '.' | '?' | '!'));
+        let unstaged = count(|entry| !matches!(entry.worktree, '.' | '?' | '!'));
+        let untracked = count(|entry| entry.index == '?');
+        if staged + unstaged + untracked == 0 {
+            summary.push_str(", clean");
+        } else {
+            summary.push_str(&format!(
+                ": {staged} staged, {unstaged} unstaged, {untracked} untracked"
+            ));
+        }
+        summary
+    }
+}
+
+/// One-line summary of `git status --porcelain=v2 --branch -z` output,
+/// such as `on main: 2 staged, 1 unstaged, 0 untracked`.
+pub(crate) fn status_summary(stdout: &str) -> String {
+    parse_status(stdout).summary()
+}
+
+/// Parses `git status --porcelain=v2 --branch -z`.
+fn parse_status(stdout: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut records = stdout.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        let Some((kind, rest)) = record.split_once(' ') else {
+            continue;
+        };
+        match kind {
+            "#" => {
+                let Some((key, value)) = rest.split_once(' ') else {
+                    continue;
+                };
+                match key {
+                    "branch.head" if value != "(detached)" => {
+                        status.branch = Some(value.to_string());
+                    }
+                    "branch.upstream" => status.upstream = Some(value.to_string()),
+                    "branch.ab" => {
+                        for count in value.split(' ') {
+                            if let Some(ahead) = count.strip_prefix('+') {
+                                status.ahead = ahead.parse().unwrap_or(0);
+                            } else if let Some(behind) = count.strip_prefix('-') {
+                                status.behind = behind.parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // Ordinary, renamed or copied, and unmerged entries: the status
+            // letters, then fields up to the path, which may hold spaces.
+            "1" | "2" | "u" => {
+                let fields = match kind {
+                    "1" => 8,
+                    "2" => 9,
+                    _ => 10,
+                };
+                let mut parts = rest.splitn(fields, ' ');
+                let mut letters = parts.next().unwrap_or("..").chars();
+                let Some(path) = parts.nth(fields - 2) else {
+                    continue;
+                };
+                let original_path = if kind == "2" {
+                    records.next().map(str::to_string)
+                } else {
+                    None
+                };
+                status.entries.push(StatusEntry {
+                    path: path.to_string(),
+                    index: letters.next().unwrap_or('.'),
+                    worktree: letters.next().unwrap_or('.'),
+                    original_path,
+                    conflicted: kind == "u",
+                });
+            }
+            "?" | "!" => {
+                let letter = if kind == "?" { '?' } else { '!' };
+                status.entries.push(StatusEntry {
+                    path: rest.to_string(),
+                    index: letter,
+                    worktree: letter,
+                    original_path: None,
+                    conflicted: false,
+                });
+            }
+            _ => {}
+        }
+    }
+    status
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct DiffFile {
+    path: String,
+    added: usize,
+    removed: usize,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    binary: bool,
+}
+
+/// Counts the added and removed lines of each file in a unified diff.
+fn parse_diff(patch: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut in_hunk = false;
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header
+                .rsplit_once(" b/")
+                .map_or(header, |(_, path)| path)
+                .to_string();
+            files.push(DiffFile {
+                path,
+                added: 0,
+                removed: 0,
+                binary: false,
+            });
+            in_hunk = false;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if !in_hunk {
+            if line.starts_with("Binary files ") {
+                file.binary = true;
+            }
+        } else if line.starts_with('+') {
+            file.added += 1;
+        } else if line.starts_with('-') {
+            file.removed += 1;
+        }
+    }
+    files
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct LogEntry {
+    hash: String,
+    author: String,
+    /// ISO 8601 author date.
+    date: String,
+    subject: String,
+}
+
+fn parse_log(stdout: &str) -> Vec<LogEntry> {
+    stdout
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split(FIELD_SEPARATOR);
+            Some(LogEntry {
+                hash: fields.next().filter(|hash| !hash.is_empty())?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct BranchEntry {
+    name: String,
+    current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<String>,
+    commit: String,
+}
+
+fn parse_branches(stdout: &str) -> Vec<BranchEntry> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(FIELD_SEPARATOR);
+            let head = fields.next()?;
+            let name = fields.next()?.to_string();
+            let upstream = fields.next()?;
+            let commit = fields.next()?.to_string();
+            Some(BranchEntry {
+                name,
+                current: head == "*",
+                upstream: (!upstream.is_empty()).then(|| upstream.to_string()),
+                commit,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct StashEntry {
+    name: String,
+    message: String,
+}
+
+fn parse_stashes(stdout: &str) -> Vec<StashEntry> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, message) = line.split_once(FIELD_SEPARATOR)?;
+            Some(StashEntry {
+                name: name.to_string(),
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct CommitResult {
+    branch: String,
+    commit: String,
+    subject: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    root_commit: bool,
+}
+
+/// Parses the first line `git commit` prints, such as
+/// `[main (root-commit) 1a2b3c4] Initial commit`.
+fn parse_commit_line(stdout: &str) -> Option<CommitResult> {
+    let line = stdout.lines().next()?.strip_prefix('[')?;
+    let (header, subject) = line.split_once("] ")?;
+    let (branch, commit) = header.rsplit_once(' ')?;
+    let (branch, root_commit) = match branch.strip_suffix(" (root-commit)") {
+        Some(branch) => (branch, true),
+        None => (branch, false),
+    };
+    Some(CommitResult {
+        branch: branch.to_string(),
+        commit: commit.to_string(),
+        subject: subject.to_string(),
+        root_commit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn args(json: Value) -> GitArgs {
+        serde_json::from_value(json).expect("valid git args")
+    }
+
+    #[test]
+    fn subcommands_are_classified_for_approval() {
+        let cases = [
+            (json!({"subcommand": "status"}), GitAccess::ReadOnly),
+            (json!({"subcommand": "log"}), GitAccess::ReadOnly),
+            (json!({"subcommand": "branch"}), GitAccess::ReadOnly),
+            (
+                json!({"subcommand": "stash", "action": "list"}),
+                GitAccess::ReadOnly,
+            ),
+            (
+                json!({"subcommand": "branch", "name": "topic"}),
+                GitAccess::LocalWrite,
+            ),
+            (json!({"subcommand": "stash"}), GitAccess::LocalWrite),
+            (
+                json!({"subcommand": "add", "paths": ["a.rs"]}),
+                GitAccess::LocalWrite,
+            ),
+            (
+                json!({"subcommand": "commit", "message": "m"}),
+                GitAccess::Commit,
+            ),
+            (json!({"subcommand": "push"}), GitAccess::Remote),
+        ];
+        for (json, expected) in cases {
+            assert_eq!(args(json.clone()).access(), expected, "{json}");
+        }
+    }
+
+    #[test]
+    fn git_args_use_machine_readable_flags_and_reject_option_like_refs() {
+        assert_eq!(
+            git_command(
+                &args(json!({"subcommand": "diff", "staged": true, "paths": ["src"]}))
+                    .git_args()
+                    .unwrap()
+            ),
+            [
+                "git",
+                "--no-pager",
+                "-c",
+                "color.ui=false",
+                "diff",
+                "--cached",
+                "--no-color",
+                "--no-ext-diff",
+                "--",
+                "src",
+            ]
+        );
+        assert_eq!(
+            args(json!({"subcommand": "branch", "name": "--delete"})).git_args(),
+            Err("name must be a ref name, got '--delete'".to_string())
+        );
+        assert_eq!(
+            args(json!({"subcommand": "commit"})).git_args(),
+            Err("commit needs a message".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_porcelain_v2_status() {
+        let stdout = [
+            "# branch.oid 1234567890abcdef",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 M. N... 100644 100644 100644 aaa bbb src/lib.rs",
+            "1 .M N... 100644 100644 100644 aaa bbb docs/with space.md",
+            "2 R. N... 100644 100644 100644 aaa bbb R100 new.rs",
+            "old.rs",
+            "? notes.txt",
+            "",
+        ]
+        .join("\0");
+
+        let status = parse_status(&stdout);
+
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(
+            status.entries[1],
+            StatusEntry {
+                path: "docs/with space.md".to_string(),
+                index: '.',
+                worktree: 'M',
+                original_path: None,
+                conflicted: false,
+            }
+        );
+        assert_eq!(status.entries[2].original_path.as_deref(), Some("old.rs"));
+        assert_eq!(
+            status.summary(),
+            "on main (ahead 2, behind 1): 2 staged, 1 unstaged, 1 untracked"
+        );
+    }
+
+    #[test]
+    fn parses_diff_log_branch_and_commit_output() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                     index aaa..bbb 100644\n\
+                     --- a/src/lib.rs\n\
+                     +++ b/src/lib.rs\n\
+                     @@ -1,2 +1,2 @@\n\
+                     -old\n\
+                     +new\n\
+                     +more\n\
+                     diff --git a/logo.png b/logo.png\n\
+                     Binary files a/logo.png and b/logo.png differ\n";
+        assert_eq!(
+            parse_diff(patch),
+            vec![
+                DiffFile {
+                    path: "src/lib.rs".to_string(),
+                    added: 2,
+                    removed: 1,
+                    binary: false,
+                },
+                DiffFile {
+                    path: "logo.png".to_string(),
+                    added: 0,
+                    removed: 0,
+                    binary: true,
+                },
+            ]
+        );
+
+        let log = "abc\x1fAda\x1f2024-01-02T03:04:05+00:00\x1fFix it\x1e\n\
+                   def\x1fBob\x1f2024-01-01T00:00:00+00:00\x1fStart\x1e\n";
+        let commits = parse_log(log);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[1].subject, "Start");
+
+        let branches =
+            parse_branches("*\x1fmain\x1forigin/main\x1fabc1234\n \x1ftopic\x1f\x1fdef5678\n");
+        assert!(branches[0].current);
+        assert_eq!(branches[1].upstream, None);
+
+        assert_eq!(
+            parse_commit_line("[main (root-commit) 1a2b3c4] Initial commit\n 1 file changed\n"),
+            Some(CommitResult {
+                branch: "main".to_string(),
+                commit: "1a2b3c4".to_string(),
+                subject: "Initial commit".to_string(),
+                root_commit: true,
+            })
+        );
+    }
+}
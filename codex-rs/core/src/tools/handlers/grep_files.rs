@@ -41,6 +41,10 @@ impl ToolHandler for GrepFilesHandler {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation { payload, turn, .. } = invocation;
 
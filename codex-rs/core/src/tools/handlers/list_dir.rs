@@ -1,48 +1,95 @@
-use std::collections::VecDeque;
-use std::ffi::OsStr;
-use std::fs::FileType;
 use std::path::Path;
-use std::path::PathBuf;
 
 use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
 use codex_utils_string::take_bytes_at_char_boundary;
+use ignore::DirEntry;
 use serde::Deserialize;
-use tokio::fs;
+use serde::Serialize;
 
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::events::ListDirSummary;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::workspace::workspace_path;
+use crate::tools::handlers::workspace::workspace_walker;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
 pub struct ListDirHandler;
 
 const MAX_ENTRY_LENGTH: usize = 500;
-const INDENTATION_SPACES: usize = 2;
+/// Deeper requests are clamped, so a listing of the repository root stays a
+/// listing rather than a dump of every dependency tree under it.
+const MAX_DEPTH: usize = 5;
+const DEFAULT_MAX_ENTRIES: usize = 100;
+const MAX_ENTRIES: usize = 500;
 
 fn default_offset() -> usize {
     1
 }
 
-fn default_limit() -> usize {
-    25
+fn default_depth() -> usize {
+    1
 }
 
-fn default_depth() -> usize {
-    2
+fn default_max_entries() -> usize {
+    DEFAULT_MAX_ENTRIES
 }
 
 #[derive(Deserialize)]
 struct ListDirArgs {
-    dir_path: String,
-    #[serde(default = "default_offset")]
-    offset: usize,
-    #[serde(default = "default_limit")]
-    limit: usize,
+    /// Absolute, or relative to the turn's working directory.
+    #[serde(alias = "dir_path")]
+    path: String,
     #[serde(default = "default_depth")]
     depth: usize,
+    /// Also list dotfiles. Entries excluded by ignore files stay hidden.
+    #[serde(default)]
+    include_hidden: bool,
+    /// 1-indexed entry to start from, for paging through a long listing.
+    #[serde(default = "default_offset")]
+    offset: usize,
+    #[serde(alias = "limit", default = "default_max_entries")]
+    max_entries: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Entry {
+    /// Relative to the listed directory, with `/` separators.
+    name: String,
+    kind: EntryKind,
+    /// In bytes; files only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    /// RFC 3339, in UTC.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntryKind {
+    Directory,
+    File,
+    Symlink,
+    Other,
+}
+
+#[derive(Serialize)]
+struct Listing {
+    path: String,
+    entries: Vec<Entry>,
+    /// Says how many entries were left out and how to get them; absent when
+    /// the listing is complete.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated: Option<String>,
 }
 
 #[async_trait]
@@ -51,8 +98,18 @@ impl ToolHandler for ListDirHandler {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -66,10 +123,11 @@ impl ToolHandler for ListDirHandler {
         let args: ListDirArgs = parse_arguments(&arguments)?;
 
         let ListDirArgs {
-            dir_path,
-            offset,
-            limit,
+            path,
             depth,
+            include_hidden,
+            offset,
+            max_entries,
         } = args;
 
         if offset == 0 {
@@ -78,9 +136,9 @@ impl ToolHandler for ListDirHandler {
             ));
         }
 
-        if limit == 0 {
+        if max_entries == 0 {
             return Err(FunctionCallError::RespondToModel(
-                "limit must be greater than zero".to_string(),
+                "max_entries must be greater than zero".to_string(),
             ));
         }
 
@@ -90,181 +148,122 @@ impl ToolHandler for ListDirHandler {
             ));
         }
 
-        let path = PathBuf::from(&dir_path);
-        if !path.is_absolute() {
-            return Err(FunctionCallError::RespondToModel(
-                "dir_path must be an absolute path".to_string(),
-            ));
-        }
-
-        let entries = list_dir_slice(&path, offset, limit, depth).await?;
-        let mut output = Vec::with_capacity(entries.len() + 1);
-        output.push(format!("Absolute path: {}", path.display()));
-        output.extend(entries);
+        let depth = depth.min(MAX_DEPTH);
+        let max_entries = max_entries.min(MAX_ENTRIES);
+        let path = turn.resolve_path(Some(path));
+        let emitter = ToolEmitter::list_dir(path.clone(), depth);
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        emitter.begin(event_ctx).await;
+
+        let result = match workspace_path(&turn, &path) {
+            Ok(root) => tokio::task::spawn_blocking(move || {
+                list(&root, depth, include_hidden, offset, max_entries)
+            })
+            .await
+            .unwrap_or_else(|err| {
+                Err(FunctionCallError::RespondToModel(format!(
+                    "failed to read directory: {err}"
+                )))
+            }),
+            Err(err) => Err(err),
+        };
+        let summary = result.as_ref().map(|listing| ListDirSummary {
+            entry_count: listing.entries.len(),
+            truncated: listing.truncated.is_some(),
+        });
+        emitter.finish_list_dir(event_ctx, summary).await;
+        let listing = result?;
+
+        let content = serde_json::to_string(&listing).map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to serialize entries: {err}"))
+        })?;
         Ok(ToolOutput::Function {
-            content: output.join("\n"),
+            content,
             content_items: None,
             success: Some(true),
         })
     }
 }
 
-async fn list_dir_slice(
-    path: &Path,
-    offset: usize,
-    limit: usize,
+/// Lists what [`workspace_walker`] finds under `root` down to `depth` levels,
+/// each directory's entries directly after it and in file name order, then
+/// returns `max_entries` of them starting at the 1-indexed `offset`.
+fn list(
+    root: &Path,
     depth: usize,
-) -> Result<Vec<String>, FunctionCallError> {
-    let mut entries = Vec::new();
-    collect_entries(path, Path::new(""), depth, &mut entries).await?;
-
-    if entries.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
-
-    let start_index = offset - 1;
-    if start_index >= entries.len() {
-        return Err(FunctionCallError::RespondToModel(
-            "offset exceeds directory entry count".to_string(),
-        ));
-    }
-
-    let remaining_entries = entries.len() - start_index;
-    let capped_limit = limit.min(remaining_entries);
-    let end_index = start_index + capped_limit;
-    let selected_entries = &entries[start_index..end_index];
-    let mut formatted = Vec::with_capacity(selected_entries.len());
-
-    for entry in selected_entries {
-        formatted.push(format_entry_line(entry));
-    }
-
-    if end_index < entries.len() {
-        formatted.push(format!("More than {capped_limit} entries found"));
+    include_hidden: bool,
+    offset: usize,
+    max_entries: usize,
+) -> Result<Listing, FunctionCallError> {
+    if !root.is_dir() {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{} is not a directory",
+            root.display()
+        )));
     }
 
-    Ok(formatted)
-}
-
-async fn collect_entries(
-    dir_path: &Path,
-    relative_prefix: &Path,
-    depth: usize,
-    entries: &mut Vec<DirEntry>,
-) -> Result<(), FunctionCallError> {
-    let mut queue = VecDeque::new();
-    queue.push_back((dir_path.to_path_buf(), relative_prefix.to_path_buf(), depth));
+    let mut walker = workspace_walker(root, include_hidden);
+    walker.max_depth(Some(depth)).follow_links(false);
 
-    while let Some((current_dir, prefix, remaining_depth)) = queue.pop_front() {
-        let mut read_dir = fs::read_dir(&current_dir).await.map_err(|err| {
+    let mut entries = Vec::new();
+    let mut total = 0;
+    for entry in walker.build() {
+        let entry = entry.map_err(|err| {
             FunctionCallError::RespondToModel(format!("failed to read directory: {err}"))
         })?;
-
-        let mut dir_entries = Vec::new();
-
-        while let Some(entry) = read_dir.next_entry().await.map_err(|err| {
-            FunctionCallError::RespondToModel(format!("failed to read directory: {err}"))
-        })? {
-            let file_type = entry.file_type().await.map_err(|err| {
-                FunctionCallError::RespondToModel(format!("failed to inspect entry: {err}"))
-            })?;
-
-            let file_name = entry.file_name();
-            let relative_path = if prefix.as_os_str().is_empty() {
-                PathBuf::from(&file_name)
-            } else {
-                prefix.join(&file_name)
-            };
-
-            let display_name = format_entry_component(&file_name);
-            let display_depth = prefix.components().count();
-            let sort_key = format_entry_name(&relative_path);
-            let kind = DirEntryKind::from(&file_type);
-            dir_entries.push((
-                entry.path(),
-                relative_path,
-                kind,
-                DirEntry {
-                    name: sort_key,
-                    display_name,
-                    depth: display_depth,
-                    kind,
-                },
-            ));
+        if entry.depth() == 0 {
+            continue;
         }
-
-        dir_entries.sort_unstable_by(|a, b| a.3.name.cmp(&b.3.name));
-
-        for (entry_path, relative_path, kind, dir_entry) in dir_entries {
-            if kind == DirEntryKind::Directory && remaining_depth > 1 {
-                queue.push_back((entry_path, relative_path, remaining_depth - 1));
-            }
-            entries.push(dir_entry);
+        total += 1;
+        if total >= offset && entries.len() < max_entries {
+            entries.push(describe(root, &entry));
         }
     }
 
-    Ok(())
-}
-
-fn format_entry_name(path: &Path) -> String {
-    let normalized = path.to_string_lossy().replace("\\", "/");
-    if normalized.len() > MAX_ENTRY_LENGTH {
-        take_bytes_at_char_boundary(&normalized, MAX_ENTRY_LENGTH).to_string()
-    } else {
-        normalized
+    if total > 0 && offset > total {
+        return Err(FunctionCallError::RespondToModel(
+            "offset exceeds directory entry count".to_string(),
+        ));
     }
-}
 
-fn format_entry_component(name: &OsStr) -> String {
-    let normalized = name.to_string_lossy();
-    if normalized.len() > MAX_ENTRY_LENGTH {
-        take_bytes_at_char_boundary(&normalized, MAX_ENTRY_LENGTH).to_string()
-    } else {
-        normalized.to_string()
-    }
+    let shown_through = offset - 1 + entries.len();
+    let truncated = (shown_through < total).then(|| {
+        format!(
+            "{} more entries; pass offset {} to see them",
+            total - shown_through,
+            shown_through + 1
+        )
+    });
+    Ok(Listing {
+        path: root.display().to_string(),
+        entries,
+        truncated,
+    })
 }
 
-fn format_entry_line(entry: &DirEntry) -> String {
-    let indent = " ".repeat(entry.depth * INDENTATION_SPACES);
-    let mut name = entry.display_name.clone();
-    match entry.kind {
-        DirEntryKind::Directory => name.push('/'),
-        DirEntryKind::Symlink => name.push('@'),
-        DirEntryKind::Other => name.push('?'),
-        DirEntryKind::File => {}
-    }
-    format!("{indent}{name}")
-}
-
-#[derive(Clone)]
-struct DirEntry {
-    name: String,
-    display_name: String,
-    depth: usize,
-    kind: DirEntryKind,
-}
-
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum DirEntryKind {
-    Directory,
-    File,
-    Symlink,
-    Other,
-}
-
-impl From<&FileType> for DirEntryKind {
-    fn from(file_type: &FileType) -> Self {
-        if file_type.is_symlink() {
-            DirEntryKind::Symlink
-        } else if file_type.is_dir() {
-            DirEntryKind::Directory
-        } else if file_type.is_file() {
-            DirEntryKind::File
-        } else {
-            DirEntryKind::Other
-        }
+fn describe(root: &Path, entry: &DirEntry) -> Entry {
+    let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+    let name = relative.to_string_lossy().replace('\\', "/");
+    let name = take_bytes_at_char_boundary(&name, MAX_ENTRY_LENGTH).to_string();
+    let kind = match entry.file_type() {
+        Some(file_type) if file_type.is_symlink() => EntryKind::Symlink,
+        Some(file_type) if file_type.is_dir() => EntryKind::Directory,
+        Some(file_type) if file_type.is_file() => EntryKind::File,
+        _ => EntryKind::Other,
+    };
+    let metadata = entry.metadata().ok();
+    Entry {
+        name,
+        kind,
+        size: metadata
+            .as_ref()
+            .filter(|_| kind == EntryKind::File)
+            .map(std::fs::Metadata::len),
+        modified: metadata
+            .and_then(|metadata| metadata.modified().ok())
+            .map(|modified| {
+                DateTime::<Utc>::from(modified).to_rfc3339_opts(SecondsFormat::Secs, true)
+            }),
     }
 }
 
@@ -274,241 +273,145 @@ mod tests {
     use pretty_assertions::assert_eq;
     use tempfile::tempdir;
 
-    #[tokio::test]
-    async fn lists_directory_entries() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
-
-        let sub_dir = dir_path.join("nested");
-        tokio::fs::create_dir(&sub_dir)
-            .await
-            .expect("create sub dir");
-
-        let deeper_dir = sub_dir.join("deeper");
-        tokio::fs::create_dir(&deeper_dir)
-            .await
-            .expect("create deeper dir");
-
-        tokio::fs::write(dir_path.join("entry.txt"), b"content")
-            .await
-            .expect("write file");
-        tokio::fs::write(sub_dir.join("child.txt"), b"child")
-            .await
-            .expect("write child");
-        tokio::fs::write(deeper_dir.join("grandchild.txt"), b"grandchild")
-            .await
-            .expect("write grandchild");
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::symlink;
-            let link_path = dir_path.join("link");
-            symlink(dir_path.join("entry.txt"), &link_path).expect("create symlink");
-        }
-
-        let entries = list_dir_slice(dir_path, 1, 20, 3)
-            .await
-            .expect("list directory");
-
-        #[cfg(unix)]
-        let expected = vec![
-            "entry.txt".to_string(),
-            "link@".to_string(),
-            "nested/".to_string(),
-            "  child.txt".to_string(),
-            "  deeper/".to_string(),
-            "    grandchild.txt".to_string(),
-        ];
-
-        #[cfg(not(unix))]
-        let expected = vec![
-            "entry.txt".to_string(),
-            "nested/".to_string(),
-            "  child.txt".to_string(),
-            "  deeper/".to_string(),
-            "    grandchild.txt".to_string(),
-        ];
-
-        assert_eq!(entries, expected);
+    fn names(listing: &Listing) -> Vec<(&str, EntryKind, Option<u64>)> {
+        listing
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.kind, entry.size))
+            .collect()
     }
 
-    #[tokio::test]
-    async fn errors_when_offset_exceeds_entries() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
-        tokio::fs::create_dir(dir_path.join("nested"))
-            .await
-            .expect("create sub dir");
-
-        let err = list_dir_slice(dir_path, 10, 1, 2)
-            .await
-            .expect_err("offset exceeds entries");
-        assert_eq!(
-            err,
-            FunctionCallError::RespondToModel("offset exceeds directory entry count".to_string())
-        );
+    fn tree(root: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(root.join("nested/deeper"))?;
+        std::fs::write(root.join("root.txt"), b"root")?;
+        std::fs::write(root.join("nested/child.txt"), b"child")?;
+        std::fs::write(root.join("nested/deeper/grandchild.txt"), b"deep")?;
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn respects_depth_parameter() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
-        let nested = dir_path.join("nested");
-        let deeper = nested.join("deeper");
-        tokio::fs::create_dir(&nested).await.expect("create nested");
-        tokio::fs::create_dir(&deeper).await.expect("create deeper");
-        tokio::fs::write(dir_path.join("root.txt"), b"root")
-            .await
-            .expect("write root");
-        tokio::fs::write(nested.join("child.txt"), b"child")
-            .await
-            .expect("write nested");
-        tokio::fs::write(deeper.join("grandchild.txt"), b"deep")
-            .await
-            .expect("write deeper");
-
-        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1)
-            .await
-            .expect("list depth 1");
-        assert_eq!(
-            entries_depth_one,
-            vec!["nested/".to_string(), "root.txt".to_string(),]
-        );
+    #[test]
+    fn lists_each_directory_after_its_parent_down_to_depth() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        tree(root)?;
 
-        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2)
-            .await
-            .expect("list depth 2");
+        let listing = list(root, 1, false, 1, 10)?;
         assert_eq!(
-            entries_depth_two,
+            names(&listing),
             vec![
-                "nested/".to_string(),
-                "  child.txt".to_string(),
-                "  deeper/".to_string(),
-                "root.txt".to_string(),
+                ("nested", EntryKind::Directory, None),
+                ("root.txt", EntryKind::File, Some(4)),
             ]
         );
+        assert_eq!(listing.truncated, None);
+        assert!(listing.entries.iter().all(|entry| entry.modified.is_some()));
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3)
-            .await
-            .expect("list depth 3");
+        let listing = list(root, 3, false, 1, 10)?;
         assert_eq!(
-            entries_depth_three,
+            names(&listing),
             vec![
-                "nested/".to_string(),
-                "  child.txt".to_string(),
-                "  deeper/".to_string(),
-                "    grandchild.txt".to_string(),
-                "root.txt".to_string(),
+                ("nested", EntryKind::Directory, None),
+                ("nested/child.txt", EntryKind::File, Some(5)),
+                ("nested/deeper", EntryKind::Directory, None),
+                ("nested/deeper/grandchild.txt", EntryKind::File, Some(4)),
+                ("root.txt", EntryKind::File, Some(4)),
             ]
         );
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn paginates_in_sorted_order() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_listed_but_not_followed() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        tree(root)?;
+        std::os::unix::fs::symlink(root.join("nested"), root.join("link"))?;
 
-        let dir_a = dir_path.join("a");
-        let dir_b = dir_path.join("b");
-        tokio::fs::create_dir(&dir_a).await.expect("create a");
-        tokio::fs::create_dir(&dir_b).await.expect("create b");
+        let listing = list(root, 2, false, 1, 10)?;
 
-        tokio::fs::write(dir_a.join("a_child.txt"), b"a")
-            .await
-            .expect("write a child");
-        tokio::fs::write(dir_b.join("b_child.txt"), b"b")
-            .await
-            .expect("write b child");
-
-        let first_page = list_dir_slice(dir_path, 1, 2, 2)
-            .await
-            .expect("list page one");
         assert_eq!(
-            first_page,
+            names(&listing),
             vec![
-                "a/".to_string(),
-                "  a_child.txt".to_string(),
-                "More than 2 entries found".to_string()
+                ("link", EntryKind::Symlink, None),
+                ("nested", EntryKind::Directory, None),
+                ("nested/child.txt", EntryKind::File, Some(5)),
+                ("nested/deeper", EntryKind::Directory, None),
+                ("root.txt", EntryKind::File, Some(4)),
             ]
         );
-
-        let second_page = list_dir_slice(dir_path, 3, 2, 2)
-            .await
-            .expect("list page two");
-        assert_eq!(
-            second_page,
-            vec!["b/".to_string(), "  b_child.txt".to_string()]
-        );
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn handles_large_limit_without_overflow() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
-        tokio::fs::write(dir_path.join("alpha.txt"), b"alpha")
-            .await
-            .expect("write alpha");
-        tokio::fs::write(dir_path.join("beta.txt"), b"beta")
-            .await
-            .expect("write beta");
-        tokio::fs::write(dir_path.join("gamma.txt"), b"gamma")
-            .await
-            .expect("write gamma");
+    #[test]
+    fn hidden_entries_need_asking_for_and_ignored_ones_stay_out() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n")?;
+        std::fs::write(root.join(".env"), b"")?;
+        std::fs::create_dir(root.join("node_modules"))?;
+        std::fs::write(root.join("main.rs"), b"")?;
 
-        let entries = list_dir_slice(dir_path, 2, usize::MAX, 1)
-            .await
-            .expect("list without overflow");
+        let listing = list(root, 1, false, 1, 10)?;
+        assert_eq!(names(&listing), vec![("main.rs", EntryKind::File, Some(0))]);
+
+        let listing = list(root, 1, true, 1, 10)?;
         assert_eq!(
-            entries,
-            vec!["beta.txt".to_string(), "gamma.txt".to_string(),]
+            names(&listing),
+            vec![
+                (".env", EntryKind::File, Some(0)),
+                (".gitignore", EntryKind::File, Some(14)),
+                ("main.rs", EntryKind::File, Some(0)),
+            ]
         );
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn indicates_truncated_results() {
-        let temp = tempdir().expect("create tempdir");
-        let dir_path = temp.path();
-
-        for idx in 0..40 {
-            let file = dir_path.join(format!("file_{idx:02}.txt"));
-            tokio::fs::write(file, b"content")
-                .await
-                .expect("write file");
-        }
+    #[test]
+    fn truncation_says_how_many_entries_are_left_and_where_to_resume() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        tree(root)?;
 
-        let entries = list_dir_slice(dir_path, 1, 25, 1)
-            .await
-            .expect("list directory");
-        assert_eq!(entries.len(), 26);
+        let first_page = list(root, 3, false, 1, 3)?;
         assert_eq!(
-            entries.last(),
-            Some(&"More than 25 entries found".to_string())
+            names(&first_page),
+            vec![
+                ("nested", EntryKind::Directory, None),
+                ("nested/child.txt", EntryKind::File, Some(5)),
+                ("nested/deeper", EntryKind::Directory, None),
+            ]
+        );
+        assert_eq!(
+            first_page.truncated.as_deref(),
+            Some("2 more entries; pass offset 4 to see them")
         );
-    }
 
-    #[tokio::test]
-    async fn truncation_respects_sorted_order() -> anyhow::Result<()> {
-        let temp = tempdir()?;
-        let dir_path = temp.path();
-        let nested = dir_path.join("nested");
-        let deeper = nested.join("deeper");
-        tokio::fs::create_dir(&nested).await?;
-        tokio::fs::create_dir(&deeper).await?;
-        tokio::fs::write(dir_path.join("root.txt"), b"root").await?;
-        tokio::fs::write(nested.join("child.txt"), b"child").await?;
-        tokio::fs::write(deeper.join("grandchild.txt"), b"deep").await?;
-
-        let entries_depth_three = list_dir_slice(dir_path, 1, 3, 3).await?;
+        let second_page = list(root, 3, false, 4, 3)?;
         assert_eq!(
-            entries_depth_three,
+            names(&second_page),
             vec![
-                "nested/".to_string(),
-                "  child.txt".to_string(),
-                "  deeper/".to_string(),
-                "More than 3 entries found".to_string()
+                ("nested/deeper/grandchild.txt", EntryKind::File, Some(4)),
+                ("root.txt", EntryKind::File, Some(4)),
             ]
         );
+        assert_eq!(second_page.truncated, None);
+        Ok(())
+    }
+
+    #[test]
+    fn errors_when_offset_exceeds_entries() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        std::fs::create_dir(dir.path().join("nested"))?;
+
+        let err = list(dir.path(), 2, false, 10, 1)
+            .err()
+            .expect("offset exceeds entries");
 
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel("offset exceeds directory entry count".to_string())
+        );
         Ok(())
     }
 }
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 
 use async_trait::async_trait;
+use codex_utils_string::take_bytes_at_char_boundary;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
 use mcp_types::ListResourceTemplatesRequestParams;
@@ -19,19 +21,21 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use url::Url;
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::file_access::modified_time;
 use crate::function_tool::FunctionCallError;
-use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
-use crate::protocol::McpToolCallBeginEvent;
-use crate::protocol::McpToolCallEndEvent;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
+use crate::truncate::truncate_text;
 
 pub struct McpResourceHandler;
 
@@ -56,7 +60,14 @@ struct ListResourceTemplatesArgs {
 #[derive(Debug, Deserialize)]
 struct ReadResourceArgs {
     server: String,
-    uri: String,
+    #[serde(default)]
+    uri: Option<String>,
+    /// A `uriTemplate` from `list_mcp_resource_templates`, expanded with
+    /// `params` when `uri` is not given.
+    #[serde(default)]
+    uri_template: Option<String>,
+    #[serde(default)]
+    params: HashMap<String, Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -297,7 +308,7 @@ async fn handle_list_resources(
     .await;
 
     match payload_result {
-        Ok(payload) => match serialize_function_output(payload) {
+        Ok(payload) => match serialize_function_output(turn.as_ref(), payload) {
             Ok(output) => {
                 let ToolOutput::Function {
                     content, success, ..
@@ -408,7 +419,7 @@ async fn handle_list_resource_templates(
     .await;
 
     match payload_result {
-        Ok(payload) => match serialize_function_output(payload) {
+        Ok(payload) => match serialize_function_output(turn.as_ref(), payload) {
             Ok(output) => {
                 let ToolOutput::Function {
                     content, success, ..
@@ -460,6 +471,15 @@ async fn handle_list_resource_templates(
     }
 }
 
+/// The local file a `file://` resource URI names, if it is one.
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let url = Url::parse(uri).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    url.to_file_path().ok()
+}
+
 async fn handle_read_resource(
     session: Arc<Session>,
     turn: Arc<TurnContext>,
@@ -467,9 +487,32 @@ async fn handle_read_resource(
     arguments: Option<Value>,
 ) -> Result<ToolOutput, FunctionCallError> {
     let args: ReadResourceArgs = parse_args(arguments.clone())?;
-    let ReadResourceArgs { server, uri } = args;
+    let ReadResourceArgs {
+        server,
+        uri,
+        uri_template,
+        params,
+    } = args;
     let server = normalize_required_string("server", server)?;
-    let uri = normalize_required_string("uri", uri)?;
+    let uri = match (
+        normalize_optional_string(uri),
+        normalize_optional_string(uri_template),
+    ) {
+        (Some(uri), None) => uri,
+        (None, Some(template)) => {
+            expand_uri_template(&template, &params).map_err(FunctionCallError::RespondToModel)?
+        }
+        (Some(_), Some(_)) => {
+            return Err(FunctionCallError::RespondToModel(
+                "provide either uri or uri_template, not both".to_string(),
+            ));
+        }
+        (None, None) => {
+            return Err(FunctionCallError::RespondToModel(
+                "uri must be provided".to_string(),
+            ));
+        }
+    };
 
     let invocation = McpInvocation {
         server: server.clone(),
@@ -487,6 +530,10 @@ async fn handle_read_resource(
             .map_err(|err| {
                 FunctionCallError::RespondToModel(format!("resources/read failed: {err:#}"))
             })?;
+        if let Some(path) = file_uri_path(&uri) {
+            let modified = modified_time(&path).await;
+            session.record_file_read(path, modified).await;
+        }
 
         Ok(ReadResourcePayload {
             server,
@@ -497,7 +544,7 @@ async fn handle_read_resource(
     .await;
 
     match payload_result {
-        Ok(payload) => match serialize_function_output(payload) {
+        Ok(payload) => match serialize_function_output(turn.as_ref(), payload) {
             Ok(output) => {
                 let ToolOutput::Function {
                     content, success, ..
@@ -567,15 +614,8 @@ async fn emit_tool_call_begin(
     call_id: &str,
     invocation: McpInvocation,
 ) {
-    session
-        .send_event(
-            turn,
-            EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
-                call_id: call_id.to_string(),
-                invocation,
-            }),
-        )
-        .await;
+    let ctx = ToolEventCtx::new(session.as_ref(), turn, call_id, None);
+    ToolEmitter::mcp(invocation, None).begin(ctx).await;
 }
 
 async fn emit_tool_call_end(
@@ -586,16 +626,9 @@ async fn emit_tool_call_end(
     duration: Duration,
     result: Result<CallToolResult, String>,
 ) {
-    session
-        .send_event(
-            turn,
-            EventMsg::McpToolCallEnd(McpToolCallEndEvent {
-                call_id: call_id.to_string(),
-                invocation,
-                duration,
-                result,
-            }),
-        )
+    let ctx = ToolEventCtx::new(session.as_ref(), turn, call_id, None);
+    ToolEmitter::mcp(invocation, None)
+        .finish_mcp(ctx, duration, &result)
         .await;
 }
 
@@ -619,15 +652,28 @@ fn normalize_required_string(field: &str, value: String) -> Result<String, Funct
     }
 }
 
-fn serialize_function_output<T>(payload: T) -> Result<ToolOutput, FunctionCallError>
+/// Prepended to output that had to be cut as text and is no longer JSON.
+const TRUNCATED_JSON_NOTICE: &str = "[truncated MCP resource response; not valid JSON]";
+
+/// Serializes `payload` for the model within the turn's output budget. Long
+/// string fields are shortened first so the output stays valid JSON; only a
+/// payload too large even with every string cut short is truncated as text.
+fn serialize_function_output<T>(
+    turn: &TurnContext,
+    payload: T,
+) -> Result<ToolOutput, FunctionCallError>
 where
     T: Serialize,
 {
-    let content = serde_json::to_string(&payload).map_err(|err| {
+    let value = serde_json::to_value(&payload).map_err(|err| {
         FunctionCallError::RespondToModel(format!(
             "failed to serialize MCP resource response: {err}"
         ))
     })?;
+    let policy = turn.exec_output_truncation_policy();
+    let content = fit_json(&value, policy.byte_budget()).unwrap_or_else(|json| {
+        format!("{TRUNCATED_JSON_NOTICE}\n{}", truncate_text(&json, policy))
+    });
 
     Ok(ToolOutput::Function {
         content,
@@ -636,6 +682,126 @@ where
     })
 }
 
+/// Serializes `value` in at most `max_bytes`, cutting every string longer
+/// than the largest cap that fits. Returns the full JSON when no cap fits.
+fn fit_json(value: &Value, max_bytes: usize) -> Result<String, String> {
+    let full = value.to_string();
+    if full.len() <= max_bytes {
+        return Ok(full);
+    }
+    let mut fitted = None;
+    let (mut low, mut high) = (0, longest_string_len(value));
+    while low < high {
+        let cap = low + (high - low) / 2;
+        let mut capped = value.clone();
+        cap_strings(&mut capped, cap);
+        let json = capped.to_string();
+        if json.len() <= max_bytes {
+            fitted = Some(json);
+            low = cap + 1;
+        } else {
+            high = cap;
+        }
+    }
+    fitted.ok_or(full)
+}
+
+fn longest_string_len(value: &Value) -> usize {
+    match value {
+        Value::String(text) => text.len(),
+        Value::Array(items) => items.iter().map(longest_string_len).max().unwrap_or(0),
+        Value::Object(fields) => fields.values().map(longest_string_len).max().unwrap_or(0),
+        Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+    }
+}
+
+fn cap_strings(value: &mut Value, cap: usize) {
+    match value {
+        Value::String(text) if text.len() > cap => {
+            let kept = take_bytes_at_char_boundary(text, cap);
+            *text = format!("{kept}…[{} bytes truncated]", text.len() - kept.len());
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| cap_strings(item, cap)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| cap_strings(field, cap)),
+        _ => {}
+    }
+}
+
+/// Expands the RFC 6570 expressions `{var}`, `{+var}`, `{?var,...}`, and
+/// `{&var,...}` in a resource template. Variables in query expressions may be
+/// left out; all others must be given.
+fn expand_uri_template(template: &str, params: &HashMap<String, Value>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            return Err(format!(
+                "unterminated expression in uri_template `{template}`"
+            ));
+        };
+        let expression = &rest[start + 1..start + len];
+        rest = &rest[start + len + 1..];
+
+        let (operator, names) = match expression.chars().next() {
+            Some(operator @ ('+' | '?' | '&')) => (Some(operator), &expression[1..]),
+            Some('#' | '.' | '/' | ';' | '=' | ',' | '!' | '@' | '|') => {
+                return Err(format!(
+                    "unsupported expression `{{{expression}}}` in uri_template `{template}`"
+                ));
+            }
+            _ => (None, expression),
+        };
+        let mut values = Vec::new();
+        for name in names.split(',') {
+            let value = match params.get(name) {
+                Some(Value::String(value)) => Some(value.clone()),
+                Some(value @ (Value::Number(_) | Value::Bool(_))) => Some(value.to_string()),
+                Some(_) => return Err(format!("param `{name}` must be a string or number")),
+                None => None,
+            };
+            match (operator, value) {
+                (Some('?' | '&'), Some(value)) => {
+                    values.push(format!("{name}={}", percent_encode(&value)));
+                }
+                (Some('?' | '&'), None) => {}
+                (Some('+'), Some(value)) => values.push(value),
+                (_, Some(value)) => values.push(percent_encode(&value)),
+                (_, None) => {
+                    return Err(format!(
+                        "missing param `{name}` for uri_template `{template}`"
+                    ));
+                }
+            }
+        }
+        match operator {
+            Some(operator @ ('?' | '&')) if !values.is_empty() => {
+                expanded.push(operator);
+                expanded.push_str(&values.join("&"));
+            }
+            Some('?' | '&') => {}
+            _ => expanded.push_str(&values.join(",")),
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
 fn parse_arguments(raw_args: &str) -> Result<Option<Value>, FunctionCallError> {
     if raw_args.trim().is_empty() {
         Ok(None)
@@ -800,4 +966,57 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn expand_uri_template_fills_params() {
+        let params = HashMap::from([
+            ("id".to_string(), json!("design doc")),
+            ("path".to_string(), json!("docs/schema.sql")),
+            ("page".to_string(), json!(2)),
+        ]);
+
+        assert_eq!(
+            expand_uri_template("memo://{id}", &params),
+            Ok("memo://design%20doc".to_string())
+        );
+        assert_eq!(
+            expand_uri_template("file:///{+path}{?page,limit}", &params),
+            Ok("file:///docs/schema.sql?page=2".to_string())
+        );
+        assert_eq!(
+            expand_uri_template("memo://{missing}", &params),
+            Err("missing param `missing` for uri_template `memo://{missing}`".to_string())
+        );
+        assert!(expand_uri_template("memo://{/id}", &params).is_err());
+    }
+
+    #[test]
+    fn oversized_output_stays_json_by_cutting_long_strings() {
+        let value = json!({
+            "server": "srv",
+            "contents": [{ "uri": "memo://id", "text": "x".repeat(10_000) }],
+        });
+
+        let fitted = fit_json(&value, 500).expect("strings can be cut to fit");
+        assert!(fitted.len() <= 500);
+        let parsed: Value = serde_json::from_str(&fitted).expect("still JSON");
+        assert_eq!(parsed["server"], json!("srv"));
+        assert_eq!(parsed["contents"][0]["uri"], json!("memo://id"));
+        let text = parsed["contents"][0]["text"].as_str().expect("text");
+        assert!(text.starts_with("xxx") && text.ends_with("bytes truncated]"));
+
+        let many = json!(vec![json!({ "uri": "memo://id" }); 100]);
+        assert_eq!(fit_json(&many, 100), Err(many.to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uri_path_only_accepts_file_uris() {
+        assert_eq!(
+            file_uri_path("file:///repo/docs/schema.sql"),
+            Some(PathBuf::from("/repo/docs/schema.sql"))
+        );
+        assert_eq!(file_uri_path("memo://design%20doc"), None);
+        assert_eq!(file_uri_path("not a uri"), None);
+    }
 }
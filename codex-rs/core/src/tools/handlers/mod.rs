@@ -1,37 +1,63 @@
 pub mod apply_patch;
+mod background_jobs;
 pub(crate) mod collab;
 mod dynamic;
+mod environment_capabilities;
+mod git;
 mod grep_files;
 mod list_dir;
 mod mcp;
 mod mcp_resource;
 mod plan;
+mod read_exec_output;
 mod read_file;
+mod report_failure;
+mod report_outcome;
 mod request_user_input;
+mod search_workspace;
 mod shell;
 mod test_sync;
 mod unified_exec;
 mod view_image;
+mod workdir;
+mod workspace;
 
 pub use plan::PLAN_TOOL;
 use serde::Deserialize;
 
+use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
+use crate::is_safe_command::is_known_safe_command;
+use crate::protocol::AskForApproval;
+use crate::protocol::SandboxPolicy;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::sandboxing::ExecApprovalRequirement;
 pub use apply_patch::ApplyPatchHandler;
+pub use background_jobs::BackgroundJobsHandler;
+pub use background_jobs::KillBackgroundJobHandler;
 pub use collab::CollabHandler;
 pub use dynamic::DynamicToolHandler;
+pub use environment_capabilities::EnvironmentCapabilitiesHandler;
+pub use git::GitHandler;
+pub(crate) use git::status_summary as git_status_summary;
 pub use grep_files::GrepFilesHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
 pub use mcp_resource::McpResourceHandler;
 pub use plan::PlanHandler;
+pub use read_exec_output::ReadExecOutputHandler;
 pub use read_file::ReadFileHandler;
+pub use report_failure::ReportFailureHandler;
+pub use report_outcome::ReportOutcomeHandler;
 pub use request_user_input::RequestUserInputHandler;
+pub use search_workspace::SearchWorkspaceHandler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
+pub(crate) use workspace::workspace_walker;
 
 fn parse_arguments<T>(arguments: &str) -> Result<T, FunctionCallError>
 where
@@ -41,3 +67,48 @@ where
         FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err}"))
     })
 }
+
+/// Whether `command` only reads and would run without any approval prompt,
+/// including the one offered when the sandbox denies a command. A command
+/// run with injected env is never known to only read.
+async fn runs_read_only_without_approval(
+    invocation: &ToolInvocation,
+    command: &[String],
+    sandbox_permissions: SandboxPermissions,
+    injects_env: bool,
+) -> bool {
+    let ToolInvocation { session, turn, .. } = invocation;
+    if injects_env
+        || !is_known_safe_command(command)
+        || sandbox_permissions.requires_escalated_permissions()
+    {
+        return false;
+    }
+    if sandbox_retry_may_prompt(turn) {
+        return false;
+    }
+    let requirement = session
+        .services
+        .exec_policy
+        .create_exec_approval_requirement_for_command(
+            &session.features(),
+            command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            sandbox_permissions,
+        )
+        .await;
+    matches!(requirement, ExecApprovalRequirement::Skip { .. })
+}
+
+/// Whether a sandbox denial during this turn would offer to rerun the
+/// command without the sandbox, which prompts the user.
+fn sandbox_retry_may_prompt(turn: &TurnContext) -> bool {
+    !matches!(
+        turn.approval_policy,
+        AskForApproval::Never | AskForApproval::OnRequest
+    ) && !matches!(
+        turn.sandbox_policy,
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. }
+    )
+}
@@ -10,6 +10,8 @@ use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::spec::JsonSchema;
 use async_trait::async_trait;
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::EventMsg;
 use std::collections::BTreeMap;
@@ -17,6 +19,10 @@ use std::sync::LazyLock;
 
 pub struct PlanHandler;
 
+/// Longer plans are rejected; past this they read as a task list rather than
+/// a plan a client can show as a checklist.
+const MAX_PLAN_STEPS: usize = 20;
+
 pub static PLAN_TOOL: LazyLock<ToolSpec> = LazyLock::new(|| {
     let mut plan_item_props = BTreeMap::new();
     plan_item_props.insert("step".to_string(), JsonSchema::String { description: None });
@@ -47,7 +53,7 @@ pub static PLAN_TOOL: LazyLock<ToolSpec> = LazyLock::new(|| {
         name: "update_plan".to_string(),
         description: r#"Updates the task plan.
 Provide an optional explanation and a list of plan items, each with a step and status.
-At most one step can be in_progress at a time.
+At most one step can be in_progress at a time, and a plan has at most 20 steps.
 "#
         .to_string(),
         strict: false,
@@ -65,6 +71,10 @@ impl ToolHandler for PlanHandler {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -94,9 +104,10 @@ impl ToolHandler for PlanHandler {
     }
 }
 
-/// This function doesn't do anything useful. However, it gives the model a structured way to record its plan that clients can read and render.
-/// So it's the _inputs_ to this function that are useful to clients, not the outputs and neither are actually useful for the model other
-/// than forcing it to come up and document a plan (TBD how that affects performance).
+/// Gives the model a structured way to record its plan that clients can read
+/// and render. The plan is kept on the session, so turn summaries report it and
+/// a resumed session picks it back up; the output only tells the model the
+/// update was accepted. Invalid plans are sent back with what to fix.
 pub(crate) async fn handle_update_plan(
     session: &Session,
     turn_context: &TurnContext,
@@ -104,6 +115,8 @@ pub(crate) async fn handle_update_plan(
     _call_id: String,
 ) -> Result<String, FunctionCallError> {
     let args = parse_update_plan_arguments(&arguments)?;
+    validate_plan(&args.plan).map_err(FunctionCallError::RespondToModel)?;
+    session.set_plan(args.clone()).await;
     session
         .send_event(turn_context, EventMsg::PlanUpdate(args))
         .await;
@@ -115,3 +128,100 @@ fn parse_update_plan_arguments(arguments: &str) -> Result<UpdatePlanArgs, Functi
         FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
     })
 }
+
+/// Why `plan` cannot be accepted, phrased so the model can fix it and call
+/// the tool again.
+fn validate_plan(plan: &[PlanItemArg]) -> Result<(), String> {
+    if plan.len() > MAX_PLAN_STEPS {
+        return Err(format!(
+            "the plan has {} steps but at most {MAX_PLAN_STEPS} are allowed; merge smaller steps and call update_plan again",
+            plan.len()
+        ));
+    }
+    if let Some(index) = plan.iter().position(|item| item.step.trim().is_empty()) {
+        return Err(format!(
+            "step {} has no description; describe every step and call update_plan again",
+            index + 1
+        ));
+    }
+    let in_progress: Vec<&str> = plan
+        .iter()
+        .filter(|item| item.status == StepStatus::InProgress)
+        .map(|item| item.step.as_str())
+        .collect();
+    if in_progress.len() > 1 {
+        return Err(format!(
+            "{} steps are in_progress ({}); mark only the step you are working on as in_progress and call update_plan again",
+            in_progress.len(),
+            in_progress.join("; ")
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn step(step: &str, status: StepStatus) -> PlanItemArg {
+        PlanItemArg {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn accepts_a_plan_with_one_step_in_progress() {
+        let plan = vec![
+            step("read the code", StepStatus::Completed),
+            step("fix the bug", StepStatus::InProgress),
+            step("add a test", StepStatus::Pending),
+        ];
+
+        assert_eq!(validate_plan(&plan), Ok(()));
+    }
+
+    #[test]
+    fn rejects_several_steps_in_progress_with_what_to_fix() {
+        let plan = vec![
+            step("fix the bug", StepStatus::InProgress),
+            step("add a test", StepStatus::InProgress),
+        ];
+
+        assert_eq!(
+            validate_plan(&plan),
+            Err("2 steps are in_progress (fix the bug; add a test); mark only the step you are working on as in_progress and call update_plan again".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_blank_steps_and_overlong_plans() {
+        let blank = vec![
+            step("fix the bug", StepStatus::Pending),
+            step("  ", StepStatus::Pending),
+        ];
+        assert_eq!(
+            validate_plan(&blank),
+            Err(
+                "step 2 has no description; describe every step and call update_plan again"
+                    .to_string()
+            )
+        );
+
+        let long: Vec<_> = (0..=MAX_PLAN_STEPS)
+            .map(|index| step(&format!("step {index}"), StepStatus::Pending))
+            .collect();
+        assert!(validate_plan(&long).is_err());
+    }
+
+    #[test]
+    fn steps_may_be_given_as_titles() {
+        let args = parse_update_plan_arguments(
+            r#"{"plan":[{"title":"fix the bug","status":"in_progress"}]}"#,
+        )
+        .expect("title is accepted for step");
+
+        assert_eq!(args.plan, vec![step("fix the bug", StepStatus::InProgress)]);
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ExecOutputReadSummary;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::exec_outputs::read_page;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct ReadExecOutputHandler;
+
+#[derive(Deserialize)]
+struct ReadExecOutputArgs {
+    /// The earlier shell or exec call whose output is read.
+    call_id: String,
+    /// Lines, or matching lines with `grep`, to skip.
+    #[serde(default)]
+    offset_lines: usize,
+    /// Most lines to return; the byte budget still applies.
+    #[serde(default)]
+    limit_lines: Option<usize>,
+    /// Only return lines matching this regex.
+    #[serde(default)]
+    grep: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for ReadExecOutputHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "read_exec_output handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let ReadExecOutputArgs {
+            call_id: source_call_id,
+            offset_lines,
+            limit_lines,
+            grep,
+        } = parse_arguments(&arguments)?;
+
+        if limit_lines == Some(0) {
+            return Err(FunctionCallError::RespondToModel(
+                "limit_lines must be greater than zero".to_string(),
+            ));
+        }
+
+        let regex =
+            grep.as_deref().map(Regex::new).transpose().map_err(|err| {
+                FunctionCallError::RespondToModel(format!("invalid regex: {err}"))
+            })?;
+
+        let emitter =
+            ToolEmitter::read_exec_output(source_call_id.clone(), offset_lines, limit_lines, grep);
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        emitter.begin(event_ctx).await;
+
+        let max_bytes = turn.exec_output_truncation_policy().byte_budget();
+        let source = session
+            .services
+            .exec_outputs
+            .lock()
+            .await
+            .source(&source_call_id);
+        let result = match source {
+            Ok(source) => tokio::task::spawn_blocking(move || {
+                read_page(
+                    &source,
+                    regex.as_ref(),
+                    offset_lines,
+                    limit_lines,
+                    max_bytes,
+                )
+            })
+            .await
+            .unwrap_or_else(|err| {
+                Err(FunctionCallError::RespondToModel(format!(
+                    "failed to read saved output: {err}"
+                )))
+            }),
+            Err(err) => Err(err),
+        };
+        let summary = result.as_ref().map(|page| ExecOutputReadSummary {
+            lines: page.lines,
+            bytes: page.content.len(),
+            total_lines: page.total_lines,
+            truncated: page.truncated,
+        });
+        emitter.finish_read_exec_output(event_ctx, summary).await;
+        let page = result?;
+
+        Ok(ToolOutput::Function {
+            content: page.content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -1,15 +1,23 @@
 use std::collections::VecDeque;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use codex_utils_string::take_bytes_at_char_boundary;
 use serde::Deserialize;
+use tokio::io::AsyncReadExt;
 
+use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::events::FileReadSummary;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::workspace::workspace_path;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
@@ -18,20 +26,33 @@ pub struct ReadFileHandler;
 const MAX_LINE_LENGTH: usize = 500;
 const TAB_WIDTH: usize = 4;
 
+/// How much of the start of a file is checked for NUL bytes to tell binary
+/// files apart, as git does.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
 // TODO(jif) add support for block comments
 const COMMENT_PREFIXES: &[&str] = &["#", "//", "--"];
 
 /// JSON arguments accepted by the `read_file` tool handler.
 #[derive(Deserialize)]
 struct ReadFileArgs {
-    /// Absolute path to the file that will be read.
-    file_path: String,
+    /// Path to the file that will be read, absolute or relative to the turn's
+    /// working directory.
+    #[serde(alias = "file_path")]
+    path: String,
     /// 1-indexed line number to start reading from; defaults to 1.
-    #[serde(default = "defaults::offset")]
-    offset: usize,
+    #[serde(alias = "offset", default = "defaults::offset")]
+    start_line: usize,
+    /// Last line to return, inclusive; takes the place of `limit` when set.
+    #[serde(default)]
+    end_line: Option<usize>,
     /// Maximum number of lines to return; defaults to 2000.
     #[serde(default = "defaults::limit")]
     limit: usize,
+    /// Largest output to return; defaults to the turn's budget for command
+    /// output.
+    #[serde(default)]
+    max_bytes: Option<usize>,
     /// Determines whether the handler reads a simple slice or indentation-aware block.
     #[serde(default)]
     mode: ReadMode,
@@ -50,7 +71,7 @@ enum ReadMode {
 /// Additional configuration for indentation-aware reads.
 #[derive(Deserialize, Clone)]
 struct IndentationArgs {
-    /// Optional explicit anchor line; defaults to `offset` when omitted.
+    /// Optional explicit anchor line; defaults to `start_line` when omitted.
     #[serde(default)]
     anchor_line: Option<usize>,
     /// Maximum indentation depth to collect; `0` means unlimited.
@@ -91,14 +112,33 @@ impl LineRecord {
     }
 }
 
+/// A finished read: what goes back to the model, and the canonical path and
+/// modification time recorded in the turn's files read.
+struct FileRead {
+    content: String,
+    summary: FileReadSummary,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
 #[async_trait]
 impl ToolHandler for ReadFileHandler {
     fn kind(&self) -> ToolKind {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -112,16 +152,18 @@ impl ToolHandler for ReadFileHandler {
         let args: ReadFileArgs = parse_arguments(&arguments)?;
 
         let ReadFileArgs {
-            file_path,
-            offset,
+            path,
+            start_line,
+            end_line,
             limit,
+            max_bytes,
             mode,
             indentation,
         } = args;
 
-        if offset == 0 {
+        if start_line == 0 {
             return Err(FunctionCallError::RespondToModel(
-                "offset must be a 1-indexed line number".to_string(),
+                "start_line must be a 1-indexed line number".to_string(),
             ));
         }
 
@@ -131,28 +173,165 @@ impl ToolHandler for ReadFileHandler {
             ));
         }
 
-        let path = PathBuf::from(&file_path);
-        if !path.is_absolute() {
+        let limit = match end_line {
+            Some(end_line) if end_line < start_line => {
+                return Err(FunctionCallError::RespondToModel(
+                    "end_line must not be before start_line".to_string(),
+                ));
+            }
+            Some(end_line) => end_line - start_line + 1,
+            None => limit,
+        };
+
+        let max_bytes =
+            max_bytes.unwrap_or_else(|| turn.exec_output_truncation_policy().byte_budget());
+        if max_bytes == 0 {
             return Err(FunctionCallError::RespondToModel(
-                "file_path must be an absolute path".to_string(),
+                "max_bytes must be greater than zero".to_string(),
             ));
         }
 
-        let collected = match mode {
-            ReadMode::Slice => slice::read(&path, offset, limit).await?,
-            ReadMode::Indentation => {
-                let indentation = indentation.unwrap_or_default();
-                indentation::read_block(&path, offset, limit, indentation).await?
-            }
-        };
+        let path = turn.resolve_path(Some(path));
+        let emitter = ToolEmitter::read_file(path.clone(), start_line, end_line);
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        emitter.begin(event_ctx).await;
+
+        let result = read(
+            &turn,
+            &path,
+            start_line,
+            limit,
+            max_bytes,
+            mode,
+            indentation,
+        )
+        .await;
+        emitter
+            .finish_read_file(event_ctx, result.as_ref().map(|read| &read.summary))
+            .await;
+        let read = result?;
+        session.record_file_read(read.path, read.modified).await;
+
         Ok(ToolOutput::Function {
-            content: collected.join("\n"),
+            content: read.content,
             content_items: None,
             success: Some(true),
         })
     }
 }
 
+async fn read(
+    turn: &TurnContext,
+    path: &Path,
+    start_line: usize,
+    limit: usize,
+    max_bytes: usize,
+    mode: ReadMode,
+    indentation: Option<IndentationArgs>,
+) -> Result<FileRead, FunctionCallError> {
+    let path = workspace_path(turn, path)?;
+    let metadata = tokio::fs::metadata(&path).await.map_err(read_error)?;
+    let modified = metadata.modified().ok();
+
+    if let Some(kind) = sniff_binary(&path).await? {
+        let content = format!(
+            "binary file ({kind}, {} bytes); contents not shown",
+            metadata.len()
+        );
+        return Ok(FileRead {
+            summary: FileReadSummary {
+                lines: 0,
+                bytes: content.len(),
+                truncated: false,
+                binary: true,
+            },
+            content,
+            path,
+            modified,
+        });
+    }
+
+    let collected = match mode {
+        ReadMode::Slice => slice::read(&path, start_line, limit).await?,
+        ReadMode::Indentation => {
+            let indentation = indentation.unwrap_or_default();
+            indentation::read_block(&path, start_line, limit, indentation).await?
+        }
+    };
+    let (content, lines, truncated) = join_within_budget(&collected, max_bytes);
+    Ok(FileRead {
+        summary: FileReadSummary {
+            lines,
+            bytes: content.len(),
+            truncated,
+            binary: false,
+        },
+        content,
+        path,
+        modified,
+    })
+}
+
+fn read_error(err: std::io::Error) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
+}
+
+/// Returns the kind of file at `path` when its start contains a NUL byte.
+async fn sniff_binary(path: &Path) -> Result<Option<&'static str>, FunctionCallError> {
+    let file = tokio::fs::File::open(path).await.map_err(read_error)?;
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    file.take(BINARY_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await
+        .map_err(read_error)?;
+    Ok(head.contains(&0).then(|| binary_kind(&head)))
+}
+
+fn binary_kind(head: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "PNG image"),
+        (b"\xff\xd8\xff", "JPEG image"),
+        (b"GIF8", "GIF image"),
+        (b"%PDF", "PDF document"),
+        (b"PK\x03\x04", "zip archive"),
+        (b"\x1f\x8b", "gzip archive"),
+        (b"\x7fELF", "ELF executable"),
+        (b"MZ", "Windows executable"),
+        (b"\0asm", "WebAssembly module"),
+        (b"SQLite format 3\0", "SQLite database"),
+    ];
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map_or("binary data", |(_, kind)| kind)
+}
+
+/// Joins `lines` until the next one would go past `max_bytes`, then notes how
+/// many were left out. The first line is always kept. Also returns the number
+/// of lines kept and whether any were left out.
+fn join_within_budget(lines: &[String], max_bytes: usize) -> (String, usize, bool) {
+    let mut content = String::new();
+    let mut kept = 0;
+    for line in lines {
+        if kept > 0 {
+            if content.len() + 1 + line.len() > max_bytes {
+                break;
+            }
+            content.push('\n');
+        }
+        content.push_str(line);
+        kept += 1;
+    }
+    let omitted = lines.len() - kept;
+    if omitted == 0 {
+        return (content, kept, false);
+    }
+    content.push_str(&format!(
+        "\n[truncated: {omitted} more lines past the {max_bytes}-byte limit]"
+    ));
+    (content, kept, true)
+}
+
 mod slice {
     use crate::function_tool::FunctionCallError;
     use crate::tools::handlers::read_file::format_line;
@@ -988,4 +1167,45 @@ private:
         );
         Ok(())
     }
+
+    #[test]
+    fn byte_budget_drops_whole_lines_and_says_how_many() {
+        let lines = vec![
+            "L1: alpha".to_string(),
+            "L2: beta".to_string(),
+            "L3: gamma".to_string(),
+        ];
+
+        assert_eq!(
+            join_within_budget(&lines, 1024),
+            ("L1: alpha\nL2: beta\nL3: gamma".to_string(), 3, false)
+        );
+        assert_eq!(
+            join_within_budget(&lines, 18),
+            (
+                "L1: alpha\nL2: beta\n[truncated: 1 more lines past the 18-byte limit]".to_string(),
+                2,
+                true
+            )
+        );
+        assert_eq!(
+            join_within_budget(&lines, 1).1,
+            1,
+            "the first line is always kept"
+        );
+    }
+
+    #[tokio::test]
+    async fn binary_files_are_detected_by_nul_bytes() -> anyhow::Result<()> {
+        let mut png = NamedTempFile::new()?;
+        use std::io::Write as _;
+        png.write_all(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        let mut text = NamedTempFile::new()?;
+        text.write_all(b"\xff\xfe\nplain\n")?;
+
+        assert_eq!(sniff_binary(png.path()).await?, Some("PNG image"));
+        assert_eq!(sniff_binary(text.path()).await?, None);
+        assert_eq!(binary_kind(b"\x01\0\x02"), "binary data");
+        Ok(())
+    }
 }
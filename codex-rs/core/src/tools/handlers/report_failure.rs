@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::FailureReportedEvent;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct ReportFailureHandler;
+
+#[derive(Deserialize)]
+struct ReportFailureArgs {
+    reason: String,
+}
+
+#[async_trait]
+impl ToolHandler for ReportFailureHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "report_failure handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let ReportFailureArgs { reason } = parse_arguments(&arguments)?;
+        session
+            .send_event(
+                turn.as_ref(),
+                EventMsg::FailureReported(FailureReportedEvent {
+                    call_id,
+                    turn_id: turn.sub_id.clone(),
+                    reason,
+                }),
+            )
+            .await;
+
+        Ok(ToolOutput::Function {
+            content: "Failure recorded. End your turn with a short summary.".to_string(),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::OutcomeReportedEvent;
+use codex_protocol::protocol::OutcomeStatus;
+use codex_protocol::protocol::ReportedOutcome;
+use codex_protocol::protocol::WarningEvent;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct ReportOutcomeHandler;
+
+#[derive(Deserialize)]
+struct ReportOutcomeArgs {
+    status: OutcomeStatus,
+    summary: String,
+    #[serde(default)]
+    details: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for ReportOutcomeHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "report_outcome handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let ReportOutcomeArgs {
+            status,
+            summary,
+            details,
+        } = parse_arguments(&arguments)?;
+        let outcome = ReportedOutcome {
+            status,
+            summary,
+            details: details.filter(|details| !details.trim().is_empty()),
+        };
+        let replaced_previous = session.record_outcome(outcome.clone()).await;
+        if replaced_previous {
+            session
+                .send_event(
+                    turn.as_ref(),
+                    EventMsg::Warning(WarningEvent {
+                        message:
+                            "report_outcome was called more than once; keeping the latest outcome"
+                                .to_string(),
+                    }),
+                )
+                .await;
+        }
+        session
+            .send_event(
+                turn.as_ref(),
+                EventMsg::OutcomeReported(OutcomeReportedEvent {
+                    call_id,
+                    turn_id: turn.sub_id.clone(),
+                    outcome,
+                    replaced_previous,
+                }),
+            )
+            .await;
+
+        Ok(ToolOutput::Function {
+            content: "Outcome recorded. End your turn with a short summary.".to_string(),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
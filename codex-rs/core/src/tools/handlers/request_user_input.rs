@@ -18,6 +18,10 @@ impl ToolHandler for RequestUserInputHandler {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -0,0 +1,341 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use codex_utils_string::take_bytes_at_char_boundary;
+use ignore::overrides::OverrideBuilder;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::events::WorkspaceSearchSummary;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::workspace::workspace_walker;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct SearchWorkspaceHandler;
+
+const DEFAULT_MAX_RESULTS: usize = 50;
+const MAX_RESULTS: usize = 500;
+/// Lines shown before and after each match.
+const CONTEXT_LINES: usize = 2;
+/// Matched and context lines are cut to this many bytes.
+const MAX_LINE_BYTES: usize = 300;
+/// Larger files are skipped.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Files with a NUL byte this close to their start are skipped as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+fn default_max_results() -> usize {
+    DEFAULT_MAX_RESULTS
+}
+
+#[derive(Deserialize)]
+struct SearchWorkspaceArgs {
+    query: String,
+    #[serde(default)]
+    glob: Option<String>,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default = "default_max_results")]
+    max_results: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct SearchMatch {
+    /// Relative to the working directory.
+    path: String,
+    line_number: usize,
+    line: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context_before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context_after: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SearchResults {
+    matches: Vec<SearchMatch>,
+    /// More matches were found than are listed.
+    truncated: bool,
+}
+
+#[async_trait]
+impl ToolHandler for SearchWorkspaceHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "search_workspace handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: SearchWorkspaceArgs = parse_arguments(&arguments)?;
+
+        if args.query.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "query must not be empty".to_string(),
+            ));
+        }
+
+        if args.max_results == 0 {
+            return Err(FunctionCallError::RespondToModel(
+                "max_results must be greater than zero".to_string(),
+            ));
+        }
+
+        let pattern = if args.regex {
+            args.query.clone()
+        } else {
+            regex::escape(&args.query)
+        };
+        let matcher = Regex::new(&pattern)
+            .map_err(|err| FunctionCallError::RespondToModel(format!("invalid regex: {err}")))?;
+        let glob = args
+            .glob
+            .map(|glob| glob.trim().to_string())
+            .filter(|glob| !glob.is_empty());
+        let max_results = args.max_results.min(MAX_RESULTS);
+
+        let emitter = ToolEmitter::workspace_search(args.query, glob.clone(), args.regex);
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        emitter.begin(event_ctx).await;
+
+        let root = turn.cwd.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            search(&root, &matcher, glob.as_deref(), max_results)
+        })
+        .await
+        .unwrap_or_else(|err| {
+            Err(FunctionCallError::RespondToModel(format!(
+                "search failed: {err}"
+            )))
+        })
+        .map(|(matches, truncated)| {
+            let budget = turn.exec_output_truncation_policy().byte_budget();
+            let (matches, cut) = within_budget(matches, budget);
+            SearchResults {
+                matches,
+                truncated: truncated || cut,
+            }
+        });
+        let summary = result.as_ref().map(|results| WorkspaceSearchSummary {
+            match_count: results.matches.len(),
+            truncated: results.truncated,
+        });
+        emitter.finish_workspace_search(event_ctx, summary).await;
+        let results = result?;
+
+        let content = serde_json::to_string(&results).map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to serialize matches: {err}"))
+        })?;
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// Searches the files [`workspace_walker`] finds under `root`, hidden ones
+/// included. Also returns whether there were more than `max_results` matches.
+fn search(
+    root: &Path,
+    matcher: &Regex,
+    glob: Option<&str>,
+    max_results: usize,
+) -> Result<(Vec<SearchMatch>, bool), FunctionCallError> {
+    let mut builder = workspace_walker(root, true);
+    if let Some(glob) = glob {
+        let overrides = OverrideBuilder::new(root)
+            .add(glob)
+            .and_then(|overrides| overrides.build())
+            .map_err(|err| FunctionCallError::RespondToModel(format!("invalid glob: {err}")))?;
+        builder.overrides(overrides);
+    }
+
+    let mut matches = Vec::new();
+    for entry in builder.build().flatten() {
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        if !entry
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() <= MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            continue;
+        };
+        if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.lines().collect();
+        let path = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .into_owned();
+        for (index, line) in lines.iter().enumerate() {
+            if !matcher.is_match(line) {
+                continue;
+            }
+            if matches.len() == max_results {
+                return Ok((matches, true));
+            }
+            let after_end = (index + 1 + CONTEXT_LINES).min(lines.len());
+            matches.push(SearchMatch {
+                path: path.clone(),
+                line_number: index + 1,
+                line: clip(line),
+                context_before: lines[index.saturating_sub(CONTEXT_LINES)..index]
+                    .iter()
+                    .map(|line| clip(line))
+                    .collect(),
+                context_after: lines[index + 1..after_end]
+                    .iter()
+                    .map(|line| clip(line))
+                    .collect(),
+            });
+        }
+    }
+    Ok((matches, false))
+}
+
+fn clip(line: &str) -> String {
+    take_bytes_at_char_boundary(line, MAX_LINE_BYTES).to_string()
+}
+
+/// Keeps matches while their JSON fits in `budget` bytes. The first match is
+/// always kept. Also returns whether any were dropped.
+fn within_budget(matches: Vec<SearchMatch>, budget: usize) -> (Vec<SearchMatch>, bool) {
+    let mut kept = Vec::new();
+    let mut used = 0;
+    for search_match in matches {
+        let size = serde_json::to_string(&search_match).map_or(0, |json| json.len()) + 1;
+        if !kept.is_empty() && used + size > budget {
+            return (kept, true);
+        }
+        used += size;
+        kept.push(search_match);
+    }
+    (kept, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn literal(query: &str) -> Regex {
+        Regex::new(&regex::escape(query)).expect("valid pattern")
+    }
+
+    #[test]
+    fn finds_matches_in_file_name_order_skipping_ignored_and_binary_files() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        std::fs::create_dir(root.join("src"))?;
+        std::fs::write(root.join(".gitignore"), "target/\n")?;
+        std::fs::create_dir(root.join("target"))?;
+        std::fs::write(root.join("target/out.txt"), "needle\n")?;
+        std::fs::write(root.join("b.txt"), "one\nneedle here\nthree\n")?;
+        std::fs::write(root.join("src/a.rs"), "fn needle() {}\n")?;
+        std::fs::write(root.join("a.bin"), b"needle\0")?;
+
+        let (matches, truncated) = search(root, &literal("needle"), None, 10)?;
+
+        assert_eq!(
+            matches,
+            vec![
+                SearchMatch {
+                    path: "b.txt".to_string(),
+                    line_number: 2,
+                    line: "needle here".to_string(),
+                    context_before: vec!["one".to_string()],
+                    context_after: vec!["three".to_string()],
+                },
+                SearchMatch {
+                    path: Path::new("src").join("a.rs").to_string_lossy().into_owned(),
+                    line_number: 1,
+                    line: "fn needle() {}".to_string(),
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                },
+            ]
+        );
+        assert!(!truncated);
+        Ok(())
+    }
+
+    #[test]
+    fn glob_and_max_results_narrow_the_search() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        std::fs::write(root.join("a.rs"), "x\nx\nx\n")?;
+        std::fs::write(root.join("b.txt"), "x\n")?;
+
+        let (matches, truncated) = search(root, &literal("x"), Some("*.txt"), 10)?;
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.path.as_str(), m.line_number))
+                .collect::<Vec<_>>(),
+            vec![("b.txt", 1)]
+        );
+        assert!(!truncated);
+
+        let (matches, truncated) = search(root, &literal("x"), None, 2)?;
+        assert_eq!(matches.len(), 2);
+        assert!(truncated);
+        Ok(())
+    }
+
+    #[test]
+    fn budget_keeps_whole_matches() {
+        let search_match = |line_number| SearchMatch {
+            path: "a.rs".to_string(),
+            line_number,
+            line: "x".to_string(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        };
+        let matches = vec![search_match(1), search_match(2), search_match(3)];
+
+        let (kept, cut) = within_budget(matches, 1);
+
+        assert_eq!(kept, vec![search_match(1)]);
+        assert!(cut);
+    }
+}
@@ -1,14 +1,27 @@
 use async_trait::async_trait;
 use codex_protocol::models::ShellCommandToolCallParams;
 use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::EventMsg;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::background_jobs;
+use crate::background_jobs::NewJob;
 use crate::codex::TurnContext;
+use crate::config::types::ToolTimeouts;
+use crate::exec::ExecExpiration;
 use crate::exec::ExecParams;
-use crate::exec_env::create_env;
+use crate::exec_env::check_env_overrides;
+use crate::exec_env::injected_env;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
+use crate::loop_detection::exit_code_of;
+use crate::loop_detection::failure_loop_advice;
+use crate::loop_detection::with_advice;
 use crate::protocol::ExecCommandSource;
+use crate::sandboxing::ExecEnv;
 use crate::shell::Shell;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -17,24 +30,68 @@ use crate::tools::events::ToolEmitter;
 use crate::tools::events::ToolEventCtx;
 use crate::tools::handlers::apply_patch::intercept_apply_patch;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::runs_read_only_without_approval;
+use crate::tools::handlers::workdir::reject_workdir;
+use crate::tools::handlers::workdir::resolve_workdir;
 use crate::tools::orchestrator::ToolOrchestrator;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::runtimes::shell::ShellRequest;
 use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::Approvable;
 use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+use crate::tools::sandboxing::approval_signature;
+use crate::workspace_snapshots;
 
 pub struct ShellHandler;
 
 pub struct ShellCommandHandler;
 
+/// Background jobs get a longer default than calls the turn waits for.
+fn default_timeout(tool_timeouts: &ToolTimeouts, background: Option<bool>) -> Duration {
+    if background.unwrap_or(false) {
+        background_jobs::DEFAULT_TIMEOUT
+    } else {
+        tool_timeouts.shell
+    }
+}
+
+/// Merges the `env` of a call into `exec_params` and returns the injected
+/// variables.
+fn inject_env(
+    exec_params: &mut ExecParams,
+    env: Option<HashMap<String, String>>,
+    turn_context: &TurnContext,
+) -> Result<HashMap<String, String>, FunctionCallError> {
+    let env = env.unwrap_or_default();
+    check_env_overrides(&env, &turn_context.shell_environment_policy)
+        .map_err(FunctionCallError::RespondToModel)?;
+    exec_params.env.extend(env.clone());
+    Ok(env)
+}
+
+/// Whether a call sets any variables. Injected env can make a known-safe
+/// command run arbitrary code (`LD_PRELOAD`, `GIT_SSH_COMMAND`, ...), so
+/// such calls are treated as mutating and need the usual approval.
+fn injects_env(env: Option<&HashMap<String, String>>) -> bool {
+    env.is_some_and(|env| !env.is_empty())
+}
+
 impl ShellHandler {
-    fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> ExecParams {
+    fn to_exec_params(
+        params: ShellToolCallParams,
+        turn_context: &TurnContext,
+        tool_timeouts: &ToolTimeouts,
+    ) -> ExecParams {
         ExecParams {
             command: params.command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
-            expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            expiration: ExecExpiration::Timeout(tool_timeouts.resolve(
+                params.timeout_ms,
+                default_timeout(tool_timeouts, params.background),
+            )),
+            env: turn_context.exec_env(),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -52,6 +109,7 @@ impl ShellCommandHandler {
         params: ShellCommandToolCallParams,
         session: &crate::codex::Session,
         turn_context: &TurnContext,
+        tool_timeouts: &ToolTimeouts,
     ) -> ExecParams {
         let shell = session.user_shell();
         let command = Self::base_command(shell.as_ref(), &params.command, params.login);
@@ -59,8 +117,11 @@ impl ShellCommandHandler {
         ExecParams {
             command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
-            expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            expiration: ExecExpiration::Timeout(tool_timeouts.resolve(
+                params.timeout_ms,
+                default_timeout(tool_timeouts, params.background),
+            )),
+            env: turn_context.exec_env(),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -85,14 +146,42 @@ impl ToolHandler for ShellHandler {
         match &invocation.payload {
             ToolPayload::Function { arguments } => {
                 serde_json::from_str::<ShellToolCallParams>(arguments)
-                    .map(|params| !is_known_safe_command(&params.command))
+                    .map(|params| {
+                        injects_env(params.env.as_ref()) || !is_known_safe_command(&params.command)
+                    })
                     .unwrap_or(true)
             }
-            ToolPayload::LocalShell { params } => !is_known_safe_command(&params.command),
+            ToolPayload::LocalShell { params } => {
+                injects_env(params.env.as_ref()) || !is_known_safe_command(&params.command)
+            }
             _ => true, // unknown payloads => assume mutating
         }
     }
 
+    async fn is_parallel_safe(&self, invocation: &ToolInvocation) -> bool {
+        let params = match &invocation.payload {
+            ToolPayload::Function { arguments } => {
+                match serde_json::from_str::<ShellToolCallParams>(arguments) {
+                    Ok(params) => params,
+                    Err(_) => return false,
+                }
+            }
+            ToolPayload::LocalShell { params } => params.clone(),
+            _ => return false,
+        };
+        runs_read_only_without_approval(
+            invocation,
+            &params.command,
+            params.sandbox_permissions.unwrap_or_default(),
+            injects_env(params.env.as_ref()),
+        )
+        .await
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -103,31 +192,41 @@ impl ToolHandler for ShellHandler {
             payload,
         } = invocation;
 
+        let tool_timeouts = session.tool_timeouts().await;
         match payload {
             ToolPayload::Function { arguments } => {
-                let params: ShellToolCallParams = parse_arguments(&arguments)?;
-                let exec_params = Self::to_exec_params(params, turn.as_ref());
+                let mut params: ShellToolCallParams = parse_arguments(&arguments)?;
+                let background = params.background.unwrap_or(false);
+                let env = params.env.take();
+                let mut exec_params = Self::to_exec_params(params, turn.as_ref(), &tool_timeouts);
+                let env_overrides = inject_env(&mut exec_params, env, turn.as_ref())?;
                 Self::run_exec_like(
                     tool_name.as_str(),
                     exec_params,
+                    env_overrides,
                     session,
                     turn,
                     tracker,
                     call_id,
                     false,
+                    background,
                 )
                 .await
             }
-            ToolPayload::LocalShell { params } => {
-                let exec_params = Self::to_exec_params(params, turn.as_ref());
+            ToolPayload::LocalShell { mut params } => {
+                let env = params.env.take();
+                let mut exec_params = Self::to_exec_params(params, turn.as_ref(), &tool_timeouts);
+                let env_overrides = inject_env(&mut exec_params, env, turn.as_ref())?;
                 Self::run_exec_like(
                     tool_name.as_str(),
                     exec_params,
+                    env_overrides,
                     session,
                     turn,
                     tracker,
                     call_id,
                     false,
+                    false,
                 )
                 .await
             }
@@ -157,11 +256,34 @@ impl ToolHandler for ShellCommandHandler {
             .map(|params| {
                 let shell = invocation.session.user_shell();
                 let command = Self::base_command(shell.as_ref(), &params.command, params.login);
-                !is_known_safe_command(&command)
+                injects_env(params.env.as_ref())
+                    || !is_known_safe_command(&shell.canonical_exec_args(&command))
             })
             .unwrap_or(true)
     }
 
+    async fn is_parallel_safe(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return false;
+        };
+        let Ok(params) = serde_json::from_str::<ShellCommandToolCallParams>(arguments) else {
+            return false;
+        };
+        let shell = invocation.session.user_shell();
+        let command = Self::base_command(shell.as_ref(), &params.command, params.login);
+        runs_read_only_without_approval(
+            invocation,
+            &shell.canonical_exec_args(&command),
+            params.sandbox_permissions.unwrap_or_default(),
+            injects_env(params.env.as_ref()),
+        )
+        .await
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -178,31 +300,47 @@ impl ToolHandler for ShellCommandHandler {
             )));
         };
 
-        let params: ShellCommandToolCallParams = parse_arguments(&arguments)?;
-        let exec_params = Self::to_exec_params(params, session.as_ref(), turn.as_ref());
+        let mut params: ShellCommandToolCallParams = parse_arguments(&arguments)?;
+        let background = params.background.unwrap_or(false);
+        let env = params.env.take();
+        let tool_timeouts = session.tool_timeouts().await;
+        let mut exec_params =
+            Self::to_exec_params(params, session.as_ref(), turn.as_ref(), &tool_timeouts);
+        let env_overrides = inject_env(&mut exec_params, env, turn.as_ref())?;
         ShellHandler::run_exec_like(
             tool_name.as_str(),
             exec_params,
+            env_overrides,
             session,
             turn,
             tracker,
             call_id,
             true,
+            background,
         )
         .await
     }
 }
 
 impl ShellHandler {
+    #[allow(clippy::too_many_arguments)]
     async fn run_exec_like(
         tool_name: &str,
         exec_params: ExecParams,
+        env_overrides: HashMap<String, String>,
         session: Arc<crate::codex::Session>,
         turn: Arc<TurnContext>,
         tracker: crate::tools::context::SharedTurnDiffTracker,
         call_id: String,
         freeform: bool,
+        background: bool,
     ) -> Result<ToolOutput, FunctionCallError> {
+        if background && !session.features().enabled(Feature::BackgroundJobs) {
+            return Err(FunctionCallError::RespondToModel(
+                "background jobs are not enabled; run the command without `background`".to_string(),
+            ));
+        }
+
         // Approval policy guard for explicit escalation in non-OnRequest modes.
         if exec_params
             .sandbox_permissions
@@ -218,6 +356,22 @@ impl ShellHandler {
             )));
         }
 
+        let exec_params = match resolve_workdir(&turn.cwd, &exec_params.cwd) {
+            Ok(cwd) => ExecParams { cwd, ..exec_params },
+            Err(message) => {
+                let emitter = ToolEmitter::shell(
+                    exec_params.command,
+                    exec_params.cwd,
+                    ExecCommandSource::ModelShell,
+                    freeform,
+                    exec_params.expiration.timeout_ms(),
+                    session.as_ref(),
+                );
+                let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+                return Err(reject_workdir(emitter, event_ctx, message).await);
+            }
+        };
+
         // Intercept apply_patch if present.
         if let Some(output) = intercept_apply_patch(
             &exec_params.command,
@@ -234,26 +388,45 @@ impl ShellHandler {
             return Ok(output);
         }
 
-        let source = ExecCommandSource::Agent;
+        let injects_env = !env_overrides.is_empty();
+        let source = ExecCommandSource::ModelShell;
         let emitter = ToolEmitter::shell(
             exec_params.command.clone(),
             exec_params.cwd.clone(),
             source,
             freeform,
-        );
+            exec_params.expiration.timeout_ms(),
+            session.as_ref(),
+        )
+        .with_injected_env(injected_env(&env_overrides));
         let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
-        emitter.begin(event_ctx).await;
+        if turn.plan_only {
+            return Ok(emitter.propose(event_ctx).await);
+        }
+        workspace_snapshots::before_command(
+            session.as_ref(),
+            turn.as_ref(),
+            &exec_params.command,
+            &exec_params.cwd,
+        )
+        .await;
+        if !background {
+            emitter.begin(event_ctx).await;
+        }
 
         let features = session.features();
         let exec_approval_requirement = session
             .services
             .exec_policy
-            .create_exec_approval_requirement_for_command(
+            .create_exec_approval_requirement_for_command_with_env(
                 &features,
-                &exec_params.command,
+                &session
+                    .user_shell()
+                    .canonical_exec_args(&exec_params.command),
                 turn.approval_policy,
                 &turn.sandbox_policy,
                 exec_params.sandbox_permissions,
+                injects_env,
             )
             .await;
 
@@ -262,6 +435,7 @@ impl ShellHandler {
             cwd: exec_params.cwd.clone(),
             timeout_ms: exec_params.expiration.timeout_ms(),
             env: exec_params.env.clone(),
+            env_overrides,
             sandbox_permissions: exec_params.sandbox_permissions,
             justification: exec_params.justification.clone(),
             exec_approval_requirement,
@@ -274,17 +448,87 @@ impl ShellHandler {
             call_id: call_id.clone(),
             tool_name: tool_name.to_string(),
         };
+        if background {
+            let env: Result<ExecEnv, ToolError> = orchestrator
+                .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
+                .await;
+            return Self::start_background_job(&session, &turn, call_id, &req, env).await;
+        }
         let out = orchestrator
             .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
             .await;
+        let exit_code = exit_code_of(&out);
         let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
-        let content = emitter.finish(event_ctx, out).await?;
+        let result = emitter.finish(event_ctx, out).await;
+        let advice = match exit_code {
+            Some(exit_code) => {
+                let signature = runtime
+                    .approval_keys(&req)
+                    .first()
+                    .and_then(approval_signature);
+                failure_loop_advice(
+                    session.as_ref(),
+                    turn.as_ref(),
+                    &call_id,
+                    &req.command,
+                    signature,
+                    exit_code,
+                )
+                .await
+            }
+            None => None,
+        };
+        let content = with_advice(result, advice)?;
         Ok(ToolOutput::Function {
             content,
             content_items: None,
             success: Some(true),
         })
     }
+
+    /// Spawns the command the orchestrator prepared and answers with the id
+    /// of the new job.
+    async fn start_background_job(
+        session: &Arc<crate::codex::Session>,
+        turn: &TurnContext,
+        call_id: String,
+        req: &ShellRequest,
+        env: Result<ExecEnv, ToolError>,
+    ) -> Result<ToolOutput, FunctionCallError> {
+        let env = env.map_err(|err| {
+            FunctionCallError::RespondToModel(match err {
+                ToolError::Rejected(reason) => reason.message_for(Some("exec command")),
+                ToolError::Codex(err) => format!("failed to start background job: {err}"),
+            })
+        })?;
+        let job = NewJob {
+            call_id,
+            command: req.command.clone(),
+            env,
+            sandbox_policy: turn.sandbox_policy.clone(),
+            timeout: req
+                .timeout_ms
+                .map_or(background_jobs::DEFAULT_TIMEOUT, Duration::from_millis),
+            truncation_policy: turn.exec_output_truncation_policy(),
+        };
+        let started = session
+            .services
+            .background_jobs
+            .spawn(Arc::clone(session), turn.sub_id.clone(), job)
+            .await
+            .map_err(FunctionCallError::RespondToModel)?;
+        let job_id = started.job_id.clone();
+        session
+            .send_event(turn, EventMsg::BackgroundJobStatus(started))
+            .await;
+        Ok(ToolOutput::Function {
+            content: format!(
+                "Started background job {job_id}. Call get_background_job with this id to see its status and, once it has ended, its output."
+            ),
+            content_items: None,
+            success: Some(true),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -296,7 +540,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use crate::codex::make_session_and_context;
-    use crate::exec_env::create_env;
+    use crate::config::types::ToolTimeouts;
     use crate::is_safe_command::is_known_safe_command;
     use crate::powershell::try_find_powershell_executable_blocking;
     use crate::powershell::try_find_pwsh_executable_blocking;
@@ -316,6 +560,7 @@ mod tests {
             shell_type: ShellType::Bash,
             shell_path: PathBuf::from("/bin/bash"),
             shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
         assert_safe(&bash_shell, "ls -la");
 
@@ -323,6 +568,7 @@ mod tests {
             shell_type: ShellType::Zsh,
             shell_path: PathBuf::from("/bin/zsh"),
             shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+            argv_template: None,
         };
         assert_safe(&zsh_shell, "ls -la");
 
@@ -331,6 +577,7 @@ mod tests {
                 shell_type: ShellType::PowerShell,
                 shell_path: path.to_path_buf(),
                 shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+                argv_template: None,
             };
             assert_safe(&powershell, "ls -Name");
         }
@@ -340,6 +587,7 @@ mod tests {
                 shell_type: ShellType::PowerShell,
                 shell_path: path.to_path_buf(),
                 shell_snapshot: crate::shell::empty_shell_snapshot_receiver(),
+                argv_template: None,
             };
             assert_safe(&pwsh, "ls -Name");
         }
@@ -367,7 +615,7 @@ mod tests {
 
         let expected_command = session.user_shell().derive_exec_args(&command, true);
         let expected_cwd = turn_context.resolve_path(workdir.clone());
-        let expected_env = create_env(&turn_context.shell_environment_policy);
+        let expected_env = turn_context.exec_env();
 
         let params = ShellCommandToolCallParams {
             command,
@@ -376,9 +624,16 @@ mod tests {
             timeout_ms,
             sandbox_permissions: Some(sandbox_permissions),
             justification: justification.clone(),
+            background: None,
+            env: None,
         };
 
-        let exec_params = ShellCommandHandler::to_exec_params(params, &session, &turn_context);
+        let exec_params = ShellCommandHandler::to_exec_params(
+            params,
+            &session,
+            &turn_context,
+            &ToolTimeouts::default(),
+        );
 
         // ExecParams cannot derive Eq due to the CancellationToken field, so we manually compare the fields.
         assert_eq!(exec_params.command, expected_command);
@@ -399,6 +654,7 @@ mod tests {
             shell_type: ShellType::Bash,
             shell_path: PathBuf::from("/bin/bash"),
             shell_snapshot,
+            argv_template: None,
         };
 
         let login_command =
@@ -1,24 +1,40 @@
+use crate::exec_env::check_env_overrides;
+use crate::exec_env::injected_env;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
+use crate::loop_detection::failure_loop_advice;
 use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandSource;
 use crate::protocol::TerminalInteractionEvent;
+use crate::protocol::TerminalInteractionSource;
 use crate::sandboxing::SandboxPermissions;
 use crate::shell::Shell;
 use crate::shell::get_shell_by_model_provided_path;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::events::limit_interaction_input;
 use crate::tools::handlers::apply_patch::intercept_apply_patch;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::runs_read_only_without_approval;
+use crate::tools::handlers::workdir::reject_workdir;
+use crate::tools::handlers::workdir::resolve_workdir;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
+use crate::tools::registry::plan_only_output;
+use crate::tools::runtimes::unified_exec::UnifiedExecApprovalKey;
+use crate::tools::sandboxing::approval_signature;
 use crate::unified_exec::ExecCommandRequest;
 use crate::unified_exec::UnifiedExecContext;
 use crate::unified_exec::UnifiedExecProcessManager;
 use crate::unified_exec::UnifiedExecResponse;
 use crate::unified_exec::WriteStdinRequest;
+use crate::workspace_snapshots;
 use async_trait::async_trait;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -43,6 +59,8 @@ struct ExecCommandArgs {
     sandbox_permissions: SandboxPermissions,
     #[serde(default)]
     justification: Option<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,18 +69,28 @@ struct WriteStdinArgs {
     session_id: i32,
     #[serde(default)]
     chars: String,
-    #[serde(default = "default_write_stdin_yield_time_ms")]
-    yield_time_ms: u64,
+    /// Falls back to `tool_timeouts.unified_exec_write_wait_sec`.
+    #[serde(default)]
+    yield_time_ms: Option<u64>,
     #[serde(default)]
     max_output_tokens: Option<usize>,
 }
 
-fn default_exec_yield_time_ms() -> u64 {
-    10000
+/// Largest `write_stdin` input accepted. Bigger text belongs in a file.
+const MAX_WRITE_STDIN_BYTES: usize = 256 * 1024;
+
+fn check_write_stdin_size(chars: &str) -> Result<(), FunctionCallError> {
+    if chars.len() <= MAX_WRITE_STDIN_BYTES {
+        return Ok(());
+    }
+    Err(FunctionCallError::RespondToModel(format!(
+        "write_stdin input is {} bytes, over the {MAX_WRITE_STDIN_BYTES} byte limit; write the text to a file and `cat` it into the session instead",
+        chars.len()
+    )))
 }
 
-fn default_write_stdin_yield_time_ms() -> u64 {
-    250
+fn default_exec_yield_time_ms() -> u64 {
+    10000
 }
 
 fn default_login() -> bool {
@@ -95,8 +123,37 @@ impl ToolHandler for UnifiedExecHandler {
         let Ok(params) = serde_json::from_str::<ExecCommandArgs>(arguments) else {
             return true;
         };
-        let command = get_command(&params, invocation.session.user_shell());
-        !is_known_safe_command(&command)
+        let shell = invocation.session.user_shell();
+        let command = get_command(&params, Arc::clone(&shell));
+        // Injected env can make a known-safe command run arbitrary code.
+        !params.env.is_empty() || !is_known_safe_command(&shell.canonical_exec_args(&command))
+    }
+
+    async fn is_parallel_safe(&self, invocation: &ToolInvocation) -> bool {
+        // `write_stdin` talks to a session another call may be using, so only
+        // `exec_command`, which always starts its own, can run in parallel.
+        if invocation.tool_name != "exec_command" {
+            return false;
+        }
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return false;
+        };
+        let Ok(params) = serde_json::from_str::<ExecCommandArgs>(arguments) else {
+            return false;
+        };
+        let shell = invocation.session.user_shell();
+        let command = get_command(&params, Arc::clone(&shell));
+        runs_read_only_without_approval(
+            invocation,
+            &shell.canonical_exec_args(&command),
+            params.sandbox_permissions,
+            !params.env.is_empty(),
+        )
+        .await
+    }
+
+    fn runs_in_plan_only(&self) -> bool {
+        true
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
@@ -122,6 +179,9 @@ impl ToolHandler for UnifiedExecHandler {
         let manager: &UnifiedExecProcessManager = &session.services.unified_exec_manager;
         let context = UnifiedExecContext::new(session.clone(), turn.clone(), call_id.clone());
 
+        // The command of an `exec_command` call, and its approval signature,
+        // for noticing repeated failures if it exits within the call.
+        let mut started_command = None;
         let response = match tool_name.as_str() {
             "exec_command" => {
                 let args: ExecCommandArgs = parse_arguments(&arguments)?;
@@ -135,9 +195,17 @@ impl ToolHandler for UnifiedExecHandler {
                     max_output_tokens,
                     sandbox_permissions,
                     justification,
+                    env,
                     ..
                 } = args;
 
+                if let Err(message) =
+                    check_env_overrides(&env, &context.turn.shell_environment_policy)
+                {
+                    manager.release_process_id(&process_id).await;
+                    return Err(FunctionCallError::RespondToModel(message));
+                }
+
                 if sandbox_permissions.requires_escalated_permissions()
                     && !matches!(
                         context.turn.approval_policy,
@@ -153,13 +221,34 @@ impl ToolHandler for UnifiedExecHandler {
 
                 let workdir = workdir.filter(|value| !value.is_empty());
 
-                let workdir = workdir.map(|dir| context.turn.resolve_path(Some(dir)));
-                let cwd = workdir.clone().unwrap_or_else(|| context.turn.cwd.clone());
+                let cwd = context.turn.resolve_path(workdir);
+                let cwd = match resolve_workdir(&context.turn.cwd, &cwd) {
+                    Ok(cwd) => cwd,
+                    Err(message) => {
+                        manager.release_process_id(&process_id).await;
+                        let emitter = ToolEmitter::unified_exec(
+                            command,
+                            cwd,
+                            ExecCommandSource::ModelUnifiedExec,
+                            Some(process_id),
+                            context.session.as_ref(),
+                        );
+                        let event_ctx = ToolEventCtx::new(
+                            context.session.as_ref(),
+                            context.turn.as_ref(),
+                            &context.call_id,
+                            None,
+                        );
+                        return Err(reject_workdir(emitter, event_ctx, message).await);
+                    }
+                };
 
+                // `yield_time_ms` only bounds how long to wait for output, so
+                // the patch gets the same timeout as the apply_patch tool.
                 if let Some(output) = intercept_apply_patch(
                     &command,
                     &cwd,
-                    Some(yield_time_ms),
+                    None,
                     context.session.as_ref(),
                     context.turn.as_ref(),
                     Some(&tracker),
@@ -172,6 +261,36 @@ impl ToolHandler for UnifiedExecHandler {
                     return Ok(output);
                 }
 
+                if turn.plan_only {
+                    manager.release_process_id(&process_id).await;
+                    let emitter = ToolEmitter::unified_exec(
+                        command,
+                        cwd,
+                        ExecCommandSource::ModelUnifiedExec,
+                        None,
+                        session.as_ref(),
+                    )
+                    .with_injected_env(injected_env(&env));
+                    let event_ctx =
+                        ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+                    return Ok(emitter.propose(event_ctx).await);
+                }
+                workspace_snapshots::before_command(
+                    session.as_ref(),
+                    turn.as_ref(),
+                    &command,
+                    &cwd,
+                )
+                .await;
+
+                let signature = approval_signature(&UnifiedExecApprovalKey {
+                    command: command.clone(),
+                    cwd: cwd.clone(),
+                    tty,
+                    sandbox_permissions,
+                });
+                started_command = Some((command.clone(), signature));
+
                 manager
                     .exec_command(
                         ExecCommandRequest {
@@ -179,10 +298,11 @@ impl ToolHandler for UnifiedExecHandler {
                             process_id,
                             yield_time_ms,
                             max_output_tokens,
-                            workdir,
+                            workdir: Some(cwd),
                             tty,
                             sandbox_permissions,
                             justification,
+                            env,
                         },
                         &context,
                     )
@@ -193,11 +313,20 @@ impl ToolHandler for UnifiedExecHandler {
             }
             "write_stdin" => {
                 let args: WriteStdinArgs = parse_arguments(&arguments)?;
+                check_write_stdin_size(&args.chars)?;
+                // Input would run in a process started before plan-only mode.
+                if turn.plan_only {
+                    return Ok(plan_only_output());
+                }
+                let tool_timeouts = session.tool_timeouts().await;
+                let yield_time_ms = tool_timeouts
+                    .resolve(args.yield_time_ms, tool_timeouts.unified_exec_write_wait)
+                    .as_millis() as u64;
                 let response = manager
                     .write_stdin(WriteStdinRequest {
                         process_id: &args.session_id.to_string(),
                         input: &args.chars,
-                        yield_time_ms: args.yield_time_ms,
+                        yield_time_ms,
                         max_output_tokens: args.max_output_tokens,
                     })
                     .await
@@ -208,7 +337,11 @@ impl ToolHandler for UnifiedExecHandler {
                 let interaction = TerminalInteractionEvent {
                     call_id: response.event_call_id.clone(),
                     process_id: args.session_id.to_string(),
-                    stdin: args.chars.clone(),
+                    stdin: limit_interaction_input(
+                        &args.chars,
+                        turn.output_limits.interaction_input_bytes,
+                    ),
+                    source: TerminalInteractionSource::Agent,
                 };
                 session
                     .send_event(turn.as_ref(), EventMsg::TerminalInteraction(interaction))
@@ -223,7 +356,21 @@ impl ToolHandler for UnifiedExecHandler {
             }
         };
 
-        let content = format_response(&response);
+        let mut content = format_response(&response);
+        if let (Some((command, signature)), Some(exit_code)) = (started_command, response.exit_code)
+            && let Some(advice) = failure_loop_advice(
+                session.as_ref(),
+                turn.as_ref(),
+                &call_id,
+                &command,
+                signature,
+                exit_code,
+            )
+            .await
+        {
+            content.push_str("\n\n");
+            content.push_str(&advice);
+        }
 
         Ok(ToolOutput::Function {
             content,
@@ -342,4 +489,24 @@ mod tests {
         assert_eq!(command[2], "echo hello");
         Ok(())
     }
+
+    #[test]
+    fn write_stdin_rejects_input_over_the_byte_limit() {
+        assert_eq!(
+            check_write_stdin_size(&"a".repeat(MAX_WRITE_STDIN_BYTES)),
+            Ok(())
+        );
+
+        // Counted in bytes: this is one character short of the limit, but
+        // the last one is two bytes wide.
+        let input = format!("{}é", "a".repeat(MAX_WRITE_STDIN_BYTES - 1));
+        let Err(FunctionCallError::RespondToModel(message)) = check_write_stdin_size(&input) else {
+            panic!("expected oversized input to be rejected");
+        };
+        assert!(message.starts_with(&format!(
+            "write_stdin input is {} bytes, over the {MAX_WRITE_STDIN_BYTES} byte limit",
+            MAX_WRITE_STDIN_BYTES + 1
+        )));
+        assert!(message.contains("write the text to a file"));
+    }
 }
@@ -1,4 +1,8 @@
+use std::path::Path;
+
 use async_trait::async_trait;
+use codex_utils_image::error::ImageProcessingError;
+use codex_utils_image::load_and_resize_to_fit;
 use serde::Deserialize;
 use tokio::fs;
 
@@ -9,14 +13,19 @@ use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
 use crate::tools::handlers::parse_arguments;
+use crate::tools::handlers::workspace::workspace_path;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseInputItem;
-use codex_protocol::models::local_image_content_items_with_label_number;
 
 pub struct ViewImageHandler;
 
+/// Larger files are refused before they are decoded.
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// What the decoder reads; listed when a file is in some other format.
+const SUPPORTED_FORMATS: &str = "PNG, JPEG, GIF, WebP and BMP";
+
 #[derive(Deserialize)]
 struct ViewImageArgs {
     path: String,
@@ -28,6 +37,10 @@ impl ToolHandler for ViewImageHandler {
         ToolKind::Function
     }
 
+    fn runs_in_plan_only(&self) -> bool {
+        true
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -63,13 +76,23 @@ impl ToolHandler for ViewImageHandler {
                 abs_path.display()
             )));
         }
-        let event_path = abs_path.clone();
+        workspace_path(&turn, &abs_path)?;
+        if metadata.len() > MAX_IMAGE_BYTES {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "image at `{}` is {} bytes; view_image accepts images up to {MAX_IMAGE_BYTES} bytes",
+                abs_path.display(),
+                metadata.len()
+            )));
+        }
 
-        let content: Vec<ContentItem> =
-            local_image_content_items_with_label_number(&abs_path, None);
+        // Downscales images larger than the model accepts.
+        let image = load_and_resize_to_fit(&abs_path).map_err(|err| image_error(&abs_path, err))?;
+        let (width, height) = (image.width, image.height);
         let input = ResponseInputItem::Message {
             role: "user".to_string(),
-            content,
+            content: vec![ContentItem::InputImage {
+                image_url: image.into_data_url(),
+            }],
         };
 
         session
@@ -86,7 +109,9 @@ impl ToolHandler for ViewImageHandler {
                 turn.as_ref(),
                 EventMsg::ViewImageToolCall(ViewImageToolCallEvent {
                     call_id,
-                    path: event_path,
+                    path: abs_path,
+                    width,
+                    height,
                 }),
             )
             .await;
@@ -98,3 +123,39 @@ impl ToolHandler for ViewImageHandler {
         })
     }
 }
+
+fn image_error(path: &Path, err: ImageProcessingError) -> FunctionCallError {
+    let message = match &err {
+        ImageProcessingError::Decode { .. } if !err.is_invalid_image() => format!(
+            "`{}` is not in a supported image format; view_image accepts {SUPPORTED_FORMATS}",
+            path.display()
+        ),
+        _ => format!("unable to load image at `{}`: {err}", path.display()),
+    };
+    FunctionCallError::RespondToModel(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn unsupported_formats_list_the_supported_ones() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("data.json");
+        std::fs::write(&path, br#"{ "message": "hello" }"#)?;
+
+        let err = load_and_resize_to_fit(&path).expect_err("json is not an image");
+
+        assert_eq!(
+            image_error(&path, err),
+            FunctionCallError::RespondToModel(format!(
+                "`{}` is not in a supported image format; view_image accepts PNG, JPEG, GIF, WebP and BMP",
+                path.display()
+            ))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Checking the working directory an exec call asks for before anything is
+//! spawned, so a bad `workdir` reads as such rather than as a broken command.
+
+use std::path::Component;
+use std::path::MAIN_SEPARATOR_STR;
+use std::path::Path;
+use std::path::PathBuf;
+use std::path::Prefix;
+use std::path::PrefixComponent;
+
+use crate::function_tool::FunctionCallError;
+use crate::protocol::ExecErrorKind;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::events::ToolEventFailure;
+use crate::tools::events::ToolEventStage;
+
+/// Resolves `requested` against the turn cwd `base` and checks that it is an
+/// existing directory. Returns the canonical path, or the message for the
+/// model when the directory cannot be used.
+pub(crate) fn resolve_workdir(base: &Path, requested: &Path) -> Result<PathBuf, String> {
+    let path = join_workdir(base, requested);
+    let problem = match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.is_dir() => {
+            return Ok(dunce::canonicalize(&path).unwrap_or(path));
+        }
+        Ok(_) => "is not a directory",
+        Err(_) => "does not exist",
+    };
+    let message = format!("working directory '{}' {problem}", path.display());
+    Err(
+        match path.ancestors().skip(1).find(|ancestor| ancestor.is_dir()) {
+            Some(ancestor) => format!("{message} (did you mean {}?)", ancestor.display()),
+            None => message,
+        },
+    )
+}
+
+/// Emits begin and end events for a call whose working directory was
+/// rejected by [`resolve_workdir`], and returns the error for the model.
+pub(crate) async fn reject_workdir(
+    emitter: ToolEmitter,
+    ctx: ToolEventCtx<'_>,
+    message: String,
+) -> FunctionCallError {
+    emitter.begin(ctx).await;
+    emitter
+        .end(
+            ctx,
+            ToolEventStage::Failure(ToolEventFailure::Message {
+                kind: ExecErrorKind::InvalidWorkdir,
+                message: message.clone(),
+            }),
+        )
+        .await;
+    FunctionCallError::RespondToModel(message)
+}
+
+/// Like [`Path::join`], except that a drive-relative Windows path such as
+/// `D:src` resolves against `base` when it is on the same drive and against
+/// the drive's root otherwise, rather than against the process cwd.
+fn join_workdir(base: &Path, requested: &Path) -> PathBuf {
+    if requested.is_absolute() {
+        return requested.to_path_buf();
+    }
+    let mut components = requested.components();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return base.join(requested);
+    };
+    let rest = components.as_path();
+    let same_drive = match base.components().next() {
+        Some(Component::Prefix(base_prefix)) => {
+            matches!(
+                (drive_letter(&base_prefix), drive_letter(&prefix)),
+                (Some(a), Some(b)) if a.eq_ignore_ascii_case(&b)
+            )
+        }
+        _ => false,
+    };
+    if same_drive {
+        base.join(rest)
+    } else {
+        PathBuf::from(prefix.as_os_str())
+            .join(MAIN_SEPARATOR_STR)
+            .join(rest)
+    }
+}
+
+fn drive_letter(prefix: &PrefixComponent<'_>) -> Option<u8> {
+    match prefix.kind() {
+        Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => Some(letter),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn relative_workdirs_resolve_against_the_turn_cwd() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("src")).expect("create src");
+
+        let resolved = resolve_workdir(dir.path(), Path::new("src")).expect("valid workdir");
+        assert_eq!(
+            resolved,
+            dunce::canonicalize(dir.path().join("src")).expect("canonicalize")
+        );
+        assert_eq!(
+            resolve_workdir(dir.path(), &dir.path().join("src")),
+            Ok(resolved)
+        );
+    }
+
+    #[test]
+    fn missing_workdirs_suggest_the_closest_existing_ancestor() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("src")).expect("create src");
+
+        let missing = dir.path().join("src").join("nested").join("deeper");
+        assert_eq!(
+            resolve_workdir(dir.path(), Path::new("src/nested/deeper")),
+            Err(format!(
+                "working directory '{}' does not exist (did you mean {}?)",
+                missing.display(),
+                dir.path().join("src").display()
+            ))
+        );
+    }
+
+    #[test]
+    fn file_workdirs_are_rejected() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let file = dir.path().join("Cargo.toml");
+        std::fs::write(&file, "").expect("write file");
+
+        assert_eq!(
+            resolve_workdir(dir.path(), Path::new("Cargo.toml")),
+            Err(format!(
+                "working directory '{}' is not a directory (did you mean {}?)",
+                file.display(),
+                dir.path().display()
+            ))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drive_relative_workdirs_resolve_on_their_own_drive() {
+        let base = Path::new(r"C:\work");
+        assert_eq!(
+            join_workdir(base, Path::new("c:src")),
+            PathBuf::from(r"C:\work\src")
+        );
+        assert_eq!(
+            join_workdir(base, Path::new("D:src")),
+            PathBuf::from(r"D:\src")
+        );
+        assert_eq!(join_workdir(base, Path::new("D:")), PathBuf::from(r"D:\"));
+        assert_eq!(
+            join_workdir(base, Path::new(r"\tools")),
+            PathBuf::from(r"C:\tools")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unc_workdirs_are_kept_as_given() {
+        let base = Path::new(r"C:\work");
+        assert_eq!(
+            join_workdir(base, Path::new(r"\\server\share\project")),
+            PathBuf::from(r"\\server\share\project")
+        );
+        assert_eq!(
+            join_workdir(base, Path::new(r"\\?\UNC\server\share\project")),
+            PathBuf::from(r"\\?\UNC\server\share\project")
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Where the file tools (`read_file`, `list_dir`, `search_workspace`) may
+//! look, and how they walk the tree.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use ignore::WalkBuilder;
+
+use crate::codex::TurnContext;
+use crate::function_tool::FunctionCallError;
+
+/// Canonicalizes `path` and checks that it lies under the turn's working
+/// directory, one of its workspace roots, or one of the sandbox's writable
+/// roots. Sandboxes with full disk access allow any path.
+pub(crate) fn workspace_path(
+    turn: &TurnContext,
+    path: &Path,
+) -> Result<PathBuf, FunctionCallError> {
+    let canonical = dunce::canonicalize(path).map_err(|err| {
+        FunctionCallError::RespondToModel(format!("cannot access {}: {err}", path.display()))
+    })?;
+    if turn.sandbox_policy.has_full_disk_write_access() {
+        return Ok(canonical);
+    }
+    let roots: Vec<PathBuf> = std::iter::once(turn.cwd.clone())
+        .chain(
+            turn.workspace_roots
+                .iter()
+                .map(|root| root.path.to_path_buf()),
+        )
+        .chain(
+            turn.sandbox_policy
+                .get_writable_roots_with_cwd(&turn.cwd)
+                .into_iter()
+                .map(|root| root.root.to_path_buf()),
+        )
+        .filter_map(|root| dunce::canonicalize(root).ok())
+        .collect();
+    if within_roots(&canonical, &roots) {
+        Ok(canonical)
+    } else {
+        Err(FunctionCallError::RespondToModel(format!(
+            "{} is outside the workspace; only the working directory, workspace roots and writable roots can be read",
+            path.display()
+        )))
+    }
+}
+
+fn within_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// A walk of `root` that skips what `.gitignore` and `.ignore` files exclude,
+/// even outside a git repository, and never enters `.git`. Entries come in
+/// file name order within each directory, so the same tree always walks the
+/// same way.
+pub(crate) fn workspace_walker(root: &Path, include_hidden: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(!include_hidden)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b));
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_roots_match_whole_components() {
+        let roots = vec![PathBuf::from("/work/repo")];
+
+        assert!(within_roots(Path::new("/work/repo/src/lib.rs"), &roots));
+        assert!(!within_roots(Path::new("/work/repo-other/lib.rs"), &roots));
+        assert!(!within_roots(Path::new("/etc/passwd"), &roots));
+    }
+}
@@ -1,5 +1,7 @@
+pub(crate) mod availability;
 pub mod context;
 pub mod events;
+pub(crate) mod exec_outputs;
 pub(crate) mod handlers;
 pub mod orchestrator;
 pub mod parallel;
@@ -8,6 +10,7 @@ pub mod router;
 pub mod runtimes;
 pub mod sandboxing;
 pub mod spec;
+pub(crate) mod spill;
 
 use crate::exec::ExecToolCallOutput;
 use crate::truncate::TruncationPolicy;
@@ -22,12 +25,54 @@ pub(crate) const TELEMETRY_PREVIEW_MAX_LINES: usize = 64; // lines
 pub(crate) const TELEMETRY_PREVIEW_TRUNCATION_NOTICE: &str =
     "[... telemetry preview truncated ...]";
 
+/// The model- and UI-facing renderings of one exec output.
+///
+/// Both are derived from a single pass over the aggregated output so they
+/// always describe the same snapshot and large outputs are only copied and
+/// truncated once.
+pub(crate) struct FormattedExecOutput {
+    /// Returned to the model as the tool call result.
+    pub model: String,
+    /// Reported to clients as the end event's `formatted_output`.
+    pub ui: String,
+}
+
+impl FormattedExecOutput {
+    /// Formats `exec_output` for both audiences. When the output was spilled
+    /// to disk, `spilled` is the preview the model sees instead of the full
+    /// output.
+    pub(crate) fn new(
+        exec_output: &ExecToolCallOutput,
+        spilled: Option<&ExecToolCallOutput>,
+        freeform: bool,
+        truncation_policy: TruncationPolicy,
+    ) -> Self {
+        let content = build_content_with_timeout(exec_output);
+        let ui = formatted_truncate_text(&content, truncation_policy);
+        let model = match spilled {
+            Some(spilled) if freeform => {
+                format_exec_output_for_model_freeform(spilled, truncation_policy)
+            }
+            Some(spilled) => format_exec_output_for_model_structured(spilled, truncation_policy),
+            None if freeform => freeform_from_content(exec_output, &content, truncation_policy),
+            None => structured_from_formatted(exec_output, &ui),
+        };
+        Self { model, ui }
+    }
+}
+
 /// Format the combined exec output for sending back to the model.
 /// Includes exit code and duration metadata; truncates large bodies safely.
 pub fn format_exec_output_for_model_structured(
     exec_output: &ExecToolCallOutput,
     truncation_policy: TruncationPolicy,
 ) -> String {
+    let formatted_output = format_exec_output_str(exec_output, truncation_policy);
+    structured_from_formatted(exec_output, &formatted_output)
+}
+
+/// Wraps already truncated output in the structured JSON payload.
+fn structured_from_formatted(exec_output: &ExecToolCallOutput, formatted_output: &str) -> String {
     let ExecToolCallOutput {
         exit_code,
         duration,
@@ -49,10 +94,8 @@ pub fn format_exec_output_for_model_structured(
     // round to 1 decimal place
     let duration_seconds = ((duration.as_secs_f32()) * 10.0).round() / 10.0;
 
-    let formatted_output = format_exec_output_str(exec_output, truncation_policy);
-
     let payload = ExecOutput {
-        output: &formatted_output,
+        output: formatted_output,
         metadata: ExecMetadata {
             exit_code: *exit_code,
             duration_seconds,
@@ -66,15 +109,23 @@ pub fn format_exec_output_for_model_structured(
 pub fn format_exec_output_for_model_freeform(
     exec_output: &ExecToolCallOutput,
     truncation_policy: TruncationPolicy,
+) -> String {
+    let content = build_content_with_timeout(exec_output);
+    freeform_from_content(exec_output, &content, truncation_policy)
+}
+
+/// Renders the freeform response from the untruncated `content`.
+fn freeform_from_content(
+    exec_output: &ExecToolCallOutput,
+    content: &str,
+    truncation_policy: TruncationPolicy,
 ) -> String {
     // round to 1 decimal place
     let duration_seconds = ((exec_output.duration.as_secs_f32()) * 10.0).round() / 10.0;
 
-    let content = build_content_with_timeout(exec_output);
-
     let total_lines = content.lines().count();
 
-    let formatted_output = truncate_text(&content, truncation_policy);
+    let formatted_output = truncate_text(content, truncation_policy);
 
     let mut sections = Vec::new();
 
@@ -112,3 +163,58 @@ fn build_content_with_timeout(exec_output: &ExecToolCallOutput) -> String {
         exec_output.aggregated_output.text.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::StreamOutput;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn exec_output(aggregated: String) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code: 1,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(aggregated),
+            duration: Duration::from_millis(1_250),
+            suspended: false,
+            timed_out: false,
+            deltas: Default::default(),
+        }
+    }
+
+    #[test]
+    fn single_pass_matches_separate_renderings() {
+        let output = exec_output("line\n".repeat(20_000));
+        let policy = TruncationPolicy::Bytes(4_096);
+
+        let structured = FormattedExecOutput::new(&output, None, false, policy);
+        assert_eq!(
+            structured.model,
+            format_exec_output_for_model_structured(&output, policy)
+        );
+        assert_eq!(structured.ui, format_exec_output_str(&output, policy));
+
+        let freeform = FormattedExecOutput::new(&output, None, true, policy);
+        assert_eq!(
+            freeform.model,
+            format_exec_output_for_model_freeform(&output, policy)
+        );
+        assert_eq!(freeform.ui, structured.ui);
+    }
+
+    #[test]
+    fn spilled_preview_only_changes_the_model_rendering() {
+        let output = exec_output("full output\n".repeat(1_000));
+        let spilled = exec_output("preview".to_string());
+        let policy = TruncationPolicy::Bytes(4_096);
+
+        let formatted = FormattedExecOutput::new(&output, Some(&spilled), false, policy);
+        assert_eq!(
+            formatted.model,
+            format_exec_output_for_model_structured(&spilled, policy)
+        );
+        assert_eq!(formatted.ui, format_exec_output_str(&output, policy));
+    }
+}
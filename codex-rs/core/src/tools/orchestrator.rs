@@ -5,12 +5,15 @@ Central place for approvals + sandbox selection + retry semantics. Drives a
 simple sequence for any ToolRuntime: approval → select sandbox → attempt →
 retry without sandbox on denial (no re‑approval thanks to caching).
 */
+use crate::codex::TurnContext;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
 use crate::sandboxing::SandboxManager;
+use crate::tools::sandboxing::Approvable;
 use crate::tools::sandboxing::ApprovalCtx;
 use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::RejectionReason;
 use crate::tools::sandboxing::SandboxAttempt;
 use crate::tools::sandboxing::SandboxOverride;
 use crate::tools::sandboxing::ToolCtx;
@@ -19,7 +22,11 @@ use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::default_exec_approval_requirement;
 use codex_otel::ToolDecisionSource;
 use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecApproval;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::ToolCallBlockKind;
+use codex_protocol::protocol::ToolCallBlockedEvent;
 
 pub(crate) struct ToolOrchestrator {
     sandbox: SandboxManager,
@@ -37,7 +44,7 @@ impl ToolOrchestrator {
         tool: &mut T,
         req: &Rq,
         tool_ctx: &ToolCtx<'_>,
-        turn_ctx: &crate::codex::TurnContext,
+        turn_ctx: &TurnContext,
         approval_policy: AskForApproval,
     ) -> Result<Out, ToolError>
     where
@@ -51,6 +58,7 @@ impl ToolOrchestrator {
 
         // 1) Approval
         let mut already_approved = false;
+        let mut edited_req = None;
 
         let requirement = tool.exec_approval_requirement(req).unwrap_or_else(|| {
             default_exec_approval_requirement(approval_policy, &turn_ctx.sandbox_policy)
@@ -58,8 +66,19 @@ impl ToolOrchestrator {
         match requirement {
             ExecApprovalRequirement::Skip { .. } => {
                 otel.tool_decision(otel_tn, otel_ci, &ReviewDecision::Approved, otel_cfg);
+                tool_ctx
+                    .session
+                    .record_exec_approval(&tool_ctx.call_id, ExecApproval::AutoApproved)
+                    .await;
             }
             ExecApprovalRequirement::Forbidden { reason } => {
+                report_blocked(
+                    tool_ctx,
+                    turn_ctx,
+                    ToolCallBlockKind::Approval,
+                    &reason.to_string(),
+                )
+                .await;
                 return Err(ToolError::Rejected(reason));
             }
             ExecApprovalRequirement::NeedsApproval { reason, .. } => {
@@ -68,22 +87,21 @@ impl ToolOrchestrator {
                     turn: turn_ctx,
                     call_id: &tool_ctx.call_id,
                     retry_reason: reason,
+                    sandbox_denial_output: None,
                 };
                 let decision = tool.start_approval_async(req, approval_ctx).await;
 
                 otel.tool_decision(otel_tn, otel_ci, &decision, otel_user.clone());
 
-                match decision {
-                    ReviewDecision::Denied | ReviewDecision::Abort => {
-                        return Err(ToolError::Rejected("rejected by user".to_string()));
-                    }
-                    ReviewDecision::Approved
-                    | ReviewDecision::ApprovedExecpolicyAmendment { .. }
-                    | ReviewDecision::ApprovedForSession => {}
-                }
+                edited_req = approved_request(tool, req, decision, tool_ctx, turn_ctx).await?;
                 already_approved = true;
+                tool_ctx
+                    .session
+                    .record_exec_approval(&tool_ctx.call_id, ExecApproval::UserApproved)
+                    .await;
             }
         }
+        let req = edited_req.as_ref().unwrap_or(req);
 
         // 2) First attempt under the selected sandbox.
         let initial_sandbox = match tool.sandbox_mode_for_first_attempt(req) {
@@ -110,6 +128,7 @@ impl ToolOrchestrator {
             }
             Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
                 if !tool.escalate_on_failure() {
+                    report_sandbox_denial(tool_ctx, turn_ctx, &output).await;
                     return Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied {
                         output,
                     })));
@@ -117,12 +136,14 @@ impl ToolOrchestrator {
                 // Under `Never` or `OnRequest`, do not retry without sandbox; surface a concise
                 // sandbox denial that preserves the original output.
                 if !tool.wants_no_sandbox_approval(approval_policy) {
+                    report_sandbox_denial(tool_ctx, turn_ctx, &output).await;
                     return Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied {
                         output,
                     })));
                 }
 
                 // Ask for approval before retrying without sandbox.
+                let mut edited_retry = None;
                 if !tool.should_bypass_approval(approval_policy, already_approved) {
                     let reason_msg = build_denial_reason_from_output(output.as_ref());
                     let approval_ctx = ApprovalCtx {
@@ -130,20 +151,20 @@ impl ToolOrchestrator {
                         turn: turn_ctx,
                         call_id: &tool_ctx.call_id,
                         retry_reason: Some(reason_msg),
+                        sandbox_denial_output: denial_output_tail(output.as_ref()),
                     };
 
                     let decision = tool.start_approval_async(req, approval_ctx).await;
                     otel.tool_decision(otel_tn, otel_ci, &decision, otel_user);
 
-                    match decision {
-                        ReviewDecision::Denied | ReviewDecision::Abort => {
-                            return Err(ToolError::Rejected("rejected by user".to_string()));
-                        }
-                        ReviewDecision::Approved
-                        | ReviewDecision::ApprovedExecpolicyAmendment { .. }
-                        | ReviewDecision::ApprovedForSession => {}
-                    }
+                    edited_retry =
+                        approved_request(tool, req, decision, tool_ctx, turn_ctx).await?;
                 }
+                let req = edited_retry.as_ref().unwrap_or(req);
+                tool_ctx
+                    .session
+                    .record_exec_approval(&tool_ctx.call_id, ExecApproval::Escalated)
+                    .await;
 
                 let escalated_attempt = SandboxAttempt {
                     sandbox: crate::exec::SandboxType::None,
@@ -166,3 +187,107 @@ fn build_denial_reason_from_output(_output: &ExecToolCallOutput) -> String {
     // output so we can evolve heuristics later without touching call sites.
     "command failed; retry without sandbox?".to_string()
 }
+
+/// The last lines of a denied attempt's output: enough to show what the
+/// sandbox blocked without sending a whole build log with the request.
+fn denial_output_tail(output: &ExecToolCallOutput) -> Option<String> {
+    const MAX_LINES: usize = 20;
+    let text = output.aggregated_output.text.trim_end();
+    if text.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    Some(lines[lines.len().saturating_sub(MAX_LINES)..].join("\n"))
+}
+
+/// Turns an approval decision into the request to run: `None` runs the one
+/// that was proposed, `Some` the version the user edited.
+async fn approved_request<Rq, T>(
+    tool: &T,
+    req: &Rq,
+    decision: ReviewDecision,
+    tool_ctx: &ToolCtx<'_>,
+    turn_ctx: &TurnContext,
+) -> Result<Option<Rq>, ToolError>
+where
+    T: Approvable<Rq>,
+{
+    match decision {
+        ReviewDecision::Denied | ReviewDecision::Abort => {
+            Err(rejected(tool_ctx, turn_ctx, None).await)
+        }
+        ReviewDecision::DeniedWithReason { reason } => {
+            Err(rejected(tool_ctx, turn_ctx, Some(reason)).await)
+        }
+        ReviewDecision::ApprovedWithEdit { command } => {
+            match tool.with_edited_command(req, command) {
+                Some(edited) => Ok(Some(edited)),
+                None => Err(rejected(
+                    tool_ctx,
+                    turn_ctx,
+                    Some("this tool cannot run an edited command".to_string()),
+                )
+                .await),
+            }
+        }
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedExecpolicyAmendment { .. }
+        | ReviewDecision::ApprovedForSession => Ok(None),
+    }
+}
+
+/// A denial by the headless approval policy explains itself to the model;
+/// anything else was declined by a person, who may have said why.
+async fn rejected(
+    tool_ctx: &ToolCtx<'_>,
+    turn_ctx: &TurnContext,
+    user_reason: Option<String>,
+) -> ToolError {
+    let headless_reason = tool_ctx
+        .session
+        .take_rejection_reason(&tool_ctx.call_id)
+        .await;
+    let reason = match headless_reason {
+        Some(rule) => RejectionReason::PolicyDenied { rule },
+        None => RejectionReason::UserDenied {
+            feedback: user_reason,
+        },
+    };
+    report_blocked(
+        tool_ctx,
+        turn_ctx,
+        ToolCallBlockKind::Approval,
+        &reason.to_string(),
+    )
+    .await;
+    ToolError::Rejected(reason)
+}
+
+async fn report_sandbox_denial(
+    tool_ctx: &ToolCtx<'_>,
+    turn_ctx: &TurnContext,
+    output: &ExecToolCallOutput,
+) {
+    let message = format!(
+        "the sandbox denied the command (exit code {})",
+        output.exit_code
+    );
+    report_blocked(tool_ctx, turn_ctx, ToolCallBlockKind::Sandbox, &message).await;
+}
+
+/// Lets clients tell a call that was stopped before it could do its work
+/// from one that ran and failed.
+async fn report_blocked(
+    tool_ctx: &ToolCtx<'_>,
+    turn_ctx: &TurnContext,
+    kind: ToolCallBlockKind,
+    message: &str,
+) {
+    let event = EventMsg::ToolCallBlocked(ToolCallBlockedEvent {
+        call_id: tool_ctx.call_id.clone(),
+        turn_id: turn_ctx.sub_id.clone(),
+        kind,
+        message: message.to_string(),
+    });
+    tool_ctx.session.send_event(turn_ctx, event).await;
+}
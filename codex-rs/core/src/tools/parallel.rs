@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
 use tokio_util::either::Either;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
@@ -12,13 +13,20 @@ use tracing::trace_span;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::error::CodexErr;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
+use crate::tool_tokens;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolPayload;
 use crate::tools::router::ToolCall;
 use crate::tools::router::ToolRouter;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ToolCallAbortedEvent;
+
+/// Most calls made parallel by `parallel_read_only_tools` that run at once.
+const MAX_PARALLEL_READ_ONLY_CALLS: usize = 8;
 
 #[derive(Clone)]
 pub(crate) struct ToolCallRuntime {
@@ -27,6 +35,7 @@ pub(crate) struct ToolCallRuntime {
     turn_context: Arc<TurnContext>,
     tracker: SharedTurnDiffTracker,
     parallel_execution: Arc<RwLock<()>>,
+    read_only_slots: Arc<Semaphore>,
 }
 
 impl ToolCallRuntime {
@@ -42,6 +51,7 @@ impl ToolCallRuntime {
             turn_context,
             tracker,
             parallel_execution: Arc::new(RwLock::new(())),
+            read_only_slots: Arc::new(Semaphore::new(MAX_PARALLEL_READ_ONLY_CALLS)),
         }
     }
 
@@ -58,6 +68,7 @@ impl ToolCallRuntime {
         let turn = Arc::clone(&self.turn_context);
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
+        let read_only_slots = Arc::clone(&self.read_only_slots);
         let started = Instant::now();
 
         let dispatch_span = trace_span!(
@@ -70,25 +81,84 @@ impl ToolCallRuntime {
 
         let handle: AbortOnDropHandle<Result<ResponseInputItem, FunctionCallError>> =
             AbortOnDropHandle::new(tokio::spawn(async move {
-                tokio::select! {
-                    _ = cancellation_token.cancelled() => {
-                        let secs = started.elapsed().as_secs_f32().max(0.1);
-                        dispatch_span.record("aborted", true);
-                        Ok(Self::aborted_response(&call, secs))
-                    },
-                    res = async {
-                        let _guard = if supports_parallel {
+                let call_id = call.call_id.clone();
+                session
+                    .register_running_tool_call(call_id.clone(), cancellation_token.clone())
+                    .await;
+                let result = async {
+                    let dispatch = async {
+                        // Read-only calls that would not ask for approval may
+                        // share the read lock, up to a limit; anything else
+                        // still runs alone.
+                        let parallel_read_only = !supports_parallel
+                            && session.features().enabled(Feature::ParallelReadOnlyTools)
+                            && router
+                                .is_parallel_safe_call(
+                                    Arc::clone(&session),
+                                    Arc::clone(&turn),
+                                    Arc::clone(&tracker),
+                                    &call,
+                                )
+                                .await;
+                        let _guard = if supports_parallel || parallel_read_only {
                             Either::Left(lock.read().await)
                         } else {
                             Either::Right(lock.write().await)
                         };
+                        let _slot = if parallel_read_only {
+                            read_only_slots.acquire_owned().await.ok()
+                        } else {
+                            None
+                        };
 
                         router
-                            .dispatch_tool_call(session, turn, tracker, call.clone())
+                            .dispatch_tool_call(
+                                Arc::clone(&session),
+                                Arc::clone(&turn),
+                                tracker,
+                                call.clone(),
+                            )
                             .instrument(dispatch_span.clone())
                             .await
-                    } => res,
+                    };
+                    tokio::pin!(dispatch);
+                    tokio::select! {
+                        _ = cancellation_token.cancelled() => {},
+                        res = &mut dispatch => return res,
+                    }
+
+                    let elapsed = started.elapsed();
+                    let secs = elapsed.as_secs_f32().max(0.1);
+                    dispatch_span.record("aborted", true);
+                    // An MCP server is told to stop the call, which then winds
+                    // down on its own and reports the abort in its end event.
+                    if let ToolPayload::Mcp { server, .. } = &call.payload
+                        && session.cancel_mcp_tool_call(server, &call.call_id).await
+                    {
+                        let _ = dispatch.await;
+                    } else {
+                        let event = EventMsg::ToolCallAborted(ToolCallAbortedEvent {
+                            call_id: call.call_id.clone(),
+                            turn_id: turn.sub_id.clone(),
+                            tool_name: call.tool_name.clone(),
+                            duration: elapsed,
+                        });
+                        session.send_event(&turn, event).await;
+                        session.close_exec_call(&call.call_id).await;
+                    }
+                    Ok(Self::aborted_response(&call, secs))
+                }
+                .await;
+                session.unregister_running_tool_call(&call_id).await;
+                session.record_tool_time(started.elapsed()).await;
+                if let Ok(output) = &result {
+                    let tokens =
+                        tool_tokens::estimate_output_tokens(output, turn.truncation_policy);
+                    session
+                        .record_tool_output_tokens(&call_id, &call.tool_name, tokens)
+                        .await;
                 }
+                result
             }));
 
         async move {
@@ -4,6 +4,9 @@ use std::time::Duration;
 
 use crate::client_common::tools::ToolSpec;
 use crate::function_tool::FunctionCallError;
+use crate::function_tool::ToolCallError;
+use crate::tools::availability::ToolAvailability;
+use crate::tools::availability::unavailable_tool_message;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -38,6 +41,23 @@ pub trait ToolHandler: Send + Sync {
         false
     }
 
+    /// Returns `true` if the [ToolInvocation] can run alongside other calls from
+    /// the same model response when `parallel_read_only_tools` is enabled. Only
+    /// calls that neither mutate the environment nor ask for approval qualify,
+    /// so this must also stay defensive.
+    async fn is_parallel_safe(&self, _invocation: &ToolInvocation) -> bool {
+        false
+    }
+
+    /// Returns `true` if the handler may be called in plan-only mode, because
+    /// it only reads the workspace or the session's own state, or because it
+    /// checks `TurnContext::plan_only` and reports a proposal through
+    /// `ToolEmitter::propose` itself. Calls to other handlers are answered
+    /// with [`plan_only_output`] without reaching them.
+    fn runs_in_plan_only(&self) -> bool {
+        false
+    }
+
     /// Perform the actual [ToolInvocation] and returns a [ToolOutput] containing
     /// the final output to return to the model.
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError>;
@@ -45,11 +65,18 @@ pub trait ToolHandler: Send + Sync {
 
 pub struct ToolRegistry {
     handlers: HashMap<String, Arc<dyn ToolHandler>>,
+    availability: ToolAvailability,
 }
 
 impl ToolRegistry {
-    pub fn new(handlers: HashMap<String, Arc<dyn ToolHandler>>) -> Self {
-        Self { handlers }
+    pub(crate) fn new(
+        handlers: HashMap<String, Arc<dyn ToolHandler>>,
+        availability: ToolAvailability,
+    ) -> Self {
+        Self {
+            handlers,
+            availability,
+        }
     }
 
     pub fn handler(&self, name: &str) -> Option<Arc<dyn ToolHandler>> {
@@ -67,7 +94,7 @@ impl ToolRegistry {
     pub async fn dispatch(
         &self,
         invocation: ToolInvocation,
-    ) -> Result<ResponseInputItem, FunctionCallError> {
+    ) -> Result<ResponseInputItem, ToolCallError> {
         let tool_name = invocation.tool_name.clone();
         let call_id_owned = invocation.call_id.clone();
         let otel = invocation.turn.client.get_otel_manager();
@@ -77,8 +104,14 @@ impl ToolRegistry {
         let handler = match self.handler(tool_name.as_ref()) {
             Some(handler) => handler,
             None => {
-                let message =
-                    unsupported_tool_call_message(&invocation.payload, tool_name.as_ref());
+                let message = if self
+                    .availability
+                    .blocks_call(tool_name.as_ref(), &invocation.payload)
+                {
+                    unavailable_tool_message(tool_name.as_ref())
+                } else {
+                    unsupported_tool_call_message(&invocation.payload, tool_name.as_ref())
+                };
                 otel.tool_result(
                     tool_name.as_ref(),
                     &call_id_owned,
@@ -87,7 +120,9 @@ impl ToolRegistry {
                     false,
                     &message,
                 );
-                return Err(FunctionCallError::RespondToModel(message));
+                return Err(
+                    FunctionCallError::RespondToModel(message).for_call(call_id_owned, tool_name)
+                );
             }
         };
 
@@ -101,7 +136,7 @@ impl ToolRegistry {
                 false,
                 &message,
             );
-            return Err(FunctionCallError::Fatal(message));
+            return Err(FunctionCallError::Fatal(message).for_call(call_id_owned, tool_name));
         }
 
         let output_cell = tokio::sync::Mutex::new(None);
@@ -116,6 +151,12 @@ impl ToolRegistry {
                     let output_cell = &output_cell;
                     let invocation = invocation;
                     async move {
+                        if invocation.turn.plan_only && !handler.runs_in_plan_only() {
+                            let output = plan_only_output();
+                            let preview = output.log_preview();
+                            *output_cell.lock().await = Some(output);
+                            return Ok((preview, false));
+                        }
                         if handler.is_mutating(&invocation).await {
                             tracing::trace!("waiting for tool gate");
                             invocation.turn.tool_call_gate.wait_ready().await;
@@ -139,16 +180,30 @@ impl ToolRegistry {
         match result {
             Ok(_) => {
                 let mut guard = output_cell.lock().await;
-                let output = guard.take().ok_or_else(|| {
-                    FunctionCallError::Fatal("tool produced no output".to_string())
-                })?;
+                let Some(output) = guard.take() else {
+                    return Err(
+                        FunctionCallError::Fatal("tool produced no output".to_string())
+                            .for_call(call_id_owned, tool_name),
+                    );
+                };
                 Ok(output.into_response(&call_id_owned, &payload_for_response))
             }
-            Err(err) => Err(err),
+            Err(err) => Err(err.for_call(call_id_owned, tool_name)),
         }
     }
 }
 
+/// What the model is told of a tool call that plan-only mode kept from running.
+const PLAN_ONLY_MESSAGE: &str = "not executed (plan-only mode)";
+
+pub(crate) fn plan_only_output() -> ToolOutput {
+    ToolOutput::Function {
+        content: PLAN_ONLY_MESSAGE.to_string(),
+        content_items: None,
+        success: Some(false),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfiguredToolSpec {
     pub spec: ToolSpec,
@@ -167,6 +222,7 @@ impl ConfiguredToolSpec {
 pub struct ToolRegistryBuilder {
     handlers: HashMap<String, Arc<dyn ToolHandler>>,
     specs: Vec<ConfiguredToolSpec>,
+    availability: ToolAvailability,
 }
 
 impl ToolRegistryBuilder {
@@ -174,6 +230,7 @@ impl ToolRegistryBuilder {
         Self {
             handlers: HashMap::new(),
             specs: Vec::new(),
+            availability: ToolAvailability::default(),
         }
     }
 
@@ -219,8 +276,18 @@ impl ToolRegistryBuilder {
     //     }
     // }
 
+    /// Drops the specs and handlers of tools whose name fails `keep`.
+    pub(crate) fn retain_tools(&mut self, keep: impl Fn(&str) -> bool) {
+        self.specs.retain(|configured| keep(configured.spec.name()));
+        self.handlers.retain(|name, _| keep(name));
+    }
+
+    pub(crate) fn set_availability(&mut self, availability: ToolAvailability) {
+        self.availability = availability;
+    }
+
     pub fn build(self) -> (Vec<ConfiguredToolSpec>, ToolRegistry) {
-        let registry = ToolRegistry::new(self.handlers);
+        let registry = ToolRegistry::new(self.handlers, self.availability);
         (self.specs, registry)
     }
 }
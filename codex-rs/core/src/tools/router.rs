@@ -2,7 +2,9 @@ use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
+use crate::function_tool::ToolCallError;
 use crate::sandboxing::SandboxPermissions;
+use crate::tool_budget;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolPayload;
@@ -17,8 +19,12 @@ use codex_protocol::models::ResponseItem;
 use codex_protocol::models::ShellToolCallParams;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tracing::debug;
 use tracing::instrument;
 
+/// Result of a tool call that arrived after `Op::SoftInterrupt`.
+const TURN_ENDED_BY_USER: &str = "turn ended by user; this tool call was not run";
+
 #[derive(Clone, Debug)]
 pub struct ToolCall {
     pub tool_name: String,
@@ -115,6 +121,8 @@ impl ToolRouter {
                             timeout_ms: exec.timeout_ms,
                             sandbox_permissions: Some(SandboxPermissions::UseDefault),
                             justification: None,
+                            background: None,
+                            env: None,
                         };
                         Ok(Some(ToolCall {
                             tool_name: "local_shell".to_string(),
@@ -128,6 +136,33 @@ impl ToolRouter {
         }
     }
 
+    /// Whether `call` may run alongside other calls from the same response
+    /// under `parallel_read_only_tools`; see
+    /// [`crate::tools::registry::ToolHandler::is_parallel_safe`].
+    pub async fn is_parallel_safe_call(
+        &self,
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        tracker: SharedTurnDiffTracker,
+        call: &ToolCall,
+    ) -> bool {
+        let Some(handler) = self.registry.handler(&call.tool_name) else {
+            return false;
+        };
+        if !handler.matches_kind(&call.payload) {
+            return false;
+        }
+        let invocation = ToolInvocation {
+            session,
+            turn,
+            tracker,
+            call_id: call.call_id.clone(),
+            tool_name: call.tool_name.clone(),
+            payload: call.payload.clone(),
+        };
+        handler.is_parallel_safe(&invocation).await
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     pub async fn dispatch_tool_call(
         &self,
@@ -142,7 +177,17 @@ impl ToolRouter {
             payload,
         } = call;
         let payload_outputs_custom = matches!(payload, ToolPayload::Custom { .. });
-        let failure_call_id = call_id.clone();
+
+        if session.turn_soft_interrupted().await {
+            let err = FunctionCallError::RespondToModel(TURN_ENDED_BY_USER.to_string())
+                .for_call(call_id, tool_name);
+            return Ok(Self::failure_response(payload_outputs_custom, err));
+        }
+
+        if let Err(message) = tool_budget::charge_tool_call(&session, &turn, &call_id).await {
+            let err = FunctionCallError::RespondToModel(message).for_call(call_id, tool_name);
+            return Ok(Self::failure_response(payload_outputs_custom, err));
+        }
 
         let invocation = ToolInvocation {
             session,
@@ -155,21 +200,22 @@ impl ToolRouter {
 
         match self.registry.dispatch(invocation).await {
             Ok(response) => Ok(response),
-            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
-            Err(err) => Ok(Self::failure_response(
-                failure_call_id,
-                payload_outputs_custom,
-                err,
-            )),
+            Err(ToolCallError {
+                error: FunctionCallError::Fatal(message),
+                ..
+            }) => Err(FunctionCallError::Fatal(message)),
+            Err(err) => Ok(Self::failure_response(payload_outputs_custom, err)),
         }
     }
 
-    fn failure_response(
-        call_id: String,
-        payload_outputs_custom: bool,
-        err: FunctionCallError,
-    ) -> ResponseInputItem {
+    fn failure_response(payload_outputs_custom: bool, err: ToolCallError) -> ResponseInputItem {
         let message = err.to_string();
+        debug!(
+            call_id = %err.call_id,
+            tool_name = %err.tool_name,
+            "tool call failed: {message}"
+        );
+        let call_id = err.call_id;
         if payload_outputs_custom {
             ResponseInputItem::CustomToolCallOutput {
                 call_id,
@@ -12,6 +12,7 @@ use crate::sandboxing::execute_env;
 use crate::tools::sandboxing::Approvable;
 use crate::tools::sandboxing::ApprovalCtx;
 use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::RejectionReason;
 use crate::tools::sandboxing::SandboxAttempt;
 use crate::tools::sandboxing::Sandboxable;
 use crate::tools::sandboxing::SandboxablePreference;
@@ -20,6 +21,7 @@ use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::with_cached_approval;
 use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::CLEAR_READONLY_ENV_VAR;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::FileChange;
 use codex_protocol::protocol::ReviewDecision;
@@ -27,15 +29,19 @@ use codex_utils_absolute_path::AbsolutePathBuf;
 use futures::future::BoxFuture;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct ApplyPatchRequest {
     pub action: ApplyPatchAction,
     pub file_paths: Vec<AbsolutePathBuf>,
-    pub changes: std::collections::HashMap<PathBuf, FileChange>,
+    pub changes: Arc<HashMap<PathBuf, FileChange>>,
     pub exec_approval_requirement: ExecApprovalRequirement,
     pub timeout_ms: Option<u64>,
     pub codex_exe: Option<PathBuf>,
+    /// Lets the patch change read-only files; see
+    /// [`codex_apply_patch::ApplyPatchOptions::clear_readonly`].
+    pub clear_readonly: bool,
 }
 
 #[derive(Default)]
@@ -51,8 +57,11 @@ impl ApplyPatchRuntime {
         let exe = if let Some(path) = &req.codex_exe {
             path.clone()
         } else {
-            env::current_exe()
-                .map_err(|e| ToolError::Rejected(format!("failed to determine codex exe: {e}")))?
+            env::current_exe().map_err(|e| {
+                ToolError::Rejected(RejectionReason::Unavailable {
+                    message: format!("failed to determine codex exe: {e}"),
+                })
+            })?
         };
         let program = exe.to_string_lossy().to_string();
         Ok(CommandSpec {
@@ -61,7 +70,11 @@ impl ApplyPatchRuntime {
             cwd: req.action.cwd.clone(),
             expiration: req.timeout_ms.into(),
             // Run apply_patch with a minimal environment for determinism and to avoid leaks.
-            env: HashMap::new(),
+            env: if req.clear_readonly {
+                HashMap::from([(CLEAR_READONLY_ENV_VAR.to_string(), "1".to_string())])
+            } else {
+                HashMap::new()
+            },
             sandbox_permissions: SandboxPermissions::UseDefault,
             justification: None,
         })
@@ -72,6 +85,8 @@ impl ApplyPatchRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            delta_chunk_bytes: ctx.turn.output_limits.delta_chunk_bytes,
+            spill_path: None,
         })
     }
 }
@@ -102,7 +117,7 @@ impl Approvable<ApplyPatchRequest> for ApplyPatchRuntime {
         let call_id = ctx.call_id.to_string();
         let retry_reason = ctx.retry_reason.clone();
         let approval_keys = self.approval_keys(req);
-        let changes = req.changes.clone();
+        let changes = req.changes.as_ref().clone();
         Box::pin(async move {
             if let Some(reason) = retry_reason {
                 let rx_approve = session
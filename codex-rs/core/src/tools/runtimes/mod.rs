@@ -8,6 +8,7 @@ use crate::exec::ExecExpiration;
 use crate::sandboxing::CommandSpec;
 use crate::sandboxing::SandboxPermissions;
 use crate::shell::Shell;
+use crate::tools::sandboxing::RejectionReason;
 use crate::tools::sandboxing::ToolError;
 use std::collections::HashMap;
 use std::path::Path;
@@ -26,9 +27,11 @@ pub(crate) fn build_command_spec(
     sandbox_permissions: SandboxPermissions,
     justification: Option<String>,
 ) -> Result<CommandSpec, ToolError> {
-    let (program, args) = command
-        .split_first()
-        .ok_or_else(|| ToolError::Rejected("command args are empty".to_string()))?;
+    let (program, args) = command.split_first().ok_or_else(|| {
+        ToolError::Rejected(RejectionReason::Unavailable {
+            message: "command args are empty".to_string(),
+        })
+    })?;
     Ok(CommandSpec {
         program: program.clone(),
         args: args.to_vec(),
@@ -2,11 +2,15 @@
 Runtime: shell
 
 Executes shell requests under the orchestrator: asks for approval when needed,
-builds a CommandSpec, and runs it under the current SandboxAttempt.
+builds a CommandSpec, and runs it under the current SandboxAttempt. Background
+jobs use the same approval and sandbox selection but only get the prepared
+ExecEnv back; the caller spawns it.
 */
 use crate::exec::ExecToolCallOutput;
+use crate::exec_env::injected_env;
 use crate::features::Feature;
 use crate::powershell::prefix_powershell_script_with_utf8;
+use crate::sandboxing::ExecEnv;
 use crate::sandboxing::SandboxPermissions;
 use crate::sandboxing::execute_env;
 use crate::shell::ShellType;
@@ -23,8 +27,11 @@ use crate::tools::sandboxing::ToolCtx;
 use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::with_cached_approval;
+use crate::tools::spill::spill_dir;
+use crate::tools::spill::spill_path;
 use codex_protocol::protocol::ReviewDecision;
 use futures::future::BoxFuture;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
@@ -33,6 +40,8 @@ pub struct ShellRequest {
     pub cwd: PathBuf,
     pub timeout_ms: Option<u64>,
     pub env: std::collections::HashMap<String, String>,
+    /// The variables in `env` that the call itself set.
+    pub env_overrides: std::collections::HashMap<String, String>,
     pub sandbox_permissions: SandboxPermissions,
     pub justification: Option<String>,
     pub exec_approval_requirement: ExecApprovalRequirement,
@@ -45,6 +54,9 @@ pub struct ShellRuntime;
 pub(crate) struct ApprovalKey {
     command: Vec<String>,
     cwd: PathBuf,
+    /// An approval for a command does not carry over to the same command run
+    /// with different variables.
+    env: BTreeMap<String, String>,
     sandbox_permissions: SandboxPermissions,
 }
 
@@ -58,8 +70,44 @@ impl ShellRuntime {
             sub_id: ctx.turn.sub_id.clone(),
             call_id: ctx.call_id.clone(),
             tx_event: ctx.session.get_tx_event(),
+            delta_chunk_bytes: ctx.turn.output_limits.delta_chunk_bytes,
+            spill_path: ctx.turn.output_limits.spill_threshold.map(|_| {
+                spill_path(
+                    &spill_dir(ctx.turn, &ctx.session.conversation_id.to_string()),
+                    &ctx.call_id,
+                )
+            }),
         })
     }
+
+    fn exec_env(
+        req: &ShellRequest,
+        attempt: &SandboxAttempt<'_>,
+        ctx: &ToolCtx<'_>,
+    ) -> Result<ExecEnv, ToolError> {
+        let base_command = &req.command;
+        let session_shell = ctx.session.user_shell();
+        let command = maybe_wrap_shell_lc_with_snapshot(base_command, session_shell.as_ref());
+        let command = if matches!(session_shell.shell_type, ShellType::PowerShell)
+            && ctx.session.features().enabled(Feature::PowershellUtf8)
+        {
+            prefix_powershell_script_with_utf8(&command)
+        } else {
+            command
+        };
+
+        let spec = build_command_spec(
+            &command,
+            &req.cwd,
+            &req.env,
+            req.timeout_ms.into(),
+            req.sandbox_permissions,
+            req.justification.clone(),
+        )?;
+        attempt
+            .env_for(spec)
+            .map_err(|err| ToolError::Codex(err.into()))
+    }
 }
 
 impl Sandboxable for ShellRuntime {
@@ -78,10 +126,22 @@ impl Approvable<ShellRequest> for ShellRuntime {
         vec![ApprovalKey {
             command: req.command.clone(),
             cwd: req.cwd.clone(),
+            env: req.env_overrides.clone().into_iter().collect(),
             sandbox_permissions: req.sandbox_permissions,
         }]
     }
 
+    fn with_edited_command(
+        &self,
+        req: &ShellRequest,
+        command: Vec<String>,
+    ) -> Option<ShellRequest> {
+        Some(ShellRequest {
+            command,
+            ..req.clone()
+        })
+    }
+
     fn start_approval_async<'a>(
         &'a mut self,
         req: &'a ShellRequest,
@@ -90,10 +150,12 @@ impl Approvable<ShellRequest> for ShellRuntime {
         let keys = self.approval_keys(req);
         let command = req.command.clone();
         let cwd = req.cwd.clone();
+        let injected_env = injected_env(&req.env_overrides);
         let reason = ctx
             .retry_reason
             .clone()
             .or_else(|| req.justification.clone());
+        let sandbox_denial_output = ctx.sandbox_denial_output.clone();
         let session = ctx.session;
         let turn = ctx.turn;
         let call_id = ctx.call_id.to_string();
@@ -105,10 +167,12 @@ impl Approvable<ShellRequest> for ShellRuntime {
                         call_id,
                         command,
                         cwd,
+                        injected_env,
                         reason,
                         req.exec_approval_requirement
                             .proposed_execpolicy_amendment()
                             .cloned(),
+                        sandbox_denial_output,
                     )
                     .await
             })
@@ -144,31 +208,23 @@ impl ToolRuntime<ShellRequest, ExecToolCallOutput> for ShellRuntime {
         attempt: &SandboxAttempt<'_>,
         ctx: &ToolCtx<'_>,
     ) -> Result<ExecToolCallOutput, ToolError> {
-        let base_command = &req.command;
-        let session_shell = ctx.session.user_shell();
-        let command = maybe_wrap_shell_lc_with_snapshot(base_command, session_shell.as_ref());
-        let command = if matches!(session_shell.shell_type, ShellType::PowerShell)
-            && ctx.session.features().enabled(Feature::PowershellUtf8)
-        {
-            prefix_powershell_script_with_utf8(&command)
-        } else {
-            command
-        };
-
-        let spec = build_command_spec(
-            &command,
-            &req.cwd,
-            &req.env,
-            req.timeout_ms.into(),
-            req.sandbox_permissions,
-            req.justification.clone(),
-        )?;
-        let env = attempt
-            .env_for(spec)
-            .map_err(|err| ToolError::Codex(err.into()))?;
+        let env = Self::exec_env(req, attempt, ctx)?;
         let out = execute_env(env, attempt.policy, Self::stdout_stream(ctx))
             .await
             .map_err(ToolError::Codex)?;
         Ok(out)
     }
 }
+
+/// Background jobs: returns the command ready to run under the selected
+/// sandbox without starting it.
+impl ToolRuntime<ShellRequest, ExecEnv> for ShellRuntime {
+    async fn run(
+        &mut self,
+        req: &ShellRequest,
+        attempt: &SandboxAttempt<'_>,
+        ctx: &ToolCtx<'_>,
+    ) -> Result<ExecEnv, ToolError> {
+        Self::exec_env(req, attempt, ctx)
+    }
+}
@@ -7,6 +7,7 @@ the process manager to spawn PTYs once an ExecEnv is prepared.
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecExpiration;
+use crate::exec_env::injected_env;
 use crate::features::Feature;
 use crate::powershell::prefix_powershell_script_with_utf8;
 use crate::sandboxing::SandboxPermissions;
@@ -16,6 +17,7 @@ use crate::tools::runtimes::maybe_wrap_shell_lc_with_snapshot;
 use crate::tools::sandboxing::Approvable;
 use crate::tools::sandboxing::ApprovalCtx;
 use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::RejectionReason;
 use crate::tools::sandboxing::SandboxAttempt;
 use crate::tools::sandboxing::SandboxOverride;
 use crate::tools::sandboxing::Sandboxable;
@@ -29,6 +31,7 @@ use crate::unified_exec::UnifiedExecProcess;
 use crate::unified_exec::UnifiedExecProcessManager;
 use codex_protocol::protocol::ReviewDecision;
 use futures::future::BoxFuture;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -37,6 +40,8 @@ pub struct UnifiedExecRequest {
     pub command: Vec<String>,
     pub cwd: PathBuf,
     pub env: HashMap<String, String>,
+    /// The variables in `env` that the call itself set.
+    pub env_overrides: HashMap<String, String>,
     pub tty: bool,
     pub sandbox_permissions: SandboxPermissions,
     pub justification: Option<String>,
@@ -47,6 +52,9 @@ pub struct UnifiedExecRequest {
 pub struct UnifiedExecApprovalKey {
     pub command: Vec<String>,
     pub cwd: PathBuf,
+    /// An approval for a command does not carry over to the same command run
+    /// with different variables.
+    pub env: BTreeMap<String, String>,
     pub tty: bool,
     pub sandbox_permissions: SandboxPermissions,
 }
@@ -56,10 +64,12 @@ pub struct UnifiedExecRuntime<'a> {
 }
 
 impl UnifiedExecRequest {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         command: Vec<String>,
         cwd: PathBuf,
         env: HashMap<String, String>,
+        env_overrides: HashMap<String, String>,
         tty: bool,
         sandbox_permissions: SandboxPermissions,
         justification: Option<String>,
@@ -69,6 +79,7 @@ impl UnifiedExecRequest {
             command,
             cwd,
             env,
+            env_overrides,
             tty,
             sandbox_permissions,
             justification,
@@ -100,11 +111,23 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
         vec![UnifiedExecApprovalKey {
             command: req.command.clone(),
             cwd: req.cwd.clone(),
+            env: req.env_overrides.clone().into_iter().collect(),
             tty: req.tty,
             sandbox_permissions: req.sandbox_permissions,
         }]
     }
 
+    fn with_edited_command(
+        &self,
+        req: &UnifiedExecRequest,
+        command: Vec<String>,
+    ) -> Option<UnifiedExecRequest> {
+        Some(UnifiedExecRequest {
+            command,
+            ..req.clone()
+        })
+    }
+
     fn start_approval_async<'b>(
         &'b mut self,
         req: &'b UnifiedExecRequest,
@@ -116,10 +139,12 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
         let call_id = ctx.call_id.to_string();
         let command = req.command.clone();
         let cwd = req.cwd.clone();
+        let injected_env = injected_env(&req.env_overrides);
         let reason = ctx
             .retry_reason
             .clone()
             .or_else(|| req.justification.clone());
+        let sandbox_denial_output = ctx.sandbox_denial_output.clone();
         Box::pin(async move {
             with_cached_approval(&session.services, "unified_exec", keys, || async move {
                 session
@@ -128,10 +153,12 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
                         call_id,
                         command,
                         cwd,
+                        injected_env,
                         reason,
                         req.exec_approval_requirement
                             .proposed_execpolicy_amendment()
                             .cloned(),
+                        sandbox_denial_output,
                     )
                     .await
             })
@@ -189,7 +216,11 @@ impl<'a> ToolRuntime<UnifiedExecRequest, UnifiedExecProcess> for UnifiedExecRunt
             req.sandbox_permissions,
             req.justification.clone(),
         )
-        .map_err(|_| ToolError::Rejected("missing command line for PTY".to_string()))?;
+        .map_err(|_| {
+            ToolError::Rejected(RejectionReason::Unavailable {
+                message: "missing command line for PTY".to_string(),
+            })
+        })?;
         let exec_env = attempt
             .env_for(spec)
             .map_err(|err| ToolError::Codex(err.into()))?;
@@ -202,7 +233,9 @@ impl<'a> ToolRuntime<UnifiedExecRequest, UnifiedExecProcess> for UnifiedExecRunt
                         output: Box::new(output),
                     }))
                 }
-                other => ToolError::Rejected(other.to_string()),
+                other => ToolError::Rejected(RejectionReason::Unavailable {
+                    message: other.to_string(),
+                }),
             })
     }
 }
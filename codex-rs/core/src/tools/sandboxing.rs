@@ -16,6 +16,7 @@ use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
 use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::path::Path;
@@ -35,7 +36,7 @@ impl ApprovalStore {
     where
         K: Serialize,
     {
-        let s = serde_json::to_string(key).ok()?;
+        let s = approval_signature(key)?;
         self.map.get(&s).cloned()
     }
 
@@ -43,12 +44,21 @@ impl ApprovalStore {
     where
         K: Serialize,
     {
-        if let Ok(s) = serde_json::to_string(&key) {
+        if let Some(s) = approval_signature(&key) {
             self.map.insert(s, value);
         }
     }
 }
 
+/// The string an approval key is cached under. Requests with equal
+/// signatures share a session approval.
+pub(crate) fn approval_signature<K>(key: &K) -> Option<String>
+where
+    K: Serialize,
+{
+    serde_json::to_string(key).ok()
+}
+
 /// Takes a vector of approval keys and returns a ReviewDecision.
 /// There will be one key in most cases, but apply_patch can modify multiple files at once.
 ///
@@ -109,6 +119,8 @@ pub(crate) struct ApprovalCtx<'a> {
     pub turn: &'a TurnContext,
     pub call_id: &'a str,
     pub retry_reason: Option<String>,
+    /// Output of the sandboxed attempt when asking to retry without it.
+    pub sandbox_denial_output: Option<String>,
 }
 
 // Specifies what tool orchestrator should do with a given tool call.
@@ -131,7 +143,7 @@ pub(crate) enum ExecApprovalRequirement {
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     },
     /// Execution forbidden for this tool call.
-    Forbidden { reason: String },
+    Forbidden { reason: RejectionReason },
 }
 
 impl ExecApprovalRequirement {
@@ -223,6 +235,13 @@ pub(crate) trait Approvable<Req> {
         !matches!(policy, AskForApproval::Never | AskForApproval::OnRequest)
     }
 
+    /// The request to run instead when the user approves an edited command.
+    /// Tools whose requests are not a command line return `None`, which
+    /// rejects the call.
+    fn with_edited_command(&self, _req: &Req, _command: Vec<String>) -> Option<Req> {
+        None
+    }
+
     fn start_approval_async<'a>(
         &'a mut self,
         req: &'a Req,
@@ -255,10 +274,55 @@ pub(crate) struct ToolCtx<'a> {
 
 #[derive(Debug)]
 pub(crate) enum ToolError {
-    Rejected(String),
+    Rejected(RejectionReason),
     Codex(CodexErr),
 }
 
+/// Why a tool call was not allowed to run. Producers pick a reason and
+/// [`RejectionReason::message_for`] turns it into the text the model sees, so
+/// nothing downstream has to match on phrases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RejectionReason {
+    /// A person declined the approval request, optionally saying why.
+    UserDenied { feedback: Option<String> },
+    /// A policy forbids the call. `rule` is the policy's own explanation.
+    PolicyDenied { rule: String },
+    /// An exec policy prefix rule without a justification forbids `command`.
+    DenyList { command: String, pattern: String },
+    /// The call could not be prepared, so there was nothing to approve.
+    Unavailable { message: String },
+}
+
+impl RejectionReason {
+    /// The model-facing message for a rejected call. `subject` names what was
+    /// rejected (e.g. "exec command") for a plain user denial.
+    pub(crate) fn message_for(&self, subject: Option<&str>) -> String {
+        match (self, subject) {
+            (Self::UserDenied { feedback: None }, Some(subject)) => {
+                format!("{subject} rejected by user")
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UserDenied { feedback: None } => write!(f, "rejected by user"),
+            Self::UserDenied {
+                feedback: Some(feedback),
+            } => write!(f, "rejected by user: {feedback}"),
+            Self::PolicyDenied { rule } => write!(f, "{rule}"),
+            Self::DenyList { command, pattern } => write!(
+                f,
+                "`{command}` rejected: policy forbids commands starting with `{pattern}`"
+            ),
+            Self::Unavailable { message } => write!(f, "{message}"),
+        }
+    }
+}
+
 pub(crate) trait ToolRuntime<Req, Out>: Approvable<Req> + Sandboxable {
     async fn run(
         &mut self,
@@ -323,4 +387,36 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn rejection_reasons_render_the_model_facing_phrases() {
+        let denied = RejectionReason::UserDenied { feedback: None };
+        assert_eq!(
+            denied.message_for(Some("exec command")),
+            "exec command rejected by user"
+        );
+        assert_eq!(denied.message_for(None), "rejected by user");
+        assert_eq!(
+            RejectionReason::UserDenied {
+                feedback: Some("use rg instead".to_string()),
+            }
+            .message_for(Some("exec command")),
+            "rejected by user: use rg instead"
+        );
+        assert_eq!(
+            RejectionReason::DenyList {
+                command: "rm -rf /".to_string(),
+                pattern: "rm".to_string(),
+            }
+            .message_for(Some("exec command")),
+            "`rm -rf /` rejected: policy forbids commands starting with `rm`"
+        );
+        assert_eq!(
+            RejectionReason::PolicyDenied {
+                rule: "blocked by policy".to_string(),
+            }
+            .message_for(Some("patch")),
+            "blocked by policy"
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
 use crate::features::Feature;
 use crate::features::Features;
+use crate::tools::availability::ToolAvailability;
 use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
@@ -30,7 +31,16 @@ pub(crate) struct ToolsConfig {
     pub web_search_mode: Option<WebSearchMode>,
     pub collab_tools: bool,
     pub collaboration_modes_tools: bool,
+    pub report_failure_tool: bool,
+    pub report_outcome_tool: bool,
+    pub search_workspace_tool: bool,
+    pub git_tool: bool,
+    pub git_remote_operations: bool,
+    pub background_jobs: bool,
+    pub capabilities_tool: bool,
+    pub read_exec_output_tool: bool,
     pub experimental_supported_tools: Vec<String>,
+    pub availability: ToolAvailability,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -49,6 +59,14 @@ impl ToolsConfig {
         let include_apply_patch_tool = features.enabled(Feature::ApplyPatchFreeform);
         let include_collab_tools = features.enabled(Feature::Collab);
         let include_collaboration_modes_tools = features.enabled(Feature::CollaborationModes);
+        let include_report_failure_tool = features.enabled(Feature::ReportFailure);
+        let include_report_outcome_tool = features.enabled(Feature::ReportOutcome);
+        let include_search_workspace_tool = features.enabled(Feature::SearchWorkspace);
+        let include_git_tool = features.enabled(Feature::GitTool);
+        let include_git_remote_operations = features.enabled(Feature::GitToolRemote);
+        let include_background_jobs = features.enabled(Feature::BackgroundJobs);
+        let include_capabilities_tool = features.enabled(Feature::CapabilityDescriptor);
+        let include_read_exec_output_tool = features.enabled(Feature::ReadExecOutput);
 
         let shell_type = if !features.enabled(Feature::ShellTool) {
             ConfigShellToolType::Disabled
@@ -81,11 +99,65 @@ impl ToolsConfig {
             web_search_mode: *web_search_mode,
             collab_tools: include_collab_tools,
             collaboration_modes_tools: include_collaboration_modes_tools,
+            report_failure_tool: include_report_failure_tool,
+            report_outcome_tool: include_report_outcome_tool,
+            search_workspace_tool: include_search_workspace_tool,
+            git_tool: include_git_tool,
+            git_remote_operations: include_git_remote_operations,
+            background_jobs: include_background_jobs,
+            capabilities_tool: include_capabilities_tool,
+            read_exec_output_tool: include_read_exec_output_tool,
             experimental_supported_tools: model_info.experimental_supported_tools.clone(),
+            availability: ToolAvailability::default(),
         }
     }
+
+    /// Applies the `[[model_tools]]` settings for the model. Disabling
+    /// `shell` removes every shell tool, and disabling `unified_exec` falls
+    /// back to `shell_command`. Enabling `apply_patch` or an experimental
+    /// tool offers it even when the model does not by default. Other
+    /// disabled tools are left out of the specs in `build_specs`.
+    pub fn with_availability(mut self, availability: ToolAvailability) -> Self {
+        if availability.is_disabled("shell") {
+            self.shell_type = ConfigShellToolType::Disabled;
+        } else if self.shell_type == ConfigShellToolType::UnifiedExec
+            && availability.is_disabled("unified_exec")
+        {
+            self.shell_type = ConfigShellToolType::ShellCommand;
+        }
+
+        if availability.is_disabled("apply_patch") {
+            self.apply_patch_tool_type = None;
+        } else if availability.is_enabled("apply_patch") && self.apply_patch_tool_type.is_none() {
+            self.apply_patch_tool_type = Some(ApplyPatchToolType::Freeform);
+        }
+
+        if availability.is_disabled("web_search") {
+            self.web_search_mode = None;
+        }
+
+        for tool in EXPERIMENTAL_TOOLS {
+            if availability.is_enabled(tool)
+                && !self
+                    .experimental_supported_tools
+                    .iter()
+                    .any(|name| name == tool)
+            {
+                self.experimental_supported_tools.push(tool.to_string());
+            }
+        }
+        self.experimental_supported_tools
+            .retain(|tool| !availability.is_disabled(tool));
+
+        self.availability = availability;
+        self
+    }
 }
 
+/// Tools offered only to models that list them in
+/// `experimental_supported_tools`, unless `[[model_tools]]` enables them.
+const EXPERIMENTAL_TOOLS: &[&str] = &["grep_files", "read_file", "list_dir"];
+
 /// Generic JSON‑Schema subset needed for our tool definitions
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -142,6 +214,23 @@ impl From<JsonSchema> for AdditionalProperties {
     }
 }
 
+/// `env` of the exec tools: variables set for this command only.
+fn create_env_property() -> JsonSchema {
+    JsonSchema::Object {
+        properties: BTreeMap::new(),
+        required: None,
+        additional_properties: Some(
+            JsonSchema::String {
+                description: Some(
+                    "Environment variables to set for this command only, as name-value pairs, on top of the usual environment. Variables the environment policy filters out are refused."
+                        .to_string(),
+                ),
+            }
+            .into(),
+        ),
+    }
+}
+
 fn create_exec_command_tool() -> ToolSpec {
     let properties = BTreeMap::from([
         (
@@ -217,6 +306,7 @@ fn create_exec_command_tool() -> ToolSpec {
                 ),
             },
         ),
+        ("env".to_string(), create_env_property()),
     ]);
 
     ToolSpec::Function(ResponsesApiTool {
@@ -280,8 +370,17 @@ fn create_write_stdin_tool() -> ToolSpec {
     })
 }
 
-fn create_shell_tool() -> ToolSpec {
-    let properties = BTreeMap::from([
+fn create_background_property() -> JsonSchema {
+    JsonSchema::Boolean {
+        description: Some(
+            "Start the command as a background job and return its id right away instead of waiting for it. Use for long builds and test runs, then check on it with get_background_job."
+                .to_string(),
+        ),
+    }
+}
+
+fn create_shell_tool(background_jobs: bool) -> ToolSpec {
+    let mut properties = BTreeMap::from([
         (
             "command".to_string(),
             JsonSchema::Array {
@@ -313,6 +412,7 @@ fn create_shell_tool() -> ToolSpec {
                 description: Some("Only set if sandbox_permissions is \"require_escalated\". 1-sentence explanation of why we want to run this command.".to_string()),
             },
         ),
+        ("env".to_string(), create_env_property()),
     ]);
 
     let description  = if cfg!(windows) {
@@ -332,6 +432,10 @@ Examples of valid command strings:
 - Always set the `workdir` param when using the shell function. Do not use `cd` unless absolutely necessary."#
     }.to_string();
 
+    if background_jobs {
+        properties.insert("background".to_string(), create_background_property());
+    }
+
     ToolSpec::Function(ResponsesApiTool {
         name: "shell".to_string(),
         description,
@@ -344,8 +448,8 @@ Examples of valid command strings:
     })
 }
 
-fn create_shell_command_tool() -> ToolSpec {
-    let properties = BTreeMap::from([
+fn create_shell_command_tool(background_jobs: bool) -> ToolSpec {
+    let mut properties = BTreeMap::from([
         (
             "command".to_string(),
             JsonSchema::String {
@@ -387,6 +491,7 @@ fn create_shell_command_tool() -> ToolSpec {
                 description: Some("Only set if sandbox_permissions is \"require_escalated\". 1-sentence explanation of why we want to run this command.".to_string()),
             },
         ),
+        ("env".to_string(), create_env_property()),
     ]);
 
     let description = if cfg!(windows) {
@@ -405,6 +510,10 @@ Examples of valid command strings:
 - Always set the `workdir` param when using the shell_command function. Do not use `cd` unless absolutely necessary."#
     }.to_string();
 
+    if background_jobs {
+        properties.insert("background".to_string(), create_background_property());
+    }
+
     ToolSpec::Function(ResponsesApiTool {
         name: "shell_command".to_string(),
         description,
@@ -616,14 +725,354 @@ fn create_request_user_input_tool() -> ToolSpec {
     properties.insert("questions".to_string(), questions_schema);
 
     ToolSpec::Function(ResponsesApiTool {
-        name: "request_user_input".to_string(),
-        description:
-            "Request user input for one to three short questions and wait for the response."
-                .to_string(),
+        name: "request_user_input".to_string(),
+        description:
+            "Request user input for one to three short questions and wait for the response."
+                .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["questions".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_job_id_properties() -> BTreeMap<String, JsonSchema> {
+    BTreeMap::from([(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("The job id returned when the job was started.".to_string()),
+        },
+    )])
+}
+
+fn create_get_background_job_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "get_background_job".to_string(),
+        description:
+            "Shows the status of a background job and, once it has ended, its exit code and output."
+                .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: create_job_id_properties(),
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_list_background_jobs_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "list_background_jobs".to_string(),
+        description: "Lists the background jobs of this session with their status, oldest first."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_kill_background_job_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "kill_background_job".to_string(),
+        description: "Stops a running background job and returns its final status and output."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: create_job_id_properties(),
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_get_environment_capabilities_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "get_environment_capabilities".to_string(),
+        description: "Returns what the sandbox and policies currently allow: sandbox mode, network access, writable roots, approval policy, available tools, and limits. Check it before trying network access or writes you are unsure are allowed."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_read_exec_output_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "call_id".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Call id of an earlier shell or exec call in this session.".to_string(),
+                ),
+            },
+        ),
+        (
+            "offset_lines".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "Lines to skip, counting only matching lines when grep is set (defaults to 0)."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "limit_lines".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "Most lines to return (defaults to as many as fit the output budget)."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "grep".to_string(),
+            JsonSchema::String {
+                description: Some("Only return lines matching this regex.".to_string()),
+            },
+        ),
+    ]);
+    ToolSpec::Function(ResponsesApiTool {
+        name: "read_exec_output".to_string(),
+        description: "Pages through the full output of an earlier command without running it again, such as output that was truncated or saved to a file. Lines are numbered as in the full output."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["call_id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_report_outcome_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "status".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "How the task went: success, failure, or needs_human when it needs a decision or action only a person can take."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "summary".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "What was done, or why it was not, in one or two sentences.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "details".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional specifics for whoever reads the outcome, such as failing tests or the decision needed."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "report_outcome".to_string(),
+        description: "Report how the task went, for automation that reads the result. Call this exactly once, at the very end of the task, after all other work is done; then end your turn with a short summary. If you call it again, only the last outcome counts."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["status".to_string(), "summary".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_report_failure_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "reason".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Why the task cannot be completed, in one or two sentences.".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "report_failure".to_string(),
+        description: "Report that the task cannot be completed. Call this instead of claiming success when you are blocked or the requested outcome is not achievable, then end your turn with a short summary."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["reason".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_search_workspace_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "query".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Text to search for; a regular expression when regex is true.".to_string(),
+                ),
+            },
+        ),
+        (
+            "glob".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Only search files whose path matches this glob (e.g. \"*.rs\" or \"src/**\")."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "regex".to_string(),
+            JsonSchema::Boolean {
+                description: Some(
+                    "Treat query as a regular expression. Defaults to false.".to_string(),
+                ),
+            },
+        ),
+        (
+            "max_results".to_string(),
+            JsonSchema::Number {
+                description: Some("Most matches to return (default 50, at most 500).".to_string()),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "search_workspace".to_string(),
+        description: "Searches the files in the working directory that are not ignored by .gitignore, returning each match's path, line number, line, and nearby lines as JSON. Prefer this over grep or rg."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_git_tool(remote_operations: bool) -> ToolSpec {
+    let subcommands = if remote_operations {
+        "status, diff, log, branch, add, commit, stash, push, pull, or fetch"
+    } else {
+        "status, diff, log, branch, add, commit, or stash"
+    };
+    let mut properties = BTreeMap::from([
+        (
+            "subcommand".to_string(),
+            JsonSchema::String {
+                description: Some(format!("One of {subcommands}.")),
+            },
+        ),
+        (
+            "paths".to_string(),
+            JsonSchema::Array {
+                items: Box::new(JsonSchema::String { description: None }),
+                description: Some(
+                    "Paths to limit status, diff or log to; required for add.".to_string(),
+                ),
+            },
+        ),
+        (
+            "message".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Commit message for commit; optional message for stash push.".to_string(),
+                ),
+            },
+        ),
+        (
+            "staged".to_string(),
+            JsonSchema::Boolean {
+                description: Some(
+                    "For diff: show staged changes instead of unstaged ones.".to_string(),
+                ),
+            },
+        ),
+        (
+            "max_count".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "For log: most commits to list (default 20, at most 200).".to_string(),
+                ),
+            },
+        ),
+        (
+            "name".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "For branch: create a branch with this name instead of listing branches."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "start_point".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "For branch: where the new branch starts. Defaults to HEAD.".to_string(),
+                ),
+            },
+        ),
+        (
+            "action".to_string(),
+            JsonSchema::String {
+                description: Some("For stash: push (default), pop, or list.".to_string()),
+            },
+        ),
+        (
+            "workdir".to_string(),
+            JsonSchema::String {
+                description: Some("The working directory to run git in.".to_string()),
+            },
+        ),
+        (
+            "timeout_ms".to_string(),
+            JsonSchema::Number {
+                description: Some("The timeout for the command in milliseconds.".to_string()),
+            },
+        ),
+    ]);
+    if remote_operations {
+        properties.insert(
+            "remote".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "For push, pull or fetch: the remote. Defaults to the upstream.".to_string(),
+                ),
+            },
+        );
+        properties.insert(
+            "branch".to_string(),
+            JsonSchema::String {
+                description: Some("For push or pull: the remote branch; needs remote.".to_string()),
+            },
+        );
+    }
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "git".to_string(),
+        description: "Runs a git subcommand in the working directory and returns the parsed result as JSON. Prefer this over running git through the shell."
+            .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["questions".to_string()]),
+            required: Some(vec!["subcommand".to_string()]),
             additional_properties: Some(false.into()),
         },
     })
@@ -774,7 +1223,7 @@ fn create_read_file_tool() -> ToolSpec {
             "anchor_line".to_string(),
             JsonSchema::Number {
                 description: Some(
-                    "Anchor line to center the indentation lookup on (defaults to offset)."
+                    "Anchor line to center the indentation lookup on (defaults to start_line)."
                         .to_string(),
                 ),
             },
@@ -818,25 +1267,43 @@ fn create_read_file_tool() -> ToolSpec {
 
     let properties = BTreeMap::from([
         (
-            "file_path".to_string(),
+            "path".to_string(),
             JsonSchema::String {
-                description: Some("Absolute path to the file".to_string()),
+                description: Some(
+                    "Path to the file, absolute or relative to the working directory.".to_string(),
+                ),
             },
         ),
         (
-            "offset".to_string(),
+            "start_line".to_string(),
             JsonSchema::Number {
                 description: Some(
                     "The line number to start reading from. Must be 1 or greater.".to_string(),
                 ),
             },
         ),
+        (
+            "end_line".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "The last line to return, inclusive. Takes the place of limit.".to_string(),
+                ),
+            },
+        ),
         (
             "limit".to_string(),
             JsonSchema::Number {
                 description: Some("The maximum number of lines to return.".to_string()),
             },
         ),
+        (
+            "max_bytes".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "The most output to return; later lines are left out with a note.".to_string(),
+                ),
+            },
+        ),
         (
             "mode".to_string(),
             JsonSchema::String {
@@ -860,12 +1327,12 @@ fn create_read_file_tool() -> ToolSpec {
     ToolSpec::Function(ResponsesApiTool {
         name: "read_file".to_string(),
         description:
-            "Reads a local file with 1-indexed line numbers, supporting slice and indentation-aware block modes."
+            "Reads a file in the workspace with 1-indexed line numbers, supporting slice and indentation-aware block modes. Binary files are described instead of shown. Prefer this over sed or cat."
                 .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["file_path".to_string()]),
+            required: Some(vec!["path".to_string()]),
             additional_properties: Some(false.into()),
         },
     })
@@ -874,30 +1341,44 @@ fn create_read_file_tool() -> ToolSpec {
 fn create_list_dir_tool() -> ToolSpec {
     let properties = BTreeMap::from([
         (
-            "dir_path".to_string(),
+            "path".to_string(),
             JsonSchema::String {
-                description: Some("Absolute path to the directory to list.".to_string()),
+                description: Some(
+                    "The directory to list, absolute or relative to the working directory."
+                        .to_string(),
+                ),
             },
         ),
         (
-            "offset".to_string(),
+            "depth".to_string(),
             JsonSchema::Number {
                 description: Some(
-                    "The entry number to start listing from. Must be 1 or greater.".to_string(),
+                    "How many levels to descend; defaults to 1 and is capped at 5.".to_string(),
                 ),
             },
         ),
         (
-            "limit".to_string(),
+            "include_hidden".to_string(),
+            JsonSchema::Boolean {
+                description: Some(
+                    "Also list dotfiles. Entries excluded by .gitignore stay hidden.".to_string(),
+                ),
+            },
+        ),
+        (
+            "offset".to_string(),
             JsonSchema::Number {
-                description: Some("The maximum number of entries to return.".to_string()),
+                description: Some(
+                    "The entry number to start listing from. Must be 1 or greater.".to_string(),
+                ),
             },
         ),
         (
-            "depth".to_string(),
+            "max_entries".to_string(),
             JsonSchema::Number {
                 description: Some(
-                    "The maximum directory depth to traverse. Must be 1 or greater.".to_string(),
+                    "The maximum number of entries to return; defaults to 100 and is capped at 500."
+                        .to_string(),
                 ),
             },
         ),
@@ -906,12 +1387,12 @@ fn create_list_dir_tool() -> ToolSpec {
     ToolSpec::Function(ResponsesApiTool {
         name: "list_dir".to_string(),
         description:
-            "Lists entries in a local directory with 1-indexed entry numbers and simple type labels."
+            "Lists a workspace directory as JSON entries with name, kind, size and modification time, skipping what .gitignore excludes. Long listings say how many entries were left out."
                 .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["dir_path".to_string()]),
+            required: Some(vec!["path".to_string()]),
             additional_properties: Some(false.into()),
         },
     })
@@ -1000,22 +1481,39 @@ fn create_read_mcp_resource_tool() -> ToolSpec {
             "uri".to_string(),
             JsonSchema::String {
                 description: Some(
-                    "Resource URI to read. Must be one of the URIs returned by list_mcp_resources."
+                    "Resource URI to read. Must be one of the URIs returned by list_mcp_resources. Omit when using uri_template."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "uri_template".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "A uriTemplate returned by list_mcp_resource_templates, read with its variables filled in from params."
                         .to_string(),
                 ),
             },
         ),
+        (
+            "params".to_string(),
+            JsonSchema::Object {
+                properties: BTreeMap::new(),
+                required: None,
+                additional_properties: Some(true.into()),
+            },
+        ),
     ]);
 
     ToolSpec::Function(ResponsesApiTool {
         name: "read_mcp_resource".to_string(),
         description:
-            "Read a specific resource from an MCP server given the server name and resource URI."
+            "Read a specific resource from an MCP server given the server name and either a resource URI or a resource template with params."
                 .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["server".to_string(), "uri".to_string()]),
+            required: Some(vec!["server".to_string()]),
             additional_properties: Some(false.into()),
         },
     })
@@ -1103,7 +1601,8 @@ pub(crate) fn mcp_tool_to_openai_tool(
     // `type`, so we coerce/sanitize here for compatibility.
     let mut serialized_input_schema = serde_json::to_value(input_schema)?;
     sanitize_json_schema(&mut serialized_input_schema);
-    let input_schema = serde_json::from_value::<JsonSchema>(serialized_input_schema)?;
+    let mut input_schema = serde_json::from_value::<JsonSchema>(serialized_input_schema)?;
+    canonicalize_json_schema(&mut input_schema);
 
     Ok(ResponsesApiTool {
         name: fully_qualified_name,
@@ -1130,7 +1629,35 @@ fn dynamic_tool_to_openai_tool(
 pub fn parse_tool_input_schema(input_schema: &JsonValue) -> Result<JsonSchema, serde_json::Error> {
     let mut input_schema = input_schema.clone();
     sanitize_json_schema(&mut input_schema);
-    serde_json::from_value::<JsonSchema>(input_schema)
+    let mut input_schema = serde_json::from_value::<JsonSchema>(input_schema)?;
+    canonicalize_json_schema(&mut input_schema);
+    Ok(input_schema)
+}
+
+/// Sorts every `required` list, so a schema serializes to the same bytes
+/// however its source ordered them. Tool definitions lead every request, and
+/// any change to them defeats the provider's prompt cache.
+fn canonicalize_json_schema(schema: &mut JsonSchema) {
+    match schema {
+        JsonSchema::Boolean { .. } | JsonSchema::String { .. } | JsonSchema::Number { .. } => {}
+        JsonSchema::Array { items, .. } => canonicalize_json_schema(items),
+        JsonSchema::Object {
+            properties,
+            required,
+            additional_properties,
+        } => {
+            for property in properties.values_mut() {
+                canonicalize_json_schema(property);
+            }
+            if let Some(required) = required {
+                required.sort();
+                required.dedup();
+            }
+            if let Some(AdditionalProperties::Schema(schema)) = additional_properties {
+                canonicalize_json_schema(schema);
+            }
+        }
+    }
 }
 
 /// Sanitize a JSON Schema (as serde_json::Value) so it can fit our limited
@@ -1251,15 +1778,23 @@ pub(crate) fn build_specs(
     dynamic_tools: &[DynamicToolSpec],
 ) -> ToolRegistryBuilder {
     use crate::tools::handlers::ApplyPatchHandler;
+    use crate::tools::handlers::BackgroundJobsHandler;
     use crate::tools::handlers::CollabHandler;
     use crate::tools::handlers::DynamicToolHandler;
+    use crate::tools::handlers::EnvironmentCapabilitiesHandler;
+    use crate::tools::handlers::GitHandler;
     use crate::tools::handlers::GrepFilesHandler;
+    use crate::tools::handlers::KillBackgroundJobHandler;
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
     use crate::tools::handlers::McpResourceHandler;
     use crate::tools::handlers::PlanHandler;
+    use crate::tools::handlers::ReadExecOutputHandler;
     use crate::tools::handlers::ReadFileHandler;
+    use crate::tools::handlers::ReportFailureHandler;
+    use crate::tools::handlers::ReportOutcomeHandler;
     use crate::tools::handlers::RequestUserInputHandler;
+    use crate::tools::handlers::SearchWorkspaceHandler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
     use crate::tools::handlers::TestSyncHandler;
@@ -1282,7 +1817,7 @@ pub(crate) fn build_specs(
 
     match &config.shell_type {
         ConfigShellToolType::Default => {
-            builder.push_spec(create_shell_tool());
+            builder.push_spec(create_shell_tool(config.background_jobs));
         }
         ConfigShellToolType::Local => {
             builder.push_spec(ToolSpec::LocalShell {});
@@ -1297,7 +1832,7 @@ pub(crate) fn build_specs(
             // Do nothing.
         }
         ConfigShellToolType::ShellCommand => {
-            builder.push_spec(create_shell_command_tool());
+            builder.push_spec(create_shell_command_tool(config.background_jobs));
         }
     }
 
@@ -1309,6 +1844,21 @@ pub(crate) fn build_specs(
         builder.register_handler("shell_command", shell_command_handler);
     }
 
+    if config.background_jobs
+        && matches!(
+            config.shell_type,
+            ConfigShellToolType::Default | ConfigShellToolType::ShellCommand
+        )
+    {
+        let background_jobs_handler = Arc::new(BackgroundJobsHandler);
+        builder.push_spec_with_parallel_support(create_get_background_job_tool(), true);
+        builder.push_spec_with_parallel_support(create_list_background_jobs_tool(), true);
+        builder.push_spec(create_kill_background_job_tool());
+        builder.register_handler("get_background_job", background_jobs_handler.clone());
+        builder.register_handler("list_background_jobs", background_jobs_handler);
+        builder.register_handler("kill_background_job", Arc::new(KillBackgroundJobHandler));
+    }
+
     builder.push_spec_with_parallel_support(create_list_mcp_resources_tool(), true);
     builder.push_spec_with_parallel_support(create_list_mcp_resource_templates_tool(), true);
     builder.push_spec_with_parallel_support(create_read_mcp_resource_tool(), true);
@@ -1324,6 +1874,40 @@ pub(crate) fn build_specs(
         builder.register_handler("request_user_input", request_user_input_handler);
     }
 
+    if config.report_failure_tool {
+        builder.push_spec(create_report_failure_tool());
+        builder.register_handler("report_failure", Arc::new(ReportFailureHandler));
+    }
+
+    if config.report_outcome_tool {
+        builder.push_spec(create_report_outcome_tool());
+        builder.register_handler("report_outcome", Arc::new(ReportOutcomeHandler));
+    }
+
+    if config.capabilities_tool {
+        builder.push_spec_with_parallel_support(create_get_environment_capabilities_tool(), true);
+        builder.register_handler(
+            "get_environment_capabilities",
+            Arc::new(EnvironmentCapabilitiesHandler),
+        );
+    }
+
+    if config.read_exec_output_tool {
+        builder.push_spec_with_parallel_support(create_read_exec_output_tool(), true);
+        builder.register_handler("read_exec_output", Arc::new(ReadExecOutputHandler));
+    }
+
+    if config.search_workspace_tool {
+        builder.push_spec_with_parallel_support(create_search_workspace_tool(), true);
+        builder.register_handler("search_workspace", Arc::new(SearchWorkspaceHandler));
+    }
+
+    if config.git_tool {
+        builder
+            .push_spec_with_parallel_support(create_git_tool(config.git_remote_operations), true);
+        builder.register_handler("git", Arc::new(GitHandler));
+    }
+
     if let Some(apply_patch_tool_type) = &config.apply_patch_tool_type {
         match apply_patch_tool_type {
             ApplyPatchToolType::Freeform => {
@@ -1436,9 +2020,22 @@ pub(crate) fn build_specs(
         }
     }
 
+    builder.retain_tools(|name| !config.availability.is_disabled(name));
+    builder.set_availability(config.availability.clone());
     builder
 }
 
+/// Names of the built-in and dynamic tools offered to the model under
+/// `config`, in the order they are sent. MCP tools are not included since
+/// they are listed per turn.
+pub(crate) fn tool_names(config: &ToolsConfig, dynamic_tools: &[DynamicToolSpec]) -> Vec<String> {
+    let (specs, _) = build_specs(config, None, dynamic_tools).build();
+    specs
+        .iter()
+        .map(|configured| configured.spec.name().to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::client_common::tools::FreeformTool;
@@ -1536,6 +2133,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn model_tools_availability_filters_specs() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::UnifiedExec);
+        let availability = ToolAvailability::for_model(
+            &[crate::config::types::ModelToolsToml {
+                model: "gpt-5*".to_string(),
+                tools: BTreeMap::from([
+                    ("unified_exec".to_string(), false),
+                    ("web_search".to_string(), false),
+                    ("view_image".to_string(), false),
+                    ("grep_files".to_string(), true),
+                ]),
+                mcp_servers: BTreeMap::new(),
+            }],
+            &model_info.slug,
+        );
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Live),
+        })
+        .with_availability(availability);
+
+        assert_eq!(tools_config.shell_type, ConfigShellToolType::ShellCommand);
+        let names = tool_names(&tools_config, &[]);
+        assert_contains_tool_names(
+            &build_specs(&tools_config, None, &[]).build().0,
+            &["shell_command", "grep_files", "update_plan"],
+        );
+        for disabled in ["exec_command", "write_stdin", "web_search", "view_image"] {
+            assert!(
+                !names.iter().any(|name| name == disabled),
+                "expected {disabled} to be filtered out; had: {names:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_full_toolset_specs_for_gpt5_codex_unified_exec_web_search() {
         let config = test_config();
@@ -1646,6 +2283,215 @@ mod tests {
         assert_contains_tool_names(&tools, &["request_user_input"]);
     }
 
+    #[test]
+    fn report_failure_requires_report_failure_feature() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "report_failure"),
+            "report_failure should be disabled by default"
+        );
+
+        features.enable(Feature::ReportFailure);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert_contains_tool_names(&tools, &["report_failure"]);
+    }
+
+    #[test]
+    fn background_jobs_add_the_shell_option_and_job_tools() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::BackgroundJobs);
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        tools_config.shell_type = ConfigShellToolType::ShellCommand;
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert_contains_tool_names(
+            &tools,
+            &[
+                "shell_command",
+                "get_background_job",
+                "list_background_jobs",
+                "kill_background_job",
+            ],
+        );
+        let ToolSpec::Function(ResponsesApiTool {
+            parameters: JsonSchema::Object { properties, .. },
+            ..
+        }) = &find_tool(&tools, "shell_command").spec
+        else {
+            panic!("expected shell_command to be a function tool");
+        };
+        assert!(properties.contains_key("background"));
+
+        tools_config.shell_type = ConfigShellToolType::UnifiedExec;
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "get_background_job"),
+            "job tools need a shell tool that takes `background`"
+        );
+    }
+
+    #[test]
+    fn report_outcome_requires_its_feature() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "report_outcome"),
+            "report_outcome should be disabled by default"
+        );
+
+        features.enable(Feature::ReportOutcome);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert_contains_tool_names(&tools, &["report_outcome"]);
+    }
+
+    #[test]
+    fn get_environment_capabilities_requires_its_feature() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|t| t.spec.name() == "get_environment_capabilities"),
+            "get_environment_capabilities should be disabled by default"
+        );
+
+        features.enable(Feature::CapabilityDescriptor);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert_contains_tool_names(&tools, &["get_environment_capabilities"]);
+    }
+
+    #[test]
+    fn read_exec_output_requires_its_feature() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "read_exec_output"),
+            "read_exec_output should be disabled by default"
+        );
+
+        features.enable(Feature::ReadExecOutput);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert_contains_tool_names(&tools, &["read_exec_output"]);
+    }
+
+    #[test]
+    fn search_workspace_requires_its_feature_and_runs_in_parallel() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "search_workspace"),
+            "search_workspace should be disabled by default"
+        );
+
+        features.enable(Feature::SearchWorkspace);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(find_tool(&tools, "search_workspace").supports_parallel_tool_calls);
+    }
+
+    #[test]
+    fn git_tool_offers_remote_operations_only_with_their_feature() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools.iter().any(|t| t.spec.name() == "git"),
+            "git should be disabled by default"
+        );
+
+        let has_remote = |features: &Features| {
+            let tools_config = ToolsConfig::new(&ToolsConfigParams {
+                model_info: &model_info,
+                features,
+                web_search_mode: Some(WebSearchMode::Cached),
+            });
+            let (tools, _) = build_specs(&tools_config, None, &[]).build();
+            let ToolSpec::Function(ResponsesApiTool {
+                parameters: JsonSchema::Object { properties, .. },
+                ..
+            }) = &find_tool(&tools, "git").spec
+            else {
+                panic!("git should be a function tool");
+            };
+            properties.contains_key("remote")
+        };
+        features.enable(Feature::GitTool);
+        assert!(!has_remote(&features));
+        features.enable(Feature::GitToolRemote);
+        assert!(has_remote(&features));
+    }
+
     fn assert_model_tools(
         model_slug: &str,
         features: &Features,
@@ -2084,8 +2930,8 @@ mod tests {
                                     ),
                                 ]),
                                 required: Some(vec![
-                                    "string_property".to_string(),
                                     "number_property".to_string(),
+                                    "string_property".to_string(),
                                 ]),
                                 additional_properties: Some(false.into()),
                             },
@@ -2403,7 +3249,7 @@ mod tests {
 
     #[test]
     fn test_shell_tool() {
-        let tool = super::create_shell_tool();
+        let tool = super::create_shell_tool(false);
         let ToolSpec::Function(ResponsesApiTool {
             description, name, ..
         }) = &tool
@@ -2433,7 +3279,7 @@ Examples of valid command strings:
 
     #[test]
     fn test_shell_command_tool() {
-        let tool = super::create_shell_command_tool();
+        let tool = super::create_shell_command_tool(false);
         let ToolSpec::Function(ResponsesApiTool {
             description, name, ..
         }) = &tool
@@ -2549,8 +3395,8 @@ Examples of valid command strings:
                                     ),
                                 ]),
                                 required: Some(vec![
-                                    "string_property".to_string(),
                                     "number_property".to_string(),
+                                    "string_property".to_string(),
                                 ]),
                                 additional_properties: Some(
                                     JsonSchema::Object {
@@ -0,0 +1,168 @@
+//! Saves command output larger than `output_limits.spill_threshold` to disk so
+//! the model can be pointed at the full text while only a truncated copy is
+//! sent back to it.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use crate::codex::TurnContext;
+use crate::exec::ExecToolCallOutput;
+
+/// Directory that holds spilled output, in the session temp dir or, when the
+/// session has none, under the state dir with one subdirectory per thread.
+const SPILL_DIR: &str = "tool_output";
+
+/// Directory the spilled output of a turn is saved in.
+pub(crate) fn spill_dir(turn: &TurnContext, thread_id: &str) -> PathBuf {
+    match &turn.tmpdir {
+        Some(tmpdir) => tmpdir.join(SPILL_DIR),
+        None => turn
+            .client
+            .config()
+            .state_dir
+            .join(SPILL_DIR)
+            .join(thread_id),
+    }
+}
+
+/// Where the full output of `call_id` is saved.
+pub(crate) fn spill_path(spill_dir: &Path, call_id: &str) -> PathBuf {
+    spill_dir.join(format!("{call_id}.txt"))
+}
+
+/// Saves the aggregated output to [`spill_path`] when the command produced
+/// more than `threshold` bytes and returns an output whose aggregated text
+/// ends with a note naming the file. The capture may already have streamed
+/// the full output there; otherwise the retained text is written. Returns
+/// `None` when nothing was spilled, including when the file could not be
+/// written.
+pub(crate) async fn spill_exec_output(
+    output: &ExecToolCallOutput,
+    threshold: Option<usize>,
+    spill_dir: &Path,
+    call_id: &str,
+) -> Option<ExecToolCallOutput> {
+    let text = &output.aggregated_output.text;
+    let path = spill_path(spill_dir, call_id);
+    let total_bytes = text
+        .len()
+        .saturating_add(output.aggregated_output.omitted_bytes);
+    if threshold.is_none_or(|threshold| total_bytes <= threshold) {
+        if threshold.is_some() {
+            // A streamed copy is not needed for output under the threshold.
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+        return None;
+    }
+
+    let saved_bytes = match tokio::fs::metadata(&path).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => {
+            let written = async {
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(&path, text).await
+            }
+            .await;
+            if let Err(err) = written {
+                warn!(
+                    "failed to save output of {call_id} to {}: {err}",
+                    path.display()
+                );
+                return None;
+            }
+            text.len() as u64
+        }
+    };
+
+    let mut spilled = output.clone();
+    spilled.aggregated_output.text = format!(
+        "{text}\n[full output ({saved_bytes} bytes) saved to {}]",
+        path.display()
+    );
+    Some(spilled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::StreamOutput;
+    use pretty_assertions::assert_eq;
+
+    fn output(text: &str) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            aggregated_output: StreamOutput::new(text.to_string()),
+            ..ExecToolCallOutput::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn spills_only_output_over_the_threshold() {
+        let spill_dir = tempfile::tempdir().expect("tempdir");
+        let output = output("0123456789");
+
+        assert!(
+            spill_exec_output(&output, None, spill_dir.path(), "call-1")
+                .await
+                .is_none()
+        );
+        assert!(
+            spill_exec_output(&output, Some(10), spill_dir.path(), "call-1")
+                .await
+                .is_none()
+        );
+
+        let spilled = spill_exec_output(&output, Some(4), spill_dir.path(), "call-1")
+            .await
+            .expect("output over the threshold is spilled");
+        let path = spill_path(spill_dir.path(), "call-1");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("spill file"),
+            "0123456789"
+        );
+        assert_eq!(
+            spilled.aggregated_output.text,
+            format!(
+                "0123456789\n[full output (10 bytes) saved to {}]",
+                path.display()
+            )
+        );
+        assert_eq!(spilled.stdout.text, output.stdout.text);
+    }
+
+    #[tokio::test]
+    async fn keeps_streamed_output_and_counts_omitted_bytes() {
+        let spill_dir = tempfile::tempdir().expect("tempdir");
+        let path = spill_path(spill_dir.path(), "call-1");
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        std::fs::write(&path, "0123456789abcdef").expect("write streamed output");
+        let mut output = output("01[...]ef");
+        output.aggregated_output.omitted_bytes = 12;
+
+        let spilled = spill_exec_output(&output, Some(10), spill_dir.path(), "call-1")
+            .await
+            .expect("output over the threshold is spilled");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("spill file"),
+            "0123456789abcdef"
+        );
+        assert_eq!(
+            spilled.aggregated_output.text,
+            format!(
+                "01[...]ef\n[full output (16 bytes) saved to {}]",
+                path.display()
+            )
+        );
+
+        output.aggregated_output.omitted_bytes = 0;
+        assert!(
+            spill_exec_output(&output, Some(10), spill_dir.path(), "call-1")
+                .await
+                .is_none()
+        );
+        assert!(!path.exists());
+    }
+}
@@ -3,6 +3,10 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -10,11 +14,18 @@ use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
 
+use crate::path_utils::event_path_key;
+use crate::path_utils::normalize_event_path;
 use crate::protocol::FileChange;
+use crate::protocol::WorkspaceRoot;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
 
+/// Files modified this recently may change again without their mtime moving
+/// (coarse timestamp granularity), so their diffs are never reused.
+const RACY_MTIME_WINDOW: Duration = Duration::from_secs(2);
+
 struct BaselineFileInfo {
     path: PathBuf,
     content: Vec<u8>,
@@ -29,17 +40,77 @@ struct BaselineFileInfo {
 /// 2. Keep a stable internal filename (uuid) per external path for rename tracking.
 /// 3. To compute the aggregated unified diff, compare each baseline snapshot to the current file on disk entirely in-memory
 ///    using the `similar` crate and emit unified diffs with rewritten external paths.
+///
+/// The diff itself is computed from a [`TurnDiffSnapshot`], so callers that
+/// share the tracker behind a lock only hold it while the snapshot is taken.
+/// Handing the result back through [`TurnDiffTracker::apply_diff`] keeps the
+/// git roots the diff discovered and drops diffs of superseded snapshots.
+///
+/// Each file's diff text is cached together with the (mtime, size, mode) of
+/// the file it was computed from. A later diff only reads and re-diffs files
+/// that a patch touched since then or whose stat changed, e.g. because they
+/// were edited outside of codex.
 #[derive(Default)]
 pub struct TurnDiffTracker {
-    /// Map external path -> internal filename (uuid).
+    /// Map external path -> internal filename (uuid). Keyed by
+    /// [`event_path_key`], so differently spelled paths to one file share an
+    /// entry, including NFC and NFD spellings on macOS. The other maps keep
+    /// paths as given, since those are what the filesystem is asked for.
     external_to_temp_name: HashMap<PathBuf, String>,
-    /// Internal filename -> baseline file info.
-    baseline_file_info: HashMap<String, BaselineFileInfo>,
+    /// Internal filename -> baseline file info. Shared with snapshots so
+    /// taking one does not copy file contents.
+    baseline_file_info: HashMap<String, Arc<BaselineFileInfo>>,
     /// Internal filename -> external path as of current accumulated state (after applying all changes).
     /// This is where renames are tracked.
     temp_name_to_current_path: HashMap<String, PathBuf>,
-    /// Cache of known git worktree roots to avoid repeated filesystem walks.
-    git_root_cache: Vec<PathBuf>,
+    /// Internal filename -> number of `on_patch_begin` calls that touched it.
+    /// Bumping the count marks the file dirty for cached diffs.
+    patch_counts: HashMap<String, u64>,
+    diff_cache: SharedDiffCache,
+    git_roots: GitRootCache,
+    /// Number of snapshots taken; each snapshot is numbered with it.
+    snapshots_taken: u64,
+    /// Number of the newest snapshot whose diff was applied.
+    latest_applied: u64,
+}
+
+type SharedDiffCache = Arc<Mutex<HashMap<String, CachedFileDiff>>>;
+
+/// Diff text for one tracked file and what it was computed from.
+struct CachedFileDiff {
+    patch_count: u64,
+    current_path: PathBuf,
+    stamp: FileStamp,
+    diff: String,
+}
+
+/// The stat fields used to decide whether a file changed since it was
+/// diffed. `None` stands for a missing file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileStamp(Option<(SystemTime, u64, FileMode)>);
+
+impl FileStamp {
+    /// Returns `None` when the file's mtime is unavailable, which disables
+    /// caching for it.
+    fn of(path: &Path) -> Option<Self> {
+        let Ok(meta) = fs::symlink_metadata(path) else {
+            return Some(Self(None));
+        };
+        let modified = meta.modified().ok()?;
+        let mode = file_mode_for_path(path).unwrap_or(FileMode::Regular);
+        Some(Self(Some((modified, meta.len(), mode))))
+    }
+
+    /// Whether a diff computed at `now` can be trusted to stay valid for as
+    /// long as the stamp does not change.
+    fn is_stable_at(&self, now: SystemTime) -> bool {
+        match self.0 {
+            None => true,
+            Some((modified, ..)) => modified
+                .checked_add(RACY_MTIME_WINDOW)
+                .is_some_and(|stable_after| stable_after <= now),
+        }
+    }
 }
 
 impl TurnDiffTracker {
@@ -47,17 +118,35 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    /// A tracker that shows files under a workspace root as
+    /// `name/relative/path` rather than relative to their git root, so files
+    /// from different repositories can be told apart.
+    pub fn with_workspace_roots(workspace_roots: Vec<WorkspaceRoot>) -> Self {
+        Self {
+            git_roots: GitRootCache {
+                roots: Vec::new(),
+                workspace_roots,
+            },
+            ..Self::default()
+        }
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
     /// - Also updates internal mappings for move/rename events.
     pub fn on_patch_begin(&mut self, changes: &HashMap<PathBuf, FileChange>) {
         for (path, change) in changes.iter() {
+            let key = event_path_key(path);
+            if let Some(internal) = self.external_to_temp_name.get(&key) {
+                *self.patch_counts.entry(internal.clone()).or_default() += 1;
+            }
+
             // Ensure a stable internal filename exists for this external path.
-            if !self.external_to_temp_name.contains_key(path) {
+            if !self.external_to_temp_name.contains_key(&key) {
                 let internal = Uuid::new_v4().to_string();
                 self.external_to_temp_name
-                    .insert(path.clone(), internal.clone());
+                    .insert(key.clone(), internal.clone());
                 self.temp_name_to_current_path
                     .insert(internal.clone(), path.clone());
 
@@ -69,7 +158,8 @@ impl TurnDiffTracker {
                     let oid = if mode == Some(FileMode::Symlink) {
                         format!("{:x}", git_blob_sha1_hex_bytes(&content))
                     } else {
-                        self.git_blob_oid_for_path(path)
+                        self.git_roots
+                            .blob_oid_for_path(path)
                             .unwrap_or_else(|| format!("{:x}", git_blob_sha1_hex_bytes(&content)))
                     };
                     Some(BaselineFileInfo {
@@ -89,7 +179,7 @@ impl TurnDiffTracker {
 
                 if let Some(baseline_file_info) = baseline_file_info {
                     self.baseline_file_info
-                        .insert(internal.clone(), baseline_file_info);
+                        .insert(internal.clone(), Arc::new(baseline_file_info));
                 }
             }
 
@@ -99,19 +189,19 @@ impl TurnDiffTracker {
                 ..
             } = change
             {
-                let uuid_filename = match self.external_to_temp_name.get(path) {
+                let uuid_filename = match self.external_to_temp_name.get(&key) {
                     Some(i) => i.clone(),
                     None => {
                         // This should be rare, but if we haven't mapped the source, create it with no baseline.
                         let i = Uuid::new_v4().to_string();
                         self.baseline_file_info.insert(
                             i.clone(),
-                            BaselineFileInfo {
+                            Arc::new(BaselineFileInfo {
                                 path: path.clone(),
                                 content: vec![],
                                 mode: FileMode::Regular,
                                 oid: ZERO_OID.to_string(),
-                            },
+                            }),
                         );
                         i
                     }
@@ -120,9 +210,9 @@ impl TurnDiffTracker {
                 self.temp_name_to_current_path
                     .insert(uuid_filename.clone(), dest.clone());
                 // Update forward file_mapping: external current -> internal name.
-                self.external_to_temp_name.remove(path);
+                self.external_to_temp_name.remove(&key);
                 self.external_to_temp_name
-                    .insert(dest.clone(), uuid_filename);
+                    .insert(event_path_key(dest), uuid_filename);
             };
         }
     }
@@ -138,158 +228,191 @@ impl TurnDiffTracker {
             })
     }
 
-    /// Find the git worktree root for a file/directory by walking up to the first ancestor containing a `.git` entry.
-    /// Uses a simple cache of known roots and avoids negative-result caching for simplicity.
-    fn find_git_root_cached(&mut self, start: &Path) -> Option<PathBuf> {
-        let dir = if start.is_dir() {
-            start
-        } else {
-            start.parent()?
-        };
-
-        // Fast path: if any cached root is an ancestor of this path, use it.
-        if let Some(root) = self
-            .git_root_cache
+    /// Capture the baselines and current paths of all tracked files. This is
+    /// cheap; reading files and diffing happen in [`TurnDiffSnapshot::diff`].
+    pub fn snapshot(&mut self) -> TurnDiffSnapshot {
+        self.snapshots_taken += 1;
+        let files = self
+            .baseline_file_info
             .iter()
-            .find(|r| dir.starts_with(r))
-            .cloned()
-        {
-            return Some(root);
+            .filter_map(|(internal, info)| {
+                let current_path = self.get_path_for_internal(internal)?;
+                Some(SnapshotFile {
+                    internal: internal.clone(),
+                    baseline: Arc::clone(info),
+                    current_path,
+                    patch_count: self.patch_counts.get(internal).copied().unwrap_or_default(),
+                })
+            })
+            .collect();
+        TurnDiffSnapshot {
+            seq: self.snapshots_taken,
+            files,
+            diff_cache: Arc::clone(&self.diff_cache),
+            git_roots: self.git_roots.clone(),
         }
+    }
 
-        // Walk up to find a `.git` marker.
-        let mut cur = dir.to_path_buf();
-        loop {
-            let git_marker = cur.join(".git");
-            if git_marker.is_dir() || git_marker.is_file() {
-                if !self.git_root_cache.iter().any(|r| r == &cur) {
-                    self.git_root_cache.push(cur.clone());
-                }
-                return Some(cur);
-            }
-
-            // On Windows, avoid walking above the drive or UNC share root.
-            #[cfg(windows)]
-            {
-                if is_windows_drive_or_unc_root(&cur) {
-                    return None;
-                }
-            }
+    /// Recompute the aggregated unified diff by comparing all of the in-memory snapshots that were
+    /// collected before the first time they were touched by apply_patch during this turn with
+    /// the current repo state.
+    pub fn get_unified_diff(&mut self) -> Result<Option<String>> {
+        let diff = self.snapshot().diff();
+        Ok(self.apply_diff(diff))
+    }
 
-            if let Some(parent) = cur.parent() {
-                cur = parent.to_path_buf();
-            } else {
-                return None;
-            }
+    /// Take back a diff computed from one of this tracker's snapshots.
+    /// Remembers the git roots it discovered and returns its text, or `None`
+    /// when it is empty or a diff of a later snapshot was already applied.
+    pub fn apply_diff(&mut self, diff: SnapshotDiff) -> Option<String> {
+        self.git_roots.merge(diff.git_roots);
+        if diff.seq <= self.latest_applied {
+            return None;
         }
+        self.latest_applied = diff.seq;
+        diff.unified_diff
     }
+}
 
-    /// Return a display string for `path` relative to its git root if found, else absolute.
-    fn relative_to_git_root_str(&mut self, path: &Path) -> String {
-        let s = if let Some(root) = self.find_git_root_cached(path) {
-            if let Ok(rel) = path.strip_prefix(&root) {
-                rel.display().to_string()
-            } else {
-                path.display().to_string()
-            }
-        } else {
-            path.display().to_string()
-        };
-        s.replace('\\', "/")
+/// Baselines and current paths of the files a [`TurnDiffTracker`] knew about
+/// when [`TurnDiffTracker::snapshot`] was called.
+pub struct TurnDiffSnapshot {
+    seq: u64,
+    files: Vec<SnapshotFile>,
+    diff_cache: SharedDiffCache,
+    git_roots: GitRootCache,
+}
+
+/// The diff of a [`TurnDiffSnapshot`], to be handed back to
+/// [`TurnDiffTracker::apply_diff`].
+pub struct SnapshotDiff {
+    seq: u64,
+    git_roots: Vec<PathBuf>,
+    unified_diff: Option<String>,
+}
+
+/// Baseline of a tracked file with its current external path.
+struct SnapshotFile {
+    internal: String,
+    baseline: Arc<BaselineFileInfo>,
+    current_path: PathBuf,
+    patch_count: u64,
+}
+
+impl TurnDiffSnapshot {
+    /// Compute the aggregated unified diff between the captured baselines and
+    /// the files currently on disk.
+    pub fn unified_diff(mut self) -> Result<Option<String>> {
+        Ok(self.aggregated_diff())
     }
 
-    /// Ask git to compute the blob SHA-1 for the file at `path` within its repository.
-    /// Returns None if no repository is found or git invocation fails.
-    fn git_blob_oid_for_path(&mut self, path: &Path) -> Option<String> {
-        let root = self.find_git_root_cached(path)?;
-        // Compute a path relative to the repo root for better portability across platforms.
-        let rel = path.strip_prefix(&root).unwrap_or(path);
-        let output = Command::new("git")
-            .arg("-C")
-            .arg(&root)
-            .arg("hash-object")
-            .arg("--")
-            .arg(rel)
-            .output()
-            .ok()?;
-        if !output.status.success() {
-            return None;
+    /// Like [`Self::unified_diff`], but keeps what the tracker needs to learn
+    /// from the diff.
+    pub fn diff(mut self) -> SnapshotDiff {
+        let unified_diff = self.aggregated_diff();
+        SnapshotDiff {
+            seq: self.seq,
+            git_roots: self.git_roots.roots,
+            unified_diff,
         }
-        let s = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if s.len() == 40 { Some(s) } else { None }
     }
 
-    /// Recompute the aggregated unified diff by comparing all of the in-memory snapshots that were
-    /// collected before the first time they were touched by apply_patch during this turn with
-    /// the current repo state.
-    pub fn get_unified_diff(&mut self) -> Result<Option<String>> {
+    fn aggregated_diff(&mut self) -> Option<String> {
         let mut aggregated = String::new();
 
-        // Compute diffs per tracked internal file in a stable order by external path.
-        let mut baseline_file_names: Vec<String> =
-            self.baseline_file_info.keys().cloned().collect();
+        // Compute diffs per tracked file in a stable order by external path.
+        let mut files = std::mem::take(&mut self.files);
         // Sort lexicographically by full repo-relative path to match git behavior.
-        baseline_file_names.sort_by_key(|internal| {
-            self.get_path_for_internal(internal)
-                .map(|p| self.relative_to_git_root_str(&p))
-                .unwrap_or_default()
-        });
+        files
+            .sort_by_cached_key(|file| self.git_roots.relative_to_git_root_str(&file.current_path));
 
-        for internal in baseline_file_names {
-            aggregated.push_str(self.get_file_diff(&internal).as_str());
+        for file in files {
+            aggregated.push_str(self.cached_file_diff(&file).as_str());
             if !aggregated.ends_with('\n') {
                 aggregated.push('\n');
             }
         }
 
         if aggregated.trim().is_empty() {
-            Ok(None)
+            None
         } else {
-            Ok(Some(aggregated))
+            Some(aggregated)
         }
     }
 
-    fn get_file_diff(&mut self, internal_file_name: &str) -> String {
-        let mut aggregated = String::new();
+    /// Returns the cached diff for `file` when neither a patch nor anything
+    /// else touched it since it was computed; otherwise re-diffs it.
+    fn cached_file_diff(&mut self, file: &SnapshotFile) -> String {
+        let stamp = FileStamp::of(&file.current_path);
+        if let Some(stamp) = stamp
+            && let Ok(cache) = self.diff_cache.lock()
+            && let Some(cached) = cache.get(&file.internal)
+            && cached.patch_count == file.patch_count
+            && cached.current_path == file.current_path
+            && cached.stamp == stamp
+        {
+            return cached.diff.clone();
+        }
 
-        // Snapshot lightweight fields only.
-        let (baseline_external_path, baseline_mode, left_oid) = {
-            if let Some(info) = self.baseline_file_info.get(internal_file_name) {
-                (info.path.clone(), info.mode, info.oid.clone())
-            } else {
-                (PathBuf::new(), FileMode::Regular, ZERO_OID.to_string())
+        let diff = self.get_file_diff(&file.baseline, &file.current_path);
+        if let Some(stamp) = stamp
+            && stamp.is_stable_at(SystemTime::now())
+            && let Ok(mut cache) = self.diff_cache.lock()
+        {
+            let newer_cached = cache
+                .get(&file.internal)
+                .is_some_and(|cached| cached.patch_count > file.patch_count);
+            if !newer_cached {
+                cache.insert(
+                    file.internal.clone(),
+                    CachedFileDiff {
+                        patch_count: file.patch_count,
+                        current_path: file.current_path.clone(),
+                        stamp,
+                        diff: diff.clone(),
+                    },
+                );
             }
-        };
-        let current_external_path = match self.get_path_for_internal(internal_file_name) {
-            Some(p) => p,
-            None => return aggregated,
-        };
+        }
+        diff
+    }
+
+    fn get_file_diff(
+        &mut self,
+        baseline: &BaselineFileInfo,
+        current_external_path: &Path,
+    ) -> String {
+        let mut aggregated = String::new();
 
-        let current_mode = file_mode_for_path(&current_external_path).unwrap_or(FileMode::Regular);
-        let right_bytes = blob_bytes(&current_external_path, current_mode);
+        let baseline_external_path = &baseline.path;
+        let baseline_mode = baseline.mode;
+        let left_oid = baseline.oid.as_str();
 
-        // Compute displays with &mut self before borrowing any baseline content.
-        let left_display = self.relative_to_git_root_str(&baseline_external_path);
-        let right_display = self.relative_to_git_root_str(&current_external_path);
+        let current_mode = file_mode_for_path(current_external_path).unwrap_or(FileMode::Regular);
+        let right_bytes = blob_bytes(current_external_path, current_mode);
+
+        let left_display = self
+            .git_roots
+            .relative_to_git_root_str(baseline_external_path);
+        let right_display = self
+            .git_roots
+            .relative_to_git_root_str(current_external_path);
 
-        // Compute right oid before borrowing baseline content.
         let right_oid = if let Some(b) = right_bytes.as_ref() {
             if current_mode == FileMode::Symlink {
                 format!("{:x}", git_blob_sha1_hex_bytes(b))
             } else {
-                self.git_blob_oid_for_path(&current_external_path)
+                self.git_roots
+                    .blob_oid_for_path(current_external_path)
                     .unwrap_or_else(|| format!("{:x}", git_blob_sha1_hex_bytes(b)))
             }
         } else {
             ZERO_OID.to_string()
         };
 
-        // Borrow baseline content only after all &mut self uses are done.
-        let left_present = left_oid.as_str() != ZERO_OID;
+        let left_present = left_oid != ZERO_OID;
         let left_bytes: Option<&[u8]> = if left_present {
-            self.baseline_file_info
-                .get(internal_file_name)
-                .map(|i| i.content.as_slice())
+            Some(baseline.content.as_slice())
         } else {
             None
         };
@@ -368,6 +491,104 @@ impl TurnDiffTracker {
     }
 }
 
+/// Cache of known git worktree roots to avoid repeated filesystem walks.
+#[derive(Clone, Default)]
+struct GitRootCache {
+    roots: Vec<PathBuf>,
+    workspace_roots: Vec<WorkspaceRoot>,
+}
+
+impl GitRootCache {
+    fn merge(&mut self, roots: Vec<PathBuf>) {
+        for root in roots {
+            if !self.roots.contains(&root) {
+                self.roots.push(root);
+            }
+        }
+    }
+
+    /// Find the git worktree root for a file/directory by walking up to the first ancestor containing a `.git` entry.
+    /// Uses a simple cache of known roots and avoids negative-result caching for simplicity.
+    fn find_git_root_cached(&mut self, start: &Path) -> Option<PathBuf> {
+        let dir = if start.is_dir() {
+            start
+        } else {
+            start.parent()?
+        };
+
+        // Fast path: if any cached root is an ancestor of this path, use it.
+        if let Some(root) = self.roots.iter().find(|r| dir.starts_with(r)).cloned() {
+            return Some(root);
+        }
+
+        // Walk up to find a `.git` marker.
+        let mut cur = dir.to_path_buf();
+        loop {
+            let git_marker = cur.join(".git");
+            if git_marker.is_dir() || git_marker.is_file() {
+                if !self.roots.iter().any(|r| r == &cur) {
+                    self.roots.push(cur.clone());
+                }
+                return Some(cur);
+            }
+
+            // On Windows, avoid walking above the drive or UNC share root.
+            #[cfg(windows)]
+            {
+                if is_windows_drive_or_unc_root(&cur) {
+                    return None;
+                }
+            }
+
+            if let Some(parent) = cur.parent() {
+                cur = parent.to_path_buf();
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// Return a display string for `path`: `name/relative/path` under a
+    /// workspace root, else relative to its git root if found, else absolute.
+    fn relative_to_git_root_str(&mut self, path: &Path) -> String {
+        let path = normalize_event_path(path);
+        let path = path.as_path();
+        let s = if let Some(shown) = WorkspaceRoot::display_path(&self.workspace_roots, path) {
+            shown
+        } else if let Some(root) = self.find_git_root_cached(path) {
+            if let Ok(rel) = path.strip_prefix(&root) {
+                rel.display().to_string()
+            } else {
+                path.display().to_string()
+            }
+        } else {
+            path.display().to_string()
+        };
+        s.replace('\\', "/")
+    }
+
+    /// Ask git to compute the blob SHA-1 for the file at `path` within its repository.
+    /// Returns None if no repository is found or git invocation fails.
+    fn blob_oid_for_path(&mut self, path: &Path) -> Option<String> {
+        let root = self.find_git_root_cached(path)?;
+        // Compute a path relative to the repo root for better portability across platforms.
+        let rel = path.strip_prefix(&root).unwrap_or(path);
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .arg("hash-object")
+            .arg("--")
+            .arg(rel)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let s = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if s.len() == 40 { Some(s) } else { None }
+    }
+}
+
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
 fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
@@ -467,6 +688,7 @@ fn is_windows_drive_or_unc_root(p: &std::path::Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use codex_utils_absolute_path::AbsolutePathBuf;
     use pretty_assertions::assert_eq;
     use tempfile::tempdir;
 
@@ -571,6 +793,29 @@ index {ZERO_OID}..{right_oid}
         assert_eq!(combined, expected_combined);
     }
 
+    #[test]
+    fn files_under_a_workspace_root_are_shown_with_its_name() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        let mut acc = TurnDiffTracker::with_workspace_roots(vec![WorkspaceRoot {
+            name: "web".to_string(),
+            path: AbsolutePathBuf::from_absolute_path(dir.path()).unwrap(),
+        }]);
+        acc.on_patch_begin(&HashMap::from([(
+            file.clone(),
+            FileChange::Add {
+                content: "foo\n".to_string(),
+            },
+        )]));
+        fs::write(&file, "foo\n").unwrap();
+
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        assert!(
+            diff.starts_with("diff --git a/web/a.txt b/web/a.txt\n"),
+            "{diff}"
+        );
+    }
+
     #[test]
     fn accumulates_delete() {
         let dir = tempdir().unwrap();
@@ -893,4 +1138,218 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn patch_begins_progress_while_a_spawned_task_diffs() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+        let add = |file: &Path| {
+            HashMap::from([(
+                file.to_path_buf(),
+                FileChange::Add {
+                    content: String::new(),
+                },
+            )])
+        };
+        for i in 0..20 {
+            let file = dir.path().join(format!("file-{i:02}.txt"));
+            tracker.lock().await.on_patch_begin(&add(&file));
+            fs::write(&file, format!("{i}\n")).unwrap();
+        }
+
+        let (taken_tx, taken_rx) = tokio::sync::oneshot::channel();
+        let (diff_tx, diff_rx) = tokio::sync::oneshot::channel();
+        let differ = tokio::spawn({
+            let tracker = Arc::clone(&tracker);
+            async move {
+                let snapshot = tracker.lock().await.snapshot();
+                taken_tx.send(()).unwrap();
+                diff_rx.await.unwrap();
+                let diff = snapshot.diff();
+                tracker.lock().await.apply_diff(diff)
+            }
+        });
+        taken_rx.await.unwrap();
+
+        // The differ holds a snapshot it has yet to diff; patch begins still
+        // get the lock, and a diff of a later snapshot can go out first.
+        let late = dir.path().join("late.txt");
+        tracker
+            .try_lock()
+            .expect("tracker is not locked while diffing")
+            .on_patch_begin(&add(&late));
+        fs::write(&late, "late\n").unwrap();
+        assert!(!differ.is_finished());
+        let newer = tracker.lock().await.snapshot();
+        let newer = tracker.lock().await.apply_diff(newer.diff()).unwrap();
+        assert_eq!(newer.matches("diff --git ").count(), 21);
+        assert!(newer.contains("late.txt"));
+
+        // The superseded diff is dropped, so the last one sent is final.
+        diff_tx.send(()).unwrap();
+        assert_eq!(differ.await.unwrap(), None);
+
+        // Git roots a snapshot's diff discovered are kept by the tracker.
+        assert_eq!(tracker.lock().await.git_roots.roots.len(), 1);
+    }
+
+    /// Writes `content` to `path` with a fixed mtime well outside the racy
+    /// window so its diff can be cached.
+    fn write_settled(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+            .unwrap();
+    }
+
+    fn update(path: &Path) -> HashMap<PathBuf, FileChange> {
+        HashMap::from([(
+            path.to_path_buf(),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: None,
+            },
+        )])
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn differently_spelled_paths_to_one_file_share_a_baseline() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "one\n").unwrap();
+        let mut tracker = TurnDiffTracker::new();
+        tracker.on_patch_begin(&update(&file));
+        fs::write(&file, "two\n").unwrap();
+
+        let respelled = dir
+            .path()
+            .join("A.TXT")
+            .to_string_lossy()
+            .replace('\\', "/");
+        let verbatim = PathBuf::from(format!(r"\\?\{}", file.display()));
+        tracker.on_patch_begin(&update(Path::new(&respelled)));
+        tracker.on_patch_begin(&update(&verbatim));
+
+        assert_eq!(tracker.baseline_file_info.len(), 1);
+        let diff = tracker.get_unified_diff().unwrap().unwrap();
+        assert_eq!(diff.matches("diff --git").count(), 1);
+        assert!(diff.contains("-one") && diff.contains("+two"));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn differently_normalized_paths_to_one_file_share_a_baseline() {
+        let dir = tempdir().unwrap();
+        let nfc = dir.path().join("caf\u{e9}.txt");
+        let nfd = dir.path().join("cafe\u{301}.txt");
+        let mut tracker = TurnDiffTracker::new();
+        tracker.on_patch_begin(&HashMap::from([(
+            nfc.clone(),
+            FileChange::Add {
+                content: "one\n".to_string(),
+            },
+        )]));
+        fs::write(&nfc, "one\n").unwrap();
+
+        // The filesystem resolves either spelling to the same file.
+        tracker.on_patch_begin(&update(&nfd));
+        fs::write(&nfd, "two\n").unwrap();
+
+        assert_eq!(tracker.baseline_file_info.len(), 1);
+        let diff = tracker.get_unified_diff().unwrap().unwrap();
+        assert_eq!(diff.matches("diff --git").count(), 1);
+        assert!(diff.contains("+two") && !diff.contains("+one"));
+    }
+
+    #[test]
+    fn clean_files_reuse_cached_diffs_until_their_stat_changes() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        write_settled(&file, "one\n");
+        let mut tracker = TurnDiffTracker::new();
+        tracker.on_patch_begin(&update(&file));
+        write_settled(&file, "two\n");
+        let first = tracker.get_unified_diff().unwrap().unwrap();
+        assert!(first.contains("+two"));
+
+        // Same size and mtime: the cached diff is reused without reading.
+        write_settled(&file, "TWO\n");
+        assert_eq!(tracker.get_unified_diff().unwrap().unwrap(), first);
+
+        // Edited outside of codex: the new mtime forces a re-diff.
+        fs::write(&file, "three\n").unwrap();
+        let edited = tracker.get_unified_diff().unwrap().unwrap();
+        assert!(edited.contains("+three"));
+    }
+
+    #[test]
+    fn patched_files_are_rediffed_even_with_unchanged_stat() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        write_settled(&file, "one\n");
+        let mut tracker = TurnDiffTracker::new();
+        tracker.on_patch_begin(&update(&file));
+        write_settled(&file, "two\n");
+        assert!(
+            tracker
+                .get_unified_diff()
+                .unwrap()
+                .unwrap()
+                .contains("+two")
+        );
+
+        tracker.on_patch_begin(&update(&file));
+        write_settled(&file, "TWO\n");
+        assert!(
+            tracker
+                .get_unified_diff()
+                .unwrap()
+                .unwrap()
+                .contains("+TWO")
+        );
+    }
+
+    /// 2,000 tracked files with one modified between diffs: only that file
+    /// is read again. `benches/tool_pipeline.rs` times the same fixture.
+    #[test]
+    fn diff_of_2000_tracked_files_rereads_only_the_modified_one() {
+        let dir = tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..2_000)
+            .map(|i| dir.path().join(format!("file-{i:04}.txt")))
+            .collect();
+        for (i, file) in files.iter().enumerate() {
+            write_settled(file, &format!("line {i}\n").repeat(50));
+        }
+        let mut tracker = TurnDiffTracker::new();
+        let changes: HashMap<PathBuf, FileChange> =
+            files.iter().flat_map(|file| update(file)).collect();
+        tracker.on_patch_begin(&changes);
+        for (i, file) in files.iter().enumerate() {
+            write_settled(file, &format!("line {i}\n").repeat(51));
+        }
+
+        let cold = tracker.get_unified_diff().unwrap().unwrap();
+
+        let modified = &files[1_234];
+        tracker.on_patch_begin(&update(modified));
+        write_settled(modified, "rewritten\n");
+        // A clean file whose contents change behind an unchanged stat keeps
+        // its cached diff, showing it was not read again.
+        write_settled(&files[7], &"LINE 7\n".repeat(51));
+
+        let warm = tracker.get_unified_diff().unwrap().unwrap();
+
+        assert_eq!(warm.matches("diff --git ").count(), 2_000);
+        assert!(warm.contains("+rewritten"));
+        assert!(!warm.contains("LINE 7"));
+        let others = |diff: &str| -> Vec<String> {
+            diff.split("diff --git ")
+                .filter(|block| !block.contains("file-1234.txt"))
+                .map(str::to_string)
+                .collect()
+        };
+        assert_eq!(others(&warm), others(&cold));
+    }
 }
@@ -0,0 +1,143 @@
+//! Periodic `TurnProgress` events while a turn runs tools.
+//!
+//! A reporter task samples the turn's [`ToolActivity`] once per configured
+//! interval. It stays quiet until tools have been running for a full
+//! interval, so a turn whose tool calls all finish quickly sends nothing.
+//! The token estimate covers the exec output finished since the last model
+//! request, which is what the next request will add to the context. Each
+//! event also says what is left of the turn's tool budgets.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::time::MissedTickBehavior;
+use tokio_util::task::AbortOnDropHandle;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::protocol::EventMsg;
+use crate::protocol::ToolBudgetRemaining;
+use crate::protocol::TurnProgressEvent;
+use crate::state::ToolActivity;
+use crate::truncate::approx_tokens_from_byte_count;
+
+/// Starts reporting progress for the turn; the reporter stops when the
+/// returned handle is dropped. `None` when progress events are disabled.
+pub(crate) fn spawn_reporter(
+    sess: &Arc<Session>,
+    turn_context: &Arc<TurnContext>,
+) -> Option<AbortOnDropHandle<()>> {
+    let interval = turn_context.turn_progress.interval?;
+    let sess = Arc::clone(sess);
+    let turn_context = Arc::clone(turn_context);
+    let turn_started = Instant::now();
+    Some(AbortOnDropHandle::new(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Some(activity) = sess.tool_activity().await else {
+                continue;
+            };
+            let tool_budget = sess.tool_budget_remaining(&turn_context.sub_id).await;
+            let now = Instant::now();
+            if let Some(event) = progress_event(
+                &turn_context.sub_id,
+                activity,
+                tool_budget,
+                now.duration_since(turn_started),
+                now,
+                interval,
+            ) {
+                sess.send_event(&turn_context, EventMsg::TurnProgress(event))
+                    .await;
+            }
+        }
+    })))
+}
+
+/// The event to send at `now`, or `None` when tools have not been running
+/// for at least `interval`.
+fn progress_event(
+    turn_id: &str,
+    activity: ToolActivity,
+    tool_budget: ToolBudgetRemaining,
+    elapsed: Duration,
+    now: Instant,
+    interval: Duration,
+) -> Option<TurnProgressEvent> {
+    let busy_since = activity.busy_since?;
+    if now.saturating_duration_since(busy_since) < interval {
+        return None;
+    }
+    Some(TurnProgressEvent {
+        turn_id: turn_id.to_string(),
+        tool_calls_completed: activity.completed,
+        tool_calls_running: activity.running,
+        elapsed,
+        estimated_output_tokens: approx_tokens_from_byte_count(activity.pending_output_bytes),
+        tool_budget,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const INTERVAL: Duration = Duration::from_secs(2);
+
+    fn activity(busy_since: Option<Instant>) -> ToolActivity {
+        ToolActivity {
+            completed: 3,
+            running: 2,
+            busy_since,
+            pending_output_bytes: 4_001,
+        }
+    }
+
+    #[test]
+    fn reports_only_once_tools_have_run_for_an_interval() {
+        let now = Instant::now();
+        let elapsed = Duration::from_secs(9);
+        let budget = ToolBudgetRemaining {
+            turn_tool_calls: Some(7),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            progress_event("turn", activity(None), budget, elapsed, now, INTERVAL),
+            None
+        );
+        assert_eq!(
+            progress_event(
+                "turn",
+                activity(Some(now - Duration::from_millis(500))),
+                budget,
+                elapsed,
+                now,
+                INTERVAL,
+            ),
+            None
+        );
+        assert_eq!(
+            progress_event(
+                "turn",
+                activity(Some(now - Duration::from_secs(5))),
+                budget,
+                elapsed,
+                now,
+                INTERVAL,
+            ),
+            Some(TurnProgressEvent {
+                turn_id: "turn".to_string(),
+                tool_calls_completed: 3,
+                tool_calls_running: 2,
+                elapsed,
+                estimated_output_tokens: 1_001,
+                tool_budget: budget,
+            })
+        );
+    }
+}
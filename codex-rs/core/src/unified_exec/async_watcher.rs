@@ -2,18 +2,24 @@ use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::sync::broadcast;
 use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::Sleep;
+use tokio_util::sync::CancellationToken;
 
 use super::UnifiedExecContext;
 use super::process::UnifiedExecProcess;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::exec::DeltaTracker;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::MAX_EXEC_OUTPUT_DELTAS_PER_CALL;
+use crate::exec::OutputDeltaStats;
 use crate::exec::StreamOutput;
+use crate::exec_timing::ExecStopwatch;
+use crate::exec_timing::ExecTiming;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecCommandSource;
@@ -21,87 +27,88 @@ use crate::protocol::ExecOutputStream;
 use crate::tools::events::ToolEmitter;
 use crate::tools::events::ToolEventCtx;
 use crate::tools::events::ToolEventStage;
-use crate::unified_exec::head_tail_buffer::HeadTailBuffer;
 
 pub(crate) const TRAILING_OUTPUT_GRACE: Duration = Duration::from_millis(100);
 
-/// Upper bound for a single ExecCommandOutputDelta chunk emitted by unified exec.
-///
-/// The unified exec output buffer already caps *retained* output (see
-/// `UNIFIED_EXEC_OUTPUT_MAX_BYTES`), but we also cap per-event payload size so
-/// downstream event consumers (especially app-server JSON-RPC) don't have to
-/// process arbitrarily large delta payloads.
-const UNIFIED_EXEC_OUTPUT_DELTA_MAX_BYTES: usize = 8192;
+/// Spawn a background task that continuously reads from the PTY and emits
+/// ExecCommandOutputDelta events on UTF‑8 boundaries. The end event takes its
+/// output from the process's own capture, which does not depend on this task
+/// keeping up.
+pub(crate) fn start_streaming_output(process: &UnifiedExecProcess, context: &UnifiedExecContext) {
+    tokio::spawn(stream_output(
+        process.output_receiver(),
+        process.cancellation_token(),
+        process.output_drained_notify(),
+        process.delta_tracker(),
+        Arc::clone(&context.session),
+        Arc::clone(&context.turn),
+        context.call_id.clone(),
+    ));
+}
 
-/// Spawn a background task that continuously reads from the PTY, appends to the
-/// shared transcript, and emits ExecCommandOutputDelta events on UTF‑8
-/// boundaries.
-pub(crate) fn start_streaming_output(
-    process: &UnifiedExecProcess,
-    context: &UnifiedExecContext,
-    transcript: Arc<Mutex<HeadTailBuffer>>,
+/// Body of the [`start_streaming_output`] task: runs until the output closes
+/// or the grace period after exit runs out.
+async fn stream_output(
+    mut receiver: broadcast::Receiver<Vec<u8>>,
+    exit_token: CancellationToken,
+    output_drained: Arc<Notify>,
+    deltas: Arc<DeltaTracker>,
+    session_ref: Arc<Session>,
+    turn_ref: Arc<TurnContext>,
+    call_id: String,
 ) {
-    let mut receiver = process.output_receiver();
-    let output_drained = process.output_drained_notify();
-    let exit_token = process.cancellation_token();
+    use tokio::sync::broadcast::error::RecvError;
 
-    let session_ref = Arc::clone(&context.session);
-    let turn_ref = Arc::clone(&context.turn);
-    let call_id = context.call_id.clone();
+    let mut pending = Vec::<u8>::new();
+    let mut emitted_deltas: usize = 0;
 
-    tokio::spawn(async move {
-        use tokio::sync::broadcast::error::RecvError;
+    let mut grace_sleep: Option<Pin<Box<Sleep>>> = None;
 
-        let mut pending = Vec::<u8>::new();
-        let mut emitted_deltas: usize = 0;
-
-        let mut grace_sleep: Option<Pin<Box<Sleep>>> = None;
+    loop {
+        tokio::select! {
+            _ = exit_token.cancelled(), if grace_sleep.is_none() => {
+                let deadline = Instant::now() + TRAILING_OUTPUT_GRACE;
+                grace_sleep.replace(Box::pin(tokio::time::sleep_until(deadline)));
+            }
 
-        loop {
-            tokio::select! {
-                _ = exit_token.cancelled(), if grace_sleep.is_none() => {
-                    let deadline = Instant::now() + TRAILING_OUTPUT_GRACE;
-                    grace_sleep.replace(Box::pin(tokio::time::sleep_until(deadline)));
+            _ = async {
+                if let Some(sleep) = grace_sleep.as_mut() {
+                    sleep.as_mut().await;
                 }
+            }, if grace_sleep.is_some() => {
+                output_drained.notify_one();
+                break;
+            }
 
-                _ = async {
-                    if let Some(sleep) = grace_sleep.as_mut() {
-                        sleep.as_mut().await;
+            received = receiver.recv() => {
+                let chunk = match received {
+                    Ok(chunk) => chunk,
+                    Err(RecvError::Lagged(_)) => {
+                        deltas.record_dropped();
+                        continue;
+                    },
+                    Err(RecvError::Closed) => {
+                        output_drained.notify_one();
+                        break;
                     }
-                }, if grace_sleep.is_some() => {
-                    output_drained.notify_one();
-                    break;
-                }
-
-                received = receiver.recv() => {
-                    let chunk = match received {
-                        Ok(chunk) => chunk,
-                        Err(RecvError::Lagged(_)) => {
-                            continue;
-                        },
-                        Err(RecvError::Closed) => {
-                            output_drained.notify_one();
-                            break;
-                        }
-                    };
-
-                    process_chunk(
-                        &mut pending,
-                        &transcript,
-                        &call_id,
-                        &session_ref,
-                        &turn_ref,
-                        &mut emitted_deltas,
-                        chunk,
-                    ).await;
-                }
+                };
+
+                process_chunk(
+                    &mut pending,
+                    &call_id,
+                    &session_ref,
+                    &turn_ref,
+                    &mut emitted_deltas,
+                    &deltas,
+                    chunk,
+                ).await;
             }
         }
-    });
+    }
 }
 
 /// Spawn a background watcher that waits for the PTY to exit and then emits a
-/// single ExecCommandEnd event with the aggregated transcript.
+/// single ExecCommandEnd event with the captured output.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn spawn_exit_watcher(
     process: Arc<UnifiedExecProcess>,
@@ -111,8 +118,7 @@ pub(crate) fn spawn_exit_watcher(
     command: Vec<String>,
     cwd: PathBuf,
     process_id: String,
-    transcript: Arc<Mutex<HeadTailBuffer>>,
-    started_at: Instant,
+    stopwatch: ExecStopwatch,
 ) {
     let exit_token = process.cancellation_token();
     let output_drained = process.output_drained_notify();
@@ -122,7 +128,9 @@ pub(crate) fn spawn_exit_watcher(
         output_drained.notified().await;
 
         let exit_code = process.exit_code().unwrap_or(-1);
-        let duration = Instant::now().saturating_duration_since(started_at);
+        let timing = stopwatch.stop();
+        let deltas = process.delta_tracker().stats();
+        let aggregated_output = process.aggregated_output().await;
         emit_exec_end_for_unified_exec(
             session_ref,
             turn_ref,
@@ -130,10 +138,10 @@ pub(crate) fn spawn_exit_watcher(
             command,
             cwd,
             Some(process_id),
-            transcript,
-            String::new(),
+            aggregated_output,
             exit_code,
-            duration,
+            timing,
+            deltas,
         )
         .await;
     });
@@ -141,39 +149,46 @@ pub(crate) fn spawn_exit_watcher(
 
 async fn process_chunk(
     pending: &mut Vec<u8>,
-    transcript: &Arc<Mutex<HeadTailBuffer>>,
     call_id: &str,
     session_ref: &Arc<Session>,
     turn_ref: &Arc<TurnContext>,
     emitted_deltas: &mut usize,
+    deltas: &DeltaTracker,
     chunk: Vec<u8>,
 ) {
     pending.extend_from_slice(&chunk);
-    while let Some(prefix) = split_valid_utf8_prefix(pending) {
-        {
-            let mut guard = transcript.lock().await;
-            guard.push_chunk(prefix.to_vec());
-        }
-
+    // The unified exec output buffer already caps *retained* output (see
+    // `UNIFIED_EXEC_OUTPUT_MAX_BYTES`), but we also cap per-event payload size
+    // (`output_limits.delta_chunk_bytes`) so downstream event consumers
+    // (especially app-server JSON-RPC) don't have to process arbitrarily large
+    // delta payloads.
+    let max_bytes = turn_ref.output_limits.delta_chunk_bytes;
+    while let Some(prefix) = split_valid_utf8_prefix_with_max(pending, max_bytes) {
         if *emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+            deltas.record_dropped();
             continue;
         }
 
+        let len = prefix.len();
         let event = ExecCommandOutputDeltaEvent {
             call_id: call_id.to_string(),
             stream: ExecOutputStream::Stdout,
             chunk: prefix,
         };
-        session_ref
+        let delivered = session_ref
             .send_event(turn_ref.as_ref(), EventMsg::ExecCommandOutputDelta(event))
             .await;
+        if delivered {
+            deltas.record_sent(len);
+        } else {
+            deltas.record_dropped();
+        }
         *emitted_deltas += 1;
     }
 }
 
-/// Emit an ExecCommandEnd event for a unified exec session, using the transcript
-/// as the primary source of aggregated_output and falling back to the provided
-/// text when the transcript is empty.
+/// Emit an ExecCommandEnd event for a unified exec session with the output the
+/// process captured.
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn emit_exec_end_for_unified_exec(
     session_ref: Arc<Session>,
@@ -182,36 +197,34 @@ pub(crate) async fn emit_exec_end_for_unified_exec(
     command: Vec<String>,
     cwd: PathBuf,
     process_id: Option<String>,
-    transcript: Arc<Mutex<HeadTailBuffer>>,
-    fallback_output: String,
+    aggregated_output: String,
     exit_code: i32,
-    duration: Duration,
+    timing: ExecTiming,
+    deltas: OutputDeltaStats,
 ) {
-    let aggregated_output = resolve_aggregated_output(&transcript, fallback_output).await;
     let output = ExecToolCallOutput {
         exit_code,
         stdout: StreamOutput::new(aggregated_output.clone()),
         stderr: StreamOutput::new(String::new()),
         aggregated_output: StreamOutput::new(aggregated_output),
-        duration,
+        duration: timing.duration,
+        suspended: timing.suspended,
         timed_out: false,
+        deltas,
     };
     let event_ctx = ToolEventCtx::new(session_ref.as_ref(), turn_ref.as_ref(), &call_id, None);
     let emitter = ToolEmitter::unified_exec(
-        &command,
+        command,
         cwd,
-        ExecCommandSource::UnifiedExecStartup,
+        ExecCommandSource::ModelUnifiedExec,
         process_id,
+        session_ref.as_ref(),
     );
     emitter
-        .emit(event_ctx, ToolEventStage::Success(output))
+        .end(event_ctx, ToolEventStage::Success(output))
         .await;
 }
 
-fn split_valid_utf8_prefix(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
-    split_valid_utf8_prefix_with_max(buffer, UNIFIED_EXEC_OUTPUT_DELTA_MAX_BYTES)
-}
-
 fn split_valid_utf8_prefix_with_max(buffer: &mut Vec<u8>, max_bytes: usize) -> Option<Vec<u8>> {
     if buffer.is_empty() {
         return None;
@@ -238,21 +251,9 @@ fn split_valid_utf8_prefix_with_max(buffer: &mut Vec<u8>, max_bytes: usize) -> O
     Some(byte)
 }
 
-async fn resolve_aggregated_output(
-    transcript: &Arc<Mutex<HeadTailBuffer>>,
-    fallback: String,
-) -> String {
-    let guard = transcript.lock().await;
-    if guard.retained_bytes() == 0 {
-        return fallback;
-    }
-
-    String::from_utf8_lossy(&guard.to_bytes()).to_string()
-}
-
 #[cfg(test)]
 mod tests {
-    use super::split_valid_utf8_prefix_with_max;
+    use super::*;
 
     use pretty_assertions::assert_eq;
 
@@ -50,8 +50,6 @@ impl HeadTailBuffer {
         self.head_bytes.saturating_add(self.tail_bytes)
     }
 
-    // Used for tests.
-    #[allow(dead_code)]
     /// Total bytes that were dropped from the middle due to the size cap.
     pub(crate) fn omitted_bytes(&self) -> usize {
         self.omitted_bytes
@@ -90,6 +88,8 @@ impl HeadTailBuffer {
         self.push_to_tail(chunk);
     }
 
+    // Used for tests.
+    #[allow(dead_code)]
     /// Snapshot the retained output as a list of chunks.
     ///
     /// The returned chunks are ordered as: head chunks first, then tail chunks.
@@ -116,6 +116,14 @@ impl HeadTailBuffer {
         out
     }
 
+    /// Consume the buffer, returning the retained head and tail separately so
+    /// callers can mark where the omitted middle was.
+    pub(crate) fn into_head_and_tail(self) -> (Vec<u8>, Vec<u8>) {
+        (self.head.concat(), self.tail.concat())
+    }
+
+    // Used for tests.
+    #[allow(dead_code)]
     /// Drain all retained chunks from the buffer and reset its state.
     ///
     /// The drained chunks are returned in head-then-tail order. Omitted bytes
@@ -249,6 +257,15 @@ mod tests {
         assert!(buf.omitted_bytes() > 0);
     }
 
+    #[test]
+    fn into_head_and_tail_splits_at_the_omitted_middle() {
+        let mut buf = HeadTailBuffer::new(4);
+        buf.push_chunk(b"0123456789".to_vec());
+
+        assert_eq!(buf.omitted_bytes(), 6);
+        assert_eq!(buf.into_head_and_tail(), (b"01".to_vec(), b"89".to_vec()));
+    }
+
     #[test]
     fn fills_head_then_tail_across_multiple_chunks() {
         let mut buf = HeadTailBuffer::new(10);
@@ -37,7 +37,8 @@ use crate::sandboxing::SandboxPermissions;
 
 mod async_watcher;
 mod errors;
-mod head_tail_buffer;
+pub(crate) mod head_tail_buffer;
+mod output_ring;
 mod process;
 mod process_manager;
 
@@ -82,6 +83,9 @@ pub(crate) struct ExecCommandRequest {
     pub tty: bool,
     pub sandbox_permissions: SandboxPermissions,
     pub justification: Option<String>,
+    /// Variables set on top of the policy environment, already checked
+    /// against `shell_environment_policy`.
+    pub env: HashMap<String, String>,
 }
 
 #[derive(Debug)]
@@ -205,6 +209,7 @@ mod tests {
                     tty: true,
                     sandbox_permissions: SandboxPermissions::UseDefault,
                     justification: None,
+                    env: HashMap::new(),
                 },
                 &context,
             )
@@ -477,4 +482,38 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn user_input_and_resize_reach_the_process() -> anyhow::Result<()> {
+        skip_if_sandbox!(Ok(()));
+
+        let (session, turn) = test_session_and_turn().await;
+
+        let open =
+            exec_command(&session, &turn, "read line; stty size; echo got $line", 250).await?;
+        let process_id = open
+            .process_id
+            .as_ref()
+            .expect("expected process id")
+            .as_str();
+
+        let manager = &session.services.unified_exec_manager;
+        manager.resize_process(process_id, 33, 77).await?;
+        let call_id = manager.write_user_input(process_id, "hi\n").await?;
+        assert_eq!(call_id, "call");
+
+        let out = write_stdin(&session, process_id, "", 2_500).await?;
+        assert!(
+            out.output.contains("33 77"),
+            "expected resized PTY: {}",
+            out.output
+        );
+        assert!(
+            out.output.contains("got hi"),
+            "expected user input: {}",
+            out.output
+        );
+
+        Ok(())
+    }
 }
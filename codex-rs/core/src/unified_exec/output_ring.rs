@@ -0,0 +1,381 @@
+use crate::unified_exec::UNIFIED_EXEC_OUTPUT_MAX_BYTES;
+
+/// A fixed-capacity byte ring holding the most recent output of a unified
+/// exec process.
+///
+/// Appends never reallocate once the ring is full; the oldest bytes are
+/// overwritten instead and counted as evicted. Positions are absolute byte
+/// offsets into everything ever appended, so a reader can keep a cursor and
+/// later ask for whatever arrived after it, learning how many bytes it missed
+/// if the ring wrapped in between.
+#[derive(Debug)]
+pub(crate) struct OutputRing {
+    capacity: usize,
+    /// Grows up to `capacity`, then stays that size.
+    buf: Vec<u8>,
+    /// Physical index of the oldest retained byte. Always 0 until `buf` is
+    /// full.
+    start: usize,
+    /// Absolute offset one past the newest byte.
+    end: u64,
+    /// Cursor used by [`OutputRing::read_unread`].
+    read_cursor: u64,
+}
+
+/// Bytes returned by a cursor read.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct RingRead {
+    pub bytes: Vec<u8>,
+    /// Bytes between the requested cursor and `bytes` that were evicted
+    /// before they could be read.
+    pub evicted: u64,
+    /// Cursor to pass to the next read.
+    pub next: u64,
+}
+
+impl Default for OutputRing {
+    fn default() -> Self {
+        Self::new(UNIFIED_EXEC_OUTPUT_MAX_BYTES)
+    }
+}
+
+impl OutputRing {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buf: Vec::new(),
+            start: 0,
+            end: 0,
+            read_cursor: 0,
+        }
+    }
+
+    // Used for tests.
+    #[allow(dead_code)]
+    /// Number of bytes currently held.
+    pub(crate) fn retained_bytes(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Absolute offset of the oldest retained byte.
+    fn oldest(&self) -> u64 {
+        self.end - self.buf.len() as u64
+    }
+
+    // Used for tests.
+    #[allow(dead_code)]
+    /// Total bytes overwritten since the ring was created.
+    pub(crate) fn evicted_bytes(&self) -> u64 {
+        self.oldest()
+    }
+
+    /// Appends `data`, overwriting the oldest bytes once the ring is full.
+    pub(crate) fn push(&mut self, mut data: &[u8]) {
+        self.end += data.len() as u64;
+        if self.capacity == 0 {
+            return;
+        }
+        if data.len() >= self.capacity {
+            data = &data[data.len() - self.capacity..];
+            self.buf.clear();
+            self.grow_to(self.capacity);
+            self.buf.extend_from_slice(data);
+            self.start = 0;
+            return;
+        }
+
+        let free = self.capacity - self.buf.len();
+        if free > 0 {
+            let fill = free.min(data.len());
+            self.grow_to(self.buf.len() + fill);
+            self.buf.extend_from_slice(&data[..fill]);
+            data = &data[fill..];
+        }
+        while !data.is_empty() {
+            let n = (self.capacity - self.start).min(data.len());
+            self.buf[self.start..self.start + n].copy_from_slice(&data[..n]);
+            self.start = (self.start + n) % self.capacity;
+            data = &data[n..];
+        }
+    }
+
+    /// Returns the retained bytes after `cursor` without consuming them.
+    ///
+    /// The result never starts or ends inside a UTF-8 sequence: continuation
+    /// bytes orphaned by eviction are skipped (and counted as evicted), and an
+    /// incomplete sequence at the end is left for the next read.
+    pub(crate) fn read_from(&self, cursor: u64) -> RingRead {
+        let from = cursor.clamp(self.oldest(), self.end);
+        let mut evicted = from.saturating_sub(cursor);
+        let mut start = from;
+        let mut bytes = self.copy_range(from);
+
+        // Only eviction can leave a cursor inside a sequence; the rest of the
+        // sequence is unreadable, so skip it as evicted too.
+        let orphaned = bytes
+            .iter()
+            .take(3)
+            .take_while(|byte| is_continuation(**byte))
+            .count();
+        bytes.drain(..orphaned);
+        start += orphaned as u64;
+        evicted += orphaned as u64;
+        let held_back = incomplete_utf8_suffix_len(&bytes);
+        bytes.truncate(bytes.len() - held_back);
+
+        let next = start + bytes.len() as u64;
+        RingRead {
+            bytes,
+            evicted,
+            next,
+        }
+    }
+
+    /// Reads everything appended since the previous call and advances the
+    /// ring's own cursor past it.
+    pub(crate) fn read_unread(&mut self) -> RingRead {
+        let read = self.read_from(self.read_cursor);
+        self.read_cursor = read.next;
+        read
+    }
+
+    /// Like [`OutputRing::read_unread`] without advancing the cursor.
+    pub(crate) fn peek_unread(&self) -> RingRead {
+        self.read_from(self.read_cursor)
+    }
+
+    /// Makes room for `needed` bytes, doubling like `Vec` would but never
+    /// allocating past `capacity`.
+    fn grow_to(&mut self, needed: usize) {
+        if needed <= self.buf.capacity() {
+            return;
+        }
+        let target = needed.max(self.buf.capacity() * 2).min(self.capacity);
+        self.buf.reserve_exact(target - self.buf.len());
+    }
+
+    /// Copies the logical range `[from, end)` out of the ring.
+    fn copy_range(&self, from: u64) -> Vec<u8> {
+        let skip = (from - self.oldest()) as usize;
+        let len = self.buf.len() - skip;
+        let mut out = Vec::with_capacity(len);
+        if len == 0 {
+            return out;
+        }
+        let first = (self.start + skip) % self.buf.len();
+        let until_wrap = (self.buf.len() - first).min(len);
+        out.extend_from_slice(&self.buf[first..first + until_wrap]);
+        out.extend_from_slice(&self.buf[..len - until_wrap]);
+        out
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+/// Length of a trailing multi-byte sequence that has not been fully written
+/// yet, or 0 when `bytes` ends on a character boundary.
+fn incomplete_utf8_suffix_len(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(4) {
+        let byte = bytes[bytes.len() - back];
+        if is_continuation(byte) {
+            continue;
+        }
+        let width = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return 0,
+        };
+        return if back < width { back } else { 0 };
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn reads_return_output_appended_since_the_cursor() {
+        let mut ring = OutputRing::new(8);
+        ring.push(b"abc");
+        assert_eq!(
+            ring.read_unread(),
+            RingRead {
+                bytes: b"abc".to_vec(),
+                evicted: 0,
+                next: 3,
+            }
+        );
+        ring.push(b"de");
+        assert_eq!(ring.read_unread().bytes, b"de".to_vec());
+        assert_eq!(
+            ring.read_unread(),
+            RingRead {
+                bytes: Vec::new(),
+                evicted: 0,
+                next: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn wrapping_evicts_the_oldest_bytes() {
+        let mut ring = OutputRing::new(4);
+        ring.push(b"0123");
+        ring.push(b"45");
+        assert_eq!(ring.retained_bytes(), 4);
+        assert_eq!(ring.evicted_bytes(), 2);
+        assert_eq!(
+            ring.read_from(0),
+            RingRead {
+                bytes: b"2345".to_vec(),
+                evicted: 2,
+                next: 6,
+            }
+        );
+
+        // A chunk larger than the ring keeps only its end.
+        ring.push(b"abcdefgh");
+        assert_eq!(ring.read_from(6).bytes, b"efgh".to_vec());
+        assert_eq!(ring.read_from(6).evicted, 4);
+    }
+
+    #[test]
+    fn reads_never_split_utf8_sequences() {
+        let mut ring = OutputRing::new(64);
+        let euro = "€".as_bytes();
+        ring.push(b"a");
+        ring.push(&euro[..2]);
+        let first = ring.read_unread();
+        assert_eq!(first.bytes, b"a".to_vec());
+        assert_eq!(first.next, 1);
+
+        ring.push(&euro[2..]);
+        assert_eq!(ring.read_unread().bytes, euro.to_vec());
+
+        // Evicting the first byte of a sequence drops its orphaned tail too.
+        let mut ring = OutputRing::new(4);
+        ring.push("x€".as_bytes());
+        ring.push(b"yz");
+        let read = ring.read_from(0);
+        assert_eq!(read.bytes, b"yz".to_vec());
+        assert_eq!(read.evicted, 4);
+    }
+
+    #[test]
+    fn peeking_does_not_advance_the_cursor() {
+        let mut ring = OutputRing::new(8);
+        ring.push(b"abc");
+        assert_eq!(ring.peek_unread().bytes, b"abc".to_vec());
+        assert_eq!(ring.read_unread().bytes, b"abc".to_vec());
+        assert!(ring.peek_unread().bytes.is_empty());
+    }
+
+    /// Checks random append/read sequences against a model that keeps every
+    /// byte ever written.
+    #[test]
+    fn random_append_read_sequences_match_model() {
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let capacity = rng.random_range(0..64);
+            let mut ring = OutputRing::new(capacity);
+            let mut all: Vec<u8> = Vec::new();
+            let mut cursor = 0u64;
+
+            for _ in 0..200 {
+                if rng.random_bool(0.6) {
+                    let len = rng.random_range(0..2 * capacity.max(1));
+                    let chunk: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+                    ring.push(&chunk);
+                    all.extend_from_slice(&chunk);
+                } else {
+                    let read = ring.read_from(cursor);
+                    let start = read.next - read.bytes.len() as u64;
+                    assert_eq!(start, cursor + read.evicted, "seed {seed}");
+                    assert!(read.next <= all.len() as u64, "seed {seed}");
+                    assert_eq!(
+                        read.bytes,
+                        all[start as usize..read.next as usize].to_vec(),
+                        "seed {seed}"
+                    );
+                    cursor = read.next;
+                }
+
+                let retained = all.len().min(capacity);
+                assert_eq!(ring.retained_bytes(), retained, "seed {seed}");
+                assert_eq!(
+                    ring.evicted_bytes(),
+                    (all.len() - retained) as u64,
+                    "seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_utf8_output_reads_back_as_valid_utf8() {
+        const SAMPLES: [&str; 6] = ["a", "é", "€", "😀", "\n", "xyz"];
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut ring = OutputRing::new(rng.random_range(1..48));
+            let mut pending: Vec<u8> = Vec::new();
+
+            for _ in 0..300 {
+                for _ in 0..rng.random_range(0..6) {
+                    let sample = SAMPLES[rng.random_range(0..SAMPLES.len())];
+                    pending.extend_from_slice(sample.as_bytes());
+                }
+                // Split writes at arbitrary byte offsets, like a PTY would.
+                let split = rng.random_range(0..=pending.len());
+                ring.push(&pending[..split]);
+                pending.drain(..split);
+
+                if rng.random_bool(0.4) {
+                    let read = ring.read_unread();
+                    assert!(
+                        std::str::from_utf8(&read.bytes).is_ok(),
+                        "seed {seed}: {:?}",
+                        read.bytes
+                    );
+                }
+            }
+        }
+    }
+
+    /// Soak test: four simulated hours of a chatty process printing a line
+    /// every 100ms, polled every 30s, never grows the ring past its capacity.
+    #[test]
+    fn hours_of_chatty_output_keep_memory_bounded() {
+        const CAPACITY: usize = 64 * 1024;
+        const TICKS: usize = 4 * 60 * 60 * 10;
+        let mut ring = OutputRing::new(CAPACITY);
+        let mut consumed = 0u64;
+        let mut written = 0u64;
+
+        for tick in 0..TICKS {
+            let line = format!("{tick:>8} building target {} of {TICKS}\n", tick % 977);
+            ring.push(line.as_bytes());
+            written += line.len() as u64;
+
+            if tick % 300 == 0 {
+                let read = ring.read_unread();
+                consumed += read.evicted + read.bytes.len() as u64;
+                assert!(ring.retained_bytes() <= CAPACITY);
+                assert!(ring.buf.capacity() <= CAPACITY);
+            }
+        }
+
+        let read = ring.read_unread();
+        consumed += read.evicted + read.bytes.len() as u64;
+        assert_eq!(consumed, written);
+        assert_eq!(ring.buf.capacity(), CAPACITY);
+        assert_eq!(ring.evicted_bytes(), written - CAPACITY as u64);
+    }
+}
@@ -9,6 +9,7 @@ use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tokio_util::sync::CancellationToken;
 
+use crate::exec::DeltaTracker;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StreamOutput;
@@ -20,9 +21,11 @@ use codex_utils_pty::SpawnedPty;
 
 use super::UNIFIED_EXEC_OUTPUT_MAX_TOKENS;
 use super::UnifiedExecError;
+use super::async_watcher::TRAILING_OUTPUT_GRACE;
 use super::head_tail_buffer::HeadTailBuffer;
+use super::output_ring::OutputRing;
 
-pub(crate) type OutputBuffer = Arc<Mutex<HeadTailBuffer>>;
+pub(crate) type OutputBuffer = Arc<Mutex<OutputRing>>;
 pub(crate) struct OutputHandles {
     pub(crate) output_buffer: OutputBuffer,
     pub(crate) output_notify: Arc<Notify>,
@@ -38,6 +41,11 @@ pub(crate) struct UnifiedExecProcess {
     output_drained: Arc<Notify>,
     output_task: JoinHandle<()>,
     sandbox_type: SandboxType,
+    deltas: Arc<DeltaTracker>,
+    /// Everything the process wrote, fed from a channel that never lags.
+    capture: Arc<Mutex<HeadTailBuffer>>,
+    /// Cancelled once every chunk is in `capture`.
+    capture_closed: CancellationToken,
 }
 
 impl UnifiedExecProcess {
@@ -46,7 +54,7 @@ impl UnifiedExecProcess {
         initial_output_rx: tokio::sync::broadcast::Receiver<Vec<u8>>,
         sandbox_type: SandboxType,
     ) -> Self {
-        let output_buffer = Arc::new(Mutex::new(HeadTailBuffer::default()));
+        let output_buffer = Arc::new(Mutex::new(OutputRing::default()));
         let output_notify = Arc::new(Notify::new());
         let cancellation_token = CancellationToken::new();
         let output_drained = Arc::new(Notify::new());
@@ -58,7 +66,7 @@ impl UnifiedExecProcess {
                 match receiver.recv().await {
                     Ok(chunk) => {
                         let mut guard = buffer_clone.lock().await;
-                        guard.push_chunk(chunk);
+                        guard.push(&chunk);
                         drop(guard);
                         notify_clone.notify_waiters();
                     }
@@ -68,6 +76,22 @@ impl UnifiedExecProcess {
             }
         });
 
+        let capture = Arc::new(Mutex::new(HeadTailBuffer::default()));
+        let capture_closed = CancellationToken::new();
+        match process_handle.take_output_capture() {
+            Some(mut chunks) => {
+                let capture = Arc::clone(&capture);
+                let capture_closed = capture_closed.clone();
+                tokio::spawn(async move {
+                    while let Some(chunk) = chunks.recv().await {
+                        capture.lock().await.push_chunk(chunk);
+                    }
+                    capture_closed.cancel();
+                });
+            }
+            None => capture_closed.cancel(),
+        }
+
         Self {
             process_handle,
             output_buffer,
@@ -76,6 +100,9 @@ impl UnifiedExecProcess {
             output_drained,
             output_task,
             sandbox_type,
+            deltas: Arc::default(),
+            capture,
+            capture_closed,
         }
     }
 
@@ -103,6 +130,20 @@ impl UnifiedExecProcess {
         Arc::clone(&self.output_drained)
     }
 
+    /// Counts the `ExecCommandOutputDelta` events streamed for the process.
+    pub(super) fn delta_tracker(&self) -> Arc<DeltaTracker> {
+        Arc::clone(&self.deltas)
+    }
+
+    /// The output for the process's `ExecCommandEnd` event. Waits for the
+    /// output to close, but no longer than [`TRAILING_OUTPUT_GRACE`], since a
+    /// background child can keep it open after the process exits.
+    pub(super) async fn aggregated_output(&self) -> String {
+        let _ = tokio::time::timeout(TRAILING_OUTPUT_GRACE, self.capture_closed.cancelled()).await;
+        let guard = self.capture.lock().await;
+        String::from_utf8_lossy(&guard.to_bytes()).to_string()
+    }
+
     pub(super) fn has_exited(&self) -> bool {
         self.process_handle.has_exited()
     }
@@ -111,15 +152,24 @@ impl UnifiedExecProcess {
         self.process_handle.exit_code()
     }
 
+    pub(super) fn resize(&self, rows: u16, cols: u16) -> Result<(), UnifiedExecError> {
+        self.process_handle
+            .resize(rows, cols)
+            .map_err(|err| UnifiedExecError::Resize {
+                message: err.to_string(),
+            })
+    }
+
     pub(super) fn terminate(&self) {
         self.process_handle.terminate();
         self.cancellation_token.cancel();
         self.output_task.abort();
     }
 
-    async fn snapshot_output(&self) -> Vec<Vec<u8>> {
+    /// Output the next `write_stdin` poll would return, left unread.
+    async fn snapshot_output(&self) -> Vec<u8> {
         let guard = self.output_buffer.lock().await;
-        guard.snapshot_chunks()
+        guard.peek_unread().bytes
     }
 
     pub(crate) fn sandbox_type(&self) -> SandboxType {
@@ -130,11 +180,7 @@ impl UnifiedExecProcess {
         let _ =
             tokio::time::timeout(Duration::from_millis(20), self.output_notify.notified()).await;
 
-        let collected_chunks = self.snapshot_output().await;
-        let mut aggregated: Vec<u8> = Vec::new();
-        for chunk in collected_chunks {
-            aggregated.extend_from_slice(&chunk);
-        }
+        let aggregated = self.snapshot_output().await;
         let aggregated_text = String::from_utf8_lossy(&aggregated).to_string();
         self.check_for_sandbox_denial_with_text(&aggregated_text)
             .await?;
@@ -10,13 +10,13 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
-use crate::exec_env::create_env;
+use crate::exec_env::injected_env;
+use crate::exec_timing::ExecStopwatch;
 use crate::protocol::ExecCommandSource;
 use crate::sandboxing::ExecEnv;
 use crate::sandboxing::SandboxPermissions;
 use crate::tools::events::ToolEmitter;
 use crate::tools::events::ToolEventCtx;
-use crate::tools::events::ToolEventStage;
 use crate::tools::orchestrator::ToolOrchestrator;
 use crate::tools::runtimes::unified_exec::UnifiedExecRequest as UnifiedExecToolRequest;
 use crate::tools::runtimes::unified_exec::UnifiedExecRuntime;
@@ -41,7 +41,7 @@ use crate::unified_exec::async_watcher::spawn_exit_watcher;
 use crate::unified_exec::async_watcher::start_streaming_output;
 use crate::unified_exec::clamp_yield_time;
 use crate::unified_exec::generate_chunk_id;
-use crate::unified_exec::head_tail_buffer::HeadTailBuffer;
+use crate::unified_exec::output_ring::RingRead;
 use crate::unified_exec::process::OutputBuffer;
 use crate::unified_exec::process::OutputHandles;
 use crate::unified_exec::process::UnifiedExecProcess;
@@ -129,6 +129,7 @@ impl UnifiedExecProcessManager {
                 request.sandbox_permissions,
                 request.justification,
                 request.tty,
+                &request.env,
                 context,
             )
             .await;
@@ -141,7 +142,6 @@ impl UnifiedExecProcessManager {
             }
         };
 
-        let transcript = Arc::new(tokio::sync::Mutex::new(HeadTailBuffer::default()));
         let event_ctx = ToolEventCtx::new(
             context.session.as_ref(),
             context.turn.as_ref(),
@@ -149,19 +149,22 @@ impl UnifiedExecProcessManager {
             None,
         );
         let emitter = ToolEmitter::unified_exec(
-            &request.command,
+            request.command.clone(),
             cwd.clone(),
-            ExecCommandSource::UnifiedExecStartup,
+            ExecCommandSource::ModelUnifiedExec,
             Some(request.process_id.clone()),
-        );
-        emitter.emit(event_ctx, ToolEventStage::Begin).await;
+            context.session.as_ref(),
+        )
+        .with_injected_env(injected_env(&request.env));
+        emitter.begin(event_ctx).await;
 
-        start_streaming_output(&process, context, Arc::clone(&transcript));
+        start_streaming_output(&process, context);
 
         let max_tokens = resolve_max_tokens(request.max_output_tokens);
         let yield_time_ms = clamp_yield_time(request.yield_time_ms);
 
         let start = Instant::now();
+        let stopwatch = ExecStopwatch::start();
         // For the initial exec_command call, we both stream output to events
         // (via start_streaming_output above) and collect a snapshot here for
         // the tool response body.
@@ -198,10 +201,10 @@ impl UnifiedExecProcessManager {
                 request.command.clone(),
                 cwd,
                 Some(process_id),
-                Arc::clone(&transcript),
-                output.clone(),
+                process.aggregated_output().await,
                 exit,
-                wall_time,
+                stopwatch.stop(),
+                process.delta_tracker().stats(),
             )
             .await;
 
@@ -218,9 +221,9 @@ impl UnifiedExecProcessManager {
                 &request.command,
                 cwd.clone(),
                 start,
+                stopwatch,
                 process_id,
                 request.tty,
-                Arc::clone(&transcript),
             )
             .await;
         };
@@ -334,6 +337,47 @@ impl UnifiedExecProcessManager {
         Ok(response)
     }
 
+    /// Writes input typed by the user (rather than the model) to a process and
+    /// returns the call id of the command that started it.
+    pub(crate) async fn write_user_input(
+        &self,
+        process_id: &str,
+        input: &str,
+    ) -> Result<String, UnifiedExecError> {
+        let (writer_tx, call_id) =
+            {
+                let mut store = self.process_store.lock().await;
+                let entry = store.processes.get_mut(process_id).ok_or(
+                    UnifiedExecError::UnknownProcessId {
+                        process_id: process_id.to_string(),
+                    },
+                )?;
+                if !entry.tty {
+                    return Err(UnifiedExecError::StdinClosed);
+                }
+                entry.last_used = Instant::now();
+                (entry.process.writer_sender(), entry.call_id.clone())
+            };
+        Self::send_input(&writer_tx, input.as_bytes()).await?;
+        Ok(call_id)
+    }
+
+    pub(crate) async fn resize_process(
+        &self,
+        process_id: &str,
+        rows: u16,
+        cols: u16,
+    ) -> Result<(), UnifiedExecError> {
+        let store = self.process_store.lock().await;
+        let entry = store
+            .processes
+            .get(process_id)
+            .ok_or(UnifiedExecError::UnknownProcessId {
+                process_id: process_id.to_string(),
+            })?;
+        entry.process.resize(rows, cols)
+    }
+
     async fn refresh_process_state(&self, process_id: &str) -> ProcessStatus {
         let mut store = self.process_store.lock().await;
         let Some(entry) = store.processes.get(process_id) else {
@@ -408,9 +452,9 @@ impl UnifiedExecProcessManager {
         command: &[String],
         cwd: PathBuf,
         started_at: Instant,
+        stopwatch: ExecStopwatch,
         process_id: String,
         tty: bool,
-        transcript: Arc<tokio::sync::Mutex<HeadTailBuffer>>,
     ) {
         let entry = ProcessEntry {
             process: Arc::clone(&process),
@@ -445,8 +489,7 @@ impl UnifiedExecProcessManager {
             command.to_vec(),
             cwd,
             process_id,
-            transcript,
-            started_at,
+            stopwatch,
         );
     }
 
@@ -484,6 +527,9 @@ impl UnifiedExecProcessManager {
         UnifiedExecProcess::from_spawned(spawned, env.sandbox).await
     }
 
+    /// `env_overrides` are the variables the call set, already checked
+    /// against the policy; they win over the unified exec defaults.
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn open_session_with_sandbox(
         &self,
         command: &[String],
@@ -491,9 +537,11 @@ impl UnifiedExecProcessManager {
         sandbox_permissions: SandboxPermissions,
         justification: Option<String>,
         tty: bool,
+        env_overrides: &HashMap<String, String>,
         context: &UnifiedExecContext,
     ) -> Result<UnifiedExecProcess, UnifiedExecError> {
-        let env = apply_unified_exec_env(create_env(&context.turn.shell_environment_policy));
+        let mut env = apply_unified_exec_env(context.turn.exec_env());
+        env.extend(env_overrides.clone());
         let features = context.session.features();
         let mut orchestrator = ToolOrchestrator::new();
         let mut runtime = UnifiedExecRuntime::new(self);
@@ -501,18 +549,20 @@ impl UnifiedExecProcessManager {
             .session
             .services
             .exec_policy
-            .create_exec_approval_requirement_for_command(
+            .create_exec_approval_requirement_for_command_with_env(
                 &features,
-                command,
+                &context.session.user_shell().canonical_exec_args(command),
                 context.turn.approval_policy,
                 &context.turn.sandbox_policy,
                 sandbox_permissions,
+                !env_overrides.is_empty(),
             )
             .await;
         let req = UnifiedExecToolRequest::new(
             command.to_vec(),
             cwd,
             env,
+            env_overrides.clone(),
             tty,
             sandbox_permissions,
             justification,
@@ -547,17 +597,17 @@ impl UnifiedExecProcessManager {
         let mut collected: Vec<u8> = Vec::with_capacity(4096);
         let mut exit_signal_received = cancellation_token.is_cancelled();
         loop {
-            let drained_chunks: Vec<Vec<u8>>;
+            let read: RingRead;
             let mut wait_for_output = None;
             {
                 let mut guard = output_buffer.lock().await;
-                drained_chunks = guard.drain_chunks();
-                if drained_chunks.is_empty() {
+                read = guard.read_unread();
+                if read.bytes.is_empty() && read.evicted == 0 {
                     wait_for_output = Some(output_notify.notified());
                 }
             }
 
-            if drained_chunks.is_empty() {
+            if read.bytes.is_empty() && read.evicted == 0 {
                 exit_signal_received |= cancellation_token.is_cancelled();
                 let remaining = deadline.saturating_duration_since(Instant::now());
                 if remaining == Duration::ZERO {
@@ -584,9 +634,12 @@ impl UnifiedExecProcessManager {
                 continue;
             }
 
-            for chunk in drained_chunks {
-                collected.extend_from_slice(&chunk);
+            if read.evicted > 0 {
+                collected.extend_from_slice(
+                    format!("\n[... {} bytes omitted ...]\n", read.evicted).as_bytes(),
+                );
             }
+            collected.extend_from_slice(&read.bytes);
 
             exit_signal_received |= cancellation_token.is_cancelled();
             if Instant::now() >= deadline {
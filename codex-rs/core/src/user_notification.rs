@@ -1,44 +1,121 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::NotificationKind;
+use codex_protocol::protocol::ToolBudgetKind;
 use serde::Serialize;
 use tracing::error;
 use tracing::warn;
 
-#[derive(Debug, Default)]
-pub(crate) struct UserNotifier {
-    notify_command: Option<Vec<String>>,
+/// How long a `notify` program may run before it is killed.
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivers [`UserNotification`]s outside the session.
+pub(crate) trait Notifier: Send + Sync {
+    /// Hands `notification` off without waiting for it to be delivered, and
+    /// returns whether anything was sent.
+    fn notify(&self, notification: &UserNotification) -> bool;
 }
 
-impl UserNotifier {
-    pub(crate) fn notify(&self, notification: &UserNotification) {
-        if let Some(notify_command) = &self.notify_command
-            && !notify_command.is_empty()
-        {
-            self.invoke_notify(notify_command, notification)
+/// Drops every notification; used when no `notify` program is configured.
+pub(crate) struct NoopNotifier;
+
+impl Notifier for NoopNotifier {
+    fn notify(&self, _notification: &UserNotification) -> bool {
+        false
+    }
+}
+
+/// Runs the configured `notify` program with the notification, serialized
+/// as JSON, as its last argument.
+pub(crate) struct CommandNotifier {
+    command: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandNotifier {
+    pub(crate) fn new(command: Vec<String>) -> Self {
+        Self {
+            command,
+            timeout: NOTIFY_TIMEOUT,
         }
     }
+}
 
-    fn invoke_notify(&self, notify_command: &[String], notification: &UserNotification) {
+impl Notifier for CommandNotifier {
+    fn notify(&self, notification: &UserNotification) -> bool {
+        let Some((program, args)) = self.command.split_first() else {
+            return false;
+        };
         let Ok(json) = serde_json::to_string(&notification) else {
             error!("failed to serialise notification payload");
-            return;
+            return false;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("no async runtime to run notifier '{program}'");
+            return false;
         };
 
-        let mut command = std::process::Command::new(&notify_command[0]);
-        if notify_command.len() > 1 {
-            command.args(&notify_command[1..]);
-        }
-        command.arg(json);
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(args)
+            .arg(json)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        let program = program.clone();
+        let timeout = self.timeout;
+        // Fire-and-forget: the session never waits on the program, which is
+        // killed if it outlives the timeout.
+        runtime.spawn(async move {
+            let mut child = match command.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    warn!("failed to spawn notifier '{program}': {e}");
+                    return;
+                }
+            };
+            if tokio::time::timeout(timeout, child.wait()).await.is_err() {
+                warn!("notifier '{program}' still running after {timeout:?}; killing it");
+                let _ = child.kill().await;
+            }
+        });
+        true
+    }
+}
 
-        // Fire-and-forget – we do not wait for completion.
-        if let Err(e) = command.spawn() {
-            warn!("failed to spawn notifier '{}': {e}", notify_command[0]);
-        }
+pub(crate) struct UserNotifier {
+    notifier: Box<dyn Notifier>,
+}
+
+impl Default for UserNotifier {
+    fn default() -> Self {
+        Self::with_notifier(Box::new(NoopNotifier))
     }
+}
 
+impl UserNotifier {
+    /// A notifier running the `notify` program, or a no-op without one.
     pub(crate) fn new(notify: Option<Vec<String>>) -> Self {
-        Self {
-            notify_command: notify,
+        match notify {
+            Some(command) if !command.is_empty() => {
+                Self::with_notifier(Box::new(CommandNotifier::new(command)))
+            }
+            _ => Self::default(),
         }
     }
+
+    pub(crate) fn with_notifier(notifier: Box<dyn Notifier>) -> Self {
+        Self { notifier }
+    }
+
+    /// Sends `notification`, returning whether it went anywhere.
+    pub(crate) fn notify(&self, notification: &UserNotification) -> bool {
+        self.notifier.notify(notification)
+    }
 }
 
 /// User can configure a program that will receive notifications. Each
@@ -59,12 +136,92 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    /// A command or patch is waiting for the user's approval.
+    #[serde(rename_all = "kebab-case")]
+    ApprovalRequested {
+        thread_id: String,
+        turn_id: String,
+        call_id: String,
+        /// The command to run, or the files a patch changes.
+        summary: String,
+    },
+
+    /// A `[tool_budget]` limit ran out.
+    #[serde(rename_all = "kebab-case")]
+    BudgetExceeded {
+        thread_id: String,
+        turn_id: String,
+        budget: ToolBudgetKind,
+        limit: u64,
+    },
+
+    /// The turn failed with an error.
+    #[serde(rename_all = "kebab-case")]
+    Error {
+        thread_id: String,
+        turn_id: String,
+        message: String,
+    },
+}
+
+impl UserNotification {
+    /// The notification `event` warrants, if any. Turn completion is sent by
+    /// the turn itself, which knows its input messages.
+    pub(crate) fn for_event(thread_id: String, event: &Event) -> Option<Self> {
+        match &event.msg {
+            EventMsg::ExecApprovalRequest(ev) => Some(Self::ApprovalRequested {
+                thread_id,
+                turn_id: ev.turn_id.clone(),
+                call_id: ev.call_id.clone(),
+                summary: ev.command.join(" "),
+            }),
+            EventMsg::ApplyPatchApprovalRequest(ev) => {
+                let mut files: Vec<String> = ev
+                    .changes
+                    .keys()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                files.sort();
+                Some(Self::ApprovalRequested {
+                    thread_id,
+                    turn_id: ev.turn_id.clone(),
+                    call_id: ev.call_id.clone(),
+                    summary: files.join(", "),
+                })
+            }
+            EventMsg::BudgetExceeded(ev) => Some(Self::BudgetExceeded {
+                thread_id,
+                turn_id: ev.turn_id.clone(),
+                budget: ev.budget,
+                limit: ev.limit,
+            }),
+            EventMsg::Error(ev) => Some(Self::Error {
+                thread_id,
+                turn_id: event.id.clone(),
+                message: ev.message.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> NotificationKind {
+        match self {
+            Self::AgentTurnComplete { .. } => NotificationKind::TurnComplete,
+            Self::ApprovalRequested { .. } => NotificationKind::ApprovalRequested,
+            Self::BudgetExceeded { .. } => NotificationKind::BudgetExceeded,
+            Self::Error { .. } => NotificationKind::Error,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Result;
+    use codex_protocol::protocol::BackgroundEventEvent;
+    use codex_protocol::protocol::BudgetExceededEvent;
+    use codex_protocol::protocol::ErrorEvent;
 
     #[test]
     fn test_user_notification() -> Result<()> {
@@ -84,4 +241,64 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn budget_and_error_events_map_to_notifications() -> Result<()> {
+        let budget = Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::BudgetExceeded(BudgetExceededEvent {
+                call_id: "call-41".to_string(),
+                turn_id: "turn-1".to_string(),
+                budget: ToolBudgetKind::TurnToolCalls,
+                limit: 40,
+                ends_turn: false,
+            }),
+        };
+        let notification = UserNotification::for_event("thread".to_string(), &budget)
+            .expect("budget exhaustion notifies");
+        assert_eq!(notification.kind(), NotificationKind::BudgetExceeded);
+        assert_eq!(
+            serde_json::to_string(&notification)?,
+            r#"{"type":"budget-exceeded","thread-id":"thread","turn-id":"turn-1","budget":"turn_tool_calls","limit":40}"#
+        );
+
+        let error = Event {
+            id: "turn-2".to_string(),
+            msg: EventMsg::Error(ErrorEvent {
+                message: "boom".to_string(),
+                codex_error_info: None,
+            }),
+        };
+        assert_eq!(
+            UserNotification::for_event("thread".to_string(), &error),
+            Some(UserNotification::Error {
+                thread_id: "thread".to_string(),
+                turn_id: "turn-2".to_string(),
+                message: "boom".to_string(),
+            })
+        );
+
+        let background = Event {
+            id: "turn-2".to_string(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: "working".to_string(),
+            }),
+        };
+        assert_eq!(
+            UserNotification::for_event("thread".to_string(), &background),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn without_a_command_nothing_is_sent() {
+        let notification = UserNotification::Error {
+            thread_id: "thread".to_string(),
+            turn_id: "turn".to_string(),
+            message: "boom".to_string(),
+        };
+        assert!(!UserNotifier::new(None).notify(&notification));
+        assert!(!UserNotifier::new(Some(Vec::new())).notify(&notification));
+    }
 }
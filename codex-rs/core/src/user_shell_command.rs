@@ -36,7 +36,7 @@ fn format_user_shell_command_body(
     sections.push("Output:".to_string());
     sections.push(format_exec_output_str(
         exec_output,
-        turn_context.truncation_policy,
+        turn_context.exec_output_truncation_policy(),
     ));
     sections.push("</result>".to_string());
     sections.join("\n")
@@ -89,7 +89,9 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new("hi".to_string()),
             duration: Duration::from_secs(1),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let (_, turn_context) = make_session_and_context().await;
         let item = user_shell_command_record_item("echo hi", &exec_output, &turn_context);
@@ -113,7 +115,9 @@ mod tests {
             stderr: StreamOutput::new("stderr-only".to_string()),
             aggregated_output: StreamOutput::new("combined output wins".to_string()),
             duration: Duration::from_millis(120),
+            suspended: false,
             timed_out: false,
+            deltas: Default::default(),
         };
         let (_, turn_context) = make_session_and_context().await;
         let record = format_user_shell_command_record("false", &exec_output, &turn_context);
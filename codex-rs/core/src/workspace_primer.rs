@@ -0,0 +1,278 @@
+//! Overview of the working directory handed to the model when a session
+//! starts, so it does not spend its first tool calls finding its way around.
+//!
+//! With the `workspace_primer` feature, a new session records a developer
+//! message holding the README's first heading, the top-level entries of the
+//! working directory, the build system and test command recognized from
+//! them, and a one-line `git status` summary, then sends the same overview
+//! as `WorkspacePrimer`. Entries excluded by ignore files are left out, and
+//! no file is read past a few kilobytes.
+
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::Path;
+
+use codex_protocol::models::DeveloperInstructions;
+use codex_protocol::models::ResponseItem;
+
+use crate::git_info::get_git_repo_root;
+use crate::git_info::run_git_command_with_timeout;
+use crate::protocol::WorkspacePrimerEvent;
+use crate::tools::handlers::git_status_summary;
+use crate::tools::handlers::workspace_walker;
+
+/// Top-level entries listed at most.
+const MAX_ENTRIES: usize = 40;
+
+/// Top-level entries looked at at most, including those not listed.
+const MAX_SCANNED_ENTRIES: usize = 1_000;
+
+/// Bytes read from the start of the README.
+const README_BYTES: u64 = 4 * 1024;
+
+/// Bytes read from `package.json` or a `Makefile` to find a test command.
+const MANIFEST_BYTES: u64 = 64 * 1024;
+
+/// Characters of the README heading kept.
+const MAX_HEADING_CHARS: usize = 120;
+
+const README_NAMES: &[&str] = &[
+    "README.md",
+    "README",
+    "README.rst",
+    "README.txt",
+    "readme.md",
+];
+
+/// Gathers the overview of `cwd`.
+pub(crate) async fn gather(cwd: &Path) -> WorkspacePrimerEvent {
+    let scan_cwd = cwd.to_path_buf();
+    let (readme_heading, entries, entries_omitted, build) =
+        tokio::task::spawn_blocking(move || scan(&scan_cwd))
+            .await
+            .unwrap_or_default();
+    let (build_system, test_command) = build.unzip();
+    WorkspacePrimerEvent {
+        cwd: cwd.to_path_buf(),
+        readme_heading,
+        entries,
+        entries_omitted,
+        build_system,
+        test_command: test_command.flatten(),
+        git_status: git_status(cwd).await,
+    }
+}
+
+/// The developer message carrying `primer` to the model.
+pub(crate) fn developer_message(primer: &WorkspacePrimerEvent) -> ResponseItem {
+    let mut lines = vec![
+        "Overview of the working directory, gathered when the session started:".to_string(),
+        "<workspace_primer>".to_string(),
+        format!("  <cwd>{}</cwd>", primer.cwd.display()),
+    ];
+    if let Some(heading) = &primer.readme_heading {
+        lines.push(format!("  <readme_heading>{heading}</readme_heading>"));
+    }
+    let entries = primer.entries.join(", ");
+    if primer.entries_omitted > 0 {
+        lines.push(format!(
+            "  <entries omitted=\"{}\">{entries}</entries>",
+            primer.entries_omitted
+        ));
+    } else {
+        lines.push(format!("  <entries>{entries}</entries>"));
+    }
+    if let Some(build_system) = &primer.build_system {
+        lines.push(format!("  <build_system>{build_system}</build_system>"));
+    }
+    if let Some(test_command) = &primer.test_command {
+        lines.push(format!("  <test_command>{test_command}</test_command>"));
+    }
+    if let Some(git_status) = &primer.git_status {
+        lines.push(format!("  <git_status>{git_status}</git_status>"));
+    }
+    lines.push("</workspace_primer>".to_string());
+    DeveloperInstructions::new(lines.join("\n")).into()
+}
+
+type BuildInfo = (String, Option<String>);
+
+/// The README heading, the listed and omitted top-level entries, and the
+/// build system with its test command.
+fn scan(cwd: &Path) -> (Option<String>, Vec<String>, usize, Option<BuildInfo>) {
+    let mut names = BTreeSet::new();
+    let mut entries = Vec::new();
+    let mut omitted = 0;
+    let walker = workspace_walker(cwd, false).max_depth(Some(1)).build();
+    for entry in walker
+        .flatten()
+        .filter(|entry| entry.depth() == 1)
+        .take(MAX_SCANNED_ENTRIES)
+    {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entries.len() < MAX_ENTRIES {
+            let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+            entries.push(if is_dir {
+                format!("{name}/")
+            } else {
+                name.clone()
+            });
+        } else {
+            omitted += 1;
+        }
+        names.insert(name);
+    }
+    let readme_heading = README_NAMES
+        .iter()
+        .find(|name| names.contains(**name))
+        .and_then(|name| read_prefix(&cwd.join(name), README_BYTES))
+        .and_then(|readme| readme_heading(&readme));
+    let build = detect_build(cwd, &names);
+    (readme_heading, entries, omitted, build)
+}
+
+/// Reads at most `limit` bytes from the start of `path`.
+fn read_prefix(path: &Path, limit: u64) -> Option<String> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(limit)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The first Markdown heading, or failing that the first non-empty line.
+fn readme_heading(readme: &str) -> Option<String> {
+    let mut lines = readme.lines().map(str::trim);
+    let line = lines
+        .clone()
+        .find(|line| line.starts_with('#'))
+        .or_else(|| lines.find(|line| !line.is_empty()))?;
+    let heading = line.trim_start_matches('#').trim();
+    (!heading.is_empty()).then(|| heading.chars().take(MAX_HEADING_CHARS).collect())
+}
+
+/// Recognizes the build system from the top-level `names` of `cwd`.
+fn detect_build(cwd: &Path, names: &BTreeSet<String>) -> Option<BuildInfo> {
+    let has = |name: &str| names.contains(name);
+    if has("Cargo.toml") {
+        return Some(("cargo".to_string(), Some("cargo test".to_string())));
+    }
+    if has("package.json") {
+        let manager = if has("pnpm-lock.yaml") {
+            "pnpm"
+        } else if has("yarn.lock") {
+            "yarn"
+        } else if has("bun.lockb") || has("bun.lock") {
+            "bun"
+        } else {
+            "npm"
+        };
+        let has_test_script = read_prefix(&cwd.join("package.json"), MANIFEST_BYTES)
+            .and_then(|manifest| serde_json::from_str::<serde_json::Value>(&manifest).ok())
+            .is_some_and(|manifest| manifest["scripts"]["test"].is_string());
+        return Some((
+            manager.to_string(),
+            has_test_script.then(|| format!("{manager} test")),
+        ));
+    }
+    if has("go.mod") {
+        return Some(("go".to_string(), Some("go test ./...".to_string())));
+    }
+    if has("pyproject.toml") || has("setup.py") {
+        return Some(("python".to_string(), Some("pytest".to_string())));
+    }
+    if has("pom.xml") {
+        return Some(("maven".to_string(), Some("mvn test".to_string())));
+    }
+    if has("build.gradle") || has("build.gradle.kts") {
+        let gradle = if has("gradlew") {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        return Some(("gradle".to_string(), Some(format!("{gradle} test"))));
+    }
+    if has("Makefile") {
+        let has_test_target = read_prefix(&cwd.join("Makefile"), MANIFEST_BYTES)
+            .is_some_and(|makefile| makefile.lines().any(|line| line.starts_with("test:")));
+        return Some((
+            "make".to_string(),
+            has_test_target.then(|| "make test".to_string()),
+        ));
+    }
+    if has("CMakeLists.txt") {
+        return Some(("cmake".to_string(), None));
+    }
+    None
+}
+
+/// One-line `git status` summary, or `None` outside a repository.
+async fn git_status(cwd: &Path) -> Option<String> {
+    get_git_repo_root(cwd)?;
+    let output =
+        run_git_command_with_timeout(&["status", "--porcelain=v2", "--branch", "-z"], cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(git_status_summary(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn gathers_readme_entries_and_build_system_respecting_ignore_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        std::fs::write(root.join("README.md"), "[badge]\n\n# Widget\n\nText\n").expect("write");
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"test": "jest"}}"#,
+        )
+        .expect("write");
+        std::fs::write(root.join("yarn.lock"), "").expect("write");
+        std::fs::write(root.join(".ignore"), "dist/\n").expect("write");
+        std::fs::create_dir(root.join("src")).expect("mkdir");
+        std::fs::create_dir(root.join("dist")).expect("mkdir");
+
+        let primer = gather(root).await;
+
+        assert_eq!(
+            primer,
+            WorkspacePrimerEvent {
+                cwd: root.to_path_buf(),
+                readme_heading: Some("Widget".to_string()),
+                entries: vec![
+                    "README.md".to_string(),
+                    "package.json".to_string(),
+                    "src/".to_string(),
+                    "yarn.lock".to_string(),
+                ],
+                entries_omitted: 0,
+                build_system: Some("yarn".to_string()),
+                test_command: Some("yarn test".to_string()),
+                git_status: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn long_listings_are_cut_at_the_entry_budget() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for index in 0..MAX_ENTRIES + 5 {
+            std::fs::write(dir.path().join(format!("file-{index:03}")), "").expect("write");
+        }
+
+        let primer = gather(dir.path()).await;
+
+        assert_eq!(
+            (primer.entries.len(), primer.entries_omitted),
+            (MAX_ENTRIES, 5)
+        );
+        assert_eq!(primer.build_system, None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Named workspace roots: directories a session works in besides its cwd.
+//!
+//! Roots come from `[workspace_roots]` in config.toml, the overrides a new
+//! session starts with, and `Op::UpdateWorkspaceRoots`. Under a
+//! workspace-write sandbox every root is also a writable root, so the
+//! writable set is the union of the configured writable roots and the
+//! workspace roots.
+
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::WorkspaceRoot;
+use codex_utils_absolute_path::AbsolutePathBuf;
+
+/// Checks that `name` can prefix a displayed path: not empty, not `.` or
+/// `..`, and without slashes or whitespace.
+pub(crate) fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !matches!(name, "" | "." | "..")
+        && !name
+            .chars()
+            .any(|c| c == '/' || c == '\\' || c.is_whitespace());
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid workspace root name `{name}`; names cannot be empty or contain slashes or whitespace"
+        ))
+    }
+}
+
+/// Makes every root writable under a workspace-write `policy`. Other
+/// policies are left as they are.
+pub(crate) fn extend_writable_roots(policy: &mut SandboxPolicy, roots: &[WorkspaceRoot]) {
+    if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = policy {
+        for root in roots {
+            if !writable_roots.contains(&root.path) {
+                writable_roots.push(root.path.clone());
+            }
+        }
+    }
+}
+
+/// The writable roots of a workspace-write `policy`; empty for the others.
+pub(crate) fn writable_roots(policy: &SandboxPolicy) -> Vec<AbsolutePathBuf> {
+    match policy {
+        SandboxPolicy::WorkspaceWrite { writable_roots, .. } => writable_roots.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// The outcome of an `Op::UpdateWorkspaceRoots`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct RootsUpdate {
+    pub(crate) roots: Vec<WorkspaceRoot>,
+    pub(crate) removed: Vec<WorkspaceRoot>,
+    pub(crate) sandbox_policy: SandboxPolicy,
+}
+
+/// Removes the roots named in `remove`, then adds `add`, moving the writable
+/// roots of `sandbox_policy` along. A removed root's directory stays
+/// writable while another root still points at it.
+pub(crate) fn update(
+    roots: &[WorkspaceRoot],
+    sandbox_policy: &SandboxPolicy,
+    add: &[WorkspaceRoot],
+    remove: &[String],
+) -> Result<RootsUpdate, String> {
+    let mut roots = roots.to_vec();
+    let mut removed = Vec::new();
+    for name in remove {
+        let index = roots
+            .iter()
+            .position(|root| &root.name == name)
+            .ok_or_else(|| format!("no workspace root named `{name}`"))?;
+        removed.push(roots.remove(index));
+    }
+    for root in add {
+        validate_name(&root.name)?;
+        if roots.iter().any(|existing| existing.name == root.name) {
+            return Err(format!("workspace root `{}` already exists", root.name));
+        }
+        if !root.path.as_path().is_dir() {
+            return Err(format!(
+                "workspace root `{}` is not a directory: {}",
+                root.name,
+                root.path.display()
+            ));
+        }
+        roots.push(root.clone());
+    }
+
+    let mut sandbox_policy = sandbox_policy.clone();
+    if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut sandbox_policy {
+        writable_roots.retain(|path| {
+            !removed.iter().any(|root| &root.path == path)
+                || roots.iter().any(|root| &root.path == path)
+        });
+    }
+    extend_writable_roots(&mut sandbox_policy, &roots);
+    Ok(RootsUpdate {
+        roots,
+        removed,
+        sandbox_policy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn root(name: &str, dir: &TempDir) -> WorkspaceRoot {
+        WorkspaceRoot {
+            name: name.to_string(),
+            path: AbsolutePathBuf::from_absolute_path(dir.path()).expect("absolute tempdir"),
+        }
+    }
+
+    #[test]
+    fn added_roots_become_writable_and_removed_ones_stop_being() {
+        let web = TempDir::new().expect("tempdir");
+        let api = TempDir::new().expect("tempdir");
+        let mut policy = SandboxPolicy::new_workspace_write_policy();
+        extend_writable_roots(&mut policy, &[root("web", &web)]);
+
+        let added =
+            update(&[root("web", &web)], &policy, &[root("api", &api)], &[]).expect("add api");
+        assert_eq!(
+            writable_roots(&added.sandbox_policy),
+            vec![root("web", &web).path, root("api", &api).path]
+        );
+
+        let removed = update(
+            &added.roots,
+            &added.sandbox_policy,
+            &[],
+            &["web".to_string()],
+        )
+        .expect("remove web");
+        assert_eq!(removed.roots, vec![root("api", &api)]);
+        assert_eq!(removed.removed, vec![root("web", &web)]);
+        assert_eq!(
+            writable_roots(&removed.sandbox_policy),
+            vec![root("api", &api).path]
+        );
+    }
+
+    #[test]
+    fn invalid_updates_change_nothing() {
+        let web = TempDir::new().expect("tempdir");
+        let policy = SandboxPolicy::new_read_only_policy();
+        let roots = [root("web", &web)];
+
+        for (add, remove, message) in [
+            (
+                vec![root("web", &web)],
+                vec![],
+                "workspace root `web` already exists",
+            ),
+            (
+                vec![],
+                vec!["api".to_string()],
+                "no workspace root named `api`",
+            ),
+            (
+                vec![root("my repo", &web)],
+                vec![],
+                "invalid workspace root name `my repo`; names cannot be empty or contain slashes or whitespace",
+            ),
+        ] {
+            assert_eq!(
+                update(&roots, &policy, &add, &remove),
+                Err(message.to_string())
+            );
+        }
+    }
+}
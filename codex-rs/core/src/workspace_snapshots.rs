@@ -0,0 +1,535 @@
+//! Snapshots of the working tree the user can go back to.
+//!
+//! `Op::SnapshotWorkspace` takes one on request and, unless
+//! `[workspace_snapshots] before_destructive_commands` is off, one is taken
+//! before the first command of a turn that looks destructive (`rm -rf`,
+//! `git reset --hard`, ...). In a git repository a snapshot is a ghost
+//! commit of the working tree, kept from garbage collection by a ref under
+//! `refs/codex/snapshots/<thread id>/`. Outside one, the files are copied
+//! under the session's temp directory, within `max_copy_files` and
+//! `max_copy_bytes`. `Op::RestoreSnapshot` refuses to delete files created
+//! since the snapshot unless forced. Only the newest `max_snapshots` are
+//! kept, and their refs are deleted when the session shuts down unless
+//! `keep_after_session` is set.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_git::CreateGhostCommitOptions;
+use codex_git::GhostCommit;
+use codex_git::RestoreGhostCommitOptions;
+use codex_git::create_ghost_commit_with_report;
+use codex_git::restore_ghost_commit_with_options;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::command_safety::command_risk::classify_command_risk;
+use crate::config::GhostSnapshotConfig;
+use crate::config::types::WorkspaceSnapshots;
+use crate::git_info::get_git_repo_root;
+use crate::git_info::run_git_command_with_timeout;
+use crate::parse_command::shlex_join;
+use crate::protocol::EventMsg;
+use crate::protocol::WarningEvent;
+use crate::protocol::WorkspaceSnapshot;
+use crate::protocol::WorkspaceSnapshotCreatedEvent;
+use crate::protocol::WorkspaceSnapshotKind;
+use crate::protocol::WorkspaceSnapshotReason;
+use crate::protocol::WorkspaceSnapshotRestoredEvent;
+use crate::tools::handlers::workspace_walker;
+
+/// Namespace of the refs that keep git snapshots alive.
+const REF_PREFIX: &str = "refs/codex/snapshots";
+
+/// The session's snapshots, oldest first.
+pub(crate) struct SnapshotStore {
+    limits: WorkspaceSnapshots,
+    snapshots: Vec<StoredSnapshot>,
+    next_id: u64,
+    /// The turn that already took its automatic snapshot.
+    auto_snapshot_turn: Option<String>,
+}
+
+#[derive(Clone)]
+struct StoredSnapshot {
+    info: WorkspaceSnapshot,
+    saved: Saved,
+}
+
+/// Where a snapshot's files are.
+#[derive(Clone)]
+enum Saved {
+    Git {
+        commit: GhostCommit,
+        reference: String,
+    },
+    Copy {
+        dir: PathBuf,
+        /// Files copied, relative to the snapshot's `cwd`.
+        files: BTreeSet<PathBuf>,
+    },
+}
+
+/// What restoring a snapshot found.
+enum Restore {
+    Done,
+    /// Refused: these files were created since the snapshot.
+    WouldRemove(Vec<PathBuf>),
+}
+
+impl SnapshotStore {
+    pub(crate) fn new(limits: WorkspaceSnapshots) -> Self {
+        Self {
+            limits,
+            snapshots: Vec::new(),
+            next_id: 0,
+            auto_snapshot_turn: None,
+        }
+    }
+
+    pub(crate) fn list(&self) -> Vec<WorkspaceSnapshot> {
+        self.snapshots
+            .iter()
+            .map(|snapshot| snapshot.info.clone())
+            .collect()
+    }
+
+    /// Keeps `snapshot`; returns the snapshots dropped to stay within
+    /// `max_snapshots`.
+    fn push(&mut self, snapshot: StoredSnapshot) -> Vec<StoredSnapshot> {
+        self.snapshots.push(snapshot);
+        let excess = self
+            .snapshots
+            .len()
+            .saturating_sub(self.limits.max_snapshots);
+        self.snapshots.drain(..excess).collect()
+    }
+
+    fn get(&self, id: &str) -> Option<StoredSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.info.id == id)
+            .cloned()
+    }
+}
+
+/// Takes a snapshot of the turn's working directory and sends
+/// `WorkspaceSnapshotCreated`. `command` is the destructive command it is
+/// taken before, if any.
+pub(crate) async fn take(
+    session: &Session,
+    turn: &TurnContext,
+    reason: WorkspaceSnapshotReason,
+    command: Vec<String>,
+) -> Result<WorkspaceSnapshot, String> {
+    let (id, limits) = {
+        let mut store = session.services.workspace_snapshots.lock().await;
+        store.next_id += 1;
+        (format!("snap-{}", store.next_id), store.limits)
+    };
+    let saved = if get_git_repo_root(&turn.cwd).is_some() {
+        save_git(session, turn, &id).await?
+    } else {
+        save_copy(session, &turn.cwd, &id, limits).await?
+    };
+    let info = WorkspaceSnapshot {
+        id,
+        cwd: turn.cwd.clone(),
+        kind: match saved {
+            Saved::Git { .. } => WorkspaceSnapshotKind::Git,
+            Saved::Copy { .. } => WorkspaceSnapshotKind::Copy,
+        },
+        reason,
+        turn_id: (reason == WorkspaceSnapshotReason::DestructiveCommand)
+            .then(|| turn.sub_id.clone()),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    let dropped = session
+        .services
+        .workspace_snapshots
+        .lock()
+        .await
+        .push(StoredSnapshot {
+            info: info.clone(),
+            saved,
+        });
+    for snapshot in dropped {
+        discard(&snapshot).await;
+    }
+    let event = WorkspaceSnapshotCreatedEvent {
+        snapshot: info.clone(),
+        command,
+    };
+    session
+        .send_event(turn, EventMsg::WorkspaceSnapshotCreated(event))
+        .await;
+    Ok(info)
+}
+
+/// Takes the turn's automatic snapshot when `command` is its first
+/// destructive command. A snapshot that fails is reported as a warning and
+/// not retried in the same turn.
+pub(crate) async fn before_command(
+    session: &Session,
+    turn: &TurnContext,
+    command: &[String],
+    cwd: &Path,
+) {
+    if !classify_command_risk(command, cwd).is_some_and(|risk| risk.is_destructive()) {
+        return;
+    }
+    {
+        let mut store = session.services.workspace_snapshots.lock().await;
+        if !store.limits.before_destructive_commands
+            || store.auto_snapshot_turn.as_deref() == Some(turn.sub_id.as_str())
+        {
+            return;
+        }
+        store.auto_snapshot_turn = Some(turn.sub_id.clone());
+    }
+    let reason = WorkspaceSnapshotReason::DestructiveCommand;
+    if let Err(err) = take(session, turn, reason, command.to_vec()).await {
+        let message = format!(
+            "could not snapshot the working tree before `{}`: {err}",
+            shlex_join(command)
+        );
+        session
+            .send_event(turn, EventMsg::Warning(WarningEvent { message }))
+            .await;
+    }
+}
+
+/// Restores snapshot `id`, refusing to delete files created since it unless
+/// `force`.
+pub(crate) async fn restore(
+    session: &Session,
+    turn: &TurnContext,
+    id: &str,
+    force: bool,
+) -> WorkspaceSnapshotRestoredEvent {
+    let refused = |message: String, would_remove: Vec<PathBuf>| WorkspaceSnapshotRestoredEvent {
+        id: id.to_string(),
+        restored: false,
+        message,
+        would_remove,
+    };
+    let snapshot = session.services.workspace_snapshots.lock().await.get(id);
+    let Some(snapshot) = snapshot else {
+        return refused(format!("no snapshot `{id}` in this session"), Vec::new());
+    };
+    let cwd = snapshot.info.cwd;
+    let ghost_snapshot = turn.ghost_snapshot.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        restore_blocking(&cwd, &snapshot.saved, ghost_snapshot, force)
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    match result {
+        Ok(Restore::Done) => WorkspaceSnapshotRestoredEvent {
+            id: id.to_string(),
+            restored: true,
+            message: format!("restored the working tree to snapshot {id}"),
+            would_remove: Vec::new(),
+        },
+        Ok(Restore::WouldRemove(paths)) => refused(
+            format!(
+                "restoring snapshot {id} would delete {} file(s) created since; \
+                 restore with force to delete them",
+                paths.len()
+            ),
+            paths,
+        ),
+        Err(err) => refused(
+            format!("failed to restore snapshot {id}: {err}"),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Deletes the refs of the session's git snapshots unless
+/// `keep_after_session`; copies go with the session's temp directory.
+pub(crate) async fn shutdown(session: &Session) {
+    let snapshots = {
+        let mut store = session.services.workspace_snapshots.lock().await;
+        if store.limits.keep_after_session {
+            return;
+        }
+        std::mem::take(&mut store.snapshots)
+    };
+    for snapshot in snapshots {
+        discard(&snapshot).await;
+    }
+}
+
+async fn save_git(session: &Session, turn: &TurnContext, id: &str) -> Result<Saved, String> {
+    let cwd = turn.cwd.clone();
+    let ghost_snapshot = turn.ghost_snapshot.clone();
+    let message = format!("codex snapshot {id}");
+    let commit = tokio::task::spawn_blocking(move || {
+        let options = CreateGhostCommitOptions::new(&cwd)
+            .message(&message)
+            .ghost_snapshot(ghost_snapshot);
+        create_ghost_commit_with_report(&options).map(|(commit, _)| commit)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())?;
+    let reference = format!("{REF_PREFIX}/{}/{id}", session.conversation_id);
+    let pinned = run_git_command_with_timeout(&["update-ref", &reference, commit.id()], &turn.cwd)
+        .await
+        .is_some_and(|output| output.status.success());
+    if !pinned {
+        return Err(format!("`git update-ref {reference}` failed"));
+    }
+    Ok(Saved::Git { commit, reference })
+}
+
+async fn save_copy(
+    session: &Session,
+    cwd: &Path,
+    id: &str,
+    limits: WorkspaceSnapshots,
+) -> Result<Saved, String> {
+    let Some(tmpdir) = session.tmpdir().await else {
+        return Err("the session has no temp directory to copy the files into".to_string());
+    };
+    let dir = tmpdir.path().as_path().join("snapshots").join(id);
+    let source = cwd.to_path_buf();
+    let dest = dir.clone();
+    let copied = tokio::task::spawn_blocking(move || copy_tree(&source, &dest, limits))
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|result| result);
+    match copied {
+        Ok(files) => Ok(Saved::Copy { dir, files }),
+        Err(err) => {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            Err(err)
+        }
+    }
+}
+
+/// Removes what keeps a dropped snapshot.
+async fn discard(snapshot: &StoredSnapshot) {
+    match &snapshot.saved {
+        Saved::Git { reference, .. } => {
+            let _ =
+                run_git_command_with_timeout(&["update-ref", "-d", reference], &snapshot.info.cwd)
+                    .await;
+        }
+        Saved::Copy { dir, .. } => {
+            let _ = tokio::fs::remove_dir_all(dir).await;
+        }
+    }
+}
+
+/// The files of `root` a copy snapshot covers, relative to `root`: regular
+/// files not excluded by ignore files, hidden ones included.
+fn list_files(root: &Path) -> BTreeSet<PathBuf> {
+    workspace_walker(root, true)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Copies the files of `root` into `dest`; fails past the copy limits.
+fn copy_tree(
+    root: &Path,
+    dest: &Path,
+    limits: WorkspaceSnapshots,
+) -> Result<BTreeSet<PathBuf>, String> {
+    let files = list_files(root);
+    if files.len() > limits.max_copy_files {
+        return Err(format!(
+            "{} has more than {} files to copy",
+            root.display(),
+            limits.max_copy_files
+        ));
+    }
+    let mut bytes = 0;
+    for file in &files {
+        bytes += std::fs::metadata(root.join(file)).map_or(0, |metadata| metadata.len());
+        if bytes > limits.max_copy_bytes {
+            return Err(format!(
+                "{} has more than {} bytes to copy",
+                root.display(),
+                limits.max_copy_bytes
+            ));
+        }
+    }
+    for file in &files {
+        copy_file(&root.join(file), &dest.join(file))?;
+    }
+    Ok(files)
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| format!("{}: {err}", parent.display()))?;
+    }
+    std::fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|err| format!("{}: {err}", from.display()))
+}
+
+fn restore_blocking(
+    cwd: &Path,
+    saved: &Saved,
+    ghost_snapshot: GhostSnapshotConfig,
+    force: bool,
+) -> Result<Restore, String> {
+    match saved {
+        Saved::Git { commit, .. } => {
+            let created = created_untracked(cwd, commit)?;
+            if !created.is_empty() && !force {
+                return Ok(Restore::WouldRemove(created));
+            }
+            let options = RestoreGhostCommitOptions::new(cwd).ghost_snapshot(ghost_snapshot);
+            restore_ghost_commit_with_options(&options, commit).map_err(|err| err.to_string())?;
+        }
+        Saved::Copy { dir, files } => {
+            let created: Vec<PathBuf> = list_files(cwd).difference(files).cloned().collect();
+            if !created.is_empty() && !force {
+                return Ok(Restore::WouldRemove(
+                    created.iter().map(|path| cwd.join(path)).collect(),
+                ));
+            }
+            for file in files {
+                copy_file(&dir.join(file), &cwd.join(file))?;
+            }
+            for file in created {
+                let path = cwd.join(file);
+                std::fs::remove_file(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+            }
+        }
+    }
+    Ok(Restore::Done)
+}
+
+/// Untracked files under `cwd` that did not exist when `commit` was taken,
+/// as absolute paths.
+fn created_untracked(cwd: &Path, commit: &GhostCommit) -> Result<Vec<PathBuf>, String> {
+    let repo_root = get_git_repo_root(cwd).ok_or("not in a git repository")?;
+    let output = std::process::Command::new("git")
+        .args([
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "-z",
+            "--full-name",
+        ])
+        .current_dir(cwd)
+        .output()
+        .map_err(|err| format!("git ls-files: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git ls-files: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let files = commit.preexisting_untracked_files();
+    let dirs = commit.preexisting_untracked_dirs();
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| !files.contains(path) && !dirs.iter().any(|dir| path.starts_with(dir)))
+        .map(|path| repo_root.join(path))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    #[test]
+    fn copy_snapshots_refuse_to_delete_new_files_unless_forced() {
+        let work = tempfile::tempdir().expect("tempdir");
+        let store = tempfile::tempdir().expect("tempdir");
+        let root = work.path();
+        std::fs::create_dir(root.join("src")).expect("mkdir");
+        std::fs::write(root.join("src/lib.rs"), "before").expect("write");
+        std::fs::write(root.join("notes.txt"), "keep").expect("write");
+
+        let files =
+            copy_tree(root, store.path(), WorkspaceSnapshots::default()).expect("copy tree");
+        let saved = Saved::Copy {
+            dir: store.path().to_path_buf(),
+            files,
+        };
+        std::fs::write(root.join("src/lib.rs"), "after").expect("write");
+        std::fs::remove_file(root.join("notes.txt")).expect("remove");
+        std::fs::write(root.join("new.txt"), "new").expect("write");
+
+        let refused =
+            restore_blocking(root, &saved, GhostSnapshotConfig::default(), false).expect("restore");
+        assert!(
+            matches!(&refused, Restore::WouldRemove(paths) if paths == &vec![root.join("new.txt")])
+        );
+        assert!(root.join("new.txt").exists());
+
+        let forced =
+            restore_blocking(root, &saved, GhostSnapshotConfig::default(), true).expect("restore");
+        assert!(matches!(forced, Restore::Done));
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/lib.rs")).expect("read"),
+            "before"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes.txt")).expect("read"),
+            "keep"
+        );
+        assert!(!root.join("new.txt").exists());
+    }
+
+    #[test]
+    fn copy_snapshots_stop_at_the_file_limit() {
+        let work = tempfile::tempdir().expect("tempdir");
+        let store = tempfile::tempdir().expect("tempdir");
+        for index in 0..3 {
+            std::fs::write(work.path().join(format!("{index}.txt")), "x").expect("write");
+        }
+        let limits = WorkspaceSnapshots {
+            max_copy_files: 2,
+            ..WorkspaceSnapshots::default()
+        };
+
+        assert!(copy_tree(work.path(), store.path(), limits).is_err());
+    }
+
+    #[test]
+    fn git_snapshots_list_untracked_files_created_since() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(repo.path())
+                .status()
+                .expect("run git");
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(repo.path().join("a.txt"), "a").expect("write");
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "first"]);
+        std::fs::write(repo.path().join("old.txt"), "old").expect("write");
+
+        let (commit, _) =
+            create_ghost_commit_with_report(&CreateGhostCommitOptions::new(repo.path()))
+                .expect("ghost commit");
+        std::fs::write(repo.path().join("new.txt"), "new").expect("write");
+        let root = get_git_repo_root(repo.path()).expect("repo root");
+
+        assert_eq!(
+            created_untracked(repo.path(), &commit).expect("untracked"),
+            vec![root.join("new.txt")]
+        );
+    }
+}
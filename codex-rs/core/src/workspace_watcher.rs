@@ -0,0 +1,398 @@
+//! Noticing files changed outside Codex while a session runs.
+//!
+//! With the `workspace_watcher` feature, the session watches its working
+//! directory and workspace roots for the rest of its life. Only files the
+//! agent has read or patched matter: once events have stopped coming for
+//! [`DEBOUNCE`], each changed file the agent saw is looked at again, and one
+//! whose modification time no longer matches the time the agent last saw is
+//! stale. Files excluded by a root's ignore files and anything under `.git`
+//! are skipped. At the start of each turn the model gets a developer message
+//! listing the stale files it was not told about yet, and a patch to a stale
+//! file is flagged the same way as one to a file that changed during the
+//! turn. Reading or patching the file again makes it current. Where native
+//! file watching is unavailable, the roots are polled instead.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::models::DeveloperInstructions;
+use codex_protocol::models::ResponseItem;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use notify::RecursiveMode;
+use notify::Watcher;
+use tokio::sync::mpsc;
+use tokio_util::task::AbortOnDropHandle;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::file_access::modified_time;
+
+/// Quiet time after the last file event before changes are looked at.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often the roots are scanned when they have to be polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Stale files named in one developer message; the rest are counted.
+const MAX_LISTED: usize = 20;
+
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".git/info/exclude"];
+
+pub(crate) struct WorkspaceWatcher {
+    files: Arc<Mutex<WatchedFiles>>,
+    watch: Mutex<Watch>,
+    _debounce: AbortOnDropHandle<()>,
+}
+
+#[derive(Default)]
+struct WatchedFiles {
+    /// Modification time of each file when the agent last read or wrote it;
+    /// `None` for files that did not exist then.
+    seen: HashMap<PathBuf, Option<SystemTime>>,
+    /// Seen files that changed on disk since, and whether the model was
+    /// told.
+    stale: BTreeMap<PathBuf, bool>,
+    /// Ignore rules of each watched root.
+    ignores: Vec<Gitignore>,
+}
+
+struct Watch {
+    watcher: Box<dyn Watcher + Send>,
+    polling: bool,
+    roots: Vec<PathBuf>,
+    tx: mpsc::UnboundedSender<PathBuf>,
+}
+
+impl WorkspaceWatcher {
+    /// Starts a watcher with no roots; see [`Self::sync_roots`]. `None`
+    /// when neither native watching nor polling could be set up.
+    pub(crate) fn start() -> Option<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (watcher, polling) = match native_watcher(tx.clone()) {
+            Ok(watcher) => (watcher, false),
+            Err(err) => {
+                warn!("native file watching is unavailable, polling instead: {err}");
+                (poll_watcher(tx.clone()).ok()?, true)
+            }
+        };
+        let files = Arc::new(Mutex::new(WatchedFiles::default()));
+        let debounce = tokio::spawn(debounce(Arc::clone(&files), rx));
+        Some(Self {
+            files,
+            watch: Mutex::new(Watch {
+                watcher,
+                polling,
+                roots: Vec::new(),
+                tx,
+            }),
+            _debounce: AbortOnDropHandle::new(debounce),
+        })
+    }
+
+    /// Watches exactly `roots`, which may have changed since the last call.
+    pub(crate) fn sync_roots(&self, roots: &[PathBuf]) {
+        let roots: Vec<PathBuf> = roots
+            .iter()
+            .map(|root| dunce::canonicalize(root).unwrap_or_else(|_| root.clone()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let Ok(mut watch) = self.watch.lock() else {
+            return;
+        };
+        if watch.roots == roots {
+            return;
+        }
+        for root in watch.roots.clone() {
+            if !roots.contains(&root) {
+                let _ = watch.watcher.unwatch(&root);
+            }
+        }
+        let added: Vec<PathBuf> = roots
+            .iter()
+            .filter(|root| !watch.roots.contains(root))
+            .cloned()
+            .collect();
+        watch.roots.retain(|root| roots.contains(root));
+        for root in added {
+            watch.add_root(root);
+        }
+        if let Ok(mut files) = self.files.lock() {
+            files.ignores = watch.roots.iter().map(|root| ignore_rules(root)).collect();
+        }
+    }
+
+    /// Remembers `modified` as the time the agent last saw `path`.
+    pub(crate) fn record_seen(&self, path: PathBuf, modified: Option<SystemTime>) {
+        if let Ok(mut files) = self.files.lock() {
+            files.stale.remove(&path);
+            files.seen.insert(path, modified);
+        }
+    }
+
+    /// Whether `path` changed outside Codex since the agent last saw it.
+    pub(crate) fn is_stale(&self, path: &Path) -> bool {
+        self.files
+            .lock()
+            .is_ok_and(|files| files.stale.contains_key(path))
+    }
+
+    /// Stale files the model was not told about yet, which from now on
+    /// count as told.
+    pub(crate) fn take_unreported(&self) -> Vec<PathBuf> {
+        let Ok(mut files) = self.files.lock() else {
+            return Vec::new();
+        };
+        files
+            .stale
+            .iter_mut()
+            .filter(|(_, reported)| !**reported)
+            .map(|(path, reported)| {
+                *reported = true;
+                path.clone()
+            })
+            .collect()
+    }
+}
+
+impl Watch {
+    /// Watches `root`, moving to polling if the native watcher cannot.
+    fn add_root(&mut self, root: PathBuf) {
+        if let Err(err) = self.watcher.watch(&root, RecursiveMode::Recursive) {
+            if self.polling {
+                warn!("failed to watch {}: {err}", root.display());
+                return;
+            }
+            warn!(
+                "failed to watch {}, polling the workspace instead: {err}",
+                root.display()
+            );
+            let Ok(watcher) = poll_watcher(self.tx.clone()) else {
+                return;
+            };
+            self.watcher = watcher;
+            self.polling = true;
+            for root in self.roots.clone() {
+                if let Err(err) = self.watcher.watch(&root, RecursiveMode::Recursive) {
+                    warn!("failed to watch {}: {err}", root.display());
+                }
+            }
+            if let Err(err) = self.watcher.watch(&root, RecursiveMode::Recursive) {
+                warn!("failed to watch {}: {err}", root.display());
+                return;
+            }
+        }
+        self.roots.push(root);
+    }
+}
+
+fn native_watcher(tx: mpsc::UnboundedSender<PathBuf>) -> notify::Result<Box<dyn Watcher + Send>> {
+    Ok(Box::new(notify::recommended_watcher(event_handler(tx))?))
+}
+
+fn poll_watcher(tx: mpsc::UnboundedSender<PathBuf>) -> notify::Result<Box<dyn Watcher + Send>> {
+    let config = notify::Config::default().with_poll_interval(POLL_INTERVAL);
+    Ok(Box::new(notify::PollWatcher::new(
+        event_handler(tx),
+        config,
+    )?))
+}
+
+/// Forwards the paths of events that may have changed a file's contents.
+fn event_handler(
+    tx: mpsc::UnboundedSender<PathBuf>,
+) -> impl FnMut(notify::Result<notify::Event>) + Send + 'static {
+    move |event| {
+        let Ok(event) = event else {
+            return;
+        };
+        if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    }
+}
+
+/// Waits for file events to stop coming for [`DEBOUNCE`], then checks the
+/// files they named.
+async fn debounce(files: Arc<Mutex<WatchedFiles>>, mut rx: mpsc::UnboundedReceiver<PathBuf>) {
+    while let Some(path) = rx.recv().await {
+        let mut changed = BTreeSet::from([path]);
+        while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+            changed.insert(path);
+        }
+        check_changed(&files, changed).await;
+    }
+}
+
+/// Marks the files in `changed` that the agent saw, that no ignore rule
+/// excludes, and whose modification time moved since, as stale.
+async fn check_changed(files: &Mutex<WatchedFiles>, changed: BTreeSet<PathBuf>) {
+    let candidates: Vec<(PathBuf, Option<SystemTime>)> = {
+        let Ok(files) = files.lock() else {
+            return;
+        };
+        changed
+            .into_iter()
+            .filter(|path| !files.is_ignored(path))
+            .filter_map(|path| {
+                let seen = *files.seen.get(&path)?;
+                Some((path, seen))
+            })
+            .collect()
+    };
+    for (path, seen) in candidates {
+        let modified = modified_time(&path).await;
+        if modified == seen {
+            continue;
+        }
+        if let Ok(mut files) = files.lock()
+            && files.seen.get(&path) == Some(&seen)
+        {
+            files.stale.insert(path, false);
+        }
+    }
+}
+
+impl WatchedFiles {
+    fn is_ignored(&self, path: &Path) -> bool {
+        if path
+            .components()
+            .any(|component| component.as_os_str() == ".git")
+        {
+            return true;
+        }
+        self.ignores.iter().any(|ignore| {
+            path.starts_with(ignore.path())
+                && ignore.matched_path_or_any_parents(path, false).is_ignore()
+        })
+    }
+}
+
+/// The rules of the ignore files at the top of `root`.
+fn ignore_rules(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for name in IGNORE_FILES {
+        let path = root.join(name);
+        if path.is_file() {
+            let _ = builder.add(path);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Watches the turn's roots and tells the model which files it saw have
+/// changed outside Codex since.
+pub(crate) async fn start_turn(session: &Session, turn: &TurnContext) {
+    let Some(watcher) = session.workspace_watcher() else {
+        return;
+    };
+    let mut roots = vec![turn.cwd.clone()];
+    roots.extend(
+        turn.workspace_roots
+            .iter()
+            .map(|root| root.path.as_path().to_path_buf()),
+    );
+    watcher.sync_roots(&roots);
+    let stale = watcher.take_unreported();
+    if stale.is_empty() {
+        return;
+    }
+    let mut lines = vec![
+        "These files changed on disk outside Codex since you last read or edited them; \
+         re-read them before relying on their contents or patching them:"
+            .to_string(),
+    ];
+    lines.extend(
+        stale
+            .iter()
+            .take(MAX_LISTED)
+            .map(|path| format!("- {}", turn.display_path(path))),
+    );
+    if stale.len() > MAX_LISTED {
+        lines.push(format!("- ... and {} more", stale.len() - MAX_LISTED));
+    }
+    let message: ResponseItem = DeveloperInstructions::new(lines.join("\n")).into();
+    session.record_conversation_items(turn, &[message]).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn changed_seen_files_become_stale_until_seen_again() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dunce::canonicalize(dir.path()).expect("canonicalize");
+        std::fs::write(root.join(".gitignore"), "target/\n").expect("write");
+        std::fs::create_dir(root.join("target")).expect("mkdir");
+        let seen = root.join("lib.rs");
+        let ignored = root.join("target/out.rs");
+        let unseen = root.join("main.rs");
+        for path in [&seen, &ignored, &unseen] {
+            std::fs::write(path, "old").expect("write");
+        }
+
+        let files = Mutex::new(WatchedFiles {
+            ignores: vec![ignore_rules(&root)],
+            ..Default::default()
+        });
+        for path in [&seen, &ignored] {
+            files
+                .lock()
+                .expect("lock")
+                .seen
+                .insert(path.clone(), Some(SystemTime::UNIX_EPOCH));
+        }
+
+        check_changed(
+            &files,
+            BTreeSet::from([seen.clone(), ignored.clone(), unseen.clone()]),
+        )
+        .await;
+        let stale: Vec<PathBuf> = files.lock().expect("lock").stale.keys().cloned().collect();
+        assert_eq!(stale, vec![seen.clone()]);
+
+        // Seeing the file again makes it current.
+        let modified = modified_time(&seen).await;
+        {
+            let mut files = files.lock().expect("lock");
+            files.stale.clear();
+            files.seen.insert(seen.clone(), modified);
+        }
+        check_changed(&files, BTreeSet::from([seen])).await;
+        assert!(files.lock().expect("lock").stale.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stale_files_are_reported_once() {
+        let watcher = WorkspaceWatcher::start().expect("watcher");
+        let path = PathBuf::from("/workspace/lib.rs");
+        watcher.record_seen(path.clone(), None);
+        watcher
+            .files
+            .lock()
+            .expect("lock")
+            .stale
+            .insert(path.clone(), false);
+
+        assert!(watcher.is_stale(&path));
+        assert_eq!(watcher.take_unreported(), vec![path.clone()]);
+        assert_eq!(watcher.take_unreported(), Vec::<PathBuf>::new());
+        assert!(watcher.is_stale(&path));
+
+        watcher.record_seen(path.clone(), None);
+        assert!(!watcher.is_stale(&path));
+    }
+}
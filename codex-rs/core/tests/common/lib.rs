@@ -219,6 +219,33 @@ where
     }
 }
 
+/// Events of the current turn up to and including `TurnComplete`, waiting at
+/// most ten seconds for each.
+pub async fn turn_events(codex: &CodexThread) -> Vec<codex_core::protocol::EventMsg> {
+    let mut events = Vec::new();
+    loop {
+        let event =
+            wait_for_event_with_timeout(codex, |_| true, tokio::time::Duration::from_secs(10))
+                .await;
+        let done = matches!(event, codex_core::protocol::EventMsg::TurnComplete(_));
+        events.push(event);
+        if done {
+            return events;
+        }
+    }
+}
+
+/// The text of each `AgentMessage` in `events`.
+pub fn agent_messages(events: &[codex_core::protocol::EventMsg]) -> Vec<String> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            codex_core::protocol::EventMsg::AgentMessage(message) => Some(message.message.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
 pub fn sandbox_env_var() -> &'static str {
     codex_core::spawn::CODEX_SANDBOX_ENV_VAR
 }
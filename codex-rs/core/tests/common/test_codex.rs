@@ -17,6 +17,8 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::user_input::UserInput;
 use serde_json::Value;
@@ -25,8 +27,10 @@ use wiremock::MockServer;
 
 use crate::load_default_config_for_test;
 use crate::responses::WebSocketTestServer;
+use crate::responses::mount_sse_sequence;
 use crate::responses::start_mock_server;
 use crate::streaming_sse::StreamingSseServer;
+use crate::turn_events;
 use crate::wait_for_event;
 use wiremock::Match;
 use wiremock::matchers::path_regex;
@@ -118,6 +122,17 @@ impl TestCodexBuilder {
             .await
     }
 
+    /// Builds against `model` instead of a mock HTTP server.
+    pub async fn build_with_scripted_model(
+        &mut self,
+        model: &ScriptedModel,
+    ) -> anyhow::Result<TestCodex> {
+        let home = Arc::new(TempDir::new()?);
+        let base_url = model.provider().base_url.unwrap_or_default();
+        self.build_with_home_and_base_url(base_url, home, None)
+            .await
+    }
+
     pub async fn resume(
         &mut self,
         server: &wiremock::MockServer,
@@ -264,6 +279,34 @@ impl TestCodex {
         prompt: &str,
         approval_policy: AskForApproval,
         sandbox_policy: SandboxPolicy,
+    ) -> Result<()> {
+        self.submit_user_turn(prompt, approval_policy, sandbox_policy)
+            .await?;
+        wait_for_event(&self.codex, |event| {
+            matches!(event, EventMsg::TurnComplete(_))
+        })
+        .await;
+        Ok(())
+    }
+
+    /// Runs one turn and returns its events up to and including
+    /// `TurnComplete`, waiting at most ten seconds for each.
+    pub async fn submit_turn_collect_events(
+        &self,
+        prompt: &str,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+    ) -> Result<Vec<EventMsg>> {
+        self.submit_user_turn(prompt, approval_policy, sandbox_policy)
+            .await?;
+        Ok(turn_events(&self.codex).await)
+    }
+
+    async fn submit_user_turn(
+        &self,
+        prompt: &str,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
     ) -> Result<()> {
         let session_model = self.session_configured.model.clone();
         self.codex
@@ -283,17 +326,18 @@ impl TestCodex {
                 personality: None,
             })
             .await?;
-
-        wait_for_event(&self.codex, |event| {
-            matches!(event, EventMsg::TurnComplete(_))
-        })
-        .await;
         Ok(())
     }
 }
 
+/// Where a [`TestCodexHarness`] gets its model responses from.
+enum ModelBackend {
+    Server(MockServer),
+    Scripted(ScriptedModel),
+}
+
 pub struct TestCodexHarness {
-    server: MockServer,
+    backend: ModelBackend,
     test: TestCodex,
 }
 
@@ -309,11 +353,50 @@ impl TestCodexHarness {
     pub async fn with_builder(mut builder: TestCodexBuilder) -> Result<Self> {
         let server = start_mock_server().await;
         let test = builder.build(&server).await?;
-        Ok(Self { server, test })
+        Ok(Self {
+            backend: ModelBackend::Server(server),
+            test,
+        })
+    }
+
+    /// Like [`Self::with_builder`], answering from an in-process
+    /// [`ScriptedModel`] instead of a mock HTTP server.
+    pub async fn scripted_with_builder(mut builder: TestCodexBuilder) -> Result<Self> {
+        let model = ScriptedModel::new([]);
+        let test = builder.build_with_scripted_model(&model).await?;
+        Ok(Self {
+            backend: ModelBackend::Scripted(model),
+            test,
+        })
     }
 
     pub fn server(&self) -> &MockServer {
-        &self.server
+        match &self.backend {
+            ModelBackend::Server(server) => server,
+            ModelBackend::Scripted(_) => panic!("this harness uses a scripted model"),
+        }
+    }
+
+    pub fn scripted_model(&self) -> &ScriptedModel {
+        match &self.backend {
+            ModelBackend::Scripted(model) => model,
+            ModelBackend::Server(_) => panic!("this harness uses a mock server"),
+        }
+    }
+
+    /// Queues one response per SSE body, on whichever backend the harness
+    /// uses.
+    pub async fn mount_sse_sequence(&self, bodies: Vec<String>) {
+        match &self.backend {
+            ModelBackend::Server(server) => {
+                mount_sse_sequence(server, bodies).await;
+            }
+            ModelBackend::Scripted(model) => {
+                for body in bodies {
+                    model.push_turn(ScriptedTurn::sse(body));
+                }
+            }
+        }
     }
 
     pub fn test(&self) -> &TestCodex {
@@ -343,8 +426,12 @@ impl TestCodexHarness {
     }
 
     pub async fn request_bodies(&self) -> Vec<Value> {
+        let server = match &self.backend {
+            ModelBackend::Server(server) => server,
+            ModelBackend::Scripted(model) => return model.requests(),
+        };
         let path_matcher = path_regex(".*/responses$");
-        self.server
+        server
             .received_requests()
             .await
             .expect("mock server should not fail")
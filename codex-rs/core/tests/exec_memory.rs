@@ -0,0 +1,85 @@
+//! Measures this process's resident memory while a command floods its
+//! output. It is its own test binary so no other test shares the process
+//! and skews the numbers.
+#![cfg(target_os = "linux")]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::exec::ExecParams;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::sandboxing::SandboxPermissions;
+use pretty_assertions::assert_eq;
+use tokio_util::sync::CancellationToken;
+
+/// How much of each stream `exec` keeps in memory.
+const EXEC_OUTPUT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Resident set size of this process, in KiB.
+fn resident_kib() -> u64 {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("VmRSS:"))
+                .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn capturing_100_mib_of_output_keeps_memory_bounded() -> Result<()> {
+    const OUTPUT_BYTES: u64 = 100 * 1024 * 1024;
+    let cwd = std::env::current_dir()?;
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            format!(
+                "yes 0123456789 | head -c {OUTPUT_BYTES}; yes err | head -c {OUTPUT_BYTES} >&2"
+            ),
+        ],
+        cwd: cwd.clone(),
+        expiration: 60_000.into(),
+        env: std::env::vars().collect(),
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        arg0: None,
+    };
+
+    let baseline = resident_kib();
+    let done = CancellationToken::new();
+    let sampler = tokio::spawn({
+        let done = done.clone();
+        async move {
+            let mut peak = 0;
+            while !done.is_cancelled() {
+                peak = peak.max(resident_kib());
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            peak
+        }
+    });
+    let output =
+        process_exec_tool_call(params, &SandboxPolicy::DangerFullAccess, &cwd, &None, None).await?;
+    done.cancel();
+    let peak = sampler.await?.max(resident_kib());
+
+    assert_eq!(
+        output.stdout.omitted_bytes as u64,
+        OUTPUT_BYTES - EXEC_OUTPUT_MAX_BYTES
+    );
+    assert_eq!(
+        output.stderr.omitted_bytes as u64,
+        OUTPUT_BYTES - EXEC_OUTPUT_MAX_BYTES
+    );
+    // Three capped buffers plus read chunks; well under one copy of the output.
+    let growth_kib = peak.saturating_sub(baseline);
+    assert!(
+        growth_kib < 64 * 1024,
+        "resident memory grew by {growth_kib} KiB while capturing {OUTPUT_BYTES} bytes per stream"
+    );
+    Ok(())
+}
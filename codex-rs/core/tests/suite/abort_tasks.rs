@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
 use codex_protocol::user_input::UserInput;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_function_call;
@@ -60,6 +61,14 @@ async fn interrupt_long_running_tool_emits_turn_aborted() {
 
     codex.submit(Op::Interrupt).await.unwrap();
 
+    // The running call is reported as aborted before the turn is.
+    let aborted = wait_for_event(&codex, |ev| matches!(ev, EventMsg::ToolCallAborted(_))).await;
+    let EventMsg::ToolCallAborted(aborted) = aborted else {
+        unreachable!("matched ToolCallAborted");
+    };
+    assert_eq!(aborted.call_id, "call_sleep");
+    assert_eq!(aborted.tool_name, "shell_command");
+
     // Expect TurnAborted soon after.
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
 }
@@ -239,3 +248,80 @@ async fn interrupt_persists_turn_aborted_marker_in_next_request() {
         "expected <turn_aborted> marker in follow-up request"
     );
 }
+
+/// A soft interrupt lets the running command finish, refuses the calls after it in the same
+/// response and ends the turn without another request; the refusal reaches the model with the
+/// next turn.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn soft_interrupt_finishes_the_running_tool_call_then_ends_the_turn() {
+    let first_args = json!({ "command": "sleep 1; echo migrated" }).to_string();
+    let second_args = json!({ "command": "echo next" }).to_string();
+    let first_body = sse(vec![
+        ev_response_created("resp-soft"),
+        ev_function_call("call-running", "shell_command", &first_args),
+        ev_function_call("call-refused", "shell_command", &second_args),
+        ev_completed("resp-soft"),
+    ]);
+    let follow_up_body = sse(vec![
+        ev_response_created("resp-followup"),
+        ev_completed("resp-followup"),
+    ]);
+
+    let server = start_mock_server().await;
+    let response_mock = mount_sse_sequence(&server, vec![first_body, follow_up_body]).await;
+
+    let codex = test_codex()
+        .with_model("gpt-5.1")
+        .build(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "run the migration".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+    codex.submit(Op::SoftInterrupt).await.unwrap();
+
+    let EventMsg::ExecCommandEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandEnd(_))).await
+    else {
+        unreachable!("waited for ExecCommandEnd");
+    };
+    assert_eq!((end.call_id.as_str(), end.exit_code), ("call-running", 0));
+    assert_matches!(
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await,
+        EventMsg::TurnAborted(ev) if ev.reason == TurnAbortReason::SoftInterrupt
+    );
+    assert_eq!(response_mock.requests().len(), 1);
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "what happened?".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2);
+    let refused = requests[1]
+        .function_call_output_text("call-refused")
+        .expect("refused call output");
+    assert!(
+        refused.contains("turn ended by user"),
+        "unexpected output: {refused}"
+    );
+}
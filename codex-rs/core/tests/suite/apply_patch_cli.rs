@@ -14,6 +14,7 @@ use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::user_input::UserInput;
@@ -23,7 +24,6 @@ use core_test_support::responses::ev_assistant_message;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_function_call;
 use core_test_support::responses::ev_response_created;
-use core_test_support::responses::mount_sse_sequence;
 use core_test_support::responses::sse;
 use core_test_support::skip_if_no_network;
 use core_test_support::test_codex::TestCodexBuilder;
@@ -42,13 +42,19 @@ pub async fn apply_patch_harness() -> Result<TestCodexHarness> {
     apply_patch_harness_with(|builder| builder).await
 }
 
+/// A harness answered by an in-process scripted model.
 async fn apply_patch_harness_with(
     configure: impl FnOnce(TestCodexBuilder) -> TestCodexBuilder,
 ) -> Result<TestCodexHarness> {
-    let builder = configure(test_codex()).with_config(|config| {
+    TestCodexHarness::scripted_with_builder(apply_patch_builder(configure)).await
+}
+
+fn apply_patch_builder(
+    configure: impl FnOnce(TestCodexBuilder) -> TestCodexBuilder,
+) -> TestCodexBuilder {
+    configure(test_codex()).with_config(|config| {
         config.include_apply_patch_tool = true;
-    });
-    TestCodexHarness::with_builder(builder).await
+    })
 }
 
 pub async fn mount_apply_patch(
@@ -58,11 +64,14 @@ pub async fn mount_apply_patch(
     assistant_msg: &str,
     output_type: ApplyPatchModelOutput,
 ) {
-    mount_sse_sequence(
-        harness.server(),
-        apply_patch_responses(call_id, patch, assistant_msg, output_type),
-    )
-    .await;
+    harness
+        .mount_sse_sequence(apply_patch_responses(
+            call_id,
+            patch,
+            assistant_msg,
+            output_type,
+        ))
+        .await;
 }
 
 fn apply_patch_responses(
@@ -92,8 +101,6 @@ fn apply_patch_responses(
 async fn apply_patch_cli_multiple_operations_integration(
     output_type: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     // Seed workspace state
@@ -138,8 +145,6 @@ D delete.txt
 #[test_case(ApplyPatchModelOutput::ShellViaHeredoc)]
 #[test_case(ApplyPatchModelOutput::ShellCommandViaHeredoc)]
 async fn apply_patch_cli_multiple_chunks(model_output: ApplyPatchModelOutput) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("multi.txt");
@@ -167,8 +172,6 @@ async fn apply_patch_cli_multiple_chunks(model_output: ApplyPatchModelOutput) ->
 async fn apply_patch_cli_moves_file_to_new_directory(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let original = harness.path("old/name.txt");
@@ -196,8 +199,6 @@ async fn apply_patch_cli_moves_file_to_new_directory(
 async fn apply_patch_cli_updates_file_appends_trailing_newline(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("no_newline.txt");
@@ -224,8 +225,6 @@ async fn apply_patch_cli_updates_file_appends_trailing_newline(
 async fn apply_patch_cli_insert_only_hunk_modifies_file(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("insert_only.txt");
@@ -250,8 +249,6 @@ async fn apply_patch_cli_insert_only_hunk_modifies_file(
 async fn apply_patch_cli_move_overwrites_existing_destination(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let original = harness.path("old/name.txt");
@@ -281,8 +278,6 @@ async fn apply_patch_cli_move_overwrites_existing_destination(
 async fn apply_patch_cli_move_without_content_change_has_no_turn_diff(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -342,8 +337,6 @@ async fn apply_patch_cli_move_without_content_change_has_no_turn_diff(
 async fn apply_patch_cli_add_overwrites_existing_file(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let path = harness.path("duplicate.txt");
@@ -368,8 +361,6 @@ async fn apply_patch_cli_add_overwrites_existing_file(
 async fn apply_patch_cli_rejects_invalid_hunk_header(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let patch = "*** Begin Patch\n*** Frobnicate File: foo\n*** End Patch";
@@ -400,8 +391,6 @@ async fn apply_patch_cli_rejects_invalid_hunk_header(
 async fn apply_patch_cli_reports_missing_context(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("modify.txt");
@@ -434,8 +423,6 @@ async fn apply_patch_cli_reports_missing_context(
 async fn apply_patch_cli_reports_missing_target_file(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let patch = "*** Begin Patch\n*** Update File: missing.txt\n@@\n-nope\n+better\n*** End Patch";
@@ -470,8 +457,6 @@ async fn apply_patch_cli_reports_missing_target_file(
 async fn apply_patch_cli_delete_missing_file_reports_error(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let patch = "*** Begin Patch\n*** Delete File: missing.txt\n*** End Patch";
@@ -505,8 +490,6 @@ async fn apply_patch_cli_delete_missing_file_reports_error(
 #[test_case(ApplyPatchModelOutput::ShellViaHeredoc)]
 #[test_case(ApplyPatchModelOutput::ShellCommandViaHeredoc)]
 async fn apply_patch_cli_rejects_empty_patch(model_output: ApplyPatchModelOutput) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let patch = "*** Begin Patch\n*** End Patch";
@@ -532,8 +515,6 @@ async fn apply_patch_cli_rejects_empty_patch(model_output: ApplyPatchModelOutput
 async fn apply_patch_cli_delete_directory_reports_verification_error(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     fs::create_dir(harness.path("dir"))?;
@@ -559,8 +540,6 @@ async fn apply_patch_cli_delete_directory_reports_verification_error(
 async fn apply_patch_cli_rejects_path_traversal_outside_workspace(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let escape_path = harness
@@ -612,8 +591,6 @@ async fn apply_patch_cli_rejects_path_traversal_outside_workspace(
 async fn apply_patch_cli_rejects_move_path_traversal_outside_workspace(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let escape_path = harness
@@ -666,8 +643,6 @@ async fn apply_patch_cli_rejects_move_path_traversal_outside_workspace(
 async fn apply_patch_cli_verification_failure_has_no_side_effects(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness_with(|builder| {
         builder.with_config(|config| {
             config.features.enable(Feature::ApplyPatchFreeform);
@@ -693,8 +668,6 @@ async fn apply_patch_cli_verification_failure_has_no_side_effects(
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn apply_patch_shell_command_heredoc_with_cd_updates_relative_workdir() -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     // Prepare a file inside a subdir; update it via cd && apply_patch heredoc form.
@@ -716,7 +689,7 @@ async fn apply_patch_shell_command_heredoc_with_cd_updates_relative_workdir() ->
             ev_completed("resp-2"),
         ]),
     ];
-    mount_sse_sequence(harness.server(), bodies).await;
+    harness.mount_sse_sequence(bodies).await;
 
     harness.submit("apply via shell heredoc with cd").await?;
 
@@ -733,7 +706,12 @@ async fn apply_patch_shell_command_heredoc_with_cd_updates_relative_workdir() ->
 async fn apply_patch_cli_can_use_shell_command_output_as_patch_input() -> Result<()> {
     skip_if_no_network!(Ok(()));
 
-    let harness = apply_patch_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
+    // Each response depends on the previous tool output, so this one stays on
+    // a mock server.
+    let harness = TestCodexHarness::with_builder(apply_patch_builder(|builder| {
+        builder.with_model("gpt-5.1")
+    }))
+    .await?;
 
     let source_contents = "line1\nnaïve café\nline3\n";
     let source_path = harness.path("source.txt");
@@ -857,8 +835,6 @@ async fn apply_patch_cli_can_use_shell_command_output_as_patch_input() -> Result
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -884,7 +860,7 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
             ev_completed("resp-2"),
         ]),
     ];
-    mount_sse_sequence(harness.server(), bodies).await;
+    harness.mount_sse_sequence(bodies).await;
 
     let model = test.session_configured.model.clone();
     codex
@@ -907,19 +883,23 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
 
     let mut saw_turn_diff = None;
     let mut saw_patch_begin = false;
+    let mut patch_turn_id = None;
     let mut patch_end_success = None;
     wait_for_event(&codex, |event| match event {
         EventMsg::PatchApplyBegin(begin) => {
             saw_patch_begin = true;
             assert_eq!(begin.call_id, call_id);
+            patch_turn_id = Some(begin.turn_id.clone());
             false
         }
         EventMsg::PatchApplyEnd(end) => {
             assert_eq!(end.call_id, call_id);
+            assert_eq!(Some(&end.turn_id), patch_turn_id.as_ref());
             patch_end_success = Some(end.success);
             false
         }
         EventMsg::TurnDiff(ev) => {
+            assert_eq!(Some(&ev.turn_id), patch_turn_id.as_ref());
             saw_turn_diff = Some(ev.unified_diff.clone());
             false
         }
@@ -940,8 +920,6 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn apply_patch_shell_command_failure_propagates_error_and_skips_diff() -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -964,7 +942,7 @@ async fn apply_patch_shell_command_failure_propagates_error_and_skips_diff() ->
             ev_completed("resp-2"),
         ]),
     ];
-    mount_sse_sequence(harness.server(), bodies).await;
+    harness.mount_sse_sequence(bodies).await;
 
     let model = test.session_configured.model.clone();
     codex
@@ -1020,8 +998,6 @@ async fn apply_patch_shell_command_failure_propagates_error_and_skips_diff() ->
 async fn apply_patch_function_accepts_lenient_heredoc_wrapped_patch(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let file_name = "lenient.txt";
@@ -1044,8 +1020,6 @@ async fn apply_patch_function_accepts_lenient_heredoc_wrapped_patch(
 #[test_case(ApplyPatchModelOutput::ShellViaHeredoc)]
 #[test_case(ApplyPatchModelOutput::ShellCommandViaHeredoc)]
 async fn apply_patch_cli_end_of_file_anchor(model_output: ApplyPatchModelOutput) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("tail.txt");
@@ -1069,8 +1043,6 @@ async fn apply_patch_cli_end_of_file_anchor(model_output: ApplyPatchModelOutput)
 async fn apply_patch_cli_missing_second_chunk_context_rejected(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("two_chunks.txt");
@@ -1104,8 +1076,6 @@ async fn apply_patch_cli_missing_second_chunk_context_rejected(
 async fn apply_patch_emits_turn_diff_event_with_unified_diff(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -1163,8 +1133,6 @@ async fn apply_patch_emits_turn_diff_event_with_unified_diff(
 async fn apply_patch_turn_diff_for_rename_with_content_change(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -1222,8 +1190,6 @@ async fn apply_patch_turn_diff_for_rename_with_content_change(
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn apply_patch_aggregates_diff_across_multiple_tool_calls() -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -1248,7 +1214,7 @@ async fn apply_patch_aggregates_diff_across_multiple_tool_calls() -> Result<()>
         ev_assistant_message("msg-1", "ok"),
         ev_completed("resp-3"),
     ]);
-    mount_sse_sequence(harness.server(), vec![s1, s2, s3]).await;
+    harness.mount_sse_sequence(vec![s1, s2, s3]).await;
 
     let model = test.session_configured.model.clone();
     codex
@@ -1290,8 +1256,6 @@ async fn apply_patch_aggregates_diff_across_multiple_tool_calls() -> Result<()>
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn apply_patch_aggregates_diff_preserves_success_after_failure() -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
     let test = harness.test();
     let codex = test.codex.clone();
@@ -1319,7 +1283,7 @@ async fn apply_patch_aggregates_diff_preserves_success_after_failure() -> Result
             ev_completed("resp-3"),
         ]),
     ];
-    mount_sse_sequence(harness.server(), responses).await;
+    harness.mount_sse_sequence(responses).await;
 
     let model = test.session_configured.model.clone();
     codex
@@ -1387,8 +1351,6 @@ async fn apply_patch_aggregates_diff_preserves_success_after_failure() -> Result
 async fn apply_patch_change_context_disambiguates_target(
     model_output: ApplyPatchModelOutput,
 ) -> Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = apply_patch_harness().await?;
 
     let target = harness.path("multi_ctx.txt");
@@ -1405,3 +1367,109 @@ async fn apply_patch_change_context_disambiguates_target(
     assert_eq!(contents, "fn a\nx=10\ny=2\nfn b\nx=11\ny=20\n");
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[test_case(ApplyPatchModelOutput::Function, AskForApproval::Never)]
+#[test_case(ApplyPatchModelOutput::Function, AskForApproval::OnFailure)]
+#[test_case(ApplyPatchModelOutput::Function, AskForApproval::OnRequest)]
+#[test_case(ApplyPatchModelOutput::Function, AskForApproval::UnlessTrusted)]
+#[test_case(ApplyPatchModelOutput::ShellViaHeredoc, AskForApproval::Never)]
+#[test_case(ApplyPatchModelOutput::ShellViaHeredoc, AskForApproval::OnFailure)]
+#[test_case(ApplyPatchModelOutput::ShellViaHeredoc, AskForApproval::OnRequest)]
+#[test_case(ApplyPatchModelOutput::ShellViaHeredoc, AskForApproval::UnlessTrusted)]
+#[test_case(ApplyPatchModelOutput::ShellCommandViaHeredoc, AskForApproval::Never)]
+#[test_case(
+    ApplyPatchModelOutput::ShellCommandViaHeredoc,
+    AskForApproval::OnFailure
+)]
+#[test_case(
+    ApplyPatchModelOutput::ShellCommandViaHeredoc,
+    AskForApproval::OnRequest
+)]
+#[test_case(
+    ApplyPatchModelOutput::ShellCommandViaHeredoc,
+    AskForApproval::UnlessTrusted
+)]
+async fn apply_patch_auto_approved_follows_patch_policy_for_every_invocation(
+    model_output: ApplyPatchModelOutput,
+    approval_policy: AskForApproval,
+) -> Result<()> {
+    let harness = apply_patch_harness().await?;
+    let test = harness.test();
+    let codex = test.codex.clone();
+    let cwd = test.cwd.clone();
+
+    let patch = "*** Begin Patch\n*** Add File: approved.txt\n+hello\n*** End Patch";
+    let call_id = "apply-approval-semantics";
+    mount_apply_patch(&harness, call_id, patch, "ok", model_output).await;
+
+    let model = test.session_configured.model.clone();
+    codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "apply the patch".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+
+    // Only `UnlessTrusted` asks about patches; the shell command that carries
+    // the patch must not change that.
+    let needs_approval = approval_policy == AskForApproval::UnlessTrusted;
+    let mut auto_approved = None;
+    let mut saw_approval_request = false;
+    let mut saw_turn_diff = false;
+    loop {
+        let event = wait_for_event(&codex, |event| {
+            matches!(
+                event,
+                EventMsg::PatchApplyBegin(_)
+                    | EventMsg::ApplyPatchApprovalRequest(_)
+                    | EventMsg::ExecApprovalRequest(_)
+                    | EventMsg::TurnDiff(_)
+                    | EventMsg::TurnComplete(_)
+            )
+        })
+        .await;
+        match event {
+            EventMsg::PatchApplyBegin(begin) => {
+                assert_eq!(begin.call_id, call_id);
+                auto_approved = Some(begin.auto_approved);
+            }
+            EventMsg::ApplyPatchApprovalRequest(request) => {
+                assert_eq!(request.call_id, call_id);
+                saw_approval_request = true;
+                codex
+                    .submit(Op::PatchApproval {
+                        id: "0".into(),
+                        decision: ReviewDecision::Approved,
+                    })
+                    .await?;
+            }
+            EventMsg::ExecApprovalRequest(request) => {
+                panic!("unexpected exec approval request: {:?}", request.call_id)
+            }
+            EventMsg::TurnDiff(_) => saw_turn_diff = true,
+            EventMsg::TurnComplete(_) => break,
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    assert_eq!(auto_approved, Some(!needs_approval));
+    assert_eq!(saw_approval_request, needs_approval);
+    assert!(saw_turn_diff, "expected a turn diff for the added file");
+    assert_eq!(
+        fs::read_to_string(cwd.path().join("approved.txt"))?,
+        "hello\n"
+    );
+    Ok(())
+}
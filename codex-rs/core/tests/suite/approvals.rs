@@ -30,6 +30,7 @@ use pretty_assertions::assert_eq;
 use regex_lite::Regex;
 use serde_json::Value;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
@@ -1839,3 +1840,87 @@ async fn approving_execpolicy_amendment_persists_policy_and_skips_future_prompts
 
     Ok(())
 }
+
+#[tokio::test(flavor = "current_thread")]
+#[cfg(unix)]
+async fn session_approval_does_not_cover_different_injected_env() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let approval_policy = AskForApproval::UnlessTrusted;
+    let sandbox_policy = SandboxPolicy::ReadOnly;
+    let sandbox_policy_for_config = sandbox_policy.clone();
+    let mut builder = test_codex().with_config(move |config| {
+        config.approval_policy = Constrained::allow_any(approval_policy);
+        config.sandbox_policy = Constrained::allow_any(sandbox_policy_for_config);
+    });
+    let test = builder.build(&server).await?;
+
+    let greeting_call = |call_id: &str, greeting: &str| -> Result<Value> {
+        let args = serde_json::to_string(&json!({
+            "command": "echo hi",
+            "env": { "GREETING": greeting },
+        }))?;
+        Ok(ev_function_call(call_id, "shell_command", &args))
+    };
+    let turns = [
+        ("env-first", "a"),
+        ("env-same", "a"),
+        ("env-different", "b"),
+    ];
+    for (call_id, greeting) in turns {
+        let _ = mount_sse_once(
+            &server,
+            sse(vec![
+                ev_response_created(&format!("resp-{call_id}")),
+                greeting_call(call_id, greeting)?,
+                ev_completed(&format!("resp-{call_id}")),
+            ]),
+        )
+        .await;
+        let _ = mount_sse_once(
+            &server,
+            sse(vec![
+                ev_assistant_message(&format!("msg-{call_id}"), "done"),
+                ev_completed(&format!("resp-{call_id}-done")),
+            ]),
+        )
+        .await;
+    }
+
+    // Calls that set variables always ask, and show what they set.
+    submit_turn(&test, "env-first", approval_policy, sandbox_policy.clone()).await?;
+    let approval = expect_exec_approval(&test, "echo hi").await;
+    assert_eq!(
+        approval.injected_env,
+        BTreeMap::from([("GREETING".to_string(), "a".to_string())])
+    );
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id,
+            decision: ReviewDecision::ApprovedForSession,
+        })
+        .await?;
+    wait_for_completion(&test).await;
+
+    // The session approval covers the same command with the same variables...
+    submit_turn(&test, "env-same", approval_policy, sandbox_policy.clone()).await?;
+    wait_for_completion_without_approval(&test).await;
+
+    // ...but not the same command with a different value.
+    submit_turn(&test, "env-different", approval_policy, sandbox_policy).await?;
+    let approval = expect_exec_approval(&test, "echo hi").await;
+    assert_eq!(
+        approval.injected_env,
+        BTreeMap::from([("GREETING".to_string(), "b".to_string())])
+    );
+    test.codex
+        .submit(Op::ExecApproval {
+            id: approval.turn_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await?;
+    wait_for_completion(&test).await;
+
+    Ok(())
+}
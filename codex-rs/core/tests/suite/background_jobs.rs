@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::features::Feature;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::BackgroundJobStatus;
+use codex_core::protocol::BackgroundJobStatusEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+fn job_statuses(events: &[EventMsg]) -> Vec<BackgroundJobStatus> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::BackgroundJobStatus(ev) => Some(ev.status),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn wait_for_job_end(test: &TestCodex) -> BackgroundJobStatusEvent {
+    let event = wait_for_event_with_timeout(
+        &test.codex,
+        |event| matches!(event, EventMsg::BackgroundJobStatus(ev) if ev.status.is_finished()),
+        Duration::from_secs(20),
+    )
+    .await;
+    let EventMsg::BackgroundJobStatus(event) = event else {
+        unreachable!("waited for BackgroundJobStatus");
+    };
+    event
+}
+
+fn tool_output(request: &Value, call_id: &str) -> Option<String> {
+    request["input"].as_array()?.iter().find_map(|item| {
+        (item["type"] == "function_call_output" && item["call_id"] == call_id)
+            .then(|| item["output"].as_str().map(str::to_string))
+            .flatten()
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn jobs_outlive_their_turn_and_keep_their_output() -> Result<()> {
+    skip_if_windows!(Ok(()));
+
+    let model = ScriptedModel::new([
+        ScriptedTurn::new().function_call(
+            "call-1",
+            "shell_command",
+            &json!({"command": "sleep 1; echo built", "background": true}),
+        ),
+        ScriptedTurn::new().message("build started"),
+        ScriptedTurn::new().function_call("call-2", "get_background_job", &json!({"id": "job-1"})),
+        ScriptedTurn::new().message("build done"),
+    ]);
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.features.enable(Feature::BackgroundJobs))
+        .build_with_scripted_model(&model)
+        .await?;
+
+    let events = test
+        .submit_turn_collect_events(
+            "start the build",
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+        )
+        .await?;
+    assert_eq!(job_statuses(&events), vec![BackgroundJobStatus::Running]);
+    let started = tool_output(&model.requests()[1], "call-1").expect("shell_command output");
+    assert!(started.contains("job-1"), "unexpected output: {started}");
+
+    let ended = wait_for_job_end(&test).await;
+    assert_eq!(
+        (ended.job_id.as_str(), ended.call_id.as_str(), ended.status),
+        ("job-1", "call-1", BackgroundJobStatus::Completed)
+    );
+    assert_eq!(ended.exit_code, Some(0));
+
+    test.submit_turn_collect_events(
+        "is it done?",
+        AskForApproval::Never,
+        SandboxPolicy::DangerFullAccess,
+    )
+    .await?;
+    let job: Value = serde_json::from_str(
+        &tool_output(&model.requests()[3], "call-2").expect("get_background_job output"),
+    )?;
+    assert_eq!(job["status"], "completed");
+    assert_eq!(job["output"].as_str().map(str::trim), Some("built"));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shutdown_kills_running_jobs_and_records_their_status() -> Result<()> {
+    skip_if_windows!(Ok(()));
+
+    let model = ScriptedModel::new([
+        ScriptedTurn::new().function_call(
+            "call-1",
+            "shell_command",
+            &json!({"command": "sleep 30", "background": true}),
+        ),
+        ScriptedTurn::new().message("started"),
+    ]);
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.features.enable(Feature::BackgroundJobs))
+        .build_with_scripted_model(&model)
+        .await?;
+
+    test.submit_turn_collect_events(
+        "start the server",
+        AskForApproval::Never,
+        SandboxPolicy::DangerFullAccess,
+    )
+    .await?;
+    test.codex.submit(Op::Shutdown).await?;
+
+    let ended = wait_for_job_end(&test).await;
+    assert_eq!(ended.status, BackgroundJobStatus::Killed);
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ShutdownComplete)
+    })
+    .await;
+
+    let rollout_path = test.codex.rollout_path().expect("rollout path");
+    let rollout = std::fs::read_to_string(rollout_path)?;
+    assert!(
+        rollout
+            .lines()
+            .any(|line| line.contains("\"background_job_status\"")
+                && line.contains("\"status\":\"killed\"")),
+        "killed job missing from rollout"
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn background_is_refused_without_the_feature() -> Result<()> {
+    skip_if_windows!(Ok(()));
+
+    let model = ScriptedModel::new([
+        ScriptedTurn::new().function_call(
+            "call-1",
+            "shell_command",
+            &json!({"command": "echo hi", "background": true}),
+        ),
+        ScriptedTurn::new().message("ok"),
+    ]);
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .build_with_scripted_model(&model)
+        .await?;
+
+    let events = test
+        .submit_turn_collect_events(
+            "start it",
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+        )
+        .await?;
+
+    assert_eq!(job_statuses(&events), Vec::new());
+    let output = tool_output(&model.requests()[1], "call-1").expect("shell_command output");
+    assert!(
+        output.contains("background jobs are not enabled"),
+        "unexpected output: {output}"
+    );
+    Ok(())
+}
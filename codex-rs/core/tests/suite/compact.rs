@@ -21,6 +21,7 @@ use core_test_support::load_default_config_for_test;
 use core_test_support::responses::ev_local_shell_call;
 use core_test_support::responses::ev_reasoning_item;
 use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodexHarness;
 use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
 use core_test_support::wait_for_event_match;
@@ -31,6 +32,7 @@ use core_test_support::responses::ev_assistant_message;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_completed_with_tokens;
 use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_shell_command_call;
 use core_test_support::responses::mount_compact_json_once;
 use core_test_support::responses::mount_response_sequence;
 use core_test_support::responses::mount_sse_once;
@@ -468,14 +470,21 @@ async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
     let server = start_mock_server().await;
 
     let non_openai_provider_name = non_openai_model_provider(&server).name;
-    let codex = test_codex()
+    let test = test_codex()
         .with_config(move |config| {
             config.model_provider.name = non_openai_provider_name;
         })
         .build(&server)
         .await
-        .expect("build codex")
-        .codex;
+        .expect("build codex");
+    // Dropping the fixture deletes its working directory, so the shell calls
+    // below fail to spawn.
+    let spawn_failure = format!(
+        "working directory does not exist: {}",
+        test.cwd.path().display()
+    );
+    let codex = test.codex.clone();
+    drop(test);
 
     // user message
     let user_message = "create an app";
@@ -726,7 +735,7 @@ async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
       },
       {
         "call_id": "r1-shell",
-        "output": "execution error: Io(Os { code: 2, kind: NotFound, message: \"No such file or directory\" })",
+        "output": spawn_failure,
         "type": "function_call_output"
       },
       {
@@ -836,7 +845,7 @@ async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
       },
       {
         "call_id": "r3-shell",
-        "output": "execution error: Io(Os { code: 2, kind: NotFound, message: \"No such file or directory\" })",
+        "output": spawn_failure,
         "type": "function_call_output"
       },
       {
@@ -946,7 +955,7 @@ async fn multiple_auto_compact_per_task_runs_after_token_limit_hit() {
       },
       {
         "call_id": "r6-shell",
-        "output": "execution error: Io(Os { code: 2, kind: NotFound, message: \"No such file or directory\" })",
+        "output": spawn_failure,
         "type": "function_call_output"
       },
       {
@@ -2239,3 +2248,61 @@ async fn auto_compact_runs_when_reasoning_header_clears_between_turns() {
         "remote compaction should run once after the reasoning header clears"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compacted_history_keeps_a_digest_of_tool_calls() -> anyhow::Result<()> {
+    let harness =
+        TestCodexHarness::scripted_with_builder(test_codex().with_config(set_test_compact_prompt))
+            .await?;
+    harness
+        .mount_sse_sequence(vec![
+            sse(vec![
+                ev_shell_command_call("call-exit", "exit 3"),
+                ev_completed("r1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m1", FIRST_REPLY),
+                ev_completed("r2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m2", SUMMARY_TEXT),
+                ev_completed("r3"),
+            ]),
+            sse(vec![ev_completed("r4")]),
+        ])
+        .await;
+    harness.submit("run the failing command").await?;
+
+    let codex = harness.test().codex.clone();
+    codex.submit(Op::Compact).await?;
+    let compacted = wait_for_event_match(&codex, |ev| match ev {
+        EventMsg::ContextCompacted(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+    assert_eq!(compacted.turns_digested, 1);
+    assert!(compacted.tokens_before > 0);
+
+    harness.submit(THIRD_USER_MSG).await?;
+
+    let bodies = harness.request_bodies().await;
+    assert_eq!(bodies.len(), 4);
+    let input = bodies[3]["input"].as_array().expect("input array");
+    let digest = input
+        .iter()
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .find(|text| text.starts_with("<tool_digest>"))
+        .expect("post-compaction request should carry the tool digest");
+    let turns: serde_json::Value =
+        serde_json::from_str(digest.lines().nth(2).expect("digest json"))?;
+    assert_eq!(
+        turns[0]["commands"],
+        json!([{ "command": "exit 3", "exit_code": 3 }])
+    );
+    assert!(
+        !bodies[3].to_string().contains("call-exit"),
+        "the tool call itself should be compacted away"
+    );
+    Ok(())
+}
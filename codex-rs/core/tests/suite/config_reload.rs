@@ -0,0 +1,57 @@
+use anyhow::Result;
+use codex_core::config::Constrained;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::start_mock_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reload_rejects_added_writable_roots_without_opt_in() -> Result<()> {
+    let server = start_mock_server().await;
+    let mut builder = test_codex().with_config(|config| {
+        config.sandbox_policy = Constrained::allow_any(SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        });
+    });
+    let test = builder.build(&server).await?;
+
+    let extra_dir = TempDir::new()?;
+    let extra_root = extra_dir.path().display().to_string();
+    std::fs::write(
+        test.codex_home_path().join("config.toml"),
+        format!(
+            "sandbox_mode = \"workspace-write\"\n\n[sandbox_workspace_write]\nwritable_roots = [{extra_root:?}]\n"
+        ),
+    )?;
+
+    test.codex.submit(Op::ReloadConfig).await?;
+    let EventMsg::ConfigReloaded(reloaded) = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ConfigReloaded(_))
+    })
+    .await
+    else {
+        unreachable!("wait_for_event returned an unexpected event");
+    };
+
+    let change = reloaded
+        .changes
+        .iter()
+        .find(|change| change.key == "sandbox_workspace_write.writable_roots")
+        .unwrap_or_else(|| panic!("writable_roots change missing: {:?}", reloaded.changes));
+    let reason = change
+        .rejected_reason
+        .as_deref()
+        .unwrap_or_else(|| panic!("added writable root was applied: {change:?}"));
+    assert!(
+        reason.contains("sandbox_workspace_write.writable_roots"),
+        "unexpected reason: {reason}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use codex_core::CodexAuth;
+use codex_core::Conversation;
+use codex_core::IN_PROCESS_TOOL_SERVER;
+use codex_core::InProcessTool;
+use codex_core::ModelProviderInfo;
+use codex_core::ThreadManager;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_protocol::user_input::UserInput;
+use core_test_support::load_default_config_for_test;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn in_process_tool_runs_through_the_conversation_stream() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let responses = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("call-1", "shout", r#"{"text":"hi"}"#),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_function_call("call-2", "shout", r#"{"text":""}"#),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    let home = TempDir::new()?;
+    let mut config = load_default_config_for_test(&home).await;
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let manager = ThreadManager::with_models_provider_and_home(
+        CodexAuth::from_api_key("dummy"),
+        config.model_provider.clone(),
+        config.codex_home.clone(),
+    );
+    let shout = InProcessTool::new(
+        "shout",
+        "Upper-cases text.",
+        json!({
+            "type": "object",
+            "properties": { "text": { "type": "string" } },
+            "required": ["text"]
+        }),
+        |arguments: Value| async move {
+            match arguments["text"].as_str() {
+                Some("") | None => Err("nothing to shout".to_string()),
+                Some(text) => Ok(text.to_uppercase()),
+            }
+        },
+    );
+    let conversation = Conversation::start_in(Arc::new(manager), config, vec![shout]).await?;
+
+    let events: Vec<EventMsg> = conversation
+        .submit(vec![UserInput::Text {
+            text: "shout hi".to_string(),
+            text_elements: Vec::new(),
+        }])
+        .await?
+        .collect()
+        .await;
+
+    let tool_calls: Vec<(String, String, bool)> = events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::McpToolCallEnd(end) => Some((
+                end.invocation.server.clone(),
+                end.call_id.clone(),
+                end.is_success(),
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        tool_calls,
+        vec![
+            (
+                IN_PROCESS_TOOL_SERVER.to_string(),
+                "call-1".to_string(),
+                true
+            ),
+            (
+                IN_PROCESS_TOOL_SERVER.to_string(),
+                "call-2".to_string(),
+                false
+            ),
+        ]
+    );
+    assert!(matches!(events.last(), Some(EventMsg::TurnComplete(_))));
+    assert_eq!(
+        responses.function_call_output_text("call-1"),
+        Some("HI".to_string())
+    );
+    assert_eq!(
+        responses.function_call_output_text("call-2"),
+        Some("nothing to shout".to_string())
+    );
+
+    conversation.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use codex_app_server_protocol::ConfigLayerSource;
+use codex_core::config_loader::ConfigLayerEntry;
+use codex_core::config_loader::ConfigLayerStack;
+use codex_core::config_loader::ConfigRequirements;
+use codex_core::config_loader::ConfigRequirementsToml;
+use codex_core::protocol::EffectiveConfigValue;
+use codex_core::protocol::EventMsg;
+use core_test_support::responses::start_mock_server;
+use core_test_support::test_absolute_path;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use toml::Value as TomlValue;
+
+fn layer(source: ConfigLayerSource, toml: &str) -> ConfigLayerEntry {
+    let config: TomlValue = toml::from_str(toml).expect("parse layer toml");
+    ConfigLayerEntry::new(source, config)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_start_reports_each_effective_value_and_its_source() -> Result<()> {
+    let server = start_mock_server().await;
+    let user_file = test_absolute_path("/home/dev/.codex/config.toml");
+    let dot_codex_folder = test_absolute_path("/repo/.codex");
+    let project_file = dot_codex_folder.as_path().join("config.toml");
+
+    let layers = vec![
+        layer(
+            ConfigLayerSource::User {
+                file: user_file.clone(),
+            },
+            "model = \"user-model\"\nmodel_reasoning_effort = \"low\"\n",
+        ),
+        layer(
+            ConfigLayerSource::Project { dot_codex_folder },
+            "model = \"project-model\"\n\n[sandbox_workspace_write]\nwritable_roots = [\"/repo/out\"]\n",
+        ),
+    ];
+    let mut builder = test_codex().with_config(move |config| {
+        config.config_layer_stack = ConfigLayerStack::new(
+            layers,
+            ConfigRequirements::default(),
+            ConfigRequirementsToml::default(),
+        )
+        .expect("build config layer stack");
+    });
+    let TestCodex { codex, .. } = builder.build(&server).await?;
+
+    let report = wait_for_event_match(&codex, |event| match event {
+        EventMsg::EffectiveConfigResponse(report) => Some(report.clone()),
+        _ => None,
+    })
+    .await;
+
+    let user_source = format!("user {}", user_file.as_path().display());
+    let project_source = format!("project {}", project_file.display());
+    assert_eq!(
+        report
+            .layers
+            .iter()
+            .map(|layer| layer.source.as_str())
+            .collect::<Vec<_>>(),
+        vec![project_source.as_str(), user_source.as_str()]
+    );
+    assert_eq!(
+        report.values.get("model"),
+        Some(&EffectiveConfigValue {
+            value: "\"project-model\"".to_string(),
+            source: project_source.clone(),
+        })
+    );
+    assert_eq!(
+        report.values.get("model_reasoning_effort"),
+        Some(&EffectiveConfigValue {
+            value: "\"low\"".to_string(),
+            source: user_source,
+        })
+    );
+    assert_eq!(
+        report
+            .values
+            .get("sandbox_workspace_write.writable_roots.0"),
+        Some(&EffectiveConfigValue {
+            value: "\"/repo/out\"".to_string(),
+            source: project_source,
+        })
+    );
+
+    Ok(())
+}
@@ -3,6 +3,7 @@
 use anyhow::Result;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecErrorKind;
 use codex_core::protocol::Op;
 use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::ReasoningSummary;
@@ -16,6 +17,7 @@ use core_test_support::responses::sse;
 use core_test_support::responses::start_mock_server;
 use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
 use serde_json::json;
 use std::fs;
 
@@ -98,12 +100,16 @@ async fn execpolicy_blocks_shell_invocation() -> Result<()> {
     })
     .await;
 
+    let error = end.error.expect("rejected calls report an error");
+    assert_eq!(error.kind, ExecErrorKind::Rejected);
     assert!(
-        end.aggregated_output
+        error
+            .message
             .contains("policy forbids commands starting with `echo`"),
-        "unexpected output: {}",
-        end.aggregated_output
+        "unexpected message: {}",
+        error.message
     );
+    assert_eq!(end.stderr, "");
 
     Ok(())
 }
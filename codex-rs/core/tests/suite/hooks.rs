@@ -0,0 +1,175 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::time::Duration;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::HookExecutedEvent;
+use codex_core::protocol::HookTrigger;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnCompleteEvent;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_with_timeout;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+async fn submit_text(codex: &codex_core::CodexThread, text: &str) -> anyhow::Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Which of the two arrived first.
+#[derive(Debug, PartialEq)]
+enum First {
+    Hook,
+    TurnComplete,
+}
+
+/// Waits for the turn to complete and for its hook to be reported, in
+/// whichever order they arrive.
+async fn turn_and_hook(
+    codex: &codex_core::CodexThread,
+) -> (First, HookExecutedEvent, TurnCompleteEvent) {
+    let (mut first, mut hook, mut complete) = (None, None, None);
+    while hook.is_none() || complete.is_none() {
+        let event = wait_for_event_with_timeout(
+            codex,
+            |ev| matches!(ev, EventMsg::HookExecuted(_) | EventMsg::TurnComplete(_)),
+            Duration::from_secs(10),
+        )
+        .await;
+        match event {
+            EventMsg::HookExecuted(event) => {
+                first.get_or_insert(First::Hook);
+                hook = Some(event);
+            }
+            EventMsg::TurnComplete(event) => {
+                first.get_or_insert(First::TurnComplete);
+                complete = Some(event);
+            }
+            _ => unreachable!("filtered by the predicate"),
+        }
+    }
+    match (first, hook, complete) {
+        (Some(first), Some(hook), Some(complete)) => (first, hook, complete),
+        _ => unreachable!("the loop only ends once both arrived"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_complete_hook_receives_payload() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![ev_assistant_message("m1", "Done"), ev_completed("r1")]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks.on_turn_complete = Some(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "echo \"$CODEX_HOOK\"; printf '%s' \"$CODEX_HOOK_PAYLOAD\" >&2".to_string(),
+            ]);
+            cfg.hooks.sandbox = false;
+        })
+        .build(&server)
+        .await?;
+
+    submit_text(&codex, "hello world").await?;
+    let (_, hook, _) = turn_and_hook(&codex).await;
+
+    assert_eq!(hook.hook, HookTrigger::TurnComplete);
+    assert_eq!(hook.exit_code, Some(0));
+    assert_eq!(hook.stdout, "turn_complete\n");
+    let payload: Value = serde_json::from_str(&hook.stderr)?;
+    assert_eq!(payload["hook"], json!("turn_complete"));
+    assert_eq!(payload["last_agent_message"], json!("Done"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failing_hook_does_not_fail_the_turn() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![ev_assistant_message("m1", "Done"), ev_completed("r1")]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks.on_turn_complete = Some(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "echo broken >&2; exit 3".to_string(),
+            ]);
+            cfg.hooks.sandbox = false;
+        })
+        .build(&server)
+        .await?;
+
+    submit_text(&codex, "hello world").await?;
+    let (_, hook, complete) = turn_and_hook(&codex).await;
+
+    assert_eq!(hook.exit_code, Some(3));
+    assert_eq!(hook.stderr, "broken\n");
+    assert_eq!(complete.last_agent_message.as_deref(), Some("Done"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn slow_hook_is_reported_after_the_turn_completes() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![ev_assistant_message("m1", "Done"), ev_completed("r1")]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks.on_turn_complete = Some(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "sleep 2; echo finished".to_string(),
+            ]);
+            cfg.hooks.sandbox = false;
+        })
+        .build(&server)
+        .await?;
+
+    submit_text(&codex, "hello world").await?;
+    let (first, hook, _) = turn_and_hook(&codex).await;
+
+    assert_eq!(first, First::TurnComplete);
+    assert_eq!(hook.exit_code, Some(0));
+    assert_eq!(hook.stdout, "finished\n");
+
+    Ok(())
+}
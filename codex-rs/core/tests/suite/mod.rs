@@ -20,6 +20,7 @@ mod apply_patch_cli;
 #[cfg(not(target_os = "windows"))]
 mod approvals;
 mod auth_refresh;
+mod background_jobs;
 mod cli_stream;
 mod client;
 mod client_websockets;
@@ -28,12 +29,16 @@ mod collaboration_instructions;
 mod compact;
 mod compact_remote;
 mod compact_resume_fork;
+mod config_reload;
+mod conversation;
 mod deprecation_notice;
+mod effective_config;
 mod exec;
 mod exec_policy;
 mod fork_thread;
 mod grep_files;
 mod hierarchical_agents;
+mod hooks;
 mod image_rollout;
 mod items;
 mod json_result;
@@ -49,7 +54,9 @@ mod otel;
 mod pending_input;
 mod permissions_messages;
 mod personality;
+mod plan_only;
 mod prompt_caching;
+mod provider_failover;
 mod quota_exceeded;
 mod read_file;
 mod remote_models;
@@ -60,6 +67,7 @@ mod resume_warning;
 mod review;
 mod rmcp_client;
 mod rollout_list_find;
+mod scripted_model;
 mod seatbelt;
 mod shell_command;
 mod shell_serialization;
@@ -67,6 +75,8 @@ mod shell_snapshot;
 mod skills;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod stream_recovery;
+mod switch_model;
 mod text_encoding_fix;
 mod tool_harness;
 mod tool_parallelism;
@@ -78,3 +88,4 @@ mod user_notification;
 mod user_shell_cmd;
 mod view_image;
 mod web_search_cached;
+mod workspace_roots;
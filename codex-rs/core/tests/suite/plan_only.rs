@@ -0,0 +1,131 @@
+use anyhow::Result;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+fn ran_or_asked(event: &EventMsg) -> bool {
+    matches!(
+        event,
+        EventMsg::ExecCommandBegin(_)
+            | EventMsg::PatchApplyBegin(_)
+            | EventMsg::ExecApprovalRequest(_)
+            | EventMsg::ApplyPatchApprovalRequest(_)
+    )
+}
+
+fn tool_outputs(request: &Value) -> Vec<String> {
+    request["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match item["type"].as_str() {
+            Some("function_call_output") => item["output"].as_str().map(str::to_string),
+            Some("custom_tool_call_output") => item["output"].as_str().map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn calls_are_proposed_without_running_or_asking_and_never_run_later() -> Result<()> {
+    skip_if_windows!(Ok(()));
+
+    let patch = "*** Begin Patch\n*** Add File: notes.txt\n+hello\n*** End Patch";
+    let model = ScriptedModel::new([
+        ScriptedTurn::new()
+            .function_call(
+                "call-1",
+                "shell_command",
+                &json!({"command": "touch touched.txt"}),
+            )
+            .custom_tool_call("call-2", "apply_patch", patch),
+        ScriptedTurn::new().message("planned"),
+        ScriptedTurn::new().message("ok"),
+    ]);
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.plan_only = true)
+        .build_with_scripted_model(&model)
+        .await?;
+
+    let events = test
+        .submit_turn_collect_events(
+            "make the changes",
+            AskForApproval::UnlessTrusted,
+            SandboxPolicy::new_read_only_policy(),
+        )
+        .await?;
+
+    assert!(!events.iter().any(ran_or_asked));
+    let proposed_exec: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::ProposedExec(ev) => Some(ev),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(proposed_exec.len(), 1);
+    assert!(proposed_exec[0].plan_only);
+    assert_eq!(proposed_exec[0].command.call_id, "call-1");
+    assert!(
+        proposed_exec[0]
+            .command
+            .command
+            .join(" ")
+            .contains("touch touched.txt")
+    );
+    let proposed_patch: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::ProposedPatch(ev) => Some(ev),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(proposed_patch.len(), 1);
+    assert!(proposed_patch[0].plan_only);
+    assert_eq!(
+        proposed_patch[0].patch.summaries[0].path,
+        test.workspace_path("notes.txt")
+    );
+    assert_eq!(
+        tool_outputs(&model.requests()[1]),
+        vec![
+            "not executed (plan-only mode)".to_string(),
+            "not executed (plan-only mode)".to_string(),
+        ]
+    );
+
+    test.codex
+        .submit(Op::SetPlanOnly { enabled: false })
+        .await?;
+    let changed = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::PlanOnlyChanged(_))
+    })
+    .await;
+    assert!(matches!(
+        changed,
+        EventMsg::PlanOnlyChanged(ev) if !ev.plan_only
+    ));
+
+    let events = test
+        .submit_turn_collect_events(
+            "thanks",
+            AskForApproval::UnlessTrusted,
+            SandboxPolicy::new_read_only_policy(),
+        )
+        .await?;
+
+    assert!(!events.iter().any(ran_or_asked));
+    assert!(!test.workspace_path("touched.txt").exists());
+    assert!(!test.workspace_path("notes.txt").exists());
+    Ok(())
+}
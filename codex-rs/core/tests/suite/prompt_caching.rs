@@ -7,6 +7,7 @@ use codex_core::protocol::AskForApproval;
 use codex_core::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::PromptCacheStats;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningSummary;
 use codex_core::shell::Shell;
@@ -19,7 +20,9 @@ use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use core_test_support::load_sse_fixture_with_id;
+use core_test_support::responses::ev_response_created;
 use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
 use core_test_support::responses::start_mock_server;
 use core_test_support::skip_if_no_network;
 use core_test_support::test_codex::TestCodex;
@@ -845,3 +848,77 @@ async fn send_user_turn_with_changes_sends_environment_context() -> anyhow::Resu
 
     Ok(())
 }
+
+fn ev_completed_with_cached_tokens(id: &str, input_tokens: i64, cached_tokens: i64) -> String {
+    sse(vec![
+        ev_response_created(id),
+        serde_json::json!({
+            "type": "response.completed",
+            "response": {
+                "id": id,
+                "usage": {
+                    "input_tokens": input_tokens,
+                    "input_tokens_details": { "cached_tokens": cached_tokens },
+                    "output_tokens": 0,
+                    "output_tokens_details": null,
+                    "total_tokens": input_tokens
+                }
+            }
+        }),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_summary_reports_prompt_cache_hits() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(&server, ev_completed_with_cached_tokens("resp-1", 1000, 0)).await;
+    mount_sse_once(
+        &server,
+        ev_completed_with_cached_tokens("resp-2", 1200, 896),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex().build(&server).await?;
+
+    let mut summaries = Vec::new();
+    for text in ["hello 1", "hello 2"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text {
+                    text: text.into(),
+                    text_elements: Vec::new(),
+                }],
+                final_output_json_schema: None,
+            })
+            .await?;
+        let EventMsg::TurnSummary(summary) =
+            wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnSummary(_))).await
+        else {
+            unreachable!("wait_for_event returned an unexpected event");
+        };
+        summaries.push(summary.prompt_cache);
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+    }
+
+    assert_eq!(
+        summaries,
+        vec![
+            PromptCacheStats {
+                hits: 0,
+                misses: 1,
+                cached_input_tokens: 0,
+                input_tokens: 1000,
+            },
+            PromptCacheStats {
+                hits: 1,
+                misses: 0,
+                cached_input_tokens: 896,
+                input_tokens: 1200,
+            },
+        ]
+    );
+
+    Ok(())
+}
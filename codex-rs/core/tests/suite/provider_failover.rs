@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::config::types::FailoverRetry;
+use codex_core::config::types::FailoverTarget;
+use codex_core::config::types::ProviderFailover;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::ProviderModel;
+use codex_core::protocol::ProviderSwitchReason;
+use codex_core::protocol::ProviderSwitchedEvent;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use codex_protocol::user_input::UserInput;
+use core_test_support::agent_messages;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::turn_events;
+use pretty_assertions::assert_eq;
+
+const NO_RETRIES: FailoverRetry = FailoverRetry {
+    max_retries: 0,
+    backoff: Duration::ZERO,
+};
+
+async fn build(primary: &ScriptedModel, backup: &ScriptedModel) -> Result<TestCodex> {
+    let backup_provider = backup.provider();
+    test_codex()
+        .with_config(move |config| {
+            config.provider_failover = Some(ProviderFailover {
+                chain: vec![FailoverTarget {
+                    provider_id: "backup".to_string(),
+                    provider: backup_provider,
+                    model: None,
+                }],
+                rate_limit: NO_RETRIES,
+                server_error: NO_RETRIES,
+                disconnect: NO_RETRIES,
+            });
+        })
+        .build_with_scripted_model(primary)
+        .await
+}
+
+async fn send(test: &TestCodex, text: &str) -> Result<()> {
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    Ok(())
+}
+
+fn provider_switches(events: &[EventMsg]) -> Vec<ProviderSwitchedEvent> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::ProviderSwitched(event) => Some(event.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rate_limited_turn_is_retried_on_the_next_provider() -> Result<()> {
+    let primary = ScriptedModel::new([ScriptedTurn::rate_limited(1)]);
+    let backup = ScriptedModel::new([
+        ScriptedTurn::new().message("from backup").usage(10, 2),
+        ScriptedTurn::new().message("still backup"),
+    ]);
+    let test = build(&primary, &backup).await?;
+    let model = test.session_configured.model.clone();
+
+    send(&test, "hi").await?;
+    let events = turn_events(&test.codex).await;
+
+    assert_eq!(
+        provider_switches(&events),
+        vec![ProviderSwitchedEvent {
+            from: ProviderModel {
+                provider: "openai".to_string(),
+                model: model.clone(),
+            },
+            to: ProviderModel {
+                provider: "backup".to_string(),
+                model,
+            },
+            reason: ProviderSwitchReason::RateLimited,
+            retried_in_turn: true,
+        }]
+    );
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, EventMsg::Error(_))),
+        "the switch should hide the rate limit"
+    );
+    assert_eq!(agent_messages(&events), vec!["from backup".to_string()]);
+    let by_provider = events
+        .iter()
+        .rev()
+        .find_map(|event| match event {
+            EventMsg::TokenCount(count) => count.info.as_ref().map(|info| info.by_provider.clone()),
+            _ => None,
+        })
+        .expect("token count");
+    assert_eq!(
+        by_provider
+            .iter()
+            .map(|(provider, usage)| (provider.clone(), usage.total_tokens))
+            .collect::<Vec<_>>(),
+        vec![("backup".to_string(), 12)]
+    );
+
+    send(&test, "again").await?;
+    let events = turn_events(&test.codex).await;
+    assert_eq!(agent_messages(&events), vec!["still backup".to_string()]);
+    assert_eq!(primary.requests().len(), 1);
+    assert_eq!(backup.requests().len(), 2);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn partial_response_fails_the_turn_and_the_next_turn_switches() -> Result<()> {
+    // `created`, `added`, `delta` and `done`: the message is recorded before
+    // the stream is cut off.
+    let primary = ScriptedModel::new([ScriptedTurn::new().message("partial").disconnect_after(4)]);
+    let backup = ScriptedModel::new([ScriptedTurn::new().message("from backup")]);
+    let test = build(&primary, &backup).await?;
+
+    send(&test, "hi").await?;
+    let events = turn_events(&test.codex).await;
+    let switches = provider_switches(&events);
+    assert_eq!(switches.len(), 1);
+    assert_eq!(switches[0].reason, ProviderSwitchReason::Disconnected);
+    assert!(!switches[0].retried_in_turn);
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, EventMsg::Error(_))),
+        "expected the cut-off turn to fail"
+    );
+    assert_eq!(backup.requests().len(), 0);
+
+    send(&test, "again").await?;
+    let events = turn_events(&test.codex).await;
+    assert_eq!(agent_messages(&events), vec!["from backup".to_string()]);
+    assert_eq!(primary.requests().len(), 1);
+    Ok(())
+}
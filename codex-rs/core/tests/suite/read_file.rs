@@ -21,9 +21,9 @@ async fn read_file_tool_returns_requested_lines() -> anyhow::Result<()> {
 
     let call_id = "read-file-call";
     let arguments = json!({
-        "file_path": file_path,
-        "offset": 2,
-        "limit": 2,
+        "path": file_path,
+        "start_line": 2,
+        "end_line": 3,
     })
     .to_string();
 
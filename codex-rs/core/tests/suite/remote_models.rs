@@ -189,7 +189,7 @@ async fn remote_models_remote_model_uses_unified_exec() -> Result<()> {
     })
     .await;
 
-    assert_eq!(begin_event.source, ExecCommandSource::UnifiedExecStartup);
+    assert_eq!(begin_event.source, ExecCommandSource::ModelUnifiedExec);
 
     wait_for_event(&codex, |event| matches!(event, EventMsg::TurnComplete(_))).await;
 
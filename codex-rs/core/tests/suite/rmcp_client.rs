@@ -8,12 +8,16 @@ use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
+use codex_core::config::types::McpEnvSource;
+use codex_core::config::types::McpSamplingConfig;
+use codex_core::config::types::McpSamplingPolicy;
 use codex_core::config::types::McpServerConfig;
 use codex_core::config::types::McpServerTransportConfig;
 
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerState;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::SandboxPolicy;
@@ -85,6 +89,7 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
                             expected_env_value.to_string(),
                         )])),
                         env_vars: Vec::new(),
+                        env_sources: None,
                         cwd: None,
                     },
                     enabled: true,
@@ -94,6 +99,8 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -148,6 +155,8 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
         .as_ref()
         .expect("rmcp echo tool should return success");
     assert_eq!(result.is_error, Some(false));
+    assert!(!end.is_error);
+    assert_eq!(end.error_source, None);
     assert!(
         result.content.is_empty(),
         "content should default to an empty array"
@@ -175,6 +184,42 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
 
     server.verify().await;
 
+    // The rollout keeps the redacted previews instead of the raw payloads.
+    fixture.codex.submit(Op::Shutdown).await?;
+    wait_for_event(&fixture.codex, |ev| {
+        matches!(ev, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let rollout_path = fixture.codex.rollout_path().expect("rollout path");
+    let rollout = std::fs::read_to_string(rollout_path)?;
+    let record = |event_type: &str| -> Value {
+        rollout
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .find(|line| line["payload"]["type"] == event_type)
+            .unwrap_or_else(|| panic!("{event_type} missing from rollout"))
+    };
+    let begin_record = record("mcp_tool_call_begin");
+    assert_eq!(
+        begin_record["payload"]["invocation"]["arguments"],
+        Value::Null
+    );
+    assert_eq!(
+        begin_record["payload"]["arguments_preview"],
+        json!(r#"{"message":"ping"}"#)
+    );
+    let end_record = record("mcp_tool_call_end");
+    assert_eq!(
+        end_record["payload"]["invocation"]["arguments"],
+        Value::Null
+    );
+    let persisted_result = &end_record["payload"]["result"]["Ok"];
+    assert_eq!(persisted_result.get("structuredContent"), None);
+    assert_eq!(
+        persisted_result["content"][0]["text"],
+        end_record["payload"]["result_preview"]
+    );
+
     Ok(())
 }
 
@@ -226,6 +271,7 @@ async fn stdio_image_responses_round_trip() -> anyhow::Result<()> {
                             OPENAI_PNG.to_string(),
                         )])),
                         env_vars: Vec::new(),
+                        env_sources: None,
                         cwd: None,
                     },
                     enabled: true,
@@ -235,6 +281,8 @@ async fn stdio_image_responses_round_trip() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -282,6 +330,8 @@ async fn stdio_image_responses_round_trip() -> anyhow::Result<()> {
                 tool: "image".to_string(),
                 arguments: Some(json!({})),
             },
+            timeout_ms: Some(60_000),
+            arguments_preview: "{}".to_string(),
         },
     );
 
@@ -425,6 +475,7 @@ async fn stdio_image_completions_round_trip() -> anyhow::Result<()> {
                             OPENAI_PNG.to_string(),
                         )])),
                         env_vars: Vec::new(),
+                        env_sources: None,
                         cwd: None,
                     },
                     enabled: true,
@@ -434,6 +485,8 @@ async fn stdio_image_completions_round_trip() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -480,6 +533,8 @@ async fn stdio_image_completions_round_trip() -> anyhow::Result<()> {
                 tool: "image".to_string(),
                 arguments: Some(json!({})),
             },
+            timeout_ms: Some(60_000),
+            arguments_preview: "{}".to_string(),
         },
     );
 
@@ -572,6 +627,7 @@ async fn stdio_server_propagates_whitelisted_env_vars() -> anyhow::Result<()> {
                         args: Vec::new(),
                         env: None,
                         env_vars: vec!["MCP_TEST_VALUE".to_string()],
+                        env_sources: None,
                         cwd: None,
                     },
                     enabled: true,
@@ -581,6 +637,8 @@ async fn stdio_server_propagates_whitelisted_env_vars() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -731,6 +789,8 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                         bearer_token_env_var: None,
                         http_headers: None,
                         env_http_headers: None,
+                        header_sources: None,
+                        tls: None,
                     },
                     enabled: true,
                     disabled_reason: None,
@@ -739,6 +799,8 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -921,6 +983,8 @@ async fn streamable_http_with_oauth_round_trip() -> anyhow::Result<()> {
                         bearer_token_env_var: None,
                         http_headers: None,
                         env_http_headers: None,
+                        header_sources: None,
+                        tls: None,
                     },
                     enabled: true,
                     disabled_reason: None,
@@ -929,6 +993,8 @@ async fn streamable_http_with_oauth_round_trip() -> anyhow::Result<()> {
                     enabled_tools: None,
                     disabled_tools: None,
                     scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
                 },
             );
             config
@@ -1027,6 +1093,175 @@ async fn streamable_http_with_oauth_round_trip() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial(mcp_test_value)]
+async fn failed_stdio_server_is_reported_and_can_be_retried() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    let rmcp_test_server_bin = stdio_server_bin()?;
+    let missing_bin = tempdir()?.path().join("missing-mcp-server");
+
+    let fixture = test_codex()
+        .with_config(move |config| {
+            let stdio_server =
+                |command: String, enabled_tools: Option<Vec<String>>| McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command,
+                        args: Vec::new(),
+                        env: None,
+                        env_vars: Vec::new(),
+                        env_sources: None,
+                        cwd: None,
+                    },
+                    enabled: true,
+                    disabled_reason: None,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                    enabled_tools,
+                    disabled_tools: None,
+                    scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
+                };
+            let mut servers = config.mcp_servers.get().clone();
+            servers.insert(
+                "rmcp".to_string(),
+                stdio_server(rmcp_test_server_bin, Some(vec!["echo".to_string()])),
+            );
+            servers.insert(
+                "broken".to_string(),
+                stdio_server(missing_bin.to_string_lossy().into_owned(), None),
+            );
+            config
+                .mcp_servers
+                .set(servers)
+                .expect("test mcp servers should accept any configuration");
+        })
+        .build(&server)
+        .await?;
+
+    let mut statuses = HashMap::new();
+    while statuses.len() < 2 {
+        let EventMsg::McpServerStatus(status) = wait_for_event(&fixture.codex, |ev| {
+            matches!(ev, EventMsg::McpServerStatus(_))
+        })
+        .await
+        else {
+            unreachable!("event guard guarantees McpServerStatus");
+        };
+        statuses.insert(status.server.clone(), status);
+    }
+
+    let ready = &statuses["rmcp"];
+    assert_eq!(ready.state, McpServerState::Ready);
+    assert_eq!(ready.error, None);
+    assert_eq!(ready.configured_tools, Some(vec!["echo".to_string()]));
+    assert_eq!(ready.effective_tools, vec!["echo".to_string()]);
+
+    let failed = &statuses["broken"];
+    assert_eq!(failed.state, McpServerState::Failed);
+    assert!(failed.error.is_some());
+    assert!(failed.effective_tools.is_empty());
+
+    fixture
+        .codex
+        .submit(Op::RetryMcpServer {
+            server: "broken".to_string(),
+        })
+        .await?;
+    let EventMsg::McpServerStatus(retried) = wait_for_event(&fixture.codex, |ev| {
+        matches!(ev, EventMsg::McpServerStatus(_))
+    })
+    .await
+    else {
+        unreachable!("event guard guarantees McpServerStatus");
+    };
+    assert_eq!(retried.server, "broken");
+    assert_eq!(retried.state, McpServerState::Failed);
+
+    fixture
+        .codex
+        .submit(Op::RetryMcpServer {
+            server: "rmcp".to_string(),
+        })
+        .await?;
+    let EventMsg::Warning(warning) =
+        wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::Warning(_))).await
+    else {
+        unreachable!("event guard guarantees Warning");
+    };
+    assert_eq!(
+        warning.message,
+        "Cannot retry MCP server: MCP server 'rmcp' is already running"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn stdio_server_with_missing_env_source_is_reported_as_failed() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    let rmcp_test_server_bin = stdio_server_bin()?;
+
+    let fixture = test_codex()
+        .with_config(move |config| {
+            let mut servers = config.mcp_servers.get().clone();
+            servers.insert(
+                "needs_secret".to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: rmcp_test_server_bin,
+                        args: Vec::new(),
+                        env: None,
+                        env_vars: Vec::new(),
+                        env_sources: Some(HashMap::from([(
+                            "MCP_TEST_VALUE".to_string(),
+                            McpEnvSource::Env("CODEX_TEST_MISSING_MCP_SECRET".to_string()),
+                        )])),
+                        cwd: None,
+                    },
+                    enabled: true,
+                    disabled_reason: None,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                    enabled_tools: None,
+                    disabled_tools: None,
+                    scopes: None,
+                    max_restart_attempts: None,
+                    sampling: None,
+                },
+            );
+            config
+                .mcp_servers
+                .set(servers)
+                .expect("test mcp servers should accept any configuration");
+        })
+        .build(&server)
+        .await?;
+
+    let EventMsg::McpServerStatus(status) = wait_for_event(
+        &fixture.codex,
+        |ev| matches!(ev, EventMsg::McpServerStatus(status) if status.server == "needs_secret"),
+    )
+    .await
+    else {
+        unreachable!("event guard guarantees McpServerStatus");
+    };
+    assert_eq!(status.state, McpServerState::Failed);
+    assert_eq!(
+        status.error.as_deref(),
+        Some(
+            "MCP client for `needs_secret` failed to start: MCP startup failed: environment variable `CODEX_TEST_MISSING_MCP_SECRET` for `MCP_TEST_VALUE` is not set"
+        )
+    );
+    assert!(status.effective_tools.is_empty());
+
+    Ok(())
+}
+
 async fn wait_for_streamable_http_server(
     server_child: &mut Child,
     address: &str,
@@ -1125,3 +1360,302 @@ impl Drop for EnvVarGuard {
         }
     }
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial(mcp_test_value)]
+async fn sampling_under_auto_is_clamped_to_the_remaining_budget() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+
+    let call_id = "sample-1";
+    let server_name = "rmcp";
+    let tool_name = format!("mcp__{server_name}__sample");
+
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_response_created("resp-1"),
+            responses::ev_function_call(
+                call_id,
+                &tool_name,
+                "{\"prompt\":\"Say hello.\",\"max_tokens\":200}",
+            ),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    // The server's sampling request runs on the same provider.
+    let sampling_mock = mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-sample", "hello"),
+            responses::ev_completed("resp-sample"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-1", "rmcp sample tool completed."),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    let rmcp_test_server_bin = stdio_server_bin()?;
+
+    let fixture = test_codex()
+        .with_config(move |config| {
+            let mut servers = config.mcp_servers.get().clone();
+            servers.insert(
+                server_name.to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: rmcp_test_server_bin,
+                        args: Vec::new(),
+                        env: None,
+                        env_vars: Vec::new(),
+                        env_sources: None,
+                        cwd: None,
+                    },
+                    enabled: true,
+                    disabled_reason: None,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                    enabled_tools: None,
+                    disabled_tools: None,
+                    scopes: None,
+                    max_restart_attempts: None,
+                    sampling: Some(McpSamplingConfig {
+                        policy: McpSamplingPolicy::Auto,
+                        model: None,
+                        token_budget: Some(50),
+                    }),
+                },
+            );
+            config
+                .mcp_servers
+                .set(servers)
+                .expect("test mcp servers should accept any configuration");
+        })
+        .build(&server)
+        .await?;
+    let session_model = fixture.session_configured.model.clone();
+
+    fixture
+        .codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "call the rmcp sample tool".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            cwd: fixture.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+            model: session_model,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+
+    let EventMsg::McpSamplingRequest(request) = wait_for_event(&fixture.codex, |ev| {
+        matches!(ev, EventMsg::McpSamplingRequest(_))
+    })
+    .await
+    else {
+        unreachable!("event guard guarantees McpSamplingRequest");
+    };
+    assert!(!request.requires_approval);
+    assert_eq!(request.request.max_tokens, 50);
+
+    let EventMsg::McpToolCallEnd(end) = wait_for_event(&fixture.codex, |ev| {
+        matches!(ev, EventMsg::McpToolCallEnd(_))
+    })
+    .await
+    else {
+        unreachable!("event guard guarantees McpToolCallEnd");
+    };
+    let result = end.result.expect("rmcp sample tool should return success");
+    assert_eq!(result.is_error, Some(false));
+    match result.content.as_slice() {
+        [ContentBlock::TextContent(text)] => assert_eq!(text.text, "hello"),
+        other => panic!("expected the sampled text, got {other:?}"),
+    }
+
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+
+    assert_eq!(
+        sampling_mock.single_request().body_json()["max_output_tokens"],
+        json!(50)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial(mcp_test_value)]
+async fn crashed_stdio_server_is_restarted_with_its_tools() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    let server_name = "rmcp";
+
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_response_created("resp-1"),
+            responses::ev_function_call("crash-1", &format!("mcp__{server_name}__crash"), "{}"),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-1", "the server crashed."),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_response_created("resp-3"),
+            responses::ev_function_call(
+                "echo-1",
+                &format!("mcp__{server_name}__echo"),
+                "{\"message\":\"back\"}",
+            ),
+            responses::ev_completed("resp-3"),
+        ]),
+    )
+    .await;
+    mount_sse_once(
+        &server,
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-2", "the server is back."),
+            responses::ev_completed("resp-4"),
+        ]),
+    )
+    .await;
+
+    let rmcp_test_server_bin = stdio_server_bin()?;
+    let fixture = test_codex()
+        .with_config(move |config| {
+            let mut servers = config.mcp_servers.get().clone();
+            servers.insert(
+                server_name.to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: rmcp_test_server_bin,
+                        args: Vec::new(),
+                        env: None,
+                        env_vars: Vec::new(),
+                        env_sources: None,
+                        cwd: None,
+                    },
+                    enabled: true,
+                    disabled_reason: None,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                    enabled_tools: Some(vec!["crash".to_string(), "echo".to_string()]),
+                    disabled_tools: None,
+                    scopes: None,
+                    max_restart_attempts: Some(2),
+                    sampling: None,
+                },
+            );
+            config
+                .mcp_servers
+                .set(servers)
+                .expect("test mcp servers should accept any configuration");
+        })
+        .build(&server)
+        .await?;
+    let session_model = fixture.session_configured.model.clone();
+    let submit_turn = |text: &str| {
+        fixture.codex.submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            cwd: fixture.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+            model: session_model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            collaboration_mode: None,
+            personality: None,
+        })
+    };
+
+    let EventMsg::McpServerStatus(started) = wait_for_event(
+        &fixture.codex,
+        |ev| matches!(ev, EventMsg::McpServerStatus(status) if status.server == server_name),
+    )
+    .await
+    else {
+        unreachable!("event guard guarantees McpServerStatus");
+    };
+    assert_eq!(started.state, McpServerState::Ready);
+
+    submit_turn("crash the rmcp server").await?;
+    // The restart runs in the background, so its statuses and the end of the
+    // turn can arrive in any order.
+    let mut states = Vec::new();
+    let mut restarted = None;
+    let mut turn_complete = false;
+    while restarted.is_none() || !turn_complete {
+        match wait_for_event(&fixture.codex, |ev| {
+            matches!(ev, EventMsg::TurnComplete(_))
+                || matches!(ev, EventMsg::McpServerStatus(status) if status.server == server_name)
+        })
+        .await
+        {
+            EventMsg::TurnComplete(_) => turn_complete = true,
+            EventMsg::McpServerStatus(status) => {
+                states.push(status.state);
+                if status.state == McpServerState::Ready {
+                    restarted = Some(status);
+                }
+            }
+            _ => unreachable!("event guard guarantees TurnComplete or McpServerStatus"),
+        }
+    }
+    assert_eq!(
+        states,
+        vec![
+            McpServerState::Down,
+            McpServerState::Restarting,
+            McpServerState::Ready,
+        ]
+    );
+    let restarted = restarted.expect("restarted status");
+    assert_eq!(
+        restarted.configured_tools,
+        Some(vec!["crash".to_string(), "echo".to_string()])
+    );
+    assert_eq!(
+        restarted.effective_tools,
+        vec!["crash".to_string(), "echo".to_string()]
+    );
+
+    submit_turn("call the rmcp echo tool").await?;
+    let EventMsg::McpToolCallEnd(end) = wait_for_event(&fixture.codex, |ev| {
+        matches!(ev, EventMsg::McpToolCallEnd(_))
+    })
+    .await
+    else {
+        unreachable!("event guard guarantees McpToolCallEnd");
+    };
+    assert!(end.is_success(), "echo after restart failed: {end:?}");
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use codex_protocol::user_input::UserInput;
+use core_test_support::agent_messages;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::turn_events;
+use pretty_assertions::assert_eq;
+
+async fn send(test: &TestCodex, text: &str) -> Result<()> {
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: text.to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streamed_deltas_and_usage_reach_the_session() -> Result<()> {
+    let model = ScriptedModel::new([ScriptedTurn::new()
+        .message_deltas(&["Hello, ", "world"])
+        .usage(40, 2)]);
+    let test = test_codex().build_with_scripted_model(&model).await?;
+
+    send(&test, "hi").await?;
+    let events = turn_events(&test.codex).await;
+
+    let deltas: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::AgentMessageDelta(delta) => Some(delta.delta.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(deltas, vec!["Hello, ".to_string(), "world".to_string()]);
+    assert_eq!(agent_messages(&events), vec!["Hello, world".to_string()]);
+    let last_usage = events.iter().rev().find_map(|event| match event {
+        EventMsg::TokenCount(count) => count
+            .info
+            .as_ref()
+            .map(|info| info.last_token_usage.clone()),
+        _ => None,
+    });
+    assert_eq!(last_usage.map(|usage| usage.total_tokens), Some(42));
+    assert_eq!(model.requests().len(), 1);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_stream_cut_off_midway_is_retried() -> Result<()> {
+    let model = ScriptedModel::new([
+        ScriptedTurn::new().message("lost").disconnect_after(2),
+        ScriptedTurn::new().message("recovered"),
+    ]);
+    let test = test_codex().build_with_scripted_model(&model).await?;
+
+    send(&test, "hi").await?;
+    let events = turn_events(&test.codex).await;
+
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, EventMsg::StreamError(_))),
+        "expected the cut-off stream to be reported"
+    );
+    assert_eq!(agent_messages(&events), vec!["recovered".to_string()]);
+    assert_eq!(model.requests().len(), 2);
+    assert_eq!(model.remaining_turns(), 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_rate_limited_request_fails_the_turn_and_the_next_turn_works() -> Result<()> {
+    let model = ScriptedModel::new([
+        ScriptedTurn::rate_limited(1),
+        ScriptedTurn::new().message("back"),
+    ]);
+    let test = test_codex().build_with_scripted_model(&model).await?;
+
+    send(&test, "first").await?;
+    let events = turn_events(&test.codex).await;
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, EventMsg::Error(_))),
+        "expected the rate limit to fail the turn"
+    );
+
+    send(&test, "second").await?;
+    let events = turn_events(&test.codex).await;
+    assert_eq!(agent_messages(&events), vec!["back".to_string()]);
+    Ok(())
+}
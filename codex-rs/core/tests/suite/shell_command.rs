@@ -1,19 +1,21 @@
 use std::time::Duration;
 
 use anyhow::Result;
+use codex_core::config::types::EnvironmentVariablePattern;
 use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
 use core_test_support::assert_regex_match;
 use core_test_support::responses::ev_assistant_message;
 use core_test_support::responses::ev_completed;
 use core_test_support::responses::ev_function_call;
 use core_test_support::responses::ev_response_created;
-use core_test_support::responses::mount_sse_sequence;
 use core_test_support::responses::sse;
-use core_test_support::skip_if_no_network;
 use core_test_support::skip_if_windows;
 use core_test_support::test_codex::TestCodexBuilder;
 use core_test_support::test_codex::TestCodexHarness;
 use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
 use serde_json::json;
 use test_case::test_case;
 
@@ -59,7 +61,7 @@ async fn shell_command_harness_with(
     let builder = configure(test_codex()).with_config(|config| {
         config.include_apply_patch_tool = true;
     });
-    TestCodexHarness::with_builder(builder).await
+    TestCodexHarness::scripted_with_builder(builder).await
 }
 
 async fn mount_shell_responses(
@@ -68,7 +70,9 @@ async fn mount_shell_responses(
     command: &str,
     login: Option<bool>,
 ) {
-    mount_sse_sequence(harness.server(), shell_responses(call_id, command, login)).await;
+    harness
+        .mount_sse_sequence(shell_responses(call_id, command, login))
+        .await;
 }
 
 async fn mount_shell_responses_with_timeout(
@@ -78,11 +82,14 @@ async fn mount_shell_responses_with_timeout(
     login: Option<bool>,
     timeout: Duration,
 ) {
-    mount_sse_sequence(
-        harness.server(),
-        shell_responses_with_timeout(call_id, command, login, timeout.as_millis() as i64),
-    )
-    .await;
+    harness
+        .mount_sse_sequence(shell_responses_with_timeout(
+            call_id,
+            command,
+            login,
+            timeout.as_millis() as i64,
+        ))
+        .await;
 }
 
 fn assert_shell_command_output(output: &str, expected: &str) -> Result<()> {
@@ -102,8 +109,6 @@ fn assert_shell_command_output(output: &str, expected: &str) -> Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn shell_command_works() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     let call_id = "shell-command-call";
@@ -118,8 +123,6 @@ async fn shell_command_works() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn output_with_login() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     let call_id = "shell-command-call-login-true";
@@ -134,8 +137,6 @@ async fn output_with_login() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn output_without_login() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     let call_id = "shell-command-call-login-false";
@@ -150,8 +151,6 @@ async fn output_without_login() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn multi_line_output_with_login() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
 
     let call_id = "shell-command-call-first-extra-login";
@@ -172,7 +171,6 @@ async fn multi_line_output_with_login() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn pipe_output_with_login() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
     skip_if_windows!(Ok(()));
 
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
@@ -189,7 +187,6 @@ async fn pipe_output_with_login() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn pipe_output_without_login() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
     skip_if_windows!(Ok(()));
 
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
@@ -206,8 +203,6 @@ async fn pipe_output_without_login() -> anyhow::Result<()> {
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn shell_command_times_out_with_timeout_ms() -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
     let call_id = "shell-command-timeout";
     let command = if cfg!(windows) {
@@ -243,8 +238,6 @@ async fn shell_command_times_out_with_timeout_ms() -> anyhow::Result<()> {
 #[test_case(true ; "with_login")]
 #[test_case(false ; "without_login")]
 async fn unicode_output(login: bool) -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| {
         builder.with_model("gpt-5.2").with_config(|config| {
             config.features.enable(Feature::PowershellUtf8);
@@ -273,8 +266,6 @@ async fn unicode_output(login: bool) -> anyhow::Result<()> {
 #[test_case(true ; "with_login")]
 #[test_case(false ; "without_login")]
 async fn unicode_output_with_newlines(login: bool) -> anyhow::Result<()> {
-    skip_if_no_network!(Ok(()));
-
     let harness = shell_command_harness_with(|builder| {
         builder.with_model("gpt-5.2").with_config(|config| {
             config.features.enable(Feature::PowershellUtf8);
@@ -298,3 +289,112 @@ async fn unicode_output_with_newlines(login: bool) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn shell_responses_with_env(call_id: &str, command: &str, env: serde_json::Value) -> Vec<String> {
+    let args = json!({
+        "command": command,
+        "env": env,
+    });
+
+    #[allow(clippy::expect_used)]
+    let arguments = serde_json::to_string(&args).expect("serialize shell command arguments");
+
+    vec![
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_function_call(call_id, "shell_command", &arguments),
+            ev_completed("resp-1"),
+        ]),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    ]
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn env_is_set_for_the_command() -> anyhow::Result<()> {
+    skip_if_windows!(Ok(()));
+    let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
+
+    let call_id = "shell-command-env";
+    harness
+        .mount_sse_sequence(shell_responses_with_env(
+            call_id,
+            "echo \"$GREETING\"",
+            json!({"GREETING": "hello, world"}),
+        ))
+        .await;
+    harness
+        .submit("run the echo command with GREETING set")
+        .await?;
+
+    let output = harness.function_call_stdout(call_id).await;
+    assert_shell_command_output(&output, "hello, world")?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn env_excluded_by_the_policy_is_refused() -> anyhow::Result<()> {
+    skip_if_windows!(Ok(()));
+    let harness = shell_command_harness_with(|builder| {
+        builder.with_model("gpt-5.1").with_config(|config| {
+            config.shell_environment_policy.exclude =
+                vec![EnvironmentVariablePattern::new_case_insensitive("LD_*")];
+        })
+    })
+    .await?;
+
+    let call_id = "shell-command-env-blocked";
+    harness
+        .mount_sse_sequence(shell_responses_with_env(
+            call_id,
+            "echo hi",
+            json!({"LD_PRELOAD": "/tmp/hook.so"}),
+        ))
+        .await;
+    harness.submit("run the command with LD_PRELOAD").await?;
+
+    let output = harness.function_call_stdout(call_id).await;
+    assert_eq!(
+        output,
+        "environment variable `LD_PRELOAD` is blocked by `exclude` in [shell_environment_policy]; run the command without it"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_tmpdir_is_exported_and_removed_at_shutdown() -> anyhow::Result<()> {
+    skip_if_windows!(Ok(()));
+    let harness = shell_command_harness_with(|builder| builder.with_model("gpt-5.1")).await?;
+    let tmpdir = harness
+        .test()
+        .session_configured
+        .tmpdir
+        .clone()
+        .expect("session tmpdir");
+    assert!(tmpdir.is_dir(), "{} was not created", tmpdir.display());
+
+    let call_id = "shell-command-tmpdir";
+    mount_shell_responses(&harness, call_id, "echo \"$CODEX_TMPDIR\"", None).await;
+    harness.submit("print the session temp dir").await?;
+
+    let output = harness.function_call_stdout(call_id).await;
+    assert!(
+        output.contains(&tmpdir.display().to_string()),
+        "unexpected output: {output}"
+    );
+
+    let codex = &harness.test().codex;
+    codex.submit(Op::Shutdown).await?;
+    wait_for_event(codex, |event| matches!(event, EventMsg::ShutdownComplete)).await;
+    assert!(
+        !tmpdir.exists(),
+        "{} outlived the session",
+        tmpdir.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::StreamRecoveryStrategy;
+use codex_core::protocol::StreamRetryEvent;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use core_test_support::agent_messages;
+use core_test_support::skip_if_windows;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+fn stream_retries(events: &[EventMsg]) -> Vec<StreamRetryEvent> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::StreamRetry(retry) => Some(retry.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn input_items_of_type<'a>(request: &'a Value, kind: &str) -> Vec<&'a Value> {
+    request["input"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter(|item| item["type"].as_str() == Some(kind))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_calls_before_a_disconnect_run_once_and_the_retry_resumes_after_them() -> Result<()> {
+    skip_if_windows!(Ok(()));
+
+    let model = ScriptedModel::new([
+        ScriptedTurn::new()
+            .function_call(
+                "call-1",
+                "shell_command",
+                &json!({"command": "echo one >> calls.txt"}),
+            )
+            .function_call(
+                "call-2",
+                "shell_command",
+                &json!({"command": "echo two >> calls.txt"}),
+            )
+            .message("never completes")
+            .disconnect_after(3),
+        // The retried stream repeats the first call, which must not run again.
+        ScriptedTurn::new()
+            .function_call(
+                "call-1",
+                "shell_command",
+                &json!({"command": "echo one >> calls.txt"}),
+            )
+            .message("done"),
+    ]);
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .build_with_scripted_model(&model)
+        .await?;
+
+    let events = test
+        .submit_turn_collect_events(
+            "run both commands",
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+        )
+        .await?;
+
+    let retries = stream_retries(&events);
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0].attempt, 1);
+    assert_eq!(
+        retries[0].strategy,
+        StreamRecoveryStrategy::ResumeAfterToolCalls
+    );
+    assert_eq!(retries[0].items_preserved, 2);
+    assert_eq!(retries[0].tool_calls_preserved, 2);
+    let mut calls: Vec<String> = std::fs::read_to_string(test.workspace_path("calls.txt"))?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    calls.sort();
+    assert_eq!(calls, vec!["one".to_string(), "two".to_string()]);
+    assert_eq!(agent_messages(&events), vec!["done".to_string()]);
+
+    let requests = model.requests();
+    assert_eq!(requests.len(), 2);
+    let outputs: Vec<&str> = input_items_of_type(&requests[1], "function_call_output")
+        .into_iter()
+        .filter_map(|item| item["call_id"].as_str())
+        .collect();
+    assert_eq!(outputs, vec!["call-1", "call-2"]);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_message_before_a_disconnect_is_kept_and_the_model_asked_to_continue() -> Result<()> {
+    let model = ScriptedModel::new([
+        ScriptedTurn::new()
+            .message("first half")
+            .message("lost")
+            .disconnect_after(4),
+        ScriptedTurn::new().message("second half"),
+    ]);
+    let test = test_codex().build_with_scripted_model(&model).await?;
+
+    let events = test
+        .submit_turn_collect_events(
+            "write something long",
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+        )
+        .await?;
+
+    let retries = stream_retries(&events);
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0].strategy, StreamRecoveryStrategy::Continue);
+    assert_eq!(retries[0].items_preserved, 1);
+    assert_eq!(retries[0].tool_calls_preserved, 0);
+    assert_eq!(
+        agent_messages(&events),
+        vec!["first half".to_string(), "second half".to_string()]
+    );
+
+    let requests = model.requests();
+    assert_eq!(requests.len(), 2);
+    let messages = input_items_of_type(&requests[1], "message");
+    let [.., kept, note] = messages.as_slice() else {
+        panic!("expected the kept message and a continuation note: {messages:?}");
+    };
+    assert_eq!(kept["role"], "assistant");
+    assert_eq!(kept["content"][0]["text"], "first half");
+    assert_eq!(note["role"], "developer");
+    assert!(
+        note["content"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("cut off")),
+        "unexpected continuation note: {note}"
+    );
+    Ok(())
+}
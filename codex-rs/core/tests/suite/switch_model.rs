@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ModelChangedEvent;
+use codex_core::protocol::Op;
+use codex_core::protocol::ProviderModel;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use codex_protocol::user_input::UserInput;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn switch_model_moves_the_next_turn_to_the_new_provider() -> Result<()> {
+    let primary = ScriptedModel::new([]);
+    let backup = ScriptedModel::new([ScriptedTurn::new().message("from backup")]);
+    let backup_provider = backup.provider();
+    let test = test_codex()
+        .with_config(move |config| {
+            config
+                .model_providers
+                .insert("backup".to_string(), backup_provider);
+        })
+        .build_with_scripted_model(&primary)
+        .await?;
+    let model = test.session_configured.model.clone();
+
+    test.codex
+        .submit(Op::SwitchModel {
+            model: "gpt-4.1".to_string(),
+            provider: Some("missing".to_string()),
+        })
+        .await?;
+    let error = wait_for_event(&test.codex, |event| matches!(event, EventMsg::Error(_))).await;
+    let EventMsg::Error(error) = error else {
+        unreachable!();
+    };
+    assert_eq!(error.message, "unknown model provider `missing`");
+
+    test.codex
+        .submit(Op::SwitchModel {
+            model: "gpt-4.1".to_string(),
+            provider: Some("backup".to_string()),
+        })
+        .await?;
+    let changed = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::ModelChanged(_))
+    })
+    .await;
+    let EventMsg::ModelChanged(ModelChangedEvent {
+        from,
+        to,
+        compacts_history,
+        ..
+    }) = changed
+    else {
+        unreachable!();
+    };
+    assert_eq!(
+        (from, to),
+        (
+            ProviderModel {
+                provider: "openai".to_string(),
+                model,
+            },
+            ProviderModel {
+                provider: "backup".to_string(),
+                model: "gpt-4.1".to_string(),
+            },
+        )
+    );
+    assert!(!compacts_history);
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hi".to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    wait_for_event_with_timeout(
+        &test.codex,
+        |event| matches!(event, EventMsg::TurnComplete(_)),
+        Duration::from_secs(10),
+    )
+    .await;
+
+    assert_eq!(primary.requests().len(), 0);
+    let requests = backup.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["model"], "gpt-4.1");
+    Ok(())
+}
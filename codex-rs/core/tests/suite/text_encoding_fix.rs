@@ -71,6 +71,7 @@ fn decode_shell_output(bytes: &[u8]) -> String {
     StreamOutput {
         text: bytes.to_vec(),
         truncated_after_lines: None,
+        omitted_bytes: 0,
     }
     .from_utf8_lossy()
     .text
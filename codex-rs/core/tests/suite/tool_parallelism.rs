@@ -2,9 +2,11 @@
 #![allow(clippy::unwrap_used)]
 
 use std::fs;
+use std::io::Write;
 use std::time::Duration;
 use std::time::Instant;
 
+use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
@@ -213,6 +215,96 @@ async fn mixed_tools_fall_back_to_serial() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_only_shell_commands_run_in_parallel_when_enabled() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex().with_model("gpt-5.1").with_config(|config| {
+        config.features.enable(Feature::ParallelReadOnlyTools);
+    });
+    let test = builder.build(&server).await?;
+
+    // Each `cat` only finishes once both FIFOs have a reader, so the calls
+    // only complete quickly when they overlap; run serially, the first one
+    // times out.
+    let fifos = ["first.fifo", "second.fifo"].map(|name| test.cwd.path().join(name));
+    for fifo in &fifos {
+        let status = std::process::Command::new("mkfifo").arg(fifo).status()?;
+        assert!(status.success(), "mkfifo failed for {}", fifo.display());
+    }
+    let writer = std::thread::spawn({
+        let fifos = fifos.clone();
+        move || -> std::io::Result<()> {
+            let mut writers = Vec::new();
+            for fifo in &fifos {
+                writers.push(fs::OpenOptions::new().write(true).open(fifo)?);
+            }
+            for mut writer in writers {
+                writer.write_all(b"done\n")?;
+            }
+            Ok(())
+        }
+    });
+
+    let cat_args = |name: &str| {
+        serde_json::to_string(&json!({
+            "command": format!("cat {name}"),
+            "timeout_ms": 3_000,
+        }))
+    };
+    let first_response = sse(vec![
+        json!({"type": "response.created", "response": {"id": "resp-1"}}),
+        ev_function_call("call-1", "shell_command", &cat_args("first.fifo")?),
+        ev_function_call("call-2", "shell_command", &cat_args("second.fifo")?),
+        ev_completed("resp-1"),
+    ]);
+    let second_response = sse(vec![
+        ev_assistant_message("msg-1", "done"),
+        ev_completed("resp-2"),
+    ]);
+    mount_sse_sequence(&server, vec![first_response, second_response]).await;
+
+    let duration = run_turn_and_measure(&test, "read both fifos").await?;
+    assert_parallel_duration(duration);
+    writer.join().unwrap()?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn mutating_shell_commands_stay_serial_when_parallel_read_only_enabled() -> anyhow::Result<()>
+{
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex().with_model("gpt-5.1").with_config(|config| {
+        config.features.enable(Feature::ParallelReadOnlyTools);
+    });
+    let test = builder.build(&server).await?;
+
+    let shell_args = serde_json::to_string(&json!({
+        "command": "sleep 0.3",
+        "timeout_ms": 1_000,
+    }))?;
+    let first_response = sse(vec![
+        json!({"type": "response.created", "response": {"id": "resp-1"}}),
+        ev_function_call("call-1", "shell_command", &shell_args),
+        ev_function_call("call-2", "shell_command", &shell_args),
+        ev_completed("resp-1"),
+    ]);
+    let second_response = sse(vec![
+        ev_assistant_message("msg-1", "done"),
+        ev_completed("resp-2"),
+    ]);
+    mount_sse_sequence(&server, vec![first_response, second_response]).await;
+
+    let duration = run_turn_and_measure(&test, "run sleep twice").await?;
+    assert_serial_duration(duration);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn tool_results_grouped() -> anyhow::Result<()> {
     skip_if_no_network!(Ok(()));
@@ -423,6 +423,7 @@ async fn mcp_tool_call_output_exceeds_limit_truncated_for_model() -> Result<()>
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -432,6 +433,8 @@ async fn mcp_tool_call_output_exceeds_limit_truncated_for_model() -> Result<()>
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
         config
@@ -516,6 +519,7 @@ async fn mcp_image_output_preserves_image_and_no_text_summary() -> Result<()> {
                         openai_png.to_string(),
                     )])),
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -525,6 +529,8 @@ async fn mcp_image_output_preserves_image_and_no_text_summary() -> Result<()> {
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
         config
@@ -780,6 +786,7 @@ async fn mcp_tool_call_output_not_truncated_with_custom_limit() -> Result<()> {
                     args: Vec::new(),
                     env: None,
                     env_vars: Vec::new(),
+                    env_sources: None,
                     cwd: None,
                 },
                 enabled: true,
@@ -789,6 +796,8 @@ async fn mcp_tool_call_output_not_truncated_with_custom_limit() -> Result<()> {
                 enabled_tools: None,
                 disabled_tools: None,
                 scopes: None,
+                max_restart_attempts: None,
+                sampling: None,
             },
         );
         config
@@ -351,7 +351,7 @@ async fn unified_exec_emits_exec_command_begin_event() -> Result<()> {
 
     assert_command(&begin_event.command, "-lc", "/bin/echo hello unified exec");
 
-    assert_eq!(begin_event.cwd, cwd.path());
+    assert_eq!(begin_event.cwd, dunce::canonicalize(cwd.path())?);
 
     wait_for_event(&codex, |event| matches!(event, EventMsg::TurnComplete(_))).await;
 
@@ -429,7 +429,7 @@ async fn unified_exec_resolves_relative_workdir() -> Result<()> {
 
     assert_eq!(
         begin_event.cwd,
-        cwd.path().join(workdir_rel),
+        dunce::canonicalize(cwd.path().join(workdir_rel))?,
         "exec_command cwd should resolve relative workdir against turn cwd",
     );
 
@@ -509,7 +509,8 @@ async fn unified_exec_respects_workdir_override() -> Result<()> {
     .await;
 
     assert_eq!(
-        begin_event.cwd, workdir,
+        begin_event.cwd,
+        dunce::canonicalize(&workdir)?,
         "exec_command cwd should reflect the requested workdir override"
     );
 
@@ -1205,7 +1206,7 @@ async fn unified_exec_emits_one_begin_and_one_end_event() -> Result<()> {
         open_event.interaction_input.is_none(),
         "startup begin events should not include interaction input"
     );
-    assert_eq!(open_event.source, ExecCommandSource::UnifiedExecStartup);
+    assert_eq!(open_event.source, ExecCommandSource::ModelUnifiedExec);
 
     let end_event = &end_events[0];
     assert_eq!(end_event.call_id, open_call_id);
@@ -2230,6 +2231,98 @@ PY
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unified_exec_end_event_has_all_output_when_deltas_fall_behind() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    skip_if_sandbox!(Ok(()));
+    skip_if_windows!(Ok(()));
+
+    let server = start_mock_server().await;
+
+    // One-byte deltas make streaming far slower than the command writes, so
+    // the stream lags behind the output and runs into the per-call delta cap.
+    let mut builder = test_codex().with_config(|config| {
+        config.use_experimental_unified_exec_tool = true;
+        config.features.enable(Feature::UnifiedExec);
+        config.output_limits.delta_chunk_bytes = 1;
+    });
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = builder.build(&server).await?;
+
+    let line_count = 5_000;
+    let call_id = "uexec-backpressure";
+    let args = json!({
+        "cmd": format!("i=0; while [ $i -lt {line_count} ]; do echo line-$i; i=$((i+1)); done"),
+        "yield_time_ms": 10_000,
+    });
+    let responses = vec![
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_function_call(call_id, "exec_command", &serde_json::to_string(&args)?),
+            ev_completed("resp-1"),
+        ]),
+        sse(vec![
+            ev_response_created("resp-2"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    ];
+    mount_sse_sequence(&server, responses).await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: "write a lot of output".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+
+    let mut streamed = Vec::new();
+    let end_event = loop {
+        let event = wait_for_event_with_timeout(
+            &codex,
+            |event| {
+                matches!(
+                    event,
+                    EventMsg::ExecCommandOutputDelta(_) | EventMsg::ExecCommandEnd(_)
+                )
+            },
+            Duration::from_secs(30),
+        )
+        .await;
+        match event {
+            EventMsg::ExecCommandOutputDelta(delta) if delta.call_id == call_id => {
+                streamed.extend_from_slice(&delta.chunk);
+            }
+            EventMsg::ExecCommandEnd(end) if end.call_id == call_id => break end,
+            _ => {}
+        }
+    };
+
+    let expected: String = (0..line_count).map(|i| format!("line-{i}\n")).collect();
+    assert!(end_event.deltas_incomplete);
+    assert_eq!(end_event.delta_bytes_emitted, streamed.len() as u64);
+    assert!(streamed.len() < expected.len());
+    assert_eq!(end_event.aggregated_output, expected);
+
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TurnComplete(_))).await;
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn unified_exec_timeout_and_followup_poll() -> Result<()> {
     skip_if_no_network!(Ok(()));
@@ -3,13 +3,17 @@
 use std::os::unix::fs::PermissionsExt;
 
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::NotificationKind;
 use codex_core::protocol::Op;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
 use codex_protocol::user_input::UserInput;
 use core_test_support::fs_wait;
 use core_test_support::responses;
 use core_test_support::skip_if_no_network;
 use core_test_support::test_codex::TestCodex;
 use core_test_support::test_codex::test_codex;
+use core_test_support::turn_events;
 use core_test_support::wait_for_event;
 use pretty_assertions::assert_eq;
 use serde_json::Value;
@@ -78,3 +82,85 @@ echo -n "${@: -1}" > $(dirname "${0}")/notify.txt"#,
 
     Ok(())
 }
+
+fn notifications_sent(events: &[EventMsg]) -> Vec<NotificationKind> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::NotificationSent(sent) => Some(sent.kind),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_completion_and_errors_notify_and_are_recorded() -> anyhow::Result<()> {
+    let model = ScriptedModel::new([
+        ScriptedTurn::new().message("Done"),
+        ScriptedTurn::rate_limited(1),
+    ]);
+
+    let notify_dir = TempDir::new()?;
+    let notify_script = notify_dir.path().join("notify.sh");
+    std::fs::write(
+        &notify_script,
+        r#"#!/bin/bash
+set -e
+echo "${@: -1}" >> $(dirname "${0}")/notify.jsonl"#,
+    )?;
+    std::fs::set_permissions(&notify_script, std::fs::Permissions::from_mode(0o755))?;
+    let notify_file = notify_dir.path().join("notify.jsonl");
+    let notify_script_str = notify_script.to_str().unwrap().to_string();
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(move |cfg| cfg.notify = Some(vec![notify_script_str]))
+        .build_with_scripted_model(&model)
+        .await?;
+
+    for text in ["first", "second"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text {
+                    text: text.into(),
+                    text_elements: Vec::new(),
+                }],
+                final_output_json_schema: None,
+            })
+            .await?;
+        let events = turn_events(&codex).await;
+        let expected = if text == "first" {
+            NotificationKind::TurnComplete
+        } else {
+            NotificationKind::Error
+        };
+        assert!(
+            notifications_sent(&events).contains(&expected),
+            "expected a {expected:?} notification in {events:?}"
+        );
+    }
+
+    // The notifier runs in the background; wait for both payloads.
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    let payloads: Vec<Value> = loop {
+        let raw = tokio::fs::read_to_string(&notify_file)
+            .await
+            .unwrap_or_default();
+        let payloads: Vec<Value> = raw
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        if payloads.len() >= 2 || tokio::time::Instant::now() >= deadline {
+            break payloads;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    let mut types: Vec<&str> = payloads
+        .iter()
+        .filter_map(|payload| payload["type"].as_str())
+        .collect();
+    types.sort_unstable();
+    types.dedup();
+    assert_eq!(types, vec!["agent-turn-complete", "error"]);
+
+    Ok(())
+}
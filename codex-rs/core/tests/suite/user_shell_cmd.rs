@@ -335,3 +335,43 @@ async fn user_shell_command_is_truncated_only_once() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn user_shell_cmd_end_event_reports_dropped_deltas() -> anyhow::Result<()> {
+    let server = start_mock_server().await;
+    // One-byte deltas run into the per-call delta limit long before the
+    // command has printed everything.
+    let mut builder = test_codex().with_config(|config| {
+        config.output_limits.delta_chunk_bytes = 1;
+    });
+    let test = builder.build(&server).await?;
+
+    test.codex
+        .submit(Op::RunUserShellCommand {
+            command: "head -c 12000 /dev/zero | tr '\\0' a".to_string(),
+        })
+        .await?;
+
+    let mut delta_bytes = 0u64;
+    let end = loop {
+        let event = wait_for_event(&test.codex, |ev| {
+            matches!(
+                ev,
+                EventMsg::ExecCommandOutputDelta(_) | EventMsg::ExecCommandEnd(_)
+            )
+        })
+        .await;
+        match event {
+            EventMsg::ExecCommandOutputDelta(delta) => delta_bytes += delta.chunk.len() as u64,
+            EventMsg::ExecCommandEnd(end) => break end,
+            _ => unreachable!(),
+        }
+    };
+
+    assert_eq!(end.aggregated_output, "a".repeat(12_000));
+    assert_eq!(end.delta_bytes_emitted, delta_bytes);
+    assert_eq!(end.delta_bytes_emitted, 10_000);
+    assert!(end.deltas_incomplete);
+    Ok(())
+}
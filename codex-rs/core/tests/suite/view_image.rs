@@ -263,6 +263,10 @@ async fn view_image_tool_attaches_local_image() -> anyhow::Result<()> {
     assert!(resized_height <= 768);
     assert!(resized_width < 4096);
     assert!(resized_height < 1024);
+    assert_eq!(
+        (tool_event.width, tool_event.height),
+        (resized_width, resized_height)
+    );
 
     Ok(())
 }
@@ -340,7 +344,7 @@ async fn view_image_tool_errors_when_path_is_directory() -> anyhow::Result<()> {
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-async fn view_image_tool_placeholder_for_non_image_files() -> anyhow::Result<()> {
+async fn view_image_tool_lists_supported_formats_for_non_image_files() -> anyhow::Result<()> {
     skip_if_no_network!(Ok(()));
 
     let server = start_mock_server().await;
@@ -403,36 +407,17 @@ async fn view_image_tool_placeholder_for_non_image_files() -> anyhow::Result<()>
         "non-image file should not produce an input_image message"
     );
 
-    let placeholder = request
-        .inputs_of_type("message")
-        .iter()
-        .find_map(|item| {
-            let content = item.get("content").and_then(Value::as_array)?;
-            content.iter().find_map(|span| {
-                if span.get("type").and_then(Value::as_str) == Some("input_text") {
-                    let text = span.get("text").and_then(Value::as_str)?;
-                    if text.contains("Codex could not read the local image at")
-                        && text.contains("unsupported MIME type `application/json`")
-                    {
-                        return Some(text.to_string());
-                    }
-                }
-                None
-            })
-        })
-        .expect("placeholder text found");
-
-    assert!(
-        placeholder.contains(&abs_path.display().to_string()),
-        "placeholder should mention path: {placeholder}"
-    );
-
-    let output_text = mock
-        .single_request()
+    let output_text = request
         .function_call_output_content_and_success(call_id)
         .and_then(|(content, _)| content)
         .expect("output text present");
-    assert_eq!(output_text, "attached local image path");
+    assert_eq!(
+        output_text,
+        format!(
+            "`{}` is not in a supported image format; view_image accepts PNG, JPEG, GIF, WebP and BMP",
+            abs_path.display()
+        )
+    );
 
     Ok(())
 }
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::config::Constrained;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::WorkspaceRoot;
+use codex_core::scripted_model::ScriptedModel;
+use codex_core::scripted_model::ScriptedTurn;
+use codex_protocol::user_input::UserInput;
+use codex_utils_absolute_path::AbsolutePathBuf;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn added_roots_are_writable_and_shown_to_the_model() -> Result<()> {
+    let api_dir = TempDir::new()?;
+    let api = WorkspaceRoot {
+        name: "api".to_string(),
+        path: AbsolutePathBuf::from_absolute_path(api_dir.path())?,
+    };
+    let model = ScriptedModel::new([ScriptedTurn::new().message("done")]);
+    let test = test_codex()
+        .with_config(|config| {
+            config.sandbox_policy =
+                Constrained::allow_any(SandboxPolicy::new_workspace_write_policy());
+        })
+        .build_with_scripted_model(&model)
+        .await?;
+
+    test.codex
+        .submit(Op::UpdateWorkspaceRoots {
+            add: vec![api.clone()],
+            remove: Vec::new(),
+        })
+        .await?;
+    let changed = wait_for_event_with_timeout(
+        &test.codex,
+        |event| matches!(event, EventMsg::WorkspaceRootsChanged(_)),
+        Duration::from_secs(10),
+    )
+    .await;
+    let EventMsg::WorkspaceRootsChanged(changed) = changed else {
+        unreachable!("waited for WorkspaceRootsChanged");
+    };
+    assert_eq!(changed.added, vec![api.clone()]);
+    assert_eq!(changed.roots, vec![api.clone()]);
+    assert!(changed.writable_roots.contains(&api.path));
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hi".to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    let requests = model.requests();
+    assert_eq!(requests.len(), 1);
+    let expected = format!("<root name=\"api\">{}</root>", api_dir.path().display());
+    assert!(
+        requests[0]["input"].to_string().contains(&expected),
+        "request should list the workspace root: {}",
+        requests[0]["input"]
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unknown_roots_cannot_be_removed() -> Result<()> {
+    let model = ScriptedModel::new(Vec::<ScriptedTurn>::new());
+    let test = test_codex().build_with_scripted_model(&model).await?;
+
+    test.codex
+        .submit(Op::UpdateWorkspaceRoots {
+            add: Vec::new(),
+            remove: vec!["web".to_string()],
+        })
+        .await?;
+    let error = wait_for_event(&test.codex, |event| matches!(event, EventMsg::Error(_))).await;
+    let EventMsg::Error(error) = error else {
+        unreachable!("waited for Error");
+    };
+    assert_eq!(error.message, "no workspace root named `web`");
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::live_session::attach;
+use codex_core::live_session::is_live;
+use codex_protocol::ThreadId;
+use supports_color::Stream;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor_with_events_jsonl_output::EventProcessorWithEventsJsonOutput;
+use crate::event_processor_with_human_output::EventProcessorWithHumanOutput;
+
+/// Follow the events of a session running in another process, read-only.
+#[derive(Parser, Debug)]
+pub struct AttachCli {
+    /// Id of the running session, as printed when it started.
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+
+    /// Print every protocol event to stdout as JSONL.
+    #[arg(long = "json", default_value_t = false)]
+    pub json: bool,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
+
+/// Streams the events of a live session until it ends. The session must have
+/// been started with the `live_attach` feature enabled.
+pub async fn run_attach(cli: AttachCli) -> anyhow::Result<()> {
+    let AttachCli {
+        session_id,
+        json,
+        config_overrides,
+    } = cli;
+    let cli_kv_overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides_and_harness_overrides(
+        cli_kv_overrides,
+        ConfigOverrides::default(),
+    )
+    .await?;
+
+    let Ok(thread_id) = ThreadId::from_string(&session_id) else {
+        eprintln!("`{session_id}` is not a session id.");
+        std::process::exit(1);
+    };
+    if !is_live(&config.codex_home, thread_id) {
+        eprintln!(
+            "Session {thread_id} is not running, or was started without live attach. Start it with `--enable live_attach` to follow it from another terminal."
+        );
+        std::process::exit(1);
+    }
+    let mut reader = attach(&config.codex_home, thread_id).await?;
+
+    let mut event_processor: Box<dyn EventProcessor> = if json {
+        Box::new(EventProcessorWithEventsJsonOutput::new(None))
+    } else {
+        Box::new(EventProcessorWithHumanOutput::create_with_ansi(
+            supports_color::on_cached(Stream::Stderr).is_some(),
+            &config,
+            None,
+        ))
+    };
+    // The session may run several turns; keep following until it shuts down
+    // or the connection closes.
+    while let Some(event) = reader.next_event().await? {
+        if let CodexStatus::Shutdown = event_processor.process_event(event) {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use codex_protocol::user_input::UserInput;
+use codex_utils_image::load_and_resize_to_fit;
+
+/// Largest piped stdin attached as a document. Beyond this a log mostly
+/// spends context on lines the model will not need.
+const MAX_DOCUMENT_BYTES: usize = 512 * 1024;
+
+/// Largest image file accepted by `--image`.
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Name under which piped stdin is attached.
+const STDIN_DOCUMENT_NAME: &str = "stdin";
+
+/// Turns piped stdin into a document attached next to the prompt. Empty input
+/// attaches nothing.
+pub(crate) fn stdin_document(text: String) -> Result<Option<UserInput>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    if text.len() > MAX_DOCUMENT_BYTES {
+        return Err(format!(
+            "stdin is {}, more than the {} that can be attached. Pipe only the relevant part, e.g. `tail -n 500 build.log | codex exec ...`.",
+            format_size(text.len() as u64),
+            format_size(MAX_DOCUMENT_BYTES as u64)
+        ));
+    }
+    Ok(Some(UserInput::Document {
+        name: STDIN_DOCUMENT_NAME.to_string(),
+        text,
+    }))
+}
+
+/// Checks that `path` is an image the model can be sent. Formats other than
+/// PNG and JPEG are converted to PNG when the request is built; this makes
+/// sure that will work before the run starts.
+pub(crate) fn check_image(path: &Path) -> Result<(), String> {
+    let display = path.display();
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("cannot attach image {display}: {e}"))?;
+    if metadata.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "image {display} is {}, more than the {} that can be attached. Crop it or save it as a smaller JPEG first.",
+            format_size(metadata.len()),
+            format_size(MAX_IMAGE_BYTES)
+        ));
+    }
+    load_and_resize_to_fit(path).map(|_| ()).map_err(|e| {
+        format!("cannot attach image {display}: {e}. Supported formats are PNG, JPEG, GIF, WebP, and BMP.")
+    })
+}
+
+fn format_size(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    } else {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn stdin_document_skips_empty_input_and_rejects_oversized_input() {
+        assert_eq!(stdin_document(" \n".to_string()), Ok(None));
+        assert_eq!(
+            stdin_document("error: linker failed\n".to_string()),
+            Ok(Some(UserInput::Document {
+                name: "stdin".to_string(),
+                text: "error: linker failed\n".to_string(),
+            }))
+        );
+
+        let err = stdin_document("x".repeat(MAX_DOCUMENT_BYTES + 1)).unwrap_err();
+        assert!(err.starts_with("stdin is 512.0 KiB, more than the 512.0 KiB"));
+        assert!(err.contains("tail -n 500"));
+    }
+
+    #[test]
+    fn check_image_rejects_files_that_are_not_images() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("shot.png");
+        std::fs::write(&path, "not an image").expect("write file");
+
+        let err = check_image(&path).unwrap_err();
+        assert!(err.starts_with(&format!("cannot attach image {}", path.display())));
+        assert!(check_image(&dir.path().join("missing.png")).is_err());
+    }
+}
@@ -73,6 +73,12 @@ pub struct Cli {
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
 
+    /// Resolve approval requests without prompting: `auto-readonly`,
+    /// `deny-all`, or `allow-list=<FILE>` with one allowed command prefix per
+    /// line. Without it, the run never asks for approval.
+    #[arg(long = "approvals", value_name = "POLICY")]
+    pub approvals: Option<String>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
@@ -89,10 +95,42 @@ pub struct Cli {
     )]
     pub json: bool,
 
+    /// Print every protocol event to stdout as JSONL, followed by a summary
+    /// line with the last agent message, token usage, and changed files.
+    #[arg(
+        long = "json-events",
+        default_value_t = false,
+        global = true,
+        conflicts_with = "json"
+    )]
+    pub json_events: bool,
+
     /// Specifies file where the last message from the agent should be written.
     #[arg(long = "output-last-message", short = 'o', value_name = "FILE")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Specifies file where the final unified diff of the run should be
+    /// written. Its stats are written to `<FILE>.stats.json`.
+    #[arg(long = "output-diff", value_name = "FILE")]
+    pub output_diff_file: Option<PathBuf>,
+
+    /// Exit with the reported-failure code (4) when the agent's final message
+    /// matches this regular expression.
+    #[arg(long = "fail-if", value_name = "PATTERN")]
+    pub fail_if: Option<String>,
+
+    /// Interrupt the run and exit with code 124 if it has not finished after
+    /// this many seconds. Commands the agent runs are cut short so they end
+    /// by then too.
+    #[arg(long = "max-duration", value_name = "SECONDS")]
+    pub max_duration_secs: Option<u64>,
+
+    /// Continue the session with this id, from `codex exec` or the
+    /// interactive client, instead of starting a new one. Same as
+    /// `codex exec resume <SESSION_ID>`.
+    #[arg(long = "resume", value_name = "SESSION_ID")]
+    pub resume_session_id: Option<String>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
@@ -0,0 +1,142 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor::handle_last_message;
+use crate::exec_events::ExecExit;
+use crate::exec_events::ExecSummary;
+use crate::exec_events::ExecSummaryEvent;
+use codex_core::config::Config;
+use codex_core::protocol;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::ReportedOutcome;
+use serde::Serialize;
+use tracing::error;
+
+/// Prints every `EventMsg` as one JSON object per line, in the protocol's own
+/// serde shape, and an [`ExecSummaryEvent`] as the last line.
+pub struct EventProcessorWithEventsJsonOutput {
+    last_message_path: Option<PathBuf>,
+    last_agent_message: Option<String>,
+    last_total_token_usage: Option<protocol::TokenUsage>,
+    tool_output_tokens: u64,
+    estimated_cost: Option<f64>,
+    files_changed: BTreeSet<PathBuf>,
+    outcome: Option<ReportedOutcome>,
+}
+
+impl EventProcessorWithEventsJsonOutput {
+    pub fn new(last_message_path: Option<PathBuf>) -> Self {
+        Self {
+            last_message_path,
+            last_agent_message: None,
+            last_total_token_usage: None,
+            tool_output_tokens: 0,
+            estimated_cost: None,
+            files_changed: BTreeSet::new(),
+            outcome: None,
+        }
+    }
+
+    /// Updates the summary with `event`.
+    pub fn record_event(&mut self, event: &protocol::Event) {
+        match &event.msg {
+            protocol::EventMsg::TokenCount(ev) => {
+                if let Some(info) = &ev.info {
+                    self.last_total_token_usage = Some(info.total_token_usage.clone());
+                    self.tool_output_tokens = info.tool_output_tokens;
+                }
+            }
+            protocol::EventMsg::TurnSummary(ev) => {
+                if let Some(cost) = &ev.cost {
+                    self.estimated_cost = Some(cost.session);
+                }
+            }
+            protocol::EventMsg::PatchApplyEnd(ev) if ev.success => {
+                for (path, change) in &ev.changes {
+                    self.files_changed.insert(path.clone());
+                    if let FileChange::Update {
+                        move_path: Some(move_path),
+                        ..
+                    } = change
+                    {
+                        self.files_changed.insert(move_path.clone());
+                    }
+                }
+            }
+            protocol::EventMsg::OutcomeReported(ev) => {
+                self.outcome = Some(ev.outcome.clone());
+            }
+            protocol::EventMsg::TurnComplete(ev) => {
+                self.last_agent_message = ev.last_agent_message.clone();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn summary(&self) -> ExecSummary {
+        ExecSummary {
+            last_agent_message: self.last_agent_message.clone(),
+            total_token_usage: self.last_total_token_usage.clone(),
+            tool_output_tokens: self.tool_output_tokens,
+            estimated_cost: self.estimated_cost,
+            files_changed: self.files_changed.iter().cloned().collect(),
+            outcome: self.outcome.clone(),
+            exit: ExecExit::default(),
+        }
+    }
+}
+
+#[allow(clippy::print_stdout)]
+fn print_json_line<T: Serialize>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(line) => {
+            println!("{line}");
+        }
+        Err(e) => {
+            error!("Failed to serialize event: {e:?}");
+        }
+    }
+}
+
+impl EventProcessor for EventProcessorWithEventsJsonOutput {
+    fn print_config_summary(&mut self, _: &Config, _: &str, ev: &protocol::SessionConfiguredEvent) {
+        // A resumed session replays its history here; leave it out so the
+        // output looks the same as for a fresh run.
+        let ev = protocol::SessionConfiguredEvent {
+            initial_messages: None,
+            ..ev.clone()
+        };
+        self.process_event(protocol::Event {
+            id: "".to_string(),
+            msg: protocol::EventMsg::SessionConfigured(ev),
+        });
+    }
+
+    fn process_event(&mut self, event: protocol::Event) -> CodexStatus {
+        self.record_event(&event);
+        print_json_line(&event.msg);
+
+        match &event.msg {
+            protocol::EventMsg::TurnComplete(protocol::TurnCompleteEvent {
+                last_agent_message,
+            }) => {
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                CodexStatus::InitiateShutdown
+            }
+            protocol::EventMsg::ShutdownComplete => CodexStatus::Shutdown,
+            _ => CodexStatus::Running,
+        }
+    }
+
+    fn print_final_output(&mut self, exit: &ExecExit) {
+        let summary = ExecSummary {
+            exit: exit.clone(),
+            ..self.summary()
+        };
+        print_json_line(&ExecSummaryEvent::ExecSummary(summary));
+    }
+}
@@ -18,15 +18,24 @@ use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
-use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecOutcome;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FileChangeKind;
+use codex_core::protocol::FileChangeSummary;
+use codex_core::protocol::HeadlessApprovalRequest;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerState;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::OutcomeStatus;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ProviderSwitchReason;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::StreamRecoveryStrategy;
+use codex_core::protocol::ToolBudgetKind;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnCompleteEvent;
 use codex_core::protocol::TurnDiffEvent;
@@ -38,11 +47,13 @@ use owo_colors::Style;
 use shlex::try_join;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 use std::time::Instant;
 
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
+use crate::exec_events::ExecExit;
 use codex_common::create_config_summary_entries;
 use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
@@ -228,6 +239,39 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 };
                 ts_msg!(self, "{} {}", "mcp startup:".style(self.cyan), joined);
             }
+            EventMsg::McpServerStatus(status) => match status.state {
+                McpServerState::Ready => {
+                    let tools = if status.effective_tools.is_empty() {
+                        "no tools".to_string()
+                    } else {
+                        status.effective_tools.join(", ")
+                    };
+                    ts_msg!(
+                        self,
+                        "{} {} tools: {}",
+                        "mcp:".style(self.cyan),
+                        status.server,
+                        tools
+                    );
+                }
+                McpServerState::Down | McpServerState::Disabled => {
+                    let what = if status.state == McpServerState::Down {
+                        "went down"
+                    } else {
+                        "was disabled"
+                    };
+                    ts_msg!(
+                        self,
+                        "{} {} {what}: {}",
+                        "mcp:".style(self.cyan),
+                        status.server,
+                        status.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                // Startup failures are already reported by `McpStartupUpdate`.
+                McpServerState::Failed | McpServerState::Cancelled | McpServerState::Restarting => {
+                }
+            },
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 ts_msg!(self, "{}", message.style(self.dimmed));
             }
@@ -242,6 +286,28 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 };
                 ts_msg!(self, "{}", message.style(self.dimmed));
             }
+            EventMsg::StreamRetry(ev) => match ev.strategy {
+                StreamRecoveryStrategy::Restart => {}
+                StreamRecoveryStrategy::ResumeAfterToolCalls => {
+                    ts_msg!(
+                        self,
+                        "{}",
+                        format!(
+                            "stream cut off; resuming after {} completed tool call(s)",
+                            ev.tool_calls_preserved
+                        )
+                        .style(self.dimmed)
+                    );
+                }
+                StreamRecoveryStrategy::Continue => {
+                    ts_msg!(
+                        self,
+                        "{}",
+                        "stream cut off; asking the model to continue its response"
+                            .style(self.dimmed)
+                    );
+                }
+            },
             EventMsg::TurnStarted(_) => {
                 // Ignore.
             }
@@ -258,6 +324,288 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     "auto-cancelling (not supported in exec mode)".style(self.dimmed)
                 );
             }
+            EventMsg::McpSamplingRequest(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} {}",
+                    "sampling request".style(self.magenta),
+                    ev.server_name.style(self.dimmed),
+                    ev.model.style(self.dimmed)
+                );
+                if ev.requires_approval {
+                    ts_msg!(
+                        self,
+                        "{}",
+                        "auto-declining (approval not supported in exec mode)".style(self.dimmed)
+                    );
+                }
+            }
+            EventMsg::McpSamplingResponse(ev) => match ev.result {
+                Ok(_) => ts_msg!(
+                    self,
+                    "{} {}",
+                    "sampling response".style(self.magenta),
+                    ev.server_name.style(self.dimmed)
+                ),
+                Err(err) => ts_msg!(
+                    self,
+                    "{} {} {}",
+                    "sampling failed".style(self.red),
+                    ev.server_name.style(self.dimmed),
+                    err.style(self.dimmed)
+                ),
+            },
+            EventMsg::HeadlessApprovalDecision(ev) => {
+                let subject = match &ev.request {
+                    HeadlessApprovalRequest::Exec { command, .. } => {
+                        escape_command(command).style(self.bold).to_string()
+                    }
+                    HeadlessApprovalRequest::Patch { files } => format!(
+                        "patch to {}",
+                        files
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                };
+                match ev.decision {
+                    ReviewDecision::Denied
+                    | ReviewDecision::DeniedWithReason { .. }
+                    | ReviewDecision::Abort => ts_msg!(
+                        self,
+                        "{} {} {}",
+                        format!("{} denied", ev.policy).style(self.red),
+                        subject,
+                        ev.reason.style(self.dimmed)
+                    ),
+                    _ => ts_msg!(
+                        self,
+                        "{} {}",
+                        format!("{} approved", ev.policy).style(self.green),
+                        subject
+                    ),
+                }
+            }
+            EventMsg::ToolCallAborted(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} after {}",
+                    "aborted".style(self.red),
+                    ev.tool_name,
+                    format_duration(ev.duration)
+                );
+            }
+            EventMsg::FailureReported(ev) => {
+                ts_msg!(self, "{} {}", "failure reported".style(self.red), ev.reason);
+            }
+            EventMsg::OutcomeReported(ev) => {
+                let label = format!("outcome: {}", ev.outcome.status);
+                let label = match ev.outcome.status {
+                    OutcomeStatus::Success => label.style(self.green),
+                    OutcomeStatus::Failure => label.style(self.red),
+                    OutcomeStatus::NeedsHuman => label.style(self.yellow),
+                };
+                ts_msg!(self, "{} {}", label, ev.outcome.summary);
+                if let Some(details) = &ev.outcome.details {
+                    ts_msg!(self, "  {}", details.style(self.dimmed));
+                }
+            }
+            EventMsg::LoopDetected(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} failed {} times in a row (exit code {})",
+                    "warning:".style(self.yellow).style(self.bold),
+                    escape_command(&ev.command).style(self.bold),
+                    ev.failures,
+                    ev.exit_code
+                );
+            }
+            EventMsg::GitStateChanged(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} {}",
+                    "warning:".style(self.yellow).style(self.bold),
+                    escape_command(&ev.command).style(self.bold),
+                    ev.before.describe_change(&ev.after)
+                );
+            }
+            EventMsg::BudgetExceeded(ev) => {
+                let budget = match ev.budget {
+                    ToolBudgetKind::TurnToolCalls => format!("{} tool calls per turn", ev.limit),
+                    ToolBudgetKind::SessionToolCalls => {
+                        format!("{} tool calls per session", ev.limit)
+                    }
+                    ToolBudgetKind::TurnExecTime => format!(
+                        "{} of command time per turn",
+                        format_duration(Duration::from_millis(ev.limit))
+                    ),
+                };
+                ts_msg!(
+                    self,
+                    "{} tool budget exhausted ({budget}){}",
+                    "warning:".style(self.yellow).style(self.bold),
+                    if ev.ends_turn {
+                        "; ending the turn"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            EventMsg::ProviderSwitched(ev) => {
+                let reason = match ev.reason {
+                    ProviderSwitchReason::RateLimited => "rate limits",
+                    ProviderSwitchReason::ServerError => "server errors",
+                    ProviderSwitchReason::Disconnected => "dropped connections",
+                };
+                ts_msg!(
+                    self,
+                    "{} switched from {} ({}) to {} ({}) after repeated {reason}{}",
+                    "warning:".style(self.yellow).style(self.bold),
+                    ev.from.provider,
+                    ev.from.model,
+                    ev.to.provider.style(self.bold),
+                    ev.to.model,
+                    if ev.retried_in_turn {
+                        ""
+                    } else {
+                        "; the next turn will use it"
+                    }
+                );
+            }
+            EventMsg::ModelChanged(ev) => {
+                ts_msg!(
+                    self,
+                    "{} model changed from {} ({}) to {} ({}){}",
+                    "info:".style(self.cyan),
+                    ev.from.model,
+                    ev.from.provider,
+                    ev.to.model.style(self.bold),
+                    ev.to.provider,
+                    if ev.compacts_history {
+                        "; compacting the history to fit its context window"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            EventMsg::WorkspacePrimer(ev) => {
+                ts_msg!(
+                    self,
+                    "{} shared a workspace primer: {}",
+                    "info:".style(self.cyan),
+                    ev.summary()
+                );
+            }
+            EventMsg::WorkspaceSnapshotCreated(ev) => {
+                ts_msg!(
+                    self,
+                    "{} took snapshot {} of {}{}",
+                    "info:".style(self.cyan),
+                    ev.snapshot.id.style(self.bold),
+                    ev.snapshot.cwd.display(),
+                    if ev.command.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" before `{}`", escape_command(&ev.command))
+                    }
+                );
+            }
+            EventMsg::WorkspaceSnapshotRestored(ev) => {
+                if ev.restored {
+                    ts_msg!(self, "{} {}", "info:".style(self.cyan), ev.message);
+                } else {
+                    ts_msg!(
+                        self,
+                        "{} {}",
+                        "warning:".style(self.yellow).style(self.bold),
+                        ev.message
+                    );
+                }
+                for path in &ev.would_remove {
+                    ts_msg!(self, "  {}", path.display());
+                }
+            }
+            EventMsg::WorkspaceSnapshots(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} snapshot(s)",
+                    "info:".style(self.cyan),
+                    ev.snapshots.len()
+                );
+                for snapshot in &ev.snapshots {
+                    ts_msg!(self, "  {} {}", snapshot.id, snapshot.cwd.display());
+                }
+            }
+            EventMsg::CapabilitiesChanged(ev) => {
+                ts_msg!(
+                    self,
+                    "{} capabilities changed: {}",
+                    "info:".style(self.cyan),
+                    ev.before.describe_change(&ev.after)
+                );
+            }
+            EventMsg::WorkspaceRootsChanged(ev) => {
+                let names: Vec<&str> = ev.roots.iter().map(|root| root.name.as_str()).collect();
+                ts_msg!(
+                    self,
+                    "{} workspace roots are now: {}",
+                    "info:".style(self.cyan),
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                );
+            }
+            EventMsg::PlanOnlyChanged(ev) => {
+                ts_msg!(
+                    self,
+                    "{} plan-only mode is {}",
+                    "info:".style(self.cyan),
+                    if ev.plan_only { "on" } else { "off" }
+                );
+            }
+            EventMsg::ProposedExec(ev) => {
+                ts_msg!(
+                    self,
+                    "{} {} in {}",
+                    "proposed (not run)".style(self.italic).style(self.magenta),
+                    escape_command(&ev.command.command).style(self.bold),
+                    ev.command.cwd.to_string_lossy(),
+                );
+            }
+            EventMsg::ProposedPatch(ev) => {
+                let paths: Vec<String> = ev
+                    .patch
+                    .summaries
+                    .iter()
+                    .map(|summary| summary.path.to_string_lossy().into_owned())
+                    .collect();
+                ts_msg!(
+                    self,
+                    "{} {}",
+                    "proposed patch (not applied)"
+                        .style(self.italic)
+                        .style(self.magenta),
+                    paths.join(", ").style(self.bold),
+                );
+            }
+            EventMsg::BackgroundJobStatus(ev) => {
+                let exit = ev
+                    .exit_code
+                    .map(|code| format!(" (exit {code})"))
+                    .unwrap_or_default();
+                ts_msg!(
+                    self,
+                    "{} {} {}{}: {}",
+                    "background job".style(self.magenta),
+                    ev.job_id,
+                    ev.status,
+                    exit,
+                    escape_command(&ev.command).style(self.bold),
+                );
+            }
             EventMsg::TurnComplete(TurnCompleteEvent { last_agent_message }) => {
                 let last_message = last_agent_message.as_deref();
                 if let Some(output_file) = self.last_message_path.as_deref() {
@@ -304,25 +652,26 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     cwd.to_string_lossy(),
                 );
             }
-            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
-                aggregated_output,
-                duration,
-                exit_code,
-                ..
-            }) => {
-                let duration = format!(" in {}", format_duration(duration));
+            EventMsg::ExecCommandEnd(end) => {
+                if let Some(ExecOutcome::SpawnFailed { message, .. }) = &end.outcome {
+                    let title = format!(" failed to start: {message}");
+                    ts_msg!(self, "{}", title.style(self.red));
+                    return CodexStatus::Running;
+                }
+                let duration = format!(" in {}", format_duration(end.duration));
 
-                let truncated_output = aggregated_output
+                let truncated_output = end
+                    .display_output()
                     .lines()
                     .take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL)
                     .collect::<Vec<_>>()
                     .join("\n");
-                match exit_code {
+                match end.exit_code {
                     0 => {
                         let title = format!(" succeeded{duration}:");
                         ts_msg!(self, "{}", title.style(self.green));
                     }
-                    _ => {
+                    exit_code => {
                         let title = format!(" exited {exit_code}{duration}:");
                         ts_msg!(self, "{}", title.style(self.red));
                     }
@@ -332,6 +681,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
                 call_id: _,
                 invocation,
+                timeout_ms: _,
+                arguments_preview: _,
             }) => {
                 ts_msg!(
                     self,
@@ -347,6 +698,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     result,
                     invocation,
                     duration,
+                    ..
                 } = tool_call_end_event;
 
                 let duration = format!(" in {}", format_duration(duration));
@@ -377,6 +729,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 call_id,
                 auto_approved,
                 changes,
+                summaries,
                 ..
             }) => {
                 // Store metadata so we can calculate duration later when we
@@ -454,6 +807,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         }
                     }
                 }
+
+                // Large patches arrive without their contents; list what is
+                // being touched instead.
+                if changes.is_empty() {
+                    for summary in &summaries {
+                        let header = format_file_change_summary(summary);
+                        eprintln!("{}", header.style(self.magenta));
+                    }
+                }
             }
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
                 call_id,
@@ -490,7 +852,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     eprintln!("{}", line.style(self.dimmed));
                 }
             }
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
                 ts_msg!(
                     self,
                     "{}",
@@ -576,9 +938,20 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_msg!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::SoftInterrupt => {
+                    ts_msg!(self, "task ended after its running tool call");
+                }
             },
-            EventMsg::ContextCompacted(_) => {
-                ts_msg!(self, "context compacted");
+            EventMsg::ContextCompacted(ev) => {
+                if ev.tokens_reclaimed > 0 {
+                    ts_msg!(
+                        self,
+                        "context compacted, ~{} tokens reclaimed",
+                        ev.tokens_reclaimed
+                    );
+                } else {
+                    ts_msg!(self, "context compacted");
+                }
             }
             EventMsg::CollabAgentSpawnBegin(CollabAgentSpawnBeginEvent {
                 call_id,
@@ -743,8 +1116,27 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::TerminalInteraction(_)
             | EventMsg::ExecCommandOutputDelta(_)
             | EventMsg::GetHistoryEntryResponse(_)
+            | EventMsg::TurnSummary(_)
+            | EventMsg::TurnProgress(_)
+            | EventMsg::FileReadBegin(_)
+            | EventMsg::FileReadEnd(_)
+            | EventMsg::WorkspaceSearchBegin(_)
+            | EventMsg::WorkspaceSearchEnd(_)
+            | EventMsg::ListDirBegin(_)
+            | EventMsg::ListDirEnd(_)
+            | EventMsg::ExecOutputReadBegin(_)
+            | EventMsg::ExecOutputReadEnd(_)
+            | EventMsg::FileAccessLedger(_)
             | EventMsg::McpListToolsResponse(_)
+            | EventMsg::McpListPromptsResponse(_)
+            | EventMsg::McpPromptResponse(_)
+            | EventMsg::McpToolCallProgress(_)
+            | EventMsg::McpToolCallOutputDelta(_)
             | EventMsg::ListCustomPromptsResponse(_)
+            | EventMsg::EffectiveConfigResponse(_)
+            | EventMsg::ConfigReloaded(_)
+            | EventMsg::HookExecuted(_)
+            | EventMsg::NotificationSent(_)
             | EventMsg::ListSkillsResponse(_)
             | EventMsg::RawResponseItem(_)
             | EventMsg::UserMessage(_)
@@ -763,12 +1155,13 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::UndoStarted(_)
             | EventMsg::ThreadRolledBack(_)
             | EventMsg::RequestUserInput(_)
-            | EventMsg::DynamicToolCallRequest(_) => {}
+            | EventMsg::DynamicToolCallRequest(_)
+            | EventMsg::ToolCallBlocked(_) => {}
         }
         CodexStatus::Running
     }
 
-    fn print_final_output(&mut self) {
+    fn print_final_output(&mut self, exit: &ExecExit) {
         if let Some(usage_info) = &self.last_total_token_usage {
             eprintln!(
                 "{}\n{}",
@@ -777,6 +1170,13 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             );
         }
 
+        if let Some(message) = &exit.message {
+            eprintln!(
+                "{} {message}",
+                format!("exit code {}:", exit.code).style(self.red)
+            );
+        }
+
         // If the user has not piped the final message to a file, they will see
         // it twice: once written to stderr as part of the normal event
         // processing, and once here on stdout. We print the token summary above
@@ -796,6 +1196,27 @@ fn escape_command(command: &[String]) -> String {
     try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "))
 }
 
+fn format_file_change_summary(summary: &FileChangeSummary) -> String {
+    let marker = match (summary.kind, &summary.move_path) {
+        (FileChangeKind::Add, _) => "A",
+        (FileChangeKind::Delete, _) => "D",
+        (FileChangeKind::Update, Some(_)) => "R",
+        (FileChangeKind::Update, None) => "M",
+    };
+    let path = match &summary.move_path {
+        Some(dest) => format!(
+            "{} -> {}",
+            summary.path.to_string_lossy(),
+            dest.to_string_lossy()
+        ),
+        None => summary.path.to_string_lossy().into_owned(),
+    };
+    format!(
+        "{marker} {path} ({} bytes, contents omitted)",
+        summary.bytes
+    )
+}
+
 fn format_file_change(change: &FileChange) -> &'static str {
     match change {
         FileChange::Add { .. } => "A",
@@ -118,6 +118,7 @@ impl EventProcessorWithJsonOutput {
             protocol::EventMsg::AgentReasoning(ev) => self.handle_reasoning_event(ev),
             protocol::EventMsg::ExecCommandBegin(ev) => self.handle_exec_command_begin(ev),
             protocol::EventMsg::ExecCommandEnd(ev) => self.handle_exec_command_end(ev),
+            protocol::EventMsg::ToolCallAborted(ev) => self.handle_tool_call_aborted(ev),
             protocol::EventMsg::TerminalInteraction(ev) => self.handle_terminal_interaction(ev),
             protocol::EventMsg::ExecCommandOutputDelta(ev) => {
                 self.handle_output_chunk(&ev.call_id, &ev.chunk)
@@ -606,6 +607,32 @@ impl EventProcessorWithJsonOutput {
         }
     }
 
+    /// Large patches arrive with their contents omitted, so fall back to the
+    /// per-file summaries when `changes` is empty.
+    fn file_update_changes(&self, ev: &protocol::PatchApplyBeginEvent) -> Vec<FileUpdateChange> {
+        if ev.changes.is_empty() {
+            return ev
+                .summaries
+                .iter()
+                .map(|summary| FileUpdateChange {
+                    path: summary.path.to_str().unwrap_or("").to_string(),
+                    kind: match summary.kind {
+                        protocol::FileChangeKind::Add => PatchChangeKind::Add,
+                        protocol::FileChangeKind::Delete => PatchChangeKind::Delete,
+                        protocol::FileChangeKind::Update => PatchChangeKind::Update,
+                    },
+                })
+                .collect();
+        }
+        ev.changes
+            .iter()
+            .map(|(path, change)| FileUpdateChange {
+                path: path.to_str().unwrap_or("").to_string(),
+                kind: self.map_change_kind(change),
+            })
+            .collect()
+    }
+
     fn handle_patch_apply_end(&mut self, ev: &protocol::PatchApplyEndEvent) -> Vec<ThreadEvent> {
         if let Some(running_patch_apply) = self.running_patch_applies.remove(&ev.call_id) {
             let status = if ev.success {
@@ -617,14 +644,7 @@ impl EventProcessorWithJsonOutput {
                 id: self.get_next_item_id(),
 
                 details: ThreadItemDetails::FileChange(FileChangeItem {
-                    changes: running_patch_apply
-                        .changes
-                        .iter()
-                        .map(|(path, change)| FileUpdateChange {
-                            path: path.to_str().unwrap_or("").to_string(),
-                            kind: self.map_change_kind(change),
-                        })
-                        .collect(),
+                    changes: self.file_update_changes(&running_patch_apply),
                     status,
                 }),
             };
@@ -653,10 +673,9 @@ impl EventProcessorWithJsonOutput {
         } else {
             CommandExecutionStatus::Failed
         };
-        let aggregated_output = if ev.aggregated_output.is_empty() {
-            aggregated_output
-        } else {
-            ev.aggregated_output.clone()
+        let aggregated_output = match ev.display_output() {
+            "" => aggregated_output,
+            output => output.to_string(),
         };
         let item = ThreadItem {
             id: item_id,
@@ -672,6 +691,33 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
     }
 
+    /// Completes a command that was still running when its turn was
+    /// interrupted, since it will not get an `ExecCommandEnd`.
+    fn handle_tool_call_aborted(
+        &mut self,
+        ev: &protocol::ToolCallAbortedEvent,
+    ) -> Vec<ThreadEvent> {
+        let Some(RunningCommand {
+            command,
+            item_id,
+            aggregated_output,
+        }) = self.running_commands.remove(&ev.call_id)
+        else {
+            return Vec::new();
+        };
+        let item = ThreadItem {
+            id: item_id,
+            details: ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                command,
+                aggregated_output,
+                exit_code: None,
+                status: CommandExecutionStatus::Failed,
+            }),
+        };
+
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
+    }
+
     fn todo_items_from_plan(&self, args: &UpdatePlanArgs) -> Vec<TodoItem> {
         args.plan
             .iter()
@@ -1,8 +1,11 @@
+use codex_protocol::protocol::ReportedOutcome;
+use codex_protocol::protocol::TokenUsage;
 use mcp_types::ContentBlock as McpContentBlock;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use ts_rs::TS;
 
 /// Top-level JSONL events emitted by codex exec
@@ -300,3 +303,78 @@ pub struct TodoItem {
 pub struct TodoListItem {
     pub items: Vec<TodoItem>,
 }
+
+/// Last line printed by `codex exec --json-events`, after the protocol
+/// events. Shares the `type` tag with `EventMsg` so every line can be
+/// dispatched on it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecSummaryEvent {
+    ExecSummary(ExecSummary),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
+pub struct ExecSummary {
+    /// The agent's final message, if the turn produced one.
+    pub last_agent_message: Option<String>,
+    /// Tokens used by the whole session, as last reported by `token_count`.
+    pub total_token_usage: Option<TokenUsage>,
+    /// Estimated tokens of the tool output the session sent to the model.
+    pub tool_output_tokens: u64,
+    /// Approximate cost of the session, when the model has a price under
+    /// `[token_prices]`.
+    pub estimated_cost: Option<f64>,
+    /// Files touched by successfully applied patches, sorted.
+    pub files_changed: Vec<PathBuf>,
+    /// The last outcome the model gave with `report_outcome`, if any.
+    pub outcome: Option<ReportedOutcome>,
+    /// The process exit code and why it was chosen.
+    pub exit: ExecExit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
+pub struct ExecExit {
+    pub code: i32,
+    pub reason: ExitReason,
+    /// What led to a non-zero exit, e.g. the error or the failure reported
+    /// by the model.
+    pub message: Option<String>,
+}
+
+/// Why `codex exec` exited with its code. When several apply, the first in
+/// this list that does wins.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    /// Codex panicked. Exit code 101.
+    InternalError,
+    /// The run did not finish within `--max-duration`. Exit code 124.
+    Timeout,
+    /// The model or its provider failed the turn. Exit code 3.
+    ModelError,
+    /// The model called `report_failure` or reported a `failure` outcome,
+    /// or the final message matched `--fail-if`. Exit code 4.
+    ReportedFailure,
+    /// The model reported a `needs_human` outcome. Exit code 6.
+    NeedsHuman,
+    /// The sandbox or an approval decision stopped a tool call and the turn
+    /// ended without getting past it. Exit code 5.
+    Blocked,
+    /// The turn completed. Exit code 0.
+    #[default]
+    Completed,
+}
+
+impl ExitReason {
+    pub fn code(self) -> i32 {
+        match self {
+            Self::InternalError => 101,
+            Self::Timeout => 124,
+            Self::ModelError => 3,
+            Self::ReportedFailure => 4,
+            Self::NeedsHuman => 6,
+            Self::Blocked => 5,
+            Self::Completed => 0,
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use codex_core::protocol::CodexErrorInfo;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::OutcomeStatus;
+use codex_core::protocol::ReportedOutcome;
+use regex_lite::Regex;
+
+use crate::exec_events::ExecExit;
+use crate::exec_events::ExitReason;
+
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+/// Records panics on any thread, including ones a task survives, so the run
+/// can exit with [`ExitReason::InternalError`].
+pub(crate) fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        PANICKED.store(true, Ordering::SeqCst);
+        previous(info);
+    }));
+}
+
+/// Collects what decides the exit code of a run from its events.
+#[derive(Debug, Default)]
+pub(crate) struct ExitStatusTracker {
+    fail_if: Option<Regex>,
+    model_error: Option<String>,
+    reported_failure: Option<String>,
+    /// The last `report_outcome` call; later calls replace earlier ones.
+    outcome: Option<ReportedOutcome>,
+    /// A block that kept a finished turn from getting anywhere.
+    blocked: Option<String>,
+    /// The latest block in the turn in progress. It only counts once the turn
+    /// ends with it as the last tool result, or with no tool call succeeding.
+    turn_block: Option<String>,
+    last_result_blocked: bool,
+    tool_succeeded: bool,
+    timeout: Option<Duration>,
+}
+
+impl ExitStatusTracker {
+    pub(crate) fn new(fail_if: Option<Regex>) -> Self {
+        Self {
+            fail_if,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn record_event(&mut self, msg: &EventMsg) {
+        match msg {
+            EventMsg::Error(ev) if ev.codex_error_info == Some(CodexErrorInfo::SandboxError) => {
+                self.record_block(&ev.message);
+            }
+            EventMsg::Error(ev) => {
+                self.model_error.get_or_insert_with(|| ev.message.clone());
+            }
+            EventMsg::ToolCallBlocked(ev) => {
+                self.record_block(&ev.message);
+            }
+            EventMsg::ExecCommandEnd(ev) if ev.exit_code == 0 => self.record_tool_success(),
+            EventMsg::PatchApplyEnd(ev) if ev.success => self.record_tool_success(),
+            EventMsg::McpToolCallEnd(ev) if ev.is_success() => self.record_tool_success(),
+            EventMsg::FailureReported(ev) => {
+                self.reported_failure
+                    .get_or_insert_with(|| ev.reason.clone());
+            }
+            EventMsg::OutcomeReported(ev) => {
+                self.outcome = Some(ev.outcome.clone());
+            }
+            EventMsg::TurnComplete(ev) => {
+                if let Some(message) = self.turn_blocking_message() {
+                    let message = message.to_string();
+                    self.blocked.get_or_insert(message);
+                }
+                self.turn_block = None;
+                self.last_result_blocked = false;
+                self.tool_succeeded = false;
+                if let (Some(pattern), Some(message)) = (&self.fail_if, &ev.last_agent_message)
+                    && pattern.is_match(message)
+                {
+                    self.reported_failure.get_or_insert_with(|| {
+                        format!("the final message matches --fail-if `{pattern}`")
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record_block(&mut self, message: &str) {
+        self.turn_block = Some(message.to_string());
+        self.last_result_blocked = true;
+    }
+
+    fn record_tool_success(&mut self) {
+        self.last_result_blocked = false;
+        self.tool_succeeded = true;
+    }
+
+    /// The block that kept the turn in progress from finishing, if any: one
+    /// the model recovered from by running something else does not count.
+    fn turn_blocking_message(&self) -> Option<&str> {
+        if self.last_result_blocked || !self.tool_succeeded {
+            self.turn_block.as_deref()
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn record_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    pub(crate) fn exit(&self) -> ExecExit {
+        let (reason, message) = if PANICKED.load(Ordering::SeqCst) {
+            (
+                ExitReason::InternalError,
+                Some("codex panicked; see stderr for details".to_string()),
+            )
+        } else if let Some(timeout) = self.timeout {
+            (
+                ExitReason::Timeout,
+                Some(format!(
+                    "the run did not finish within {}s",
+                    timeout.as_secs()
+                )),
+            )
+        } else if let Some(message) = &self.model_error {
+            (ExitReason::ModelError, Some(message.clone()))
+        } else if let Some(message) = &self.reported_failure {
+            (ExitReason::ReportedFailure, Some(message.clone()))
+        } else if let Some(ReportedOutcome {
+            status: OutcomeStatus::Failure,
+            summary,
+            ..
+        }) = &self.outcome
+        {
+            (ExitReason::ReportedFailure, Some(summary.clone()))
+        } else if let Some(ReportedOutcome {
+            status: OutcomeStatus::NeedsHuman,
+            summary,
+            ..
+        }) = &self.outcome
+        {
+            (ExitReason::NeedsHuman, Some(summary.clone()))
+        } else if let Some(message) = self
+            .blocked
+            .as_deref()
+            .or_else(|| self.turn_blocking_message())
+        {
+            (ExitReason::Blocked, Some(message.to_string()))
+        } else {
+            (ExitReason::Completed, None)
+        };
+        ExecExit {
+            code: reason.code(),
+            reason,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::ErrorEvent;
+    use codex_core::protocol::ExecCommandEndEvent;
+    use codex_core::protocol::ExecCommandSource;
+    use codex_core::protocol::FailureReportedEvent;
+    use codex_core::protocol::OutcomeReportedEvent;
+    use codex_core::protocol::ToolCallBlockKind;
+    use codex_core::protocol::ToolCallBlockedEvent;
+    use codex_core::protocol::TurnCompleteEvent;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn turn_complete(message: &str) -> EventMsg {
+        EventMsg::TurnComplete(TurnCompleteEvent {
+            last_agent_message: Some(message.to_string()),
+        })
+    }
+
+    fn blocked() -> EventMsg {
+        EventMsg::ToolCallBlocked(ToolCallBlockedEvent {
+            call_id: "call-1".to_string(),
+            turn_id: "turn-1".to_string(),
+            kind: ToolCallBlockKind::Sandbox,
+            message: "the sandbox denied the command (exit code 1)".to_string(),
+        })
+    }
+
+    #[test]
+    fn completed_turn_exits_zero() {
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&turn_complete("done"));
+        assert_eq!(tracker.exit(), ExecExit::default());
+    }
+
+    fn command_end(exit_code: i32) -> EventMsg {
+        EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+            call_id: "call-2".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec!["cargo".to_string(), "test".to_string()],
+            cwd: PathBuf::from("/repo"),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::ModelShell,
+            interaction_input: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            aggregated_output: String::new(),
+            exit_code,
+            outcome: None,
+            duration: Duration::from_millis(10),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
+            formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
+        })
+    }
+
+    #[test]
+    fn a_block_the_model_recovers_from_exits_zero() {
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&blocked());
+        tracker.record_event(&command_end(0));
+        tracker.record_event(&turn_complete("done"));
+        assert_eq!(tracker.exit(), ExecExit::default());
+    }
+
+    #[test]
+    fn a_block_that_ends_the_turn_exits_blocked() {
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&command_end(0));
+        tracker.record_event(&blocked());
+        tracker.record_event(&command_end(1));
+        tracker.record_event(&turn_complete("the sandbox stopped me"));
+        assert_eq!(tracker.exit().reason, ExitReason::Blocked);
+        assert_eq!(tracker.exit().code, 5);
+
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&blocked());
+        tracker.record_event(&command_end(2));
+        tracker.record_event(&turn_complete("nothing worked"));
+        assert_eq!(tracker.exit().reason, ExitReason::Blocked);
+    }
+
+    #[test]
+    fn fail_if_pattern_and_report_failure_outrank_blocks() {
+        let mut tracker = ExitStatusTracker::new(Some(Regex::new("(?i)cannot").unwrap()));
+        tracker.record_event(&blocked());
+        assert_eq!(tracker.exit().reason, ExitReason::Blocked);
+        assert_eq!(tracker.exit().code, 5);
+
+        tracker.record_event(&turn_complete("I cannot finish this."));
+        assert_eq!(
+            tracker.exit(),
+            ExecExit {
+                code: 4,
+                reason: ExitReason::ReportedFailure,
+                message: Some("the final message matches --fail-if `(?i)cannot`".to_string()),
+            }
+        );
+
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&EventMsg::FailureReported(FailureReportedEvent {
+            call_id: "call-2".to_string(),
+            turn_id: "turn-1".to_string(),
+            reason: "tests need network access".to_string(),
+        }));
+        assert_eq!(tracker.exit().reason, ExitReason::ReportedFailure);
+        assert_eq!(
+            tracker.exit().message.as_deref(),
+            Some("tests need network access")
+        );
+    }
+
+    fn outcome(status: OutcomeStatus, summary: &str) -> EventMsg {
+        EventMsg::OutcomeReported(OutcomeReportedEvent {
+            call_id: "call-outcome".to_string(),
+            turn_id: "turn-1".to_string(),
+            outcome: ReportedOutcome {
+                status,
+                summary: summary.to_string(),
+                details: None,
+            },
+            replaced_previous: false,
+        })
+    }
+
+    #[test]
+    fn the_last_reported_outcome_decides_the_exit() {
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&outcome(OutcomeStatus::Failure, "tests fail"));
+        assert_eq!(
+            tracker.exit(),
+            ExecExit {
+                code: 4,
+                reason: ExitReason::ReportedFailure,
+                message: Some("tests fail".to_string()),
+            }
+        );
+
+        tracker.record_event(&outcome(OutcomeStatus::NeedsHuman, "which API version?"));
+        assert_eq!(
+            tracker.exit(),
+            ExecExit {
+                code: 6,
+                reason: ExitReason::NeedsHuman,
+                message: Some("which API version?".to_string()),
+            }
+        );
+
+        tracker.record_event(&outcome(OutcomeStatus::Success, "done"));
+        assert_eq!(tracker.exit(), ExecExit::default());
+    }
+
+    #[test]
+    fn timeout_and_model_errors_outrank_the_rest() {
+        let mut tracker = ExitStatusTracker::new(None);
+        tracker.record_event(&blocked());
+        tracker.record_event(&EventMsg::Error(ErrorEvent {
+            message: "stream disconnected".to_string(),
+            codex_error_info: None,
+        }));
+        assert_eq!(tracker.exit().reason, ExitReason::ModelError);
+        assert_eq!(tracker.exit().code, 3);
+
+        tracker.record_timeout(Duration::from_secs(60));
+        assert_eq!(
+            tracker.exit(),
+            ExecExit {
+                code: 124,
+                reason: ExitReason::Timeout,
+                message: Some("the run did not finish within 60s".to_string()),
+            }
+        );
+    }
+}
@@ -1,15 +1,26 @@
 // - In the default output mode, it is paramount that the only thing written to
 //   stdout is the final message (if any).
-// - In --json mode, stdout must be valid JSONL, one event per line.
+// - In --json and --json-events modes, stdout must be valid JSONL, one event
+//   per line.
 // For both modes, any other output must be written to stderr.
 #![deny(clippy::print_stdout)]
 
+#[cfg(unix)]
+mod attach;
+mod attachments;
 mod cli;
 mod event_processor;
+pub mod event_processor_with_events_jsonl_output;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+mod exit_status;
+mod output_diff;
 
+#[cfg(unix)]
+pub use attach::AttachCli;
+#[cfg(unix)]
+pub use attach::run_attach;
 pub use cli::Cli;
 pub use cli::Command;
 pub use cli::ReviewArgs;
@@ -30,25 +41,32 @@ use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::config::resolve_oss_provider;
 use codex_core::config_loader::ConfigLoadError;
 use codex_core::config_loader::format_config_error_with_source;
+use codex_core::features::Feature;
 use codex_core::git_info::get_git_repo_root;
+use codex_core::headless_approvals::HeadlessApprovals;
 use codex_core::models_manager::manager::RefreshStrategy;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SessionSource;
 use codex_protocol::approvals::ElicitationAction;
 use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::SessionMetaLine;
 use codex_protocol::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
+use event_processor_with_events_jsonl_output::EventProcessorWithEventsJsonOutput;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+use regex_lite::Regex;
 use serde_json::Value;
 use std::io::IsTerminal;
 use std::io::Read;
 use std::path::PathBuf;
+use std::time::Duration;
 use supports_color::Stream;
 use tracing::debug;
 use tracing::error;
@@ -56,11 +74,16 @@ use tracing::info;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
+use crate::attachments::check_image;
+use crate::attachments::stdin_document;
 use crate::cli::Command as ExecCommand;
+use crate::cli::ResumeArgs;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::exit_status::ExitStatusTracker;
+use crate::output_diff::handle_output_diff;
 use codex_core::default_client::set_default_originator;
-use codex_core::find_thread_path_by_id_str;
+use codex_core::find_thread_path_by_id_str_in_state_dirs;
 
 enum InitialOperation {
     UserTurn {
@@ -76,6 +99,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     if let Err(err) = set_default_originator("codex_exec".to_string()) {
         tracing::warn!(?err, "Failed to set codex exec originator override {err:?}");
     }
+    codex_core::install_rollout_panic_hook();
 
     let Cli {
         command,
@@ -91,13 +115,36 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         add_dir,
         color,
         last_message_file,
+        output_diff_file,
         json: json_mode,
+        json_events: json_events_mode,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
+        approvals,
+        fail_if,
+        max_duration_secs,
+        resume_session_id,
         config_overrides,
     } = cli;
 
+    // `--resume <id>` is shorthand for the `resume <id>` subcommand.
+    let command = match (command, resume_session_id) {
+        (command, None) => command,
+        (None, Some(session_id)) => Some(ExecCommand::Resume(ResumeArgs {
+            session_id: Some(session_id),
+            last: false,
+            all: false,
+            images: Vec::new(),
+            prompt: None,
+        })),
+        #[allow(clippy::print_stderr)]
+        (Some(_), Some(_)) => {
+            eprintln!("--resume cannot be combined with a subcommand.");
+            std::process::exit(1);
+        }
+    };
+
     let (stdout_with_ansi, stderr_with_ansi) = match color {
         cli::Color::Always => (true, true),
         cli::Color::Never => (false, false),
@@ -138,6 +185,37 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
+    let headless_approvals = match approvals.as_deref().map(HeadlessApprovals::parse) {
+        Some(Ok(policy)) => Some(policy),
+        None => None,
+        #[allow(clippy::print_stderr)]
+        Some(Err(e)) => {
+            eprintln!("Error parsing --approvals: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let fail_if = match fail_if.as_deref().map(Regex::new) {
+        Some(Ok(pattern)) => Some(pattern),
+        None => None,
+        #[allow(clippy::print_stderr)]
+        Some(Err(e)) => {
+            eprintln!("Error parsing --fail-if: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let resume_images = match &command {
+        Some(ExecCommand::Resume(args)) => args.images.as_slice(),
+        _ => &[],
+    };
+    for path in images.iter().chain(resume_images) {
+        if let Err(e) = check_image(path) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+
     let resolved_cwd = cwd.clone();
     let config_cwd = match resolved_cwd.as_deref() {
         Some(path) => AbsolutePathBuf::from_absolute_path(path.canonicalize()?)?,
@@ -164,15 +242,15 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         {
             Ok(config_toml) => config_toml,
             Err(err) => {
-                let config_error = err
+                let config_load_error = err
                     .get_ref()
-                    .and_then(|err| err.downcast_ref::<ConfigLoadError>())
-                    .map(ConfigLoadError::config_error);
-                if let Some(config_error) = config_error {
-                    eprintln!(
-                        "Error loading config.toml:\n{}",
-                        format_config_error_with_source(config_error)
-                    );
+                    .and_then(|err| err.downcast_ref::<ConfigLoadError>());
+                if let Some(config_load_error) = config_load_error {
+                    let formatted: Vec<String> = config_load_error
+                        .config_errors()
+                        .map(format_config_error_with_source)
+                        .collect();
+                    eprintln!("Error loading config.toml:\n{}", formatted.join("\n\n"));
                 } else {
                     eprintln!("Error loading config.toml: {err}");
                 }
@@ -216,8 +294,12 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         model,
         review_model: None,
         config_profile,
-        // Default to never ask for approvals in headless mode. Feature flags can override.
-        approval_policy: Some(AskForApproval::Never),
+        // Default to never ask for approvals in headless mode. With `--approvals`,
+        // the configured policy decides what needs approval and the headless
+        // policy answers it.
+        approval_policy: headless_approvals
+            .is_none()
+            .then_some(AskForApproval::Never),
         sandbox_mode,
         cwd: resolved_cwd,
         model_provider: model_provider.clone(),
@@ -230,17 +312,30 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         show_raw_agent_reasoning: oss.then_some(true),
         tools_web_search_request: None,
         ephemeral: None,
+        headless_approvals,
         additional_writable_roots: add_dir,
+        workspace_roots: Default::default(),
     };
 
-    let config =
+    let mut config =
         Config::load_with_cli_overrides_and_harness_overrides(cli_kv_overrides, overrides).await?;
+    // Lets the model end the run with the reported-failure exit code.
+    config.features.enable(Feature::ReportFailure);
+    let max_duration = max_duration_secs.map(Duration::from_secs);
+    // Tool timeouts are clamped to the time left, so no single command can
+    // use up the whole budget.
+    config.tool_timeouts.deadline = max_duration.map(|max| std::time::Instant::now() + max);
 
     if let Err(err) = enforce_login_restrictions(&config) {
         eprintln!("{err}");
         std::process::exit(1);
     }
 
+    if let Err(err) = ensure_no_interactive_prompts(&config) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+
     let ollama_chat_support_notice = match ollama_chat_deprecation_notice(&config).await {
         Ok(notice) => notice,
         Err(err) => {
@@ -273,13 +368,20 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         .with(otel_logger_layer)
         .try_init();
 
-    let mut event_processor: Box<dyn EventProcessor> = match json_mode {
-        true => Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone())),
-        _ => Box::new(EventProcessorWithHumanOutput::create_with_ansi(
+    exit_status::install_panic_hook();
+
+    let mut event_processor: Box<dyn EventProcessor> = if json_events_mode {
+        Box::new(EventProcessorWithEventsJsonOutput::new(
+            last_message_file.clone(),
+        ))
+    } else if json_mode {
+        Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
+    } else {
+        Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
             &config,
             last_message_file.clone(),
-        )),
+        ))
     };
     if let Some(notice) = ollama_chat_support_notice {
         event_processor.process_event(Event {
@@ -348,6 +450,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             thread_manager
                 .resume_thread_from_rollout(config.clone(), path, auth_manager.clone())
                 .await?
+        } else if let Some(id_str) = args.session_id.as_deref().filter(|_| !args.last) {
+            eprintln!("{}", missing_session_message(&config, id_str).await);
+            std::process::exit(1);
         } else {
             thread_manager.start_thread(config.clone()).await?
         }
@@ -372,6 +477,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                     }
                 })
                 .or(root_prompt);
+            let stdin_document = read_stdin_document(prompt_arg.as_deref());
             let prompt_text = resolve_prompt(prompt_arg);
             let mut items: Vec<UserInput> = imgs
                 .into_iter()
@@ -383,6 +489,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 // CLI input doesn't track UI element ranges, so none are available here.
                 text_elements: Vec::new(),
             });
+            items.extend(stdin_document);
             let output_schema = load_output_schema(output_schema_path.clone());
             (
                 InitialOperation::UserTurn {
@@ -393,6 +500,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             )
         }
         (None, root_prompt, imgs) => {
+            let stdin_document = read_stdin_document(root_prompt.as_deref());
             let prompt_text = resolve_prompt(root_prompt);
             let mut items: Vec<UserInput> = imgs
                 .into_iter()
@@ -403,6 +511,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 // CLI input doesn't track UI element ranges, so none are available here.
                 text_elements: Vec::new(),
             });
+            items.extend(stdin_document);
             let output_schema = load_output_schema(output_schema_path);
             (
                 InitialOperation::UserTurn {
@@ -489,10 +598,33 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     // Run the loop until the task is complete.
-    // Track whether a fatal error was reported by the server so we can
-    // exit with a non-zero status for automation-friendly signaling.
-    let mut error_seen = false;
-    while let Some(event) = rx.recv().await {
+    // Track what went wrong so the exit code tells scripts how the run ended.
+    let mut exit_status = ExitStatusTracker::new(fail_if);
+    let mut deadline = config
+        .tool_timeouts
+        .deadline
+        .map(tokio::time::Instant::from_std);
+    // The tracker re-sends the whole turn's diff, so the last one is final.
+    let mut last_turn_diff = None;
+    loop {
+        let event = match (deadline, max_duration) {
+            (Some(at), Some(max_duration)) => tokio::select! {
+                event = rx.recv() => event,
+                () = tokio::time::sleep_until(at) => {
+                    deadline = None;
+                    exit_status.record_timeout(max_duration);
+                    // Interrupting reports in-flight tool calls as aborted;
+                    // shutting down then flushes the rollout.
+                    thread.submit(Op::Interrupt).await?;
+                    thread.submit(Op::Shutdown).await?;
+                    continue;
+                }
+            },
+            _ => rx.recv().await,
+        };
+        let Some(event) = event else {
+            break;
+        };
         if let EventMsg::ElicitationRequest(ev) = &event.msg {
             // Automatically cancel elicitation requests in exec mode.
             thread
@@ -503,8 +635,21 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 })
                 .await?;
         }
-        if matches!(event.msg, EventMsg::Error(_)) {
-            error_seen = true;
+        if let EventMsg::McpSamplingRequest(ev) = &event.msg
+            && ev.requires_approval
+        {
+            // Nobody can approve sampling requests in exec mode.
+            thread
+                .submit(Op::ResolveMcpSampling {
+                    server_name: ev.server_name.clone(),
+                    request_id: ev.id.clone(),
+                    decision: ReviewDecision::Denied,
+                })
+                .await?;
+        }
+        exit_status.record_event(&event.msg);
+        if let EventMsg::TurnDiff(ev) = &event.msg {
+            last_turn_diff = Some(ev.unified_diff.clone());
         }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
@@ -517,9 +662,13 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             }
         }
     }
-    event_processor.print_final_output();
-    if error_seen {
-        std::process::exit(1);
+    let exit = exit_status.exit();
+    event_processor.print_final_output(&exit);
+    if let Some(output_file) = output_diff_file.as_deref() {
+        handle_output_diff(last_turn_diff.as_deref(), output_file);
+    }
+    if exit.code != 0 {
+        std::process::exit(exit.code);
     }
 
     Ok(())
@@ -538,6 +687,7 @@ async fn resolve_resume_path(
         };
         match codex_core::RolloutRecorder::find_latest_thread_path(
             &config.codex_home,
+            &config.state_dir,
             1,
             None,
             codex_core::ThreadSortKey::UpdatedAt,
@@ -555,13 +705,98 @@ async fn resolve_resume_path(
             }
         }
     } else if let Some(id_str) = args.session_id.as_deref() {
-        let path = find_thread_path_by_id_str(&config.codex_home, id_str).await?;
+        let path =
+            find_thread_path_by_id_str_in_state_dirs(&config.codex_home, &config.state_dir, id_str)
+                .await?;
         Ok(path)
     } else {
         Ok(None)
     }
 }
 
+/// Number of sessions listed when `resume` is given an unknown id.
+const RECENT_SESSIONS_LISTED: usize = 5;
+
+/// Explains that no session has id `id_str` and lists the most recently
+/// updated ones, whichever client recorded them.
+async fn missing_session_message(config: &Config, id_str: &str) -> String {
+    let recent = match codex_core::RolloutRecorder::list_threads_with_state_dir(
+        &config.codex_home,
+        &config.state_dir,
+        RECENT_SESSIONS_LISTED,
+        None,
+        codex_core::ThreadSortKey::UpdatedAt,
+        &[],
+        None,
+        &config.model_provider_id,
+    )
+    .await
+    {
+        Ok(page) => page
+            .items
+            .iter()
+            .filter_map(|item| {
+                let meta = item.head.iter().find_map(|value| {
+                    serde_json::from_value::<SessionMetaLine>(value.clone()).ok()
+                })?;
+                Some(RecentSession {
+                    id: meta.meta.id.to_string(),
+                    updated_at: item.updated_at.clone(),
+                    cwd: meta.meta.cwd,
+                })
+            })
+            .collect(),
+        Err(e) => {
+            error!("Error listing threads: {e}");
+            Vec::new()
+        }
+    };
+    format_missing_session(id_str, &recent)
+}
+
+struct RecentSession {
+    id: String,
+    updated_at: Option<String>,
+    cwd: PathBuf,
+}
+
+fn format_missing_session(id_str: &str, recent: &[RecentSession]) -> String {
+    let mut message = format!("No saved session found with ID {id_str}.");
+    if recent.is_empty() {
+        return message;
+    }
+    message.push_str(" Recent sessions:");
+    for session in recent {
+        let updated_at = session.updated_at.as_deref().unwrap_or("-");
+        message.push_str(&format!(
+            "\n  {}  {updated_at}  {}",
+            session.id,
+            session.cwd.display()
+        ));
+    }
+    message
+}
+
+/// Rejects configurations that would leave the run waiting on a prompt nobody
+/// can answer.
+fn ensure_no_interactive_prompts(config: &Config) -> Result<(), String> {
+    let approval_policy = config.approval_policy.value();
+    if config.headless_approvals.is_none() && approval_policy != AskForApproval::Never {
+        return Err(format!(
+            "approval_policy `{approval_policy}` would prompt for approval, which codex exec cannot answer. \
+             Pass --approvals auto-readonly, deny-all, or allow-list=<FILE> to resolve approvals without prompting."
+        ));
+    }
+    if config.features.enabled(Feature::CollaborationModes) {
+        return Err(
+            "the collaboration_modes feature lets the model ask questions, which codex exec cannot answer. \
+             Disable it with -c features.collaboration_modes=false."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
 fn load_output_schema(path: Option<PathBuf>) -> Option<Value> {
     let path = path?;
 
@@ -661,6 +896,30 @@ fn decode_utf16(
     String::from_utf16(&units).map_err(|_| PromptDecodeError::InvalidUtf16 { encoding })
 }
 
+/// With a prompt on the command line, piped stdin is attached as a document
+/// instead of being read as the prompt.
+fn read_stdin_document(prompt_arg: Option<&str>) -> Option<UserInput> {
+    if matches!(prompt_arg, None | Some("-")) || std::io::stdin().is_terminal() {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    if let Err(e) = std::io::stdin().read_to_end(&mut bytes) {
+        eprintln!("Failed to read stdin: {e}");
+        std::process::exit(1);
+    }
+    let document = decode_prompt_bytes(&bytes)
+        .map_err(|e| format!("Failed to read stdin: {e}"))
+        .and_then(stdin_document);
+    match document {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn resolve_prompt(prompt_arg: Option<String>) -> String {
     match prompt_arg {
         Some(p) if p != "-" => p,
@@ -872,4 +1131,22 @@ mod tests {
 
         assert_eq!(err, PromptDecodeError::InvalidUtf8 { valid_up_to: 0 });
     }
+
+    #[test]
+    fn missing_session_message_lists_recent_sessions() {
+        assert_eq!(
+            format_missing_session("abc", &[]),
+            "No saved session found with ID abc."
+        );
+
+        let recent = [RecentSession {
+            id: "019a0000-0000-7000-8000-000000000001".to_string(),
+            updated_at: Some("2025-01-02T03:04:05Z".to_string()),
+            cwd: PathBuf::from("/work/repo"),
+        }];
+        assert_eq!(
+            format_missing_session("abc", &recent),
+            "No saved session found with ID abc. Recent sessions:\n  019a0000-0000-7000-8000-000000000001  2025-01-02T03:04:05Z  /work/repo"
+        );
+    }
 }
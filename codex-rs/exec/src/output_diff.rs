@@ -0,0 +1,56 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::DiffStats;
+
+/// Writes the run's final diff to `output_file` and its [`DiffStats`] to
+/// `<output_file>.stats.json`. A run without changes writes an empty diff.
+pub(crate) fn handle_output_diff(unified_diff: Option<&str>, output_file: &Path) {
+    let unified_diff = unified_diff.unwrap_or_default();
+    if let Err(e) = std::fs::write(output_file, unified_diff) {
+        eprintln!("Failed to write diff file {output_file:?}: {e}");
+        return;
+    }
+
+    let stats_path = stats_path(output_file);
+    let stats = DiffStats::from_unified_diff(unified_diff);
+    match serde_json::to_string_pretty(&stats) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&stats_path, json) {
+                eprintln!("Failed to write diff stats file {stats_path:?}: {e}");
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize diff stats: {e}"),
+    }
+}
+
+fn stats_path(output_file: &Path) -> PathBuf {
+    let mut path = OsString::from(output_file.as_os_str());
+    path.push(".stats.json");
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty_diff_writes_empty_file_and_zero_stats() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let output_file = dir.path().join("changes.diff");
+
+        handle_output_diff(None, &output_file);
+
+        assert_eq!(std::fs::read_to_string(&output_file).unwrap(), "");
+        let stats: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("changes.diff.stats.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            serde_json::json!({ "files_changed": 0, "lines_added": 0, "lines_removed": 0 })
+        );
+    }
+}
@@ -2,4 +2,5 @@
 // The submodules live in `tests/suite/`.
 mod suite;
 
+mod event_processor_with_events_json_output;
 mod event_processor_with_json_output;
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::OutcomeReportedEvent;
+use codex_core::protocol::OutcomeStatus;
+use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ReportedOutcome;
+use codex_core::protocol::TokenCountEvent;
+use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::TurnCompleteEvent;
+use codex_core::protocol::TurnCost;
+use codex_core::protocol::TurnSummaryEvent;
+use codex_exec::event_processor_with_events_jsonl_output::EventProcessorWithEventsJsonOutput;
+use codex_exec::exec_events::ExecExit;
+use codex_exec::exec_events::ExecSummary;
+use codex_exec::exec_events::ExecSummaryEvent;
+use codex_exec::exec_events::ExitReason;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn event(id: &str, msg: EventMsg) -> Event {
+    Event {
+        id: id.to_string(),
+        msg,
+    }
+}
+
+fn patch_apply_end(call_id: &str, success: bool, changes: &[(&str, FileChange)]) -> Event {
+    event(
+        call_id,
+        EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+            call_id: call_id.to_string(),
+            turn_id: "turn-1".to_string(),
+            stdout: String::new(),
+            stderr: String::new(),
+            success,
+            changes: changes
+                .iter()
+                .map(|(path, change)| (PathBuf::from(path), change.clone()))
+                .collect::<HashMap<_, _>>(),
+        }),
+    )
+}
+
+#[test]
+fn summary_collects_last_message_token_usage_cost_changed_files_and_outcome() {
+    let mut ep = EventProcessorWithEventsJsonOutput::new(None);
+    assert_eq!(ep.summary(), ExecSummary::default());
+
+    let usage = TokenUsage {
+        input_tokens: 1200,
+        cached_input_tokens: 200,
+        output_tokens: 300,
+        reasoning_output_tokens: 50,
+        total_tokens: 1500,
+    };
+    ep.record_event(&event(
+        "t1",
+        EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: usage.clone(),
+                last_token_usage: usage.clone(),
+                model_context_window: None,
+                by_provider: Default::default(),
+                prompt_cache: Default::default(),
+                tool_output_tokens: 480,
+            }),
+            rate_limits: None,
+        }),
+    ));
+    // A later count without usage info keeps the last known total.
+    ep.record_event(&event(
+        "t2",
+        EventMsg::TokenCount(TokenCountEvent {
+            info: None,
+            rate_limits: None,
+        }),
+    ));
+    ep.record_event(&patch_apply_end(
+        "p1",
+        true,
+        &[
+            (
+                "src/b.rs",
+                FileChange::Add {
+                    content: "b".to_string(),
+                },
+            ),
+            (
+                "src/a.rs",
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: Some(PathBuf::from("src/c.rs")),
+                },
+            ),
+        ],
+    ));
+    ep.record_event(&patch_apply_end(
+        "p2",
+        false,
+        &[(
+            "src/failed.rs",
+            FileChange::Delete {
+                content: String::new(),
+            },
+        )],
+    ));
+    for (status, summary) in [
+        (OutcomeStatus::Failure, "tests fail"),
+        (OutcomeStatus::Success, "fixed the tests"),
+    ] {
+        ep.record_event(&event(
+            "t3",
+            EventMsg::OutcomeReported(OutcomeReportedEvent {
+                call_id: "call-outcome".to_string(),
+                turn_id: "t3".to_string(),
+                outcome: ReportedOutcome {
+                    status,
+                    summary: summary.to_string(),
+                    details: None,
+                },
+                replaced_previous: status == OutcomeStatus::Success,
+            }),
+        ));
+    }
+    ep.record_event(&event(
+        "t3",
+        EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 2,
+            tool_time: Duration::from_secs(3),
+            model_time: Duration::from_secs(5),
+            plan: None,
+            prompt_cache: Default::default(),
+            cautions: Vec::new(),
+            tool_output_tokens: 480,
+            tool_call_tokens: Vec::new(),
+            token_usage: usage.clone(),
+            cost: Some(TurnCost {
+                turn: 0.004,
+                tool_output: 0.001,
+                session: 0.006,
+            }),
+        }),
+    ));
+    ep.record_event(&event(
+        "t3",
+        EventMsg::TurnComplete(TurnCompleteEvent {
+            last_agent_message: Some("done".to_string()),
+        }),
+    ));
+
+    assert_eq!(
+        ep.summary(),
+        ExecSummary {
+            last_agent_message: Some("done".to_string()),
+            total_token_usage: Some(usage),
+            tool_output_tokens: 480,
+            estimated_cost: Some(0.006),
+            files_changed: vec![
+                PathBuf::from("src/a.rs"),
+                PathBuf::from("src/b.rs"),
+                PathBuf::from("src/c.rs"),
+            ],
+            outcome: Some(ReportedOutcome {
+                status: OutcomeStatus::Success,
+                summary: "fixed the tests".to_string(),
+                details: None,
+            }),
+            exit: ExecExit::default(),
+        }
+    );
+}
+
+#[test]
+fn summary_line_is_tagged_like_protocol_events() {
+    let line = serde_json::to_value(ExecSummaryEvent::ExecSummary(ExecSummary {
+        last_agent_message: Some("done".to_string()),
+        total_token_usage: None,
+        tool_output_tokens: 0,
+        estimated_cost: None,
+        files_changed: vec![PathBuf::from("README.md")],
+        outcome: Some(ReportedOutcome {
+            status: OutcomeStatus::Failure,
+            summary: "tests need network access".to_string(),
+            details: None,
+        }),
+        exit: ExecExit {
+            code: 4,
+            reason: ExitReason::ReportedFailure,
+            message: Some("tests need network access".to_string()),
+        },
+    }))
+    .expect("serialize summary");
+
+    assert_eq!(
+        line,
+        json!({
+            "type": "exec_summary",
+            "last_agent_message": "done",
+            "total_token_usage": null,
+            "tool_output_tokens": 0,
+            "estimated_cost": null,
+            "files_changed": ["README.md"],
+            "outcome": {
+                "status": "failure",
+                "summary": "tests need network access",
+            },
+            "exit": {
+                "code": 4,
+                "reason": "reported_failure",
+                "message": "tests need network access",
+            },
+        })
+    );
+}
@@ -12,13 +12,17 @@ use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExecCommandSource;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FileChangeKind;
+use codex_core::protocol::FileChangeSummary;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallErrorSource;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::ToolCallAbortedEvent;
 use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
@@ -31,6 +35,8 @@ use codex_exec::exec_events::CollabToolCallStatus;
 use codex_exec::exec_events::CommandExecutionItem;
 use codex_exec::exec_events::CommandExecutionStatus;
 use codex_exec::exec_events::ErrorItem;
+use codex_exec::exec_events::FileChangeItem;
+use codex_exec::exec_events::FileUpdateChange;
 use codex_exec::exec_events::ItemCompletedEvent;
 use codex_exec::exec_events::ItemStartedEvent;
 use codex_exec::exec_events::ItemUpdatedEvent;
@@ -96,6 +102,9 @@ fn session_configured_produces_thread_started_event() {
             history_entry_count: 0,
             initial_messages: None,
             rollout_path: Some(rollout_path),
+            shell: None,
+            tools: None,
+            tmpdir: None,
         }),
     );
     let out = ep.collect_thread_events(&ev);
@@ -114,6 +123,7 @@ fn task_started_produces_turn_started_event() {
         "t1",
         EventMsg::TurnStarted(codex_core::protocol::TurnStartedEvent {
             model_context_window: Some(32_000),
+            tools: None,
         }),
     ));
 
@@ -274,6 +284,8 @@ fn mcp_tool_call_begin_and_end_emit_item_events() {
         EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
             call_id: "call-1".to_string(),
             invocation: invocation.clone(),
+            timeout_ms: None,
+            arguments_preview: String::new(),
         }),
     );
     let begin_events = ep.collect_thread_events(&begin);
@@ -305,6 +317,9 @@ fn mcp_tool_call_begin_and_end_emit_item_events() {
                 is_error: None,
                 structured_content: None,
             }),
+            is_error: false,
+            error_source: None,
+            result_preview: String::new(),
         }),
     );
     let end_events = ep.collect_thread_events(&end);
@@ -343,6 +358,8 @@ fn mcp_tool_call_failure_sets_failed_status() {
         EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
             call_id: "call-2".to_string(),
             invocation: invocation.clone(),
+            timeout_ms: None,
+            arguments_preview: String::new(),
         }),
     );
     ep.collect_thread_events(&begin);
@@ -354,6 +371,9 @@ fn mcp_tool_call_failure_sets_failed_status() {
             invocation,
             duration: Duration::from_millis(5),
             result: Err("tool exploded".to_string()),
+            is_error: true,
+            error_source: Some(McpToolCallErrorSource::Transport),
+            result_preview: String::new(),
         }),
     );
     let events = ep.collect_thread_events(&end);
@@ -391,6 +411,8 @@ fn mcp_tool_call_defaults_arguments_and_preserves_structured_content() {
         EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
             call_id: "call-3".to_string(),
             invocation: invocation.clone(),
+            timeout_ms: None,
+            arguments_preview: String::new(),
         }),
     );
     let begin_events = ep.collect_thread_events(&begin);
@@ -426,6 +448,9 @@ fn mcp_tool_call_defaults_arguments_and_preserves_structured_content() {
                 is_error: None,
                 structured_content: Some(json!({ "status": "ok" })),
             }),
+            is_error: false,
+            error_source: None,
+            result_preview: String::new(),
         }),
     );
     let events = ep.collect_thread_events(&end);
@@ -784,8 +809,10 @@ fn exec_command_end_success_produces_completed_command_item() {
             command: command.clone(),
             cwd: cwd.clone(),
             parsed_cmd: parsed_cmd.clone(),
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
+            timeout_ms: None,
+            injected_env: Default::default(),
         }),
     );
     let out_begin = ep.collect_thread_events(&begin);
@@ -814,14 +841,23 @@ fn exec_command_end_success_produces_completed_command_item() {
             command,
             cwd,
             parsed_cmd,
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: String::new(),
             stderr: String::new(),
             aggregated_output: "hi\n".to_string(),
             exit_code: 0,
+            outcome: None,
             duration: Duration::from_millis(5),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     );
     let out_ok = ep.collect_thread_events(&end_ok);
@@ -861,8 +897,10 @@ fn command_execution_output_delta_updates_item_progress() {
             command: command.clone(),
             cwd: cwd.clone(),
             parsed_cmd: parsed_cmd.clone(),
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
+            timeout_ms: None,
+            injected_env: Default::default(),
         }),
     );
     let out_begin = ep.collect_thread_events(&begin);
@@ -901,14 +939,23 @@ fn command_execution_output_delta_updates_item_progress() {
             command,
             cwd,
             parsed_cmd,
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: String::new(),
             stderr: String::new(),
             aggregated_output: String::new(),
             exit_code: 0,
+            outcome: None,
             duration: Duration::from_millis(3),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -945,8 +992,10 @@ fn exec_command_end_failure_produces_failed_command_item() {
             command: command.clone(),
             cwd: cwd.clone(),
             parsed_cmd: parsed_cmd.clone(),
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
+            timeout_ms: None,
+            injected_env: Default::default(),
         }),
     );
     assert_eq!(
@@ -974,14 +1023,23 @@ fn exec_command_end_failure_produces_failed_command_item() {
             command,
             cwd,
             parsed_cmd,
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: String::new(),
             stderr: String::new(),
             aggregated_output: String::new(),
             exit_code: 1,
+            outcome: None,
             duration: Duration::from_millis(2),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     );
     let out_fail = ep.collect_thread_events(&end_fail);
@@ -1015,20 +1073,74 @@ fn exec_command_end_without_begin_is_ignored() {
             command: Vec::new(),
             cwd: PathBuf::from("."),
             parsed_cmd: Vec::new(),
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: String::new(),
             stderr: String::new(),
             aggregated_output: String::new(),
             exit_code: 0,
+            outcome: None,
             duration: Duration::from_millis(1),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     );
     let out = ep.collect_thread_events(&end_only);
     assert!(out.is_empty());
 }
 
+#[test]
+fn tool_call_aborted_completes_running_command_as_failed() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let begin = event(
+        "c1",
+        EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "3".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec!["sleep".to_string(), "60".to_string()],
+            cwd: std::env::current_dir().unwrap(),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::ModelShell,
+            interaction_input: None,
+            timeout_ms: None,
+            injected_env: Default::default(),
+        }),
+    );
+    ep.collect_thread_events(&begin);
+
+    let aborted = event(
+        "c2",
+        EventMsg::ToolCallAborted(ToolCallAbortedEvent {
+            call_id: "3".to_string(),
+            turn_id: "turn-1".to_string(),
+            tool_name: "shell_command".to_string(),
+            duration: Duration::from_secs(5),
+        }),
+    );
+    assert_eq!(
+        ep.collect_thread_events(&aborted),
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: ThreadItem {
+                id: "item_0".to_string(),
+                details: ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                    command: "sleep 60".to_string(),
+                    aggregated_output: String::new(),
+                    exit_code: None,
+                    status: CommandExecutionStatus::Failed,
+                }),
+            },
+        })]
+    );
+}
+
 #[test]
 fn patch_apply_success_produces_item_completed_patchapply() {
     let mut ep = EventProcessorWithJsonOutput::new(None);
@@ -1063,6 +1175,8 @@ fn patch_apply_success_produces_item_completed_patchapply() {
             turn_id: "turn-1".to_string(),
             auto_approved: true,
             changes: changes.clone(),
+            timeout_ms: None,
+            summaries: Vec::new(),
         }),
     );
     let out_begin = ep.collect_thread_events(&begin);
@@ -1135,6 +1249,8 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
             turn_id: "turn-2".to_string(),
             auto_approved: false,
             changes: changes.clone(),
+            timeout_ms: None,
+            summaries: Vec::new(),
         }),
     );
     assert!(ep.collect_thread_events(&begin).is_empty());
@@ -1171,6 +1287,59 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
     }
 }
 
+#[test]
+fn large_patch_apply_uses_summaries_for_file_changes() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+
+    // Over the content limit the begin event carries summaries only.
+    let begin = event(
+        "p1",
+        EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: "call-3".to_string(),
+            turn_id: "turn-3".to_string(),
+            auto_approved: true,
+            changes: std::collections::HashMap::new(),
+            timeout_ms: None,
+            summaries: vec![FileChangeSummary {
+                path: PathBuf::from("big.txt"),
+                kind: FileChangeKind::Add,
+                move_path: None,
+                bytes: 1 << 20,
+                sha256: "00".repeat(32),
+            }],
+        }),
+    );
+    assert!(ep.collect_thread_events(&begin).is_empty());
+
+    let end = event(
+        "p2",
+        EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+            call_id: "call-3".to_string(),
+            turn_id: "turn-3".to_string(),
+            stdout: "applied 1 change".to_string(),
+            stderr: String::new(),
+            success: true,
+            changes: std::collections::HashMap::new(),
+        }),
+    );
+    let out_end = ep.collect_thread_events(&end);
+    assert_eq!(
+        out_end,
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: ThreadItem {
+                id: "item_0".to_string(),
+                details: ThreadItemDetails::FileChange(FileChangeItem {
+                    changes: vec![FileUpdateChange {
+                        path: "big.txt".to_string(),
+                        kind: PatchChangeKind::Add,
+                    }],
+                    status: PatchApplyStatus::Completed,
+                }),
+            },
+        })]
+    );
+}
+
 #[test]
 fn task_complete_produces_turn_completed_with_usage() {
     let mut ep = EventProcessorWithJsonOutput::new(None);
@@ -1187,6 +1356,9 @@ fn task_complete_produces_turn_completed_with_usage() {
         total_token_usage: usage.clone(),
         last_token_usage: usage,
         model_context_window: None,
+        by_provider: Default::default(),
+        prompt_cache: Default::default(),
+        tool_output_tokens: 0,
     };
     let token_count_event = event(
         "e1",
@@ -0,0 +1,112 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+
+/// Verify that a final message matching `--fail-if` exits with the
+/// reported-failure code.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exits_with_reported_failure_when_final_message_matches_fail_if() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_response_created("response_1"),
+        responses::ev_assistant_message("response_1", "TASK FAILED: tests need network"),
+        responses::ev_completed("response_1"),
+    ]);
+    responses::mount_sse_once(&server, body).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--fail-if")
+        .arg("^TASK FAILED")
+        .arg("run the tests")
+        .assert()
+        .code(4);
+
+    Ok(())
+}
+
+/// Verify that a `report_failure` call exits with the reported-failure code
+/// even though the turn itself completes.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exits_with_reported_failure_when_model_calls_report_failure() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let first = responses::sse(vec![
+        responses::ev_response_created("response_1"),
+        responses::ev_function_call(
+            "call_1",
+            "report_failure",
+            r#"{"reason":"tests need network access"}"#,
+        ),
+        responses::ev_completed("response_1"),
+    ]);
+    let second = responses::sse(vec![
+        responses::ev_response_created("response_2"),
+        responses::ev_assistant_message("response_2", "I could not run the tests."),
+        responses::ev_completed("response_2"),
+    ]);
+    responses::mount_sse_sequence(&server, vec![first, second]).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--json-events")
+        .arg("run the tests")
+        .assert()
+        .code(4)
+        .stdout(predicates::str::contains(
+            r#""exit":{"code":4,"reason":"reported_failure","message":"tests need network access"}"#,
+        ));
+
+    Ok(())
+}
+
+/// Verify that the last `report_outcome` call decides the exit code and
+/// shows up in the summary line.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exits_with_needs_human_when_the_last_outcome_says_so() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let first = responses::sse(vec![
+        responses::ev_response_created("response_1"),
+        responses::ev_function_call(
+            "call_1",
+            "report_outcome",
+            r#"{"status":"success","summary":"tests pass"}"#,
+        ),
+        responses::ev_function_call(
+            "call_2",
+            "report_outcome",
+            r#"{"status":"needs_human","summary":"pick an API version"}"#,
+        ),
+        responses::ev_completed("response_1"),
+    ]);
+    let second = responses::sse(vec![
+        responses::ev_response_created("response_2"),
+        responses::ev_assistant_message("response_2", "Waiting on you."),
+        responses::ev_completed("response_2"),
+    ]);
+    responses::mount_sse_sequence(&server, vec![first, second]).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-c")
+        .arg("features.report_outcome=true")
+        .arg("--json-events")
+        .arg("update the client")
+        .assert()
+        .code(6)
+        .stdout(predicates::str::contains(
+            r#""outcome":{"status":"needs_human","summary":"pick an API version"}"#,
+        ))
+        .stdout(predicates::str::contains(
+            r#""exit":{"code":6,"reason":"needs_human","message":"pick an API version"}"#,
+        ));
+
+    Ok(())
+}
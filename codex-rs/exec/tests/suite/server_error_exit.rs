@@ -5,7 +5,7 @@ use core_test_support::responses;
 use core_test_support::test_codex_exec::test_codex_exec;
 
 /// Verify that when the server reports an error, `codex-exec` exits with a
-/// non-zero status code so automation can detect failures.
+/// with the model-error status code so automation can detect failures.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn exits_non_zero_when_server_reports_error() -> anyhow::Result<()> {
     let test = test_codex_exec();
@@ -27,7 +27,7 @@ async fn exits_non_zero_when_server_reports_error() -> anyhow::Result<()> {
         .arg("tell me something")
         .arg("--experimental-json")
         .assert()
-        .code(1);
+        .code(3);
 
     Ok(())
 }
@@ -0,0 +1,37 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+
+/// Verify that piped stdin is attached as a document next to a prompt given
+/// on the command line.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn attaches_piped_stdin_as_document() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_response_created("response_1"),
+        responses::ev_assistant_message("response_1", "Fixed"),
+        responses::ev_completed("response_1"),
+    ]);
+    let response_mock = responses::mount_sse_once(&server, body).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("fix this")
+        .write_stdin("error: linker failed\n")
+        .assert()
+        .code(0);
+
+    let texts = response_mock.single_request().message_input_texts("user");
+    assert!(texts.iter().any(|text| text == "fix this"));
+    assert!(
+        texts
+            .iter()
+            .any(|text| text == "<document name=\"stdin\">\nerror: linker failed\n\n</document>")
+    );
+
+    Ok(())
+}
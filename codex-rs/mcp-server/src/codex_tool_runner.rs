@@ -19,6 +19,7 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TurnCompleteEvent;
 use codex_protocol::ThreadId;
@@ -232,10 +233,13 @@ async fn run_codex_tool_session_inner(
                         turn_id: _,
                         command,
                         cwd,
+                        injected_env: _,
                         call_id,
                         reason: _,
                         proposed_execpolicy_amendment: _,
                         parsed_cmd,
+                        risk: _,
+                        sandbox_denial_output: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -269,6 +273,26 @@ async fn run_codex_tool_session_inner(
                         // TODO: forward elicitation requests to the client?
                         continue;
                     }
+                    EventMsg::McpSamplingRequest(ev) => {
+                        // The MCP client has no way to approve sampling
+                        // requests, so decline the ones that need approval
+                        // instead of leaving the server waiting forever.
+                        if ev.requires_approval
+                            && let Err(err) = thread
+                                .submit(Op::ResolveMcpSampling {
+                                    server_name: ev.server_name,
+                                    request_id: ev.id,
+                                    decision: ReviewDecision::Denied,
+                                })
+                                .await
+                        {
+                            tracing::error!("failed to decline sampling request: {err}");
+                        }
+                        continue;
+                    }
+                    EventMsg::McpSamplingResponse(_) => {
+                        continue;
+                    }
                     EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
                         call_id,
                         turn_id: _,
@@ -314,7 +338,9 @@ async fn run_codex_tool_session_inner(
                     EventMsg::AgentReasoningDelta(_) => {
                         // TODO: think how we want to support this in the MCP
                     }
-                    EventMsg::McpStartupUpdate(_) | EventMsg::McpStartupComplete(_) => {
+                    EventMsg::McpStartupUpdate(_)
+                    | EventMsg::McpStartupComplete(_)
+                    | EventMsg::McpServerStatus(_) => {
                         // Ignored in MCP tool runner.
                     }
                     EventMsg::AgentMessage(AgentMessageEvent { .. }) => {
@@ -329,7 +355,35 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::McpToolCallBegin(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::McpListToolsResponse(_)
+                    | EventMsg::McpListPromptsResponse(_)
+                    | EventMsg::McpPromptResponse(_)
+                    | EventMsg::McpToolCallProgress(_)
+                    | EventMsg::McpToolCallOutputDelta(_)
+                    | EventMsg::HeadlessApprovalDecision(_)
+                    | EventMsg::ToolCallBlocked(_)
+                    | EventMsg::ToolCallAborted(_)
+                    | EventMsg::FailureReported(_)
+                    | EventMsg::OutcomeReported(_)
+                    | EventMsg::LoopDetected(_)
+                    | EventMsg::GitStateChanged(_)
+                    | EventMsg::BudgetExceeded(_)
+                    | EventMsg::ProviderSwitched(_)
+                    | EventMsg::ModelChanged(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::EffectiveConfigResponse(_)
+                    | EventMsg::ConfigReloaded(_)
+                    | EventMsg::HookExecuted(_)
+                    | EventMsg::NotificationSent(_)
+                    | EventMsg::WorkspaceRootsChanged(_)
+                    | EventMsg::WorkspacePrimer(_)
+                    | EventMsg::WorkspaceSnapshotCreated(_)
+                    | EventMsg::WorkspaceSnapshotRestored(_)
+                    | EventMsg::WorkspaceSnapshots(_)
+                    | EventMsg::CapabilitiesChanged(_)
+                    | EventMsg::PlanOnlyChanged(_)
+                    | EventMsg::ProposedExec(_)
+                    | EventMsg::ProposedPatch(_)
+                    | EventMsg::BackgroundJobStatus(_)
                     | EventMsg::ListSkillsResponse(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::TerminalInteraction(_)
@@ -337,9 +391,21 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::ExecCommandEnd(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::StreamError(_)
+                    | EventMsg::StreamRetry(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
+                    | EventMsg::FileReadBegin(_)
+                    | EventMsg::FileReadEnd(_)
+                    | EventMsg::WorkspaceSearchBegin(_)
+                    | EventMsg::WorkspaceSearchEnd(_)
+                    | EventMsg::ListDirBegin(_)
+                    | EventMsg::ListDirEnd(_)
+                    | EventMsg::ExecOutputReadBegin(_)
+                    | EventMsg::ExecOutputReadEnd(_)
+                    | EventMsg::FileAccessLedger(_)
                     | EventMsg::TurnDiff(_)
+                    | EventMsg::TurnSummary(_)
+                    | EventMsg::TurnProgress(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchEnd(_)
                     | EventMsg::GetHistoryEntryResponse(_)
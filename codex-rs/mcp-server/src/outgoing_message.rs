@@ -268,6 +268,9 @@ mod tests {
                 history_entry_count: 1000,
                 initial_messages: None,
                 rollout_path: Some(rollout_file.path().to_path_buf()),
+                shell: None,
+                tools: None,
+                tmpdir: None,
             }),
         };
 
@@ -308,6 +311,9 @@ mod tests {
             history_entry_count: 1000,
             initial_messages: None,
             rollout_path: Some(rollout_file.path().to_path_buf()),
+            shell: None,
+            tools: None,
+            tmpdir: None,
         };
         let event = Event {
             id: "1".to_string(),
@@ -372,6 +378,9 @@ mod tests {
             history_entry_count: 1000,
             initial_messages: None,
             rollout_path: Some(rollout_file.path().to_path_buf()),
+            shell: None,
+            tools: None,
+            tmpdir: None,
         };
         let event = Event {
             id: "1".to_string(),
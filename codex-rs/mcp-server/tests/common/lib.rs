@@ -10,6 +10,7 @@ use mcp_types::JSONRPCResponse;
 pub use mock_model_server::create_mock_chat_completions_server;
 pub use responses::create_apply_patch_sse_response;
 pub use responses::create_final_assistant_message_sse_response;
+pub use responses::create_function_call_sse_response;
 pub use responses::create_shell_command_sse_response;
 use serde::de::DeserializeOwned;
 
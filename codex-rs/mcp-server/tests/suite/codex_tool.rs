@@ -26,9 +26,11 @@ use tokio::time::timeout;
 use wiremock::MockServer;
 
 use core_test_support::skip_if_no_network;
+use core_test_support::stdio_server_bin;
 use mcp_test_support::McpProcess;
 use mcp_test_support::create_apply_patch_sse_response;
 use mcp_test_support::create_final_assistant_message_sse_response;
+use mcp_test_support::create_function_call_sse_response;
 use mcp_test_support::create_mock_chat_completions_server;
 use mcp_test_support::create_shell_command_sse_response;
 use mcp_test_support::format_with_current_shell;
@@ -430,6 +432,88 @@ async fn codex_tool_passes_base_instructions() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The MCP client cannot approve sampling requests, so one that needs
+/// approval is declined instead of leaving the codex tool call waiting.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sampling_request_needing_approval_is_declined() {
+    skip_if_no_network!();
+
+    if let Err(err) = sampling_request_needing_approval_is_declined().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn sampling_request_needing_approval_is_declined() -> anyhow::Result<()> {
+    let server = create_mock_chat_completions_server(vec![
+        create_function_call_sse_response(
+            "mcp__rmcp__sample",
+            &json!({ "prompt": "Say hello." }),
+            "call-sample",
+        )?,
+        create_final_assistant_message_sse_response("The server could not sample.")?,
+    ])
+    .await;
+
+    let codex_home = TempDir::new()?;
+    let rmcp_test_server_bin = stdio_server_bin()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "never"
+sandbox_policy = "read-only"
+
+model_provider = "mock_provider"
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+
+[mcp_servers.rmcp]
+command = {rmcp_test_server_bin:?}
+sampling = {{ policy = "ask" }}
+"#,
+            server_uri = server.uri(),
+        ),
+    )?;
+    let mut mcp_process = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp_process.initialize()).await??;
+
+    let codex_request_id = mcp_process
+        .send_codex_tool_call(CodexToolCallParam {
+            prompt: "Ask the rmcp server to sample.".to_string(),
+            ..Default::default()
+        })
+        .await?;
+    let codex_response = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp_process.read_stream_until_response_message(RequestId::Integer(codex_request_id)),
+    )
+    .await??;
+    assert_eq!(
+        codex_response.result["structuredContent"]["content"],
+        json!("The server could not sample.")
+    );
+
+    // The declined sampling request reached the model as the tool's output.
+    let requests = server
+        .received_requests()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("mock server should record requests"))?;
+    assert_eq!(requests.len(), 2);
+    let follow_up = requests[1].body_json::<serde_json::Value>()?.to_string();
+    assert!(
+        follow_up.contains("declined by the user"),
+        "expected the declined sampling request in the tool output, got {follow_up}"
+    );
+
+    Ok(())
+}
+
 fn create_expected_patch_approval_elicitation_request(
     elicitation_request_id: RequestId,
     changes: HashMap<PathBuf, FileChange>,
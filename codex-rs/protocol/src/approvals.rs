@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::parse_command::ParsedCommand;
 use crate::protocol::FileChange;
+use crate::protocol::ReviewDecision;
 use mcp_types::RequestId;
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -49,6 +51,11 @@ pub struct ExecApprovalRequestEvent {
     pub command: Vec<String>,
     /// The command's working directory.
     pub cwd: PathBuf,
+    /// Variables the call sets for this command, as in
+    /// `ExecCommandBeginEvent.injected_env`. Values that look like secrets
+    /// are redacted.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub injected_env: BTreeMap<String, String>,
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
@@ -57,6 +64,35 @@ pub struct ExecApprovalRequestEvent {
     #[ts(optional)]
     pub proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     pub parsed_cmd: Vec<ParsedCommand>,
+    /// What the command would touch, when it could be split into plain
+    /// commands. Absent for scripts that use more than simple words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub risk: Option<CommandRisk>,
+    /// Output of the sandboxed attempt, when this request asks to retry the
+    /// command without the sandbox after it was denied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub sandbox_denial_output: Option<String>,
+}
+
+/// Risk classification of an exec request, derived from its arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CommandRisk {
+    /// The plain commands the request runs, each as program then arguments.
+    pub commands: Vec<Vec<String>>,
+    /// Arguments that make a command destructive, such as `-rf` or `--hard`.
+    pub destructive_args: Vec<String>,
+    /// Filesystem paths named by the commands, resolved against the cwd.
+    pub paths: Vec<PathBuf>,
+    /// Whether any of the commands is expected to use the network.
+    pub network: bool,
+}
+
+impl CommandRisk {
+    pub fn is_destructive(&self) -> bool {
+        !self.destructive_args.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -93,3 +129,26 @@ pub struct ApplyPatchApprovalRequestEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
 }
+
+/// An approval request answered by the headless approval policy instead of
+/// a person. Nothing waits for a reply.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct HeadlessApprovalDecisionEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub request: HeadlessApprovalRequest,
+    /// Name of the policy that decided, e.g. `deny-all`.
+    pub policy: String,
+    pub decision: ReviewDecision,
+    /// Why the request was approved or denied. A denial's reason is what the
+    /// model is told.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum HeadlessApprovalRequest {
+    Exec { command: Vec<String>, cwd: PathBuf },
+    Patch { files: Vec<PathBuf> },
+}
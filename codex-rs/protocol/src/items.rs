@@ -5,6 +5,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::UserMessageEvent;
 use crate::protocol::WebSearchEndEvent;
 use crate::user_input::ByteRange;
+use crate::user_input::DocumentReference;
 use crate::user_input::TextElement;
 use crate::user_input::UserInput;
 use schemars::JsonSchema;
@@ -71,6 +72,7 @@ impl UserMessageItem {
             images: Some(self.image_urls()),
             local_images: self.local_image_paths(),
             text_elements: self.text_elements(),
+            documents: self.documents(),
         })
     }
 
@@ -122,6 +124,19 @@ impl UserMessageItem {
             .collect()
     }
 
+    pub fn documents(&self) -> Vec<DocumentReference> {
+        self.content
+            .iter()
+            .filter_map(|c| match c {
+                UserInput::Document { name, text } => Some(DocumentReference {
+                    name: name.clone(),
+                    size_bytes: text.len(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn local_image_paths(&self) -> Vec<std::path::PathBuf> {
         self.content
             .iter()
@@ -392,6 +392,9 @@ const IMAGE_CLOSE_TAG: &str = "</image>";
 const LOCAL_IMAGE_OPEN_TAG_PREFIX: &str = "<image name=";
 const LOCAL_IMAGE_OPEN_TAG_SUFFIX: &str = ">";
 const LOCAL_IMAGE_CLOSE_TAG: &str = IMAGE_CLOSE_TAG;
+const DOCUMENT_OPEN_TAG_PREFIX: &str = "<document name=\"";
+const DOCUMENT_OPEN_TAG_SUFFIX: &str = "\">\n";
+const DOCUMENT_CLOSE_TAG: &str = "\n</document>";
 
 pub fn image_open_tag_text() -> String {
     IMAGE_OPEN_TAG.to_string()
@@ -419,6 +422,19 @@ pub fn is_local_image_close_tag_text(text: &str) -> bool {
     is_image_close_tag_text(text)
 }
 
+/// Wraps an attached document so the model can tell it apart from the
+/// prompt, and so history can map it back to a [`UserInput::Document`].
+pub fn document_text(name: &str, text: &str) -> String {
+    format!("{DOCUMENT_OPEN_TAG_PREFIX}{name}{DOCUMENT_OPEN_TAG_SUFFIX}{text}{DOCUMENT_CLOSE_TAG}")
+}
+
+/// Returns the name and text of a document wrapped by [`document_text`].
+pub fn parse_document_text(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix(DOCUMENT_OPEN_TAG_PREFIX)?;
+    let rest = rest.strip_suffix(DOCUMENT_CLOSE_TAG)?;
+    rest.split_once(DOCUMENT_OPEN_TAG_SUFFIX)
+}
+
 pub fn is_image_open_tag_text(text: &str) -> bool {
     text == IMAGE_OPEN_TAG
 }
@@ -612,6 +628,9 @@ impl From<Vec<UserInput>> for ResponseInputItem {
                         local_image_content_items_with_label_number(&path, Some(image_index))
                     }
                     UserInput::Skill { .. } => Vec::new(), // Skill bodies are injected later in core
+                    UserInput::Document { name, text } => vec![ContentItem::InputText {
+                        text: document_text(&name, &text),
+                    }],
                 })
                 .collect::<Vec<ContentItem>>(),
         }
@@ -633,6 +652,15 @@ pub struct ShellToolCallParams {
     pub sandbox_permissions: Option<SandboxPermissions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub justification: Option<String>,
+    /// Start the command as a background job and return its id right away.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub background: Option<bool>,
+    /// Variables to set for this command only, on top of the environment
+    /// built from `shell_environment_policy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub env: Option<HashMap<String, String>>,
 }
 
 /// If the `name` of a `ResponseItem::FunctionCall` is `shell_command`, the
@@ -653,6 +681,15 @@ pub struct ShellCommandToolCallParams {
     pub sandbox_permissions: Option<SandboxPermissions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub justification: Option<String>,
+    /// Start the command as a background job and return its id right away.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub background: Option<bool>,
+    /// Variables to set for this command only, on top of the environment
+    /// built from `shell_environment_policy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub env: Option<HashMap<String, String>>,
 }
 
 /// Responses API compatible content items that can be returned by a tool call.
@@ -1104,6 +1141,8 @@ mod tests {
                 timeout_ms: Some(1000),
                 sandbox_permissions: None,
                 justification: None,
+                background: None,
+                env: None,
             },
             params
         );
@@ -1137,6 +1176,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn wraps_document_user_input_with_tags() -> Result<()> {
+        let item = ResponseInputItem::from(vec![
+            UserInput::Text {
+                text: "fix this".to_string(),
+                text_elements: Vec::new(),
+            },
+            UserInput::Document {
+                name: "stdin".to_string(),
+                text: "error: linker failed".to_string(),
+            },
+        ]);
+
+        match item {
+            ResponseInputItem::Message { content, .. } => {
+                let expected = vec![
+                    ContentItem::InputText {
+                        text: "fix this".to_string(),
+                    },
+                    ContentItem::InputText {
+                        text: "<document name=\"stdin\">\nerror: linker failed\n</document>"
+                            .to_string(),
+                    },
+                ];
+                assert_eq!(content, expected);
+                let ContentItem::InputText { text } = &content[1] else {
+                    panic!("expected document text");
+                };
+                assert_eq!(
+                    parse_document_text(text),
+                    Some(("stdin", "error: linker failed"))
+                );
+            }
+            other => panic!("expected message response but got {other:?}"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn local_image_read_error_adds_placeholder() -> Result<()> {
         let dir = tempdir()?;
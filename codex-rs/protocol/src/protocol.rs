@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt;
@@ -16,6 +17,7 @@ use crate::approvals::ElicitationRequestEvent;
 use crate::config_types::CollaborationMode;
 use crate::config_types::Personality;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::config_types::SandboxMode;
 use crate::custom_prompts::CustomPrompt;
 use crate::dynamic_tools::DynamicToolCallRequest;
 use crate::dynamic_tools::DynamicToolResponse;
@@ -32,9 +34,15 @@ use crate::request_user_input::RequestUserInputResponse;
 use crate::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::Prompt as McpPrompt;
+use mcp_types::PromptMessage as McpPromptMessage;
 use mcp_types::RequestId;
 use mcp_types::Resource as McpResource;
 use mcp_types::ResourceTemplate as McpResourceTemplate;
+use mcp_types::TextContent;
 use mcp_types::Tool as McpTool;
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -46,9 +54,12 @@ use tracing::error;
 use ts_rs::TS;
 
 pub use crate::approvals::ApplyPatchApprovalRequestEvent;
+pub use crate::approvals::CommandRisk;
 pub use crate::approvals::ElicitationAction;
 pub use crate::approvals::ExecApprovalRequestEvent;
 pub use crate::approvals::ExecPolicyAmendment;
+pub use crate::approvals::HeadlessApprovalDecisionEvent;
+pub use crate::approvals::HeadlessApprovalRequest;
 pub use crate::request_user_input::RequestUserInputEvent;
 
 /// Open/close tags for special user-input blocks. Used across crates to avoid
@@ -87,6 +98,31 @@ pub enum Op {
     /// This server sends [`EventMsg::TurnAborted`] in response.
     Interrupt,
 
+    /// End the current turn once the running tool call has finished. Later
+    /// tool calls of the same model response are refused and the model is
+    /// not asked to continue; the turn ends with [`EventMsg::TurnAborted`]
+    /// and [`TurnAbortReason::SoftInterrupt`]. A second `SoftInterrupt` or
+    /// an `Interrupt` while one is pending aborts the turn right away.
+    SoftInterrupt,
+
+    /// Cancel a single tool call of the current turn, including one that is
+    /// still waiting for approval. The model receives an aborted result for
+    /// the call and the turn continues. Unknown or finished calls are ignored.
+    CancelToolCall { call_id: String },
+
+    /// Write user-typed input to a running unified exec process. The write is
+    /// reported back as a [`EventMsg::TerminalInteraction`] sourced from the
+    /// user so it shows up in the transcript alongside the model's input.
+    UnifiedExecInput { process_id: String, input: String },
+
+    /// Resize the PTY of a running unified exec process, e.g. to match the
+    /// panel a client renders its output in. Ignored for unknown processes.
+    ResizeUnifiedExecProcess {
+        process_id: String,
+        rows: u16,
+        cols: u16,
+    },
+
     /// Legacy user input.
     ///
     /// Prefer [`Op::UserTurn`] so the caller provides full turn context
@@ -209,6 +245,17 @@ pub enum Op {
         decision: ElicitationAction,
     },
 
+    /// Answer an `McpSamplingRequest` event that requires approval.
+    ResolveMcpSampling {
+        /// Name of the MCP server that issued the request.
+        server_name: String,
+        /// Request identifier from the MCP server.
+        request_id: RequestId,
+        /// `Approved` runs the completion, `ApprovedForSession` also runs the
+        /// server's later requests without asking. Anything else refuses it.
+        decision: ReviewDecision,
+    },
+
     /// Resolve a request_user_input tool call.
     #[serde(rename = "user_input_answer", alias = "request_user_input_response")]
     UserInputAnswer {
@@ -245,9 +292,49 @@ pub enum Op {
     /// Request MCP servers to reinitialize and refresh cached tool lists.
     RefreshMcpServers { config: McpServerRefreshConfig },
 
+    /// Start a configured MCP server again after it failed or was cancelled
+    /// during startup. Progress is reported with `EventMsg::McpStartupUpdate`
+    /// and the outcome with `EventMsg::McpServerStatus`.
+    RetryMcpServer { server: String },
+
+    /// List the tools of a running MCP server again. Turns started afterwards
+    /// see the new tools. The outcome is reported with
+    /// `EventMsg::McpServerStatus`, followed by an
+    /// `EventMsg::McpListToolsResponse` with the full tool inventory.
+    RefreshMcpTools { server: String },
+
+    /// Request the prompts advertised by MCP servers.
+    /// Reply is delivered via `EventMsg::McpListPromptsResponse`.
+    ListMcpPrompts,
+
+    /// Fetch an MCP prompt with `prompts/get` and add the messages it returns
+    /// to the input of the next turn. Reply is delivered via
+    /// `EventMsg::McpPromptResponse`; when it carries an error, nothing was
+    /// added.
+    RunMcpPrompt {
+        server: String,
+        prompt: String,
+        #[serde(default)]
+        arguments: HashMap<String, String>,
+    },
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Request the merged config for this session along with the layer that
+    /// set each value. Reply is delivered via `EventMsg::EffectiveConfigResponse`,
+    /// which is also sent once right after `SessionConfigured`.
+    GetEffectiveConfig,
+
+    /// Re-read config files from disk and apply the settings that may change
+    /// mid-session: tool timeouts, the tool output token budget, the shell
+    /// environment policy, and the approval and sandbox policy. Changes that
+    /// relax the approval or sandbox policy need the same
+    /// `allow_policy_overrides` opt-in as project configs. Other changes (for
+    /// example `model`) are rejected. Reply is delivered via
+    /// `EventMsg::ConfigReloaded`.
+    ReloadConfig,
+
     /// Request the list of skills for the provided `cwd` values or the session default.
     ListSkills {
         /// Working directories to scope repo skills discovery.
@@ -293,6 +380,66 @@ pub enum Op {
 
     /// Request the list of available models.
     ListModels,
+
+    /// Raise the session's tool budgets, e.g. once the user agreed to let a
+    /// turn that ran out of one go on. Limits left unset, or set lower than
+    /// the current ones, stay as they are; budgets without a limit stay
+    /// unlimited.
+    RaiseToolBudget {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_calls_per_turn: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_calls_per_session: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_exec_ms_per_turn: Option<u64>,
+    },
+
+    /// Switch the session to `model` from the next turn on. `provider` is a
+    /// key of `model_providers`; the current provider is kept when it is
+    /// unset. Answered with `ModelChanged`, or an error for an unknown
+    /// provider.
+    SwitchModel {
+        model: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        provider: Option<String>,
+    },
+
+    /// Remove the workspace roots named in `remove`, then add `add`, from
+    /// the next turn on. The sandbox's writable roots follow. Answered with
+    /// `WorkspaceRootsChanged`, or an error when a name is unknown, taken or
+    /// invalid, or a path is not a directory; nothing changes then.
+    UpdateWorkspaceRoots {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        add: Vec<WorkspaceRoot>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        remove: Vec<String>,
+    },
+
+    /// Turn plan-only mode on or off from the next turn on. In plan-only
+    /// mode no tool call runs a command, changes a file, or reaches an MCP
+    /// server or client tool; commands and patches are reported as
+    /// `ProposedExec` and `ProposedPatch` instead. Answered with
+    /// `PlanOnlyChanged`. Turning the mode off does not run anything that
+    /// was proposed.
+    SetPlanOnly { enabled: bool },
+
+    /// Snapshot the working tree, e.g. before a turn the user considers
+    /// risky, so `RestoreSnapshot` can go back to it. Answered with
+    /// `WorkspaceSnapshotCreated`, or an error when no snapshot could be
+    /// taken.
+    SnapshotWorkspace,
+
+    /// Restore the working tree to snapshot `id`. Refused when files
+    /// created since the snapshot would be deleted, unless `force`. Answered
+    /// with `WorkspaceSnapshotRestored`.
+    RestoreSnapshot {
+        id: String,
+        #[serde(default)]
+        force: bool,
+    },
+
+    /// List the session's snapshots. Answered with `WorkspaceSnapshots`.
+    ListSnapshots,
 }
 
 /// Determines the conditions under which the user is consulted to approve
@@ -434,6 +581,38 @@ impl WritableRoot {
     }
 }
 
+/// A named directory the session works in besides its `cwd`, e.g. the
+/// backend repository next to a frontend one. Every root is writable under a
+/// workspace-write sandbox, and paths under a root are shown as
+/// `name/relative/path`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceRoot {
+    pub name: String,
+    pub path: AbsolutePathBuf,
+}
+
+impl WorkspaceRoot {
+    /// `path` relative to the root it falls under, prefixed with the root's
+    /// name. The innermost root wins when roots nest. `None` when `path` is
+    /// under none of `roots`.
+    pub fn display_path(roots: &[WorkspaceRoot], path: &Path) -> Option<String> {
+        let (root, relative) = roots
+            .iter()
+            .filter_map(|root| {
+                path.strip_prefix(root.path.as_path())
+                    .ok()
+                    .map(|relative| (root, relative))
+            })
+            .min_by_key(|(_, relative)| relative.components().count())?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        Some(if relative.is_empty() {
+            root.name.clone()
+        } else {
+            format!("{}/{relative}", root.name)
+        })
+    }
+}
+
 impl FromStr for SandboxPolicy {
     type Err = serde_json::Error;
 
@@ -698,6 +877,18 @@ pub enum EventMsg {
     #[serde(rename = "task_complete", alias = "turn_complete")]
     TurnComplete(TurnCompleteEvent),
 
+    /// Where the time of a completed turn went, sent just before
+    /// [`EventMsg::TurnComplete`].
+    TurnSummary(TurnSummaryEvent),
+
+    /// The files a completed turn read and wrote, sent after
+    /// [`EventMsg::TurnSummary`] when there were any.
+    FileAccessLedger(FileAccessLedgerEvent),
+
+    /// Sent periodically while a turn runs tools, so clients can show
+    /// progress between model responses.
+    TurnProgress(TurnProgressEvent),
+
     /// Usage update for the current session, including totals and last turn.
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
@@ -734,10 +925,19 @@ pub enum EventMsg {
     /// Aggregate MCP startup completion summary.
     McpStartupComplete(McpStartupCompleteEvent),
 
+    /// Outcome of starting a single MCP server, including its tools.
+    McpServerStatus(McpServerStatusEvent),
+
     McpToolCallBegin(McpToolCallBeginEvent),
 
     McpToolCallEnd(McpToolCallEndEvent),
 
+    /// Progress reported by an MCP server for an in-flight tool call.
+    McpToolCallProgress(McpToolCallProgressEvent),
+
+    /// Partial output streamed by an MCP server for an in-flight tool call.
+    McpToolCallOutputDelta(McpToolCallOutputDeltaEvent),
+
     WebSearchBegin(WebSearchBeginEvent),
 
     WebSearchEnd(WebSearchEndEvent),
@@ -764,8 +964,17 @@ pub enum EventMsg {
 
     ElicitationRequest(ElicitationRequestEvent),
 
+    /// An MCP server asked for a model completion (`sampling/createMessage`).
+    McpSamplingRequest(McpSamplingRequestEvent),
+
+    /// How an MCP sampling request was answered.
+    McpSamplingResponse(McpSamplingResponseEvent),
+
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An approval request answered by the headless approval policy.
+    HeadlessApprovalDecision(HeadlessApprovalDecisionEvent),
+
     /// Notification advising the user that something they are using has been
     /// deprecated and should be phased out.
     DeprecationNotice(DeprecationNoticeEvent),
@@ -780,6 +989,10 @@ pub enum EventMsg {
     /// and the system is handling it (e.g., retrying with backoff).
     StreamError(StreamErrorEvent),
 
+    /// A model stream was cut off and its sampling request is retried;
+    /// records what was kept from the partial response.
+    StreamRetry(StreamRetryEvent),
+
     /// Notification that the agent is about to apply a code patch. Mirrors
     /// `ExecCommandBegin` so front‑ends can show progress indicators.
     PatchApplyBegin(PatchApplyBeginEvent),
@@ -787,17 +1000,124 @@ pub enum EventMsg {
     /// Notification that a patch application has finished.
     PatchApplyEnd(PatchApplyEndEvent),
 
+    /// The agent started reading a file with the `read_file` tool.
+    FileReadBegin(FileReadBeginEvent),
+
+    /// A `read_file` call finished.
+    FileReadEnd(FileReadEndEvent),
+
+    /// The agent started a `search_workspace` call.
+    WorkspaceSearchBegin(WorkspaceSearchBeginEvent),
+
+    /// A `search_workspace` call finished.
+    WorkspaceSearchEnd(WorkspaceSearchEndEvent),
+
+    /// The agent started a `list_dir` call.
+    ListDirBegin(ListDirBeginEvent),
+
+    /// A `list_dir` call finished.
+    ListDirEnd(ListDirEndEvent),
+
+    /// The agent started a `read_exec_output` call.
+    ExecOutputReadBegin(ExecOutputReadBeginEvent),
+
+    /// A `read_exec_output` call finished.
+    ExecOutputReadEnd(ExecOutputReadEndEvent),
+
+    /// A tool call was stopped by the sandbox or an approval decision.
+    ToolCallBlocked(ToolCallBlockedEvent),
+
+    /// A tool call was still running when its turn was interrupted.
+    ToolCallAborted(ToolCallAbortedEvent),
+
+    /// The model called `report_failure` to say the task cannot be done.
+    FailureReported(FailureReportedEvent),
+
+    /// The model called `report_outcome`. Only the latest outcome of a
+    /// session counts.
+    OutcomeReported(OutcomeReportedEvent),
+
+    /// The same command failed with the same exit code several times in a
+    /// row; the model was told to try something else.
+    LoopDetected(LoopDetectedEvent),
+
+    /// A command that was not recognized as a git command meant to do so
+    /// moved HEAD, switched branches or changed the stash.
+    GitStateChanged(GitStateChangedEvent),
+
+    /// A tool budget from `[tool_budget]` ran out; further tool calls fail
+    /// until it is raised or, for per-turn budgets, the next turn starts.
+    BudgetExceeded(BudgetExceededEvent),
+
+    /// The session moved to another model provider under
+    /// `[provider_failover]` after the current one kept failing.
+    ProviderSwitched(ProviderSwitchedEvent),
+
+    /// `Op::SwitchModel` took effect; turns from now on use the new model.
+    ModelChanged(ModelChangedEvent),
+
     TurnDiff(TurnDiffEvent),
 
+    /// A `[hooks]` command finished, failed to start, or timed out.
+    HookExecuted(HookExecutedEvent),
+
+    /// A notification was handed to the `notify` program.
+    NotificationSent(NotificationSentEvent),
+
+    /// `Op::UpdateWorkspaceRoots` took effect.
+    WorkspaceRootsChanged(WorkspaceRootsChangedEvent),
+
+    /// What the `workspace_primer` feature told the model about the working
+    /// directory when the session started.
+    WorkspacePrimer(WorkspacePrimerEvent),
+
+    /// A snapshot of the working tree was taken.
+    WorkspaceSnapshotCreated(WorkspaceSnapshotCreatedEvent),
+
+    /// Outcome of `Op::RestoreSnapshot`.
+    WorkspaceSnapshotRestored(WorkspaceSnapshotRestoredEvent),
+
+    /// The session's snapshots, in reply to `Op::ListSnapshots`.
+    WorkspaceSnapshots(WorkspaceSnapshotsEvent),
+
+    /// What the sandbox and policies let the model do changed, and the model
+    /// was told.
+    CapabilitiesChanged(CapabilitiesChangedEvent),
+
+    /// `Op::SetPlanOnly` took effect.
+    PlanOnlyChanged(PlanOnlyChangedEvent),
+
+    /// The model asked for a command in plan-only mode; it was not run.
+    ProposedExec(ProposedExecEvent),
+
+    /// The model asked for a patch in plan-only mode; it was not applied.
+    ProposedPatch(ProposedPatchEvent),
+
+    /// A shell command run with `background: true` started or ended. The end
+    /// is reported in whichever turn is running then.
+    BackgroundJobStatus(BackgroundJobStatusEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
+    /// List of MCP prompts clients can run with `Op::RunMcpPrompt`.
+    McpListPromptsResponse(McpListPromptsResponseEvent),
+
+    /// Result of `Op::RunMcpPrompt`.
+    McpPromptResponse(McpPromptResponseEvent),
+
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Merged config for the session and where each value came from.
+    EffectiveConfigResponse(EffectiveConfigResponseEvent),
+
+    /// Settings that changed on disk in response to `Op::ReloadConfig`.
+    ConfigReloaded(ConfigReloadedEvent),
+
     /// List of skills available to the agent.
     ListSkillsResponse(ListSkillsResponseEvent),
 
@@ -1075,18 +1395,137 @@ pub struct WarningEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
-pub struct ContextCompactedEvent;
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextCompactedEvent {
+    /// History items replaced by the compacted history.
+    #[serde(default)]
+    pub items_compacted: u32,
+    /// Turns whose tool calls were kept as structured digests.
+    #[serde(default)]
+    pub turns_digested: u32,
+    /// Estimated tokens the history took before compacting.
+    #[serde(default)]
+    pub tokens_before: i64,
+    /// Estimated tokens compacting freed; negative if the history grew.
+    #[serde(default)]
+    pub tokens_reclaimed: i64,
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnCompleteEvent {
     pub last_agent_message: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnSummaryEvent {
+    /// Number of tool calls the turn ran.
+    pub tool_calls: u32,
+    /// Time spent running tool calls, summed over calls that ran in parallel.
+    #[ts(type = "string")]
+    pub tool_time: Duration,
+    /// Time spent waiting for the model to stream its responses.
+    #[ts(type = "string")]
+    pub model_time: Duration,
+    /// The session's plan as of the end of the turn, if the agent made one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub plan: Option<UpdatePlanArgs>,
+    /// How the provider's prompt cache served the turn's model requests.
+    #[serde(default)]
+    pub prompt_cache: PromptCacheStats,
+    /// Things the user should double-check, such as commands that changed
+    /// git state unexpectedly.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cautions: Vec<String>,
+    /// Estimated tokens of the tool output the turn sent to the model.
+    #[serde(default)]
+    pub tool_output_tokens: u64,
+    /// `tool_output_tokens` by tool call, in the order the calls finished.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_call_tokens: Vec<ToolCallTokens>,
+    /// Tokens the provider reported for the turn's model requests, to
+    /// calibrate the estimates against.
+    #[serde(default)]
+    pub token_usage: TokenUsage,
+    /// Approximate cost, when the model has a price under `[token_prices]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cost: Option<TurnCost>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ToolCallTokens {
+    pub call_id: String,
+    pub tool_name: String,
+    /// Estimated tokens of the output sent to the model.
+    pub estimated_tokens: u64,
+}
+
+/// Approximate costs, in the currency of the `[token_prices]` table.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct TurnCost {
+    /// The turn's model requests.
+    pub turn: f64,
+    /// The part of `turn` spent sending tool output to the model once.
+    pub tool_output: f64,
+    /// Every model request of the session so far.
+    pub session: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct FileAccessLedgerEvent {
+    pub turn_id: String,
+    /// Files read with `read_file`, by commands recognized as reading a file,
+    /// or as `file://` MCP resources. Relative to the turn's working
+    /// directory when under it; sorted, without duplicates.
+    pub read: Vec<PathBuf>,
+    /// Files created, changed or deleted by patches or by commands recognized
+    /// as writing them, listed like `read`.
+    pub written: Vec<PathBuf>,
+    /// Files left out of `read` because the list was too long.
+    pub read_omitted: usize,
+    /// Files left out of `written` because the list was too long.
+    pub written_omitted: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct TurnProgressEvent {
+    pub turn_id: String,
+    /// Tool calls of the turn that have finished.
+    pub tool_calls_completed: u32,
+    /// Tool calls of the turn still running.
+    pub tool_calls_running: u32,
+    /// Time since the turn started.
+    #[ts(type = "string")]
+    pub elapsed: Duration,
+    /// Rough number of tokens the tool output not yet sent to the model will
+    /// add to the context.
+    pub estimated_output_tokens: u64,
+    /// What is left of the `[tool_budget]` limits.
+    #[serde(default)]
+    pub tool_budget: ToolBudgetRemaining,
+}
+
+/// What is left of each tool budget; `None` for budgets without a limit.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ToolBudgetRemaining {
+    pub turn_tool_calls: Option<u32>,
+    pub session_tool_calls: Option<u32>,
+    /// Milliseconds the turn's exec calls may still run.
+    #[ts(type = "number | null")]
+    pub turn_exec_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnStartedEvent {
     // TODO(aibrahim): make this not optional
     pub model_context_window: Option<i64>,
+
+    /// Built-in and dynamic tools offered to the model this turn, after
+    /// `[[model_tools]]` settings. `None` for turns that offer no tools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tools: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq, JsonSchema, TS)]
@@ -1110,6 +1549,49 @@ pub struct TokenUsageInfo {
     // TODO(aibrahim): make this not optional
     #[ts(type = "number | null")]
     pub model_context_window: Option<i64>,
+    /// `total_token_usage` split by the id of the model provider that served
+    /// it; sessions can move providers under `[provider_failover]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_provider: BTreeMap<String, TokenUsage>,
+    /// How the provider's prompt cache served the session's model requests.
+    #[serde(default)]
+    pub prompt_cache: PromptCacheStats,
+    /// Estimated tokens of the tool output the session sent to the model.
+    #[serde(default)]
+    pub tool_output_tokens: u64,
+}
+
+/// Prompt cache hits and misses, read from the usage the provider reports
+/// for each model request.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PromptCacheStats {
+    /// Requests that reported cached input tokens.
+    pub hits: u32,
+    /// Requests that reported usage without cached input tokens.
+    pub misses: u32,
+    /// Input tokens of those requests served from the cache.
+    #[ts(type = "number")]
+    pub cached_input_tokens: i64,
+    /// All input tokens of those requests.
+    #[ts(type = "number")]
+    pub input_tokens: i64,
+}
+
+impl PromptCacheStats {
+    /// Counts the usage of one model request.
+    pub fn record(&mut self, usage: &TokenUsage) {
+        if usage.cached_input_tokens > 0 {
+            self.hits = self.hits.saturating_add(1);
+        } else {
+            self.misses = self.misses.saturating_add(1);
+        }
+        self.cached_input_tokens += usage.cached_input_tokens.max(0);
+        self.input_tokens += usage.input_tokens.max(0);
+    }
+
+    pub fn requests(&self) -> u32 {
+        self.hits.saturating_add(self.misses)
+    }
 }
 
 impl TokenUsageInfo {
@@ -1128,6 +1610,9 @@ impl TokenUsageInfo {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window,
+                by_provider: BTreeMap::new(),
+                prompt_cache: PromptCacheStats::default(),
+                tool_output_tokens: 0,
             },
         };
         if let Some(last) = last {
@@ -1139,6 +1624,16 @@ impl TokenUsageInfo {
     pub fn append_last_usage(&mut self, last: &TokenUsage) {
         self.total_token_usage.add_assign(last);
         self.last_token_usage = last.clone();
+        self.prompt_cache.record(last);
+    }
+
+    /// Adds `last` to the usage of `provider_id`, the provider that served
+    /// it.
+    pub fn attribute_to_provider(&mut self, provider_id: &str, last: &TokenUsage) {
+        self.by_provider
+            .entry(provider_id.to_string())
+            .or_default()
+            .add_assign(last);
     }
 
     pub fn fill_to_context_window(&mut self, context_window: i64) {
@@ -1161,6 +1656,9 @@ impl TokenUsageInfo {
             total_token_usage: TokenUsage::default(),
             last_token_usage: TokenUsage::default(),
             model_context_window: Some(context_window),
+            by_provider: BTreeMap::new(),
+            prompt_cache: PromptCacheStats::default(),
+            tool_output_tokens: 0,
         };
         info.fill_to_context_window(context_window);
         info
@@ -1320,6 +1818,9 @@ pub struct UserMessageEvent {
     /// UI-defined spans within `message` used to render or persist special elements.
     #[serde(default)]
     pub text_elements: Vec<crate::user_input::TextElement>,
+    /// Documents attached to the message. Their text is not part of `message`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<crate::user_input::DocumentReference>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1371,6 +1872,15 @@ pub struct McpToolCallBeginEvent {
     /// Identifier so this can be paired with the McpToolCallEnd event.
     pub call_id: String,
     pub invocation: McpInvocation,
+    /// Timeout applied to this call in milliseconds, after config defaults
+    /// and the `tool_timeouts.max_sec` ceiling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub timeout_ms: Option<u64>,
+    /// Arguments sent to the server, with secrets redacted and truncated to
+    /// the configured output limits.
+    #[serde(default)]
+    pub arguments_preview: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
@@ -1382,6 +1892,46 @@ pub struct McpToolCallEndEvent {
     pub duration: Duration,
     /// Result of the tool call. Note this could be an error.
     pub result: Result<CallToolResult, String>,
+    /// Whether the call failed, either in transport or as reported by the
+    /// server.
+    #[serde(default)]
+    pub is_error: bool,
+    /// Where the failure came from when `is_error` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error_source: Option<McpToolCallErrorSource>,
+    /// Result content, with secrets redacted and truncated to the configured
+    /// output limits. Holds the error message for transport failures.
+    #[serde(default)]
+    pub result_preview: String,
+}
+
+/// Distinguishes a call that never got a result from the server from one the
+/// server answered with `isError: true`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum McpToolCallErrorSource {
+    /// The request failed or timed out before the server returned a result.
+    Transport,
+    /// The server returned a result flagged as an error.
+    Tool,
+    /// The turn was interrupted while the call was running, and the server
+    /// was sent `notifications/cancelled`.
+    Aborted,
+}
+
+impl McpToolCallBeginEvent {
+    /// A copy without the raw arguments, which can hold credentials, for
+    /// records that outlive the session. `arguments_preview` is kept.
+    pub fn without_raw_payload(&self) -> Self {
+        Self {
+            invocation: McpInvocation {
+                arguments: None,
+                ..self.invocation.clone()
+            },
+            ..self.clone()
+        }
+    }
 }
 
 impl McpToolCallEndEvent {
@@ -1391,6 +1941,58 @@ impl McpToolCallEndEvent {
             Err(_) => false,
         }
     }
+
+    /// A copy without the raw arguments and result, which can hold
+    /// credentials, for records that outlive the session. The result is
+    /// replaced by `result_preview`: the text content of a successful call,
+    /// or the message of a failed one.
+    pub fn without_raw_payload(&self) -> Self {
+        let result = match &self.result {
+            Ok(result) => Ok(CallToolResult {
+                content: vec![ContentBlock::TextContent(TextContent {
+                    annotations: None,
+                    text: self.result_preview.clone(),
+                    r#type: "text".to_string(),
+                })],
+                is_error: result.is_error,
+                structured_content: None,
+            }),
+            Err(_) => Err(self.result_preview.clone()),
+        };
+        Self {
+            invocation: McpInvocation {
+                arguments: None,
+                ..self.invocation.clone()
+            },
+            result,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpToolCallProgressEvent {
+    /// Identifier for the McpToolCallBegin this progress belongs to.
+    pub call_id: String,
+    /// Progress so far, in units chosen by the server.
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub total: Option<f64>,
+    /// `progress` as a share of `total`, from 0 to 100, when the total is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub percentage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpToolCallOutputDeltaEvent {
+    /// Identifier for the McpToolCallBegin that produced this output.
+    pub call_id: String,
+    pub delta: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1780,16 +2382,31 @@ pub struct ReviewLineRange {
     pub end: u32,
 }
 
+/// Who asked for a command to run. The aliases are the names earlier versions
+/// wrote, so old rollouts still parse.
 #[derive(
     Debug, Clone, Copy, Display, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS, Default,
 )]
 #[serde(rename_all = "snake_case")]
 pub enum ExecCommandSource {
+    /// A `shell` tool call from the model.
     #[default]
-    Agent,
+    #[serde(alias = "agent")]
+    ModelShell,
+    /// A process the model started with `exec_command`.
+    #[serde(alias = "unified_exec_startup")]
+    ModelUnifiedExec,
+    /// A command the user ran with the TUI's `!` shell escape.
     UserShell,
-    UnifiedExecStartup,
-    UnifiedExecInteraction,
+    /// Input the user typed into a running `exec_command` process.
+    #[serde(alias = "unified_exec_interaction")]
+    UserUnifiedExecInput,
+    /// A model command rerun outside the sandbox after the sandbox denied
+    /// it. Only end events carry it; the begin event names the original
+    /// source.
+    EscalatedRetry,
+    /// A command run by a `[hooks]` entry.
+    Hook,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1807,13 +2424,23 @@ pub struct ExecCommandBeginEvent {
     /// The command's working directory if not the default cwd for the agent.
     pub cwd: PathBuf,
     pub parsed_cmd: Vec<ParsedCommand>,
-    /// Where the command originated. Defaults to Agent for backward compatibility.
+    /// Where the command originated. Defaults to ModelShell for backward compatibility.
     #[serde(default)]
     pub source: ExecCommandSource,
     /// Raw input sent to a unified exec session (if this is an interaction event).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub interaction_input: Option<String>,
+    /// Timeout applied to this call in milliseconds, after config defaults
+    /// and the `tool_timeouts.max_sec` ceiling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub timeout_ms: Option<u64>,
+    /// Variables the call set for this command on top of the
+    /// `shell_environment_policy` environment. Values that look like
+    /// secrets are redacted.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub injected_env: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1831,7 +2458,7 @@ pub struct ExecCommandEndEvent {
     /// The command's working directory if not the default cwd for the agent.
     pub cwd: PathBuf,
     pub parsed_cmd: Vec<ParsedCommand>,
-    /// Where the command originated. Defaults to Agent for backward compatibility.
+    /// Where the command originated. Defaults to ModelShell for backward compatibility.
     #[serde(default)]
     pub source: ExecCommandSource,
     /// Raw input sent to a unified exec session (if this is an interaction event).
@@ -1841,18 +2468,147 @@ pub struct ExecCommandEndEvent {
 
     /// Captured stdout
     pub stdout: String,
-    /// Captured stderr
+    /// Captured stderr. Only ever what the process printed; see `error` for
+    /// failures of Codex's own.
     pub stderr: String,
     /// Captured aggregated output
     #[serde(default)]
     pub aggregated_output: String,
-    /// The command's exit code.
+    /// The command's exit code. `-1` when the command never started; see
+    /// `outcome`.
     pub exit_code: i32,
+    /// How the command ended. `None` for clients that predate this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub outcome: Option<ExecOutcome>,
     /// The duration of the command execution.
     #[ts(type = "string")]
     pub duration: Duration,
+    /// Whether `duration` was timed by Codex around the call rather than
+    /// measured from the child process, e.g. for a command that was rejected
+    /// or failed before producing output.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    /// The machine most likely slept while the command ran. `duration`
+    /// comes from a monotonic clock and leaves the time asleep out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool,
+    /// Bytes of NUL and other control characters removed from the output
+    /// fields above, when there were any. The spilled output file, if any,
+    /// keeps them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub sanitized_bytes: Option<u64>,
+    /// Bytes of output sent in `ExecCommandOutputDelta` events for this
+    /// call. This event is authoritative: a client whose concatenated deltas
+    /// add up to a different length should render `aggregated_output`.
+    #[serde(default)]
+    pub delta_bytes_emitted: u64,
+    /// Set when Codex knows some output never went out as a delta, e.g. past
+    /// the per-call delta limit or because the stream fell behind.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deltas_incomplete: bool,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// File holding the full output when it was too large to send to the
+    /// model whole.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub saved_output_path: Option<PathBuf>,
+    /// How the command was cleared to run. `None` for commands the user ran
+    /// and for clients that predate this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub approval: Option<ExecApproval>,
+    /// Why the call failed, when the command did not simply exit non-zero:
+    /// it was rejected, timed out, denied by the sandbox, or could not be
+    /// run at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<ExecErrorInfo>,
+}
+
+impl ExecCommandEndEvent {
+    /// What the command printed or, if it printed nothing, why it failed.
+    /// For display; the output fields never hold Codex's own errors.
+    pub fn display_output(&self) -> &str {
+        match &self.error {
+            Some(error) if self.aggregated_output.is_empty() => &error.message,
+            _ => &self.aggregated_output,
+        }
+    }
+}
+
+/// A failure of Codex's own while running an exec call, as opposed to
+/// anything the command printed.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ExecErrorInfo {
+    pub kind: ExecErrorKind,
+    /// What the model was told.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecErrorKind {
+    /// The user or the approval policy rejected the command.
+    Rejected,
+    /// The requested working directory cannot be used.
+    InvalidWorkdir,
+    /// The command ran past its timeout.
+    Timeout,
+    /// The sandbox denied the command.
+    SandboxDenied,
+    /// The process could not be started.
+    SpawnFailed,
+    /// The process was terminated by a signal.
+    Killed,
+    /// The command was still running when its turn ended or was aborted.
+    Interrupted,
+    /// Codex failed while running the command.
+    Internal,
+}
+
+#[derive(Debug, Clone, Copy, Display, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecApproval {
+    /// Ran without asking, as allowed by the approval policy or exec policy.
+    AutoApproved,
+    /// The user approved the command.
+    UserApproved,
+    /// Rerun outside the sandbox after the sandbox denied it.
+    Escalated,
+}
+
+/// How an exec call ended, so clients can tell a command that failed from
+/// one that never ran.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum ExecOutcome {
+    /// The process ran and exited on its own.
+    Spawned { exit_code: i32 },
+    /// The process could not be started.
+    SpawnFailed {
+        error_kind: SpawnErrorKind,
+        message: String,
+    },
+    /// The process was stopped after exceeding its timeout.
+    TimedOut,
+    /// The process was terminated by a signal.
+    Killed { signal: i32 },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnErrorKind {
+    /// The program does not exist or is not on `PATH`.
+    CommandNotFound,
+    /// The program exists but is not executable.
+    PermissionDenied,
+    /// The working directory does not exist.
+    CwdNotFound,
+    Other,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1861,6 +2617,12 @@ pub struct ViewImageToolCallEvent {
     pub call_id: String,
     /// Local filesystem path provided to the tool.
     pub path: PathBuf,
+    /// Size of the image as sent to the model, after any downscaling. Zero in
+    /// events recorded before sizes were reported.
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
@@ -1893,6 +2655,19 @@ pub struct TerminalInteractionEvent {
     pub process_id: String,
     /// Stdin sent to the running session.
     pub stdin: String,
+    /// Who wrote `stdin` to the session.
+    #[serde(default)]
+    pub source: TerminalInteractionSource,
+}
+
+#[derive(
+    Debug, Clone, Copy, Display, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalInteractionSource {
+    #[default]
+    Agent,
+    User,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1940,6 +2715,35 @@ pub struct StreamErrorEvent {
     pub additional_details: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct StreamRetryEvent {
+    /// Retry number, starting at 1.
+    pub attempt: u64,
+    pub max_retries: u64,
+    /// Why the stream ended.
+    pub error: String,
+    /// Output items that completed before the stream was cut off. They stay
+    /// in the history.
+    pub items_preserved: u32,
+    /// Tool calls among those items. Each ran once and its output is kept.
+    pub tool_calls_preserved: u32,
+    pub strategy: StreamRecoveryStrategy,
+}
+
+/// How a sampling request is retried after its stream was cut off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamRecoveryStrategy {
+    /// Nothing completed before the disconnect; the request is sent again.
+    Restart,
+    /// Tool calls completed; the retry carries them and their output, so the
+    /// model picks up after them.
+    ResumeAfterToolCalls,
+    /// Only messages or reasoning completed; the retry carries them and asks
+    /// the model to continue where it stopped.
+    Continue,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamInfoEvent {
     pub message: String,
@@ -1955,8 +2759,19 @@ pub struct PatchApplyBeginEvent {
     pub turn_id: String,
     /// If true, there was no ApplyPatchApprovalRequest for this patch.
     pub auto_approved: bool,
-    /// The changes to be applied.
+    /// The changes to be applied. Left empty when their combined size is
+    /// over `output_limits.patch_event_content_bytes`; the full contents then
+    /// arrive with `PatchApplyEnd` and the following `TurnDiff`.
+    #[serde(default)]
     pub changes: HashMap<PathBuf, FileChange>,
+    /// One summary per changed file, sorted by path. Always present.
+    #[serde(default)]
+    pub summaries: Vec<FileChangeSummary>,
+    /// Timeout applied to this call in milliseconds, after config defaults
+    /// and the `tool_timeouts.max_sec` ceiling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub timeout_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -1978,21 +2793,678 @@ pub struct PatchApplyEndEvent {
     pub changes: HashMap<PathBuf, FileChange>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
-pub struct TurnDiffEvent {
-    pub unified_diff: String,
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct FileReadBeginEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    /// The file being read, resolved against the turn's working directory.
+    pub path: PathBuf,
+    /// First line requested, 1-indexed.
+    pub start_line: usize,
+    /// Last line requested, when the call gave one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub end_line: Option<usize>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
-pub struct GetHistoryEntryResponseEvent {
-    pub offset: usize,
-    pub log_id: u64,
-    /// The entry at the requested offset, if available and parseable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub entry: Option<HistoryEntry>,
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct FileReadEndEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub path: PathBuf,
+    /// Lines returned to the model.
+    pub lines: usize,
+    /// Bytes returned to the model.
+    pub bytes: usize,
+    /// The read stopped at its byte budget before the requested range ended.
+    pub truncated: bool,
+    /// The file looked binary, so only its size and type were returned.
+    pub binary: bool,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    /// Why the read failed; `None` when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct WorkspaceSearchBeginEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub query: String,
+    /// Only files matching this glob were searched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub glob: Option<String>,
+    /// `query` is a regular expression rather than literal text.
+    pub regex: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct WorkspaceSearchEndEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub query: String,
+    /// Matches returned to the model.
+    pub match_count: usize,
+    /// More matches were found than were returned.
+    pub truncated: bool,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    /// Why the search failed; `None` when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ListDirBeginEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub path: PathBuf,
+    /// How many levels below `path` are listed.
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ListDirEndEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub path: PathBuf,
+    /// Entries returned to the model.
+    pub entry_count: usize,
+    /// The directory had more entries than were returned.
+    pub truncated: bool,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    /// Why the listing failed; `None` when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ExecOutputReadBeginEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    /// The earlier exec call whose output is read.
+    pub source_call_id: String,
+    /// Lines skipped before the first one returned.
+    pub offset_lines: usize,
+    /// Most lines to return; the rest of the output when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub limit_lines: Option<usize>,
+    /// Only lines matching this regex were read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub grep: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ExecOutputReadEndEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub source_call_id: String,
+    /// Lines returned to the model.
+    pub lines: usize,
+    /// Bytes returned to the model.
+    pub bytes: usize,
+    /// Lines of the output, or of its lines matching `grep`.
+    pub total_lines: usize,
+    /// The read stopped at its byte budget before the requested range ended.
+    pub truncated: bool,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    /// Why the read failed; `None` when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ToolCallBlockedEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub kind: ToolCallBlockKind,
+    /// What the model was told.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallBlockKind {
+    /// The sandbox denied the command and it was not retried outside it.
+    Sandbox,
+    /// The call was denied by an approval decision or forbidden by policy.
+    Approval,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct ToolCallAbortedEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    pub tool_name: String,
+    /// How long the call ran before it was stopped.
+    #[ts(type = "string")]
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct FailureReportedEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    /// The model's explanation of why the task failed.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct OutcomeReportedEvent {
+    pub call_id: String,
+    pub turn_id: String,
+    #[serde(flatten)]
+    pub outcome: ReportedOutcome,
+    /// Whether this outcome replaced one reported earlier in the session.
+    pub replaced_previous: bool,
+}
+
+/// How the model says the task went, for callers that should not have to
+/// parse its final message.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ReportedOutcome {
+    pub status: OutcomeStatus,
+    /// One or two sentences on what was done or why it was not.
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OutcomeStatus {
+    Success,
+    Failure,
+    /// The task needs a decision or an action only a person can take.
+    NeedsHuman,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct LoopDetectedEvent {
+    /// The call whose result carried the advice to change approach.
+    pub call_id: String,
+    pub turn_id: String,
+    pub command: Vec<String>,
+    pub exit_code: i32,
+    /// Consecutive failures with `exit_code`, counting this one.
+    pub failures: u32,
+}
+
+/// The parts of a repository's state commands are not expected to change
+/// behind the agent's back.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct GitStateSnapshot {
+    /// Commit id HEAD resolves to.
+    pub head: String,
+    /// Current branch, or `None` when HEAD is detached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub branch: Option<String>,
+    /// Entries in `git stash list`.
+    pub stash_count: u32,
+}
+
+impl GitStateSnapshot {
+    /// What changed from `self` to `after`, phrased to follow the command
+    /// that changed it, e.g. "moved HEAD from 1a2b3c4 to 5d6e7f8".
+    pub fn describe_change(&self, after: &GitStateSnapshot) -> String {
+        let branch = |branch: &Option<String>| {
+            branch
+                .clone()
+                .unwrap_or_else(|| "detached HEAD".to_string())
+        };
+        let short = |head: &str| head.get(..7).unwrap_or(head).to_string();
+        let mut changes = Vec::new();
+        if self.branch != after.branch {
+            changes.push(format!(
+                "switched from {} to {}",
+                branch(&self.branch),
+                branch(&after.branch)
+            ));
+        }
+        if self.head != after.head {
+            changes.push(format!(
+                "moved HEAD from {} to {}",
+                short(&self.head),
+                short(&after.head)
+            ));
+        }
+        if self.stash_count != after.stash_count {
+            changes.push(format!(
+                "changed the stash from {} to {} entries",
+                self.stash_count, after.stash_count
+            ));
+        }
+        changes.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct GitStateChangedEvent {
+    /// The exec call after which the change was noticed.
+    pub command_call_id: String,
+    pub turn_id: String,
+    pub command: Vec<String>,
+    pub before: GitStateSnapshot,
+    pub after: GitStateSnapshot,
+}
+
+/// One of the limits of the `[tool_budget]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolBudgetKind {
+    TurnToolCalls,
+    SessionToolCalls,
+    TurnExecTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct BudgetExceededEvent {
+    /// The first call refused because the budget ran out.
+    pub call_id: String,
+    pub turn_id: String,
+    pub budget: ToolBudgetKind,
+    /// The limit that was reached: a number of calls, or milliseconds for
+    /// `turn_exec_time`.
+    #[ts(type = "number")]
+    pub limit: u64,
+    /// Whether the turn ends here rather than going back to the model.
+    pub ends_turn: bool,
+}
+
+/// A model provider and the model used with it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProviderModel {
+    /// Key of the provider in `model_providers`.
+    pub provider: String,
+    pub model: String,
+}
+
+/// The class of errors that made the session leave a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderSwitchReason {
+    /// HTTP 429 responses.
+    RateLimited,
+    /// HTTP 5xx responses.
+    ServerError,
+    /// Dropped connections and streams cut off before the response
+    /// completed.
+    Disconnected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProviderSwitchedEvent {
+    pub from: ProviderModel,
+    pub to: ProviderModel,
+    pub reason: ProviderSwitchReason,
+    /// Whether the failed request is retried on the new provider in this
+    /// turn. It is not when part of its response was already recorded; the
+    /// turn then fails and later turns use the new provider.
+    pub retried_in_turn: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ModelChangedEvent {
+    pub from: ProviderModel,
+    pub to: ProviderModel,
+    /// Context window of the new model, when known.
+    #[ts(type = "number | null")]
+    pub model_context_window: Option<i64>,
+    /// Whether the history is compacted first because it would not fit the
+    /// new model's context window.
+    pub compacts_history: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceRootsChangedEvent {
+    pub added: Vec<WorkspaceRoot>,
+    pub removed: Vec<WorkspaceRoot>,
+    /// All workspace roots from now on.
+    pub roots: Vec<WorkspaceRoot>,
+    /// The sandbox's writable roots from now on; empty unless the sandbox is
+    /// workspace-write.
+    pub writable_roots: Vec<AbsolutePathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspacePrimerEvent {
+    pub cwd: PathBuf,
+    /// First heading of the README, if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub readme_heading: Option<String>,
+    /// Top-level entries of `cwd` not excluded by ignore files, in name
+    /// order; directories end with `/`.
+    pub entries: Vec<String>,
+    /// Entries left out of `entries` because the list was too long.
+    pub entries_omitted: usize,
+    /// Build system recognized from the files in `cwd`, such as `cargo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub build_system: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub test_command: Option<String>,
+    /// Branch and working tree summary, such as `on main: 1 staged, 0
+    /// unstaged, 2 untracked`; `None` outside a git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub git_status: Option<String>,
+}
+
+impl WorkspacePrimerEvent {
+    /// One line on what the model was told, e.g. "12 top-level entries ·
+    /// cargo · tests: `cargo test` · on main, clean".
+    pub fn summary(&self) -> String {
+        let total = self.entries.len() + self.entries_omitted;
+        let mut parts = vec![if total == 1 {
+            "1 top-level entry".to_string()
+        } else {
+            format!("{total} top-level entries")
+        }];
+        parts.extend(self.build_system.clone());
+        parts.extend(
+            self.test_command
+                .as_ref()
+                .map(|command| format!("tests: `{command}`")),
+        );
+        parts.extend(self.git_status.clone());
+        parts.join(" · ")
+    }
+}
+
+/// A snapshot of the working tree that `Op::RestoreSnapshot` can go back to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceSnapshot {
+    pub id: String,
+    /// Directory the snapshot covers.
+    pub cwd: PathBuf,
+    pub kind: WorkspaceSnapshotKind,
+    pub reason: WorkspaceSnapshotReason,
+    /// The turn the snapshot was taken in, if one was running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub turn_id: Option<String>,
+    /// Unix timestamp (seconds since epoch) of the snapshot.
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceSnapshotKind {
+    /// An unreferenced commit holding the working tree, kept alive by a ref
+    /// under `refs/codex/snapshots/`.
+    Git,
+    /// A copy of the files under the session's temp directory, for
+    /// directories outside a git repository.
+    Copy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceSnapshotReason {
+    /// Asked for with `Op::SnapshotWorkspace`.
+    Requested,
+    /// Taken before the turn's first destructive command.
+    DestructiveCommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceSnapshotCreatedEvent {
+    pub snapshot: WorkspaceSnapshot,
+    /// The destructive command the snapshot was taken before.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceSnapshotRestoredEvent {
+    pub id: String,
+    /// False when the restore was refused or failed; `message` says why.
+    pub restored: bool,
+    pub message: String,
+    /// Files created since the snapshot that restoring would delete, when
+    /// the restore was refused because of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub would_remove: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct WorkspaceSnapshotsEvent {
+    /// Oldest first.
+    pub snapshots: Vec<WorkspaceSnapshot>,
+}
+
+/// What the sandbox and policies let the model do, as described to it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EnvironmentCapabilities {
+    pub sandbox_mode: SandboxMode,
+    pub network_access: bool,
+    /// Where commands may write; empty unless `sandbox_mode` is
+    /// `workspace-write`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub writable_roots: Vec<PathBuf>,
+    pub approval_policy: AskForApproval,
+    pub plan_only: bool,
+    /// Built-in and dynamic tools; MCP tools are not listed.
+    pub tools: Vec<String>,
+    /// Default timeout of a shell command.
+    pub command_timeout_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub max_tool_calls_per_turn: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub max_tool_calls_per_session: Option<u32>,
+}
+
+impl EnvironmentCapabilities {
+    /// What changed from `self` to `after`, e.g. "network access on,
+    /// approvals never".
+    pub fn describe_change(&self, after: &EnvironmentCapabilities) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let limit =
+            |max: Option<u32>| max.map_or_else(|| "none".to_string(), |max| max.to_string());
+        let mut changes = Vec::new();
+        if self.sandbox_mode != after.sandbox_mode {
+            changes.push(format!("sandbox {}", after.sandbox_mode));
+        }
+        if self.network_access != after.network_access {
+            changes.push(format!("network access {}", on_off(after.network_access)));
+        }
+        if self.writable_roots != after.writable_roots && !after.writable_roots.is_empty() {
+            changes.push(format!("{} writable roots", after.writable_roots.len()));
+        }
+        if self.approval_policy != after.approval_policy {
+            changes.push(format!("approvals {}", after.approval_policy));
+        }
+        if self.plan_only != after.plan_only {
+            changes.push(format!("plan-only mode {}", on_off(after.plan_only)));
+        }
+        let added = after
+            .tools
+            .iter()
+            .filter(|tool| !self.tools.contains(tool))
+            .count();
+        let removed = self
+            .tools
+            .iter()
+            .filter(|tool| !after.tools.contains(tool))
+            .count();
+        if added > 0 {
+            changes.push(format!("{added} tool(s) added"));
+        }
+        if removed > 0 {
+            changes.push(format!("{removed} tool(s) removed"));
+        }
+        if self.command_timeout_ms != after.command_timeout_ms {
+            changes.push(format!(
+                "command timeout {}s",
+                after.command_timeout_ms / 1000
+            ));
+        }
+        if self.max_tool_calls_per_turn != after.max_tool_calls_per_turn {
+            changes.push(format!(
+                "tool calls per turn {}",
+                limit(after.max_tool_calls_per_turn)
+            ));
+        }
+        if self.max_tool_calls_per_session != after.max_tool_calls_per_session {
+            changes.push(format!(
+                "tool calls per session {}",
+                limit(after.max_tool_calls_per_session)
+            ));
+        }
+        changes.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CapabilitiesChangedEvent {
+    pub turn_id: String,
+    pub before: EnvironmentCapabilities,
+    pub after: EnvironmentCapabilities,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PlanOnlyChangedEvent {
+    pub plan_only: bool,
+}
+
+/// A command the model asked for while the session was in plan-only mode.
+/// The model was told it did not run, and it is never run later.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProposedExecEvent {
+    /// What `ExecCommandBegin` would have carried had the command run.
+    #[serde(flatten)]
+    pub command: ExecCommandBeginEvent,
+    /// The mode the call was made in, so clients can label the proposal
+    /// without tracking `PlanOnlyChanged`. Only plan-only mode proposes.
+    pub plan_only: bool,
+}
+
+/// A patch the model asked for while the session was in plan-only mode.
+/// The model was told it was not applied, and it is never applied later.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProposedPatchEvent {
+    /// What `PatchApplyBegin` would have carried had the patch been applied.
+    #[serde(flatten)]
+    pub patch: PatchApplyBeginEvent,
+    /// The mode the call was made in, as on [`ProposedExecEvent`].
+    pub plan_only: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct BackgroundJobStatusEvent {
+    pub job_id: String,
+    /// The shell call that started the job.
+    pub call_id: String,
+    pub command: Vec<String>,
+    pub status: BackgroundJobStatus,
+    /// Set once the process has exited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub exit_code: Option<i32>,
+    /// How long the job ran; zero while it is still running.
+    #[ts(type = "string")]
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BackgroundJobStatus {
+    Running,
+    /// The command exited with status 0.
+    Completed,
+    /// The command exited with another status or could not be run.
+    Failed,
+    /// The command ran past its timeout and was killed.
+    TimedOut,
+    /// The command was stopped by `kill_background_job` or at shutdown.
+    Killed,
+}
+
+impl BackgroundJobStatus {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, BackgroundJobStatus::Running)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct NotificationSentEvent {
+    pub kind: NotificationKind,
+}
+
+/// What a `notify` notification is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    TurnComplete,
+    ApprovalRequested,
+    BudgetExceeded,
+    Error,
+}
+
+/// Lifecycle point that runs a `[hooks]` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum HookTrigger {
+    SessionStart,
+    PatchApplied,
+    TurnComplete,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct HookExecutedEvent {
+    pub hook: HookTrigger,
+    /// The configured command.
+    pub command: Vec<String>,
+    /// `None` when the command could not be started or was killed.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    pub timed_out: bool,
+    /// Why the command could not be run, if it could not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnDiffEvent {
+    /// Turn ID whose changes this diff aggregates.
+    /// Uses `#[serde(default)]` for backwards compatibility.
+    #[serde(default)]
+    pub turn_id: String,
+    pub unified_diff: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct GetHistoryEntryResponseEvent {
+    pub offset: usize,
+    pub log_id: u64,
+    /// The entry at the requested offset, if available and parseable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
@@ -2004,6 +3476,51 @@ pub struct McpListToolsResponseEvent {
     pub auth_statuses: std::collections::HashMap<String, McpAuthStatus>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpListPromptsResponseEvent {
+    /// Prompts grouped by server name.
+    pub prompts: std::collections::HashMap<String, Vec<McpPrompt>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpPromptResponseEvent {
+    pub server: String,
+    pub prompt: String,
+    /// Why the prompt could not be fetched, including arguments that do not
+    /// match the prompt's definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+    /// Messages added to the input of the next turn.
+    #[serde(default)]
+    pub messages: Vec<McpPromptMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpSamplingRequestEvent {
+    pub server_name: String,
+    /// Request identifier from the MCP server.
+    pub id: RequestId,
+    pub request: CreateMessageRequestParams,
+    /// Model the completion runs on.
+    pub model: String,
+    /// Whether the completion waits for an `Op::ResolveMcpSampling` answer.
+    pub requires_approval: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpSamplingResponseEvent {
+    pub server_name: String,
+    /// Request identifier from the MCP server.
+    pub id: RequestId,
+    /// The completion returned to the server, or why the request was refused
+    /// or failed.
+    pub result: Result<CreateMessageResult, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub token_usage: Option<TokenUsage>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpStartupUpdateEvent {
     /// Server name being started.
@@ -2035,6 +3552,42 @@ pub struct McpStartupFailure {
     pub error: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct McpServerStatusEvent {
+    /// Server name from the config.
+    pub server: String,
+    pub state: McpServerState,
+    /// Why the server failed to start, went down, or was disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+    /// The `enabled_tools` allowlist from the server config; `None` allows
+    /// every tool the server lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub configured_tools: Option<Vec<String>>,
+    /// Tools that came online after `enabled_tools` and `disabled_tools`
+    /// were applied, sorted by name. Empty unless `state` is `ready`.
+    #[serde(default)]
+    pub effective_tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum McpServerState {
+    Ready,
+    Failed,
+    Cancelled,
+    /// A running server's connection closed; a restart follows unless the
+    /// server is disabled.
+    Down,
+    /// Codex is starting the server again after it went down.
+    Restarting,
+    /// The server kept crashing and will not be restarted this session.
+    Disabled,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(rename_all = "snake_case")]
@@ -2063,6 +3616,62 @@ pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
 }
 
+/// Response payload for `Op::GetEffectiveConfig`, also sent at session start.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EffectiveConfigResponseEvent {
+    /// Config layers, highest precedence first. Disabled layers (for example
+    /// an untrusted project's `.codex/config.toml`) are included so clients can
+    /// explain why a file was ignored.
+    pub layers: Vec<EffectiveConfigLayer>,
+    /// Maps each dotted config key (e.g. `sandbox_workspace_write.network_access`,
+    /// or `sandbox_workspace_write.writable_roots.0` for an array item) to its
+    /// effective value and the layer that set it.
+    pub values: BTreeMap<String, EffectiveConfigValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EffectiveConfigValue {
+    /// The value as inline TOML, e.g. `"workspace-write"` or `true`.
+    pub value: String,
+    /// The `source` of the layer that set it.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct EffectiveConfigLayer {
+    /// Human-readable description of the layer, e.g. `project /repo/.codex/config.toml`.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub disabled_reason: Option<String>,
+}
+
+/// Response payload for `Op::ReloadConfig`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ConfigReloadedEvent {
+    /// Every changed key, sorted by key. Empty when nothing changed on disk.
+    pub changes: Vec<ConfigChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ConfigChange {
+    /// Dotted config key, e.g. `tool_timeouts.shell_sec`.
+    pub key: String,
+    /// Previous value as inline TOML; `None` when the key was unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub old_value: Option<String>,
+    /// New value as inline TOML; `None` when the key was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub new_value: Option<String>,
+    /// Why the change was not applied to this session. `None` means it now
+    /// applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rejected_reason: Option<String>,
+}
+
 /// Response payload for `Op::ListSkills`.
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ListSkillsResponseEvent {
@@ -2164,6 +3773,25 @@ pub struct SessionConfiguredEvent {
     /// Path in which the rollout is stored. Can be `None` for ephemeral threads
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rollout_path: Option<PathBuf>,
+
+    /// Shell that wraps model-issued scripts: the shell binary, or the argv
+    /// template when a custom shell is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub shell: Option<String>,
+
+    /// Built-in and dynamic tools offered to the model, after
+    /// `[[model_tools]]` settings. MCP tools are reported by
+    /// `EventMsg::McpListToolsResponse`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tools: Option<Vec<String>>,
+
+    /// Per-session temporary directory, exported to commands as
+    /// `CODEX_TMPDIR` and removed at clean shutdown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tmpdir: Option<PathBuf>,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -2184,11 +3812,19 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved an edited version of the command; `command` runs in
+    /// place of the one the agent proposed.
+    ApprovedWithEdit { command: Vec<String> },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
     Denied,
 
+    /// Like `Denied`, with a note for the agent explaining why or what to do
+    /// instead.
+    DeniedWithReason { reason: String },
+
     /// User has denied this command and the agent should not do anything until
     /// the user's next command.
     Abort,
@@ -2202,7 +3838,9 @@ impl ReviewDecision {
             ReviewDecision::Approved => "approved",
             ReviewDecision::ApprovedExecpolicyAmendment { .. } => "approved_with_amendment",
             ReviewDecision::ApprovedForSession => "approved_for_session",
+            ReviewDecision::ApprovedWithEdit { .. } => "approved_with_edit",
             ReviewDecision::Denied => "denied",
+            ReviewDecision::DeniedWithReason { .. } => "denied_with_reason",
             ReviewDecision::Abort => "abort",
         }
     }
@@ -2224,6 +3862,46 @@ pub enum FileChange {
     },
 }
 
+impl FileChange {
+    pub fn kind(&self) -> FileChangeKind {
+        match self {
+            FileChange::Add { .. } => FileChangeKind::Add,
+            FileChange::Delete { .. } => FileChangeKind::Delete,
+            FileChange::Update { .. } => FileChangeKind::Update,
+        }
+    }
+
+    /// The added or deleted file contents, or the unified diff of an update.
+    pub fn content(&self) -> &str {
+        match self {
+            FileChange::Add { content } | FileChange::Delete { content } => content,
+            FileChange::Update { unified_diff, .. } => unified_diff,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Add,
+    Delete,
+    Update,
+}
+
+/// Size and identity of one file in a patch, without its contents.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct FileChangeSummary {
+    pub path: PathBuf,
+    pub kind: FileChangeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub move_path: Option<PathBuf>,
+    /// Bytes of [`FileChange::content`].
+    pub bytes: u64,
+    /// Hex SHA-256 of [`FileChange::content`].
+    pub sha256: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Chunk {
     /// 1-based line index of the first line in the original file
@@ -2243,6 +3921,8 @@ pub enum TurnAbortReason {
     Interrupted,
     Replaced,
     ReviewEnded,
+    /// Ended by [`Op::SoftInterrupt`] after its running tool call finished.
+    SoftInterrupt,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
@@ -2367,6 +4047,39 @@ mod tests {
         assert!(enabled.has_full_network_access());
     }
 
+    #[test]
+    fn workspace_paths_are_shown_under_the_innermost_root() -> Result<()> {
+        let base = std::env::temp_dir();
+        let root = |name: &str, path: &Path| -> Result<WorkspaceRoot> {
+            Ok(WorkspaceRoot {
+                name: name.to_string(),
+                path: AbsolutePathBuf::from_absolute_path(path)?,
+            })
+        };
+        let roots = vec![
+            root("work", &base.join("work"))?,
+            root("api", &base.join("work").join("api"))?,
+        ];
+
+        assert_eq!(
+            WorkspaceRoot::display_path(&roots, &base.join("work").join("api").join("main.rs")),
+            Some("api/main.rs".to_string())
+        );
+        assert_eq!(
+            WorkspaceRoot::display_path(&roots, &base.join("work").join("README.md")),
+            Some("work/README.md".to_string())
+        );
+        assert_eq!(
+            WorkspaceRoot::display_path(&roots, &base.join("work")),
+            Some("work".to_string())
+        );
+        assert_eq!(
+            WorkspaceRoot::display_path(&roots, &base.join("elsewhere")),
+            None
+        );
+        Ok(())
+    }
+
     #[test]
     fn item_started_event_from_web_search_emits_begin_event() {
         let event = ItemStartedEvent {
@@ -2480,6 +4193,7 @@ mod tests {
             images: None,
             local_images: Vec::new(),
             text_elements: Vec::new(),
+            documents: Vec::new(),
         };
 
         let json_event = serde_json::to_value(event)?;
@@ -2516,6 +4230,9 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: Some(rollout_file.path().to_path_buf()),
+                shell: None,
+                tools: None,
+                tmpdir: None,
             }),
         };
 
@@ -2601,4 +4318,84 @@ mod tests {
         assert_eq!(value["msg"]["cancelled"][0], "c");
         Ok(())
     }
+
+    #[test]
+    fn turn_diff_event_deserializes_without_turn_id() -> Result<()> {
+        let msg: EventMsg = serde_json::from_value(json!({
+            "type": "turn_diff",
+            "unified_diff": "diff",
+        }))?;
+        let EventMsg::TurnDiff(event) = msg else {
+            panic!("expected a turn diff event");
+        };
+        assert_eq!(event.turn_id, "");
+        assert_eq!(event.unified_diff, "diff");
+
+        let value = serde_json::to_value(EventMsg::TurnDiff(TurnDiffEvent {
+            turn_id: "turn-1".to_string(),
+            unified_diff: "diff".to_string(),
+        }))?;
+        assert_eq!(value["turn_id"], "turn-1");
+        Ok(())
+    }
+
+    #[test]
+    fn exec_error_is_omitted_when_absent_and_read_when_present() -> Result<()> {
+        let mut event: ExecCommandEndEvent = serde_json::from_value(json!({
+            "call_id": "call-1",
+            "turn_id": "turn-1",
+            "command": ["rm", "-rf", "build"],
+            "cwd": "/repo",
+            "parsed_cmd": [],
+            "stdout": "",
+            "stderr": "",
+            "exit_code": -1,
+            "duration": { "secs": 0, "nanos": 0 },
+            "formatted_output": "exec command rejected by user",
+        }))?;
+        assert_eq!(event.error, None);
+        assert_eq!(serde_json::to_value(&event)?.get("error"), None);
+        assert_eq!(event.display_output(), "");
+
+        event.error = Some(ExecErrorInfo {
+            kind: ExecErrorKind::Rejected,
+            message: "exec command rejected by user".to_string(),
+        });
+        assert_eq!(
+            serde_json::to_value(&event)?["error"],
+            json!({ "kind": "rejected", "message": "exec command rejected by user" })
+        );
+        assert_eq!(event.display_output(), "exec command rejected by user");
+
+        event.aggregated_output = "partial output\n".to_string();
+        assert_eq!(event.display_output(), "partial output\n");
+        Ok(())
+    }
+
+    #[test]
+    fn exec_command_source_reads_the_old_names() -> Result<()> {
+        for (old, source) in [
+            ("agent", ExecCommandSource::ModelShell),
+            ("unified_exec_startup", ExecCommandSource::ModelUnifiedExec),
+            ("user_shell", ExecCommandSource::UserShell),
+            (
+                "unified_exec_interaction",
+                ExecCommandSource::UserUnifiedExecInput,
+            ),
+        ] {
+            assert_eq!(
+                serde_json::from_value::<ExecCommandSource>(json!(old))?,
+                source
+            );
+        }
+        assert_eq!(
+            serde_json::to_value(ExecCommandSource::ModelUnifiedExec)?,
+            json!("model_unified_exec")
+        );
+        assert_eq!(
+            serde_json::to_value(ExecCommandSource::EscalatedRetry)?,
+            json!("escalated_retry")
+        );
+        Ok(())
+    }
 }
@@ -29,6 +29,17 @@ pub enum UserInput {
         name: String,
         path: std::path::PathBuf,
     },
+
+    /// Text document attached to the message, e.g. piped stdin. It is sent to
+    /// the model in full, but history events only keep a [`DocumentReference`].
+    Document { name: String, text: String },
+}
+
+/// Name and size of a [`UserInput::Document`], recorded in place of its text.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS, JsonSchema)]
+pub struct DocumentReference {
+    pub name: String,
+    pub size_bytes: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS, JsonSchema)]
@@ -7,11 +7,13 @@ use rmcp::ServiceExt;
 use rmcp::handler::server::ServerHandler;
 use rmcp::model::CallToolRequestParam;
 use rmcp::model::CallToolResult;
+use rmcp::model::CreateMessageRequestParam;
 use rmcp::model::JsonObject;
 use rmcp::model::ListResourceTemplatesResult;
 use rmcp::model::ListResourcesResult;
 use rmcp::model::ListToolsResult;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::ProgressNotificationParam;
 use rmcp::model::RawResource;
 use rmcp::model::RawResourceTemplate;
 use rmcp::model::ReadResourceRequestParam;
@@ -40,7 +42,15 @@ pub fn stdio() -> (tokio::io::Stdin, tokio::io::Stdout) {
 }
 impl TestToolServer {
     fn new() -> Self {
-        let tools = vec![Self::echo_tool(), Self::image_tool()];
+        let tools = vec![
+            Self::echo_tool(),
+            Self::image_tool(),
+            Self::wait_for_cancel_tool(),
+            Self::announce_tools_changed_tool(),
+            Self::sample_tool(),
+            Self::crash_tool(),
+            Self::report_progress_tool(),
+        ];
         let resources = vec![Self::memo_resource()];
         let resource_templates = vec![Self::memo_template()];
         Self {
@@ -86,6 +96,95 @@ impl TestToolServer {
         )
     }
 
+    fn wait_for_cancel_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }))
+        .expect("wait_for_cancel tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("wait_for_cancel"),
+            Cow::Borrowed(
+                "Block until the client cancels the call, then record it in MCP_TEST_CANCEL_MARKER.",
+            ),
+            Arc::new(schema),
+        )
+    }
+
+    fn announce_tools_changed_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }))
+        .expect("announce_tools_changed tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("announce_tools_changed"),
+            Cow::Borrowed("Send notifications/tools/list_changed to the client."),
+            Arc::new(schema),
+        )
+    }
+
+    fn crash_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }))
+        .expect("crash tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("crash"),
+            Cow::Borrowed("Exit the server process without answering the call."),
+            Arc::new(schema),
+        )
+    }
+
+    fn report_progress_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "steps": { "type": "integer" }
+            },
+            "required": ["steps"],
+            "additionalProperties": false
+        }))
+        .expect("report_progress tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("report_progress"),
+            Cow::Borrowed("Send a notifications/progress for each step right away, then return."),
+            Arc::new(schema),
+        )
+    }
+
+    fn sample_tool() -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "prompt": { "type": "string" },
+                "max_tokens": { "type": "integer" }
+            },
+            "required": ["prompt"],
+            "additionalProperties": false
+        }))
+        .expect("sample tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed("sample"),
+            Cow::Borrowed("Ask the client for a completion of the prompt and return its text."),
+            Arc::new(schema),
+        )
+    }
+
     fn memo_resource() -> Resource {
         let raw = RawResource {
             uri: MEMO_URI.to_string(),
@@ -118,6 +217,17 @@ impl TestToolServer {
     }
 }
 
+#[derive(Deserialize)]
+struct SampleArgs {
+    prompt: String,
+    max_tokens: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ReportProgressArgs {
+    steps: u32,
+}
+
 #[derive(Deserialize)]
 struct EchoArgs {
     message: String,
@@ -204,7 +314,7 @@ impl ServerHandler for TestToolServer {
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         match request.name.as_ref() {
             "echo" => {
@@ -263,6 +373,80 @@ impl ServerHandler for TestToolServer {
                     data_b64, mime_type,
                 )]))
             }
+            "wait_for_cancel" => {
+                // rmcp cancels `context.ct` when the client sends
+                // `notifications/cancelled` for this request.
+                context.ct.cancelled().await;
+                if let Ok(marker) = std::env::var("MCP_TEST_CANCEL_MARKER") {
+                    std::fs::write(marker, "cancelled")
+                        .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                }
+                Ok(CallToolResult::success(Vec::new()))
+            }
+            "crash" => std::process::exit(1),
+            "announce_tools_changed" => {
+                context
+                    .peer
+                    .notify_tool_list_changed()
+                    .await
+                    .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                Ok(CallToolResult::success(Vec::new()))
+            }
+            "report_progress" => {
+                let args: ReportProgressArgs = serde_json::from_value(serde_json::Value::Object(
+                    request.arguments.unwrap_or_default().into_iter().collect(),
+                ))
+                .map_err(|err| McpError::invalid_params(err.to_string(), None))?;
+                let progress_token = context.meta.get_progress_token().ok_or_else(|| {
+                    McpError::invalid_params("report_progress needs a progress token", None)
+                })?;
+                for step in 1..=args.steps {
+                    context
+                        .peer
+                        .notify_progress(ProgressNotificationParam {
+                            progress_token: progress_token.clone(),
+                            progress: f64::from(step),
+                            total: Some(f64::from(args.steps)),
+                            message: Some(format!("step {step}")),
+                        })
+                        .await
+                        .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                }
+                // The client handles notifications concurrently with
+                // responses; give the last one time to land first.
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                Ok(CallToolResult::success(Vec::new()))
+            }
+            "sample" => {
+                let args: SampleArgs = serde_json::from_value(serde_json::Value::Object(
+                    request.arguments.unwrap_or_default().into_iter().collect(),
+                ))
+                .map_err(|err| McpError::invalid_params(err.to_string(), None))?;
+                let params: CreateMessageRequestParam = serde_json::from_value(json!({
+                    "messages": [{
+                        "role": "user",
+                        "content": { "type": "text", "text": args.prompt },
+                    }],
+                    "maxTokens": args.max_tokens.unwrap_or(256),
+                }))
+                .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                // A declined request is reported to the model rather than
+                // failing the call, so callers can observe the decision.
+                match context.peer.create_message(params).await {
+                    Ok(result) => {
+                        let text = serde_json::to_value(&result)
+                            .ok()
+                            .and_then(|value| value["content"]["text"].as_str().map(str::to_string))
+                            .unwrap_or_default();
+                        Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                            text,
+                        )]))
+                    }
+                    Err(err) => Ok(CallToolResult::error(vec![rmcp::model::Content::text(
+                        format!("sampling failed: {err}"),
+                    )])),
+                }
+            }
             other => Err(McpError::invalid_params(
                 format!("unknown tool: {other}"),
                 None,
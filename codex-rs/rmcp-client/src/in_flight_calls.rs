@@ -0,0 +1,113 @@
+//! Tracks tool calls that are waiting on the server by the `call_id` their
+//! caller knows them by, so a call can be cancelled without holding on to its
+//! MCP request id.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use rmcp::model::RequestId;
+use tokio::sync::oneshot;
+
+/// Error returned by [`crate::RmcpClient::call_tool`] for a call that was
+/// cancelled with [`crate::RmcpClient::cancel_tool_call`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCallCancelled;
+
+impl fmt::Display for ToolCallCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("tool call was cancelled by the client")
+    }
+}
+
+impl std::error::Error for ToolCallCancelled {}
+
+#[derive(Clone, Default)]
+pub(crate) struct InFlightCalls {
+    calls: Arc<Mutex<HashMap<String, InFlightCall>>>,
+}
+
+struct InFlightCall {
+    request_id: RequestId,
+    cancel: oneshot::Sender<()>,
+}
+
+impl InFlightCalls {
+    /// Records that `call_id` is waiting on `request_id` until the returned
+    /// guard is dropped. The receiver fires if the call is cancelled first.
+    pub(crate) fn register(
+        &self,
+        call_id: String,
+        request_id: RequestId,
+    ) -> (InFlightRegistration, oneshot::Receiver<()>) {
+        let (cancel, cancelled) = oneshot::channel();
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.insert(
+                call_id.clone(),
+                InFlightCall {
+                    request_id: request_id.clone(),
+                    cancel,
+                },
+            );
+        }
+        let registration = InFlightRegistration {
+            calls: self.clone(),
+            call_id,
+            request_id,
+        };
+        (registration, cancelled)
+    }
+
+    /// Signals the call registered under `call_id` to cancel itself and
+    /// returns its request id, or `None` if no such call is in flight.
+    pub(crate) fn cancel(&self, call_id: &str) -> Option<RequestId> {
+        let call = self.calls.lock().ok()?.remove(call_id)?;
+        let _ = call.cancel.send(());
+        Some(call.request_id)
+    }
+}
+
+pub(crate) struct InFlightRegistration {
+    calls: InFlightCalls,
+    call_id: String,
+    request_id: RequestId,
+}
+
+impl Drop for InFlightRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut calls) = self.calls.calls.lock()
+            && calls
+                .get(&self.call_id)
+                .is_some_and(|call| call.request_id == self.request_id)
+        {
+            calls.remove(&self.call_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rmcp::model::NumberOrString;
+
+    #[test]
+    fn cancel_signals_the_registered_call_once() {
+        let calls = InFlightCalls::default();
+        let request_id = NumberOrString::Number(3);
+        let (registration, mut cancelled) = calls.register("call-1".to_string(), request_id);
+
+        assert_eq!(calls.cancel("other-call"), None);
+        assert!(cancelled.try_recv().is_err());
+
+        assert_eq!(calls.cancel("call-1"), Some(NumberOrString::Number(3)));
+        assert_eq!(cancelled.try_recv(), Ok(()));
+        assert_eq!(calls.cancel("call-1"), None);
+
+        drop(registration);
+        let (_registration, _cancelled) =
+            calls.register("call-2".to_string(), NumberOrString::Number(4));
+        assert_eq!(calls.cancel("call-2"), Some(NumberOrString::Number(4)));
+    }
+}
@@ -1,15 +1,18 @@
 mod auth_status;
 mod find_codex_home;
+mod in_flight_calls;
 mod logging_client_handler;
 mod oauth;
 mod perform_oauth_login;
 mod program_resolver;
 mod rmcp_client;
+mod tool_call_updates;
 mod utils;
 
 pub use auth_status::determine_streamable_http_auth_status;
 pub use auth_status::supports_oauth_login;
 pub use codex_protocol::protocol::McpAuthStatus;
+pub use in_flight_calls::ToolCallCancelled;
 pub use oauth::OAuthCredentialsStoreMode;
 pub use oauth::StoredOAuthTokens;
 pub use oauth::WrappedOAuthTokenResponse;
@@ -22,7 +25,12 @@ pub use perform_oauth_login::perform_oauth_login_return_url;
 pub use rmcp::model::ElicitationAction;
 pub use rmcp_client::Elicitation;
 pub use rmcp_client::ElicitationResponse;
+pub use rmcp_client::HttpTlsConfig;
 pub use rmcp_client::ListToolsWithConnectorIdResult;
 pub use rmcp_client::RmcpClient;
 pub use rmcp_client::SendElicitation;
+pub use rmcp_client::SendSampling;
 pub use rmcp_client::ToolWithConnectorId;
+pub use tool_call_updates::MCP_TOOL_OUTPUT_DELTA_METHOD;
+pub use tool_call_updates::ToolCallUpdate;
+pub use tool_call_updates::ToolCallUpdateSender;
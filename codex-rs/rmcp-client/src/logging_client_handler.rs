@@ -6,47 +6,104 @@ use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientInfo;
 use rmcp::model::CreateElicitationRequestParam;
 use rmcp::model::CreateElicitationResult;
+use rmcp::model::CreateMessageRequestParam;
+use rmcp::model::CreateMessageResult;
+use rmcp::model::CustomNotification;
+use rmcp::model::ErrorCode;
 use rmcp::model::LoggingLevel;
 use rmcp::model::LoggingMessageNotificationParam;
 use rmcp::model::ProgressNotificationParam;
+use rmcp::model::ProgressToken;
 use rmcp::model::RequestId;
 use rmcp::model::ResourceUpdatedNotificationParam;
 use rmcp::service::NotificationContext;
 use rmcp::service::RequestContext;
+use serde::Deserialize;
+use tokio::sync::Notify;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
 use crate::rmcp_client::SendElicitation;
+use crate::rmcp_client::SendSampling;
+use crate::tool_call_updates::MCP_TOOL_OUTPUT_DELTA_METHOD;
+use crate::tool_call_updates::ToolCallUpdate;
+use crate::tool_call_updates::ToolCallUpdateRegistry;
+use crate::utils::convert_to_mcp;
+use crate::utils::convert_to_rmcp;
 
 #[derive(Clone)]
 pub(crate) struct LoggingClientHandler {
     client_info: ClientInfo,
     send_elicitation: Arc<SendElicitation>,
+    /// Answers sampling requests; `None` refuses them.
+    send_sampling: Option<Arc<SendSampling>>,
+    tool_call_updates: ToolCallUpdateRegistry,
+    tools_changed: Arc<Notify>,
 }
 
 impl LoggingClientHandler {
-    pub(crate) fn new(client_info: ClientInfo, send_elicitation: SendElicitation) -> Self {
+    pub(crate) fn new(
+        client_info: ClientInfo,
+        send_elicitation: SendElicitation,
+        send_sampling: Option<SendSampling>,
+        tool_call_updates: ToolCallUpdateRegistry,
+        tools_changed: Arc<Notify>,
+    ) -> Self {
         Self {
             client_info,
             send_elicitation: Arc::new(send_elicitation),
+            send_sampling: send_sampling.map(Arc::new),
+            tool_call_updates,
+            tools_changed,
         }
     }
 }
 
+fn to_mcp_request_id(id: RequestId) -> mcp_types::RequestId {
+    match id {
+        RequestId::String(id) => mcp_types::RequestId::String(id.to_string()),
+        RequestId::Number(id) => mcp_types::RequestId::Integer(id),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolOutputDeltaParams {
+    progress_token: ProgressToken,
+    delta: String,
+}
+
 impl ClientHandler for LoggingClientHandler {
     async fn create_elicitation(
         &self,
         request: CreateElicitationRequestParam,
         context: RequestContext<RoleClient>,
     ) -> Result<CreateElicitationResult, rmcp::ErrorData> {
-        let id = match context.id {
-            RequestId::String(id) => mcp_types::RequestId::String(id.to_string()),
-            RequestId::Number(id) => mcp_types::RequestId::Integer(id),
+        (self.send_elicitation)(to_mcp_request_id(context.id), request)
+            .await
+            .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::ErrorData> {
+        let Some(send_sampling) = &self.send_sampling else {
+            return Err(rmcp::ErrorData::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "sampling is not supported by this client",
+                None,
+            ));
         };
-        (self.send_elicitation)(id, request)
+        let params = convert_to_mcp(params)
+            .map_err(|err| rmcp::ErrorData::invalid_params(err.to_string(), None))?;
+        let result = send_sampling(to_mcp_request_id(context.id), params)
             .await
+            .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))?;
+        convert_to_rmcp(result)
             .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))
     }
 
@@ -66,10 +123,42 @@ impl ClientHandler for LoggingClientHandler {
         params: ProgressNotificationParam,
         _context: NotificationContext<RoleClient>,
     ) {
-        info!(
+        debug!(
             "MCP server progress notification (token: {:?}, progress: {}, total: {:?}, message: {:?})",
             params.progress_token, params.progress, params.total, params.message
         );
+        self.tool_call_updates.dispatch(
+            &params.progress_token,
+            ToolCallUpdate::Progress {
+                progress: f64::from(params.progress),
+                total: params.total.map(f64::from),
+                message: params.message,
+            },
+        );
+    }
+
+    async fn on_custom_notification(
+        &self,
+        notification: CustomNotification,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        if notification.method != MCP_TOOL_OUTPUT_DELTA_METHOD {
+            debug!("ignoring MCP server notification `{}`", notification.method);
+            return;
+        }
+        match notification
+            .params
+            .map(serde_json::from_value::<ToolOutputDeltaParams>)
+        {
+            Some(Ok(ToolOutputDeltaParams {
+                progress_token,
+                delta,
+            })) => self
+                .tool_call_updates
+                .dispatch(&progress_token, ToolCallUpdate::OutputDelta(delta)),
+            Some(Err(err)) => warn!("invalid {MCP_TOOL_OUTPUT_DELTA_METHOD} notification: {err}"),
+            None => warn!("{MCP_TOOL_OUTPUT_DELTA_METHOD} notification without params"),
+        }
     }
 
     async fn on_resource_updated(
@@ -86,6 +175,7 @@ impl ClientHandler for LoggingClientHandler {
 
     async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
         info!("MCP server tool list changed");
+        self.tools_changed.notify_one();
     }
 
     async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
@@ -12,8 +12,14 @@ use futures::FutureExt;
 use futures::future::BoxFuture;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializeResult;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
 use mcp_types::ListResourceTemplatesRequestParams;
 use mcp_types::ListResourceTemplatesResult;
 use mcp_types::ListResourcesRequestParams;
@@ -25,7 +31,9 @@ use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use mcp_types::Tool;
 use reqwest::header::HeaderMap;
+use rmcp::model::CallToolRequest;
 use rmcp::model::CallToolRequestParam;
+use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientNotification;
 use rmcp::model::ClientRequest;
 use rmcp::model::CreateElicitationRequestParam;
@@ -33,10 +41,13 @@ use rmcp::model::CreateElicitationResult;
 use rmcp::model::CustomNotification;
 use rmcp::model::CustomRequest;
 use rmcp::model::Extensions;
+use rmcp::model::GetPromptRequestParam;
 use rmcp::model::InitializeRequestParam;
 use rmcp::model::PaginatedRequestParam;
 use rmcp::model::ReadResourceRequestParam;
 use rmcp::model::ServerResult;
+use rmcp::service::Peer;
+use rmcp::service::PeerRequestOptions;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
 use rmcp::service::{self};
@@ -50,17 +61,24 @@ use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::sync::oneshot;
 use tokio::time;
 use tracing::info;
 use tracing::warn;
 
+use crate::in_flight_calls::InFlightCalls;
+use crate::in_flight_calls::ToolCallCancelled;
 use crate::load_oauth_tokens;
 use crate::logging_client_handler::LoggingClientHandler;
 use crate::oauth::OAuthCredentialsStoreMode;
 use crate::oauth::OAuthPersistor;
 use crate::oauth::StoredOAuthTokens;
 use crate::program_resolver;
+use crate::tool_call_updates::ToolCallUpdateRegistry;
+use crate::tool_call_updates::ToolCallUpdateSender;
 use crate::utils::apply_default_headers;
+use crate::utils::apply_tls;
 use crate::utils::build_default_headers;
 use crate::utils::convert_call_tool_result;
 use crate::utils::convert_to_mcp;
@@ -97,6 +115,23 @@ pub type SendElicitation = Box<
     dyn Fn(RequestId, Elicitation) -> BoxFuture<'static, Result<ElicitationResponse>> + Send + Sync,
 >;
 
+/// Interface for answering `sampling/createMessage` requests from the server.
+pub type SendSampling = Box<
+    dyn Fn(RequestId, CreateMessageRequestParams) -> BoxFuture<'static, Result<CreateMessageResult>>
+        + Send
+        + Sync,
+>;
+
+/// TLS settings for a streamable HTTP server. All files are PEM encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpTlsConfig {
+    /// Certificates trusted in addition to the system roots.
+    pub ca_certificate: Option<PathBuf>,
+    /// Certificate presented to the server, together with `client_key`.
+    pub client_certificate: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
 pub struct ToolWithConnectorId {
     pub tool: Tool,
     pub connector_id: Option<String>,
@@ -108,10 +143,66 @@ pub struct ListToolsWithConnectorIdResult {
     pub tools: Vec<ToolWithConnectorId>,
 }
 
+/// How long a cancelled tool call waits for the server to answer the
+/// cancelled request. Servers are not required to answer at all.
+const CANCEL_ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tells the server a request was abandoned unless disarmed by clearing
+/// `peer` before it is dropped.
+struct CancelOnDrop {
+    peer: Option<Peer<RoleClient>>,
+    request_id: rmcp::model::RequestId,
+}
+
+impl CancelOnDrop {
+    /// Sends the cancellation now instead of on drop.
+    async fn notify(&mut self) {
+        if let Some(peer) = self.peer.take() {
+            notify_cancelled(peer, self.request_id.clone()).await;
+        }
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let Some(peer) = self.peer.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(notify_cancelled(peer, self.request_id.clone()));
+    }
+}
+
+async fn notify_cancelled(peer: Peer<RoleClient>, request_id: rmcp::model::RequestId) {
+    if let Err(err) = peer
+        .notify_cancelled(CancelledNotificationParam {
+            request_id,
+            reason: Some(ToolCallCancelled.to_string()),
+        })
+        .await
+    {
+        warn!("failed to send MCP cancellation notification: {err}");
+    }
+}
+
+/// Resolves once the call is cancelled; never for calls without a `call_id`.
+async fn wait_for_cancel(cancelled: Option<oneshot::Receiver<()>>) {
+    match cancelled {
+        Some(cancelled) if cancelled.await.is_ok() => {}
+        _ => std::future::pending().await,
+    }
+}
+
 /// MCP client implemented on top of the official `rmcp` SDK.
 /// https://github.com/modelcontextprotocol/rust-sdk
 pub struct RmcpClient {
     state: Mutex<ClientState>,
+    tool_call_updates: ToolCallUpdateRegistry,
+    in_flight_calls: InFlightCalls,
+    /// Signalled by `notifications/tools/list_changed`.
+    tools_changed: Arc<Notify>,
 }
 
 impl RmcpClient {
@@ -168,6 +259,9 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(PendingTransport::ChildProcess(transport)),
             }),
+            tool_call_updates: ToolCallUpdateRegistry::default(),
+            in_flight_calls: InFlightCalls::default(),
+            tools_changed: Arc::new(Notify::new()),
         })
     }
 
@@ -178,9 +272,11 @@ impl RmcpClient {
         bearer_token: Option<String>,
         http_headers: Option<HashMap<String, String>>,
         env_http_headers: Option<HashMap<String, String>>,
+        tls: Option<HttpTlsConfig>,
         store_mode: OAuthCredentialsStoreMode,
     ) -> Result<Self> {
         let default_headers = build_default_headers(http_headers, env_http_headers)?;
+        let tls = tls.unwrap_or_default();
 
         let initial_oauth_tokens = match bearer_token {
             Some(_) => None,
@@ -200,6 +296,7 @@ impl RmcpClient {
                 initial_tokens,
                 store_mode,
                 default_headers.clone(),
+                &tls,
             )
             .await?;
             PendingTransport::StreamableHttpWithOAuth {
@@ -212,8 +309,11 @@ impl RmcpClient {
                 http_config = http_config.auth_header(bearer_token);
             }
 
-            let http_client =
-                apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
+            let http_client = apply_tls(
+                apply_default_headers(reqwest::Client::builder(), &default_headers),
+                &tls,
+            )?
+            .build()?;
 
             let transport = StreamableHttpClientTransport::with_client(http_client, http_config);
             PendingTransport::StreamableHttp { transport }
@@ -222,6 +322,9 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(transport),
             }),
+            tool_call_updates: ToolCallUpdateRegistry::default(),
+            in_flight_calls: InFlightCalls::default(),
+            tools_changed: Arc::new(Notify::new()),
         })
     }
 
@@ -232,9 +335,16 @@ impl RmcpClient {
         params: InitializeRequestParams,
         timeout: Option<Duration>,
         send_elicitation: SendElicitation,
+        send_sampling: Option<SendSampling>,
     ) -> Result<InitializeResult> {
         let rmcp_params: InitializeRequestParam = convert_to_rmcp(params.clone())?;
-        let client_handler = LoggingClientHandler::new(rmcp_params, send_elicitation);
+        let client_handler = LoggingClientHandler::new(
+            rmcp_params,
+            send_elicitation,
+            send_sampling,
+            self.tool_call_updates.clone(),
+            Arc::clone(&self.tools_changed),
+        );
 
         let (transport, oauth_persistor) = {
             let mut guard = self.state.lock().await;
@@ -401,23 +511,135 @@ impl RmcpClient {
         Ok(converted)
     }
 
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.refresh_oauth_if_needed().await;
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_prompts(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/list").await?;
+        let converted = convert_to_mcp(result)?;
+        self.persist_oauth_tokens().await;
+        Ok(converted)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        params: GetPromptRequestParams,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        self.refresh_oauth_if_needed().await;
+        let service = self.service().await?;
+        let rmcp_params: GetPromptRequestParam = convert_to_rmcp(params)?;
+        let fut = service.get_prompt(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/get").await?;
+        let converted = convert_to_mcp(result)?;
+        self.persist_oauth_tokens().await;
+        Ok(converted)
+    }
+
+    /// Calls a tool. Progress and partial output the server reports for the
+    /// call are sent to `updates`.
+    ///
+    /// A call made with `updates` or a `call_id` is tracked while in flight:
+    /// if the returned future is dropped before the call finishes, the server
+    /// is sent `notifications/cancelled`. A call made with a `call_id` can
+    /// also be stopped with [`Self::cancel_tool_call`], in which case this
+    /// fails with [`ToolCallCancelled`].
     pub async fn call_tool(
         &self,
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        updates: Option<ToolCallUpdateSender>,
+        call_id: Option<String>,
     ) -> Result<CallToolResult> {
         self.refresh_oauth_if_needed().await;
         let service = self.service().await?;
         let params = CallToolRequestParams { arguments, name };
         let rmcp_params: CallToolRequestParam = convert_to_rmcp(params)?;
-        let fut = service.call_tool(rmcp_params);
-        let rmcp_result = run_with_timeout(fut, timeout, "tools/call").await?;
+        let rmcp_result = if updates.is_none() && call_id.is_none() {
+            run_with_timeout(service.call_tool(rmcp_params), timeout, "tools/call").await?
+        } else {
+            self.call_tool_tracked(&service, rmcp_params, timeout, updates, call_id)
+                .await?
+        };
         let converted = convert_call_tool_result(rmcp_result)?;
         self.persist_oauth_tokens().await;
         Ok(converted)
     }
 
+    async fn call_tool_tracked(
+        &self,
+        service: &RunningService<RoleClient, LoggingClientHandler>,
+        rmcp_params: CallToolRequestParam,
+        timeout: Option<Duration>,
+        updates: Option<ToolCallUpdateSender>,
+        call_id: Option<String>,
+    ) -> Result<rmcp::model::CallToolResult> {
+        // Subscribe before sending: the server may report progress before
+        // `send_cancellable_request` returns the call's progress token.
+        let mut subscription = updates.map(|updates| self.tool_call_updates.subscribe(updates));
+        let handle = service
+            .send_cancellable_request(
+                ClientRequest::CallToolRequest(CallToolRequest::new(rmcp_params)),
+                PeerRequestOptions::no_options(),
+            )
+            .await
+            .map_err(|err| anyhow!("tools/call failed: {err}"))?;
+        if let Some(subscription) = &mut subscription {
+            subscription.bind(&handle.progress_token);
+        }
+        let (_in_flight, cancelled) = match call_id {
+            Some(call_id) => {
+                let (registration, cancelled) =
+                    self.in_flight_calls.register(call_id, handle.id.clone());
+                (Some(registration), Some(cancelled))
+            }
+            None => (None, None),
+        };
+        let mut cancel_on_drop = CancelOnDrop {
+            peer: Some(service.peer().clone()),
+            request_id: handle.id.clone(),
+        };
+        let mut pending_response = handle.await_response().boxed();
+        let response = tokio::select! {
+            response = run_with_timeout(&mut pending_response, timeout, "tools/call") => {
+                Some(response)
+            }
+            () = wait_for_cancel(cancelled) => None,
+        };
+        let Some(response) = response else {
+            cancel_on_drop.notify().await;
+            let _ = time::timeout(CANCEL_ACK_TIMEOUT, pending_response).await;
+            return Err(ToolCallCancelled.into());
+        };
+        // A timed-out call is abandoned too, so only a response disarms the
+        // cancellation.
+        if response.is_ok() {
+            cancel_on_drop.peer = None;
+        }
+        match response? {
+            ServerResult::CallToolResult(result) => Ok(result),
+            other => Err(anyhow!("unexpected response to tools/call: {other:?}")),
+        }
+    }
+
+    /// Cancels the in-flight call started with `call_id`: the server is sent
+    /// `notifications/cancelled`, and the call fails with
+    /// [`ToolCallCancelled`] once the server answers or
+    /// [`CANCEL_ACK_TIMEOUT`] passes. Returns `false` if no such call is in
+    /// flight.
+    pub fn cancel_tool_call(&self, call_id: &str) -> bool {
+        self.in_flight_calls.cancel(call_id).is_some()
+    }
+
     pub async fn send_custom_notification(
         &self,
         method: &str,
@@ -448,6 +670,39 @@ impl RmcpClient {
         Ok(response)
     }
 
+    /// Sends `ping` and waits for the server to answer. Used to notice a
+    /// streamable HTTP server that went away, which does not close anything
+    /// on our side.
+    pub async fn ping(&self, timeout: Option<Duration>) -> Result<()> {
+        let service: Arc<RunningService<RoleClient, LoggingClientHandler>> = self.service().await?;
+        run_with_timeout(
+            service.send_request(ClientRequest::CustomRequest(CustomRequest::new(
+                "ping", None,
+            ))),
+            timeout,
+            "ping",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the connection to an initialized server has closed, for
+    /// example because the server process exited.
+    pub async fn is_closed(&self) -> bool {
+        let guard = self.state.lock().await;
+        match &*guard {
+            ClientState::Ready { service, .. } => service.peer().is_transport_closed(),
+            ClientState::Connecting { .. } => false,
+        }
+    }
+
+    /// Resolves once the server sends `notifications/tools/list_changed`.
+    /// A notification that arrives while nobody is waiting is kept for the
+    /// next call.
+    pub async fn tool_list_changed(&self) {
+        self.tools_changed.notified().await;
+    }
+
     async fn service(&self) -> Result<Arc<RunningService<RoleClient, LoggingClientHandler>>> {
         let guard = self.state.lock().await;
         match &*guard {
@@ -492,12 +747,16 @@ async fn create_oauth_transport_and_runtime(
     initial_tokens: StoredOAuthTokens,
     credentials_store: OAuthCredentialsStoreMode,
     default_headers: HeaderMap,
+    tls: &HttpTlsConfig,
 ) -> Result<(
     StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
     OAuthPersistor,
 )> {
-    let http_client =
-        apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
+    let http_client = apply_tls(
+        apply_default_headers(reqwest::Client::builder(), &default_headers),
+        tls,
+    )?
+    .build()?;
     let mut oauth_state = OAuthState::new(url.to_string(), Some(http_client.clone())).await?;
 
     oauth_state
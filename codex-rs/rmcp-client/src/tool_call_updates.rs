@@ -0,0 +1,169 @@
+//! Routes progress and partial-output notifications to the tool call that
+//! asked for them, matched by the request's progress token.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use rmcp::model::ProgressToken;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Method of the notification servers send to stream partial output of a tool
+/// call. Its params are `{ "progressToken": ..., "delta": "<text>" }`.
+pub const MCP_TOOL_OUTPUT_DELTA_METHOD: &str = "codex/tool_output_delta";
+
+/// Updates a server sends about an in-flight tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolCallUpdate {
+    /// A `notifications/progress` notification.
+    Progress {
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    },
+    /// Partial output from a [`MCP_TOOL_OUTPUT_DELTA_METHOD`] notification.
+    OutputDelta(String),
+}
+
+pub type ToolCallUpdateSender = UnboundedSender<ToolCallUpdate>;
+
+/// Shared between an [`crate::RmcpClient`] and its notification handler.
+#[derive(Clone, Default)]
+pub(crate) struct ToolCallUpdateRegistry {
+    inner: Arc<Mutex<Subscriptions>>,
+}
+
+#[derive(Default)]
+struct Subscriptions {
+    senders: HashMap<String, ToolCallUpdateSender>,
+    /// Calls subscribed before their request was sent, so their progress
+    /// token is not known yet.
+    unbound: usize,
+    /// Updates for unknown tokens that arrived while a call was unbound; they
+    /// may be for that call.
+    early: HashMap<String, Vec<ToolCallUpdate>>,
+}
+
+impl Subscriptions {
+    fn release_unbound(&mut self) {
+        self.unbound = self.unbound.saturating_sub(1);
+        if self.unbound == 0 {
+            self.early.clear();
+        }
+    }
+}
+
+impl ToolCallUpdateRegistry {
+    /// Subscribes `sender` to the updates of a call about to be sent. Once
+    /// the request is sent, [`ToolCallUpdateSubscription::bind`] it to the
+    /// request's progress token; updates are forwarded until the returned
+    /// guard is dropped.
+    pub(crate) fn subscribe(&self, sender: ToolCallUpdateSender) -> ToolCallUpdateSubscription {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.unbound += 1;
+        }
+        ToolCallUpdateSubscription {
+            registry: self.clone(),
+            sender: Some(sender),
+            key: None,
+        }
+    }
+
+    /// Sends `update` to the call subscribed to `token`. Updates for unknown
+    /// tokens are held while a call is yet to be bound, and dropped
+    /// otherwise.
+    pub(crate) fn dispatch(&self, token: &ProgressToken, update: ToolCallUpdate) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let key = token_key(token);
+        if let Some(sender) = inner.senders.get(&key) {
+            let _ = sender.send(update);
+        } else if inner.unbound > 0 {
+            inner.early.entry(key).or_default().push(update);
+        }
+    }
+}
+
+pub(crate) struct ToolCallUpdateSubscription {
+    registry: ToolCallUpdateRegistry,
+    sender: Option<ToolCallUpdateSender>,
+    key: Option<String>,
+}
+
+impl ToolCallUpdateSubscription {
+    /// Routes updates for `token` to this subscription, starting with those
+    /// that arrived before the call's request returned.
+    pub(crate) fn bind(&mut self, token: &ProgressToken) {
+        let Some(sender) = self.sender.take() else {
+            return;
+        };
+        let Ok(mut inner) = self.registry.inner.lock() else {
+            return;
+        };
+        let key = token_key(token);
+        for update in inner.early.remove(&key).unwrap_or_default() {
+            let _ = sender.send(update);
+        }
+        inner.release_unbound();
+        inner.senders.insert(key.clone(), sender);
+        self.key = Some(key);
+    }
+}
+
+impl Drop for ToolCallUpdateSubscription {
+    fn drop(&mut self) {
+        let Ok(mut inner) = self.registry.inner.lock() else {
+            return;
+        };
+        match self.key.take() {
+            Some(key) => {
+                inner.senders.remove(&key);
+            }
+            None if self.sender.is_some() => inner.release_unbound(),
+            None => {}
+        }
+    }
+}
+
+fn token_key(token: &ProgressToken) -> String {
+    token.0.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rmcp::model::NumberOrString;
+
+    #[test]
+    fn updates_reach_the_subscribed_call_until_it_finishes() {
+        let registry = ToolCallUpdateRegistry::default();
+        let token = ProgressToken(NumberOrString::Number(7));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut subscription = registry.subscribe(tx);
+        // Sent by the server before the request returned its token.
+        registry.dispatch(&token, ToolCallUpdate::OutputDelta("early".to_string()));
+        subscription.bind(&token);
+        registry.dispatch(&token, ToolCallUpdate::OutputDelta("partial".to_string()));
+        registry.dispatch(
+            &ProgressToken(NumberOrString::Number(8)),
+            ToolCallUpdate::OutputDelta("other call".to_string()),
+        );
+        drop(subscription);
+        registry.dispatch(&token, ToolCallUpdate::OutputDelta("late".to_string()));
+
+        assert_eq!(
+            rx.try_recv().ok(),
+            Some(ToolCallUpdate::OutputDelta("early".to_string()))
+        );
+        assert_eq!(
+            rx.try_recv().ok(),
+            Some(ToolCallUpdate::OutputDelta("partial".to_string()))
+        );
+        assert!(rx.try_recv().is_err());
+        let inner = registry.inner.lock().unwrap();
+        assert!(inner.senders.is_empty() && inner.early.is_empty());
+    }
+}
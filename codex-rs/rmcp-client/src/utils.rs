@@ -6,7 +6,9 @@ use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolResult;
+use reqwest::Certificate;
 use reqwest::ClientBuilder;
+use reqwest::Identity;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
@@ -15,6 +17,8 @@ use rmcp::service::ServiceError;
 use serde_json::Value;
 use tokio::time;
 
+use crate::rmcp_client::HttpTlsConfig;
+
 pub(crate) async fn run_with_timeout<F, T>(
     fut: F,
     timeout: Option<Duration>,
@@ -154,6 +158,43 @@ pub(crate) fn apply_default_headers(
     }
 }
 
+pub(crate) fn apply_tls(mut builder: ClientBuilder, tls: &HttpTlsConfig) -> Result<ClientBuilder> {
+    if let Some(path) = &tls.ca_certificate {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("failed to read CA certificate {}", path.display()))?;
+        let certificates = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("invalid CA certificate {}", path.display()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    match (&tls.client_certificate, &tls.client_key) {
+        (Some(certificate), Some(key)) => {
+            let mut pem = std::fs::read(certificate).with_context(|| {
+                format!(
+                    "failed to read client certificate {}",
+                    certificate.display()
+                )
+            })?;
+            pem.push(b'\n');
+            pem.extend(
+                std::fs::read(key)
+                    .with_context(|| format!("failed to read client key {}", key.display()))?,
+            );
+            let identity = Identity::from_pem(&pem)
+                .context("client certificate and key do not form a valid identity")?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(anyhow!(
+                "client_certificate and client_key must be set together"
+            ));
+        }
+    }
+    Ok(builder)
+}
+
 #[cfg(unix)]
 pub(crate) const DEFAULT_ENV_VARS: &[&str] = &[
     "HOME",
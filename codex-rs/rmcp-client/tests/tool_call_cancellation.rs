@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_rmcp_client::ElicitationAction;
+use codex_rmcp_client::ElicitationResponse;
+use codex_rmcp_client::RmcpClient;
+use codex_rmcp_client::ToolCallCancelled;
+use codex_utils_cargo_bin::CargoBinError;
+use futures::FutureExt as _;
+use mcp_types::ClientCapabilities;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn stdio_server_bin() -> Result<PathBuf, CargoBinError> {
+    codex_utils_cargo_bin::cargo_bin("test_stdio_server")
+}
+
+fn init_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-test".into(),
+            version: "0.0.0-test".into(),
+            title: Some("Codex rmcp cancellation test".into()),
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_string(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel_tool_call_sends_cancelled_notification() -> anyhow::Result<()> {
+    let marker_dir = tempfile::tempdir()?;
+    let marker = marker_dir.path().join("cancelled");
+    let client = RmcpClient::new_stdio_client(
+        stdio_server_bin()?.into(),
+        Vec::<OsString>::new(),
+        Some(HashMap::from([(
+            "MCP_TEST_CANCEL_MARKER".to_string(),
+            marker.to_string_lossy().into_owned(),
+        )])),
+        &[],
+        None,
+    )
+    .await?;
+    client
+        .initialize(
+            init_params(),
+            Some(Duration::from_secs(5)),
+            Box::new(|_, _| {
+                async {
+                    Ok(ElicitationResponse {
+                        action: ElicitationAction::Accept,
+                        content: Some(json!({})),
+                    })
+                }
+                .boxed()
+            }),
+            None,
+        )
+        .await?;
+    let client = Arc::new(client);
+
+    assert!(!client.cancel_tool_call("call-1"));
+    let call = tokio::spawn({
+        let client = Arc::clone(&client);
+        async move {
+            client
+                .call_tool(
+                    "wait_for_cancel".to_string(),
+                    Some(json!({})),
+                    Some(Duration::from_secs(30)),
+                    None,
+                    Some("call-1".to_string()),
+                )
+                .await
+        }
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !client.cancel_tool_call("call-1") {
+        assert!(Instant::now() < deadline, "call never became cancellable");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let err = call.await?.expect_err("cancelled call should fail");
+    assert_eq!(
+        err.downcast_ref::<ToolCallCancelled>(),
+        Some(&ToolCallCancelled)
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !marker.exists() {
+        assert!(
+            Instant::now() < deadline,
+            "server never saw notifications/cancelled"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(std::fs::read_to_string(&marker)?, "cancelled");
+
+    Ok(())
+}
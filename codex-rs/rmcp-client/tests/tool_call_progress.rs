@@ -0,0 +1,94 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_rmcp_client::ElicitationAction;
+use codex_rmcp_client::ElicitationResponse;
+use codex_rmcp_client::RmcpClient;
+use codex_rmcp_client::ToolCallUpdate;
+use codex_utils_cargo_bin::CargoBinError;
+use futures::FutureExt as _;
+use mcp_types::ClientCapabilities;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn stdio_server_bin() -> Result<PathBuf, CargoBinError> {
+    codex_utils_cargo_bin::cargo_bin("test_stdio_server")
+}
+
+fn init_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-test".into(),
+            version: "0.0.0-test".into(),
+            title: Some("Codex rmcp progress test".into()),
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_string(),
+    }
+}
+
+/// The server reports progress as soon as the call arrives, which can be
+/// before the client learns the call's progress token; none of it is lost.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn progress_sent_right_away_reaches_the_call() -> anyhow::Result<()> {
+    let client = RmcpClient::new_stdio_client(
+        stdio_server_bin()?.into(),
+        Vec::<OsString>::new(),
+        None,
+        &[],
+        None,
+    )
+    .await?;
+    client
+        .initialize(
+            init_params(),
+            Some(Duration::from_secs(5)),
+            Box::new(|_, _| {
+                async {
+                    Ok(ElicitationResponse {
+                        action: ElicitationAction::Accept,
+                        content: Some(json!({})),
+                    })
+                }
+                .boxed()
+            }),
+            None,
+        )
+        .await?;
+
+    let (updates_tx, mut updates_rx) = tokio::sync::mpsc::unbounded_channel();
+    client
+        .call_tool(
+            "report_progress".to_string(),
+            Some(json!({ "steps": 3 })),
+            Some(Duration::from_secs(5)),
+            Some(updates_tx),
+            None,
+        )
+        .await?;
+
+    // The client drops its sender once the call finishes.
+    let mut updates = Vec::new();
+    while let Some(update) = updates_rx.recv().await {
+        updates.push(update);
+    }
+    let expected: Vec<ToolCallUpdate> = (1..=3)
+        .map(|step| ToolCallUpdate::Progress {
+            progress: f64::from(step),
+            total: Some(3.0),
+            message: Some(format!("step {step}")),
+        })
+        .collect();
+    assert_eq!(updates, expected);
+
+    Ok(())
+}
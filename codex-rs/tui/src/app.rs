@@ -24,6 +24,7 @@ use crate::history_cell::UpdateAvailableHistoryCell;
 use crate::model_migration::ModelMigrationOutcome;
 use crate::model_migration::migration_copy_for_models;
 use crate::model_migration::run_model_migration_prompt;
+use crate::pager_overlay::OutputOpenRequest;
 use crate::pager_overlay::Overlay;
 use crate::render::highlight::highlight_bash_to_lines;
 use crate::render::renderable::Renderable;
@@ -521,6 +522,9 @@ pub(crate) struct App {
 
     // Pager overlay state (Transcript or Static like Diff)
     pub(crate) overlay: Option<Overlay>,
+    /// Exec outputs expanded in the transcript overlay, kept across openings
+    /// for as long as the app runs.
+    pub(crate) expanded_transcript_outputs: HashSet<String>,
     pub(crate) deferred_history_lines: Vec<Line<'static>>,
     has_emitted_history_lines: bool,
 
@@ -1052,6 +1056,7 @@ impl App {
             enhanced_keys_supported,
             transcript_cells: Vec::new(),
             overlay: None,
+            expanded_transcript_outputs: HashSet::new(),
             deferred_history_lines: Vec::new(),
             has_emitted_history_lines: false,
             commit_anim_running: Arc::new(AtomicBool::new(false)),
@@ -1181,6 +1186,11 @@ impl App {
     ) -> Result<AppRunControl> {
         if self.overlay.is_some() {
             let _ = self.handle_backtrack_overlay_event(tui, event).await?;
+            if let Some(Overlay::Transcript(t)) = &mut self.overlay
+                && let Some(request) = t.take_open_request()
+            {
+                self.open_transcript_output(tui, request).await;
+            }
         } else {
             match event {
                 TuiEvent::Key(key_event) => {
@@ -1200,6 +1210,7 @@ impl App {
                         self.render_transcript_once(tui);
                     }
                     self.chat_widget.maybe_post_pending_notification(tui);
+                    self.chat_widget.apply_pending_exec_output();
                     if self
                         .chat_widget
                         .handle_paste_burst_tick(tui.frame_requester())
@@ -1266,6 +1277,7 @@ impl App {
                 match crate::resume_picker::run_resume_picker(
                     tui,
                     &self.config.codex_home,
+                    &self.config.state_dir,
                     &self.config.model_provider_id,
                     false,
                 )
@@ -1351,9 +1363,50 @@ impl App {
                             }
                         }
                     }
-                    SessionSelection::Exit
-                    | SessionSelection::StartFresh
-                    | SessionSelection::Fork(_) => {}
+                    SessionSelection::Fork(path) => {
+                        let summary = session_summary(
+                            self.chat_widget.token_usage(),
+                            self.chat_widget.thread_id(),
+                        );
+                        match self
+                            .server
+                            .fork_thread(usize::MAX, self.config.clone(), path.clone())
+                            .await
+                        {
+                            Ok(forked) => {
+                                self.shutdown_current_thread().await;
+                                let init = self.chatwidget_init_for_forked_or_resumed_thread(
+                                    tui,
+                                    self.config.clone(),
+                                );
+                                self.chat_widget = ChatWidget::new_from_existing(
+                                    init,
+                                    forked.thread,
+                                    forked.session_configured,
+                                );
+                                self.reset_thread_event_state();
+                                if let Some(summary) = summary {
+                                    let mut lines: Vec<Line<'static>> =
+                                        vec![summary.usage_line.clone().into()];
+                                    if let Some(command) = summary.resume_command {
+                                        let spans = vec![
+                                            "To continue this session, run ".into(),
+                                            command.cyan(),
+                                        ];
+                                        lines.push(spans.into());
+                                    }
+                                    self.chat_widget.add_plain_history_lines(lines);
+                                }
+                            }
+                            Err(err) => {
+                                let path_display = path.display();
+                                self.chat_widget.add_error_message(format!(
+                                    "Failed to fork session from {path_display}: {err}"
+                                ));
+                            }
+                        }
+                    }
+                    SessionSelection::Exit | SessionSelection::StartFresh => {}
                 }
 
                 // Leaving alt-screen may blank the inline viewport; force a redraw either way.
@@ -1503,6 +1556,15 @@ impl App {
             AppEvent::UpdateModel(model) => {
                 self.chat_widget.set_model(&model);
             }
+            AppEvent::ExportTranscript { path, format } => {
+                self.chat_widget.export_transcript(path, format);
+            }
+            AppEvent::CopyLastAgentMessage => {
+                self.chat_widget.copy_last_agent_message();
+            }
+            AppEvent::CopyTurnDiff => {
+                self.chat_widget.copy_turn_diff();
+            }
             AppEvent::UpdateCollaborationMode(mask) => {
                 self.chat_widget.set_collaboration_mask(mask);
             }
@@ -2070,6 +2132,25 @@ impl App {
                         "E L I C I T A T I O N".to_string(),
                     ));
                 }
+                ApprovalRequest::McpSampling {
+                    server_name,
+                    model,
+                    prompt,
+                    ..
+                } => {
+                    let _ = tui.enter_alt_screen();
+                    let paragraph = Paragraph::new(vec![
+                        Line::from(vec!["Server: ".into(), server_name.bold()]),
+                        Line::from(vec!["Model: ".into(), model.bold()]),
+                        Line::from(""),
+                        Line::from(prompt),
+                    ])
+                    .wrap(Wrap { trim: false });
+                    self.overlay = Some(Overlay::new_static_with_renderables(
+                        vec![Box::new(paragraph)],
+                        "S A M P L I N G".to_string(),
+                    ));
+                }
             },
         }
         Ok(AppRunControl::Continue)
@@ -2129,6 +2210,9 @@ impl App {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: thread.rollout_path(),
+                shell: None,
+                tools: None,
+                tmpdir: None,
             }),
         };
         let channel =
@@ -2245,6 +2329,48 @@ impl App {
         tui.frame_requester().schedule_frame();
     }
 
+    /// Shows an exec output from the transcript overlay in the editor, or in
+    /// `$PAGER` when no editor is set. Failures are reported in the history.
+    async fn open_transcript_output(&mut self, tui: &mut tui::Tui, request: OutputOpenRequest) {
+        let viewer_cmd = match external_editor::resolve_viewer_command() {
+            Ok(cmd) => cmd,
+            Err(external_editor::EditorError::MissingEditor) => {
+                self.chat_widget
+                    .add_to_history(history_cell::new_error_event(
+                        "Cannot open output: set $VISUAL, $EDITOR or $PAGER".to_string(),
+                    ));
+                return;
+            }
+            Err(err) => {
+                self.chat_widget
+                    .add_to_history(history_cell::new_error_event(format!(
+                        "Failed to open output: {err}",
+                    )));
+                return;
+            }
+        };
+
+        let result = tui
+            .with_restored(tui::RestoreMode::KeepRaw, || async {
+                match &request {
+                    OutputOpenRequest::Text(text) => {
+                        external_editor::view_text(text, &viewer_cmd).await
+                    }
+                    OutputOpenRequest::File(path) => {
+                        external_editor::open_file(path, &viewer_cmd).await
+                    }
+                }
+            })
+            .await;
+        if let Err(err) = result {
+            self.chat_widget
+                .add_to_history(history_cell::new_error_event(format!(
+                    "Failed to open output: {err}",
+                )));
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
     fn request_external_editor_launch(&mut self, tui: &mut tui::Tui) {
         self.chat_widget
             .set_external_editor_state(ExternalEditorState::Requested);
@@ -2272,7 +2398,20 @@ impl App {
             } => {
                 // Enter alternate screen and set viewport to full size.
                 let _ = tui.enter_alt_screen();
-                self.overlay = Some(Overlay::new_transcript(self.transcript_cells.clone()));
+                self.overlay = Some(Overlay::new_transcript(
+                    self.transcript_cells.clone(),
+                    self.expanded_transcript_outputs.clone(),
+                ));
+                tui.frame_requester().schedule_frame();
+            }
+            KeyEvent {
+                code: KeyCode::Char('o'),
+                modifiers: crossterm::event::KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => {
+                let _ = tui.enter_alt_screen();
+                self.overlay = Some(Overlay::new_diff(self.chat_widget.turn_diff()));
                 tui.frame_requester().schedule_frame();
             }
             KeyEvent {
@@ -2448,6 +2587,7 @@ mod tests {
             file_search,
             transcript_cells: Vec::new(),
             overlay: None,
+            expanded_transcript_outputs: HashSet::new(),
             deferred_history_lines: Vec::new(),
             has_emitted_history_lines: false,
             enhanced_keys_supported: false,
@@ -2500,6 +2640,7 @@ mod tests {
                 file_search,
                 transcript_cells: Vec::new(),
                 overlay: None,
+                expanded_transcript_outputs: HashSet::new(),
                 deferred_history_lines: Vec::new(),
                 has_emitted_history_lines: false,
                 enhanced_keys_supported: false,
@@ -2751,6 +2892,9 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: Some(PathBuf::new()),
+                shell: None,
+                tools: None,
+                tmpdir: None,
             };
             Arc::new(new_session_info(
                 app.chat_widget.config_ref(),
@@ -2803,6 +2947,9 @@ mod tests {
                 history_entry_count: 0,
                 initial_messages: None,
                 rollout_path: Some(PathBuf::new()),
+                shell: None,
+                tools: None,
+                tmpdir: None,
             }),
         });
 
@@ -2848,6 +2995,9 @@ mod tests {
             history_entry_count: 0,
             initial_messages: None,
             rollout_path: Some(PathBuf::new()),
+            shell: None,
+            tools: None,
+            tmpdir: None,
         };
 
         app.chat_widget.handle_codex_event(Event {
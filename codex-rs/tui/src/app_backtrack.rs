@@ -220,7 +220,10 @@ impl App {
     /// Open transcript overlay (enters alternate screen and shows full transcript).
     pub(crate) fn open_transcript_overlay(&mut self, tui: &mut tui::Tui) {
         let _ = tui.enter_alt_screen();
-        self.overlay = Some(Overlay::new_transcript(self.transcript_cells.clone()));
+        self.overlay = Some(Overlay::new_transcript(
+            self.transcript_cells.clone(),
+            self.expanded_transcript_outputs.clone(),
+        ));
         tui.frame_requester().schedule_frame();
     }
 
@@ -232,6 +235,9 @@ impl App {
             let lines = std::mem::take(&mut self.deferred_history_lines);
             tui.insert_history_lines(lines);
         }
+        if let Some(Overlay::Transcript(t)) = &self.overlay {
+            self.expanded_transcript_outputs = t.expanded_outputs().clone();
+        }
         self.overlay = None;
         self.backtrack.overlay_preview_active = false;
         if was_backtrack {
@@ -380,6 +386,14 @@ impl App {
             return Ok(());
         }
 
+        // Like the transcript's live tail, the diff viewer follows the turn:
+        // hand it the latest diff before each draw.
+        if let TuiEvent::Draw = &event
+            && let Some(Overlay::Diff(d)) = &mut self.overlay
+        {
+            d.set_diff(self.chat_widget.turn_diff());
+        }
+
         if let Some(overlay) = &mut self.overlay {
             overlay.handle_event(tui, event)?;
             if overlay.is_done() {
@@ -19,6 +19,7 @@ use codex_protocol::openai_models::ModelPreset;
 
 use crate::bottom_pane::ApprovalRequest;
 use crate::history_cell::HistoryCell;
+use crate::transcript_export::ExportFormat;
 
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
@@ -92,6 +93,18 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Write the current conversation to `path` from `/export`.
+    ExportTranscript {
+        path: PathBuf,
+        format: ExportFormat,
+    },
+
+    /// Copy the last agent message to the clipboard.
+    CopyLastAgentMessage,
+
+    /// Copy the current turn's diff to the clipboard.
+    CopyTurnDiff,
+
     InsertHistoryCell(Box<dyn HistoryCell>),
 
     StartCommitAnimation,
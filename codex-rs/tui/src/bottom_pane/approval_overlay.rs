@@ -1,4 +1,7 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
 use crate::app_event::AppEvent;
@@ -8,7 +11,11 @@ use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
 use crate::bottom_pane::list_selection_view::SelectionItem;
 use crate::bottom_pane::list_selection_view::SelectionViewParams;
+use crate::bottom_pane::popup_consts::standard_popup_hint_line;
+use crate::bottom_pane::textarea::TextArea;
+use crate::bottom_pane::textarea::TextAreaState;
 use crate::diff_render::DiffSummary;
+use crate::diff_render::display_path_for;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
 use crate::key_hint;
@@ -18,6 +25,8 @@ use crate::render::renderable::ColumnRenderable;
 use crate::render::renderable::Renderable;
 use codex_core::features::Feature;
 use codex_core::features::Features;
+use codex_core::parse_command::extract_shell_command;
+use codex_core::protocol::CommandRisk;
 use codex_core::protocol::ElicitationAction;
 use codex_core::protocol::ExecPolicyAmendment;
 use codex_core::protocol::FileChange;
@@ -34,19 +43,32 @@ use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
+use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::Wrap;
 
+/// Lines of the denied sandbox attempt's output shown with a retry request.
+const SANDBOX_DENIAL_OUTPUT_MAX_LINES: usize = 8;
+
 /// Request coming from the agent that needs user approval.
 #[derive(Clone, Debug)]
 pub(crate) enum ApprovalRequest {
     Exec {
         id: String,
+        /// The tool call waiting for this approval.
+        call_id: String,
         command: Vec<String>,
+        cwd: PathBuf,
         reason: Option<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        risk: Option<CommandRisk>,
+        sandbox_denial_output: Option<String>,
+        /// Variables the command sets, with secret-looking values redacted.
+        injected_env: BTreeMap<String, String>,
     },
     ApplyPatch {
         id: String,
+        /// The tool call waiting for this approval.
+        call_id: String,
         reason: Option<String>,
         cwd: PathBuf,
         changes: HashMap<PathBuf, FileChange>,
@@ -56,6 +78,13 @@ pub(crate) enum ApprovalRequest {
         request_id: RequestId,
         message: String,
     },
+    McpSampling {
+        server_name: String,
+        request_id: RequestId,
+        model: String,
+        /// Text of the request's last message.
+        prompt: String,
+    },
 }
 
 /// Modal overlay asking the user to approve or deny one or more requests.
@@ -66,6 +95,9 @@ pub(crate) struct ApprovalOverlay {
     app_event_tx: AppEventSender,
     list: ListSelectionView,
     options: Vec<ApprovalOption>,
+    /// Set while collecting the text for a deny-with-reason or edit decision;
+    /// it replaces the option list until submitted or dismissed.
+    input: Option<DecisionInput>,
     current_complete: bool,
     done: bool,
     features: Features,
@@ -80,6 +112,7 @@ impl ApprovalOverlay {
             app_event_tx: app_event_tx.clone(),
             list: ListSelectionView::new(Default::default(), app_event_tx),
             options: Vec::new(),
+            input: None,
             current_complete: false,
             done: false,
             features,
@@ -96,6 +129,7 @@ impl ApprovalOverlay {
         self.current_request = Some(request.clone());
         let ApprovalRequestState { variant, header } = ApprovalRequestState::from(request);
         self.current_variant = Some(variant.clone());
+        self.input = None;
         self.current_complete = false;
         let (options, params) = Self::build_options(variant, header, &self.features);
         self.options = options;
@@ -123,6 +157,10 @@ impl ApprovalOverlay {
                 elicitation_options(),
                 format!("{server_name} needs your approval."),
             ),
+            ApprovalVariant::McpSampling { server_name, .. } => (
+                sampling_options(),
+                format!("{server_name} wants to use the model."),
+            ),
         };
 
         let header = Box::new(ColumnRenderable::with([
@@ -171,6 +209,10 @@ impl ApprovalOverlay {
                 (ApprovalVariant::Exec { id, command, .. }, ApprovalDecision::Review(decision)) => {
                     self.handle_exec_decision(id, command, decision.clone());
                 }
+                (ApprovalVariant::Exec { command, .. }, ApprovalDecision::Input(kind)) => {
+                    self.input = Some(DecisionInput::new(*kind, command));
+                    return;
+                }
                 (ApprovalVariant::ApplyPatch { id, .. }, ApprovalDecision::Review(decision)) => {
                     self.handle_patch_decision(id, decision.clone());
                 }
@@ -183,6 +225,15 @@ impl ApprovalOverlay {
                 ) => {
                     self.handle_elicitation_decision(server_name, request_id, *decision);
                 }
+                (
+                    ApprovalVariant::McpSampling {
+                        server_name,
+                        request_id,
+                    },
+                    ApprovalDecision::Review(decision),
+                ) => {
+                    self.handle_sampling_decision(server_name, request_id, decision.clone());
+                }
                 _ => {}
             }
         }
@@ -200,6 +251,50 @@ impl ApprovalOverlay {
         }));
     }
 
+    /// Sends the decision typed into the input field. Empty reasons and
+    /// commands that do not parse keep the field open.
+    fn submit_input(&mut self) {
+        let (Some(input), Some(ApprovalVariant::Exec { id, command, .. })) =
+            (self.input.as_ref(), self.current_variant.as_ref())
+        else {
+            return;
+        };
+        let text = input.textarea.text().trim();
+        let decision = match input.kind {
+            DecisionInputKind::DenyReason if text.is_empty() => return,
+            DecisionInputKind::DenyReason => ReviewDecision::DeniedWithReason {
+                reason: text.to_string(),
+            },
+            DecisionInputKind::EditCommand => match edited_command(command, text) {
+                Some(edited) if &edited == command => ReviewDecision::Approved,
+                Some(edited) => ReviewDecision::ApprovedWithEdit { command: edited },
+                None => return,
+            },
+        };
+        let (id, command) = (id.clone(), command.clone());
+        self.input = None;
+        self.handle_exec_decision(&id, &command, decision);
+        self.current_complete = true;
+        self.advance_queue();
+    }
+
+    fn handle_input_key_event(&mut self, key_event: KeyEvent) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => self.input = None,
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => self.submit_input(),
+            other => input.textarea.input(other),
+        }
+    }
+
     fn handle_patch_decision(&self, id: &str, decision: ReviewDecision) {
         self.app_event_tx.send(AppEvent::CodexOp(Op::PatchApproval {
             id: id.to_string(),
@@ -221,6 +316,20 @@ impl ApprovalOverlay {
             }));
     }
 
+    fn handle_sampling_decision(
+        &self,
+        server_name: &str,
+        request_id: &RequestId,
+        decision: ReviewDecision,
+    ) {
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::ResolveMcpSampling {
+                server_name: server_name.to_string(),
+                request_id: request_id.clone(),
+                decision,
+            }));
+    }
+
     fn advance_queue(&mut self) {
         if let Some(next) = self.queue.pop() {
             self.set_current(next);
@@ -263,6 +372,10 @@ impl ApprovalOverlay {
 
 impl BottomPaneView for ApprovalOverlay {
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.input.is_some() {
+            self.handle_input_key_event(key_event);
+            return;
+        }
         if self.try_handle_shortcut(&key_event) {
             return;
         }
@@ -296,6 +409,12 @@ impl BottomPaneView for ApprovalOverlay {
                         ElicitationAction::Cancel,
                     );
                 }
+                ApprovalVariant::McpSampling {
+                    server_name,
+                    request_id,
+                } => {
+                    self.handle_sampling_decision(server_name, request_id, ReviewDecision::Denied);
+                }
             }
         }
         self.queue.clear();
@@ -307,6 +426,17 @@ impl BottomPaneView for ApprovalOverlay {
         self.done
     }
 
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        let Some(input) = self.input.as_mut() else {
+            return false;
+        };
+        if pasted.is_empty() {
+            return false;
+        }
+        input.textarea.insert_str(&pasted);
+        true
+    }
+
     fn try_consume_approval_request(
         &mut self,
         request: ApprovalRequest,
@@ -314,19 +444,74 @@ impl BottomPaneView for ApprovalOverlay {
         self.enqueue_request(request);
         None
     }
+
+    fn is_approval_request(&self) -> bool {
+        !self.done
+    }
+
+    fn approval_call_id(&self) -> Option<&str> {
+        if self.done {
+            return None;
+        }
+        match self.current_request.as_ref()? {
+            ApprovalRequest::Exec { call_id, .. } | ApprovalRequest::ApplyPatch { call_id, .. } => {
+                Some(call_id.as_str())
+            }
+            ApprovalRequest::McpElicitation { .. } | ApprovalRequest::McpSampling { .. } => None,
+        }
+    }
+
+    fn dismiss_current_request(&mut self) {
+        if self.done {
+            return;
+        }
+        // Elicitations and sampling requests belong to an MCP server rather
+        // than a tool call, so tell the server instead of leaving it waiting.
+        if !self.current_complete {
+            match self.current_variant.as_ref() {
+                Some(ApprovalVariant::McpElicitation {
+                    server_name,
+                    request_id,
+                }) => {
+                    self.handle_elicitation_decision(
+                        server_name,
+                        request_id,
+                        ElicitationAction::Cancel,
+                    );
+                }
+                Some(ApprovalVariant::McpSampling {
+                    server_name,
+                    request_id,
+                }) => {
+                    self.handle_sampling_decision(server_name, request_id, ReviewDecision::Denied);
+                }
+                _ => {}
+            }
+        }
+        self.advance_queue();
+    }
 }
 
 impl Renderable for ApprovalOverlay {
     fn desired_height(&self, width: u16) -> u16 {
-        self.list.desired_height(width)
+        match &self.input {
+            Some(input) => input.desired_height(width),
+            None => self.list.desired_height(width),
+        }
     }
 
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.list.render(area, buf);
+        match &self.input {
+            Some(input) => input.render(area, buf),
+            None => self.list.render(area, buf),
+        }
     }
 
     fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
-        self.list.cursor_pos(area)
+        match &self.input {
+            Some(input) => input.cursor_pos(area),
+            None => self.list.cursor_pos(area),
+        }
     }
 }
 
@@ -340,21 +525,42 @@ impl From<ApprovalRequest> for ApprovalRequestState {
         match value {
             ApprovalRequest::Exec {
                 id,
+                call_id: _,
                 command,
+                cwd,
                 reason,
                 proposed_execpolicy_amendment,
+                risk,
+                sandbox_denial_output,
+                injected_env,
             } => {
                 let mut header: Vec<Line<'static>> = Vec::new();
                 if let Some(reason) = reason {
                     header.push(Line::from(vec!["Reason: ".into(), reason.italic()]));
                     header.push(Line::from(""));
                 }
-                let full_cmd = strip_bash_lc_and_escape(&command);
-                let mut full_cmd_lines = highlight_bash_to_lines(&full_cmd);
-                if let Some(first) = full_cmd_lines.first_mut() {
-                    first.spans.insert(0, Span::from("$ "));
+                match risk.filter(|risk| !risk.commands.is_empty()) {
+                    Some(risk) => header.extend(command_risk_lines(&risk, &cwd)),
+                    None => {
+                        let full_cmd = strip_bash_lc_and_escape(&command);
+                        let mut full_cmd_lines = highlight_bash_to_lines(&full_cmd);
+                        if let Some(first) = full_cmd_lines.first_mut() {
+                            first.spans.insert(0, Span::from("$ "));
+                        }
+                        header.extend(full_cmd_lines);
+                    }
+                }
+                if !injected_env.is_empty() {
+                    header.push(Line::from(""));
+                    header.push(Line::from("Environment:".dim()));
+                    header.extend(injected_env.into_iter().map(|(name, value)| {
+                        Line::from(vec!["  ".into(), name.bold(), "=".dim(), value.into()])
+                    }));
+                }
+                if let Some(output) = sandbox_denial_output {
+                    header.push(Line::from(""));
+                    header.extend(sandbox_denial_lines(&output));
                 }
-                header.extend(full_cmd_lines);
                 Self {
                     variant: ApprovalVariant::Exec {
                         id,
@@ -366,6 +572,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
             }
             ApprovalRequest::ApplyPatch {
                 id,
+                call_id: _,
                 reason,
                 cwd,
                 changes,
@@ -405,6 +612,27 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     header: Box::new(header),
                 }
             }
+            ApprovalRequest::McpSampling {
+                server_name,
+                request_id,
+                model,
+                prompt,
+            } => {
+                let header = Paragraph::new(vec![
+                    Line::from(vec!["Server: ".into(), server_name.clone().bold()]),
+                    Line::from(vec!["Model: ".into(), model.bold()]),
+                    Line::from(""),
+                    Line::from(prompt),
+                ])
+                .wrap(Wrap { trim: false });
+                Self {
+                    variant: ApprovalVariant::McpSampling {
+                        server_name,
+                        request_id,
+                    },
+                    header: Box::new(header),
+                }
+            }
         }
     }
 }
@@ -423,14 +651,232 @@ enum ApprovalVariant {
         server_name: String,
         request_id: RequestId,
     },
+    McpSampling {
+        server_name: String,
+        request_id: RequestId,
+    },
 }
 
 #[derive(Clone)]
 enum ApprovalDecision {
     Review(ReviewDecision),
+    /// Asks for text before the decision can be made.
+    Input(DecisionInputKind),
     McpElicitation(ElicitationAction),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DecisionInputKind {
+    /// A note sent to Codex along with the denial.
+    DenyReason,
+    /// The command to run instead of the proposed one.
+    EditCommand,
+}
+
+/// Text field shown in place of the option list for decisions that need
+/// text from the user.
+struct DecisionInput {
+    kind: DecisionInputKind,
+    textarea: TextArea,
+    textarea_state: RefCell<TextAreaState>,
+}
+
+impl DecisionInput {
+    fn new(kind: DecisionInputKind, command: &[String]) -> Self {
+        let mut textarea = TextArea::new();
+        if kind == DecisionInputKind::EditCommand {
+            textarea.insert_str(&strip_bash_lc_and_escape(command));
+        }
+        Self {
+            kind,
+            textarea,
+            textarea_state: RefCell::new(TextAreaState::default()),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self.kind {
+            DecisionInputKind::DenyReason => "Why shouldn't Codex run this command?",
+            DecisionInputKind::EditCommand => "Edit the command, then run it",
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self.kind {
+            DecisionInputKind::DenyReason => "Tell Codex why, or what to do instead",
+            DecisionInputKind::EditCommand => "Command to run",
+        }
+    }
+
+    fn input_height(&self, width: u16) -> u16 {
+        self.textarea
+            .desired_height(width.saturating_sub(2))
+            .clamp(1, 8)
+    }
+
+    fn textarea_rect(&self, area: Rect) -> Rect {
+        Rect {
+            x: area.x.saturating_add(2),
+            y: area.y.saturating_add(2),
+            width: area.width.saturating_sub(2),
+            height: self
+                .input_height(area.width)
+                .min(area.height.saturating_sub(2)),
+        }
+    }
+}
+
+impl Renderable for DecisionInput {
+    fn desired_height(&self, width: u16) -> u16 {
+        // Title, blank, input, blank, hint.
+        self.input_height(width) + 4
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width <= 2 {
+            return;
+        }
+        Line::from(vec!["  ".into(), self.title().bold()]).render(area, buf);
+
+        let textarea_rect = self.textarea_rect(area);
+        for row in 0..textarea_rect.height {
+            "▌ ".cyan().render(
+                Rect {
+                    x: area.x,
+                    y: textarea_rect.y + row,
+                    width: 2,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+        let mut state = self.textarea_state.borrow_mut();
+        StatefulWidgetRef::render_ref(&(&self.textarea), textarea_rect, buf, &mut state);
+        if self.textarea.text().is_empty() {
+            Line::from(self.placeholder().dim()).render(textarea_rect, buf);
+        }
+
+        let hint_y = textarea_rect.bottom().saturating_add(1);
+        if hint_y < area.bottom() {
+            Line::from_iter(
+                std::iter::once(Span::from("  ")).chain(standard_popup_hint_line().spans),
+            )
+            .render(
+                Rect {
+                    x: area.x,
+                    y: hint_y,
+                    width: area.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+    }
+
+    fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        let textarea_rect = self.textarea_rect(area);
+        if textarea_rect.height == 0 || textarea_rect.width == 0 {
+            return None;
+        }
+        let state = *self.textarea_state.borrow();
+        self.textarea.cursor_pos_with_state(textarea_rect, state)
+    }
+}
+
+/// The command to run after the user edited its text. Shell wrappers keep
+/// their shell and only have their script replaced; anything else is split
+/// like a shell would.
+fn edited_command(original: &[String], text: &str) -> Option<Vec<String>> {
+    if text.is_empty() {
+        return None;
+    }
+    if let Some((_, script)) = extract_shell_command(original)
+        && original.last().map(String::as_str) == Some(script)
+    {
+        let mut edited = original.to_vec();
+        if let Some(last) = edited.last_mut() {
+            *last = text.to_string();
+        }
+        return Some(edited);
+    }
+    shlex::split(text).filter(|words| !words.is_empty())
+}
+
+/// The commands the request runs, one per line with the program in bold and
+/// destructive arguments in red, followed by badges and the paths touched.
+fn command_risk_lines(risk: &CommandRisk, cwd: &Path) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = risk
+        .commands
+        .iter()
+        .map(|words| {
+            let mut spans: Vec<Span<'static>> = vec!["• ".dim()];
+            for (i, word) in words.iter().enumerate() {
+                let shown = shlex::try_quote(word)
+                    .map(|quoted| quoted.into_owned())
+                    .unwrap_or_else(|_| word.clone());
+                if i > 0 {
+                    spans.push(" ".into());
+                }
+                spans.push(if i == 0 {
+                    shown.bold()
+                } else if risk.destructive_args.contains(word) {
+                    shown.red().bold()
+                } else {
+                    shown.into()
+                });
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let mut badges: Vec<Span<'static>> = Vec::new();
+    if risk.is_destructive() {
+        badges.push("[destructive]".red().bold());
+    }
+    if risk.network {
+        if !badges.is_empty() {
+            badges.push(" ".into());
+        }
+        badges.push("[network]".cyan().bold());
+    }
+    if !badges.is_empty() || !risk.paths.is_empty() {
+        lines.push(Line::from(""));
+    }
+    if !badges.is_empty() {
+        lines.push(Line::from_iter(
+            std::iter::once(Span::from("Risk: ")).chain(badges),
+        ));
+    }
+    if !risk.paths.is_empty() {
+        let paths = risk
+            .paths
+            .iter()
+            .map(|path| match display_path_for(path, cwd) {
+                shown if shown.is_empty() => ".".to_string(),
+                shown => shown,
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(Line::from(vec!["Paths: ".into(), paths.into()]));
+    }
+    lines
+}
+
+/// What the sandbox printed when it stopped the previous attempt, which is
+/// usually why the command is being retried without it.
+fn sandbox_denial_lines(output: &str) -> Vec<Line<'static>> {
+    let output_lines: Vec<&str> = output.lines().collect();
+    let tail = &output_lines[output_lines
+        .len()
+        .saturating_sub(SANDBOX_DENIAL_OUTPUT_MAX_LINES)..];
+    std::iter::once(Line::from("Output of the sandboxed attempt:"))
+        .chain(
+            tail.iter()
+                .map(|line| Line::from(vec!["  ".into(), line.to_string().dim()])),
+        )
+        .collect()
+}
+
 #[derive(Clone)]
 struct ApprovalOption {
     label: String,
@@ -451,12 +897,20 @@ fn exec_options(
     proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     features: &Features,
 ) -> Vec<ApprovalOption> {
-    vec![ApprovalOption {
-        label: "Yes, proceed".to_string(),
-        decision: ApprovalDecision::Review(ReviewDecision::Approved),
-        display_shortcut: None,
-        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
-    }]
+    vec![
+        ApprovalOption {
+            label: "Yes, proceed".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::Approved),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+        },
+        ApprovalOption {
+            label: "Yes, and don't ask again for this command".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::ApprovedForSession),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
+        },
+    ]
     .into_iter()
     .chain(
         proposed_execpolicy_amendment
@@ -481,12 +935,32 @@ fn exec_options(
                 })
             }),
     )
-    .chain([ApprovalOption {
-        label: "No, and tell Codex what to do differently".to_string(),
-        decision: ApprovalDecision::Review(ReviewDecision::Abort),
-        display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
-        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
-    }])
+    .chain([
+        ApprovalOption {
+            label: "Edit the command, then run it".to_string(),
+            decision: ApprovalDecision::Input(DecisionInputKind::EditCommand),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('e'))],
+        },
+        ApprovalOption {
+            label: "No, but continue without running it".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::Denied),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('d'))],
+        },
+        ApprovalOption {
+            label: "No, and tell Codex why".to_string(),
+            decision: ApprovalDecision::Input(DecisionInputKind::DenyReason),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('r'))],
+        },
+        ApprovalOption {
+            label: "No, and tell Codex what to do differently".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::Abort),
+            display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+        },
+    ])
     .collect()
 }
 
@@ -536,6 +1010,29 @@ fn elicitation_options() -> Vec<ApprovalOption> {
     ]
 }
 
+fn sampling_options() -> Vec<ApprovalOption> {
+    vec![
+        ApprovalOption {
+            label: "Yes, run this request".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::Approved),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+        },
+        ApprovalOption {
+            label: "Yes, and don't ask again for this server".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::ApprovedForSession),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
+        },
+        ApprovalOption {
+            label: "No, decline this request".to_string(),
+            decision: ApprovalDecision::Review(ReviewDecision::Denied),
+            display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+        },
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -546,9 +1043,14 @@ mod tests {
     fn make_exec_request() -> ApprovalRequest {
         ApprovalRequest::Exec {
             id: "test".to_string(),
+            call_id: "call-1".to_string(),
             command: vec!["echo".to_string(), "hi".to_string()],
+            cwd: PathBuf::from("/repo"),
             reason: Some("reason".to_string()),
             proposed_execpolicy_amendment: None,
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: BTreeMap::new(),
         }
     }
 
@@ -563,6 +1065,28 @@ mod tests {
         assert!(view.is_complete());
     }
 
+    #[test]
+    fn dismissing_a_request_sends_no_decision_and_shows_the_next() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx, Features::with_defaults());
+        view.enqueue_request(make_exec_request());
+
+        assert_eq!(view.approval_call_id(), Some("call-1"));
+        view.dismiss_current_request();
+        assert!(
+            rx.try_recv().is_err(),
+            "dismissing must not answer the request"
+        );
+        assert!(view.is_approval_request());
+        assert!(!view.is_complete());
+
+        view.dismiss_current_request();
+        assert!(view.is_complete());
+        assert!(!view.is_approval_request());
+        assert_eq!(view.approval_call_id(), None);
+    }
+
     #[test]
     fn shortcut_triggers_selection() {
         let (tx, mut rx) = unbounded_channel::<AppEvent>();
@@ -581,6 +1105,45 @@ mod tests {
         assert!(saw_op, "expected approval decision to emit an op");
     }
 
+    #[test]
+    fn sampling_request_resolves_with_the_chosen_decision() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::McpSampling {
+                server_name: "docs".to_string(),
+                request_id: RequestId::Integer(7),
+                model: "gpt-5.1".to_string(),
+                prompt: "summarize this".to_string(),
+            },
+            tx,
+            Features::with_defaults(),
+        );
+        assert_eq!(view.approval_call_id(), None);
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+
+        let mut decisions = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ResolveMcpSampling {
+                server_name,
+                request_id,
+                decision,
+            }) = ev
+            {
+                decisions.push((server_name, request_id, decision));
+            }
+        }
+        assert_eq!(
+            decisions,
+            vec![(
+                "docs".to_string(),
+                RequestId::Integer(7),
+                ReviewDecision::ApprovedForSession
+            )]
+        );
+        assert!(view.is_complete());
+    }
+
     #[test]
     fn exec_prefix_option_emits_execpolicy_amendment() {
         let (tx, mut rx) = unbounded_channel::<AppEvent>();
@@ -588,11 +1151,16 @@ mod tests {
         let mut view = ApprovalOverlay::new(
             ApprovalRequest::Exec {
                 id: "test".to_string(),
+                call_id: "call-1".to_string(),
                 command: vec!["echo".to_string()],
+                cwd: PathBuf::from("/repo"),
                 reason: None,
                 proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
                     "echo".to_string(),
                 ])),
+                risk: None,
+                sandbox_denial_output: None,
+                injected_env: BTreeMap::new(),
             },
             tx,
             Features::with_defaults(),
@@ -626,11 +1194,16 @@ mod tests {
         let mut view = ApprovalOverlay::new(
             ApprovalRequest::Exec {
                 id: "test".to_string(),
+                call_id: "call-1".to_string(),
                 command: vec!["echo".to_string()],
+                cwd: PathBuf::from("/repo"),
                 reason: None,
                 proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
                     "echo".to_string(),
                 ])),
+                risk: None,
+                sandbox_denial_output: None,
+                injected_env: BTreeMap::new(),
             },
             tx,
             {
@@ -639,7 +1212,7 @@ mod tests {
                 features
             },
         );
-        assert_eq!(view.options.len(), 2);
+        assert_eq!(view.options.len(), 6);
         view.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
         assert!(!view.is_complete());
         assert!(rx.try_recv().is_err());
@@ -652,9 +1225,14 @@ mod tests {
         let command = vec!["echo".into(), "hello".into(), "world".into()];
         let exec_request = ApprovalRequest::Exec {
             id: "test".into(),
+            call_id: "call-1".into(),
             command,
+            cwd: PathBuf::from("/repo"),
             reason: None,
             proposed_execpolicy_amendment: None,
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: BTreeMap::new(),
         };
 
         let view = ApprovalOverlay::new(exec_request, tx, Features::with_defaults());
@@ -676,6 +1254,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn header_lists_injected_env() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let exec_request = ApprovalRequest::Exec {
+            id: "test".into(),
+            call_id: "call-1".into(),
+            command: vec!["ls".into()],
+            cwd: PathBuf::from("/repo"),
+            reason: None,
+            proposed_execpolicy_amendment: None,
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: BTreeMap::from([("LD_PRELOAD".to_string(), "/tmp/x.so".to_string())]),
+        };
+
+        let view = ApprovalOverlay::new(exec_request, tx, Features::with_defaults());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, view.desired_height(80)));
+        view.render(Rect::new(0, 0, 80, view.desired_height(80)), &mut buf);
+
+        let rendered: Vec<String> = (0..buf.area.height)
+            .map(|row| {
+                (0..buf.area.width)
+                    .map(|col| buf[(col, row)].symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(
+            rendered
+                .iter()
+                .any(|line| line.contains("LD_PRELOAD=/tmp/x.so")),
+            "expected header to list the injected env, got {rendered:?}"
+        );
+    }
+
     #[test]
     fn exec_history_cell_wraps_with_two_space_indent() {
         let command = vec![
@@ -724,4 +1337,186 @@ mod tests {
         }
         assert_eq!(decision, Some(ReviewDecision::Approved));
     }
+
+    fn render_lines(view: &ApprovalOverlay, width: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, view.desired_height(width));
+        let mut buf = Buffer::empty(area);
+        view.render(area, &mut buf);
+        (0..area.height)
+            .map(|row| {
+                (0..area.width)
+                    .map(|col| buf[(col, row)].symbol().to_string())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn next_exec_decision(
+        rx: &mut tokio::sync::mpsc::UnboundedReceiver<AppEvent>,
+    ) -> Option<ReviewDecision> {
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision, .. }) = ev {
+                return Some(decision);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn header_shows_command_structure_and_risk() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let command: Vec<String> = vec![
+            "bash".into(),
+            "-lc".into(),
+            "rm -rf build && curl -O https://example.com/a.tgz".into(),
+        ];
+        let request = ApprovalRequest::Exec {
+            id: "test".into(),
+            call_id: "call-1".into(),
+            command,
+            cwd: PathBuf::from("/repo"),
+            reason: Some("command failed; retry without sandbox?".into()),
+            proposed_execpolicy_amendment: None,
+            risk: Some(CommandRisk {
+                commands: vec![
+                    vec!["rm".into(), "-rf".into(), "build".into()],
+                    vec![
+                        "curl".into(),
+                        "-O".into(),
+                        "https://example.com/a.tgz".into(),
+                    ],
+                ],
+                destructive_args: vec!["-rf".into()],
+                paths: vec![PathBuf::from("/repo/build")],
+                network: true,
+            }),
+            sandbox_denial_output: Some("rm: build: Operation not permitted".into()),
+            injected_env: BTreeMap::new(),
+        };
+
+        let view = ApprovalOverlay::new(request, tx, Features::with_defaults());
+        let rendered = render_lines(&view, 80);
+        for expected in [
+            "  • rm -rf build",
+            "  • curl -O https://example.com/a.tgz",
+            "  Risk: [destructive] [network]",
+            "  Paths: build",
+            "  Output of the sandboxed attempt:",
+            "    rm: build: Operation not permitted",
+        ] {
+            assert!(
+                rendered.iter().any(|line| line == expected),
+                "expected {expected:?} in {rendered:?}"
+            );
+        }
+        assert!(
+            !rendered.iter().any(|line| line.contains("$ rm")),
+            "structured commands replace the raw command: {rendered:?}"
+        );
+    }
+
+    #[test]
+    fn header_falls_back_to_raw_command_without_risk() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let view = ApprovalOverlay::new(make_exec_request(), tx, Features::with_defaults());
+        let rendered = render_lines(&view, 80);
+        assert!(rendered.iter().any(|line| line == "  $ echo hi"));
+        assert!(!rendered.iter().any(|line| line.starts_with("  Risk:")));
+    }
+
+    #[test]
+    fn deny_with_reason_sends_typed_reason() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx, Features::with_defaults());
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+        assert!(!view.is_complete());
+
+        // An empty reason keeps the field open.
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!view.is_complete());
+
+        for ch in "use printf".chars() {
+            view.handle_key_event(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert_eq!(
+            next_exec_decision(&mut rx),
+            Some(ReviewDecision::DeniedWithReason {
+                reason: "use printf".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn edit_keeps_shell_wrapper_and_sends_edited_command() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let request = ApprovalRequest::Exec {
+            id: "test".into(),
+            call_id: "call-1".into(),
+            command: vec!["bash".into(), "-lc".into(), "ls".into()],
+            cwd: PathBuf::from("/repo"),
+            reason: None,
+            proposed_execpolicy_amendment: None,
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: BTreeMap::new(),
+        };
+        let mut view = ApprovalOverlay::new(request, tx, Features::with_defaults());
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE));
+        assert_eq!(
+            view.input.as_ref().map(|input| input.textarea.text()),
+            Some("ls")
+        );
+        assert!(
+            render_lines(&view, 60)
+                .iter()
+                .any(|line| line == "  Edit the command, then run it")
+        );
+
+        for ch in " -la".chars() {
+            view.handle_key_event(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            next_exec_decision(&mut rx),
+            Some(ReviewDecision::ApprovedWithEdit {
+                command: vec!["bash".into(), "-lc".into(), "ls -la".into()]
+            })
+        );
+    }
+
+    #[test]
+    fn esc_in_input_returns_to_options() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx, Features::with_defaults());
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE));
+        view.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(view.input.is_none());
+        assert!(!view.is_complete());
+        assert_eq!(next_exec_decision(&mut rx), None);
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert_eq!(
+            next_exec_decision(&mut rx),
+            Some(ReviewDecision::ApprovedForSession)
+        );
+    }
+
+    #[test]
+    fn edited_command_splits_plain_commands() {
+        let original: Vec<String> = vec!["echo".into(), "hi".into()];
+        assert_eq!(
+            edited_command(&original, "echo 'hello world'"),
+            Some(vec!["echo".to_string(), "hello world".to_string()])
+        );
+        assert_eq!(edited_command(&original, "echo 'unterminated"), None);
+    }
 }
@@ -21,6 +21,20 @@ pub(crate) trait BottomPaneView: Renderable {
         CancellationEvent::NotHandled
     }
 
+    /// Return `true` if the view asks the user to approve a tool call.
+    fn is_approval_request(&self) -> bool {
+        false
+    }
+
+    /// The tool call the approval currently shown belongs to, if any.
+    fn approval_call_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Drop the request currently shown without answering it, because the
+    /// tool call it belongs to was cancelled.
+    fn dismiss_current_request(&mut self) {}
+
     /// Optional paste handler. Return true if the view modified its state and
     /// needs a redraw.
     fn handle_paste(&mut self, _pasted: String) -> bool {
@@ -122,6 +122,7 @@ use crate::render::RectExt;
 use crate::render::renderable::Renderable;
 use crate::slash_command::SlashCommand;
 use crate::style::user_message_style;
+use crate::token_usage::ContextUsage;
 use codex_common::fuzzy_match::fuzzy_match;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
@@ -244,8 +245,7 @@ pub(crate) struct ChatComposer {
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
     footer_flash: Option<FooterFlash>,
-    context_window_percent: Option<i64>,
-    context_window_used_tokens: Option<i64>,
+    context_usage: Option<ContextUsage>,
     skills: Option<Vec<SkillMetadata>>,
     dismissed_skill_popup_token: Option<String>,
     /// When enabled, `Enter` submits immediately and `Tab` requests queuing behavior.
@@ -330,8 +330,7 @@ impl ChatComposer {
             footer_mode: FooterMode::ShortcutSummary,
             footer_hint_override: None,
             footer_flash: None,
-            context_window_percent: None,
-            context_window_used_tokens: None,
+            context_usage: None,
             skills: None,
             dismissed_skill_popup_token: None,
             steer_enabled: false,
@@ -624,7 +623,7 @@ impl ChatComposer {
         self.footer_hint_override = items;
     }
 
-    #[cfg(test)]
+    /// Replace the footer hint with `line` until `duration` has passed.
     pub(crate) fn show_footer_flash(&mut self, line: Line<'static>, duration: Duration) {
         let expires_at = Instant::now()
             .checked_add(duration)
@@ -638,6 +637,21 @@ impl ChatComposer {
             .is_some_and(|flash| Instant::now() < flash.expires_at)
     }
 
+    #[cfg(test)]
+    pub(crate) fn footer_flash_text(&self) -> Option<String> {
+        self.footer_flash_visible()
+            .then(|| self.footer_flash.as_ref())
+            .flatten()
+            .map(|flash| {
+                flash
+                    .line
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+    }
+
     /// Replace the entire composer content with `text` and reset cursor.
     /// This clears any pending paste payloads.
     pub(crate) fn set_text_content(
@@ -1903,7 +1917,13 @@ impl ChatComposer {
                     self.collaboration_modes_enabled,
                     self.personality_command_enabled,
                 )
-                && cmd == SlashCommand::Review
+                && matches!(
+                    cmd,
+                    SlashCommand::Review
+                        | SlashCommand::Ps
+                        | SlashCommand::Export
+                        | SlashCommand::Model
+                )
             {
                 self.textarea.set_text_clearing_elements("");
                 return Some(InputResult::CommandWithArgs(cmd, rest.to_string()));
@@ -2225,8 +2245,7 @@ impl ChatComposer {
             quit_shortcut_key: self.quit_shortcut_key,
             steer_enabled: self.steer_enabled,
             collaboration_modes_enabled: self.collaboration_modes_enabled,
-            context_window_percent: self.context_window_percent,
-            context_window_used_tokens: self.context_window_used_tokens,
+            context_usage: self.context_usage,
         }
     }
 
@@ -2497,13 +2516,8 @@ impl ChatComposer {
         self.is_task_running = running;
     }
 
-    pub(crate) fn set_context_window(&mut self, percent: Option<i64>, used_tokens: Option<i64>) {
-        if self.context_window_percent == percent && self.context_window_used_tokens == used_tokens
-        {
-            return;
-        }
-        self.context_window_percent = percent;
-        self.context_window_used_tokens = used_tokens;
+    pub(crate) fn set_context_usage(&mut self, usage: Option<ContextUsage>) {
+        self.context_usage = usage;
     }
 
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
@@ -3955,6 +3969,37 @@ mod tests {
         assert!(composer.textarea.is_empty(), "composer should be cleared");
     }
 
+    #[test]
+    fn slash_ps_with_query_dispatches_command_with_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        type_chars_humanlike(&mut composer, &['/', 'p', 's', ' ', 'r', 'e', 'p', 'l']);
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match result {
+            InputResult::CommandWithArgs(cmd, args) => {
+                assert_eq!(cmd, SlashCommand::Ps);
+                assert_eq!(args, "repl");
+            }
+            _ => panic!("expected '/ps repl' to dispatch with args"),
+        }
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn extract_args_supports_quoted_paths_single_arg() {
         let args = extract_positional_args_for_prompt_line(
@@ -14,6 +14,8 @@ use crate::key_hint;
 use crate::key_hint::KeyBinding;
 use crate::render::line_utils::prefix_lines;
 use crate::status::format_tokens_compact;
+use crate::token_usage::ContextLevel;
+use crate::token_usage::ContextUsage;
 use crate::ui_consts::FOOTER_INDENT_COLS;
 use crossterm::event::KeyCode;
 use ratatui::buffer::Buffer;
@@ -42,8 +44,7 @@ pub(crate) struct FooterProps {
     ///
     /// This is rendered when `mode` is `FooterMode::QuitShortcutReminder`.
     pub(crate) quit_shortcut_key: KeyBinding,
-    pub(crate) context_window_percent: Option<i64>,
-    pub(crate) context_window_used_tokens: Option<i64>,
+    pub(crate) context_usage: Option<ContextUsage>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -203,10 +204,7 @@ fn footer_lines(props: FooterProps) -> Vec<Line<'static>> {
             vec![quit_shortcut_reminder_line(props.quit_shortcut_key)]
         }
         FooterMode::ShortcutSummary => {
-            let mut line = context_window_line(
-                props.context_window_percent,
-                props.context_window_used_tokens,
-            );
+            let mut line = context_usage_line(props.context_usage);
             line.push_span(" · ".dim());
             line.extend(vec![
                 key_hint::plain(KeyCode::Char('?')).into(),
@@ -230,10 +228,7 @@ fn footer_lines(props: FooterProps) -> Vec<Line<'static>> {
         }
         FooterMode::EscHint => vec![esc_hint_line(props.esc_backtrack_hint)],
         FooterMode::ContextOnly => {
-            let mut line = context_window_line(
-                props.context_window_percent,
-                props.context_window_used_tokens,
-            );
+            let mut line = context_usage_line(props.context_usage);
             if props.is_task_running && props.steer_enabled {
                 line.push_span(" · ".dim());
                 line.push_span(key_hint::plain(KeyCode::Tab));
@@ -396,18 +391,47 @@ fn build_columns(entries: Vec<Line<'static>>) -> Vec<Line<'static>> {
         .collect()
 }
 
-fn context_window_line(percent: Option<i64>, used_tokens: Option<i64>) -> Line<'static> {
-    if let Some(percent) = percent {
-        let percent = percent.clamp(0, 100);
-        return Line::from(vec![Span::from(format!("{percent}% context left")).dim()]);
-    }
+/// Remaining context, then this turn's and the conversation's tokens. The
+/// lead segment changes color as the conversation nears auto-compaction, and
+/// estimated numbers are prefixed with `~`.
+fn context_usage_line(usage: Option<ContextUsage>) -> Line<'static> {
+    let Some(usage) = usage else {
+        return Line::from(vec![Span::from("100% context left").dim()]);
+    };
 
-    if let Some(tokens) = used_tokens {
-        let used_fmt = format_tokens_compact(tokens);
-        return Line::from(vec![Span::from(format!("{used_fmt} used")).dim()]);
+    let approx = if usage.estimated { "~" } else { "" };
+    let lead = match usage.percent_left {
+        Some(percent) => format!("{approx}{}% context left", percent.clamp(0, 100)),
+        None => format!("{approx}{} used", format_tokens_compact(usage.total_tokens)),
+    };
+    let lead = match usage.level {
+        ContextLevel::Normal => Span::from(lead).dim(),
+        ContextLevel::NearCompaction => Span::from(lead).magenta(),
+        ContextLevel::CompactionImminent => Span::from(lead).red(),
+    };
+    let mut spans = vec![lead];
+    if let Some(turn_tokens) = usage.turn_tokens.filter(|tokens| *tokens > 0) {
+        spans.push(
+            format!(
+                " · {approx}{} this turn",
+                format_tokens_compact(turn_tokens)
+            )
+            .dim(),
+        );
     }
-
-    Line::from(vec![Span::from("100% context left").dim()])
+    if usage.percent_left.is_some() {
+        spans.push(
+            format!(
+                " · {approx}{} total",
+                format_tokens_compact(usage.total_tokens)
+            )
+            .dim(),
+        );
+    }
+    if usage.estimated {
+        spans.push(" (estimated)".dim());
+    }
+    Line::from(spans)
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -662,8 +686,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -677,8 +700,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -692,8 +714,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: true,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -707,8 +728,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -722,8 +742,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -737,8 +756,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -752,8 +770,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -767,8 +784,13 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: Some(72),
-                context_window_used_tokens: None,
+                context_usage: Some(ContextUsage {
+                    percent_left: Some(72),
+                    total_tokens: 45_210,
+                    turn_tokens: Some(3_400),
+                    estimated: false,
+                    level: ContextLevel::Normal,
+                }),
             },
         );
 
@@ -782,8 +804,10 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: Some(123_456),
+                context_usage: Some(ContextUsage {
+                    total_tokens: 123_456,
+                    ..ContextUsage::default()
+                }),
             },
         );
 
@@ -797,8 +821,7 @@ mod tests {
                 steer_enabled: false,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -812,8 +835,7 @@ mod tests {
                 steer_enabled: true,
                 collaboration_modes_enabled: false,
                 quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-                context_window_percent: None,
-                context_window_used_tokens: None,
+                context_usage: None,
             },
         );
 
@@ -825,8 +847,7 @@ mod tests {
             steer_enabled: false,
             collaboration_modes_enabled: true,
             quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-            context_window_percent: None,
-            context_window_used_tokens: None,
+            context_usage: None,
         };
 
         snapshot_footer_with_indicator(
@@ -851,8 +872,7 @@ mod tests {
             steer_enabled: false,
             collaboration_modes_enabled: true,
             quit_shortcut_key: key_hint::ctrl(KeyCode::Char('c')),
-            context_window_percent: None,
-            context_window_used_tokens: None,
+            context_usage: None,
         };
 
         snapshot_footer_with_indicator(
@@ -862,4 +882,35 @@ mod tests {
             Some(CollaborationModeIndicator::Plan),
         );
     }
+
+    #[test]
+    fn context_usage_line_marks_estimates_and_compaction() {
+        let text = |line: &Line<'_>| -> String {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+
+        let line = context_usage_line(Some(ContextUsage {
+            percent_left: Some(12),
+            total_tokens: 180_000,
+            turn_tokens: Some(9_500),
+            estimated: true,
+            level: ContextLevel::CompactionImminent,
+        }));
+        assert_eq!(
+            text(&line),
+            "~12% context left · ~9.5K this turn · ~180K total (estimated)"
+        );
+        assert_eq!(line.spans[0].style.fg, Some(ratatui::style::Color::Red));
+
+        let line = context_usage_line(Some(ContextUsage {
+            percent_left: Some(30),
+            level: ContextLevel::NearCompaction,
+            ..ContextUsage::default()
+        }));
+        assert_eq!(text(&line), "30% context left · 0 total");
+        assert_eq!(line.spans[0].style.fg, Some(ratatui::style::Color::Magenta));
+    }
 }
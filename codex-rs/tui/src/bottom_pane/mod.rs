@@ -23,6 +23,7 @@ use crate::key_hint::KeyBinding;
 use crate::render::renderable::FlexRenderable;
 use crate::render::renderable::Renderable;
 use crate::render::renderable::RenderableItem;
+use crate::token_usage::ContextUsage;
 use crate::tui::FrameRequester;
 use bottom_pane_view::BottomPaneView;
 use codex_core::features::Features;
@@ -34,6 +35,7 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::text::Line;
 use std::time::Duration;
 
 mod approval_overlay;
@@ -75,7 +77,10 @@ mod scroll_state;
 mod selection_popup_common;
 mod textarea;
 mod unified_exec_footer;
+mod unified_exec_panel;
 pub(crate) use feedback_view::FeedbackNoteView;
+pub(crate) use unified_exec_panel::UnifiedExecPanel;
+pub(crate) use unified_exec_panel::UnifiedExecSessions;
 
 /// How long the "press again to quit" hint stays visible.
 ///
@@ -141,8 +146,7 @@ pub(crate) struct BottomPane {
     unified_exec_footer: UnifiedExecFooter,
     /// Queued user messages to show above the composer while a turn is running.
     queued_user_messages: QueuedUserMessages,
-    context_window_percent: Option<i64>,
-    context_window_used_tokens: Option<i64>,
+    context_usage: Option<ContextUsage>,
 }
 
 pub(crate) struct BottomPaneParams {
@@ -189,8 +193,7 @@ impl BottomPane {
             queued_user_messages: QueuedUserMessages::new(),
             esc_backtrack_hint: false,
             animations_enabled,
-            context_window_percent: None,
-            context_window_used_tokens: None,
+            context_usage: None,
         }
     }
 
@@ -231,12 +234,19 @@ impl BottomPane {
 
     #[cfg(test)]
     pub(crate) fn context_window_percent(&self) -> Option<i64> {
-        self.context_window_percent
+        self.context_usage.and_then(|usage| usage.percent_left)
     }
 
     #[cfg(test)]
     pub(crate) fn context_window_used_tokens(&self) -> Option<i64> {
-        self.context_window_used_tokens
+        self.context_usage
+            .filter(|usage| usage.percent_left.is_none())
+            .map(|usage| usage.total_tokens)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn context_usage(&self) -> Option<ContextUsage> {
+        self.context_usage
     }
 
     fn active_view(&self) -> Option<&dyn BottomPaneView> {
@@ -426,27 +436,45 @@ impl BottomPane {
 
         self.composer
             .show_quit_shortcut_hint(key, self.has_input_focus);
+        self.schedule_frame_after(QUIT_SHORTCUT_TIMEOUT);
+        self.request_redraw();
+    }
+
+    /// Clear the "press again to quit" hint immediately.
+    pub(crate) fn clear_quit_shortcut_hint(&mut self) {
+        self.composer.clear_quit_shortcut_hint(self.has_input_focus);
+        self.request_redraw();
+    }
+
+    /// Show a transient status message in place of the footer hint.
+    pub(crate) fn show_footer_flash(&mut self, line: Line<'static>, duration: Duration) {
+        self.composer.show_footer_flash(line, duration);
+        self.schedule_frame_after(duration);
+        self.request_redraw();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn footer_flash_text(&self) -> Option<String> {
+        self.composer.footer_flash_text()
+    }
+
+    /// Redraw once `delay` has passed so transient footer content expires
+    /// even if no other event triggers a draw.
+    fn schedule_frame_after(&self, delay: Duration) {
         let frame_requester = self.frame_requester.clone();
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
-                tokio::time::sleep(QUIT_SHORTCUT_TIMEOUT).await;
+                tokio::time::sleep(delay).await;
                 frame_requester.schedule_frame();
             });
         } else {
             // In tests (and other non-Tokio contexts), fall back to a thread so
             // the hint can still expire without requiring an explicit draw.
             std::thread::spawn(move || {
-                std::thread::sleep(QUIT_SHORTCUT_TIMEOUT);
+                std::thread::sleep(delay);
                 frame_requester.schedule_frame();
             });
         }
-        self.request_redraw();
-    }
-
-    /// Clear the "press again to quit" hint immediately.
-    pub(crate) fn clear_quit_shortcut_hint(&mut self) {
-        self.composer.clear_quit_shortcut_hint(self.has_input_focus);
-        self.request_redraw();
     }
 
     #[cfg(test)]
@@ -525,16 +553,13 @@ impl BottomPane {
         }
     }
 
-    pub(crate) fn set_context_window(&mut self, percent: Option<i64>, used_tokens: Option<i64>) {
-        if self.context_window_percent == percent && self.context_window_used_tokens == used_tokens
-        {
+    pub(crate) fn set_context_usage(&mut self, usage: Option<ContextUsage>) {
+        if self.context_usage == usage {
             return;
         }
 
-        self.context_window_percent = percent;
-        self.context_window_used_tokens = used_tokens;
-        self.composer
-            .set_context_window(percent, self.context_window_used_tokens);
+        self.context_usage = usage;
+        self.composer.set_context_usage(usage);
         self.request_redraw();
     }
 
@@ -615,6 +640,38 @@ impl BottomPane {
         self.push_view(Box::new(modal));
     }
 
+    /// True when the active view asks the user to approve a tool call.
+    pub(crate) fn approval_request_active(&self) -> bool {
+        self.view_stack
+            .last()
+            .is_some_and(|view| view.is_approval_request())
+    }
+
+    /// The tool call the approval currently shown belongs to.
+    pub(crate) fn current_approval_call_id(&self) -> Option<String> {
+        self.view_stack
+            .last()
+            .and_then(|view| view.approval_call_id())
+            .map(str::to_string)
+    }
+
+    /// Drop the approval request currently shown, without answering it,
+    /// because its tool call was cancelled.
+    pub(crate) fn dismiss_current_approval(&mut self) {
+        let Some(view) = self.view_stack.last_mut() else {
+            return;
+        };
+        if !view.is_approval_request() {
+            return;
+        }
+        view.dismiss_current_request();
+        if view.is_complete() {
+            self.view_stack.pop();
+            self.on_active_view_complete();
+        }
+        self.request_redraw();
+    }
+
     /// Called when the agent requests user input.
     pub fn push_user_input_request(&mut self, request: RequestUserInputEvent) {
         let request = if let Some(view) = self.view_stack.last_mut() {
@@ -792,9 +849,14 @@ mod tests {
     fn exec_request() -> ApprovalRequest {
         ApprovalRequest::Exec {
             id: "1".to_string(),
+            call_id: "call-1".to_string(),
             command: vec!["echo".into(), "ok".into()],
+            cwd: PathBuf::from("/tmp"),
             reason: None,
             proposed_execpolicy_amendment: None,
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: Default::default(),
         }
     }
 
@@ -0,0 +1,561 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use codex_ansi_escape::ansi_escape_line;
+use codex_core::protocol::Op;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::key_hint;
+use crate::live_wrap::take_prefix_by_width;
+use crate::render::renderable::Renderable;
+
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+
+/// Lines of output kept per background terminal.
+const MAX_SCROLLBACK_LINES: usize = 2_000;
+
+/// Rows of terminal output shown by the panel; the PTY is sized to match.
+const OUTPUT_ROWS: u16 = 12;
+
+/// Output and liveness of the unified exec processes started in this
+/// session. The chat widget feeds it from exec events; the panel reads it.
+#[derive(Debug, Default)]
+pub(crate) struct UnifiedExecSessions {
+    sessions: Vec<UnifiedExecSession>,
+}
+
+#[derive(Debug)]
+struct UnifiedExecSession {
+    process_id: String,
+    call_id: String,
+    command_display: String,
+    exit_code: Option<i32>,
+    lines: VecDeque<String>,
+    partial: String,
+}
+
+impl UnifiedExecSessions {
+    pub(crate) fn begin(&mut self, process_id: String, call_id: String, command_display: String) {
+        self.sessions
+            .retain(|session| session.process_id != process_id);
+        self.sessions.push(UnifiedExecSession {
+            process_id,
+            call_id,
+            command_display,
+            exit_code: None,
+            lines: VecDeque::new(),
+            partial: String::new(),
+        });
+    }
+
+    /// Appends output streamed for the call that started a session. Returns
+    /// `false` when `call_id` did not start any session.
+    pub(crate) fn push_output(&mut self, call_id: &str, chunk: &str) -> bool {
+        let Some(session) = self
+            .sessions
+            .iter_mut()
+            .rev()
+            .find(|session| session.call_id == call_id)
+        else {
+            return false;
+        };
+        session.push_output(chunk);
+        true
+    }
+
+    pub(crate) fn end(&mut self, process_id: &str, exit_code: i32) {
+        if let Some(session) = self.get_mut(process_id) {
+            session.exit_code = Some(exit_code);
+        }
+    }
+
+    /// Resolves `/ps <query>`: an exact process id, or else the most recent
+    /// session whose command mentions `query`, preferring running ones.
+    pub(crate) fn find(&self, query: &str) -> Option<String> {
+        if self.get(query).is_some() {
+            return Some(query.to_string());
+        }
+        let matching = || {
+            self.sessions
+                .iter()
+                .rev()
+                .filter(|session| session.command_display.contains(query))
+        };
+        matching()
+            .find(|session| session.exit_code.is_none())
+            .or_else(|| matching().next())
+            .map(|session| session.process_id.clone())
+    }
+
+    pub(crate) fn command_display(&self, process_id: &str) -> Option<String> {
+        self.get(process_id)
+            .map(|session| session.command_display.clone())
+    }
+
+    fn get(&self, process_id: &str) -> Option<&UnifiedExecSession> {
+        self.sessions
+            .iter()
+            .find(|session| session.process_id == process_id)
+    }
+
+    fn get_mut(&mut self, process_id: &str) -> Option<&mut UnifiedExecSession> {
+        self.sessions
+            .iter_mut()
+            .find(|session| session.process_id == process_id)
+    }
+
+    fn next_after(&self, process_id: &str) -> Option<&str> {
+        let idx = self
+            .sessions
+            .iter()
+            .position(|session| session.process_id == process_id)?;
+        let next = &self.sessions[(idx + 1) % self.sessions.len()];
+        Some(next.process_id.as_str())
+    }
+}
+
+impl UnifiedExecSession {
+    fn push_output(&mut self, chunk: &str) {
+        let mut pieces = chunk.split('\n');
+        if let Some(first) = pieces.next() {
+            self.partial.push_str(first);
+        }
+        for piece in pieces {
+            let line = std::mem::replace(&mut self.partial, piece.to_string());
+            self.lines.push_back(overwrite_carriage_returns(&line));
+            if self.lines.len() > MAX_SCROLLBACK_LINES {
+                self.lines.pop_front();
+            }
+        }
+    }
+
+    /// Scrollback including the line still being written.
+    fn scrollback(&self) -> impl Iterator<Item = String> + '_ {
+        let partial = (!self.partial.is_empty()).then(|| overwrite_carriage_returns(&self.partial));
+        self.lines.iter().cloned().chain(partial)
+    }
+
+    fn scrollback_len(&self) -> usize {
+        self.lines.len() + usize::from(!self.partial.is_empty())
+    }
+}
+
+/// Keeps what a terminal would show for a line redrawn with `\r`, such as a
+/// progress bar.
+fn overwrite_carriage_returns(line: &str) -> String {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    line.rsplit('\r').next().unwrap_or(line).to_string()
+}
+
+/// Inline viewer for a background terminal opened with `/ps <query>`. Shows
+/// the session's output with scrollback and forwards typed lines to it.
+pub(crate) struct UnifiedExecPanel {
+    sessions: Arc<Mutex<UnifiedExecSessions>>,
+    process_id: String,
+    app_event_tx: AppEventSender,
+    input: String,
+    /// Lines scrolled up from the bottom of the output.
+    scroll_back: usize,
+    /// PTY size last requested, so a resize is only sent when it changes.
+    pty_size: Cell<Option<(u16, u16)>>,
+    complete: bool,
+}
+
+impl UnifiedExecPanel {
+    pub(crate) fn new(
+        sessions: Arc<Mutex<UnifiedExecSessions>>,
+        process_id: String,
+        app_event_tx: AppEventSender,
+    ) -> Self {
+        Self {
+            sessions,
+            process_id,
+            app_event_tx,
+            input: String::new(),
+            scroll_back: 0,
+            pty_size: Cell::new(None),
+            complete: false,
+        }
+    }
+
+    fn with_session<T>(&self, f: impl FnOnce(&UnifiedExecSession) -> T) -> Option<T> {
+        let sessions = self.sessions.lock().ok()?;
+        sessions.get(&self.process_id).map(f)
+    }
+
+    fn is_running(&self) -> bool {
+        self.with_session(|session| session.exit_code.is_none())
+            .unwrap_or(false)
+    }
+
+    fn submit_input(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        let mut input = std::mem::take(&mut self.input);
+        input.push('\n');
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::UnifiedExecInput {
+                process_id: self.process_id.clone(),
+                input,
+            }));
+        self.scroll_back = 0;
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        let max = self
+            .with_session(UnifiedExecSession::scrollback_len)
+            .unwrap_or(0)
+            .saturating_sub(OUTPUT_ROWS as usize);
+        self.scroll_back = (self.scroll_back + lines).min(max);
+    }
+
+    fn show_next_session(&mut self) {
+        let next = self.sessions.lock().ok().and_then(|sessions| {
+            sessions
+                .next_after(&self.process_id)
+                .map(ToString::to_string)
+        });
+        if let Some(next) = next
+            && next != self.process_id
+        {
+            self.process_id = next;
+            self.input.clear();
+            self.scroll_back = 0;
+            self.pty_size.set(None);
+        }
+    }
+
+    /// Keeps the PTY the same size as the area its output is shown in.
+    fn sync_pty_size(&self, rows: u16, cols: u16) {
+        if !self.is_running() || self.pty_size.get() == Some((rows, cols)) {
+            return;
+        }
+        self.pty_size.set(Some((rows, cols)));
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::ResizeUnifiedExecProcess {
+                process_id: self.process_id.clone(),
+                rows,
+                cols,
+            }));
+    }
+
+    fn header_line(&self, width: u16) -> Line<'static> {
+        let (command, status) = self
+            .with_session(|session| {
+                let status = match session.exit_code {
+                    None => "running".green(),
+                    Some(0) => "exited".dim(),
+                    Some(code) => format!("exited {code}").red(),
+                };
+                (session.command_display.clone(), status)
+            })
+            .unwrap_or_else(|| (String::new(), "unknown".dim()));
+        let mut spans: Vec<Span<'static>> = vec![
+            "Background terminal ".bold(),
+            self.process_id.clone().cyan(),
+            " · ".dim(),
+            status,
+        ];
+        let used: usize = spans.iter().map(Span::width).sum();
+        let budget = (width as usize).saturating_sub(used + 3);
+        if budget > 0 && !command.is_empty() {
+            let (command, _, _) =
+                take_prefix_by_width(command.lines().next().unwrap_or(""), budget);
+            spans.push(" · ".dim());
+            spans.push(command.into());
+        }
+        spans.into()
+    }
+
+    fn output_lines(&self, rows: usize) -> Vec<Line<'static>> {
+        self.with_session(|session| {
+            let len = session.scrollback_len();
+            let end = len.saturating_sub(self.scroll_back);
+            let start = end.saturating_sub(rows);
+            session
+                .scrollback()
+                .skip(start)
+                .take(end - start)
+                .map(|line| ansi_escape_line(&line))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    fn input_line(&self) -> Line<'static> {
+        if self.is_running() {
+            vec!["› ".cyan(), self.input.clone().into()].into()
+        } else {
+            "  process exited; input is disabled".dim().into()
+        }
+    }
+
+    fn hint_line(&self) -> Line<'static> {
+        let mut spans: Vec<Span<'static>> = vec![
+            key_hint::plain(KeyCode::Enter).into(),
+            " send  ".dim(),
+            key_hint::plain(KeyCode::Up).into(),
+            "/".dim(),
+            key_hint::plain(KeyCode::Down).into(),
+            " scroll  ".dim(),
+            key_hint::plain(KeyCode::Tab).into(),
+            " next terminal  ".dim(),
+            key_hint::plain(KeyCode::Esc).into(),
+            " close".dim(),
+        ];
+        if self.scroll_back > 0 {
+            spans.push(format!("  ↑ {} lines", self.scroll_back).dim());
+        }
+        spans.into()
+    }
+}
+
+impl BottomPaneView for UnifiedExecPanel {
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.on_ctrl_c();
+            }
+            KeyCode::Enter => self.submit_input(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_back = self.scroll_back.saturating_sub(1),
+            KeyCode::PageUp => self.scroll_up(OUTPUT_ROWS as usize),
+            KeyCode::PageDown => {
+                self.scroll_back = self.scroll_back.saturating_sub(OUTPUT_ROWS as usize);
+            }
+            KeyCode::End => self.scroll_back = 0,
+            KeyCode::Tab => self.show_next_session(),
+            KeyCode::Char(c)
+                if !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_ctrl_c(&mut self) -> CancellationEvent {
+        self.complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        if pasted.is_empty() {
+            return false;
+        }
+        self.input.push_str(&pasted.replace(['\r', '\n'], " "));
+        true
+    }
+}
+
+impl Renderable for UnifiedExecPanel {
+    fn desired_height(&self, _width: u16) -> u16 {
+        OUTPUT_ROWS + 3
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let rows = area.height.saturating_sub(3).min(OUTPUT_ROWS);
+        self.sync_pty_size(rows.max(1), area.width);
+
+        let mut lines = vec![self.header_line(area.width)];
+        let mut output = self.output_lines(rows as usize);
+        output.resize(rows as usize, Line::default());
+        lines.extend(output);
+        lines.push(self.input_line());
+        lines.push(self.hint_line());
+        Paragraph::new(lines).render(area, buf);
+    }
+
+    fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        if !self.is_running() || area.height < 3 {
+            return None;
+        }
+        let rows = area.height.saturating_sub(3).min(OUTPUT_ROWS);
+        let input_width = Line::from(vec!["› ".into(), self.input.clone().into()]).width() as u16;
+        Some((
+            area.x + input_width.min(area.width.saturating_sub(1)),
+            area.y + 1 + rows,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc::UnboundedReceiver;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn sessions_with_output(output: &str) -> Arc<Mutex<UnifiedExecSessions>> {
+        let mut sessions = UnifiedExecSessions::default();
+        sessions.begin(
+            "1000".to_string(),
+            "call-1".to_string(),
+            "python3 -i".to_string(),
+        );
+        sessions.push_output("call-1", output);
+        Arc::new(Mutex::new(sessions))
+    }
+
+    fn panel(
+        sessions: Arc<Mutex<UnifiedExecSessions>>,
+    ) -> (UnifiedExecPanel, UnboundedReceiver<AppEvent>) {
+        let (tx, rx) = unbounded_channel::<AppEvent>();
+        let panel = UnifiedExecPanel::new(sessions, "1000".to_string(), AppEventSender::new(tx));
+        (panel, rx)
+    }
+
+    fn render_lines(panel: &UnifiedExecPanel, width: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, panel.desired_height(width));
+        let mut buf = Buffer::empty(area);
+        panel.render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scrollback_joins_chunks_and_applies_carriage_returns() {
+        let mut sessions = UnifiedExecSessions::default();
+        sessions.begin("1000".to_string(), "call-1".to_string(), "sh".to_string());
+        assert!(sessions.push_output("call-1", "hel"));
+        assert!(sessions.push_output("call-1", "lo\r\n10%\r50%\r100%\nprompt> "));
+        assert!(!sessions.push_output("call-2", "ignored\n"));
+
+        let session = sessions.get("1000").expect("session");
+        let lines: Vec<String> = session.scrollback().collect();
+        assert_eq!(lines, vec!["hello", "100%", "prompt> "]);
+    }
+
+    #[test]
+    fn find_matches_id_then_running_command() {
+        let mut sessions = UnifiedExecSessions::default();
+        sessions.begin(
+            "1".to_string(),
+            "call-1".to_string(),
+            "python3 a.py".to_string(),
+        );
+        sessions.begin(
+            "2".to_string(),
+            "call-2".to_string(),
+            "python3 b.py".to_string(),
+        );
+        sessions.end("2", 0);
+
+        assert_eq!(sessions.find("2"), Some("2".to_string()));
+        assert_eq!(sessions.find("python3"), Some("1".to_string()));
+        assert_eq!(sessions.find("b.py"), Some("2".to_string()));
+        assert_eq!(sessions.find("node"), None);
+    }
+
+    #[test]
+    fn renders_header_output_and_resizes_pty() {
+        let sessions = sessions_with_output(">>> 1 + 1\n2\n>>> ");
+        let (panel, mut rx) = panel(sessions);
+
+        let lines = render_lines(&panel, 60);
+        assert_eq!(lines[0], "Background terminal 1000 · running · python3 -i");
+        assert_eq!(lines[1..4], [">>> 1 + 1", "2", ">>>"]);
+        assert_eq!(lines[1 + OUTPUT_ROWS as usize], "›");
+
+        match rx.try_recv() {
+            Ok(AppEvent::CodexOp(Op::ResizeUnifiedExecProcess {
+                process_id,
+                rows,
+                cols,
+            })) => {
+                assert_eq!((process_id.as_str(), rows, cols), ("1000", OUTPUT_ROWS, 60));
+            }
+            other => panic!("expected a resize op, got {other:?}"),
+        }
+
+        // Rendering again at the same size does not resize again.
+        render_lines(&panel, 60);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn enter_sends_typed_line_to_the_session() {
+        let sessions = sessions_with_output(">>> ");
+        let (mut panel, mut rx) = panel(sessions);
+
+        for c in "1 + 1".chars() {
+            panel.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        panel.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match rx.try_recv() {
+            Ok(AppEvent::CodexOp(Op::UnifiedExecInput { process_id, input })) => {
+                assert_eq!((process_id.as_str(), input.as_str()), ("1000", "1 + 1\n"));
+            }
+            other => panic!("expected unified exec input, got {other:?}"),
+        }
+        assert!(panel.input.is_empty());
+    }
+
+    #[test]
+    fn exited_session_disables_input() {
+        let sessions = sessions_with_output("bye\n");
+        sessions.lock().expect("lock").end("1000", 3);
+        let (mut panel, mut rx) = panel(sessions);
+
+        panel.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        panel.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        let lines = render_lines(&panel, 60);
+        assert!(lines[0].contains("exited 3"), "header: {}", lines[0]);
+        assert_eq!(
+            lines[1 + OUTPUT_ROWS as usize],
+            "  process exited; input is disabled"
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn scrolling_shows_earlier_output() {
+        let output: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let (mut panel, _rx) = panel(sessions_with_output(&output));
+
+        panel.handle_key_event(KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE));
+        let lines = render_lines(&panel, 40);
+        assert_eq!(lines[1], "line 1");
+        assert_eq!(lines[OUTPUT_ROWS as usize], "line 12");
+
+        panel.handle_key_event(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        let lines = render_lines(&panel, 40);
+        assert_eq!(lines[OUTPUT_ROWS as usize], "line 20");
+    }
+}
@@ -25,6 +25,7 @@ use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -33,6 +34,7 @@ use codex_app_server_protocol::AuthMode;
 use codex_backend_client::Client as BackendClient;
 use codex_core::config::Config;
 use codex_core::config::ConstraintResult;
+use codex_core::config::types::CtrlCBehavior;
 use codex_core::config::types::Notifications;
 use codex_core::features::FEATURES;
 use codex_core::features::Feature;
@@ -48,6 +50,9 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundJobStatus;
+use codex_core::protocol::BackgroundJobStatusEvent;
+use codex_core::protocol::BudgetExceededEvent;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::DeprecationNoticeEvent;
 use codex_core::protocol::ErrorEvent;
@@ -62,24 +67,35 @@ use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::ListSkillsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpSamplingRequestEvent;
 use codex_core::protocol::McpStartupCompleteEvent;
 use codex_core::protocol::McpStartupStatus;
 use codex_core::protocol::McpStartupUpdateEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::ModelChangedEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::ProposedPatchEvent;
+use codex_core::protocol::ProviderSwitchReason;
+use codex_core::protocol::ProviderSwitchedEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SkillMetadata as ProtocolSkillMetadata;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::StreamRecoveryStrategy;
+use codex_core::protocol::StreamRetryEvent;
 use codex_core::protocol::TerminalInteractionEvent;
+use codex_core::protocol::TerminalInteractionSource;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::ToolBudgetKind;
+use codex_core::protocol::ToolCallAbortedEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnCompleteEvent;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnSummaryEvent;
 use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UndoStartedEvent;
 use codex_core::protocol::UserMessageEvent;
@@ -87,6 +103,8 @@ use codex_core::protocol::ViewImageToolCallEvent;
 use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
+use codex_core::protocol::WorkspaceRootsChangedEvent;
+use codex_core::protocol::WorkspaceSearchEndEvent;
 use codex_core::skills::model::SkillMetadata;
 use codex_otel::OtelManager;
 use codex_protocol::ThreadId;
@@ -106,6 +124,7 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use mcp_types::SamplingMessageContent;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -146,8 +165,12 @@ use crate::bottom_pane::QUIT_SHORTCUT_TIMEOUT;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
 use crate::bottom_pane::SelectionViewParams;
+use crate::bottom_pane::UnifiedExecPanel;
+use crate::bottom_pane::UnifiedExecSessions;
 use crate::bottom_pane::custom_prompt_view::CustomPromptView;
 use crate::bottom_pane::popup_consts::standard_popup_hint_line;
+use crate::clipboard_copy;
+use crate::clipboard_copy::CopyMethod;
 use crate::clipboard_paste::paste_image_to_temp_png;
 use crate::collab;
 use crate::collaboration_modes;
@@ -174,6 +197,9 @@ use crate::render::renderable::RenderableItem;
 use crate::slash_command::SlashCommand;
 use crate::status::RateLimitSnapshotDisplay;
 use crate::text_formatting::truncate_text;
+use crate::token_usage::TokenUsageTracker;
+use crate::transcript_export;
+use crate::transcript_export::ExportFormat;
 use crate::tui::FrameRequester;
 mod interrupts;
 use self::interrupts::InterruptManager;
@@ -191,6 +217,7 @@ use std::path::Path;
 use chrono::Local;
 use codex_common::approval_presets::ApprovalPreset;
 use codex_common::approval_presets::builtin_approval_presets;
+use codex_common::elapsed::format_duration;
 use codex_core::AuthManager;
 use codex_core::CodexAuth;
 use codex_core::ThreadManager;
@@ -256,7 +283,7 @@ impl UnifiedExecWaitStreak {
 fn is_unified_exec_source(source: ExecCommandSource) -> bool {
     matches!(
         source,
-        ExecCommandSource::UnifiedExecStartup | ExecCommandSource::UnifiedExecInteraction
+        ExecCommandSource::ModelUnifiedExec | ExecCommandSource::UserUnifiedExecInput
     )
 }
 
@@ -271,6 +298,24 @@ const RATE_LIMIT_WARNING_THRESHOLDS: [f64; 3] = [75.0, 90.0, 95.0];
 const NUDGE_MODEL_SLUG: &str = "gpt-5.1-codex-mini";
 const RATE_LIMIT_SWITCH_PROMPT_THRESHOLD: f64 = 90.0;
 
+/// How long an escalating Ctrl+C waits for the next press before starting over.
+const CTRL_C_ESCALATION_WINDOW: Duration = Duration::from_secs(3);
+
+/// What the last escalating Ctrl+C press did (see [`CtrlCBehavior::Escalate`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CtrlCStage {
+    ToolCallCancelled,
+    TurnEnding,
+    TurnInterrupted,
+    QuitPrompted,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CtrlCEscalation {
+    stage: CtrlCStage,
+    expires_at: Instant,
+}
+
 #[derive(Default)]
 struct RateLimitWarningState {
     secondary_index: usize,
@@ -416,6 +461,10 @@ pub(crate) struct ChatWidget {
     /// flushing. It is intentionally allowed to wrap, which implies a rare one-time cache collision
     /// where the overlay may briefly treat new tail content as already cached.
     active_cell_revision: u64,
+    /// Latest `TurnDiffEvent` diff of the running or last turn, shown by the diff viewer.
+    turn_diff: String,
+    /// Final text of the most recent agent message, for copying.
+    last_agent_message: Option<String>,
     config: Config,
     /// The unmasked collaboration mode settings (always Custom mode).
     ///
@@ -429,6 +478,8 @@ pub(crate) struct ChatWidget {
     session_header: SessionHeader,
     initial_user_message: Option<UserMessage>,
     token_info: Option<TokenUsageInfo>,
+    /// Per-turn token accounting for the context indicator and `/usage`.
+    token_usage_tracker: TokenUsageTracker,
     rate_limit_snapshot: Option<RateLimitSnapshotDisplay>,
     plan_type: Option<PlanType>,
     rate_limit_warnings: RateLimitWarningState,
@@ -444,6 +495,10 @@ pub(crate) struct ChatWidget {
     unified_exec_wait_streak: Option<UnifiedExecWaitStreak>,
     task_complete_pending: bool,
     unified_exec_processes: Vec<UnifiedExecProcessSummary>,
+    /// Output and liveness of every background terminal started in this
+    /// session, shown by the `/ps <query>` panel. Unlike
+    /// `unified_exec_processes` this survives the end of a turn.
+    unified_exec_sessions: Arc<Mutex<UnifiedExecSessions>>,
     /// Tracks whether codex-core currently considers an agent turn to be in progress.
     ///
     /// This is kept separate from `mcp_startup_status` so that MCP startup progress (or completion)
@@ -485,6 +540,12 @@ pub(crate) struct ChatWidget {
     /// We require the second press to match this key so `Ctrl+C` followed by
     /// `Ctrl+D` (or vice versa) doesn't quit accidentally.
     quit_shortcut_key: Option<KeyBinding>,
+    /// Tool calls Ctrl+C can cancel, oldest first. A call that asks for
+    /// approval moves to the back so the dialog's call is cancelled first.
+    cancellable_tool_calls: Vec<String>,
+    /// The last step of an escalating Ctrl+C, while the next press may
+    /// continue from it.
+    ctrl_c_escalation: Option<CtrlCEscalation>,
     // Simple review mode flag; used to adjust layout and banners.
     is_review_mode: bool,
     // Snapshot of token usage to restore after review mode exits.
@@ -803,6 +864,7 @@ impl ChatWidget {
     }
 
     fn on_agent_message(&mut self, message: String) {
+        self.last_agent_message = Some(message.clone());
         // If we have a stream_controller, then the final agent message is redundant and will be a
         // duplicate of what has already been streamed.
         if self.stream_controller.is_none() {
@@ -862,10 +924,13 @@ impl ChatWidget {
 
     fn on_task_started(&mut self) {
         self.agent_turn_running = true;
+        self.token_usage_tracker.start_turn();
+        self.turn_diff.clear();
         self.saw_plan_update_this_turn = false;
         self.bottom_pane.clear_quit_shortcut_hint();
         self.quit_shortcut_expires_at = None;
         self.quit_shortcut_key = None;
+        self.ctrl_c_escalation = None;
         self.update_task_running_state();
         self.retry_status_header = None;
         self.bottom_pane.set_interrupt_hint_visible(true);
@@ -883,6 +948,7 @@ impl ChatWidget {
         self.agent_turn_running = false;
         self.update_task_running_state();
         self.running_commands.clear();
+        self.cancellable_tool_calls.clear();
         self.suppressed_exec_calls.clear();
         self.last_unified_wait = None;
         self.unified_exec_wait_streak = None;
@@ -902,6 +968,14 @@ impl ChatWidget {
         self.maybe_show_pending_rate_limit_prompt();
     }
 
+    fn on_turn_summary(&mut self, summary: TurnSummaryEvent) {
+        if summary.tool_calls == 0 {
+            return;
+        }
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_turn_summary(&summary));
+    }
+
     fn maybe_prompt_plan_implementation(&mut self, last_agent_message: Option<&str>) {
         if !self.collaboration_modes_enabled() {
             return;
@@ -978,46 +1052,24 @@ impl ChatWidget {
     }
 
     pub(crate) fn set_token_info(&mut self, info: Option<TokenUsageInfo>) {
-        match info {
-            Some(info) => self.apply_token_info(info),
-            None => {
-                self.bottom_pane.set_context_window(None, None);
-                self.token_info = None;
-            }
-        }
+        self.token_usage_tracker.set_reported(info.clone());
+        self.token_info = info;
+        self.refresh_context_indicator();
     }
 
-    fn apply_token_info(&mut self, info: TokenUsageInfo) {
-        let percent = self.context_remaining_percent(&info);
-        let used_tokens = self.context_used_tokens(&info, percent.is_some());
-        self.bottom_pane.set_context_window(percent, used_tokens);
-        self.token_info = Some(info);
-    }
-
-    fn context_remaining_percent(&self, info: &TokenUsageInfo) -> Option<i64> {
-        info.model_context_window.map(|window| {
-            info.last_token_usage
-                .percent_of_context_window_remaining(window)
-        })
-    }
-
-    fn context_used_tokens(&self, info: &TokenUsageInfo, percent_known: bool) -> Option<i64> {
-        if percent_known {
-            return None;
-        }
-
-        Some(info.total_token_usage.tokens_in_context_window())
+    fn refresh_context_indicator(&mut self) {
+        // Providers that report usage send their own window; the config's is
+        // only needed for estimates.
+        let usage = self.token_usage_tracker.context_usage(
+            self.config.model_context_window,
+            self.config.model_auto_compact_token_limit,
+        );
+        self.bottom_pane.set_context_usage(usage);
     }
 
     fn restore_pre_review_token_info(&mut self) {
         if let Some(saved) = self.pre_review_token_info.take() {
-            match saved {
-                Some(info) => self.apply_token_info(info),
-                None => {
-                    self.bottom_pane.set_context_window(None, None);
-                    self.token_info = None;
-                }
-            }
+            self.set_token_info(saved);
         }
     }
 
@@ -1099,6 +1151,7 @@ impl ChatWidget {
         self.agent_turn_running = false;
         self.update_task_running_state();
         self.running_commands.clear();
+        self.cancellable_tool_calls.clear();
         self.suppressed_exec_calls.clear();
         self.last_unified_wait = None;
         self.unified_exec_wait_streak = None;
@@ -1121,6 +1174,155 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// Reports the spent tool budget and, for a live event, offers to double
+    /// it for the rest of the session.
+    fn on_budget_exceeded(&mut self, ev: BudgetExceededEvent, from_replay: bool) {
+        let budget = match ev.budget {
+            ToolBudgetKind::TurnToolCalls => format!("{} tool calls per turn", ev.limit),
+            ToolBudgetKind::SessionToolCalls => format!("{} tool calls per session", ev.limit),
+            ToolBudgetKind::TurnExecTime => format!(
+                "{} of command time per turn",
+                format_duration(Duration::from_millis(ev.limit))
+            ),
+        };
+        let outcome = if ev.ends_turn {
+            "the turn was ended"
+        } else {
+            "further tool calls will fail"
+        };
+        self.on_warning(format!("Tool budget exhausted ({budget}); {outcome}"));
+        if from_replay {
+            return;
+        }
+
+        let raised = ev.limit.saturating_mul(2);
+        let op = match ev.budget {
+            ToolBudgetKind::TurnToolCalls => Op::RaiseToolBudget {
+                max_calls_per_turn: Some(u32::try_from(raised).unwrap_or(u32::MAX)),
+                max_calls_per_session: None,
+                max_exec_ms_per_turn: None,
+            },
+            ToolBudgetKind::SessionToolCalls => Op::RaiseToolBudget {
+                max_calls_per_turn: None,
+                max_calls_per_session: Some(u32::try_from(raised).unwrap_or(u32::MAX)),
+                max_exec_ms_per_turn: None,
+            },
+            ToolBudgetKind::TurnExecTime => Op::RaiseToolBudget {
+                max_calls_per_turn: None,
+                max_calls_per_session: None,
+                max_exec_ms_per_turn: Some(raised),
+            },
+        };
+        let items = vec![
+            SelectionItem {
+                name: "Double the budget".to_string(),
+                description: Some("Applies for the rest of this session.".to_string()),
+                actions: vec![Box::new(move |tx| tx.send(AppEvent::CodexOp(op.clone())))],
+                dismiss_on_select: true,
+                ..Default::default()
+            },
+            SelectionItem {
+                name: "Keep the budget".to_string(),
+                dismiss_on_select: true,
+                ..Default::default()
+            },
+        ];
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Tool budget exhausted".to_string()),
+            subtitle: Some(format!("The agent used up its {budget}.")),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    /// Reports a `[provider_failover]` switch and, for a live event, follows
+    /// the session to the new model so later turns keep using it.
+    fn on_provider_switched(&mut self, ev: ProviderSwitchedEvent, from_replay: bool) {
+        let reason = match ev.reason {
+            ProviderSwitchReason::RateLimited => "rate limits",
+            ProviderSwitchReason::ServerError => "server errors",
+            ProviderSwitchReason::Disconnected => "dropped connections",
+        };
+        let outcome = if ev.retried_in_turn {
+            "retrying there"
+        } else {
+            "the next turn will use it"
+        };
+        self.on_warning(format!(
+            "Switched from {} ({}) to {} ({}) after repeated {reason}; {outcome}",
+            ev.from.provider, ev.from.model, ev.to.provider, ev.to.model
+        ));
+        if !from_replay {
+            self.set_model(&ev.to.model);
+        }
+    }
+
+    /// Reports an `Op::SwitchModel` that took effect and, for a live event,
+    /// sends later turns to the new model.
+    fn on_model_changed(&mut self, ev: ModelChangedEvent, from_replay: bool) {
+        let hint = ev
+            .compacts_history
+            .then(|| "compacting the history to fit its context window".to_string());
+        self.add_info_message(
+            format!("Model changed to {} ({})", ev.to.model, ev.to.provider),
+            hint,
+        );
+        if !from_replay {
+            self.set_model(&ev.to.model);
+        }
+    }
+
+    fn on_workspace_roots_changed(&mut self, ev: WorkspaceRootsChangedEvent) {
+        let names: Vec<&str> = ev.roots.iter().map(|root| root.name.as_str()).collect();
+        let changes: Vec<String> = ev
+            .added
+            .iter()
+            .map(|root| format!("added {} ({})", root.name, root.path.display()))
+            .chain(
+                ev.removed
+                    .iter()
+                    .map(|root| format!("removed {}", root.name)),
+            )
+            .collect();
+        self.add_info_message(
+            if names.is_empty() {
+                "No workspace roots".to_string()
+            } else {
+                format!("Workspace roots: {}", names.join(", "))
+            },
+            (!changes.is_empty()).then(|| changes.join("; ")),
+        );
+    }
+
+    fn on_proposed_patch(&mut self, ev: ProposedPatchEvent) {
+        let paths: Vec<String> = ev
+            .patch
+            .summaries
+            .iter()
+            .map(|summary| display_path_for(&summary.path, &self.config.cwd))
+            .collect();
+        self.add_info_message(
+            format!("Proposed patch (not applied): {}", paths.join(", ")),
+            None,
+        );
+    }
+
+    fn on_background_job_status(&mut self, ev: BackgroundJobStatusEvent) {
+        let command = strip_bash_lc_and_escape(&ev.command);
+        let message = match (ev.status, ev.exit_code) {
+            (BackgroundJobStatus::Running, _) => {
+                format!("Background job {} started: {command}", ev.job_id)
+            }
+            (status, Some(code)) => {
+                format!("Background job {} {status} (exit {code})", ev.job_id)
+            }
+            (status, None) => format!("Background job {} {status}", ev.job_id),
+        };
+        let hint = ev.status.is_finished().then_some(command);
+        self.add_info_message(message, hint);
+    }
+
     fn on_mcp_startup_update(&mut self, ev: McpStartupUpdateEvent) {
         let mut status = self.mcp_startup_status.take().unwrap_or_default();
         if let McpStartupStatus::Failed { error } = &ev.status {
@@ -1196,10 +1398,20 @@ impl ChatWidget {
         // Finalize, log a gentle prompt, and clear running state.
         self.finalize_turn();
 
-        if reason != TurnAbortReason::ReviewEnded {
-            self.add_to_history(history_cell::new_error_event(
-                "Conversation interrupted - tell the model what to do differently. Something went wrong? Hit `/feedback` to report the issue.".to_owned(),
-            ));
+        match reason {
+            TurnAbortReason::ReviewEnded => {}
+            TurnAbortReason::SoftInterrupt => {
+                self.add_to_history(history_cell::new_info_event(
+                    "Turn ended after its running tool call - tell the model what to do next."
+                        .to_owned(),
+                    None,
+                ));
+            }
+            TurnAbortReason::Interrupted | TurnAbortReason::Replaced => {
+                self.add_to_history(history_cell::new_error_event(
+                    "Conversation interrupted - tell the model what to do differently. Something went wrong? Hit `/feedback` to report the issue.".to_owned(),
+                ));
+            }
         }
 
         if let Some(combined) = self.drain_queued_messages_for_restore() {
@@ -1303,6 +1515,18 @@ impl ChatWidget {
         );
     }
 
+    fn on_mcp_sampling_request(&mut self, ev: McpSamplingRequestEvent) {
+        // Requests within the server's budget run without asking.
+        if !ev.requires_approval {
+            return;
+        }
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_mcp_sampling(ev),
+            |s| s.handle_mcp_sampling_request_now(ev2),
+        );
+    }
+
     fn on_request_user_input(&mut self, ev: RequestUserInputEvent) {
         let ev2 = ev.clone();
         self.defer_or_handle(
@@ -1313,6 +1537,11 @@ impl ChatWidget {
 
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
+        // A unified exec process outlives the call that started it, so its
+        // end event says nothing about whether that call is still running.
+        if ev.source != ExecCommandSource::ModelUnifiedExec {
+            self.track_cancellable_tool_call(&ev.call_id);
+        }
         if is_unified_exec_source(ev.source) {
             self.track_unified_exec_process_begin(&ev);
             if !is_standard_tool_call(&ev.parsed_cmd) {
@@ -1324,6 +1553,14 @@ impl ChatWidget {
     }
 
     fn on_exec_command_output_delta(&mut self, ev: ExecCommandOutputDeltaEvent) {
+        let chunk = String::from_utf8_lossy(&ev.chunk);
+        if self
+            .unified_exec_sessions
+            .lock()
+            .is_ok_and(|mut sessions| sessions.push_output(&ev.call_id, &chunk))
+        {
+            self.request_redraw();
+        }
         let Some(cell) = self
             .active_cell
             .as_mut()
@@ -1332,13 +1569,39 @@ impl ChatWidget {
             return;
         };
 
-        if cell.append_output(&ev.call_id, std::str::from_utf8(&ev.chunk).unwrap_or("")) {
-            self.bump_active_cell_revision();
+        // Buffered only; the next frame applies everything received since the
+        // last one, so a chatty command cannot starve the UI.
+        if cell.append_output(&ev.call_id, &chunk) {
             self.request_redraw();
         }
     }
 
+    /// Applies exec output streamed since the last frame to the active cell.
+    pub(crate) fn apply_pending_exec_output(&mut self) {
+        if self
+            .active_cell
+            .as_mut()
+            .and_then(|c| c.as_any_mut().downcast_mut::<ExecCell>())
+            .is_some_and(ExecCell::apply_pending_output)
+        {
+            self.bump_active_cell_revision();
+        }
+    }
+
     fn on_terminal_interaction(&mut self, ev: TerminalInteractionEvent) {
+        if ev.source == TerminalInteractionSource::User {
+            // Typed into the `/ps` panel, possibly between turns.
+            let command_display = self
+                .unified_exec_sessions
+                .lock()
+                .ok()
+                .and_then(|sessions| sessions.command_display(&ev.process_id));
+            self.add_to_history(history_cell::new_unified_exec_interaction(
+                command_display,
+                ev.stdin,
+            ));
+            return;
+        }
         if !self.bottom_pane.is_task_running() {
             return;
         }
@@ -1390,6 +1653,17 @@ impl ChatWidget {
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
+        self.track_cancellable_tool_call(&event.call_id);
+        if event.changes.is_empty() && !event.summaries.is_empty() {
+            let files = event.summaries.len();
+            let bytes: u64 = event.summaries.iter().map(|summary| summary.bytes).sum();
+            let noun = if files == 1 { "file" } else { "files" };
+            self.add_to_history(history_cell::new_info_event(
+                format!("Editing {files} {noun} ({bytes} bytes, too large to preview)"),
+                None,
+            ));
+            return;
+        }
         self.add_to_history(history_cell::new_patch_event(
             event.changes,
             &self.config.cwd,
@@ -1399,13 +1673,14 @@ impl ChatWidget {
     fn on_view_image_tool_call(&mut self, event: ViewImageToolCallEvent) {
         self.flush_answer_stream_with_separator();
         self.add_to_history(history_cell::new_view_image_tool_call(
-            event.path,
+            &event,
             &self.config.cwd,
         ));
         self.request_redraw();
     }
 
     fn on_patch_apply_end(&mut self, event: codex_core::protocol::PatchApplyEndEvent) {
+        self.untrack_cancellable_tool_call(&event.call_id);
         let ev2 = event.clone();
         self.defer_or_handle(
             |q| q.push_patch_end(event),
@@ -1414,7 +1689,12 @@ impl ChatWidget {
     }
 
     fn on_exec_command_end(&mut self, ev: ExecCommandEndEvent) {
-        if is_unified_exec_source(ev.source) {
+        self.untrack_cancellable_tool_call(&ev.call_id);
+        self.record_tool_output(&ev.formatted_output);
+        // An escalated retry of an `exec_command` call still ends its process.
+        let escalated_unified_exec =
+            ev.source == ExecCommandSource::EscalatedRetry && ev.process_id.is_some();
+        if is_unified_exec_source(ev.source) || escalated_unified_exec {
             if let Some(process_id) = ev.process_id.as_deref()
                 && self
                     .unified_exec_wait_streak
@@ -1432,12 +1712,40 @@ impl ChatWidget {
         self.defer_or_handle(|q| q.push_exec_end(ev), |s| s.handle_exec_end_now(ev2));
     }
 
+    fn on_tool_call_aborted(&mut self, ev: ToolCallAbortedEvent) {
+        self.untrack_cancellable_tool_call(&ev.call_id);
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_tool_call_aborted(ev),
+            |s| s.handle_tool_call_aborted_now(ev2),
+        );
+    }
+
+    fn track_cancellable_tool_call(&mut self, call_id: &str) {
+        self.cancellable_tool_calls.retain(|id| id != call_id);
+        self.cancellable_tool_calls.push(call_id.to_string());
+    }
+
+    fn untrack_cancellable_tool_call(&mut self, call_id: &str) {
+        self.cancellable_tool_calls.retain(|id| id != call_id);
+    }
+
+    fn record_tool_output(&mut self, output: &str) {
+        self.token_usage_tracker.record_tool_output(output);
+        if self.token_usage_tracker.reported().is_none() {
+            self.refresh_context_indicator();
+        }
+    }
+
     fn track_unified_exec_process_begin(&mut self, ev: &ExecCommandBeginEvent) {
-        if ev.source != ExecCommandSource::UnifiedExecStartup {
+        if ev.source != ExecCommandSource::ModelUnifiedExec {
             return;
         }
         let key = ev.process_id.clone().unwrap_or(ev.call_id.to_string());
         let command_display = strip_bash_lc_and_escape(&ev.command);
+        if let Ok(mut sessions) = self.unified_exec_sessions.lock() {
+            sessions.begin(key.clone(), ev.call_id.clone(), command_display.clone());
+        }
         if let Some(existing) = self
             .unified_exec_processes
             .iter_mut()
@@ -1455,6 +1763,9 @@ impl ChatWidget {
 
     fn track_unified_exec_process_end(&mut self, ev: &ExecCommandEndEvent) {
         let key = ev.process_id.clone().unwrap_or(ev.call_id.to_string());
+        if let Ok(mut sessions) = self.unified_exec_sessions.lock() {
+            sessions.end(&key, ev.exit_code);
+        }
         let before = self.unified_exec_processes.len();
         self.unified_exec_processes
             .retain(|process| process.key != key);
@@ -1481,11 +1792,18 @@ impl ChatWidget {
     }
 
     fn on_mcp_tool_call_begin(&mut self, ev: McpToolCallBeginEvent) {
+        self.track_cancellable_tool_call(&ev.call_id);
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_mcp_begin(ev), |s| s.handle_mcp_begin_now(ev2));
     }
 
     fn on_mcp_tool_call_end(&mut self, ev: McpToolCallEndEvent) {
+        self.untrack_cancellable_tool_call(&ev.call_id);
+        let output = match &ev.result {
+            Ok(result) => serde_json::to_string(result).unwrap_or_default(),
+            Err(err) => err.clone(),
+        };
+        self.record_tool_output(&output);
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_mcp_end(ev), |s| s.handle_mcp_end_now(ev2));
     }
@@ -1499,6 +1817,11 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_web_search_call(ev.query));
     }
 
+    fn on_workspace_search_end(&mut self, ev: WorkspaceSearchEndEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_workspace_search(&ev));
+    }
+
     fn on_collab_event(&mut self, cell: PlainHistoryCell) {
         self.flush_answer_stream_with_separator();
         self.add_to_history(cell);
@@ -1524,6 +1847,12 @@ impl ChatWidget {
 
     fn on_turn_diff(&mut self, unified_diff: String) {
         debug!("TurnDiffEvent: {unified_diff}");
+        self.turn_diff = unified_diff;
+        self.request_redraw();
+    }
+
+    pub(crate) fn turn_diff(&self) -> &str {
+        &self.turn_diff
     }
 
     fn on_deprecation_notice(&mut self, event: DeprecationNoticeEvent) {
@@ -1572,6 +1901,27 @@ impl ChatWidget {
         self.set_status(message, additional_details);
     }
 
+    /// Notes in the history what a retry after a cut-off stream kept; a
+    /// plain restart is already shown by the reconnecting status.
+    fn on_stream_retry(&mut self, ev: StreamRetryEvent) {
+        let message = match ev.strategy {
+            StreamRecoveryStrategy::Restart => return,
+            StreamRecoveryStrategy::ResumeAfterToolCalls => format!(
+                "Stream cut off; resuming after {} completed tool call{}",
+                ev.tool_calls_preserved,
+                if ev.tool_calls_preserved == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            ),
+            StreamRecoveryStrategy::Continue => {
+                "Stream cut off; asking the model to continue".to_string()
+            }
+        };
+        self.add_info_message(message, None);
+    }
+
     /// Periodic tick to commit at most one queued line to history with a small delay,
     /// animating the output.
     pub(crate) fn on_commit_tick(&mut self) {
@@ -1672,8 +2022,7 @@ impl ChatWidget {
             Some(rc) => (rc.command, rc.parsed_cmd, rc.source),
             None => (ev.command.clone(), ev.parsed_cmd.clone(), ev.source),
         };
-        let is_unified_exec_interaction =
-            matches!(source, ExecCommandSource::UnifiedExecInteraction);
+        let is_unified_exec_interaction = matches!(source, ExecCommandSource::UserUnifiedExecInput);
 
         let needs_new = self
             .active_cell
@@ -1702,12 +2051,18 @@ impl ChatWidget {
                     exit_code: ev.exit_code,
                     formatted_output: String::new(),
                     aggregated_output: String::new(),
+                    saved_output_path: None,
+                    approval: ev.approval,
+                    interrupted: false,
                 }
             } else {
                 CommandOutput {
                     exit_code: ev.exit_code,
                     formatted_output: ev.formatted_output.clone(),
-                    aggregated_output: ev.aggregated_output.clone(),
+                    aggregated_output: ev.display_output().to_string(),
+                    saved_output_path: ev.saved_output_path.clone(),
+                    approval: ev.approval,
+                    interrupted: false,
                 }
             };
             cell.complete_call(&ev.call_id, output, ev.duration);
@@ -1722,6 +2077,43 @@ impl ChatWidget {
         self.had_work_activity = true;
     }
 
+    /// Ends the transcript entry of a call that was cancelled before it
+    /// reported an end event of its own.
+    pub(crate) fn handle_tool_call_aborted_now(&mut self, ev: ToolCallAbortedEvent) {
+        self.running_commands.remove(&ev.call_id);
+        let Some(cell) = self.active_cell.as_mut() else {
+            return;
+        };
+        if let Some(exec) = cell.as_any_mut().downcast_mut::<ExecCell>() {
+            if !exec
+                .iter_calls()
+                .any(|call| call.call_id == ev.call_id && call.output.is_none())
+            {
+                return;
+            }
+            let output = CommandOutput {
+                exit_code: 1,
+                formatted_output: String::new(),
+                aggregated_output: String::new(),
+                saved_output_path: None,
+                approval: None,
+                interrupted: true,
+            };
+            exec.complete_call(&ev.call_id, output, ev.duration);
+            if exec.should_flush() {
+                self.flush_active_cell();
+            } else {
+                self.bump_active_cell_revision();
+                self.request_redraw();
+            }
+        } else if let Some(tool) = cell.as_any_mut().downcast_mut::<McpToolCallCell>()
+            && tool.call_id() == ev.call_id
+        {
+            tool.mark_failed();
+            self.flush_active_cell();
+        }
+    }
+
     pub(crate) fn handle_patch_apply_end_now(
         &mut self,
         event: codex_core::protocol::PatchApplyEndEvent,
@@ -1743,9 +2135,14 @@ impl ChatWidget {
 
         let request = ApprovalRequest::Exec {
             id,
+            call_id: ev.call_id,
             command: ev.command,
+            cwd: ev.cwd,
             reason: ev.reason,
             proposed_execpolicy_amendment: ev.proposed_execpolicy_amendment,
+            risk: ev.risk,
+            sandbox_denial_output: ev.sandbox_denial_output,
+            injected_env: ev.injected_env,
         };
         self.bottom_pane
             .push_approval_request(request, &self.config.features);
@@ -1761,6 +2158,7 @@ impl ChatWidget {
 
         let request = ApprovalRequest::ApplyPatch {
             id,
+            call_id: ev.call_id,
             reason: ev.reason,
             changes: ev.changes.clone(),
             cwd: self.config.cwd.clone(),
@@ -1791,6 +2189,29 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    pub(crate) fn handle_mcp_sampling_request_now(&mut self, ev: McpSamplingRequestEvent) {
+        self.flush_answer_stream_with_separator();
+
+        self.notify(Notification::ElicitationRequested {
+            server_name: ev.server_name.clone(),
+        });
+
+        let prompt = match ev.request.messages.last().map(|message| &message.content) {
+            Some(SamplingMessageContent::TextContent(content)) => content.text.clone(),
+            Some(_) => "(non-text message)".to_string(),
+            None => String::new(),
+        };
+        let request = ApprovalRequest::McpSampling {
+            server_name: ev.server_name,
+            request_id: ev.id,
+            model: ev.model,
+            prompt,
+        };
+        self.bottom_pane
+            .push_approval_request(request, &self.config.features);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_request_user_input_now(&mut self, ev: RequestUserInputEvent) {
         self.flush_answer_stream_with_separator();
         self.bottom_pane.push_user_input_request(ev);
@@ -1807,7 +2228,7 @@ impl ChatWidget {
                 source: ev.source,
             },
         );
-        let is_wait_interaction = matches!(ev.source, ExecCommandSource::UnifiedExecInteraction)
+        let is_wait_interaction = matches!(ev.source, ExecCommandSource::UserUnifiedExecInput)
             && ev
                 .interaction_input
                 .as_deref()
@@ -1879,6 +2300,7 @@ impl ChatWidget {
             invocation,
             duration,
             result,
+            ..
         } = ev;
 
         let extra_cell = match self
@@ -1968,6 +2390,8 @@ impl ChatWidget {
             }),
             active_cell,
             active_cell_revision: 0,
+            turn_diff: String::new(),
+            last_agent_message: None,
             config,
             skills_all: Vec::new(),
             skills_initial_state: None,
@@ -1979,6 +2403,7 @@ impl ChatWidget {
             session_header: SessionHeader::new(header_model),
             initial_user_message,
             token_info: None,
+            token_usage_tracker: TokenUsageTracker::default(),
             rate_limit_snapshot: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
@@ -1991,6 +2416,7 @@ impl ChatWidget {
             unified_exec_wait_streak: None,
             task_complete_pending: false,
             unified_exec_processes: Vec::new(),
+            unified_exec_sessions: Arc::default(),
             agent_turn_running: false,
             mcp_startup_status: None,
             interrupts: InterruptManager::new(),
@@ -2006,6 +2432,8 @@ impl ChatWidget {
             pending_notification: None,
             quit_shortcut_expires_at: None,
             quit_shortcut_key: None,
+            cancellable_tool_calls: Vec::new(),
+            ctrl_c_escalation: None,
             is_review_mode: false,
             pre_review_token_info: None,
             needs_final_message_separator: false,
@@ -2093,6 +2521,8 @@ impl ChatWidget {
             }),
             active_cell,
             active_cell_revision: 0,
+            turn_diff: String::new(),
+            last_agent_message: None,
             config,
             skills_all: Vec::new(),
             skills_initial_state: None,
@@ -2104,6 +2534,7 @@ impl ChatWidget {
             session_header: SessionHeader::new(header_model),
             initial_user_message,
             token_info: None,
+            token_usage_tracker: TokenUsageTracker::default(),
             rate_limit_snapshot: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
@@ -2116,6 +2547,7 @@ impl ChatWidget {
             unified_exec_wait_streak: None,
             task_complete_pending: false,
             unified_exec_processes: Vec::new(),
+            unified_exec_sessions: Arc::default(),
             agent_turn_running: false,
             mcp_startup_status: None,
             interrupts: InterruptManager::new(),
@@ -2132,6 +2564,8 @@ impl ChatWidget {
             pending_notification: None,
             quit_shortcut_expires_at: None,
             quit_shortcut_key: None,
+            cancellable_tool_calls: Vec::new(),
+            ctrl_c_escalation: None,
             is_review_mode: false,
             pre_review_token_info: None,
             needs_final_message_separator: false,
@@ -2219,6 +2653,8 @@ impl ChatWidget {
             }),
             active_cell: None,
             active_cell_revision: 0,
+            turn_diff: String::new(),
+            last_agent_message: None,
             config,
             skills_all: Vec::new(),
             skills_initial_state: None,
@@ -2230,6 +2666,7 @@ impl ChatWidget {
             session_header: SessionHeader::new(header_model),
             initial_user_message,
             token_info: None,
+            token_usage_tracker: TokenUsageTracker::default(),
             rate_limit_snapshot: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
@@ -2242,6 +2679,7 @@ impl ChatWidget {
             unified_exec_wait_streak: None,
             task_complete_pending: false,
             unified_exec_processes: Vec::new(),
+            unified_exec_sessions: Arc::default(),
             agent_turn_running: false,
             mcp_startup_status: None,
             interrupts: InterruptManager::new(),
@@ -2257,6 +2695,8 @@ impl ChatWidget {
             pending_notification: None,
             quit_shortcut_expires_at: None,
             quit_shortcut_key: None,
+            cancellable_tool_calls: Vec::new(),
+            ctrl_c_escalation: None,
             is_review_mode: false,
             pre_review_token_info: None,
             needs_final_message_separator: false,
@@ -2609,6 +3049,9 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Export => {
+                self.open_export_popup();
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -2621,6 +3064,9 @@ impl ChatWidget {
             SlashCommand::Ps => {
                 self.add_ps_output();
             }
+            SlashCommand::Usage => {
+                self.add_usage_output();
+            }
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
@@ -2694,6 +3140,21 @@ impl ChatWidget {
                     self.open_collaboration_modes_popup();
                 }
             }
+            SlashCommand::Model if !trimmed.is_empty() => {
+                // `/model <model> [provider]` switches without the picker.
+                let mut words = trimmed.split_whitespace();
+                let model = words.next().unwrap_or_default().to_string();
+                let provider = words.next().map(str::to_string);
+                self.submit_op(Op::SwitchModel { model, provider });
+            }
+            SlashCommand::Ps if !trimmed.is_empty() => {
+                self.open_unified_exec_panel(trimmed);
+            }
+            SlashCommand::Export if !trimmed.is_empty() => {
+                let path = self.config.cwd.join(trimmed);
+                let format = ExportFormat::from_path(&path);
+                self.export_transcript(path, format);
+            }
             SlashCommand::Review if !trimmed.is_empty() => {
                 self.submit_op(Op::Review {
                     review_request: ReviewRequest {
@@ -2915,7 +3376,7 @@ impl ChatWidget {
     /// `replay_initial_messages()`. Callers should treat `None` as a "fake" id
     /// that must not be used to correlate follow-up actions.
     fn dispatch_event_msg(&mut self, id: Option<String>, msg: EventMsg, from_replay: bool) {
-        let is_stream_error = matches!(&msg, EventMsg::StreamError(_));
+        let is_stream_error = matches!(&msg, EventMsg::StreamError(_) | EventMsg::StreamRetry(_));
         if !is_stream_error {
             self.restore_retry_status_header_if_present();
         }
@@ -2932,7 +3393,11 @@ impl ChatWidget {
 
         match msg {
             EventMsg::SessionConfigured(e) => self.on_session_configured(e),
-            EventMsg::AgentMessage(AgentMessageEvent { message }) => self.on_agent_message(message),
+            EventMsg::AgentMessage(AgentMessageEvent { message }) => {
+                self.token_usage_tracker.record_reply(&message);
+                self.refresh_context_indicator();
+                self.on_agent_message(message)
+            }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
                 self.on_agent_message_delta(delta)
             }
@@ -2950,6 +3415,7 @@ impl ChatWidget {
             EventMsg::TurnComplete(TurnCompleteEvent { last_agent_message }) => {
                 self.on_task_complete(last_agent_message, from_replay)
             }
+            EventMsg::TurnSummary(summary) => self.on_turn_summary(summary),
             EventMsg::TokenCount(ev) => {
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
@@ -2959,7 +3425,7 @@ impl ChatWidget {
             EventMsg::McpStartupUpdate(ev) => self.on_mcp_startup_update(ev),
             EventMsg::McpStartupComplete(ev) => self.on_mcp_startup_complete(ev),
             EventMsg::TurnAborted(ev) => match ev.reason {
-                TurnAbortReason::Interrupted => {
+                TurnAbortReason::Interrupted | TurnAbortReason::SoftInterrupt => {
                     self.on_interrupted_turn(ev.reason);
                 }
                 TurnAbortReason::Replaced => {
@@ -2977,6 +3443,12 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::McpSamplingRequest(ev) => {
+                // Replayed requests were answered in the session that made them.
+                if !from_replay {
+                    self.on_mcp_sampling_request(ev);
+                }
+            }
             EventMsg::ElicitationRequest(ev) => {
                 self.on_elicitation_request(ev);
             }
@@ -2992,8 +3464,87 @@ impl ChatWidget {
             EventMsg::ViewImageToolCall(ev) => self.on_view_image_tool_call(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
             EventMsg::McpToolCallEnd(ev) => self.on_mcp_tool_call_end(ev),
+            EventMsg::ToolCallAborted(ev) => self.on_tool_call_aborted(ev),
+            EventMsg::LoopDetected(ev) => self.on_warning(format!(
+                "`{}` failed {} times in a row with exit code {}",
+                strip_bash_lc_and_escape(&ev.command),
+                ev.failures,
+                ev.exit_code
+            )),
+            EventMsg::GitStateChanged(ev) => self.on_warning(format!(
+                "`{}` {}",
+                strip_bash_lc_and_escape(&ev.command),
+                ev.before.describe_change(&ev.after)
+            )),
+            EventMsg::BudgetExceeded(ev) => self.on_budget_exceeded(ev, from_replay),
+            EventMsg::ProviderSwitched(ev) => self.on_provider_switched(ev, from_replay),
+            EventMsg::ModelChanged(ev) => self.on_model_changed(ev, from_replay),
+            EventMsg::WorkspaceRootsChanged(ev) => self.on_workspace_roots_changed(ev),
+            EventMsg::WorkspacePrimer(ev) => self.add_info_message(
+                "Shared a workspace primer with the model".to_string(),
+                Some(ev.summary()),
+            ),
+            EventMsg::WorkspaceSnapshotCreated(ev) => self.add_info_message(
+                format!("Took snapshot {}", ev.snapshot.id),
+                Some(if ev.command.is_empty() {
+                    ev.snapshot.cwd.display().to_string()
+                } else {
+                    format!("before `{}`", strip_bash_lc_and_escape(&ev.command))
+                }),
+            ),
+            EventMsg::WorkspaceSnapshotRestored(ev) => {
+                if ev.restored {
+                    self.add_info_message(ev.message, None);
+                } else if ev.would_remove.is_empty() {
+                    self.on_warning(ev.message);
+                } else {
+                    let paths: Vec<String> = ev
+                        .would_remove
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect();
+                    self.on_warning(format!("{}: {}", ev.message, paths.join(", ")));
+                }
+            }
+            EventMsg::WorkspaceSnapshots(ev) => {
+                let lines: Vec<String> = ev
+                    .snapshots
+                    .iter()
+                    .map(|snapshot| format!("{} {}", snapshot.id, snapshot.cwd.display()))
+                    .collect();
+                self.add_info_message(
+                    format!("{} snapshot(s)", ev.snapshots.len()),
+                    (!lines.is_empty()).then(|| lines.join("; ")),
+                );
+            }
+            EventMsg::CapabilitiesChanged(ev) => self.add_info_message(
+                "Told the model its capabilities changed".to_string(),
+                Some(ev.before.describe_change(&ev.after)),
+            ),
+            EventMsg::PlanOnlyChanged(ev) => self.add_info_message(
+                if ev.plan_only {
+                    "Plan-only mode on: commands and patches are proposed, not run".to_string()
+                } else {
+                    "Plan-only mode off".to_string()
+                },
+                None,
+            ),
+            EventMsg::ProposedExec(ev) => self.add_info_message(
+                format!(
+                    "Proposed (not run): {}",
+                    strip_bash_lc_and_escape(&ev.command.command)
+                ),
+                None,
+            ),
+            EventMsg::ProposedPatch(ev) => self.on_proposed_patch(ev),
+            EventMsg::BackgroundJobStatus(ev) => self.on_background_job_status(ev),
+            EventMsg::OutcomeReported(ev) => self.add_info_message(
+                format!("Outcome: {} — {}", ev.outcome.status, ev.outcome.summary),
+                ev.outcome.details,
+            ),
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
+            EventMsg::WorkspaceSearchEnd(ev) => self.on_workspace_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
@@ -3005,7 +3556,9 @@ impl ChatWidget {
                 });
             }
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
+                self.on_turn_diff(unified_diff)
+            }
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
@@ -3017,7 +3570,10 @@ impl ChatWidget {
                 additional_details,
                 ..
             }) => self.on_stream_error(message, additional_details),
+            EventMsg::StreamRetry(ev) => self.on_stream_retry(ev),
             EventMsg::UserMessage(ev) => {
+                self.token_usage_tracker.record_prompt(&ev.message);
+                self.refresh_context_indicator();
                 if from_replay {
                     self.on_user_message_event(ev);
                 }
@@ -3044,7 +3600,29 @@ impl ChatWidget {
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ReasoningRawContentDelta(_)
-            | EventMsg::DynamicToolCallRequest(_) => {}
+            | EventMsg::DynamicToolCallRequest(_)
+            | EventMsg::EffectiveConfigResponse(_)
+            | EventMsg::ConfigReloaded(_)
+            | EventMsg::HookExecuted(_)
+            | EventMsg::NotificationSent(_)
+            | EventMsg::McpServerStatus(_)
+            | EventMsg::McpListPromptsResponse(_)
+            | EventMsg::McpPromptResponse(_)
+            | EventMsg::McpToolCallProgress(_)
+            | EventMsg::McpToolCallOutputDelta(_)
+            | EventMsg::TurnProgress(_)
+            | EventMsg::FileReadBegin(_)
+            | EventMsg::FileReadEnd(_)
+            | EventMsg::WorkspaceSearchBegin(_)
+            | EventMsg::ListDirBegin(_)
+            | EventMsg::ListDirEnd(_)
+            | EventMsg::ExecOutputReadBegin(_)
+            | EventMsg::ExecOutputReadEnd(_)
+            | EventMsg::FileAccessLedger(_)
+            | EventMsg::McpSamplingResponse(_)
+            | EventMsg::HeadlessApprovalDecision(_)
+            | EventMsg::ToolCallBlocked(_)
+            | EventMsg::FailureReported(_) => {}
         }
     }
 
@@ -3222,6 +3800,17 @@ impl ChatWidget {
         ));
     }
 
+    pub(crate) fn add_usage_output(&mut self) {
+        let usage = self.token_usage_tracker.context_usage(
+            self.config.model_context_window,
+            self.config.model_auto_compact_token_limit,
+        );
+        self.add_to_history(history_cell::new_token_usage_output(
+            usage,
+            self.token_usage_tracker.turns(),
+        ));
+    }
+
     pub(crate) fn add_ps_output(&mut self) {
         let processes = self
             .unified_exec_processes
@@ -3231,6 +3820,131 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_unified_exec_processes_output(processes));
     }
 
+    /// Opens the background terminal matching `query` in the bottom pane.
+    fn open_unified_exec_panel(&mut self, query: &str) {
+        let process_id = self
+            .unified_exec_sessions
+            .lock()
+            .ok()
+            .and_then(|sessions| sessions.find(query));
+        let Some(process_id) = process_id else {
+            self.add_error_message(format!("No background terminal matches '{query}'."));
+            return;
+        };
+        self.bottom_pane.show_view(Box::new(UnifiedExecPanel::new(
+            Arc::clone(&self.unified_exec_sessions),
+            process_id,
+            self.app_event_tx.clone(),
+        )));
+        self.request_redraw();
+    }
+
+    fn open_export_popup(&mut self) {
+        let export_item = |format: ExportFormat, cwd: &Path| {
+            let path = transcript_export::default_export_path(cwd, format);
+            SelectionItem {
+                name: format!("Export transcript as {}", format.label()),
+                description: Some(path.display().to_string()),
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::ExportTranscript {
+                        path: path.clone(),
+                        format,
+                    });
+                })],
+                dismiss_on_select: true,
+                ..Default::default()
+            }
+        };
+        let copy_item =
+            |name: &str, event: fn() -> AppEvent, disabled_reason: Option<&str>| SelectionItem {
+                name: name.to_string(),
+                is_disabled: disabled_reason.is_some(),
+                disabled_reason: disabled_reason.map(str::to_string),
+                actions: vec![Box::new(move |tx| tx.send(event()))],
+                dismiss_on_select: true,
+                ..Default::default()
+            };
+        let items = vec![
+            export_item(ExportFormat::Markdown, &self.config.cwd),
+            export_item(ExportFormat::Html, &self.config.cwd),
+            copy_item(
+                "Copy last agent message",
+                || AppEvent::CopyLastAgentMessage,
+                self.last_agent_message
+                    .is_none()
+                    .then_some("no agent message yet"),
+            ),
+            copy_item(
+                "Copy turn diff",
+                || AppEvent::CopyTurnDiff,
+                self.turn_diff
+                    .trim()
+                    .is_empty()
+                    .then_some("no changes in this turn"),
+            ),
+        ];
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Export".to_string()),
+            subtitle: Some("Use /export <path> to choose where the transcript goes.".to_string()),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    /// Writes the conversation so far to `path`, reporting the outcome in the
+    /// transcript.
+    pub(crate) fn export_transcript(&mut self, path: PathBuf, format: ExportFormat) {
+        let Some(rollout_path) = self.rollout_path() else {
+            self.add_error_message("Nothing to export yet.".to_string());
+            return;
+        };
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let cell =
+                match transcript_export::export_transcript(&rollout_path, &path, format).await {
+                    Ok(()) => history_cell::new_info_event(
+                        format!("Exported transcript to {}", path.display()),
+                        None,
+                    ),
+                    Err(err) => history_cell::new_error_event(format!(
+                        "Failed to export transcript to {}: {err}",
+                        path.display()
+                    )),
+                };
+            tx.send(AppEvent::InsertHistoryCell(Box::new(cell)));
+        });
+    }
+
+    pub(crate) fn copy_last_agent_message(&mut self) {
+        match self.last_agent_message.clone() {
+            Some(message) => self.copy_to_clipboard(&message, "the last agent message"),
+            None => self.add_error_message("No agent message to copy yet.".to_string()),
+        }
+    }
+
+    pub(crate) fn copy_turn_diff(&mut self) {
+        if self.turn_diff.trim().is_empty() {
+            self.add_error_message("No changes in this turn to copy.".to_string());
+            return;
+        }
+        let diff = self.turn_diff.clone();
+        self.copy_to_clipboard(&diff, "the turn diff");
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        match clipboard_copy::copy_text(text) {
+            Ok(CopyMethod::Clipboard) => {
+                self.add_info_message(format!("Copied {what} to the clipboard."), None)
+            }
+            Ok(CopyMethod::Osc52) => self.add_info_message(
+                format!("Sent {what} to the terminal clipboard."),
+                Some("(OSC 52)".to_string()),
+            ),
+            Err(err) => self.add_error_message(format!("Could not copy {what}: {err}")),
+        }
+    }
+
     fn stop_rate_limit_poller(&mut self) {
         if let Some(handle) = self.rate_limit_poller.take() {
             handle.abort();
@@ -4972,7 +5686,13 @@ impl ChatWidget {
     ///
     /// If the same quit shortcut is pressed again before expiry, this requests a shutdown-first
     /// quit.
+    ///
+    /// With `tui.ctrl_c = "escalate"` (the default) presses while the agent is working are handled
+    /// by [`Self::escalate_ctrl_c`] instead.
     fn on_ctrl_c(&mut self) {
+        if self.config.tui_ctrl_c == CtrlCBehavior::Escalate && self.escalate_ctrl_c() {
+            return;
+        }
         let key = key_hint::ctrl(KeyCode::Char('c'));
         let modal_or_popup_active = !self.bottom_pane.no_modal_or_popup_active();
         if self.bottom_pane.on_ctrl_c() == CancellationEvent::Handled {
@@ -5011,6 +5731,101 @@ impl ChatWidget {
         }
     }
 
+    /// Escalating Ctrl+C: the first press cancels the running tool call, a press within
+    /// [`CTRL_C_ESCALATION_WINDOW`] of the previous one ends the turn once its running tool calls
+    /// finish (`Op::SoftInterrupt`), the next interrupts the turn, and the one after asks for one
+    /// more press to quit. Without a tool call to cancel, the first press interrupts the turn. An
+    /// open approval dialog counts as its tool call running, so the steps are the same with or
+    /// without one.
+    ///
+    /// Returns `false` when the press should get the default handling, e.g. when nothing is
+    /// running or a popup or draft should be dismissed first.
+    fn escalate_ctrl_c(&mut self) -> bool {
+        let previous = self
+            .ctrl_c_escalation
+            .take()
+            .filter(|escalation| Instant::now() < escalation.expires_at)
+            .map(|escalation| escalation.stage);
+        match previous {
+            Some(CtrlCStage::QuitPrompted) => {
+                self.request_quit_without_confirmation();
+                return true;
+            }
+            Some(CtrlCStage::TurnInterrupted) => {
+                self.set_ctrl_c_stage(CtrlCStage::QuitPrompted, "Quit Codex?", "again to quit");
+                return true;
+            }
+            Some(CtrlCStage::ToolCallCancelled | CtrlCStage::TurnEnding) | None => {}
+        }
+        if !self.is_cancellable_work_active() {
+            return false;
+        }
+
+        let approval_active = self.bottom_pane.approval_request_active();
+        if !approval_active && self.bottom_pane.on_ctrl_c() == CancellationEvent::Handled {
+            return true;
+        }
+
+        // Elicitations have no call of their own; they wait on the MCP call.
+        let call_id = approval_active
+            .then(|| self.bottom_pane.current_approval_call_id())
+            .flatten()
+            .or_else(|| self.cancellable_tool_calls.last().cloned());
+        if previous.is_none()
+            && let Some(call_id) = call_id
+        {
+            self.untrack_cancellable_tool_call(&call_id);
+            self.submit_op(Op::CancelToolCall { call_id });
+            if approval_active {
+                self.bottom_pane.dismiss_current_approval();
+            }
+            self.set_ctrl_c_stage(
+                CtrlCStage::ToolCallCancelled,
+                "Cancelled the running tool call.",
+                "again to interrupt the turn",
+            );
+            return true;
+        }
+
+        if previous == Some(CtrlCStage::ToolCallCancelled) {
+            self.submit_op(Op::SoftInterrupt);
+            self.set_ctrl_c_stage(
+                CtrlCStage::TurnEnding,
+                "The turn will end after its running tool calls.",
+                "again to interrupt it now",
+            );
+            return true;
+        }
+
+        // Interrupting the turn settles every pending approval, so the dialogs
+        // only need to close.
+        while self.bottom_pane.approval_request_active() {
+            self.bottom_pane.dismiss_current_approval();
+        }
+        self.submit_op(Op::Interrupt);
+        self.set_ctrl_c_stage(
+            CtrlCStage::TurnInterrupted,
+            "Interrupted the turn.",
+            "again to quit",
+        );
+        true
+    }
+
+    /// Records an escalating Ctrl+C step and explains it in the footer.
+    fn set_ctrl_c_stage(&mut self, stage: CtrlCStage, what_happened: &str, next_press: &str) {
+        self.ctrl_c_escalation = Some(CtrlCEscalation {
+            stage,
+            expires_at: Instant::now() + CTRL_C_ESCALATION_WINDOW,
+        });
+        let line = Line::from(vec![
+            format!("{what_happened} ").into(),
+            key_hint::ctrl(KeyCode::Char('c')).into(),
+            format!(" {next_press}").dim(),
+        ]);
+        self.bottom_pane
+            .show_footer_flash(line, CTRL_C_ESCALATION_WINDOW);
+    }
+
     /// Handles a Ctrl+D press at the chat-widget layer.
     ///
     /// Ctrl-D only participates in quit when the composer is empty and no modal/popup is active.
@@ -5356,6 +6171,7 @@ impl ChatWidget {
 
     pub(crate) fn clear_token_usage(&mut self) {
         self.token_info = None;
+        self.token_usage_tracker.set_reported(None);
     }
 
     fn as_renderable(&self) -> RenderableItem<'_> {
@@ -36,6 +36,7 @@ use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExecCommandSource;
+use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::ExecPolicyAmendment;
 use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::FileChange;
@@ -45,16 +46,22 @@ use codex_core::protocol::McpStartupUpdateEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::PromptCacheStats;
+use codex_core::protocol::ProviderModel;
 use codex_core::protocol::RateLimitWindow;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SessionSource;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::StreamRecoveryStrategy;
+use codex_core::protocol::StreamRetryEvent;
 use codex_core::protocol::TerminalInteractionEvent;
+use codex_core::protocol::TerminalInteractionSource;
 use codex_core::protocol::TokenCountEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
 use codex_core::protocol::TurnCompleteEvent;
+use codex_core::protocol::TurnCost;
 use codex_core::protocol::TurnStartedEvent;
 use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UndoStartedEvent;
@@ -157,12 +164,16 @@ async fn resumed_initial_messages_render_history() {
                 images: None,
                 text_elements: Vec::new(),
                 local_images: Vec::new(),
+                documents: Vec::new(),
             }),
             EventMsg::AgentMessage(AgentMessageEvent {
                 message: "assistant reply".to_string(),
             }),
         ]),
         rollout_path: Some(rollout_file.path().to_path_buf()),
+        shell: None,
+        tools: None,
+        tmpdir: None,
     };
 
     chat.handle_codex_event(Event {
@@ -222,8 +233,12 @@ async fn replayed_user_message_preserves_text_elements_and_local_images() {
             images: None,
             text_elements: text_elements.clone(),
             local_images: local_images.clone(),
+            documents: Vec::new(),
         })]),
         rollout_path: Some(rollout_file.path().to_path_buf()),
+        shell: None,
+        tools: None,
+        tmpdir: None,
     };
 
     chat.handle_codex_event(Event {
@@ -271,6 +286,9 @@ async fn submission_preserves_text_elements_and_local_images() {
         history_entry_count: 0,
         initial_messages: None,
         rollout_path: Some(rollout_file.path().to_path_buf()),
+        shell: None,
+        tools: None,
+        tmpdir: None,
     };
     chat.handle_codex_event(Event {
         id: "initial".into(),
@@ -679,6 +697,9 @@ async fn context_indicator_shows_used_tokens_when_window_unknown() {
         total_token_usage: token_usage.clone(),
         last_token_usage: token_usage,
         model_context_window: None,
+        by_provider: Default::default(),
+        prompt_cache: Default::default(),
+        tool_output_tokens: 0,
     };
 
     chat.handle_codex_event(Event {
@@ -696,6 +717,45 @@ async fn context_indicator_shows_used_tokens_when_window_unknown() {
     );
 }
 
+/// Providers that never report usage get an estimate, replaced once usage arrives.
+#[tokio::test]
+async fn context_indicator_estimates_until_usage_is_reported() {
+    let (mut chat, _rx, _ops) = make_chatwidget_manual(None).await;
+    chat.config.model_context_window = Some(100_000);
+
+    chat.handle_codex_event(Event {
+        id: "turn".into(),
+        msg: EventMsg::UserMessage(UserMessageEvent {
+            message: "x".repeat(400),
+            images: None,
+            text_elements: Vec::new(),
+            local_images: Vec::new(),
+            documents: Vec::new(),
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "turn".into(),
+        msg: EventMsg::AgentMessage(AgentMessageEvent {
+            message: "y".repeat(200),
+        }),
+    });
+
+    let usage = chat.bottom_pane.context_usage().expect("estimated usage");
+    assert!(usage.estimated);
+    assert_eq!(usage.total_tokens, 150);
+
+    chat.handle_codex_event(Event {
+        id: "turn".into(),
+        msg: EventMsg::TokenCount(TokenCountEvent {
+            info: Some(make_token_info(12_700, 13_000)),
+            rate_limits: None,
+        }),
+    });
+    let usage = chat.bottom_pane.context_usage().expect("reported usage");
+    assert!(!usage.estimated);
+    assert_eq!(usage.percent_left, Some(30));
+}
+
 #[cfg_attr(
     target_os = "macos",
     ignore = "system configuration APIs are blocked under macOS seatbelt"
@@ -795,6 +855,8 @@ async fn make_chatwidget_manual(
         bottom_pane: bottom,
         active_cell: None,
         active_cell_revision: 0,
+        turn_diff: String::new(),
+        last_agent_message: None,
         config: cfg,
         current_collaboration_mode,
         active_collaboration_mask: None,
@@ -804,6 +866,7 @@ async fn make_chatwidget_manual(
         session_header: SessionHeader::new(resolved_model.clone()),
         initial_user_message: None,
         token_info: None,
+        token_usage_tracker: TokenUsageTracker::default(),
         rate_limit_snapshot: None,
         plan_type: None,
         rate_limit_warnings: RateLimitWarningState::default(),
@@ -818,6 +881,7 @@ async fn make_chatwidget_manual(
         unified_exec_wait_streak: None,
         task_complete_pending: false,
         unified_exec_processes: Vec::new(),
+        unified_exec_sessions: Arc::default(),
         agent_turn_running: false,
         mcp_startup_status: None,
         interrupts: InterruptManager::new(),
@@ -834,6 +898,8 @@ async fn make_chatwidget_manual(
         pending_notification: None,
         quit_shortcut_expires_at: None,
         quit_shortcut_key: None,
+        cancellable_tool_calls: Vec::new(),
+        ctrl_c_escalation: None,
         is_review_mode: false,
         pre_review_token_info: None,
         needs_final_message_separator: false,
@@ -931,6 +997,9 @@ fn make_token_info(total_tokens: i64, context_window: i64) -> TokenUsageInfo {
         total_token_usage: usage(total_tokens),
         last_token_usage: usage(total_tokens),
         model_context_window: Some(context_window),
+        by_provider: Default::default(),
+        prompt_cache: Default::default(),
+        tool_output_tokens: 0,
     }
 }
 
@@ -1350,6 +1419,9 @@ async fn exec_approval_emits_proposed_command_and_decision_history() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
@@ -1394,6 +1466,9 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
@@ -1444,6 +1519,9 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         reason: None,
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
@@ -1486,6 +1564,8 @@ fn begin_exec_with_source(
         parsed_cmd,
         source,
         interaction_input,
+        timeout_ms: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: call_id.to_string(),
@@ -1509,8 +1589,10 @@ fn begin_unified_exec_startup(
         command,
         cwd,
         parsed_cmd: Vec::new(),
-        source: ExecCommandSource::UnifiedExecStartup,
+        source: ExecCommandSource::ModelUnifiedExec,
         interaction_input: None,
+        timeout_ms: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: call_id.to_string(),
@@ -1526,12 +1608,13 @@ fn terminal_interaction(chat: &mut ChatWidget, call_id: &str, process_id: &str,
             call_id: call_id.to_string(),
             process_id: process_id.to_string(),
             stdin: stdin.to_string(),
+            source: TerminalInteractionSource::Agent,
         }),
     });
 }
 
 fn begin_exec(chat: &mut ChatWidget, call_id: &str, raw_cmd: &str) -> ExecCommandBeginEvent {
-    begin_exec_with_source(chat, call_id, raw_cmd, ExecCommandSource::Agent)
+    begin_exec_with_source(chat, call_id, raw_cmd, ExecCommandSource::ModelShell)
 }
 
 fn end_exec(
@@ -1555,6 +1638,8 @@ fn end_exec(
         source,
         interaction_input,
         process_id,
+        timeout_ms: _,
+        injected_env: _,
     } = begin_event;
     chat.handle_codex_event(Event {
         id: call_id.clone(),
@@ -1571,8 +1656,17 @@ fn end_exec(
             stderr: stderr.to_string(),
             aggregated_output: aggregated.clone(),
             exit_code,
+            outcome: None,
             duration: std::time::Duration::from_millis(5),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: aggregated,
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     });
 }
@@ -1713,6 +1807,113 @@ async fn ctrl_c_shutdown_works_with_caps_lock() {
     assert_matches!(rx.try_recv(), Ok(AppEvent::Exit(ExitMode::ShutdownFirst)));
 }
 
+#[tokio::test]
+async fn ctrl_c_escalates_from_tool_call_to_turn_to_quit() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(None).await;
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    chat.on_task_started();
+    begin_exec(&mut chat, "call-sleep", "sleep 600");
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(
+        op_rx.try_recv(),
+        Ok(Op::CancelToolCall { call_id }) if call_id == "call-sleep"
+    );
+    assert_eq!(
+        chat.bottom_pane.footer_flash_text().as_deref(),
+        Some("Cancelled the running tool call. ctrl + c again to interrupt the turn")
+    );
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::ToolCallAborted(ToolCallAbortedEvent {
+            call_id: "call-sleep".into(),
+            turn_id: "turn-1".into(),
+            tool_name: "shell".into(),
+            duration: Duration::from_secs(2),
+        }),
+    });
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "the cancelled command should be flushed");
+    assert!(lines_to_single_string(&cells[0]).contains("sleep 600"));
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(op_rx.try_recv(), Ok(Op::SoftInterrupt));
+    assert_eq!(
+        chat.bottom_pane.footer_flash_text().as_deref(),
+        Some("The turn will end after its running tool calls. ctrl + c again to interrupt it now")
+    );
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(op_rx.try_recv(), Ok(Op::Interrupt));
+    assert_eq!(
+        chat.bottom_pane.footer_flash_text().as_deref(),
+        Some("Interrupted the turn. ctrl + c again to quit")
+    );
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(
+        chat.bottom_pane.footer_flash_text().as_deref(),
+        Some("Quit Codex? ctrl + c again to quit")
+    );
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(rx.try_recv(), Ok(AppEvent::Exit(ExitMode::ShutdownFirst)));
+}
+
+#[tokio::test]
+async fn ctrl_c_cancels_the_call_waiting_for_approval() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(None).await;
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    chat.on_task_started();
+    begin_exec(&mut chat, "call-running", "sleep 600");
+    chat.handle_codex_event(Event {
+        id: "sub-approve".into(),
+        msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-approve".into(),
+            turn_id: "turn-1".into(),
+            command: vec!["rm".into(), "-rf".into(), "build".into()],
+            cwd: PathBuf::from("/repo"),
+            reason: None,
+            proposed_execpolicy_amendment: None,
+            parsed_cmd: vec![],
+            risk: None,
+            sandbox_denial_output: None,
+            injected_env: Default::default(),
+        }),
+    });
+    assert!(chat.bottom_pane.approval_request_active());
+    drain_insert_history(&mut rx);
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(
+        op_rx.try_recv(),
+        Ok(Op::CancelToolCall { call_id }) if call_id == "call-approve"
+    );
+    assert!(!chat.bottom_pane.approval_request_active());
+    assert_matches!(op_rx.try_recv(), Err(TryRecvError::Empty));
+    assert!(
+        drain_insert_history(&mut rx).is_empty(),
+        "the dismissed approval should not record a decision"
+    );
+
+    chat.handle_key_event(ctrl_c);
+    assert_matches!(op_rx.try_recv(), Ok(Op::SoftInterrupt));
+}
+
+#[tokio::test]
+async fn ctrl_c_interrupts_the_turn_when_configured() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual(None).await;
+    chat.config.tui_ctrl_c = CtrlCBehavior::Interrupt;
+    chat.on_task_started();
+    begin_exec(&mut chat, "call-sleep", "sleep 600");
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+    assert_matches!(op_rx.try_recv(), Ok(Op::Interrupt));
+    assert_eq!(chat.bottom_pane.footer_flash_text(), None);
+}
+
 #[tokio::test]
 async fn ctrl_d_quits_without_prompt() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
@@ -1838,14 +2039,23 @@ async fn exec_end_without_begin_uses_event_command() {
             command,
             cwd,
             parsed_cmd,
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: "done".to_string(),
             stderr: String::new(),
             aggregated_output: "done".to_string(),
             exit_code: 0,
+            outcome: None,
             duration: std::time::Duration::from_millis(5),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: "done".to_string(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     });
 
@@ -1871,7 +2081,7 @@ async fn exec_history_shows_unified_exec_startup_commands() {
         &mut chat,
         "call-startup",
         "echo unified exec startup",
-        ExecCommandSource::UnifiedExecStartup,
+        ExecCommandSource::ModelUnifiedExec,
     );
     assert!(
         drain_insert_history(&mut rx).is_empty(),
@@ -1898,7 +2108,7 @@ async fn exec_history_shows_unified_exec_tool_calls() {
         &mut chat,
         "call-startup",
         "ls",
-        ExecCommandSource::UnifiedExecStartup,
+        ExecCommandSource::ModelUnifiedExec,
     );
     end_exec(&mut chat, begin, "", "", 0);
 
@@ -1906,6 +2116,31 @@ async fn exec_history_shows_unified_exec_tool_calls() {
     assert_eq!(blob, "• Explored\n  └ List ls\n");
 }
 
+#[tokio::test]
+async fn exec_output_deltas_stream_into_active_cell_at_frame_rate() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.on_task_started();
+
+    begin_exec(&mut chat, "call-build", "cargo build");
+    for chunk in ["Compiling foo\n", "[=>  ] 10%\r", "[==> ] 50%"] {
+        chat.handle_codex_event(Event {
+            id: "call-build".to_string(),
+            msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id: "call-build".to_string(),
+                stream: ExecOutputStream::Stdout,
+                chunk: chunk.as_bytes().to_vec(),
+            }),
+        });
+    }
+    assert_eq!(active_blob(&chat), "• Running cargo build\n");
+
+    chat.apply_pending_exec_output();
+    assert_eq!(
+        active_blob(&chat),
+        "• Running cargo build\n  └ Compiling foo\n    [==> ] 50%\n"
+    );
+}
+
 #[tokio::test]
 async fn unified_exec_end_after_task_complete_is_suppressed() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
@@ -1915,7 +2150,7 @@ async fn unified_exec_end_after_task_complete_is_suppressed() {
         &mut chat,
         "call-startup",
         "echo unified exec startup",
-        ExecCommandSource::UnifiedExecStartup,
+        ExecCommandSource::ModelUnifiedExec,
     );
     drain_insert_history(&mut rx);
 
@@ -1941,6 +2176,7 @@ async fn unified_exec_interaction_after_task_complete_is_suppressed() {
             call_id: "call-1".to_string(),
             process_id: "proc-1".to_string(),
             stdin: "ls\n".to_string(),
+            source: TerminalInteractionSource::Agent,
         }),
     });
 
@@ -1951,6 +2187,275 @@ async fn unified_exec_interaction_after_task_complete_is_suppressed() {
     );
 }
 
+#[tokio::test]
+async fn ps_with_query_opens_background_terminal_panel() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.on_task_started();
+    begin_unified_exec_startup(&mut chat, "call-repl", "1000", "python3 -i");
+    chat.handle_codex_event(Event {
+        id: "call-repl".to_string(),
+        msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+            call_id: "call-repl".to_string(),
+            stream: ExecOutputStream::Stdout,
+            chunk: b"Python 3\n>>> ".to_vec(),
+        }),
+    });
+    chat.on_task_complete(None, false);
+    drain_insert_history(&mut rx);
+
+    chat.dispatch_command_with_args(SlashCommand::Ps, "python3".to_string());
+    let popup = render_bottom_popup(&chat, 80);
+    assert!(
+        popup.contains("Background terminal 1000 · running · python3 -i"),
+        "expected panel header: {popup}"
+    );
+    assert!(popup.contains("Python 3"), "expected output: {popup}");
+
+    // Input the user typed into the panel is recorded even between turns.
+    chat.handle_codex_event(Event {
+        id: "sub-1".to_string(),
+        msg: EventMsg::TerminalInteraction(TerminalInteractionEvent {
+            call_id: "call-repl".to_string(),
+            process_id: "1000".to_string(),
+            stdin: "1 + 1\n".to_string(),
+            source: TerminalInteractionSource::User,
+        }),
+    });
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected the user input in the transcript");
+    assert!(lines_to_single_string(&cells[0]).contains("1 + 1"));
+}
+
+#[tokio::test]
+async fn ps_with_unknown_query_reports_an_error() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.dispatch_command_with_args(SlashCommand::Ps, "node".to_string());
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert!(lines_to_single_string(&cells[0]).contains("No background terminal matches 'node'."));
+}
+
+#[tokio::test]
+async fn export_popup_lists_export_and_copy_actions() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.dispatch_command(SlashCommand::Export);
+
+    let popup = render_bottom_popup(&chat, 100);
+    assert!(popup.contains("Export transcript as Markdown"), "{popup}");
+    assert!(popup.contains("Export transcript as HTML"), "{popup}");
+    assert!(popup.contains("Copy last agent message"), "{popup}");
+    assert!(popup.contains("Copy turn diff"), "{popup}");
+}
+
+#[tokio::test]
+async fn export_without_a_rollout_reports_an_error() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.dispatch_command_with_args(SlashCommand::Export, "chat.md".to_string());
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert!(lines_to_single_string(&cells[0]).contains("Nothing to export yet."));
+}
+
+#[tokio::test]
+async fn copy_last_agent_message_without_a_message_reports_an_error() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.copy_last_agent_message();
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert!(lines_to_single_string(&cells[0]).contains("No agent message to copy yet."));
+}
+
+#[tokio::test]
+async fn turn_summary_adds_a_tool_time_footer() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 3,
+            tool_time: std::time::Duration::from_millis(1_500),
+            model_time: std::time::Duration::from_millis(250),
+            plan: None,
+            prompt_cache: Default::default(),
+            cautions: Vec::new(),
+            tool_output_tokens: 0,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(
+        lines_to_single_string(&cells[0]).trim_end(),
+        "  3 tool calls · tools 1.50s · model 250ms"
+    );
+}
+
+#[tokio::test]
+async fn turn_summary_counts_finished_plan_steps() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 1,
+            tool_time: std::time::Duration::from_millis(40),
+            model_time: std::time::Duration::from_millis(250),
+            plan: Some(UpdatePlanArgs {
+                explanation: None,
+                plan: vec![
+                    PlanItemArg {
+                        step: "fix the bug".to_string(),
+                        status: StepStatus::Completed,
+                    },
+                    PlanItemArg {
+                        step: "add a test".to_string(),
+                        status: StepStatus::InProgress,
+                    },
+                ],
+            }),
+            prompt_cache: Default::default(),
+            cautions: Vec::new(),
+            tool_output_tokens: 0,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(
+        lines_to_single_string(&cells[0]).trim_end(),
+        "  1 tool call · tools 40ms · model 250ms · plan 1/2 done"
+    );
+}
+
+#[tokio::test]
+async fn turn_summary_reports_prompt_cache_hits() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 1,
+            tool_time: std::time::Duration::from_millis(40),
+            model_time: std::time::Duration::from_millis(250),
+            plan: None,
+            prompt_cache: PromptCacheStats {
+                hits: 2,
+                misses: 1,
+                cached_input_tokens: 1_536,
+                input_tokens: 3_000,
+            },
+            cautions: Vec::new(),
+            tool_output_tokens: 0,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(
+        lines_to_single_string(&cells[0]).trim_end(),
+        "  1 tool call · tools 40ms · model 250ms · cache hits 2/3 (1,536 cached tokens)"
+    );
+}
+
+#[tokio::test]
+async fn turn_summary_without_tool_calls_adds_nothing() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 0,
+            tool_time: std::time::Duration::ZERO,
+            model_time: std::time::Duration::from_millis(900),
+            plan: None,
+            prompt_cache: Default::default(),
+            cautions: Vec::new(),
+            tool_output_tokens: 0,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: None,
+        }),
+    });
+
+    assert!(drain_insert_history(&mut rx).is_empty());
+}
+
+#[tokio::test]
+async fn turn_summary_lists_cautions_below_the_footer() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 1,
+            tool_time: std::time::Duration::from_millis(40),
+            model_time: std::time::Duration::from_millis(250),
+            plan: None,
+            prompt_cache: Default::default(),
+            cautions: vec!["`make release` moved HEAD from 1a2b3c4 to 5d6e7f8".to_string()],
+            tool_output_tokens: 0,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: None,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(
+        lines_to_single_string(&cells[0]).trim_end(),
+        "  1 tool call · tools 40ms · model 250ms\n  ⚠ `make release` moved HEAD from 1a2b3c4 to 5d6e7f8"
+    );
+}
+
+#[tokio::test]
+async fn turn_summary_shows_tool_output_tokens_and_cost() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.handle_codex_event(Event {
+        id: "turn-1".into(),
+        msg: EventMsg::TurnSummary(TurnSummaryEvent {
+            tool_calls: 2,
+            tool_time: std::time::Duration::from_millis(40),
+            model_time: std::time::Duration::from_millis(250),
+            plan: None,
+            prompt_cache: Default::default(),
+            cautions: Vec::new(),
+            tool_output_tokens: 1_250,
+            tool_call_tokens: Vec::new(),
+            token_usage: Default::default(),
+            cost: Some(TurnCost {
+                turn: 0.0123,
+                tool_output: 0.0025,
+                session: 0.0456,
+            }),
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(
+        lines_to_single_string(&cells[0]).trim_end(),
+        "  2 tool calls · tools 40ms · model 250ms · tool output ~1,250 tokens · cost ~0.0123 (session ~0.0456)"
+    );
+}
+
 #[tokio::test]
 async fn unified_exec_wait_after_final_agent_message_snapshot() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
@@ -1958,6 +2463,7 @@ async fn unified_exec_wait_after_final_agent_message_snapshot() {
         id: "turn-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -1992,6 +2498,7 @@ async fn unified_exec_wait_before_streamed_agent_message_snapshot() {
         id: "turn-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -2037,6 +2544,7 @@ async fn unified_exec_wait_status_header_updates_on_late_command_display() {
         call_id: "call-1".to_string(),
         process_id: "proc-1".to_string(),
         stdin: String::new(),
+        source: TerminalInteractionSource::Agent,
     });
 
     assert!(chat.active_cell.is_none());
@@ -2713,6 +3221,8 @@ async fn view_image_tool_call_adds_history_cell() {
         msg: EventMsg::ViewImageToolCall(ViewImageToolCallEvent {
             call_id: "call-image".into(),
             path: image_path,
+            width: 800,
+            height: 600,
         }),
     });
 
@@ -2762,6 +3272,7 @@ async fn interrupted_turn_error_message_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -3491,6 +4002,9 @@ async fn approval_modal_exec_snapshot() -> anyhow::Result<()> {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -3544,6 +4058,9 @@ async fn approval_modal_exec_without_reason_snapshot() -> anyhow::Result<()> {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
@@ -3584,6 +4101,9 @@ async fn approval_modal_exec_multiline_prefix_hides_execpolicy_option_snapshot()
         reason: None,
         proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(command)),
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-multiline-trunc".into(),
@@ -3751,6 +4271,7 @@ async fn interrupt_clears_unified_exec_wait_streak_snapshot() {
         id: "turn-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -3824,6 +4345,7 @@ async fn ui_snapshots_small_heights_task_running() {
         id: "task-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -3855,6 +4377,7 @@ async fn status_widget_and_approval_modal_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     // Provide a deterministic header for the status line.
@@ -3879,6 +4402,9 @@ async fn status_widget_and_approval_modal_snapshot() {
             "hello world".into(),
         ])),
         parsed_cmd: vec![],
+        risk: None,
+        sandbox_denial_output: None,
+        injected_env: Default::default(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -3907,6 +4433,7 @@ async fn status_widget_active_snapshot() {
         id: "task-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     // Provide a deterministic header via a bold reasoning chunk.
@@ -3956,6 +4483,7 @@ async fn mcp_startup_complete_does_not_clear_running_task() {
         id: "task-1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -4048,6 +4576,8 @@ async fn apply_patch_events_emit_history_cells() {
         turn_id: "turn-c1".into(),
         auto_approved: true,
         changes: changes2,
+        timeout_ms: None,
+        summaries: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -4125,6 +4655,8 @@ async fn apply_patch_manual_approval_adjusts_header() {
             turn_id: "turn-c1".into(),
             auto_approved: false,
             changes: apply_changes,
+            timeout_ms: None,
+            summaries: Vec::new(),
         }),
     });
 
@@ -4178,6 +4710,8 @@ async fn apply_patch_manual_flow_snapshot() {
             turn_id: "turn-c1".into(),
             auto_approved: false,
             changes: apply_changes,
+            timeout_ms: None,
+            summaries: Vec::new(),
         }),
     });
     let approved_lines = drain_insert_history(&mut rx)
@@ -4287,6 +4821,8 @@ async fn apply_patch_full_flow_integration_like() {
             turn_id: "turn-call-1".into(),
             auto_approved: false,
             changes: changes2,
+            timeout_ms: None,
+            summaries: Vec::new(),
         }),
     });
     let mut end_changes = HashMap::new();
@@ -4486,6 +5022,144 @@ async fn stream_error_updates_status_indicator() {
     assert_eq!(status.details(), Some(details));
 }
 
+#[tokio::test]
+async fn stream_retry_notes_kept_tool_calls_and_keeps_reconnecting_status() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.bottom_pane.set_task_running(true);
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::StreamError(StreamErrorEvent {
+            message: "Reconnecting... 1/5".to_string(),
+            codex_error_info: Some(CodexErrorInfo::Other),
+            additional_details: None,
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::StreamRetry(StreamRetryEvent {
+            attempt: 1,
+            max_retries: 5,
+            error: "stream closed before response.completed".to_string(),
+            items_preserved: 2,
+            tool_calls_preserved: 2,
+            strategy: StreamRecoveryStrategy::ResumeAfterToolCalls,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one info history cell");
+    let rendered = lines_to_single_string(&cells[0]);
+    assert!(
+        rendered.contains("Stream cut off; resuming after 2 completed tool calls"),
+        "info cell missing content: {rendered}"
+    );
+    let status = chat
+        .bottom_pane
+        .status_widget()
+        .expect("status indicator should be visible");
+    assert_eq!(status.header(), "Reconnecting... 1/5");
+}
+
+#[tokio::test]
+async fn budget_exceeded_warns_and_offers_to_double_the_budget() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::BudgetExceeded(BudgetExceededEvent {
+            call_id: "call-41".to_string(),
+            turn_id: "sub-1".to_string(),
+            budget: ToolBudgetKind::TurnToolCalls,
+            limit: 40,
+            ends_turn: false,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one warning history cell");
+    let rendered = lines_to_single_string(&cells[0]);
+    assert!(
+        rendered.contains("Tool budget exhausted (40 tool calls per turn)"),
+        "warning cell missing content: {rendered}"
+    );
+    let popup = render_bottom_popup(&chat, 80);
+    assert!(
+        popup.contains("Double the budget"),
+        "expected budget prompt: {popup}"
+    );
+
+    chat.handle_key_event(KeyEvent::from(KeyCode::Enter));
+    match rx.try_recv() {
+        Ok(AppEvent::CodexOp(Op::RaiseToolBudget {
+            max_calls_per_turn: Some(80),
+            max_calls_per_session: None,
+            max_exec_ms_per_turn: None,
+        })) => {}
+        other => panic!("expected Op::RaiseToolBudget, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn provider_switch_warns_and_follows_the_new_model() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::ProviderSwitched(ProviderSwitchedEvent {
+            from: ProviderModel {
+                provider: "openai".to_string(),
+                model: "gpt-5".to_string(),
+            },
+            to: ProviderModel {
+                provider: "azure".to_string(),
+                model: "gpt-4.1".to_string(),
+            },
+            reason: ProviderSwitchReason::RateLimited,
+            retried_in_turn: true,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one warning history cell");
+    let rendered = lines_to_single_string(&cells[0]);
+    assert!(
+        rendered.contains("Switched from openai (gpt-5) to azure (gpt-4.1)"),
+        "warning cell missing content: {rendered}"
+    );
+    assert_eq!(chat.current_model(), "gpt-4.1");
+}
+
+#[tokio::test]
+async fn model_changed_event_adds_info_cell_and_follows_the_new_model() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::ModelChanged(ModelChangedEvent {
+            from: ProviderModel {
+                provider: "openai".to_string(),
+                model: "gpt-5".to_string(),
+            },
+            to: ProviderModel {
+                provider: "azure".to_string(),
+                model: "gpt-4.1".to_string(),
+            },
+            model_context_window: Some(128_000),
+            compacts_history: true,
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one info history cell");
+    let rendered = lines_to_single_string(&cells[0]);
+    assert!(
+        rendered.contains("Model changed to gpt-4.1 (azure)"),
+        "info cell missing content: {rendered}"
+    );
+    assert!(
+        rendered.contains("compacting the history"),
+        "info cell missing compaction hint: {rendered}"
+    );
+    assert_eq!(chat.current_model(), "gpt-4.1");
+}
+
 #[tokio::test]
 async fn warning_event_adds_warning_history_cell() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
@@ -4512,6 +5186,7 @@ async fn stream_recovery_restores_previous_status_header() {
         id: "task".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     drain_insert_history(&mut rx);
@@ -4549,6 +5224,7 @@ async fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
 
@@ -4716,8 +5392,10 @@ async fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             command: command.clone(),
             cwd: cwd.clone(),
             parsed_cmd: parsed_cmd.clone(),
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
+            timeout_ms: None,
+            injected_env: Default::default(),
         }),
     });
     chat.handle_codex_event(Event {
@@ -4729,20 +5407,30 @@ async fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             command,
             cwd,
             parsed_cmd,
-            source: ExecCommandSource::Agent,
+            source: ExecCommandSource::ModelShell,
             interaction_input: None,
             stdout: String::new(),
             stderr: String::new(),
             aggregated_output: String::new(),
             exit_code: 0,
+            outcome: None,
             duration: std::time::Duration::from_millis(16000),
+            estimated: false,
+            suspended: false,
+            sanitized_bytes: None,
+            delta_bytes_emitted: 0,
+            deltas_incomplete: false,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            error: None,
         }),
     });
     chat.handle_codex_event(Event {
         id: "t1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -4790,6 +5478,7 @@ async fn chatwidget_markdown_code_blocks_vt100_snapshot() {
         id: "t1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     // Build a vt100 visual from the history insertions only (no UI overlay)
@@ -4879,6 +5568,7 @@ async fn chatwidget_tall() {
         id: "t1".into(),
         msg: EventMsg::TurnStarted(TurnStartedEvent {
             model_context_window: None,
+            tools: None,
         }),
     });
     for i in 0..30 {
@@ -0,0 +1,74 @@
+//! Copying text out of the TUI.
+//!
+//! The system clipboard is tried first. When no clipboard provider is
+//! reachable, which is the usual case over SSH, the text is handed to the
+//! terminal with an OSC 52 escape so the terminal on the user's machine can
+//! place it on its own clipboard.
+
+use std::io::Write;
+
+use base64::Engine as _;
+
+/// How [`copy_text`] delivered the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CopyMethod {
+    Clipboard,
+    Osc52,
+}
+
+pub(crate) fn copy_text(text: &str) -> Result<CopyMethod, String> {
+    match copy_to_system_clipboard(text) {
+        Ok(()) => Ok(CopyMethod::Clipboard),
+        Err(clipboard_err) => write_osc52(text)
+            .map(|()| CopyMethod::Osc52)
+            .map_err(|err| format!("{clipboard_err}; OSC 52 fallback failed: {err}")),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn copy_to_system_clipboard(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| e.to_string())
+}
+
+/// Android/Termux does not support arboard.
+#[cfg(target_os = "android")]
+fn copy_to_system_clipboard(_text: &str) -> Result<(), String> {
+    Err("the clipboard is unsupported on Android".to_string())
+}
+
+fn write_osc52(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
+/// The escape asking the terminal to put `text` on the clipboard. Inside tmux
+/// the sequence is wrapped in a passthrough so it reaches the outer terminal.
+fn osc52_sequence(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let sequence = format!("\x1b]52;c;{encoded}\x07");
+    if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;\x1b{sequence}\x1b\\")
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn osc52_sequence_carries_base64_text() {
+        let sequence = osc52_sequence("hello");
+        let inner = "\x1b]52;c;aGVsbG8=\x07";
+        if std::env::var_os("TMUX").is_some() {
+            assert_eq!(sequence, format!("\x1bPtmux;\x1b{inner}\x1b\\"));
+        } else {
+            assert_eq!(sequence, inner);
+        }
+    }
+}
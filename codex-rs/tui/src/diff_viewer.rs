@@ -0,0 +1,732 @@
+//! Full-screen viewer for the current turn's diff (`Ctrl+O`).
+//!
+//! The left pane lists the changed files with their line counts; the right pane shows the hunks
+//! of the selected file, with the changed words of each replaced line highlighted. `App` hands
+//! the viewer the latest `TurnDiffEvent` diff on every draw, so it follows the turn as it edits
+//! files; the diff is only reparsed when it changes.
+
+use std::io::Result;
+use std::ops::Range;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+
+use crate::clipboard_copy::copy_text;
+use crate::key_hint;
+use crate::key_hint::KeyBinding;
+use crate::pager_overlay::render_key_hints;
+use crate::tui;
+use crate::tui::TuiEvent;
+
+const KEY_UP: KeyBinding = key_hint::plain(KeyCode::Up);
+const KEY_DOWN: KeyBinding = key_hint::plain(KeyCode::Down);
+const KEY_K: KeyBinding = key_hint::plain(KeyCode::Char('k'));
+const KEY_J: KeyBinding = key_hint::plain(KeyCode::Char('j'));
+const KEY_PAGE_UP: KeyBinding = key_hint::plain(KeyCode::PageUp);
+const KEY_PAGE_DOWN: KeyBinding = key_hint::plain(KeyCode::PageDown);
+const KEY_SPACE: KeyBinding = key_hint::plain(KeyCode::Char(' '));
+const KEY_LEFT: KeyBinding = key_hint::plain(KeyCode::Left);
+const KEY_RIGHT: KeyBinding = key_hint::plain(KeyCode::Right);
+const KEY_N: KeyBinding = key_hint::plain(KeyCode::Char('n'));
+const KEY_P: KeyBinding = key_hint::plain(KeyCode::Char('p'));
+const KEY_Y: KeyBinding = key_hint::plain(KeyCode::Char('y'));
+const KEY_Q: KeyBinding = key_hint::plain(KeyCode::Char('q'));
+const KEY_ESC: KeyBinding = key_hint::plain(KeyCode::Esc);
+const KEY_CTRL_C: KeyBinding = key_hint::ctrl(KeyCode::Char('c'));
+const KEY_CTRL_O: KeyBinding = key_hint::ctrl(KeyCode::Char('o'));
+
+const DIFF_VIEWER_KEY_HINTS: &[(&[KeyBinding], &str)] = &[
+    (&[KEY_LEFT, KEY_RIGHT], "file"),
+    (&[KEY_N, KEY_P], "hunk"),
+    (&[KEY_UP, KEY_DOWN], "scroll"),
+    (&[KEY_Y], "copy file diff"),
+    (&[KEY_Q], "close"),
+];
+
+/// Widest the file list gets; it never takes more than a third of the screen.
+const FILE_LIST_MAX_WIDTH: u16 = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HunkLine {
+    Context(String),
+    Insert(String),
+    Delete(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk {
+    header: String,
+    lines: Vec<HunkLine>,
+}
+
+/// One file's section of a unified diff.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FileDiff {
+    /// `None` for an added file.
+    old_path: Option<String>,
+    /// `None` for a deleted file.
+    new_path: Option<String>,
+    binary: bool,
+    hunks: Vec<Hunk>,
+    added: usize,
+    removed: usize,
+    /// The file's section of the diff as received, for copying.
+    raw: String,
+}
+
+impl FileDiff {
+    fn status(&self) -> char {
+        match (&self.old_path, &self.new_path) {
+            (None, Some(_)) => 'A',
+            (Some(_), None) => 'D',
+            (Some(old), Some(new)) if old != new => 'R',
+            _ => 'M',
+        }
+    }
+
+    fn display_path(&self) -> String {
+        match (&self.old_path, &self.new_path) {
+            (Some(old), Some(new)) if old != new => format!("{old} → {new}"),
+            (_, Some(path)) | (Some(path), None) => path.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn stats_spans(&self) -> Vec<Span<'static>> {
+        if self.binary {
+            return vec!["binary".dim()];
+        }
+        vec![
+            format!("+{}", self.added).green(),
+            " ".into(),
+            format!("-{}", self.removed).red(),
+        ]
+    }
+}
+
+/// Splits a unified diff as produced by the turn diff tracker into files.
+/// Renames show up as differing `a/` and `b/` paths (or `rename from`/`rename
+/// to` lines) and binary files as a `Binary files ...` line without hunks.
+fn parse_unified_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut in_hunk = false;
+    for raw_line in diff.split_inclusive('\n') {
+        let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let (old, new) = parse_git_header_paths(paths);
+            files.push(FileDiff {
+                old_path: Some(old),
+                new_path: Some(new),
+                ..FileDiff::default()
+            });
+            in_hunk = false;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        file.raw.push_str(raw_line);
+
+        if line.starts_with("@@") {
+            file.hunks.push(Hunk {
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+            in_hunk = true;
+        } else if in_hunk {
+            let Some(hunk) = file.hunks.last_mut() else {
+                continue;
+            };
+            if let Some(text) = line.strip_prefix('+') {
+                file.added += 1;
+                hunk.lines.push(HunkLine::Insert(text.to_string()));
+            } else if let Some(text) = line.strip_prefix('-') {
+                file.removed += 1;
+                hunk.lines.push(HunkLine::Delete(text.to_string()));
+            } else if let Some(text) = line.strip_prefix(' ') {
+                hunk.lines.push(HunkLine::Context(text.to_string()));
+            } else if line.is_empty() {
+                hunk.lines.push(HunkLine::Context(String::new()));
+            }
+            // `\ No newline at end of file` is not shown.
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            file.old_path = header_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file.new_path = header_path(path, "b/");
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.new_path = Some(path.to_string());
+        } else if line.starts_with("Binary files ") {
+            file.binary = true;
+        }
+    }
+    files
+}
+
+fn parse_git_header_paths(paths: &str) -> (String, String) {
+    paths
+        .strip_prefix("a/")
+        .and_then(|rest| rest.split_once(" b/"))
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .unwrap_or_else(|| (paths.to_string(), paths.to_string()))
+}
+
+fn header_path(path: &str, prefix: &str) -> Option<String> {
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// Splits a line into identifiers, runs of whitespace, and single punctuation
+/// characters, so highlighted changes start and end on token boundaries.
+fn tokenize(text: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<Class> = None;
+    for (i, c) in text.char_indices() {
+        let current = class(c);
+        let continues = matches!(&prev, Some(p) if *p == current && current != Class::Other);
+        if !continues && i > start {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        prev = Some(current);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Byte ranges of `old` and `new` that differ once the tokens they share at
+/// the start and end are set aside. `None` when the lines have nothing in
+/// common, in which case highlighting the whole line would add nothing.
+fn changed_ranges(old: &str, new: &str) -> Option<(Range<usize>, Range<usize>)> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let prefix = old_tokens
+        .iter()
+        .zip(&new_tokens)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_tokens.len().min(new_tokens.len()) - prefix;
+    let suffix = old_tokens
+        .iter()
+        .rev()
+        .zip(new_tokens.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if (prefix == 0 && suffix == 0) || old == new {
+        return None;
+    }
+    let range = |tokens: &[&str], len: usize| {
+        let start: usize = tokens[..prefix].iter().map(|t| t.len()).sum();
+        let end: usize = len
+            - tokens[tokens.len() - suffix..]
+                .iter()
+                .map(|t| t.len())
+                .sum::<usize>();
+        start..end
+    };
+    Some((range(&old_tokens, old.len()), range(&new_tokens, new.len())))
+}
+
+fn diff_line(
+    sign: &'static str,
+    text: &str,
+    style: Style,
+    emphasis: Option<Range<usize>>,
+) -> Line<'static> {
+    let expand = |s: &str| s.replace('\t', "    ");
+    let mut spans = vec![Span::styled(sign, style)];
+    match emphasis {
+        Some(range) if !range.is_empty() => {
+            spans.push(Span::styled(expand(&text[..range.start]), style));
+            spans.push(Span::styled(
+                expand(&text[range.clone()]),
+                style.add_modifier(Modifier::REVERSED),
+            ));
+            spans.push(Span::styled(expand(&text[range.end..]), style));
+        }
+        _ => spans.push(Span::styled(expand(text), style)),
+    }
+    Line::from(spans)
+}
+
+fn push_hunk_lines(hunk: &Hunk, out: &mut Vec<Line<'static>>) {
+    let add = Style::default().green();
+    let del = Style::default().red();
+    let lines = &hunk.lines;
+    let mut i = 0;
+    while i < lines.len() {
+        let HunkLine::Delete(_) = &lines[i] else {
+            out.push(match &lines[i] {
+                HunkLine::Insert(text) => diff_line("+", text, add, None),
+                HunkLine::Context(text) | HunkLine::Delete(text) => {
+                    diff_line(" ", text, Style::default(), None)
+                }
+            });
+            i += 1;
+            continue;
+        };
+        // A run of deletions directly followed by as many insertions reads
+        // as lines being replaced: pair them up and highlight what changed.
+        let deletes_end = i + lines[i..]
+            .iter()
+            .take_while(|l| matches!(l, HunkLine::Delete(_)))
+            .count();
+        let inserts_end = deletes_end
+            + lines[deletes_end..]
+                .iter()
+                .take_while(|l| matches!(l, HunkLine::Insert(_)))
+                .count();
+        let deletes = &lines[i..deletes_end];
+        let inserts = &lines[deletes_end..inserts_end];
+        let paired = deletes.len() == inserts.len();
+        let ranges: Vec<Option<(Range<usize>, Range<usize>)>> = deletes
+            .iter()
+            .zip(inserts)
+            .map(|pair| match pair {
+                (HunkLine::Delete(old), HunkLine::Insert(new)) if paired => {
+                    changed_ranges(old, new)
+                }
+                _ => None,
+            })
+            .collect();
+        for (index, line) in deletes.iter().enumerate() {
+            if let HunkLine::Delete(old) = line {
+                let emphasis = ranges.get(index).cloned().flatten().map(|(old, _)| old);
+                out.push(diff_line("-", old, del, emphasis));
+            }
+        }
+        for (index, line) in inserts.iter().enumerate() {
+            if let HunkLine::Insert(new) = line {
+                let emphasis = ranges.get(index).cloned().flatten().map(|(_, new)| new);
+                out.push(diff_line("+", new, add, emphasis));
+            }
+        }
+        i = inserts_end;
+    }
+}
+
+pub(crate) struct DiffViewer {
+    diff: String,
+    files: Vec<FileDiff>,
+    selected: usize,
+    hunk: usize,
+    scroll: usize,
+    last_body_height: usize,
+    status: Option<String>,
+    is_done: bool,
+}
+
+impl DiffViewer {
+    pub(crate) fn new(diff: &str) -> Self {
+        Self {
+            diff: diff.to_string(),
+            files: parse_unified_diff(diff),
+            selected: 0,
+            hunk: 0,
+            scroll: 0,
+            last_body_height: 0,
+            status: None,
+            is_done: false,
+        }
+    }
+
+    /// Replaces the diff with a newer one for the same turn, keeping the
+    /// selected file when it is still part of it.
+    pub(crate) fn set_diff(&mut self, diff: &str) {
+        if self.diff == diff {
+            return;
+        }
+        let selected_path = self.files.get(self.selected).map(FileDiff::display_path);
+        self.diff = diff.to_string();
+        self.files = parse_unified_diff(diff);
+        match selected_path
+            .and_then(|path| self.files.iter().position(|f| f.display_path() == path))
+        {
+            Some(index) => {
+                self.selected = index;
+                let hunks = self.files[index].hunks.len();
+                self.hunk = self.hunk.min(hunks.saturating_sub(1));
+            }
+            None => self.select_file(0),
+        }
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) => {
+                self.handle_key_event(key_event);
+                tui.frame_requester().schedule_frame();
+                Ok(())
+            }
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        self.status = None;
+        let page = self.last_body_height.max(1);
+        match key_event {
+            e if KEY_Q.is_press(e)
+                || KEY_ESC.is_press(e)
+                || KEY_CTRL_C.is_press(e)
+                || KEY_CTRL_O.is_press(e) =>
+            {
+                self.is_done = true;
+            }
+            e if KEY_RIGHT.is_press(e) => {
+                self.select_file((self.selected + 1).min(self.files.len().saturating_sub(1)));
+            }
+            e if KEY_LEFT.is_press(e) => self.select_file(self.selected.saturating_sub(1)),
+            e if KEY_N.is_press(e) => self.next_hunk(),
+            e if KEY_P.is_press(e) => self.previous_hunk(),
+            e if KEY_DOWN.is_press(e) || KEY_J.is_press(e) => self.scroll += 1,
+            e if KEY_UP.is_press(e) || KEY_K.is_press(e) => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            e if KEY_PAGE_DOWN.is_press(e) || KEY_SPACE.is_press(e) => self.scroll += page,
+            e if KEY_PAGE_UP.is_press(e) => self.scroll = self.scroll.saturating_sub(page),
+            e if KEY_Y.is_press(e) => self.copy_selected_file(),
+            _ => {}
+        }
+    }
+
+    fn select_file(&mut self, index: usize) {
+        self.selected = index;
+        self.hunk = 0;
+        self.scroll = 0;
+    }
+
+    fn next_hunk(&mut self) {
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+        if self.hunk + 1 < file.hunks.len() {
+            self.hunk += 1;
+            self.scroll = self.hunk_offsets()[self.hunk];
+        } else if self.selected + 1 < self.files.len() {
+            self.select_file(self.selected + 1);
+        }
+    }
+
+    fn previous_hunk(&mut self) {
+        if self.hunk > 0 {
+            self.hunk -= 1;
+            self.scroll = self.hunk_offsets()[self.hunk];
+        } else if self.selected > 0 {
+            self.select_file(self.selected - 1);
+            let last = self.files[self.selected].hunks.len().saturating_sub(1);
+            self.hunk = last;
+            self.scroll = self.hunk_offsets().get(last).copied().unwrap_or(0);
+        }
+    }
+
+    fn copy_selected_file(&mut self) {
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+        self.status = Some(match copy_text(&file.raw) {
+            Ok(_) => format!("Copied the diff of {}", file.display_path()),
+            Err(err) => format!("Could not copy to the clipboard: {err}"),
+        });
+    }
+
+    /// Lines of the right pane for the selected file.
+    fn file_lines(&self) -> (Vec<Line<'static>>, Vec<usize>) {
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        let Some(file) = self.files.get(self.selected) else {
+            return (lines, offsets);
+        };
+        let mut header = Line::from(vec![file.display_path().bold(), " ".into()]);
+        header.extend(file.stats_spans());
+        lines.push(header);
+        if file.binary {
+            lines.push("Binary file; contents not shown".dim().into());
+        }
+        for hunk in &file.hunks {
+            offsets.push(lines.len());
+            lines.push(hunk.header.clone().cyan().into());
+            push_hunk_lines(hunk, &mut lines);
+        }
+        (lines, offsets)
+    }
+
+    fn hunk_offsets(&self) -> Vec<usize> {
+        self.file_lines().1
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        if area.height < 3 || area.width < 10 {
+            return;
+        }
+        let (added, removed) = self
+            .files
+            .iter()
+            .fold((0, 0), |(a, r), f| (a + f.added, r + f.removed));
+        let files = self.files.len();
+        let title = Line::from(vec![
+            "Turn diff".bold(),
+            format!(" · {files} {}  ", if files == 1 { "file" } else { "files" }).dim(),
+            format!("+{added}").green(),
+            " ".into(),
+            format!("-{removed}").red(),
+        ]);
+        title.render_ref(Rect::new(area.x, area.y, area.width, 1), buf);
+
+        let body = Rect::new(area.x, area.y + 1, area.width, area.height - 2);
+        let footer = Rect::new(area.x, area.y + area.height - 1, area.width, 1);
+        match &self.status {
+            Some(status) => Line::from(format!(" {status}"))
+                .dim()
+                .render_ref(footer, buf),
+            None => render_key_hints(footer, buf, DIFF_VIEWER_KEY_HINTS),
+        }
+
+        if self.files.is_empty() {
+            Paragraph::new("No changes in this turn yet.".dim()).render(body, buf);
+            return;
+        }
+
+        let list_width = FILE_LIST_MAX_WIDTH.min(body.width / 3);
+        let list = Rect::new(body.x, body.y, list_width, body.height);
+        let separator = Rect::new(body.x + list_width, body.y, 1, body.height);
+        let hunks = Rect::new(
+            separator.x + 2,
+            body.y,
+            body.width.saturating_sub(list_width + 2),
+            body.height,
+        );
+        self.render_file_list(list, buf);
+        for y in separator.y..separator.y + separator.height {
+            buf[(separator.x, y)]
+                .set_symbol("│")
+                .set_style(Style::default().dim());
+        }
+
+        let height = hunks.height as usize;
+        self.last_body_height = height;
+        let (lines, _) = self.file_lines();
+        self.scroll = self.scroll.min(lines.len().saturating_sub(height));
+        let visible: Vec<Line<'static>> =
+            lines.into_iter().skip(self.scroll).take(height).collect();
+        Paragraph::new(visible).render(hunks, buf);
+    }
+
+    fn render_file_list(&self, area: Rect, buf: &mut Buffer) {
+        let height = area.height as usize;
+        let first = self.selected.saturating_sub(height.saturating_sub(1));
+        for (row, (index, file)) in self
+            .files
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .enumerate()
+        {
+            let status = match file.status() {
+                'A' => "A".green(),
+                'D' => "D".red(),
+                'R' => "R".magenta(),
+                _ => "M".cyan(),
+            };
+            let mut line = Line::from(vec![
+                status,
+                " ".into(),
+                file.display_path().into(),
+                " ".into(),
+            ]);
+            line.extend(file.stats_spans());
+            if index == self.selected {
+                line = line.reversed();
+            }
+            line.render_ref(Rect::new(area.x, area.y + row as u16, area.width, 1), buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    let total = add(1, 2);
++    let total = add(1, 3);
+ }
+@@ -10,2 +10,3 @@
+ // end
++// more
+diff --git a/old.txt b/new.txt
+index 3333333..4444444 100644
+--- a/old.txt
++++ b/new.txt
+@@ -1 +1 @@
+-hello
++goodbye
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..5555555
+--- /dev/null
++++ b/logo.png
+Binary files differ
+";
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn parses_files_stats_renames_and_binary_markers() {
+        let files = parse_unified_diff(DIFF);
+        let summary: Vec<(char, String, usize, usize, bool, usize)> = files
+            .iter()
+            .map(|f| {
+                (
+                    f.status(),
+                    f.display_path(),
+                    f.added,
+                    f.removed,
+                    f.binary,
+                    f.hunks.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('M', "src/lib.rs".to_string(), 2, 1, false, 2),
+                ('R', "old.txt → new.txt".to_string(), 1, 1, false, 1),
+                ('A', "logo.png".to_string(), 0, 0, true, 0),
+            ]
+        );
+        assert!(files[1].raw.starts_with("diff --git a/old.txt b/new.txt\n"));
+        assert!(files[1].raw.ends_with("+goodbye\n"));
+    }
+
+    #[test]
+    fn highlights_changed_tokens_of_replaced_lines() {
+        let old = "    let total = add(1, 2);";
+        let new = "    let total = add(1, 3);";
+        let (old_range, new_range) = changed_ranges(old, new).expect("lines share tokens");
+        assert_eq!(&old[old_range], "2");
+        assert_eq!(&new[new_range], "3");
+
+        let (old_range, new_range) =
+            changed_ranges("let count = 1;", "let total_count = 1;").expect("shared tokens");
+        assert_eq!(old_range, 4..9);
+        assert_eq!(new_range, 4..15);
+        assert_eq!(changed_ranges("hello", "goodbye"), None);
+    }
+
+    #[test]
+    fn navigates_hunks_across_files() {
+        let mut viewer = DiffViewer::new(DIFF);
+        viewer.handle_key_event(key(KeyCode::Char('n')));
+        assert_eq!((viewer.selected, viewer.hunk), (0, 1));
+        assert_eq!(viewer.scroll, viewer.hunk_offsets()[1]);
+
+        viewer.handle_key_event(key(KeyCode::Char('n')));
+        assert_eq!((viewer.selected, viewer.hunk, viewer.scroll), (1, 0, 0));
+
+        viewer.handle_key_event(key(KeyCode::Char('p')));
+        assert_eq!((viewer.selected, viewer.hunk), (0, 1));
+
+        viewer.handle_key_event(key(KeyCode::Right));
+        viewer.handle_key_event(key(KeyCode::Right));
+        viewer.handle_key_event(key(KeyCode::Right));
+        assert_eq!(viewer.selected, 2);
+
+        viewer.handle_key_event(key(KeyCode::Char('q')));
+        assert!(viewer.is_done());
+    }
+
+    #[test]
+    fn live_updates_keep_the_selected_file() {
+        let mut viewer = DiffViewer::new(DIFF);
+        viewer.handle_key_event(key(KeyCode::Right));
+
+        let (_, rest) = DIFF
+            .split_once("diff --git a/old.txt")
+            .expect("second file");
+        viewer.set_diff(&format!("diff --git a/old.txt{rest}"));
+        assert_eq!(viewer.files.len(), 2);
+        assert_eq!(viewer.selected, 0);
+        assert_eq!(viewer.files[0].display_path(), "old.txt → new.txt");
+
+        viewer.set_diff("");
+        assert_eq!(viewer.files.len(), 0);
+    }
+
+    #[test]
+    fn renders_file_list_and_selected_hunks() {
+        let mut viewer = DiffViewer::new(DIFF);
+        let area = Rect::new(0, 0, 80, 10);
+        let mut buf = Buffer::empty(area);
+        viewer.render(area, &mut buf);
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(rows[0], "Turn diff · 3 files  +3 -2");
+        assert!(rows[1].starts_with("M src/lib.rs +2 -1"));
+        assert!(rows[2].starts_with("R old.txt → new.txt +1 -1"));
+        assert!(rows[3].starts_with("A logo.png binary"));
+        assert!(rows[4].ends_with("│ -    let total = add(1, 2);"));
+        assert!(rows[5].ends_with("│ +    let total = add(1, 3);"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+/// Completed lines kept for a running command. Only the last few are shown;
+/// the rest keep the view stable while lines wrap differently on resize.
+const LIVE_OUTPUT_MAX_LINES: usize = 64;
+
+/// Output buffered between frames beyond which it is applied right away, so
+/// a command that floods stdout cannot grow the buffer without bound.
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+
+/// Tail of a running command's output, rebuilt from its output deltas.
+///
+/// Deltas are only buffered when they arrive and are applied once per frame
+/// by [`LiveOutput::apply_pending`], so a fast producer costs one redraw per
+/// frame rather than one per chunk.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LiveOutput {
+    lines: VecDeque<String>,
+    /// The line being written, not yet ended by `\n`.
+    current: String,
+    /// A `\r` moved the cursor back to the start of `current`; the next
+    /// character overwrites it, which is how progress bars redraw in place.
+    carriage_return: bool,
+    pending: String,
+}
+
+impl LiveOutput {
+    pub(crate) fn push(&mut self, chunk: &str) {
+        self.pending.push_str(chunk);
+        if self.pending.len() > MAX_PENDING_BYTES {
+            self.apply_pending();
+        }
+    }
+
+    /// Applies the output received since the last call. Returns whether
+    /// anything changed.
+    pub(crate) fn apply_pending(&mut self) -> bool {
+        if self.pending.is_empty() {
+            return false;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        for ch in pending.chars() {
+            match ch {
+                '\n' => {
+                    self.carriage_return = false;
+                    self.lines.push_back(std::mem::take(&mut self.current));
+                    if self.lines.len() > LIVE_OUTPUT_MAX_LINES {
+                        self.lines.pop_front();
+                    }
+                }
+                '\r' => self.carriage_return = true,
+                ch => {
+                    if self.carriage_return {
+                        self.carriage_return = false;
+                        self.current.clear();
+                    }
+                    self.current.push(ch);
+                }
+            }
+        }
+        // Hand the allocation back for the next frame's deltas.
+        self.pending = pending;
+        self.pending.clear();
+        true
+    }
+
+    /// The last `max` lines of output, including the unfinished one.
+    pub(crate) fn tail(&self, max: usize) -> Vec<&str> {
+        let current = (!self.current.is_empty()).then_some(self.current.as_str());
+        let lines: Vec<&str> = self
+            .lines
+            .iter()
+            .map(String::as_str)
+            .chain(current)
+            .collect();
+        lines[lines.len().saturating_sub(max)..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn output_is_applied_per_frame_and_carriage_returns_redraw_in_place() {
+        let mut output = LiveOutput::default();
+        output.push("Compiling foo\n");
+        output.push("[=>   ] 10%\r");
+        assert_eq!(output.tail(5), Vec::<&str>::new());
+
+        assert!(output.apply_pending());
+        assert_eq!(output.tail(5), vec!["Compiling foo", "[=>   ] 10%"]);
+
+        output.push("[====>] 90%\r\n");
+        output.push("done\r\n");
+        assert!(output.apply_pending());
+        assert!(!output.apply_pending());
+        assert_eq!(output.tail(5), vec!["Compiling foo", "[====>] 90%", "done"]);
+        assert_eq!(output.tail(1), vec!["done"]);
+    }
+
+    #[test]
+    fn keeps_a_bounded_tail() {
+        let mut output = LiveOutput::default();
+        for i in 0..LIVE_OUTPUT_MAX_LINES * 2 {
+            output.push(&format!("line {i}\n"));
+        }
+        output.apply_pending();
+        assert_eq!(output.lines.len(), LIVE_OUTPUT_MAX_LINES);
+        assert_eq!(
+            output.tail(1),
+            vec![format!("line {}", LIVE_OUTPUT_MAX_LINES * 2 - 1)]
+        );
+
+        output.push(&"x".repeat(MAX_PENDING_BYTES + 1));
+        assert!(output.pending.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+mod live_output;
 mod model;
 mod render;
 
@@ -7,6 +8,7 @@ pub(crate) use model::ExecCall;
 pub(crate) use model::ExecCell;
 pub(crate) use render::OutputLinesParams;
 pub(crate) use render::TOOL_CALL_MAX_LINES;
+pub(crate) use render::TranscriptOutputView;
 pub(crate) use render::new_active_exec_command;
 pub(crate) use render::output_lines;
 pub(crate) use render::spinner;
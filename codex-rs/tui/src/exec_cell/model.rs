@@ -1,9 +1,16 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
 
+use codex_core::protocol::ExecApproval;
 use codex_core::protocol::ExecCommandSource;
 use codex_protocol::parse_command::ParsedCommand;
 
+use super::live_output::LiveOutput;
+use crate::exec_command::strip_bash_lc_and_escape;
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct CommandOutput {
     pub(crate) exit_code: i32,
@@ -11,6 +18,12 @@ pub(crate) struct CommandOutput {
     pub(crate) aggregated_output: String,
     /// The formatted output of the command, as seen by the model.
     pub(crate) formatted_output: String,
+    /// File holding the full output, when it was too large for the model.
+    pub(crate) saved_output_path: Option<PathBuf>,
+    /// How the call was cleared to run, when core reported it.
+    pub(crate) approval: Option<ExecApproval>,
+    /// Whether the call was cut short instead of exiting on its own.
+    pub(crate) interrupted: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +42,8 @@ pub(crate) struct ExecCall {
 pub(crate) struct ExecCell {
     pub(crate) calls: Vec<ExecCall>,
     animations_enabled: bool,
+    /// Output streamed so far by calls that are still running, by call id.
+    live_output: HashMap<String, LiveOutput>,
 }
 
 impl ExecCell {
@@ -36,6 +51,7 @@ impl ExecCell {
         Self {
             calls: vec![call],
             animations_enabled,
+            live_output: HashMap::new(),
         }
     }
 
@@ -61,6 +77,7 @@ impl ExecCell {
             Some(Self {
                 calls: [self.calls.clone(), vec![call]].concat(),
                 animations_enabled: self.animations_enabled,
+                live_output: self.live_output.clone(),
             })
         } else {
             None
@@ -78,6 +95,7 @@ impl ExecCell {
             call.duration = Some(duration);
             call.start_time = None;
         }
+        self.live_output.remove(call_id);
     }
 
     pub(crate) fn should_flush(&self) -> bool {
@@ -97,9 +115,13 @@ impl ExecCell {
                     exit_code: 1,
                     formatted_output: String::new(),
                     aggregated_output: String::new(),
+                    saved_output_path: None,
+                    approval: None,
+                    interrupted: true,
                 });
             }
         }
+        self.live_output.clear();
     }
 
     pub(crate) fn is_exploring_cell(&self) -> bool {
@@ -125,18 +147,65 @@ impl ExecCell {
         self.calls.iter()
     }
 
+    /// The full output of every finished call, each under its command line,
+    /// for viewing outside the TUI.
+    pub(crate) fn output_text(&self) -> String {
+        self.calls
+            .iter()
+            .filter_map(|call| {
+                let output = call.output.as_ref()?;
+                Some(format!(
+                    "$ {}\n{}",
+                    strip_bash_lc_and_escape(&call.command),
+                    output.aggregated_output
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The file the full output was saved to, when a call's output was too
+    /// large to send to the model whole.
+    pub(crate) fn saved_output_path(&self) -> Option<&Path> {
+        self.calls.iter().find_map(|call| {
+            call.output
+                .as_ref()
+                .and_then(|output| output.saved_output_path.as_deref())
+        })
+    }
+
+    /// Buffers output streamed by a running call. It shows up once
+    /// [`ExecCell::apply_pending_output`] runs for the next frame.
     pub(crate) fn append_output(&mut self, call_id: &str, chunk: &str) -> bool {
         if chunk.is_empty() {
             return false;
         }
-        let Some(call) = self.calls.iter_mut().rev().find(|c| c.call_id == call_id) else {
+        if !self
+            .calls
+            .iter()
+            .any(|c| c.call_id == call_id && c.output.is_none())
+        {
             return false;
-        };
-        let output = call.output.get_or_insert_with(CommandOutput::default);
-        output.aggregated_output.push_str(chunk);
+        }
+        self.live_output
+            .entry(call_id.to_string())
+            .or_default()
+            .push(chunk);
         true
     }
 
+    /// Applies output buffered since the last frame. Returns whether the cell
+    /// needs to be redrawn.
+    pub(crate) fn apply_pending_output(&mut self) -> bool {
+        self.live_output
+            .values_mut()
+            .fold(false, |changed, output| output.apply_pending() || changed)
+    }
+
+    pub(crate) fn live_output(&self, call_id: &str) -> Option<&LiveOutput> {
+        self.live_output.get(call_id)
+    }
+
     pub(super) fn is_exploring_call(call: &ExecCall) -> bool {
         !matches!(call.source, ExecCommandSource::UserShell)
             && !call.parsed.is_empty()
@@ -157,6 +226,6 @@ impl ExecCall {
     }
 
     pub(crate) fn is_unified_exec_interaction(&self) -> bool {
-        matches!(self.source, ExecCommandSource::UnifiedExecInteraction)
+        matches!(self.source, ExecCommandSource::UserUnifiedExecInput)
     }
 }
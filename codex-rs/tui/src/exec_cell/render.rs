@@ -15,6 +15,7 @@ use crate::wrapping::word_wrap_lines;
 use codex_ansi_escape::ansi_escape_line;
 use codex_common::elapsed::format_duration;
 use codex_core::bash::extract_bash_command;
+use codex_core::protocol::ExecApproval;
 use codex_core::protocol::ExecCommandSource;
 use codex_protocol::parse_command::ParsedCommand;
 use itertools::Itertools;
@@ -26,7 +27,13 @@ use unicode_width::UnicodeWidthStr;
 
 pub(crate) const TOOL_CALL_MAX_LINES: usize = 5;
 const USER_SHELL_TOOL_CALL_MAX_LINES: usize = 50;
+/// Output lines an exec cell keeps when collapsed in the transcript overlay.
+const TRANSCRIPT_COLLAPSED_OUTPUT_LINES: usize = 5;
 const MAX_INTERACTION_PREVIEW_CHARS: usize = 80;
+/// Columns the command keeps on the header line before the status column is
+/// dropped to make room for it.
+const MIN_COMMAND_WIDTH_WITH_STATUS: usize = 24;
+const STATUS_COLUMN_GAP: usize = 2;
 
 pub(crate) struct OutputLinesParams {
     pub(crate) line_limit: usize,
@@ -88,6 +95,37 @@ fn summarize_interaction_input(input: &str) -> String {
     preview
 }
 
+/// The right-aligned summary of a finished call: how it was approved, how it
+/// exited and how long it took.
+fn status_column_spans(call: &ExecCall) -> Option<Vec<Span<'static>>> {
+    if call.is_unified_exec_interaction() {
+        return None;
+    }
+    let output = call.output.as_ref()?;
+    let duration = call.duration?;
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    if let Some(approval) = output.approval {
+        spans.push(match approval {
+            ExecApproval::AutoApproved => "auto-approved".dim(),
+            ExecApproval::UserApproved => "user-approved".cyan(),
+            ExecApproval::Escalated => "escalated".magenta(),
+        });
+        spans.push("  ".into());
+    }
+    if output.interrupted {
+        spans.push("interrupted".red());
+        return Some(spans);
+    }
+    if output.exit_code == 0 {
+        spans.push("✓".green());
+    } else {
+        spans.push(format!("✗ {}", output.exit_code).red());
+    }
+    spans.push("  ".into());
+    spans.push(format_duration(duration).dim());
+    Some(spans)
+}
+
 #[derive(Clone)]
 pub(crate) struct OutputLines {
     pub(crate) lines: Vec<Line<'static>>,
@@ -207,6 +245,45 @@ impl HistoryCell for ExecCell {
     }
 
     fn transcript_lines(&self, width: u16) -> Vec<Line<'static>> {
+        self.transcript_output_lines(width, TranscriptOutputView::default())
+    }
+}
+
+/// How the transcript overlay shows an exec cell's output.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TranscriptOutputView {
+    /// Show every output line rather than the last few.
+    pub(crate) expanded: bool,
+    /// The cell is the one the overlay's output keys act on.
+    pub(crate) selected: bool,
+}
+
+impl Default for TranscriptOutputView {
+    fn default() -> Self {
+        Self {
+            expanded: true,
+            selected: false,
+        }
+    }
+}
+
+impl ExecCell {
+    /// Whether the transcript overlay offers to collapse this cell: some call
+    /// printed more than fits collapsed, or saved its output to a file.
+    pub(crate) fn has_collapsible_output(&self) -> bool {
+        self.iter_calls().any(|call| {
+            call.output.as_ref().is_some_and(|output| {
+                output.saved_output_path.is_some()
+                    || output.formatted_output.lines().count() > TRANSCRIPT_COLLAPSED_OUTPUT_LINES
+            })
+        })
+    }
+
+    pub(crate) fn transcript_output_lines(
+        &self,
+        width: u16,
+        view: TranscriptOutputView,
+    ) -> Vec<Line<'static>> {
         let mut lines: Vec<Line<'static>> = vec![];
         for (i, call) in self.iter_calls().enumerate() {
             if i > 0 {
@@ -214,19 +291,36 @@ impl HistoryCell for ExecCell {
             }
             let script = strip_bash_lc_and_escape(&call.command);
             let highlighted_script = highlight_bash_to_lines(&script);
+            let initial_indent: Span<'static> = if view.selected && i == 0 {
+                "› ".cyan().bold()
+            } else {
+                "$ ".magenta()
+            };
             let cmd_display = word_wrap_lines(
                 &highlighted_script,
                 RtOptions::new(width as usize)
-                    .initial_indent("$ ".magenta().into())
+                    .initial_indent(initial_indent.into())
                     .subsequent_indent("    ".into()),
             );
             lines.extend(cmd_display);
 
             if let Some(output) = call.output.as_ref() {
                 if !call.is_unified_exec_interaction() {
+                    let output_lines: Vec<&str> = output.formatted_output.lines().collect();
+                    let shown = if view.expanded {
+                        &output_lines[..]
+                    } else {
+                        let start = output_lines
+                            .len()
+                            .saturating_sub(TRANSCRIPT_COLLAPSED_OUTPUT_LINES);
+                        if start > 0 {
+                            lines.push(Self::ellipsis_line(start));
+                        }
+                        &output_lines[start..]
+                    };
                     let wrap_width = width.max(1) as usize;
                     let wrap_opts = RtOptions::new(wrap_width);
-                    for unwrapped in output.formatted_output.lines().map(ansi_escape_line) {
+                    for unwrapped in shown.iter().copied().map(ansi_escape_line) {
                         let wrapped = word_wrap_line(&unwrapped, wrap_opts.clone());
                         push_owned_lines(&wrapped, &mut lines);
                     }
@@ -245,6 +339,13 @@ impl HistoryCell for ExecCell {
                 };
                 result.push_span(format!(" • {duration}").dim());
                 lines.push(result);
+                if let Some(path) = output.saved_output_path.as_ref() {
+                    lines.push(
+                        format!("full output saved to {}", path.display())
+                            .dim()
+                            .into(),
+                    );
+                }
             }
         }
         lines
@@ -383,6 +484,16 @@ impl ExecCell {
             Line::from(vec![bullet.clone(), " ".into(), title.bold(), " ".into()])
         };
         let header_prefix_width = header_line.width();
+        let status = status_column_spans(call).filter(|spans| {
+            let status_width: usize = spans.iter().map(Span::width).sum();
+            (width as usize)
+                >= header_prefix_width
+                    + MIN_COMMAND_WIDTH_WITH_STATUS
+                    + STATUS_COLUMN_GAP
+                    + status_width
+        });
+        let status_width: usize = status.iter().flatten().map(Span::width).sum::<usize>()
+            + status.as_ref().map_or(0, |_| STATUS_COLUMN_GAP);
 
         let cmd_display = if call.is_unified_exec_interaction() {
             format_unified_exec_interaction(&call.command, call.interaction_input.as_deref())
@@ -398,7 +509,9 @@ impl ExecCell {
         let mut continuation_lines: Vec<Line<'static>> = Vec::new();
 
         if let Some((first, rest)) = highlighted_lines.split_first() {
-            let available_first_width = (width as usize).saturating_sub(header_prefix_width).max(1);
+            let available_first_width = (width as usize)
+                .saturating_sub(header_prefix_width + status_width)
+                .max(1);
             let first_opts =
                 RtOptions::new(available_first_width).word_splitter(WordSplitter::NoHyphenation);
             let mut first_wrapped: Vec<Line<'static>> = Vec::new();
@@ -417,6 +530,13 @@ impl ExecCell {
             }
         }
 
+        if let Some(status) = status {
+            let status_width: usize = status.iter().map(Span::width).sum();
+            let padding = (width as usize).saturating_sub(header_line.width() + status_width);
+            header_line.push_span(" ".repeat(padding));
+            header_line.extend(status);
+        }
+
         let mut lines: Vec<Line<'static>> = vec![header_line];
 
         let continuation_lines = Self::limit_lines_from_start(
@@ -486,6 +606,35 @@ impl ExecCell {
                     ));
                 }
             }
+        } else if let Some(live_output) = self.live_output(&call.call_id) {
+            // While the command runs, follow the end of its output like a
+            // terminal would.
+            let display_limit = if call.is_user_shell_command() {
+                USER_SHELL_TOOL_CALL_MAX_LINES
+            } else {
+                layout.output_max_lines
+            };
+            let output_opts = RtOptions::new(layout.output_block.wrap_width(width))
+                .word_splitter(WordSplitter::NoHyphenation);
+            let mut wrapped_output: Vec<Line<'static>> = Vec::new();
+            for raw in live_output.tail(display_limit) {
+                let mut line = ansi_escape_line(raw);
+                line.spans.iter_mut().for_each(|span| {
+                    span.style = span.style.add_modifier(Modifier::DIM);
+                });
+                push_owned_lines(
+                    &word_wrap_line(&line, output_opts.clone()),
+                    &mut wrapped_output,
+                );
+            }
+            let tail_start = wrapped_output.len().saturating_sub(display_limit);
+            if tail_start < wrapped_output.len() {
+                lines.extend(prefix_lines(
+                    wrapped_output.split_off(tail_start),
+                    Span::from(layout.output_block.initial_prefix).dim(),
+                    Span::from(layout.output_block.subsequent_prefix),
+                ));
+            }
         }
 
         lines
@@ -612,6 +761,7 @@ const EXEC_DISPLAY_LAYOUT: ExecDisplayLayout = ExecDisplayLayout::new(
 mod tests {
     use super::*;
     use codex_core::protocol::ExecCommandSource;
+    use std::time::Duration;
 
     #[test]
     fn user_shell_output_is_limited_by_screen_lines() {
@@ -633,6 +783,9 @@ mod tests {
             exit_code: 0,
             aggregated_output,
             formatted_output: String::new(),
+            saved_output_path: None,
+            approval: None,
+            interrupted: false,
         };
         let width = 20;
         let layout = EXEC_DISPLAY_LAYOUT;
@@ -702,4 +855,109 @@ mod tests {
             "expected at most {USER_SHELL_TOOL_CALL_MAX_LINES} screen lines of user shell output, got {output_screen_lines}",
         );
     }
+
+    fn finished_cell(output: CommandOutput) -> ExecCell {
+        let mut cell = new_active_exec_command(
+            "call-id".to_string(),
+            vec!["bash".into(), "-lc".into(), "cargo test".into()],
+            Vec::new(),
+            ExecCommandSource::ModelShell,
+            None,
+            false,
+        );
+        cell.complete_call("call-id", output, Duration::from_millis(1_250));
+        cell
+    }
+
+    fn header(cell: &ExecCell, width: u16) -> String {
+        cell.command_display_lines(width)[0]
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn finished_command_shows_status_column_when_it_fits() {
+        let cell = finished_cell(CommandOutput {
+            exit_code: 101,
+            approval: Some(ExecApproval::Escalated),
+            ..Default::default()
+        });
+
+        let wide = header(&cell, 60);
+        assert_eq!(wide.width(), 60);
+        assert!(wide.starts_with("• Ran cargo test "));
+        assert!(wide.ends_with("escalated  ✗ 101  1.25s"));
+
+        assert_eq!(header(&cell, 30), "• Ran cargo test");
+    }
+
+    #[test]
+    fn interrupted_command_status_omits_exit_code_and_duration() {
+        let cell = finished_cell(CommandOutput {
+            exit_code: 1,
+            approval: Some(ExecApproval::AutoApproved),
+            interrupted: true,
+            ..Default::default()
+        });
+
+        assert!(header(&cell, 80).ends_with("auto-approved  interrupted"));
+    }
+
+    #[test]
+    fn running_command_shows_output_tail_until_it_ends() {
+        let mut cell = new_active_exec_command(
+            "call-id".to_string(),
+            vec!["bash".into(), "-lc".into(), "cargo build".into()],
+            Vec::new(),
+            ExecCommandSource::ModelShell,
+            None,
+            false,
+        );
+        let rendered = |cell: &ExecCell| {
+            cell.command_display_lines(80)
+                .iter()
+                .map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for i in 0..10 {
+            assert!(cell.append_output("call-id", &format!("step {i}\n")));
+        }
+        assert!(cell.append_output("call-id", "[=>  ] 10%\r[==> ] 50%"));
+        assert_eq!(rendered(&cell).len(), 1);
+        assert!(cell.apply_pending_output());
+        assert_eq!(
+            rendered(&cell)[1..],
+            [
+                "  └ step 6",
+                "    step 7",
+                "    step 8",
+                "    step 9",
+                "    [==> ] 50%",
+            ]
+        );
+
+        cell.complete_call(
+            "call-id",
+            CommandOutput {
+                exit_code: 0,
+                aggregated_output: "done\n".to_string(),
+                formatted_output: String::new(),
+                saved_output_path: None,
+                approval: None,
+                interrupted: false,
+            },
+            Duration::from_secs(1),
+        );
+        assert!(!cell.apply_pending_output());
+        assert_eq!(rendered(&cell)[1..], ["  └ done"]);
+        assert!(!cell.append_output("call-id", "late\n"));
+    }
 }
@@ -1,5 +1,6 @@
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::process::Stdio;
 
 use color_eyre::eyre::Report;
@@ -31,9 +32,20 @@ fn resolve_windows_program(program: &str) -> std::path::PathBuf {
 /// Resolve the editor command from environment variables.
 /// Prefers `VISUAL` over `EDITOR`.
 pub(crate) fn resolve_editor_command() -> std::result::Result<Vec<String>, EditorError> {
-    let raw = env::var("VISUAL")
-        .or_else(|_| env::var("EDITOR"))
-        .map_err(|_| EditorError::MissingEditor)?;
+    resolve_command_from_env(&["VISUAL", "EDITOR"])
+}
+
+/// Resolve a command to show a file with: the editor when one is set,
+/// otherwise `PAGER`.
+pub(crate) fn resolve_viewer_command() -> std::result::Result<Vec<String>, EditorError> {
+    resolve_command_from_env(&["VISUAL", "EDITOR", "PAGER"])
+}
+
+fn resolve_command_from_env(vars: &[&str]) -> std::result::Result<Vec<String>, EditorError> {
+    let raw = vars
+        .iter()
+        .find_map(|var| env::var(var).ok())
+        .ok_or(EditorError::MissingEditor)?;
     let parts = {
         #[cfg(windows)]
         {
@@ -60,6 +72,27 @@ pub(crate) async fn run_editor(seed: &str, editor_cmd: &[String]) -> Result<Stri
     let temp_path = Builder::new().suffix(".md").tempfile()?.into_temp_path();
     fs::write(&temp_path, seed)?;
 
+    open_file(&temp_path, editor_cmd).await?;
+
+    let contents = fs::read_to_string(&temp_path)?;
+    Ok(contents)
+}
+
+/// Write `text` to a temp file and show it with `viewer_cmd`, waiting for the
+/// viewer to exit. Edits made in the viewer are discarded.
+pub(crate) async fn view_text(text: &str, viewer_cmd: &[String]) -> Result<()> {
+    let temp_path = Builder::new().suffix(".log").tempfile()?.into_temp_path();
+    fs::write(&temp_path, text)?;
+    open_file(&temp_path, viewer_cmd).await
+}
+
+/// Run `editor_cmd` on `path` with the terminal handed over, waiting for it
+/// to exit.
+pub(crate) async fn open_file(path: &Path, editor_cmd: &[String]) -> Result<()> {
+    if editor_cmd.is_empty() {
+        return Err(Report::msg("editor command is empty"));
+    }
+
     let mut cmd = {
         #[cfg(windows)]
         {
@@ -75,7 +108,7 @@ pub(crate) async fn run_editor(seed: &str, editor_cmd: &[String]) -> Result<Stri
         cmd.args(&editor_cmd[1..]);
     }
     let status = cmd
-        .arg(&temp_path)
+        .arg(path)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
@@ -85,9 +118,7 @@ pub(crate) async fn run_editor(seed: &str, editor_cmd: &[String]) -> Result<Stri
     if !status.success() {
         return Err(Report::msg(format!("editor exited with status {status}")));
     }
-
-    let contents = fs::read_to_string(&temp_path)?;
-    Ok(contents)
+    Ok(())
 }
 
 #[cfg(test)]
@@ -101,6 +132,7 @@ mod tests {
     struct EnvGuard {
         visual: Option<String>,
         editor: Option<String>,
+        pager: Option<String>,
     }
 
     impl EnvGuard {
@@ -108,6 +140,7 @@ mod tests {
             Self {
                 visual: env::var("VISUAL").ok(),
                 editor: env::var("EDITOR").ok(),
+                pager: env::var("PAGER").ok(),
             }
         }
     }
@@ -116,6 +149,7 @@ mod tests {
         fn drop(&mut self) {
             restore_env("VISUAL", self.visual.take());
             restore_env("EDITOR", self.editor.take());
+            restore_env("PAGER", self.pager.take());
         }
     }
 
@@ -152,6 +186,22 @@ mod tests {
         ));
     }
 
+    #[test]
+    #[serial]
+    fn resolve_viewer_falls_back_to_pager() {
+        let _guard = EnvGuard::new();
+        unsafe {
+            env::remove_var("VISUAL");
+            env::remove_var("EDITOR");
+            env::set_var("PAGER", "less -R");
+        }
+        let cmd = resolve_viewer_command().unwrap();
+        assert_eq!(cmd, vec!["less".to_string(), "-R".to_string()]);
+        unsafe { env::set_var("EDITOR", "ed") };
+        let cmd = resolve_viewer_command().unwrap();
+        assert_eq!(cmd, vec!["ed".to_string()]);
+    }
+
     #[tokio::test]
     #[cfg(unix)]
     async fn run_editor_returns_updated_content() {
@@ -25,9 +25,12 @@ use crate::render::line_utils::line_to_static;
 use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
 use crate::render::renderable::Renderable;
+use crate::status::format_tokens_compact;
 use crate::style::user_message_style;
 use crate::text_formatting::format_and_truncate_tool_result;
 use crate::text_formatting::truncate_text;
+use crate::token_usage::ContextUsage;
+use crate::token_usage::TurnUsage;
 use crate::tooltips;
 use crate::ui_consts::LIVE_PREFIX_COLS;
 use crate::update_action::UpdateAction;
@@ -36,6 +39,7 @@ use crate::wrapping::RtOptions;
 use crate::wrapping::word_wrap_line;
 use crate::wrapping::word_wrap_lines;
 use base64::Engine;
+use codex_common::elapsed::format_duration;
 use codex_common::format_env_display::format_env_display;
 use codex_core::config::Config;
 use codex_core::config::types::McpServerTransportConfig;
@@ -43,6 +47,10 @@ use codex_core::protocol::FileChange;
 use codex_core::protocol::McpAuthStatus;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::TurnSummaryEvent;
+use codex_core::protocol::ViewImageToolCallEvent;
+use codex_core::protocol::WorkspaceSearchEndEvent;
+use codex_protocol::num_format::format_with_separators;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::plan_tool::PlanItemArg;
 use codex_protocol::plan_tool::StepStatus;
@@ -654,6 +662,71 @@ pub(crate) fn new_unified_exec_processes_output(processes: Vec<String>) -> Compo
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(summary)])
 }
 
+/// Most recent turns listed by `/usage`.
+const USAGE_MAX_TURNS: usize = 20;
+
+/// `/usage`: the context indicator's numbers plus what each turn spent.
+pub(crate) fn new_token_usage_output(
+    usage: Option<ContextUsage>,
+    turns: &[TurnUsage],
+) -> CompositeHistoryCell {
+    let command = PlainHistoryCell::new(vec!["/usage".magenta().into()]);
+    let mut lines: Vec<Line<'static>> = vec![vec!["Token usage".bold()].into(), "".into()];
+
+    let Some(usage) = usage else {
+        lines.push("  • No token usage recorded yet.".italic().into());
+        let summary = PlainHistoryCell::new(lines);
+        return CompositeHistoryCell::new(vec![Box::new(command), Box::new(summary)]);
+    };
+    let approx = if usage.estimated { "~" } else { "" };
+    let mut context = format!(
+        "  Conversation: {approx}{} tokens",
+        format_tokens_compact(usage.total_tokens)
+    );
+    if let Some(percent) = usage.percent_left {
+        context.push_str(&format!(
+            " · {approx}{}% context left",
+            percent.clamp(0, 100)
+        ));
+    }
+    lines.push(context.into());
+
+    let hidden = turns.len().saturating_sub(USAGE_MAX_TURNS);
+    if hidden > 0 {
+        lines.push(format!("  … {hidden} earlier turns").dim().into());
+    }
+    for (idx, turn) in turns.iter().enumerate().skip(hidden) {
+        let total = match turn.reported_tokens() {
+            Some(tokens) => format_tokens_compact(tokens),
+            None => format!("~{}", format_tokens_compact(turn.estimated_tokens())),
+        };
+        lines.push(
+            vec![
+                format!("  Turn {}: ", idx + 1).into(),
+                format!("{total} tokens").bold(),
+                format!(
+                    " · prompt ~{} · reply ~{} · tool output ~{}",
+                    format_tokens_compact(turn.prompt_estimate),
+                    format_tokens_compact(turn.reply_estimate),
+                    format_tokens_compact(turn.tool_output_estimate),
+                )
+                .dim(),
+            ]
+            .into(),
+        );
+    }
+    lines.push("".into());
+    let note = if usage.estimated {
+        "  The provider does not report usage; all numbers are estimated from message sizes."
+    } else {
+        "  Prompt, reply and tool output sizes are estimates; turn totals are reported."
+    };
+    lines.push(note.dim().into());
+
+    let summary = PlainHistoryCell::new(lines);
+    CompositeHistoryCell::new(vec![Box::new(command), Box::new(summary)])
+}
+
 fn truncate_exec_snippet(full_cmd: &str) -> String {
     let mut snippet = match full_cmd.split_once('\n') {
         Some((first, _)) => format!("{first} ..."),
@@ -714,6 +787,18 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
+        ApprovedWithEdit { command: edited } => {
+            let snippet = Span::from(exec_snippet(&edited)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "edited".bold(),
+                    " the command and approved codex to run ".into(),
+                    snippet,
+                ],
+            )
+        }
         Denied => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -726,6 +811,20 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
+        DeniedWithReason { reason } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            (
+                "✗ ".red(),
+                vec![
+                    "You ".into(),
+                    "did not approve".bold(),
+                    " codex to run ".into(),
+                    snippet,
+                    ": ".into(),
+                    reason.italic(),
+                ],
+            )
+        }
         Abort => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -754,6 +853,60 @@ pub(crate) fn new_review_status_line(message: String) -> PlainHistoryCell {
     }
 }
 
+/// Dim footer line closing a turn that ran tools, splitting its time between
+/// the tools and the model, and counting finished plan steps when there is a
+/// plan. Estimated tool output tokens and approximate costs are added when
+/// known. The turn's cautions follow on lines of their own.
+pub(crate) fn new_turn_summary(summary: &TurnSummaryEvent) -> PlainHistoryCell {
+    let calls = if summary.tool_calls == 1 {
+        "1 tool call".to_string()
+    } else {
+        format!("{} tool calls", summary.tool_calls)
+    };
+    let mut text = format!(
+        "  {calls} · tools {} · model {}",
+        format_duration(summary.tool_time),
+        format_duration(summary.model_time)
+    );
+    if let Some(plan) = summary.plan.as_ref().filter(|plan| !plan.plan.is_empty()) {
+        let completed = plan
+            .plan
+            .iter()
+            .filter(|item| matches!(item.status, StepStatus::Completed))
+            .count();
+        text.push_str(&format!(" · plan {completed}/{} done", plan.plan.len()));
+    }
+    let cache = summary.prompt_cache;
+    if cache.requests() > 0 {
+        text.push_str(&format!(
+            " · cache hits {}/{} ({} cached tokens)",
+            cache.hits,
+            cache.requests(),
+            format_with_separators(cache.cached_input_tokens)
+        ));
+    }
+    if summary.tool_output_tokens > 0 {
+        text.push_str(&format!(
+            " · tool output ~{} tokens",
+            format_with_separators(i64::try_from(summary.tool_output_tokens).unwrap_or(i64::MAX))
+        ));
+    }
+    if let Some(cost) = summary.cost {
+        text.push_str(&format!(
+            " · cost ~{:.4} (session ~{:.4})",
+            cost.turn, cost.session
+        ));
+    }
+    let mut lines = vec![Line::from(text.dim())];
+    lines.extend(
+        summary
+            .cautions
+            .iter()
+            .map(|caution| Line::from(format!("  ⚠ {caution}").yellow())),
+    );
+    PlainHistoryCell { lines }
+}
+
 #[derive(Debug)]
 pub(crate) struct PatchHistoryCell {
     changes: HashMap<PathBuf, FileChange>,
@@ -1347,6 +1500,23 @@ pub(crate) fn new_web_search_call(query: String) -> PrefixedWrappedHistoryCell {
     PrefixedWrappedHistoryCell::new(text, "• ".dim(), "  ")
 }
 
+pub(crate) fn new_workspace_search(ev: &WorkspaceSearchEndEvent) -> PrefixedWrappedHistoryCell {
+    let outcome = match &ev.error {
+        Some(error) => format!(" · failed: {error}"),
+        None if ev.match_count == 1 => " · 1 match".to_string(),
+        None if ev.truncated => format!(" · first {} matches", ev.match_count),
+        None => format!(" · {} matches", ev.match_count),
+    };
+    let text: Text<'static> = Line::from(vec![
+        "Searched workspace".bold(),
+        " ".into(),
+        ev.query.clone().into(),
+        outcome.dim(),
+    ])
+    .into();
+    PrefixedWrappedHistoryCell::new(text, "• ".dim(), "  ")
+}
+
 /// If the first content is an image, return a new cell with the image.
 /// TODO(rgwood-dd): Handle images properly even if they're not the first result.
 fn try_new_completed_mcp_tool_call_with_image_output(
@@ -1501,6 +1671,7 @@ pub(crate) fn new_mcp_tools_output(
                 args,
                 env,
                 env_vars,
+                env_sources,
                 cwd,
             } => {
                 let args_suffix = if args.is_empty() {
@@ -1515,7 +1686,13 @@ pub(crate) fn new_mcp_tools_output(
                     lines.push(vec!["    • Cwd: ".into(), cwd.display().to_string().into()].into());
                 }
 
-                let env_display = format_env_display(env.as_ref(), env_vars);
+                let mut env_names = env_vars.clone();
+                if let Some(env_sources) = env_sources.as_ref() {
+                    let mut sourced: Vec<String> = env_sources.keys().cloned().collect();
+                    sourced.sort();
+                    env_names.extend(sourced);
+                }
+                let env_display = format_env_display(env.as_ref(), &env_names);
                 if env_display != "-" {
                     lines.push(vec!["    • Env: ".into(), env_display.into()].into());
                 }
@@ -1718,6 +1895,9 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
                 exit_code: 1,
                 formatted_output: String::new(),
                 aggregated_output: stderr,
+                saved_output_path: None,
+                approval: None,
+                interrupted: false,
             }),
             OutputLinesParams {
                 line_limit: TOOL_CALL_MAX_LINES,
@@ -1732,12 +1912,18 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
-pub(crate) fn new_view_image_tool_call(path: PathBuf, cwd: &Path) -> PlainHistoryCell {
-    let display_path = display_path_for(&path, cwd);
+pub(crate) fn new_view_image_tool_call(
+    event: &ViewImageToolCallEvent,
+    cwd: &Path,
+) -> PlainHistoryCell {
+    let mut detail = display_path_for(&event.path, cwd);
+    if event.width > 0 && event.height > 0 {
+        detail.push_str(&format!(" · {}×{}", event.width, event.height));
+    }
 
     let lines: Vec<Line<'static>> = vec![
         vec!["• ".dim(), "Viewed Image".bold()].into(),
-        vec!["  └ ".dim(), display_path.dim()].into(),
+        vec!["  └ ".dim(), detail.dim()].into(),
     ];
 
     PlainHistoryCell { lines }
@@ -1899,6 +2085,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn usage_output_lists_turns_and_marks_estimates() {
+        let mut tracker = crate::token_usage::TokenUsageTracker::default();
+        tracker.start_turn();
+        tracker.record_prompt(&"p".repeat(40));
+        tracker.record_tool_output(&"t".repeat(4_000));
+        let usage = tracker.context_usage(Some(100_000), None);
+
+        let cell = new_token_usage_output(usage, tracker.turns());
+        let rendered = render_lines(&cell.display_lines(120));
+        assert_eq!(
+            rendered,
+            vec![
+                "/usage",
+                "",
+                "Token usage",
+                "",
+                "  Conversation: ~1.01K tokens · ~100% context left",
+                "  Turn 1: ~1.01K tokens · prompt ~10 · reply ~0 · tool output ~1K",
+                "",
+                "  The provider does not report usage; all numbers are estimated from message sizes.",
+            ]
+        );
+    }
+
     #[test]
     fn ps_output_empty_snapshot() {
         let cell = new_unified_exec_processes_output(Vec::new());
@@ -1945,6 +2156,7 @@ mod tests {
                 args: vec![],
                 env: Some(env),
                 env_vars: vec!["APP_TOKEN".to_string()],
+                env_sources: None,
                 cwd: None,
             },
             enabled: true,
@@ -1954,6 +2166,8 @@ mod tests {
             enabled_tools: None,
             disabled_tools: None,
             scopes: None,
+            max_restart_attempts: None,
+            sampling: None,
         };
         let mut servers = config.mcp_servers.get().clone();
         servers.insert("docs".to_string(), stdio_config);
@@ -1968,6 +2182,8 @@ mod tests {
                 bearer_token_env_var: Some("MCP_TOKEN".to_string()),
                 http_headers: Some(headers),
                 env_http_headers: Some(env_headers),
+                header_sources: None,
+                tls: None,
             },
             enabled: true,
             disabled_reason: None,
@@ -1976,6 +2192,8 @@ mod tests {
             enabled_tools: None,
             disabled_tools: None,
             scopes: None,
+            max_restart_attempts: None,
+            sampling: None,
         };
         servers.insert("http".to_string(), http_config);
         config
@@ -2357,7 +2575,7 @@ mod tests {
                     },
                 ],
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2384,7 +2602,7 @@ mod tests {
                     cmd: "rg shimmer_spans".into(),
                 }],
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2403,7 +2621,7 @@ mod tests {
                     cmd: "cat shimmer.rs".into(),
                     path: "shimmer.rs".into(),
                 }],
-                ExecCommandSource::Agent,
+                ExecCommandSource::ModelShell,
                 None,
             )
             .unwrap();
@@ -2418,7 +2636,7 @@ mod tests {
                     cmd: "cat status_indicator_widget.rs".into(),
                     path: "status_indicator_widget.rs".into(),
                 }],
-                ExecCommandSource::Agent,
+                ExecCommandSource::ModelShell,
                 None,
             )
             .unwrap();
@@ -2453,7 +2671,7 @@ mod tests {
                     },
                 ],
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2477,7 +2695,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), cmd],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2503,7 +2721,7 @@ mod tests {
                 command: vec!["echo".into(), "ok".into()],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2527,7 +2745,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), long],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2550,7 +2768,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), cmd],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2574,7 +2792,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), cmd],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2598,7 +2816,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), "seq 1 10 1>&2 && false".into()],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2615,6 +2833,9 @@ mod tests {
                 exit_code: 1,
                 formatted_output: String::new(),
                 aggregated_output: stderr,
+                saved_output_path: None,
+                approval: None,
+                interrupted: false,
             },
             Duration::from_millis(1),
         );
@@ -2648,7 +2869,7 @@ mod tests {
                 command: vec!["bash".into(), "-lc".into(), long_cmd.to_string()],
                 parsed: Vec::new(),
                 output: None,
-                source: ExecCommandSource::Agent,
+                source: ExecCommandSource::ModelShell,
                 start_time: Some(Instant::now()),
                 duration: None,
                 interaction_input: None,
@@ -2663,6 +2884,9 @@ mod tests {
                 exit_code: 1,
                 formatted_output: String::new(),
                 aggregated_output: stderr,
+                saved_output_path: None,
+                approval: None,
+                interrupted: false,
             },
             Duration::from_millis(5),
         );
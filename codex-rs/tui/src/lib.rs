@@ -25,7 +25,7 @@ use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::config::resolve_oss_provider;
 use codex_core::config_loader::ConfigLoadError;
 use codex_core::config_loader::format_config_error_with_source;
-use codex_core::find_thread_path_by_id_str;
+use codex_core::find_thread_path_by_id_str_in_state_dirs;
 use codex_core::get_platform_sandbox;
 use codex_core::path_utils;
 use codex_core::protocol::AskForApproval;
@@ -55,6 +55,7 @@ mod ascii_animation;
 mod bottom_pane;
 mod chatwidget;
 mod cli;
+mod clipboard_copy;
 mod clipboard_paste;
 mod collab;
 mod collaboration_modes;
@@ -62,6 +63,7 @@ mod color;
 pub mod custom_terminal;
 mod cwd_prompt;
 mod diff_render;
+mod diff_viewer;
 mod exec_cell;
 mod exec_command;
 mod external_editor;
@@ -94,7 +96,9 @@ mod streaming;
 mod style;
 mod terminal_palette;
 mod text_formatting;
+mod token_usage;
 mod tooltips;
+mod transcript_export;
 mod tui;
 mod ui_consts;
 pub mod update_action;
@@ -186,15 +190,15 @@ pub async fn run_main(
     {
         Ok(config_toml) => config_toml,
         Err(err) => {
-            let config_error = err
+            let config_load_error = err
                 .get_ref()
-                .and_then(|err| err.downcast_ref::<ConfigLoadError>())
-                .map(ConfigLoadError::config_error);
-            if let Some(config_error) = config_error {
-                eprintln!(
-                    "Error loading config.toml:\n{}",
-                    format_config_error_with_source(config_error)
-                );
+                .and_then(|err| err.downcast_ref::<ConfigLoadError>());
+            if let Some(config_load_error) = config_load_error {
+                let formatted: Vec<String> = config_load_error
+                    .config_errors()
+                    .map(format_config_error_with_source)
+                    .collect();
+                eprintln!("Error loading config.toml:\n{}", formatted.join("\n\n"));
             } else {
                 eprintln!("Error loading config.toml: {err}");
             }
@@ -374,6 +378,7 @@ async fn run_ratatui_app(
 
     tooltips::announcement::prewarm();
 
+    codex_core::install_rollout_panic_hook();
     // Forward panic reports through tracing so they appear in the UI status
     // line, but do not swallow the default/color-eyre panic handler.
     // Chain to the previous hook so users still get a rich panic report
@@ -484,14 +489,21 @@ async fn run_ratatui_app(
     let use_fork = cli.fork_picker || cli.fork_last || cli.fork_session_id.is_some();
     let session_selection = if use_fork {
         if let Some(id_str) = cli.fork_session_id.as_deref() {
-            match find_thread_path_by_id_str(&config.codex_home, id_str).await? {
+            match find_thread_path_by_id_str_in_state_dirs(
+                &config.codex_home,
+                &config.state_dir,
+                id_str,
+            )
+            .await?
+            {
                 Some(path) => resume_picker::SessionSelection::Fork(path),
                 None => return missing_session_exit(id_str, "fork"),
             }
         } else if cli.fork_last {
             let provider_filter = vec![config.model_provider_id.clone()];
-            match RolloutRecorder::list_threads(
+            match RolloutRecorder::list_threads_with_state_dir(
                 &config.codex_home,
+                &config.state_dir,
                 1,
                 None,
                 ThreadSortKey::UpdatedAt,
@@ -512,6 +524,7 @@ async fn run_ratatui_app(
             match resume_picker::run_fork_picker(
                 &mut tui,
                 &config.codex_home,
+                &config.state_dir,
                 &config.model_provider_id,
                 cli.fork_show_all,
             )
@@ -533,7 +546,13 @@ async fn run_ratatui_app(
             resume_picker::SessionSelection::StartFresh
         }
     } else if let Some(id_str) = cli.resume_session_id.as_deref() {
-        match find_thread_path_by_id_str(&config.codex_home, id_str).await? {
+        match find_thread_path_by_id_str_in_state_dirs(
+            &config.codex_home,
+            &config.state_dir,
+            id_str,
+        )
+        .await?
+        {
             Some(path) => resume_picker::SessionSelection::Resume(path),
             None => return missing_session_exit(id_str, "resume"),
         }
@@ -546,6 +565,7 @@ async fn run_ratatui_app(
         };
         match RolloutRecorder::find_latest_thread_path(
             &config.codex_home,
+            &config.state_dir,
             1,
             None,
             ThreadSortKey::UpdatedAt,
@@ -563,6 +583,7 @@ async fn run_ratatui_app(
         match resume_picker::run_resume_picker(
             &mut tui,
             &config.codex_home,
+            &config.state_dir,
             &config.model_provider_id,
             cli.resume_show_all,
         )
@@ -640,6 +661,9 @@ async fn run_ratatui_app(
     .await;
 
     restore();
+    // An immediate or fatal exit skips the session shutdown, so write what
+    // the rollouts still have queued.
+    codex_core::sync_open_rollouts();
     // Mark the end of the recorded session.
     session_log::log_session_end();
     // ignore error when collecting usage – report underlying error instead
@@ -878,7 +902,10 @@ mod tests {
         let temp_dir = TempDir::new()?;
         let mut config = build_config(&temp_dir).await?;
         config.did_user_set_custom_approval_policy_or_sandbox_mode = false;
-        config.active_project = ProjectConfig { trust_level: None };
+        config.active_project = ProjectConfig {
+            trust_level: None,
+            allow_policy_overrides: None,
+        };
         config.set_windows_sandbox_globally(false);
 
         let should_show = should_show_trust_screen(&config);
@@ -901,7 +928,10 @@ mod tests {
         let temp_dir = TempDir::new()?;
         let mut config = build_config(&temp_dir).await?;
         config.did_user_set_custom_approval_policy_or_sandbox_mode = false;
-        config.active_project = ProjectConfig { trust_level: None };
+        config.active_project = ProjectConfig {
+            trust_level: None,
+            allow_policy_overrides: None,
+        };
         config.set_windows_sandbox_globally(true);
 
         let should_show = should_show_trust_screen(&config);
@@ -926,6 +956,7 @@ mod tests {
         config.did_user_set_custom_approval_policy_or_sandbox_mode = false;
         config.active_project = ProjectConfig {
             trust_level: Some(TrustLevel::Untrusted),
+            allow_policy_overrides: None,
         };
 
         let should_show = should_show_trust_screen(&config);
@@ -15,11 +15,16 @@
 //! recomputed. `ChatWidget` is responsible for producing a key that changes when the active cell
 //! mutates in place or when its transcript output is time-dependent.
 
+use std::collections::HashSet;
 use std::io::Result;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::chatwidget::ActiveCellTranscriptKey;
+use crate::diff_viewer::DiffViewer;
+use crate::exec_cell::ExecCell;
+use crate::exec_cell::TranscriptOutputView;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::UserHistoryCell;
 use crate::key_hint;
@@ -49,11 +54,15 @@ use ratatui::widgets::Wrap;
 pub(crate) enum Overlay {
     Transcript(TranscriptOverlay),
     Static(StaticOverlay),
+    Diff(DiffViewer),
 }
 
 impl Overlay {
-    pub(crate) fn new_transcript(cells: Vec<Arc<dyn HistoryCell>>) -> Self {
-        Self::Transcript(TranscriptOverlay::new(cells))
+    pub(crate) fn new_transcript(
+        cells: Vec<Arc<dyn HistoryCell>>,
+        expanded_outputs: HashSet<String>,
+    ) -> Self {
+        Self::Transcript(TranscriptOverlay::new(cells).with_expanded_outputs(expanded_outputs))
     }
 
     pub(crate) fn new_static_with_lines(lines: Vec<Line<'static>>, title: String) -> Self {
@@ -67,10 +76,15 @@ impl Overlay {
         Self::Static(StaticOverlay::with_renderables(renderables, title))
     }
 
+    pub(crate) fn new_diff(unified_diff: &str) -> Self {
+        Self::Diff(DiffViewer::new(unified_diff))
+    }
+
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match self {
             Overlay::Transcript(o) => o.handle_event(tui, event),
             Overlay::Static(o) => o.handle_event(tui, event),
+            Overlay::Diff(o) => o.handle_event(tui, event),
         }
     }
 
@@ -78,6 +92,7 @@ impl Overlay {
         match self {
             Overlay::Transcript(o) => o.is_done(),
             Overlay::Static(o) => o.is_done(),
+            Overlay::Diff(o) => o.is_done(),
         }
     }
 }
@@ -103,6 +118,12 @@ const KEY_ESC: KeyBinding = key_hint::plain(KeyCode::Esc);
 const KEY_ENTER: KeyBinding = key_hint::plain(KeyCode::Enter);
 const KEY_CTRL_T: KeyBinding = key_hint::ctrl(KeyCode::Char('t'));
 const KEY_CTRL_C: KeyBinding = key_hint::ctrl(KeyCode::Char('c'));
+const KEY_TAB: KeyBinding = key_hint::plain(KeyCode::Tab);
+const KEY_BACK_TAB: KeyBinding = key_hint::plain(KeyCode::BackTab);
+const KEY_SHIFT_BACK_TAB: KeyBinding = key_hint::shift(KeyCode::BackTab);
+const KEY_O: KeyBinding = key_hint::plain(KeyCode::Char('o'));
+const KEY_E: KeyBinding = key_hint::plain(KeyCode::Char('e'));
+const KEY_F: KeyBinding = key_hint::plain(KeyCode::Char('f'));
 
 // Common pager navigation hints rendered on the first line
 const PAGER_KEY_HINTS: &[(&[KeyBinding], &str)] = &[
@@ -112,7 +133,7 @@ const PAGER_KEY_HINTS: &[(&[KeyBinding], &str)] = &[
 ];
 
 // Render a single line of key hints from (key(s), description) pairs.
-fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&[KeyBinding], &str)]) {
+pub(crate) fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&[KeyBinding], &str)]) {
     let mut spans: Vec<Span<'static>> = vec![" ".into()];
     let mut first = true;
     for (keys, desc) in pairs {
@@ -420,6 +441,50 @@ impl Renderable for CellRenderable {
     }
 }
 
+/// An exec cell whose output the transcript can collapse to its last lines.
+struct ExecOutputRenderable {
+    cell: Arc<dyn HistoryCell>,
+    view: TranscriptOutputView,
+}
+
+impl ExecOutputRenderable {
+    fn lines(&self, width: u16) -> Vec<Line<'static>> {
+        match self.cell.as_any().downcast_ref::<ExecCell>() {
+            Some(exec) => exec.transcript_output_lines(width, self.view),
+            None => self.cell.transcript_lines(width),
+        }
+    }
+}
+
+impl Renderable for ExecOutputRenderable {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(Text::from(self.lines(area.width))).render(area, buf);
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.lines(width).len() as u16
+    }
+}
+
+/// Output the transcript asked `App` to show outside the TUI.
+#[derive(Debug, PartialEq)]
+pub(crate) enum OutputOpenRequest {
+    /// The output as received, written to a temp file first.
+    Text(String),
+    /// The file a spilled output was saved to.
+    File(PathBuf),
+}
+
+/// Key under which the collapsed state of a cell is remembered, if the cell
+/// has output worth collapsing.
+fn collapsible_output_key(cell: &dyn HistoryCell) -> Option<&str> {
+    let exec = cell.as_any().downcast_ref::<ExecCell>()?;
+    if !exec.has_collapsible_output() {
+        return None;
+    }
+    exec.calls.first().map(|call| call.call_id.as_str())
+}
+
 pub(crate) struct TranscriptOverlay {
     /// Pager UI state and the renderables currently displayed.
     ///
@@ -429,6 +494,14 @@ pub(crate) struct TranscriptOverlay {
     /// Committed transcript cells (does not include the live tail).
     cells: Vec<Arc<dyn HistoryCell>>,
     highlight_cell: Option<usize>,
+    /// Exec cells showing their full output, by the id of their first call.
+    /// Everything else with long output is collapsed.
+    expanded_outputs: HashSet<String>,
+    /// Index of the exec cell the output keys act on.
+    selected_output: Option<usize>,
+    /// Output to show in the editor or pager; taken by `App`, which owns the
+    /// terminal.
+    open_request: Option<OutputOpenRequest>,
     /// Cache key for the render-only live tail appended after committed cells.
     live_tail_key: Option<LiveTailKey>,
     is_done: bool,
@@ -457,27 +530,49 @@ impl TranscriptOverlay {
     pub(crate) fn new(transcript_cells: Vec<Arc<dyn HistoryCell>>) -> Self {
         Self {
             view: PagerView::new(
-                Self::render_cells(&transcript_cells, None),
+                Self::render_cells(&transcript_cells, None, &HashSet::new(), None),
                 "T R A N S C R I P T".to_string(),
                 usize::MAX,
             ),
             cells: transcript_cells,
             highlight_cell: None,
+            expanded_outputs: HashSet::new(),
+            selected_output: None,
+            open_request: None,
             live_tail_key: None,
             is_done: false,
         }
     }
 
+    /// Restores which outputs were expanded the last time the transcript was
+    /// open.
+    pub(crate) fn with_expanded_outputs(mut self, expanded_outputs: HashSet<String>) -> Self {
+        self.expanded_outputs = expanded_outputs;
+        self.rebuild_renderables();
+        self
+    }
+
     fn render_cells(
         cells: &[Arc<dyn HistoryCell>],
         highlight_cell: Option<usize>,
+        expanded_outputs: &HashSet<String>,
+        selected_output: Option<usize>,
     ) -> Vec<Box<dyn Renderable>> {
         cells
             .iter()
             .enumerate()
             .flat_map(|(i, c)| {
                 let mut v: Vec<Box<dyn Renderable>> = Vec::new();
-                let mut cell_renderable = if c.as_any().is::<UserHistoryCell>() {
+                let output_key = collapsible_output_key(c.as_ref());
+                let mut cell_renderable = if let Some(key) = output_key {
+                    Box::new(CachedRenderable::new(ExecOutputRenderable {
+                        cell: c.clone(),
+                        view: TranscriptOutputView {
+                            expanded: expanded_outputs.contains(key),
+                            selected: selected_output == Some(i),
+                        },
+                    })) as Box<dyn Renderable>
+                } else if c.as_any().is::<UserHistoryCell>() {
                     Box::new(CachedRenderable::new(CellRenderable {
                         cell: c.clone(),
                         style: if highlight_cell == Some(i) {
@@ -519,7 +614,12 @@ impl TranscriptOverlay {
         let had_prior_cells = !self.cells.is_empty();
         let tail_renderable = self.take_live_tail_renderable();
         self.cells.push(cell);
-        self.view.renderables = Self::render_cells(&self.cells, self.highlight_cell);
+        self.view.renderables = Self::render_cells(
+            &self.cells,
+            self.highlight_cell,
+            &self.expanded_outputs,
+            self.selected_output,
+        );
         if let Some(tail) = tail_renderable {
             let tail = if !had_prior_cells
                 && self
@@ -606,12 +706,118 @@ impl TranscriptOverlay {
 
     fn rebuild_renderables(&mut self) {
         let tail_renderable = self.take_live_tail_renderable();
-        self.view.renderables = Self::render_cells(&self.cells, self.highlight_cell);
+        self.view.renderables = Self::render_cells(
+            &self.cells,
+            self.highlight_cell,
+            &self.expanded_outputs,
+            self.selected_output,
+        );
         if let Some(tail) = tail_renderable {
             self.view.renderables.push(tail);
         }
     }
 
+    /// Which outputs are expanded, for `App` to restore the next time the
+    /// transcript opens.
+    pub(crate) fn expanded_outputs(&self) -> &HashSet<String> {
+        &self.expanded_outputs
+    }
+
+    /// Takes the output the user asked to open in the editor or pager.
+    pub(crate) fn take_open_request(&mut self) -> Option<OutputOpenRequest> {
+        self.open_request.take()
+    }
+
+    fn has_collapsible_outputs(&self) -> bool {
+        self.cells
+            .iter()
+            .any(|cell| collapsible_output_key(cell.as_ref()).is_some())
+    }
+
+    /// Moves the output selection to the next collapsible exec cell in
+    /// `forward` direction, starting from the newest one.
+    fn select_output(&mut self, forward: bool) {
+        let candidates: Vec<usize> = self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| collapsible_output_key(cell.as_ref()).is_some())
+            .map(|(i, _)| i)
+            .collect();
+        let next = match self.selected_output {
+            None => candidates.last().copied(),
+            Some(current) if forward => candidates
+                .iter()
+                .copied()
+                .find(|&i| i > current)
+                .or(Some(current)),
+            Some(current) => candidates
+                .iter()
+                .rev()
+                .copied()
+                .find(|&i| i < current)
+                .or(Some(current)),
+        };
+        if next != self.selected_output {
+            self.selected_output = next;
+            self.rebuild_renderables();
+        }
+        if let Some(idx) = self.selected_output {
+            self.view.scroll_chunk_into_view(idx);
+        }
+    }
+
+    /// The selected exec cell, selecting the newest one if nothing is.
+    fn output_target(&mut self) -> Option<&ExecCell> {
+        if self.selected_output.is_none() {
+            self.select_output(false);
+        }
+        let cell = self.cells.get(self.selected_output?)?;
+        cell.as_any().downcast_ref::<ExecCell>()
+    }
+
+    fn toggle_selected_output(&mut self) {
+        let Some(key) = self
+            .output_target()
+            .and_then(|exec| exec.calls.first())
+            .map(|call| call.call_id.clone())
+        else {
+            return;
+        };
+        if !self.expanded_outputs.remove(&key) {
+            self.expanded_outputs.insert(key);
+        }
+        self.rebuild_renderables();
+        if let Some(idx) = self.selected_output {
+            self.view.scroll_chunk_into_view(idx);
+        }
+    }
+
+    /// Handles the keys that act on exec outputs. Returns whether `key_event`
+    /// was one of them.
+    fn handle_output_key(&mut self, key_event: KeyEvent) -> bool {
+        match key_event {
+            e if KEY_TAB.is_press(e) => self.select_output(true),
+            e if KEY_BACK_TAB.is_press(e) || KEY_SHIFT_BACK_TAB.is_press(e) => {
+                self.select_output(false);
+            }
+            e if KEY_O.is_press(e) => self.toggle_selected_output(),
+            e if KEY_E.is_press(e) => {
+                self.open_request = self
+                    .output_target()
+                    .map(|exec| OutputOpenRequest::Text(exec.output_text()));
+            }
+            e if KEY_F.is_press(e) => {
+                self.open_request = self
+                    .output_target()
+                    .and_then(ExecCell::saved_output_path)
+                    .map(|path| OutputOpenRequest::File(path.to_path_buf()));
+            }
+            _ => return false,
+        }
+        true
+    }
+
     /// Removes and returns the cached live-tail renderable, if present.
     ///
     /// The live tail is represented as a single optional renderable appended after the committed
@@ -648,6 +854,24 @@ impl TranscriptOverlay {
             pairs.push((&[KEY_ESC], "to edit prev"));
         }
         render_key_hints(line2, buf, &pairs);
+
+        if self.has_collapsible_outputs() {
+            let line3 = Rect::new(area.x, area.y.saturating_add(2), area.width, 1);
+            let mut pairs: Vec<(&[KeyBinding], &str)> = vec![
+                (&[KEY_TAB], "to select output"),
+                (&[KEY_O], "to expand/collapse"),
+                (&[KEY_E], "to open in editor"),
+            ];
+            let selected_saved_output = self
+                .selected_output
+                .and_then(|idx| self.cells.get(idx))
+                .and_then(|cell| cell.as_any().downcast_ref::<ExecCell>())
+                .is_some_and(|exec| exec.saved_output_path().is_some());
+            if selected_saved_output {
+                pairs.push((&[KEY_F], "to open saved output"));
+            }
+            render_key_hints(line3, buf, &pairs);
+        }
     }
 
     pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
@@ -667,6 +891,10 @@ impl TranscriptOverlay {
                     self.is_done = true;
                     Ok(())
                 }
+                e if self.handle_output_key(e) => {
+                    tui.frame_requester().schedule_frame();
+                    Ok(())
+                }
                 other => self.view.handle_key_event(tui, other),
             },
             TuiEvent::Draw => {
@@ -972,7 +1200,7 @@ mod tests {
             "exec-1".into(),
             vec!["bash".into(), "-lc".into(), "ls".into()],
             vec![ParsedCommand::Unknown { cmd: "ls".into() }],
-            ExecCommandSource::Agent,
+            ExecCommandSource::ModelShell,
             None,
             true,
         );
@@ -982,6 +1210,9 @@ mod tests {
                 exit_code: 0,
                 aggregated_output: "src\nREADME.md\n".into(),
                 formatted_output: "src\nREADME.md\n".into(),
+                saved_output_path: None,
+                approval: None,
+                interrupted: false,
             },
             Duration::from_millis(420),
         );
@@ -1000,6 +1231,106 @@ mod tests {
         assert_snapshot!("transcript_overlay_apply_patch_scroll_vt100", snapshot);
     }
 
+    fn finished_exec_cell(
+        call_id: &str,
+        output: &str,
+        saved_output_path: Option<PathBuf>,
+    ) -> Arc<dyn HistoryCell> {
+        let mut exec_cell = crate::exec_cell::new_active_exec_command(
+            call_id.into(),
+            vec!["bash".into(), "-lc".into(), "cargo test".into()],
+            vec![ParsedCommand::Unknown {
+                cmd: "cargo test".into(),
+            }],
+            ExecCommandSource::ModelShell,
+            None,
+            true,
+        );
+        exec_cell.complete_call(
+            call_id,
+            CommandOutput {
+                exit_code: 0,
+                aggregated_output: output.into(),
+                formatted_output: output.into(),
+                saved_output_path,
+                approval: None,
+                interrupted: false,
+            },
+            Duration::from_millis(420),
+        );
+        Arc::new(exec_cell)
+    }
+
+    #[test]
+    fn long_exec_output_is_collapsed_until_expanded() {
+        let output: String = (0..20).map(|i| format!("out{i}\n")).collect();
+        let mut overlay = TranscriptOverlay::new(vec![finished_exec_cell("exec-1", &output, None)]);
+        let area = Rect::new(0, 0, 100, 30);
+
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+        let collapsed = buffer_to_text(&buf, area);
+        assert!(collapsed.contains("… +15 lines"), "{collapsed}");
+        assert!(!collapsed.contains("out14\n"), "{collapsed}");
+        assert!(collapsed.contains("out19"), "{collapsed}");
+        assert!(collapsed.contains("to expand/collapse"), "{collapsed}");
+
+        assert!(overlay.handle_output_key(KeyEvent::from(KeyCode::Char('o'))));
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+        let expanded = buffer_to_text(&buf, area);
+        assert!(expanded.contains("› cargo test"), "{expanded}");
+        assert!(expanded.contains("out0"), "{expanded}");
+        assert!(!expanded.contains("… +15 lines"), "{expanded}");
+        assert_eq!(
+            overlay.expanded_outputs(),
+            &HashSet::from(["exec-1".to_string()])
+        );
+
+        // Reopening the transcript restores the expanded state.
+        let reopened = TranscriptOverlay::new(vec![finished_exec_cell("exec-1", &output, None)])
+            .with_expanded_outputs(overlay.expanded_outputs().clone());
+        let lines = reopened.view.renderables[0].desired_height(100);
+        assert_eq!(lines, 22);
+    }
+
+    #[test]
+    fn output_keys_select_cells_and_request_opening_outputs() {
+        let long_output: String = (0..10).map(|i| format!("line{i}\n")).collect();
+        let saved = PathBuf::from("/state/tool_output/thread/exec-2.txt");
+        let mut overlay = TranscriptOverlay::new(vec![
+            finished_exec_cell("exec-1", &long_output, None),
+            finished_exec_cell("exec-short", "ok\n", None),
+            finished_exec_cell("exec-2", "tail\n", Some(saved.clone())),
+        ]);
+
+        // With nothing selected, the newest output is the target.
+        assert!(overlay.handle_output_key(KeyEvent::from(KeyCode::Char('f'))));
+        assert_eq!(overlay.selected_output, Some(2));
+        assert_eq!(
+            overlay.take_open_request(),
+            Some(OutputOpenRequest::File(saved))
+        );
+        assert_eq!(overlay.take_open_request(), None);
+
+        // Short outputs are skipped when moving the selection.
+        assert!(overlay.handle_output_key(KeyEvent::from(KeyCode::BackTab)));
+        assert_eq!(overlay.selected_output, Some(0));
+        overlay.handle_output_key(KeyEvent::from(KeyCode::Char('f')));
+        assert_eq!(overlay.take_open_request(), None);
+        overlay.handle_output_key(KeyEvent::from(KeyCode::Char('e')));
+        assert_eq!(
+            overlay.take_open_request(),
+            Some(OutputOpenRequest::Text(format!(
+                "$ cargo test\n{long_output}"
+            )))
+        );
+
+        assert!(overlay.handle_output_key(KeyEvent::from(KeyCode::Tab)));
+        assert_eq!(overlay.selected_output, Some(2));
+        assert!(!overlay.handle_output_key(KeyEvent::from(KeyCode::Char('x'))));
+    }
+
     #[test]
     fn transcript_overlay_keeps_scroll_pinned_at_bottom() {
         let mut overlay = TranscriptOverlay::new(
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -5,13 +6,21 @@ use std::sync::Arc;
 
 use chrono::DateTime;
 use chrono::Utc;
+use codex_common::fuzzy_match::fuzzy_match;
 use codex_core::Cursor;
+use codex_core::DiffStats;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::RolloutRecorder;
 use codex_core::ThreadItem;
+use codex_core::ThreadProvenance;
 use codex_core::ThreadSortKey;
+use codex_core::ThreadSummary;
 use codex_core::ThreadsPage;
+use codex_core::archive_rollout;
+use codex_core::live_session;
 use codex_core::path_utils;
+use codex_core::read_thread_summary;
+use codex_protocol::ThreadId;
 use codex_protocol::items::TurnItem;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
@@ -20,9 +29,15 @@ use crossterm::event::KeyEventKind;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
 use ratatui::layout::Rect;
+use ratatui::style::Style;
 use ratatui::style::Stylize as _;
 use ratatui::text::Line;
 use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Padding;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Wrap;
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -39,6 +54,10 @@ use codex_protocol::protocol::SessionMetaLine;
 
 const PAGE_SIZE: usize = 25;
 const LOAD_NEAR_THRESHOLD: usize = 5;
+/// Prefix shown before the preview of sessions stored in a project-local state dir.
+const PROJECT_TAG: &str = "[project] ";
+/// Narrowest screen that still gets the preview pane beside the list.
+const PREVIEW_MIN_WIDTH: u16 = 120;
 
 #[derive(Debug, Clone)]
 pub enum SessionSelection {
@@ -80,6 +99,7 @@ impl SessionPickerAction {
 #[derive(Clone)]
 struct PageLoadRequest {
     codex_home: PathBuf,
+    state_dir: PathBuf,
     cursor: Option<Cursor>,
     request_token: usize,
     search_token: Option<usize>,
@@ -88,26 +108,37 @@ struct PageLoadRequest {
 
 type PageLoader = Arc<dyn Fn(PageLoadRequest) + Send + Sync>;
 
+/// Reads the summary shown in the preview pane for the rollout at a path.
+type SummaryLoader = Arc<dyn Fn(PathBuf) + Send + Sync>;
+
 enum BackgroundEvent {
     PageLoaded {
         request_token: usize,
         search_token: Option<usize>,
         page: std::io::Result<ThreadsPage>,
     },
+    SummaryLoaded {
+        path: PathBuf,
+        summary: std::io::Result<ThreadSummary>,
+    },
 }
 
-/// Interactive session picker that lists recorded rollout files with simple
+/// Interactive session picker that lists recorded rollout files with fuzzy
 /// search and pagination. Shows the first user input as the preview, relative
-/// time (e.g., "5 seconds ago"), and the absolute path.
+/// time (e.g., "5 seconds ago"), and the absolute path. The query also takes
+/// `branch:<name>` and `repo:<name>` filters, and wide screens get a pane
+/// with the selected session's last reply and diff stats.
 pub async fn run_resume_picker(
     tui: &mut Tui,
     codex_home: &Path,
+    state_dir: &Path,
     default_provider: &str,
     show_all: bool,
 ) -> Result<SessionSelection> {
     run_session_picker(
         tui,
         codex_home,
+        state_dir,
         default_provider,
         show_all,
         SessionPickerAction::Resume,
@@ -118,12 +149,14 @@ pub async fn run_resume_picker(
 pub async fn run_fork_picker(
     tui: &mut Tui,
     codex_home: &Path,
+    state_dir: &Path,
     default_provider: &str,
     show_all: bool,
 ) -> Result<SessionSelection> {
     run_session_picker(
         tui,
         codex_home,
+        state_dir,
         default_provider,
         show_all,
         SessionPickerAction::Fork,
@@ -134,6 +167,7 @@ pub async fn run_fork_picker(
 async fn run_session_picker(
     tui: &mut Tui,
     codex_home: &Path,
+    state_dir: &Path,
     default_provider: &str,
     show_all: bool,
     action: SessionPickerAction,
@@ -153,8 +187,9 @@ async fn run_session_picker(
         let tx = loader_tx.clone();
         tokio::spawn(async move {
             let provider_filter = vec![request.default_provider.clone()];
-            let page = RolloutRecorder::list_threads(
+            let page = RolloutRecorder::list_threads_with_state_dir(
                 &request.codex_home,
+                &request.state_dir,
                 PAGE_SIZE,
                 request.cursor.as_ref(),
                 ThreadSortKey::CreatedAt,
@@ -171,10 +206,21 @@ async fn run_session_picker(
         });
     });
 
+    let summary_tx = bg_tx.clone();
+    let summary_loader: SummaryLoader = Arc::new(move |path: PathBuf| {
+        let tx = summary_tx.clone();
+        tokio::spawn(async move {
+            let summary = read_thread_summary(&path).await;
+            let _ = tx.send(BackgroundEvent::SummaryLoaded { path, summary });
+        });
+    });
+
     let mut state = PickerState::new(
         codex_home.to_path_buf(),
+        state_dir.to_path_buf(),
         alt.tui.frame_requester(),
         page_loader,
+        summary_loader,
         default_provider.clone(),
         show_all,
         filter_cwd,
@@ -203,6 +249,9 @@ async fn run_session_picker(
                             let list_height = size.height.saturating_sub(4) as usize;
                             state.update_view_rows(list_height);
                             state.ensure_minimum_rows_for_view(list_height);
+                            if size.width >= PREVIEW_MIN_WIDTH {
+                                state.load_selected_summary();
+                            }
                         }
                         draw_picker(alt.tui, &state)?;
                     }
@@ -240,6 +289,7 @@ impl Drop for AltScreenGuard<'_> {
 
 struct PickerState {
     codex_home: PathBuf,
+    state_dir: PathBuf,
     requester: FrameRequester,
     pagination: PaginationState,
     all_rows: Vec<Row>,
@@ -252,11 +302,23 @@ struct PickerState {
     next_request_token: usize,
     next_search_token: usize,
     page_loader: PageLoader,
+    summary_loader: SummaryLoader,
+    summaries: HashMap<PathBuf, SummaryState>,
     view_rows: Option<usize>,
     default_provider: String,
     show_all: bool,
     filter_cwd: Option<PathBuf>,
     action: SessionPickerAction,
+    /// Session waiting for `y` before it is archived.
+    pending_archive: Option<PathBuf>,
+    /// Outcome of the last export or archive, shown in the header.
+    status: Option<String>,
+}
+
+enum SummaryState {
+    Loading,
+    Loaded(ThreadSummary),
+    Unavailable,
 }
 
 struct PaginationState {
@@ -316,13 +378,86 @@ struct Row {
     updated_at: Option<DateTime<Utc>>,
     cwd: Option<PathBuf>,
     git_branch: Option<String>,
+    repository_url: Option<String>,
+    thread_id: Option<ThreadId>,
+    provenance: ThreadProvenance,
+}
+
+/// A search query split into `branch:` and `repo:` filters and the free-text
+/// terms that are fuzzy matched against each row.
+#[derive(Debug, Default, PartialEq)]
+struct SearchQuery {
+    terms: Vec<String>,
+    branch: Option<String>,
+    repo: Option<String>,
+}
+
+impl SearchQuery {
+    fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        for token in query.split_whitespace() {
+            if let Some(branch) = token.strip_prefix("branch:") {
+                parsed.branch = (!branch.is_empty()).then(|| branch.to_lowercase());
+            } else if let Some(repo) = token.strip_prefix("repo:") {
+                parsed.repo = (!repo.is_empty()).then(|| repo.to_lowercase());
+            } else {
+                parsed.terms.push(token.to_string());
+            }
+        }
+        parsed
+    }
+
+    /// Match score for `row`, smaller is better, or `None` when it is
+    /// filtered out. Every term has to match the preview, branch or cwd.
+    fn score(&self, row: &Row) -> Option<i32> {
+        let branch = row.git_branch.as_deref().map(str::to_lowercase);
+        let cwd = row
+            .cwd
+            .as_ref()
+            .map(|cwd| cwd.to_string_lossy().to_lowercase());
+        if let Some(wanted) = &self.branch
+            && !branch
+                .as_ref()
+                .is_some_and(|branch| branch.contains(wanted))
+        {
+            return None;
+        }
+        if let Some(wanted) = &self.repo {
+            let in_repo = row
+                .repository_url
+                .as_ref()
+                .is_some_and(|url| url.to_lowercase().contains(wanted))
+                || cwd.as_ref().is_some_and(|cwd| cwd.contains(wanted));
+            if !in_repo {
+                return None;
+            }
+        }
+
+        let mut total = 0i32;
+        for term in &self.terms {
+            let best = [
+                Some(row.preview.as_str()),
+                branch.as_deref(),
+                cwd.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .filter_map(|haystack| fuzzy_match(haystack, term).map(|(_, score)| score))
+            .min()?;
+            total = total.saturating_add(best);
+        }
+        Some(total)
+    }
 }
 
 impl PickerState {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         codex_home: PathBuf,
+        state_dir: PathBuf,
         requester: FrameRequester,
         page_loader: PageLoader,
+        summary_loader: SummaryLoader,
         default_provider: String,
         show_all: bool,
         filter_cwd: Option<PathBuf>,
@@ -330,6 +465,7 @@ impl PickerState {
     ) -> Self {
         Self {
             codex_home,
+            state_dir,
             requester,
             pagination: PaginationState {
                 next_cursor: None,
@@ -347,11 +483,15 @@ impl PickerState {
             next_request_token: 0,
             next_search_token: 0,
             page_loader,
+            summary_loader,
+            summaries: HashMap::new(),
             view_rows: None,
             default_provider,
             show_all,
             filter_cwd,
             action,
+            pending_archive: None,
+            status: None,
         }
     }
 
@@ -360,6 +500,17 @@ impl PickerState {
     }
 
     async fn handle_key(&mut self, key: KeyEvent) -> Result<Option<SessionSelection>> {
+        if let Some(path) = self.pending_archive.take() {
+            if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                self.archive(path).await;
+            } else {
+                self.status = Some(String::from("Archive cancelled"));
+            }
+            self.request_frame();
+            return Ok(None);
+        }
+        self.status = None;
+
         match key.code {
             KeyCode::Esc => return Ok(Some(SessionSelection::StartFresh)),
             KeyCode::Char('c')
@@ -374,6 +525,31 @@ impl PickerState {
                     return Ok(Some(self.action.selection(row.path.clone())));
                 }
             }
+            KeyCode::Char('f')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                if let Some(row) = self.filtered_rows.get(self.selected) {
+                    return Ok(Some(SessionSelection::Fork(row.path.clone())));
+                }
+            }
+            KeyCode::Char('e')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.export_selected().await;
+                self.request_frame();
+            }
+            KeyCode::Char('d')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.confirm_archive_selected();
+                self.request_frame();
+            }
             KeyCode::Up => {
                 if self.selected > 0 {
                     self.selected -= 1;
@@ -429,6 +605,82 @@ impl PickerState {
         Ok(None)
     }
 
+    /// Copies the selected rollout into the current directory.
+    async fn export_selected(&mut self) {
+        let Some(row) = self.filtered_rows.get(self.selected) else {
+            return;
+        };
+        let Some(file_name) = row.path.file_name() else {
+            return;
+        };
+        let result = match std::env::current_dir() {
+            Ok(dir) => {
+                let destination = dir.join(file_name);
+                tokio::fs::copy(&row.path, &destination)
+                    .await
+                    .map(|_| destination)
+            }
+            Err(err) => Err(err),
+        };
+        self.status = Some(match result {
+            Ok(destination) => format!("Exported to {}", destination.display()),
+            Err(err) => format!("Failed to export session: {err}"),
+        });
+    }
+
+    /// Asks for confirmation before archiving the selected session. Sessions
+    /// still running in another Codex are refused.
+    fn confirm_archive_selected(&mut self) {
+        let Some(row) = self.filtered_rows.get(self.selected) else {
+            return;
+        };
+        if row
+            .thread_id
+            .is_some_and(|thread_id| live_session::is_live(&self.codex_home, thread_id))
+        {
+            self.status = Some(String::from(
+                "This session is still running; exit it before archiving",
+            ));
+            return;
+        }
+        self.pending_archive = Some(row.path.clone());
+    }
+
+    async fn archive(&mut self, path: PathBuf) {
+        let Some(row) = self.all_rows.iter().find(|row| row.path == path) else {
+            return;
+        };
+        let root = match row.provenance {
+            ThreadProvenance::Global => &self.codex_home,
+            ThreadProvenance::Project => &self.state_dir,
+        };
+        match archive_rollout(root, &path).await {
+            Ok(_) => {
+                self.all_rows.retain(|row| row.path != path);
+                self.summaries.remove(&path);
+                self.status = Some(String::from("Session archived"));
+                self.apply_filter();
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to archive session: {err}"));
+            }
+        }
+    }
+
+    /// Starts reading the preview pane summary for the selected row unless
+    /// it is already loaded or on its way.
+    fn load_selected_summary(&mut self) {
+        let Some(row) = self.filtered_rows.get(self.selected) else {
+            return;
+        };
+        if self.summaries.contains_key(&row.path) {
+            return;
+        }
+        self.summaries
+            .insert(row.path.clone(), SummaryState::Loading);
+        (self.summary_loader)(row.path.clone());
+    }
+
     fn start_initial_load(&mut self) {
         self.reset_pagination();
         self.all_rows.clear();
@@ -446,6 +698,7 @@ impl PickerState {
 
         (self.page_loader)(PageLoadRequest {
             codex_home: self.codex_home.clone(),
+            state_dir: self.state_dir.clone(),
             cursor: None,
             request_token,
             search_token: None,
@@ -473,6 +726,14 @@ impl PickerState {
                 let completed_token = pending.search_token.or(search_token);
                 self.continue_search_if_token_matches(completed_token);
             }
+            BackgroundEvent::SummaryLoaded { path, summary } => {
+                let summary = match summary {
+                    Ok(summary) => SummaryState::Loaded(summary),
+                    Err(_) => SummaryState::Unavailable,
+                };
+                self.summaries.insert(path, summary);
+                self.request_frame();
+            }
         }
         Ok(())
     }
@@ -516,11 +777,13 @@ impl PickerState {
         if self.query.is_empty() {
             self.filtered_rows = base_iter.cloned().collect();
         } else {
-            let q = self.query.to_lowercase();
-            self.filtered_rows = base_iter
-                .filter(|r| r.preview.to_lowercase().contains(&q))
-                .cloned()
+            let query = SearchQuery::parse(&self.query);
+            let mut scored: Vec<(i32, &Row)> = base_iter
+                .filter_map(|row| query.score(row).map(|score| (score, row)))
                 .collect();
+            // Stable, so equally good matches keep their newest-first order.
+            scored.sort_by_key(|(score, _)| *score);
+            self.filtered_rows = scored.into_iter().map(|(_, row)| row.clone()).collect();
         }
         if self.selected >= self.filtered_rows.len() {
             self.selected = self.filtered_rows.len().saturating_sub(1);
@@ -676,6 +939,7 @@ impl PickerState {
 
         (self.page_loader)(PageLoadRequest {
             codex_home: self.codex_home.clone(),
+            state_dir: self.state_dir.clone(),
             cursor: Some(cursor),
             request_token,
             search_token,
@@ -712,7 +976,7 @@ fn head_to_row(item: &ThreadItem) -> Row {
         .and_then(parse_timestamp_str)
         .or(created_at);
 
-    let (cwd, git_branch) = extract_session_meta_from_head(&item.head);
+    let meta = extract_session_meta_from_head(&item.head);
     let preview = preview_from_head(&item.head)
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
@@ -723,20 +987,38 @@ fn head_to_row(item: &ThreadItem) -> Row {
         preview,
         created_at,
         updated_at,
-        cwd,
-        git_branch,
+        cwd: meta.cwd,
+        git_branch: meta.git_branch,
+        repository_url: meta.repository_url,
+        thread_id: meta.thread_id,
+        provenance: item.provenance,
     }
 }
 
-fn extract_session_meta_from_head(head: &[serde_json::Value]) -> (Option<PathBuf>, Option<String>) {
+#[derive(Default)]
+struct HeadMeta {
+    cwd: Option<PathBuf>,
+    git_branch: Option<String>,
+    repository_url: Option<String>,
+    thread_id: Option<ThreadId>,
+}
+
+fn extract_session_meta_from_head(head: &[serde_json::Value]) -> HeadMeta {
     for value in head {
         if let Ok(meta_line) = serde_json::from_value::<SessionMetaLine>(value.clone()) {
-            let cwd = Some(meta_line.meta.cwd);
-            let git_branch = meta_line.git.and_then(|git| git.branch);
-            return (cwd, git_branch);
+            let (git_branch, repository_url) = meta_line
+                .git
+                .map(|git| (git.branch, git.repository_url))
+                .unwrap_or_default();
+            return HeadMeta {
+                cwd: Some(meta_line.meta.cwd),
+                git_branch,
+                repository_url,
+                thread_id: Some(meta_line.meta.id),
+            };
         }
     }
-    (None, None)
+    HeadMeta::default()
 }
 
 fn paths_match(a: &Path, b: &Path) -> bool {
@@ -777,21 +1059,31 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
     let height = tui.terminal.size()?.height;
     tui.draw(height, |frame| {
         let area = frame.area();
-        let [header, search, columns, list, hint] = Layout::vertical([
+        let [header, search, body, hint] = Layout::vertical([
             Constraint::Length(1),
             Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Min(area.height.saturating_sub(4)),
+            Constraint::Min(area.height.saturating_sub(3)),
             Constraint::Length(1),
         ])
         .areas(area);
+        let (table, preview) = if area.width >= PREVIEW_MIN_WIDTH {
+            let [table, preview] =
+                Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(body);
+            (table, Some(preview))
+        } else {
+            (body, None)
+        };
+        let [columns, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(table);
 
-        // Header
-        frame.render_widget_ref(Line::from(vec![state.action.title().bold().cyan()]), header);
+        frame.render_widget_ref(render_header_line(state), header);
 
         // Search line
         let q = if state.query.is_empty() {
-            "Type to search".dim().to_string()
+            "Type to search (branch:<name> and repo:<name> filter)"
+                .dim()
+                .to_string()
         } else {
             format!("Search: {}", state.query)
         };
@@ -802,13 +1094,31 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
         // Column headers and list
         render_column_headers(frame, columns, &metrics);
         render_list(frame, list, state, &metrics);
+        if let Some(preview) = preview {
+            render_preview(frame, preview, state);
+        }
 
         // Hint line
         let action_label = state.action.action_label();
-        let hint_line: Line = vec![
+        let mut hint_spans: Vec<Span> = vec![
             key_hint::plain(KeyCode::Enter).into(),
             format!(" to {action_label} ").dim(),
             "    ".dim(),
+        ];
+        if matches!(state.action, SessionPickerAction::Resume) {
+            hint_spans.extend([
+                key_hint::ctrl(KeyCode::Char('f')).into(),
+                " to fork ".dim(),
+                "    ".dim(),
+            ]);
+        }
+        hint_spans.extend([
+            key_hint::ctrl(KeyCode::Char('e')).into(),
+            " to export ".dim(),
+            "    ".dim(),
+            key_hint::ctrl(KeyCode::Char('d')).into(),
+            " to archive ".dim(),
+            "    ".dim(),
             key_hint::plain(KeyCode::Esc).into(),
             " to start new ".dim(),
             "    ".dim(),
@@ -819,12 +1129,84 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
             "/".dim(),
             key_hint::plain(KeyCode::Down).into(),
             " to browse".dim(),
-        ]
-        .into();
-        frame.render_widget_ref(hint_line, hint);
+        ]);
+        frame.render_widget_ref(Line::from(hint_spans), hint);
     })
 }
 
+/// The title, followed by the archive prompt or the last action's outcome.
+fn render_header_line(state: &PickerState) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = vec![state.action.title().bold().cyan()];
+    if state.pending_archive.is_some() {
+        spans.extend([
+            "    Archive this session? ".red(),
+            key_hint::plain(KeyCode::Char('y')).into(),
+            " to confirm, any other key to cancel".dim(),
+        ]);
+    } else if let Some(status) = &state.status {
+        spans.push("    ".into());
+        spans.push(Span::from(status.clone()).dim());
+    }
+    spans.into()
+}
+
+/// Preview pane for the selected session: its first message, the agent's
+/// last reply and the size of the changes it made.
+fn render_preview(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &PickerState) {
+    let Some(row) = state.filtered_rows.get(state.selected) else {
+        return;
+    };
+    let mut lines: Vec<Line> = vec![
+        "First message".bold().into(),
+        Line::from(row.preview.clone()),
+        Line::from(""),
+        "Last reply".bold().into(),
+    ];
+    match state.summaries.get(&row.path) {
+        Some(SummaryState::Loaded(summary)) => {
+            match &summary.last_agent_message {
+                Some(message) => {
+                    lines.extend(message.lines().map(|line| Line::from(line.to_string())))
+                }
+                None => lines.push("(no reply yet)".dim().into()),
+            }
+            lines.push(Line::from(""));
+            lines.push(diff_stats_line(&summary.diff_stats));
+        }
+        Some(SummaryState::Unavailable) => {
+            lines.push("Could not read this session".italic().dim().into());
+        }
+        Some(SummaryState::Loading) | None => {
+            lines.push("Loading…".italic().dim().into());
+        }
+    }
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::LEFT)
+            .border_style(Style::new().dim())
+            .padding(Padding::left(1)),
+    );
+    frame.render_widget_ref(paragraph, area);
+}
+
+fn diff_stats_line(stats: &DiffStats) -> Line<'static> {
+    if stats.is_empty() {
+        return "No file changes".dim().into();
+    }
+    let files = if stats.files_changed == 1 {
+        "file"
+    } else {
+        "files"
+    };
+    vec![
+        format!("{} {files} changed  ", stats.files_changed).into(),
+        format!("+{}", stats.lines_added).green(),
+        " ".into(),
+        format!("-{}", stats.lines_removed).red(),
+    ]
+    .into()
+}
+
 fn render_list(
     frame: &mut crate::custom_terminal::Frame,
     area: Rect,
@@ -909,6 +1291,10 @@ fn render_list(
         if add_leading_gap {
             preview_width = preview_width.saturating_sub(2);
         }
+        let project_tag = matches!(row.provenance, ThreadProvenance::Project);
+        if project_tag {
+            preview_width = preview_width.saturating_sub(PROJECT_TAG.len());
+        }
         let preview = truncate_text(&row.preview, preview_width);
         let mut spans: Vec<Span> = vec![marker];
         if let Some(updated) = updated_span {
@@ -926,6 +1312,9 @@ fn render_list(
         if add_leading_gap {
             spans.push("  ".into());
         }
+        if project_tag {
+            spans.push(PROJECT_TAG.magenta());
+        }
         spans.push(preview.into());
 
         let line: Line = spans.into();
@@ -1150,6 +1539,7 @@ mod tests {
             head: head_with_ts_and_user_text(ts, &[preview]),
             created_at: Some(ts.to_string()),
             updated_at: Some(ts.to_string()),
+            provenance: ThreadProvenance::Global,
         }
     }
 
@@ -1216,12 +1606,14 @@ mod tests {
             head: head_with_ts_and_user_text("2025-01-01T00:00:00Z", &["A"]),
             created_at: Some("2025-01-01T00:00:00Z".into()),
             updated_at: Some("2025-01-01T00:00:00Z".into()),
+            provenance: ThreadProvenance::Global,
         };
         let b = ThreadItem {
             path: PathBuf::from("/tmp/b.jsonl"),
             head: head_with_ts_and_user_text("2025-01-02T00:00:00Z", &["B"]),
             created_at: Some("2025-01-02T00:00:00Z".into()),
             updated_at: Some("2025-01-02T00:00:00Z".into()),
+            provenance: ThreadProvenance::Global,
         };
         let rows = rows_from_items(vec![a, b]);
         assert_eq!(rows.len(), 2);
@@ -1238,6 +1630,7 @@ mod tests {
             head,
             created_at: Some("2025-01-01T00:00:00Z".into()),
             updated_at: Some("2025-01-01T01:00:00Z".into()),
+            provenance: ThreadProvenance::Global,
         };
 
         let row = head_to_row(&item);
@@ -1261,9 +1654,11 @@ mod tests {
 
         let loader: PageLoader = Arc::new(|_| {});
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1279,6 +1674,9 @@ mod tests {
                 updated_at: Some(now - Duration::seconds(42)),
                 cwd: None,
                 git_branch: None,
+                repository_url: None,
+                thread_id: None,
+                provenance: ThreadProvenance::Global,
             },
             Row {
                 path: PathBuf::from("/tmp/b.jsonl"),
@@ -1287,6 +1685,9 @@ mod tests {
                 updated_at: Some(now - Duration::minutes(35)),
                 cwd: None,
                 git_branch: None,
+                repository_url: None,
+                thread_id: None,
+                provenance: ThreadProvenance::Global,
             },
             Row {
                 path: PathBuf::from("/tmp/c.jsonl"),
@@ -1295,6 +1696,9 @@ mod tests {
                 updated_at: Some(now - Duration::hours(2)),
                 cwd: None,
                 git_branch: None,
+                repository_url: None,
+                thread_id: None,
+                provenance: ThreadProvenance::Global,
             },
         ];
         state.all_rows = rows.clone();
@@ -1410,9 +1814,11 @@ mod tests {
 
         let loader: PageLoader = Arc::new(|_| {});
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1492,9 +1898,11 @@ mod tests {
     fn pageless_scrolling_deduplicates_and_keeps_order() {
         let loader: PageLoader = Arc::new(|_| {});
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1561,9 +1969,11 @@ mod tests {
         });
 
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1593,9 +2003,11 @@ mod tests {
     async fn page_navigation_uses_view_rows() {
         let loader: PageLoader = Arc::new(|_| {});
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1638,9 +2050,11 @@ mod tests {
     async fn up_at_bottom_does_not_scroll_when_visible() {
         let loader: PageLoader = Arc::new(|_| {});
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1683,9 +2097,11 @@ mod tests {
         });
 
         let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
             PathBuf::from("/tmp"),
             FrameRequester::test_dummy(),
             loader,
+            Arc::new(|_| {}),
             String::from("openai"),
             true,
             None,
@@ -1787,4 +2203,171 @@ mod tests {
         assert!(!state.search_state.is_active());
         assert!(state.pagination.reached_scan_cap);
     }
+
+    fn test_row(path: &str, preview: &str, branch: Option<&str>, cwd: &str) -> Row {
+        Row {
+            path: PathBuf::from(path),
+            preview: preview.to_string(),
+            created_at: None,
+            updated_at: None,
+            cwd: Some(PathBuf::from(cwd)),
+            git_branch: branch.map(str::to_string),
+            repository_url: None,
+            thread_id: None,
+            provenance: ThreadProvenance::Global,
+        }
+    }
+
+    fn test_state(codex_home: &Path, summary_loader: SummaryLoader) -> PickerState {
+        PickerState::new(
+            codex_home.to_path_buf(),
+            codex_home.to_path_buf(),
+            FrameRequester::test_dummy(),
+            Arc::new(|_| {}),
+            summary_loader,
+            String::from("openai"),
+            true,
+            None,
+            SessionPickerAction::Resume,
+        )
+    }
+
+    #[test]
+    fn search_fuzzy_ranks_and_filters_by_branch_and_repo() {
+        let mut state = test_state(Path::new("/tmp"), Arc::new(|_| {}));
+        state.all_rows = vec![
+            test_row(
+                "/tmp/a.jsonl",
+                "Refactor the pager",
+                Some("main"),
+                "/work/codex",
+            ),
+            test_row(
+                "/tmp/b.jsonl",
+                "Pager scroll fix",
+                Some("feature/pager"),
+                "/work/codex",
+            ),
+            test_row("/tmp/c.jsonl", "Pager docs", Some("main"), "/work/website"),
+        ];
+        let paths = |state: &PickerState| -> Vec<PathBuf> {
+            state
+                .filtered_rows
+                .iter()
+                .map(|row| row.path.clone())
+                .collect()
+        };
+
+        state.set_query("pgr".to_string());
+        assert_eq!(
+            paths(&state),
+            vec![
+                PathBuf::from("/tmp/b.jsonl"),
+                PathBuf::from("/tmp/c.jsonl"),
+                PathBuf::from("/tmp/a.jsonl"),
+            ]
+        );
+
+        state.set_query("pager branch:main".to_string());
+        assert_eq!(
+            paths(&state),
+            vec![PathBuf::from("/tmp/c.jsonl"), PathBuf::from("/tmp/a.jsonl")]
+        );
+
+        state.set_query("repo:codex branch:main".to_string());
+        assert_eq!(paths(&state), vec![PathBuf::from("/tmp/a.jsonl")]);
+    }
+
+    #[tokio::test]
+    async fn archive_asks_for_confirmation_and_removes_the_row() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let day_dir = codex_home.path().join("sessions/2025/01/02");
+        std::fs::create_dir_all(&day_dir).expect("sessions dir");
+        let rollout = day_dir.join("rollout-2025-01-02T00-00-00-abc.jsonl");
+        std::fs::write(&rollout, "{}\n").expect("rollout");
+
+        let mut state = test_state(codex_home.path(), Arc::new(|_| {}));
+        let row = test_row(rollout.to_str().expect("utf-8 path"), "hello", None, "/w");
+        state.all_rows = vec![row.clone()];
+        state.filtered_rows = vec![row];
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+
+        state.handle_key(ctrl_d).await.unwrap();
+        assert_eq!(state.pending_archive, Some(rollout.clone()));
+        state
+            .handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE))
+            .await
+            .unwrap();
+        assert!(rollout.exists());
+        assert_eq!(state.status.as_deref(), Some("Archive cancelled"));
+
+        state.handle_key(ctrl_d).await.unwrap();
+        state
+            .handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE))
+            .await
+            .unwrap();
+        assert!(!rollout.exists());
+        assert!(
+            codex_home
+                .path()
+                .join("archived_sessions/rollout-2025-01-02T00-00-00-abc.jsonl")
+                .exists()
+        );
+        assert!(state.all_rows.is_empty());
+        assert!(state.filtered_rows.is_empty());
+        assert_eq!(state.status.as_deref(), Some("Session archived"));
+    }
+
+    #[test]
+    fn preview_pane_shows_loaded_summary() {
+        use crate::custom_terminal::Terminal;
+        use crate::test_backend::VT100Backend;
+
+        let requested: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = requested.clone();
+        let mut state = test_state(
+            Path::new("/tmp"),
+            Arc::new(move |path| sink.lock().unwrap().push(path)),
+        );
+        let row = test_row("/tmp/a.jsonl", "Fix the pager", Some("main"), "/w");
+        state.all_rows = vec![row.clone()];
+        state.filtered_rows = vec![row];
+
+        state.load_selected_summary();
+        state.load_selected_summary();
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![PathBuf::from("/tmp/a.jsonl")]
+        );
+        state
+            .handle_background_event(BackgroundEvent::SummaryLoaded {
+                path: PathBuf::from("/tmp/a.jsonl"),
+                summary: Ok(ThreadSummary {
+                    last_agent_message: Some("Fixed scrolling.".to_string()),
+                    diff_stats: DiffStats {
+                        files_changed: 2,
+                        lines_added: 10,
+                        lines_removed: 3,
+                    },
+                }),
+            })
+            .unwrap();
+
+        let width: u16 = 40;
+        let height: u16 = 8;
+        let backend = VT100Backend::new(width, height);
+        let mut terminal = Terminal::with_options(backend).expect("terminal");
+        terminal.set_viewport_area(Rect::new(0, 0, width, height));
+        {
+            let mut frame = terminal.get_frame();
+            let area = frame.area();
+            render_preview(&mut frame, area, &state);
+        }
+        terminal.flush().expect("flush");
+
+        let screen = terminal.backend().to_string();
+        assert!(screen.contains("Fix the pager"), "{screen}");
+        assert!(screen.contains("Fixed scrolling."), "{screen}");
+        assert!(screen.contains("2 files changed  +10 -3"), "{screen}");
+    }
 }
@@ -30,8 +30,10 @@ pub enum SlashCommand {
     Agent,
     // Undo,
     Diff,
+    Export,
     Mention,
     Status,
+    Usage,
     Mcp,
     Logout,
     Quit,
@@ -56,9 +58,11 @@ impl SlashCommand {
             // SlashCommand::Undo => "ask Codex to undo a turn",
             SlashCommand::Quit | SlashCommand::Exit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Export => "export the transcript or copy the last reply or turn diff",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Skills => "use skills to improve how Codex performs specific tasks",
             SlashCommand::Status => "show current session configuration and token usage",
+            SlashCommand::Usage => "show token usage for each turn",
             SlashCommand::Ps => "list background terminals",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Personality => "choose a communication style for responses",
@@ -99,9 +103,11 @@ impl SlashCommand {
             | SlashCommand::Review
             | SlashCommand::Logout => false,
             SlashCommand::Diff
+            | SlashCommand::Export
             | SlashCommand::Mention
             | SlashCommand::Skills
             | SlashCommand::Status
+            | SlashCommand::Usage
             | SlashCommand::Ps
             | SlashCommand::Mcp
             | SlashCommand::Feedback
@@ -45,6 +45,9 @@ fn token_info_for(model_slug: &str, config: &Config, usage: &TokenUsage) -> Toke
         total_token_usage: usage.clone(),
         last_token_usage: usage.clone(),
         model_context_window: context_window,
+        by_provider: Default::default(),
+        prompt_cache: Default::default(),
+        tool_output_tokens: 0,
     }
 }
 
@@ -893,6 +896,9 @@ async fn status_context_window_uses_last_usage() {
         total_token_usage: total_usage.clone(),
         last_token_usage: last_usage,
         model_context_window: config.model_context_window,
+        by_provider: Default::default(),
+        prompt_cache: Default::default(),
+        tool_output_tokens: 0,
     };
     let composite = new_status_output(
         &config,
@@ -0,0 +1,277 @@
+//! Per-turn token accounting behind the footer's context indicator and the
+//! `/usage` breakdown.
+//!
+//! Reported usage comes from `TokenCount` events. Providers that never report
+//! usage still get an indicator: the tracker estimates tokens from the text
+//! exchanged (user prompts, agent replies and tool output) at roughly four
+//! bytes per token, and everything derived from it is marked as estimated.
+
+use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TokenUsageInfo;
+
+/// Rough bytes-per-token ratio used when the provider reports no usage.
+const APPROX_BYTES_PER_TOKEN: usize = 4;
+
+/// Fraction of the compaction limit at which the indicator starts warning.
+const WARN_COMPACTION_FRACTION: f64 = 0.75;
+
+/// Fraction of the compaction limit at which compaction is imminent.
+const CRITICAL_COMPACTION_FRACTION: f64 = 0.9;
+
+/// Tokens spent by a single turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TurnUsage {
+    /// Reported conversation total when the turn started.
+    reported_total_at_start: Option<i64>,
+    /// Latest reported conversation total while the turn was current.
+    reported_total: Option<i64>,
+    pub(crate) prompt_estimate: i64,
+    pub(crate) reply_estimate: i64,
+    pub(crate) tool_output_estimate: i64,
+}
+
+impl TurnUsage {
+    /// Tokens the provider reported for this turn, if it reported any.
+    pub(crate) fn reported_tokens(&self) -> Option<i64> {
+        let end = self.reported_total?;
+        Some((end - self.reported_total_at_start.unwrap_or(0)).max(0))
+    }
+
+    pub(crate) fn estimated_tokens(&self) -> i64 {
+        self.prompt_estimate + self.reply_estimate + self.tool_output_estimate
+    }
+}
+
+/// How close the conversation is to automatic compaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ContextLevel {
+    #[default]
+    Normal,
+    NearCompaction,
+    CompactionImminent,
+}
+
+/// What the footer shows about token usage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ContextUsage {
+    /// Remaining share of the context window, when the window is known.
+    pub(crate) percent_left: Option<i64>,
+    /// Tokens used by the conversation so far.
+    pub(crate) total_tokens: i64,
+    /// Tokens used by the current (or last) turn.
+    pub(crate) turn_tokens: Option<i64>,
+    /// The numbers are estimated from message sizes rather than reported.
+    pub(crate) estimated: bool,
+    pub(crate) level: ContextLevel,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TokenUsageTracker {
+    turns: Vec<TurnUsage>,
+    reported: Option<TokenUsageInfo>,
+    /// Estimated tokens in context since the last reset, for providers that
+    /// report nothing.
+    estimated_in_context: i64,
+}
+
+impl TokenUsageTracker {
+    pub(crate) fn start_turn(&mut self) {
+        let reported_total = self
+            .reported
+            .as_ref()
+            .map(|info| info.total_token_usage.total_tokens);
+        self.turns.push(TurnUsage {
+            reported_total_at_start: reported_total,
+            ..TurnUsage::default()
+        });
+    }
+
+    pub(crate) fn record_prompt(&mut self, text: &str) {
+        let tokens = approx_tokens(text);
+        self.current_turn_mut().prompt_estimate += tokens;
+        self.estimated_in_context += tokens;
+    }
+
+    pub(crate) fn record_reply(&mut self, text: &str) {
+        let tokens = approx_tokens(text);
+        self.current_turn_mut().reply_estimate += tokens;
+        self.estimated_in_context += tokens;
+    }
+
+    pub(crate) fn record_tool_output(&mut self, text: &str) {
+        let tokens = approx_tokens(text);
+        self.current_turn_mut().tool_output_estimate += tokens;
+        self.estimated_in_context += tokens;
+    }
+
+    /// Records the usage from a `TokenCount` event. `None` means the context
+    /// was reset (e.g. compacted), so estimates start over as well.
+    pub(crate) fn set_reported(&mut self, info: Option<TokenUsageInfo>) {
+        match &info {
+            Some(info) => {
+                let total = info.total_token_usage.total_tokens;
+                if let Some(turn) = self.turns.last_mut() {
+                    turn.reported_total = Some(total);
+                }
+            }
+            None => self.estimated_in_context = 0,
+        }
+        self.reported = info;
+    }
+
+    pub(crate) fn reported(&self) -> Option<&TokenUsageInfo> {
+        self.reported.as_ref()
+    }
+
+    pub(crate) fn turns(&self) -> &[TurnUsage] {
+        &self.turns
+    }
+
+    /// The footer indicator. `context_window` comes from the config and is
+    /// only used for estimates; reported usage carries its own window.
+    pub(crate) fn context_usage(
+        &self,
+        context_window: Option<i64>,
+        auto_compact_limit: Option<i64>,
+    ) -> Option<ContextUsage> {
+        if let Some(info) = &self.reported {
+            let window = info.model_context_window;
+            let in_context = info.last_token_usage.tokens_in_context_window();
+            return Some(ContextUsage {
+                percent_left: window.map(|window| {
+                    info.last_token_usage
+                        .percent_of_context_window_remaining(window)
+                }),
+                total_tokens: info.total_token_usage.tokens_in_context_window(),
+                turn_tokens: self.turns.last().and_then(TurnUsage::reported_tokens),
+                estimated: false,
+                level: context_level(in_context, window, auto_compact_limit),
+            });
+        }
+
+        if self.estimated_in_context == 0 {
+            return None;
+        }
+        let in_context = self.estimated_in_context;
+        Some(ContextUsage {
+            percent_left: context_window.map(|window| {
+                TokenUsage {
+                    total_tokens: in_context,
+                    ..Default::default()
+                }
+                .percent_of_context_window_remaining(window)
+            }),
+            total_tokens: in_context,
+            turn_tokens: self.turns.last().map(TurnUsage::estimated_tokens),
+            estimated: true,
+            level: context_level(in_context, context_window, auto_compact_limit),
+        })
+    }
+
+    fn current_turn_mut(&mut self) -> &mut TurnUsage {
+        if self.turns.is_empty() {
+            self.start_turn();
+        }
+        let last = self.turns.len() - 1;
+        &mut self.turns[last]
+    }
+}
+
+/// Mirrors core's default of compacting at 90% of the context window.
+fn context_level(
+    in_context: i64,
+    context_window: Option<i64>,
+    auto_compact_limit: Option<i64>,
+) -> ContextLevel {
+    let Some(limit) = auto_compact_limit.or_else(|| context_window.map(|window| window * 9 / 10))
+    else {
+        return ContextLevel::Normal;
+    };
+    if limit <= 0 {
+        return ContextLevel::Normal;
+    }
+    let fraction = in_context as f64 / limit as f64;
+    if fraction >= CRITICAL_COMPACTION_FRACTION {
+        ContextLevel::CompactionImminent
+    } else if fraction >= WARN_COMPACTION_FRACTION {
+        ContextLevel::NearCompaction
+    } else {
+        ContextLevel::Normal
+    }
+}
+
+fn approx_tokens(text: &str) -> i64 {
+    text.len().div_ceil(APPROX_BYTES_PER_TOKEN) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn info(total: i64, last: i64, window: Option<i64>) -> TokenUsageInfo {
+        TokenUsageInfo {
+            total_token_usage: TokenUsage {
+                total_tokens: total,
+                ..TokenUsage::default()
+            },
+            last_token_usage: TokenUsage {
+                total_tokens: last,
+                ..TokenUsage::default()
+            },
+            model_context_window: window,
+            by_provider: Default::default(),
+            prompt_cache: Default::default(),
+            tool_output_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn reported_usage_tracks_turn_tokens_and_compaction_level() {
+        let mut tracker = TokenUsageTracker::default();
+        tracker.start_turn();
+        tracker.set_reported(Some(info(20_000, 20_000, Some(100_000))));
+        tracker.start_turn();
+        tracker.record_tool_output(&"x".repeat(400));
+        tracker.set_reported(Some(info(95_000, 80_000, Some(100_000))));
+
+        assert_eq!(
+            tracker.context_usage(None, None),
+            Some(ContextUsage {
+                percent_left: Some(23),
+                total_tokens: 95_000,
+                turn_tokens: Some(75_000),
+                estimated: false,
+                level: ContextLevel::NearCompaction,
+            })
+        );
+        assert_eq!(tracker.turns()[1].tool_output_estimate, 100);
+        assert_eq!(
+            tracker.context_usage(None, Some(85_000)).map(|u| u.level),
+            Some(ContextLevel::CompactionImminent)
+        );
+    }
+
+    #[test]
+    fn unreported_usage_is_estimated_and_reset_with_the_context() {
+        let mut tracker = TokenUsageTracker::default();
+        assert_eq!(tracker.context_usage(Some(100_000), None), None);
+
+        tracker.start_turn();
+        tracker.record_prompt(&"p".repeat(40));
+        tracker.record_reply(&"r".repeat(80));
+        tracker.record_tool_output(&"t".repeat(4_000));
+
+        let usage = tracker
+            .context_usage(Some(100_000), None)
+            .expect("estimated usage");
+        assert!(usage.estimated);
+        assert_eq!(usage.total_tokens, 1_030);
+        assert_eq!(usage.turn_tokens, Some(1_030));
+        assert_eq!(usage.percent_left, Some(100));
+
+        tracker.set_reported(None);
+        assert_eq!(tracker.context_usage(Some(100_000), None), None);
+        assert_eq!(tracker.turns()[0].estimated_tokens(), 1_030);
+    }
+}
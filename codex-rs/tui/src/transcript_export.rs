@@ -0,0 +1,465 @@
+//! Exporting the current conversation to a Markdown or HTML file.
+//!
+//! The export is built from the session's rollout rather than from the
+//! rendered transcript, so messages keep the Markdown the model wrote instead
+//! of the terminal's wrapped and styled lines.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::RolloutRecorder;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ReportedOutcome;
+use codex_core::protocol::RolloutItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Picks the format from a destination's extension, defaulting to
+    /// Markdown.
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("html" | "htm") => ExportFormat::Html,
+            _ => ExportFormat::Markdown,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Html => "HTML",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// `codex-transcript-<local time>.<ext>` under `cwd`.
+pub(crate) fn default_export_path(cwd: &Path, format: ExportFormat) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    cwd.join(format!("codex-transcript-{stamp}.{}", format.extension()))
+}
+
+/// Reads the rollout at `rollout_path` and writes it to `destination`.
+pub(crate) async fn export_transcript(
+    rollout_path: &Path,
+    destination: &Path,
+    format: ExportFormat,
+) -> std::io::Result<()> {
+    let history = RolloutRecorder::get_rollout_history(rollout_path).await?;
+    let blocks = transcript_blocks(&history.get_rollout_items());
+    let text = match format {
+        ExportFormat::Markdown => render_markdown(&blocks),
+        ExportFormat::Html => render_html(&blocks),
+    };
+    if let Some(parent) = destination.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(destination, text).await
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    User(String),
+    Agent(String),
+    ToolCall { name: String, input: String },
+    ToolOutput(String),
+    Plan(Vec<PlanItemArg>),
+    Outcome(ReportedOutcome),
+}
+
+/// `update_plan` calls show up as [`Block::Plan`] checklists, built from the
+/// plan events, rather than as tool calls.
+const PLAN_TOOL_NAME: &str = "update_plan";
+
+/// `report_outcome` calls are left out too; the last outcome closes the
+/// transcript as a [`Block::Outcome`].
+const OUTCOME_TOOL_NAME: &str = "report_outcome";
+
+fn transcript_blocks(items: &[RolloutItem]) -> Vec<Block> {
+    let mut hidden_calls = HashSet::new();
+    let mut outcome = None;
+    let mut blocks: Vec<Block> = items
+        .iter()
+        .filter_map(|item| match item {
+            RolloutItem::EventMsg(EventMsg::UserMessage(ev)) => {
+                Some(Block::User(ev.message.clone()))
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+                Some(Block::Agent(ev.message.clone()))
+            }
+            RolloutItem::EventMsg(EventMsg::PlanUpdate(update)) => {
+                Some(Block::Plan(update.plan.clone()))
+            }
+            RolloutItem::EventMsg(EventMsg::OutcomeReported(ev)) => {
+                outcome = Some(ev.outcome.clone());
+                None
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall { name, call_id, .. })
+                if name == PLAN_TOOL_NAME || name == OUTCOME_TOOL_NAME =>
+            {
+                hidden_calls.insert(call_id.clone());
+                None
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { call_id, .. })
+                if hidden_calls.contains(call_id) =>
+            {
+                None
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                name, arguments, ..
+            }) => Some(Block::ToolCall {
+                name: name.clone(),
+                input: pretty_json(arguments),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall { name, input, .. }) => {
+                Some(Block::ToolCall {
+                    name: name.clone(),
+                    input: input.clone(),
+                })
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { output, .. }) => {
+                Some(Block::ToolOutput(output.content.clone()))
+            }
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCallOutput { output, .. }) => {
+                Some(Block::ToolOutput(output.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    blocks.extend(outcome.map(Block::Outcome));
+    blocks
+}
+
+/// Tool arguments arrive as a JSON string; indent them when they parse.
+fn pretty_json(arguments: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(arguments)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or_else(|_| arguments.to_string())
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::from("# Codex transcript\n");
+    for block in blocks {
+        out.push('\n');
+        match block {
+            Block::User(message) => {
+                out.push_str("## You\n\n");
+                out.push_str(message.trim_end());
+                out.push('\n');
+            }
+            Block::Agent(message) => {
+                out.push_str("## Codex\n\n");
+                out.push_str(message.trim_end());
+                out.push('\n');
+            }
+            Block::ToolCall { name, input } => {
+                out.push_str(&format!("**Tool call** `{name}`\n\n"));
+                out.push_str(&fenced(input, "json"));
+            }
+            Block::ToolOutput(output) => {
+                out.push_str("**Output**\n\n");
+                out.push_str(&fenced(output, "text"));
+            }
+            Block::Plan(steps) => {
+                out.push_str("**Plan**\n\n");
+                for item in steps {
+                    let (check, note) = match item.status {
+                        StepStatus::Completed => ("x", ""),
+                        StepStatus::InProgress => (" ", " _(in progress)_"),
+                        StepStatus::Pending => (" ", ""),
+                    };
+                    out.push_str(&format!("- [{check}] {}{note}\n", item.step));
+                }
+            }
+            Block::Outcome(outcome) => {
+                out.push_str("## Outcome\n\n");
+                out.push_str(&format!("**{}**: {}\n", outcome.status, outcome.summary));
+                if let Some(details) = &outcome.details {
+                    out.push('\n');
+                    out.push_str(details.trim_end());
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A code fence one backtick longer than any run inside `content`.
+fn fenced(content: &str, language: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", content.trim_end())
+}
+
+fn render_html(blocks: &[Block]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Codex transcript</title>\n<style>\nbody { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; }\n.message { white-space: pre-wrap; }\npre { background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }\n</style>\n</head>\n<body>\n<h1>Codex transcript</h1>\n",
+    );
+    for block in blocks {
+        match block {
+            Block::User(message) => out.push_str(&format!(
+                "<h2>You</h2>\n<div class=\"message\">{}</div>\n",
+                escape_html(message.trim_end())
+            )),
+            Block::Agent(message) => out.push_str(&format!(
+                "<h2>Codex</h2>\n<div class=\"message\">{}</div>\n",
+                escape_html(message.trim_end())
+            )),
+            Block::ToolCall { name, input } => out.push_str(&format!(
+                "<p><strong>Tool call</strong> <code>{}</code></p>\n<pre>{}</pre>\n",
+                escape_html(name),
+                escape_html(input.trim_end())
+            )),
+            Block::ToolOutput(output) => out.push_str(&format!(
+                "<p><strong>Output</strong></p>\n<pre>{}</pre>\n",
+                escape_html(output.trim_end())
+            )),
+            Block::Plan(steps) => {
+                out.push_str("<p><strong>Plan</strong></p>\n<ul>\n");
+                for item in steps {
+                    let (check, note) = match item.status {
+                        StepStatus::Completed => (" checked", ""),
+                        StepStatus::InProgress => ("", " <em>(in progress)</em>"),
+                        StepStatus::Pending => ("", ""),
+                    };
+                    out.push_str(&format!(
+                        "<li><input type=\"checkbox\" disabled{check}> {}{note}</li>\n",
+                        escape_html(&item.step)
+                    ));
+                }
+                out.push_str("</ul>\n");
+            }
+            Block::Outcome(outcome) => {
+                out.push_str(&format!(
+                    "<h2>Outcome</h2>\n<p><strong>{}</strong>: {}</p>\n",
+                    outcome.status,
+                    escape_html(&outcome.summary)
+                ));
+                if let Some(details) = &outcome.details {
+                    out.push_str(&format!(
+                        "<div class=\"message\">{}</div>\n",
+                        escape_html(details.trim_end())
+                    ));
+                }
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::AgentMessageEvent;
+    use codex_core::protocol::OutcomeReportedEvent;
+    use codex_core::protocol::OutcomeStatus;
+    use codex_core::protocol::UserMessageEvent;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::plan_tool::UpdatePlanArgs;
+    use pretty_assertions::assert_eq;
+
+    fn sample_items() -> Vec<RolloutItem> {
+        vec![
+            RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+                message: "list files".to_string(),
+                images: None,
+                local_images: Vec::new(),
+                text_elements: Vec::new(),
+                documents: Vec::new(),
+            })),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call-1".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "a.rs\n<b>.rs".to_string(),
+                    ..Default::default()
+                },
+            }),
+            RolloutItem::EventMsg(EventMsg::AgentMessage(AgentMessageEvent {
+                message: "Found **two** files.".to_string(),
+            })),
+        ]
+    }
+
+    #[test]
+    fn markdown_keeps_messages_and_fences_tool_calls() {
+        let markdown = render_markdown(&transcript_blocks(&sample_items()));
+        assert_eq!(
+            markdown,
+            "# Codex transcript\n\
+             \n## You\n\nlist files\n\
+             \n**Tool call** `shell`\n\n```json\n{\n  \"command\": [\n    \"ls\"\n  ]\n}\n```\n\
+             \n**Output**\n\n```text\na.rs\n<b>.rs\n```\n\
+             \n## Codex\n\nFound **two** files.\n"
+        );
+    }
+
+    #[test]
+    fn plan_updates_become_checklists_instead_of_tool_calls() {
+        let plan = vec![
+            PlanItemArg {
+                step: "fix the bug".to_string(),
+                status: StepStatus::Completed,
+            },
+            PlanItemArg {
+                step: "add a test".to_string(),
+                status: StepStatus::InProgress,
+            },
+            PlanItemArg {
+                step: "update docs".to_string(),
+                status: StepStatus::Pending,
+            },
+        ];
+        let items = vec![
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "update_plan".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-plan".to_string(),
+            }),
+            RolloutItem::EventMsg(EventMsg::PlanUpdate(UpdatePlanArgs {
+                explanation: None,
+                plan,
+            })),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: "call-plan".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "Plan updated".to_string(),
+                    ..Default::default()
+                },
+            }),
+        ];
+
+        assert_eq!(
+            render_markdown(&transcript_blocks(&items)),
+            "# Codex transcript\n\
+             \n**Plan**\n\n\
+             - [x] fix the bug\n\
+             - [ ] add a test _(in progress)_\n\
+             - [ ] update docs\n"
+        );
+    }
+
+    #[test]
+    fn only_the_last_outcome_is_exported_and_closes_the_transcript() {
+        let outcome_call = |call_id: &str| {
+            [
+                RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                    id: None,
+                    name: "report_outcome".to_string(),
+                    arguments: "{}".to_string(),
+                    call_id: call_id.to_string(),
+                }),
+                RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                    call_id: call_id.to_string(),
+                    output: FunctionCallOutputPayload {
+                        content: "Outcome recorded.".to_string(),
+                        ..Default::default()
+                    },
+                }),
+            ]
+        };
+        let reported = |status: OutcomeStatus, summary: &str, details: Option<&str>| {
+            RolloutItem::EventMsg(EventMsg::OutcomeReported(OutcomeReportedEvent {
+                call_id: "call".to_string(),
+                turn_id: "turn-1".to_string(),
+                outcome: ReportedOutcome {
+                    status,
+                    summary: summary.to_string(),
+                    details: details.map(str::to_string),
+                },
+                replaced_previous: false,
+            }))
+        };
+        let mut items = Vec::new();
+        items.extend(outcome_call("call-1"));
+        items.push(reported(OutcomeStatus::Failure, "tests fail", None));
+        items.extend(outcome_call("call-2"));
+        items.push(reported(
+            OutcomeStatus::NeedsHuman,
+            "pick an API version",
+            Some("v1 and v2 both build."),
+        ));
+        items.push(RolloutItem::EventMsg(EventMsg::AgentMessage(
+            AgentMessageEvent {
+                message: "Waiting on you.".to_string(),
+            },
+        )));
+
+        assert_eq!(
+            render_markdown(&transcript_blocks(&items)),
+            "# Codex transcript\n\
+             \n## Codex\n\nWaiting on you.\n\
+             \n## Outcome\n\n**needs_human**: pick an API version\n\nv1 and v2 both build.\n"
+        );
+    }
+
+    #[test]
+    fn fence_outgrows_backticks_in_content() {
+        assert_eq!(
+            fenced("```rust\nfn main() {}\n```", "text"),
+            "````text\n```rust\nfn main() {}\n```\n````\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_content() {
+        let html = render_html(&transcript_blocks(&sample_items()));
+        assert!(html.contains("<pre>a.rs\n&lt;b&gt;.rs</pre>"));
+        assert!(html.contains("<div class=\"message\">Found **two** files.</div>"));
+    }
+
+    #[test]
+    fn format_follows_the_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out/chat.HTML")),
+            ExportFormat::Html
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("chat.txt")),
+            ExportFormat::Markdown
+        );
+    }
+}
@@ -73,8 +73,11 @@ fn kill_process(pid: u32) -> io::Result<()> {
     }
 }
 
-async fn read_output_stream<R>(mut reader: R, output_tx: broadcast::Sender<Vec<u8>>)
-where
+async fn read_output_stream<R>(
+    mut reader: R,
+    output_tx: broadcast::Sender<Vec<u8>>,
+    capture_tx: mpsc::UnboundedSender<Vec<u8>>,
+) where
     R: AsyncRead + Unpin,
 {
     let mut buf = vec![0u8; 8_192];
@@ -82,6 +85,7 @@ where
         match reader.read(&mut buf).await {
             Ok(0) => break,
             Ok(n) => {
+                let _ = capture_tx.send(buf[..n].to_vec());
                 let _ = output_tx.send(buf[..n].to_vec());
             }
             Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
@@ -159,6 +163,7 @@ async fn spawn_process_with_stdin_mode(
     let (writer_tx, mut writer_rx) = mpsc::channel::<Vec<u8>>(128);
     let (output_tx, _) = broadcast::channel::<Vec<u8>>(256);
     let initial_output_rx = output_tx.subscribe();
+    let (capture_tx, capture_rx) = mpsc::unbounded_channel::<Vec<u8>>();
 
     let writer_handle = if let Some(stdin) = stdin {
         let writer = Arc::new(tokio::sync::Mutex::new(stdin));
@@ -176,16 +181,20 @@ async fn spawn_process_with_stdin_mode(
 
     let stdout_handle = stdout.map(|stdout| {
         let output_tx = output_tx.clone();
+        let capture_tx = capture_tx.clone();
         tokio::spawn(async move {
-            read_output_stream(BufReader::new(stdout), output_tx).await;
+            read_output_stream(BufReader::new(stdout), output_tx, capture_tx).await;
         })
     });
     let stderr_handle = stderr.map(|stderr| {
         let output_tx = output_tx.clone();
+        let capture_tx = capture_tx.clone();
         tokio::spawn(async move {
-            read_output_stream(BufReader::new(stderr), output_tx).await;
+            read_output_stream(BufReader::new(stderr), output_tx, capture_tx).await;
         })
     });
+    // Only the readers hold senders, so the capture closes with the output.
+    drop(capture_tx);
     let mut reader_abort_handles = Vec::new();
     if let Some(handle) = stdout_handle.as_ref() {
         reader_abort_handles.push(handle.abort_handle());
@@ -223,6 +232,7 @@ async fn spawn_process_with_stdin_mode(
         writer_tx,
         output_tx,
         initial_output_rx,
+        capture_rx,
         Box::new(PipeChildTerminator {
             #[cfg(windows)]
             pid,
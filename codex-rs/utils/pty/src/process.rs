@@ -5,6 +5,7 @@ use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 
 use portable_pty::MasterPty;
+use portable_pty::PtySize;
 use portable_pty::SlavePty;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
@@ -31,6 +32,7 @@ impl fmt::Debug for PtyHandles {
 pub struct ProcessHandle {
     writer_tx: mpsc::Sender<Vec<u8>>,
     output_tx: broadcast::Sender<Vec<u8>>,
+    output_capture: StdMutex<Option<mpsc::UnboundedReceiver<Vec<u8>>>>,
     killer: StdMutex<Option<Box<dyn ChildTerminator>>>,
     reader_handle: StdMutex<Option<JoinHandle<()>>>,
     reader_abort_handles: StdMutex<Vec<AbortHandle>>,
@@ -55,6 +57,7 @@ impl ProcessHandle {
         writer_tx: mpsc::Sender<Vec<u8>>,
         output_tx: broadcast::Sender<Vec<u8>>,
         initial_output_rx: broadcast::Receiver<Vec<u8>>,
+        output_capture: mpsc::UnboundedReceiver<Vec<u8>>,
         killer: Box<dyn ChildTerminator>,
         reader_handle: JoinHandle<()>,
         reader_abort_handles: Vec<AbortHandle>,
@@ -68,6 +71,7 @@ impl ProcessHandle {
             Self {
                 writer_tx,
                 output_tx,
+                output_capture: StdMutex::new(Some(output_capture)),
                 killer: StdMutex::new(Some(killer)),
                 reader_handle: StdMutex::new(Some(reader_handle)),
                 reader_abort_handles: StdMutex::new(reader_abort_handles),
@@ -91,6 +95,18 @@ impl ProcessHandle {
         self.output_tx.subscribe()
     }
 
+    /// Takes the receiver that gets every stdout/stderr chunk from the start,
+    /// in order. Unlike [`Self::output_receiver`] it never lags, so a slow
+    /// consumer still sees all of the output; it closes once the output
+    /// does. Chunks queue up until it is taken, and only the first call
+    /// gets it.
+    pub fn take_output_capture(&self) -> Option<mpsc::UnboundedReceiver<Vec<u8>>> {
+        self.output_capture
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+    }
+
     /// True if the child process has exited.
     pub fn has_exited(&self) -> bool {
         self.exit_status.load(std::sync::atomic::Ordering::SeqCst)
@@ -101,6 +117,23 @@ impl ProcessHandle {
         self.exit_code.lock().ok().and_then(|guard| *guard)
     }
 
+    /// Resizes the PTY backing this process. Fails for pipe-backed processes.
+    pub fn resize(&self, rows: u16, cols: u16) -> anyhow::Result<()> {
+        let handles = self
+            ._pty_handles
+            .lock()
+            .map_err(|_| anyhow::anyhow!("PTY handles lock poisoned"))?;
+        let Some(handles) = handles.as_ref() else {
+            anyhow::bail!("process is not attached to a PTY");
+        };
+        handles._master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+    }
+
     /// Attempts to kill the child and abort helper tasks.
     pub fn terminate(&self) {
         if let Ok(mut killer_opt) = self.killer.lock() {
@@ -91,6 +91,7 @@ pub async fn spawn_process(
     let (writer_tx, mut writer_rx) = mpsc::channel::<Vec<u8>>(128);
     let (output_tx, _) = broadcast::channel::<Vec<u8>>(256);
     let initial_output_rx = output_tx.subscribe();
+    let (capture_tx, capture_rx) = mpsc::unbounded_channel::<Vec<u8>>();
 
     let mut reader = pair.master.try_clone_reader()?;
     let output_tx_clone = output_tx.clone();
@@ -100,6 +101,7 @@ pub async fn spawn_process(
             match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => {
+                    let _ = capture_tx.send(buf[..n].to_vec());
                     let _ = output_tx_clone.send(buf[..n].to_vec());
                 }
                 Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
@@ -156,6 +158,7 @@ pub async fn spawn_process(
         writer_tx,
         output_tx,
         initial_output_rx,
+        capture_rx,
         Box::new(PtyChildTerminator { killer }),
         reader_handle,
         Vec::new(),
@@ -152,6 +152,40 @@ async fn pipe_process_round_trips_stdin() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pty_resize_is_visible_to_the_child() -> anyhow::Result<()> {
+    let args = vec!["-c".to_string(), "read _; stty size".to_string()];
+    let env_map: HashMap<String, String> = std::env::vars().collect();
+    let spawned = spawn_pty_process("sh", &args, Path::new("."), &env_map, &None).await?;
+    spawned.session.resize(40, 100)?;
+    spawned.session.writer_sender().send(b"\n".to_vec()).await?;
+
+    let (output, code) = collect_output_until_exit(spawned.output_rx, spawned.exit_rx, 5_000).await;
+    let text = String::from_utf8_lossy(&output);
+
+    assert!(text.contains("40 100"), "expected resized PTY: {text:?}");
+    assert_eq!(code, 0, "expected sh to exit cleanly");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pipe_process_cannot_be_resized() -> anyhow::Result<()> {
+    let Some(python) = find_python() else {
+        eprintln!("python not found; skipping pipe_process_cannot_be_resized");
+        return Ok(());
+    };
+
+    let args = vec!["-c".to_string(), "pass".to_string()];
+    let env_map: HashMap<String, String> = std::env::vars().collect();
+    let spawned = spawn_pipe_process(&python, &args, Path::new("."), &env_map, &None).await?;
+
+    assert!(spawned.session.resize(24, 80).is_err());
+
+    Ok(())
+}
+
 #[cfg(unix)]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn pipe_process_detaches_from_parent_session() -> anyhow::Result<()> {
@@ -285,3 +319,33 @@ async fn pipe_terminate_aborts_detached_readers() -> anyhow::Result<()> {
         ),
     }
 }
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn output_capture_keeps_chunks_a_lagging_receiver_drops() -> anyhow::Result<()> {
+    let env_map: HashMap<String, String> = std::env::vars().collect();
+    // Each `echo` is its own write, so this is far more chunks than the
+    // broadcast channel holds.
+    let script = "i=0; while [ $i -lt 2000 ]; do echo line-$i; i=$((i+1)); done";
+    let (program, args) = shell_command(script);
+    let spawned = spawn_pipe_process(&program, &args, Path::new("."), &env_map, &None).await?;
+
+    let mut capture = spawned
+        .session
+        .take_output_capture()
+        .ok_or_else(|| anyhow::anyhow!("output capture already taken"))?;
+    assert!(spawned.session.take_output_capture().is_none());
+    let _ = spawned.exit_rx.await;
+
+    let mut collected = Vec::new();
+    while let Some(chunk) =
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), capture.recv()).await?
+    {
+        collected.extend_from_slice(&chunk);
+    }
+
+    let expected: String = (0..2000).map(|i| format!("line-{i}\n")).collect();
+    assert_eq!(String::from_utf8_lossy(&collected), expected);
+
+    Ok(())
+}